@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS milk_records (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    doe_id INTEGER NOT NULL,
+    recorded_on DATE NOT NULL,
+    yield_litres REAL NOT NULL CHECK(yield_litres >= 0),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (doe_id) REFERENCES goats(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_milk_records_doe ON milk_records(doe_id, recorded_on);
@@ -0,0 +1 @@
+ALTER TABLE goats ADD COLUMN space_id INTEGER REFERENCES spaces(id) ON DELETE SET NULL;
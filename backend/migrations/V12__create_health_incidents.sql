@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS health_incidents (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL,
+    space_id INTEGER,
+    kind TEXT NOT NULL CHECK(kind IN ('Disease', 'Parasite', 'Injury', 'Other')),
+    condition TEXT NOT NULL,
+    observed_on DATE NOT NULL,
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (space_id) REFERENCES spaces(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_health_incidents_observed ON health_incidents(observed_on);
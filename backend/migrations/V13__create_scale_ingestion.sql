@@ -0,0 +1,27 @@
+ALTER TABLE goats ADD COLUMN tag_id TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_goats_tag_id ON goats(tag_id);
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    revoked INTEGER NOT NULL DEFAULT 0,
+    last_used_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS scale_readings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    api_key_id INTEGER,
+    tag_id TEXT NOT NULL,
+    weight REAL NOT NULL CHECK(weight > 0),
+    read_at TIMESTAMP NOT NULL,
+    goat_id INTEGER,
+    weight_record_id INTEGER,
+    FOREIGN KEY (api_key_id) REFERENCES api_keys(id) ON DELETE SET NULL,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE SET NULL,
+    FOREIGN KEY (weight_record_id) REFERENCES weight_records(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_scale_readings_tag ON scale_readings(tag_id);
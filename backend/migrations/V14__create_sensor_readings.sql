@@ -0,0 +1,14 @@
+ALTER TABLE sensors ADD COLUMN name TEXT;
+ALTER TABLE sensors ADD COLUMN space_id INTEGER REFERENCES spaces(id) ON DELETE SET NULL;
+ALTER TABLE sensors ADD COLUMN min_threshold REAL;
+ALTER TABLE sensors ADD COLUMN max_threshold REAL;
+
+CREATE TABLE IF NOT EXISTS sensor_readings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sensor_id INTEGER NOT NULL,
+    value REAL NOT NULL,
+    read_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (sensor_id) REFERENCES sensors(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_sensor_readings_sensor ON sensor_readings(sensor_id, read_at);
@@ -0,0 +1 @@
+ALTER TABLE sensors ADD COLUMN goat_id INTEGER REFERENCES goats(id) ON DELETE SET NULL;
@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS geofences (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    boundary TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS goat_positions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL,
+    lat REAL NOT NULL,
+    lon REAL NOT NULL,
+    recorded_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_goat_positions_goat ON goat_positions(goat_id, recorded_at);
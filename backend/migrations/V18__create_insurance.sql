@@ -0,0 +1,28 @@
+CREATE TABLE IF NOT EXISTS insurance_policies (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL,
+    provider TEXT NOT NULL,
+    policy_number TEXT NOT NULL,
+    sum_insured REAL NOT NULL CHECK(sum_insured > 0),
+    start_date DATE,
+    expiry_date DATE NOT NULL,
+    notes TEXT,
+    renewal_task_id INTEGER,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (provider, policy_number),
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (renewal_task_id) REFERENCES tasks(id) ON DELETE SET NULL
+);
+
+CREATE TABLE IF NOT EXISTS insurance_claims (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    policy_id INTEGER NOT NULL,
+    claim_date DATE NOT NULL,
+    reason TEXT NOT NULL,
+    amount_claimed REAL NOT NULL CHECK(amount_claimed > 0),
+    amount_settled REAL,
+    status TEXT CHECK(status IN ('Filed', 'Approved', 'Rejected', 'Settled')) NOT NULL DEFAULT 'Filed',
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (policy_id) REFERENCES insurance_policies(id) ON DELETE CASCADE
+);
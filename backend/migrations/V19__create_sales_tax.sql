@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS farm_settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS tax_rates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    rate_percent REAL NOT NULL CHECK(rate_percent >= 0 AND rate_percent <= 100),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS sale_details (
+    transaction_id INTEGER PRIMARY KEY,
+    invoice_number TEXT UNIQUE,
+    buyer_name TEXT,
+    buyer_gstin TEXT,
+    tax_rate_id INTEGER,
+    tax_percent REAL NOT NULL DEFAULT 0,
+    FOREIGN KEY (transaction_id) REFERENCES transactions(id) ON DELETE CASCADE,
+    FOREIGN KEY (tax_rate_id) REFERENCES tax_rates(id) ON DELETE SET NULL
+);
@@ -0,0 +1,2 @@
+ALTER TABLE transactions ADD COLUMN currency TEXT;
+ALTER TABLE transactions ADD COLUMN exchange_rate REAL CHECK(exchange_rate > 0);
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS budgets (
+    category TEXT PRIMARY KEY,
+    monthly_amount REAL NOT NULL CHECK(monthly_amount >= 0),
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,2 @@
+ALTER TABLE goats ADD COLUMN updated_at TIMESTAMP;
+UPDATE goats SET updated_at = created_at;
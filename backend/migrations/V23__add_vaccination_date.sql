@@ -0,0 +1 @@
+ALTER TABLE goat_vaccines ADD COLUMN given_on DATE;
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS breed_catalog (
+    name TEXT PRIMARY KEY COLLATE NOCASE,
+    purpose TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
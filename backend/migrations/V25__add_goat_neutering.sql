@@ -0,0 +1,2 @@
+ALTER TABLE goats ADD COLUMN neutered_on DATE;
+ALTER TABLE goats ADD COLUMN neutered_reason TEXT;
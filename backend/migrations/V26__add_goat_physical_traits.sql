@@ -0,0 +1,3 @@
+ALTER TABLE goats ADD COLUMN horns TEXT;
+ALTER TABLE goats ADD COLUMN coat_color TEXT;
+ALTER TABLE goats ADD COLUMN marks TEXT;
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS genetic_tags (
+    goat_id INTEGER NOT NULL,
+    tag TEXT NOT NULL COLLATE NOCASE,
+    PRIMARY KEY (goat_id, tag),
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE
+);
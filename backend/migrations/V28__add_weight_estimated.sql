@@ -0,0 +1 @@
+ALTER TABLE weight_records ADD COLUMN estimated INTEGER NOT NULL DEFAULT 0;
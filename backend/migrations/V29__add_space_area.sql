@@ -0,0 +1 @@
+ALTER TABLE spaces ADD COLUMN area_m2 REAL;
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS paddocks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    area_m2 REAL,
+    capacity INTEGER,
+    rest_days INTEGER NOT NULL,
+    last_grazed_on DATE
+);
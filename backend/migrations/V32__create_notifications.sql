@@ -0,0 +1,16 @@
+ALTER TABLE workers ADD COLUMN notify_by TEXT NOT NULL DEFAULT 'App';
+
+CREATE TABLE IF NOT EXISTS notifications (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    worker_id INTEGER NOT NULL,
+    goat_id INTEGER NOT NULL,
+    note_id INTEGER,
+    message TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    read INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY (worker_id) REFERENCES workers(id) ON DELETE CASCADE,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (note_id) REFERENCES goat_notes(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_worker ON notifications(worker_id, read);
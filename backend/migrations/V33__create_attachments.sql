@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS attachments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER,
+    task_id INTEGER,
+    author TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    data BLOB NOT NULL,
+    duration_secs REAL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((goat_id IS NULL) <> (task_id IS NULL)),
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_attachments_goat ON attachments(goat_id);
+
+CREATE INDEX IF NOT EXISTS idx_attachments_task ON attachments(task_id);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS goat_trash (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    deleted_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_goat_trash_deleted ON goat_trash(deleted_at);
+CREATE INDEX IF NOT EXISTS idx_events_recorded ON events(recorded_at);
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role TEXT PRIMARY KEY,
+    granted TEXT NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
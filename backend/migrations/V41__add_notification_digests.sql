@@ -0,0 +1 @@
+ALTER TABLE notifications ADD COLUMN digested_at TIMESTAMP;
@@ -0,0 +1,31 @@
+CREATE TABLE IF NOT EXISTS alert_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    metric TEXT NOT NULL,
+    condition TEXT NOT NULL,
+    threshold REAL NOT NULL,
+    active INTEGER NOT NULL DEFAULT 1,
+    firing INTEGER NOT NULL DEFAULT 0,
+    last_fired_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Alerts notify workers without a goat, so goat_id becomes optional
+CREATE TABLE notifications_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    worker_id INTEGER NOT NULL,
+    goat_id INTEGER,
+    note_id INTEGER,
+    message TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    read INTEGER NOT NULL DEFAULT 0,
+    digested_at TIMESTAMP,
+    FOREIGN KEY (worker_id) REFERENCES workers(id) ON DELETE CASCADE,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (note_id) REFERENCES goat_notes(id) ON DELETE SET NULL
+);
+INSERT INTO notifications_new (id, worker_id, goat_id, note_id, message, created_at, read, digested_at)
+    SELECT id, worker_id, goat_id, note_id, message, created_at, read, digested_at FROM notifications;
+DROP TABLE notifications;
+ALTER TABLE notifications_new RENAME TO notifications;
+CREATE INDEX IF NOT EXISTS idx_notifications_worker ON notifications(worker_id, read);
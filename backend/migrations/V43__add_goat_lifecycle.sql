@@ -0,0 +1,13 @@
+ALTER TABLE goats ADD COLUMN lifecycle_stage TEXT;
+
+CREATE TABLE IF NOT EXISTS lifecycle_changes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL,
+    from_stage TEXT,
+    to_stage TEXT NOT NULL,
+    changed_on DATE NOT NULL,
+    note TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_lifecycle_changes_goat ON lifecycle_changes(goat_id);
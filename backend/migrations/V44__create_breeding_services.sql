@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS breeding_services (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    doe_id INTEGER NOT NULL,
+    served_on DATE NOT NULL,
+    method TEXT CHECK(method IN ('Natural', 'Artificial')) NOT NULL,
+    buck_id INTEGER,
+    outside_sire_name TEXT,
+    outside_sire_breed TEXT,
+    outside_sire_registration TEXT,
+    outside_sire_source TEXT,
+    straw_id TEXT,
+    technician TEXT,
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (doe_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (buck_id) REFERENCES goats(id) ON DELETE SET NULL
+);
+CREATE INDEX IF NOT EXISTS idx_breeding_services_doe ON breeding_services(doe_id, served_on);
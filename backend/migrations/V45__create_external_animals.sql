@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS external_animals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    gender TEXT CHECK(gender IN ('Male', 'Female')) NOT NULL,
+    breed TEXT,
+    registration TEXT,
+    owner TEXT,
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+ALTER TABLE goats ADD COLUMN sire_external_id INTEGER REFERENCES external_animals(id) ON DELETE SET NULL;
+ALTER TABLE goats ADD COLUMN dam_external_id INTEGER REFERENCES external_animals(id) ON DELETE SET NULL;
+ALTER TABLE kidding_records ADD COLUMN external_buck_id INTEGER REFERENCES external_animals(id) ON DELETE SET NULL;
+ALTER TABLE breeding_services ADD COLUMN external_sire_id INTEGER REFERENCES external_animals(id) ON DELETE SET NULL;
@@ -0,0 +1 @@
+ALTER TABLE goats ADD COLUMN quick_note TEXT;
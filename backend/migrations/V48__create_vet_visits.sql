@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS vet_visits (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    vet_id INTEGER REFERENCES workers(id) ON DELETE SET NULL,
+    status TEXT NOT NULL CHECK(status IN ('Requested', 'Scheduled', 'Completed')),
+    reason TEXT NOT NULL,
+    requested_on DATE NOT NULL,
+    scheduled_for DATE,
+    completed_on DATE,
+    summary TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_vet_visits_vet ON vet_visits(vet_id, scheduled_for);
+
+CREATE TABLE IF NOT EXISTS vet_visit_goats (
+    visit_id INTEGER NOT NULL REFERENCES vet_visits(id) ON DELETE CASCADE,
+    goat_id INTEGER NOT NULL REFERENCES goats(id) ON DELETE CASCADE,
+    incident_id INTEGER REFERENCES health_incidents(id) ON DELETE SET NULL,
+    PRIMARY KEY (visit_id, goat_id)
+);
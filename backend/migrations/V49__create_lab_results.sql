@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS lab_results (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL REFERENCES goats(id) ON DELETE CASCADE,
+    test_type TEXT NOT NULL,
+    sampled_on DATE NOT NULL,
+    lab TEXT,
+    notes TEXT,
+    report BLOB,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_lab_results_goat ON lab_results(goat_id, sampled_on);
+
+CREATE TABLE IF NOT EXISTS lab_values (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    result_id INTEGER NOT NULL REFERENCES lab_results(id) ON DELETE CASCADE,
+    analyte TEXT NOT NULL,
+    value REAL NOT NULL,
+    unit TEXT NOT NULL,
+    low REAL,
+    high REAL
+);
@@ -0,0 +1,37 @@
+
+CREATE TABLE IF NOT EXISTS reminder_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    task_title TEXT NOT NULL,
+    scope TEXT CHECK(scope IN ('Goat', 'Pen')) NOT NULL,
+    interval_days INTEGER NOT NULL CHECK(interval_days > 0),
+    lead_days INTEGER NOT NULL DEFAULT 0,
+    active INTEGER NOT NULL DEFAULT 1,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS tasks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title TEXT NOT NULL,
+    notes TEXT,
+    goat_id INTEGER,
+    space_id INTEGER,
+    rule_id INTEGER,
+    due_date DATE NOT NULL,
+    status TEXT CHECK(status IN ('Pending', 'Done', 'Skipped')) NOT NULL DEFAULT 'Pending',
+    completed_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (space_id) REFERENCES spaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (rule_id) REFERENCES reminder_rules(id) ON DELETE SET NULL
+);
+
+CREATE TABLE IF NOT EXISTS reminders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL,
+    remind_on DATE NOT NULL,
+    message TEXT NOT NULL,
+    dismissed INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
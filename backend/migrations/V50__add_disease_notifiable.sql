@@ -0,0 +1,23 @@
+ALTER TABLE diseases ADD COLUMN notifiable INTEGER NOT NULL DEFAULT 0;
+
+-- Diseases notifiable to animal health authorities in most countries
+INSERT INTO diseases (name)
+SELECT seed.name FROM (
+    SELECT 'Brucellosis' AS name
+    UNION ALL SELECT 'Tuberculosis'
+    UNION ALL SELECT 'Anthrax'
+    UNION ALL SELECT 'Peste des petits ruminants'
+    UNION ALL SELECT 'Foot and mouth disease'
+    UNION ALL SELECT 'Rabies'
+    UNION ALL SELECT 'Sheep and goat pox'
+    UNION ALL SELECT 'Contagious caprine pleuropneumonia'
+    UNION ALL SELECT 'Bluetongue'
+    UNION ALL SELECT 'Q fever'
+) seed
+WHERE NOT EXISTS (SELECT 1 FROM diseases d WHERE lower(d.name) = lower(seed.name));
+
+UPDATE diseases SET notifiable = 1 WHERE lower(name) IN (
+    'brucellosis', 'tuberculosis', 'anthrax', 'peste des petits ruminants',
+    'foot and mouth disease', 'rabies', 'sheep and goat pox',
+    'contagious caprine pleuropneumonia', 'bluetongue', 'q fever'
+);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS traceability_records (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER REFERENCES goats(id) ON DELETE SET NULL,
+    transaction_id INTEGER NOT NULL UNIQUE REFERENCES transactions(id) ON DELETE CASCADE,
+    token TEXT NOT NULL UNIQUE,
+    record TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_traceability_records_goat ON traceability_records(goat_id);
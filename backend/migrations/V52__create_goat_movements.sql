@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS goat_movements (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    from_kind TEXT NOT NULL CHECK(from_kind IN ('Farm', 'Market', 'Buyer')),
+    from_name TEXT NOT NULL,
+    to_kind TEXT NOT NULL CHECK(to_kind IN ('Farm', 'Market', 'Buyer')),
+    to_name TEXT NOT NULL,
+    departed_on DATE NOT NULL,
+    arrived_on DATE,
+    transporter TEXT,
+    vehicle TEXT,
+    permit_number TEXT,
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_goat_movements_departed ON goat_movements(departed_on);
+
+CREATE TABLE IF NOT EXISTS goat_movement_goats (
+    movement_id INTEGER NOT NULL REFERENCES goat_movements(id) ON DELETE CASCADE,
+    goat_id INTEGER NOT NULL REFERENCES goats(id) ON DELETE CASCADE,
+    PRIMARY KEY (movement_id, goat_id)
+);
+CREATE INDEX IF NOT EXISTS idx_goat_movement_goats_goat ON goat_movement_goats(goat_id);
@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS tag_counters (
+    stem TEXT PRIMARY KEY,
+    last_number INTEGER NOT NULL
+);
@@ -0,0 +1,21 @@
+-- Signing in takes the owner's password or a worker's PIN, stored hashed
+CREATE TABLE IF NOT EXISTS owner_credentials (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    password_hash TEXT NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+ALTER TABLE workers ADD COLUMN pin_hash TEXT;
+
+-- Sessions signed in without credentials are signed out
+UPDATE sessions SET revoked_at = CURRENT_TIMESTAMP
+WHERE revoked_at IS NULL AND worker_id IS NULL;
+
+-- A session without a worker is the owner's, so a removed worker's
+-- sessions are signed out before their worker_id is cleared
+CREATE TRIGGER IF NOT EXISTS revoke_sessions_of_removed_worker
+BEFORE DELETE ON workers
+BEGIN
+    UPDATE sessions SET revoked_at = CURRENT_TIMESTAMP
+    WHERE worker_id = OLD.id AND revoked_at IS NULL;
+END;
@@ -0,0 +1,17 @@
+-- The movement register is kept by law, so removing a goat no longer
+-- erases its movements. Each row keeps the goat's name and ear tag as they
+-- were when it moved; goat_id is cleared once the goat is removed.
+CREATE TABLE goat_movement_goats_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    movement_id INTEGER NOT NULL REFERENCES goat_movements(id) ON DELETE CASCADE,
+    goat_id INTEGER REFERENCES goats(id) ON DELETE SET NULL,
+    goat_name TEXT NOT NULL,
+    tag_id TEXT,
+    UNIQUE (movement_id, goat_id)
+);
+INSERT INTO goat_movement_goats_new (movement_id, goat_id, goat_name, tag_id)
+    SELECT mg.movement_id, mg.goat_id, g.name, g.tag_id
+    FROM goat_movement_goats mg JOIN goats g ON g.id = mg.goat_id;
+DROP TABLE goat_movement_goats;
+ALTER TABLE goat_movement_goats_new RENAME TO goat_movement_goats;
+CREATE INDEX IF NOT EXISTS idx_goat_movement_goats_goat ON goat_movement_goats(goat_id);
@@ -0,0 +1,9 @@
+-- Deleting a goat only marks it; its weighings, milk records, notes and
+-- other records stay until the retention cleanup purges the goat. Queries
+-- of the herd skip goats with deleted_at set.
+ALTER TABLE goats ADD COLUMN deleted_at TIMESTAMP;
+CREATE INDEX IF NOT EXISTS idx_goats_deleted ON goats(deleted_at);
+
+-- A deleted goat's ear tag may be given to another goat.
+DROP INDEX IF EXISTS idx_goats_tag_id;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_goats_tag_id ON goats(tag_id) WHERE deleted_at IS NULL;
@@ -0,0 +1,8 @@
+-- Who made each change: the worker (or owner) and device of a session, an
+-- access token, or a background job.
+ALTER TABLE events ADD COLUMN actor TEXT;
+
+-- Goat registrations and updates are logged for field history even when
+-- the backend does not run event-sourced; those rows have sourced = 0 and
+-- are left out of the event feed and replays.
+ALTER TABLE events ADD COLUMN sourced INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,11 @@
+-- Failed sign-ins, counted to lock out guessing of passwords and PINs;
+-- account is 'owner' or 'worker:' and the worker's name in lowercase
+CREATE TABLE IF NOT EXISTS sign_in_attempts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account TEXT NOT NULL,
+    ip TEXT,
+    attempted_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_sign_in_attempts_account
+    ON sign_in_attempts(account, attempted_at);
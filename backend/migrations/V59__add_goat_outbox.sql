@@ -0,0 +1,27 @@
+-- Goats changed since they were last copied to a PostgreSQL goat store,
+-- with a count of their changes so a copy that raced a later change is
+-- redone (see repository::PostgresGoatStore)
+CREATE TABLE IF NOT EXISTS goat_outbox (
+    goat_id INTEGER PRIMARY KEY,
+    changes INTEGER NOT NULL
+);
+
+CREATE TRIGGER IF NOT EXISTS goat_outbox_insert AFTER INSERT ON goats
+BEGIN
+    INSERT INTO goat_outbox (goat_id, changes) VALUES (NEW.id, 1)
+        ON CONFLICT(goat_id) DO UPDATE SET changes = changes + 1;
+END;
+
+CREATE TRIGGER IF NOT EXISTS goat_outbox_update AFTER UPDATE ON goats
+BEGIN
+    INSERT INTO goat_outbox (goat_id, changes) VALUES (NEW.id, 1)
+        ON CONFLICT(goat_id) DO UPDATE SET changes = changes + 1;
+END;
+
+CREATE TRIGGER IF NOT EXISTS goat_outbox_delete AFTER DELETE ON goats
+BEGIN
+    INSERT INTO goat_outbox (goat_id, changes) VALUES (OLD.id, 1)
+        ON CONFLICT(goat_id) DO UPDATE SET changes = changes + 1;
+END;
+
+INSERT OR IGNORE INTO goat_outbox (goat_id, changes) SELECT id, 1 FROM goats;
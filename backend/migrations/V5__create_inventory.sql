@@ -0,0 +1,25 @@
+
+CREATE TABLE IF NOT EXISTS inventory_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    category TEXT CHECK(category IN ('Feed', 'Medicine', 'Equipment', 'Other')) NOT NULL,
+    quantity REAL NOT NULL DEFAULT 0,
+    unit TEXT NOT NULL,
+    unit_cost REAL NOT NULL DEFAULT 0,
+    expiry_date DATE,
+    reorder_level REAL NOT NULL DEFAULT 0,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS inventory_consumption (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id INTEGER NOT NULL,
+    quantity REAL NOT NULL CHECK(quantity > 0),
+    purpose TEXT CHECK(purpose IN ('Treatment', 'Feeding', 'Other')) NOT NULL,
+    goat_id INTEGER,
+    consumed_on DATE NOT NULL,
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (item_id) REFERENCES inventory_items(id) ON DELETE CASCADE,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE SET NULL
+);
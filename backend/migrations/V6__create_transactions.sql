@@ -0,0 +1,18 @@
+
+ALTER TABLE inventory_consumption ADD COLUMN space_id INTEGER REFERENCES spaces(id) ON DELETE SET NULL;
+
+CREATE TABLE IF NOT EXISTS transactions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT CHECK(kind IN ('Income', 'Expense')) NOT NULL,
+    category TEXT NOT NULL,
+    amount REAL NOT NULL CHECK(amount >= 0),
+    description TEXT NOT NULL DEFAULT '',
+    goat_id INTEGER,
+    space_id INTEGER,
+    consumption_id INTEGER,
+    date DATE NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE SET NULL,
+    FOREIGN KEY (space_id) REFERENCES spaces(id) ON DELETE SET NULL,
+    FOREIGN KEY (consumption_id) REFERENCES inventory_consumption(id) ON DELETE CASCADE
+);
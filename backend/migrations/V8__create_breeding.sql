@@ -0,0 +1,16 @@
+ALTER TABLE goats ADD COLUMN sire_id INTEGER REFERENCES goats(id) ON DELETE SET NULL;
+ALTER TABLE goats ADD COLUMN dam_id INTEGER REFERENCES goats(id) ON DELETE SET NULL;
+ALTER TABLE goats ADD COLUMN date_of_birth DATE;
+
+CREATE TABLE IF NOT EXISTS kidding_records (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    doe_id INTEGER NOT NULL,
+    buck_id INTEGER,
+    kidded_on DATE NOT NULL,
+    kids_born INTEGER NOT NULL CHECK(kids_born >= 0),
+    kids_alive INTEGER NOT NULL CHECK(kids_alive >= 0 AND kids_alive <= kids_born),
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (doe_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (buck_id) REFERENCES goats(id) ON DELETE SET NULL
+);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS weight_records (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL,
+    weighed_on DATE NOT NULL,
+    weight REAL NOT NULL CHECK(weight > 0),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_weight_records_goat ON weight_records(goat_id, weighed_on);
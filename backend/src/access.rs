@@ -0,0 +1,81 @@
+//! Read-only mode: while the farm's `read_only` setting is on, the API
+//! only serves reads.
+//!
+//! The `enforce_read_only` middleware refuses every request that could
+//! change data (anything but `GET`, `HEAD` and `OPTIONS`) with HTTP 403, so
+//! the mode holds for every client, not just the dashboard that hides its
+//! forms. Exempt are the owner's switch of the mode itself (so it can be
+//! switched off again), crash reports, the operator's tenant endpoints,
+//! signing devices in and out, and readings pushed by devices with their
+//! API keys. Other settings stay unchanged until the mode is off.
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::settings::load_settings;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::web;
+use tracing::{debug, warn};
+
+/// Scopes that accept changes in read-only mode.
+const EXEMPT_SCOPES: [&str; 3] = ["/client-errors", "/tenants", "/sessions"];
+
+/// Endpoint switching read-only mode, which only the owner may call (see
+/// `crate::handlers::settings::set_read_only`).
+const READ_ONLY_SWITCH: &str = "/settings/read-only";
+
+/// Device ingestion endpoints, which keep accepting readings in read-only mode.
+const DEVICE_ENDPOINTS: [&str; 3] = ["/scale/readings", "/sensors/readings", "/gps/positions"];
+
+/// Whether a `method` request to `path` may run while the farm is read-only.
+pub fn allowed_when_read_only(method: &Method, path: &str) -> bool {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return true;
+    }
+    let in_scope = |scope: &str| {
+        path.strip_prefix(scope)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    };
+    EXEMPT_SCOPES.into_iter().any(in_scope)
+        || (*method == Method::POST && DEVICE_ENDPOINTS.contains(&path))
+        || (*method == Method::PUT && path == READ_ONLY_SWITCH)
+}
+
+/// Refuses `req` if the farm is read-only and it is not exempt.
+fn check_read_only(req: &ServiceRequest) -> Result<(), AppError> {
+    if allowed_when_read_only(req.method(), req.path()) {
+        return Ok(());
+    }
+    // Without a pool (e.g. an unknown tenant) the handler reports the problem
+    let Some(db) = req.app_data::<web::Data<DbPool>>() else {
+        return Ok(());
+    };
+    let conn = db.get_conn()?;
+    if load_settings(&conn)?.read_only {
+        warn!(method = %req.method(), path = req.path(), "Refused change in read-only mode");
+        return Err(AppError::Forbidden("The farm is in read-only mode".into()));
+    }
+    debug!(path = req.path(), "Read-only mode off, allowing change");
+    Ok(())
+}
+
+/// Middleware enforcing read-only mode.
+///
+/// Must run after `crate::tenants::resolve_tenant`, so it sees the
+/// requesting tenant's database.
+///
+/// # Errors
+/// - Responds with HTTP 403 to changes while the farm is read-only.
+pub async fn enforce_read_only(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    if let Err(e) = check_read_only(&req) {
+        return Ok(req.error_response(e).map_into_right_body());
+    }
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
@@ -0,0 +1,166 @@
+//! Evaluation of alert rules on herd metrics (see `shared::alerts`).
+//!
+//! `evaluate_alerts` computes the dashboard figures (see
+//! `handlers::stats::load_stats`) and the number of sick goats, and checks
+//! every active rule against them. A rule whose condition starts to hold
+//! fires: every worker whose role may view the metric's module gets an
+//! in-app notification, which the digests also send by email or SMS (see
+//! `crate::digests`). It fires again only once the condition has cleared.
+//! The scheduler evaluates the rules on each pass.
+
+use crate::errors::{AppError, ParseEnumError};
+use crate::handlers::scale::READ_AT_FORMAT;
+use crate::handlers::stats::load_stats;
+use crate::handlers::workers::load_workers;
+use crate::permissions::worker_permissions;
+use chrono::{NaiveDate, Utc};
+use rusqlite::{Connection, Row, params};
+use shared::alerts::{AlertCondition, AlertMetric, AlertRule};
+use shared::permissions::{PermissionAction, PermissionModule};
+use shared::stats::Kpi;
+use tracing::{debug, info};
+
+/// Health statuses counted as well; any other status is sick.
+const HEALTHY_STATUSES: [&str; 3] = ["healthy", "good", "excellent"];
+
+/// Columns read by `row_to_rule`, in order.
+pub const RULE_COLUMNS: &str =
+    "id, name, metric, condition, threshold, active, firing, last_fired_at";
+
+/// Reads an alert rule selected with `RULE_COLUMNS`.
+pub fn row_to_rule(row: &Row) -> Result<AlertRule, rusqlite::Error> {
+    let parse_error = |e: String, name: &'static str| {
+        rusqlite::Error::ToSqlConversionFailure(Box::new(AppError::ParseError(
+            ParseEnumError::new(&e, name),
+        )))
+    };
+    let metric: String = row.get(2)?;
+    let condition: String = row.get(3)?;
+    Ok(AlertRule {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        metric: AlertMetric::from_str(&metric).map_err(|e| parse_error(e, "AlertMetric"))?,
+        condition: AlertCondition::from_str(&condition)
+            .map_err(|e| parse_error(e, "AlertCondition"))?,
+        threshold: row.get(4)?,
+        active: row.get(5)?,
+        firing: row.get(6)?,
+        last_fired_at: row.get(7)?,
+    })
+}
+
+/// Goats whose health status is not one of `HEALTHY_STATUSES`.
+fn count_sick_goats(conn: &Connection) -> Result<f64, AppError> {
+    let sql = format!(
+        "SELECT COUNT(*) FROM goats \
+         WHERE deleted_at IS NULL AND LOWER(TRIM(health_status)) NOT IN ({})",
+        HEALTHY_STATUSES.map(|s| format!("'{}'", s)).join(", ")
+    );
+    let count: i64 = conn.query_row(&sql, [], |row| row.get(0))?;
+    Ok(count as f64)
+}
+
+/// Current value of each metric as of `as_of`, with its value in the
+/// previous period where it has one.
+pub fn metric_values(
+    conn: &Connection,
+    as_of: NaiveDate,
+) -> Result<Vec<(AlertMetric, f64, Option<f64>)>, AppError> {
+    let stats = load_stats(conn, as_of)?;
+    let kpi = |kpi: &Kpi| (kpi.value, kpi.previous());
+    AlertMetric::ALL
+        .into_iter()
+        .map(|metric| {
+            let (value, previous) = match metric {
+                AlertMetric::HerdSize => kpi(&stats.herd_size),
+                AlertMetric::HerdValue => kpi(&stats.herd_value),
+                AlertMetric::MilkThisWeek => kpi(&stats.milk_this_week),
+                AlertMetric::ExpensesThisMonth => kpi(&stats.expenses_this_month),
+                AlertMetric::SickGoats => (count_sick_goats(conn)?, None),
+            };
+            Ok((metric, value, previous))
+        })
+        .collect()
+}
+
+/// Sends `message` as an in-app notification to every worker whose role may
+/// view `module`, returning how many were notified.
+pub fn notify_viewers(
+    conn: &Connection,
+    module: PermissionModule,
+    message: &str,
+    sent_at: &str,
+) -> Result<usize, AppError> {
+    let mut notified = 0;
+    for worker in load_workers(conn)? {
+        let permissions = worker_permissions(conn, worker.id)?;
+        if !permissions.allows(module, PermissionAction::View) {
+            continue;
+        }
+        conn.execute(
+            "INSERT INTO notifications (worker_id, message, created_at) VALUES (?1, ?2, ?3)",
+            params![worker.id, message, sent_at],
+        )?;
+        notified += 1;
+    }
+    Ok(notified)
+}
+
+/// Notifies every worker whose role may view `rule`'s module that it fired
+/// at `value`, returning how many were notified.
+fn notify_firing(
+    conn: &Connection,
+    rule: &AlertRule,
+    value: f64,
+    fired_at: &str,
+) -> Result<usize, AppError> {
+    let message = format!(
+        "Alert {}: {} (now {})",
+        rule.name.trim(),
+        rule.describe(),
+        (value * 100.0).round() / 100.0
+    );
+    notify_viewers(conn, rule.metric.module(), &message, fired_at)
+}
+
+/// Checks every active alert rule against the metrics as of `today`,
+/// notifying workers of the rules that start to fire. Returns how many
+/// rules fired.
+pub fn evaluate_alerts(conn: &mut Connection, today: NaiveDate) -> Result<usize, AppError> {
+    let values = metric_values(conn, today)?;
+    let fired_at = Utc::now().format(READ_AT_FORMAT).to_string();
+    let tx = conn.transaction()?;
+    let rules = {
+        let mut stmt = tx.prepare(&format!(
+            "SELECT {} FROM alert_rules WHERE active = 1 ORDER BY id",
+            RULE_COLUMNS
+        ))?;
+        stmt.query_map([], row_to_rule)?
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    let mut fired = 0;
+    for rule in rules {
+        let Some((_, value, previous)) = values.iter().find(|(m, _, _)| *m == rule.metric) else {
+            continue;
+        };
+        let holds = rule.holds(*value, *previous);
+        if holds == rule.firing {
+            continue;
+        }
+        if holds {
+            let notified = notify_firing(&tx, &rule, *value, &fired_at)?;
+            tx.execute(
+                "UPDATE alert_rules SET firing = 1, last_fired_at = ?1 WHERE id = ?2",
+                params![fired_at, rule.id],
+            )?;
+            info!(rule = %rule.name, value, notified, "Alert rule fired");
+            fired += 1;
+        } else {
+            tx.execute("UPDATE alert_rules SET firing = 0 WHERE id = ?1", [rule.id])?;
+            debug!(rule = %rule.name, value, "Alert rule cleared");
+        }
+    }
+    tx.commit()?;
+    Ok(fired)
+}
@@ -0,0 +1,485 @@
+//! Export and import of the whole farm as a portable archive (see
+//! `shared::archive` for the format).
+//!
+//! Tables are exported generically, every row with every column, so the
+//! archive keeps up with new migrations without changes here. Left out are
+//! `SKIPPED_TABLES`, which belong to the instance rather than the farm, and
+//! `SKIPPED_COLUMNS`; an archive carries no way to sign in to the farm, and
+//! an import never changes how anyone signs in.
+//!
+//! An import only fills a farm without goats, and does so in one
+//! transaction: each archived table replaces the rows the migrations seeded
+//! (settings, report templates, the breed catalog) and keeps its IDs, so
+//! references between rows stay intact. Every table and file is checked
+//! against the SHA-256 in the manifest before anything is written, and the
+//! references between the restored rows when the transaction commits.
+//!
+//! `encrypt_archive` and `decrypt_archive` seal a finished archive with a
+//! passphrase and open it again, as laid out in `shared::archive`.
+
+use crate::errors::AppError;
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use chrono::Utc;
+use rand::RngCore;
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{Connection, ErrorCode, params_from_iter};
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+use shared::archive::{
+    ARCHIVE_FORMAT, ARCHIVE_VERSION, ArchiveFile, ArchiveManifest, ArchiveSummary, ArchiveTable,
+    ENCRYPTED_MAGIC, MANIFEST_PATH, check_passphrase, is_encrypted,
+};
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Write};
+use tracing::{debug, info, warn};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Largest archive accepted for import.
+pub const MAX_ARCHIVE_BYTES: usize = 256 * 1024 * 1024;
+
+/// Largest unpacked entry read from an archive, and largest total of all
+/// of them, guarding against archives that unpack to far more than they
+/// weigh.
+const MAX_ENTRY_BYTES: u64 = 256 * 1024 * 1024;
+const MAX_UNPACKED_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Most entries read from an archive: the manifest, one per table and one
+/// per photo or recording.
+const MAX_ENTRIES: usize = 50_000;
+
+/// Tables that are not part of the farm: the migration history, background
+/// jobs, the API keys and sessions of this instance's devices, the OAuth
+/// sign-ins under way and the accounts linked for them, the access tokens
+/// of scripts, the owner's password, and recent failed sign-ins.
+const SKIPPED_TABLES: [&str; 9] = [
+    "schema_migrations",
+    "jobs",
+    "api_keys",
+    "sessions",
+    "oauth_states",
+    "oauth_identities",
+    "access_tokens",
+    "owner_credentials",
+    "sign_in_attempts",
+];
+
+/// Columns of farm tables that hold credentials: workers' PINs, which the
+/// owner sets again after an import.
+const SKIPPED_COLUMNS: [(&str, &str); 1] = [("workers", "pin_hash")];
+
+/// Whether `column` of `table` is archived.
+fn is_archived(table: &str, column: &str) -> bool {
+    !SKIPPED_COLUMNS.contains(&(table, column))
+}
+
+/// Length of the random salt the passphrase is stretched with.
+const SALT_LEN: usize = 16;
+
+/// Length of the AES-GCM nonce.
+const NONCE_LEN: usize = 12;
+
+/// Hex-encoded SHA-256 of `bytes`.
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Latest migration applied to the database.
+fn schema_version(conn: &Connection) -> Result<i64, AppError> {
+    Ok(conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+/// Names of the tables holding farm data, in alphabetical order.
+fn farm_tables(conn: &Connection) -> Result<Vec<String>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master
+         WHERE type = 'table' AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\'
+         ORDER BY name",
+    )?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    let mut tables = Vec::new();
+    for name in names {
+        let name = name?;
+        if !SKIPPED_TABLES.contains(&name.as_str()) {
+            tables.push(name);
+        }
+    }
+    Ok(tables)
+}
+
+/// Column names of `table`, which must be one of `farm_tables`.
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>, AppError> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info(\"{}\")", table))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(columns)
+}
+
+/// Maps a failure to write the archive in memory.
+fn write_error(e: impl std::fmt::Display) -> AppError {
+    AppError::Upstream(format!("Farm archive could not be written: {}", e))
+}
+
+/// Adds the file `path` holding `bytes` to the archive.
+fn write_entry(
+    zip: &mut ZipWriter<Cursor<Vec<u8>>>,
+    path: &str,
+    bytes: &[u8],
+    options: SimpleFileOptions,
+) -> Result<(), AppError> {
+    zip.start_file(path, options).map_err(write_error)?;
+    zip.write_all(bytes).map_err(write_error)
+}
+
+/// Writes every farm table, and the binary values in them, into a new
+/// archive and returns its bytes.
+///
+/// # Errors
+/// - `AppError::DbError` if a table cannot be read.
+pub fn export_archive(conn: &Connection) -> Result<Vec<u8>, AppError> {
+    let json_options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    // Photos and recordings are compressed already
+    let file_options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let mut manifest = ArchiveManifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        schema_version: schema_version(conn)?,
+        exported_at: Utc::now(),
+        tables: Vec::new(),
+        files: Vec::new(),
+    };
+
+    for table in farm_tables(conn)? {
+        let mut stmt = conn.prepare(&format!("SELECT * FROM \"{}\" ORDER BY rowid", table))?;
+        let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+        let mut rows = stmt.query([])?;
+        let mut records = Vec::new();
+        while let Some(row) = rows.next()? {
+            let mut record = Map::new();
+            for (i, column) in columns.iter().enumerate() {
+                if !is_archived(&table, column) {
+                    continue;
+                }
+                let value = match row.get_ref(i)? {
+                    ValueRef::Null => Value::Null,
+                    ValueRef::Integer(n) => n.into(),
+                    ValueRef::Real(x) => x.into(),
+                    ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned().into(),
+                    ValueRef::Blob(bytes) => {
+                        let path = format!("files/{}/{}.{}", table, records.len() + 1, column);
+                        write_entry(&mut zip, &path, bytes, file_options)?;
+                        manifest.files.push(ArchiveFile {
+                            path: path.clone(),
+                            bytes: bytes.len() as u64,
+                            sha256: sha256_hex(bytes),
+                        });
+                        json!({ "file": path })
+                    }
+                };
+                record.insert(column.clone(), value);
+            }
+            records.push(Value::Object(record));
+        }
+        debug!(table = %table, rows = records.len(), "Archived table");
+
+        let path = format!("tables/{}.json", table);
+        let bytes = serde_json::to_vec(&records)?;
+        write_entry(&mut zip, &path, &bytes, json_options)?;
+        manifest.tables.push(ArchiveTable {
+            name: table,
+            rows: records.len(),
+            path,
+            sha256: sha256_hex(&bytes),
+        });
+    }
+
+    let bytes = serde_json::to_vec_pretty(&manifest)?;
+    write_entry(&mut zip, MANIFEST_PATH, &bytes, json_options)?;
+    let archive = zip.finish().map_err(write_error)?.into_inner();
+    info!(
+        tables = manifest.tables.len(),
+        files = manifest.files.len(),
+        bytes = archive.len(),
+        "Exported farm archive"
+    );
+    Ok(archive)
+}
+
+/// Reads the file `path` from the archive, adding its size to `unpacked`,
+/// the bytes read from the archive so far.
+fn read_entry(
+    zip: &mut ZipArchive<Cursor<&[u8]>>,
+    path: &str,
+    unpacked: &mut u64,
+) -> Result<Vec<u8>, AppError> {
+    let entry = zip
+        .by_name(path)
+        .map_err(|_| AppError::InvalidInput(format!("The archive is missing {}", path)))?;
+    let limit = MAX_ENTRY_BYTES.min(MAX_UNPACKED_BYTES.saturating_sub(*unpacked));
+    let mut bytes = Vec::new();
+    entry
+        .take(limit + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| AppError::InvalidInput(format!("{} is damaged: {}", path, e)))?;
+    if bytes.len() as u64 > limit {
+        return Err(AppError::InvalidInput(format!(
+            "{} is too large; archives may unpack to at most {} MB, and each file to {} MB",
+            path,
+            MAX_UNPACKED_BYTES / (1024 * 1024),
+            MAX_ENTRY_BYTES / (1024 * 1024)
+        )));
+    }
+    *unpacked += bytes.len() as u64;
+    Ok(bytes)
+}
+
+/// Fails unless `bytes` hash to `sha256`.
+fn check_digest(path: &str, bytes: &[u8], sha256: &str) -> Result<(), AppError> {
+    if sha256_hex(bytes) != sha256.to_ascii_lowercase() {
+        warn!(path, "Archive entry does not match its checksum");
+        return Err(AppError::InvalidInput(format!(
+            "{} does not match its checksum; the archive is damaged",
+            path
+        )));
+    }
+    Ok(())
+}
+
+/// Maps a constraint the archived rows break, such as a reference to a row
+/// the archive does not hold, to `AppError::InvalidInput`.
+fn inconsistent(e: rusqlite::Error) -> AppError {
+    match e {
+        rusqlite::Error::SqliteFailure(failure, detail)
+            if failure.code == ErrorCode::ConstraintViolation =>
+        {
+            AppError::InvalidInput(format!(
+                "The archive's records are inconsistent: {}",
+                detail.unwrap_or_else(|| failure.to_string())
+            ))
+        }
+        e => AppError::DbError(e),
+    }
+}
+
+/// Converts an archived cell back to an SQLite value.
+fn to_sql(value: &Value, files: &HashMap<String, Vec<u8>>) -> Result<SqlValue, AppError> {
+    Ok(match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(n) => SqlValue::Integer(n),
+            None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        Value::Object(object) => {
+            let path = object.get("file").and_then(Value::as_str).ok_or_else(|| {
+                AppError::InvalidInput("Archived values may only refer to files".into())
+            })?;
+            let bytes = files.get(path).ok_or_else(|| {
+                AppError::InvalidInput(format!("{} is not listed in the manifest", path))
+            })?;
+            SqlValue::Blob(bytes.clone())
+        }
+        Value::Array(_) => {
+            return Err(AppError::InvalidInput(
+                "Archived values may not be lists".into(),
+            ));
+        }
+    })
+}
+
+/// Restores the farm in `archive` into this database, which must hold no
+/// goats.
+///
+/// Tables this instance does not have are left out and listed in the
+/// summary; columns it does not have are ignored, and columns the archive
+/// lacks get their defaults.
+///
+/// # Errors
+/// - `AppError::InvalidInput` if `archive` is not a farm archive, holds too
+///   many files or unpacks to too much, any entry fails its checksum, the
+///   archive was written by an instance with newer migrations, or the farm
+///   already has goats. Nothing is imported then.
+pub fn import_archive(conn: &mut Connection, archive: &[u8]) -> Result<ArchiveSummary, AppError> {
+    let mut zip = ZipArchive::new(Cursor::new(archive))
+        .map_err(|e| AppError::InvalidInput(format!("Not a farm archive: {}", e)))?;
+    if zip.len() > MAX_ENTRIES {
+        return Err(AppError::InvalidInput(format!(
+            "The archive holds {} files, more than the {} a farm archive may",
+            zip.len(),
+            MAX_ENTRIES
+        )));
+    }
+    let mut unpacked = 0;
+    let manifest: ArchiveManifest =
+        serde_json::from_slice(&read_entry(&mut zip, MANIFEST_PATH, &mut unpacked)?).map_err(
+            |e| AppError::InvalidInput(format!("The archive manifest is unreadable: {}", e)),
+        )?;
+    if manifest.format != ARCHIVE_FORMAT || manifest.version > ARCHIVE_VERSION {
+        return Err(AppError::InvalidInput(format!(
+            "Unsupported archive format {} version {}",
+            manifest.format, manifest.version
+        )));
+    }
+    let local_version = schema_version(conn)?;
+    if manifest.schema_version > local_version {
+        return Err(AppError::InvalidInput(format!(
+            "The archive needs database version {}, this instance is at {}; upgrade before importing",
+            manifest.schema_version, local_version
+        )));
+    }
+    let goats: i64 = conn.query_row("SELECT COUNT(*) FROM goats", [], |row| row.get(0))?;
+    if goats > 0 {
+        return Err(AppError::InvalidInput(
+            "Farm archives can only be imported into a farm without goats".into(),
+        ));
+    }
+
+    let mut files = HashMap::new();
+    for file in &manifest.files {
+        let bytes = read_entry(&mut zip, &file.path, &mut unpacked)?;
+        check_digest(&file.path, &bytes, &file.sha256)?;
+        files.insert(file.path.clone(), bytes);
+    }
+
+    let local_tables = farm_tables(conn)?;
+    let mut summary = ArchiveSummary {
+        files: files.len(),
+        ..Default::default()
+    };
+    let mut tables = Vec::new();
+    for table in &manifest.tables {
+        let bytes = read_entry(&mut zip, &table.path, &mut unpacked)?;
+        check_digest(&table.path, &bytes, &table.sha256)?;
+        if !local_tables.contains(&table.name) {
+            warn!(table = %table.name, "Skipping archived table this instance does not have");
+            summary.skipped.push(table.name.clone());
+            continue;
+        }
+        let rows: Vec<Map<String, Value>> = serde_json::from_slice(&bytes)
+            .map_err(|e| AppError::InvalidInput(format!("{} is unreadable: {}", table.path, e)))?;
+        if rows.len() != table.rows {
+            return Err(AppError::InvalidInput(format!(
+                "{} holds {} rows, the manifest lists {}",
+                table.path,
+                rows.len(),
+                table.rows
+            )));
+        }
+        tables.push((table.name.as_str(), rows));
+    }
+
+    let tx = conn.transaction()?;
+    // Rows refer to tables restored later; references are checked on commit
+    tx.pragma_update(None, "defer_foreign_keys", true)?;
+    for (name, _) in &tables {
+        tx.execute(&format!("DELETE FROM \"{}\"", name), [])?;
+    }
+    for (name, rows) in &tables {
+        let columns: Vec<String> = table_columns(&tx, name)?
+            .into_iter()
+            .filter(|c| is_archived(name, c))
+            .collect();
+        for row in rows {
+            let mut names = Vec::new();
+            let mut values = Vec::new();
+            for (column, value) in row.iter().filter(|(c, _)| columns.contains(c)) {
+                names.push(format!("\"{}\"", column));
+                values.push(to_sql(value, &files)?);
+            }
+            let sql = if names.is_empty() {
+                format!("INSERT INTO \"{}\" DEFAULT VALUES", name)
+            } else {
+                format!(
+                    "INSERT INTO \"{}\" ({}) VALUES ({})",
+                    name,
+                    names.join(", "),
+                    vec!["?"; names.len()].join(", ")
+                )
+            };
+            tx.prepare_cached(&sql)?
+                .execute(params_from_iter(values))
+                .map_err(inconsistent)?;
+        }
+        debug!(table = %name, rows = rows.len(), "Restored table");
+        summary.tables += 1;
+        summary.rows += rows.len();
+    }
+    tx.commit().map_err(inconsistent)?;
+
+    info!(
+        tables = summary.tables,
+        rows = summary.rows,
+        files = summary.files,
+        exported_at = %manifest.exported_at,
+        "Imported farm archive"
+    );
+    Ok(summary)
+}
+
+/// Derives the AES-256 key for `passphrase` and `salt`.
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key<Aes256Gcm>, AppError> {
+    let mut key = Key::<Aes256Gcm>::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| AppError::InvalidInput(format!("Unusable passphrase: {}", e)))?;
+    Ok(key)
+}
+
+/// Encrypts `archive` with `passphrase`, with a fresh salt and nonce.
+///
+/// # Errors
+/// - `AppError::InvalidInput` if the passphrase is too short.
+pub fn encrypt_archive(archive: &[u8], passphrase: &str) -> Result<Vec<u8>, AppError> {
+    check_passphrase(passphrase).map_err(AppError::InvalidInput)?;
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt)?);
+    let sealed = cipher
+        .encrypt(Nonce::from_slice(&nonce), archive)
+        .map_err(|_| write_error("encryption failed"))?;
+
+    let mut file = Vec::with_capacity(ENCRYPTED_MAGIC.len() + SALT_LEN + NONCE_LEN + sealed.len());
+    file.extend_from_slice(ENCRYPTED_MAGIC);
+    file.extend_from_slice(&salt);
+    file.extend_from_slice(&nonce);
+    file.extend_from_slice(&sealed);
+    Ok(file)
+}
+
+/// Decrypts an archive written by `encrypt_archive`.
+///
+/// # Errors
+/// - `AppError::InvalidInput` if `file` is not an encrypted archive, or the
+///   passphrase is wrong or the file was damaged; the two cannot be told
+///   apart.
+pub fn decrypt_archive(file: &[u8], passphrase: &str) -> Result<Vec<u8>, AppError> {
+    let header = ENCRYPTED_MAGIC.len() + SALT_LEN + NONCE_LEN;
+    if !is_encrypted(file) || file.len() < header {
+        return Err(AppError::InvalidInput(
+            "Not an encrypted farm archive".into(),
+        ));
+    }
+    let salt = &file[ENCRYPTED_MAGIC.len()..ENCRYPTED_MAGIC.len() + SALT_LEN];
+    let nonce = &file[ENCRYPTED_MAGIC.len() + SALT_LEN..header];
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, salt)?);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), &file[header..])
+        .map_err(|_| {
+            warn!("Farm archive could not be decrypted");
+            AppError::InvalidInput("Wrong passphrase, or the archive is damaged".into())
+        })
+}
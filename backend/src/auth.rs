@@ -0,0 +1,912 @@
+//! API-key authentication for devices that push data (e.g. weigh scales),
+//! and the sessions of devices signed in to the dashboard.
+//!
+//! Keys are random 32-byte values, hex encoded and prefixed with `yagi_`.
+//! Only their SHA-256 hash is stored, so a leaked database does not leak
+//! usable keys. Devices send the key in an `X-Api-Key` header or as an
+//! `Authorization: Bearer` token.
+//!
+//! Session tokens are made and stored the same way and sent in the
+//! `shared::sessions::SESSION_HEADER` header. The `check_session` middleware
+//! refuses requests with a revoked or unknown token, and those without one
+//! unless they carry an access token or are public (see `is_public`), and
+//! records each session's last activity. Signing in takes the owner's
+//! password, set when the farm is set up (see `ensure_owner_password`), or
+//! a worker's PIN (see `check_credentials`). Both are stored as Argon2
+//! hashes since, unlike keys, people choose them, and repeated failures
+//! lock the account out for a while.
+//!
+//! Scripts authenticate with personal access tokens, made and stored the
+//! same way and sent as `Authorization: Bearer` tokens. The
+//! `check_access_token` middleware refuses unknown bearer tokens, except
+//! device API keys on the routes that take them, and requests outside a
+//! token's scope (see `shared::tokens`), and records when each token was
+//! last used.
+//!
+//! Workers can also sign in with an `OAuthProvider`; the providers the
+//! server offers are registered as `web::Data<OAuthProviders>`. The first
+//! sign-in links the provider's account to the worker whose contact is the
+//! same verified email (see `link_identity`). `GoogleOAuth`, the built-in
+//! provider, exchanges codes with Google over HTTPS (see `crate::outbound`)
+//! and checks the ID token's signature against Google's published keys.
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::outbound::HttpEndpoint;
+use crate::permissions::{action_for_method, module_for_path, worker_permissions};
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::{Method, header};
+use actix_web::middleware::Next;
+use actix_web::{HttpMessage, HttpRequest, web};
+use argon2::password_hash::SaltString;
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use rand::RngCore;
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use shared::permissions::{PermissionAction, PermissionModule};
+use shared::sessions::{SESSION_HEADER, validate_owner_password};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tracing::{debug, info, trace, warn};
+
+/// Header carrying a device API key.
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+/// Prefix on every generated key, making keys easy to recognise in configs.
+const KEY_PREFIX: &str = "yagi_";
+
+/// Generates a new random API key.
+pub fn generate_key() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}{}", KEY_PREFIX, hex)
+}
+
+/// Hex-encoded SHA-256 hash of `key`, as stored in `api_keys.key_hash`.
+pub fn hash_key(key: &str) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Extracts the API key from the `X-Api-Key` header or a bearer token.
+fn presented_key(req: &HttpRequest) -> Option<&str> {
+    if let Some(key) = req.headers().get(API_KEY_HEADER) {
+        return key.to_str().ok();
+    }
+    req.headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// Checks the request carries a valid, unrevoked API key and returns the
+/// key's ID, recording when it was last used.
+///
+/// # Errors
+/// - `AppError::Unauthorized` if no key is sent or it is unknown or revoked.
+pub fn authenticate(conn: &Connection, req: &HttpRequest) -> Result<i64, AppError> {
+    verify_key(conn, presented_key(req).unwrap_or_default())
+}
+
+/// Checks `key` is a valid, unrevoked API key and returns its ID, recording
+/// when it was last used. For clients that cannot send headers, such as
+/// calendar apps subscribed to a feed URL carrying the key.
+///
+/// # Errors
+/// - `AppError::Unauthorized` if the key is empty, unknown or revoked.
+pub fn verify_key(conn: &Connection, key: &str) -> Result<i64, AppError> {
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(AppError::Unauthorized("Missing API key".into()));
+    }
+    let id: i64 = conn
+        .query_row(
+            "SELECT id FROM api_keys WHERE key_hash = ?1 AND revoked = 0",
+            [hash_key(key)],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            warn!("Rejected unknown or revoked API key");
+            AppError::Unauthorized("Invalid API key".into())
+        })?;
+    conn.execute(
+        "UPDATE api_keys SET last_used_at = CURRENT_TIMESTAMP WHERE id = ?1",
+        [id],
+    )?;
+    debug!(api_key_id = id, "Authenticated API key");
+    Ok(id)
+}
+
+/// The session a request was made in, added to the request's extensions by
+/// `check_session`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurrentSession(pub i64);
+
+/// Checks `token` belongs to a session that has not been revoked and
+/// returns the session's ID, recording the activity from `ip`.
+///
+/// # Errors
+/// - `AppError::Unauthorized` if the token is unknown or its session was
+///   revoked.
+pub fn verify_session(conn: &Connection, token: &str, ip: Option<&str>) -> Result<i64, AppError> {
+    let id: i64 = conn
+        .query_row(
+            "SELECT id FROM sessions WHERE token_hash = ?1 AND revoked_at IS NULL",
+            [hash_key(token.trim())],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            warn!("Rejected unknown or revoked session token");
+            AppError::Unauthorized("This device was signed out; sign in again".into())
+        })?;
+    // At most one write a minute per session
+    conn.execute(
+        "UPDATE sessions SET last_seen_at = CURRENT_TIMESTAMP, ip = COALESCE(?2, ip)
+         WHERE id = ?1 AND last_seen_at <= datetime('now', '-1 minute')",
+        params![id, ip],
+    )?;
+    debug!(session_id = id, "Authenticated session");
+    Ok(id)
+}
+
+/// Hashes a password or PIN with Argon2 and a fresh salt, as stored in
+/// `owner_credentials` and `workers.pin_hash`.
+pub fn hash_secret(secret: &str) -> Result<String, AppError> {
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let unusable = |e: argon2::password_hash::Error| {
+        AppError::InvalidInput(format!("Unusable password: {}", e))
+    };
+    let salt = SaltString::encode_b64(&salt).map_err(unusable)?;
+    let hash = Argon2::default()
+        .hash_password(secret.as_bytes(), &salt)
+        .map_err(unusable)?;
+    Ok(hash.to_string())
+}
+
+/// Whether `secret` is the password or PIN `hash` was made from.
+fn secret_matches(hash: &str, secret: &str) -> bool {
+    PasswordHash::new(hash).is_ok_and(|hash| {
+        Argon2::default()
+            .verify_password(secret.as_bytes(), &hash)
+            .is_ok()
+    })
+}
+
+/// Minutes over which failed sign-ins are counted.
+const SIGN_IN_WINDOW_MINUTES: i64 = 15;
+
+/// Failed sign-ins to one account from one address, within
+/// `SIGN_IN_WINDOW_MINUTES`, after which that address is locked out of it.
+const MAX_FAILURES_PER_ADDRESS: i64 = 5;
+
+/// Failed sign-ins to one account from anywhere, within
+/// `SIGN_IN_WINDOW_MINUTES`, after which it is locked out altogether.
+const MAX_FAILURES_PER_ACCOUNT: i64 = 20;
+
+/// A hash no PIN or password is checked against in earnest, verified when
+/// there is nothing to verify so an unknown worker takes as long to refuse
+/// as a wrong PIN.
+static DUMMY_HASH: LazyLock<String> =
+    LazyLock::new(|| hash_secret("no such worker").expect("Failed to hash the dummy secret"));
+
+/// Refuses to check credentials for `account` from `ip` while it is locked
+/// out (see `MAX_FAILURES_PER_ADDRESS` and `MAX_FAILURES_PER_ACCOUNT`).
+fn check_lockout(conn: &Connection, account: &str, ip: Option<&str>) -> Result<(), AppError> {
+    let (from_address, from_anywhere): (i64, i64) = conn.query_row(
+        "SELECT COALESCE(SUM(ip IS ?2), 0), COUNT(*) FROM sign_in_attempts
+         WHERE account = ?1 AND attempted_at > datetime('now', ?3)",
+        params![account, ip, format!("-{} minutes", SIGN_IN_WINDOW_MINUTES)],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    if from_address >= MAX_FAILURES_PER_ADDRESS || from_anywhere >= MAX_FAILURES_PER_ACCOUNT {
+        warn!(account, ?ip, "Refused sign-in to a locked account");
+        return Err(AppError::TooManyRequests(format!(
+            "Too many failed sign-ins; try again in {} minutes",
+            SIGN_IN_WINDOW_MINUTES
+        )));
+    }
+    Ok(())
+}
+
+/// Records a failed sign-in to `account` from `ip`, forgetting those too
+/// old to count.
+fn record_failure(conn: &Connection, account: &str, ip: Option<&str>) -> Result<(), AppError> {
+    conn.execute(
+        "DELETE FROM sign_in_attempts WHERE attempted_at <= datetime('now', ?1)",
+        [format!("-{} minutes", SIGN_IN_WINDOW_MINUTES)],
+    )?;
+    conn.execute(
+        "INSERT INTO sign_in_attempts (account, ip) VALUES (?1, ?2)",
+        params![account, ip],
+    )?;
+    Ok(())
+}
+
+/// Checks the credentials of a device signing in from `ip`: the PIN of the
+/// worker called `worker`, or the owner's password without one. Returns
+/// the worker's ID, or `None` for the owner.
+///
+/// Failures are recorded, and an account that failed too often recently is
+/// locked out, from `ip` or altogether; a success clears the failures from
+/// `ip`.
+///
+/// # Errors
+/// - `AppError::Unauthorized` for an unknown worker, one without a PIN, a
+///   wrong PIN or password, or while the owner has no password.
+/// - `AppError::TooManyRequests` while the account is locked out.
+pub fn check_credentials(
+    conn: &Connection,
+    worker: Option<&str>,
+    secret: &str,
+    ip: Option<&str>,
+) -> Result<Option<i64>, AppError> {
+    let account = match worker {
+        Some(name) => format!("worker:{}", name.trim().to_lowercase()),
+        None => "owner".to_string(),
+    };
+    check_lockout(conn, &account, ip)?;
+
+    let checked = match worker {
+        Some(name) => {
+            let found: Option<(i64, Option<String>)> = conn
+                .query_row(
+                    "SELECT id, pin_hash FROM workers WHERE lower(trim(name)) = lower(trim(?1))",
+                    [name],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            match found {
+                Some((id, Some(hash))) if secret_matches(&hash, secret) => Ok(Some(id)),
+                _ => {
+                    if found.is_none_or(|(_, hash)| hash.is_none()) {
+                        secret_matches(&DUMMY_HASH, secret);
+                    }
+                    Err(AppError::Unauthorized("Unknown worker or wrong PIN".into()))
+                }
+            }
+        }
+        None => {
+            let stored: Option<String> = conn
+                .query_row(
+                    "SELECT password_hash FROM owner_credentials WHERE id = 1",
+                    [],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            match stored {
+                Some(hash) if secret_matches(&hash, secret) => Ok(None),
+                Some(_) => Err(AppError::Unauthorized("Wrong password".into())),
+                None => {
+                    secret_matches(&DUMMY_HASH, secret);
+                    Err(AppError::Unauthorized(
+                        "The owner's password has not been set up".into(),
+                    ))
+                }
+            }
+        }
+    };
+    match &checked {
+        Ok(worker_id) => {
+            conn.execute(
+                "DELETE FROM sign_in_attempts WHERE account = ?1 AND ip IS ?2",
+                params![account, ip],
+            )?;
+            debug!(?worker_id, "Credentials accepted");
+        }
+        Err(e) => {
+            record_failure(conn, &account, ip)?;
+            warn!(account, ?ip, "Refused sign-in: {}", e);
+        }
+    }
+    checked
+}
+
+/// Sets the owner's password at setup, if the farm has none yet: to
+/// `configured` if given, else to a random one, which is returned to show
+/// the operator once.
+///
+/// # Errors
+/// - `AppError::InvalidInput` if `configured` is too short.
+pub fn ensure_owner_password(
+    conn: &Connection,
+    configured: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM owner_credentials WHERE id = 1)",
+        [],
+        |row| row.get(0),
+    )?;
+    if exists {
+        return Ok(None);
+    }
+    match configured {
+        Some(password) => {
+            set_owner_password(conn, password)?;
+            Ok(None)
+        }
+        None => {
+            let mut bytes = [0u8; 9];
+            rand::thread_rng().fill_bytes(&mut bytes);
+            let password: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            set_owner_password(conn, &password)?;
+            Ok(Some(password))
+        }
+    }
+}
+
+/// Sets the owner's password to `password`.
+///
+/// # Errors
+/// - `AppError::InvalidInput` if the password is too short.
+pub fn set_owner_password(conn: &Connection, password: &str) -> Result<(), AppError> {
+    validate_owner_password(password).map_err(AppError::InvalidInput)?;
+    conn.execute(
+        "INSERT INTO owner_credentials (id, password_hash) VALUES (1, ?1)
+         ON CONFLICT(id) DO UPDATE SET password_hash = excluded.password_hash,
+             updated_at = CURRENT_TIMESTAMP",
+        [hash_secret(password)?],
+    )?;
+    info!("Owner password set");
+    Ok(())
+}
+
+/// Whether a `method` request to `path` signs a device in, directly or
+/// with an OAuth provider.
+fn signs_in(method: &Method, path: &str) -> bool {
+    (*method == Method::POST && path == "/sessions") || path.starts_with("/sessions/oauth")
+}
+
+/// Routes whose handlers check a device API key themselves (see
+/// `authenticate`), as `(method, path)`; a path ending in `/` covers the
+/// paths below it.
+const API_KEY_ROUTES: [(Method, &str); 4] = [
+    (Method::POST, "/scale/readings"),
+    (Method::POST, "/sensors/readings"),
+    (Method::POST, "/gps/positions"),
+    (Method::GET, "/calendar/feed.ics"),
+];
+
+/// Whether a `method` request to `path` is checked by its handler with a
+/// device API key instead of a session.
+pub fn takes_api_key(method: &Method, path: &str) -> bool {
+    API_KEY_ROUTES.iter().any(|(m, route)| {
+        m == method
+            && match route.strip_suffix('/') {
+                Some(scope) => path.strip_prefix(scope).is_some_and(|rest| rest.len() > 1),
+                None => path == *route,
+            }
+    })
+}
+
+/// Whether a `method` request to `path` may be made without signing in:
+/// signing in itself, public share links, the operator's tenant endpoints
+/// (which check the admin key), crash reports, and routes taking a device
+/// API key.
+pub fn is_public(method: &Method, path: &str) -> bool {
+    let in_scope = |scope: &str| {
+        path.strip_prefix(scope)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    };
+    signs_in(method, path)
+        || in_scope("/public")
+        || in_scope("/tenants")
+        || (*method == Method::POST && path == "/client-errors")
+        || takes_api_key(method, path)
+}
+
+/// Checks the session token on `req` and returns its session, if it
+/// carries one. Signing in is let through, so a signed-out device can sign
+/// in again.
+fn check_session_token(req: &ServiceRequest) -> Result<Option<i64>, AppError> {
+    let Some(token) = req.headers().get(SESSION_HEADER) else {
+        return Ok(None);
+    };
+    if signs_in(req.method(), req.path()) {
+        return Ok(None);
+    }
+    // Without a pool (e.g. an unknown tenant) the handler reports the problem
+    let Some(db) = req.app_data::<web::Data<DbPool>>() else {
+        return Ok(None);
+    };
+    let token = token
+        .to_str()
+        .map_err(|_| AppError::Unauthorized("Invalid session token".into()))?;
+    let ip = req.peer_addr().map(|addr| addr.ip().to_string());
+    let conn = db.get_conn()?;
+    verify_session(&conn, token, ip.as_deref()).map(Some)
+}
+
+/// Checks `req` was made in a session, with a personal access token (see
+/// `check_access_token`), or to a public route, adding the session to the
+/// request's extensions.
+fn check_signed_in(req: &ServiceRequest) -> Result<(), AppError> {
+    if let Some(id) = check_session_token(req)? {
+        req.extensions_mut().insert(CurrentSession(id));
+        return Ok(());
+    }
+    if req.extensions().contains::<TokenScope>() || is_public(req.method(), req.path()) {
+        return Ok(());
+    }
+    debug!(method = %req.method(), path = req.path(), "Refused request without a session");
+    Err(AppError::Unauthorized("Sign in first".into()))
+}
+
+/// Middleware checking session tokens.
+///
+/// Requests with a valid token get a `CurrentSession` extension. Those
+/// without one are refused unless they carry a personal access token or
+/// are public (see `is_public`). Must run after
+/// `crate::tenants::resolve_tenant`, so it sees the requesting tenant's
+/// database, and after `check_access_token`.
+///
+/// # Errors
+/// - Responds with HTTP 401 to a request with a revoked or unknown token,
+///   or without one to a route that is not public.
+pub async fn check_session(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    if let Err(e) = check_signed_in(&req) {
+        return Ok(req.error_response(e).map_into_right_body());
+    }
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
+
+/// The scope of a personal access token (see `shared::tokens`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenScope {
+    pub id: i64,
+    pub read_only: bool,
+    /// Modules the token may reach; empty for all of them
+    pub modules: Vec<PermissionModule>,
+    /// The worker who made the token, whose role also limits it
+    pub worker_id: Option<i64>,
+}
+
+/// Looks up `token` among the personal access tokens, recording its use.
+/// Returns `None` for anything else, such as a device API key.
+///
+/// # Errors
+/// - `AppError::Unauthorized` if the token was revoked.
+pub fn verify_access_token(conn: &Connection, token: &str) -> Result<Option<TokenScope>, AppError> {
+    let found = conn
+        .query_row(
+            "SELECT id, read_only, modules, worker_id, revoked_at IS NOT NULL
+             FROM access_tokens WHERE token_hash = ?1",
+            [hash_key(token.trim())],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, bool>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<i64>>(3)?,
+                    row.get::<_, bool>(4)?,
+                ))
+            },
+        )
+        .optional()?;
+    let Some((id, read_only, modules, worker_id, revoked)) = found else {
+        return Ok(None);
+    };
+    if revoked {
+        warn!(access_token_id = id, "Rejected revoked access token");
+        return Err(AppError::Unauthorized(
+            "This access token was revoked".into(),
+        ));
+    }
+    // At most one write a minute per token
+    conn.execute(
+        "UPDATE access_tokens SET last_used_at = CURRENT_TIMESTAMP
+         WHERE id = ?1 AND (last_used_at IS NULL OR last_used_at <= datetime('now', '-1 minute'))",
+        [id],
+    )?;
+    debug!(access_token_id = id, "Authenticated access token");
+    Ok(Some(TokenScope {
+        id,
+        read_only,
+        modules: serde_json::from_str(&modules)?,
+        worker_id,
+    }))
+}
+
+/// Checks a `method` request to `path` is within `scope`: no changes with a
+/// read-only token, only the token's modules if it is limited to some, no
+/// more than the role of the worker who made it allows, and never the
+/// tokens themselves.
+///
+/// # Errors
+/// - `AppError::Forbidden` naming what the token may not do.
+pub fn check_token_scope(
+    conn: &Connection,
+    scope: &TokenScope,
+    method: &Method,
+    path: &str,
+) -> Result<(), AppError> {
+    if path == "/tokens" || path.starts_with("/tokens/") {
+        return Err(AppError::Forbidden(
+            "Access tokens cannot manage access tokens".into(),
+        ));
+    }
+    let action = action_for_method(method);
+    if scope.read_only && action != PermissionAction::View {
+        return Err(AppError::Forbidden("This access token is read-only".into()));
+    }
+    let module = module_for_path(path);
+    if !scope.modules.is_empty() && !module.is_some_and(|m| scope.modules.contains(&m)) {
+        return Err(AppError::Forbidden(format!(
+            "This access token cannot reach {}",
+            path
+        )));
+    }
+    if let Some(module) = module
+        && !worker_permissions(conn, scope.worker_id)?.allows(module, action)
+    {
+        return Err(AppError::Forbidden(format!(
+            "This access token may not {} {}",
+            action.label().to_lowercase(),
+            module.label()
+        )));
+    }
+    Ok(())
+}
+
+/// Checks the bearer token on `req` is a personal access token within its
+/// scope, adding the scope to the request's extensions. On the routes that
+/// check a device API key (see `takes_api_key`) the handler checks it
+/// instead. A request without a bearer token must carry a session token,
+/// which `check_session` checks, or be public.
+fn check_bearer_token(req: &ServiceRequest) -> Result<(), AppError> {
+    let Some(token) = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        if req.headers().contains_key(SESSION_HEADER) || is_public(req.method(), req.path()) {
+            return Ok(());
+        }
+        debug!(method = %req.method(), path = req.path(), "Refused request without credentials");
+        return Err(AppError::Unauthorized("Sign in first".into()));
+    };
+    if takes_api_key(req.method(), req.path()) {
+        return Ok(());
+    }
+    // Without a pool (e.g. an unknown tenant) the handler reports the problem
+    let Some(db) = req.app_data::<web::Data<DbPool>>() else {
+        return Ok(());
+    };
+    let conn = db.get_conn()?;
+    let Some(scope) = verify_access_token(&conn, token)? else {
+        warn!(path = req.path(), "Rejected unknown bearer token");
+        return Err(AppError::Unauthorized("Invalid access token".into()));
+    };
+    check_token_scope(&conn, &scope, req.method(), req.path()).inspect_err(|e| {
+        warn!(
+            access_token_id = scope.id,
+            path = req.path(),
+            "Refused request: {}",
+            e
+        );
+    })?;
+    req.extensions_mut().insert(scope);
+    Ok(())
+}
+
+/// Middleware checking personal access tokens.
+///
+/// Requests with a token within its scope get the `TokenScope` as an
+/// extension; those with a session token, and public ones, pass on to
+/// `check_session`. Must run after `crate::tenants::resolve_tenant`, so it
+/// sees the requesting tenant's database, and before `check_session`.
+///
+/// # Errors
+/// - Responds with HTTP 401 to a request with an unknown or revoked token,
+///   or without credentials to a route that is not public.
+/// - Responds with HTTP 403 to a request outside the token's scope.
+pub async fn check_access_token(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    if let Err(e) = check_bearer_token(&req) {
+        return Ok(req.error_response(e).map_into_right_body());
+    }
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
+
+/// How long an OAuth provider may take to connect and to answer.
+pub const OAUTH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// The account an OAuth provider says signed in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OAuthIdentity {
+    /// The provider's stable ID for the account.
+    pub subject: String,
+    pub email: Option<String>,
+    /// Whether the provider checked the account owns `email`.
+    pub email_verified: bool,
+}
+
+/// An OAuth 2 provider workers can sign in with.
+///
+/// `identify` may block and is called on a worker thread.
+pub trait OAuthProvider: Send + Sync {
+    /// Short name used in URLs and stored with linked accounts, e.g. `google`.
+    fn name(&self) -> &str;
+
+    /// The provider's sign-in page, which sends the browser back to
+    /// `redirect_uri` with a code and `state`.
+    fn authorize_url(&self, state: &str, redirect_uri: &str) -> String;
+
+    /// The account that signed in, from the `code` the browser came back
+    /// with.
+    fn identify(&self, code: &str, redirect_uri: &str) -> Result<OAuthIdentity, AppError>;
+}
+
+/// The OAuth providers this server offers.
+#[derive(Clone, Default)]
+pub struct OAuthProviders(pub Vec<Arc<dyn OAuthProvider>>);
+
+impl OAuthProviders {
+    /// The provider called `name`, if offered.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn OAuthProvider>> {
+        self.0.iter().find(|p| p.name() == name).cloned()
+    }
+
+    /// Names of the providers offered.
+    pub fn names(&self) -> Vec<String> {
+        self.0.iter().map(|p| p.name().to_string()).collect()
+    }
+}
+
+/// Encodes `pairs` as `application/x-www-form-urlencoded`.
+fn form_encode(pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, utf8_percent_encode(value, NON_ALPHANUMERIC)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Google's sign-in page.
+const GOOGLE_AUTHORIZE_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+
+/// Google's token endpoint, where sign-in codes are exchanged.
+pub const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+
+/// The keys Google signs its ID tokens with, as a JSON Web Key Set.
+pub const GOOGLE_CERTS_URL: &str = "https://www.googleapis.com/oauth2/v3/certs";
+
+/// Issuers Google signs its ID tokens as.
+const GOOGLE_ISSUERS: [&str; 2] = ["https://accounts.google.com", "accounts.google.com"];
+
+/// Signs in with Google accounts through OpenID Connect.
+///
+/// Codes are exchanged at Google's token endpoint over HTTPS. The ID token
+/// that comes back is trusted only once its signature checks out against
+/// Google's published keys, which are fetched on first use and again
+/// whenever a token names a key not seen yet, as Google rotates them.
+#[derive(Debug, Clone)]
+pub struct GoogleOAuth {
+    client_id: String,
+    client_secret: String,
+    token_endpoint: HttpEndpoint,
+    certs_endpoint: HttpEndpoint,
+    keys: Arc<Mutex<JwkSet>>,
+}
+
+/// The claims of a Google ID token this server reads, besides the issuer,
+/// audience and expiry checked on decoding.
+#[derive(Deserialize)]
+struct GoogleClaims {
+    sub: String,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+}
+
+impl GoogleOAuth {
+    /// Google sign-in for the OAuth client `client_id`.
+    pub fn new(client_id: &str, client_secret: &str) -> Self {
+        GoogleOAuth {
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            token_endpoint: HttpEndpoint::new(GOOGLE_TOKEN_URL, "Google token endpoint")
+                .expect("Google's token URL is valid"),
+            certs_endpoint: HttpEndpoint::new(GOOGLE_CERTS_URL, "Google signing keys")
+                .expect("Google's key set URL is valid"),
+            keys: Arc::new(Mutex::new(JwkSet { keys: Vec::new() })),
+        }
+    }
+
+    /// Exchanges codes at `token_url` and fetches signing keys from
+    /// `certs_url` instead of Google's own endpoints, e.g. to go through a
+    /// proxy.
+    pub fn with_endpoints(mut self, token_url: &str, certs_url: &str) -> Result<Self, String> {
+        self.token_endpoint = HttpEndpoint::new(token_url, "Google token endpoint")?;
+        self.certs_endpoint = HttpEndpoint::new(certs_url, "Google signing keys")?;
+        Ok(self)
+    }
+
+    /// The key Google signs with under `kid`, fetching Google's keys again
+    /// if it is not among those known.
+    fn signing_key(&self, kid: &str) -> Result<Option<DecodingKey>, AppError> {
+        let mut keys = self
+            .keys
+            .lock()
+            .map_err(|_| AppError::Upstream("Google signing keys unavailable".into()))?;
+        if keys.find(kid).is_none() {
+            debug!(kid, "Fetching Google signing keys");
+            let (status, body) = self.certs_endpoint.get(OAUTH_TIMEOUT).map_err(|e| {
+                AppError::Upstream(format!("Google signing keys unreachable: {}", e))
+            })?;
+            if !(200..300).contains(&status) {
+                return Err(AppError::Upstream(format!(
+                    "Google signing keys unavailable ({})",
+                    status
+                )));
+            }
+            *keys = serde_json::from_slice(&body).map_err(|e| {
+                AppError::Upstream(format!("Unreadable Google signing keys: {}", e))
+            })?;
+        }
+        keys.find(kid)
+            .map(DecodingKey::from_jwk)
+            .transpose()
+            .map_err(|e| AppError::Upstream(format!("Unusable Google signing key: {}", e)))
+    }
+
+    /// The account in the ID token Google answered a code exchange with,
+    /// once its signature, issuer, audience and expiry check out.
+    fn read_id_token(&self, id_token: &str) -> Result<OAuthIdentity, AppError> {
+        let invalid = |why: &str| AppError::Upstream(format!("Invalid Google ID token: {}", why));
+        let header = decode_header(id_token).map_err(|e| invalid(&e.to_string()))?;
+        let kid = header.kid.ok_or_else(|| invalid("names no signing key"))?;
+        let key = self
+            .signing_key(&kid)?
+            .ok_or_else(|| invalid("signed with an unknown key"))?;
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.set_issuer(&GOOGLE_ISSUERS);
+        validation.set_audience(&[&self.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        let claims = decode::<GoogleClaims>(id_token, &key, &validation)
+            .map_err(|e| invalid(&e.to_string()))?
+            .claims;
+        Ok(OAuthIdentity {
+            subject: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified,
+        })
+    }
+}
+
+impl OAuthProvider for GoogleOAuth {
+    fn name(&self) -> &str {
+        "google"
+    }
+
+    fn authorize_url(&self, state: &str, redirect_uri: &str) -> String {
+        let query = form_encode(&[
+            ("client_id", &self.client_id),
+            ("redirect_uri", redirect_uri),
+            ("response_type", "code"),
+            ("scope", "openid email"),
+            ("state", state),
+            ("prompt", "select_account"),
+        ]);
+        format!("{}?{}", GOOGLE_AUTHORIZE_URL, query)
+    }
+
+    fn identify(&self, code: &str, redirect_uri: &str) -> Result<OAuthIdentity, AppError> {
+        debug!(
+            address = self.token_endpoint.address(),
+            "Exchanging Google sign-in code"
+        );
+        let body = form_encode(&[
+            ("code", code),
+            ("client_id", &self.client_id),
+            ("client_secret", &self.client_secret),
+            ("redirect_uri", redirect_uri),
+            ("grant_type", "authorization_code"),
+        ]);
+        let (status, body) = self
+            .token_endpoint
+            .post(
+                "",
+                "application/x-www-form-urlencoded",
+                body.as_bytes(),
+                OAUTH_TIMEOUT,
+            )
+            .map_err(|e| AppError::Upstream(format!("Google token endpoint unreachable: {}", e)))?;
+        trace!(status, bytes = body.len(), "Google token endpoint answered");
+        if !(200..300).contains(&status) {
+            return Err(AppError::Upstream(format!(
+                "Google refused the sign-in ({}): {}",
+                status,
+                String::from_utf8_lossy(&body)
+            )));
+        }
+
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            id_token: String,
+        }
+        let tokens: TokenResponse = serde_json::from_slice(&body)
+            .map_err(|e| AppError::Upstream(format!("Unreadable Google token response: {}", e)))?;
+        self.read_id_token(&tokens.id_token)
+    }
+}
+
+/// The worker who signed in as `identity` at `provider`.
+///
+/// An account signing in for the first time is linked to the worker whose
+/// contact is its email, compared without case, if the provider verified
+/// the email. Later sign-ins find the worker by the account alone.
+///
+/// # Errors
+/// - `AppError::Forbidden` if the account is not linked and its email is
+///   unverified or no worker's contact.
+pub fn link_identity(
+    conn: &Connection,
+    provider: &str,
+    identity: &OAuthIdentity,
+) -> Result<i64, AppError> {
+    let linked: Option<i64> = conn
+        .query_row(
+            "SELECT worker_id FROM oauth_identities WHERE provider = ?1 AND subject = ?2",
+            params![provider, identity.subject],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(worker_id) = linked {
+        debug!(worker_id, provider, "Found linked account");
+        return Ok(worker_id);
+    }
+
+    let verified = identity
+        .email
+        .as_deref()
+        .filter(|_| identity.email_verified);
+    let Some(email) = verified else {
+        warn!(provider, "Refused account without a verified email");
+        return Err(AppError::Forbidden(format!(
+            "Your {} account has no verified email",
+            provider
+        )));
+    };
+    let worker_id: i64 = conn
+        .query_row(
+            "SELECT id FROM workers WHERE lower(trim(contact)) = lower(?1) ORDER BY id LIMIT 1",
+            [email.trim()],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            warn!(provider, "No worker has the signed-in email");
+            AppError::Forbidden(format!(
+                "No worker has the email {}; ask the farm to add it to your worker profile",
+                email
+            ))
+        })?;
+    conn.execute(
+        "INSERT INTO oauth_identities (provider, subject, email, worker_id) VALUES (?1, ?2, ?3, ?4)",
+        params![provider, identity.subject, email, worker_id],
+    )?;
+    info!(worker_id, provider, "Linked account to worker");
+    Ok(worker_id)
+}
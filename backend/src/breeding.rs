@@ -0,0 +1,607 @@
+//! Breeding recommendation engine.
+//!
+//! Every eligible buck is paired with every eligible doe and the pairing is
+//! scored on breed match, health, age and past kidding outcomes. The expected
+//! inbreeding coefficient of the kids is computed from the pedigree with
+//! Wright's path method, penalising the score and flagging close-relative
+//! matings (first cousins or closer). The same pedigree, with each goat's
+//! genetic tags, backs the pedigree tree view. Service records are matched
+//! with the kidding records to tell which services took, and success rates
+//! compare natural service with AI.
+
+use crate::errors::{AppError, ParseEnumError};
+use crate::scheduler::DATE_FORMAT;
+use chrono::NaiveDate;
+use rusqlite::{Connection, OptionalExtension};
+use shared::breeding::{
+    BreedingRecommendation, BreedingService, MethodSuccess, OutsideSire, PedigreeNode,
+    ServiceMethod, ServiceOutcome, min_breeding_age_days, neutered_label,
+};
+use shared::{Breed, Gender};
+use std::collections::HashMap;
+use tracing::{debug, trace};
+
+/// How many generations of ancestors are searched for common ancestors.
+pub const PEDIGREE_DEPTH: u32 = 4;
+
+/// Inbreeding coefficient of a first-cousin mating; at or above this the
+/// pair is flagged as close relatives.
+pub const CLOSE_RELATIVE_THRESHOLD: f64 = 0.0625;
+
+pub use shared::breeding::{BUCK_MIN_AGE_DAYS, DOE_MIN_AGE_DAYS};
+
+/// Age, in days, beyond which a doe's pairings are penalised.
+pub const DOE_SENIOR_AGE_DAYS: i64 = 8 * 365;
+
+/// Does bred within this many days are assumed pregnant and skipped.
+pub const GESTATION_DAYS: i64 = 150;
+
+/// Days either side of `GESTATION_DAYS` after a service within which a
+/// kidding is credited to it.
+pub const KIDDING_WINDOW_DAYS: i64 = 10;
+
+/// Score every pairing starts from before adjustments.
+const BASE_SCORE: f64 = 50.0;
+
+/// A goat considered for breeding, with the fields the engine needs.
+#[derive(Debug, Clone)]
+pub struct BreedingCandidate {
+    pub id: i64,
+    pub name: String,
+    pub breed: Breed,
+    pub gender: Gender,
+    pub health_status: String,
+    pub offspring: i32,
+    pub date_of_birth: Option<NaiveDate>,
+    pub last_bred: Option<NaiveDate>,
+    /// Herd sire's ID, or the negated ID of an external sire (see
+    /// `ParentMap`).
+    pub sire_id: Option<i64>,
+    /// Herd dam's ID, or the negated ID of an external dam.
+    pub dam_id: Option<i64>,
+    /// Castrated or spayed; never paired.
+    pub neutered: bool,
+    /// In the trash; only walked as an ancestor, never paired.
+    pub deleted: bool,
+}
+
+/// Aggregated kidding outcomes for one parent.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct KiddingStats {
+    pub kiddings: u32,
+    pub kids_born: u32,
+    pub kids_alive: u32,
+}
+
+impl KiddingStats {
+    /// Fraction of kids born alive, if any kids were recorded.
+    pub fn survival_rate(&self) -> Option<f64> {
+        (self.kids_born > 0).then(|| self.kids_alive as f64 / self.kids_born as f64)
+    }
+}
+
+/// Kidding stats keyed by goat ID, split by the parent's role.
+#[derive(Debug, Clone, Default)]
+pub struct KiddingHistory {
+    pub as_doe: HashMap<i64, KiddingStats>,
+    pub as_buck: HashMap<i64, KiddingStats>,
+}
+
+/// Parses an optional date column, ignoring malformed values.
+fn parse_date(value: Option<String>) -> Option<NaiveDate> {
+    value.and_then(|v| NaiveDate::parse_from_str(&v, DATE_FORMAT).ok())
+}
+
+/// Loads every goat as a breeding candidate, including those in the trash
+/// so the ancestry of their kids stays known.
+pub fn load_candidates(conn: &Connection) -> Result<Vec<BreedingCandidate>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, breed, gender, COALESCE(health_status, ''), COALESCE(offspring, 0), \
+             date_of_birth, last_bred, COALESCE(sire_id, -sire_external_id), \
+             COALESCE(dam_id, -dam_external_id), neutered_on IS NOT NULL, \
+             deleted_at IS NOT NULL FROM goats",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, i32>(5)?,
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, Option<String>>(7)?,
+                row.get::<_, Option<i64>>(8)?,
+                row.get::<_, Option<i64>>(9)?,
+                row.get::<_, bool>(10)?,
+                row.get::<_, bool>(11)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    rows.into_iter()
+        .map(
+            |(
+                id,
+                name,
+                breed,
+                gender,
+                health_status,
+                offspring,
+                dob,
+                last_bred,
+                sire_id,
+                dam_id,
+                neutered,
+                deleted,
+            )| {
+                Ok(BreedingCandidate {
+                    id,
+                    name,
+                    breed: Breed::from_str(&breed),
+                    gender: Gender::from_str(&gender)
+                        .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "Gender")))?,
+                    health_status,
+                    offspring,
+                    date_of_birth: parse_date(dob),
+                    last_bred: parse_date(last_bred),
+                    sire_id,
+                    dam_id,
+                    neutered,
+                    deleted,
+                })
+            },
+        )
+        .collect()
+}
+
+/// Aggregates all kidding records per doe and per buck.
+pub fn load_kidding_history(conn: &Connection) -> Result<KiddingHistory, AppError> {
+    let mut stmt =
+        conn.prepare("SELECT doe_id, buck_id, kids_born, kids_alive FROM kidding_records")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, Option<i64>>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, u32>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut history = KiddingHistory::default();
+    for (doe_id, buck_id, born, alive) in rows {
+        let add = |stats: &mut KiddingStats| {
+            stats.kiddings += 1;
+            stats.kids_born += born;
+            stats.kids_alive += alive;
+        };
+        add(history.as_doe.entry(doe_id).or_default());
+        if let Some(buck_id) = buck_id {
+            add(history.as_buck.entry(buck_id).or_default());
+        }
+    }
+    Ok(history)
+}
+
+/// Parent links keyed by goat ID: `(sire_id, dam_id)`. External animals
+/// (see `shared::breeding::ExternalAnimal`) are keyed by their negated ID,
+/// so kids of the same outside buck are still seen as related.
+pub type ParentMap = HashMap<i64, (Option<i64>, Option<i64>)>;
+
+/// Builds the parent map from a set of candidates.
+pub fn parent_map(candidates: &[BreedingCandidate]) -> ParentMap {
+    candidates
+        .iter()
+        .map(|c| (c.id, (c.sire_id, c.dam_id)))
+        .collect()
+}
+
+/// Collects every ancestor of `id` up to `PEDIGREE_DEPTH` generations,
+/// with the length of each path leading to it. The goat itself is included
+/// at depth 0 so parent–offspring matings are detected.
+pub fn ancestor_paths(id: i64, parents: &ParentMap) -> HashMap<i64, Vec<u32>> {
+    fn walk(id: i64, depth: u32, parents: &ParentMap, out: &mut HashMap<i64, Vec<u32>>) {
+        out.entry(id).or_default().push(depth);
+        if depth == PEDIGREE_DEPTH {
+            return;
+        }
+        if let Some((sire, dam)) = parents.get(&id) {
+            for parent in [sire, dam].into_iter().flatten() {
+                walk(*parent, depth + 1, parents, out);
+            }
+        }
+    }
+    let mut out = HashMap::new();
+    walk(id, 0, parents, &mut out);
+    out
+}
+
+/// Expected inbreeding coefficient of kids from `buck` × `doe`.
+///
+/// Sums `(1/2)^(n1 + n2 + 1)` over every pair of paths to each common
+/// ancestor, where `n1` and `n2` are the generations from each parent.
+/// Ancestors' own inbreeding is ignored.
+pub fn inbreeding_coefficient(buck: i64, doe: i64, parents: &ParentMap) -> f64 {
+    let buck_paths = ancestor_paths(buck, parents);
+    let doe_paths = ancestor_paths(doe, parents);
+    let mut coefficient = 0.0;
+    for (ancestor, buck_depths) in &buck_paths {
+        let Some(doe_depths) = doe_paths.get(ancestor) else {
+            continue;
+        };
+        for n1 in buck_depths {
+            for n2 in doe_depths {
+                coefficient += 0.5_f64.powi((n1 + n2 + 1) as i32);
+            }
+        }
+    }
+    coefficient
+}
+
+/// Loads every goat's genetic tags keyed by goat ID, each list sorted by
+/// tag.
+pub fn load_genetic_tags(conn: &Connection) -> Result<HashMap<i64, Vec<String>>, AppError> {
+    let mut stmt = conn.prepare("SELECT goat_id, tag FROM genetic_tags ORDER BY tag")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+    for (goat_id, tag) in rows {
+        tags.entry(goat_id).or_default().push(tag);
+    }
+    Ok(tags)
+}
+
+/// Builds the pedigree tree of the goat named `name`, with
+/// `PEDIGREE_DEPTH` generations of ancestors, including those in the
+/// trash, and each goat's tags. Returns `None` for an unknown goat.
+pub fn load_pedigree_tree(conn: &Connection, name: &str) -> Result<Option<PedigreeNode>, AppError> {
+    // External parents are keyed by their negated ID, as in `ParentMap`
+    let mut stmt = conn.prepare(
+        "SELECT id, name, COALESCE(sire_id, -sire_external_id), \
+             COALESCE(dam_id, -dam_external_id) FROM goats \
+         UNION ALL SELECT -id, name, NULL, NULL FROM external_animals",
+    )?;
+    let goats: HashMap<i64, (String, Option<i64>, Option<i64>)> = stmt
+        .query_map([], |row| {
+            Ok((row.get(0)?, (row.get(1)?, row.get(2)?, row.get(3)?)))
+        })?
+        .collect::<Result<_, _>>()?;
+    let live: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let tags = load_genetic_tags(conn)?;
+
+    fn node(
+        id: i64,
+        depth: u32,
+        goats: &HashMap<i64, (String, Option<i64>, Option<i64>)>,
+        tags: &HashMap<i64, Vec<String>>,
+    ) -> Option<Box<PedigreeNode>> {
+        let (name, sire, dam) = goats.get(&id)?;
+        let parent = |parent: &Option<i64>| {
+            parent
+                .filter(|_| depth < PEDIGREE_DEPTH)
+                .and_then(|p| node(p, depth + 1, goats, tags))
+        };
+        Some(Box::new(PedigreeNode {
+            name: name.clone(),
+            external: id < 0,
+            tags: tags.get(&id).cloned().unwrap_or_default(),
+            sire: parent(sire),
+            dam: parent(dam),
+        }))
+    }
+
+    trace!(name, id = ?live, "Building pedigree tree");
+    Ok(live.and_then(|id| node(id, 0, &goats, &tags)).map(|n| *n))
+}
+
+/// Returns true if the health status describes a goat fit for breeding.
+fn is_healthy(status: &str) -> bool {
+    matches!(
+        status.trim().to_ascii_lowercase().as_str(),
+        "healthy" | "good" | "excellent"
+    )
+}
+
+/// Age in days on `today`, if the date of birth is known.
+fn age_days(goat: &BreedingCandidate, today: NaiveDate) -> Option<i64> {
+    goat.date_of_birth.map(|dob| (today - dob).num_days())
+}
+
+/// Returns why a goat cannot be bred right now, if anything.
+fn ineligibility(goat: &BreedingCandidate, today: NaiveDate) -> Option<String> {
+    if goat.deleted {
+        return Some(format!("{} is in the trash", goat.name));
+    }
+    if goat.neutered {
+        return Some(format!(
+            "{} is a {}",
+            goat.name,
+            neutered_label(&goat.gender).to_lowercase()
+        ));
+    }
+    if let Some(age) = age_days(goat, today)
+        && age < min_breeding_age_days(&goat.gender)
+    {
+        return Some(format!("{} is too young ({} days)", goat.name, age));
+    }
+    if goat.gender == Gender::Female
+        && let Some(bred) = goat.last_bred
+    {
+        let since = (today - bred).num_days();
+        if (0..GESTATION_DAYS).contains(&since) {
+            return Some(format!("{} was bred {} days ago", goat.name, since));
+        }
+    }
+    None
+}
+
+/// Scores a single pairing.
+pub fn score_pair(
+    buck: &BreedingCandidate,
+    doe: &BreedingCandidate,
+    history: &KiddingHistory,
+    parents: &ParentMap,
+    today: NaiveDate,
+) -> BreedingRecommendation {
+    let mut score = BASE_SCORE;
+    let mut reasons = Vec::new();
+    let mut warnings = Vec::new();
+
+    if buck.breed == doe.breed {
+        score += 15.0;
+        reasons.push(format!("Same breed ({})", Breed::to_str(&buck.breed)));
+    } else {
+        score += 5.0;
+        reasons.push(format!(
+            "Crossbreed {} × {}",
+            Breed::to_str(&buck.breed),
+            Breed::to_str(&doe.breed)
+        ));
+    }
+
+    for goat in [buck, doe] {
+        if !is_healthy(&goat.health_status) {
+            score -= 25.0;
+            warnings.push(format!(
+                "{} health status is '{}'",
+                goat.name, goat.health_status
+            ));
+        }
+        if goat.date_of_birth.is_none() {
+            warnings.push(format!("Age of {} is unknown", goat.name));
+        }
+    }
+    if age_days(doe, today).is_some_and(|age| age > DOE_SENIOR_AGE_DAYS) {
+        score -= 10.0;
+        warnings.push(format!("{} is past prime breeding age", doe.name));
+    }
+
+    match history
+        .as_doe
+        .get(&doe.id)
+        .and_then(|s| s.survival_rate().map(|r| (s, r)))
+    {
+        Some((stats, rate)) => {
+            score += 20.0 * rate;
+            reasons.push(format!(
+                "{} kid survival {:.0}% over {} kiddings",
+                doe.name,
+                rate * 100.0,
+                stats.kiddings
+            ));
+        }
+        None if doe.offspring > 0 => {
+            score += 5.0;
+            reasons.push(format!(
+                "{} has {} recorded offspring",
+                doe.name, doe.offspring
+            ));
+        }
+        None => {}
+    }
+    if let Some((stats, rate)) = history
+        .as_buck
+        .get(&buck.id)
+        .and_then(|s| s.survival_rate().map(|r| (s, r)))
+    {
+        score += 10.0 * rate;
+        reasons.push(format!(
+            "{} sired kids with {:.0}% survival over {} kiddings",
+            buck.name,
+            rate * 100.0,
+            stats.kiddings
+        ));
+    }
+
+    let inbreeding = inbreeding_coefficient(buck.id, doe.id, parents);
+    let close_relatives = inbreeding >= CLOSE_RELATIVE_THRESHOLD;
+    if inbreeding > 0.0 {
+        score -= inbreeding * 200.0;
+        let message = format!("Inbreeding coefficient {:.1}%", inbreeding * 100.0);
+        if close_relatives {
+            warnings.push(format!("Close relatives: {}", message));
+        } else {
+            warnings.push(message);
+        }
+    }
+
+    trace!(buck = %buck.name, doe = %doe.name, score, inbreeding, "Scored pairing");
+    BreedingRecommendation {
+        buck_name: buck.name.clone(),
+        doe_name: doe.name.clone(),
+        score: score.clamp(0.0, 100.0),
+        inbreeding_coefficient: inbreeding,
+        close_relatives,
+        reasons,
+        warnings,
+    }
+}
+
+/// Scores every eligible buck–doe pairing, best first.
+///
+/// Goats that are too young, and does still within gestation of their last
+/// breeding, are skipped. If `doe_name` is given only that doe is paired.
+pub fn recommend(
+    candidates: &[BreedingCandidate],
+    history: &KiddingHistory,
+    today: NaiveDate,
+    doe_name: Option<&str>,
+) -> Vec<BreedingRecommendation> {
+    let parents = parent_map(candidates);
+    let eligible = |goat: &&BreedingCandidate| match ineligibility(goat, today) {
+        Some(reason) => {
+            debug!("Skipping for breeding: {}", reason);
+            false
+        }
+        None => true,
+    };
+    let bucks: Vec<&BreedingCandidate> = candidates
+        .iter()
+        .filter(|c| c.gender == Gender::Male)
+        .filter(eligible)
+        .collect();
+    let does: Vec<&BreedingCandidate> = candidates
+        .iter()
+        .filter(|c| c.gender == Gender::Female)
+        .filter(|c| doe_name.is_none_or(|name| c.name == name))
+        .filter(eligible)
+        .collect();
+
+    let mut recommendations: Vec<BreedingRecommendation> = does
+        .iter()
+        .flat_map(|doe| {
+            bucks
+                .iter()
+                .map(|buck| score_pair(buck, doe, history, &parents, today))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    recommendations.sort_by(|a, b| b.score.total_cmp(&a.score));
+    recommendations
+}
+
+/// Outcome of a service on `served_on`, given the dates the doe kidded.
+/// A kidding `GESTATION_DAYS` later, give or take `KIDDING_WINDOW_DAYS`,
+/// means it took; once the window has passed without one the doe is open.
+pub fn service_outcome(
+    served_on: NaiveDate,
+    kiddings: &[NaiveDate],
+    today: NaiveDate,
+) -> ServiceOutcome {
+    let window = (GESTATION_DAYS - KIDDING_WINDOW_DAYS)..=(GESTATION_DAYS + KIDDING_WINDOW_DAYS);
+    if kiddings
+        .iter()
+        .any(|k| window.contains(&(*k - served_on).num_days()))
+    {
+        ServiceOutcome::Kidded
+    } else if (today - served_on).num_days() > *window.end() {
+        ServiceOutcome::Open
+    } else {
+        ServiceOutcome::Pending
+    }
+}
+
+/// Loads the services of every doe, or only of `doe_name`, newest first,
+/// with their outcomes as of `today`.
+pub fn load_services(
+    conn: &Connection,
+    doe_name: Option<&str>,
+    today: NaiveDate,
+) -> Result<Vec<BreedingService>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT s.id, d.name, s.served_on, s.method, b.name, s.outside_sire_name, \
+             s.outside_sire_breed, s.outside_sire_registration, s.outside_sire_source, \
+             s.straw_id, s.technician, s.notes, \
+             (SELECT GROUP_CONCAT(k.kidded_on) FROM kidding_records k WHERE k.doe_id = s.doe_id) \
+         FROM breeding_services s \
+         JOIN goats d ON d.id = s.doe_id AND d.deleted_at IS NULL \
+         LEFT JOIN goats b ON b.id = s.buck_id \
+         WHERE (?1 IS NULL OR d.name = ?1) \
+         ORDER BY s.served_on DESC, s.id DESC",
+    )?;
+    let rows = stmt
+        .query_map([doe_name], |row| {
+            let outside_name: Option<String> = row.get(5)?;
+            let outside_sire = match outside_name {
+                Some(name) => Some(OutsideSire {
+                    name,
+                    breed: row.get(6)?,
+                    registration: row.get(7)?,
+                    source: row.get(8)?,
+                }),
+                None => None,
+            };
+            let service = BreedingService {
+                id: row.get(0)?,
+                doe_name: row.get(1)?,
+                served_on: row.get(2)?,
+                method: ServiceMethod::Natural,
+                buck_name: row.get(4)?,
+                outside_sire,
+                straw_id: row.get(9)?,
+                technician: row.get(10)?,
+                notes: row.get(11)?,
+                outcome: None,
+            };
+            Ok((
+                service,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(12)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    rows.into_iter()
+        .map(|(mut service, method, kiddings)| {
+            service.method = ServiceMethod::from_str(&method)
+                .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "ServiceMethod")))?;
+            let kiddings: Vec<NaiveDate> = kiddings
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|d| NaiveDate::parse_from_str(d, DATE_FORMAT).ok())
+                .collect();
+            service.outcome = parse_date(Some(service.served_on.clone()))
+                .map(|served_on| service_outcome(served_on, &kiddings, today));
+            Ok(service)
+        })
+        .collect()
+}
+
+/// Tallies the outcomes of `services` for each method, natural first.
+pub fn success_rates(services: &[BreedingService]) -> Vec<MethodSuccess> {
+    ServiceMethod::ALL
+        .into_iter()
+        .map(|method| {
+            let mut success = MethodSuccess {
+                method,
+                services: 0,
+                kidded: 0,
+                open: 0,
+                pending: 0,
+            };
+            for service in services.iter().filter(|s| s.method == method) {
+                success.services += 1;
+                match service.outcome {
+                    Some(ServiceOutcome::Kidded) => success.kidded += 1,
+                    Some(ServiceOutcome::Open) => success.open += 1,
+                    Some(ServiceOutcome::Pending) | None => success.pending += 1,
+                }
+            }
+            debug!(method = ?method, services = success.services, "Tallied service outcomes");
+            success
+        })
+        .collect()
+}
@@ -0,0 +1,200 @@
+//! iCalendar (RFC 5545) feed of the farm's pending tasks, reminders and
+//! vet visits.
+//!
+//! Each task, each undismissed reminder and each scheduled vet visit becomes
+//! an all-day event, so a farmer can subscribe to the feed from Google
+//! Calendar or a phone's calendar app. UIDs are stable (`task-{id}@yagi`,
+//! `reminder-{id}@yagi`, `vet-visit-{id}@yagi`) so subscribed calendars
+//! update events in place rather than duplicating them on every refresh.
+
+use crate::errors::AppError;
+use crate::scheduler::DATE_FORMAT;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use rusqlite::Connection;
+use tracing::{debug, warn};
+
+/// Name shown for the subscribed calendar.
+pub const CALENDAR_NAME: &str = "Yagi farm reminders";
+
+/// Longest content line allowed before folding, in octets.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// One all-day event in the feed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub date: NaiveDate,
+    pub summary: String,
+    pub description: Option<String>,
+}
+
+/// Loads every pending task, undismissed reminder and scheduled vet visit,
+/// in date order.
+/// Rows with malformed dates are skipped.
+pub fn load_events(conn: &Connection) -> Result<Vec<CalendarEvent>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT t.id, t.title, t.notes, g.name, s.name, t.due_date
+         FROM tasks t
+         LEFT JOIN goats g ON g.id = t.goat_id
+         LEFT JOIN spaces s ON s.id = t.space_id
+         WHERE t.status = 'Pending'",
+    )?;
+    let tasks = stmt
+        .query_map([], |row| {
+            let goat: Option<String> = row.get(3)?;
+            let pen: Option<String> = row.get(4)?;
+            let notes: Option<String> = row.get(2)?;
+            let details: Vec<String> = [
+                goat.map(|g| format!("Goat: {}", g)),
+                pen.map(|p| format!("Pen: {}", p)),
+                notes.filter(|n| !n.trim().is_empty()),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            Ok((
+                format!("task-{}@yagi", row.get::<_, i64>(0)?),
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(1)?,
+                (!details.is_empty()).then(|| details.join("\n")),
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.remind_on, r.message, t.title
+         FROM reminders r
+         JOIN tasks t ON t.id = r.task_id
+         WHERE r.dismissed = 0",
+    )?;
+    let reminders = stmt
+        .query_map([], |row| {
+            Ok((
+                format!("reminder-{}@yagi", row.get::<_, i64>(0)?),
+                row.get::<_, String>(1)?,
+                format!("Reminder: {}", row.get::<_, String>(2)?),
+                Some(format!("Task: {}", row.get::<_, String>(3)?)),
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT v.id, v.scheduled_for, v.reason, w.name,
+                (SELECT GROUP_CONCAT(name, ', ') FROM
+                    (SELECT g.name FROM vet_visit_goats vg
+                     JOIN goats g ON g.id = vg.goat_id AND g.deleted_at IS NULL
+                     WHERE vg.visit_id = v.id ORDER BY g.name))
+         FROM vet_visits v
+         LEFT JOIN workers w ON w.id = v.vet_id
+         WHERE v.status = 'Scheduled'",
+    )?;
+    let visits = stmt
+        .query_map([], |row| {
+            let vet: Option<String> = row.get(3)?;
+            let goats: Option<String> = row.get(4)?;
+            let details: Vec<String> = [
+                vet.map(|v| format!("Vet: {}", v)),
+                goats.map(|g| format!("Goats: {}", g)),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            Ok((
+                format!("vet-visit-{}@yagi", row.get::<_, i64>(0)?),
+                row.get::<_, String>(1)?,
+                format!("Vet visit: {}", row.get::<_, String>(2)?),
+                (!details.is_empty()).then(|| details.join("\n")),
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut events: Vec<CalendarEvent> = tasks
+        .into_iter()
+        .chain(reminders)
+        .chain(visits)
+        .filter_map(|(uid, date, summary, description)| {
+            match NaiveDate::parse_from_str(&date, DATE_FORMAT) {
+                Ok(date) => Some(CalendarEvent {
+                    uid,
+                    date,
+                    summary,
+                    description,
+                }),
+                Err(_) => {
+                    warn!(uid, date, "Skipping calendar event with malformed date");
+                    None
+                }
+            }
+        })
+        .collect();
+    events.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.uid.cmp(&b.uid)));
+    debug!(events = events.len(), "Calendar events loaded");
+    Ok(events)
+}
+
+/// Escapes a TEXT value: backslashes, commas, semicolons and newlines.
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ',' => escaped.push_str("\\,"),
+            ';' => escaped.push_str("\\;"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Folds a content line longer than 75 octets onto continuation lines
+/// starting with a space, never splitting a UTF-8 character, and ends every
+/// line with CRLF.
+pub fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/// Renders `events` as an iCalendar document stamped with `now`.
+pub fn to_ics(events: &[CalendarEvent], now: DateTime<Utc>) -> String {
+    let stamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let day = |date: NaiveDate| date.format("%Y%m%d").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Yagi//Farm Reminders//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(CALENDAR_NAME)),
+    ];
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", event.uid));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", day(event.date)));
+        // DTEND is exclusive, so an all-day event ends the next day
+        lines.push(format!(
+            "DTEND;VALUE=DATE:{}",
+            day(event.date + Days::new(1))
+        ));
+        lines.push(format!("SUMMARY:{}", escape_text(&event.summary)));
+        if let Some(description) = &event.description {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        lines.push("TRANSP:TRANSPARENT".to_string());
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold_line(line)).collect()
+}
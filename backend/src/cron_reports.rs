@@ -0,0 +1,150 @@
+//! Writes the census reports and sends notification digests, for running
+//! weekly from cron on the server, e.g.:
+//!
+//! ```text
+//! 0 6 * * 1  cd /srv/yagi && cron_reports --out reports/weekly
+//! ```
+//!
+//! Options:
+//! - `--out DIR`: where to write the reports (default `reports`).
+//! - `--template KEY`: a report template to write, repeatable; every
+//!   template by default.
+//! - `--as-of YYYY-MM-DD`: census date; defaults to today.
+//! - `--no-digest`: only write the reports.
+//!
+//! Each template is written as CSV and PDF (see `backend::handlers::reports`).
+//! Like the server, it reads the database named by `YAGI_DATABASE_URL`
+//! (default `livestock.db`; for a PostgreSQL URL, the SQLite database at
+//! `YAGI_SQLITE_PATH`), and sends digests through the relay at
+//! `YAGI_MESSAGE_GATEWAY_URL` (see `backend::digests`); without one, no
+//! digests are sent.
+//!
+//! When `YAGI_TENANTS_DIR` is set, as for a multi-tenant server (see
+//! `backend::tenants`), it does this for every tenant instead, writing each
+//! farm's reports to a directory named after its slug under `--out`. A farm
+//! that fails does not stop the others.
+
+use backend::db::DbPool;
+use backend::digests::send_digests;
+use backend::handlers::reports::census_files;
+use backend::messaging::HttpMessageGateway;
+use backend::repository::StorageBackend;
+use backend::tenants::TenantRegistry;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use tracing::{error, info};
+
+/// What to do, from the command line.
+struct Options {
+    out: PathBuf,
+    templates: Vec<String>,
+    as_of: Option<String>,
+    digest: bool,
+}
+
+/// Parses the command-line arguments, excluding the program name.
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        out: PathBuf::from("reports"),
+        templates: Vec::new(),
+        as_of: None,
+        digest: true,
+    };
+    let mut args = args;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--out" => options.out = PathBuf::from(value()?),
+            "--template" => options.templates.push(value()?),
+            "--as-of" => options.as_of = Some(value()?),
+            "--no-digest" => options.digest = false,
+            other => return Err(format!("Unknown argument {}", other)),
+        }
+    }
+    Ok(options)
+}
+
+/// Writes the reports of the farm in `db_pool` to `out`, then sends its
+/// digests through `gateway`, if any.
+fn run_farm(
+    db_pool: &DbPool,
+    options: &Options,
+    out: &Path,
+    gateway: Option<&HttpMessageGateway>,
+) -> Result<(), String> {
+    let conn = db_pool.get_conn().map_err(|e| e.to_string())?;
+
+    let files = census_files(&conn, &options.templates, options.as_of.as_deref())
+        .map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(out).map_err(|e| e.to_string())?;
+    for (filename, body) in &files {
+        let path = out.join(filename);
+        std::fs::write(&path, body).map_err(|e| format!("{}: {}", path.display(), e))?;
+        info!(path = %path.display(), bytes = body.len(), "Report written");
+    }
+
+    if let Some(gateway) = gateway {
+        send_digests(&conn, gateway).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let gateway = match std::env::var("YAGI_MESSAGE_GATEWAY_URL") {
+        _ if !options.digest => None,
+        Ok(url) => Some(HttpMessageGateway::new(&url)?),
+        Err(_) => {
+            info!("YAGI_MESSAGE_GATEWAY_URL is not set; no digests sent");
+            None
+        }
+    };
+
+    let Ok(dir) = std::env::var("YAGI_TENANTS_DIR") else {
+        let url = std::env::var("YAGI_DATABASE_URL").unwrap_or_else(|_| "livestock.db".to_string());
+        let mut backend = StorageBackend::from_url(&url);
+        if let Ok(path) = std::env::var("YAGI_SQLITE_PATH") {
+            backend = backend.with_sqlite_path(&path);
+        }
+        // Reports read the SQLite database, which also holds the goats
+        // served from PostgreSQL
+        let db_pool = match backend {
+            StorageBackend::Sqlite(path) | StorageBackend::Postgres { sqlite: path, .. } => {
+                DbPool::new(&path).map_err(|e| e.to_string())?
+            }
+            _ => return Err(format!("{} is not a SQLite database", url)),
+        };
+        return run_farm(&db_pool, &options, &options.out, gateway.as_ref());
+    };
+    let admin_key = std::env::var("YAGI_ADMIN_KEY")
+        .map_err(|_| "YAGI_ADMIN_KEY must be set in multi-tenant mode".to_string())?;
+    let registry = TenantRegistry::open(dir, &admin_key).map_err(|e| e.to_string())?;
+    let mut failed = 0;
+    for tenant in registry.list().map_err(|e| e.to_string())? {
+        let out = options.out.join(&tenant.slug);
+        let result = registry
+            .resolve_slug(&tenant.slug)
+            .map_err(|e| e.to_string())
+            .and_then(|db_pool| run_farm(&db_pool, &options, &out, gateway.as_ref()));
+        if let Err(e) = result {
+            error!(tenant = %tenant.slug, "Reports failed: {}", e);
+            failed += 1;
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        _ => Err(format!("Reports failed for {} farms", failed)),
+    }
+}
+
+fn main() -> ExitCode {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .init();
+    match parse_args(std::env::args().skip(1)).and_then(run) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
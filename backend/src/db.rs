@@ -14,15 +14,13 @@ use crate::db_helpers::{str_to_breed, str_to_gender};
 use crate::errors::{AppError, ParseEnumError};
 use r2d2::{Pool, PooledConnection};
 use r2d2_sqlite::SqliteConnectionManager;
-use shared::{Breed, DiseaseRef, Gender, GoatParams, VaccineRef};
-//use refinery::embed_migrations;
-use rusqlite::{Connection, OpenFlags, OptionalExtension, Row, Transaction};
+use shared::{Breed, DiseaseRef, Gender, Goat, GoatParams, VaccineRef};
+use shared::physical::{CoatColor, HornStatus};
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Row};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tracing::{error, info, trace};
 
-// Embed refinery migrations located inside the `migrations` directory under `src`.
-//embed_migrations!("migrations");
-
 /// Thread-safe database pool using r2d2 and rusqlite with connection multiplexing.
 #[derive(Clone)]
 pub struct DbPool {
@@ -58,11 +56,11 @@ impl DbPool {
                 .map_err(AppError::DbError)?;
         }
 
-        // Run migrations here if desired
-        //{
-        //    let conn = pool.get().map_err(AppError::DbError)?;
-        //    // run_migrations(&mut conn).map_err(AppError::DbError)?;
-        //}
+        // Bring the schema up to date before handing out connections
+        {
+            let mut conn = pool.get().map_err(AppError::PoolError)?;
+            run_migrations(&mut conn)?;
+        }
 
         info!("Database WAL enabled and ready for use with connection pool");
 
@@ -71,6 +69,43 @@ impl DbPool {
         })
     }
 
+    /// Creates a fresh, fully migrated database that lives only in memory,
+    /// for demos and tests. Every pooled connection shares the same database,
+    /// which is discarded when the pool is dropped.
+    ///
+    /// # Errors
+    /// Fails if the pool cannot be created or migrations fail.
+    pub fn in_memory() -> Result<Self, AppError> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let uri = format!(
+            "file:yagi-memory-{}-{}?mode=memory&cache=shared",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        );
+        info!(uri, "Creating in-memory database");
+
+        let manager = SqliteConnectionManager::file(uri).with_flags(
+            OpenFlags::SQLITE_OPEN_READ_WRITE
+                | OpenFlags::SQLITE_OPEN_CREATE
+                | OpenFlags::SQLITE_OPEN_URI,
+        );
+        // The database disappears with its last connection, so pooled
+        // connections must never all be closed at once.
+        let pool = Pool::builder()
+            .max_lifetime(None)
+            .idle_timeout(None)
+            .build(manager)
+            .map_err(AppError::PoolError)?;
+        {
+            let mut conn = pool.get().map_err(AppError::PoolError)?;
+            run_migrations(&mut conn)?;
+        }
+
+        Ok(Self {
+            pool: Arc::new(pool),
+        })
+    }
+
     /// Acquires a pooled SQLite connection for use in queries.
     pub fn get_conn(&self) -> Result<PooledConnection<SqliteConnectionManager>, AppError> {
         self.pool.get().map_err(AppError::PoolError)
@@ -96,6 +131,22 @@ pub fn row_to_goat(row: &Row) -> Result<GoatParams, AppError> {
         error!(e);
         AppError::ParseError(ParseEnumError::new(&e, "Gender"))
     })?;
+    // Physical traits are read by name; queries selecting fewer columns
+    // leave them unset
+    let horns = match row.get::<_, Option<String>>("horns").ok().flatten() {
+        Some(s) => Some(
+            HornStatus::from_str(&s)
+                .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "HornStatus")))?,
+        ),
+        None => None,
+    };
+    let coat_color = match row.get::<_, Option<String>>("coat_color").ok().flatten() {
+        Some(s) => Some(
+            CoatColor::from_str(&s)
+                .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "CoatColor")))?,
+        ),
+        None => None,
+    };
 
     Ok(GoatParams {
         breed,
@@ -110,6 +161,23 @@ pub fn row_to_goat(row: &Row) -> Result<GoatParams, AppError> {
         health_status: row.get(10)?,
         vaccinations: Vec::new(),
         diseases: Vec::new(),
+        horns,
+        coat_color,
+        marks: row.get("marks").ok().flatten(),
+        quick_note: row.get("quick_note").ok().flatten(),
+    })
+}
+
+/// Maps a row of `SELECT * FROM goats` to a `Goat` with its ID, tag, birth
+/// date and timestamps, via `row_to_goat`.
+pub fn row_to_goat_record(row: &Row) -> Result<Goat, AppError> {
+    Ok(Goat {
+        id: row.get("id")?,
+        params: row_to_goat(row)?,
+        tag_id: row.get("tag_id")?,
+        date_of_birth: row.get("date_of_birth")?,
+        created_at: row.get("created_at")?,
+        updated_at: row.get("updated_at")?,
     })
 }
 
@@ -124,7 +192,7 @@ pub fn fetch_vaccines(conn: &Connection, goat_id: i64) -> Result<Vec<VaccineRef>
     trace!(goat_id, "Fetching vaccine list");
 
     let mut stmt = conn.prepare(
-        "SELECT v.id, v.name FROM vaccines v INNER JOIN goat_vaccines gv ON v.id = gv.vaccine_id WHERE gv.goat_id = ?1"
+        "SELECT v.id, v.name, gv.given_on FROM vaccines v INNER JOIN goat_vaccines gv ON v.id = gv.vaccine_id WHERE gv.goat_id = ?1"
     ).map_err(AppError::DbError)?;
 
     let vaccines: Vec<VaccineRef> = stmt
@@ -133,6 +201,7 @@ pub fn fetch_vaccines(conn: &Connection, goat_id: i64) -> Result<Vec<VaccineRef>
                 Ok(VaccineRef {
                     id: row.get(0)?,
                     name: row.get(1)?,
+                    given_on: row.get(2)?,
                 })
             }
         })?
@@ -173,30 +242,345 @@ pub fn fetch_diseases(conn: &Connection, goat_id: i64) -> Result<Vec<DiseaseRef>
     Ok(diseases)
 }
 
-/// Runs all embedded refinery migrations on the provided connection,
+/// Embedded schema migrations, applied in order by `run_migrations`.
+///
+/// Each entry is `(version, name, sql)`; versions must be strictly increasing.
+/// The SQL files live in the crate's `migrations` directory.
+const MIGRATIONS: &[(i64, &str, &str)] = &[
+    (1, "create_goats", include_str!("../migrations/V1__create_goats.sql")),
+    (
+        2,
+        "create_vaccinations_disesases",
+        include_str!("../migrations/V2__create_vaccinations_disesases.sql"),
+    ),
+    (
+        3,
+        "create_workers_equipment_sensors_spaces",
+        include_str!("../migrations/V3__create_workers_equipment_sensors_spaces.sql"),
+    ),
+    (
+        4,
+        "create_tasks_reminder_rules",
+        include_str!("../migrations/V4__create_tasks_reminder_rules.sql"),
+    ),
+    (
+        5,
+        "create_inventory",
+        include_str!("../migrations/V5__create_inventory.sql"),
+    ),
+    (
+        6,
+        "create_transactions",
+        include_str!("../migrations/V6__create_transactions.sql"),
+    ),
+    (
+        7,
+        "create_client_errors",
+        include_str!("../migrations/V7__create_client_errors.sql"),
+    ),
+    (
+        8,
+        "create_breeding",
+        include_str!("../migrations/V8__create_breeding.sql"),
+    ),
+    (
+        9,
+        "create_weight_records",
+        include_str!("../migrations/V9__create_weight_records.sql"),
+    ),
+    (
+        10,
+        "create_milk_records",
+        include_str!("../migrations/V10__create_milk_records.sql"),
+    ),
+    (
+        11,
+        "add_goat_space",
+        include_str!("../migrations/V11__add_goat_space.sql"),
+    ),
+    (
+        12,
+        "create_health_incidents",
+        include_str!("../migrations/V12__create_health_incidents.sql"),
+    ),
+    (
+        13,
+        "create_scale_ingestion",
+        include_str!("../migrations/V13__create_scale_ingestion.sql"),
+    ),
+    (
+        14,
+        "create_sensor_readings",
+        include_str!("../migrations/V14__create_sensor_readings.sql"),
+    ),
+    (
+        15,
+        "add_sensor_goat",
+        include_str!("../migrations/V15__add_sensor_goat.sql"),
+    ),
+    (
+        16,
+        "create_gps_positions",
+        include_str!("../migrations/V16__create_gps_positions.sql"),
+    ),
+    (
+        17,
+        "create_report_templates",
+        include_str!("../migrations/V17__create_report_templates.sql"),
+    ),
+    (
+        18,
+        "create_insurance",
+        include_str!("../migrations/V18__create_insurance.sql"),
+    ),
+    (
+        19,
+        "create_sales_tax",
+        include_str!("../migrations/V19__create_sales_tax.sql"),
+    ),
+    (
+        20,
+        "add_transaction_currency",
+        include_str!("../migrations/V20__add_transaction_currency.sql"),
+    ),
+    (
+        21,
+        "create_budgets",
+        include_str!("../migrations/V21__create_budgets.sql"),
+    ),
+    (
+        22,
+        "add_goat_updated_at",
+        include_str!("../migrations/V22__add_goat_updated_at.sql"),
+    ),
+    (
+        23,
+        "add_vaccination_date",
+        include_str!("../migrations/V23__add_vaccination_date.sql"),
+    ),
+    (
+        24,
+        "create_breed_catalog",
+        include_str!("../migrations/V24__create_breed_catalog.sql"),
+    ),
+    (
+        25,
+        "add_goat_neutering",
+        include_str!("../migrations/V25__add_goat_neutering.sql"),
+    ),
+    (
+        26,
+        "add_goat_physical_traits",
+        include_str!("../migrations/V26__add_goat_physical_traits.sql"),
+    ),
+    (
+        27,
+        "create_genetic_tags",
+        include_str!("../migrations/V27__create_genetic_tags.sql"),
+    ),
+    (
+        28,
+        "add_weight_estimated",
+        include_str!("../migrations/V28__add_weight_estimated.sql"),
+    ),
+    (
+        29,
+        "add_space_area",
+        include_str!("../migrations/V29__add_space_area.sql"),
+    ),
+    (
+        30,
+        "create_paddocks",
+        include_str!("../migrations/V30__create_paddocks.sql"),
+    ),
+    (
+        31,
+        "create_goat_notes",
+        include_str!("../migrations/V31__create_goat_notes.sql"),
+    ),
+    (
+        32,
+        "create_notifications",
+        include_str!("../migrations/V32__create_notifications.sql"),
+    ),
+    (
+        33,
+        "create_attachments",
+        include_str!("../migrations/V33__create_attachments.sql"),
+    ),
+    (
+        34,
+        "create_event_log",
+        include_str!("../migrations/V34__create_event_log.sql"),
+    ),
+    (
+        35,
+        "create_jobs",
+        include_str!("../migrations/V35__create_jobs.sql"),
+    ),
+    (
+        36,
+        "create_goat_trash",
+        include_str!("../migrations/V36__create_goat_trash.sql"),
+    ),
+    (
+        37,
+        "create_sessions",
+        include_str!("../migrations/V37__create_sessions.sql"),
+    ),
+    (
+        38,
+        "create_oauth_identities",
+        include_str!("../migrations/V38__create_oauth_identities.sql"),
+    ),
+    (
+        39,
+        "create_role_permissions",
+        include_str!("../migrations/V39__create_role_permissions.sql"),
+    ),
+    (
+        40,
+        "create_access_tokens",
+        include_str!("../migrations/V40__create_access_tokens.sql"),
+    ),
+    (
+        41,
+        "add_notification_digests",
+        include_str!("../migrations/V41__add_notification_digests.sql"),
+    ),
+    (
+        42,
+        "create_alert_rules",
+        include_str!("../migrations/V42__create_alert_rules.sql"),
+    ),
+    (
+        43,
+        "add_goat_lifecycle",
+        include_str!("../migrations/V43__add_goat_lifecycle.sql"),
+    ),
+    (
+        44,
+        "create_breeding_services",
+        include_str!("../migrations/V44__create_breeding_services.sql"),
+    ),
+    (
+        45,
+        "create_external_animals",
+        include_str!("../migrations/V45__create_external_animals.sql"),
+    ),
+    (
+        46,
+        "create_export_templates",
+        include_str!("../migrations/V46__create_export_templates.sql"),
+    ),
+    (
+        47,
+        "add_goat_quick_note",
+        include_str!("../migrations/V47__add_goat_quick_note.sql"),
+    ),
+    (
+        48,
+        "create_vet_visits",
+        include_str!("../migrations/V48__create_vet_visits.sql"),
+    ),
+    (
+        49,
+        "create_lab_results",
+        include_str!("../migrations/V49__create_lab_results.sql"),
+    ),
+    (
+        50,
+        "add_disease_notifiable",
+        include_str!("../migrations/V50__add_disease_notifiable.sql"),
+    ),
+    (
+        51,
+        "create_traceability_records",
+        include_str!("../migrations/V51__create_traceability_records.sql"),
+    ),
+    (
+        52,
+        "create_goat_movements",
+        include_str!("../migrations/V52__create_goat_movements.sql"),
+    ),
+    (
+        53,
+        "create_tag_counters",
+        include_str!("../migrations/V53__create_tag_counters.sql"),
+    ),
+    (
+        54,
+        "create_sign_in_credentials",
+        include_str!("../migrations/V54__create_sign_in_credentials.sql"),
+    ),
+    (
+        55,
+        "keep_movements_of_removed_goats",
+        include_str!("../migrations/V55__keep_movements_of_removed_goats.sql"),
+    ),
+    (
+        56,
+        "soft_delete_goats",
+        include_str!("../migrations/V56__soft_delete_goats.sql"),
+    ),
+    (
+        57,
+        "record_event_actors",
+        include_str!("../migrations/V57__record_event_actors.sql"),
+    ),
+    (
+        58,
+        "limit_sign_in_attempts",
+        include_str!("../migrations/V58__limit_sign_in_attempts.sql"),
+    ),
+    (
+        59,
+        "add_goat_outbox",
+        include_str!("../migrations/V59__add_goat_outbox.sql"),
+    ),
+];
+
+/// Runs all embedded migrations that have not yet been applied,
 /// ensuring the database schema is current.
 ///
+/// Applied versions are recorded in the `schema_migrations` table, and each
+/// migration runs inside its own transaction.
+///
 /// # Errors
-/// Returns an application database error if migration fails.
+/// Returns an application database error if any migration fails.
 ///
 /// # Logging
-/// Logs migration success and applied migration info at info level,
-/// or failure at error level.
-// pub fn run_migrations(conn: &mut Connection) -> Result<(), AppError> {
-//     info!("Migrations disabled currently!");
-//info!("Running database migrations...");
-//match embedded_migrations::run(conn) {
-//    Ok(report) => {
-//        info!(affected = ?report.applied_migrations(), "Migrations applied");
-//        Ok(())
-//    }
-//    Err(e) => {
-//        error!("Migration failure: {:?}", e);
-//        Err(AppError::DbError(rusqlite::Error::ExecuteReturnedResults))
-//    }
-//}
-//    Ok(())
-//}
+/// Logs each applied migration at info level, or failure at error level.
+pub fn run_migrations(conn: &mut Connection) -> Result<(), AppError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        );",
+    )?;
+
+    let current: i64 = conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    )?;
+
+    for (version, name, sql) in MIGRATIONS.iter().filter(|(v, _, _)| *v > current) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql).map_err(|e| {
+            error!(version, name, "Migration failure: {:?}", e);
+            AppError::DbError(e)
+        })?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version, name) VALUES (?1, ?2)",
+            rusqlite::params![version, name],
+        )?;
+        tx.commit()?;
+        info!(version, name, "Applied migration");
+    }
+    Ok(())
+}
 
 /// Attempts to fetch the ID of the vaccine by name in the given transaction.
 /// Inserts the vaccine if missing, ensuring referential integrity.
@@ -206,7 +590,7 @@ pub fn fetch_diseases(conn: &Connection, goat_id: i64) -> Result<Vec<DiseaseRef>
 ///
 /// # Logging
 /// Forwards errors and logs keys steps and outcomes.
-pub fn get_or_insert_vaccine(tx: &Transaction, vaccine: &VaccineRef) -> Result<i64, AppError> {
+pub fn get_or_insert_vaccine(tx: &Connection, vaccine: &VaccineRef) -> Result<i64, AppError> {
     if let Some(id) = vaccine.id {
         return Ok(id);
     }
@@ -219,7 +603,7 @@ pub fn get_or_insert_vaccine(tx: &Transaction, vaccine: &VaccineRef) -> Result<i
 }
 
 /// Like `get_or_insert_vaccine`, but for diseases.
-pub fn get_or_insert_disease(tx: &Transaction, disease: &DiseaseRef) -> Result<i64, AppError> {
+pub fn get_or_insert_disease(tx: &Connection, disease: &DiseaseRef) -> Result<i64, AppError> {
     if let Some(id) = disease.id {
         return Ok(id);
     }
@@ -0,0 +1,115 @@
+//! Email and SMS digests of workers' unread notifications.
+//!
+//! Mention notifications are sent one by one as they are made (see
+//! `handlers::notes`). A digest gathers the ones still unread into a single
+//! `OutgoingMessage` per worker who asked for email or SMS, so nothing is
+//! missed when a mention went out while the gateway was down or the worker
+//! has not opened the dashboard. Digests are sent by the `cron_reports`
+//! binary; each notification goes into at most one digest.
+
+use crate::errors::AppError;
+use crate::handlers::scale::READ_AT_FORMAT;
+use crate::handlers::workers::load_workers;
+use crate::messaging::{MessageGateway, OutgoingMessage};
+use chrono::Utc;
+use rusqlite::{Connection, params};
+use shared::notifications::{NotifyChannel, notification_link};
+use tracing::{debug, info, warn};
+
+/// Most notifications quoted in one digest; the rest are only counted.
+pub const DIGEST_MAX_LINES: usize = 10;
+
+/// A digest due to one worker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Digest {
+    pub worker_id: i64,
+    /// The notifications it covers, to be marked digested once sent.
+    pub notification_ids: Vec<i64>,
+    pub message: OutgoingMessage,
+}
+
+/// Builds the text of a digest of `messages`, newest first.
+fn digest_text(messages: &[String]) -> String {
+    let mut lines = vec![match messages.len() {
+        1 => "You have 1 unread notification on Yagi:".to_string(),
+        n => format!("You have {} unread notifications on Yagi:", n),
+    }];
+    lines.extend(
+        messages
+            .iter()
+            .take(DIGEST_MAX_LINES)
+            .map(|message| format!("- {}", message)),
+    );
+    if messages.len() > DIGEST_MAX_LINES {
+        lines.push(format!("…and {} more", messages.len() - DIGEST_MAX_LINES));
+    }
+    lines.join("\n")
+}
+
+/// The digests due: one for each worker notified by email or SMS, with a
+/// contact, and unread notifications not yet in a digest. Each links to
+/// where the newest notification does.
+pub fn pending_digests(conn: &Connection) -> Result<Vec<Digest>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, goat_id, message FROM notifications
+         WHERE worker_id = ?1 AND read = 0 AND digested_at IS NULL
+         ORDER BY created_at DESC, id DESC",
+    )?;
+    let mut digests = Vec::new();
+    for worker in load_workers(conn)? {
+        let (Some(worker_id), Some(contact)) = (worker.id, worker.contact.as_deref()) else {
+            continue;
+        };
+        if worker.notify_by == NotifyChannel::App || contact.trim().is_empty() {
+            continue;
+        }
+        let rows = stmt
+            .query_map([worker_id], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<Result<Vec<(i64, Option<i64>, String)>, _>>()?;
+        let Some((_, newest_goat, _)) = rows.first() else {
+            continue;
+        };
+        let link = notification_link(*newest_goat);
+        let messages: Vec<String> = rows.iter().map(|(_, _, m)| m.clone()).collect();
+        debug!(worker = %worker.name, count = rows.len(), "Digest due");
+        digests.push(Digest {
+            worker_id,
+            notification_ids: rows.iter().map(|(id, _, _)| *id).collect(),
+            message: OutgoingMessage {
+                channel: worker.notify_by,
+                to: contact.trim().to_string(),
+                text: digest_text(&messages),
+                link,
+            },
+        });
+    }
+    Ok(digests)
+}
+
+/// Sends the pending digests through `gateway` and returns how many were
+/// sent.
+///
+/// A digest the gateway refuses is logged and left pending, so its
+/// notifications are tried again in the next run; the others are still
+/// sent.
+pub fn send_digests(conn: &Connection, gateway: &dyn MessageGateway) -> Result<usize, AppError> {
+    let digested_at = Utc::now().format(READ_AT_FORMAT).to_string();
+    let mut sent = 0;
+    for digest in pending_digests(conn)? {
+        if let Err(e) = gateway.send(&digest.message) {
+            warn!(worker_id = digest.worker_id, "Digest not sent: {}", e);
+            continue;
+        }
+        for id in &digest.notification_ids {
+            conn.execute(
+                "UPDATE notifications SET digested_at = ?1 WHERE id = ?2",
+                params![digested_at, id],
+            )?;
+        }
+        sent += 1;
+    }
+    info!(sent, "Notification digests sent");
+    Ok(sent)
+}
@@ -13,11 +13,29 @@ pub enum AppError {
     #[error("Connection pool error: {0}")]
     PoolError(#[from] r2d2::Error),
 
+    #[error("PostgreSQL error: {0}")]
+    PostgresError(#[from] postgres::Error),
+
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+
     #[error("Parsing error: {0}")]
     ParseError(#[from] ParseEnumError),
+
+    #[error("Serialization error: {0}")]
+    SerializeError(#[from] serde_json::Error),
+
+    #[error("Upstream service error: {0}")]
+    Upstream(String),
 }
 
 /// Error type for enum parsing failures with context.
@@ -61,14 +79,38 @@ impl ResponseError for AppError {
                 tracing::error!("Connection pool error: {:?}", e);
                 HttpResponse::InternalServerError().body(format!("Internal database error: {}", e))
             }
+            AppError::PostgresError(e) => {
+                tracing::error!("PostgreSQL error: {:?}", e);
+                HttpResponse::InternalServerError().body(format!("Internal database error: {}", e))
+            }
             AppError::InvalidInput(msg) => {
                 tracing::warn!("Invalid input error: {}", msg);
                 HttpResponse::BadRequest().body(msg.clone())
             }
+            AppError::Unauthorized(msg) => {
+                tracing::warn!("Unauthorized request: {}", msg);
+                HttpResponse::Unauthorized().body(msg.clone())
+            }
+            AppError::Forbidden(msg) => {
+                tracing::warn!("Forbidden request: {}", msg);
+                HttpResponse::Forbidden().body(msg.clone())
+            }
+            AppError::TooManyRequests(msg) => {
+                tracing::warn!("Too many requests: {}", msg);
+                HttpResponse::TooManyRequests().body(msg.clone())
+            }
             AppError::ParseError(e) => {
                 tracing::warn!("Parsing error: {}", e);
                 HttpResponse::BadRequest().body(format!("Parsing error: {}", e))
             }
+            AppError::SerializeError(e) => {
+                tracing::error!("Serialization error: {:?}", e);
+                HttpResponse::InternalServerError().body(format!("Internal serialization error: {}", e))
+            }
+            AppError::Upstream(msg) => {
+                tracing::error!("Upstream service error: {}", msg);
+                HttpResponse::BadGateway().body(msg.clone())
+            }
         }
     }
 }
@@ -0,0 +1,98 @@
+//! Extension point for estimating a goat's weight from a photo.
+//!
+//! Photo-based estimation is experimental and runs outside this server: the
+//! handler hands the photo to a `WeightEstimator` registered as
+//! `web::Data<dyn WeightEstimator>`, and the dashboard uses the answer to
+//! pre-fill a weighing marked as estimated. `HttpWeightEstimator`, the
+//! built-in estimator, posts the photo to a service at a configured URL
+//! (`YAGI_WEIGHT_ESTIMATOR_URL` for the server binary). Without a
+//! registered estimator the feature is off.
+
+use crate::errors::AppError;
+use crate::outbound::HttpEndpoint;
+use shared::growth::WeightEstimate;
+use std::time::Duration;
+use tracing::{debug, trace};
+
+/// How long the estimation service may take to connect and to answer.
+pub const ESTIMATE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A photo of a goat taken beside an object of known size, which lets the
+/// estimator scale the goat's outline.
+#[derive(Debug, Clone)]
+pub struct GoatPhoto {
+    /// Encoded image, e.g. a JPEG from the phone's camera.
+    pub image: Vec<u8>,
+    /// MIME type of `image`, e.g. `image/jpeg`.
+    pub content_type: String,
+    /// Length of the reference object in the photo, in centimetres.
+    pub reference_cm: f64,
+}
+
+/// Estimates weights from photos.
+///
+/// `estimate` may block and is called on a worker thread.
+pub trait WeightEstimator: Send + Sync {
+    /// The estimated weight of the goat in `photo`.
+    fn estimate(&self, photo: &GoatPhoto) -> Result<WeightEstimate, AppError>;
+}
+
+/// Posts photos to an estimation service over HTTP or HTTPS.
+///
+/// The service receives the image as the request body, with its MIME type
+/// as `Content-Type` and the reference length as a `reference_cm` query
+/// parameter, and answers with a JSON `WeightEstimate`.
+#[derive(Debug, Clone)]
+pub struct HttpWeightEstimator {
+    endpoint: HttpEndpoint,
+}
+
+impl HttpWeightEstimator {
+    /// An estimator for the service at `url`, which must be an
+    /// `http://` or `https://` URL (see `HttpEndpoint::new`).
+    pub fn new(url: &str) -> Result<Self, String> {
+        Ok(HttpWeightEstimator {
+            endpoint: HttpEndpoint::new(url, "Weight estimator")?,
+        })
+    }
+}
+
+impl WeightEstimator for HttpWeightEstimator {
+    fn estimate(&self, photo: &GoatPhoto) -> Result<WeightEstimate, AppError> {
+        debug!(
+            address = self.endpoint.address(),
+            bytes = photo.image.len(),
+            "Sending photo to weight estimator"
+        );
+        let (status, body) = self
+            .endpoint
+            .post(
+                &format!("reference_cm={}", photo.reference_cm),
+                &photo.content_type,
+                &photo.image,
+                ESTIMATE_TIMEOUT,
+            )
+            .map_err(|e| AppError::Upstream(format!("Weight estimator unreachable: {}", e)))?;
+        trace!(status, bytes = body.len(), "Weight estimator answered");
+        if !(200..300).contains(&status) {
+            return Err(AppError::Upstream(format!(
+                "Weight estimator answered {}: {}",
+                status,
+                String::from_utf8_lossy(&body).trim()
+            )));
+        }
+        let estimate: WeightEstimate = serde_json::from_slice(&body).map_err(|e| {
+            AppError::Upstream(format!(
+                "Weight estimator sent an unreadable estimate: {}",
+                e
+            ))
+        })?;
+        if estimate.weight <= 0.0 {
+            return Err(AppError::Upstream(format!(
+                "Weight estimator returned an impossible weight of {} kg",
+                estimate.weight
+            )));
+        }
+        Ok(estimate)
+    }
+}
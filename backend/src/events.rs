@@ -0,0 +1,479 @@
+//! Event log of changes to the herd.
+//!
+//! Every change to a goat's record goes through `EventLog::apply`, which
+//! appends it to the `events` table as a `DomainEvent` and then `project`s
+//! it onto the tables, in the transaction making the change. Goats, and the
+//! weighings and sales that events add, get their IDs from `next_id` before
+//! the event is built, so the event states them.
+//!
+//! When the server runs event-sourced (`web::Data<EventSourcing>` is
+//! registered, which the server binary does when `YAGI_EVENT_SOURCING` is
+//! set), every event is kept. The log is then an ordered record of the
+//! herd: the feed behind `GET /events` for audit trails, delta sync and
+//! webhooks, which resume from the last event ID they saw. And `replay`
+//! rebuilds the goats, with their pedigrees, placement, lifecycle stages,
+//! neuterings, tags and trash, along with their weighings, lifecycle
+//! history and sales, in a database without goats, as long as the farm
+//! keeps its whole log (see `RetentionPolicy::event_days`).
+//!
+//! `field_history` reads the log the other way round, listing the changes
+//! to one field of a goat for the detail view. So that it works without
+//! event sourcing too, goat registrations and updates are always logged;
+//! while event sourcing is off they are marked as not sourced and left out
+//! of the feed and replays, as the rest of the log is missing.
+//!
+//! Each event records its `Actor`: who made the change, by the session,
+//! access token or background job it came from.
+//!
+//! Handlers take an `EventLog` extractor and call `EventLog::apply`.
+
+use crate::auth::{CurrentSession, TokenScope};
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::finance::insert_transaction;
+use crate::repository::{insert_goat, patch_goat};
+use crate::retention::move_to_trash;
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpMessage, HttpRequest, web};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde_json::{Map, Value};
+use shared::events::{DomainEvent, FieldChange, HISTORY_FIELDS, StoredEvent};
+use shared::lifecycle::LifecycleStage;
+use std::future::{Ready, ready};
+use tracing::{debug, info, trace};
+
+/// Marker registered as `web::Data<EventSourcing>` to turn the event log on.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventSourcing;
+
+/// Who made a change, as recorded with its events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Actor {
+    /// A request made without a session or access token.
+    #[default]
+    Unknown,
+    /// A request made in the session with this ID.
+    Session(i64),
+    /// A request made with the personal access token with this ID.
+    AccessToken(i64),
+    /// A device, such as a scale, sending the API key with this ID.
+    ApiKey(i64),
+    /// A background job (see `crate::jobs`).
+    Job,
+}
+
+impl Actor {
+    /// The actor of `req`, by the session or access token it was made with.
+    pub fn for_request(req: &HttpRequest) -> Self {
+        let extensions = req.extensions();
+        if let Some(session) = extensions.get::<CurrentSession>() {
+            return Actor::Session(session.0);
+        }
+        match extensions.get::<TokenScope>() {
+            Some(scope) => Actor::AccessToken(scope.id),
+            None => Actor::Unknown,
+        }
+    }
+
+    /// How the log names the actor: the worker, or `Owner`, with the device
+    /// or access token they used, or the API key's device. `None` if unknown.
+    fn describe(self, conn: &Connection) -> Result<Option<String>, AppError> {
+        let (query, id) = match self {
+            Actor::Unknown => return Ok(None),
+            Actor::Job => return Ok(Some("Background job".to_string())),
+            Actor::Session(id) => (
+                "SELECT COALESCE(w.name, 'Owner') || ' on ' || s.device \
+                 FROM sessions s LEFT JOIN workers w ON w.id = s.worker_id WHERE s.id = ?1",
+                id,
+            ),
+            Actor::AccessToken(id) => (
+                "SELECT COALESCE(w.name, 'Owner') || ' with token ' || t.name \
+                 FROM access_tokens t LEFT JOIN workers w ON w.id = t.worker_id WHERE t.id = ?1",
+                id,
+            ),
+            Actor::ApiKey(id) => (
+                "SELECT name || ' (API key)' FROM api_keys WHERE id = ?1",
+                id,
+            ),
+        };
+        Ok(conn.query_row(query, [id], |row| row.get(0)).optional()?)
+    }
+}
+
+/// Where a request's changes are logged, and who they are by; see the
+/// module docs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventLog {
+    enabled: bool,
+    actor: Actor,
+}
+
+impl EventLog {
+    /// A log that records events.
+    pub fn enabled() -> Self {
+        EventLog {
+            enabled: true,
+            actor: Actor::Unknown,
+        }
+    }
+
+    /// A log that only records goat registrations and updates, for field
+    /// history, as when event sourcing is off.
+    pub fn disabled() -> Self {
+        EventLog {
+            enabled: false,
+            actor: Actor::Unknown,
+        }
+    }
+
+    /// The log for `req`: enabled if the app registers `EventSourcing`, and
+    /// recording the session or access token of `req` as the actor.
+    pub fn for_request(req: &HttpRequest) -> Self {
+        EventLog {
+            enabled: req.app_data::<web::Data<EventSourcing>>().is_some(),
+            actor: Actor::for_request(req),
+        }
+    }
+
+    /// The same log, recording `actor` as who made the changes.
+    pub fn by(mut self, actor: Actor) -> Self {
+        self.actor = actor;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Makes the change `event` states through `conn`, which should be the
+    /// transaction making it: records the event and projects it onto the
+    /// tables (see `project`).
+    pub fn apply(&self, conn: &Connection, event: &DomainEvent) -> Result<(), AppError> {
+        self.record(conn, event)?;
+        project(conn, event)
+    }
+
+    /// Appends `event` through `conn` if event sourcing is on or `event`
+    /// registers or updates a goat.
+    fn record(&self, conn: &Connection, event: &DomainEvent) -> Result<(), AppError> {
+        let for_history = matches!(
+            event,
+            DomainEvent::GoatRegistered { .. } | DomainEvent::GoatUpdated { .. }
+        );
+        if self.enabled || for_history {
+            insert_event(conn, event, self.actor, self.enabled)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromRequest for EventLog {
+    type Error = AppError;
+    type Future = Ready<Result<Self, AppError>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(EventLog::for_request(req)))
+    }
+}
+
+/// The ID the next row added to `table`, which numbers its rows with
+/// `AUTOINCREMENT`, gets unless it is given one.
+pub fn next_id(conn: &Connection, table: &str) -> Result<i64, AppError> {
+    Ok(conn.query_row(
+        &format!(
+            "SELECT MAX(COALESCE((SELECT seq FROM sqlite_sequence WHERE name = ?1), 0), \
+                    COALESCE((SELECT MAX(id) FROM {}), 0)) + 1",
+            table
+        ),
+        [table],
+        |row| row.get(0),
+    )?)
+}
+
+/// Appends `event` to the log and returns its ID.
+pub fn append(conn: &Connection, event: &DomainEvent) -> Result<i64, AppError> {
+    insert_event(conn, event, Actor::Unknown, true)
+}
+
+/// Appends `event` by `actor`, left out of the feed unless `sourced`, and
+/// returns its ID.
+fn insert_event(
+    conn: &Connection,
+    event: &DomainEvent,
+    actor: Actor,
+    sourced: bool,
+) -> Result<i64, AppError> {
+    conn.execute(
+        "INSERT INTO events (kind, goat_id, payload, actor, sourced) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            event.kind(),
+            event.goat_id(),
+            serde_json::to_string(event)?,
+            actor.describe(conn)?,
+            sourced
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    trace!(
+        event_id = id,
+        kind = event.kind(),
+        sourced,
+        "Appended event"
+    );
+    Ok(id)
+}
+
+/// Reads the events `stmt` selects with `params`, as `id, recorded_at,
+/// actor, payload`.
+fn read_events(
+    stmt: &mut rusqlite::Statement,
+    params: impl rusqlite::Params,
+) -> Result<Vec<StoredEvent>, AppError> {
+    let rows = stmt
+        .query_map(params, |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, DateTime<Utc>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    rows.into_iter()
+        .map(|(id, recorded_at, actor, payload)| {
+            Ok(StoredEvent {
+                id,
+                recorded_at,
+                actor,
+                event: serde_json::from_str(&payload)?,
+            })
+        })
+        .collect()
+}
+
+/// Up to `limit` events with an ID greater than `after`, oldest first,
+/// leaving out those logged only for field history.
+pub fn events_after(
+    conn: &Connection,
+    after: i64,
+    limit: i64,
+) -> Result<Vec<StoredEvent>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, recorded_at, actor, payload FROM events \
+         WHERE id > ?1 AND sourced = 1 ORDER BY id LIMIT ?2",
+    )?;
+    read_events(&mut stmt, params![after, limit])
+}
+
+/// Every event about the goat with ID `goat_id`, oldest first, including
+/// those logged only for field history.
+pub fn events_for_goat(conn: &Connection, goat_id: i64) -> Result<Vec<StoredEvent>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, recorded_at, actor, payload FROM events WHERE goat_id = ?1 ORDER BY id",
+    )?;
+    read_events(&mut stmt, [goat_id])
+}
+
+/// The changes `events` (about one goat, oldest first) made to its
+/// `HISTORY_FIELDS`, or only to `field` if given, newest first.
+///
+/// A field counts from the value the goat was registered with. Updates
+/// that left a value as it was, as a full `PUT /goats` does for most
+/// fields, are not changes. Should the goat's ID have been reused, only the
+/// changes since the last registration are kept.
+pub fn field_history(
+    events: &[StoredEvent],
+    field: Option<&str>,
+) -> Result<Vec<FieldChange>, AppError> {
+    let mut current = Map::new();
+    let mut changes = Vec::new();
+    for stored in events {
+        let values = match &stored.event {
+            DomainEvent::GoatRegistered { goat } => {
+                current.clear();
+                changes.clear();
+                serde_json::to_value(&goat.params)?
+            }
+            DomainEvent::GoatUpdated { update, .. } => serde_json::to_value(update)?,
+            _ => continue,
+        };
+        let Value::Object(values) = values else {
+            continue;
+        };
+        for (name, to) in values {
+            if !HISTORY_FIELDS.contains(&name.as_str()) || field.is_some_and(|f| f != name) {
+                continue;
+            }
+            let from = current.insert(name.clone(), to.clone());
+            if from.as_ref() == Some(&to) {
+                continue;
+            }
+            changes.push(FieldChange {
+                event_id: stored.id,
+                changed_at: stored.recorded_at,
+                actor: stored.actor.clone(),
+                field: name,
+                from,
+                to,
+            });
+        }
+    }
+    changes.reverse();
+    Ok(changes)
+}
+
+/// Applies `event` to the tables through `conn`, as `EventLog::apply` does
+/// when the change is made. Goats and the records events add keep the IDs
+/// the events state.
+///
+/// # Errors
+/// - `AppError::InvalidInput` if the event changes a goat that does not
+///   exist.
+pub fn project(conn: &Connection, event: &DomainEvent) -> Result<(), AppError> {
+    let goat_id = event.goat_id();
+    let affected = match event {
+        DomainEvent::GoatRegistered { goat } => {
+            insert_goat(conn, goat.id, &goat.params)?;
+            conn.execute(
+                "UPDATE goats SET tag_id = ?1, date_of_birth = ?2 WHERE id = ?3",
+                params![goat.tag_id, goat.date_of_birth, goat_id],
+            )?
+        }
+        DomainEvent::GoatUpdated { update, .. } => {
+            usize::from(patch_goat(conn, goat_id, update)?.is_some())
+        }
+        DomainEvent::GoatRemoved { .. } => {
+            move_to_trash(conn, goat_id)?;
+            1
+        }
+        DomainEvent::GoatRestored { .. } => {
+            conn.execute("DELETE FROM goat_trash WHERE goat_id = ?1", [goat_id])?;
+            conn.execute(
+                "UPDATE goats SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+                [goat_id],
+            )?
+        }
+        DomainEvent::GoatPurged { .. } => {
+            conn.execute("DELETE FROM goat_trash WHERE goat_id = ?1", [goat_id])?;
+            conn.execute(
+                "DELETE FROM goats WHERE id = ?1 AND deleted_at IS NOT NULL",
+                [goat_id],
+            )?
+        }
+        DomainEvent::TagAssigned { tag_id, .. } => conn.execute(
+            "UPDATE goats SET tag_id = ?1 WHERE id = ?2",
+            params![tag_id, goat_id],
+        )?,
+        DomainEvent::GoatPlaced { space_id, .. } => conn.execute(
+            "UPDATE goats SET space_id = ?1 WHERE id = ?2",
+            params![space_id, goat_id],
+        )?,
+        DomainEvent::LifecycleChanged {
+            stage,
+            changed_on,
+            note,
+            ..
+        } => {
+            let from: Option<Option<String>> = conn
+                .query_row(
+                    "SELECT lifecycle_stage FROM goats WHERE id = ?1",
+                    [goat_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            match from {
+                Some(from) => {
+                    let to = LifecycleStage::to_str(stage);
+                    conn.execute(
+                        "UPDATE goats SET lifecycle_stage = ?1 WHERE id = ?2",
+                        params![to, goat_id],
+                    )?;
+                    conn.execute(
+                        "INSERT INTO lifecycle_changes (goat_id, from_stage, to_stage, changed_on, note) \
+                         VALUES (?1, ?2, ?3, ?4, ?5)",
+                        params![goat_id, from, to, changed_on, note],
+                    )?
+                }
+                None => 0,
+            }
+        }
+        DomainEvent::PedigreeRecorded {
+            sire_id,
+            dam_id,
+            sire_external_id,
+            dam_external_id,
+            date_of_birth,
+            ..
+        } => conn.execute(
+            "UPDATE goats SET sire_id = ?1, dam_id = ?2, sire_external_id = ?3, \
+                 dam_external_id = ?4, date_of_birth = ?5 WHERE id = ?6",
+            params![
+                sire_id,
+                dam_id,
+                sire_external_id,
+                dam_external_id,
+                date_of_birth,
+                goat_id
+            ],
+        )?,
+        DomainEvent::GoatNeutered {
+            neutered_on,
+            reason,
+            ..
+        } => conn.execute(
+            "UPDATE goats SET neutered_on = ?1, neutered_reason = ?2 WHERE id = ?3",
+            params![neutered_on, reason, goat_id],
+        )?,
+        DomainEvent::NeuteringCleared { .. } => conn.execute(
+            "UPDATE goats SET neutered_on = NULL, neutered_reason = NULL WHERE id = ?1",
+            [goat_id],
+        )?,
+        DomainEvent::WeightRecorded { record, .. } => conn.execute(
+            "INSERT INTO weight_records (id, goat_id, weighed_on, weight, estimated) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                record.id,
+                goat_id,
+                record.weighed_on,
+                record.weight,
+                record.estimated
+            ],
+        )?,
+        DomainEvent::GoatSold { transaction, .. } => {
+            insert_transaction(conn, transaction.id, transaction, Some(goat_id))?;
+            1
+        }
+    };
+    if affected == 0 {
+        return Err(AppError::InvalidInput(format!(
+            "No goat found with id {}",
+            goat_id
+        )));
+    }
+    debug!(kind = event.kind(), goat_id, "Projected event");
+    Ok(())
+}
+
+/// Rebuilds the goats of `db`, with their weighings, lifecycle history and
+/// sales, from `events`, in one transaction, and returns how many events
+/// were applied.
+///
+/// `db` must not have any goats yet. Spaces, external animals and tax rates
+/// that events refer to must already exist.
+pub fn replay(db: &DbPool, events: &[StoredEvent]) -> Result<usize, AppError> {
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+    let goats: i64 = tx.query_row("SELECT COUNT(*) FROM goats", [], |row| row.get(0))?;
+    if goats > 0 {
+        return Err(AppError::InvalidInput(
+            "Events can only be replayed into a database without goats".into(),
+        ));
+    }
+    for stored in events {
+        project(&tx, &stored.event)?;
+    }
+    tx.commit()?;
+    info!(count = events.len(), "Replayed events");
+    Ok(events.len())
+}
@@ -3,6 +3,7 @@
 use chrono::NaiveDate;
 use rand::{Rng, seq::SliceRandom};
 use rusqlite::{Connection, Result, params};
+use shared::{Breed, Gender, GoatParams};
 use tracing::{info, trace};
 use tracing_subscriber;
 
@@ -61,7 +62,7 @@ fn main() -> Result<()> {
         "Chegu",
         "Jakhrana",
     ];
-    let genders = ["Male", "Female"];
+    let genders = [Gender::Male, Gender::Female];
     let diets = ["Hay", "Pasture", "Mixed"];
 
     // Cache vaccine IDs
@@ -80,22 +81,36 @@ fn main() -> Result<()> {
 
     // Insert ~20 goats with random associations
     for i in 1..=20 {
-        let breed = breeds[rng.gen_range(0..breeds.len())];
-        let name = format!("Goat{}", i);
-        let gender = genders[rng.gen_range(0..genders.len())];
-        let offspring = rng.gen_range(0..5);
         let cost = rng.gen_range(100.0..250.0);
-        let weight = rng.gen_range(40.0..90.0);
-        let current_price = cost * rng.gen_range(1.1..1.5);
-        let diet = diets[rng.gen_range(0..diets.len())];
-        let last_bred = random_date("2024-01-01", "2025-08-01").to_string();
-        let health_status = if i % 15 == 0 { "recovering" } else { "healthy" };
+        let goat = GoatParams::builder(format!("Goat{}", i))
+            .breed(Breed::from_str(breeds[rng.gen_range(0..breeds.len())]))
+            .gender(genders[rng.gen_range(0..genders.len())].clone())
+            .offspring(rng.gen_range(0..5))
+            .cost(cost)
+            .weight(rng.gen_range(40.0..90.0))
+            .current_price(cost * rng.gen_range(1.1..1.5))
+            .diet(diets[rng.gen_range(0..diets.len())])
+            .last_bred(random_date("2024-01-01", "2025-08-01").to_string())
+            .health_status(if i % 15 == 0 { "recovering" } else { "healthy" })
+            .build()
+            .expect("Sample goat is valid");
 
         trace!("Inserting goat");
         conn.execute(
             "INSERT INTO goats (breed, name, gender, offspring, cost, weight, current_price, diet, last_bred, health_status)
             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-            params![breed, name, gender, offspring, cost, weight, current_price, diet, last_bred, health_status],
+            params![
+                Breed::to_str(&goat.breed),
+                goat.name,
+                Gender::to_str(&goat.gender),
+                goat.offspring,
+                goat.cost,
+                goat.weight,
+                goat.current_price,
+                goat.diet,
+                goat.last_bred,
+                goat.health_status
+            ],
         )?;
 
         let goat_id = conn.last_insert_rowid();
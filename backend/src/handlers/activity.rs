@@ -0,0 +1,83 @@
+//! This module builds the dashboard's recent activity feed: goats added,
+//! sold and treated, newest first (see `shared::activity`).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use actix_web::{HttpResponse, Responder, web};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, Row};
+use shared::activity::{ACTIVITY_LIMIT, ActivityEvent, ActivityKind};
+use tracing::{debug, info};
+
+/// Every event as `(kind, goat_id, goat_name, detail, amount, at, seq)`,
+/// where `detail` is the breed, the sale currency, or the condition, and
+/// `seq` orders events entered in the same second.
+const ACTIVITY_SQL: &str = "\
+    SELECT 'GoatAdded', id, name, breed, NULL, created_at, id FROM goats WHERE deleted_at IS NULL \
+    UNION ALL \
+    SELECT 'GoatSold', g.id, g.name, t.currency, t.amount, t.created_at, t.id \
+    FROM transactions t JOIN goats g ON g.id = t.goat_id AND g.deleted_at IS NULL \
+    WHERE t.kind = 'Income' AND t.category = 'Sale' \
+    UNION ALL \
+    SELECT 'GoatTreated', g.id, g.name, h.condition, NULL, h.created_at, h.id \
+    FROM health_incidents h JOIN goats g ON g.id = h.goat_id AND g.deleted_at IS NULL \
+    ORDER BY 6 DESC, 7 DESC \
+    LIMIT ?1";
+
+fn row_to_event(row: &Row) -> rusqlite::Result<ActivityEvent> {
+    let kind = match row.get::<_, String>(0)?.as_str() {
+        "GoatAdded" => ActivityKind::GoatAdded,
+        "GoatSold" => ActivityKind::GoatSold,
+        _ => ActivityKind::GoatTreated,
+    };
+    let goat_name: String = row.get(2)?;
+    let detail: Option<String> = row.get(3)?;
+    let summary = match kind {
+        ActivityKind::GoatAdded => {
+            format!("Added {} ({})", goat_name, detail.unwrap_or_default())
+        }
+        ActivityKind::GoatSold => format!(
+            "Sold {} for {:.2}{}",
+            goat_name,
+            row.get::<_, f64>(4)?,
+            detail.map(|c| format!(" {}", c)).unwrap_or_default()
+        ),
+        ActivityKind::GoatTreated => {
+            format!("Treated {} for {}", goat_name, detail.unwrap_or_default())
+        }
+    };
+    Ok(ActivityEvent {
+        kind,
+        goat_id: row.get(1)?,
+        goat_name,
+        summary,
+        at: row.get::<_, Option<DateTime<Utc>>>(5)?.unwrap_or_default(),
+    })
+}
+
+/// Loads the `limit` most recently entered events.
+pub fn load_activity(conn: &Connection, limit: usize) -> Result<Vec<ActivityEvent>, AppError> {
+    let mut stmt = conn.prepare(ACTIVITY_SQL)?;
+    let events = stmt
+        .query_map([limit as i64], row_to_event)?
+        .collect::<Result<_, _>>()?;
+    Ok(events)
+}
+
+/// Handler for the recent activity feed.
+///
+/// # HTTP Method
+/// - `GET /activity`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of the `ACTIVITY_LIMIT` most
+///   recently entered `ActivityEvent`s, newest first. Sales count only when
+///   recorded against a goat still in the herd.
+pub async fn get_activity(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /activity called");
+    let conn = db.get_conn()?;
+    let events = load_activity(&conn, ACTIVITY_LIMIT)?;
+
+    info!("Returning {} activity events", events.len());
+    Ok(HttpResponse::Ok().json(events))
+}
@@ -0,0 +1,127 @@
+//! This module manages alert rules on herd metrics (see `shared::alerts`).
+//!
+//! Rules are evaluated periodically by the background scheduler (see
+//! `crate::alerts`); the `POST /alerts/evaluate` endpoint triggers an
+//! evaluation on demand.
+
+use crate::alerts::{RULE_COLUMNS, evaluate_alerts, row_to_rule};
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::settings::farm_today;
+use actix_web::{HttpResponse, Responder, web};
+use rusqlite::params;
+use shared::alerts::{AlertCondition, AlertMetric, AlertRule};
+use tracing::{debug, info, warn};
+
+/// Handler for listing all alert rules.
+///
+/// # HTTP Method
+/// - `GET /alerts`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `AlertRule`s, oldest first, each
+///   saying whether it is firing.
+pub async fn get_alert_rules(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /alerts called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM alert_rules ORDER BY id",
+        RULE_COLUMNS
+    ))?;
+    let rules = stmt
+        .query_map([], row_to_rule)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    info!("Returning {} alert rules", rules.len());
+    Ok(HttpResponse::Ok().json(rules))
+}
+
+/// Handler for creating an alert rule.
+///
+/// # HTTP Method
+/// - `POST /alerts`
+///
+/// # Request
+/// - JSON `AlertRule`; `id`, `firing` and `last_fired_at` are ignored.
+///
+/// # Success
+/// - Returns HTTP 201 with the rule as stored.
+///
+/// # Errors
+/// - Returns HTTP 400 for a rule without a name, a negative threshold, or
+///   a change condition on a metric without history.
+pub async fn add_alert_rule(
+    db: web::Data<DbPool>,
+    rule: web::Json<AlertRule>,
+) -> Result<impl Responder, AppError> {
+    debug!(name = %rule.name, "POST /alerts called");
+    rule.validate().map_err(AppError::InvalidInput)?;
+
+    let conn = db.get_conn()?;
+    conn.execute(
+        "INSERT INTO alert_rules (name, metric, condition, threshold, active) \
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            rule.name.trim(),
+            AlertMetric::to_str(&rule.metric),
+            AlertCondition::to_str(&rule.condition),
+            rule.threshold,
+            rule.active,
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    let stored = conn.query_row(
+        &format!("SELECT {} FROM alert_rules WHERE id = ?1", RULE_COLUMNS),
+        [id],
+        row_to_rule,
+    )?;
+
+    info!(rule_id = id, "Alert rule created");
+    Ok(HttpResponse::Created().json(stored))
+}
+
+/// Handler for deleting an alert rule. Notifications it made are kept.
+///
+/// # HTTP Method
+/// - `DELETE /alerts/{id}`
+///
+/// # Errors
+/// - Returns HTTP 400 if no rule matches the ID.
+pub async fn delete_alert_rule(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(rule_id = id, "DELETE /alerts/{{id}} called");
+
+    let conn = db.get_conn()?;
+    let affected = conn.execute("DELETE FROM alert_rules WHERE id = ?1", [id])?;
+    if affected == 0 {
+        warn!(rule_id = id, "Alert rule not found for deletion");
+        return Err(AppError::InvalidInput(format!(
+            "No alert rule found with id {}",
+            id
+        )));
+    }
+
+    info!(rule_id = id, "Alert rule deleted");
+    Ok(HttpResponse::Ok().body("Alert rule deleted"))
+}
+
+/// Handler for evaluating all alert rules immediately.
+///
+/// # HTTP Method
+/// - `POST /alerts/evaluate`
+///
+/// # Success
+/// - Returns HTTP 200 with `{"fired": n}`, the number of rules that started
+///   firing.
+pub async fn evaluate_alerts_now(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("POST /alerts/evaluate called");
+    let mut conn = db.get_conn()?;
+    let today = farm_today(&conn)?;
+    let fired = evaluate_alerts(&mut conn, today)?;
+
+    info!(fired, "Alert rules evaluated on demand");
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "fired": fired })))
+}
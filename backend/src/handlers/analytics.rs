@@ -0,0 +1,200 @@
+//! This module handles herd performance analytics. Feed efficiency joins
+//! feeding logs (inventory consumption with purpose `Feeding`) with weight
+//! history to give FCR and cost per kg gain per goat and per pen.
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use actix_web::{HttpResponse, Responder, web};
+use shared::analytics::{FeedEfficiency, FeedEfficiencyReport};
+use std::collections::HashMap;
+use tracing::{debug, info, trace};
+
+/// Kilograms per unit of a feed item, for the mass units feed is stocked in.
+///
+/// Feed stocked in other units (bales, bags) still counts towards cost but
+/// not towards the kilograms used for FCR.
+fn kg_per_unit(unit: &str) -> Option<f64> {
+    match unit.trim().to_ascii_lowercase().as_str() {
+        "kg" | "kgs" | "kilogram" | "kilograms" => Some(1.0),
+        "g" | "gram" | "grams" => Some(0.001),
+        "quintal" | "quintals" => Some(100.0),
+        "t" | "tonne" | "tonnes" | "ton" | "tons" => Some(1000.0),
+        _ => None,
+    }
+}
+
+/// One feeding log entry.
+struct Feeding {
+    goat_id: Option<i64>,
+    space_id: Option<i64>,
+    consumed_on: String,
+    kg: f64,
+    cost: f64,
+}
+
+/// First and last weighing of a goat.
+struct WeighPeriod {
+    start: String,
+    end: String,
+    gain: f64,
+}
+
+/// Sums the kilograms and cost of the feedings matching `include` within
+/// `period`, or of all of them when there is no period.
+fn sum_feed(
+    feedings: &[Feeding],
+    period: Option<(&str, &str)>,
+    include: impl Fn(&Feeding) -> bool,
+) -> (f64, f64) {
+    feedings
+        .iter()
+        .filter(|f| include(f))
+        .filter(|f| {
+            period.is_none_or(|(start, end)| (start..=end).contains(&f.consumed_on.as_str()))
+        })
+        .fold((0.0, 0.0), |(kg, cost), f| (kg + f.kg, cost + f.cost))
+}
+
+/// Handler for feed conversion ratio and cost per kg gain.
+///
+/// A goat's period runs from its first to its last weighing; only feed logged
+/// to it within that period counts. A pen combines feed logged to the pen or
+/// to any goat in it, over the span of its goats' periods, against their
+/// total gain. Goats and pens with no feed logged are left out.
+///
+/// # HTTP Method
+/// - `GET /analytics/feed-efficiency`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON `FeedEfficiencyReport`, most efficient
+///   (lowest FCR) first and entries without a ratio last.
+pub async fn get_feed_efficiency(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /analytics/feed-efficiency called");
+    let conn = db.get_conn()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT c.goat_id, c.space_id, c.consumed_on, c.quantity, i.unit, i.unit_cost \
+         FROM inventory_consumption c JOIN inventory_items i ON i.id = c.item_id \
+         WHERE c.purpose = 'Feeding'",
+    )?;
+    let feedings: Vec<Feeding> = stmt
+        .query_map([], |row| {
+            let quantity: f64 = row.get(3)?;
+            let unit: String = row.get(4)?;
+            let unit_cost: f64 = row.get(5)?;
+            Ok(Feeding {
+                goat_id: row.get(0)?,
+                space_id: row.get(1)?,
+                consumed_on: row.get(2)?,
+                kg: kg_per_unit(&unit).map_or(0.0, |factor| quantity * factor),
+                cost: quantity * unit_cost,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT goat_id, weighed_on, weight FROM weight_records WHERE estimated = 0 \
+         ORDER BY goat_id, weighed_on, id",
+    )?;
+    let mut periods: HashMap<i64, WeighPeriod> = HashMap::new();
+    let mut first_weight: HashMap<i64, f64> = HashMap::new();
+    for row in stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, f64>(2)?,
+        ))
+    })? {
+        let (goat_id, weighed_on, weight) = row?;
+        let first = *first_weight.entry(goat_id).or_insert(weight);
+        let period = periods.entry(goat_id).or_insert_with(|| WeighPeriod {
+            start: weighed_on.clone(),
+            end: weighed_on.clone(),
+            gain: 0.0,
+        });
+        period.end = weighed_on;
+        period.gain = weight - first;
+    }
+
+    let mut stmt = conn.prepare("SELECT id, name, space_id FROM goats WHERE deleted_at IS NULL")?;
+    let goats = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut report = FeedEfficiencyReport::default();
+    for (id, name, _) in &goats {
+        let period = periods.get(id);
+        let (kg, cost) = sum_feed(
+            &feedings,
+            period.map(|p| (p.start.as_str(), p.end.as_str())),
+            |f| f.goat_id == Some(*id),
+        );
+        if kg == 0.0 && cost == 0.0 {
+            continue;
+        }
+        trace!(goat_id = id, kg, cost, "Computed goat feed efficiency");
+        report.goats.push(FeedEfficiency::new(
+            name.clone(),
+            period.map(|p| (p.start.clone(), p.end.clone())),
+            kg,
+            cost,
+            period.map_or(0.0, |p| p.gain),
+        ));
+    }
+
+    let mut stmt = conn.prepare("SELECT id, name FROM spaces")?;
+    let spaces = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    for (space_id, space_name) in spaces {
+        let members: Vec<i64> = goats
+            .iter()
+            .filter(|(_, _, s)| *s == Some(space_id))
+            .map(|(id, _, _)| *id)
+            .collect();
+        let member_periods: Vec<&WeighPeriod> =
+            members.iter().filter_map(|id| periods.get(id)).collect();
+        let span = member_periods
+            .iter()
+            .map(|p| p.start.as_str())
+            .min()
+            .zip(member_periods.iter().map(|p| p.end.as_str()).max());
+        let (kg, cost) = sum_feed(&feedings, span, |f| {
+            f.space_id == Some(space_id) || f.goat_id.is_some_and(|g| members.contains(&g))
+        });
+        if kg == 0.0 && cost == 0.0 {
+            continue;
+        }
+        report.pens.push(FeedEfficiency::new(
+            space_name,
+            span.map(|(start, end)| (start.to_string(), end.to_string())),
+            kg,
+            cost,
+            member_periods.iter().map(|p| p.gain).sum(),
+        ));
+    }
+
+    let by_fcr = |a: &FeedEfficiency, b: &FeedEfficiency| match (a.fcr, b.fcr) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.name.cmp(&b.name),
+    };
+    report.goats.sort_by(by_fcr);
+    report.pens.sort_by(by_fcr);
+
+    info!(
+        "Returning feed efficiency for {} goats and {} pens",
+        report.goats.len(),
+        report.pens.len()
+    );
+    Ok(HttpResponse::Ok().json(report))
+}
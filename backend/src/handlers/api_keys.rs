@@ -0,0 +1,105 @@
+//! This module handles the device API keys used by `crate::auth`.
+
+use crate::auth::{generate_key, hash_key};
+use crate::db::DbPool;
+use crate::errors::AppError;
+use actix_web::{HttpResponse, Responder, web};
+use shared::scale::{ApiKey, IssuedApiKey, NewApiKey};
+use tracing::{debug, info, warn};
+
+/// Handler for listing API keys, newest first.
+///
+/// # HTTP Method
+/// - `GET /api-keys`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `ApiKey` (without the keys).
+pub async fn get_api_keys(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /api-keys called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, name, revoked, created_at, last_used_at FROM api_keys ORDER BY id DESC",
+    )?;
+    let keys: Vec<ApiKey> = stmt
+        .query_map([], |row| {
+            Ok(ApiKey {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                revoked: row.get(2)?,
+                created_at: row.get(3)?,
+                last_used_at: row.get(4)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    info!("Returning {} API keys", keys.len());
+    Ok(HttpResponse::Ok().json(keys))
+}
+
+/// Handler for creating an API key for a device.
+///
+/// # HTTP Method
+/// - `POST /api-keys`
+///
+/// # Success
+/// - Returns HTTP 201 with a JSON `IssuedApiKey`. The key cannot be
+///   retrieved again later.
+///
+/// # Errors
+/// - Returns HTTP 400 if the name is empty.
+pub async fn add_api_key(
+    db: web::Data<DbPool>,
+    new_key: web::Json<NewApiKey>,
+) -> Result<impl Responder, AppError> {
+    debug!(name = %new_key.name, "POST /api-keys called");
+    let name = new_key.name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput(
+            "API key name must not be empty".into(),
+        ));
+    }
+    let key = generate_key();
+    let conn = db.get_conn()?;
+    conn.execute(
+        "INSERT INTO api_keys (name, key_hash) VALUES (?1, ?2)",
+        [name, &hash_key(&key)],
+    )?;
+    let id = conn.last_insert_rowid();
+
+    info!(api_key_id = id, "API key created");
+    Ok(HttpResponse::Created().json(IssuedApiKey {
+        id,
+        name: name.to_string(),
+        key,
+    }))
+}
+
+/// Handler for revoking an API key. Revoked keys stay listed.
+///
+/// # HTTP Method
+/// - `DELETE /api-keys/{id}`
+///
+/// # Success
+/// - Returns HTTP 200 once the key is revoked.
+///
+/// # Errors
+/// - Returns HTTP 400 if no key has the given ID.
+pub async fn revoke_api_key(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(api_key_id = id, "DELETE /api-keys/{{id}} called");
+    let conn = db.get_conn()?;
+    let updated = conn.execute("UPDATE api_keys SET revoked = 1 WHERE id = ?1", [id])?;
+    if updated == 0 {
+        warn!(api_key_id = id, "API key not found");
+        return Err(AppError::InvalidInput(format!(
+            "No API key found with ID {}",
+            id
+        )));
+    }
+
+    info!(api_key_id = id, "API key revoked");
+    Ok(HttpResponse::Ok().body("API key revoked"))
+}
@@ -0,0 +1,138 @@
+//! This module serves the whole farm as a portable archive and restores one
+//! (see `crate::archive`), for moving a farm between instances. Either
+//! direction may use a passphrase, sent in the `PASSPHRASE_HEADER` header,
+//! to keep the archive encrypted. Only the owner, signed in or with a token
+//! of theirs, may do either: an archive holds the whole farm.
+
+use crate::archive::{decrypt_archive, encrypt_archive, export_archive, import_archive};
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::sessions::requester;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use chrono::Utc;
+use percent_encoding::percent_decode;
+use rusqlite::Connection;
+use shared::archive::{PASSPHRASE_HEADER, is_encrypted};
+use tracing::{debug, info, warn};
+
+/// Reads the percent-encoded passphrase from the request, if one was sent.
+fn passphrase(req: &HttpRequest) -> Result<Option<String>, AppError> {
+    let Some(value) = req.headers().get(PASSPHRASE_HEADER) else {
+        return Ok(None);
+    };
+    let passphrase = percent_decode(value.as_bytes())
+        .decode_utf8()
+        .map_err(|_| AppError::InvalidInput("The passphrase must be UTF-8".into()))?;
+    Ok(Some(passphrase.into_owned()))
+}
+
+/// Refuses `req` unless the owner made it.
+///
+/// # Errors
+/// - `AppError::Unauthorized` if `req` has neither a session nor a token.
+/// - `AppError::Forbidden` if a worker made it.
+fn require_owner(conn: &Connection, req: &HttpRequest) -> Result<(), AppError> {
+    if let Some(worker_id) = requester(conn, req)? {
+        warn!(
+            worker_id,
+            path = req.path(),
+            "Refused farm archive request by a worker"
+        );
+        return Err(AppError::Forbidden(
+            "Only the owner may export or import the farm".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Handler for downloading the farm archive.
+///
+/// # HTTP Method
+/// - `GET /archive`
+///
+/// # Request
+/// - Optional `PASSPHRASE_HEADER` header to encrypt the archive with.
+///
+/// # Success
+/// - Returns HTTP 200 with the archive as an attachment named after the day
+///   of the export: a ZIP file, or with a passphrase its encryption
+///   (`.zip.enc`).
+///
+/// # Errors
+/// - Returns HTTP 400 for a passphrase shorter than
+///   `shared::archive::MIN_PASSPHRASE_CHARS`.
+/// - Returns HTTP 403 unless the owner asked.
+pub async fn get_archive(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+) -> Result<impl Responder, AppError> {
+    debug!("GET /archive called");
+    let passphrase = passphrase(&req)?;
+    let conn = db.get_conn()?;
+    require_owner(&conn, &req)?;
+    let archive = export_archive(&conn)?;
+    let date = Utc::now().format("%Y-%m-%d");
+    let (archive, content_type, name) = match passphrase {
+        Some(passphrase) => (
+            encrypt_archive(&archive, &passphrase)?,
+            "application/octet-stream",
+            format!("farm-{}.zip.enc", date),
+        ),
+        None => (archive, "application/zip", format!("farm-{}.zip", date)),
+    };
+
+    info!(bytes = archive.len(), name = %name, "Returning farm archive");
+    Ok(HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", name),
+        ))
+        .body(archive))
+}
+
+/// Handler for restoring a farm from an archive.
+///
+/// # HTTP Method
+/// - `POST /archive`
+///
+/// # Request
+/// - The archive as the raw body, at most
+///   `crate::archive::MAX_ARCHIVE_BYTES`.
+/// - `PASSPHRASE_HEADER` header, required if the archive is encrypted.
+///
+/// # Success
+/// - Returns HTTP 200 with an `ArchiveSummary` of what was restored.
+///
+/// # Errors
+/// - Returns HTTP 400 if the body is not a farm archive, fails its
+///   checksums, needs a newer instance, or the farm already has goats, and
+///   for an encrypted archive without its passphrase or with a wrong one.
+///   Nothing is imported then.
+/// - Returns HTTP 403 unless the owner asked.
+pub async fn import_farm_archive(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    body: web::Bytes,
+) -> Result<impl Responder, AppError> {
+    debug!(bytes = body.len(), "POST /archive called");
+    let mut conn = db.get_conn()?;
+    require_owner(&conn, &req)?;
+    let archive = if is_encrypted(&body) {
+        let passphrase = passphrase(&req)?.ok_or_else(|| {
+            AppError::InvalidInput("This archive is encrypted; enter its passphrase".into())
+        })?;
+        decrypt_archive(&body, &passphrase)?
+    } else {
+        body.to_vec()
+    };
+    let summary = import_archive(&mut conn, &archive)?;
+
+    info!(
+        tables = summary.tables,
+        rows = summary.rows,
+        "Farm archive imported"
+    );
+    Ok(HttpResponse::Ok().json(summary))
+}
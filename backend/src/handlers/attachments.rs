@@ -0,0 +1,246 @@
+//! This module handles files attached to goats and tasks, such as voice
+//! notes recorded in the dashboard (see `shared::attachments`).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::scale::READ_AT_FORMAT;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Deserialize;
+use shared::attachments::{Attachment, check_voice_note};
+use tracing::{debug, info, warn};
+
+/// Query parameters naming the goat or task whose attachments are listed.
+#[derive(Deserialize)]
+pub struct AttachmentsQuery {
+    pub goat_name: Option<String>,
+    pub task_id: Option<i64>,
+}
+
+/// Query parameters accepted by `POST /attachments/voice`.
+#[derive(Deserialize)]
+pub struct VoiceNoteQuery {
+    pub goat_name: Option<String>,
+    pub task_id: Option<i64>,
+    pub author: String,
+    /// Length of the recording, in seconds.
+    pub duration_secs: f64,
+}
+
+/// Columns read by `row_to_attachment`, with the attachments aliased `a`
+/// and their goat, if any, `g`.
+const ATTACHMENT_COLUMNS: &str = "a.id, g.name, a.task_id, a.author, a.content_type, \
+     a.duration_secs, LENGTH(a.data), a.created_at";
+
+fn row_to_attachment(row: &Row) -> rusqlite::Result<Attachment> {
+    Ok(Attachment {
+        id: row.get(0)?,
+        goat_name: row.get(1)?,
+        task_id: row.get(2)?,
+        author: row.get(3)?,
+        content_type: row.get(4)?,
+        duration_secs: row.get(5)?,
+        size_bytes: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+/// Resolves the goat or task an attachment belongs to into its
+/// `(goat_id, task_id)` pair; exactly one must be given and exist.
+fn resolve_target(
+    conn: &Connection,
+    goat_name: Option<&str>,
+    task_id: Option<i64>,
+) -> Result<(Option<i64>, Option<i64>), AppError> {
+    match (goat_name.map(str::trim), task_id) {
+        (Some(name), None) => {
+            let goat_id: i64 = conn
+                .query_row(
+                    "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+                    [name],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .ok_or_else(|| {
+                    AppError::InvalidInput(format!("No goat found with name {}", name))
+                })?;
+            Ok((Some(goat_id), None))
+        }
+        (None, Some(task_id)) => {
+            conn.query_row("SELECT id FROM tasks WHERE id = ?1", [task_id], |row| {
+                row.get::<_, i64>(0)
+            })
+            .optional()?
+            .ok_or_else(|| AppError::InvalidInput(format!("No task found with id {}", task_id)))?;
+            Ok((None, Some(task_id)))
+        }
+        _ => Err(AppError::InvalidInput(
+            "Give either goat_name or task_id".into(),
+        )),
+    }
+}
+
+/// Handler for listing the attachments of a goat or task.
+///
+/// # HTTP Method
+/// - `GET /attachments?goat_name=Rani` or `GET /attachments?task_id=5`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `Attachment`, oldest first.
+///
+/// # Errors
+/// - Returns HTTP 400 unless exactly one existing goat or task is given.
+pub async fn get_attachments(
+    db: web::Data<DbPool>,
+    query: web::Query<AttachmentsQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat = ?query.goat_name, task_id = ?query.task_id, "GET /attachments called");
+    let conn = db.get_conn()?;
+    let (goat_id, task_id) = resolve_target(&conn, query.goat_name.as_deref(), query.task_id)?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM attachments a LEFT JOIN goats g ON g.id = a.goat_id
+         WHERE a.goat_id IS ?1 AND a.task_id IS ?2 ORDER BY a.created_at, a.id",
+        ATTACHMENT_COLUMNS
+    ))?;
+    let attachments: Vec<Attachment> = stmt
+        .query_map(params![goat_id, task_id], row_to_attachment)?
+        .collect::<Result<_, _>>()?;
+
+    info!("Returning {} attachments", attachments.len());
+    Ok(HttpResponse::Ok().json(attachments))
+}
+
+/// Handler for uploading a voice note to a goat or task.
+///
+/// # HTTP Method
+/// - `POST /attachments/voice?goat_name=Rani&author=Ravi&duration_secs=12.5`
+///   (or `task_id=5` instead of `goat_name`)
+///
+/// # Request
+/// - The raw recording (at most `MAX_ATTACHMENT_BYTES`) with an `audio/*`
+///   `Content-Type`, as produced by the browser's `MediaRecorder`.
+///
+/// # Success
+/// - Returns HTTP 201 with the stored `Attachment`.
+///
+/// # Errors
+/// - Returns HTTP 400 if the recording is empty, not audio or too long, the
+///   author is empty, or not exactly one existing goat or task is given.
+pub async fn add_voice_note(
+    db: web::Data<DbPool>,
+    req: HttpRequest,
+    query: web::Query<VoiceNoteQuery>,
+    body: web::Bytes,
+) -> Result<impl Responder, AppError> {
+    debug!(
+        goat = ?query.goat_name,
+        task_id = ?query.task_id,
+        bytes = body.len(),
+        "POST /attachments/voice called"
+    );
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    check_voice_note(&content_type, query.duration_secs, body.len())
+        .map_err(AppError::InvalidInput)?;
+    let author = query.author.trim();
+    if author.is_empty() {
+        return Err(AppError::InvalidInput("Author must not be empty".into()));
+    }
+    let conn = db.get_conn()?;
+    let (goat_id, task_id) = resolve_target(&conn, query.goat_name.as_deref(), query.task_id)?;
+    conn.execute(
+        "INSERT INTO attachments (goat_id, task_id, author, content_type, data, duration_secs, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            goat_id,
+            task_id,
+            author,
+            content_type,
+            body.as_ref(),
+            query.duration_secs,
+            Utc::now().format(READ_AT_FORMAT).to_string()
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    let stored = conn.query_row(
+        &format!(
+            "SELECT {} FROM attachments a LEFT JOIN goats g ON g.id = a.goat_id WHERE a.id = ?1",
+            ATTACHMENT_COLUMNS
+        ),
+        [id],
+        row_to_attachment,
+    )?;
+
+    info!(attachment_id = id, bytes = body.len(), "Voice note added");
+    Ok(HttpResponse::Created().json(stored))
+}
+
+/// Handler for fetching an attachment's content, e.g. to play a voice note.
+///
+/// # HTTP Method
+/// - `GET /attachments/{id}`
+///
+/// # Success
+/// - Returns HTTP 200 with the content and its original `Content-Type`.
+///
+/// # Errors
+/// - Returns HTTP 400 if the attachment does not exist.
+pub async fn get_attachment(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(attachment_id = id, "GET /attachments/{{id}} called");
+    let conn = db.get_conn()?;
+    let (data, content_type): (Vec<u8>, String) = conn
+        .query_row(
+            "SELECT data, content_type FROM attachments WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::InvalidInput(format!("No attachment found with ID {}", id)))?;
+
+    info!(
+        attachment_id = id,
+        bytes = data.len(),
+        "Returning attachment"
+    );
+    Ok(HttpResponse::Ok().content_type(content_type).body(data))
+}
+
+/// Handler for deleting an attachment.
+///
+/// # HTTP Method
+/// - `DELETE /attachments/{id}`
+///
+/// # Success
+/// - Returns HTTP 200 with a confirmation message.
+///
+/// # Errors
+/// - Returns HTTP 400 if the attachment does not exist.
+pub async fn delete_attachment(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(attachment_id = id, "DELETE /attachments/{{id}} called");
+    let conn = db.get_conn()?;
+    let affected = conn.execute("DELETE FROM attachments WHERE id = ?1", [id])?;
+    if affected == 0 {
+        warn!(attachment_id = id, "Attachment not found for deletion");
+        return Err(AppError::InvalidInput(format!(
+            "No attachment found with ID {}",
+            id
+        )));
+    }
+
+    info!(attachment_id = id, "Attachment deleted");
+    Ok(HttpResponse::Ok().body("Attachment deleted"))
+}
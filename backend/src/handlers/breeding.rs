@@ -0,0 +1,685 @@
+//! This module handles pedigree updates and trees, genetic tags,
+//! neuterings, kidding records, service records with AI success rates,
+//! breeding pair recommendations (see `crate::breeding` for the scoring
+//! engine), and heat predictions from activity tags (see `crate::heat`).
+
+use crate::breeding::{
+    load_candidates, load_genetic_tags, load_kidding_history, load_pedigree_tree, load_services,
+    recommend, success_rates,
+};
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::events::EventLog;
+use crate::handlers::external_animals::{external_id_with_gender, find_external};
+use crate::handlers::parse_date;
+use crate::handlers::settings::farm_today;
+use crate::heat::load_predictions;
+use actix_web::{HttpResponse, Responder, web};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Deserialize;
+use shared::breeding::{
+    BreedingService, GeneticTags, KiddingRecord, Neutering, OutsideSire, Pedigree, ServiceMethod,
+    normalize_tags,
+};
+use shared::events::DomainEvent;
+use shared::{Gender, GoatUpdate};
+use tracing::{debug, info, warn};
+
+/// Default number of pairings returned by `GET /breeding/recommendations`.
+const DEFAULT_RECOMMENDATION_LIMIT: usize = 20;
+
+/// Query parameters accepted by `GET /breeding/recommendations`.
+#[derive(Deserialize)]
+pub struct RecommendationQuery {
+    /// Only pair this doe.
+    pub doe: Option<String>,
+    /// Maximum number of pairings to return.
+    pub limit: Option<usize>,
+}
+
+/// Query parameters accepted by `GET /breeding/kiddings`.
+#[derive(Deserialize)]
+pub struct KiddingQuery {
+    pub doe_name: Option<String>,
+}
+
+/// Query parameters accepted by `GET /breeding/services`.
+#[derive(Deserialize)]
+pub struct ServiceQuery {
+    pub doe_name: Option<String>,
+}
+
+/// Query parameters accepted by `GET /breeding/tags`.
+#[derive(Deserialize)]
+pub struct TagQuery {
+    /// Only list goats with this tag, ignoring case.
+    pub tag: Option<String>,
+}
+
+/// Resolves a goat name to its ID, requiring the given gender.
+fn goat_id_with_gender(conn: &Connection, name: &str, gender: &str) -> Result<i64, AppError> {
+    let row: Option<(i64, String)> = conn
+        .query_row(
+            "SELECT id, gender FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    match row {
+        Some((id, actual)) if actual == gender => Ok(id),
+        Some((_, actual)) => Err(AppError::InvalidInput(format!(
+            "Goat {} is {}, expected {}",
+            name, actual, gender
+        ))),
+        None => Err(AppError::InvalidInput(format!(
+            "No goat found with name {}",
+            name
+        ))),
+    }
+}
+
+/// Rejects dates not in `DATE_FORMAT`.
+fn validate_date(value: &str, field: &str) -> Result<(), AppError> {
+    parse_date(value, field).map(|_| ())
+}
+
+/// Handler for setting a goat's sire, dam, and date of birth.
+///
+/// # HTTP Method
+/// - `PUT /breeding/pedigree`
+///
+/// # Request
+/// - JSON `Pedigree`. Omitted parents and birth date are cleared. A parent
+///   flagged external is looked up in the external registry instead of the
+///   herd.
+///
+/// # Success
+/// - Returns HTTP 200 once the pedigree is stored.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown goat or external animal, a sire that
+///   is not male, a dam that is not female, a goat listed as its own
+///   parent, or a malformed date.
+pub async fn update_pedigree(
+    db: web::Data<DbPool>,
+    events: EventLog,
+    pedigree: web::Json<Pedigree>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat = %pedigree.goat_name, "PUT /breeding/pedigree called");
+    if let Some(dob) = &pedigree.date_of_birth {
+        validate_date(dob, "date_of_birth")?;
+    }
+    let conn = db.get_conn()?;
+    let goat_id: i64 = conn
+        .query_row(
+            "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [&pedigree.goat_name],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!("No goat found with name {}", pedigree.goat_name))
+        })?;
+
+    let (sire_id, sire_external_id) = match &pedigree.sire_name {
+        Some(name) if pedigree.sire_external => (
+            None,
+            Some(external_id_with_gender(&conn, name, Gender::Male)?),
+        ),
+        Some(name) => (Some(goat_id_with_gender(&conn, name, "Male")?), None),
+        None => (None, None),
+    };
+    let (dam_id, dam_external_id) = match &pedigree.dam_name {
+        Some(name) if pedigree.dam_external => (
+            None,
+            Some(external_id_with_gender(&conn, name, Gender::Female)?),
+        ),
+        Some(name) => (Some(goat_id_with_gender(&conn, name, "Female")?), None),
+        None => (None, None),
+    };
+    if sire_id == Some(goat_id) || dam_id == Some(goat_id) {
+        warn!(goat_id, "Goat listed as its own parent");
+        return Err(AppError::InvalidInput(
+            "A goat cannot be its own parent".into(),
+        ));
+    }
+
+    events.apply(
+        &conn,
+        &DomainEvent::PedigreeRecorded {
+            goat_id,
+            sire_id,
+            dam_id,
+            sire_external_id,
+            dam_external_id,
+            date_of_birth: pedigree.date_of_birth.clone(),
+        },
+    )?;
+    info!(
+        goat_id,
+        ?sire_id,
+        ?dam_id,
+        ?sire_external_id,
+        ?dam_external_id,
+        "Pedigree updated"
+    );
+    Ok(HttpResponse::Ok().body("Pedigree updated"))
+}
+
+/// Handler for a goat's pedigree tree: its ancestors up to
+/// `PEDIGREE_DEPTH` generations back, each with its genetic tags.
+///
+/// # HTTP Method
+/// - `GET /breeding/pedigree/{goat_name}`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON `PedigreeNode`; unknown parents are null.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown goat.
+pub async fn get_pedigree_tree(
+    db: web::Data<DbPool>,
+    path: web::Path<String>,
+) -> Result<impl Responder, AppError> {
+    let goat_name = path.into_inner();
+    debug!(goat_name, "GET /breeding/pedigree/{{goat_name}} called");
+    let conn = db.get_conn()?;
+    let tree = load_pedigree_tree(&conn, &goat_name)?
+        .ok_or_else(|| AppError::InvalidInput(format!("No goat found with name {}", goat_name)))?;
+
+    info!(goat_name, "Returning pedigree tree");
+    Ok(HttpResponse::Ok().json(tree))
+}
+
+/// Handler for listing goats' genetic tags.
+///
+/// # HTTP Method
+/// - `GET /breeding/tags?tag=High twinning`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `GeneticTags` by goat name,
+///   covering only goats with at least one tag (or with `tag`, if given).
+pub async fn get_genetic_tags(
+    db: web::Data<DbPool>,
+    query: web::Query<TagQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(tag = ?query.tag, "GET /breeding/tags called");
+    let conn = db.get_conn()?;
+    let tags = load_genetic_tags(&conn)?;
+    let mut stmt =
+        conn.prepare("SELECT id, name FROM goats WHERE deleted_at IS NULL ORDER BY name")?;
+    let goats: Vec<(i64, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let wanted = query.tag.as_deref().map(str::trim);
+    let tagged: Vec<GeneticTags> = goats
+        .into_iter()
+        .filter_map(|(id, goat_name)| {
+            let tags = tags.get(&id)?.clone();
+            wanted
+                .is_none_or(|w| tags.iter().any(|t| t.eq_ignore_ascii_case(w)))
+                .then_some(GeneticTags { goat_name, tags })
+        })
+        .collect();
+
+    info!("Returning genetic tags for {} goats", tagged.len());
+    Ok(HttpResponse::Ok().json(tagged))
+}
+
+/// Handler for setting a goat's genetic tags, replacing any it had.
+///
+/// # HTTP Method
+/// - `PUT /breeding/tags`
+///
+/// # Request
+/// - JSON `GeneticTags`. Tags are trimmed and duplicates differing only in
+///   case are dropped; an empty list clears the goat's tags.
+///
+/// # Success
+/// - Returns HTTP 200 with the stored `GeneticTags`.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown goat or a blank or overlong tag.
+pub async fn set_genetic_tags(
+    db: web::Data<DbPool>,
+    tags: web::Json<GeneticTags>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat = %tags.goat_name, "PUT /breeding/tags called");
+    let normalized = normalize_tags(&tags.tags).map_err(AppError::InvalidInput)?;
+
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+    let goat_id: i64 = tx
+        .query_row(
+            "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [&tags.goat_name],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!("No goat found with name {}", tags.goat_name))
+        })?;
+    tx.execute("DELETE FROM genetic_tags WHERE goat_id = ?1", [goat_id])?;
+    for tag in &normalized {
+        tx.execute(
+            "INSERT INTO genetic_tags (goat_id, tag) VALUES (?1, ?2)",
+            params![goat_id, tag],
+        )?;
+    }
+    tx.commit()?;
+
+    info!(goat_id, count = normalized.len(), "Genetic tags updated");
+    Ok(HttpResponse::Ok().json(GeneticTags {
+        goat_name: tags.goat_name.clone(),
+        tags: normalized,
+    }))
+}
+
+/// Handler for listing neutered goats by name.
+///
+/// # HTTP Method
+/// - `GET /breeding/neuterings`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `Neutering`.
+pub async fn get_neuterings(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /breeding/neuterings called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(
+        "SELECT name, neutered_on, neutered_reason FROM goats \
+         WHERE neutered_on IS NOT NULL AND deleted_at IS NULL ORDER BY name",
+    )?;
+    let neuterings: Vec<Neutering> = stmt
+        .query_map([], |row| {
+            Ok(Neutering {
+                goat_name: row.get(0)?,
+                neutered_on: row.get(1)?,
+                reason: row.get(2)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    info!("Returning {} neuterings", neuterings.len());
+    Ok(HttpResponse::Ok().json(neuterings))
+}
+
+/// Handler for recording that a goat was castrated or spayed. A doe's
+/// last-bred date is kept as history.
+///
+/// # HTTP Method
+/// - `PUT /breeding/neuterings`
+///
+/// # Request
+/// - JSON `Neutering`. A blank reason is stored as none.
+///
+/// # Success
+/// - Returns HTTP 200 once the neutering is stored.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown goat or a malformed date.
+pub async fn record_neutering(
+    db: web::Data<DbPool>,
+    events: EventLog,
+    neutering: web::Json<Neutering>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat = %neutering.goat_name, "PUT /breeding/neuterings called");
+    validate_date(&neutering.neutered_on, "neutered_on")?;
+    let reason = neutering
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|r| !r.is_empty());
+
+    let conn = db.get_conn()?;
+    let goat_id: i64 = conn
+        .query_row(
+            "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [&neutering.goat_name],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!("No goat found with name {}", neutering.goat_name))
+        })?;
+    events.apply(
+        &conn,
+        &DomainEvent::GoatNeutered {
+            goat_id,
+            neutered_on: neutering.neutered_on.clone(),
+            reason: reason.map(str::to_string),
+        },
+    )?;
+    info!(goat = %neutering.goat_name, "Neutering recorded");
+    Ok(HttpResponse::Ok().body("Neutering recorded"))
+}
+
+/// Handler for clearing a neutering recorded by mistake.
+///
+/// # HTTP Method
+/// - `DELETE /breeding/neuterings/{goat_name}`
+///
+/// # Errors
+/// - Returns HTTP 400 if the goat is unknown or not neutered.
+pub async fn delete_neutering(
+    db: web::Data<DbPool>,
+    events: EventLog,
+    path: web::Path<String>,
+) -> Result<impl Responder, AppError> {
+    let goat_name = path.into_inner();
+    debug!(
+        goat_name,
+        "DELETE /breeding/neuterings/{{goat_name}} called"
+    );
+    let conn = db.get_conn()?;
+    let Some(goat_id) = conn
+        .query_row(
+            "SELECT id FROM goats \
+             WHERE name = ?1 AND neutered_on IS NOT NULL AND deleted_at IS NULL",
+            [&goat_name],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()?
+    else {
+        warn!(goat_name, "Goat not neutered");
+        return Err(AppError::InvalidInput(format!(
+            "{} has no neutering recorded",
+            goat_name
+        )));
+    };
+    events.apply(&conn, &DomainEvent::NeuteringCleared { goat_id })?;
+    info!(goat_name, "Neutering cleared");
+    Ok(HttpResponse::Ok().body("Neutering cleared"))
+}
+
+/// Handler for listing kidding records, newest first.
+///
+/// # HTTP Method
+/// - `GET /breeding/kiddings?doe_name=Rani`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of kidding records.
+pub async fn get_kiddings(
+    db: web::Data<DbPool>,
+    query: web::Query<KiddingQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(doe_name = ?query.doe_name, "GET /breeding/kiddings called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(
+        "SELECT k.id, d.name, COALESCE(b.name, x.name), x.id IS NOT NULL, k.kidded_on, \
+             k.kids_born, k.kids_alive, k.notes \
+         FROM kidding_records k \
+         JOIN goats d ON d.id = k.doe_id AND d.deleted_at IS NULL \
+         LEFT JOIN goats b ON b.id = k.buck_id \
+         LEFT JOIN external_animals x ON x.id = k.external_buck_id \
+         WHERE (?1 IS NULL OR d.name = ?1) \
+         ORDER BY k.kidded_on DESC, k.id DESC",
+    )?;
+    let records: Vec<KiddingRecord> = stmt
+        .query_map([&query.doe_name], |row| {
+            Ok(KiddingRecord {
+                id: row.get(0)?,
+                doe_name: row.get(1)?,
+                buck_name: row.get(2)?,
+                buck_external: row.get(3)?,
+                kidded_on: row.get(4)?,
+                kids_born: row.get(5)?,
+                kids_alive: row.get(6)?,
+                notes: row.get(7)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    info!("Returning {} kidding records", records.len());
+    Ok(HttpResponse::Ok().json(records))
+}
+
+/// Handler for recording a kidding outcome.
+///
+/// # HTTP Method
+/// - `POST /breeding/kiddings`
+///
+/// # Success
+/// - Returns HTTP 201 on successful insertion.
+///
+/// # Errors
+/// - Returns HTTP 400 if more kids survived than were born, the doe or buck
+///   (a herd goat, or an external animal if flagged so) is unknown or of the
+///   wrong gender, or the date is malformed.
+pub async fn add_kidding(
+    db: web::Data<DbPool>,
+    record: web::Json<KiddingRecord>,
+) -> Result<impl Responder, AppError> {
+    debug!(doe = %record.doe_name, "POST /breeding/kiddings called");
+    if record.kids_alive > record.kids_born {
+        return Err(AppError::InvalidInput(
+            "kids_alive cannot exceed kids_born".into(),
+        ));
+    }
+    validate_date(&record.kidded_on, "kidded_on")?;
+
+    let conn = db.get_conn()?;
+    let doe_id = goat_id_with_gender(&conn, &record.doe_name, "Female")?;
+    let (buck_id, external_buck_id) = match &record.buck_name {
+        Some(name) if record.buck_external => (
+            None,
+            Some(external_id_with_gender(&conn, name, Gender::Male)?),
+        ),
+        Some(name) => (Some(goat_id_with_gender(&conn, name, "Male")?), None),
+        None => (None, None),
+    };
+
+    conn.execute(
+        "INSERT INTO kidding_records (doe_id, buck_id, external_buck_id, kidded_on, kids_born, \
+             kids_alive, notes) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            doe_id,
+            buck_id,
+            external_buck_id,
+            record.kidded_on,
+            record.kids_born,
+            record.kids_alive,
+            record.notes,
+        ],
+    )?;
+    info!(
+        kidding_id = conn.last_insert_rowid(),
+        doe_id, "Kidding recorded"
+    );
+    Ok(HttpResponse::Created().body("Kidding added"))
+}
+
+/// Handler for listing services, newest first.
+///
+/// # HTTP Method
+/// - `GET /breeding/services?doe_name=Rani`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `BreedingService`, each with its
+///   outcome as of today.
+pub async fn get_services(
+    db: web::Data<DbPool>,
+    query: web::Query<ServiceQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(doe_name = ?query.doe_name, "GET /breeding/services called");
+    let conn = db.get_conn()?;
+    let services = load_services(&conn, query.doe_name.as_deref(), farm_today(&conn)?)?;
+
+    info!("Returning {} services", services.len());
+    Ok(HttpResponse::Ok().json(services))
+}
+
+/// Handler for recording a service, natural or by AI. The doe's last-bred
+/// date moves up to the service date. An outside sire named in the external
+/// registry is linked to it, with its registry details filling any left
+/// blank.
+///
+/// # HTTP Method
+/// - `POST /breeding/services`
+///
+/// # Request
+/// - JSON `BreedingService`; `id` and `outcome` are ignored, and blank
+///   optional fields are stored as none.
+///
+/// # Success
+/// - Returns HTTP 201 on successful insertion.
+///
+/// # Errors
+/// - Returns HTTP 400 for a service without exactly one sire, an AI service
+///   without a straw ID, a natural service with AI details, an unknown doe
+///   or buck or one of the wrong gender (including a registry animal named
+///   as outside sire that is not male), or a malformed date.
+pub async fn add_service(
+    db: web::Data<DbPool>,
+    events: EventLog,
+    service: web::Json<BreedingService>,
+) -> Result<impl Responder, AppError> {
+    debug!(doe = %service.doe_name, method = ?service.method, "POST /breeding/services called");
+    service.validate().map_err(AppError::InvalidInput)?;
+    validate_date(&service.served_on, "served_on")?;
+    let text = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    let mut sire = service.outside_sire.clone().map(|s| OutsideSire {
+        name: s.name.trim().to_string(),
+        breed: text(&s.breed),
+        registration: text(&s.registration),
+        source: text(&s.source),
+    });
+
+    let conn = db.get_conn()?;
+    let doe_id = goat_id_with_gender(&conn, &service.doe_name, "Female")?;
+    let buck_id = match &service.buck_name {
+        Some(name) => Some(goat_id_with_gender(&conn, name, "Male")?),
+        None => None,
+    };
+    let external_sire_id = match sire.as_mut() {
+        Some(sire) => match find_external(&conn, &sire.name)? {
+            Some(animal) => {
+                let id = external_id_with_gender(&conn, &animal.name, Gender::Male)?;
+                sire.breed = sire.breed.take().or(animal.breed);
+                sire.registration = sire.registration.take().or(animal.registration);
+                sire.source = sire.source.take().or(animal.owner);
+                Some(id)
+            }
+            None => None,
+        },
+        None => None,
+    };
+    conn.execute(
+        "INSERT INTO breeding_services (doe_id, served_on, method, buck_id, external_sire_id, \
+             outside_sire_name, outside_sire_breed, outside_sire_registration, \
+             outside_sire_source, straw_id, technician, notes) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            doe_id,
+            service.served_on,
+            ServiceMethod::to_str(&service.method),
+            buck_id,
+            external_sire_id,
+            sire.as_ref().map(|s| &s.name),
+            sire.as_ref().and_then(|s| s.breed.clone()),
+            sire.as_ref().and_then(|s| s.registration.clone()),
+            sire.as_ref().and_then(|s| s.source.clone()),
+            text(&service.straw_id),
+            text(&service.technician),
+            text(&service.notes),
+        ],
+    )?;
+    let service_id = conn.last_insert_rowid();
+    let bred: bool = conn.query_row(
+        "SELECT last_bred IS NULL OR last_bred < ?1 FROM goats WHERE id = ?2",
+        params![service.served_on, doe_id],
+        |row| row.get(0),
+    )?;
+    if bred {
+        let update = GoatUpdate {
+            last_bred: Some(Some(service.served_on.clone())),
+            ..GoatUpdate::default()
+        };
+        events.apply(
+            &conn,
+            &DomainEvent::GoatUpdated {
+                goat_id: doe_id,
+                update,
+            },
+        )?;
+    }
+
+    info!(service_id, doe_id, "Service recorded");
+    Ok(HttpResponse::Created().body("Service added"))
+}
+
+/// Handler for comparing how often natural service and AI end in a kidding.
+///
+/// # HTTP Method
+/// - `GET /breeding/services/success`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `MethodSuccess`, natural service
+///   first, counting every service's outcome as of today.
+pub async fn get_service_success(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /breeding/services/success called");
+    let conn = db.get_conn()?;
+    let services = load_services(&conn, None, farm_today(&conn)?)?;
+    let rates = success_rates(&services);
+
+    info!("Returning success rates over {} services", services.len());
+    Ok(HttpResponse::Ok().json(rates))
+}
+
+/// Handler for suggesting buck–doe pairings.
+///
+/// # HTTP Method
+/// - `GET /breeding/recommendations?doe=Rani&limit=10`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `BreedingRecommendation`, best first.
+///   Close-relative matings are included but flagged and scored down.
+pub async fn get_recommendations(
+    db: web::Data<DbPool>,
+    query: web::Query<RecommendationQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(doe = ?query.doe, "GET /breeding/recommendations called");
+    let conn = db.get_conn()?;
+    let candidates = load_candidates(&conn)?;
+    let history = load_kidding_history(&conn)?;
+
+    let mut recommendations = recommend(
+        &candidates,
+        &history,
+        farm_today(&conn)?,
+        query.doe.as_deref(),
+    );
+    recommendations.truncate(query.limit.unwrap_or(DEFAULT_RECOMMENDATION_LIMIT));
+
+    info!(
+        "Returning {} breeding recommendations",
+        recommendations.len()
+    );
+    Ok(HttpResponse::Ok().json(recommendations))
+}
+
+/// Handler for heat status and predicted next heat of each tagged doe.
+///
+/// # HTTP Method
+/// - `GET /breeding/heat`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `HeatPrediction`, does in heat
+///   first, then by next heat. Only does wearing an activity tag and not
+///   presumed pregnant are included.
+pub async fn get_heat_predictions(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /breeding/heat called");
+    let conn = db.get_conn()?;
+    let predictions = load_predictions(&conn, farm_today(&conn)?)?;
+
+    let in_heat = predictions.iter().filter(|p| p.in_heat).count();
+    info!(in_heat, "Returning {} heat predictions", predictions.len());
+    Ok(HttpResponse::Ok().json(predictions))
+}
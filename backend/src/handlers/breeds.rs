@@ -0,0 +1,155 @@
+//! This module serves the breed catalog: the built-in breeds plus those
+//! added for the farm, each with its purpose (see `shared::breeds`).
+//!
+//! Adding or removing a breed changes the list every form offers, so it
+//! takes the owner or a worker whose role may edit settings.
+
+use crate::db::DbPool;
+use crate::errors::{AppError, ParseEnumError};
+use crate::handlers::sessions::requester;
+use crate::permissions::worker_permissions;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use rusqlite::{Connection, OptionalExtension, params};
+use shared::breeds::{BreedPurpose, CatalogBreed, builtin_catalog, validate_catalog_name};
+use shared::permissions::{PermissionAction, PermissionModule};
+use tracing::{debug, info, warn};
+
+/// Loads the built-in breeds followed by the added ones, by name.
+pub fn load_catalog(conn: &Connection) -> Result<Vec<CatalogBreed>, AppError> {
+    let mut stmt = conn.prepare("SELECT name, purpose FROM breed_catalog ORDER BY name")?;
+    let added: Vec<CatalogBreed> = stmt
+        .query_map([], |row| {
+            let purpose: String = row.get(1)?;
+            Ok(CatalogBreed {
+                name: row.get(0)?,
+                purpose: BreedPurpose::from_str(&purpose).map_err(|e| {
+                    rusqlite::Error::ToSqlConversionFailure(Box::new(AppError::ParseError(
+                        ParseEnumError::new(&e, "BreedPurpose"),
+                    )))
+                })?,
+                builtin: false,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let mut catalog = builtin_catalog();
+    catalog.extend(added);
+    Ok(catalog)
+}
+
+/// Refuses `req` unless it was made by the owner or by a worker whose role
+/// may edit settings.
+fn require_catalog_editor(conn: &Connection, req: &HttpRequest) -> Result<(), AppError> {
+    let permissions = worker_permissions(conn, requester(conn, req)?)?;
+    if !permissions.allows(PermissionModule::Settings, PermissionAction::Edit) {
+        warn!(role = ?permissions.role, "Refused breed catalog change");
+        return Err(AppError::Forbidden(
+            "Changing the breed catalog needs permission to edit settings".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Handler for listing the breed catalog.
+///
+/// # HTTP Method
+/// - `GET /breeds`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `CatalogBreed`: the built-in
+///   breeds first, then the added ones by name.
+pub async fn get_breeds(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /breeds called");
+    let conn = db.get_conn()?;
+    let catalog = load_catalog(&conn)?;
+
+    info!("Returning {} catalog breeds", catalog.len());
+    Ok(HttpResponse::Ok().json(catalog))
+}
+
+/// Handler for adding a breed to the catalog.
+///
+/// # HTTP Method
+/// - `POST /breeds`
+///
+/// # Request
+/// - JSON `CatalogBreed`; `builtin` is ignored.
+///
+/// # Success
+/// - Returns HTTP 201 with the stored `CatalogBreed`.
+///
+/// # Errors
+/// - Returns HTTP 401 without a session or access token.
+/// - Returns HTTP 403 to a worker whose role may not edit settings.
+/// - Returns HTTP 400 for a blank name, "Other", a built-in breed, or a
+///   breed already in the catalog.
+pub async fn add_breed(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    breed: web::Json<CatalogBreed>,
+) -> Result<impl Responder, AppError> {
+    debug!(name = %breed.name, purpose = ?breed.purpose, "POST /breeds called");
+    let conn = db.get_conn()?;
+    require_catalog_editor(&conn, &req)?;
+    let name = validate_catalog_name(&breed.name).map_err(AppError::InvalidInput)?;
+
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT name FROM breed_catalog WHERE name = ?1",
+            [&name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(existing) = existing {
+        warn!(name, "Breed already in catalog");
+        return Err(AppError::InvalidInput(format!(
+            "{} is already in the catalog",
+            existing
+        )));
+    }
+    conn.execute(
+        "INSERT INTO breed_catalog (name, purpose) VALUES (?1, ?2)",
+        params![name, BreedPurpose::to_str(&breed.purpose)],
+    )?;
+
+    info!(name, purpose = ?breed.purpose, "Breed added to catalog");
+    Ok(HttpResponse::Created().json(CatalogBreed {
+        name,
+        purpose: breed.purpose,
+        builtin: false,
+    }))
+}
+
+/// Handler for removing an added breed from the catalog. Goats of that
+/// breed keep it; it is just no longer offered.
+///
+/// # HTTP Method
+/// - `DELETE /breeds/{name}`
+///
+/// # Errors
+/// - Returns HTTP 401 without a session or access token.
+/// - Returns HTTP 403 to a worker whose role may not edit settings.
+/// - Returns HTTP 400 if no added breed has that name; built-in breeds
+///   cannot be removed.
+pub async fn delete_breed(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    path: web::Path<String>,
+) -> Result<impl Responder, AppError> {
+    let name = path.into_inner();
+    debug!(name, "DELETE /breeds/{{name}} called");
+    let conn = db.get_conn()?;
+    require_catalog_editor(&conn, &req)?;
+
+    let affected = conn.execute("DELETE FROM breed_catalog WHERE name = ?1", [&name])?;
+    if affected == 0 {
+        warn!(name, "Breed not in catalog");
+        return Err(AppError::InvalidInput(format!(
+            "{} is not an added breed",
+            name
+        )));
+    }
+
+    info!(name, "Breed removed from catalog");
+    Ok(HttpResponse::Ok().body("Breed removed"))
+}
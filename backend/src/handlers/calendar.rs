@@ -0,0 +1,55 @@
+//! This module serves the iCalendar feed of tasks, reminders and vet visits
+//! built by `crate::calendar`.
+
+use crate::auth::{authenticate, verify_key};
+use crate::calendar::{load_events, to_ics};
+use crate::db::DbPool;
+use crate::errors::AppError;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use chrono::Utc;
+use serde::Deserialize;
+use tracing::{debug, info};
+
+/// Query parameters accepted by `GET /calendar/feed.ics`.
+#[derive(Deserialize)]
+pub struct FeedQuery {
+    /// API key, for calendar apps that cannot send headers.
+    pub key: Option<String>,
+}
+
+/// Handler for the subscribable calendar feed.
+///
+/// # HTTP Method
+/// - `GET /calendar/feed.ics?key=yagi_...`
+///
+/// # Request
+/// - An API key (see `POST /api-keys`) in the `key` query parameter, so the
+///   URL can be pasted into Google Calendar or a phone's calendar app, or in
+///   an `X-Api-Key` header or bearer token.
+///
+/// # Success
+/// - Returns HTTP 200 with a `text/calendar` document holding an all-day
+///   event per pending task, undismissed reminder and scheduled vet visit.
+///
+/// # Errors
+/// - Returns HTTP 401 for a missing, unknown or revoked API key.
+pub async fn get_feed(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    query: web::Query<FeedQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!("GET /calendar/feed.ics called");
+    let conn = db.get_conn()?;
+    let api_key_id = match &query.key {
+        Some(key) => verify_key(&conn, key)?,
+        None => authenticate(&conn, &req)?,
+    };
+    let events = load_events(&conn)?;
+
+    info!(api_key_id, events = events.len(), "Returning calendar feed");
+    Ok(HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .insert_header((header::CONTENT_DISPOSITION, "inline; filename=\"yagi.ics\""))
+        .body(to_ics(&events, Utc::now())))
+}
@@ -0,0 +1,83 @@
+//! This module receives crash reports from the frontend and lists them for
+//! troubleshooting.
+//!
+//! Reports may arrive via `navigator.sendBeacon`, which cannot set a JSON
+//! content type, so the body is parsed manually instead of using `web::Json`.
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use actix_web::{HttpResponse, Responder, web};
+use rusqlite::params;
+use shared::diagnostics::ClientErrorReport;
+use tracing::{debug, error, info};
+
+/// Longest stored message; anything beyond is truncated.
+const MAX_MESSAGE_LEN: usize = 4000;
+
+/// Number of reports returned by `GET /client-errors`.
+const RECENT_REPORTS_LIMIT: i64 = 100;
+
+/// Handler for storing a client crash report.
+///
+/// # HTTP Method
+/// - `POST /client-errors`
+///
+/// # Request
+/// - JSON `ClientErrorReport`, with any content type.
+///
+/// # Success
+/// - Returns HTTP 201 once the report is stored.
+///
+/// # Errors
+/// - Returns HTTP 400 if the body is not a valid report.
+pub async fn report_client_error(
+    db: web::Data<DbPool>,
+    body: web::Bytes,
+) -> Result<impl Responder, AppError> {
+    debug!(len = body.len(), "POST /client-errors called");
+    let report: ClientErrorReport = serde_json::from_slice(&body)
+        .map_err(|e| AppError::InvalidInput(format!("Malformed crash report: {}", e)))?;
+    let message: String = report.message.chars().take(MAX_MESSAGE_LEN).collect();
+
+    error!(section = ?report.section, url = ?report.url, "Client reported error: {}", message);
+
+    let conn = db.get_conn()?;
+    conn.execute(
+        "INSERT INTO client_errors (section, message, url, user_agent) VALUES (?1, ?2, ?3, ?4)",
+        params![report.section, message, report.url, report.user_agent],
+    )?;
+
+    info!(report_id = conn.last_insert_rowid(), "Stored client error report");
+    Ok(HttpResponse::Created().body("Report received"))
+}
+
+/// Handler for listing the most recent client crash reports.
+///
+/// # HTTP Method
+/// - `GET /client-errors`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of reports, newest first.
+pub async fn get_client_errors(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /client-errors called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, section, message, url, user_agent, created_at FROM client_errors \
+         ORDER BY id DESC LIMIT ?1",
+    )?;
+    let reports: Vec<ClientErrorReport> = stmt
+        .query_map([RECENT_REPORTS_LIMIT], |row| {
+            Ok(ClientErrorReport {
+                id: row.get(0)?,
+                section: row.get(1)?,
+                message: row.get(2)?,
+                url: row.get(3)?,
+                user_agent: row.get(4)?,
+                reported_at: row.get(5)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    info!("Returning {} client error reports", reports.len());
+    Ok(HttpResponse::Ok().json(reports))
+}
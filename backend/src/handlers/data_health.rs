@@ -0,0 +1,193 @@
+//! This module scans the herd records for data quality problems: missing
+//! dates of birth, zero weights, prices far from the breed's market price,
+//! duplicate tag IDs and vaccinations without a date (see
+//! `shared::data_health`).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use actix_web::{HttpResponse, Responder, web};
+use rusqlite::Connection;
+use shared::data_health::{
+    DataHealthReport, DataIssue, IssueKind, MIN_PRICE_PEERS, median, price_off_market,
+};
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+/// The columns of a goat the checks look at.
+struct GoatRow {
+    id: i64,
+    name: String,
+    breed: String,
+    weight: Option<f64>,
+    price: Option<f64>,
+    date_of_birth: Option<String>,
+    tag_id: Option<String>,
+}
+
+fn issue(kind: IssueKind, goat: &GoatRow, message: String) -> DataIssue {
+    DataIssue {
+        kind,
+        goat_id: goat.id,
+        goat_name: goat.name.clone(),
+        message,
+    }
+}
+
+/// Flags goats priced far from the median price of their breed.
+fn price_issues(goats: &[GoatRow]) -> Vec<DataIssue> {
+    let mut prices: HashMap<&str, Vec<f64>> = HashMap::new();
+    for goat in goats {
+        if let Some(price) = goat.price.filter(|p| *p > 0.0) {
+            prices.entry(&goat.breed).or_default().push(price);
+        }
+    }
+    let markets: HashMap<&str, f64> = prices
+        .into_iter()
+        .filter(|(_, prices)| prices.len() >= MIN_PRICE_PEERS)
+        .filter_map(|(breed, mut prices)| Some((breed, median(&mut prices)?)))
+        .collect();
+    goats
+        .iter()
+        .filter_map(|goat| {
+            let market = *markets.get(goat.breed.as_str())?;
+            let price = goat.price.filter(|p| *p > 0.0)?;
+            price_off_market(price, market).then(|| {
+                issue(
+                    IssueKind::PriceOffMarket,
+                    goat,
+                    format!(
+                        "Price {:.2} is far from the {} median of {:.2}",
+                        price, goat.breed, market
+                    ),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Flags goats sharing a tag ID once case and surrounding spaces are
+/// ignored (the database only rejects exact duplicates).
+fn duplicate_tag_issues(goats: &[GoatRow]) -> Vec<DataIssue> {
+    let mut by_tag: HashMap<String, Vec<&GoatRow>> = HashMap::new();
+    for goat in goats {
+        if let Some(tag) = goat
+            .tag_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+        {
+            by_tag.entry(tag.to_uppercase()).or_default().push(goat);
+        }
+    }
+    let mut issues = Vec::new();
+    for sharing in by_tag.values().filter(|goats| goats.len() > 1) {
+        for goat in sharing {
+            let others: Vec<&str> = sharing
+                .iter()
+                .filter(|other| other.id != goat.id)
+                .map(|other| other.name.as_str())
+                .collect();
+            issues.push(issue(
+                IssueKind::DuplicateTag,
+                goat,
+                format!(
+                    "Tag '{}' is also used by {}",
+                    goat.tag_id.as_deref().unwrap_or_default(),
+                    others.join(", ")
+                ),
+            ));
+        }
+    }
+    issues
+}
+
+/// Runs every check over the herd.
+pub fn check_data_health(conn: &Connection) -> Result<DataHealthReport, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, breed, weight, current_price, date_of_birth, tag_id FROM goats \
+         WHERE deleted_at IS NULL",
+    )?;
+    let goats: Vec<GoatRow> = stmt
+        .query_map([], |row| {
+            Ok(GoatRow {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                breed: row.get(2)?,
+                weight: row.get(3)?,
+                price: row.get(4)?,
+                date_of_birth: row.get(5)?,
+                tag_id: row.get(6)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let mut issues = Vec::new();
+    for goat in &goats {
+        if goat.date_of_birth.is_none() {
+            issues.push(issue(
+                IssueKind::MissingBirthDate,
+                goat,
+                "No date of birth recorded".to_string(),
+            ));
+        }
+        if goat.weight.is_none_or(|w| w <= 0.0) {
+            issues.push(issue(
+                IssueKind::ZeroWeight,
+                goat,
+                "Weight is 0 kg".to_string(),
+            ));
+        }
+    }
+    issues.extend(price_issues(&goats));
+    issues.extend(duplicate_tag_issues(&goats));
+
+    let mut stmt = conn.prepare(
+        "SELECT g.id, g.name, v.name FROM goat_vaccines gv \
+         JOIN goats g ON g.id = gv.goat_id AND g.deleted_at IS NULL \
+         JOIN vaccines v ON v.id = gv.vaccine_id \
+         WHERE gv.given_on IS NULL \
+         ORDER BY v.name",
+    )?;
+    let undated = stmt
+        .query_map([], |row| {
+            Ok(DataIssue {
+                kind: IssueKind::UndatedVaccination,
+                goat_id: row.get(0)?,
+                goat_name: row.get(1)?,
+                message: format!("{} vaccination has no date", row.get::<_, String>(2)?),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    issues.extend(undated);
+
+    // Stable, so vaccinations of one goat stay in vaccine order
+    issues.sort_by_key(|i| {
+        let rank = IssueKind::ALL.iter().position(|k| *k == i.kind);
+        (rank, i.goat_name.clone())
+    });
+    Ok(DataHealthReport {
+        goats_checked: goats.len(),
+        issues,
+    })
+}
+
+/// Handler for the data quality report.
+///
+/// # HTTP Method
+/// - `GET /data-health`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON `DataHealthReport` listing every problem
+///   found, grouped by kind and then by goat name.
+pub async fn get_data_health(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /data-health called");
+    let conn = db.get_conn()?;
+    let report = check_data_health(&conn)?;
+
+    info!(
+        goats = report.goats_checked,
+        issues = report.issues.len(),
+        "Returning data health report"
+    );
+    Ok(HttpResponse::Ok().json(report))
+}
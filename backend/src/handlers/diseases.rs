@@ -0,0 +1,362 @@
+//! This module handles the disease catalog, which diseases in it are
+//! notifiable, and the compliance report of notifiable cases (see
+//! `shared::diseases`).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::lab_results::{LabResultsQuery, load_lab_results};
+use crate::handlers::settings::farm_today;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::http::header;
+use actix_web::{HttpResponse, Responder, web};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Deserialize;
+use shared::diseases::{
+    CaseStatus, CatalogDisease, ComplianceCase, ComplianceReport, DiseaseTest, NewDisease,
+    NotifiableFlag, names_disease,
+};
+use std::collections::{BTreeMap, HashMap};
+use tracing::{debug, info, warn};
+
+/// Output format of `GET /diseases/compliance`.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ComplianceFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// Query parameters accepted by `GET /diseases/compliance`.
+#[derive(Deserialize)]
+pub struct ComplianceQuery {
+    #[serde(default)]
+    pub format: ComplianceFormat,
+}
+
+/// Columns read by `row_to_disease`.
+const DISEASE_COLUMNS: &str = "d.id, d.name, d.notifiable, \
+     (SELECT COUNT(*) FROM goat_diseases gd WHERE gd.disease_id = d.id)";
+
+/// Maps a `DISEASE_COLUMNS` row to a `CatalogDisease`.
+fn row_to_disease(row: &Row) -> rusqlite::Result<CatalogDisease> {
+    Ok(CatalogDisease {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        notifiable: row.get(2)?,
+        goat_count: row.get(3)?,
+    })
+}
+
+/// Loads the catalogued disease with ID `id`.
+fn load_disease(conn: &Connection, id: i64) -> Result<CatalogDisease, AppError> {
+    conn.query_row(
+        &format!("SELECT {} FROM diseases d WHERE d.id = ?1", DISEASE_COLUMNS),
+        [id],
+        row_to_disease,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::InvalidInput(format!("No disease found with ID {}", id)))
+}
+
+/// Handler for listing the disease catalog by name.
+///
+/// # HTTP Method
+/// - `GET /diseases`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `CatalogDisease`.
+pub async fn get_diseases(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /diseases called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM diseases d ORDER BY d.name COLLATE NOCASE",
+        DISEASE_COLUMNS
+    ))?;
+    let diseases = stmt
+        .query_map([], row_to_disease)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    info!("Returning {} catalogued diseases", diseases.len());
+    Ok(HttpResponse::Ok().json(diseases))
+}
+
+/// Handler for adding a disease to the catalog.
+///
+/// # HTTP Method
+/// - `POST /diseases`
+///
+/// # Request
+/// - JSON `NewDisease`.
+///
+/// # Success
+/// - Returns HTTP 201 with the stored `CatalogDisease`.
+///
+/// # Errors
+/// - Returns HTTP 400 if the name is empty or already catalogued, ignoring
+///   case.
+pub async fn add_disease(
+    db: web::Data<DbPool>,
+    payload: web::Json<NewDisease>,
+) -> Result<impl Responder, AppError> {
+    let name = payload.name.trim();
+    debug!(
+        name,
+        notifiable = payload.notifiable,
+        "POST /diseases called"
+    );
+    if name.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Disease name must not be empty".into(),
+        ));
+    }
+    let conn = db.get_conn()?;
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT name FROM diseases WHERE lower(name) = lower(?1)",
+            [name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(existing) = existing {
+        return Err(AppError::InvalidInput(format!(
+            "{} is already in the catalog",
+            existing
+        )));
+    }
+    conn.execute(
+        "INSERT INTO diseases (name, notifiable) VALUES (?1, ?2)",
+        params![name, payload.notifiable],
+    )?;
+    let stored = load_disease(&conn, conn.last_insert_rowid())?;
+
+    info!(disease_id = stored.id, "Disease catalogued");
+    Ok(HttpResponse::Created().json(stored))
+}
+
+/// Handler for flagging a catalogued disease notifiable, or clearing the
+/// flag.
+///
+/// # HTTP Method
+/// - `PUT /diseases/{id}/notifiable`
+///
+/// # Request
+/// - JSON `NotifiableFlag`.
+///
+/// # Success
+/// - Returns HTTP 200 with the updated `CatalogDisease`.
+///
+/// # Errors
+/// - Returns HTTP 400 if the disease does not exist.
+pub async fn set_notifiable(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+    payload: web::Json<NotifiableFlag>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(
+        disease_id = id,
+        notifiable = payload.notifiable,
+        "PUT /diseases/{{id}}/notifiable called"
+    );
+    let conn = db.get_conn()?;
+    let affected = conn.execute(
+        "UPDATE diseases SET notifiable = ?1 WHERE id = ?2",
+        params![payload.notifiable, id],
+    )?;
+    if affected == 0 {
+        warn!(disease_id = id, "Disease not found for flagging");
+        return Err(AppError::InvalidInput(format!(
+            "No disease found with ID {}",
+            id
+        )));
+    }
+    let updated = load_disease(&conn, id)?;
+
+    info!(
+        disease_id = id,
+        notifiable = updated.notifiable,
+        "Disease flag updated"
+    );
+    Ok(HttpResponse::Ok().json(updated))
+}
+
+/// The case of `goat` in `found`, added with `status` if missing and
+/// otherwise raised to `status` if that is more serious.
+fn case_entry<'a>(
+    found: &'a mut BTreeMap<String, ComplianceCase>,
+    tags: &HashMap<String, Option<String>>,
+    disease: &str,
+    goat: &str,
+    status: CaseStatus,
+) -> &'a mut ComplianceCase {
+    let case = found
+        .entry(goat.to_string())
+        .or_insert_with(|| ComplianceCase {
+            disease: disease.to_string(),
+            goat_name: goat.to_string(),
+            tag_id: tags.get(goat).cloned().flatten(),
+            status,
+            first_reported: None,
+            tests: Vec::new(),
+        });
+    case.status = case.status.min(status);
+    case
+}
+
+/// Builds the compliance report of every notifiable disease.
+///
+/// A goat is listed under a disease when the disease is on its record,
+/// when a health incident's condition names it, or when a lab result's
+/// test type names it (see `names_disease`). Cases are ordered by disease,
+/// then status, then goat.
+pub fn compliance_report(conn: &Connection) -> Result<ComplianceReport, AppError> {
+    let mut stmt =
+        conn.prepare("SELECT id, name FROM diseases WHERE notifiable = 1 ORDER BY name")?;
+    let diseases = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare("SELECT name, tag_id FROM goats WHERE deleted_at IS NULL")?;
+    let tags = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+        })?
+        .collect::<Result<HashMap<_, _>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT gd.disease_id, g.name FROM goat_diseases gd JOIN goats g ON g.id = gd.goat_id AND g.deleted_at IS NULL",
+    )?;
+    let on_record = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT g.name, h.condition, h.observed_on FROM health_incidents h \
+         JOIN goats g ON g.id = h.goat_id AND g.deleted_at IS NULL ORDER BY h.observed_on",
+    )?;
+    let incidents = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut lab_results = load_lab_results(conn, &LabResultsQuery::default())?;
+    lab_results.reverse();
+
+    let mut cases = Vec::new();
+    for (disease_id, disease) in &diseases {
+        // Keyed by goat, so each goat has one case per disease
+        let mut found: BTreeMap<String, ComplianceCase> = BTreeMap::new();
+        for (_, goat) in on_record.iter().filter(|(id, _)| id == disease_id) {
+            case_entry(&mut found, &tags, disease, goat, CaseStatus::Confirmed);
+        }
+        for (goat, condition, observed_on) in &incidents {
+            if names_disease(condition, disease) {
+                case_entry(&mut found, &tags, disease, goat, CaseStatus::Suspected)
+                    .first_reported
+                    .get_or_insert_with(|| observed_on.clone());
+            }
+        }
+        for result in &lab_results {
+            if names_disease(&result.test_type, disease) {
+                let out_of_range = !result.flagged().is_empty();
+                let status = if out_of_range {
+                    CaseStatus::Suspected
+                } else {
+                    CaseStatus::Tested
+                };
+                case_entry(&mut found, &tags, disease, &result.goat_name, status)
+                    .tests
+                    .push(DiseaseTest {
+                        sampled_on: result.sampled_on.clone(),
+                        test_type: result.test_type.clone(),
+                        out_of_range,
+                    });
+            }
+        }
+        let mut disease_cases: Vec<ComplianceCase> = found.into_values().collect();
+        disease_cases.sort_by_key(|c| c.status);
+        cases.extend(disease_cases);
+    }
+
+    Ok(ComplianceReport {
+        generated_on: farm_today(conn)?.format(DATE_FORMAT).to_string(),
+        diseases: diseases.into_iter().map(|(_, name)| name).collect(),
+        cases,
+    })
+}
+
+/// Renders `report` as CSV, one row per case.
+pub fn compliance_csv(report: &ComplianceReport) -> Result<Vec<u8>, AppError> {
+    let csv_error = |e: csv::Error| AppError::InvalidInput(format!("Cannot write CSV: {}", e));
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record([
+            "Disease",
+            "Goat",
+            "Tag ID",
+            "Status",
+            "First Reported",
+            "Tests",
+        ])
+        .map_err(csv_error)?;
+    for case in &report.cases {
+        writer
+            .write_record([
+                case.disease.clone(),
+                case.goat_name.clone(),
+                case.tag_id.clone().unwrap_or_default(),
+                CaseStatus::to_str(&case.status).to_string(),
+                case.first_reported.clone().unwrap_or_default(),
+                case.tests_label(),
+            ])
+            .map_err(csv_error)?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| AppError::InvalidInput(format!("Cannot write CSV: {}", e)))
+}
+
+/// Handler for the notifiable disease compliance report.
+///
+/// # HTTP Method
+/// - `GET /diseases/compliance?format=csv`
+///
+/// # Success
+/// - Returns HTTP 200 with a `ComplianceReport` for `format=json` (the
+///   default), or a `text/csv` attachment dated today, for authorities.
+pub async fn get_compliance(
+    db: web::Data<DbPool>,
+    query: web::Query<ComplianceQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(format = ?query.format, "GET /diseases/compliance called");
+    let conn = db.get_conn()?;
+    let report = compliance_report(&conn)?;
+
+    info!(
+        diseases = report.diseases.len(),
+        cases = report.cases.len(),
+        "Compliance report built"
+    );
+    match query.format {
+        ComplianceFormat::Json => Ok(HttpResponse::Ok().json(report)),
+        ComplianceFormat::Csv => Ok(HttpResponse::Ok()
+            .content_type("text/csv")
+            .insert_header((
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"notifiable-diseases-{}.csv\"",
+                    report.generated_on
+                ),
+            ))
+            .body(compliance_csv(&report)?)),
+    }
+}
@@ -0,0 +1,98 @@
+//! This module serves the event log (see `crate::events`) to clients that
+//! follow it, such as audit views, delta sync and webhook relays, and the
+//! change history of goat fields derived from it.
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::events::{events_after, events_for_goat, field_history};
+use actix_web::{HttpResponse, Responder, web};
+use serde::Deserialize;
+use shared::events::HISTORY_FIELDS;
+use tracing::{debug, info};
+
+/// Most events returned by one `GET /events`.
+const MAX_EVENTS: u32 = 500;
+
+/// Query parameters accepted by `GET /events`.
+#[derive(Deserialize)]
+pub struct EventQuery {
+    /// Only events with a larger ID; the last ID a client has seen.
+    pub after: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+/// Handler for reading the event log, oldest first.
+///
+/// # HTTP Method
+/// - `GET /events?after=42&limit=100`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `StoredEvent`. Without `after`
+///   the log is read from the start. The log is empty unless the server
+///   runs event-sourced; goat changes logged only for field history are
+///   left out.
+pub async fn get_events(
+    db: web::Data<DbPool>,
+    query: web::Query<EventQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(after = ?query.after, "GET /events called");
+    let limit = query.limit.unwrap_or(100).min(MAX_EVENTS);
+    let conn = db.get_conn()?;
+    let events = events_after(&conn, query.after.unwrap_or(0), limit as i64)?;
+
+    info!("Returning {} events", events.len());
+    Ok(HttpResponse::Ok().json(events))
+}
+
+/// Query parameters accepted by `GET /goats/{id}/history`.
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    /// One of `shared::events::HISTORY_FIELDS`; all of them if left out.
+    pub field: Option<String>,
+}
+
+/// Handler for the change history of a goat's fields.
+///
+/// # HTTP Method
+/// - `GET /goats/{id}/history?field=current_price`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `FieldChange`, newest first,
+///   each with who made it. Changes are logged whether or not the server
+///   runs event-sourced, so only those made before goats were logged for
+///   history are missing.
+///
+/// # Errors
+/// - Returns HTTP 400 if no goat has that ID or `field` is not a goat field.
+pub async fn get_field_history(
+    db: web::Data<DbPool>,
+    id: web::Path<i64>,
+    query: web::Query<HistoryQuery>,
+) -> Result<impl Responder, AppError> {
+    let id = id.into_inner();
+    debug!(goat_id = id, field = ?query.field, "GET /goats/{{id}}/history called");
+    if let Some(field) = &query.field
+        && !HISTORY_FIELDS.contains(&field.as_str())
+    {
+        return Err(AppError::InvalidInput(format!(
+            "Unknown goat field: {}",
+            field
+        )));
+    }
+    let conn = db.get_conn()?;
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM goats WHERE id = ?1 AND deleted_at IS NULL)",
+        [id],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Err(AppError::InvalidInput(format!(
+            "No goat found with ID {}",
+            id
+        )));
+    }
+    let changes = field_history(&events_for_goat(&conn, id)?, query.field.as_deref())?;
+
+    info!(goat_id = id, "Returning {} field changes", changes.len());
+    Ok(HttpResponse::Ok().json(changes))
+}
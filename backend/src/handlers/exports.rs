@@ -0,0 +1,179 @@
+//! This module handles saved export templates and runs them, writing the
+//! goats each one selects as CSV or JSON (see `shared::exports`).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::settings::farm_today;
+use crate::repository::Goats;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::http::header;
+use actix_web::{HttpResponse, Responder, web};
+use rusqlite::{Connection, params};
+use shared::exports::{ExportFormat, ExportTable, ExportTemplate, build_export};
+use tracing::{debug, info, warn};
+
+/// Every saved template, ordered by key.
+pub fn load_export_templates(conn: &Connection) -> Result<Vec<ExportTemplate>, AppError> {
+    let mut stmt = conn.prepare("SELECT definition FROM export_templates ORDER BY template_key")?;
+    let definitions = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut templates = Vec::with_capacity(definitions.len());
+    for definition in definitions {
+        templates.push(serde_json::from_str(&definition)?);
+    }
+    Ok(templates)
+}
+
+/// Finds the template with `key`.
+///
+/// # Errors
+/// - `AppError::InvalidInput` if there is no such template.
+fn find_export_template(conn: &Connection, key: &str) -> Result<ExportTemplate, AppError> {
+    load_export_templates(conn)?
+        .into_iter()
+        .find(|template| template.key == key)
+        .ok_or_else(|| AppError::InvalidInput(format!("No export template with key {}", key)))
+}
+
+/// Renders an export as CSV: a heading row, then one row per goat.
+pub fn export_csv(table: &ExportTable) -> Result<Vec<u8>, AppError> {
+    let csv_error = |e: csv::Error| AppError::InvalidInput(format!("Cannot write CSV: {}", e));
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&table.columns).map_err(csv_error)?;
+    for row in &table.rows {
+        writer.write_record(row).map_err(csv_error)?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| AppError::InvalidInput(format!("Cannot write CSV: {}", e)))
+}
+
+/// Handler for listing saved export templates.
+///
+/// # HTTP Method
+/// - `GET /exports/templates`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `ExportTemplate`, ordered by key.
+pub async fn get_export_templates(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /exports/templates called");
+    let conn = db.get_conn()?;
+    let templates = load_export_templates(&conn)?;
+
+    info!("Returning {} export templates", templates.len());
+    Ok(HttpResponse::Ok().json(templates))
+}
+
+/// Handler for saving an export template.
+///
+/// # HTTP Method
+/// - `POST /exports/templates`
+///
+/// # Request
+/// - JSON `ExportTemplate`.
+///
+/// # Success
+/// - Returns HTTP 201 with the stored `ExportTemplate`.
+///
+/// # Errors
+/// - Returns HTTP 400 for an invalid template or a key already in use.
+pub async fn add_export_template(
+    db: web::Data<DbPool>,
+    template: web::Json<ExportTemplate>,
+) -> Result<impl Responder, AppError> {
+    debug!(key = %template.key, "POST /exports/templates called");
+    let mut template = template.into_inner();
+    template.key = template.key.trim().to_string();
+    template.name = template.name.trim().to_string();
+    template.validate().map_err(AppError::InvalidInput)?;
+
+    let conn = db.get_conn()?;
+    if load_export_templates(&conn)?
+        .iter()
+        .any(|t| t.key == template.key)
+    {
+        return Err(AppError::InvalidInput(format!(
+            "An export template with key {} already exists",
+            template.key
+        )));
+    }
+    conn.execute(
+        "INSERT INTO export_templates (template_key, definition) VALUES (?1, ?2)",
+        params![template.key, serde_json::to_string(&template)?],
+    )?;
+
+    info!(key = %template.key, "Export template added");
+    Ok(HttpResponse::Created().json(template))
+}
+
+/// Handler for removing a saved export template.
+///
+/// # HTTP Method
+/// - `DELETE /exports/templates/{key}`
+///
+/// # Success
+/// - Returns HTTP 200 once the template is removed.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown key.
+pub async fn delete_export_template(
+    db: web::Data<DbPool>,
+    key: web::Path<String>,
+) -> Result<impl Responder, AppError> {
+    let key = key.into_inner();
+    debug!(%key, "DELETE /exports/templates called");
+    let conn = db.get_conn()?;
+    let deleted = conn.execute(
+        "DELETE FROM export_templates WHERE template_key = ?1",
+        [&key],
+    )?;
+    if deleted == 0 {
+        warn!(%key, "Export template not found for deletion");
+        return Err(AppError::InvalidInput(format!(
+            "No export template with key {}",
+            key
+        )));
+    }
+
+    info!(%key, "Export template deleted");
+    Ok(HttpResponse::Ok().body("Export template deleted"))
+}
+
+/// Handler for running a saved export.
+///
+/// # HTTP Method
+/// - `GET /exports/run/{key}`
+///
+/// # Success
+/// - Returns HTTP 200 with the goats template `key` selects, in its columns,
+///   as a `text/csv` or `application/json` attachment named after the
+///   template and today's date.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown template.
+pub async fn run_export(
+    db: web::Data<DbPool>,
+    goats: Goats,
+    key: web::Path<String>,
+) -> Result<impl Responder, AppError> {
+    let key = key.into_inner();
+    debug!(%key, "GET /exports/run called");
+    let conn = db.get_conn()?;
+    let template = find_export_template(&conn, &key)?;
+    let today = farm_today(&conn)?.format(DATE_FORMAT).to_string();
+    let table = build_export(&template, &goats.list()?);
+
+    let (content_type, body) = match template.format {
+        ExportFormat::Csv => ("text/csv", export_csv(&table)?),
+        ExportFormat::Json => ("application/json", serde_json::to_vec(&table)?),
+    };
+    info!(%key, rows = table.rows.len(), "Export built");
+    Ok(HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", template.file_name(&today)),
+        ))
+        .body(body))
+}
@@ -0,0 +1,220 @@
+//! This module manages the registry of external animals: bucks and does
+//! kept off the farm that pedigrees and breeding records may name (see
+//! `shared::breeding::ExternalAnimal`). They never appear in the herd.
+
+use crate::db::DbPool;
+use crate::errors::{AppError, ParseEnumError};
+use crate::events::EventLog;
+use actix_web::{HttpResponse, Responder, web};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use shared::Gender;
+use shared::breeding::ExternalAnimal;
+use shared::events::DomainEvent;
+use tracing::{debug, info, warn};
+
+/// Columns read by `row_to_external`, in order.
+const EXTERNAL_COLUMNS: &str = "id, name, gender, breed, registration, owner, notes";
+
+/// Reads an external animal selected with `EXTERNAL_COLUMNS`.
+fn row_to_external(row: &Row) -> Result<ExternalAnimal, rusqlite::Error> {
+    let gender: String = row.get(2)?;
+    Ok(ExternalAnimal {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        gender: Gender::from_str(&gender).map_err(|e| {
+            rusqlite::Error::ToSqlConversionFailure(Box::new(AppError::ParseError(
+                ParseEnumError::new(&e, "Gender"),
+            )))
+        })?,
+        breed: row.get(3)?,
+        registration: row.get(4)?,
+        owner: row.get(5)?,
+        notes: row.get(6)?,
+    })
+}
+
+/// Looks up the external animal named `name`.
+pub fn find_external(conn: &Connection, name: &str) -> Result<Option<ExternalAnimal>, AppError> {
+    Ok(conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM external_animals WHERE name = ?1",
+                EXTERNAL_COLUMNS
+            ),
+            [name],
+            row_to_external,
+        )
+        .optional()?)
+}
+
+/// Resolves an external animal's name to its ID, requiring the given
+/// gender.
+pub fn external_id_with_gender(
+    conn: &Connection,
+    name: &str,
+    gender: Gender,
+) -> Result<i64, AppError> {
+    match find_external(conn, name)? {
+        Some(animal) if animal.gender == gender => Ok(animal.id.unwrap_or_default()),
+        Some(animal) => Err(AppError::InvalidInput(format!(
+            "External animal {} is {}, expected {}",
+            name,
+            Gender::to_str(&animal.gender),
+            Gender::to_str(&gender)
+        ))),
+        None => Err(AppError::InvalidInput(format!(
+            "No external animal found with name {}",
+            name
+        ))),
+    }
+}
+
+/// Handler for listing the external animals by name.
+///
+/// # HTTP Method
+/// - `GET /breeding/external`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `ExternalAnimal`.
+pub async fn get_external_animals(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /breeding/external called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM external_animals ORDER BY name",
+        EXTERNAL_COLUMNS
+    ))?;
+    let animals = stmt
+        .query_map([], row_to_external)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    info!("Returning {} external animals", animals.len());
+    Ok(HttpResponse::Ok().json(animals))
+}
+
+/// Handler for adding an animal to the external registry.
+///
+/// # HTTP Method
+/// - `POST /breeding/external`
+///
+/// # Request
+/// - JSON `ExternalAnimal`; `id` is ignored and blank details are stored as
+///   none.
+///
+/// # Success
+/// - Returns HTTP 201 with the animal as stored.
+///
+/// # Errors
+/// - Returns HTTP 400 for a blank or overlong name, or a name already in
+///   the registry or the herd.
+pub async fn add_external_animal(
+    db: web::Data<DbPool>,
+    animal: web::Json<ExternalAnimal>,
+) -> Result<impl Responder, AppError> {
+    debug!(name = %animal.name, "POST /breeding/external called");
+    animal.validate().map_err(AppError::InvalidInput)?;
+    let name = animal.name.trim();
+    let text = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+
+    let conn = db.get_conn()?;
+    let taken: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM goats WHERE name = ?1 AND deleted_at IS NULL) \
+             OR EXISTS(SELECT 1 FROM external_animals WHERE name = ?1)",
+        [name],
+        |row| row.get(0),
+    )?;
+    if taken {
+        warn!(name, "External animal name already in use");
+        return Err(AppError::InvalidInput(format!(
+            "{} is already the name of a goat or external animal",
+            name
+        )));
+    }
+    conn.execute(
+        "INSERT INTO external_animals (name, gender, breed, registration, owner, notes) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            name,
+            Gender::to_str(&animal.gender),
+            text(&animal.breed),
+            text(&animal.registration),
+            text(&animal.owner),
+            text(&animal.notes),
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    let stored = conn.query_row(
+        &format!(
+            "SELECT {} FROM external_animals WHERE id = ?1",
+            EXTERNAL_COLUMNS
+        ),
+        [id],
+        row_to_external,
+    )?;
+
+    info!(external_id = id, "External animal added");
+    Ok(HttpResponse::Created().json(stored))
+}
+
+/// Handler for removing an animal from the external registry. Pedigrees,
+/// kiddings and services naming it keep everything else and lose only the
+/// link.
+///
+/// # HTTP Method
+/// - `DELETE /breeding/external/{id}`
+///
+/// # Errors
+/// - Returns HTTP 400 if no external animal matches the ID.
+pub async fn delete_external_animal(
+    db: web::Data<DbPool>,
+    events: EventLog,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(external_id = id, "DELETE /breeding/external/{{id}} called");
+
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+    let affected = tx.execute("DELETE FROM external_animals WHERE id = ?1", [id])?;
+    if affected == 0 {
+        warn!(external_id = id, "External animal not found for deletion");
+        return Err(AppError::InvalidInput(format!(
+            "No external animal found with id {}",
+            id
+        )));
+    }
+    let pedigrees = tx
+        .prepare(
+            "SELECT id, sire_id, dam_id, sire_external_id, dam_external_id, date_of_birth \
+             FROM goats WHERE sire_external_id = ?1 OR dam_external_id = ?1",
+        )?
+        .query_map([id], |row| {
+            Ok(DomainEvent::PedigreeRecorded {
+                goat_id: row.get(0)?,
+                sire_id: row.get(1)?,
+                dam_id: row.get(2)?,
+                sire_external_id: row.get::<_, Option<i64>>(3)?.filter(|&s| s != id),
+                dam_external_id: row.get::<_, Option<i64>>(4)?.filter(|&d| d != id),
+                date_of_birth: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    for pedigree in &pedigrees {
+        events.apply(&tx, pedigree)?;
+    }
+    for sql in [
+        "UPDATE kidding_records SET external_buck_id = NULL WHERE external_buck_id = ?1",
+        "UPDATE breeding_services SET external_sire_id = NULL WHERE external_sire_id = ?1",
+    ] {
+        tx.execute(sql, [id])?;
+    }
+    tx.commit()?;
+
+    info!(external_id = id, "External animal deleted");
+    Ok(HttpResponse::Ok().body("External animal deleted"))
+}
@@ -0,0 +1,1044 @@
+//! This module handles the finance ledger: manual income/expense entries,
+//! expenses allocated automatically from inventory consumption, and
+//! per-goat profitability.
+//!
+//! Sales may carry invoice details and a GST rate; `GET /finance/sales-register`
+//! lists them with CGST/SGST/IGST split out for the farm's accountant.
+//!
+//! Amounts in a foreign currency are stored as entered with their exchange
+//! rate, and converted to the base currency wherever they are summed.
+//!
+//! Expense categories may have a standing monthly budget;
+//! `GET /finance/budgets/variance` compares a month's spending against it.
+
+use crate::db::DbPool;
+use crate::errors::{AppError, ParseEnumError};
+use crate::events::{EventLog, next_id};
+use crate::handlers::parse_date;
+use crate::handlers::settings::{farm_today, load_settings};
+use crate::handlers::traceability::record_sale;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::http::header;
+use actix_web::{HttpResponse, Responder, web};
+use chrono::{Datelike, Months, NaiveDate};
+use rusqlite::{Connection, OptionalExtension, Row, Transaction as DbTransaction, params};
+use serde::Deserialize;
+use shared::events::DomainEvent;
+use shared::finance::{
+    Budget, BudgetReport, BudgetVariance, FinanceCategory, GoatProfitability, SaleDetails,
+    SalesRegister, SalesRegisterRow, TaxRate, Transaction, TransactionKind, gstin_state_code,
+    round_paise, validate_currency_code, validate_gstin,
+};
+use shared::inventory::{InventoryCategory, InventoryItem};
+use tracing::{debug, info, trace, warn};
+
+/// Query parameters accepted by `GET /transactions`.
+#[derive(Deserialize)]
+pub struct TransactionQuery {
+    pub goat_name: Option<String>,
+}
+
+/// Output formats of `GET /finance/sales-register`.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RegisterFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// Query parameters accepted by `GET /finance/budgets/variance`.
+///
+/// `month` is `YYYY-MM` and defaults to the current month.
+#[derive(Deserialize)]
+pub struct BudgetVarianceQuery {
+    pub month: Option<String>,
+}
+
+/// Query parameters accepted by `GET /finance/sales-register`.
+///
+/// The period defaults to the current month up to today.
+#[derive(Deserialize)]
+pub struct SalesRegisterQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    #[serde(default)]
+    pub format: RegisterFormat,
+}
+
+const TRANSACTION_SELECT: &str = "SELECT t.id, t.kind, t.category, t.amount, t.description, g.name, t.space_id, \
+     t.consumption_id, t.date, s.transaction_id, s.invoice_number, s.buyer_name, s.buyer_gstin, \
+     s.tax_rate_id, s.tax_percent, t.currency, t.exchange_rate \
+     FROM transactions t LEFT JOIN goats g ON g.id = t.goat_id \
+     LEFT JOIN sale_details s ON s.transaction_id = t.id";
+
+/// Maps a row selected with `TRANSACTION_SELECT` to a `Transaction`.
+pub(crate) fn row_to_transaction(row: &Row) -> Result<Transaction, AppError> {
+    let kind: String = row.get(1)?;
+    let category: String = row.get(2)?;
+    let sale = match row.get::<_, Option<i64>>(9)? {
+        Some(_) => Some(SaleDetails {
+            invoice_number: row.get(10)?,
+            buyer_name: row.get(11)?,
+            buyer_gstin: row.get(12)?,
+            tax_rate_id: row.get(13)?,
+            tax_percent: row.get(14)?,
+        }),
+        None => None,
+    };
+    Ok(Transaction {
+        id: row.get(0)?,
+        kind: TransactionKind::from_str(&kind)
+            .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "TransactionKind")))?,
+        category: FinanceCategory::from_str(&category)
+            .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "FinanceCategory")))?,
+        amount: row.get(3)?,
+        description: row.get(4)?,
+        goat_name: row.get(5)?,
+        space_id: row.get(6)?,
+        consumption_id: row.get(7)?,
+        date: row.get(8)?,
+        sale,
+        currency: row.get(15)?,
+        exchange_rate: row.get(16)?,
+    })
+}
+
+/// The finance category inventory of `category` is charged to.
+fn expense_category(category: &InventoryCategory) -> FinanceCategory {
+    match category {
+        InventoryCategory::Feed => FinanceCategory::Feed,
+        InventoryCategory::Medicine => FinanceCategory::Medicine,
+        InventoryCategory::Equipment => FinanceCategory::Equipment,
+        InventoryCategory::Other => FinanceCategory::Other,
+    }
+}
+
+/// Charges the cost of consumed inventory as an expense attributed to a goat or pen.
+///
+/// The amount is `quantity * unit_cost`; nothing is recorded for free items.
+/// Must run in the same database transaction as the consumption insert.
+///
+/// # Returns
+/// The ID of the created transaction, if any.
+pub fn record_consumption_expense(
+    tx: &DbTransaction,
+    consumption_id: i64,
+    item: &InventoryItem,
+    quantity: f64,
+    goat_id: Option<i64>,
+    space_id: Option<i64>,
+    date: &str,
+) -> Result<Option<i64>, AppError> {
+    let amount = quantity * item.unit_cost;
+    if amount <= 0.0 {
+        trace!(consumption_id, "Consumed item has no cost, skipping expense");
+        return Ok(None);
+    }
+    let category = expense_category(&item.category);
+
+    tx.execute(
+        "INSERT INTO transactions (kind, category, amount, description, goat_id, space_id, consumption_id, date) \
+         VALUES ('Expense', ?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            FinanceCategory::to_str(&category),
+            amount,
+            format!("{} {} {}", quantity, item.unit, item.name),
+            goat_id,
+            space_id,
+            consumption_id,
+            date,
+        ],
+    )?;
+    let id = tx.last_insert_rowid();
+    debug!(transaction_id = id, consumption_id, amount, "Allocated consumption cost");
+    Ok(Some(id))
+}
+
+/// Writes off the cost of disposed inventory as an expense of the farm as a
+/// whole.
+///
+/// Like `record_consumption_expense`, nothing is recorded for free items and
+/// it must run in the same database transaction as the consumption insert.
+///
+/// # Returns
+/// The ID of the created transaction, if any.
+pub fn record_disposal_expense(
+    tx: &DbTransaction,
+    consumption_id: i64,
+    item: &InventoryItem,
+    quantity: f64,
+    date: &str,
+) -> Result<Option<i64>, AppError> {
+    let amount = quantity * item.unit_cost;
+    if amount <= 0.0 {
+        trace!(consumption_id, "Disposed item has no cost, skipping expense");
+        return Ok(None);
+    }
+    tx.execute(
+        "INSERT INTO transactions (kind, category, amount, description, consumption_id, date) \
+         VALUES ('Expense', ?1, ?2, ?3, ?4, ?5)",
+        params![
+            FinanceCategory::to_str(&expense_category(&item.category)),
+            amount,
+            format!("Disposed of {} {} {}", quantity, item.unit, item.name),
+            consumption_id,
+            date,
+        ],
+    )?;
+    let id = tx.last_insert_rowid();
+    debug!(transaction_id = id, consumption_id, amount, "Wrote off disposed stock");
+    Ok(Some(id))
+}
+
+/// Handler for listing transactions, newest first.
+///
+/// # HTTP Method
+/// - `GET /transactions?goat_name=Rani`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of transactions.
+pub async fn get_transactions(
+    db: web::Data<DbPool>,
+    query: web::Query<TransactionQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat_name = ?query.goat_name, "GET /transactions called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE (?1 IS NULL OR g.name = ?1) ORDER BY t.date DESC, t.id DESC",
+        TRANSACTION_SELECT
+    ))?;
+    let transactions: Result<Vec<Transaction>, rusqlite::Error> = stmt
+        .query_map([&query.goat_name], |row| {
+            row_to_transaction(row)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+        })?
+        .collect();
+    let transactions = transactions?;
+
+    info!("Returning {} transactions", transactions.len());
+    Ok(HttpResponse::Ok().json(transactions))
+}
+
+/// Checks a transaction's currency against the farm's base currency.
+///
+/// # Returns
+/// The currency and exchange rate to store: both `None` for the base
+/// currency, which needs no conversion.
+fn prepare_currency(
+    conn: &Connection,
+    currency: Option<&str>,
+    exchange_rate: Option<f64>,
+) -> Result<(Option<String>, Option<f64>), AppError> {
+    let base_currency = load_settings(conn)?.base_currency;
+    let currency = currency
+        .map(|c| c.trim().to_uppercase())
+        .filter(|c| !c.is_empty() && *c != base_currency);
+    match (currency, exchange_rate) {
+        (None, None) => Ok((None, None)),
+        (None, Some(1.0)) => Ok((None, None)),
+        (None, Some(_)) => Err(AppError::InvalidInput(format!(
+            "Amounts in the base currency {} take no exchange rate",
+            base_currency
+        ))),
+        (Some(currency), rate) => {
+            validate_currency_code(&currency).map_err(AppError::InvalidInput)?;
+            match rate {
+                Some(rate) if rate.is_finite() && rate > 0.0 => Ok((Some(currency), Some(rate))),
+                _ => Err(AppError::InvalidInput(format!(
+                    "Amounts in {} need a positive exchange rate to {}",
+                    currency, base_currency
+                ))),
+            }
+        }
+    }
+}
+
+/// Checks a sale's invoice details and resolves the rate to apply.
+///
+/// # Returns
+/// The details to store, with the GSTIN upper-cased and `tax_percent` taken
+/// from the selected tax rate (zero without one).
+fn prepare_sale(conn: &Connection, sale: &SaleDetails) -> Result<SaleDetails, AppError> {
+    let buyer_gstin = sale
+        .buyer_gstin
+        .as_deref()
+        .map(|g| g.trim().to_uppercase())
+        .filter(|g| !g.is_empty());
+    if let Some(gstin) = &buyer_gstin {
+        validate_gstin(gstin).map_err(AppError::InvalidInput)?;
+    }
+    let invoice_number = sale
+        .invoice_number
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(str::to_string);
+    if let Some(number) = &invoice_number {
+        let taken: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sale_details WHERE invoice_number = ?1)",
+            [number],
+            |row| row.get(0),
+        )?;
+        if taken {
+            return Err(AppError::InvalidInput(format!(
+                "Invoice number {} is already used",
+                number
+            )));
+        }
+    }
+    let tax_percent = match sale.tax_rate_id {
+        Some(id) => conn
+            .query_row(
+                "SELECT rate_percent FROM tax_rates WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| AppError::InvalidInput(format!("No tax rate found with id {}", id)))?,
+        None => 0.0,
+    };
+    Ok(SaleDetails {
+        invoice_number,
+        buyer_name: sale.buyer_name.clone(),
+        buyer_gstin,
+        tax_rate_id: sale.tax_rate_id,
+        tax_percent,
+    })
+}
+
+/// Inserts `transaction` as given, with its currency and sale details
+/// already checked, for goat `goat_id`. The transaction gets ID `id` if
+/// given, or the next free one, which is returned.
+pub(crate) fn insert_transaction(
+    tx: &Connection,
+    id: Option<i64>,
+    transaction: &Transaction,
+    goat_id: Option<i64>,
+) -> Result<i64, AppError> {
+    tx.execute(
+        "INSERT INTO transactions \
+         (id, kind, category, amount, description, goat_id, space_id, date, currency, exchange_rate) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            id,
+            TransactionKind::to_str(&transaction.kind),
+            FinanceCategory::to_str(&transaction.category),
+            transaction.amount,
+            transaction.description,
+            goat_id,
+            transaction.space_id,
+            transaction.date,
+            transaction.currency,
+            transaction.exchange_rate,
+        ],
+    )?;
+    let transaction_id = tx.last_insert_rowid();
+    if let Some(sale) = &transaction.sale {
+        tx.execute(
+            "INSERT INTO sale_details \
+             (transaction_id, invoice_number, buyer_name, buyer_gstin, tax_rate_id, tax_percent) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                transaction_id,
+                sale.invoice_number,
+                sale.buyer_name,
+                sale.buyer_gstin,
+                sale.tax_rate_id,
+                sale.tax_percent,
+            ],
+        )?;
+    }
+    Ok(transaction_id)
+}
+
+/// Handler for recording a manual transaction.
+///
+/// # HTTP Method
+/// - `POST /transactions`
+///
+/// # Request
+/// - JSON `Transaction`. Income may carry `sale` invoice details; its
+///   `tax_percent` is ignored and taken from `tax_rate_id`. A `currency`
+///   other than the base currency needs an `exchange_rate`.
+///
+/// # Success
+/// - Returns HTTP 201 on successful insertion. Selling a goat also takes
+///   its traceability record (see `crate::handlers::traceability`).
+///
+/// # Errors
+/// - Returns HTTP 400 for a negative amount, an unknown goat, sale details
+///   on an expense, an invalid buyer GSTIN, a reused invoice number, an
+///   unknown tax rate, an invalid currency code, or a missing or
+///   non-positive exchange rate.
+pub async fn add_transaction(
+    db: web::Data<DbPool>,
+    events: EventLog,
+    transaction: web::Json<Transaction>,
+) -> Result<impl Responder, AppError> {
+    debug!(amount = transaction.amount, "POST /transactions called");
+    if transaction.amount < 0.0 {
+        return Err(AppError::InvalidInput(
+            "Transaction amount must not be negative".into(),
+        ));
+    }
+
+    if transaction.sale.is_some() && transaction.kind != TransactionKind::Income {
+        return Err(AppError::InvalidInput(
+            "Only income can have sale details".into(),
+        ));
+    }
+
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+    let goat_id: Option<i64> = match &transaction.goat_name {
+        Some(name) => Some(
+            tx.query_row(
+                "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+                [name],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| AppError::InvalidInput(format!("No goat found with name {}", name)))?,
+        ),
+        None => None,
+    };
+    let sale = transaction
+        .sale
+        .as_ref()
+        .map(|sale| prepare_sale(&tx, sale))
+        .transpose()?;
+    let (currency, exchange_rate) = prepare_currency(
+        &tx,
+        transaction.currency.as_deref(),
+        transaction.exchange_rate,
+    )?;
+
+    let mut stored = Transaction {
+        id: None,
+        consumption_id: None,
+        sale,
+        currency,
+        exchange_rate,
+        ..transaction.into_inner()
+    };
+    // A goat sale, as the activity feed counts them, traced for the buyer
+    let transaction_id = match goat_id {
+        Some(goat_id)
+            if stored.kind == TransactionKind::Income
+                && stored.category == FinanceCategory::Sale =>
+        {
+            let transaction_id = next_id(&tx, "transactions")?;
+            stored.id = Some(transaction_id);
+            events.apply(
+                &tx,
+                &DomainEvent::GoatSold {
+                    goat_id,
+                    transaction: stored.clone(),
+                },
+            )?;
+            record_sale(&tx, goat_id, &stored)?;
+            transaction_id
+        }
+        _ => insert_transaction(&tx, None, &stored, goat_id)?,
+    };
+    tx.commit()?;
+
+    info!(
+        transaction_id,
+        sale = stored.sale.is_some(),
+        currency = ?stored.currency,
+        "Transaction recorded"
+    );
+    Ok(HttpResponse::Created().body("Transaction added"))
+}
+
+/// Handler for deleting a manual transaction.
+///
+/// # HTTP Method
+/// - `DELETE /transactions/{id}`
+///
+/// # Errors
+/// - Returns HTTP 400 if no transaction matches the ID, or if it was generated
+///   from inventory consumption (those follow their consumption record).
+pub async fn delete_transaction(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(transaction_id = id, "DELETE /transactions/{{id}} called");
+
+    let conn = db.get_conn()?;
+    let affected = conn.execute(
+        "DELETE FROM transactions WHERE id = ?1 AND consumption_id IS NULL",
+        [id],
+    )?;
+    if affected == 0 {
+        warn!(transaction_id = id, "Transaction not found or not deletable");
+        return Err(AppError::InvalidInput(format!(
+            "No manual transaction found with id {}",
+            id
+        )));
+    }
+
+    conn.execute("DELETE FROM sale_details WHERE transaction_id = ?1", [id])?;
+
+    info!(transaction_id = id, "Transaction deleted");
+    Ok(HttpResponse::Ok().body("Transaction deleted"))
+}
+
+/// Computes every goat's profitability, unsorted.
+///
+/// Profit is the goat's current price plus income attributed to it, less its
+/// purchase cost and attributed expenses. Transactions are converted to the
+/// base currency at their recorded exchange rate.
+pub fn load_profitability(conn: &Connection) -> Result<Vec<GoatProfitability>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT g.name, COALESCE(g.cost, 0), COALESCE(g.current_price, 0), \
+             COALESCE(SUM(CASE WHEN t.kind = 'Expense' THEN t.amount * COALESCE(t.exchange_rate, 1) END), 0), \
+             COALESCE(SUM(CASE WHEN t.kind = 'Income' THEN t.amount * COALESCE(t.exchange_rate, 1) END), 0) \
+         FROM goats g LEFT JOIN transactions t ON t.goat_id = g.id \
+         WHERE g.deleted_at IS NULL \
+         GROUP BY g.id",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            let cost: f64 = row.get(1)?;
+            let current_price: f64 = row.get(2)?;
+            let expenses: f64 = row.get(3)?;
+            let income: f64 = row.get(4)?;
+            Ok(GoatProfitability {
+                goat_name: row.get(0)?,
+                cost,
+                current_price,
+                expenses,
+                income,
+                profit: current_price + income - cost - expenses,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(rows)
+}
+
+/// Handler for per-goat profitability including allocated input costs.
+///
+/// # HTTP Method
+/// - `GET /finance/goats`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `GoatProfitability`, least profitable first.
+pub async fn get_goat_profitability(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /finance/goats called");
+    let conn = db.get_conn()?;
+    let mut rows = load_profitability(&conn)?;
+    rows.sort_by(|a, b| a.profit.total_cmp(&b.profit));
+
+    info!("Returning profitability for {} goats", rows.len());
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+/// Handler for listing the configured tax rates.
+///
+/// # HTTP Method
+/// - `GET /finance/tax-rates`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `TaxRate`s, lowest rate first.
+pub async fn get_tax_rates(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /finance/tax-rates called");
+    let conn = db.get_conn()?;
+    let mut stmt =
+        conn.prepare("SELECT id, name, rate_percent FROM tax_rates ORDER BY rate_percent, name")?;
+    let rates: Vec<TaxRate> = stmt
+        .query_map([], |row| {
+            Ok(TaxRate {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                rate_percent: row.get(2)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    info!("Returning {} tax rates", rates.len());
+    Ok(HttpResponse::Ok().json(rates))
+}
+
+/// Handler for adding a tax rate.
+///
+/// # HTTP Method
+/// - `POST /finance/tax-rates`
+///
+/// # Success
+/// - Returns HTTP 201 on successful insertion.
+///
+/// # Errors
+/// - Returns HTTP 400 for an empty or duplicate name, or a rate outside
+///   0-100 percent.
+pub async fn add_tax_rate(
+    db: web::Data<DbPool>,
+    rate: web::Json<TaxRate>,
+) -> Result<impl Responder, AppError> {
+    debug!(name = %rate.name, rate = rate.rate_percent, "POST /finance/tax-rates called");
+    let name = rate.name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("Tax rate name is required".into()));
+    }
+    if !(0.0..=100.0).contains(&rate.rate_percent) {
+        return Err(AppError::InvalidInput(
+            "Tax rate must be between 0 and 100 percent".into(),
+        ));
+    }
+
+    let conn = db.get_conn()?;
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM tax_rates WHERE name = ?1)",
+        [name],
+        |row| row.get(0),
+    )?;
+    if exists {
+        return Err(AppError::InvalidInput(format!(
+            "Tax rate {} already exists",
+            name
+        )));
+    }
+    conn.execute(
+        "INSERT INTO tax_rates (name, rate_percent) VALUES (?1, ?2)",
+        params![name, rate.rate_percent],
+    )?;
+
+    info!(tax_rate_id = conn.last_insert_rowid(), "Tax rate added");
+    Ok(HttpResponse::Created().body("Tax rate added"))
+}
+
+/// Handler for deleting a tax rate. Sales already recorded keep the rate
+/// they were taxed at.
+///
+/// # HTTP Method
+/// - `DELETE /finance/tax-rates/{id}`
+///
+/// # Errors
+/// - Returns HTTP 400 if no tax rate matches the ID.
+pub async fn delete_tax_rate(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(tax_rate_id = id, "DELETE /finance/tax-rates/{{id}} called");
+
+    let conn = db.get_conn()?;
+    conn.execute(
+        "UPDATE sale_details SET tax_rate_id = NULL WHERE tax_rate_id = ?1",
+        [id],
+    )?;
+    let affected = conn.execute("DELETE FROM tax_rates WHERE id = ?1", [id])?;
+    if affected == 0 {
+        warn!(tax_rate_id = id, "Tax rate not found for deletion");
+        return Err(AppError::InvalidInput(format!(
+            "No tax rate found with id {}",
+            id
+        )));
+    }
+
+    info!(tax_rate_id = id, "Tax rate deleted");
+    Ok(HttpResponse::Ok().body("Tax rate deleted"))
+}
+
+/// Builds the sales register for `from`..=`to`.
+///
+/// Sales are income in the `Sale` or `Milk` categories, plus any other
+/// income recorded with sale details. Taxes are split by comparing the
+/// farm's GSTIN state with the buyer's, and foreign currency sales are
+/// converted to the base currency.
+pub fn load_sales_register(
+    conn: &Connection,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<SalesRegister, AppError> {
+    let settings = load_settings(conn)?;
+    let gstin = settings.gstin;
+    let farm_state = gstin.as_deref().and_then(gstin_state_code);
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE t.kind = 'Income' AND (t.category IN ('Sale', 'Milk') OR s.transaction_id IS NOT NULL) \
+         AND t.date BETWEEN ?1 AND ?2 ORDER BY t.date, t.id",
+        TRANSACTION_SELECT
+    ))?;
+    let transactions: Vec<Transaction> = stmt
+        .query_map(
+            [
+                from.format(DATE_FORMAT).to_string(),
+                to.format(DATE_FORMAT).to_string(),
+            ],
+            |row| {
+                row_to_transaction(row)
+                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+            },
+        )?
+        .collect::<Result<_, _>>()?;
+
+    let rows: Vec<SalesRegisterRow> = transactions
+        .into_iter()
+        .map(|t| {
+            let taxable_value = round_paise(t.base_amount());
+            let sale = t.sale.unwrap_or_default();
+            SalesRegisterRow {
+                transaction_id: t.id.unwrap_or_default(),
+                date: t.date,
+                invoice_number: sale.invoice_number,
+                buyer_name: sale.buyer_name,
+                place_of_supply: sale
+                    .buyer_gstin
+                    .as_deref()
+                    .and_then(gstin_state_code)
+                    .map(str::to_string),
+                buyer_gstin: sale.buyer_gstin,
+                description: t.description,
+                taxable_value,
+                tax_percent: sale.tax_percent,
+                cgst: 0.0,
+                sgst: 0.0,
+                igst: 0.0,
+                invoice_total: 0.0,
+            }
+            .with_tax(farm_state)
+        })
+        .collect();
+
+    let total = |column: fn(&SalesRegisterRow) -> f64| round_paise(rows.iter().map(column).sum());
+    Ok(SalesRegister {
+        from: from.format(DATE_FORMAT).to_string(),
+        to: to.format(DATE_FORMAT).to_string(),
+        currency: settings.base_currency,
+        taxable_value: total(|r| r.taxable_value),
+        cgst: total(|r| r.cgst),
+        sgst: total(|r| r.sgst),
+        igst: total(|r| r.igst),
+        invoice_total: total(|r| r.invoice_total),
+        gstin,
+        rows,
+    })
+}
+
+/// Renders the register as CSV with one row per sale and a totals row.
+pub fn sales_register_csv(register: &SalesRegister) -> Result<Vec<u8>, AppError> {
+    let csv_error = |e: csv::Error| AppError::InvalidInput(format!("Cannot write CSV: {}", e));
+    let money = |amount: f64| format!("{:.2}", amount);
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record([
+            "Date",
+            "Invoice No",
+            "Buyer",
+            "Buyer GSTIN",
+            "Place of Supply",
+            "Description",
+            "Taxable Value",
+            "Rate (%)",
+            "CGST",
+            "SGST",
+            "IGST",
+            "Invoice Total",
+        ])
+        .map_err(csv_error)?;
+    for row in &register.rows {
+        writer
+            .write_record([
+                row.date.clone(),
+                row.invoice_number.clone().unwrap_or_default(),
+                row.buyer_name.clone().unwrap_or_default(),
+                row.buyer_gstin.clone().unwrap_or_default(),
+                row.place_of_supply.clone().unwrap_or_default(),
+                row.description.clone(),
+                money(row.taxable_value),
+                row.tax_percent.to_string(),
+                money(row.cgst),
+                money(row.sgst),
+                money(row.igst),
+                money(row.invoice_total),
+            ])
+            .map_err(csv_error)?;
+    }
+    writer
+        .write_record([
+            "Total".to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            money(register.taxable_value),
+            String::new(),
+            money(register.cgst),
+            money(register.sgst),
+            money(register.igst),
+            money(register.invoice_total),
+        ])
+        .map_err(csv_error)?;
+    writer
+        .into_inner()
+        .map_err(|e| AppError::InvalidInput(format!("Cannot write CSV: {}", e)))
+}
+
+/// Parses an optional `DATE_FORMAT` query date.
+fn parse_query_date(value: Option<&str>, field: &str) -> Result<Option<NaiveDate>, AppError> {
+    value.map(|v| parse_date(v, field)).transpose()
+}
+
+/// Handler for the GST sales register.
+///
+/// # HTTP Method
+/// - `GET /finance/sales-register?from=2026-04-01&to=2026-06-30&format=csv`
+///
+/// # Success
+/// - Returns HTTP 200 with a `SalesRegister` for `format=json` (the
+///   default), or a `text/csv` attachment named after the period.
+///
+/// # Errors
+/// - Returns HTTP 400 for malformed dates or `from` after `to`.
+pub async fn get_sales_register(
+    db: web::Data<DbPool>,
+    query: web::Query<SalesRegisterQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(from = ?query.from, to = ?query.to, format = ?query.format, "GET /finance/sales-register called");
+    let conn = db.get_conn()?;
+    let to = match parse_query_date(query.to.as_deref(), "to")? {
+        Some(to) => to,
+        None => farm_today(&conn)?,
+    };
+    let from = parse_query_date(query.from.as_deref(), "from")?
+        .unwrap_or_else(|| to.with_day(1).expect("day 1 exists in every month"));
+    if from > to {
+        return Err(AppError::InvalidInput("from must not be after to".into()));
+    }
+
+    let conn = db.get_conn()?;
+    let register = load_sales_register(&conn, from, to)?;
+    info!(
+        sales = register.rows.len(),
+        total = register.invoice_total,
+        "Sales register built"
+    );
+    match query.format {
+        RegisterFormat::Json => Ok(HttpResponse::Ok().json(register)),
+        RegisterFormat::Csv => Ok(HttpResponse::Ok()
+            .content_type("text/csv")
+            .insert_header((
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"sales-register-{}-to-{}.csv\"",
+                    register.from, register.to
+                ),
+            ))
+            .body(sales_register_csv(&register)?)),
+    }
+}
+
+/// Handler for listing the monthly budgets.
+///
+/// # HTTP Method
+/// - `GET /finance/budgets`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `Budget`, in category order.
+pub async fn get_budgets(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /finance/budgets called");
+    let conn = db.get_conn()?;
+    let mut budgets = load_budgets(&conn)?;
+    budgets.sort_by(|a, b| a.category.cmp(&b.category));
+
+    info!("Returning {} budgets", budgets.len());
+    Ok(HttpResponse::Ok().json(budgets))
+}
+
+/// Loads every budget, unsorted.
+fn load_budgets(conn: &Connection) -> Result<Vec<Budget>, AppError> {
+    let mut stmt = conn.prepare("SELECT category, monthly_amount FROM budgets")?;
+    let budgets = stmt
+        .query_map([], |row| {
+            let category: String = row.get(0)?;
+            Ok(Budget {
+                category: FinanceCategory::from_str(&category).map_err(|e| {
+                    rusqlite::Error::ToSqlConversionFailure(Box::new(AppError::ParseError(
+                        ParseEnumError::new(&e, "FinanceCategory"),
+                    )))
+                })?,
+                monthly_amount: row.get(1)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(budgets)
+}
+
+/// Handler for setting a category's monthly budget, replacing any earlier one.
+///
+/// # HTTP Method
+/// - `PUT /finance/budgets`
+///
+/// # Request
+/// - JSON `Budget` with the amount in the base currency.
+///
+/// # Success
+/// - Returns HTTP 200 once the budget is stored.
+///
+/// # Errors
+/// - Returns HTTP 400 for a negative amount or an income category
+///   (`Sale`, `Milk`).
+pub async fn set_budget(
+    db: web::Data<DbPool>,
+    budget: web::Json<Budget>,
+) -> Result<impl Responder, AppError> {
+    debug!(category = ?budget.category, amount = budget.monthly_amount, "PUT /finance/budgets called");
+    if matches!(
+        budget.category,
+        FinanceCategory::Sale | FinanceCategory::Milk
+    ) {
+        return Err(AppError::InvalidInput(format!(
+            "{} is an income category and cannot have a budget",
+            FinanceCategory::to_str(&budget.category)
+        )));
+    }
+    if !budget.monthly_amount.is_finite() || budget.monthly_amount < 0.0 {
+        return Err(AppError::InvalidInput(
+            "Budget amount must not be negative".into(),
+        ));
+    }
+
+    let conn = db.get_conn()?;
+    conn.execute(
+        "INSERT INTO budgets (category, monthly_amount) VALUES (?1, ?2) \
+         ON CONFLICT(category) DO UPDATE SET monthly_amount = excluded.monthly_amount, \
+         updated_at = CURRENT_TIMESTAMP",
+        params![
+            FinanceCategory::to_str(&budget.category),
+            budget.monthly_amount
+        ],
+    )?;
+
+    info!(category = ?budget.category, "Budget set");
+    Ok(HttpResponse::Ok().body("Budget set"))
+}
+
+/// Handler for removing a category's budget.
+///
+/// # HTTP Method
+/// - `DELETE /finance/budgets/{category}`
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown category or one without a budget.
+pub async fn delete_budget(
+    db: web::Data<DbPool>,
+    path: web::Path<String>,
+) -> Result<impl Responder, AppError> {
+    let category = path.into_inner();
+    debug!(category, "DELETE /finance/budgets/{{category}} called");
+    FinanceCategory::from_str(&category)
+        .map_err(|e| AppError::InvalidInput(format!("Unknown finance category {}", e)))?;
+
+    let conn = db.get_conn()?;
+    let affected = conn.execute("DELETE FROM budgets WHERE category = ?1", [&category])?;
+    if affected == 0 {
+        warn!(category, "Budget not found");
+        return Err(AppError::InvalidInput(format!(
+            "No budget set for {}",
+            category
+        )));
+    }
+
+    info!(category, "Budget deleted");
+    Ok(HttpResponse::Ok().body("Budget deleted"))
+}
+
+/// Builds the budget variance report for the month starting on `month`.
+///
+/// Actual spending is the month's expenses per category, converted to the
+/// base currency. Categories without a budget are listed only when money
+/// was spent on them.
+pub fn load_budget_report(conn: &Connection, month: NaiveDate) -> Result<BudgetReport, AppError> {
+    let end = month
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .ok_or_else(|| AppError::InvalidInput("Month is out of range".into()))?;
+    let budgets = load_budgets(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT category, SUM(amount * COALESCE(exchange_rate, 1)) FROM transactions \
+         WHERE kind = 'Expense' AND date BETWEEN ?1 AND ?2 GROUP BY category",
+    )?;
+    let actuals: Vec<(String, f64)> = stmt
+        .query_map(
+            [
+                month.format(DATE_FORMAT).to_string(),
+                end.format(DATE_FORMAT).to_string(),
+            ],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?
+        .collect::<Result<_, _>>()?;
+
+    let lines: Vec<BudgetVariance> = FinanceCategory::ALL
+        .iter()
+        .filter_map(|category| {
+            let budget = budgets
+                .iter()
+                .find(|b| &b.category == category)
+                .map(|b| b.monthly_amount);
+            let actual = actuals
+                .iter()
+                .find(|(name, _)| name == FinanceCategory::to_str(category))
+                .map(|(_, amount)| *amount);
+            if budget.is_none() && actual.is_none() {
+                return None;
+            }
+            Some(BudgetVariance::new(
+                category.clone(),
+                budget,
+                actual.unwrap_or(0.0),
+            ))
+        })
+        .collect();
+
+    Ok(BudgetReport {
+        month: month.format("%Y-%m").to_string(),
+        currency: load_settings(conn)?.base_currency,
+        total_budget: round_paise(lines.iter().filter_map(|l| l.budget).sum()),
+        total_actual: round_paise(lines.iter().map(|l| l.actual).sum()),
+        lines,
+    })
+}
+
+/// Handler for a month's spending against budget.
+///
+/// # HTTP Method
+/// - `GET /finance/budgets/variance?month=2026-04`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON `BudgetReport`; lines over budget have
+///   `over_budget` set.
+///
+/// # Errors
+/// - Returns HTTP 400 for a malformed month.
+pub async fn get_budget_variance(
+    db: web::Data<DbPool>,
+    query: web::Query<BudgetVarianceQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(month = ?query.month, "GET /finance/budgets/variance called");
+    let conn = db.get_conn()?;
+    let month = match &query.month {
+        Some(month) => {
+            NaiveDate::parse_from_str(&format!("{}-01", month), DATE_FORMAT).map_err(|_| {
+                AppError::InvalidInput(format!("month must be YYYY-MM, got '{}'", month))
+            })?
+        }
+        None => farm_today(&conn)?
+            .with_day(1)
+            .expect("day 1 exists in every month"),
+    };
+    let report = load_budget_report(&conn, month)?;
+    let over_budget = report.over_budget().count();
+    if over_budget > 0 {
+        warn!(month = %report.month, over_budget, "Categories over budget");
+    }
+    info!(
+        month = %report.month,
+        lines = report.lines.len(),
+        "Budget variance built"
+    );
+    Ok(HttpResponse::Ok().json(report))
+}
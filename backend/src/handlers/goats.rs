@@ -6,14 +6,24 @@
 //!
 //! All operations return structured errors using the `AppError` type to communicate
 //! clear feedback to API clients while logging internal errors for troubleshooting.
+//!
+//! Storage goes through the `GoatRepository` trait (see `crate::repository`).
 
-use crate::db::{DbPool, get_or_insert_disease, get_or_insert_vaccine, row_to_goat};
-use crate::db_helpers::{breed_to_str, gender_to_str};
+use crate::db::DbPool;
 use crate::errors::AppError;
+use crate::http_cache::{etag_for, if_none_match};
 use crate::models::NamePayload;
-use actix_web::{HttpResponse, Responder, web};
-use rusqlite::params;
-use shared::{Breed, Gender, GoatParams};
+use crate::repository::{GoatRepository, Goats};
+use actix_web::http::header;
+use actix_web::web::Bytes;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use futures_util::stream;
+use serde::Deserialize;
+use shared::bulk::BulkPatch;
+use shared::import::{BatchSummary, check_goat};
+use shared::lifecycle::LifecycleStage;
+use shared::{Goat, GoatUpdate, NewGoat};
+use std::collections::HashSet;
 use tracing::{debug, info, trace, warn};
 
 /// Handler for retrieving the full list of goats with complete details.
@@ -21,8 +31,13 @@ use tracing::{debug, info, trace, warn};
 /// # HTTP Method
 /// - `GET /goats`
 ///
+/// # Request
+/// - Optional `If-None-Match` header with a previously returned ETag.
+///
 /// # Success
-/// - Returns HTTP 200 with JSON array containing all goats including their vaccines and diseases.
+/// - Returns HTTP 200 with a JSON array of `Goat`s (each with its `id`,
+///   `created_at` and `updated_at`), tagged with an `ETag` header.
+/// - Returns HTTP 304 with an empty body if the `If-None-Match` tag still matches.
 ///
 /// # Errors
 /// - Returns appropriate error responses if database access or mapping fails.
@@ -31,25 +46,78 @@ use tracing::{debug, info, trace, warn};
 /// - Info: Entry point of request.
 /// - Trace: Loading each goat by ID.
 /// - Error: On any failure loading individual goats.
-pub async fn get_goats(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+pub async fn get_goats(req: HttpRequest, goats: Goats) -> Result<impl Responder, AppError> {
     debug!("GET /goats called");
-    let conn = db.get_conn()?;
-    debug!("Acquired connection in get_goats");
-    let mut stmt = conn
-        .prepare("SELECT * FROM goats")
-        .map_err(AppError::DbError)?;
-    let goats: Result<Vec<GoatParams>, rusqlite::Error> = stmt
-        .query_map([], |row| {
-            row_to_goat(row).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
-        })?
-        .collect();
+    let goats = goats.list()?;
 
-    let goats = goats?; // propagate or handle your error here
+    let body = serde_json::to_vec(&goats)?;
+    let etag = etag_for(&body);
+    if if_none_match(&req, &etag) {
+        debug!(%etag, "Goat list unchanged, returning 304");
+        return Ok(HttpResponse::NotModified()
+            .insert_header((header::ETAG, etag))
+            .finish());
+    }
 
     info!("Returning {} goats", goats.len());
     Ok(HttpResponse::Ok()
         .content_type("application/json")
-        .json(goats))
+        .insert_header((header::ETAG, etag))
+        .body(body))
+}
+
+/// Number of goats loaded from the database per streamed chunk.
+const STREAM_CHUNK_SIZE: i64 = 500;
+
+/// Loads the next page of goats with an ID greater than `after_id`,
+/// serialized as NDJSON (one goat per line).
+///
+/// # Returns
+/// The encoded lines and the last ID in the page, or `None` once exhausted.
+fn load_goat_chunk(
+    goats: &dyn GoatRepository,
+    after_id: i64,
+) -> Result<Option<(Bytes, i64)>, AppError> {
+    let rows = goats.list_after(after_id, STREAM_CHUNK_SIZE)?;
+
+    let Some(last_id) = rows.last().map(|goat| goat.id) else {
+        return Ok(None);
+    };
+    let mut body = Vec::new();
+    for goat in &rows {
+        serde_json::to_writer(&mut body, goat)?;
+        body.push(b'\n');
+    }
+    trace!(after_id, count = rows.len(), "Streaming goat chunk");
+    Ok(Some((Bytes::from(body), last_id)))
+}
+
+/// Handler for streaming the goat list as newline-delimited JSON.
+///
+/// # HTTP Method
+/// - `GET /goats/stream`
+///
+/// # Success
+/// - Returns HTTP 200 with `application/x-ndjson`, one goat object per line.
+///   Goats are read from the database in chunks of `STREAM_CHUNK_SIZE`, so
+///   clients can render large herds while the rest is still arriving.
+///
+/// # Errors
+/// - A database failure mid-stream aborts the response.
+///
+/// # Logs
+/// - Debug: Entry point of request.
+/// - Trace: Each chunk sent.
+pub async fn stream_goats(goats: Goats) -> impl Responder {
+    debug!("GET /goats/stream called");
+    let chunks = stream::try_unfold(0i64, move |after_id| {
+        let goats = goats.clone();
+        async move { load_goat_chunk(&*goats, after_id) }
+    });
+
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(chunks)
 }
 
 /// Handler for adding a new goat along with vaccinations and diseases.
@@ -58,10 +126,10 @@ pub async fn get_goats(db: web::Data<DbPool>) -> Result<impl Responder, AppError
 /// - `POST /goats`
 ///
 /// # Request
-/// - JSON payload conforming to `Goat` struct.
+/// - JSON `NewGoat`.
 ///
 /// # Success
-/// - Returns HTTP 201 on successful insertion.
+/// - Returns HTTP 201 with the stored `Goat`, including its assigned `id`.
 ///
 /// # Errors
 /// - Returns error responses if input validation or database operations fail.
@@ -72,153 +140,111 @@ pub async fn get_goats(db: web::Data<DbPool>) -> Result<impl Responder, AppError
 /// - Trace: Adding each vaccine and disease link.
 /// - Info: Upon successful commit.
 pub async fn add_goat(
-    db: web::Data<DbPool>,
-    new_goat: web::Json<GoatParams>,
+    goats: Goats,
+    new_goat: web::Json<NewGoat>,
 ) -> Result<impl Responder, AppError> {
     debug!(name = %new_goat.name, "POST /goats called");
-    let mut conn = db.get_conn()?;
-    info!("Connection recieved in add_goat instance");
-
-    let tx = conn.transaction()?;
-
-    tx.execute(
-        "INSERT INTO goats (breed, name, gender, offspring, cost, weight, current_price, diet, last_bred, health_status) \
-         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-        params![
-            Breed::to_str(&new_goat.breed),
-            &new_goat.name,
-            Gender::to_str(&new_goat.gender),
-            &new_goat.offspring,
-            &new_goat.cost,
-            &new_goat.weight,
-            &new_goat.current_price,
-            &new_goat.diet,
-            &new_goat.last_bred,
-            &new_goat.health_status,
-        ]
-    )?;
-
-    let goat_id = tx.last_insert_rowid();
-    debug!(goat_id, "Inserted goat base record");
+    let goat = goats.insert(&new_goat)?;
+    info!(
+        goat_id = goat.id,
+        "Successfully added new goat with associations"
+    );
+    Ok(HttpResponse::Created().json(goat))
+}
 
-    for vaccine in &new_goat.vaccinations {
-        let vaccine_id = get_or_insert_vaccine(&tx, vaccine)?;
-        tx.execute(
-            "INSERT INTO goat_vaccines (goat_id, vaccine_id) VALUES (?, ?)",
-            &[&goat_id, &vaccine_id],
-        )?;
-        info!(goat_id, vaccine_id, "Linked vaccine");
+/// Checks a batch of goats to add: it must not be empty, every goat must
+/// pass `shared::import::check_goat`, and no name may repeat within the
+/// batch or the herd.
+///
+/// # Errors
+/// - `AppError::InvalidInput` listing every offending goat.
+pub fn check_batch(goats: &Goats, batch: &[NewGoat]) -> Result<(), AppError> {
+    if batch.is_empty() {
+        return Err(AppError::InvalidInput("The batch contains no goats".into()));
     }
 
-    for disease in &new_goat.diseases {
-        let disease_id = get_or_insert_disease(&tx, disease)?;
-        tx.execute(
-            "INSERT INTO goat_diseases (goat_id, disease_id) VALUES (?, ?)",
-            &[&goat_id, &disease_id],
-        )?;
-        trace!(goat_id, disease_id, "Linked disease");
+    let mut names: HashSet<String> = goats.list()?.into_iter().map(|g| g.params.name).collect();
+    let mut problems = Vec::new();
+    for (i, goat) in batch.iter().enumerate() {
+        let mut errors = check_goat(goat);
+        if !names.insert(goat.name.clone()) {
+            errors.push(format!("A goat named {} already exists", goat.name));
+        }
+        if !errors.is_empty() {
+            problems.push(format!("Goat {}: {}", i + 1, errors.join("; ")));
+        }
     }
+    if !problems.is_empty() {
+        warn!(rejected = problems.len(), "Rejected goat batch");
+        return Err(AppError::InvalidInput(problems.join("\n")));
+    }
+    Ok(())
+}
 
-    tx.commit()?;
-    info!(goat_id, "Successfully added new goat with associations");
-    Ok(HttpResponse::Created().body("Goat added"))
+/// Handler for adding many goats at once, e.g. from the import wizard.
+///
+/// # HTTP Method
+/// - `POST /goats/batch`
+///
+/// # Request
+/// - JSON array of `NewGoat`.
+///
+/// # Success
+/// - Returns HTTP 201 with a `BatchSummary`. Either every goat is added or,
+///   on any error, none of them.
+///
+/// # Errors
+/// - Returns HTTP 400 for an empty batch, a goat failing
+///   `shared::import::check_goat`, or a name that is repeated in the batch
+///   or already in the herd. The message lists every offending goat.
+pub async fn add_goats(
+    goats: Goats,
+    batch: web::Json<Vec<NewGoat>>,
+) -> Result<impl Responder, AppError> {
+    debug!(count = batch.len(), "POST /goats/batch called");
+    check_batch(&goats, &batch)?;
+
+    let added = goats.insert_batch(&batch)?.len();
+    info!(added, "Added goat batch");
+    Ok(HttpResponse::Created().json(BatchSummary { added }))
 }
 
-/// Handler for updating an existing goat and its relations by ID.
+/// Handler for replacing an existing goat and its relations, found by name.
 ///
 /// # HTTP Method
 /// - `PUT /goats`
 ///
 /// # Request
-/// - JSON payload conforming to `Goat` struct, with `id` field.
+/// - JSON `NewGoat` with every field; the goat is matched by `name`.
 ///
 /// # Success
 /// - Returns HTTP 200 on successful update.
 ///
 /// # Errors
-/// - Returns HTTP 400 for missing `id` or if goat does not exist.
+/// - Returns HTTP 400 if no goat has that name.
 /// - Returns other errors on database failure.
 ///
 /// # Logs
-/// - Info: Receipt of update, including `id`.
+/// - Info: Receipt of update, including the name.
 /// - Debug: After base update, and clearing old relations.
 /// - Trace: Adding vaccine and disease links.
 /// - Warn/Error: For missing record or update failures.
 pub async fn update_goat(
-    db: web::Data<DbPool>,
-    goat: web::Json<GoatParams>,
+    goats: Goats,
+    goat: web::Json<NewGoat>,
 ) -> Result<impl Responder, AppError> {
     let name = &goat.name;
 
     info!(goat_name = name, "PUT /goats called");
 
-    let mut conn = db.get_conn()?;
-    let tx = conn.transaction()?;
-
-    debug!("Params loaded in update_goat");
-
-    let affected = tx.execute(
-        "UPDATE goats 
-         SET breed = ?, gender = ?, offspring = ?, cost = ?, weight = ?, current_price = ?, diet = ?, last_bred = ?, health_status = ? 
-         WHERE name = ?",
-        params![
-            Breed::to_str(&goat.breed),
-            Gender::to_str(&goat.gender),
-            &goat.offspring,
-            &goat.cost,
-            &goat.weight,
-            &goat.current_price,
-            &goat.diet,
-            &goat.last_bred,
-            &goat.health_status,
-            &goat.name,
-        ],
-    )?;
-
-    if affected == 0 {
+    if !goats.update(&goat)? {
         warn!(goat_name = name, "No goat found for update");
         return Err(AppError::InvalidInput(format!(
             "No goat found with name {}",
             name
         )));
-    } else {
-        // Delete existing links for the goat
-        tx.execute(
-            "DELETE FROM goat_vaccines WHERE goat_id IN (SELECT id FROM goats WHERE name = ?1 LIMIT 1)",
-            [&name],
-        )?;
-        tx.execute(
-            "DELETE FROM goat_diseases WHERE goat_id IN (SELECT id FROM goats WHERE name = ?1 LIMIT 1)",
-            [&name],
-        )?;
-        debug!(goat_name = name, "Cleared old vaccine and disease links");
-
-        // Fetch goat id
-        let goat_id: i64 = tx.query_row(
-            "SELECT id FROM goats WHERE name = ?1 LIMIT 1",
-            [&name],
-            |row| row.get(0),
-        )?;
-
-        // Insert updated vaccine links
-        for vaccine in &goat.vaccinations {
-            let vaccine_id = get_or_insert_vaccine(&tx, vaccine)?;
-            tx.execute(
-                "INSERT OR IGNORE INTO goat_vaccines (goat_id, vaccine_id) VALUES (?, ?)",
-                &[&goat_id, &vaccine_id],
-            )?;
-        }
-        // Insert updated disease links
-        for disease in &goat.diseases {
-            let disease_id = get_or_insert_disease(&tx, disease)?;
-            tx.execute(
-                "INSERT OR IGNORE INTO goat_diseases (goat_id, disease_id) VALUES (?, ?)",
-                &[&goat_id, &disease_id],
-            )?;
-        }
     }
 
-    tx.commit()?;
     info!(
         goat_name = name,
         "Updated goat and associations successfully"
@@ -226,6 +252,136 @@ pub async fn update_goat(
     Ok(HttpResponse::Ok().body("Goat updated"))
 }
 
+/// Handler for changing some of a goat's fields, found by ID.
+///
+/// # HTTP Method
+/// - `PATCH /goats/{id}`
+///
+/// # Request
+/// - JSON `GoatUpdate` with only the fields to change. Fields left out keep
+///   their stored values, so concurrent edits to them are not lost.
+///   `vaccinations` and `diseases`, when given, replace the goat's links.
+///
+/// # Success
+/// - Returns HTTP 200 with the updated `Goat`.
+///
+/// # Errors
+/// - Returns HTTP 400 if no goat has that ID, the changed goat fails
+///   `shared::import::check_goat`, or the new name belongs to another goat.
+pub async fn patch_goat(
+    goats: Goats,
+    id: web::Path<i64>,
+    update: web::Json<GoatUpdate>,
+) -> Result<impl Responder, AppError> {
+    let id = id.into_inner();
+    debug!(goat_id = id, ?update, "PATCH /goats/{{id}} called");
+
+    let Some(goat) = goats.get(id)? else {
+        warn!(goat_id = id, "No goat found for patch");
+        return Err(AppError::InvalidInput(format!(
+            "No goat found with ID {}",
+            id
+        )));
+    };
+    let errors = check_goat(&update.apply(&goat));
+    if !errors.is_empty() {
+        return Err(AppError::InvalidInput(errors.join("; ")));
+    }
+    if let Some(name) = &update.name
+        && goats.list()?.iter().any(|g| g.id != id && g.name == *name)
+    {
+        return Err(AppError::InvalidInput(format!(
+            "A goat named {} already exists",
+            name
+        )));
+    }
+
+    let goat = goats
+        .patch(id, &update)?
+        .ok_or_else(|| AppError::InvalidInput(format!("No goat found with ID {}", id)))?;
+    info!(goat_id = id, "Patched goat");
+    Ok(HttpResponse::Ok().json(goat))
+}
+
+/// Query parameters accepted by `PATCH /goats`.
+#[derive(Deserialize)]
+pub struct BulkPatchQuery {
+    /// Only list the goats the patch would change.
+    #[serde(default)]
+    pub preview: bool,
+}
+
+/// Handler for changing the same fields on every goat a filter selects,
+/// e.g. moving all milkers onto a new diet.
+///
+/// # HTTP Method
+/// - `PATCH /goats`, or `PATCH /goats?preview=true` to change nothing.
+///
+/// # Request
+/// - JSON `BulkPatch`.
+///
+/// # Success
+/// - Returns HTTP 200 with the selected `Goat`s in name order: as they are
+///   for a preview, otherwise as updated. Either every selected goat is
+///   changed or, on any error, none of them.
+///
+/// # Errors
+/// - Returns HTTP 400 if the patch fails `BulkPatch::validate` or would
+///   leave a selected goat failing `shared::import::check_goat`. The message
+///   lists every offending goat.
+pub async fn bulk_patch_goats(
+    db: web::Data<DbPool>,
+    goats: Goats,
+    query: web::Query<BulkPatchQuery>,
+    patch: web::Json<BulkPatch>,
+) -> Result<impl Responder, AppError> {
+    debug!(preview = query.preview, ?patch, "PATCH /goats called");
+    patch.validate().map_err(AppError::InvalidInput)?;
+
+    let staged: Option<HashSet<i64>> = match &patch.stage {
+        Some(stage) => {
+            let conn = db.get_conn()?;
+            let mut stmt = conn.prepare(
+                "SELECT id FROM goats WHERE lifecycle_stage = ?1 AND deleted_at IS NULL",
+            )?;
+            let ids = stmt
+                .query_map([LifecycleStage::to_str(stage)], |row| row.get(0))?
+                .collect::<Result<_, _>>()?;
+            Some(ids)
+        }
+        None => None,
+    };
+    let mut selected: Vec<Goat> = goats
+        .list()?
+        .into_iter()
+        .filter(|goat| staged.as_ref().is_none_or(|ids| ids.contains(&goat.id)))
+        .filter(|goat| patch.filter.matches(goat))
+        .collect();
+    selected.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let problems: Vec<String> = selected
+        .iter()
+        .filter_map(|goat| {
+            let errors = check_goat(&patch.update.apply(goat));
+            (!errors.is_empty()).then(|| format!("{}: {}", goat.name, errors.join("; ")))
+        })
+        .collect();
+    if !problems.is_empty() {
+        warn!(rejected = problems.len(), "Rejected bulk goat patch");
+        return Err(AppError::InvalidInput(problems.join("\n")));
+    }
+    if query.preview {
+        info!(selected = selected.len(), "Previewed bulk goat patch");
+        return Ok(HttpResponse::Ok().json(selected));
+    }
+
+    let ids: Vec<i64> = selected.iter().map(|goat| goat.id).collect();
+    let mut patched = goats.patch_batch(&ids, &patch.update)?;
+    patched.sort_by(|a, b| a.name.cmp(&b.name));
+    info!(patched = patched.len(), "Applied bulk goat patch");
+    Ok(HttpResponse::Ok().json(patched))
+}
+
 /// Handler for deleting a goat by ID.
 ///
 /// # HTTP Method
@@ -235,7 +391,8 @@ pub async fn update_goat(
 /// - JSON payload containing the goat's `id`.
 ///
 /// # Success
-/// - Returns HTTP 200 when deletion is successful.
+/// - Returns HTTP 200 with the ID of the trash entry the goat moved to, for
+///   `POST /trash/{id}/restore`.
 ///
 /// # Errors
 /// - Returns HTTP 400 if no goat matches the provided ID.
@@ -245,22 +402,19 @@ pub async fn update_goat(
 /// - Warn: If goat not found.
 /// - Info: Successful deletion.
 pub async fn delete_goat(
-    db: web::Data<DbPool>,
+    goats: Goats,
     name: web::Json<NamePayload>,
 ) -> Result<impl Responder, AppError> {
     info!(goat_id = name.name, "DELETE /goats called");
 
-    let conn = db.get_conn()?;
-    let affected = conn.execute("DELETE FROM goats WHERE name = ?", &[&name.name])?;
-
-    if affected == 0 {
+    let Some(trash_id) = goats.delete(&name.name)? else {
         warn!(goat_id = name.name, "Goat not found for deletion");
         return Err(AppError::InvalidInput(format!(
             "No goat found with name {}",
             name.name
         )));
-    }
+    };
 
-    info!(goat_id = name.name, "Goat deleted successfully");
-    Ok(HttpResponse::Ok().body("Goat deleted"))
+    info!(goat_id = name.name, trash_id, "Goat deleted successfully");
+    Ok(HttpResponse::Ok().json(trash_id))
 }
@@ -0,0 +1,318 @@
+//! This module handles GPS collar positions, grazing geofences, and alerts
+//! for goats that have strayed outside them.
+//!
+//! When a new position takes a goat from inside a geofence to outside all of
+//! them, a task due today is created so the escape shows up in the task list.
+
+use crate::auth::authenticate;
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::scale::{READ_AT_FORMAT, parse_read_at};
+use crate::handlers::settings::farm_timezone;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use shared::gps::{GeoPoint, Geofence, GoatPosition, PositionInput};
+use shared::time::{format_local, today_in};
+use tracing::{debug, info, warn};
+
+/// Loads every geofence, ordered by name.
+fn load_geofences(conn: &Connection) -> Result<Vec<Geofence>, AppError> {
+    let mut stmt = conn.prepare("SELECT id, name, boundary FROM geofences ORDER BY name")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    rows.into_iter()
+        .map(|(id, name, boundary)| {
+            Ok(Geofence {
+                id: Some(id),
+                name,
+                boundary: serde_json::from_str(&boundary)?,
+            })
+        })
+        .collect()
+}
+
+/// Places a goat's position relative to `fences`.
+fn locate(
+    fences: &[Geofence],
+    goat_name: String,
+    position: GeoPoint,
+    recorded_at: DateTime<Utc>,
+) -> GoatPosition {
+    let geofence = fences
+        .iter()
+        .find(|fence| fence.contains(&position))
+        .map(|fence| fence.name.clone());
+    GoatPosition {
+        outside: !fences.is_empty() && geofence.is_none(),
+        goat_name,
+        position,
+        recorded_at,
+        geofence,
+    }
+}
+
+/// Last known position of every collared goat, ordered by goat name.
+fn load_latest_positions(conn: &Connection) -> Result<Vec<GoatPosition>, AppError> {
+    let fences = load_geofences(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT g.name, p.lat, p.lon, p.recorded_at \
+         FROM goat_positions p JOIN goats g ON g.id = p.goat_id AND g.deleted_at IS NULL \
+         WHERE p.id = (SELECT latest.id FROM goat_positions latest \
+                       WHERE latest.goat_id = p.goat_id \
+                       ORDER BY latest.recorded_at DESC, latest.id DESC LIMIT 1) \
+         ORDER BY g.name",
+    )?;
+    let positions = stmt
+        .query_map([], |row| {
+            Ok(locate(
+                &fences,
+                row.get(0)?,
+                GeoPoint {
+                    lat: row.get(1)?,
+                    lon: row.get(2)?,
+                },
+                row.get(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(positions)
+}
+
+/// Handler for recording a collar position.
+///
+/// # HTTP Method
+/// - `POST /gps/positions`
+///
+/// # Request
+/// - `X-Api-Key` header (or `Authorization: Bearer`) with a device API key.
+/// - JSON `PositionInput`; the tag must belong to a goat.
+///
+/// # Success
+/// - Returns HTTP 201 with the goat's `GoatPosition`. If the goat has just
+///   left the geofences, a task to check on it is created.
+///
+/// # Errors
+/// - Returns HTTP 401 for a missing, unknown or revoked API key.
+/// - Returns HTTP 400 for an unknown tag, an invalid coordinate or a
+///   malformed time.
+pub async fn add_position(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    input: web::Json<PositionInput>,
+) -> Result<impl Responder, AppError> {
+    debug!(tag_id = %input.tag_id, "POST /gps/positions called");
+    let mut conn = db.get_conn()?;
+    let api_key_id = authenticate(&conn, &req)?;
+
+    let position = GeoPoint {
+        lat: input.lat,
+        lon: input.lon,
+    };
+    position.validate().map_err(AppError::InvalidInput)?;
+    let tz = farm_timezone(&conn)?;
+    let recorded_at = match &input.recorded_at {
+        Some(value) => parse_read_at(value, tz)?,
+        None => Utc::now(),
+    };
+    let tag_id = input.tag_id.trim();
+    let (goat_id, goat_name): (i64, String) = conn
+        .query_row(
+            "SELECT id, name FROM goats WHERE tag_id = ?1 AND deleted_at IS NULL",
+            [tag_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::InvalidInput(format!("No goat found with tag {}", tag_id)))?;
+
+    let tx = conn.transaction()?;
+    let fences = load_geofences(&tx)?;
+    let previous: Option<GeoPoint> = tx
+        .query_row(
+            "SELECT lat, lon FROM goat_positions WHERE goat_id = ?1 \
+             ORDER BY recorded_at DESC, id DESC LIMIT 1",
+            [goat_id],
+            |row| {
+                Ok(GeoPoint {
+                    lat: row.get(0)?,
+                    lon: row.get(1)?,
+                })
+            },
+        )
+        .optional()?;
+    let was_inside = previous.is_some_and(|p| fences.iter().any(|f| f.contains(&p)));
+    tx.execute(
+        "INSERT INTO goat_positions (goat_id, lat, lon, recorded_at) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            goat_id,
+            position.lat,
+            position.lon,
+            recorded_at.format(READ_AT_FORMAT).to_string()
+        ],
+    )?;
+    let located = locate(&fences, goat_name, position, recorded_at);
+    if was_inside && located.outside {
+        warn!(
+            goat_id,
+            lat = position.lat,
+            lon = position.lon,
+            "Goat left the geofences"
+        );
+        tx.execute(
+            "INSERT INTO tasks (title, notes, goat_id, due_date) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                format!("Check on {}: outside geofence", located.goat_name),
+                format!(
+                    "Last seen at {:.5}, {:.5} on {}",
+                    position.lat,
+                    position.lon,
+                    format_local(&recorded_at, tz)
+                ),
+                goat_id,
+                today_in(tz).format(DATE_FORMAT).to_string()
+            ],
+        )?;
+    }
+    tx.commit()?;
+
+    info!(
+        goat_id,
+        api_key_id,
+        outside = located.outside,
+        "GPS position stored"
+    );
+    Ok(HttpResponse::Created().json(located))
+}
+
+/// Handler for the last known position of every collared goat.
+///
+/// # HTTP Method
+/// - `GET /gps/positions`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `GoatPosition`, ordered by goat.
+pub async fn get_positions(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /gps/positions called");
+    let conn = db.get_conn()?;
+    let positions = load_latest_positions(&conn)?;
+
+    info!("Returning {} goat positions", positions.len());
+    Ok(HttpResponse::Ok().json(positions))
+}
+
+/// Handler for goats whose last known position is outside every geofence.
+///
+/// # HTTP Method
+/// - `GET /gps/alerts`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `GoatPosition`; empty when no
+///   geofences are defined.
+pub async fn get_alerts(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /gps/alerts called");
+    let conn = db.get_conn()?;
+    let alerts: Vec<GoatPosition> = load_latest_positions(&conn)?
+        .into_iter()
+        .filter(|p| p.outside)
+        .collect();
+
+    info!("Returning {} geofence alerts", alerts.len());
+    Ok(HttpResponse::Ok().json(alerts))
+}
+
+/// Handler for listing geofences.
+///
+/// # HTTP Method
+/// - `GET /gps/geofences`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `Geofence`, ordered by name.
+pub async fn get_geofences(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /gps/geofences called");
+    let conn = db.get_conn()?;
+    let fences = load_geofences(&conn)?;
+
+    info!("Returning {} geofences", fences.len());
+    Ok(HttpResponse::Ok().json(fences))
+}
+
+/// Handler for defining a geofence.
+///
+/// # HTTP Method
+/// - `POST /gps/geofences`
+///
+/// # Success
+/// - Returns HTTP 201 with the stored `Geofence`, including its ID.
+///
+/// # Errors
+/// - Returns HTTP 400 for an empty or duplicate name, fewer than three
+///   points, or an invalid coordinate.
+pub async fn add_geofence(
+    db: web::Data<DbPool>,
+    fence: web::Json<Geofence>,
+) -> Result<impl Responder, AppError> {
+    debug!(name = %fence.name, "POST /gps/geofences called");
+    let mut fence = fence.into_inner();
+    fence.name = fence.name.trim().to_string();
+    fence.validate().map_err(AppError::InvalidInput)?;
+    let conn = db.get_conn()?;
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM geofences WHERE name = ?1)",
+        [&fence.name],
+        |row| row.get(0),
+    )?;
+    if exists {
+        return Err(AppError::InvalidInput(format!(
+            "A geofence named {} already exists",
+            fence.name
+        )));
+    }
+    conn.execute(
+        "INSERT INTO geofences (name, boundary) VALUES (?1, ?2)",
+        params![fence.name, serde_json::to_string(&fence.boundary)?],
+    )?;
+    fence.id = Some(conn.last_insert_rowid());
+
+    info!(
+        geofence_id = fence.id,
+        points = fence.boundary.len(),
+        "Geofence added"
+    );
+    Ok(HttpResponse::Created().json(fence))
+}
+
+/// Handler for removing a geofence.
+///
+/// # HTTP Method
+/// - `DELETE /gps/geofences/{id}`
+///
+/// # Success
+/// - Returns HTTP 200 once the geofence is removed.
+///
+/// # Errors
+/// - Returns HTTP 400 if no geofence has the given ID.
+pub async fn delete_geofence(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(geofence_id = id, "DELETE /gps/geofences/{{id}} called");
+    let conn = db.get_conn()?;
+    if conn.execute("DELETE FROM geofences WHERE id = ?1", [id])? == 0 {
+        return Err(AppError::InvalidInput(format!(
+            "No geofence found with ID {}",
+            id
+        )));
+    }
+
+    info!(geofence_id = id, "Geofence deleted");
+    Ok(HttpResponse::Ok().body("Geofence deleted"))
+}
@@ -0,0 +1,141 @@
+//! This module handles grazing paddocks and proposes a weekly pasture
+//! rotation for the herd's grazing groups (see `crate::rotation`).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::settings::farm_today;
+use crate::rotation::{load_groups, load_paddocks, plan_rotation};
+use crate::scheduler::DATE_FORMAT;
+use actix_web::{HttpResponse, Responder, web};
+use chrono::NaiveDate;
+use rusqlite::params;
+use serde::Deserialize;
+use shared::grazing::{MAX_ROTATION_WEEKS, Paddock};
+use tracing::{debug, info};
+
+/// Weeks planned when the request does not say.
+const DEFAULT_ROTATION_WEEKS: u32 = 8;
+
+/// Query parameters accepted by `GET /grazing/rotation`.
+#[derive(Deserialize)]
+pub struct RotationQuery {
+    /// First day of the plan (`YYYY-MM-DD`); defaults to today.
+    pub start: Option<String>,
+    pub weeks: Option<u32>,
+}
+
+/// Handler for listing paddocks.
+///
+/// # HTTP Method
+/// - `GET /grazing/paddocks`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `Paddock` ordered by name.
+pub async fn get_paddocks(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /grazing/paddocks called");
+    let conn = db.get_conn()?;
+    let paddocks = load_paddocks(&conn)?;
+
+    info!("Returning {} paddocks", paddocks.len());
+    Ok(HttpResponse::Ok().json(paddocks))
+}
+
+/// Handler for adding a paddock.
+///
+/// # HTTP Method
+/// - `POST /grazing/paddocks`
+///
+/// # Success
+/// - Returns HTTP 201 with the stored `Paddock`, including its ID.
+///
+/// # Errors
+/// - Returns HTTP 400 for an empty or duplicate name, a rest period out of
+///   range, a non-positive area or capacity, or a malformed last grazing
+///   date.
+pub async fn add_paddock(
+    db: web::Data<DbPool>,
+    paddock: web::Json<Paddock>,
+) -> Result<impl Responder, AppError> {
+    debug!(name = %paddock.name, "POST /grazing/paddocks called");
+    let mut paddock = paddock.into_inner();
+    paddock.name = paddock.name.trim().to_string();
+    paddock.validate().map_err(AppError::InvalidInput)?;
+    if let Some(day) = &paddock.last_grazed_on
+        && NaiveDate::parse_from_str(day, DATE_FORMAT).is_err()
+    {
+        return Err(AppError::InvalidInput(format!(
+            "last_grazed_on must be YYYY-MM-DD, got '{}'",
+            day
+        )));
+    }
+    let conn = db.get_conn()?;
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM paddocks WHERE name = ?1)",
+        [&paddock.name],
+        |row| row.get(0),
+    )?;
+    if exists {
+        return Err(AppError::InvalidInput(format!(
+            "A paddock named {} already exists",
+            paddock.name
+        )));
+    }
+    conn.execute(
+        "INSERT INTO paddocks (name, area_m2, capacity, rest_days, last_grazed_on)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            paddock.name,
+            paddock.area_m2,
+            paddock.capacity,
+            paddock.rest_days,
+            paddock.last_grazed_on
+        ],
+    )?;
+    paddock.id = Some(conn.last_insert_rowid());
+
+    info!(paddock_id = paddock.id, "Paddock added");
+    Ok(HttpResponse::Created().json(paddock))
+}
+
+/// Handler for proposing a pasture rotation.
+///
+/// # HTTP Method
+/// - `GET /grazing/rotation?start=2026-04-06&weeks=8`
+///
+/// # Success
+/// - Returns HTTP 200 with a `RotationPlan`: for each week from `start`
+///   (today by default), the paddock each pen's goats graze, and the weeks
+///   where a paddock is grazed before its rest period is over, is too small
+///   for the group, or no paddock is free.
+///
+/// # Errors
+/// - Returns HTTP 400 for a malformed `start`, or `weeks` outside 1 to
+///   `MAX_ROTATION_WEEKS` (8 by default).
+pub async fn get_rotation(
+    db: web::Data<DbPool>,
+    query: web::Query<RotationQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(start = ?query.start, weeks = ?query.weeks, "GET /grazing/rotation called");
+    let conn = db.get_conn()?;
+    let start = match &query.start {
+        Some(value) => NaiveDate::parse_from_str(value, DATE_FORMAT).map_err(|_| {
+            AppError::InvalidInput(format!("start must be YYYY-MM-DD, got '{}'", value))
+        })?,
+        None => farm_today(&conn)?,
+    };
+    let weeks = query.weeks.unwrap_or(DEFAULT_ROTATION_WEEKS);
+    if !(1..=MAX_ROTATION_WEEKS).contains(&weeks) {
+        return Err(AppError::InvalidInput(format!(
+            "weeks must be between 1 and {}, got {}",
+            MAX_ROTATION_WEEKS, weeks
+        )));
+    }
+    let plan = plan_rotation(&load_paddocks(&conn)?, &load_groups(&conn)?, start, weeks);
+
+    info!(
+        weeks,
+        conflicts = plan.conflicts.len(),
+        "Returning rotation plan"
+    );
+    Ok(HttpResponse::Ok().json(plan))
+}
@@ -0,0 +1,348 @@
+//! This module handles weight history and growth benchmarking: each goat's
+//! latest weight is compared to its breed's reference growth curve (see
+//! `shared::growth`) and goats below the alert percentile are flagged.
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::estimation::{GoatPhoto, WeightEstimator};
+use crate::events::{EventLog, next_id};
+use crate::handlers::parse_date;
+use crate::handlers::settings::farm_today;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use chrono::NaiveDate;
+use rusqlite::{Connection, OptionalExtension};
+use serde::Deserialize;
+use shared::events::DomainEvent;
+use shared::growth::{
+    GROWTH_ALERT_PERCENTILE, GrowthBenchmark, GrowthHistory, WeightRecord, expected_weight,
+    weight_percentile,
+};
+use shared::{Breed, GoatUpdate};
+use tracing::{debug, info, trace};
+
+/// Largest photo accepted for weight estimation.
+pub const MAX_PHOTO_BYTES: usize = 10 * 1024 * 1024;
+
+/// Query parameters for `POST /growth/estimate`.
+#[derive(Deserialize, Debug)]
+pub struct EstimateQuery {
+    /// Length of the reference object in the photo, in centimetres.
+    pub reference_cm: f64,
+}
+
+/// Days from `date_of_birth` to `on`, if both parse.
+fn age_on(date_of_birth: Option<&str>, on: &str) -> Option<i64> {
+    let dob = NaiveDate::parse_from_str(date_of_birth?, DATE_FORMAT).ok()?;
+    let on = NaiveDate::parse_from_str(on, DATE_FORMAT).ok()?;
+    Some((on - dob).num_days())
+}
+
+/// Handler for fetching a goat's weight history.
+///
+/// # HTTP Method
+/// - `GET /growth/weights/{name}`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON `GrowthHistory`, oldest weighing first.
+///
+/// # Errors
+/// - Returns HTTP 400 if no goat has the given name.
+pub async fn get_weight_history(
+    db: web::Data<DbPool>,
+    path: web::Path<String>,
+) -> Result<impl Responder, AppError> {
+    let name = path.into_inner();
+    debug!(goat = %name, "GET /growth/weights/{{name}} called");
+    let conn = db.get_conn()?;
+    let (goat_id, breed, date_of_birth): (i64, String, Option<String>) = conn
+        .query_row(
+            "SELECT id, breed, date_of_birth FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [&name],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::InvalidInput(format!("No goat found with name {}", name)))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, weighed_on, weight, estimated FROM weight_records WHERE goat_id = ?1 \
+         ORDER BY weighed_on, id",
+    )?;
+    let records: Vec<WeightRecord> = stmt
+        .query_map([goat_id], |row| {
+            let weighed_on: String = row.get(1)?;
+            Ok(WeightRecord {
+                id: row.get(0)?,
+                goat_name: name.clone(),
+                age_days: age_on(date_of_birth.as_deref(), &weighed_on),
+                weighed_on,
+                weight: row.get(2)?,
+                estimated: row.get(3)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    info!("Returning {} weighings for {}", records.len(), name);
+    Ok(HttpResponse::Ok().json(GrowthHistory {
+        goat_name: name,
+        breed: Breed::from_str(&breed),
+        date_of_birth,
+        records,
+    }))
+}
+
+/// Records a weighing of goat `goat_id` through `events` and, if it is now
+/// the goat's most recent measured one, updates `goats.weight` to match.
+/// Estimated weighings are only logged. Returns the new weight record ID.
+pub fn insert_weight(
+    conn: &Connection,
+    events: &EventLog,
+    goat_id: i64,
+    weighed_on: &str,
+    weight: f64,
+    estimated: bool,
+) -> Result<i64, AppError> {
+    let goat_name: String =
+        conn.query_row("SELECT name FROM goats WHERE id = ?1", [goat_id], |row| {
+            row.get(0)
+        })?;
+    let record_id = next_id(conn, "weight_records")?;
+    let record = WeightRecord {
+        id: Some(record_id),
+        goat_name,
+        weighed_on: weighed_on.to_string(),
+        weight,
+        age_days: None,
+        estimated,
+    };
+    events.apply(conn, &DomainEvent::WeightRecorded { goat_id, record })?;
+    if estimated {
+        return Ok(record_id);
+    }
+
+    // Keep goats.weight in step with the most recent measured weighing
+    if is_current_weighing(conn, goat_id, record_id)? {
+        let update = GoatUpdate {
+            weight: Some(weight),
+            ..GoatUpdate::default()
+        };
+        events.apply(conn, &DomainEvent::GoatUpdated { goat_id, update })?;
+        trace!(goat_id, "Updated current weight");
+    }
+    Ok(record_id)
+}
+
+/// Whether weighing `record_id` is goat `goat_id`'s most recent measured one.
+fn is_current_weighing(conn: &Connection, goat_id: i64, record_id: i64) -> Result<bool, AppError> {
+    let latest: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM weight_records WHERE goat_id = ?1 AND estimated = 0 \
+             ORDER BY weighed_on DESC, id DESC LIMIT 1",
+            [goat_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(latest == Some(record_id))
+}
+
+/// Handler for recording a weighing.
+///
+/// If this is the goat's most recent measured weighing, its current weight is
+/// updated too. Weights estimated from a photo (`estimated: true`) are only
+/// logged.
+///
+/// # HTTP Method
+/// - `POST /growth/weights`
+///
+/// # Success
+/// - Returns HTTP 201 on successful insertion.
+///
+/// # Errors
+/// - Returns HTTP 400 if the weight is not positive, the date is malformed or
+///   in the future, or no goat has the given name.
+pub async fn add_weight(
+    db: web::Data<DbPool>,
+    events: EventLog,
+    record: web::Json<WeightRecord>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat = %record.goat_name, "POST /growth/weights called");
+    if record.weight <= 0.0 {
+        return Err(AppError::InvalidInput("weight must be positive".into()));
+    }
+    let mut conn = db.get_conn()?;
+    if parse_date(&record.weighed_on, "weighed_on")? > farm_today(&conn)? {
+        return Err(AppError::InvalidInput(
+            "weighed_on cannot be in the future".into(),
+        ));
+    }
+    let tx = conn.transaction()?;
+    let goat_id: i64 = tx
+        .query_row(
+            "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [&record.goat_name],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!("No goat found with name {}", record.goat_name))
+        })?;
+    let record_id = insert_weight(
+        &tx,
+        &events,
+        goat_id,
+        &record.weighed_on,
+        record.weight,
+        record.estimated,
+    )?;
+    tx.commit()?;
+
+    info!(
+        record_id,
+        goat_id,
+        estimated = record.estimated,
+        "Weighing recorded"
+    );
+    Ok(HttpResponse::Created().body("Weight added"))
+}
+
+/// Handler for estimating a goat's weight from a photo.
+///
+/// The photo is passed to the registered `WeightEstimator` (see
+/// `crate::estimation`); nothing is stored. The dashboard offers the estimate
+/// as a weighing marked as estimated.
+///
+/// # HTTP Method
+/// - `POST /growth/estimate?reference_cm=30`
+///
+/// # Request
+/// - The raw image (at most `MAX_PHOTO_BYTES`) with an `image/*`
+///   `Content-Type`, showing the goat beside a reference object
+///   `reference_cm` centimetres long.
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON `WeightEstimate`, in kilograms.
+///
+/// # Errors
+/// - Returns HTTP 400 if no estimator is configured, the photo is empty or
+///   not an image, or the reference length is not positive.
+/// - Returns HTTP 502 if the estimation service fails or answers nonsense.
+pub async fn estimate_weight(
+    estimator: Option<web::Data<dyn WeightEstimator>>,
+    req: HttpRequest,
+    query: web::Query<EstimateQuery>,
+    body: web::Bytes,
+) -> Result<impl Responder, AppError> {
+    debug!(bytes = body.len(), "POST /growth/estimate called");
+    let estimator = estimator.ok_or_else(|| {
+        AppError::InvalidInput("No weight estimation service is configured".into())
+    })?;
+    if query.reference_cm <= 0.0 {
+        return Err(AppError::InvalidInput(
+            "reference_cm must be positive".into(),
+        ));
+    }
+    if body.is_empty() {
+        return Err(AppError::InvalidInput("The photo is empty".into()));
+    }
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    if !content_type.starts_with("image/") {
+        return Err(AppError::InvalidInput(format!(
+            "The photo must be an image, got '{}'",
+            content_type
+        )));
+    }
+
+    let photo = GoatPhoto {
+        image: body.to_vec(),
+        content_type,
+        reference_cm: query.reference_cm,
+    };
+    let estimate = web::block(move || estimator.estimate(&photo))
+        .await
+        .map_err(|e| AppError::Upstream(format!("Weight estimation could not run: {}", e)))??;
+
+    info!(
+        weight = estimate.weight,
+        confidence = ?estimate.confidence,
+        "Weight estimated from photo"
+    );
+    Ok(HttpResponse::Ok().json(estimate))
+}
+
+/// Benchmarks goats against their breed standard, unsorted. See
+/// `get_growth_benchmarks` for which weight is used and which goats are left out.
+pub fn load_benchmarks(conn: &Connection) -> Result<Vec<GrowthBenchmark>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT g.name, g.breed, g.date_of_birth, \
+             COALESCE(w.weight, g.weight), w.weighed_on \
+         FROM goats g \
+         LEFT JOIN weight_records w ON w.id = ( \
+             SELECT id FROM weight_records WHERE goat_id = g.id AND estimated = 0 \
+             ORDER BY weighed_on DESC, id DESC LIMIT 1) \
+         WHERE g.date_of_birth IS NOT NULL AND g.deleted_at IS NULL",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<f64>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let today = farm_today(conn)?.format(DATE_FORMAT).to_string();
+    let benchmarks = rows
+        .into_iter()
+        .filter_map(|(name, breed, dob, weight, weighed_on)| {
+            let breed = Breed::from_str(&breed);
+            let weight = weight.filter(|w| *w > 0.0)?;
+            let age_days = age_on(Some(&dob), weighed_on.as_deref().unwrap_or(&today))?;
+            let expected_weight = expected_weight(&breed, age_days)?;
+            let percentile = weight_percentile(&breed, age_days, weight)?;
+            Some(GrowthBenchmark {
+                goat_name: name,
+                breed,
+                age_days,
+                weight,
+                expected_weight,
+                percentile,
+                below_expected: percentile < GROWTH_ALERT_PERCENTILE,
+            })
+        })
+        .collect();
+    Ok(benchmarks)
+}
+
+/// Handler for benchmarking every goat's growth against its breed standard.
+///
+/// Uses each goat's latest weighing, or its current weight as of today if it
+/// has never been weighed. Goats without a date of birth, or of a breed with
+/// no reference curve, are left out.
+///
+/// # HTTP Method
+/// - `GET /growth/benchmarks`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `GrowthBenchmark`, lowest percentile first.
+pub async fn get_growth_benchmarks(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /growth/benchmarks called");
+    let conn = db.get_conn()?;
+    let mut benchmarks = load_benchmarks(&conn)?;
+    benchmarks.sort_by(|a, b| a.percentile.total_cmp(&b.percentile));
+
+    info!(
+        "Returning {} growth benchmarks ({} below expected)",
+        benchmarks.len(),
+        benchmarks.iter().filter(|b| b.below_expected).count()
+    );
+    Ok(HttpResponse::Ok().json(benchmarks))
+}
@@ -0,0 +1,407 @@
+//! This module handles health incidents, their aggregation into a
+//! pen-by-month heat map, and each goat's health timeline.
+
+use crate::db::DbPool;
+use crate::errors::{AppError, ParseEnumError};
+use crate::handlers::lab_results::{LabResultsQuery, load_lab_results};
+use crate::handlers::parse_date;
+use crate::handlers::settings::farm_today;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::{HttpResponse, Responder, web};
+use chrono::{Datelike, Months, NaiveDate};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Deserialize;
+use shared::health::{
+    HealthHeatMap, HealthIncident, HeatMapRow, IncidentKind, TimelineEntry, TimelineKind,
+};
+use shared::lab::RangeFlag;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use tracing::{debug, info, trace};
+
+/// Longest period the heat map covers, to keep the grid readable.
+pub const MAX_HEATMAP_MONTHS: usize = 36;
+
+/// Label for incidents in goats that were not housed in any pen.
+const UNASSIGNED: &str = "Unassigned";
+
+/// Query parameters accepted by `GET /health/incidents`.
+#[derive(Deserialize)]
+pub struct IncidentQuery {
+    pub goat_name: Option<String>,
+}
+
+/// Query parameters accepted by `GET /health/timeline`.
+#[derive(Deserialize)]
+pub struct TimelineQuery {
+    pub goat_name: String,
+}
+
+/// Query parameters accepted by `GET /health/heatmap`.
+///
+/// `to` defaults to today and `from` to the start of the month eleven months
+/// earlier, giving a year of monthly columns.
+#[derive(Deserialize)]
+pub struct HeatMapQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub kind: Option<IncidentKind>,
+}
+
+/// `YYYY-MM` labels for every month touched by `from..=to`, oldest first.
+pub fn month_labels(from: NaiveDate, to: NaiveDate) -> Vec<String> {
+    let mut labels = Vec::new();
+    let mut month = from.with_day(1).expect("day 1 exists in every month");
+    while month <= to {
+        labels.push(month.format("%Y-%m").to_string());
+        month = month + Months::new(1);
+    }
+    labels
+}
+
+/// Maps a joined incident row (id, goat, space id, space name, kind,
+/// condition, date, notes) to a `HealthIncident`.
+fn row_to_incident(row: &Row) -> Result<HealthIncident, AppError> {
+    let kind: String = row.get(4)?;
+    Ok(HealthIncident {
+        id: row.get(0)?,
+        goat_name: row.get(1)?,
+        space_id: row.get(2)?,
+        space_name: row.get(3)?,
+        kind: IncidentKind::from_str(&kind)
+            .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "IncidentKind")))?,
+        condition: row.get(5)?,
+        observed_on: row.get(6)?,
+        notes: row.get(7)?,
+    })
+}
+
+/// Handler for listing health incidents, newest first.
+///
+/// # HTTP Method
+/// - `GET /health/incidents?goat_name=Rani`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of incidents.
+pub async fn get_incidents(
+    db: web::Data<DbPool>,
+    query: web::Query<IncidentQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat_name = ?query.goat_name, "GET /health/incidents called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(
+        "SELECT h.id, g.name, h.space_id, s.name, h.kind, h.condition, h.observed_on, h.notes \
+         FROM health_incidents h JOIN goats g ON g.id = h.goat_id AND g.deleted_at IS NULL \
+         LEFT JOIN spaces s ON s.id = h.space_id \
+         WHERE (?1 IS NULL OR g.name = ?1) \
+         ORDER BY h.observed_on DESC, h.id DESC",
+    )?;
+    let incidents: Vec<HealthIncident> = stmt
+        .query_map([&query.goat_name], |row| {
+            row_to_incident(row).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+        })?
+        .collect::<Result<_, _>>()?;
+
+    info!("Returning {} health incidents", incidents.len());
+    Ok(HttpResponse::Ok().json(incidents))
+}
+
+/// Handler for recording a health incident.
+///
+/// # HTTP Method
+/// - `POST /health/incidents`
+///
+/// # Request
+/// - JSON `HealthIncident`. Without a `space_id` the incident is located at
+///   the goat's current pen.
+///
+/// # Success
+/// - Returns HTTP 201 on successful insertion.
+///
+/// # Errors
+/// - Returns HTTP 400 if the condition is empty, the date is malformed or in
+///   the future, or the goat or space is unknown.
+pub async fn add_incident(
+    db: web::Data<DbPool>,
+    incident: web::Json<HealthIncident>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat = %incident.goat_name, "POST /health/incidents called");
+    if incident.condition.trim().is_empty() {
+        return Err(AppError::InvalidInput("condition must not be empty".into()));
+    }
+    let conn = db.get_conn()?;
+    if parse_date(&incident.observed_on, "observed_on")? > farm_today(&conn)? {
+        return Err(AppError::InvalidInput(
+            "observed_on cannot be in the future".into(),
+        ));
+    }
+
+    let (goat_id, current_space): (i64, Option<i64>) = conn
+        .query_row(
+            "SELECT id, space_id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [&incident.goat_name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!("No goat found with name {}", incident.goat_name))
+        })?;
+    if let Some(space_id) = incident.space_id {
+        conn.query_row("SELECT id FROM spaces WHERE id = ?1", [space_id], |row| {
+            row.get::<_, i64>(0)
+        })
+        .optional()?
+        .ok_or_else(|| AppError::InvalidInput(format!("No space found with ID {}", space_id)))?;
+    }
+
+    conn.execute(
+        "INSERT INTO health_incidents (goat_id, space_id, kind, condition, observed_on, notes) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            goat_id,
+            incident.space_id.or(current_space),
+            IncidentKind::to_str(&incident.kind),
+            incident.condition.trim(),
+            incident.observed_on,
+            incident.notes,
+        ],
+    )?;
+    info!(
+        incident_id = conn.last_insert_rowid(),
+        goat_id, "Health incident recorded"
+    );
+    Ok(HttpResponse::Created().body("Incident added"))
+}
+
+/// Handler for the pen-by-month incident heat map.
+///
+/// Every pen is listed, including those without incidents, so clean pens are
+/// visible for comparison; incidents outside any pen form an "Unassigned"
+/// row when present. Rows are ordered by total incidents, busiest first.
+///
+/// # HTTP Method
+/// - `GET /health/heatmap?from=2025-01-01&to=2025-12-31&kind=Parasite`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON `HealthHeatMap`.
+///
+/// # Errors
+/// - Returns HTTP 400 for malformed dates, `from` after `to`, or a period
+///   longer than `MAX_HEATMAP_MONTHS` months.
+pub async fn get_heatmap(
+    db: web::Data<DbPool>,
+    query: web::Query<HeatMapQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(from = ?query.from, to = ?query.to, kind = ?query.kind, "GET /health/heatmap called");
+    let conn = db.get_conn()?;
+    let to = match &query.to {
+        Some(to) => parse_date(to, "to")?,
+        None => farm_today(&conn)?,
+    };
+    let from = match &query.from {
+        Some(from) => parse_date(from, "from")?,
+        None => to.with_day(1).expect("day 1 exists in every month") - Months::new(11),
+    };
+    if from > to {
+        return Err(AppError::InvalidInput("from must not be after to".into()));
+    }
+    let months = month_labels(from, to);
+    if months.len() > MAX_HEATMAP_MONTHS {
+        return Err(AppError::InvalidInput(format!(
+            "Period spans {} months; at most {} are allowed",
+            months.len(),
+            MAX_HEATMAP_MONTHS
+        )));
+    }
+
+    let mut rows: Vec<HeatMapRow> = conn
+        .prepare("SELECT id, name FROM spaces ORDER BY name")?
+        .query_map([], |row| {
+            Ok(HeatMapRow {
+                space_id: Some(row.get(0)?),
+                space_name: row.get(1)?,
+                counts: vec![0; months.len()],
+                total: 0,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+    let mut index: HashMap<Option<i64>, usize> = rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| (row.space_id, i))
+        .collect();
+    let column: HashMap<&str, usize> = months
+        .iter()
+        .enumerate()
+        .map(|(i, month)| (month.as_str(), i))
+        .collect();
+
+    let mut stmt = conn.prepare(
+        "SELECT space_id, substr(observed_on, 1, 7), COUNT(*) FROM health_incidents \
+         WHERE observed_on BETWEEN ?1 AND ?2 AND (?3 IS NULL OR kind = ?3) \
+         GROUP BY space_id, substr(observed_on, 1, 7)",
+    )?;
+    let counts = stmt
+        .query_map(
+            params![
+                from.format(DATE_FORMAT).to_string(),
+                to.format(DATE_FORMAT).to_string(),
+                query.kind.as_ref().map(IncidentKind::to_str),
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, Option<i64>>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, u32>(2)?,
+                ))
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+    for (space_id, month, count) in counts {
+        let Some(&col) = column.get(month.as_str()) else {
+            continue;
+        };
+        let i = *index.entry(space_id).or_insert_with(|| {
+            rows.push(HeatMapRow {
+                space_id,
+                space_name: UNASSIGNED.to_string(),
+                counts: vec![0; months.len()],
+                total: 0,
+            });
+            rows.len() - 1
+        });
+        trace!(?space_id, month, count, "Heat map cell");
+        rows[i].counts[col] += count;
+        rows[i].total += count;
+    }
+    rows.sort_by_key(|row| Reverse(row.total));
+
+    info!(
+        pens = rows.len(),
+        months = months.len(),
+        "Returning health heat map"
+    );
+    Ok(HttpResponse::Ok().json(HealthHeatMap {
+        from: from.format(DATE_FORMAT).to_string(),
+        to: to.format(DATE_FORMAT).to_string(),
+        months,
+        rows,
+    }))
+}
+
+/// Handler for a goat's health timeline: its incidents, dated vaccinations
+/// and lab results, newest first.
+///
+/// Lab results with values outside their reference range are flagged, and
+/// their details list those values.
+///
+/// # HTTP Method
+/// - `GET /health/timeline?goat_name=Rani`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `TimelineEntry`.
+///
+/// # Errors
+/// - Returns HTTP 400 if the goat does not exist.
+pub async fn get_timeline(
+    db: web::Data<DbPool>,
+    query: web::Query<TimelineQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat_name = %query.goat_name, "GET /health/timeline called");
+    let conn = db.get_conn()?;
+    let goat_id: i64 = conn
+        .query_row(
+            "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [&query.goat_name],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!("No goat found with name {}", query.goat_name))
+        })?;
+    let entries = goat_timeline(&conn, goat_id)?;
+
+    info!(
+        goat_id,
+        "Returning {} health timeline entries",
+        entries.len()
+    );
+    Ok(HttpResponse::Ok().json(entries))
+}
+
+/// The health timeline of the goat with ID `goat_id`: its incidents, dated
+/// vaccinations and lab results, newest first.
+pub fn goat_timeline(conn: &Connection, goat_id: i64) -> Result<Vec<TimelineEntry>, AppError> {
+    let mut entries = Vec::new();
+    let mut stmt = conn.prepare(
+        "SELECT kind, condition, observed_on, notes FROM health_incidents WHERE goat_id = ?1",
+    )?;
+    let mut rows = stmt.query([goat_id])?;
+    while let Some(row) = rows.next()? {
+        let kind: String = row.get(0)?;
+        let condition: String = row.get(1)?;
+        entries.push(TimelineEntry {
+            date: row.get(2)?,
+            kind: TimelineKind::Incident,
+            title: format!("{}: {}", kind, condition),
+            details: row.get(3)?,
+            flagged: false,
+            lab_result_id: None,
+        });
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT v.name, gv.given_on FROM goat_vaccines gv \
+         JOIN vaccines v ON v.id = gv.vaccine_id \
+         WHERE gv.goat_id = ?1 AND gv.given_on IS NOT NULL",
+    )?;
+    let mut rows = stmt.query([goat_id])?;
+    while let Some(row) = rows.next()? {
+        let vaccine: String = row.get(0)?;
+        entries.push(TimelineEntry {
+            date: row.get(1)?,
+            kind: TimelineKind::Vaccination,
+            title: format!("Vaccinated: {}", vaccine),
+            details: None,
+            flagged: false,
+            lab_result_id: None,
+        });
+    }
+
+    for result in load_lab_results(conn, &LabResultsQuery::for_goat(goat_id))? {
+        let flagged: Vec<String> = result
+            .values
+            .iter()
+            .filter_map(|value| {
+                let side = match value.flag()? {
+                    RangeFlag::Low => "low",
+                    RangeFlag::High => "high",
+                };
+                Some(format!(
+                    "{} {} {} ({}, range {})",
+                    value.analyte,
+                    value.value,
+                    value.unit,
+                    side,
+                    value.range_label().unwrap_or_default()
+                ))
+            })
+            .collect();
+        entries.push(TimelineEntry {
+            date: result.sampled_on,
+            kind: TimelineKind::LabResult,
+            title: format!("Lab result: {}", result.test_type),
+            details: Some(if flagged.is_empty() {
+                "All values within range".to_string()
+            } else {
+                flagged.join("; ")
+            }),
+            flagged: !flagged.is_empty(),
+            lab_result_id: result.id,
+        });
+    }
+    // Stable, so same-day entries keep incidents before vaccinations and
+    // lab results
+    entries.sort_by(|a, b| b.date.cmp(&a.date));
+    Ok(entries)
+}
@@ -0,0 +1,140 @@
+//! This module handles importing herds exported from other livestock apps.
+//!
+//! `POST /goats/import` only turns an uploaded CSV or XLSX file into an
+//! `ImportTable` of text cells. Mapping columns to goat fields and
+//! validating rows happens in the import wizard (see `shared::import`),
+//! which commits the result through `POST /goats/batch`.
+
+use crate::errors::AppError;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::{HttpResponse, Responder, web};
+use calamine::{Data, Reader, Xlsx, open_workbook_from_rs};
+use shared::import::ImportTable;
+use std::io::Cursor;
+use tracing::{debug, info};
+
+/// Largest accepted upload.
+pub const MAX_IMPORT_BYTES: usize = 5 * 1024 * 1024;
+
+/// Most data rows accepted from one file.
+const MAX_IMPORT_ROWS: usize = 5000;
+
+/// Every XLSX file is a ZIP archive, which starts with this signature.
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Builds a table from raw rows: the first non-empty row is the header,
+/// empty rows are dropped, and every row is fitted to the header's width.
+fn to_table(rows: impl Iterator<Item = Vec<String>>) -> Result<ImportTable, AppError> {
+    let mut rows = rows.filter(|row| row.iter().any(|cell| !cell.trim().is_empty()));
+    let headers: Vec<String> = rows
+        .next()
+        .ok_or_else(|| AppError::InvalidInput("The file has no header row".into()))?
+        .into_iter()
+        .map(|header| header.trim().to_string())
+        .collect();
+    let rows: Vec<Vec<String>> = rows
+        .map(|mut row| {
+            row.resize(headers.len(), String::new());
+            row
+        })
+        .take(MAX_IMPORT_ROWS + 1)
+        .collect();
+    if rows.len() > MAX_IMPORT_ROWS {
+        return Err(AppError::InvalidInput(format!(
+            "Files may contain at most {} rows",
+            MAX_IMPORT_ROWS
+        )));
+    }
+    Ok(ImportTable { headers, rows })
+}
+
+/// Parses CSV text, detecting a comma, semicolon or tab delimiter from
+/// the first line.
+///
+/// # Errors
+/// - `AppError::InvalidInput` if the text is not UTF-8 or not valid CSV.
+pub fn parse_csv(bytes: &[u8]) -> Result<ImportTable, AppError> {
+    let text = std::str::from_utf8(bytes)
+        .map_err(|_| AppError::InvalidInput("CSV files must be UTF-8 encoded".into()))?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let first_line = text.lines().next().unwrap_or_default();
+    let delimiter = [b',', b';', b'\t']
+        .into_iter()
+        .max_by_key(|d| first_line.bytes().filter(|b| b == d).count())
+        .unwrap_or(b',');
+    debug!(delimiter = %(delimiter as char).escape_default(), "Parsing CSV upload");
+
+    let rows = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(text.as_bytes())
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(str::to_string).collect())
+                .map_err(|e| AppError::InvalidInput(format!("Invalid CSV: {}", e)))
+        })
+        .collect::<Result<Vec<Vec<String>>, _>>()?;
+    to_table(rows.into_iter())
+}
+
+/// Renders a spreadsheet cell as text, writing dates as `YYYY-MM-DD`.
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::DateTime(date) => date
+            .as_datetime()
+            .map(|date| date.format(DATE_FORMAT).to_string())
+            .unwrap_or_else(|| date.to_string()),
+        other => other.to_string(),
+    }
+}
+
+/// Parses the first worksheet of an XLSX workbook.
+///
+/// # Errors
+/// - `AppError::InvalidInput` if the workbook cannot be read or is empty.
+pub fn parse_xlsx(bytes: &[u8]) -> Result<ImportTable, AppError> {
+    let invalid = |e: calamine::XlsxError| AppError::InvalidInput(format!("Invalid XLSX: {}", e));
+    let mut workbook: Xlsx<_> = open_workbook_from_rs(Cursor::new(bytes)).map_err(invalid)?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| AppError::InvalidInput("The workbook has no worksheets".into()))?
+        .map_err(invalid)?;
+    debug!(rows = range.height(), "Parsing XLSX upload");
+    to_table(range.rows().map(|row| row.iter().map(cell_text).collect()))
+}
+
+/// Handler for reading an uploaded spreadsheet ahead of an import.
+///
+/// # HTTP Method
+/// - `POST /goats/import`
+///
+/// # Request
+/// - The raw contents of a CSV or XLSX file (at most `MAX_IMPORT_BYTES`);
+///   XLSX is recognised by its ZIP signature, anything else is read as CSV.
+///
+/// # Success
+/// - Returns HTTP 200 with an `ImportTable` of the header and data rows.
+///   Nothing is stored.
+///
+/// # Errors
+/// - Returns HTTP 400 for an empty, unreadable or oversized file.
+pub async fn parse_import(body: web::Bytes) -> Result<impl Responder, AppError> {
+    debug!(bytes = body.len(), "POST /goats/import called");
+    if body.is_empty() {
+        return Err(AppError::InvalidInput("The uploaded file is empty".into()));
+    }
+    let table = if body.starts_with(ZIP_MAGIC) {
+        parse_xlsx(&body)?
+    } else {
+        parse_csv(&body)?
+    };
+
+    info!(
+        columns = table.headers.len(),
+        rows = table.rows.len(),
+        "Parsed import file"
+    );
+    Ok(HttpResponse::Ok().json(table))
+}
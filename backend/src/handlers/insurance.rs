@@ -0,0 +1,568 @@
+//! This module handles livestock insurance: policy CRUD, the claims log, and
+//! expiry alerts.
+//!
+//! Renewal reminders are created by the background scheduler (see
+//! `scheduler::evaluate_policy_renewals`); the alerts endpoint reports the
+//! current state of cover on demand.
+
+use crate::db::DbPool;
+use crate::errors::{AppError, ParseEnumError};
+use crate::handlers::parse_date;
+use crate::handlers::settings::farm_today;
+use crate::scheduler::{DATE_FORMAT, POLICY_RENEWAL_LEAD_DAYS};
+use actix_web::{HttpResponse, Responder, web};
+use chrono::NaiveDate;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Deserialize;
+use shared::insurance::{
+    ClaimStatus, InsuranceClaim, InsurancePolicy, PolicyAlert, PolicyAlertKind,
+};
+use std::collections::HashMap;
+use tracing::{debug, info, warn};
+
+/// Columns selected for an `InsurancePolicy`, joined with the goat name.
+const POLICY_COLUMNS: &str = "p.id, g.name, p.provider, p.policy_number, p.sum_insured, \
+     p.start_date, p.expiry_date, p.notes \
+     FROM insurance_policies p JOIN goats g ON g.id = p.goat_id AND g.deleted_at IS NULL";
+
+/// Query parameters accepted by `GET /insurance/policies`.
+#[derive(Deserialize)]
+pub struct PolicyQuery {
+    pub goat_name: Option<String>,
+}
+
+/// Query parameters accepted by `GET /insurance/claims`.
+#[derive(Deserialize)]
+pub struct ClaimQuery {
+    pub policy_id: Option<i64>,
+}
+
+/// Maps a `POLICY_COLUMNS` row to an `InsurancePolicy`.
+fn row_to_policy(row: &Row) -> Result<InsurancePolicy, rusqlite::Error> {
+    Ok(InsurancePolicy {
+        id: row.get(0)?,
+        goat_name: row.get(1)?,
+        provider: row.get(2)?,
+        policy_number: row.get(3)?,
+        sum_insured: row.get(4)?,
+        start_date: row.get(5)?,
+        expiry_date: row.get(6)?,
+        notes: row.get(7)?,
+    })
+}
+
+/// Maps an `insurance_claims` row to an `InsuranceClaim`.
+fn row_to_claim(row: &Row) -> Result<InsuranceClaim, AppError> {
+    let status: String = row.get(6)?;
+    Ok(InsuranceClaim {
+        id: row.get(0)?,
+        policy_id: row.get(1)?,
+        claim_date: row.get(2)?,
+        reason: row.get(3)?,
+        amount_claimed: row.get(4)?,
+        amount_settled: row.get(5)?,
+        status: ClaimStatus::from_str(&status)
+            .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "ClaimStatus")))?,
+        notes: row.get(7)?,
+    })
+}
+
+/// Loads every policy, optionally for one goat, ordered by goat and expiry.
+pub fn fetch_policies(
+    conn: &Connection,
+    goat_name: Option<&str>,
+) -> Result<Vec<InsurancePolicy>, AppError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} WHERE (?1 IS NULL OR g.name = ?1) ORDER BY g.name, p.expiry_date, p.id",
+        POLICY_COLUMNS
+    ))?;
+    let policies = stmt
+        .query_map([goat_name], row_to_policy)?
+        .collect::<Result<_, _>>()?;
+    Ok(policies)
+}
+
+/// Loads one policy by ID.
+fn fetch_policy(conn: &Connection, id: i64) -> Result<InsurancePolicy, AppError> {
+    conn.query_row(
+        &format!("SELECT {} WHERE p.id = ?1", POLICY_COLUMNS),
+        [id],
+        row_to_policy,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::InvalidInput(format!("No policy found with id {}", id)))
+}
+
+/// Computes expiry alerts from each goat's latest policy.
+///
+/// Earlier policies are ignored, so a goat whose cover was renewed does not
+/// keep alerting about the lapsed one. Policies with an unparseable expiry
+/// date are skipped.
+pub fn compute_policy_alerts(
+    policies: &[InsurancePolicy],
+    today: NaiveDate,
+    window_days: i64,
+) -> Vec<PolicyAlert> {
+    let mut latest: HashMap<&str, (NaiveDate, &InsurancePolicy)> = HashMap::new();
+    for policy in policies {
+        let Ok(expiry) = NaiveDate::parse_from_str(&policy.expiry_date, DATE_FORMAT) else {
+            continue;
+        };
+        let entry = latest
+            .entry(policy.goat_name.as_str())
+            .or_insert((expiry, policy));
+        if expiry > entry.0 {
+            *entry = (expiry, policy);
+        }
+    }
+
+    let mut latest: Vec<(NaiveDate, &InsurancePolicy)> = latest.into_values().collect();
+    latest.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.goat_name.cmp(&b.1.goat_name)));
+    latest
+        .into_iter()
+        .filter_map(|(expiry, policy)| {
+            let days_left = (expiry - today).num_days();
+            let (kind, message) = if days_left < 0 {
+                (
+                    PolicyAlertKind::Expired,
+                    format!(
+                        "{}'s cover under {} expired on {}",
+                        policy.goat_name, policy.policy_number, expiry
+                    ),
+                )
+            } else if days_left <= window_days {
+                (
+                    PolicyAlertKind::ExpiringSoon,
+                    format!(
+                        "{}'s cover under {} expires in {} days ({})",
+                        policy.goat_name, policy.policy_number, days_left, expiry
+                    ),
+                )
+            } else {
+                return None;
+            };
+            Some(PolicyAlert {
+                policy_id: policy.id.unwrap_or_default(),
+                goat_name: policy.goat_name.clone(),
+                policy_number: policy.policy_number.clone(),
+                kind,
+                message,
+            })
+        })
+        .collect()
+}
+
+/// Validates user-supplied policy fields.
+fn validate_policy(policy: &InsurancePolicy) -> Result<(), AppError> {
+    if policy.provider.trim().is_empty() || policy.policy_number.trim().is_empty() {
+        return Err(AppError::InvalidInput(
+            "Provider and policy number are required".into(),
+        ));
+    }
+    if policy.sum_insured <= 0.0 {
+        return Err(AppError::InvalidInput(
+            "Sum insured must be positive".into(),
+        ));
+    }
+    let expiry = parse_date(&policy.expiry_date, "expiry_date")?;
+    if let Some(start) = &policy.start_date
+        && parse_date(start, "start_date")? >= expiry
+    {
+        return Err(AppError::InvalidInput(
+            "start_date must be before expiry_date".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Validates a claim against the policy it is made under, as of the farm's
+/// date `today`.
+fn validate_claim(
+    claim: &InsuranceClaim,
+    policy: &InsurancePolicy,
+    today: NaiveDate,
+) -> Result<(), AppError> {
+    if claim.reason.trim().is_empty() {
+        return Err(AppError::InvalidInput("Claim reason is required".into()));
+    }
+    if claim.amount_claimed <= 0.0 || claim.amount_claimed > policy.sum_insured {
+        return Err(AppError::InvalidInput(format!(
+            "Amount claimed must be positive and at most the sum insured ({})",
+            policy.sum_insured
+        )));
+    }
+    let claim_date = parse_date(&claim.claim_date, "claim_date")?;
+    if claim_date > today {
+        return Err(AppError::InvalidInput(
+            "claim_date cannot be in the future".into(),
+        ));
+    }
+    let covered_from = policy
+        .start_date
+        .as_deref()
+        .and_then(|d| NaiveDate::parse_from_str(d, DATE_FORMAT).ok());
+    let expiry = NaiveDate::parse_from_str(&policy.expiry_date, DATE_FORMAT).ok();
+    if covered_from.is_some_and(|start| claim_date < start)
+        || expiry.is_some_and(|expiry| claim_date > expiry)
+    {
+        return Err(AppError::InvalidInput(format!(
+            "claim_date {} is outside the policy period",
+            claim.claim_date
+        )));
+    }
+    match (&claim.status, claim.amount_settled) {
+        (ClaimStatus::Settled, None) => Err(AppError::InvalidInput(
+            "A settled claim needs the amount settled".into(),
+        )),
+        (ClaimStatus::Settled, Some(amount)) if amount < 0.0 || amount > claim.amount_claimed => {
+            Err(AppError::InvalidInput(
+                "Amount settled must be between 0 and the amount claimed".into(),
+            ))
+        }
+        (ClaimStatus::Settled, Some(_)) | (_, None) => Ok(()),
+        (_, Some(_)) => Err(AppError::InvalidInput(
+            "Only settled claims have an amount settled".into(),
+        )),
+    }
+}
+
+/// Looks up a goat's ID by name.
+fn goat_id(conn: &Connection, name: &str) -> Result<i64, AppError> {
+    conn.query_row(
+        "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+        [name],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| AppError::InvalidInput(format!("No goat found with name {}", name)))
+}
+
+/// Rejects a provider/policy number pair already used by another policy.
+fn check_unique_number(
+    conn: &Connection,
+    policy: &InsurancePolicy,
+    own_id: Option<i64>,
+) -> Result<(), AppError> {
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM insurance_policies WHERE provider = ?1 AND policy_number = ?2",
+            params![policy.provider.trim(), policy.policy_number.trim()],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match existing {
+        Some(id) if Some(id) != own_id => Err(AppError::InvalidInput(format!(
+            "{} policy {} is already recorded",
+            policy.provider, policy.policy_number
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Removes a policy's renewal task and its reminder if the task is still
+/// pending, so a changed or deleted policy does not leave a stale reminder.
+fn drop_renewal_task(conn: &Connection, policy_id: i64) -> Result<(), AppError> {
+    let task_id: Option<i64> = conn
+        .query_row(
+            "SELECT t.id FROM insurance_policies p JOIN tasks t ON t.id = p.renewal_task_id \
+             WHERE p.id = ?1 AND t.status = 'Pending'",
+            [policy_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(task_id) = task_id {
+        conn.execute("DELETE FROM reminders WHERE task_id = ?1", [task_id])?;
+        conn.execute("DELETE FROM tasks WHERE id = ?1", [task_id])?;
+        debug!(policy_id, task_id, "Dropped pending renewal task");
+    }
+    conn.execute(
+        "UPDATE insurance_policies SET renewal_task_id = NULL WHERE id = ?1",
+        [policy_id],
+    )?;
+    Ok(())
+}
+
+/// Handler for listing insurance policies.
+///
+/// # HTTP Method
+/// - `GET /insurance/policies?goat_name=Rani`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of policies, by goat and expiry.
+pub async fn get_policies(
+    db: web::Data<DbPool>,
+    query: web::Query<PolicyQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat_name = ?query.goat_name, "GET /insurance/policies called");
+    let conn = db.get_conn()?;
+    let policies = fetch_policies(&conn, query.goat_name.as_deref())?;
+
+    info!("Returning {} insurance policies", policies.len());
+    Ok(HttpResponse::Ok().json(policies))
+}
+
+/// Handler for recording an insurance policy.
+///
+/// # HTTP Method
+/// - `POST /insurance/policies`
+///
+/// # Success
+/// - Returns HTTP 201 on successful insertion.
+///
+/// # Errors
+/// - Returns HTTP 400 for missing fields, a non-positive sum insured,
+///   malformed dates, an unknown goat, or a policy number already recorded
+///   for the provider.
+pub async fn add_policy(
+    db: web::Data<DbPool>,
+    policy: web::Json<InsurancePolicy>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat = %policy.goat_name, number = %policy.policy_number, "POST /insurance/policies called");
+    validate_policy(&policy)?;
+
+    let conn = db.get_conn()?;
+    let goat_id = goat_id(&conn, &policy.goat_name)?;
+    check_unique_number(&conn, &policy, None)?;
+    conn.execute(
+        "INSERT INTO insurance_policies \
+         (goat_id, provider, policy_number, sum_insured, start_date, expiry_date, notes) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            goat_id,
+            policy.provider.trim(),
+            policy.policy_number.trim(),
+            policy.sum_insured,
+            policy.start_date,
+            policy.expiry_date,
+            policy.notes,
+        ],
+    )?;
+
+    info!(
+        policy_id = conn.last_insert_rowid(),
+        goat_id, "Insurance policy added"
+    );
+    Ok(HttpResponse::Created().body("Policy added"))
+}
+
+/// Handler for updating an insurance policy by ID.
+///
+/// Changing the expiry date discards a pending renewal task so the scheduler
+/// creates a new one for the new date.
+///
+/// # HTTP Method
+/// - `PUT /insurance/policies/{id}`
+///
+/// # Errors
+/// - Returns HTTP 400 for invalid fields, an unknown goat, a duplicate
+///   policy number, or if no policy matches the ID.
+pub async fn update_policy(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+    policy: web::Json<InsurancePolicy>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(policy_id = id, "PUT /insurance/policies/{{id}} called");
+    validate_policy(&policy)?;
+
+    let conn = db.get_conn()?;
+    let existing = fetch_policy(&conn, id)?;
+    let goat_id = goat_id(&conn, &policy.goat_name)?;
+    check_unique_number(&conn, &policy, Some(id))?;
+    if existing.expiry_date != policy.expiry_date {
+        drop_renewal_task(&conn, id)?;
+    }
+    conn.execute(
+        "UPDATE insurance_policies SET goat_id = ?1, provider = ?2, policy_number = ?3, \
+         sum_insured = ?4, start_date = ?5, expiry_date = ?6, notes = ?7 WHERE id = ?8",
+        params![
+            goat_id,
+            policy.provider.trim(),
+            policy.policy_number.trim(),
+            policy.sum_insured,
+            policy.start_date,
+            policy.expiry_date,
+            policy.notes,
+            id,
+        ],
+    )?;
+
+    info!(policy_id = id, "Insurance policy updated");
+    Ok(HttpResponse::Ok().body("Policy updated"))
+}
+
+/// Handler for deleting an insurance policy, its claims log and any pending
+/// renewal task.
+///
+/// # HTTP Method
+/// - `DELETE /insurance/policies/{id}`
+///
+/// # Errors
+/// - Returns HTTP 400 if no policy matches the ID.
+pub async fn delete_policy(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(policy_id = id, "DELETE /insurance/policies/{{id}} called");
+
+    let conn = db.get_conn()?;
+    drop_renewal_task(&conn, id)?;
+    conn.execute("DELETE FROM insurance_claims WHERE policy_id = ?1", [id])?;
+    let affected = conn.execute("DELETE FROM insurance_policies WHERE id = ?1", [id])?;
+    if affected == 0 {
+        warn!(policy_id = id, "Insurance policy not found for deletion");
+        return Err(AppError::InvalidInput(format!(
+            "No policy found with id {}",
+            id
+        )));
+    }
+
+    info!(policy_id = id, "Insurance policy deleted");
+    Ok(HttpResponse::Ok().body("Policy deleted"))
+}
+
+/// Handler for listing expiry alerts.
+///
+/// # HTTP Method
+/// - `GET /insurance/alerts`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `PolicyAlert`s for cover that
+///   lapsed or lapses within `POLICY_RENEWAL_LEAD_DAYS`, soonest first.
+pub async fn get_alerts(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /insurance/alerts called");
+    let conn = db.get_conn()?;
+    let policies = fetch_policies(&conn, None)?;
+    let alerts = compute_policy_alerts(&policies, farm_today(&conn)?, POLICY_RENEWAL_LEAD_DAYS);
+
+    info!("Returning {} insurance alerts", alerts.len());
+    Ok(HttpResponse::Ok().json(alerts))
+}
+
+/// Handler for listing the claims log, newest first.
+///
+/// # HTTP Method
+/// - `GET /insurance/claims?policy_id=1`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of claims.
+pub async fn get_claims(
+    db: web::Data<DbPool>,
+    query: web::Query<ClaimQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(policy_id = ?query.policy_id, "GET /insurance/claims called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, policy_id, claim_date, reason, amount_claimed, amount_settled, status, notes \
+         FROM insurance_claims WHERE (?1 IS NULL OR policy_id = ?1) \
+         ORDER BY claim_date DESC, id DESC",
+    )?;
+    let claims: Vec<InsuranceClaim> = stmt
+        .query_map([query.policy_id], |row| {
+            row_to_claim(row).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+        })?
+        .collect::<Result<_, _>>()?;
+
+    info!("Returning {} insurance claims", claims.len());
+    Ok(HttpResponse::Ok().json(claims))
+}
+
+/// Handler for filing a claim under a policy.
+///
+/// # HTTP Method
+/// - `POST /insurance/policies/{id}/claims`
+///
+/// # Request
+/// - JSON `InsuranceClaim`; `policy_id` is taken from the path.
+///
+/// # Success
+/// - Returns HTTP 201 on successful insertion.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown policy, an empty reason, an amount
+///   outside the sum insured, a date in the future or outside the policy
+///   period, or a settlement amount inconsistent with the status.
+pub async fn add_claim(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+    claim: web::Json<InsuranceClaim>,
+) -> Result<impl Responder, AppError> {
+    let policy_id = path.into_inner();
+    debug!(
+        policy_id,
+        amount = claim.amount_claimed,
+        "POST /insurance/policies/{{id}}/claims called"
+    );
+
+    let conn = db.get_conn()?;
+    let policy = fetch_policy(&conn, policy_id)?;
+    validate_claim(&claim, &policy, farm_today(&conn)?)?;
+    conn.execute(
+        "INSERT INTO insurance_claims \
+         (policy_id, claim_date, reason, amount_claimed, amount_settled, status, notes) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            policy_id,
+            claim.claim_date,
+            claim.reason.trim(),
+            claim.amount_claimed,
+            claim.amount_settled,
+            ClaimStatus::to_str(&claim.status),
+            claim.notes,
+        ],
+    )?;
+
+    info!(
+        claim_id = conn.last_insert_rowid(),
+        policy_id, "Insurance claim filed"
+    );
+    Ok(HttpResponse::Created().body("Claim added"))
+}
+
+/// Handler for updating a claim, typically to record the insurer's decision.
+///
+/// # HTTP Method
+/// - `PUT /insurance/claims/{id}`
+///
+/// # Request
+/// - JSON `InsuranceClaim`; the claim stays under its original policy.
+///
+/// # Errors
+/// - Returns HTTP 400 for invalid fields or if no claim matches the ID.
+pub async fn update_claim(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+    claim: web::Json<InsuranceClaim>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(claim_id = id, status = ?claim.status, "PUT /insurance/claims/{{id}} called");
+
+    let conn = db.get_conn()?;
+    let policy_id: i64 = conn
+        .query_row(
+            "SELECT policy_id FROM insurance_claims WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::InvalidInput(format!("No claim found with id {}", id)))?;
+    let policy = fetch_policy(&conn, policy_id)?;
+    validate_claim(&claim, &policy, farm_today(&conn)?)?;
+    conn.execute(
+        "UPDATE insurance_claims SET claim_date = ?1, reason = ?2, amount_claimed = ?3, \
+         amount_settled = ?4, status = ?5, notes = ?6 WHERE id = ?7",
+        params![
+            claim.claim_date,
+            claim.reason.trim(),
+            claim.amount_claimed,
+            claim.amount_settled,
+            ClaimStatus::to_str(&claim.status),
+            claim.notes,
+            id,
+        ],
+    )?;
+
+    info!(claim_id = id, status = ?claim.status, "Insurance claim updated");
+    Ok(HttpResponse::Ok().body("Claim updated"))
+}
@@ -0,0 +1,538 @@
+//! This module handles the inventory of feed, medicines, and equipment:
+//! item CRUD, consumption logging, low stock / expiry alerts, and disposal
+//! of expired stock.
+//!
+//! Consumption is tied to a treatment or feeding and optionally to a goat,
+//! and atomically reduces the item's stock level.
+
+use crate::db::DbPool;
+use crate::errors::{AppError, ParseEnumError};
+use crate::handlers::finance::{record_consumption_expense, record_disposal_expense};
+use crate::handlers::parse_date;
+use crate::handlers::settings::farm_today;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::{HttpResponse, Responder, web};
+use chrono::NaiveDate;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Deserialize;
+use shared::inventory::{
+    ConsumptionPurpose, ConsumptionRecord, Disposal, ExpiringStock, InventoryAlert,
+    InventoryAlertKind, InventoryCategory, InventoryItem,
+};
+use tracing::{debug, info, warn};
+
+/// Items expiring within this many days raise an `ExpiringSoon` alert.
+pub const EXPIRY_ALERT_WINDOW_DAYS: i64 = 30;
+
+/// Query parameters accepted by `GET /inventory/consumption`.
+#[derive(Deserialize)]
+pub struct ConsumptionQuery {
+    pub item_id: Option<i64>,
+}
+
+/// Maps an `inventory_items` row to an `InventoryItem`.
+fn row_to_item(row: &Row) -> Result<InventoryItem, AppError> {
+    let category: String = row.get(2)?;
+    Ok(InventoryItem {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        category: InventoryCategory::from_str(&category)
+            .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "InventoryCategory")))?,
+        quantity: row.get(3)?,
+        unit: row.get(4)?,
+        unit_cost: row.get(5)?,
+        expiry_date: row.get(6)?,
+        reorder_level: row.get(7)?,
+    })
+}
+
+/// Loads every inventory item ordered by name.
+pub fn fetch_items(conn: &Connection) -> Result<Vec<InventoryItem>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, category, quantity, unit, unit_cost, expiry_date, reorder_level \
+         FROM inventory_items ORDER BY name",
+    )?;
+    let items: Result<Vec<InventoryItem>, rusqlite::Error> = stmt
+        .query_map([], |row| {
+            row_to_item(row).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+        })?
+        .collect();
+    Ok(items?)
+}
+
+/// Computes low stock and expiry alerts for the given items.
+///
+/// An item is low on stock when its quantity is at or below its reorder level
+/// (and the reorder level is set). Items with an unparseable expiry date are
+/// skipped for expiry checks.
+pub fn compute_alerts(
+    items: &[InventoryItem],
+    today: NaiveDate,
+    expiry_window_days: i64,
+) -> Vec<InventoryAlert> {
+    let mut alerts = Vec::new();
+    for item in items {
+        let item_id = item.id.unwrap_or_default();
+        if item.reorder_level > 0.0 && item.quantity <= item.reorder_level {
+            alerts.push(InventoryAlert {
+                item_id,
+                item_name: item.name.clone(),
+                kind: InventoryAlertKind::LowStock,
+                message: format!(
+                    "{} is low: {} {} left (reorder at {})",
+                    item.name, item.quantity, item.unit, item.reorder_level
+                ),
+            });
+        }
+
+        let expiry = item
+            .expiry_date
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, DATE_FORMAT).ok());
+        if let Some(expiry) = expiry {
+            let days_left = (expiry - today).num_days();
+            if days_left < 0 {
+                alerts.push(InventoryAlert {
+                    item_id,
+                    item_name: item.name.clone(),
+                    kind: InventoryAlertKind::Expired,
+                    message: format!("{} expired on {}", item.name, expiry),
+                });
+            } else if days_left <= expiry_window_days {
+                alerts.push(InventoryAlert {
+                    item_id,
+                    item_name: item.name.clone(),
+                    kind: InventoryAlertKind::ExpiringSoon,
+                    message: format!("{} expires in {} days ({})", item.name, days_left, expiry),
+                });
+            }
+        }
+    }
+    alerts
+}
+
+/// Items still in stock that expired or expire within `expiry_window_days`
+/// of `today`, soonest expiry first.
+pub fn expiring_stock(
+    items: &[InventoryItem],
+    today: NaiveDate,
+    expiry_window_days: i64,
+) -> Vec<ExpiringStock> {
+    let mut expiring: Vec<ExpiringStock> = items
+        .iter()
+        .filter(|item| item.quantity > 0.0)
+        .filter_map(|item| {
+            let expiry =
+                NaiveDate::parse_from_str(item.expiry_date.as_deref()?, DATE_FORMAT).ok()?;
+            let days_left = (expiry - today).num_days();
+            (days_left <= expiry_window_days).then(|| ExpiringStock {
+                item: item.clone(),
+                days_left,
+                value: item.quantity * item.unit_cost,
+            })
+        })
+        .collect();
+    expiring.sort_by(|a, b| {
+        a.days_left
+            .cmp(&b.days_left)
+            .then_with(|| a.item.name.cmp(&b.item.name))
+    });
+    expiring
+}
+
+/// Validates user-supplied item fields.
+fn validate_item(item: &InventoryItem) -> Result<(), AppError> {
+    if item.name.trim().is_empty() {
+        return Err(AppError::InvalidInput("Item name is required".into()));
+    }
+    if item.unit.trim().is_empty() {
+        return Err(AppError::InvalidInput("Item unit is required".into()));
+    }
+    if item.quantity < 0.0 || item.reorder_level < 0.0 || item.unit_cost < 0.0 {
+        return Err(AppError::InvalidInput(
+            "Quantity, reorder level and unit cost must not be negative".into(),
+        ));
+    }
+    if let Some(expiry_date) = &item.expiry_date {
+        parse_date(expiry_date, "expiry_date")?;
+    }
+    Ok(())
+}
+
+/// Handler for listing all inventory items.
+///
+/// # HTTP Method
+/// - `GET /inventory`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of items.
+pub async fn get_items(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /inventory called");
+    let conn = db.get_conn()?;
+    let items = fetch_items(&conn)?;
+
+    info!("Returning {} inventory items", items.len());
+    Ok(HttpResponse::Ok().json(items))
+}
+
+/// Handler for adding an inventory item.
+///
+/// # HTTP Method
+/// - `POST /inventory`
+///
+/// # Success
+/// - Returns HTTP 201 on successful insertion.
+///
+/// # Errors
+/// - Returns HTTP 400 for missing name/unit, negative amounts or a
+///   malformed `expiry_date`.
+pub async fn add_item(
+    db: web::Data<DbPool>,
+    item: web::Json<InventoryItem>,
+) -> Result<impl Responder, AppError> {
+    debug!(name = %item.name, "POST /inventory called");
+    validate_item(&item)?;
+
+    let conn = db.get_conn()?;
+    conn.execute(
+        "INSERT INTO inventory_items (name, category, quantity, unit, unit_cost, expiry_date, reorder_level) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            item.name,
+            InventoryCategory::to_str(&item.category),
+            item.quantity,
+            item.unit,
+            item.unit_cost,
+            item.expiry_date,
+            item.reorder_level,
+        ],
+    )?;
+
+    info!(item_id = conn.last_insert_rowid(), "Inventory item added");
+    Ok(HttpResponse::Created().body("Item added"))
+}
+
+/// Handler for updating an inventory item by ID.
+///
+/// # HTTP Method
+/// - `PUT /inventory/{id}`
+///
+/// # Errors
+/// - Returns HTTP 400 for invalid fields or if no item matches the ID.
+pub async fn update_item(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+    item: web::Json<InventoryItem>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(item_id = id, "PUT /inventory/{{id}} called");
+    validate_item(&item)?;
+
+    let conn = db.get_conn()?;
+    let affected = conn.execute(
+        "UPDATE inventory_items SET name = ?1, category = ?2, quantity = ?3, unit = ?4, unit_cost = ?5, \
+         expiry_date = ?6, reorder_level = ?7 WHERE id = ?8",
+        params![
+            item.name,
+            InventoryCategory::to_str(&item.category),
+            item.quantity,
+            item.unit,
+            item.unit_cost,
+            item.expiry_date,
+            item.reorder_level,
+            id,
+        ],
+    )?;
+    if affected == 0 {
+        warn!(item_id = id, "Inventory item not found for update");
+        return Err(AppError::InvalidInput(format!("No item found with id {}", id)));
+    }
+
+    info!(item_id = id, "Inventory item updated");
+    Ok(HttpResponse::Ok().body("Item updated"))
+}
+
+/// Handler for deleting an inventory item and its consumption log.
+///
+/// # HTTP Method
+/// - `DELETE /inventory/{id}`
+///
+/// # Errors
+/// - Returns HTTP 400 if no item matches the ID.
+pub async fn delete_item(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(item_id = id, "DELETE /inventory/{{id}} called");
+
+    let conn = db.get_conn()?;
+    conn.execute("DELETE FROM inventory_consumption WHERE item_id = ?1", [id])?;
+    let affected = conn.execute("DELETE FROM inventory_items WHERE id = ?1", [id])?;
+    if affected == 0 {
+        warn!(item_id = id, "Inventory item not found for deletion");
+        return Err(AppError::InvalidInput(format!("No item found with id {}", id)));
+    }
+
+    info!(item_id = id, "Inventory item deleted");
+    Ok(HttpResponse::Ok().body("Item deleted"))
+}
+
+/// Handler for logging consumption of an item for a treatment or feeding.
+///
+/// # HTTP Method
+/// - `POST /inventory/{id}/consume`
+///
+/// # Request
+/// - JSON `ConsumptionRecord`; `item_id` is taken from the path. An empty
+///   `consumed_on` defaults to today.
+///
+/// # Success
+/// - Returns HTTP 201 after logging the consumption, reducing stock, and
+///   charging its cost to the goat or pen as an expense transaction.
+///
+/// # Errors
+/// - Returns HTTP 400 for a non-positive quantity, a malformed
+///   `consumed_on`, insufficient stock, an unknown item, or an unknown goat.
+pub async fn consume_item(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+    record: web::Json<ConsumptionRecord>,
+) -> Result<impl Responder, AppError> {
+    let item_id = path.into_inner();
+    debug!(item_id, quantity = record.quantity, "POST /inventory/{{id}}/consume called");
+    if record.quantity <= 0.0 {
+        return Err(AppError::InvalidInput(
+            "Consumed quantity must be positive".into(),
+        ));
+    }
+
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+
+    let item = tx
+        .query_row(
+            "SELECT id, name, category, quantity, unit, unit_cost, expiry_date, reorder_level \
+             FROM inventory_items WHERE id = ?1",
+            [item_id],
+            |row| {
+                row_to_item(row).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+            },
+        )
+        .optional()?
+        .ok_or_else(|| AppError::InvalidInput(format!("No item found with id {}", item_id)))?;
+    if record.quantity > item.quantity {
+        warn!(item_id, available = item.quantity, requested = record.quantity, "Insufficient stock");
+        return Err(AppError::InvalidInput(format!(
+            "Insufficient stock: {} available, {} requested",
+            item.quantity, record.quantity
+        )));
+    }
+
+    let goat_id: Option<i64> = match &record.goat_name {
+        Some(name) => Some(
+            tx.query_row(
+                "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+                [name],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| AppError::InvalidInput(format!("No goat found with name {}", name)))?,
+        ),
+        None => None,
+    };
+    let consumed_on = if record.consumed_on.trim().is_empty() {
+        farm_today(&tx)?.format(DATE_FORMAT).to_string()
+    } else {
+        parse_date(&record.consumed_on, "consumed_on")?;
+        record.consumed_on.clone()
+    };
+
+    tx.execute(
+        "INSERT INTO inventory_consumption (item_id, quantity, purpose, goat_id, space_id, consumed_on, notes) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            item_id,
+            record.quantity,
+            ConsumptionPurpose::to_str(&record.purpose),
+            goat_id,
+            record.space_id,
+            consumed_on,
+            record.notes,
+        ],
+    )?;
+    let consumption_id = tx.last_insert_rowid();
+    record_consumption_expense(
+        &tx,
+        consumption_id,
+        &item,
+        record.quantity,
+        goat_id,
+        record.space_id,
+        &consumed_on,
+    )?;
+    tx.execute(
+        "UPDATE inventory_items SET quantity = quantity - ?1 WHERE id = ?2",
+        params![record.quantity, item_id],
+    )?;
+    tx.commit()?;
+
+    info!(item_id, quantity = record.quantity, "Logged inventory consumption");
+    Ok(HttpResponse::Created().body("Consumption logged"))
+}
+
+/// Handler for listing the consumption log, newest first.
+///
+/// # HTTP Method
+/// - `GET /inventory/consumption?item_id=1`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of consumption records.
+pub async fn get_consumption(
+    db: web::Data<DbPool>,
+    query: web::Query<ConsumptionQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(item_id = ?query.item_id, "GET /inventory/consumption called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(
+        "SELECT c.id, c.item_id, c.quantity, c.purpose, g.name, c.space_id, c.consumed_on, c.notes \
+         FROM inventory_consumption c LEFT JOIN goats g ON g.id = c.goat_id \
+         WHERE (?1 IS NULL OR c.item_id = ?1) ORDER BY c.consumed_on DESC, c.id DESC",
+    )?;
+    let records: Vec<ConsumptionRecord> = stmt
+        .query_map([query.item_id], |row| {
+            let purpose: String = row.get(3)?;
+            Ok(ConsumptionRecord {
+                id: row.get(0)?,
+                item_id: row.get(1)?,
+                quantity: row.get(2)?,
+                purpose: ConsumptionPurpose::from_str(&purpose).map_err(|e| {
+                    rusqlite::Error::ToSqlConversionFailure(Box::new(AppError::ParseError(
+                        ParseEnumError::new(&e, "ConsumptionPurpose"),
+                    )))
+                })?,
+                goat_name: row.get(4)?,
+                space_id: row.get(5)?,
+                consumed_on: row.get(6)?,
+                notes: row.get(7)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    info!("Returning {} consumption records", records.len());
+    Ok(HttpResponse::Ok().json(records))
+}
+
+/// Handler for listing low stock and expiry alerts.
+///
+/// # HTTP Method
+/// - `GET /inventory/alerts`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `InventoryAlert`s.
+pub async fn get_alerts(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /inventory/alerts called");
+    let conn = db.get_conn()?;
+    let items = fetch_items(&conn)?;
+    let alerts = compute_alerts(&items, farm_today(&conn)?, EXPIRY_ALERT_WINDOW_DAYS);
+
+    info!("Returning {} inventory alerts", alerts.len());
+    Ok(HttpResponse::Ok().json(alerts))
+}
+
+/// Handler for listing stock that has expired or expires within
+/// `EXPIRY_ALERT_WINDOW_DAYS`.
+///
+/// # HTTP Method
+/// - `GET /inventory/expiring`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `ExpiringStock`, expired items
+///   first and then by expiry date. Items out of stock are left out.
+pub async fn get_expiring(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /inventory/expiring called");
+    let conn = db.get_conn()?;
+    let items = fetch_items(&conn)?;
+    let expiring = expiring_stock(&items, farm_today(&conn)?, EXPIRY_ALERT_WINDOW_DAYS);
+
+    info!("Returning {} expiring items", expiring.len());
+    Ok(HttpResponse::Ok().json(expiring))
+}
+
+/// Handler for disposing of all remaining stock of an item, e.g. expired
+/// medicine.
+///
+/// # HTTP Method
+/// - `POST /inventory/{id}/dispose`
+///
+/// # Success
+/// - Returns HTTP 201 with a JSON `Disposal` after logging the stock as
+///   consumed, writing its cost off as an expense, and emptying the item.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown item or one with no stock left.
+pub async fn dispose_item(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let item_id = path.into_inner();
+    debug!(item_id, "POST /inventory/{{id}}/dispose called");
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+
+    let item = tx
+        .query_row(
+            "SELECT id, name, category, quantity, unit, unit_cost, expiry_date, reorder_level \
+             FROM inventory_items WHERE id = ?1",
+            [item_id],
+            |row| {
+                row_to_item(row).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+            },
+        )
+        .optional()?
+        .ok_or_else(|| AppError::InvalidInput(format!("No item found with id {}", item_id)))?;
+    if item.quantity <= 0.0 {
+        warn!(item_id, "Nothing left to dispose of");
+        return Err(AppError::InvalidInput(format!(
+            "{} has no stock left to dispose of",
+            item.name
+        )));
+    }
+
+    let today = farm_today(&tx)?.format(DATE_FORMAT).to_string();
+    let notes = match &item.expiry_date {
+        Some(expiry) if *expiry < today => format!("Disposed of; expired on {}", expiry),
+        Some(expiry) => format!("Disposed of; expires on {}", expiry),
+        None => "Disposed of".to_string(),
+    };
+    tx.execute(
+        "INSERT INTO inventory_consumption (item_id, quantity, purpose, consumed_on, notes) \
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            item_id,
+            item.quantity,
+            ConsumptionPurpose::to_str(&ConsumptionPurpose::Other),
+            today,
+            notes,
+        ],
+    )?;
+    let consumption_id = tx.last_insert_rowid();
+    let transaction_id =
+        record_disposal_expense(&tx, consumption_id, &item, item.quantity, &today)?;
+    tx.execute(
+        "UPDATE inventory_items SET quantity = 0 WHERE id = ?1",
+        [item_id],
+    )?;
+    tx.commit()?;
+
+    info!(item_id, quantity = item.quantity, "Disposed of inventory stock");
+    Ok(HttpResponse::Created().json(Disposal {
+        item_id,
+        value: item.quantity * item.unit_cost,
+        item_name: item.name,
+        quantity: item.quantity,
+        unit: item.unit,
+        consumption_id,
+        transaction_id,
+    }))
+}
@@ -0,0 +1,209 @@
+//! This module queues background jobs and reports on them (see
+//! `crate::jobs`): census reports built for download, large goat imports and
+//! cleanups of data past the farm's retention.
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::goats::check_batch;
+use crate::handlers::reports::{ReportFormat, find_template};
+use crate::jobs::{JobQueue, JobRequest, enqueue, load_job, load_result, recent_jobs};
+use crate::repository::Goats;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::http::header;
+use actix_web::{HttpResponse, Responder, web};
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use serde::Deserialize;
+use shared::NewGoat;
+use tracing::{debug, info};
+
+/// Most jobs returned by `GET /jobs`.
+const JOB_LIST_LIMIT: u32 = 50;
+
+/// Body of `POST /jobs/census`.
+#[derive(Deserialize)]
+pub struct CensusJobInput {
+    /// Key of the report template.
+    pub key: String,
+    /// Census date (`YYYY-MM-DD`); defaults to the day the job runs.
+    pub as_of: Option<String>,
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+/// Stores `request` and wakes the runners, if a queue is registered.
+fn queue_job(
+    conn: &Connection,
+    queue: Option<web::Data<JobQueue>>,
+    request: &JobRequest,
+) -> Result<HttpResponse, AppError> {
+    let job = enqueue(conn, request)?;
+    if let Some(queue) = queue {
+        queue.notify();
+    }
+    Ok(HttpResponse::Accepted().json(job))
+}
+
+/// Handler for listing recent jobs.
+///
+/// # HTTP Method
+/// - `GET /jobs`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of the `JOB_LIST_LIMIT` most recent
+///   `Job`s, newest first.
+pub async fn get_jobs(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /jobs called");
+    let conn = db.get_conn()?;
+    let jobs = recent_jobs(&conn, JOB_LIST_LIMIT)?;
+
+    info!("Returning {} jobs", jobs.len());
+    Ok(HttpResponse::Ok().json(jobs))
+}
+
+/// Handler for a job's status and progress.
+///
+/// # HTTP Method
+/// - `GET /jobs/{id}`
+///
+/// # Success
+/// - Returns HTTP 200 with the `Job`.
+///
+/// # Errors
+/// - Returns HTTP 400 if there is no such job.
+pub async fn get_job(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(job_id = id, "GET /jobs/{{id}} called");
+    let conn = db.get_conn()?;
+    let job = load_job(&conn, id)?
+        .ok_or_else(|| AppError::InvalidInput(format!("No job found with id {}", id)))?;
+    Ok(HttpResponse::Ok().json(job))
+}
+
+/// Handler for downloading a finished job's file, e.g. a census report.
+///
+/// # HTTP Method
+/// - `GET /jobs/{id}/result`
+///
+/// # Success
+/// - Returns HTTP 200 with the file as an attachment.
+///
+/// # Errors
+/// - Returns HTTP 400 if there is no such job, it has not succeeded yet, or
+///   it produces no file.
+pub async fn get_job_result(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(job_id = id, "GET /jobs/{{id}}/result called");
+    let conn = db.get_conn()?;
+    let file = load_result(&conn, id)?
+        .ok_or_else(|| AppError::InvalidInput(format!("Job {} has no result to download", id)))?;
+
+    info!(
+        job_id = id,
+        bytes = file.bytes.len(),
+        "Returning job result"
+    );
+    Ok(HttpResponse::Ok()
+        .content_type(file.content_type)
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", file.name),
+        ))
+        .body(file.bytes))
+}
+
+/// Handler for building a census report in the background.
+///
+/// # HTTP Method
+/// - `POST /jobs/census`
+///
+/// # Request
+/// - JSON `CensusJobInput`.
+///
+/// # Success
+/// - Returns HTTP 202 with the queued `Job`. Once it has succeeded the
+///   report is at `GET /jobs/{id}/result`.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown template or a malformed `as_of`.
+pub async fn add_census_job(
+    db: web::Data<DbPool>,
+    queue: Option<web::Data<JobQueue>>,
+    input: web::Json<CensusJobInput>,
+) -> Result<impl Responder, AppError> {
+    debug!(key = %input.key, "POST /jobs/census called");
+    let input = input.into_inner();
+    let conn = db.get_conn()?;
+    find_template(&conn, &input.key)?;
+    if let Some(value) = &input.as_of {
+        NaiveDate::parse_from_str(value, DATE_FORMAT).map_err(|_| {
+            AppError::InvalidInput(format!("as_of must be YYYY-MM-DD, got '{}'", value))
+        })?;
+    }
+    queue_job(
+        &conn,
+        queue,
+        &JobRequest::CensusReport {
+            key: input.key,
+            as_of: input.as_of,
+            format: input.format,
+        },
+    )
+}
+
+/// Handler for purging the trash and event log entries past the farm's
+/// retention now, rather than at the next daily cleanup.
+///
+/// # HTTP Method
+/// - `POST /jobs/cleanup`
+///
+/// # Success
+/// - Returns HTTP 202 with the queued `Job`, whose progress counts what was
+///   purged.
+pub async fn add_cleanup_job(
+    db: web::Data<DbPool>,
+    queue: Option<web::Data<JobQueue>>,
+) -> Result<impl Responder, AppError> {
+    debug!("POST /jobs/cleanup called");
+    let conn = db.get_conn()?;
+    queue_job(&conn, queue, &JobRequest::Cleanup)
+}
+
+/// Handler for adding many goats in the background, e.g. a large import.
+///
+/// # HTTP Method
+/// - `POST /jobs/import`
+///
+/// # Request
+/// - JSON array of `NewGoat`.
+///
+/// # Success
+/// - Returns HTTP 202 with the queued `Job`, whose progress counts the goats
+///   added. Goats are added `crate::jobs::IMPORT_CHUNK` at a time, so a job
+///   failing part way keeps the goats added before.
+///
+/// # Errors
+/// - Returns HTTP 400 for a batch `POST /goats/batch` would refuse.
+pub async fn add_import_job(
+    db: web::Data<DbPool>,
+    goats: Goats,
+    queue: Option<web::Data<JobQueue>>,
+    batch: web::Json<Vec<NewGoat>>,
+) -> Result<impl Responder, AppError> {
+    debug!(count = batch.len(), "POST /jobs/import called");
+    check_batch(&goats, &batch)?;
+    let conn = db.get_conn()?;
+    queue_job(
+        &conn,
+        queue,
+        &JobRequest::GoatImport {
+            goats: batch.into_inner(),
+        },
+    )
+}
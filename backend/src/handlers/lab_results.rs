@@ -0,0 +1,300 @@
+//! This module handles lab test results of goats' samples and the PDF
+//! reports they come with (see `shared::lab`).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::parse_date;
+use crate::handlers::settings::farm_today;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Deserialize;
+use shared::lab::{LabResult, LabValue, check_report};
+use std::collections::HashMap;
+use tracing::{debug, info, warn};
+
+/// Query parameters accepted by `GET /lab-results`. Goat names are not
+/// unique, so `goat_id` picks out one goat where `goat_name` may match
+/// several.
+#[derive(Deserialize, Default)]
+pub struct LabResultsQuery {
+    pub goat_id: Option<i64>,
+    pub goat_name: Option<String>,
+}
+
+impl LabResultsQuery {
+    /// The query for the lab results of the goat with ID `goat_id`.
+    pub fn for_goat(goat_id: i64) -> Self {
+        LabResultsQuery {
+            goat_id: Some(goat_id),
+            goat_name: None,
+        }
+    }
+}
+
+/// Maps a joined result row (id, goat, test type, sample date, lab, notes,
+/// whether a report is stored) to a `LabResult` without its values.
+fn row_to_result(row: &Row) -> rusqlite::Result<LabResult> {
+    Ok(LabResult {
+        id: row.get(0)?,
+        goat_name: row.get(1)?,
+        test_type: row.get(2)?,
+        sampled_on: row.get(3)?,
+        lab: row.get(4)?,
+        values: Vec::new(),
+        notes: row.get(5)?,
+        has_report: row.get(6)?,
+    })
+}
+
+/// Loads the lab results matching `query`, of every goat when it names
+/// none, with their values in the order recorded. Newest samples come
+/// first.
+pub fn load_lab_results(
+    conn: &Connection,
+    query: &LabResultsQuery,
+) -> Result<Vec<LabResult>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT v.result_id, v.analyte, v.value, v.unit, v.low, v.high
+         FROM lab_values v
+         JOIN lab_results r ON r.id = v.result_id
+         JOIN goats g ON g.id = r.goat_id AND g.deleted_at IS NULL
+         WHERE (?1 IS NULL OR r.goat_id = ?1) AND (?2 IS NULL OR g.name = ?2)
+         ORDER BY v.id",
+    )?;
+    let mut values: HashMap<i64, Vec<LabValue>> = HashMap::new();
+    let rows = stmt.query_map(params![query.goat_id, query.goat_name], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            LabValue {
+                analyte: row.get(1)?,
+                value: row.get(2)?,
+                unit: row.get(3)?,
+                low: row.get(4)?,
+                high: row.get(5)?,
+            },
+        ))
+    })?;
+    for row in rows {
+        let (result_id, value) = row?;
+        values.entry(result_id).or_default().push(value);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT r.id, g.name, r.test_type, r.sampled_on, r.lab, r.notes, r.report IS NOT NULL
+         FROM lab_results r
+         JOIN goats g ON g.id = r.goat_id AND g.deleted_at IS NULL
+         WHERE (?1 IS NULL OR r.goat_id = ?1) AND (?2 IS NULL OR g.name = ?2)
+         ORDER BY r.sampled_on DESC, r.id DESC",
+    )?;
+    let results = stmt
+        .query_map(params![query.goat_id, query.goat_name], row_to_result)?
+        .map(|row| {
+            let mut result = row?;
+            if let Some(id) = result.id {
+                result.values = values.remove(&id).unwrap_or_default();
+            }
+            Ok(result)
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+    Ok(results)
+}
+
+/// Handler for listing lab results, newest sample first.
+///
+/// # HTTP Method
+/// - `GET /lab-results?goat_name=Rani` or `?goat_id=3` (all goats
+///   without either)
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `LabResult`.
+pub async fn get_lab_results(
+    db: web::Data<DbPool>,
+    query: web::Query<LabResultsQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat_id = ?query.goat_id, goat_name = ?query.goat_name, "GET /lab-results called");
+    let conn = db.get_conn()?;
+    let results = load_lab_results(&conn, &query)?;
+
+    info!("Returning {} lab results", results.len());
+    Ok(HttpResponse::Ok().json(results))
+}
+
+/// Handler for recording a lab result.
+///
+/// Values reported without a range are given the typical range of their
+/// analyte (see `shared::lab::REFERENCE_RANGES`), if known.
+///
+/// # HTTP Method
+/// - `POST /lab-results`
+///
+/// # Request
+/// - JSON `LabResult`; `id` and `has_report` are ignored.
+///
+/// # Success
+/// - Returns HTTP 201 with the stored `LabResult`.
+///
+/// # Errors
+/// - Returns HTTP 400 if the result is incomplete, the goat does not exist,
+///   or the sample date is malformed or in the future.
+pub async fn add_lab_result(
+    db: web::Data<DbPool>,
+    payload: web::Json<LabResult>,
+) -> Result<impl Responder, AppError> {
+    let mut result = payload.into_inner();
+    debug!(goat = %result.goat_name, test = %result.test_type, "POST /lab-results called");
+    result.validate().map_err(AppError::InvalidInput)?;
+    result.apply_reference_ranges();
+
+    let mut conn = db.get_conn()?;
+    if parse_date(&result.sampled_on, "sampled_on")? > farm_today(&conn)? {
+        return Err(AppError::InvalidInput(
+            "sampled_on cannot be in the future".into(),
+        ));
+    }
+    let tx = conn.transaction()?;
+    let goat_name = result.goat_name.trim();
+    let goat_id: i64 = tx
+        .query_row(
+            "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [goat_name],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::InvalidInput(format!("No goat found with name {}", goat_name)))?;
+    tx.execute(
+        "INSERT INTO lab_results (goat_id, test_type, sampled_on, lab, notes)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            goat_id,
+            result.test_type.trim(),
+            result.sampled_on,
+            result
+                .lab
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty()),
+            result
+                .notes
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty()),
+        ],
+    )?;
+    let id = tx.last_insert_rowid();
+    for value in &result.values {
+        tx.execute(
+            "INSERT INTO lab_values (result_id, analyte, value, unit, low, high)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                id,
+                value.analyte.trim(),
+                value.value,
+                value.unit.trim(),
+                value.low,
+                value.high
+            ],
+        )?;
+    }
+    tx.commit()?;
+
+    let stored = load_lab_results(&conn, &LabResultsQuery::for_goat(goat_id))?
+        .into_iter()
+        .find(|r| r.id == Some(id))
+        .ok_or_else(|| AppError::InvalidInput(format!("No lab result found with ID {}", id)))?;
+    info!(
+        lab_result_id = id,
+        flagged = stored.flagged().len(),
+        "Lab result added"
+    );
+    Ok(HttpResponse::Created().json(stored))
+}
+
+/// Handler for attaching the lab's report to a result, replacing any
+/// earlier one.
+///
+/// # HTTP Method
+/// - `PUT /lab-results/{id}/report`
+///
+/// # Request
+/// - The raw PDF (at most `MAX_ATTACHMENT_BYTES`) with an
+///   `application/pdf` `Content-Type`.
+///
+/// # Success
+/// - Returns HTTP 200 with a confirmation message.
+///
+/// # Errors
+/// - Returns HTTP 400 if the report is empty or not a PDF, or the result
+///   does not exist.
+pub async fn upload_report(
+    db: web::Data<DbPool>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+    body: web::Bytes,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(
+        lab_result_id = id,
+        bytes = body.len(),
+        "PUT /lab-results/{{id}}/report called"
+    );
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    check_report(content_type, body.len()).map_err(AppError::InvalidInput)?;
+    let conn = db.get_conn()?;
+    let affected = conn.execute(
+        "UPDATE lab_results SET report = ?1 WHERE id = ?2",
+        params![body.as_ref(), id],
+    )?;
+    if affected == 0 {
+        warn!(lab_result_id = id, "Lab result not found for report upload");
+        return Err(AppError::InvalidInput(format!(
+            "No lab result found with ID {}",
+            id
+        )));
+    }
+
+    info!(lab_result_id = id, bytes = body.len(), "Lab report stored");
+    Ok(HttpResponse::Ok().body("Lab report stored"))
+}
+
+/// Handler for fetching a result's PDF report.
+///
+/// # HTTP Method
+/// - `GET /lab-results/{id}/report`
+///
+/// # Success
+/// - Returns HTTP 200 with the PDF.
+///
+/// # Errors
+/// - Returns HTTP 400 if the result does not exist or has no report.
+pub async fn get_report(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(lab_result_id = id, "GET /lab-results/{{id}}/report called");
+    let conn = db.get_conn()?;
+    let report: Option<Vec<u8>> = conn
+        .query_row(
+            "SELECT report FROM lab_results WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::InvalidInput(format!("No lab result found with ID {}", id)))?;
+    let report =
+        report.ok_or_else(|| AppError::InvalidInput(format!("Lab result {} has no report", id)))?;
+
+    info!(
+        lab_result_id = id,
+        bytes = report.len(),
+        "Returning lab report"
+    );
+    Ok(HttpResponse::Ok()
+        .content_type("application/pdf")
+        .body(report))
+}
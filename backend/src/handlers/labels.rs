@@ -0,0 +1,224 @@
+//! This module renders printable sheets of ear-tag labels, each with a
+//! goat's name, tag ID and a QR code, on standard label stock (see
+//! `shared::labels`).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::reports::{FONT_NAME, show_text};
+use actix_web::http::header;
+use actix_web::{HttpResponse, Responder, web};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref};
+use qrcode::{Color, QrCode};
+use rusqlite::{Connection, OptionalExtension};
+use shared::labels::{LabelSheet, TagSheetRequest};
+use tracing::{debug, info, warn};
+
+/// PDF points in a millimetre.
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+
+/// Blank space kept inside each label's edge, in millimetres.
+const LABEL_PADDING_MM: f32 = 2.0;
+
+/// Light modules drawn around the QR code on each side. The standard asks
+/// for four, but the label's padding and white stock make up the rest.
+const QR_QUIET_MODULES: usize = 1;
+
+/// Rough width of a Helvetica character as a share of the font size, used
+/// to shrink long names until they fit.
+const CHAR_WIDTH_EM: f32 = 0.55;
+
+/// A goat as printed on its label.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagLabel {
+    pub name: String,
+    pub tag_id: Option<String>,
+}
+
+impl TagLabel {
+    /// What the QR code holds: the tag ID, or the name for untagged goats,
+    /// either of which finds the goat in a search.
+    pub fn qr_text(&self) -> &str {
+        self.tag_id.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Loads the goats named in `names`, in order.
+///
+/// # Errors
+/// - `AppError::InvalidInput` naming every goat that does not exist.
+fn load_labels(conn: &Connection, names: &[String]) -> Result<Vec<TagLabel>, AppError> {
+    let mut stmt =
+        conn.prepare("SELECT tag_id FROM goats WHERE name = ?1 AND deleted_at IS NULL")?;
+    let mut labels = Vec::with_capacity(names.len());
+    let mut missing = Vec::new();
+    for name in names {
+        match stmt
+            .query_row([name], |row| row.get::<_, Option<String>>(0))
+            .optional()?
+        {
+            Some(tag_id) => labels.push(TagLabel {
+                name: name.clone(),
+                tag_id: tag_id.filter(|t| !t.trim().is_empty()),
+            }),
+            None => missing.push(name.as_str()),
+        }
+    }
+    if !missing.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "No goat found with name {}",
+            missing.join(", ")
+        )));
+    }
+    Ok(labels)
+}
+
+/// Font size in points no larger than `size` at which `text` fits in
+/// `width` points.
+fn fitted_size(text: &str, size: f32, width: f32) -> f32 {
+    let chars = text.chars().count().max(1) as f32;
+    size.min(width / (chars * CHAR_WIDTH_EM))
+}
+
+/// Draws `code` as filled squares in a `size`-point square whose
+/// bottom-left corner is at `(x, y)`.
+fn draw_qr(content: &mut Content, code: &QrCode, x: f32, y: f32, size: f32) {
+    let width = code.width();
+    let module = size / (width + 2 * QR_QUIET_MODULES) as f32;
+    let origin = (
+        x + QR_QUIET_MODULES as f32 * module,
+        y + size - QR_QUIET_MODULES as f32 * module,
+    );
+    for (i, color) in code.to_colors().into_iter().enumerate() {
+        if color == Color::Dark {
+            let (column, row) = ((i % width) as f32, (i / width + 1) as f32);
+            content.rect(
+                origin.0 + column * module,
+                origin.1 - row * module,
+                module,
+                module,
+            );
+        }
+    }
+    content.fill_nonzero();
+}
+
+/// Renders `labels` onto `sheet`, leaving the first `skip` slots blank.
+/// Each label has the QR code on the left and the name over the tag ID on
+/// the right, both sized to the label.
+///
+/// # Errors
+/// - `AppError::InvalidInput` if a QR code cannot hold a goat's tag ID.
+pub fn tag_sheet_pdf(
+    labels: &[TagLabel],
+    sheet: &LabelSheet,
+    skip: usize,
+) -> Result<Vec<u8>, AppError> {
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let font_id = Ref::new(3);
+
+    let mm = |value: f64| value as f32 * POINTS_PER_MM;
+    let (page_width, page_height) = (mm(sheet.page_width), mm(sheet.page_height));
+    let (label_width, label_height) = (mm(sheet.label_width), mm(sheet.label_height));
+    let padding = LABEL_PADDING_MM * POINTS_PER_MM;
+    let qr_size = label_height - 2.0 * padding;
+    let text_width = label_width - qr_size - 3.0 * padding;
+    // 14 pt names on 38.1 mm tall labels, scaled for other stock
+    let name_size = label_height / 108.0 * 14.0;
+
+    let page_count = (skip + labels.len()).div_ceil(sheet.per_page());
+    let page_ids: Vec<Ref> = (0..page_count)
+        .map(|i| Ref::new(4 + 2 * i as i32))
+        .collect();
+    let mut pages: Vec<Content> = (0..page_count).map(|_| Content::new()).collect();
+    for (i, label) in labels.iter().enumerate() {
+        let (page, left, top) = sheet.position(skip + i);
+        let content = &mut pages[page];
+        let (x, y) = (mm(left), page_height - mm(top) - label_height);
+
+        let code = QrCode::new(label.qr_text()).map_err(|e| {
+            AppError::InvalidInput(format!("Cannot encode {} as a QR code: {}", label.name, e))
+        })?;
+        draw_qr(content, &code, x + padding, y + padding, qr_size);
+
+        let text_x = x + qr_size + 2.0 * padding;
+        let size = fitted_size(&label.name, name_size, text_width);
+        let name_y = y + label_height / 2.0 + size * 0.2;
+        show_text(content, text_x, name_y, size, &label.name);
+        let tag = label.tag_id.as_deref().unwrap_or("No tag ID");
+        let size = fitted_size(tag, name_size * 0.75, text_width);
+        show_text(content, text_x, name_y - size * 1.4, size, tag);
+    }
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id)
+        .kids(page_ids.iter().copied())
+        .count(page_count as i32);
+    pdf.type1_font(font_id).base_font(Name(b"Helvetica"));
+    for (content, page_id) in pages.into_iter().zip(&page_ids) {
+        let content_id = Ref::new(page_id.get() + 1);
+        let mut page = pdf.page(*page_id);
+        page.media_box(Rect::new(0.0, 0.0, page_width, page_height));
+        page.parent(page_tree_id);
+        page.contents(content_id);
+        page.resources().fonts().pair(FONT_NAME, font_id);
+        page.finish();
+        pdf.stream(content_id, &content.finish());
+    }
+    Ok(pdf.finish())
+}
+
+/// Handler for printing ear-tag labels.
+///
+/// # HTTP Method
+/// - `POST /reports/tag-sheet`
+///
+/// # Request
+/// - JSON `TagSheetRequest`: the goats to label in order, the label stock
+///   (`l7160` by default) and how many labels on the first sheet are
+///   already used.
+///
+/// # Success
+/// - Returns HTTP 200 with an `application/pdf` attachment of the sheets.
+///
+/// # Errors
+/// - Returns HTTP 400 if no goats are given, a goat does not exist, or
+///   `skip` is not less than the labels on a sheet.
+pub async fn tag_sheet(
+    db: web::Data<DbPool>,
+    request: web::Json<TagSheetRequest>,
+) -> Result<impl Responder, AppError> {
+    let request = request.into_inner();
+    debug!(
+        goats = request.goat_names.len(),
+        layout = ?request.layout,
+        "POST /reports/tag-sheet called"
+    );
+    if request.goat_names.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Choose at least one goat to print labels for".to_string(),
+        ));
+    }
+    let sheet = request.layout.sheet();
+    if request.skip >= sheet.per_page() {
+        warn!(skip = request.skip, "Tag sheet skips a whole page");
+        return Err(AppError::InvalidInput(format!(
+            "Can skip at most {} labels on a sheet of {}",
+            sheet.per_page() - 1,
+            sheet.per_page()
+        )));
+    }
+    let conn = db.get_conn()?;
+    let labels = load_labels(&conn, &request.goat_names)?;
+    let body = tag_sheet_pdf(&labels, &sheet, request.skip)?;
+
+    info!(labels = labels.len(), layout = ?request.layout, "Tag sheet rendered");
+    Ok(HttpResponse::Ok()
+        .content_type("application/pdf")
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"tag-sheet.pdf\"",
+        ))
+        .body(body))
+}
@@ -0,0 +1,234 @@
+//! This module tracks each goat's lifecycle stage (see `shared::lifecycle`).
+//!
+//! The stage lives on the goat, and every move is kept in
+//! `lifecycle_changes`. Moves outside the state machine are refused, so the
+//! history always reads as a path through it.
+
+use crate::db::DbPool;
+use crate::errors::{AppError, ParseEnumError};
+use crate::events::EventLog;
+use crate::handlers::settings::farm_today;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::{HttpResponse, Responder, web};
+use chrono::NaiveDate;
+use rusqlite::OptionalExtension;
+use shared::Gender;
+use shared::events::DomainEvent;
+use shared::lifecycle::{
+    LifecyclePipeline, LifecycleStage, PipelineGoat, PipelineStage, StageChange, StageTransition,
+    allowed_stages, check_transition,
+};
+use tracing::{debug, info, warn};
+
+fn parse_stage(stage: &str) -> Result<LifecycleStage, AppError> {
+    LifecycleStage::from_str(stage)
+        .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "LifecycleStage")))
+}
+
+fn parse_gender(gender: &str) -> Result<Gender, AppError> {
+    Gender::from_str(gender).map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "Gender")))
+}
+
+/// Handler for laying out the herd by lifecycle stage.
+///
+/// # HTTP Method
+/// - `GET /lifecycle`
+///
+/// # Success
+/// - Returns HTTP 200 with a `LifecyclePipeline`: the goats at each stage
+///   by name, each with the stages it may move to, and the goats not yet
+///   given a stage.
+pub async fn get_pipeline(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /lifecycle called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, name, gender, lifecycle_stage FROM goats \
+         WHERE deleted_at IS NULL ORDER BY name",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut pipeline = LifecyclePipeline {
+        stages: LifecycleStage::ALL
+            .into_iter()
+            .map(|stage| PipelineStage {
+                stage,
+                goats: Vec::new(),
+            })
+            .collect(),
+        untracked: Vec::new(),
+    };
+    for (id, name, gender, stage) in rows {
+        let stage = stage.as_deref().map(parse_stage).transpose()?;
+        let goat = PipelineGoat {
+            id,
+            name,
+            next: allowed_stages(stage, &parse_gender(&gender)?),
+        };
+        match stage {
+            Some(stage) => pipeline
+                .stages
+                .iter_mut()
+                .filter(|s| s.stage == stage)
+                .for_each(|s| s.goats.push(goat.clone())),
+            None => pipeline.untracked.push(goat),
+        }
+    }
+
+    info!(
+        untracked = pipeline.untracked.len(),
+        "Returning lifecycle pipeline"
+    );
+    Ok(HttpResponse::Ok().json(pipeline))
+}
+
+/// Handler for moving a goat to another lifecycle stage.
+///
+/// # HTTP Method
+/// - `PUT /lifecycle`
+///
+/// # Request
+/// - JSON `StageChange`. Without a date the move is dated today; a blank
+///   note is stored as none.
+///
+/// # Success
+/// - Returns HTTP 200 with the recorded `StageTransition`.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown goat, a malformed date, or a move the
+///   lifecycle does not allow from the goat's current stage.
+pub async fn change_stage(
+    db: web::Data<DbPool>,
+    events: EventLog,
+    change: web::Json<StageChange>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat = %change.goat_name, stage = ?change.stage, "PUT /lifecycle called");
+    let mut conn = db.get_conn()?;
+    let changed_on = match &change.changed_on {
+        Some(date) => NaiveDate::parse_from_str(date, DATE_FORMAT).map_err(|_| {
+            AppError::InvalidInput(format!("changed_on must be YYYY-MM-DD, got '{}'", date))
+        })?,
+        None => farm_today(&conn)?,
+    }
+    .format(DATE_FORMAT)
+    .to_string();
+    let note = change
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty());
+
+    let tx = conn.transaction()?;
+    let goat = tx
+        .query_row(
+            "SELECT id, gender, lifecycle_stage FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [&change.goat_name],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            },
+        )
+        .optional()?;
+    let Some((goat_id, gender, from)) = goat else {
+        return Err(AppError::InvalidInput(format!(
+            "No goat found with name {}",
+            change.goat_name
+        )));
+    };
+    let from = from.as_deref().map(parse_stage).transpose()?;
+    if let Err(e) = check_transition(from, change.stage, &parse_gender(&gender)?) {
+        warn!(goat = %change.goat_name, from = ?from, to = ?change.stage, "Lifecycle move refused");
+        return Err(AppError::InvalidInput(e));
+    }
+
+    events.apply(
+        &tx,
+        &DomainEvent::LifecycleChanged {
+            goat_id,
+            stage: change.stage,
+            changed_on: changed_on.clone(),
+            note: note.map(str::to_string),
+        },
+    )?;
+    tx.commit()?;
+
+    info!(goat = %change.goat_name, from = ?from, to = ?change.stage, "Lifecycle stage changed");
+    Ok(HttpResponse::Ok().json(StageTransition {
+        from,
+        to: change.stage,
+        changed_on,
+        note: note.map(str::to_string),
+    }))
+}
+
+/// Handler for a goat's lifecycle history.
+///
+/// # HTTP Method
+/// - `GET /lifecycle/{goat_name}/history`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `StageTransition`s, oldest first.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown goat.
+pub async fn get_history(
+    db: web::Data<DbPool>,
+    path: web::Path<String>,
+) -> Result<impl Responder, AppError> {
+    let goat_name = path.into_inner();
+    debug!(goat_name, "GET /lifecycle/{{goat_name}}/history called");
+    let conn = db.get_conn()?;
+    let goat_id: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [&goat_name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(goat_id) = goat_id else {
+        return Err(AppError::InvalidInput(format!(
+            "No goat found with name {}",
+            goat_name
+        )));
+    };
+
+    let mut stmt = conn.prepare(
+        "SELECT from_stage, to_stage, changed_on, note FROM lifecycle_changes \
+         WHERE goat_id = ?1 ORDER BY changed_on, id",
+    )?;
+    let rows = stmt
+        .query_map([goat_id], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let history = rows
+        .into_iter()
+        .map(|(from, to, changed_on, note)| {
+            Ok(StageTransition {
+                from: from.as_deref().map(parse_stage).transpose()?,
+                to: parse_stage(&to)?,
+                changed_on,
+                note,
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    info!(goat_name, "Returning {} lifecycle changes", history.len());
+    Ok(HttpResponse::Ok().json(history))
+}
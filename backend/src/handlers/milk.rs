@@ -0,0 +1,129 @@
+//! This module handles milk records and lactation analytics (see
+//! `crate::lactation` for how records are grouped into lactations).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::lactation::load_lactations;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::{HttpResponse, Responder, web};
+use chrono::NaiveDate;
+use rusqlite::{OptionalExtension, params};
+use serde::Deserialize;
+use shared::milk::MilkRecord;
+use tracing::{debug, info};
+
+/// Query parameters accepted by `GET /milk/records` and `GET /milk/lactations`.
+#[derive(Deserialize)]
+pub struct MilkQuery {
+    pub doe_name: Option<String>,
+}
+
+/// Handler for listing milk records, newest first.
+///
+/// # HTTP Method
+/// - `GET /milk/records?doe_name=Rani`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of milk records.
+pub async fn get_milk_records(
+    db: web::Data<DbPool>,
+    query: web::Query<MilkQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(doe_name = ?query.doe_name, "GET /milk/records called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(
+        "SELECT m.id, g.name, m.recorded_on, m.yield_litres FROM milk_records m \
+         JOIN goats g ON g.id = m.doe_id AND g.deleted_at IS NULL \
+         WHERE (?1 IS NULL OR g.name = ?1) \
+         ORDER BY m.recorded_on DESC, m.id DESC",
+    )?;
+    let records: Vec<MilkRecord> = stmt
+        .query_map([&query.doe_name], |row| {
+            Ok(MilkRecord {
+                id: row.get(0)?,
+                doe_name: row.get(1)?,
+                recorded_on: row.get(2)?,
+                yield_litres: row.get(3)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    info!("Returning {} milk records", records.len());
+    Ok(HttpResponse::Ok().json(records))
+}
+
+/// Handler for recording a milking.
+///
+/// # HTTP Method
+/// - `POST /milk/records`
+///
+/// # Success
+/// - Returns HTTP 201 on successful insertion.
+///
+/// # Errors
+/// - Returns HTTP 400 if the yield is negative, the date is malformed, or the
+///   goat is unknown or not a doe.
+pub async fn add_milk_record(
+    db: web::Data<DbPool>,
+    record: web::Json<MilkRecord>,
+) -> Result<impl Responder, AppError> {
+    debug!(doe = %record.doe_name, "POST /milk/records called");
+    if record.yield_litres < 0.0 {
+        return Err(AppError::InvalidInput(
+            "yield_litres cannot be negative".into(),
+        ));
+    }
+    NaiveDate::parse_from_str(&record.recorded_on, DATE_FORMAT).map_err(|_| {
+        AppError::InvalidInput(format!(
+            "recorded_on must be YYYY-MM-DD, got '{}'",
+            record.recorded_on
+        ))
+    })?;
+
+    let conn = db.get_conn()?;
+    let (doe_id, gender): (i64, String) = conn
+        .query_row(
+            "SELECT id, gender FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [&record.doe_name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!("No goat found with name {}", record.doe_name))
+        })?;
+    if gender != "Female" {
+        return Err(AppError::InvalidInput(format!(
+            "Goat {} is {}, expected Female",
+            record.doe_name, gender
+        )));
+    }
+
+    conn.execute(
+        "INSERT INTO milk_records (doe_id, recorded_on, yield_litres) VALUES (?1, ?2, ?3)",
+        params![doe_id, record.recorded_on, record.yield_litres],
+    )?;
+    info!(
+        record_id = conn.last_insert_rowid(),
+        doe_id, "Milk record added"
+    );
+    Ok(HttpResponse::Created().body("Milk record added"))
+}
+
+/// Handler for lactation analytics.
+///
+/// # HTTP Method
+/// - `GET /milk/lactations?doe_name=Rani`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `Lactation`, grouped by doe and
+///   ordered by start date, each with its weekly curve, peak yield and days in milk.
+pub async fn get_lactations(
+    db: web::Data<DbPool>,
+    query: web::Query<MilkQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(doe_name = ?query.doe_name, "GET /milk/lactations called");
+    let conn = db.get_conn()?;
+    let lactations = load_lactations(&conn, query.doe_name.as_deref())?;
+    info!("Returning {} lactations", lactations.len());
+    Ok(HttpResponse::Ok().json(lactations))
+}
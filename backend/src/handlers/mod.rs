@@ -1,3 +1,67 @@
 //! Handler modules re-export for easier imports
 
+pub mod activity;
+pub mod alerts;
+pub mod analytics;
+pub mod api_keys;
+pub mod archive;
+pub mod attachments;
+pub mod breeding;
+pub mod breeds;
+pub mod calendar;
+pub mod client_errors;
+pub mod data_health;
+pub mod diseases;
+pub mod events;
+pub mod exports;
+pub mod external_animals;
+pub mod finance;
 pub mod goats;
+pub mod gps;
+pub mod grazing;
+pub mod growth;
+pub mod health;
+pub mod import;
+pub mod insurance;
+pub mod inventory;
+pub mod jobs;
+pub mod lab_results;
+pub mod labels;
+pub mod lifecycle;
+pub mod milk;
+pub mod movements;
+pub mod notes;
+pub mod notifications;
+pub mod nutrition;
+pub mod permissions;
+pub mod pricing;
+pub mod reminders;
+pub mod reports;
+pub mod retention;
+pub mod scale;
+pub mod scoring;
+pub mod search;
+pub mod sensors;
+pub mod sessions;
+pub mod settings;
+pub mod spaces;
+pub mod stats;
+pub mod tags;
+pub mod tasks;
+pub mod tenants;
+pub mod tokens;
+pub mod traceability;
+pub mod vet_visits;
+pub mod water;
+pub mod workers;
+
+use crate::errors::AppError;
+use crate::scheduler::DATE_FORMAT;
+use chrono::NaiveDate;
+
+/// Parses a `DATE_FORMAT` date sent as `field`, rejecting malformed input.
+pub fn parse_date(value: &str, field: &str) -> Result<NaiveDate, AppError> {
+    NaiveDate::parse_from_str(value, DATE_FORMAT).map_err(|_| {
+        AppError::InvalidInput(format!("{} must be YYYY-MM-DD, got '{}'", field, value))
+    })
+}
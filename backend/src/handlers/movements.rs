@@ -0,0 +1,278 @@
+//! This module handles the movement register: goats moved between farms,
+//! markets and buyers, with their transport details (see
+//! `shared::movements`).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::parse_date;
+use actix_web::{HttpResponse, Responder, web};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Deserialize;
+use shared::movements::{AnimalMovement, Place, PlaceKind};
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, info};
+
+/// Query parameters accepted by `GET /movements`. `from` and `to` bound
+/// the day the goats left, inclusive. Goat names are not unique, so
+/// `goat_id` picks out one goat where `goat_name` may match several.
+#[derive(Deserialize, Default)]
+pub struct MovementQuery {
+    pub goat_id: Option<i64>,
+    pub goat_name: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Columns read by `row_to_movement`.
+const MOVEMENT_COLUMNS: &str = "m.id, m.from_kind, m.from_name, m.to_kind, m.to_name, \
+     m.departed_on, m.arrived_on, m.transporter, m.vehicle, m.permit_number, m.notes";
+
+/// Parses a place kind stored in column `column`.
+fn place_kind(row: &Row, column: usize) -> Result<PlaceKind, AppError> {
+    let kind: String = row.get(column)?;
+    PlaceKind::from_str(&kind)
+        .map_err(|value| AppError::InvalidInput(format!("Unknown place kind '{}'", value)))
+}
+
+/// Maps a `MOVEMENT_COLUMNS` row to an `AnimalMovement` without its goats.
+fn row_to_movement(row: &Row) -> Result<AnimalMovement, AppError> {
+    Ok(AnimalMovement {
+        id: row.get(0)?,
+        goats: Vec::new(),
+        tags: Vec::new(),
+        from: Place {
+            kind: place_kind(row, 1)?,
+            name: row.get(2)?,
+        },
+        to: Place {
+            kind: place_kind(row, 3)?,
+            name: row.get(4)?,
+        },
+        departed_on: row.get(5)?,
+        arrived_on: row.get(6)?,
+        transporter: row.get(7)?,
+        vehicle: row.get(8)?,
+        permit_number: row.get(9)?,
+        notes: row.get(10)?,
+    })
+}
+
+/// Loads the movement with ID `id`, or the movements matching `query` when
+/// `id` is `None`, latest departure first. A movement is listed with all
+/// of its goats, by name and tag as they moved, even when `query` names
+/// one of them.
+fn query_movements(
+    conn: &Connection,
+    id: Option<i64>,
+    query: &MovementQuery,
+) -> Result<Vec<AnimalMovement>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT movement_id, goat_name, tag_id FROM goat_movement_goats \
+         ORDER BY goat_name, id",
+    )?;
+    let mut goats: HashMap<i64, Vec<(String, Option<String>)>> = HashMap::new();
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)?, (row.get(1)?, row.get(2)?)))
+    })?;
+    for row in rows {
+        let (movement_id, goat) = row?;
+        goats.entry(movement_id).or_default().push(goat);
+    }
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM goat_movements m
+         WHERE (?1 IS NULL OR m.id = ?1)
+           AND (?2 IS NULL OR m.id IN (
+               SELECT movement_id FROM goat_movement_goats WHERE goat_id = ?2))
+           AND (?3 IS NULL OR m.id IN (
+               SELECT mg.movement_id FROM goat_movement_goats mg
+               JOIN goats g ON g.id = mg.goat_id AND g.deleted_at IS NULL WHERE g.name = ?3))
+           AND (?4 IS NULL OR m.departed_on >= ?4)
+           AND (?5 IS NULL OR m.departed_on <= ?5)
+         ORDER BY m.departed_on DESC, m.id DESC",
+        MOVEMENT_COLUMNS
+    ))?;
+    let mut rows = stmt.query(params![
+        id,
+        query.goat_id,
+        query.goat_name,
+        query.from,
+        query.to
+    ])?;
+    let mut movements = Vec::new();
+    while let Some(row) = rows.next()? {
+        let mut movement = row_to_movement(row)?;
+        if let Some(id) = movement.id {
+            (movement.goats, movement.tags) =
+                goats.remove(&id).unwrap_or_default().into_iter().unzip();
+        }
+        movements.push(movement);
+    }
+    Ok(movements)
+}
+
+/// Loads the movements of the goat with ID `goat_id`, oldest departure
+/// first.
+pub fn goat_movements(conn: &Connection, goat_id: i64) -> Result<Vec<AnimalMovement>, AppError> {
+    let query = MovementQuery {
+        goat_id: Some(goat_id),
+        ..MovementQuery::default()
+    };
+    let mut movements = query_movements(conn, None, &query)?;
+    movements.reverse();
+    Ok(movements)
+}
+
+/// Handler for the movement register, of one goat and one period if asked.
+///
+/// # HTTP Method
+/// - `GET /movements?goat_name=Rani&from=2026-01-01&to=2026-03-31`, or
+///   `?goat_id=3` for one goat by ID
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `AnimalMovement`, latest
+///   departure first. `from` and `to` bound the departure day, inclusive.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown goat, a malformed date, or `from`
+///   after `to`.
+pub async fn get_movements(
+    db: web::Data<DbPool>,
+    query: web::Query<MovementQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(
+        goat_id = ?query.goat_id,
+        goat_name = ?query.goat_name,
+        from = ?query.from,
+        to = ?query.to,
+        "GET /movements called"
+    );
+    let from = query
+        .from
+        .as_deref()
+        .map(|d| parse_date(d, "from"))
+        .transpose()?;
+    let to = query
+        .to
+        .as_deref()
+        .map(|d| parse_date(d, "to"))
+        .transpose()?;
+    if let (Some(from), Some(to)) = (from, to)
+        && from > to
+    {
+        return Err(AppError::InvalidInput("from must not be after to".into()));
+    }
+    let conn = db.get_conn()?;
+    if let Some(goat) = &query.goat_name {
+        conn.query_row(
+            "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [goat],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::InvalidInput(format!("No goat found with name {}", goat)))?;
+    }
+    if let Some(id) = query.goat_id {
+        conn.query_row(
+            "SELECT id FROM goats WHERE id = ?1 AND deleted_at IS NULL",
+            [id],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::InvalidInput(format!("No goat found with ID {}", id)))?;
+    }
+    let movements = query_movements(&conn, None, &query)?;
+
+    info!("Returning {} movements", movements.len());
+    Ok(HttpResponse::Ok().json(movements))
+}
+
+/// Handler for logging a movement.
+///
+/// # HTTP Method
+/// - `POST /movements`
+///
+/// # Request
+/// - JSON `AnimalMovement`; its `id` is ignored.
+///
+/// # Success
+/// - Returns HTTP 201 with the stored `AnimalMovement`.
+///
+/// # Errors
+/// - Returns HTTP 400 if the movement is invalid (see
+///   `AnimalMovement::validate`), a goat does not exist, a date is
+///   malformed, or the goats arrived before they left.
+pub async fn add_movement(
+    db: web::Data<DbPool>,
+    movement: web::Json<AnimalMovement>,
+) -> Result<impl Responder, AppError> {
+    debug!(
+        goats = movement.goats.len(),
+        departed_on = %movement.departed_on,
+        "POST /movements called"
+    );
+    movement.validate().map_err(AppError::InvalidInput)?;
+    let departed_on = parse_date(&movement.departed_on, "departed_on")?;
+    if let Some(arrived_on) = &movement.arrived_on
+        && parse_date(arrived_on, "arrived_on")? < departed_on
+    {
+        return Err(AppError::InvalidInput(
+            "The goats cannot arrive before they leave".into(),
+        ));
+    }
+    // Blank details are left unset
+    let detail = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO goat_movements (from_kind, from_name, to_kind, to_name, departed_on,
+             arrived_on, transporter, vehicle, permit_number, notes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            PlaceKind::to_str(&movement.from.kind),
+            movement.from.name.trim(),
+            PlaceKind::to_str(&movement.to.kind),
+            movement.to.name.trim(),
+            movement.departed_on,
+            movement.arrived_on,
+            detail(&movement.transporter),
+            detail(&movement.vehicle),
+            detail(&movement.permit_number),
+            detail(&movement.notes),
+        ],
+    )?;
+    let movement_id = tx.last_insert_rowid();
+    let mut seen = HashSet::new();
+    for goat in movement.goats.iter().map(|g| g.trim()) {
+        if goat.is_empty() || !seen.insert(goat) {
+            continue;
+        }
+        let (goat_id, tag_id): (i64, Option<String>) = tx
+            .query_row(
+                "SELECT id, tag_id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+                [goat],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?
+            .ok_or_else(|| AppError::InvalidInput(format!("No goat found with name {}", goat)))?;
+        tx.execute(
+            "INSERT INTO goat_movement_goats (movement_id, goat_id, goat_name, tag_id)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![movement_id, goat_id, goat, tag_id],
+        )?;
+    }
+    let stored = query_movements(&tx, Some(movement_id), &MovementQuery::default())?
+        .pop()
+        .ok_or_else(|| AppError::InvalidInput("The movement was not stored".into()))?;
+    tx.commit()?;
+
+    info!(movement_id, goats = stored.goats.len(), "Movement logged");
+    Ok(HttpResponse::Created().json(stored))
+}
@@ -0,0 +1,307 @@
+//! This module handles the notes threads workers leave on goats (see
+//! `shared::notes`), including the photo attached to a note.
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::notifications::notify_mentions;
+use crate::handlers::scale::READ_AT_FORMAT;
+use crate::jobs::{JobQueue, JobRequest, enqueue};
+use crate::messaging::MessageGateway;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Deserialize;
+use shared::notes::{GoatNote, NoteInput};
+use tracing::{debug, info, warn};
+
+/// Largest photo accepted on a note.
+pub const MAX_NOTE_PHOTO_BYTES: usize = 10 * 1024 * 1024;
+
+/// Query parameters accepted by `GET /notes`.
+#[derive(Deserialize)]
+pub struct NotesQuery {
+    pub goat_name: String,
+}
+
+/// Columns read by `row_to_note`, with the notes aliased `n` and the goats
+/// `g`.
+const NOTE_COLUMNS: &str = "n.id, g.name, n.author, n.text, n.created_at, n.photo IS NOT NULL";
+
+fn row_to_note(row: &Row) -> rusqlite::Result<GoatNote> {
+    Ok(GoatNote {
+        id: row.get(0)?,
+        goat_name: row.get(1)?,
+        author: row.get(2)?,
+        text: row.get(3)?,
+        created_at: row.get(4)?,
+        has_photo: row.get(5)?,
+    })
+}
+
+/// Looks up a goat's ID by name.
+fn goat_id(conn: &Connection, name: &str) -> Result<i64, AppError> {
+    conn.query_row(
+        "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+        [name],
+        |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| AppError::InvalidInput(format!("No goat found with name {}", name)))
+}
+
+/// Loads the note with `id`.
+fn load_note(conn: &Connection, id: i64) -> Result<GoatNote, AppError> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM goat_notes n JOIN goats g ON g.id = n.goat_id AND g.deleted_at IS NULL WHERE n.id = ?1",
+            NOTE_COLUMNS
+        ),
+        [id],
+        row_to_note,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::InvalidInput(format!("No note found with ID {}", id)))
+}
+
+/// Handler for a goat's notes thread.
+///
+/// # HTTP Method
+/// - `GET /notes?goat_name=Rani`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `GoatNote`, oldest first.
+///
+/// # Errors
+/// - Returns HTTP 400 if no goat has the given name.
+pub async fn get_notes(
+    db: web::Data<DbPool>,
+    query: web::Query<NotesQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat = %query.goat_name, "GET /notes called");
+    let conn = db.get_conn()?;
+    let goat_id = goat_id(&conn, &query.goat_name)?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM goat_notes n JOIN goats g ON g.id = n.goat_id AND g.deleted_at IS NULL
+         WHERE n.goat_id = ?1 ORDER BY n.created_at, n.id",
+        NOTE_COLUMNS
+    ))?;
+    let notes: Vec<GoatNote> = stmt
+        .query_map([goat_id], row_to_note)?
+        .collect::<Result<_, _>>()?;
+
+    info!("Returning {} notes for {}", notes.len(), query.goat_name);
+    Ok(HttpResponse::Ok().json(notes))
+}
+
+/// Handler for adding a note to a goat.
+///
+/// Workers mentioned as `@Name` in the text are notified (see
+/// `notifications::notify_mentions`); those who asked for email or SMS are
+/// also sent one through the registered `MessageGateway`, if any. With a
+/// `JobQueue` registered the messages are sent by a background job, which
+/// retries them while the gateway is unreachable; otherwise they are sent
+/// before answering, and a message that cannot be sent is logged. Either
+/// way a failed message does not fail the note.
+///
+/// # HTTP Method
+/// - `POST /notes`
+///
+/// # Request
+/// - A JSON `NoteInput`. A photo is attached afterwards with
+///   `PUT /notes/{id}/photo`.
+///
+/// # Success
+/// - Returns HTTP 201 with the stored `GoatNote`, stamped with the current
+///   time.
+///
+/// # Errors
+/// - Returns HTTP 400 for an empty or overlong author or text, or if no goat
+///   has the given name.
+pub async fn add_note(
+    db: web::Data<DbPool>,
+    gateway: Option<web::Data<dyn MessageGateway>>,
+    queue: Option<web::Data<JobQueue>>,
+    note: web::Json<NoteInput>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat = %note.goat_name, author = %note.author, "POST /notes called");
+    note.validate().map_err(AppError::InvalidInput)?;
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+    let goat_name = note.goat_name.trim();
+    let goat_id = goat_id(&tx, goat_name)?;
+    tx.execute(
+        "INSERT INTO goat_notes (goat_id, author, text, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            goat_id,
+            note.author.trim(),
+            note.text.trim(),
+            Utc::now().format(READ_AT_FORMAT).to_string()
+        ],
+    )?;
+    let note_id = tx.last_insert_rowid();
+    let mut outgoing = notify_mentions(
+        &tx,
+        goat_id,
+        goat_name,
+        note_id,
+        note.author.trim(),
+        note.text.trim(),
+    )?;
+    // Queued with the note, so the messages go out even if sending fails now
+    let queue = queue.filter(|_| gateway.is_some() && !outgoing.is_empty());
+    if queue.is_some() {
+        let messages = std::mem::take(&mut outgoing);
+        let job = enqueue(&tx, &JobRequest::SendMessages { messages })?;
+        debug!(note_id, job_id = job.id, "Mentions queued for sending");
+    }
+    tx.commit()?;
+    let stored = load_note(&conn, note_id)?;
+    if let Some(queue) = queue {
+        queue.notify();
+    }
+
+    match gateway {
+        Some(gateway) => {
+            for message in outgoing {
+                let gateway = gateway.clone();
+                match web::block(move || gateway.send(&message)).await {
+                    Ok(Ok(())) => debug!(note_id, "Mention sent"),
+                    Ok(Err(e)) => warn!(note_id, "Mention could not be sent: {}", e),
+                    Err(e) => warn!(note_id, "Mention could not be sent: {}", e),
+                }
+            }
+        }
+        None if !outgoing.is_empty() => {
+            warn!(
+                note_id,
+                count = outgoing.len(),
+                "No message gateway configured; mentions notified in-app only"
+            );
+        }
+        None => {}
+    }
+
+    info!(note_id, goat_id, "Note added");
+    Ok(HttpResponse::Created().json(stored))
+}
+
+/// Handler for attaching a photo to a note, replacing any earlier one.
+///
+/// # HTTP Method
+/// - `PUT /notes/{id}/photo`
+///
+/// # Request
+/// - The raw image (at most `MAX_NOTE_PHOTO_BYTES`) with an `image/*`
+///   `Content-Type`.
+///
+/// # Success
+/// - Returns HTTP 200 with the updated `GoatNote`.
+///
+/// # Errors
+/// - Returns HTTP 400 if the photo is empty or not an image, or the note
+///   does not exist.
+pub async fn set_photo(
+    db: web::Data<DbPool>,
+    req: HttpRequest,
+    path: web::Path<i64>,
+    body: web::Bytes,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(
+        note_id = id,
+        bytes = body.len(),
+        "PUT /notes/{{id}}/photo called"
+    );
+    if body.is_empty() {
+        return Err(AppError::InvalidInput("The photo is empty".into()));
+    }
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    if !content_type.starts_with("image/") {
+        return Err(AppError::InvalidInput(format!(
+            "The photo must be an image, got '{}'",
+            content_type
+        )));
+    }
+    let conn = db.get_conn()?;
+    let affected = conn.execute(
+        "UPDATE goat_notes SET photo = ?1, photo_type = ?2 WHERE id = ?3",
+        params![body.as_ref(), content_type, id],
+    )?;
+    if affected == 0 {
+        warn!(note_id = id, "Note not found for photo");
+        return Err(AppError::InvalidInput(format!(
+            "No note found with ID {}",
+            id
+        )));
+    }
+    let stored = load_note(&conn, id)?;
+
+    info!(note_id = id, bytes = body.len(), "Photo attached to note");
+    Ok(HttpResponse::Ok().json(stored))
+}
+
+/// Handler for fetching the photo attached to a note.
+///
+/// # HTTP Method
+/// - `GET /notes/{id}/photo`
+///
+/// # Success
+/// - Returns HTTP 200 with the image and its original `Content-Type`.
+///
+/// # Errors
+/// - Returns HTTP 400 if the note does not exist or has no photo.
+pub async fn get_photo(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(note_id = id, "GET /notes/{{id}}/photo called");
+    let conn = db.get_conn()?;
+    let (photo, content_type): (Vec<u8>, String) = conn
+        .query_row(
+            "SELECT photo, photo_type FROM goat_notes WHERE id = ?1 AND photo IS NOT NULL",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::InvalidInput(format!("Note {} has no photo", id)))?;
+
+    info!(note_id = id, bytes = photo.len(), "Returning note photo");
+    Ok(HttpResponse::Ok().content_type(content_type).body(photo))
+}
+
+/// Handler for deleting a note and its photo.
+///
+/// # HTTP Method
+/// - `DELETE /notes/{id}`
+///
+/// # Success
+/// - Returns HTTP 200 with a confirmation message.
+///
+/// # Errors
+/// - Returns HTTP 400 if the note does not exist.
+pub async fn delete_note(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(note_id = id, "DELETE /notes/{{id}} called");
+    let conn = db.get_conn()?;
+    let affected = conn.execute("DELETE FROM goat_notes WHERE id = ?1", [id])?;
+    if affected == 0 {
+        warn!(note_id = id, "Note not found for deletion");
+        return Err(AppError::InvalidInput(format!(
+            "No note found with ID {}",
+            id
+        )));
+    }
+
+    info!(note_id = id, "Note deleted");
+    Ok(HttpResponse::Ok().body("Note deleted"))
+}
@@ -0,0 +1,167 @@
+//! This module handles workers' in-app notifications, made when they are
+//! mentioned in a goat's note (see `shared::notifications`).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::scale::READ_AT_FORMAT;
+use crate::handlers::workers::load_workers;
+use crate::messaging::OutgoingMessage;
+use actix_web::{HttpResponse, Responder, web};
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Deserialize;
+use shared::notifications::{Notification, NotifyChannel, goat_link, mentioned, notification_link};
+use tracing::{debug, info, trace, warn};
+
+/// How much of the note a mention notification quotes, in characters.
+pub const MENTION_EXCERPT_CHARS: usize = 140;
+
+/// Query parameters accepted by `GET /notifications`.
+#[derive(Deserialize)]
+pub struct NotificationsQuery {
+    pub worker: String,
+    /// Only list notifications not yet marked read.
+    #[serde(default)]
+    pub unread: bool,
+}
+
+/// Records a notification for every worker mentioned in note `note_id` on
+/// goat `goat_id`, except its author, and returns the emails and SMS due
+/// to those who asked for them.
+pub fn notify_mentions(
+    conn: &Connection,
+    goat_id: i64,
+    goat_name: &str,
+    note_id: i64,
+    author: &str,
+    text: &str,
+) -> Result<Vec<OutgoingMessage>, AppError> {
+    let workers = load_workers(conn)?;
+    let names: Vec<String> = workers.iter().map(|w| w.name.clone()).collect();
+    let excerpt: String = if text.chars().count() > MENTION_EXCERPT_CHARS {
+        let cut: String = text.chars().take(MENTION_EXCERPT_CHARS - 1).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        text.to_string()
+    };
+    let message = format!("{} mentioned you on {}: {}", author, goat_name, excerpt);
+    let link = goat_link(goat_id);
+    let created_at = Utc::now().format(READ_AT_FORMAT).to_string();
+
+    let mut outgoing = Vec::new();
+    for name in mentioned(text, &names) {
+        let Some(worker) = workers.iter().find(|w| w.name == name) else {
+            continue;
+        };
+        if worker.name.eq_ignore_ascii_case(author) {
+            trace!(worker = %worker.name, "Skipping self-mention");
+            continue;
+        }
+        conn.execute(
+            "INSERT INTO notifications (worker_id, goat_id, note_id, message, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![worker.id, goat_id, note_id, message, created_at],
+        )?;
+        debug!(worker = %worker.name, note_id, "Mention notification recorded");
+        let contact = worker.contact.as_deref().unwrap_or_default().trim();
+        if worker.notify_by != NotifyChannel::App && !contact.is_empty() {
+            outgoing.push(OutgoingMessage {
+                channel: worker.notify_by,
+                to: contact.to_string(),
+                text: message.clone(),
+                link: link.clone(),
+            });
+        }
+    }
+    Ok(outgoing)
+}
+
+/// Handler for a worker's notifications.
+///
+/// # HTTP Method
+/// - `GET /notifications?worker=Asha[&unread=true]`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `Notification`, newest first.
+///
+/// # Errors
+/// - Returns HTTP 400 if no worker has the given name.
+pub async fn get_notifications(
+    db: web::Data<DbPool>,
+    query: web::Query<NotificationsQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(worker = %query.worker, unread = query.unread, "GET /notifications called");
+    let conn = db.get_conn()?;
+    let worker_id: i64 = conn
+        .query_row(
+            "SELECT id FROM workers WHERE name = ?1 COLLATE NOCASE",
+            [query.worker.trim()],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!("No worker found with name {}", query.worker))
+        })?;
+    let mut stmt = conn.prepare(
+        "SELECT n.id, w.name, g.name, n.goat_id, n.note_id, n.message, n.created_at, n.read
+         FROM notifications n
+         JOIN workers w ON w.id = n.worker_id
+         LEFT JOIN goats g ON g.id = n.goat_id
+         WHERE n.worker_id = ?1 AND (?2 = 0 OR n.read = 0)
+         ORDER BY n.created_at DESC, n.id DESC",
+    )?;
+    let notifications: Vec<Notification> = stmt
+        .query_map(params![worker_id, query.unread], |row| {
+            Ok(Notification {
+                id: row.get(0)?,
+                worker_name: row.get(1)?,
+                goat_name: row.get(2)?,
+                link: notification_link(row.get(3)?),
+                note_id: row.get(4)?,
+                message: row.get(5)?,
+                created_at: row.get(6)?,
+                read: row.get(7)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    info!(
+        "Returning {} notifications for {}",
+        notifications.len(),
+        query.worker
+    );
+    Ok(HttpResponse::Ok().json(notifications))
+}
+
+/// Handler for marking a notification read.
+///
+/// # HTTP Method
+/// - `PUT /notifications/{id}/read`
+///
+/// # Success
+/// - Returns HTTP 200 with a confirmation message.
+///
+/// # Errors
+/// - Returns HTTP 400 if the notification does not exist.
+pub async fn mark_read(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(
+        notification_id = id,
+        "PUT /notifications/{{id}}/read called"
+    );
+    let conn = db.get_conn()?;
+    let affected = conn.execute("UPDATE notifications SET read = 1 WHERE id = ?1", [id])?;
+    if affected == 0 {
+        warn!(notification_id = id, "Notification not found");
+        return Err(AppError::InvalidInput(format!(
+            "No notification found with ID {}",
+            id
+        )));
+    }
+
+    info!(notification_id = id, "Notification marked read");
+    Ok(HttpResponse::Ok().body("Notification marked read"))
+}
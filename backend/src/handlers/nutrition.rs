@@ -0,0 +1,42 @@
+//! This module handles the ration formulation endpoint (see `crate::ration`
+//! for the optimizer).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::inventory::fetch_items;
+use crate::ration::formulate_ration;
+use actix_web::{HttpResponse, Responder, web};
+use shared::nutrition::RationRequest;
+use tracing::{debug, info};
+
+/// Handler for composing the cheapest ration from the feed in stock.
+///
+/// # HTTP Method
+/// - `GET /nutrition/ration?dry_matter_kg=1.2&crude_protein_g=150&energy_mj=12&goats=10&days=30`
+///
+/// # Request
+/// - Query daily targets per goat, and how many goats the stock must feed
+///   for how many days.
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON `RationPlan`: kilograms of each feed per
+///   goat per day, its cost, and the feed in stock that was left out.
+///
+/// # Errors
+/// - Returns HTTP 400 if a target is not positive, no goats or days are
+///   given, or the feed in stock cannot meet the targets.
+pub async fn get_ration(
+    db: web::Data<DbPool>,
+    request: web::Query<RationRequest>,
+) -> Result<impl Responder, AppError> {
+    debug!(request = ?*request, "GET /nutrition/ration called");
+    let conn = db.get_conn()?;
+    let plan = formulate_ration(&fetch_items(&conn)?, &request)?;
+
+    info!(
+        "Formulated a ration of {} feeds at {:.2} a day",
+        plan.items.len(),
+        plan.cost_per_day
+    );
+    Ok(HttpResponse::Ok().json(plan))
+}
@@ -0,0 +1,110 @@
+//! This module serves the permissions matrix of workers' roles, enforced by
+//! `crate::permissions::enforce_permissions`, and lets the owner edit it.
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::sessions::current_session;
+use crate::permissions::{load_role_permissions, session_permissions};
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use rusqlite::params;
+use shared::permissions::RolePermissions;
+use tracing::{debug, info, warn};
+
+/// Handler for the permissions of every role.
+///
+/// # HTTP Method
+/// - `GET /permissions`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `RolePermissions`, by role, for
+///   each role a worker has or the owner set permissions for. Roles the
+///   owner never restricted have every permission.
+pub async fn get_permissions(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /permissions called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(
+        "SELECT trim(role) FROM workers WHERE trim(coalesce(role, '')) != ''
+         UNION SELECT role FROM role_permissions ORDER BY 1",
+    )?;
+    let roles = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    let matrix = roles
+        .iter()
+        .map(|role| load_role_permissions(&conn, role))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    info!(count = matrix.len(), "Returning role permissions");
+    Ok(HttpResponse::Ok().json(matrix))
+}
+
+/// Handler for what the requesting session may do.
+///
+/// # HTTP Method
+/// - `GET /permissions/mine`
+///
+/// # Success
+/// - Returns HTTP 200 with the JSON `MyPermissions`: the signed-in worker's
+///   role and its permissions, or every permission for the owner.
+pub async fn get_my_permissions(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+) -> Result<impl Responder, AppError> {
+    debug!("GET /permissions/mine called");
+    let conn = db.get_conn()?;
+    let permissions = session_permissions(&conn, current_session(&req))?;
+
+    info!(worker = ?permissions.worker, "Returning session permissions");
+    Ok(HttpResponse::Ok().json(permissions))
+}
+
+/// Handler for setting what the workers of a role may do.
+///
+/// # HTTP Method
+/// - `PUT /permissions`
+///
+/// # Request
+/// - JSON `RolePermissions`: the role and every permission it is granted.
+///
+/// # Success
+/// - Returns HTTP 200 with the stored `RolePermissions`, sorted.
+///
+/// # Errors
+/// - Returns HTTP 400 if the role is blank or may change a module it cannot
+///   view.
+/// - Returns HTTP 403 if a worker, not the owner, made the request.
+pub async fn update_permissions(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    permissions: web::Json<RolePermissions>,
+) -> Result<impl Responder, AppError> {
+    let mut permissions = permissions.into_inner();
+    debug!(role = %permissions.role, "PUT /permissions called");
+    let conn = db.get_conn()?;
+    if !session_permissions(&conn, current_session(&req))?.is_owner() {
+        warn!(role = %permissions.role, "Refused permissions change by a worker");
+        return Err(AppError::Forbidden(
+            "Only the owner may change permissions".into(),
+        ));
+    }
+    permissions.role = permissions.role.trim().to_string();
+    permissions.validate().map_err(AppError::InvalidInput)?;
+    permissions.granted.sort();
+    permissions.granted.dedup();
+    conn.execute(
+        "INSERT INTO role_permissions (role, granted) VALUES (?1, ?2)
+         ON CONFLICT(role) DO UPDATE SET granted = excluded.granted,
+             updated_at = CURRENT_TIMESTAMP",
+        params![
+            permissions.role,
+            serde_json::to_string(&permissions.granted)?
+        ],
+    )?;
+
+    info!(
+        role = %permissions.role,
+        granted = permissions.granted.len(),
+        "Role permissions updated"
+    );
+    Ok(HttpResponse::Ok().json(permissions))
+}
@@ -0,0 +1,103 @@
+//! This module values the herd with the farm's pricing formula (see
+//! `shared::pricing`), either as a preview or by updating each goat's
+//! `current_price`.
+
+use crate::db::{DbPool, row_to_goat};
+use crate::errors::AppError;
+use crate::events::EventLog;
+use crate::handlers::settings::load_settings;
+use actix_web::{HttpResponse, Responder, web};
+use rusqlite::Connection;
+use shared::events::DomainEvent;
+use shared::pricing::GoatValuation;
+use shared::{GoatParams, GoatUpdate};
+use tracing::{debug, info, warn};
+
+/// Values every goat with the stored formula, in name order.
+pub fn load_valuations(conn: &Connection) -> Result<Vec<GoatValuation>, AppError> {
+    let pricing = load_settings(conn)?
+        .pricing
+        .ok_or_else(|| AppError::InvalidInput("No pricing formula is set".into()))?;
+    let mut stmt = conn.prepare("SELECT * FROM goats WHERE deleted_at IS NULL ORDER BY name")?;
+    let goats: Vec<GoatParams> = stmt
+        .query_map([], |row| {
+            row_to_goat(row).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+        })?
+        .collect::<Result<_, _>>()?;
+    pricing
+        .value_herd(&goats)
+        .map_err(|e| AppError::InvalidInput(format!("Stored pricing formula is invalid: {}", e)))
+}
+
+/// Handler previewing the formula price of every goat.
+///
+/// # HTTP Method
+/// - `GET /pricing/valuations`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `GoatValuation`; goats the formula
+///   cannot price carry an `error` instead of a `formula_price`.
+///
+/// # Errors
+/// - Returns HTTP 400 if no pricing formula is set.
+pub async fn get_valuations(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /pricing/valuations called");
+    let conn = db.get_conn()?;
+    let valuations = load_valuations(&conn)?;
+
+    info!("Returning valuations for {} goats", valuations.len());
+    Ok(HttpResponse::Ok().json(valuations))
+}
+
+/// Handler setting every goat's `current_price` to its formula price.
+///
+/// # HTTP Method
+/// - `POST /pricing/apply`
+///
+/// # Success
+/// - Returns HTTP 200 with the JSON `GoatValuation`s used. Goats the formula
+///   cannot price keep their current price. Each price that changed is
+///   logged as a goat update (see `crate::events`).
+///
+/// # Errors
+/// - Returns HTTP 400 if no pricing formula is set.
+pub async fn apply_pricing(
+    db: web::Data<DbPool>,
+    events: EventLog,
+) -> Result<impl Responder, AppError> {
+    debug!("POST /pricing/apply called");
+    let mut conn = db.get_conn()?;
+    let valuations = load_valuations(&conn)?;
+
+    let tx = conn.transaction()?;
+    let mut updated = 0;
+    for valuation in &valuations {
+        match valuation.formula_price {
+            Some(price) => {
+                let (goat_id, old_price): (i64, f64) = tx.query_row(
+                    "SELECT id, current_price FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+                    [&valuation.goat_name],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )?;
+                if old_price == price {
+                    continue;
+                }
+                let update = GoatUpdate {
+                    current_price: Some(price),
+                    ..GoatUpdate::default()
+                };
+                events.apply(&tx, &DomainEvent::GoatUpdated { goat_id, update })?;
+                updated += 1;
+            }
+            None => warn!(
+                goat_name = %valuation.goat_name,
+                error = ?valuation.error,
+                "Goat left unpriced"
+            ),
+        }
+    }
+    tx.commit()?;
+
+    info!(updated, "Herd revalued with pricing formula");
+    Ok(HttpResponse::Ok().json(valuations))
+}
@@ -0,0 +1,201 @@
+//! This module manages recurring reminder rules and the reminders they produce.
+//!
+//! Rules are evaluated periodically by the background scheduler; the
+//! `POST /rules/evaluate` endpoint triggers an evaluation on demand.
+
+use crate::db::DbPool;
+use crate::errors::{AppError, ParseEnumError};
+use crate::handlers::settings::farm_today;
+use crate::scheduler::{DATE_FORMAT, evaluate_policy_renewals, evaluate_rules};
+use crate::water::evaluate_water;
+use actix_web::{HttpResponse, Responder, web};
+use rusqlite::params;
+use shared::tasks::{Reminder, ReminderRule, RuleScope};
+use tracing::{debug, info, warn};
+
+/// Handler for listing all reminder rules.
+///
+/// # HTTP Method
+/// - `GET /rules`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of rules.
+pub async fn get_rules(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /rules called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, name, task_title, scope, interval_days, lead_days, active FROM reminder_rules ORDER BY id",
+    )?;
+    let rules: Result<Vec<ReminderRule>, rusqlite::Error> = stmt
+        .query_map([], |row| {
+            let scope: String = row.get(3)?;
+            Ok(ReminderRule {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                task_title: row.get(2)?,
+                scope: RuleScope::from_str(&scope).map_err(|e| {
+                    rusqlite::Error::ToSqlConversionFailure(Box::new(AppError::ParseError(
+                        ParseEnumError::new(&e, "RuleScope"),
+                    )))
+                })?,
+                interval_days: row.get(4)?,
+                lead_days: row.get(5)?,
+                active: row.get(6)?,
+            })
+        })?
+        .collect();
+    let rules = rules?;
+
+    info!("Returning {} reminder rules", rules.len());
+    Ok(HttpResponse::Ok().json(rules))
+}
+
+/// Handler for creating a reminder rule.
+///
+/// # HTTP Method
+/// - `POST /rules`
+///
+/// # Success
+/// - Returns HTTP 201 on successful insertion.
+///
+/// # Errors
+/// - Returns HTTP 400 for an empty title or a zero interval.
+pub async fn add_rule(
+    db: web::Data<DbPool>,
+    rule: web::Json<ReminderRule>,
+) -> Result<impl Responder, AppError> {
+    debug!(name = %rule.name, "POST /rules called");
+    if rule.task_title.trim().is_empty() {
+        return Err(AppError::InvalidInput("Rule task title is required".into()));
+    }
+    if rule.interval_days == 0 {
+        return Err(AppError::InvalidInput(
+            "Rule interval must be at least one day".into(),
+        ));
+    }
+
+    let conn = db.get_conn()?;
+    conn.execute(
+        "INSERT INTO reminder_rules (name, task_title, scope, interval_days, lead_days, active) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            rule.name,
+            rule.task_title,
+            RuleScope::to_str(&rule.scope),
+            rule.interval_days,
+            rule.lead_days,
+            rule.active,
+        ],
+    )?;
+
+    info!(rule_id = conn.last_insert_rowid(), "Reminder rule created");
+    Ok(HttpResponse::Created().body("Rule added"))
+}
+
+/// Handler for deleting a reminder rule. Tasks it already generated are kept.
+///
+/// # HTTP Method
+/// - `DELETE /rules/{id}`
+///
+/// # Errors
+/// - Returns HTTP 400 if no rule matches the ID.
+pub async fn delete_rule(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(rule_id = id, "DELETE /rules/{{id}} called");
+
+    let conn = db.get_conn()?;
+    conn.execute("UPDATE tasks SET rule_id = NULL WHERE rule_id = ?1", [id])?;
+    let affected = conn.execute("DELETE FROM reminder_rules WHERE id = ?1", [id])?;
+    if affected == 0 {
+        warn!(rule_id = id, "Rule not found for deletion");
+        return Err(AppError::InvalidInput(format!(
+            "No rule found with id {}",
+            id
+        )));
+    }
+
+    info!(rule_id = id, "Reminder rule deleted");
+    Ok(HttpResponse::Ok().body("Rule deleted"))
+}
+
+/// Handler for evaluating all reminder rules immediately, together with
+/// insurance policy renewals and pen water use.
+///
+/// # HTTP Method
+/// - `POST /rules/evaluate`
+///
+/// # Success
+/// - Returns HTTP 200 with `{"created": n}`.
+pub async fn evaluate_rules_now(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("POST /rules/evaluate called");
+    let mut conn = db.get_conn()?;
+    let today = farm_today(&conn)?;
+    let created = evaluate_rules(&mut conn, today)?
+        + evaluate_policy_renewals(&mut conn, today)?
+        + evaluate_water(&mut conn, today)?;
+
+    info!(created, "Reminder rules evaluated on demand");
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "created": created })))
+}
+
+/// Handler for listing reminders that are due and not yet dismissed.
+///
+/// # HTTP Method
+/// - `GET /reminders`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of reminders, oldest first.
+pub async fn get_reminders(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /reminders called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, task_id, remind_on, message, dismissed FROM reminders \
+         WHERE dismissed = 0 AND remind_on <= ?1 ORDER BY remind_on, id",
+    )?;
+    let today = farm_today(&conn)?.format(DATE_FORMAT).to_string();
+    let reminders: Vec<Reminder> = stmt
+        .query_map([today], |row| {
+            Ok(Reminder {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                remind_on: row.get(2)?,
+                message: row.get(3)?,
+                dismissed: row.get(4)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    info!("Returning {} due reminders", reminders.len());
+    Ok(HttpResponse::Ok().json(reminders))
+}
+
+/// Handler for dismissing a reminder.
+///
+/// # HTTP Method
+/// - `PUT /reminders/{id}/dismiss`
+///
+/// # Errors
+/// - Returns HTTP 400 if no reminder matches the ID.
+pub async fn dismiss_reminder(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(reminder_id = id, "PUT /reminders/{{id}}/dismiss called");
+
+    let conn = db.get_conn()?;
+    let affected = conn.execute("UPDATE reminders SET dismissed = 1 WHERE id = ?1", [id])?;
+    if affected == 0 {
+        warn!(reminder_id = id, "Reminder not found");
+        return Err(AppError::InvalidInput(format!(
+            "No reminder found with id {}",
+            id
+        )));
+    }
+
+    info!(reminder_id = id, "Reminder dismissed");
+    Ok(HttpResponse::Ok().body("Reminder dismissed"))
+}
@@ -0,0 +1,439 @@
+//! This module handles census report templates and renders census-style
+//! headcounts as JSON, CSV or PDF (see `shared::census`).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::settings::farm_today;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::http::header;
+use actix_web::{HttpResponse, Responder, web};
+use chrono::{Datelike, NaiveDate};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use shared::census::{CensusAnimal, CensusReport, ReportTemplate, build_census};
+use tracing::{debug, info, trace, warn};
+
+/// Output formats of `GET /reports/census/{key}`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Csv,
+    Pdf,
+}
+
+/// Query parameters accepted by `GET /reports/census/{key}`.
+#[derive(Deserialize)]
+pub struct CensusQuery {
+    /// Census date (`YYYY-MM-DD`); defaults to today.
+    pub as_of: Option<String>,
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+/// A4 page size in PDF points.
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+
+/// Page margin in PDF points.
+const MARGIN: f32 = 50.0;
+
+/// Line height of table rows in PDF points.
+const LINE_HEIGHT: f32 = 16.0;
+
+/// Resource name of the Helvetica font on every PDF page.
+pub(crate) const FONT_NAME: Name<'static> = Name(b"F1");
+
+/// Table rows per PDF page, leaving room for the heading.
+const ROWS_PER_PAGE: usize = 42;
+
+/// Built-in templates followed by the farm's own, each group ordered by key.
+pub fn load_templates(conn: &Connection) -> Result<Vec<ReportTemplate>, AppError> {
+    let mut stmt = conn.prepare("SELECT definition FROM report_templates ORDER BY template_key")?;
+    let custom = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut templates = ReportTemplate::built_ins();
+    for definition in custom {
+        templates.push(serde_json::from_str(&definition)?);
+    }
+    Ok(templates)
+}
+
+/// Finds the template with `key`.
+///
+/// # Errors
+/// - `AppError::InvalidInput` if there is no such template.
+pub(crate) fn find_template(conn: &Connection, key: &str) -> Result<ReportTemplate, AppError> {
+    load_templates(conn)?
+        .into_iter()
+        .find(|template| template.key == key)
+        .ok_or_else(|| AppError::InvalidInput(format!("No report template with key {}", key)))
+}
+
+/// Completed months between `born` and `on`, or `None` if `born` is later.
+pub fn age_in_months(born: NaiveDate, on: NaiveDate) -> Option<u32> {
+    if born > on {
+        return None;
+    }
+    let months = (on.year() - born.year()) * 12 + on.month() as i32 - born.month() as i32;
+    let months = if on.day() < born.day() {
+        months - 1
+    } else {
+        months
+    };
+    Some(months as u32)
+}
+
+/// Loads every goat alive on `as_of` as a census animal. Goats born after
+/// the census date are left out; an unparseable birth date counts as unknown.
+fn load_census_animals(conn: &Connection, as_of: NaiveDate) -> Result<Vec<CensusAnimal>, AppError> {
+    let mut stmt =
+        conn.prepare("SELECT breed, gender, date_of_birth FROM goats WHERE deleted_at IS NULL")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut animals = Vec::with_capacity(rows.len());
+    for (breed, sex, born) in rows {
+        let born = born.and_then(|d| NaiveDate::parse_from_str(&d, DATE_FORMAT).ok());
+        let age_months = match born {
+            Some(born) => match age_in_months(born, as_of) {
+                Some(age) => Some(age),
+                None => {
+                    trace!(%born, "Skipping goat born after census date");
+                    continue;
+                }
+            },
+            None => None,
+        };
+        animals.push(CensusAnimal {
+            breed,
+            sex,
+            age_months,
+        });
+    }
+    Ok(animals)
+}
+
+/// The heading and body lines of a rendered report: one line per group
+/// followed by the total.
+fn report_lines(report: &CensusReport) -> (Vec<String>, Vec<Vec<String>>) {
+    let mut heading = report.columns.clone();
+    heading.push("Count".to_string());
+    let mut lines: Vec<Vec<String>> = report
+        .rows
+        .iter()
+        .map(|row| {
+            let mut line = row.groups.clone();
+            line.push(row.count.to_string());
+            line
+        })
+        .collect();
+    let mut total = vec![String::new(); report.columns.len()];
+    total[0] = "Total".to_string();
+    total.push(report.total.to_string());
+    lines.push(total);
+    (heading, lines)
+}
+
+/// Renders a report as CSV: a heading row, one row per group, and a total.
+pub fn census_csv(report: &CensusReport) -> Result<Vec<u8>, AppError> {
+    let (heading, lines) = report_lines(report);
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for record in std::iter::once(&heading).chain(&lines) {
+        writer
+            .write_record(record)
+            .map_err(|e| AppError::InvalidInput(format!("Cannot write CSV: {}", e)))?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| AppError::InvalidInput(format!("Cannot write CSV: {}", e)))
+}
+
+/// Text for the built-in Helvetica font, which only covers Latin-1.
+fn pdf_text(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?'))
+        .collect()
+}
+
+/// Shows `text` at `(x, y)` in Helvetica.
+pub(crate) fn show_text(content: &mut Content, x: f32, y: f32, size: f32, text: &str) {
+    content.begin_text();
+    content.set_font(FONT_NAME, size);
+    content.next_line(x, y);
+    content.show(Str(&pdf_text(text)));
+    content.end_text();
+}
+
+/// Renders a report as an A4 PDF table, continued over as many pages as
+/// needed, with the title and census date on each page.
+pub fn census_pdf(report: &CensusReport) -> Vec<u8> {
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let font_id = Ref::new(3);
+
+    let (heading, lines) = report_lines(report);
+    let pages: Vec<&[Vec<String>]> = lines.chunks(ROWS_PER_PAGE).collect();
+    let page_ids: Vec<Ref> = (0..pages.len())
+        .map(|i| Ref::new(4 + 2 * i as i32))
+        .collect();
+    let column_width = (PAGE_WIDTH - 2.0 * MARGIN) / heading.len() as f32;
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id)
+        .kids(page_ids.iter().copied())
+        .count(pages.len() as i32);
+    pdf.type1_font(font_id).base_font(Name(b"Helvetica"));
+
+    for (i, (page_lines, page_id)) in pages.iter().zip(&page_ids).enumerate() {
+        let content_id = Ref::new(page_id.get() + 1);
+        let mut page = pdf.page(*page_id);
+        page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+        page.parent(page_tree_id);
+        page.contents(content_id);
+        page.resources().fonts().pair(FONT_NAME, font_id);
+        page.finish();
+
+        let mut content = Content::new();
+        let mut y = PAGE_HEIGHT - MARGIN;
+        show_text(&mut content, MARGIN, y, 14.0, &report.title);
+        y -= LINE_HEIGHT * 1.25;
+        let subtitle = format!(
+            "As of {}  -  page {} of {}",
+            report.as_of,
+            i + 1,
+            pages.len()
+        );
+        show_text(&mut content, MARGIN, y, 9.0, &subtitle);
+        y -= LINE_HEIGHT * 1.5;
+        for (column, text) in heading.iter().enumerate() {
+            let x = MARGIN + column as f32 * column_width;
+            show_text(&mut content, x, y, 10.0, text);
+        }
+        content
+            .move_to(MARGIN, y - 4.0)
+            .line_to(PAGE_WIDTH - MARGIN, y - 4.0)
+            .stroke();
+        for line in page_lines.iter() {
+            y -= LINE_HEIGHT;
+            for (column, text) in line.iter().enumerate() {
+                let x = MARGIN + column as f32 * column_width;
+                show_text(&mut content, x, y, 10.0, text);
+            }
+        }
+        pdf.stream(content_id, &content.finish());
+    }
+    pdf.finish()
+}
+
+/// Handler for listing census report templates.
+///
+/// # HTTP Method
+/// - `GET /reports/templates`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `ReportTemplate`: the built-in
+///   templates, then the farm's own, each ordered by key.
+pub async fn get_templates(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /reports/templates called");
+    let conn = db.get_conn()?;
+    let templates = load_templates(&conn)?;
+
+    info!("Returning {} report templates", templates.len());
+    Ok(HttpResponse::Ok().json(templates))
+}
+
+/// Handler for adding a census report template.
+///
+/// # HTTP Method
+/// - `POST /reports/templates`
+///
+/// # Request
+/// - JSON `ReportTemplate`; `built_in` is ignored.
+///
+/// # Success
+/// - Returns HTTP 201 with the stored `ReportTemplate`.
+///
+/// # Errors
+/// - Returns HTTP 400 for an invalid template or a key already in use.
+pub async fn add_template(
+    db: web::Data<DbPool>,
+    template: web::Json<ReportTemplate>,
+) -> Result<impl Responder, AppError> {
+    debug!(key = %template.key, "POST /reports/templates called");
+    let mut template = template.into_inner();
+    template.key = template.key.trim().to_string();
+    template.title = template.title.trim().to_string();
+    template.built_in = false;
+    template.validate().map_err(AppError::InvalidInput)?;
+
+    let conn = db.get_conn()?;
+    if load_templates(&conn)?.iter().any(|t| t.key == template.key) {
+        return Err(AppError::InvalidInput(format!(
+            "A report template with key {} already exists",
+            template.key
+        )));
+    }
+    conn.execute(
+        "INSERT INTO report_templates (template_key, definition) VALUES (?1, ?2)",
+        params![template.key, serde_json::to_string(&template)?],
+    )?;
+
+    info!(key = %template.key, "Report template added");
+    Ok(HttpResponse::Created().json(template))
+}
+
+/// Handler for removing a farm's census report template.
+///
+/// # HTTP Method
+/// - `DELETE /reports/templates/{key}`
+///
+/// # Success
+/// - Returns HTTP 200 once the template is removed.
+///
+/// # Errors
+/// - Returns HTTP 400 for a built-in template or an unknown key.
+pub async fn delete_template(
+    db: web::Data<DbPool>,
+    key: web::Path<String>,
+) -> Result<impl Responder, AppError> {
+    let key = key.into_inner();
+    debug!(%key, "DELETE /reports/templates called");
+    if ReportTemplate::built_ins().iter().any(|t| t.key == key) {
+        return Err(AppError::InvalidInput(format!(
+            "Built-in report template {} cannot be deleted",
+            key
+        )));
+    }
+    let conn = db.get_conn()?;
+    let deleted = conn.execute(
+        "DELETE FROM report_templates WHERE template_key = ?1",
+        [&key],
+    )?;
+    if deleted == 0 {
+        warn!(%key, "Report template not found for deletion");
+        return Err(AppError::InvalidInput(format!(
+            "No report template with key {}",
+            key
+        )));
+    }
+
+    info!(%key, "Report template deleted");
+    Ok(HttpResponse::Ok().body("Report template deleted"))
+}
+
+/// Builds the census report of template `key`, with ages computed at
+/// `as_of` (`YYYY-MM-DD`, default today).
+///
+/// # Errors
+/// - `AppError::InvalidInput` for an unknown template or a malformed `as_of`.
+pub fn census_report(
+    conn: &Connection,
+    key: &str,
+    as_of: Option<&str>,
+) -> Result<CensusReport, AppError> {
+    let as_of = match as_of {
+        Some(value) => NaiveDate::parse_from_str(value, DATE_FORMAT).map_err(|_| {
+            AppError::InvalidInput(format!("as_of must be YYYY-MM-DD, got '{}'", value))
+        })?,
+        None => farm_today(conn)?,
+    };
+    let template = find_template(conn, key)?;
+    let animals = load_census_animals(conn, as_of)?;
+    Ok(build_census(
+        &template,
+        &animals,
+        &as_of.format(DATE_FORMAT).to_string(),
+    ))
+}
+
+/// Renders `report` as a file in `format`, returning its MIME type, file
+/// name and contents.
+pub fn census_file(
+    report: &CensusReport,
+    format: ReportFormat,
+) -> Result<(&'static str, String, Vec<u8>), AppError> {
+    let (content_type, extension, body) = match format {
+        ReportFormat::Json => ("application/json", "json", serde_json::to_vec(report)?),
+        ReportFormat::Csv => ("text/csv", "csv", census_csv(report)?),
+        ReportFormat::Pdf => ("application/pdf", "pdf", census_pdf(report)),
+    };
+    let filename = format!("{}-{}.{}", report.template, report.as_of, extension);
+    Ok((content_type, filename, body))
+}
+
+/// The census reports of templates `keys` (every template if empty) as of
+/// `as_of`, each as a CSV and a PDF file, for reports written outside a
+/// request such as by the `cron_reports` binary. Returns file names and
+/// contents.
+///
+/// # Errors
+/// - `AppError::InvalidInput` for an unknown template or a malformed `as_of`.
+pub fn census_files(
+    conn: &Connection,
+    keys: &[String],
+    as_of: Option<&str>,
+) -> Result<Vec<(String, Vec<u8>)>, AppError> {
+    let keys = if keys.is_empty() {
+        load_templates(conn)?.into_iter().map(|t| t.key).collect()
+    } else {
+        keys.to_vec()
+    };
+    let mut files = Vec::new();
+    for key in &keys {
+        let report = census_report(conn, key, as_of)?;
+        for format in [ReportFormat::Csv, ReportFormat::Pdf] {
+            let (_, filename, body) = census_file(&report, format)?;
+            files.push((filename, body));
+        }
+        debug!(%key, total = report.total, "Census files built");
+    }
+    Ok(files)
+}
+
+/// Handler for producing a census report.
+///
+/// # HTTP Method
+/// - `GET /reports/census/{key}?as_of=2026-03-31&format=csv`
+///
+/// # Success
+/// - Returns HTTP 200 with the headcount laid out by template `key`:
+///   a `CensusReport` for `format=json` (the default), or a `text/csv` or
+///   `application/pdf` attachment. Ages are computed at `as_of`, which
+///   defaults to today; goats born after it are not counted.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown template or a malformed `as_of`.
+pub async fn get_census(
+    db: web::Data<DbPool>,
+    key: web::Path<String>,
+    query: web::Query<CensusQuery>,
+) -> Result<impl Responder, AppError> {
+    let key = key.into_inner();
+    debug!(%key, format = ?query.format, "GET /reports/census called");
+    let conn = db.get_conn()?;
+    let report = census_report(&conn, &key, query.as_of.as_deref())?;
+
+    info!(%key, total = report.total, rows = report.rows.len(), "Census report built");
+    if query.format == ReportFormat::Json {
+        return Ok(HttpResponse::Ok().json(report));
+    }
+    let (content_type, filename, body) = census_file(&report, query.format)?;
+    Ok(HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        ))
+        .body(body))
+}
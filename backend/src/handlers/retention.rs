@@ -0,0 +1,74 @@
+//! This module serves the trash and what the farm's retention is about to
+//! purge (see `crate::retention`), and restores goats from the trash. The
+//! retention periods themselves are part of the farm settings.
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::events::EventLog;
+use crate::repository::Goats;
+use crate::retention::{remove_from_trash, restore_from_trash, trashed_goat, upcoming_purges};
+use actix_web::{HttpResponse, Responder, web};
+use tracing::{debug, info, warn};
+
+/// Handler for what the cleanup job is going to purge.
+///
+/// # HTTP Method
+/// - `GET /retention`
+///
+/// # Success
+/// - Returns HTTP 200 with the JSON `UpcomingPurges`: the retention policy,
+///   the trash with the day each goat is purged, and how many event log
+///   entries are due now and within the week.
+pub async fn get_retention(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /retention called");
+    let conn = db.get_conn()?;
+    let purges = upcoming_purges(&conn)?;
+
+    info!(
+        trash = purges.trash.len(),
+        events_due = purges.events_due,
+        "Returning upcoming purges"
+    );
+    Ok(HttpResponse::Ok().json(purges))
+}
+
+/// Handler for restoring a goat from the trash.
+///
+/// # HTTP Method
+/// - `POST /trash/{id}/restore`
+///
+/// # Success
+/// - Returns HTTP 201 with the restored `Goat`, which keeps its ID and all
+///   its records. A goat trashed before goats were only marked deleted comes
+///   back under a new ID with just its vaccinations and diseases.
+///
+/// # Errors
+/// - Returns HTTP 400 if the trash has no such entry or another goat has
+///   taken the ear tag since. Another goat with the same name is no obstacle.
+pub async fn restore_goat(
+    db: web::Data<DbPool>,
+    goats: Goats,
+    events: EventLog,
+    id: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = id.into_inner();
+    debug!(trash_id = id, "POST /trash/{{id}}/restore called");
+    let mut conn = db.get_conn()?;
+    let Some(trashed) = trashed_goat(&conn, id)? else {
+        warn!(trash_id = id, "No trash entry to restore");
+        return Err(AppError::InvalidInput(format!(
+            "No deleted goat found with ID {}",
+            id
+        )));
+    };
+    let goat = if restore_from_trash(&mut conn, &events, id, &trashed)? {
+        trashed
+    } else {
+        let goat = goats.insert(&trashed.params)?;
+        remove_from_trash(&conn, id)?;
+        goat
+    };
+
+    info!(trash_id = id, goat_id = goat.id, "Restored goat from trash");
+    Ok(HttpResponse::Created().json(goat))
+}
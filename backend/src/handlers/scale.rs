@@ -0,0 +1,283 @@
+//! This module handles weigh-scale readings pushed by scale bridges and the
+//! weigh session that matches them to goats by tag.
+
+use crate::auth::authenticate;
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::events::{Actor, EventLog};
+use crate::handlers::growth::insert_weight;
+use crate::handlers::settings::farm_timezone;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Deserialize;
+use shared::events::DomainEvent;
+use shared::scale::{ScaleReading, ScaleReadingInput, TagAssignment};
+use shared::time::{local_date, parse_timestamp};
+use tracing::{debug, info, trace, warn};
+
+/// Storage format of device timestamps such as `scale_readings.read_at`,
+/// which are kept in UTC like SQLite's `CURRENT_TIMESTAMP`.
+pub const READ_AT_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Most readings returned by one `GET /scale/readings`.
+const MAX_READINGS: u32 = 500;
+
+/// Query parameters accepted by `GET /scale/readings`.
+#[derive(Deserialize)]
+pub struct ReadingQuery {
+    /// Only readings with a larger ID, for polling during a weigh session.
+    pub since_id: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+/// Parses a reading time given as RFC 3339 or a local date-time in the
+/// farm's time zone `tz`.
+pub fn parse_read_at(value: &str, tz: Tz) -> Result<DateTime<Utc>, AppError> {
+    parse_timestamp(value, tz).map_err(AppError::InvalidInput)
+}
+
+/// Maps a reading row joined with its goat's name.
+fn row_to_reading(row: &Row) -> rusqlite::Result<ScaleReading> {
+    Ok(ScaleReading {
+        id: row.get(0)?,
+        tag_id: row.get(1)?,
+        weight: row.get(2)?,
+        read_at: row.get(3)?,
+        goat_name: row.get(4)?,
+        weight_record_id: row.get(5)?,
+    })
+}
+
+/// Columns selected by `row_to_reading`.
+const READING_SELECT: &str = "SELECT r.id, r.tag_id, r.weight, r.read_at, g.name, r.weight_record_id \
+     FROM scale_readings r LEFT JOIN goats g ON g.id = r.goat_id";
+
+/// Loads one reading by ID.
+fn load_reading(conn: &Connection, id: i64) -> Result<Option<ScaleReading>, AppError> {
+    Ok(conn
+        .query_row(
+            &format!("{} WHERE r.id = ?1", READING_SELECT),
+            [id],
+            row_to_reading,
+        )
+        .optional()?)
+}
+
+/// Turns a reading into a weight record for `goat_id` and links the two.
+fn match_reading(
+    conn: &Connection,
+    events: &EventLog,
+    reading_id: i64,
+    goat_id: i64,
+    read_at: &DateTime<Utc>,
+    weight: f64,
+) -> Result<(), AppError> {
+    // Weighed on the farm's date, not UTC's, which differ around midnight
+    let weighed_on = local_date(read_at, farm_timezone(conn)?)
+        .format(DATE_FORMAT)
+        .to_string();
+    let record_id = insert_weight(conn, events, goat_id, &weighed_on, weight, false)?;
+    conn.execute(
+        "UPDATE scale_readings SET goat_id = ?1, weight_record_id = ?2 WHERE id = ?3",
+        params![goat_id, record_id, reading_id],
+    )?;
+    trace!(reading_id, goat_id, record_id, "Matched scale reading");
+    Ok(())
+}
+
+/// Handler for ingesting a reading from a scale.
+///
+/// # HTTP Method
+/// - `POST /scale/readings`
+///
+/// # Request
+/// - `X-Api-Key` header (or `Authorization: Bearer`) with a device API key.
+/// - JSON `ScaleReadingInput`.
+///
+/// # Success
+/// - Returns HTTP 201 with the stored `ScaleReading`. If the tag belongs to a
+///   goat, a weight record has been created and `goat_name` is set.
+///
+/// # Errors
+/// - Returns HTTP 401 for a missing, unknown or revoked API key.
+/// - Returns HTTP 400 for an empty tag, a non-positive weight or a malformed time.
+pub async fn add_reading(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    events: EventLog,
+    input: web::Json<ScaleReadingInput>,
+) -> Result<impl Responder, AppError> {
+    debug!(tag_id = %input.tag_id, "POST /scale/readings called");
+    let mut conn = db.get_conn()?;
+    let api_key_id = authenticate(&conn, &req)?;
+    let events = events.by(Actor::ApiKey(api_key_id));
+
+    let tag_id = input.tag_id.trim();
+    if tag_id.is_empty() {
+        return Err(AppError::InvalidInput("tag_id must not be empty".into()));
+    }
+    if !(input.weight.is_finite() && input.weight > 0.0) {
+        return Err(AppError::InvalidInput("weight must be positive".into()));
+    }
+    let read_at = match &input.read_at {
+        Some(value) => parse_read_at(value, farm_timezone(&conn)?)?,
+        None => Utc::now(),
+    };
+
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO scale_readings (api_key_id, tag_id, weight, read_at) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            api_key_id,
+            tag_id,
+            input.weight,
+            read_at.format(READ_AT_FORMAT).to_string()
+        ],
+    )?;
+    let reading_id = tx.last_insert_rowid();
+    let goat_id: Option<i64> = tx
+        .query_row(
+            "SELECT id FROM goats WHERE tag_id = ?1 AND deleted_at IS NULL",
+            [tag_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match goat_id {
+        Some(goat_id) => match_reading(&tx, &events, reading_id, goat_id, &read_at, input.weight)?,
+        None => info!(reading_id, tag_id, "Scale reading for unknown tag"),
+    }
+    let reading = load_reading(&tx, reading_id)?.expect("reading was just inserted");
+    tx.commit()?;
+
+    info!(
+        reading_id,
+        api_key_id,
+        matched = goat_id.is_some(),
+        "Scale reading stored"
+    );
+    Ok(HttpResponse::Created().json(reading))
+}
+
+/// Handler for listing scale readings, oldest first.
+///
+/// # HTTP Method
+/// - `GET /scale/readings?since_id=42&limit=100`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `ScaleReading`. Without
+///   `since_id` the most recent readings are returned.
+pub async fn get_readings(
+    db: web::Data<DbPool>,
+    query: web::Query<ReadingQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(since_id = ?query.since_id, "GET /scale/readings called");
+    let limit = query.limit.unwrap_or(100).min(MAX_READINGS);
+    let conn = db.get_conn()?;
+    let mut readings: Vec<ScaleReading> = match query.since_id {
+        Some(since_id) => conn
+            .prepare(&format!(
+                "{} WHERE r.id > ?1 ORDER BY r.id LIMIT ?2",
+                READING_SELECT
+            ))?
+            .query_map(params![since_id, limit], row_to_reading)?
+            .collect::<Result<_, _>>()?,
+        None => conn
+            .prepare(&format!("{} ORDER BY r.id DESC LIMIT ?1", READING_SELECT))?
+            .query_map([limit], row_to_reading)?
+            .collect::<Result<_, _>>()?,
+    };
+    readings.sort_by_key(|r| r.id);
+
+    info!("Returning {} scale readings", readings.len());
+    Ok(HttpResponse::Ok().json(readings))
+}
+
+/// Handler for assigning a reading's tag to a goat.
+///
+/// The tag is saved on the goat, so future readings match automatically,
+/// and every unmatched reading with that tag becomes a weight record.
+///
+/// # HTTP Method
+/// - `PUT /scale/readings/{id}/goat`
+///
+/// # Request
+/// - JSON `TagAssignment`.
+///
+/// # Success
+/// - Returns HTTP 200 with the readings that were matched.
+///
+/// # Errors
+/// - Returns HTTP 400 if the reading or goat does not exist, or the tag
+///   already belongs to another goat.
+pub async fn assign_reading(
+    db: web::Data<DbPool>,
+    events: EventLog,
+    path: web::Path<i64>,
+    assignment: web::Json<TagAssignment>,
+) -> Result<impl Responder, AppError> {
+    let reading_id = path.into_inner();
+    debug!(reading_id, goat = %assignment.goat_name, "PUT /scale/readings/{{id}}/goat called");
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+
+    let reading = load_reading(&tx, reading_id)?.ok_or_else(|| {
+        AppError::InvalidInput(format!("No scale reading found with ID {}", reading_id))
+    })?;
+    let goat_id: i64 = tx
+        .query_row(
+            "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [&assignment.goat_name],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| {
+            AppError::InvalidInput(format!("No goat found with name {}", assignment.goat_name))
+        })?;
+    let owner: Option<String> = tx
+        .query_row(
+            "SELECT name FROM goats WHERE tag_id = ?1 AND id != ?2 AND deleted_at IS NULL",
+            params![reading.tag_id, goat_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(owner) = owner {
+        warn!(tag_id = %reading.tag_id, %owner, "Tag already assigned");
+        return Err(AppError::InvalidInput(format!(
+            "Tag {} already belongs to {}",
+            reading.tag_id, owner
+        )));
+    }
+    events.apply(
+        &tx,
+        &DomainEvent::TagAssigned {
+            goat_id,
+            tag_id: reading.tag_id.clone(),
+        },
+    )?;
+
+    let pending = tx
+        .prepare(
+            "SELECT id, weight, read_at FROM scale_readings \
+             WHERE tag_id = ?1 AND goat_id IS NULL ORDER BY id",
+        )?
+        .query_map([&reading.tag_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, DateTime<Utc>>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut matched = Vec::with_capacity(pending.len());
+    for (id, weight, read_at) in pending {
+        match_reading(&tx, &events, id, goat_id, &read_at, weight)?;
+        matched.extend(load_reading(&tx, id)?);
+    }
+    tx.commit()?;
+
+    info!(reading_id, goat_id, matched = matched.len(), "Tag assigned");
+    Ok(HttpResponse::Ok().json(matched))
+}
@@ -0,0 +1,44 @@
+//! This module handles the goat scoring endpoint used for keep/cull
+//! decisions (see `crate::scoring` for the model).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::settings::farm_today;
+use crate::scoring::{load_metrics, score_goats};
+use actix_web::{HttpResponse, Responder, web};
+use shared::scoring::{Recommendation, ScoreWeights};
+use tracing::{debug, info};
+
+/// Handler for scoring every goat.
+///
+/// # HTTP Method
+/// - `GET /scoring/goats?milk=2&growth=1&fertility=1&health=1&profit=1`
+///
+/// # Request
+/// - Optional query weights per metric; omitted weights default to 1.
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `GoatScore`, lowest score first.
+///
+/// # Errors
+/// - Returns HTTP 400 if a weight is negative or every weight is zero.
+pub async fn get_goat_scores(
+    db: web::Data<DbPool>,
+    weights: web::Query<ScoreWeights>,
+) -> Result<impl Responder, AppError> {
+    debug!(weights = ?*weights, "GET /scoring/goats called");
+    weights.validate().map_err(AppError::InvalidInput)?;
+    let conn = db.get_conn()?;
+    let metrics = load_metrics(&conn, farm_today(&conn)?)?;
+    let scores = score_goats(&metrics, &weights);
+
+    info!(
+        "Returning scores for {} goats ({} cull candidates)",
+        scores.len(),
+        scores
+            .iter()
+            .filter(|s| s.recommendation == Recommendation::Cull)
+            .count()
+    );
+    Ok(HttpResponse::Ok().json(scores))
+}
@@ -0,0 +1,232 @@
+//! This module handles the header's quick search across goats, tasks and
+//! transactions (see `shared::search`), suggesting similarly named goats
+//! when no goat matches. Goat names in Indic scripts are matched by their
+//! Latin transliteration (see `shared::transliterate`) and the other way
+//! round.
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use actix_web::{HttpResponse, Responder, web};
+use rusqlite::{Connection, Row};
+use serde::Deserialize;
+use shared::search::{
+    MAX_RESULTS_PER_KIND, MIN_QUERY_LEN, SearchKind, SearchResult, fold_name, suggest_names,
+};
+use shared::transliterate::has_indic;
+use tracing::{debug, info};
+
+/// Query parameters accepted by `GET /search`.
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+}
+
+/// Escapes `%`, `_` and `\` so `query` matches literally in a `LIKE ... ESCAPE '\'`.
+fn like_pattern(query: &str) -> String {
+    let mut pattern = String::from("%");
+    for c in query.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+/// Runs one search statement, which takes the `LIKE` pattern as `?1`, the
+/// raw query as `?2` (for ranking) and the limit as `?3`, and maps each row
+/// with `to_result`.
+fn search_kind(
+    conn: &Connection,
+    sql: &str,
+    query: &str,
+    to_result: impl Fn(&Row) -> rusqlite::Result<SearchResult>,
+) -> Result<Vec<SearchResult>, AppError> {
+    let mut stmt = conn.prepare(sql)?;
+    let results = stmt
+        .query_map(
+            rusqlite::params![like_pattern(query), query, MAX_RESULTS_PER_KIND as i64],
+            to_result,
+        )?
+        .collect::<Result<_, _>>()?;
+    Ok(results)
+}
+
+/// Maps a goat row of `id, name, breed, gender, coat_color, marks` to a
+/// search hit, with the last four as the detail.
+fn goat_result(row: &Row) -> rusqlite::Result<SearchResult> {
+    let mut detail = format!("{}, {}", row.get::<_, String>(2)?, row.get::<_, String>(3)?);
+    for looks in [row.get::<_, Option<String>>(4)?, row.get(5)?].into_iter().flatten() {
+        detail.push_str(", ");
+        detail.push_str(&looks);
+    }
+    Ok(SearchResult {
+        kind: SearchKind::Goat,
+        id: row.get(0)?,
+        title: row.get(1)?,
+        detail,
+        suggested: false,
+    })
+}
+
+/// Every goat as a search hit, by name.
+fn all_goats(conn: &Connection) -> Result<Vec<SearchResult>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, breed, gender, coat_color, marks FROM goats \
+         WHERE deleted_at IS NULL ORDER BY name",
+    )?;
+    let goats = stmt.query_map([], goat_result)?.collect::<Result<_, _>>()?;
+    Ok(goats)
+}
+
+/// Goats not in `found` whose names contain `query` once both are spelt in
+/// Latin letters and folded (see `fold_name`), where the name or the query
+/// is in an Indic script. `LIKE` cannot match across scripts.
+fn transliterated_goats(
+    conn: &Connection,
+    query: &str,
+    found: &[SearchResult],
+) -> Result<Vec<SearchResult>, AppError> {
+    let folded = fold_name(query);
+    if folded.is_empty() {
+        return Ok(Vec::new());
+    }
+    let query_indic = has_indic(query);
+    let hits: Vec<SearchResult> = all_goats(conn)?
+        .into_iter()
+        .filter(|g| query_indic || has_indic(&g.title))
+        .filter(|g| !found.iter().any(|f| f.id == g.id))
+        .filter(|g| fold_name(&g.title).contains(&folded))
+        .take(MAX_RESULTS_PER_KIND.saturating_sub(found.len()))
+        .collect();
+    debug!(
+        query,
+        hits = hits.len(),
+        "Matched goat names across scripts"
+    );
+    Ok(hits)
+}
+
+/// Goats whose names are close to `query` (see `suggest_names`), most
+/// similar first, marked as suggestions.
+fn suggest_goats(conn: &Connection, query: &str) -> Result<Vec<SearchResult>, AppError> {
+    let goats = all_goats(conn)?;
+    let names = suggest_names(query, goats.iter().map(|g| g.title.as_str()));
+    let suggestions: Vec<SearchResult> = names
+        .iter()
+        .filter_map(|name| goats.iter().find(|g| &g.title == name))
+        .map(|g| SearchResult {
+            suggested: true,
+            ..g.clone()
+        })
+        .collect();
+    debug!(
+        query,
+        suggestions = suggestions.len(),
+        "Suggesting goat names"
+    );
+    Ok(suggestions)
+}
+
+/// Finds goats by name, breed, coat color or marks, tasks by title or
+/// notes, and transactions by category or description, ignoring ASCII case.
+/// Within each kind, records whose title matches earliest come first; goats
+/// whose names only match across scripts follow. If no goat matches, goats
+/// with similar names are suggested instead.
+pub fn search(conn: &Connection, query: &str) -> Result<Vec<SearchResult>, AppError> {
+    let mut results = search_kind(
+        conn,
+        "SELECT id, name, breed, gender, coat_color, marks FROM goats \
+         WHERE deleted_at IS NULL AND (name LIKE ?1 ESCAPE '\\' OR breed LIKE ?1 ESCAPE '\\' \
+             OR coat_color LIKE ?1 ESCAPE '\\' OR marks LIKE ?1 ESCAPE '\\') \
+         ORDER BY instr(lower(name), lower(?2)) = 0, instr(lower(name), lower(?2)), name \
+         LIMIT ?3",
+        query,
+        goat_result,
+    )?;
+    let across_scripts = transliterated_goats(conn, query, &results)?;
+    results.extend(across_scripts);
+    if results.is_empty() {
+        results = suggest_goats(conn, query)?;
+    }
+    results.extend(search_kind(
+        conn,
+        "SELECT id, title, due_date, status FROM tasks \
+         WHERE title LIKE ?1 ESCAPE '\\' OR notes LIKE ?1 ESCAPE '\\' \
+         ORDER BY instr(lower(title), lower(?2)) = 0, status != 'Pending', due_date \
+         LIMIT ?3",
+        query,
+        |row| {
+            Ok(SearchResult {
+                kind: SearchKind::Task,
+                id: row.get(0)?,
+                title: row.get(1)?,
+                detail: format!(
+                    "{}, due {}",
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(2)?
+                ),
+                suggested: false,
+            })
+        },
+    )?);
+    results.extend(search_kind(
+        conn,
+        "SELECT id, category, description, kind, amount, currency, date FROM transactions \
+         WHERE category LIKE ?1 ESCAPE '\\' OR description LIKE ?1 ESCAPE '\\' \
+         ORDER BY instr(lower(category), lower(?2)) = 0, date DESC, id DESC \
+         LIMIT ?3",
+        query,
+        |row| {
+            let description: String = row.get(2)?;
+            let currency: Option<String> = row.get(5)?;
+            Ok(SearchResult {
+                kind: SearchKind::Transaction,
+                id: row.get(0)?,
+                title: if description.is_empty() {
+                    row.get(1)?
+                } else {
+                    format!("{}: {}", row.get::<_, String>(1)?, description)
+                },
+                detail: format!(
+                    "{} of {:.2}{} on {}",
+                    row.get::<_, String>(3)?,
+                    row.get::<_, f64>(4)?,
+                    currency.map(|c| format!(" {}", c)).unwrap_or_default(),
+                    row.get::<_, String>(6)?
+                ),
+                suggested: false,
+            })
+        },
+    )?);
+    Ok(results)
+}
+
+/// Handler for the quick search.
+///
+/// # HTTP Method
+/// - `GET /search?q=rani`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `SearchResult`s: goats, then
+///   tasks, then transactions, at most `MAX_RESULTS_PER_KIND` of each.
+///   Queries shorter than `MIN_QUERY_LEN` characters return an empty array.
+///   When no goat matches, goats with similar names are returned first with
+///   `suggested` set.
+pub async fn get_search(
+    db: web::Data<DbPool>,
+    query: web::Query<SearchQuery>,
+) -> Result<impl Responder, AppError> {
+    let q = query.q.trim();
+    debug!(q, "GET /search called");
+    if q.chars().count() < MIN_QUERY_LEN {
+        return Ok(HttpResponse::Ok().json(Vec::<SearchResult>::new()));
+    }
+
+    let conn = db.get_conn()?;
+    let results = search(&conn, q)?;
+
+    info!(q, hits = results.len(), "Returning search results");
+    Ok(HttpResponse::Ok().json(results))
+}
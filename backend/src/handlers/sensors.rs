@@ -0,0 +1,395 @@
+//! This module handles the sensor registry, environmental readings pushed by
+//! sensors, and the barn conditions shown on the dashboard.
+
+use crate::auth::authenticate;
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::scale::{READ_AT_FORMAT, parse_read_at};
+use crate::handlers::settings::farm_timezone;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use chrono::{Days, NaiveDate, Utc};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Deserialize;
+use shared::sensors::{
+    Sensor, SensorCondition, SensorKind, SensorReading, SensorReadingInput, Thresholds,
+};
+use shared::time::{day_start, today_in};
+use tracing::{debug, info, trace, warn};
+
+/// Days of readings returned when `GET /sensors/readings` has no `from`.
+const DEFAULT_WINDOW_DAYS: u64 = 7;
+
+/// Most readings returned by one `GET /sensors/readings`.
+const MAX_READINGS: u32 = 5000;
+
+/// Query parameters accepted by `GET /sensors/readings`.
+///
+/// `from` and `to` are inclusive `YYYY-MM-DD` dates; `to` defaults to today
+/// and `from` to `DEFAULT_WINDOW_DAYS` before it.
+#[derive(Deserialize)]
+pub struct SensorReadingQuery {
+    pub sensor_id: i64,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Maps a sensor row (see `load_conditions`) to its latest condition.
+fn row_to_condition(row: &Row) -> rusqlite::Result<SensorCondition> {
+    let id: i64 = row.get(0)?;
+    let thresholds = Thresholds {
+        min: row.get(5)?,
+        max: row.get(6)?,
+    };
+    let latest = match (row.get::<_, Option<f64>>(7)?, row.get(8)?) {
+        (Some(value), Some(read_at)) => Some(SensorReading {
+            sensor_id: id,
+            value,
+            read_at,
+        }),
+        _ => None,
+    };
+    Ok(SensorCondition {
+        alert: latest.as_ref().and_then(|r| thresholds.check(r.value)),
+        sensor: Sensor {
+            id: Some(id),
+            name: row.get(1)?,
+            kind: SensorKind::from_str(&row.get::<_, String>(2)?),
+            space_id: row.get(3)?,
+            space_name: row.get(4)?,
+            goat_name: row.get(9)?,
+            thresholds,
+        },
+        latest,
+    })
+}
+
+/// Loads every sensor with its latest reading, ordered by name.
+///
+/// Sensors registered before names existed are called "<type> #<id>".
+pub fn load_conditions(conn: &Connection) -> Result<Vec<SensorCondition>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT s.id, COALESCE(s.name, s.sensor_type || ' #' || s.id), s.sensor_type, \
+             s.space_id, sp.name, s.min_threshold, s.max_threshold, \
+             s.last_reading, s.last_reading_time, g.name \
+         FROM sensors s LEFT JOIN spaces sp ON sp.id = s.space_id \
+             LEFT JOIN goats g ON g.id = s.goat_id \
+         ORDER BY 2",
+    )?;
+    let conditions = stmt
+        .query_map([], row_to_condition)?
+        .collect::<Result<Vec<_>, _>>()?;
+    trace!("Loaded {} sensors", conditions.len());
+    Ok(conditions)
+}
+
+/// Fails unless a sensor with `id` exists.
+fn ensure_sensor(conn: &Connection, id: i64) -> Result<(), AppError> {
+    conn.query_row("SELECT id FROM sensors WHERE id = ?1", [id], |row| {
+        row.get::<_, i64>(0)
+    })
+    .optional()?
+    .ok_or_else(|| AppError::InvalidInput(format!("No sensor found with ID {}", id)))?;
+    Ok(())
+}
+
+/// Handler for listing registered sensors.
+///
+/// # HTTP Method
+/// - `GET /sensors`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `Sensor` ordered by name.
+pub async fn get_sensors(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /sensors called");
+    let conn = db.get_conn()?;
+    let sensors: Vec<Sensor> = load_conditions(&conn)?
+        .into_iter()
+        .map(|c| c.sensor)
+        .collect();
+
+    info!("Returning {} sensors", sensors.len());
+    Ok(HttpResponse::Ok().json(sensors))
+}
+
+/// Handler for registering a sensor.
+///
+/// # HTTP Method
+/// - `POST /sensors`
+///
+/// # Success
+/// - Returns HTTP 201 with the JSON `Sensor`, including the ID the device
+///   must send with its readings.
+///
+/// # Errors
+/// - Returns HTTP 400 if the name is empty, the thresholds are inverted or
+///   the space or goat does not exist.
+pub async fn add_sensor(
+    db: web::Data<DbPool>,
+    sensor: web::Json<Sensor>,
+) -> Result<impl Responder, AppError> {
+    debug!(name = %sensor.name, "POST /sensors called");
+    let mut sensor = sensor.into_inner();
+    sensor.name = sensor.name.trim().to_string();
+    if sensor.name.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Sensor name must not be empty".into(),
+        ));
+    }
+    sensor
+        .thresholds
+        .validate()
+        .map_err(AppError::InvalidInput)?;
+    let conn = db.get_conn()?;
+    sensor.space_name = match sensor.space_id {
+        Some(space_id) => Some(
+            conn.query_row("SELECT name FROM spaces WHERE id = ?1", [space_id], |row| {
+                row.get(0)
+            })
+            .optional()?
+            .ok_or_else(|| {
+                AppError::InvalidInput(format!("No space found with ID {}", space_id))
+            })?,
+        ),
+        None => None,
+    };
+    let goat_id: Option<i64> = match &sensor.goat_name {
+        Some(goat_name) => Some(
+            conn.query_row(
+                "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+                [goat_name],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| {
+                AppError::InvalidInput(format!("No goat found with name {}", goat_name))
+            })?,
+        ),
+        None => None,
+    };
+    conn.execute(
+        "INSERT INTO sensors \
+             (name, sensor_type, space_id, goat_id, min_threshold, max_threshold, status) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'Active')",
+        params![
+            sensor.name,
+            SensorKind::to_str(&sensor.kind),
+            sensor.space_id,
+            goat_id,
+            sensor.thresholds.min,
+            sensor.thresholds.max
+        ],
+    )?;
+    sensor.id = Some(conn.last_insert_rowid());
+
+    info!(sensor_id = ?sensor.id, "Sensor added");
+    Ok(HttpResponse::Created().json(sensor))
+}
+
+/// Handler for changing a sensor's alert thresholds.
+///
+/// # HTTP Method
+/// - `PUT /sensors/{id}/thresholds`
+///
+/// # Request
+/// - JSON `Thresholds`; a `null` bound is removed.
+///
+/// # Success
+/// - Returns HTTP 200 once the thresholds are saved.
+///
+/// # Errors
+/// - Returns HTTP 400 if the sensor does not exist or the minimum is not
+///   below the maximum.
+pub async fn update_thresholds(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+    thresholds: web::Json<Thresholds>,
+) -> Result<impl Responder, AppError> {
+    let sensor_id = path.into_inner();
+    debug!(sensor_id, "PUT /sensors/{{id}}/thresholds called");
+    thresholds.validate().map_err(AppError::InvalidInput)?;
+    let conn = db.get_conn()?;
+    ensure_sensor(&conn, sensor_id)?;
+    conn.execute(
+        "UPDATE sensors SET min_threshold = ?1, max_threshold = ?2 WHERE id = ?3",
+        params![thresholds.min, thresholds.max, sensor_id],
+    )?;
+
+    info!(sensor_id, "Thresholds updated");
+    Ok(HttpResponse::Ok().body("Thresholds updated"))
+}
+
+/// Handler for ingesting a reading from a sensor.
+///
+/// # HTTP Method
+/// - `POST /sensors/readings`
+///
+/// # Request
+/// - `X-Api-Key` header (or `Authorization: Bearer`) with a device API key.
+/// - JSON `SensorReadingInput`.
+///
+/// # Success
+/// - Returns HTTP 201 with the stored `SensorReading`. The sensor's latest
+///   reading is updated unless an older reading arrives late.
+///
+/// # Errors
+/// - Returns HTTP 401 for a missing, unknown or revoked API key.
+/// - Returns HTTP 400 for an unknown sensor, a non-finite value, negative
+///   litres from a water meter or a malformed time.
+pub async fn add_reading(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    input: web::Json<SensorReadingInput>,
+) -> Result<impl Responder, AppError> {
+    debug!(sensor_id = input.sensor_id, "POST /sensors/readings called");
+    let mut conn = db.get_conn()?;
+    let api_key_id = authenticate(&conn, &req)?;
+
+    if !input.value.is_finite() {
+        return Err(AppError::InvalidInput("value must be a number".into()));
+    }
+    let read_at = match &input.read_at {
+        Some(value) => parse_read_at(value, farm_timezone(&conn)?)?,
+        None => Utc::now(),
+    };
+    let stored_at = read_at.format(READ_AT_FORMAT).to_string();
+
+    let tx = conn.transaction()?;
+    ensure_sensor(&tx, input.sensor_id)?;
+    let sensor_type: String = tx.query_row(
+        "SELECT sensor_type FROM sensors WHERE id = ?1",
+        [input.sensor_id],
+        |row| row.get(0),
+    )?;
+    // Water meters report litres used since the last reading
+    if SensorKind::from_str(&sensor_type) == SensorKind::Water && input.value < 0.0 {
+        return Err(AppError::InvalidInput(
+            "A water meter cannot report negative litres".into(),
+        ));
+    }
+    tx.execute(
+        "INSERT INTO sensor_readings (sensor_id, value, read_at) VALUES (?1, ?2, ?3)",
+        params![input.sensor_id, input.value, stored_at],
+    )?;
+    tx.execute(
+        "UPDATE sensors SET last_reading = ?1, last_reading_time = ?2 \
+         WHERE id = ?3 AND (last_reading_time IS NULL OR last_reading_time <= ?2)",
+        params![input.value, stored_at, input.sensor_id],
+    )?;
+    let (min, max): (Option<f64>, Option<f64>) = tx.query_row(
+        "SELECT min_threshold, max_threshold FROM sensors WHERE id = ?1",
+        [input.sensor_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    tx.commit()?;
+
+    if let Some(alert) = (Thresholds { min, max }).check(input.value) {
+        warn!(
+            sensor_id = input.sensor_id,
+            value = input.value,
+            ?alert,
+            "Sensor reading out of range"
+        );
+    }
+    info!(
+        sensor_id = input.sensor_id,
+        api_key_id, "Sensor reading stored"
+    );
+    Ok(HttpResponse::Created().json(SensorReading {
+        sensor_id: input.sensor_id,
+        value: input.value,
+        read_at,
+    }))
+}
+
+/// Handler for a sensor's readings over a date range, oldest first.
+///
+/// # HTTP Method
+/// - `GET /sensors/readings?sensor_id=1&from=2025-06-01&to=2025-06-07`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `SensorReading`.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown sensor or malformed or inverted dates.
+pub async fn get_readings(
+    db: web::Data<DbPool>,
+    query: web::Query<SensorReadingQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(sensor_id = query.sensor_id, "GET /sensors/readings called");
+    let parse = |value: &str| {
+        NaiveDate::parse_from_str(value, DATE_FORMAT).map_err(|_| {
+            AppError::InvalidInput(format!("Dates must be YYYY-MM-DD, got '{}'", value))
+        })
+    };
+    let conn = db.get_conn()?;
+    let tz = farm_timezone(&conn)?;
+    let to = match &query.to {
+        Some(to) => parse(to)?,
+        None => today_in(tz),
+    };
+    let from = match &query.from {
+        Some(from) => parse(from)?,
+        None => to - Days::new(DEFAULT_WINDOW_DAYS),
+    };
+    if from > to {
+        return Err(AppError::InvalidInput("from must not be after to".into()));
+    }
+
+    ensure_sensor(&conn, query.sensor_id)?;
+    // The dates are the farm's; readings are stored in UTC
+    let mut stmt = conn.prepare(
+        "SELECT sensor_id, value, read_at FROM sensor_readings \
+         WHERE sensor_id = ?1 AND read_at >= ?2 AND read_at < ?3 \
+         ORDER BY read_at, id LIMIT ?4",
+    )?;
+    let readings: Vec<SensorReading> = stmt
+        .query_map(
+            params![
+                query.sensor_id,
+                day_start(from, tz).format(READ_AT_FORMAT).to_string(),
+                day_start(to + Days::new(1), tz)
+                    .format(READ_AT_FORMAT)
+                    .to_string(),
+                MAX_READINGS
+            ],
+            |row| {
+                Ok(SensorReading {
+                    sensor_id: row.get(0)?,
+                    value: row.get(1)?,
+                    read_at: row.get(2)?,
+                })
+            },
+        )?
+        .collect::<Result<_, _>>()?;
+
+    info!("Returning {} sensor readings", readings.len());
+    Ok(HttpResponse::Ok().json(readings))
+}
+
+/// Handler for current barn conditions.
+///
+/// # HTTP Method
+/// - `GET /sensors/conditions`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `SensorCondition` for every
+///   temperature and humidity sensor; activity tags and other sensor types
+///   are left out.
+pub async fn get_conditions(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /sensors/conditions called");
+    let conn = db.get_conn()?;
+    let conditions: Vec<SensorCondition> = load_conditions(&conn)?
+        .into_iter()
+        .filter(|c| {
+            matches!(
+                c.sensor.kind,
+                SensorKind::Temperature | SensorKind::Humidity
+            )
+        })
+        .collect();
+
+    let alerts = conditions.iter().filter(|c| c.alert.is_some()).count();
+    info!(alerts, "Returning {} sensor conditions", conditions.len());
+    Ok(HttpResponse::Ok().json(conditions))
+}
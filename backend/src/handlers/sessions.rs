@@ -0,0 +1,481 @@
+//! This module handles the sessions of devices signed in to the dashboard,
+//! checked by `crate::auth::check_session`, including signing in with an
+//! OAuth provider, and the owner's password.
+//!
+//! Workers see and sign out only their own devices; the owner sees and
+//! signs out everyone's.
+
+use crate::auth::{
+    CurrentSession, OAuthProviders, TokenScope, check_credentials, generate_key, hash_key,
+    link_identity, set_owner_password,
+};
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::permissions::session_permissions;
+use actix_web::http::header;
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, web};
+use rusqlite::{Connection, OptionalExtension, params};
+use shared::sessions::{
+    IssuedSession, MAX_DEVICE_NAME_LEN, NewSession, OAuthCallback, OAuthRedirect, OAuthStart,
+    PasswordChange, Session, describe_user_agent,
+};
+use tracing::{debug, info, warn};
+
+/// Query for sessions as read by `read_session`, with the name of the
+/// worker who signed in.
+const SESSION_QUERY: &str = "SELECT s.id, s.device, w.name, s.user_agent, s.ip, s.created_at, \
+     s.last_seen_at FROM sessions s LEFT JOIN workers w ON w.id = s.worker_id";
+
+/// Minutes an OAuth sign-in may take from start to callback.
+const OAUTH_STATE_MINUTES: u32 = 10;
+
+fn read_session(row: &rusqlite::Row) -> rusqlite::Result<Session> {
+    Ok(Session {
+        id: row.get(0)?,
+        device: row.get(1)?,
+        worker: row.get(2)?,
+        user_agent: row.get(3)?,
+        ip: row.get(4)?,
+        created_at: row.get(5)?,
+        last_seen_at: row.get(6)?,
+        current: false,
+    })
+}
+
+/// The device name to sign in under: `device` if given, else one after the
+/// browser in `req`'s `User-Agent`.
+///
+/// # Errors
+/// - `AppError::InvalidInput` if the name is longer than `MAX_DEVICE_NAME_LEN`.
+fn device_name(req: &HttpRequest, device: &str) -> Result<String, AppError> {
+    let device = match device.trim() {
+        "" => describe_user_agent(user_agent(req).as_deref().unwrap_or_default()),
+        device => device.to_string(),
+    };
+    if device.chars().count() > MAX_DEVICE_NAME_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Device names must be at most {} characters",
+            MAX_DEVICE_NAME_LEN
+        )));
+    }
+    Ok(device)
+}
+
+fn user_agent(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(header::USER_AGENT)
+        .and_then(|ua| ua.to_str().ok())
+        .map(str::to_string)
+}
+
+/// The address `req` came from.
+fn peer_ip(req: &HttpRequest) -> Option<String> {
+    req.peer_addr().map(|addr| addr.ip().to_string())
+}
+
+/// Signs in the device `req` came from as `device`, for `worker_id` if a
+/// worker signed in.
+fn create_session(
+    conn: &Connection,
+    req: &HttpRequest,
+    device: &str,
+    worker_id: Option<i64>,
+) -> Result<IssuedSession, AppError> {
+    let ip = peer_ip(req);
+    let token = generate_key();
+    conn.execute(
+        "INSERT INTO sessions (device, token_hash, user_agent, ip, worker_id)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![device, hash_key(&token), user_agent(req), ip, worker_id],
+    )?;
+    let id = conn.last_insert_rowid();
+    let session = conn.query_row(
+        &format!("{} WHERE s.id = ?1", SESSION_QUERY),
+        [id],
+        read_session,
+    )?;
+    info!(session_id = id, ?worker_id, "Device signed in");
+    Ok(IssuedSession {
+        session: Session {
+            current: true,
+            ..session
+        },
+        token,
+    })
+}
+
+/// The session `req` was made in, if it carried a valid token.
+pub fn current_session(req: &HttpRequest) -> Option<i64> {
+    req.extensions().get::<CurrentSession>().map(|s| s.0)
+}
+
+/// The worker `req` was made by, from its session or access token; `None`
+/// for the owner.
+///
+/// # Errors
+/// - `AppError::Unauthorized` if `req` has neither.
+pub fn requester(conn: &Connection, req: &HttpRequest) -> Result<Option<i64>, AppError> {
+    if let Some(session) = current_session(req) {
+        let worker_id: Option<i64> = conn
+            .query_row(
+                "SELECT worker_id FROM sessions WHERE id = ?1",
+                [session],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+        return Ok(worker_id);
+    }
+    match req.extensions().get::<TokenScope>() {
+        Some(scope) => Ok(scope.worker_id),
+        None => Err(AppError::Unauthorized("Sign in first".into())),
+    }
+}
+
+/// Active sessions, most recently seen first, with `current` marked: every
+/// one for the owner (`worker` `None`), else those of `worker`.
+fn active_sessions(
+    conn: &Connection,
+    worker: Option<i64>,
+    current: Option<i64>,
+) -> Result<Vec<Session>, AppError> {
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE s.revoked_at IS NULL AND (?1 IS NULL OR s.worker_id = ?1)
+         ORDER BY s.last_seen_at DESC, s.id DESC",
+        SESSION_QUERY
+    ))?;
+    let sessions = stmt
+        .query_map([worker], read_session)?
+        .map(|s| {
+            s.map(|s| Session {
+                current: Some(s.id) == current,
+                ..s
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(sessions)
+}
+
+/// Handler for signing in a device.
+///
+/// # HTTP Method
+/// - `POST /sessions`
+///
+/// # Request
+/// - JSON `NewSession` with the owner's password, or a worker's name and
+///   PIN. The device's `User-Agent` and address are recorded with the
+///   session.
+///
+/// # Success
+/// - Returns HTTP 201 with a JSON `IssuedSession`. The token cannot be
+///   retrieved again later.
+///
+/// # Errors
+/// - Returns HTTP 400 if the device name is longer than
+///   `MAX_DEVICE_NAME_LEN` characters.
+/// - Returns HTTP 401 for an unknown worker or a wrong PIN or password.
+/// - Returns HTTP 429 after too many failed sign-ins to the account, from
+///   the device's address or altogether.
+pub async fn sign_in(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    new_session: web::Json<NewSession>,
+) -> Result<impl Responder, AppError> {
+    debug!(device = %new_session.device, worker = ?new_session.worker, "POST /sessions called");
+    let device = device_name(&req, &new_session.device)?;
+    let conn = db.get_conn()?;
+    let worker = new_session
+        .worker
+        .as_deref()
+        .map(str::trim)
+        .filter(|w| !w.is_empty());
+    let ip = peer_ip(&req);
+    let worker_id = check_credentials(&conn, worker, &new_session.secret, ip.as_deref())?;
+    let issued = create_session(&conn, &req, &device, worker_id)?;
+    Ok(HttpResponse::Created().json(issued))
+}
+
+/// Handler for changing the owner's password. Devices already signed in
+/// stay signed in.
+///
+/// # HTTP Method
+/// - `PUT /sessions/password`
+///
+/// # Request
+/// - JSON `PasswordChange` with the current password and the new one.
+///
+/// # Success
+/// - Returns HTTP 200 once the password is changed.
+///
+/// # Errors
+/// - Returns HTTP 400 if the new password is shorter than
+///   `MIN_OWNER_PASSWORD_LEN`.
+/// - Returns HTTP 401 if the current password is wrong.
+/// - Returns HTTP 403 unless the request was made in the owner's session.
+/// - Returns HTTP 429 after too many wrong passwords.
+pub async fn change_password(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    change: web::Json<PasswordChange>,
+) -> Result<impl Responder, AppError> {
+    debug!("PUT /sessions/password called");
+    let conn = db.get_conn()?;
+    if !session_permissions(&conn, current_session(&req))?.is_owner() {
+        warn!("Refused password change by a worker");
+        return Err(AppError::Forbidden(
+            "Only the owner may change the owner's password".into(),
+        ));
+    }
+    let ip = peer_ip(&req);
+    check_credentials(&conn, None, &change.current, ip.as_deref())?;
+    set_owner_password(&conn, &change.password)?;
+    Ok(HttpResponse::Ok().body("Password changed"))
+}
+
+/// Handler for listing the signed-in devices.
+///
+/// # HTTP Method
+/// - `GET /sessions`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of the `Session`s not revoked, most
+///   recently active first: every one for the owner, a worker's own for a
+///   worker. The requesting device's session, if any, is marked `current`.
+///
+/// # Errors
+/// - Returns HTTP 401 without a session or access token.
+pub async fn get_sessions(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+) -> Result<impl Responder, AppError> {
+    debug!("GET /sessions called");
+    let conn = db.get_conn()?;
+    let worker = requester(&conn, &req)?;
+    let sessions = active_sessions(&conn, worker, current_session(&req))?;
+    info!("Returning {} sessions", sessions.len());
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+/// Handler for signing out a device. Its token is refused from then on.
+///
+/// # HTTP Method
+/// - `DELETE /sessions/{id}`
+///
+/// # Success
+/// - Returns HTTP 200 once the session is revoked.
+///
+/// # Errors
+/// - Returns HTTP 400 if no active session has the given ID.
+/// - Returns HTTP 401 without a session or access token.
+/// - Returns HTTP 403 if a worker tries to sign out someone else's device.
+pub async fn revoke_session(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(session_id = id, "DELETE /sessions/{{id}} called");
+    let conn = db.get_conn()?;
+    let worker = requester(&conn, &req)?;
+    let owner_of: Option<Option<i64>> = conn
+        .query_row(
+            "SELECT worker_id FROM sessions WHERE id = ?1 AND revoked_at IS NULL",
+            [id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(owner_of) = owner_of else {
+        warn!(session_id = id, "Active session not found");
+        return Err(AppError::InvalidInput(format!(
+            "No active session found with ID {}",
+            id
+        )));
+    };
+    if worker.is_some() && owner_of != worker {
+        warn!(
+            session_id = id,
+            ?worker,
+            "Refused signing out another person's device"
+        );
+        return Err(AppError::Forbidden(
+            "Only the owner may sign out someone else's device".into(),
+        ));
+    }
+    conn.execute(
+        "UPDATE sessions SET revoked_at = CURRENT_TIMESTAMP WHERE id = ?1",
+        [id],
+    )?;
+
+    info!(session_id = id, "Session revoked");
+    Ok(HttpResponse::Ok().body("Session revoked"))
+}
+
+/// Handler for signing out every device but the requesting one: every
+/// other device for the owner, a worker's other devices for a worker.
+///
+/// # HTTP Method
+/// - `DELETE /sessions`
+///
+/// # Success
+/// - Returns HTTP 200 with the number of sessions revoked, as JSON.
+///
+/// # Errors
+/// - Returns HTTP 401 unless the request was made in a session.
+pub async fn revoke_other_sessions(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+) -> Result<impl Responder, AppError> {
+    let current = current_session(&req);
+    debug!(?current, "DELETE /sessions called");
+    let Some(current) = current else {
+        warn!("Refused signing out other devices without a session");
+        return Err(AppError::Unauthorized(
+            "Sign in on this device to sign out the others".into(),
+        ));
+    };
+    let conn = db.get_conn()?;
+    let worker = requester(&conn, &req)?;
+    let revoked = conn.execute(
+        "UPDATE sessions SET revoked_at = CURRENT_TIMESTAMP
+         WHERE revoked_at IS NULL AND id != ?1 AND (?2 IS NULL OR worker_id = ?2)",
+        params![current, worker],
+    )?;
+
+    info!(revoked, "Signed out other devices");
+    Ok(HttpResponse::Ok().json(revoked))
+}
+
+/// Handler for listing the OAuth providers workers can sign in with.
+///
+/// # HTTP Method
+/// - `GET /sessions/oauth`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of provider names, e.g.
+///   `["google"]`; empty if none is configured.
+pub async fn get_oauth_providers(
+    providers: Option<web::Data<OAuthProviders>>,
+) -> Result<impl Responder, AppError> {
+    debug!("GET /sessions/oauth called");
+    let names = providers.map(|p| p.names()).unwrap_or_default();
+    Ok(HttpResponse::Ok().json(names))
+}
+
+/// Handler for starting to sign in with an OAuth provider.
+///
+/// # HTTP Method
+/// - `POST /sessions/oauth/{provider}`
+///
+/// # Request
+/// - JSON `OAuthStart`.
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON `OAuthRedirect` to send the browser to.
+///   The sign-in must be finished at `POST /sessions/oauth/callback` within
+///   `OAUTH_STATE_MINUTES`.
+///
+/// # Errors
+/// - Returns HTTP 400 if the provider is not offered, the redirect URI is
+///   not an `http(s)://` URL, or the device name is too long.
+pub async fn start_oauth(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    providers: Option<web::Data<OAuthProviders>>,
+    path: web::Path<String>,
+    start: web::Json<OAuthStart>,
+) -> Result<impl Responder, AppError> {
+    let name = path.into_inner();
+    debug!(provider = %name, "POST /sessions/oauth/{{provider}} called");
+    let provider = providers
+        .and_then(|p| p.get(&name))
+        .ok_or_else(|| AppError::InvalidInput(format!("Signing in with {} is not set up", name)))?;
+    let redirect_uri = start.redirect_uri.trim();
+    if !(redirect_uri.starts_with("https://") || redirect_uri.starts_with("http://")) {
+        return Err(AppError::InvalidInput(
+            "The redirect URI must be an http(s):// URL".into(),
+        ));
+    }
+    let device = device_name(&req, &start.device)?;
+
+    let state = generate_key();
+    let conn = db.get_conn()?;
+    conn.execute(
+        &format!(
+            "DELETE FROM oauth_states WHERE created_at <= datetime('now', '-{} minutes')",
+            OAUTH_STATE_MINUTES
+        ),
+        [],
+    )?;
+    conn.execute(
+        "INSERT INTO oauth_states (state_hash, provider, redirect_uri, device)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![hash_key(&state), name, redirect_uri, device],
+    )?;
+
+    info!(provider = %name, "Started OAuth sign-in");
+    Ok(HttpResponse::Ok().json(OAuthRedirect {
+        authorize_url: provider.authorize_url(&state, redirect_uri),
+    }))
+}
+
+/// Handler for finishing an OAuth sign-in, once the provider has sent the
+/// browser back.
+///
+/// # HTTP Method
+/// - `POST /sessions/oauth/callback`
+///
+/// # Request
+/// - JSON `OAuthCallback`.
+///
+/// # Success
+/// - Returns HTTP 201 with a JSON `IssuedSession` for the worker who signed
+///   in (see `crate::auth::link_identity`).
+///
+/// # Errors
+/// - Returns HTTP 400 if the state is unknown, used or expired.
+/// - Returns HTTP 403 if the account is not linked to a worker and cannot
+///   be.
+/// - Returns HTTP 502 if the provider cannot be reached or refuses the code.
+pub async fn finish_oauth(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    providers: Option<web::Data<OAuthProviders>>,
+    callback: web::Json<OAuthCallback>,
+) -> Result<impl Responder, AppError> {
+    debug!("POST /sessions/oauth/callback called");
+    let conn = db.get_conn()?;
+    let state_hash = hash_key(callback.state.trim());
+    let pending: Option<(String, String, String)> = conn
+        .query_row(
+            &format!(
+                "SELECT provider, redirect_uri, device FROM oauth_states
+                 WHERE state_hash = ?1 AND created_at > datetime('now', '-{} minutes')",
+                OAUTH_STATE_MINUTES
+            ),
+            [&state_hash],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    // Each state is good for one try
+    conn.execute(
+        "DELETE FROM oauth_states WHERE state_hash = ?1",
+        [&state_hash],
+    )?;
+    let Some((name, redirect_uri, device)) = pending else {
+        warn!("Unknown or expired OAuth state");
+        return Err(AppError::InvalidInput(
+            "This sign-in has expired; start again".into(),
+        ));
+    };
+    let provider = providers
+        .and_then(|p| p.get(&name))
+        .ok_or_else(|| AppError::InvalidInput(format!("Signing in with {} is not set up", name)))?;
+
+    let code = callback.code.clone();
+    let identity = web::block(move || provider.identify(&code, &redirect_uri))
+        .await
+        .map_err(|e| AppError::Upstream(format!("Sign-in could not run: {}", e)))??;
+    let worker_id = link_identity(&conn, &name, &identity)?;
+    let issued = create_session(&conn, &req, &device, Some(worker_id))?;
+    Ok(HttpResponse::Created().json(issued))
+}
@@ -0,0 +1,280 @@
+//! This module handles farm-wide settings, stored as key/value pairs so new
+//! settings need no migration.
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::sessions::current_session;
+use crate::permissions::session_permissions;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use rusqlite::{Connection, params};
+use shared::finance::{validate_currency_code, validate_gstin};
+use shared::settings::{
+    DEFAULT_BASE_CURRENCY, DEFAULT_LANGUAGE, FarmSettings, ReadOnlyMode, validate_language,
+};
+use shared::setup::SetupStep;
+use shared::tags::TagSeries;
+use shared::time::{DEFAULT_TIMEZONE, parse_timezone, today_in};
+use std::collections::HashMap;
+use tracing::{debug, info, warn};
+
+/// Key of `FarmSettings::farm_name`.
+const FARM_NAME_KEY: &str = "farm_name";
+/// Key of `FarmSettings::gstin`.
+const GSTIN_KEY: &str = "gstin";
+/// Key of `FarmSettings::base_currency`.
+const BASE_CURRENCY_KEY: &str = "base_currency";
+/// Key of `FarmSettings::pricing`, stored as JSON.
+const PRICING_KEY: &str = "pricing";
+/// Key of `FarmSettings::read_only`, stored as `"true"` when on.
+const READ_ONLY_KEY: &str = "read_only";
+/// Key of `FarmSettings::timezone`.
+const TIMEZONE_KEY: &str = "timezone";
+/// Key of `FarmSettings::language`.
+const LANGUAGE_KEY: &str = "language";
+/// Key of `FarmSettings::setup_step`, stored as its database string.
+const SETUP_STEP_KEY: &str = "setup_step";
+/// Key of `FarmSettings::retention`, stored as JSON.
+const RETENTION_KEY: &str = "retention";
+/// Key of `FarmSettings::tag_series`, stored as JSON.
+const TAG_SERIES_KEY: &str = "tag_series";
+
+/// Loads the farm settings, with defaults for anything never set. A farm
+/// that never went through setup starts it, unless it already has goats.
+pub fn load_settings(conn: &Connection) -> Result<FarmSettings, AppError> {
+    let mut stmt = conn.prepare("SELECT key, value FROM farm_settings")?;
+    let values: HashMap<String, String> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    let pricing = values
+        .get(PRICING_KEY)
+        .map(|json| serde_json::from_str(json))
+        .transpose()?;
+    let retention = values
+        .get(RETENTION_KEY)
+        .map(|json| serde_json::from_str(json))
+        .transpose()?
+        .unwrap_or_default();
+    let tag_series = values
+        .get(TAG_SERIES_KEY)
+        .map(|json| serde_json::from_str(json))
+        .transpose()?;
+    let setup_step = match values.get(SETUP_STEP_KEY) {
+        Some(step) => SetupStep::from_str(step)
+            .map_err(|other| AppError::InvalidInput(format!("Unknown setup step '{}'", other)))?,
+        None => {
+            let has_goats: bool =
+                conn.query_row("SELECT EXISTS(SELECT 1 FROM goats)", [], |row| row.get(0))?;
+            if has_goats {
+                SetupStep::Done
+            } else {
+                SetupStep::Farm
+            }
+        }
+    };
+    Ok(FarmSettings {
+        farm_name: values.get(FARM_NAME_KEY).cloned(),
+        gstin: values.get(GSTIN_KEY).cloned(),
+        base_currency: values
+            .get(BASE_CURRENCY_KEY)
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_BASE_CURRENCY.to_string()),
+        pricing,
+        timezone: values
+            .get(TIMEZONE_KEY)
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_TIMEZONE.to_string()),
+        language: values
+            .get(LANGUAGE_KEY)
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_LANGUAGE.to_string()),
+        read_only: values.get(READ_ONLY_KEY).is_some_and(|v| v == "true"),
+        setup_step,
+        retention,
+        tag_series,
+    })
+}
+
+/// The farm's time zone, in which calendar dates are taken.
+pub fn farm_timezone(conn: &Connection) -> Result<Tz, AppError> {
+    Ok(load_settings(conn)?.tz())
+}
+
+/// Today's date in the farm's time zone.
+pub fn farm_today(conn: &Connection) -> Result<NaiveDate, AppError> {
+    Ok(today_in(farm_timezone(conn)?))
+}
+
+/// Stores one setting, removing it when `value` is `None`.
+fn store_setting(conn: &Connection, key: &str, value: Option<&str>) -> Result<(), AppError> {
+    match value {
+        Some(value) => conn.execute(
+            "INSERT INTO farm_settings (key, value) VALUES (?1, ?2) \
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?,
+        None => conn.execute("DELETE FROM farm_settings WHERE key = ?1", [key])?,
+    };
+    Ok(())
+}
+
+/// Handler for reading the farm settings.
+///
+/// # HTTP Method
+/// - `GET /settings`
+///
+/// # Success
+/// - Returns HTTP 200 with the JSON `FarmSettings`.
+pub async fn get_settings(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /settings called");
+    let conn = db.get_conn()?;
+    let settings = load_settings(&conn)?;
+
+    info!("Returning farm settings");
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+/// Handler for replacing the farm settings.
+///
+/// # HTTP Method
+/// - `PUT /settings`
+///
+/// # Request
+/// - JSON `FarmSettings`; fields left out are cleared or reset to their
+///   defaults. `read_only` is ignored; see `set_read_only`.
+///
+/// # Success
+/// - Returns HTTP 200 with the settings as stored, e.g. with the GSTIN
+///   upper-cased.
+///
+/// # Errors
+/// - Returns HTTP 400 for an invalid GSTIN or currency code, an unknown
+///   time zone or language, a new base currency once transactions are recorded (their
+///   amounts are in the old one), a pricing formula that does not
+///   compile, a retention period out of range, or an invalid tag series.
+pub async fn update_settings(
+    db: web::Data<DbPool>,
+    settings: web::Json<FarmSettings>,
+) -> Result<impl Responder, AppError> {
+    debug!(
+        gstin = ?settings.gstin,
+        base_currency = %settings.base_currency,
+        timezone = %settings.timezone,
+        language = %settings.language,
+        setup_step = ?settings.setup_step,
+        "PUT /settings called"
+    );
+    let farm_name = settings
+        .farm_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty());
+    let gstin = settings
+        .gstin
+        .as_deref()
+        .map(|g| g.trim().to_uppercase())
+        .filter(|g| !g.is_empty());
+    if let Some(gstin) = &gstin {
+        validate_gstin(gstin).map_err(AppError::InvalidInput)?;
+    }
+    let base_currency = settings.base_currency.trim().to_uppercase();
+    validate_currency_code(&base_currency).map_err(AppError::InvalidInput)?;
+    let timezone = parse_timezone(&settings.timezone).map_err(AppError::InvalidInput)?;
+    validate_language(&settings.language).map_err(AppError::InvalidInput)?;
+    settings
+        .retention
+        .validate()
+        .map_err(AppError::InvalidInput)?;
+    let tag_series = match &settings.tag_series {
+        Some(series) => {
+            series.validate().map_err(AppError::InvalidInput)?;
+            Some(serde_json::to_string(&TagSeries {
+                prefix: series.prefix.trim().to_string(),
+                ..series.clone()
+            })?)
+        }
+        None => None,
+    };
+    let pricing = match &settings.pricing {
+        Some(pricing) => {
+            pricing
+                .compile()
+                .map_err(|e| AppError::InvalidInput(format!("Pricing formula: {}", e)))?;
+            Some(serde_json::to_string(pricing)?)
+        }
+        None => None,
+    };
+
+    let conn = db.get_conn()?;
+    let current = load_settings(&conn)?;
+    if base_currency != current.base_currency {
+        let recorded: bool =
+            conn.query_row("SELECT EXISTS(SELECT 1 FROM transactions)", [], |row| {
+                row.get(0)
+            })?;
+        if recorded {
+            return Err(AppError::InvalidInput(format!(
+                "Base currency cannot change from {} once transactions are recorded",
+                current.base_currency
+            )));
+        }
+    }
+    store_setting(&conn, FARM_NAME_KEY, farm_name)?;
+    store_setting(&conn, GSTIN_KEY, gstin.as_deref())?;
+    store_setting(&conn, BASE_CURRENCY_KEY, Some(&base_currency))?;
+    store_setting(&conn, PRICING_KEY, pricing.as_deref())?;
+    store_setting(&conn, TIMEZONE_KEY, Some(timezone.name()))?;
+    store_setting(&conn, LANGUAGE_KEY, Some(&settings.language))?;
+    store_setting(
+        &conn,
+        SETUP_STEP_KEY,
+        Some(SetupStep::to_str(&settings.setup_step)),
+    )?;
+    let retention = serde_json::to_string(&settings.retention)?;
+    store_setting(&conn, RETENTION_KEY, Some(&retention))?;
+    store_setting(&conn, TAG_SERIES_KEY, tag_series.as_deref())?;
+    let settings = load_settings(&conn)?;
+
+    info!(
+        read_only = settings.read_only,
+        setup_step = ?settings.setup_step,
+        "Farm settings updated"
+    );
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+/// Handler for switching read-only mode on or off. Reachable in read-only
+/// mode, so the owner can switch it off again.
+///
+/// # HTTP Method
+/// - `PUT /settings/read-only`
+///
+/// # Request
+/// - JSON `ReadOnlyMode`.
+///
+/// # Success
+/// - Returns HTTP 200 with the farm settings as stored.
+///
+/// # Errors
+/// - Returns HTTP 401 outside a session.
+/// - Returns HTTP 403 unless the request was made in the owner's session.
+pub async fn set_read_only(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    mode: web::Json<ReadOnlyMode>,
+) -> Result<impl Responder, AppError> {
+    debug!(read_only = mode.read_only, "PUT /settings/read-only called");
+    let conn = db.get_conn()?;
+    if !session_permissions(&conn, current_session(&req))?.is_owner() {
+        warn!("Refused read-only switch by a worker");
+        return Err(AppError::Forbidden(
+            "Only the owner may switch read-only mode".into(),
+        ));
+    }
+    store_setting(&conn, READ_ONLY_KEY, mode.read_only.then_some("true"))?;
+    let settings = load_settings(&conn)?;
+
+    info!(read_only = settings.read_only, "Read-only mode switched");
+    Ok(HttpResponse::Ok().json(settings))
+}
@@ -0,0 +1,239 @@
+//! This module handles farm spaces (pens, grazing fields), which goats are
+//! housed in each, and how full each space is.
+
+use crate::db::DbPool;
+use crate::errors::{AppError, ParseEnumError};
+use crate::events::EventLog;
+use actix_web::{HttpResponse, Responder, web};
+use rusqlite::{Connection, OptionalExtension, params};
+use shared::events::DomainEvent;
+use shared::spaces::{Space, SpaceKind, SpaceOccupancy};
+use std::collections::HashMap;
+use tracing::{debug, info, warn};
+
+/// Parses a `spaces.type` value.
+fn parse_kind(kind: &str) -> Result<SpaceKind, AppError> {
+    SpaceKind::from_str(kind)
+        .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "SpaceKind")))
+}
+
+/// Counts the goats in every space, or only in space `only` if given, and
+/// works out each space's occupancy. Spaces are ordered by name.
+fn load_occupancy(conn: &Connection, only: Option<i64>) -> Result<Vec<SpaceOccupancy>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT s.id, s.name, COALESCE(s.type, 'other'), s.capacity, s.area_m2, COUNT(g.id)
+         FROM spaces s
+         LEFT JOIN goats g ON g.space_id = s.id AND g.deleted_at IS NULL
+         WHERE ?1 IS NULL OR s.id = ?1
+         GROUP BY s.id
+         ORDER BY s.name",
+    )?;
+    let rows = stmt
+        .query_map([only], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+                row.get::<_, Option<f64>>(4)?,
+                row.get::<_, i64>(5)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    rows.into_iter()
+        .map(|(id, name, kind, capacity, area_m2, goats)| {
+            Ok(SpaceOccupancy::new(
+                id,
+                name,
+                parse_kind(&kind)?,
+                capacity,
+                area_m2,
+                goats,
+            ))
+        })
+        .collect()
+}
+
+/// Handler for listing spaces with the goats assigned to each.
+///
+/// # HTTP Method
+/// - `GET /spaces`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of spaces ordered by name.
+pub async fn get_spaces(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /spaces called");
+    let conn = db.get_conn()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT space_id, name FROM goats \
+         WHERE space_id IS NOT NULL AND deleted_at IS NULL ORDER BY name",
+    )?;
+    let mut members: HashMap<i64, Vec<String>> = HashMap::new();
+    for row in stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?)))? {
+        let (space_id, name) = row?;
+        members.entry(space_id).or_default().push(name);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, name, COALESCE(type, 'other'), capacity, area_m2 FROM spaces ORDER BY name",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+                row.get::<_, Option<f64>>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let spaces = rows
+        .into_iter()
+        .map(|(id, name, kind, capacity, area_m2)| {
+            Ok(Space {
+                id: Some(id),
+                name,
+                kind: parse_kind(&kind)?,
+                capacity,
+                area_m2,
+                goat_names: members.remove(&id).unwrap_or_default(),
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    info!("Returning {} spaces", spaces.len());
+    Ok(HttpResponse::Ok().json(spaces))
+}
+
+/// Handler for creating a space.
+///
+/// # HTTP Method
+/// - `POST /spaces`
+///
+/// # Success
+/// - Returns HTTP 201 on successful insertion.
+///
+/// # Errors
+/// - Returns HTTP 400 if the name is empty, the capacity is negative or the
+///   area is not positive.
+pub async fn add_space(
+    db: web::Data<DbPool>,
+    space: web::Json<Space>,
+) -> Result<impl Responder, AppError> {
+    debug!(name = %space.name, "POST /spaces called");
+    if space.name.trim().is_empty() {
+        return Err(AppError::InvalidInput(
+            "Space name must not be empty".into(),
+        ));
+    }
+    if space.capacity.is_some_and(|c| c < 0) {
+        return Err(AppError::InvalidInput("capacity cannot be negative".into()));
+    }
+    if space.area_m2.is_some_and(|a| a <= 0.0) {
+        return Err(AppError::InvalidInput("area must be positive".into()));
+    }
+    let conn = db.get_conn()?;
+    conn.execute(
+        "INSERT INTO spaces (name, type, capacity, area_m2) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            space.name.trim(),
+            SpaceKind::to_str(&space.kind),
+            space.capacity,
+            space.area_m2
+        ],
+    )?;
+    info!(space_id = conn.last_insert_rowid(), "Space added");
+    Ok(HttpResponse::Created().body("Space added"))
+}
+
+/// Handler for moving goats into a space.
+///
+/// Goats already elsewhere are moved; goats not listed keep their current space.
+/// Overstocking is allowed, but reported in the response's warnings.
+///
+/// # HTTP Method
+/// - `PUT /spaces/{id}/goats`
+///
+/// # Request
+/// - JSON array of goat names.
+///
+/// # Success
+/// - Returns HTTP 200 with the space's `SpaceOccupancy` once every goat is
+///   assigned.
+///
+/// # Errors
+/// - Returns HTTP 400 if the space or any goat does not exist; no goat is
+///   moved in that case.
+pub async fn assign_goats(
+    db: web::Data<DbPool>,
+    events: EventLog,
+    path: web::Path<i64>,
+    names: web::Json<Vec<String>>,
+) -> Result<impl Responder, AppError> {
+    let space_id = path.into_inner();
+    debug!(
+        space_id,
+        count = names.len(),
+        "PUT /spaces/{{id}}/goats called"
+    );
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+    tx.query_row("SELECT id FROM spaces WHERE id = ?1", [space_id], |row| {
+        row.get::<_, i64>(0)
+    })
+    .optional()?
+    .ok_or_else(|| AppError::InvalidInput(format!("No space found with ID {}", space_id)))?;
+
+    for name in names.iter() {
+        let Some(goat_id) = tx
+            .query_row(
+                "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+                [name],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?
+        else {
+            warn!(space_id, goat = %name, "Unknown goat in pen assignment");
+            return Err(AppError::InvalidInput(format!(
+                "No goat found with name {}",
+                name
+            )));
+        };
+        events.apply(&tx, &DomainEvent::GoatPlaced { goat_id, space_id })?;
+    }
+    tx.commit()?;
+
+    let occupancy = load_occupancy(&conn, Some(space_id))?
+        .pop()
+        .ok_or_else(|| AppError::InvalidInput(format!("No space found with ID {}", space_id)))?;
+    for warning in &occupancy.warnings {
+        warn!(space_id, "{}", warning);
+    }
+    info!(space_id, "Assigned {} goats", names.len());
+    Ok(HttpResponse::Ok().json(occupancy))
+}
+
+/// Handler for the occupancy of every space.
+///
+/// # HTTP Method
+/// - `GET /spaces/occupancy`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `SpaceOccupancy` ordered by
+///   space name: goats assigned against capacity and area, with warnings
+///   for overstocked spaces.
+pub async fn get_occupancy(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /spaces/occupancy called");
+    let conn = db.get_conn()?;
+    let occupancy = load_occupancy(&conn, None)?;
+
+    let overstocked = occupancy.iter().filter(|o| !o.warnings.is_empty()).count();
+    info!(
+        overstocked,
+        "Returning occupancy of {} spaces",
+        occupancy.len()
+    );
+    Ok(HttpResponse::Ok().json(occupancy))
+}
@@ -0,0 +1,164 @@
+//! This module computes the headline figures for the dashboard's KPI cards:
+//! herd size, herd value, milk this week and expenses this month, each with
+//! a short trend (see `shared::stats`).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::scale::READ_AT_FORMAT;
+use crate::handlers::settings::{farm_today, load_settings};
+use crate::scheduler::DATE_FORMAT;
+use actix_web::{HttpResponse, Responder, web};
+use chrono::{Datelike, Days, Months, NaiveDate};
+use chrono_tz::Tz;
+use rusqlite::{Connection, params};
+use serde::Deserialize;
+use shared::stats::{DashboardStats, Kpi, TREND_PERIODS};
+use shared::time::day_start;
+use tracing::{debug, info};
+
+/// Query parameters accepted by `GET /stats`.
+///
+/// `as_of` defaults to today.
+#[derive(Deserialize)]
+pub struct StatsQuery {
+    pub as_of: Option<String>,
+}
+
+/// The `TREND_PERIODS` seven-day periods ending on `as_of`, oldest first,
+/// as inclusive `(start, end)` date pairs.
+fn weeks(as_of: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+    (0..TREND_PERIODS as u64)
+        .rev()
+        .map(|back| {
+            let end = as_of - Days::new(7 * back);
+            (end - Days::new(6), end)
+        })
+        .collect()
+}
+
+/// The `TREND_PERIODS` calendar months up to `as_of`, oldest first; the last
+/// one ends on `as_of` rather than at the end of the month.
+fn months(as_of: NaiveDate) -> Vec<(NaiveDate, NaiveDate)> {
+    let this_month = as_of.with_day(1).expect("day 1 exists in every month");
+    (0..TREND_PERIODS as u32)
+        .rev()
+        .map(|back| {
+            let start = this_month - Months::new(back);
+            let end = (start + Months::new(1) - Days::new(1)).min(as_of);
+            (start, end)
+        })
+        .collect()
+}
+
+/// Periods as `(start, end)` date strings, for date columns.
+fn as_dates(periods: &[(NaiveDate, NaiveDate)]) -> Vec<(String, String)> {
+    periods
+        .iter()
+        .map(|(start, end)| {
+            (
+                start.format(DATE_FORMAT).to_string(),
+                end.format(DATE_FORMAT).to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Periods as the UTC instants their first day starts and the day after
+/// their last starts in `tz`, for timestamp columns.
+fn as_instants(periods: &[(NaiveDate, NaiveDate)], tz: Tz) -> Vec<(String, String)> {
+    let start_of = |day: NaiveDate| day_start(day, tz).format(READ_AT_FORMAT).to_string();
+    periods
+        .iter()
+        .map(|(start, end)| (start_of(*start), start_of(*end + Days::new(1))))
+        .collect()
+}
+
+/// Runs `sql`, which takes a period's bounds, once per period and collects
+/// the single number it returns.
+fn sum_per_period(
+    conn: &Connection,
+    sql: &str,
+    periods: &[(String, String)],
+) -> Result<Vec<f64>, AppError> {
+    let mut stmt = conn.prepare(sql)?;
+    periods
+        .iter()
+        .map(|(start, end)| Ok(stmt.query_row(params![start, end], |row| row.get(0))?))
+        .collect()
+}
+
+/// Computes the KPI figures as of `as_of`.
+pub fn load_stats(conn: &Connection, as_of: NaiveDate) -> Result<DashboardStats, AppError> {
+    let settings = load_settings(conn)?;
+    let weeks = weeks(as_of);
+    // Herd figures only use the end of each week (?2), the farm's midnight
+    // after it in UTC. Goats without a creation time predate it being
+    // recorded, so they count in every week.
+    let week_instants = as_instants(&weeks, settings.tz());
+    let herd_size = sum_per_period(
+        conn,
+        "SELECT COUNT(*) FROM goats \
+         WHERE deleted_at IS NULL AND (created_at IS NULL OR created_at < ?2)",
+        &week_instants,
+    )?;
+    let herd_value = sum_per_period(
+        conn,
+        "SELECT COALESCE(SUM(current_price), 0) FROM goats \
+         WHERE deleted_at IS NULL AND (created_at IS NULL OR created_at < ?2)",
+        &week_instants,
+    )?;
+    let milk = sum_per_period(
+        conn,
+        "SELECT COALESCE(SUM(yield_litres), 0) FROM milk_records \
+         WHERE recorded_on BETWEEN ?1 AND ?2",
+        &as_dates(&weeks),
+    )?;
+    let expenses = sum_per_period(
+        conn,
+        "SELECT COALESCE(SUM(amount * COALESCE(exchange_rate, 1)), 0) FROM transactions \
+         WHERE kind = 'Expense' AND date BETWEEN ?1 AND ?2",
+        &as_dates(&months(as_of)),
+    )?;
+
+    Ok(DashboardStats {
+        as_of: as_of.format(DATE_FORMAT).to_string(),
+        currency: settings.base_currency,
+        herd_size: Kpi::from_trend(herd_size),
+        herd_value: Kpi::from_trend(herd_value),
+        milk_this_week: Kpi::from_trend(milk),
+        expenses_this_month: Kpi::from_trend(expenses),
+    })
+}
+
+/// Handler for the dashboard's KPI figures.
+///
+/// # HTTP Method
+/// - `GET /stats?as_of=2025-06-30`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON `DashboardStats`; each KPI has a trend of
+///   `TREND_PERIODS` periods ending with the current one.
+///
+/// # Errors
+/// - Returns HTTP 400 if `as_of` is not a `YYYY-MM-DD` date.
+pub async fn get_stats(
+    db: web::Data<DbPool>,
+    query: web::Query<StatsQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(as_of = ?query.as_of, "GET /stats called");
+    let conn = db.get_conn()?;
+    let as_of = match &query.as_of {
+        Some(date) => NaiveDate::parse_from_str(date, DATE_FORMAT).map_err(|_| {
+            AppError::InvalidInput(format!("as_of must be YYYY-MM-DD, got '{}'", date))
+        })?,
+        None => farm_today(&conn)?,
+    };
+    let stats = load_stats(&conn, as_of)?;
+
+    info!(
+        herd_size = stats.herd_size.value,
+        as_of = %stats.as_of,
+        "Returning dashboard stats"
+    );
+    Ok(HttpResponse::Ok().json(stats))
+}
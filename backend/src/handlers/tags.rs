@@ -0,0 +1,26 @@
+//! This module tells the add goat form which tag the next goat will get
+//! (see `crate::tags`).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::tags::peek_tag;
+use actix_web::{HttpResponse, Responder, web};
+use shared::tags::NextTag;
+use tracing::{debug, info};
+
+/// Handler for the tag the next goat added will get.
+///
+/// # HTTP Method
+/// - `GET /goats/next-tag`
+///
+/// # Success
+/// - Returns HTTP 200 with a `NextTag`, without a tag if the farm has no
+///   tag series. The tag is only assigned once the goat is added.
+pub async fn get_next_tag(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /goats/next-tag called");
+    let conn = db.get_conn()?;
+    let tag_id = peek_tag(&conn)?;
+
+    info!(tag_id = ?tag_id, "Returning next tag");
+    Ok(HttpResponse::Ok().json(NextTag { tag_id }))
+}
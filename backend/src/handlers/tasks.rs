@@ -0,0 +1,187 @@
+//! This module handles creation, listing, completion, and deletion of farm tasks.
+//!
+//! Tasks are either entered manually or generated by the reminder rules
+//! scheduler (see `crate::scheduler`). Goats are referenced by name in the
+//! API and resolved to their row ID on insert.
+
+use crate::db::DbPool;
+use crate::errors::{AppError, ParseEnumError};
+use actix_web::{HttpResponse, Responder, web};
+use rusqlite::{OptionalExtension, Row, params};
+use serde::Deserialize;
+use shared::tasks::{Task, TaskStatus};
+use tracing::{debug, info, warn};
+
+/// Query parameters accepted by `GET /tasks`.
+#[derive(Deserialize)]
+pub struct TaskQuery {
+    /// Optional status filter, e.g. `Pending`.
+    pub status: Option<String>,
+}
+
+const TASK_SELECT: &str = "SELECT t.id, t.title, t.notes, g.name, t.space_id, s.name, t.rule_id, t.due_date, t.status \
+     FROM tasks t \
+     LEFT JOIN goats g ON g.id = t.goat_id \
+     LEFT JOIN spaces s ON s.id = t.space_id";
+
+/// Maps a row selected with `TASK_SELECT` to a `Task`.
+fn row_to_task(row: &Row) -> Result<Task, AppError> {
+    let status: String = row.get(8)?;
+    Ok(Task {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        notes: row.get(2)?,
+        goat_name: row.get(3)?,
+        space_id: row.get(4)?,
+        space_name: row.get(5)?,
+        rule_id: row.get(6)?,
+        due_date: row.get(7)?,
+        status: TaskStatus::from_str(&status)
+            .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "TaskStatus")))?,
+    })
+}
+
+/// Handler for listing tasks ordered by due date.
+///
+/// # HTTP Method
+/// - `GET /tasks?status=Pending`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of tasks.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown status filter.
+pub async fn get_tasks(
+    db: web::Data<DbPool>,
+    query: web::Query<TaskQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(status = ?query.status, "GET /tasks called");
+    if let Some(status) = &query.status {
+        TaskStatus::from_str(status)
+            .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "TaskStatus")))?;
+    }
+
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE (?1 IS NULL OR t.status = ?1) ORDER BY t.due_date, t.id",
+        TASK_SELECT
+    ))?;
+    let tasks: Result<Vec<Task>, rusqlite::Error> = stmt
+        .query_map([&query.status], |row| {
+            row_to_task(row).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+        })?
+        .collect();
+    let tasks = tasks?;
+
+    info!("Returning {} tasks", tasks.len());
+    Ok(HttpResponse::Ok().json(tasks))
+}
+
+/// Handler for creating a manual task.
+///
+/// # HTTP Method
+/// - `POST /tasks`
+///
+/// # Request
+/// - JSON `Task`; `id`, `rule_id` and `space_name` are ignored.
+///
+/// # Success
+/// - Returns HTTP 201 on successful insertion.
+///
+/// # Errors
+/// - Returns HTTP 400 if the title is empty or the referenced goat does not exist.
+pub async fn add_task(
+    db: web::Data<DbPool>,
+    task: web::Json<Task>,
+) -> Result<impl Responder, AppError> {
+    debug!(title = %task.title, "POST /tasks called");
+    if task.title.trim().is_empty() {
+        return Err(AppError::InvalidInput("Task title is required".into()));
+    }
+
+    let conn = db.get_conn()?;
+    let goat_id: Option<i64> = match &task.goat_name {
+        Some(name) => Some(
+            conn.query_row(
+                "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+                [name],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| AppError::InvalidInput(format!("No goat found with name {}", name)))?,
+        ),
+        None => None,
+    };
+
+    conn.execute(
+        "INSERT INTO tasks (title, notes, goat_id, space_id, due_date, status) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            task.title,
+            task.notes,
+            goat_id,
+            task.space_id,
+            task.due_date,
+            TaskStatus::to_str(&task.status),
+        ],
+    )?;
+
+    info!(task_id = conn.last_insert_rowid(), "Task created");
+    Ok(HttpResponse::Created().body("Task added"))
+}
+
+/// Handler for marking a task as done.
+///
+/// # HTTP Method
+/// - `PUT /tasks/{id}/complete`
+///
+/// # Success
+/// - Returns HTTP 200 once the task is marked `Done`.
+///
+/// # Errors
+/// - Returns HTTP 400 if no task matches the ID.
+pub async fn complete_task(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(task_id = id, "PUT /tasks/{{id}}/complete called");
+
+    let conn = db.get_conn()?;
+    let affected = conn.execute(
+        "UPDATE tasks SET status = 'Done', completed_at = CURRENT_TIMESTAMP WHERE id = ?1",
+        [id],
+    )?;
+    if affected == 0 {
+        warn!(task_id = id, "Task not found for completion");
+        return Err(AppError::InvalidInput(format!("No task found with id {}", id)));
+    }
+
+    info!(task_id = id, "Task completed");
+    Ok(HttpResponse::Ok().body("Task completed"))
+}
+
+/// Handler for deleting a task and its reminders.
+///
+/// # HTTP Method
+/// - `DELETE /tasks/{id}`
+///
+/// # Errors
+/// - Returns HTTP 400 if no task matches the ID.
+pub async fn delete_task(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(task_id = id, "DELETE /tasks/{{id}} called");
+
+    let conn = db.get_conn()?;
+    conn.execute("DELETE FROM reminders WHERE task_id = ?1", [id])?;
+    let affected = conn.execute("DELETE FROM tasks WHERE id = ?1", [id])?;
+    if affected == 0 {
+        warn!(task_id = id, "Task not found for deletion");
+        return Err(AppError::InvalidInput(format!("No task found with id {}", id)));
+    }
+
+    info!(task_id = id, "Task deleted");
+    Ok(HttpResponse::Ok().body("Task deleted"))
+}
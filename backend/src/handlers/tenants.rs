@@ -0,0 +1,86 @@
+//! This module handles the operator endpoints for hosting farms in
+//! multi-tenant mode (see `crate::tenants`).
+
+use crate::errors::AppError;
+use crate::tenants::{ADMIN_KEY_HEADER, TenantRegistry};
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use shared::tenants::NewTenant;
+use tracing::{debug, info, warn};
+
+/// Returns the registry if the request carries the operator's admin key.
+fn admin_registry(
+    req: &HttpRequest,
+    registry: Option<web::Data<TenantRegistry>>,
+) -> Result<web::Data<TenantRegistry>, AppError> {
+    let registry = registry
+        .ok_or_else(|| AppError::InvalidInput("Multi-tenant mode is not enabled".into()))?;
+    let key = req
+        .headers()
+        .get(ADMIN_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| AppError::Unauthorized("Missing admin key".into()))?;
+    if !registry.is_admin(key.trim()) {
+        warn!("Rejected invalid admin key");
+        return Err(AppError::Unauthorized("Invalid admin key".into()));
+    }
+    Ok(registry)
+}
+
+/// Handler for listing hosted farms.
+///
+/// # HTTP Method
+/// - `GET /tenants`
+///
+/// # Request
+/// - `X-Admin-Key` header with the operator's admin key.
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `Tenant`, ordered by slug.
+///
+/// # Errors
+/// - Returns HTTP 401 for a missing or wrong admin key.
+/// - Returns HTTP 400 when the backend is not in multi-tenant mode.
+pub async fn get_tenants(
+    req: HttpRequest,
+    registry: Option<web::Data<TenantRegistry>>,
+) -> Result<impl Responder, AppError> {
+    debug!("GET /tenants called");
+    let tenants = admin_registry(&req, registry)?.list()?;
+
+    info!("Returning {} tenants", tenants.len());
+    Ok(HttpResponse::Ok().json(tenants))
+}
+
+/// Handler for hosting a new farm.
+///
+/// # HTTP Method
+/// - `POST /tenants`
+///
+/// # Request
+/// - `X-Admin-Key` header with the operator's admin key.
+/// - JSON `NewTenant`.
+///
+/// # Success
+/// - Returns HTTP 201 with an `IssuedTenant`, including the tenant key. The
+///   key is not retrievable afterwards.
+///
+/// # Errors
+/// - Returns HTTP 401 for a missing or wrong admin key.
+/// - Returns HTTP 400 for an empty name, a malformed or taken slug, an owner
+///   password shorter than `shared::sessions::MIN_OWNER_PASSWORD_LEN`, or
+///   when the backend is not in multi-tenant mode.
+pub async fn add_tenant(
+    req: HttpRequest,
+    registry: Option<web::Data<TenantRegistry>>,
+    tenant: web::Json<NewTenant>,
+) -> Result<impl Responder, AppError> {
+    debug!(slug = %tenant.slug, "POST /tenants called");
+    let issued = admin_registry(&req, registry)?.create(
+        &tenant.slug,
+        &tenant.name,
+        &tenant.owner_password,
+    )?;
+
+    info!(tenant = %issued.slug, "Tenant issued");
+    Ok(HttpResponse::Created().json(issued))
+}
@@ -0,0 +1,160 @@
+//! This module manages the personal access tokens scripts use with the REST
+//! API, checked by `crate::auth::check_access_token`.
+
+use crate::auth::{generate_key, hash_key};
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::sessions::current_session;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use rusqlite::{Connection, Row, params};
+use shared::tokens::{AccessToken, IssuedAccessToken, NewAccessToken};
+use tracing::{debug, info, warn};
+
+/// Columns read by `read_token`.
+const TOKEN_QUERY: &str = "SELECT t.id, t.name, t.read_only, t.modules, w.name, t.created_at,
+        t.last_used_at, t.revoked_at IS NOT NULL
+     FROM access_tokens t LEFT JOIN workers w ON w.id = t.worker_id";
+
+fn read_token(row: &Row) -> rusqlite::Result<(AccessToken, String)> {
+    Ok((
+        AccessToken {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            read_only: row.get(2)?,
+            modules: Vec::new(),
+            worker: row.get(4)?,
+            created_at: row.get(5)?,
+            last_used_at: row.get(6)?,
+            revoked: row.get(7)?,
+        },
+        row.get(3)?,
+    ))
+}
+
+/// `token` with its modules parsed from `modules`.
+fn with_modules((token, modules): (AccessToken, String)) -> Result<AccessToken, AppError> {
+    Ok(AccessToken {
+        modules: serde_json::from_str(&modules)?,
+        ..token
+    })
+}
+
+fn load_token(conn: &Connection, id: i64) -> Result<AccessToken, AppError> {
+    with_modules(conn.query_row(
+        &format!("{} WHERE t.id = ?1", TOKEN_QUERY),
+        [id],
+        read_token,
+    )?)
+}
+
+/// Handler for listing access tokens, newest first.
+///
+/// # HTTP Method
+/// - `GET /tokens`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `AccessToken` (without the
+///   tokens), revoked ones included.
+pub async fn get_tokens(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /tokens called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(&format!("{} ORDER BY t.id DESC", TOKEN_QUERY))?;
+    let tokens = stmt
+        .query_map([], read_token)?
+        .map(|row| with_modules(row?))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    info!("Returning {} access tokens", tokens.len());
+    Ok(HttpResponse::Ok().json(tokens))
+}
+
+/// Handler for making an access token. A token made in a worker's session
+/// can do no more than the worker's role.
+///
+/// # HTTP Method
+/// - `POST /tokens`
+///
+/// # Request
+/// - JSON `NewAccessToken`: its name, whether it is read-only and the
+///   modules it may reach (all of them if empty).
+///
+/// # Success
+/// - Returns HTTP 201 with a JSON `IssuedAccessToken`. The token cannot be
+///   retrieved again later.
+///
+/// # Errors
+/// - Returns HTTP 400 if the name is empty or too long.
+pub async fn add_token(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    new_token: web::Json<NewAccessToken>,
+) -> Result<impl Responder, AppError> {
+    debug!(name = %new_token.name, "POST /tokens called");
+    new_token.validate().map_err(AppError::InvalidInput)?;
+    let mut modules = new_token.modules.clone();
+    modules.sort();
+    modules.dedup();
+    let token = generate_key();
+    let conn = db.get_conn()?;
+    let worker_id: Option<i64> = match current_session(&req) {
+        Some(session) => conn.query_row(
+            "SELECT worker_id FROM sessions WHERE id = ?1",
+            [session],
+            |row| row.get(0),
+        )?,
+        None => None,
+    };
+    conn.execute(
+        "INSERT INTO access_tokens (name, token_hash, read_only, modules, worker_id)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            new_token.name.trim(),
+            hash_key(&token),
+            new_token.read_only,
+            serde_json::to_string(&modules)?,
+            worker_id
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    let access_token = load_token(&conn, id)?;
+
+    info!(access_token_id = id, scope = %access_token.scope(), "Access token created");
+    Ok(HttpResponse::Created().json(IssuedAccessToken {
+        access_token,
+        token,
+    }))
+}
+
+/// Handler for revoking an access token. Revoked tokens stay listed.
+///
+/// # HTTP Method
+/// - `DELETE /tokens/{id}`
+///
+/// # Success
+/// - Returns HTTP 200 once the token is revoked.
+///
+/// # Errors
+/// - Returns HTTP 400 if no active token has the given ID.
+pub async fn revoke_token(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(access_token_id = id, "DELETE /tokens/{{id}} called");
+    let conn = db.get_conn()?;
+    let updated = conn.execute(
+        "UPDATE access_tokens SET revoked_at = CURRENT_TIMESTAMP
+         WHERE id = ?1 AND revoked_at IS NULL",
+        [id],
+    )?;
+    if updated == 0 {
+        warn!(access_token_id = id, "Active access token not found");
+        return Err(AppError::InvalidInput(format!(
+            "No active access token found with ID {}",
+            id
+        )));
+    }
+
+    info!(access_token_id = id, "Access token revoked");
+    Ok(HttpResponse::Ok().body("Access token revoked"))
+}
@@ -0,0 +1,281 @@
+//! This module builds the traceability records of sold goats, taken when a
+//! sale is recorded, and serves them to the farm and, by token, to buyers
+//! (see `shared::traceability`).
+
+use crate::auth::generate_key;
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::health::goat_timeline;
+use crate::handlers::movements::goat_movements;
+use crate::handlers::settings::load_settings;
+use crate::tenants::{share_token, token_of_share};
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Deserialize;
+use shared::finance::Transaction;
+use shared::lifecycle::LifecycleStage;
+use shared::traceability::{Movement, MovementKind, TraceabilityRecord};
+use tracing::{debug, info, warn};
+
+/// Query parameters accepted by `GET /traceability`.
+#[derive(Deserialize)]
+pub struct TraceabilityQuery {
+    pub goat_name: Option<String>,
+}
+
+/// Human-readable name of the lifecycle stage stored as `stage`.
+fn stage_label(stage: String) -> String {
+    LifecycleStage::from_str(&stage)
+        .map(|s| s.label().to_string())
+        .unwrap_or(stage)
+}
+
+/// The movements of the goat with ID `goat_id` up to its sale: how it came to the herd, its lifecycle stage changes and
+/// logged movements by date, the pen it was kept in and the sale itself.
+fn movement_record(
+    conn: &Connection,
+    goat_id: i64,
+    transaction: &Transaction,
+) -> Result<Vec<Movement>, AppError> {
+    let (date_of_birth, dam, created_at, space): (
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+    ) = conn.query_row(
+        "SELECT g.date_of_birth, d.name, g.created_at, s.name FROM goats g \
+         LEFT JOIN goats d ON d.id = g.dam_id \
+         LEFT JOIN spaces s ON s.id = g.space_id WHERE g.id = ?1 AND g.deleted_at IS NULL",
+        [goat_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )?;
+
+    let mut movements = vec![match dam {
+        Some(dam) => Movement {
+            date: date_of_birth,
+            kind: MovementKind::Born,
+            description: format!("Born on the farm to {}", dam),
+        },
+        None => Movement {
+            date: created_at.and_then(|at| at.get(..10).map(str::to_string)),
+            kind: MovementKind::Arrived,
+            description: "Entered the herd".to_string(),
+        },
+    }];
+
+    let mut dated = Vec::new();
+    let mut stmt = conn.prepare(
+        "SELECT from_stage, to_stage, changed_on, note FROM lifecycle_changes \
+         WHERE goat_id = ?1 ORDER BY changed_on, id",
+    )?;
+    let mut rows = stmt.query([goat_id])?;
+    while let Some(row) = rows.next()? {
+        let from: Option<String> = row.get(0)?;
+        let to = stage_label(row.get(1)?);
+        let note: Option<String> = row.get(3)?;
+        let mut description = match from {
+            Some(from) => format!("{} → {}", stage_label(from), to),
+            None => format!("Entered the lifecycle as {}", to),
+        };
+        if let Some(note) = note.filter(|n| !n.trim().is_empty()) {
+            description = format!("{} ({})", description, note.trim());
+        }
+        dated.push(Movement {
+            date: row.get(2)?,
+            kind: MovementKind::StageChanged,
+            description,
+        });
+    }
+    for movement in goat_movements(conn, goat_id)?
+        .into_iter()
+        .filter(|m| m.departed_on <= transaction.date)
+    {
+        let mut description = movement.summary();
+        if let Some(permit) = &movement.permit_number {
+            description = format!("{} (permit {})", description, permit);
+        }
+        dated.push(Movement {
+            date: Some(movement.departed_on),
+            kind: MovementKind::Transported,
+            description,
+        });
+    }
+    // Stable, so same-day stage changes come before movements
+    dated.sort_by(|a, b| a.date.cmp(&b.date));
+    movements.extend(dated);
+
+    if let Some(space) = space {
+        movements.push(Movement {
+            date: None,
+            kind: MovementKind::Kept,
+            description: format!("Kept in {}", space),
+        });
+    }
+    let buyer = transaction
+        .sale
+        .as_ref()
+        .and_then(|sale| sale.buyer_name.as_deref());
+    movements.push(Movement {
+        date: Some(transaction.date.clone()),
+        kind: MovementKind::Sold,
+        description: match buyer {
+            Some(buyer) => format!("Sold to {}", buyer),
+            None => "Sold".to_string(),
+        },
+    });
+    Ok(movements)
+}
+
+/// Takes the traceability record of the goat with ID `goat_id` as sold in
+/// `transaction`, which must be stored, and keeps it under a new token.
+///
+/// Called through the database transaction recording the sale. Lab result
+/// IDs are left out, as their reports are not shared.
+pub fn record_sale(
+    conn: &Connection,
+    goat_id: i64,
+    transaction: &Transaction,
+) -> Result<TraceabilityRecord, AppError> {
+    let transaction_id = transaction
+        .id
+        .ok_or_else(|| AppError::InvalidInput("The sale has not been stored".into()))?;
+    let (goat_name, tag_id, breed, gender, date_of_birth): (
+        String,
+        Option<String>,
+        String,
+        String,
+        Option<String>,
+    ) = conn.query_row(
+        "SELECT name, tag_id, breed, gender, date_of_birth FROM goats WHERE id = ?1 AND deleted_at IS NULL",
+        [goat_id],
+        |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        },
+    )?;
+    let settings = load_settings(conn)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT d.name FROM goat_diseases gd JOIN diseases d ON d.id = gd.disease_id \
+         WHERE gd.goat_id = ?1 ORDER BY d.name",
+    )?;
+    let diseases = stmt
+        .query_map([goat_id], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+    let mut health = goat_timeline(conn, goat_id)?;
+    for entry in &mut health {
+        entry.lab_result_id = None;
+    }
+
+    let movements = movement_record(conn, goat_id, transaction)?;
+
+    let sale = transaction.sale.as_ref();
+    let record = TraceabilityRecord {
+        token: generate_key(),
+        goat_name,
+        tag_id,
+        breed,
+        gender,
+        date_of_birth,
+        farm_name: settings.farm_name,
+        farm_gstin: settings.gstin,
+        sold_on: transaction.date.clone(),
+        buyer_name: sale.and_then(|s| s.buyer_name.clone()),
+        invoice_number: sale.and_then(|s| s.invoice_number.clone()),
+        diseases,
+        health,
+        movements,
+    };
+    conn.execute(
+        "INSERT INTO traceability_records (goat_id, transaction_id, token, record) \
+         VALUES (?1, ?2, ?3, ?4)",
+        params![
+            goat_id,
+            transaction_id,
+            record.token,
+            serde_json::to_string(&record)?
+        ],
+    )?;
+
+    debug!(goat_id, transaction_id, "Traceability record taken");
+    Ok(record)
+}
+
+/// Handler for listing traceability records, newest first.
+///
+/// # HTTP Method
+/// - `GET /traceability?goat_name=Rani`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `TraceabilityRecord`, of one
+///   goat's sales if `goat_name` is given. Their tokens are the share
+///   tokens, which name the farm when serving many (see
+///   `crate::tenants::share_token`).
+pub async fn get_records(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    query: web::Query<TraceabilityQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!(goat_name = ?query.goat_name, "GET /traceability called");
+    let conn = db.get_conn()?;
+    let mut stmt = conn.prepare(
+        "SELECT t.record FROM traceability_records t \
+         LEFT JOIN goats g ON g.id = t.goat_id \
+         WHERE ?1 IS NULL OR g.name = ?1 ORDER BY t.id DESC",
+    )?;
+    let records = stmt
+        .query_map([&query.goat_name], |row| row.get::<_, String>(0))?
+        .map(|json| {
+            let mut record: TraceabilityRecord = serde_json::from_str(&json?)?;
+            record.token = share_token(&req, &record.token);
+            Ok(record)
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+
+    info!("Returning {} traceability records", records.len());
+    Ok(HttpResponse::Ok().json(records))
+}
+
+/// Handler for the public share link of a traceability record, for the
+/// buyer. Open to requests without a session, so the token is the only
+/// key to the record. When serving many farms the token starts with the
+/// farm's slug, which `crate::tenants::resolve_tenant` reads.
+///
+/// # HTTP Method
+/// - `GET /public/traceability/{token}`
+///
+/// # Success
+/// - Returns HTTP 200 with the `TraceabilityRecord`.
+///
+/// # Errors
+/// - Returns HTTP 400 if no record has the token.
+pub async fn get_shared_record(
+    db: web::Data<DbPool>,
+    path: web::Path<String>,
+) -> Result<impl Responder, AppError> {
+    debug!("GET /public/traceability/{{token}} called");
+    let conn = db.get_conn()?;
+    let record: Option<String> = conn
+        .query_row(
+            "SELECT record FROM traceability_records WHERE token = ?1",
+            [token_of_share(&path)],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let Some(record) = record else {
+        warn!("Unknown traceability token");
+        return Err(AppError::InvalidInput(
+            "No traceability record found for this link".into(),
+        ));
+    };
+    let mut record: TraceabilityRecord = serde_json::from_str(&record)?;
+    record.token = path.into_inner();
+
+    info!(goat_name = %record.goat_name, "Returning shared traceability record");
+    Ok(HttpResponse::Ok().json(record))
+}
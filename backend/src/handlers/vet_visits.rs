@@ -0,0 +1,489 @@
+//! This module handles vet visits: requesting them, booking them with a vet
+//! on a day the vet is free, and completing them with the vet's findings,
+//! which are recorded as health incidents (see `shared::vet`).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::parse_date;
+use crate::handlers::scale::READ_AT_FORMAT;
+use crate::handlers::settings::farm_today;
+use crate::scheduler::DATE_FORMAT;
+use actix_web::{HttpResponse, Responder, web};
+use chrono::{Days, Utc};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::Deserialize;
+use shared::health::IncidentKind;
+use shared::vet::{
+    VetAvailability, VetVisit, VisitRequest, VisitSchedule, VisitStatus, VisitSummary, is_vet_role,
+};
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, info, warn};
+
+/// Days of bookings listed by `GET /vet-visits/vets` when no `to` is given.
+pub const AVAILABILITY_DAYS: u64 = 30;
+
+/// Query parameters accepted by `GET /vet-visits/vets`.
+///
+/// `from` defaults to today and `to` to `AVAILABILITY_DAYS` after `from`.
+#[derive(Deserialize)]
+pub struct AvailabilityQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Maps a `vet_visits` row joined with its vet's name to a `VetVisit`
+/// without its agenda.
+fn row_to_visit(row: &Row) -> Result<VetVisit, AppError> {
+    let status: String = row.get(1)?;
+    Ok(VetVisit {
+        id: row.get(0)?,
+        status: VisitStatus::from_str(&status)
+            .map_err(|value| AppError::InvalidInput(format!("Unknown visit status '{}'", value)))?,
+        reason: row.get(2)?,
+        agenda: Vec::new(),
+        vet_id: row.get(3)?,
+        vet_name: row.get(4)?,
+        requested_on: row.get(5)?,
+        scheduled_for: row.get(6)?,
+        completed_on: row.get(7)?,
+        summary: row.get(8)?,
+        treated: Vec::new(),
+    })
+}
+
+/// Loads the visit with ID `id`, or every visit when `id` is `None`, most
+/// recent first. Agendas are ordered by goat name.
+pub fn load_visits(conn: &Connection, id: Option<i64>) -> Result<Vec<VetVisit>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT vg.visit_id, g.name, vg.incident_id IS NOT NULL
+         FROM vet_visit_goats vg
+         JOIN goats g ON g.id = vg.goat_id AND g.deleted_at IS NULL
+         WHERE ?1 IS NULL OR vg.visit_id = ?1
+         ORDER BY g.name",
+    )?;
+    let mut agendas: HashMap<i64, (Vec<String>, Vec<String>)> = HashMap::new();
+    let rows = stmt.query_map([id], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, bool>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (visit_id, goat, treated) = row?;
+        let (agenda, treated_goats) = agendas.entry(visit_id).or_default();
+        if treated {
+            treated_goats.push(goat.clone());
+        }
+        agenda.push(goat);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT v.id, v.status, v.reason, v.vet_id, w.name, v.requested_on, v.scheduled_for,
+                v.completed_on, v.summary
+         FROM vet_visits v
+         LEFT JOIN workers w ON w.id = v.vet_id
+         WHERE ?1 IS NULL OR v.id = ?1
+         ORDER BY COALESCE(v.scheduled_for, v.requested_on) DESC, v.id DESC",
+    )?;
+    let mut rows = stmt.query([id])?;
+    let mut visits = Vec::new();
+    while let Some(row) = rows.next()? {
+        let mut visit = row_to_visit(row)?;
+        if let Some((agenda, treated)) = agendas.remove(&visit.id) {
+            visit.agenda = agenda;
+            visit.treated = treated;
+        }
+        visits.push(visit);
+    }
+    Ok(visits)
+}
+
+/// Loads the visit with ID `id`, failing if there is none.
+fn load_visit(conn: &Connection, id: i64) -> Result<VetVisit, AppError> {
+    load_visits(conn, Some(id))?
+        .pop()
+        .ok_or_else(|| AppError::InvalidInput(format!("No vet visit found with ID {}", id)))
+}
+
+/// Returns the name of worker `vet_id`, failing unless their role is a vet's.
+fn vet_name(conn: &Connection, vet_id: i64) -> Result<String, AppError> {
+    let (name, role): (String, String) = conn
+        .query_row(
+            "SELECT name, role FROM workers WHERE id = ?1",
+            [vet_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::InvalidInput(format!("No worker found with ID {}", vet_id)))?;
+    if !is_vet_role(&role) {
+        return Err(AppError::InvalidInput(format!(
+            "{} is a {}, not a vet",
+            name, role
+        )));
+    }
+    Ok(name)
+}
+
+/// Books vet `vet_id` for visit `visit_id` on `day`, failing if the day has
+/// passed or the vet already has another visit that day, and lets the vet
+/// know through an in-app notification.
+fn book_vet(
+    conn: &Connection,
+    visit_id: i64,
+    vet_id: i64,
+    day: &str,
+    reason: &str,
+) -> Result<(), AppError> {
+    let name = vet_name(conn, vet_id)?;
+    if parse_date(day, "scheduled_for")? < farm_today(conn)? {
+        return Err(AppError::InvalidInput(
+            "scheduled_for cannot be in the past".into(),
+        ));
+    }
+    let clash: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM vet_visits
+             WHERE vet_id = ?1 AND scheduled_for = ?2 AND status != 'Requested' AND id != ?3",
+            params![vet_id, day, visit_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(clash) = clash {
+        warn!(vet_id, day, clash, "Vet already booked");
+        return Err(AppError::InvalidInput(format!(
+            "{} is already booked on {}",
+            name, day
+        )));
+    }
+
+    conn.execute(
+        "UPDATE vet_visits SET status = 'Scheduled', vet_id = ?1, scheduled_for = ?2 WHERE id = ?3",
+        params![vet_id, day, visit_id],
+    )?;
+    conn.execute(
+        "INSERT INTO notifications (worker_id, message, created_at) VALUES (?1, ?2, ?3)",
+        params![
+            vet_id,
+            format!("Vet visit booked on {}: {}", day, reason),
+            Utc::now().format(READ_AT_FORMAT).to_string(),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Handler for listing vet visits.
+///
+/// # HTTP Method
+/// - `GET /vet-visits`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `VetVisit`, most recent first.
+pub async fn get_visits(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /vet-visits called");
+    let conn = db.get_conn()?;
+    let visits = load_visits(&conn, None)?;
+
+    info!("Returning {} vet visits", visits.len());
+    Ok(HttpResponse::Ok().json(visits))
+}
+
+/// Handler for requesting a vet visit.
+///
+/// # HTTP Method
+/// - `POST /vet-visits`
+///
+/// # Request
+/// - JSON `VisitRequest`. With `vet_id` and `scheduled_for` the visit is
+///   booked straight away; otherwise it waits as requested.
+///
+/// # Success
+/// - Returns HTTP 201 with the JSON `VetVisit`.
+///
+/// # Errors
+/// - Returns HTTP 400 for an invalid request, an unknown goat on the agenda,
+///   a worker who is not a vet, a day in the past, or a vet already booked
+///   that day.
+pub async fn add_visit(
+    db: web::Data<DbPool>,
+    request: web::Json<VisitRequest>,
+) -> Result<impl Responder, AppError> {
+    debug!(reason = %request.reason, "POST /vet-visits called");
+    request.validate().map_err(AppError::InvalidInput)?;
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+
+    let today = farm_today(&tx)?.format(DATE_FORMAT).to_string();
+    tx.execute(
+        "INSERT INTO vet_visits (status, reason, requested_on) VALUES ('Requested', ?1, ?2)",
+        params![request.reason.trim(), today],
+    )?;
+    let visit_id = tx.last_insert_rowid();
+    let mut seen = HashSet::new();
+    for goat in request.agenda.iter().map(|g| g.trim()) {
+        if goat.is_empty() || !seen.insert(goat) {
+            continue;
+        }
+        let goat_id: i64 = tx
+            .query_row(
+                "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+                [goat],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| AppError::InvalidInput(format!("No goat found with name {}", goat)))?;
+        tx.execute(
+            "INSERT INTO vet_visit_goats (visit_id, goat_id) VALUES (?1, ?2)",
+            params![visit_id, goat_id],
+        )?;
+    }
+    if let (Some(vet_id), Some(day)) = (request.vet_id, &request.scheduled_for) {
+        book_vet(&tx, visit_id, vet_id, day, request.reason.trim())?;
+    }
+    let visit = load_visit(&tx, visit_id)?;
+    tx.commit()?;
+
+    info!(visit_id, goats = visit.agenda.len(), "Vet visit requested");
+    Ok(HttpResponse::Created().json(visit))
+}
+
+/// Handler for the vets and the days they are booked, so visits can be
+/// scheduled on a day the vet is free.
+///
+/// # HTTP Method
+/// - `GET /vet-visits/vets?from=2026-05-01&to=2026-05-31`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `VetAvailability` ordered by vet
+///   name, listing the days each vet has a visit between `from` and `to`.
+///
+/// # Errors
+/// - Returns HTTP 400 for malformed dates or `from` after `to`.
+pub async fn get_vets(
+    db: web::Data<DbPool>,
+    query: web::Query<AvailabilityQuery>,
+) -> Result<impl Responder, AppError> {
+    debug!("GET /vet-visits/vets called");
+    let conn = db.get_conn()?;
+    let from = match &query.from {
+        Some(from) => parse_date(from, "from")?,
+        None => farm_today(&conn)?,
+    };
+    let to = match &query.to {
+        Some(to) => parse_date(to, "to")?,
+        None => from + Days::new(AVAILABILITY_DAYS),
+    };
+    if from > to {
+        return Err(AppError::InvalidInput("from must not be after to".into()));
+    }
+
+    let mut stmt = conn.prepare("SELECT id, name, role FROM workers ORDER BY name")?;
+    let mut vets: Vec<VetAvailability> = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .filter_map(|row| match row {
+            Ok((vet_id, vet_name, role)) if is_vet_role(&role) => Some(Ok(VetAvailability {
+                vet_id,
+                vet_name,
+                booked: Vec::new(),
+            })),
+            Ok(_) => None,
+            Err(e) => Some(Err(e)),
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT vet_id, scheduled_for FROM vet_visits
+         WHERE vet_id IS NOT NULL AND status != 'Requested'
+           AND scheduled_for BETWEEN ?1 AND ?2
+         ORDER BY scheduled_for",
+    )?;
+    let bookings = stmt.query_map(
+        params![
+            from.format(DATE_FORMAT).to_string(),
+            to.format(DATE_FORMAT).to_string()
+        ],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+    )?;
+    for booking in bookings {
+        let (vet_id, day) = booking?;
+        if let Some(vet) = vets.iter_mut().find(|v| v.vet_id == vet_id) {
+            vet.booked.push(day);
+        }
+    }
+
+    info!("Returning availability of {} vets", vets.len());
+    Ok(HttpResponse::Ok().json(vets))
+}
+
+/// Handler for booking a visit with a vet, or moving its booking.
+///
+/// # HTTP Method
+/// - `PUT /vet-visits/{id}/schedule`
+///
+/// # Request
+/// - JSON `VisitSchedule`.
+///
+/// # Success
+/// - Returns HTTP 200 with the scheduled JSON `VetVisit`. The vet is sent an
+///   in-app notification of the booking.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown or completed visit, a worker who is not
+///   a vet, a day in the past, or a vet already booked that day.
+pub async fn schedule_visit(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+    schedule: web::Json<VisitSchedule>,
+) -> Result<impl Responder, AppError> {
+    let visit_id = path.into_inner();
+    debug!(
+        visit_id,
+        vet_id = schedule.vet_id,
+        "PUT /vet-visits/{{id}}/schedule called"
+    );
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+
+    let visit = load_visit(&tx, visit_id)?;
+    if visit.status == VisitStatus::Completed {
+        return Err(AppError::InvalidInput(format!(
+            "Vet visit #{} is already completed",
+            visit_id
+        )));
+    }
+    book_vet(
+        &tx,
+        visit_id,
+        schedule.vet_id,
+        &schedule.scheduled_for,
+        &visit.reason,
+    )?;
+    let visit = load_visit(&tx, visit_id)?;
+    tx.commit()?;
+
+    info!(visit_id, day = %schedule.scheduled_for, "Vet visit scheduled");
+    Ok(HttpResponse::Ok().json(visit))
+}
+
+/// Handler for completing a visit with the vet's summary.
+///
+/// # HTTP Method
+/// - `PUT /vet-visits/{id}/complete`
+///
+/// # Request
+/// - JSON `VisitSummary`. Each finding must name a goat on the visit's
+///   agenda, at most once.
+///
+/// # Success
+/// - Returns HTTP 200 with the completed JSON `VetVisit`. Each finding is
+///   recorded as a health incident observed on the day of the visit, in the
+///   goat's current pen, with the treatment in its notes.
+///
+/// # Errors
+/// - Returns HTTP 400 for an unknown visit, one that is not scheduled or
+///   whose day has not come, an invalid summary, or a finding for a goat
+///   not on the agenda.
+pub async fn complete_visit(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+    summary: web::Json<VisitSummary>,
+) -> Result<impl Responder, AppError> {
+    let visit_id = path.into_inner();
+    debug!(visit_id, "PUT /vet-visits/{{id}}/complete called");
+    summary.validate().map_err(AppError::InvalidInput)?;
+    let mut conn = db.get_conn()?;
+    let tx = conn.transaction()?;
+
+    let visit = load_visit(&tx, visit_id)?;
+    let day = match (visit.status, &visit.scheduled_for) {
+        (VisitStatus::Scheduled, Some(day)) => day.clone(),
+        (VisitStatus::Completed, _) => {
+            return Err(AppError::InvalidInput(format!(
+                "Vet visit #{} is already completed",
+                visit_id
+            )));
+        }
+        _ => {
+            return Err(AppError::InvalidInput(format!(
+                "Vet visit #{} has not been scheduled",
+                visit_id
+            )));
+        }
+    };
+    let today = farm_today(&tx)?;
+    if parse_date(&day, "scheduled_for")? > today {
+        return Err(AppError::InvalidInput(format!(
+            "Vet visit #{} is not due until {}",
+            visit_id, day
+        )));
+    }
+    let vet = visit.vet_name.as_deref().unwrap_or("the vet");
+
+    let mut seen = HashSet::new();
+    for finding in &summary.findings {
+        let goat = finding.goat_name.trim();
+        if !visit.agenda.iter().any(|g| g == goat) {
+            return Err(AppError::InvalidInput(format!(
+                "{} is not on the agenda of vet visit #{}",
+                goat, visit_id
+            )));
+        }
+        if !seen.insert(goat) {
+            return Err(AppError::InvalidInput(format!(
+                "{} has more than one finding",
+                goat
+            )));
+        }
+        let (goat_id, space_id): (i64, Option<i64>) = tx.query_row(
+            "SELECT id, space_id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+            [goat],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let notes = match finding.treatment.as_deref().map(str::trim) {
+            Some(treatment) if !treatment.is_empty() => format!(
+                "Treatment: {}\nVet visit #{} with {}",
+                treatment, visit_id, vet
+            ),
+            _ => format!("Vet visit #{} with {}", visit_id, vet),
+        };
+        tx.execute(
+            "INSERT INTO health_incidents (goat_id, space_id, kind, condition, observed_on, notes) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                goat_id,
+                space_id,
+                IncidentKind::to_str(&finding.kind),
+                finding.condition.trim(),
+                day,
+                notes,
+            ],
+        )?;
+        tx.execute(
+            "UPDATE vet_visit_goats SET incident_id = ?1 WHERE visit_id = ?2 AND goat_id = ?3",
+            params![tx.last_insert_rowid(), visit_id, goat_id],
+        )?;
+    }
+    tx.execute(
+        "UPDATE vet_visits SET status = 'Completed', completed_on = ?1, summary = ?2 WHERE id = ?3",
+        params![
+            today.format(DATE_FORMAT).to_string(),
+            summary.summary.trim(),
+            visit_id
+        ],
+    )?;
+    let visit = load_visit(&tx, visit_id)?;
+    tx.commit()?;
+
+    info!(
+        visit_id,
+        incidents = summary.findings.len(),
+        "Vet visit completed"
+    );
+    Ok(HttpResponse::Ok().json(visit))
+}
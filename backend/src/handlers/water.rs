@@ -0,0 +1,80 @@
+//! This module handles pen water use and trough cleaning (see
+//! `crate::water` for how drops in intake are found).
+
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::handlers::settings::farm_today;
+use crate::scheduler::DATE_FORMAT;
+use crate::water::load_pen_water;
+use actix_web::{HttpResponse, Responder, web};
+use rusqlite::{OptionalExtension, params};
+use shared::water::TROUGH_CLEANING_TITLE;
+use tracing::{debug, info, warn};
+
+/// Handler for the water use and trough upkeep of every metered pen.
+///
+/// # HTTP Method
+/// - `GET /water`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `PenWater` ordered by pen name:
+///   litres drawn per day over the last fortnight, yesterday's drop against
+///   the baseline if any, and when the troughs were last cleaned.
+pub async fn get_water(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /water called");
+    let conn = db.get_conn()?;
+    let pens = load_pen_water(&conn, farm_today(&conn)?)?;
+
+    info!("Returning water use of {} pens", pens.len());
+    Ok(HttpResponse::Ok().json(pens))
+}
+
+/// Handler for recording that a pen's troughs were cleaned.
+///
+/// # HTTP Method
+/// - `PUT /water/{space_id}/cleaned`
+///
+/// # Success
+/// - Returns HTTP 200 once the pen's pending cleaning tasks are done, or a
+///   done cleaning task is recorded if none was pending. The next cleaning
+///   falls due `TROUGH_CLEANING_DAYS` later.
+///
+/// # Errors
+/// - Returns HTTP 400 if the space does not exist.
+pub async fn mark_cleaned(
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+) -> Result<impl Responder, AppError> {
+    let space_id = path.into_inner();
+    debug!(space_id, "PUT /water/{{space_id}}/cleaned called");
+    let conn = db.get_conn()?;
+    let name: Option<String> = conn
+        .query_row("SELECT name FROM spaces WHERE id = ?1", [space_id], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    let Some(name) = name else {
+        warn!(space_id, "Space not found for trough cleaning");
+        return Err(AppError::InvalidInput(format!(
+            "No space found with ID {}",
+            space_id
+        )));
+    };
+
+    let completed = conn.execute(
+        "UPDATE tasks SET status = 'Done', completed_at = CURRENT_TIMESTAMP \
+         WHERE space_id = ?1 AND title = ?2 AND status = 'Pending'",
+        params![space_id, TROUGH_CLEANING_TITLE],
+    )?;
+    if completed == 0 {
+        let today = farm_today(&conn)?.format(DATE_FORMAT).to_string();
+        conn.execute(
+            "INSERT INTO tasks (title, space_id, due_date, status, completed_at) \
+             VALUES (?1, ?2, ?3, 'Done', CURRENT_TIMESTAMP)",
+            params![TROUGH_CLEANING_TITLE, space_id, today],
+        )?;
+    }
+
+    info!(space_id, pen = %name, "Troughs cleaned");
+    Ok(HttpResponse::Ok().body("Troughs cleaned"))
+}
@@ -0,0 +1,245 @@
+//! This module handles the farm's workers, who can be mentioned in goat
+//! notes, how each wants to be notified (see `shared::notifications`), and
+//! the PINs they sign in with.
+//!
+//! Only the owner gives workers their role, which decides what they may do
+//! (see `crate::permissions`), and their contact, which signing in with an
+//! OAuth provider goes by (see `crate::auth::link_identity`).
+
+use crate::auth::hash_secret;
+use crate::db::DbPool;
+use crate::errors::{AppError, ParseEnumError};
+use crate::handlers::sessions::current_session;
+use crate::permissions::session_permissions;
+use actix_web::{HttpRequest, HttpResponse, Responder, web};
+use rusqlite::{Connection, OptionalExtension, params};
+use shared::notifications::{NotifyChannel, Worker};
+use shared::sessions::{NewPin, validate_pin};
+use tracing::{debug, info, warn};
+
+/// Loads every worker, ordered by name.
+pub fn load_workers(conn: &Connection) -> Result<Vec<Worker>, AppError> {
+    let mut stmt =
+        conn.prepare("SELECT id, name, role, contact, notify_by FROM workers ORDER BY name")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    rows.into_iter()
+        .map(|(id, name, role, contact, notify_by)| {
+            let notify_by = NotifyChannel::from_str(&notify_by)
+                .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "NotifyChannel")))?;
+            Ok(Worker {
+                id: Some(id),
+                name,
+                role,
+                contact,
+                notify_by,
+            })
+        })
+        .collect()
+}
+
+/// Refuses `worker` if it is invalid or another worker (other than
+/// `except`) already has its name, since mentions go by name.
+fn check_worker(conn: &Connection, worker: &Worker, except: Option<i64>) -> Result<(), AppError> {
+    worker.validate().map_err(AppError::InvalidInput)?;
+    let taken: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM workers WHERE name = ?1 COLLATE NOCASE AND id IS NOT ?2)",
+        params![worker.name.trim(), except],
+        |row| row.get(0),
+    )?;
+    if taken {
+        return Err(AppError::InvalidInput(format!(
+            "A worker named {} already exists",
+            worker.name.trim()
+        )));
+    }
+    Ok(())
+}
+
+/// `value` trimmed, or `None` if blank.
+fn non_blank(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// Refuses to give a worker `role` and `contact` unless `req` was made in
+/// the owner's session; `current` are the worker's role and contact so
+/// far, `None` for a new worker.
+fn check_owner_fields(
+    conn: &Connection,
+    req: &HttpRequest,
+    worker: &Worker,
+    current: (Option<&str>, Option<&str>),
+) -> Result<(), AppError> {
+    let changes = (
+        non_blank(worker.role.as_deref()),
+        non_blank(worker.contact.as_deref()),
+    ) != (non_blank(current.0), non_blank(current.1));
+    if changes && !session_permissions(conn, current_session(req))?.is_owner() {
+        warn!(name = %worker.name, "Refused role or contact change by a worker");
+        return Err(AppError::Forbidden(
+            "Only the owner may set workers' roles and contacts".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Handler for listing the farm's workers.
+///
+/// # HTTP Method
+/// - `GET /workers`
+///
+/// # Success
+/// - Returns HTTP 200 with a JSON array of `Worker` ordered by name.
+pub async fn get_workers(db: web::Data<DbPool>) -> Result<impl Responder, AppError> {
+    debug!("GET /workers called");
+    let conn = db.get_conn()?;
+    let workers = load_workers(&conn)?;
+
+    info!("Returning {} workers", workers.len());
+    Ok(HttpResponse::Ok().json(workers))
+}
+
+/// Handler for adding a worker.
+///
+/// # HTTP Method
+/// - `POST /workers`
+///
+/// # Success
+/// - Returns HTTP 201 on successful insertion.
+///
+/// # Errors
+/// - Returns HTTP 400 if the name is empty, contains `@` or is taken, or
+///   the contact does not fit the notification channel.
+/// - Returns HTTP 403 if the worker is given a role or contact outside the
+///   owner's session.
+pub async fn add_worker(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    worker: web::Json<Worker>,
+) -> Result<impl Responder, AppError> {
+    debug!(name = %worker.name, "POST /workers called");
+    let conn = db.get_conn()?;
+    check_worker(&conn, &worker, None)?;
+    check_owner_fields(&conn, &req, &worker, (None, None))?;
+    conn.execute(
+        "INSERT INTO workers (name, role, contact, notify_by) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            worker.name.trim(),
+            non_blank(worker.role.as_deref()),
+            non_blank(worker.contact.as_deref()),
+            NotifyChannel::to_str(&worker.notify_by)
+        ],
+    )?;
+
+    info!(worker_id = conn.last_insert_rowid(), "Worker added");
+    Ok(HttpResponse::Created().body("Worker added"))
+}
+
+/// Handler for updating a worker's details and notification channel.
+///
+/// # HTTP Method
+/// - `PUT /workers/{id}`
+///
+/// # Success
+/// - Returns HTTP 200 with a confirmation message.
+///
+/// # Errors
+/// - Returns HTTP 400 for the same reasons as `POST /workers`, or if the
+///   worker does not exist.
+/// - Returns HTTP 403 if the role or contact changes outside the owner's
+///   session.
+pub async fn update_worker(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+    worker: web::Json<Worker>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(worker_id = id, "PUT /workers/{{id}} called");
+    let conn = db.get_conn()?;
+    check_worker(&conn, &worker, Some(id))?;
+    let current: Option<(Option<String>, Option<String>)> = conn
+        .query_row(
+            "SELECT role, contact FROM workers WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((role, contact)) = current else {
+        warn!(worker_id = id, "Worker not found for update");
+        return Err(AppError::InvalidInput(format!(
+            "No worker found with ID {}",
+            id
+        )));
+    };
+    check_owner_fields(&conn, &req, &worker, (role.as_deref(), contact.as_deref()))?;
+    conn.execute(
+        "UPDATE workers SET name = ?1, role = ?2, contact = ?3, notify_by = ?4 WHERE id = ?5",
+        params![
+            worker.name.trim(),
+            non_blank(worker.role.as_deref()),
+            non_blank(worker.contact.as_deref()),
+            NotifyChannel::to_str(&worker.notify_by),
+            id
+        ],
+    )?;
+
+    info!(worker_id = id, "Worker updated");
+    Ok(HttpResponse::Ok().body("Worker updated"))
+}
+
+/// Handler for setting the PIN a worker signs in with.
+///
+/// # HTTP Method
+/// - `PUT /workers/{id}/pin`
+///
+/// # Request
+/// - JSON `NewPin`.
+///
+/// # Success
+/// - Returns HTTP 200 once the PIN is set.
+///
+/// # Errors
+/// - Returns HTTP 400 if the PIN is not 4 to 8 digits or the worker does
+///   not exist.
+/// - Returns HTTP 403 unless the request was made in the owner's session.
+pub async fn set_pin(
+    req: HttpRequest,
+    db: web::Data<DbPool>,
+    path: web::Path<i64>,
+    pin: web::Json<NewPin>,
+) -> Result<impl Responder, AppError> {
+    let id = path.into_inner();
+    debug!(worker_id = id, "PUT /workers/{{id}}/pin called");
+    let conn = db.get_conn()?;
+    if !session_permissions(&conn, current_session(&req))?.is_owner() {
+        warn!(worker_id = id, "Refused PIN change by a worker");
+        return Err(AppError::Forbidden(
+            "Only the owner may set workers' PINs".into(),
+        ));
+    }
+    validate_pin(&pin.pin).map_err(AppError::InvalidInput)?;
+    let affected = conn.execute(
+        "UPDATE workers SET pin_hash = ?1 WHERE id = ?2",
+        params![hash_secret(&pin.pin)?, id],
+    )?;
+    if affected == 0 {
+        warn!(worker_id = id, "Worker not found for PIN");
+        return Err(AppError::InvalidInput(format!(
+            "No worker found with ID {}",
+            id
+        )));
+    }
+
+    info!(worker_id = id, "Worker PIN set");
+    Ok(HttpResponse::Ok().body("PIN set"))
+}
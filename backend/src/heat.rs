@@ -0,0 +1,215 @@
+//! Heat detection from activity tag readings.
+//!
+//! Readings are summed per day and each day is compared with the doe's
+//! average over the preceding week. A day at least `SPIKE_RATIO` times the
+//! baseline is a spike; consecutive spike days form one heat. Heats one
+//! estrous cycle apart confirm each other, and the latest heat is projected
+//! forward by the cycle to predict the next (see `shared::heat`).
+
+use crate::breeding::GESTATION_DAYS;
+use crate::errors::AppError;
+use crate::handlers::scale::READ_AT_FORMAT;
+use crate::handlers::settings::farm_timezone;
+use crate::scheduler::DATE_FORMAT;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use rusqlite::{Connection, params};
+use shared::heat::{ActivitySpike, ESTROUS_CYCLE_DAYS, HeatPrediction};
+use shared::time::{day_start, local_date};
+use std::collections::BTreeMap;
+use tracing::{debug, trace};
+
+/// Days before a spike that form its baseline.
+pub const BASELINE_DAYS: i64 = 7;
+
+/// Fewest baseline days needed before a day can be judged.
+pub const MIN_BASELINE_DAYS: usize = 3;
+
+/// Activity relative to baseline at which a day counts as a spike.
+pub const SPIKE_RATIO: f64 = 1.5;
+
+/// Activity relative to baseline that gives full confidence.
+pub const FULL_CONFIDENCE_RATIO: f64 = 3.0;
+
+/// Confidence added to a heat that falls one or more cycles after another.
+pub const CYCLE_BONUS: f64 = 0.25;
+
+/// Days either side of a whole number of cycles still counted as "on cycle".
+pub const CYCLE_TOLERANCE_DAYS: i64 = 3;
+
+/// Factor applied to the prediction's confidence per cycle projected.
+pub const CYCLE_DECAY: f64 = 0.8;
+
+/// Most past heats returned per doe.
+const MAX_RECENT_HEATS: usize = 5;
+
+/// Finds heats in one doe's daily activity totals, oldest first.
+///
+/// `daily` must be sorted by date. Spike days are left out of later
+/// baselines, so one heat does not hide the next.
+pub fn detect_heats(daily: &[(NaiveDate, f64)]) -> Vec<(NaiveDate, ActivitySpike)> {
+    let mut spikes: Vec<(NaiveDate, f64, f64, f64)> = Vec::new();
+    for (i, (day, activity)) in daily.iter().enumerate() {
+        let baseline: Vec<f64> = daily[..i]
+            .iter()
+            .filter(|(d, _)| (*day - *d).num_days() <= BASELINE_DAYS)
+            .filter(|(d, _)| !spikes.iter().any(|s| s.0 == *d))
+            .map(|(_, a)| *a)
+            .collect();
+        if baseline.len() < MIN_BASELINE_DAYS {
+            continue;
+        }
+        let mean = baseline.iter().sum::<f64>() / baseline.len() as f64;
+        if mean <= 0.0 || *activity < mean * SPIKE_RATIO {
+            continue;
+        }
+        let confidence = ((activity / mean - 1.0) / (FULL_CONFIDENCE_RATIO - 1.0)).clamp(0.0, 1.0);
+        trace!(%day, activity, mean, confidence, "Activity spike");
+        spikes.push((*day, *activity, mean, confidence));
+    }
+
+    // A heat lasts a day or two; keep the strongest day of each run
+    let mut heats: Vec<(NaiveDate, f64, f64, f64)> = Vec::new();
+    let mut last_day: Option<NaiveDate> = None;
+    for spike in spikes {
+        match heats.last_mut() {
+            Some(heat) if last_day.is_some_and(|d| (spike.0 - d).num_days() <= 1) => {
+                if spike.3 > heat.3 {
+                    *heat = spike;
+                }
+            }
+            _ => heats.push(spike),
+        }
+        last_day = Some(spike.0);
+    }
+
+    let dates: Vec<NaiveDate> = heats.iter().map(|h| h.0).collect();
+    heats
+        .into_iter()
+        .map(|(day, activity, baseline, confidence)| {
+            let on_cycle = dates.iter().any(|earlier| {
+                let gap = (day - *earlier).num_days();
+                let off = gap % ESTROUS_CYCLE_DAYS;
+                gap >= ESTROUS_CYCLE_DAYS - CYCLE_TOLERANCE_DAYS
+                    && (off <= CYCLE_TOLERANCE_DAYS
+                        || off >= ESTROUS_CYCLE_DAYS - CYCLE_TOLERANCE_DAYS)
+            });
+            let confidence = if on_cycle {
+                (confidence + CYCLE_BONUS).min(1.0)
+            } else {
+                confidence
+            };
+            (
+                day,
+                ActivitySpike {
+                    date: day.format(DATE_FORMAT).to_string(),
+                    activity,
+                    baseline,
+                    confidence,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Predicts the next heat of `doe_name` as of `today` from her daily activity.
+pub fn predict_heat(
+    doe_name: &str,
+    daily: &[(NaiveDate, f64)],
+    today: NaiveDate,
+) -> HeatPrediction {
+    let heats = detect_heats(daily);
+    let Some((last, spike)) = heats.last() else {
+        return HeatPrediction {
+            doe_name: doe_name.to_string(),
+            in_heat: false,
+            last_heat: None,
+            next_heat: None,
+            confidence: 0.0,
+            recent_spikes: Vec::new(),
+        };
+    };
+
+    let in_heat = (today - *last).num_days() <= 1;
+    let mut next = *last + Days::new(ESTROUS_CYCLE_DAYS as u64);
+    let mut cycles = 1;
+    while next < today {
+        next = next + Days::new(ESTROUS_CYCLE_DAYS as u64);
+        cycles += 1;
+    }
+    let confidence = spike.confidence * CYCLE_DECAY.powi(cycles - 1);
+    debug!(doe = doe_name, %next, confidence, "Predicted next heat");
+
+    let skip = heats.len().saturating_sub(MAX_RECENT_HEATS);
+    HeatPrediction {
+        doe_name: doe_name.to_string(),
+        in_heat,
+        last_heat: Some(spike.date.clone()),
+        next_heat: Some(next.format(DATE_FORMAT).to_string()),
+        confidence,
+        recent_spikes: heats.into_iter().skip(skip).map(|(_, s)| s).collect(),
+    }
+}
+
+/// Predicts heats for every doe wearing an activity tag, does in heat first
+/// and then by next heat.
+///
+/// Does bred within `GESTATION_DAYS` are presumed pregnant and left out, as
+/// are spayed does.
+pub fn load_predictions(
+    conn: &Connection,
+    today: NaiveDate,
+) -> Result<Vec<HeatPrediction>, AppError> {
+    let bred_since = (today - Days::new(GESTATION_DAYS as u64))
+        .format(DATE_FORMAT)
+        .to_string();
+    // Readings are stored in UTC and summed per day in the farm's time zone
+    let tz = farm_timezone(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT g.name, r.read_at, r.value \
+         FROM sensor_readings r \
+             JOIN sensors s ON s.id = r.sensor_id \
+             JOIN goats g ON g.id = s.goat_id AND g.deleted_at IS NULL \
+         WHERE s.sensor_type = 'Activity' AND g.gender = 'Female' \
+             AND g.neutered_on IS NULL \
+             AND (g.last_bred IS NULL OR g.last_bred < ?1) \
+             AND r.read_at < ?2 \
+         ORDER BY g.name, r.read_at",
+    )?;
+    let tomorrow = day_start(today + Days::new(1), tz);
+    let rows = stmt
+        .query_map(
+            params![bred_since, tomorrow.format(READ_AT_FORMAT).to_string()],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, DateTime<Utc>>(1)?,
+                    row.get::<_, f64>(2)?,
+                ))
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut daily: BTreeMap<String, Vec<(NaiveDate, f64)>> = BTreeMap::new();
+    for (name, read_at, activity) in rows {
+        let day = local_date(&read_at, tz);
+        let days = daily.entry(name).or_default();
+        match days.last_mut() {
+            Some((last, total)) if *last == day => *total += activity,
+            _ => days.push((day, activity)),
+        }
+    }
+    let mut predictions: Vec<HeatPrediction> = daily
+        .iter()
+        .map(|(name, days)| predict_heat(name, days, today))
+        .collect();
+    predictions.sort_by(|a, b| {
+        b.in_heat
+            .cmp(&a.in_heat)
+            .then_with(|| match (&a.next_heat, &b.next_heat) {
+                (Some(a), Some(b)) => a.cmp(b),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            })
+    });
+    debug!("Predicted heats for {} does", predictions.len());
+    Ok(predictions)
+}
@@ -0,0 +1,32 @@
+//! Helpers for HTTP conditional requests.
+//!
+//! List endpoints tag their JSON body with a strong ETag derived from its
+//! bytes; clients echo it back in `If-None-Match` and get `304 Not Modified`
+//! when nothing changed, saving the re-download of large lists.
+
+use actix_web::HttpRequest;
+use actix_web::http::header;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Computes a quoted strong ETag for a response body.
+///
+/// `DefaultHasher::new()` uses fixed keys, so the same body always yields
+/// the same tag for a given server build.
+pub fn etag_for(body: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+/// Returns true if the request's `If-None-Match` header matches `etag`.
+///
+/// Handles comma-separated lists, the `*` wildcard and weak (`W/`) tags.
+pub fn if_none_match(req: &HttpRequest, etag: &str) -> bool {
+    req.headers()
+        .get_all(header::IF_NONE_MATCH)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
@@ -0,0 +1,419 @@
+//! Background job queue for long-running work.
+//!
+//! Handlers that would keep a client waiting, such as building a large census
+//! report, importing a big spreadsheet or sending email and SMS, store a
+//! `JobRequest` in the `jobs` table and answer at once with the queued `Job`.
+//! A `JobRunner` task per database picks queued jobs up in order, runs them
+//! on a worker thread and records their progress, which clients poll at
+//! `GET /jobs/{id}`. Report files are kept with the job for download.
+//!
+//! Jobs live in the database, so a job interrupted by a restart runs again
+//! when the runner starts. A failed attempt is retried with a growing delay
+//! until the request's attempts are used up; notifications retry this way
+//! when the message gateway is unreachable, resending only what was not yet
+//! delivered.
+//!
+//! The server binary registers a `JobQueue` as `web::Data<JobQueue>`, which
+//! wakes the runners when work is queued. Without one, notifications are
+//! sent while the request waits, as before.
+
+use crate::db::DbPool;
+use crate::errors::{AppError, ParseEnumError};
+use crate::events::{Actor, EventLog};
+use crate::handlers::goats::check_batch;
+use crate::handlers::reports::{ReportFormat, census_file, census_report};
+use crate::messaging::{MessageGateway, OutgoingMessage};
+use crate::repository::{GoatRepository, Goats, SqliteGoatRepository};
+use crate::retention::purge_expired;
+use actix_web::{rt, web};
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use serde::{Deserialize, Serialize};
+use shared::NewGoat;
+use shared::jobs::{Job, JobKind, JobStatus};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{debug, error, info, warn};
+
+/// How often runners look for due jobs, such as retries, without being woken.
+pub const JOB_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Delay before the first retry of a failed job; it doubles with every
+/// further attempt.
+pub const RETRY_DELAY_SECS: i64 = 30;
+
+/// Attempts a notification job gets before it is marked failed.
+pub const NOTIFICATION_ATTEMPTS: u32 = 5;
+
+/// Goats an import job adds per transaction, and so per progress step.
+pub const IMPORT_CHUNK: usize = 50;
+
+/// Work to run in the background.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum JobRequest {
+    /// Builds a census report file for download.
+    CensusReport {
+        key: String,
+        as_of: Option<String>,
+        format: ReportFormat,
+    },
+    /// Adds goats that already passed `handlers::goats::check_batch`.
+    GoatImport { goats: Vec<NewGoat> },
+    /// Sends emails and SMS through the registered `MessageGateway`.
+    SendMessages { messages: Vec<OutgoingMessage> },
+    /// Purges what is past the farm's retention (see `crate::retention`).
+    Cleanup,
+}
+
+impl JobRequest {
+    pub fn kind(&self) -> JobKind {
+        match self {
+            JobRequest::CensusReport { .. } => JobKind::Report,
+            JobRequest::GoatImport { .. } => JobKind::Import,
+            JobRequest::SendMessages { .. } => JobKind::Notification,
+            JobRequest::Cleanup => JobKind::Cleanup,
+        }
+    }
+
+    /// Short description shown in job lists.
+    pub fn description(&self) -> String {
+        match self {
+            JobRequest::CensusReport { key, .. } => format!("Census report {}", key),
+            JobRequest::GoatImport { goats } => format!("Import {} goats", goats.len()),
+            JobRequest::SendMessages { messages } => {
+                format!("Send {} notifications", messages.len())
+            }
+            JobRequest::Cleanup => "Purge expired trash and log entries".to_string(),
+        }
+    }
+
+    /// How many times the job may run before it is marked failed.
+    pub fn max_attempts(&self) -> u32 {
+        match self {
+            JobRequest::SendMessages { .. } => NOTIFICATION_ATTEMPTS,
+            _ => 1,
+        }
+    }
+}
+
+/// A finished job's downloadable result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobFile {
+    pub content_type: String,
+    pub name: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Wakes the job runners when work is queued; see the module docs.
+#[derive(Clone, Default)]
+pub struct JobQueue {
+    wake: Arc<Notify>,
+}
+
+impl JobQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tells the runners to look for queued jobs now.
+    pub fn notify(&self) {
+        self.wake.notify_waiters();
+    }
+}
+
+/// Runs the jobs queued in a database.
+#[derive(Clone)]
+pub struct JobRunner {
+    queue: JobQueue,
+    gateway: Option<Arc<dyn MessageGateway>>,
+    events: EventLog,
+    poll: Duration,
+}
+
+impl JobRunner {
+    /// A runner woken by `queue`, without a message gateway.
+    pub fn new(queue: JobQueue) -> Self {
+        JobRunner {
+            queue,
+            gateway: None,
+            events: EventLog::disabled(),
+            poll: JOB_POLL_INTERVAL,
+        }
+    }
+
+    /// Sends notification jobs through `gateway`.
+    pub fn with_gateway(mut self, gateway: Arc<dyn MessageGateway>) -> Self {
+        self.gateway = Some(gateway);
+        self
+    }
+
+    /// Records the goats imported and purged by jobs in `events` (see
+    /// `crate::events`).
+    pub fn with_event_log(mut self, events: EventLog) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Claims the oldest due job in `db`, runs it, and returns it as it
+    /// ended up; `None` if no job was due.
+    pub fn run_next(&self, db: &DbPool) -> Result<Option<Job>, AppError> {
+        let conn = db.get_conn()?;
+        let claimed: Option<(i64, String, u32, u32)> = conn
+            .query_row(
+                "UPDATE jobs SET status = 'running', attempts = attempts + 1, \
+                 updated_at = CURRENT_TIMESTAMP \
+                 WHERE id = (SELECT id FROM jobs WHERE status = 'queued' \
+                             AND run_after <= CURRENT_TIMESTAMP ORDER BY id LIMIT 1) \
+                 RETURNING id, payload, attempts, max_attempts",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .optional()?;
+        let Some((id, payload, attempts, max_attempts)) = claimed else {
+            return Ok(None);
+        };
+        debug!(job_id = id, attempts, "Running job");
+
+        let result = serde_json::from_str::<JobRequest>(&payload)
+            .map_err(AppError::from)
+            .and_then(|request| self.execute(db, id, request));
+        match result {
+            Ok(file) => {
+                conn.execute(
+                    "UPDATE jobs SET status = 'succeeded', error = NULL, result = ?1, \
+                     result_type = ?2, result_name = ?3, updated_at = CURRENT_TIMESTAMP \
+                     WHERE id = ?4",
+                    params![
+                        file.as_ref().map(|f| &f.bytes),
+                        file.as_ref().map(|f| &f.content_type),
+                        file.as_ref().map(|f| &f.name),
+                        id
+                    ],
+                )?;
+                info!(job_id = id, "Job succeeded");
+            }
+            Err(e) if attempts < max_attempts => {
+                let delay = RETRY_DELAY_SECS << (attempts - 1).min(10);
+                conn.execute(
+                    "UPDATE jobs SET status = 'queued', error = ?1, \
+                     run_after = datetime('now', ?2), updated_at = CURRENT_TIMESTAMP \
+                     WHERE id = ?3",
+                    params![e.to_string(), format!("+{} seconds", delay), id],
+                )?;
+                warn!(
+                    job_id = id,
+                    attempts, delay, "Job failed, will retry: {}", e
+                );
+            }
+            Err(e) => {
+                conn.execute(
+                    "UPDATE jobs SET status = 'failed', error = ?1, \
+                     updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+                    params![e.to_string(), id],
+                )?;
+                error!(job_id = id, attempts, "Job failed: {}", e);
+            }
+        }
+        load_job(&conn, id)
+    }
+
+    /// Does the work of job `id`.
+    fn execute(
+        &self,
+        db: &DbPool,
+        id: i64,
+        request: JobRequest,
+    ) -> Result<Option<JobFile>, AppError> {
+        match request {
+            JobRequest::CensusReport { key, as_of, format } => {
+                let conn = db.get_conn()?;
+                let report = census_report(&conn, &key, as_of.as_deref())?;
+                let (content_type, name, bytes) = census_file(&report, format)?;
+                set_progress(&conn, id, 1, 1)?;
+                Ok(Some(JobFile {
+                    content_type: content_type.to_string(),
+                    name,
+                    bytes,
+                }))
+            }
+            JobRequest::GoatImport { goats } => {
+                let repo = SqliteGoatRepository::new(db.clone())
+                    .with_event_log(self.events.by(Actor::Job));
+                // Names may have been taken since the job was queued
+                check_batch(&Goats(Arc::new(repo.clone())), &goats)?;
+                let conn = db.get_conn()?;
+                let mut done = 0;
+                set_progress(&conn, id, done, goats.len())?;
+                for chunk in goats.chunks(IMPORT_CHUNK) {
+                    done += repo.insert_batch(chunk)?.len();
+                    set_progress(&conn, id, done, goats.len())?;
+                }
+                Ok(None)
+            }
+            JobRequest::SendMessages { messages } => {
+                let gateway = self
+                    .gateway
+                    .as_ref()
+                    .ok_or_else(|| AppError::Upstream("No message gateway configured".into()))?;
+                let conn = db.get_conn()?;
+                set_progress(&conn, id, 0, messages.len())?;
+                for (sent, message) in messages.iter().enumerate() {
+                    if let Err(e) = gateway.send(message) {
+                        // A retry only sends what was not delivered yet
+                        let rest = JobRequest::SendMessages {
+                            messages: messages[sent..].to_vec(),
+                        };
+                        conn.execute(
+                            "UPDATE jobs SET payload = ?1 WHERE id = ?2",
+                            params![serde_json::to_string(&rest)?, id],
+                        )?;
+                        return Err(e);
+                    }
+                    set_progress(&conn, id, sent + 1, messages.len())?;
+                }
+                Ok(None)
+            }
+            JobRequest::Cleanup => {
+                let mut conn = db.get_conn()?;
+                let purged = purge_expired(&mut conn, &self.events.by(Actor::Job))?;
+                let total = purged.goats + purged.events;
+                set_progress(&conn, id, total, total)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Runs the jobs of `db` in the background until the server stops.
+    pub fn spawn(&self, db: DbPool) {
+        let runner = self.clone();
+        rt::spawn(async move {
+            match requeue_interrupted(&db) {
+                Ok(0) => {}
+                Ok(count) => info!(count, "Requeued interrupted jobs"),
+                Err(e) => error!("Interrupted jobs could not be requeued: {}", e),
+            }
+            let mut ticker = rt::time::interval(runner.poll);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = runner.queue.wake.notified() => {}
+                }
+                loop {
+                    let (job_runner, db) = (runner.clone(), db.clone());
+                    match web::block(move || job_runner.run_next(&db)).await {
+                        Ok(Ok(Some(_))) => {}
+                        Ok(Ok(None)) => break,
+                        Ok(Err(e)) => {
+                            error!("Job could not run: {}", e);
+                            break;
+                        }
+                        Err(e) => {
+                            error!("Job could not run: {}", e);
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Columns selected by `row_to_job`.
+const JOB_SELECT: &str = "SELECT id, kind, status, description, done, total, attempts, error, \
+     result_name, created_at, updated_at FROM jobs";
+
+/// Maps a row selected with `JOB_SELECT` to a `Job`.
+fn row_to_job(row: &Row) -> Result<Job, AppError> {
+    let kind: String = row.get(1)?;
+    let status: String = row.get(2)?;
+    Ok(Job {
+        id: row.get(0)?,
+        kind: JobKind::from_str(&kind)
+            .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "JobKind")))?,
+        status: JobStatus::from_str(&status)
+            .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "JobStatus")))?,
+        description: row.get(3)?,
+        done: row.get(4)?,
+        total: row.get(5)?,
+        attempts: row.get(6)?,
+        error: row.get(7)?,
+        result_name: row.get(8)?,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+/// Queues `request` and returns the queued job. Runners pick it up once
+/// woken through the `JobQueue` or at their next poll.
+pub fn enqueue(conn: &Connection, request: &JobRequest) -> Result<Job, AppError> {
+    conn.execute(
+        "INSERT INTO jobs (kind, description, payload, max_attempts) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            JobKind::to_str(&request.kind()),
+            request.description(),
+            serde_json::to_string(request)?,
+            request.max_attempts(),
+        ],
+    )?;
+    let id = conn.last_insert_rowid();
+    info!(job_id = id, kind = ?request.kind(), "Job queued");
+    load_job(conn, id)?.ok_or_else(|| AppError::InvalidInput("Queued job vanished".into()))
+}
+
+/// The job with ID `id`, if any.
+pub fn load_job(conn: &Connection, id: i64) -> Result<Option<Job>, AppError> {
+    Ok(conn
+        .query_row(&format!("{} WHERE id = ?1", JOB_SELECT), [id], |row| {
+            row_to_job(row).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+        })
+        .optional()?)
+}
+
+/// Up to `limit` jobs, newest first.
+pub fn recent_jobs(conn: &Connection, limit: u32) -> Result<Vec<Job>, AppError> {
+    let mut stmt = conn.prepare(&format!("{} ORDER BY id DESC LIMIT ?1", JOB_SELECT))?;
+    let jobs = stmt
+        .query_map([limit], |row| {
+            row_to_job(row).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(jobs)
+}
+
+/// The result file of job `id`, if it finished with one.
+pub fn load_result(conn: &Connection, id: i64) -> Result<Option<JobFile>, AppError> {
+    Ok(conn
+        .query_row(
+            "SELECT result_type, result_name, result FROM jobs \
+             WHERE id = ?1 AND status = 'succeeded' AND result IS NOT NULL",
+            [id],
+            |row| {
+                Ok(JobFile {
+                    content_type: row.get(0)?,
+                    name: row.get(1)?,
+                    bytes: row.get(2)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+/// Records that `done` of `total` steps of job `id` are done.
+fn set_progress(conn: &Connection, id: i64, done: usize, total: usize) -> Result<(), AppError> {
+    conn.execute(
+        "UPDATE jobs SET done = ?1, total = ?2, updated_at = CURRENT_TIMESTAMP WHERE id = ?3",
+        params![done as i64, total as i64, id],
+    )?;
+    Ok(())
+}
+
+/// Queues jobs left running by a stopped server again, without counting the
+/// interrupted attempt.
+fn requeue_interrupted(db: &DbPool) -> Result<usize, AppError> {
+    let conn = db.get_conn()?;
+    Ok(conn.execute(
+        "UPDATE jobs SET status = 'queued', attempts = attempts - 1 WHERE status = 'running'",
+        [],
+    )?)
+}
@@ -0,0 +1,131 @@
+//! Lactation analytics built from milk records and kidding records.
+//!
+//! Milkings are summed per day and assigned to the lactation started by the
+//! doe's most recent kidding on or before that day. Each lactation is then
+//! summarised with its weekly curve, peak and totals (see `shared::milk`).
+
+use crate::errors::AppError;
+use crate::scheduler::DATE_FORMAT;
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use shared::milk::{Lactation, LactationPoint};
+use std::collections::BTreeMap;
+use tracing::{debug, trace};
+
+/// Splits one doe's daily yields into lactations and summarises each.
+///
+/// `kiddings` and `daily` must be sorted by date. Yields before the first
+/// kidding form a lactation with no known start, measured from its first day.
+pub fn build_lactations(
+    doe_name: &str,
+    kiddings: &[NaiveDate],
+    daily: &[(NaiveDate, f64)],
+) -> Vec<Lactation> {
+    let mut groups: BTreeMap<Option<NaiveDate>, Vec<(NaiveDate, f64)>> = BTreeMap::new();
+    for (day, litres) in daily {
+        let kidding = kiddings.iter().rev().find(|k| *k <= day).copied();
+        groups.entry(kidding).or_default().push((*day, *litres));
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|(kidding, days)| {
+            let start = kidding.or_else(|| days.first().map(|(d, _)| *d))?;
+            let dim = |day: &NaiveDate| (*day - start).num_days();
+
+            let mut weeks: BTreeMap<u32, (f64, u32)> = BTreeMap::new();
+            for (day, litres) in &days {
+                let week = weeks.entry((dim(day) / 7) as u32 + 1).or_default();
+                week.0 += litres;
+                week.1 += 1;
+            }
+            let (peak_date, peak_daily_yield) =
+                days.iter().copied().max_by(|a, b| a.1.total_cmp(&b.1))?;
+            let total_yield: f64 = days.iter().map(|(_, l)| l).sum();
+            let last = days.last()?.0;
+            trace!(
+                doe = doe_name,
+                ?kidding,
+                days = days.len(),
+                "Built lactation"
+            );
+
+            Some(Lactation {
+                doe_name: doe_name.to_string(),
+                kidded_on: kidding.map(|k| k.format(DATE_FORMAT).to_string()),
+                days_in_milk: dim(&last),
+                total_yield,
+                peak_daily_yield,
+                peak_day: dim(&peak_date),
+                average_daily_yield: total_yield / days.len() as f64,
+                curve: weeks
+                    .into_iter()
+                    .map(|(week, (sum, count))| LactationPoint {
+                        week,
+                        average_daily_yield: sum / count as f64,
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Loads every lactation, optionally only for the doe named `doe_name`,
+/// ordered by doe and then by lactation start.
+pub fn load_lactations(
+    conn: &Connection,
+    doe_name: Option<&str>,
+) -> Result<Vec<Lactation>, AppError> {
+    let parse = |value: String| NaiveDate::parse_from_str(&value, DATE_FORMAT).ok();
+
+    let mut stmt = conn.prepare(
+        "SELECT g.name, m.recorded_on, SUM(m.yield_litres) FROM milk_records m \
+         JOIN goats g ON g.id = m.doe_id AND g.deleted_at IS NULL \
+         WHERE (?1 IS NULL OR g.name = ?1) \
+         GROUP BY g.name, m.recorded_on ORDER BY g.name, m.recorded_on",
+    )?;
+    let mut daily: BTreeMap<String, Vec<(NaiveDate, f64)>> = BTreeMap::new();
+    for row in stmt.query_map([doe_name], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, f64>(2)?,
+        ))
+    })? {
+        let (name, day, litres) = row?;
+        if let Some(day) = parse(day) {
+            daily.entry(name).or_default().push((day, litres));
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT g.name, k.kidded_on FROM kidding_records k JOIN goats g ON g.id = k.doe_id AND g.deleted_at IS NULL \
+         WHERE (?1 IS NULL OR g.name = ?1) ORDER BY g.name, k.kidded_on",
+    )?;
+    let mut kiddings: BTreeMap<String, Vec<NaiveDate>> = BTreeMap::new();
+    for row in stmt.query_map([doe_name], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })? {
+        let (name, day) = row?;
+        if let Some(day) = parse(day) {
+            kiddings.entry(name).or_default().push(day);
+        }
+    }
+
+    let lactations: Vec<Lactation> = daily
+        .iter()
+        .flat_map(|(name, days)| {
+            build_lactations(
+                name,
+                kiddings.get(name).map(Vec::as_slice).unwrap_or_default(),
+                days,
+            )
+        })
+        .collect();
+    debug!(
+        "Built {} lactations for {} does",
+        lactations.len(),
+        daily.len()
+    );
+    Ok(lactations)
+}
@@ -1,5 +1,31 @@
+pub mod access;
+pub mod alerts;
+pub mod archive;
+pub mod auth;
+pub mod breeding;
+pub mod calendar;
 pub mod db;
 pub mod db_helpers;
+pub mod digests;
 pub mod errors;
+pub mod estimation;
+pub mod events;
 pub mod handlers;
+pub mod heat;
+pub mod http_cache;
+pub mod jobs;
+pub mod lactation;
+pub mod messaging;
 pub mod models;
+pub mod outbound;
+pub mod permissions;
+pub mod repository;
+pub mod ration;
+pub mod retention;
+pub mod rotation;
+pub mod routes;
+pub mod scheduler;
+pub mod scoring;
+pub mod tags;
+pub mod tenants;
+pub mod water;
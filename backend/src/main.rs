@@ -6,14 +6,70 @@
 //!
 //! It ensures that the server only starts after a successful migration,
 //! preventing runtime errors related to schema mismatch.
+//!
+//! `YAGI_DATABASE_URL` names the SQLite database the farm is kept in (see
+//! `backend::repository::StorageBackend`); it defaults to `livestock.db`.
+//! A PostgreSQL URL serves goat records from PostgreSQL instead, while the
+//! farm is still kept in the SQLite database at `YAGI_SQLITE_PATH` (by
+//! default `livestock.db`).
+//! Setting `YAGI_TENANTS_DIR` (together with `YAGI_ADMIN_KEY`) starts the
+//! server in multi-tenant mode, with one database per farm in that directory
+//! (see `backend::tenants`).
+//!
+//! Running with `--ephemeral` keeps everything in memory instead, for demos
+//! and screenshots with zero setup; all data is lost on exit.
+//!
+//! While the farm's `read_only` setting is on, changes are refused (see
+//! `backend::access`).
+//!
+//! Requests from signed-in devices carry a session token, refused once the
+//! session is revoked (see `backend::auth`). The owner signs in with the
+//! password in `YAGI_OWNER_PASSWORD`, set at startup if the farm has none
+//! yet; without it a random one is set and printed once to stderr. Setting `YAGI_GOOGLE_CLIENT_ID`
+//! and `YAGI_GOOGLE_CLIENT_SECRET` lets workers sign in with Google.
+//! Requests in a worker's session are limited to what the owner lets the
+//! worker's role do (see `backend::permissions`). Scripts use personal
+//! access tokens, limited to the scope each was made with.
+//!
+//! Setting `YAGI_WEIGHT_ESTIMATOR_URL` to an `http://` or `https://` URL
+//! turns on weight estimation from photos through that service (see
+//! `backend::estimation`).
+//! Likewise `YAGI_MESSAGE_GATEWAY_URL` names the relay that emails and texts
+//! workers mentioned in goat notes (see `backend::messaging`).
+//!
+//! Setting `YAGI_EVENT_SOURCING` (to anything) makes changes to goats'
+//! details, weighings and sales also append to the event log served at
+//! `/events` (see `backend::events` for what it covers).
+//!
+//! Long-running work such as large imports, report files and notification
+//! delivery runs as background jobs (see `backend::jobs`), followed at
+//! `/jobs`.
 
 use actix_cors::Cors;
+use actix_web::http::header;
 use actix_web::{App, HttpServer, middleware, web};
-use backend::db::DbPool;
-use backend::handlers::goats;
-use tracing::info;
+use backend::access::enforce_read_only;
+use backend::auth::{
+    GoogleOAuth, OAuthProvider, OAuthProviders, check_access_token, check_session,
+    ensure_owner_password,
+};
+use backend::errors::AppError;
+use backend::estimation::{HttpWeightEstimator, WeightEstimator};
+use backend::events::{EventLog, EventSourcing};
+use backend::jobs::{JobQueue, JobRunner};
+use backend::messaging::{HttpMessageGateway, MessageGateway};
+use backend::permissions::enforce_permissions;
+use backend::repository::StorageBackend;
+use backend::tenants::{TenantRegistry, resolve_tenant};
+use backend::{routes, scheduler};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
 use tracing_subscriber;
 
+/// How often the background scheduler evaluates reminder rules.
+const RULE_EVALUATION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
 /// Main asynchronous function to configure and start the backend server.
 ///
 /// # Steps performed:
@@ -21,11 +77,23 @@ use tracing_subscriber;
 /// 2. Open SQLite database connection (or create if missing).
 /// 3. Run any pending database schema migrations; exit if migration fails.
 /// 4. Wrap the DB connection in a thread-safe pool (`DbPool`).
-/// 5. Configure the Actix web server with middleware and route handlers.
-/// 6. Bind the server to `127.0.0.1:8000` and run.
+/// 5. Start the background scheduler that evaluates reminder rules, and the
+///    runner of background jobs.
+///    In multi-tenant mode, steps 2–5 happen for every tenant instead.
+/// 6. Configure the Actix web server with middleware (CORS, response compression,
+///    request logging) and route handlers.
+/// 7. Bind the server to `127.0.0.1:8000` and run.
 ///
 /// # Panics
-/// This function will terminate the process if the database cannot be opened or if migrations fail.
+/// This function will terminate the process if the database cannot be opened or if migrations fail,
+/// if a PostgreSQL `YAGI_DATABASE_URL` cannot be connected to,
+/// if `YAGI_TENANTS_DIR` is set without `YAGI_ADMIN_KEY`, if `YAGI_WEIGHT_ESTIMATOR_URL` or
+/// `YAGI_MESSAGE_GATEWAY_URL` is not an `http://` or `https://` URL, or if
+/// `YAGI_GOOGLE_CLIENT_ID` is set without `YAGI_GOOGLE_CLIENT_SECRET`.
+///
+/// # Errors
+/// Returns an `InvalidInput` error, after logging it, if `YAGI_OWNER_PASSWORD` is
+/// too short.
 ///
 /// # Logging
 /// - Emits info-level logs during startup phases.
@@ -40,28 +108,153 @@ async fn main() -> std::io::Result<()> {
 
     info!("Starting Livestock Management Backend Server");
 
-    let db_pool = DbPool::new("livestock.db").expect("Failed to create DB pool");
+    let estimator = std::env::var("YAGI_WEIGHT_ESTIMATOR_URL").ok().map(|url| {
+        let estimator = HttpWeightEstimator::new(&url).expect("Invalid YAGI_WEIGHT_ESTIMATOR_URL");
+        info!("Estimating weights from photos with {}", url);
+        web::Data::from(Arc::new(estimator) as Arc<dyn WeightEstimator>)
+    });
+    let gateway = std::env::var("YAGI_MESSAGE_GATEWAY_URL").ok().map(|url| {
+        let gateway = HttpMessageGateway::new(&url).expect("Invalid YAGI_MESSAGE_GATEWAY_URL");
+        info!("Sending email and SMS notifications through {}", url);
+        web::Data::from(Arc::new(gateway) as Arc<dyn MessageGateway>)
+    });
+
+    let oauth = std::env::var("YAGI_GOOGLE_CLIENT_ID")
+        .ok()
+        .map(|client_id| {
+            let secret = std::env::var("YAGI_GOOGLE_CLIENT_SECRET")
+                .expect("YAGI_GOOGLE_CLIENT_ID is set without YAGI_GOOGLE_CLIENT_SECRET");
+            let google = GoogleOAuth::new(&client_id, &secret);
+            info!("Workers can sign in with Google");
+            let google: Arc<dyn OAuthProvider> = Arc::new(google);
+            web::Data::new(OAuthProviders(vec![google]))
+        });
+
+    let event_sourcing = std::env::var_os("YAGI_EVENT_SOURCING").map(|_| {
+        info!("Recording changes in the event log");
+        web::Data::new(EventSourcing)
+    });
+
+    // Background jobs run on the same services as the requests that queue them
+    let job_queue = web::Data::new(JobQueue::new());
+    let mut job_runner = JobRunner::new(job_queue.get_ref().clone());
+    if let Some(gateway) = &gateway {
+        job_runner = job_runner.with_gateway(gateway.clone().into_inner());
+    }
+    if event_sourcing.is_some() {
+        job_runner = job_runner.with_event_log(EventLog::enabled());
+    }
+
+    // In multi-tenant mode each request is served from its tenant's database,
+    // so no shared pool is registered.
+    let ephemeral = std::env::args().skip(1).any(|arg| arg == "--ephemeral");
+    let (db_pool, goat_store, tenants) = match std::env::var("YAGI_TENANTS_DIR") {
+        Ok(dir) if !ephemeral => {
+            let admin_key = std::env::var("YAGI_ADMIN_KEY")
+                .expect("YAGI_ADMIN_KEY must be set in multi-tenant mode");
+            let registry = TenantRegistry::open(dir, &admin_key)
+                .expect("Failed to open tenants directory")
+                .with_scheduler(RULE_EVALUATION_INTERVAL)
+                .with_job_runner(job_runner);
+            let generated = registry
+                .open_all()
+                .expect("Failed to open tenant databases");
+            for (slug, password) in generated {
+                eprintln!(
+                    "The owner of {} had no password; they sign in with {} until they change it",
+                    slug, password
+                );
+            }
+            (None, None, Some(web::Data::new(registry)))
+        }
+        _ => {
+            let url = match std::env::var("YAGI_DATABASE_URL") {
+                _ if ephemeral => ":memory:".to_string(),
+                Ok(url) => url,
+                Err(_) => "livestock.db".to_string(),
+            };
+            let mut backend = StorageBackend::from_url(&url);
+            if let Ok(path) = std::env::var("YAGI_SQLITE_PATH") {
+                backend = backend.with_sqlite_path(&path);
+            }
+            if backend == StorageBackend::Memory {
+                info!("Running with an ephemeral in-memory database");
+            }
+            let db_pool = backend.open().expect("Failed to create DB pool");
+            let goat_store = backend
+                .open_goat_store(&db_pool)
+                .expect("Failed to connect to PostgreSQL")
+                .map(|store| {
+                    info!("Serving goat records from PostgreSQL");
+                    web::Data::new(store)
+                });
+            let configured = std::env::var("YAGI_OWNER_PASSWORD").ok();
+            let conn = db_pool.get_conn().expect("Failed to open the database");
+            match ensure_owner_password(&conn, configured.as_deref()) {
+                Ok(Some(password)) => {
+                    // Shown once on the console, never through the log sinks
+                    warn!("YAGI_OWNER_PASSWORD is not set; a random owner password was generated");
+                    eprintln!("The owner signs in with {} until they change it", password);
+                }
+                Ok(None) => {}
+                Err(AppError::InvalidInput(msg)) => {
+                    error!("Invalid YAGI_OWNER_PASSWORD: {}", msg);
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+                }
+                Err(e) => panic!("Failed to set the owner's password: {:?}", e),
+            }
+            drop(conn);
+            scheduler::spawn_scheduler(db_pool.clone(), RULE_EVALUATION_INTERVAL);
+            job_runner.spawn(db_pool.clone());
+            (Some(web::Data::new(db_pool)), goat_store, None)
+        }
+    };
 
     // Build and run Actix web server.
     // Register logging middleware and route definitions.
     HttpServer::new(move || {
-        App::new()
-            .wrap(
-                Cors::default()
-                    .allowed_origin("http://127.0.0.1:8080/")
-                    .allow_any_origin()
-                    .allow_any_method()
-                    .allow_any_header(),
-            )
-            .wrap(middleware::Logger::default()) // Logs every request at info level.
-            .app_data(web::Data::new(db_pool.clone()))
-            .service(
-                web::scope("/goats")
-                    .route("", web::get().to(goats::get_goats))
-                    .route("", web::post().to(goats::add_goat))
-                    .route("", web::put().to(goats::update_goat))
-                    .route("", web::delete().to(goats::delete_goat)),
-            )
+        // resolve_tenant is outermost, so session and read-only checks see
+        // the tenant's database; permissions are checked once the session is
+        // known
+        let mut app = App::new()
+            .wrap(middleware::from_fn(enforce_read_only))
+            .wrap(middleware::from_fn(enforce_permissions))
+            .wrap(middleware::from_fn(check_session))
+            .wrap(middleware::from_fn(check_access_token))
+            .wrap(middleware::from_fn(resolve_tenant));
+        if let Some(db_pool) = &db_pool {
+            app = app.app_data(db_pool.clone());
+        }
+        if let Some(goat_store) = &goat_store {
+            app = app.app_data(goat_store.clone());
+        }
+        app = app.app_data(job_queue.clone());
+        if let Some(tenants) = &tenants {
+            app = app.app_data(tenants.clone());
+        }
+        if let Some(estimator) = &estimator {
+            app = app.app_data(estimator.clone());
+        }
+        if let Some(gateway) = &gateway {
+            app = app.app_data(gateway.clone());
+        }
+        if let Some(oauth) = &oauth {
+            app = app.app_data(oauth.clone());
+        }
+        if let Some(event_sourcing) = &event_sourcing {
+            app = app.app_data(event_sourcing.clone());
+        }
+        app.wrap(
+            Cors::default()
+                .allowed_origin("http://127.0.0.1:8080/")
+                .allow_any_origin()
+                .allow_any_method()
+                .allow_any_header()
+                .expose_headers(vec![header::ETAG]),
+        )
+        .wrap(middleware::Compress::default()) // Gzip/Brotli/Zstd per Accept-Encoding.
+        .wrap(middleware::Logger::default()) // Logs every request at info level.
+        .configure(routes::configure)
     })
     .bind(("127.0.0.1", 8000))?
     .run()
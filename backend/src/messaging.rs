@@ -0,0 +1,84 @@
+//! Extension point for notifying workers by email or SMS.
+//!
+//! Mentions in goat notes always become in-app notifications. Workers who
+//! chose `NotifyChannel::Email` or `NotifyChannel::Sms` are also sent an
+//! `OutgoingMessage` through the `MessageGateway` registered as
+//! `web::Data<dyn MessageGateway>`. `HttpMessageGateway`, the built-in
+//! gateway, posts the messages to a relay at a configured URL
+//! (`YAGI_MESSAGE_GATEWAY_URL` for the server binary) that owns the mail
+//! and SMS accounts. Without a registered gateway only in-app notifications
+//! are made.
+
+use crate::errors::AppError;
+use crate::outbound::HttpEndpoint;
+use serde::{Deserialize, Serialize};
+use shared::notifications::NotifyChannel;
+use std::time::Duration;
+use tracing::{debug, trace};
+
+/// How long the gateway may take to connect and to answer.
+pub const GATEWAY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An email or SMS for one worker.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OutgoingMessage {
+    /// `Email` or `Sms`; in-app notifications are never sent.
+    pub channel: NotifyChannel,
+    /// Email address or phone number.
+    pub to: String,
+    pub text: String,
+    /// Deep link into the dashboard, e.g. `#goat-12`.
+    pub link: String,
+}
+
+/// Delivers emails and SMS.
+///
+/// `send` may block and is called on a worker thread.
+pub trait MessageGateway: Send + Sync {
+    /// Hands `message` over for delivery.
+    fn send(&self, message: &OutgoingMessage) -> Result<(), AppError>;
+}
+
+/// Posts messages as JSON to a relay over HTTP or HTTPS.
+///
+/// The relay receives an `OutgoingMessage` as the request body and answers
+/// with any 2xx status once it has accepted the message.
+#[derive(Debug, Clone)]
+pub struct HttpMessageGateway {
+    endpoint: HttpEndpoint,
+}
+
+impl HttpMessageGateway {
+    /// A gateway for the relay at `url`, which must be an
+    /// `http://` or `https://` URL (see `HttpEndpoint::new`).
+    pub fn new(url: &str) -> Result<Self, String> {
+        Ok(HttpMessageGateway {
+            endpoint: HttpEndpoint::new(url, "Message gateway")?,
+        })
+    }
+}
+
+impl MessageGateway for HttpMessageGateway {
+    fn send(&self, message: &OutgoingMessage) -> Result<(), AppError> {
+        debug!(
+            address = self.endpoint.address(),
+            channel = NotifyChannel::to_str(&message.channel),
+            "Sending message through gateway"
+        );
+        let body = serde_json::to_vec(message)
+            .map_err(|e| AppError::Upstream(format!("Message could not be encoded: {}", e)))?;
+        let (status, answer) = self
+            .endpoint
+            .post("", "application/json", &body, GATEWAY_TIMEOUT)
+            .map_err(|e| AppError::Upstream(format!("Message gateway unreachable: {}", e)))?;
+        trace!(status, bytes = answer.len(), "Message gateway answered");
+        if !(200..300).contains(&status) {
+            return Err(AppError::Upstream(format!(
+                "Message gateway answered {}: {}",
+                status,
+                String::from_utf8_lossy(&answer).trim()
+            )));
+        }
+        Ok(())
+    }
+}
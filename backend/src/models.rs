@@ -1,11 +1,4 @@
-use serde::{Deserialize, Serialize};
-use shared::GoatParams;
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Goat {
-    id: Option<i64>,
-    pub params: GoatParams,
-}
+use serde::Deserialize;
 
 #[derive(Deserialize)]
 pub struct NamePayload {
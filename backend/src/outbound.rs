@@ -0,0 +1,115 @@
+//! HTTP client for the outside services the backend calls, such as the
+//! weight estimator (`crate::estimation`), the message gateway
+//! (`crate::messaging`) and Google sign-in (`crate::auth::GoogleOAuth`).
+//!
+//! `https://` URLs are verified against the Mozilla root certificates
+//! bundled with the server. Plain `http://` URLs are still accepted for
+//! services running next to the server or behind a local proxy.
+
+use std::time::Duration;
+use ureq::Agent;
+
+/// An `http://` or `https://` URL to call.
+#[derive(Debug, Clone)]
+pub struct HttpEndpoint {
+    /// The URL as given.
+    url: String,
+    /// `host:port` the URL points at.
+    address: String,
+}
+
+impl HttpEndpoint {
+    /// The endpoint at `url`, which must be an `http://` or `https://`
+    /// `host[:port]/path` URL; the scheme's usual port is assumed if none is
+    /// given. `service` names what runs there, for error messages.
+    pub fn new(url: &str, service: &str) -> Result<Self, String> {
+        let (rest, default_port) = match url.strip_prefix("https://") {
+            Some(rest) => (rest, 443),
+            None => (
+                url.strip_prefix("http://").ok_or_else(|| {
+                    format!(
+                        "{} URL must start with http:// or https://, got '{}'",
+                        service, url
+                    )
+                })?,
+                80,
+            ),
+        };
+        let host = rest.split(['/', '?']).next().unwrap_or_default();
+        if host.is_empty() {
+            return Err(format!("{} URL has no host: '{}'", service, url));
+        }
+        let address = if host.contains(':') {
+            host.to_string()
+        } else {
+            format!("{}:{}", host, default_port)
+        };
+        Ok(HttpEndpoint {
+            url: url.to_string(),
+            address,
+        })
+    }
+
+    /// `host:port` the endpoint connects to.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Posts `body` with `query` (`key=value` pairs, may be empty) appended
+    /// to the URL and returns the response's status code and body. The whole
+    /// exchange may take up to `timeout`.
+    pub fn post(
+        &self,
+        query: &str,
+        content_type: &str,
+        body: &[u8],
+        timeout: Duration,
+    ) -> std::io::Result<(u16, Vec<u8>)> {
+        let target = if query.is_empty() {
+            self.url.clone()
+        } else {
+            let separator = if self.url.contains('?') { '&' } else { '?' };
+            format!("{}{}{}", self.url, separator, query)
+        };
+        let response = agent(timeout)
+            .post(&target)
+            .header("Content-Type", content_type)
+            .header("Accept", "application/json")
+            .send(body)
+            .map_err(std::io::Error::other)?;
+        read_response(response)
+    }
+
+    /// Fetches the URL and returns the response's status code and body,
+    /// within `timeout`.
+    pub fn get(&self, timeout: Duration) -> std::io::Result<(u16, Vec<u8>)> {
+        let response = agent(timeout)
+            .get(&self.url)
+            .header("Accept", "application/json")
+            .call()
+            .map_err(std::io::Error::other)?;
+        read_response(response)
+    }
+}
+
+/// A client giving up after `timeout`, which hands back error statuses
+/// instead of failing on them.
+fn agent(timeout: Duration) -> Agent {
+    Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .http_status_as_error(false)
+        .build()
+        .new_agent()
+}
+
+/// The status code and body of `response`.
+fn read_response(
+    mut response: ureq::http::Response<ureq::Body>,
+) -> std::io::Result<(u16, Vec<u8>)> {
+    let status = response.status().as_u16();
+    let body = response
+        .body_mut()
+        .read_to_vec()
+        .map_err(std::io::Error::other)?;
+    Ok((status, body))
+}
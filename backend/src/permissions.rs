@@ -0,0 +1,229 @@
+//! Role permissions: what the workers of each role may do, as set by the
+//! owner (see `shared::permissions`).
+//!
+//! The `enforce_permissions` middleware maps each request to a module by
+//! its scope and to an action by its method, and refuses it with HTTP 403
+//! if it was made in a worker's session and the worker's role is not
+//! granted that. Scopes outside every module (dashboard-wide reads such as
+//! stats and search, signing in and out, the permissions themselves, public
+//! share links) are open to every session. Requests outside a session are
+//! left to `crate::auth`, which checks their access token or API key.
+
+use crate::auth::CurrentSession;
+use crate::db::DbPool;
+use crate::errors::AppError;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::{HttpMessage, web};
+use rusqlite::{Connection, OptionalExtension};
+use shared::permissions::{
+    MyPermissions, PermissionAction, PermissionModule, RolePermissions,
+};
+use tracing::{debug, warn};
+
+/// Scopes of each module.
+const MODULE_SCOPES: [(&str, PermissionModule); 41] = [
+    ("/goats", PermissionModule::Goats),
+    ("/notes", PermissionModule::Goats),
+    ("/attachments", PermissionModule::Goats),
+    ("/exports", PermissionModule::Goats),
+    ("/scale", PermissionModule::Goats),
+    ("/events", PermissionModule::Goats),
+    ("/trash", PermissionModule::Goats),
+    ("/lifecycle", PermissionModule::Goats),
+    ("/movements", PermissionModule::Goats),
+    ("/health", PermissionModule::Health),
+    ("/reminders", PermissionModule::Health),
+    ("/insurance", PermissionModule::Health),
+    ("/vet-visits", PermissionModule::Health),
+    ("/lab-results", PermissionModule::Health),
+    ("/diseases", PermissionModule::Health),
+    ("/breeding", PermissionModule::Breeding),
+    ("/breeds", PermissionModule::Breeding),
+    ("/growth", PermissionModule::Production),
+    ("/milk", PermissionModule::Production),
+    ("/nutrition", PermissionModule::Production),
+    ("/tasks", PermissionModule::Tasks),
+    ("/rules", PermissionModule::Tasks),
+    ("/alerts", PermissionModule::Tasks),
+    ("/calendar", PermissionModule::Tasks),
+    ("/inventory", PermissionModule::Inventory),
+    ("/transactions", PermissionModule::Finance),
+    ("/finance", PermissionModule::Finance),
+    ("/pricing", PermissionModule::Finance),
+    ("/reports", PermissionModule::Finance),
+    ("/traceability", PermissionModule::Finance),
+    ("/spaces", PermissionModule::Grazing),
+    ("/grazing", PermissionModule::Grazing),
+    ("/gps", PermissionModule::Grazing),
+    ("/sensors", PermissionModule::Grazing),
+    ("/water", PermissionModule::Grazing),
+    ("/workers", PermissionModule::Workers),
+    ("/notifications", PermissionModule::Workers),
+    ("/settings", PermissionModule::Settings),
+    ("/api-keys", PermissionModule::Settings),
+    ("/tokens", PermissionModule::Settings),
+    ("/archive", PermissionModule::Settings),
+];
+
+/// Further scopes of `PermissionModule::Settings`.
+const SETTINGS_SCOPES: [&str; 2] = ["/retention", "/jobs"];
+
+/// The module a request to `path` belongs to, if any.
+pub fn module_for_path(path: &str) -> Option<PermissionModule> {
+    let in_scope = |scope: &str| {
+        path.strip_prefix(scope)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    };
+    MODULE_SCOPES
+        .into_iter()
+        .find(|(scope, _)| in_scope(scope))
+        .map(|(_, module)| module)
+        .or_else(|| {
+            SETTINGS_SCOPES
+                .into_iter()
+                .any(in_scope)
+                .then_some(PermissionModule::Settings)
+        })
+}
+
+/// The action a `method` request takes.
+pub fn action_for_method(method: &Method) -> PermissionAction {
+    match *method {
+        Method::GET | Method::HEAD | Method::OPTIONS => PermissionAction::View,
+        Method::DELETE => PermissionAction::Delete,
+        _ => PermissionAction::Edit,
+    }
+}
+
+/// The permissions of `role`; every permission if the owner never set any.
+pub fn load_role_permissions(conn: &Connection, role: &str) -> Result<RolePermissions, AppError> {
+    let granted: Option<String> = conn
+        .query_row(
+            "SELECT granted FROM role_permissions WHERE role = ?1",
+            [role],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match granted {
+        Some(json) => Ok(RolePermissions {
+            role: role.to_string(),
+            granted: serde_json::from_str(&json)?,
+        }),
+        None => Ok(RolePermissions::unrestricted(role)),
+    }
+}
+
+/// What a request made in `session` may do: the owner's permissions if the
+/// owner signed in to it, else those of the worker who did.
+///
+/// # Errors
+/// - `AppError::Unauthorized` without a session, or if the worker who
+///   signed in is no longer on the farm.
+pub fn session_permissions(
+    conn: &Connection,
+    session: Option<i64>,
+) -> Result<MyPermissions, AppError> {
+    let Some(session) = session else {
+        return Err(AppError::Unauthorized("Sign in first".into()));
+    };
+    let worker_id: Option<i64> = conn
+        .query_row(
+            "SELECT worker_id FROM sessions WHERE id = ?1",
+            [session],
+            |row| row.get(0),
+        )
+        .optional()?
+        .flatten();
+    worker_permissions(conn, worker_id)
+}
+
+/// What worker `worker_id` may do by their role; the owner's permissions
+/// for `None`, as sessions the owner signed in to and tokens the owner made
+/// have no worker. A worker without a role may do nothing until the owner
+/// gives them one.
+///
+/// # Errors
+/// - `AppError::Unauthorized` if the worker is no longer on the farm.
+pub fn worker_permissions(
+    conn: &Connection,
+    worker_id: Option<i64>,
+) -> Result<MyPermissions, AppError> {
+    let Some(worker_id) = worker_id else {
+        return Ok(MyPermissions::owner());
+    };
+    let worker: Option<(String, Option<String>)> = conn
+        .query_row(
+            "SELECT name, role FROM workers WHERE id = ?1",
+            [worker_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((name, role)) = worker else {
+        warn!(worker_id, "Refused a worker no longer on the farm");
+        return Err(AppError::Unauthorized(
+            "This worker is no longer on the farm".into(),
+        ));
+    };
+    let role = role.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+    let granted = match &role {
+        Some(role) => load_role_permissions(conn, role)?.granted,
+        None => Vec::new(),
+    };
+    Ok(MyPermissions {
+        worker: Some(name),
+        role,
+        granted,
+    })
+}
+
+/// Refuses `req` if it was made in the session of a worker whose role may
+/// not take its action in its module.
+fn check_permissions(req: &ServiceRequest) -> Result<(), AppError> {
+    let Some(module) = module_for_path(req.path()) else {
+        return Ok(());
+    };
+    let Some(session) = req.extensions().get::<CurrentSession>().map(|s| s.0) else {
+        return Ok(());
+    };
+    // Without a pool (e.g. an unknown tenant) the handler reports the problem
+    let Some(db) = req.app_data::<web::Data<DbPool>>() else {
+        return Ok(());
+    };
+    let action = action_for_method(req.method());
+    let conn = db.get_conn()?;
+    let permissions = session_permissions(&conn, Some(session))?;
+    if permissions.allows(module, action) {
+        debug!(session_id = session, ?module, ?action, "Permission granted");
+        return Ok(());
+    }
+    let role = permissions.role.unwrap_or_default();
+    warn!(session_id = session, %role, ?module, ?action, "Refused request without permission");
+    Err(AppError::Forbidden(format!(
+        "The {} role may not {} {}",
+        role,
+        action.label().to_lowercase(),
+        module.label()
+    )))
+}
+
+/// Middleware enforcing role permissions.
+///
+/// Must run after `crate::auth::check_session`, so it sees the session a
+/// request was made in.
+///
+/// # Errors
+/// - Responds with HTTP 403 to requests the worker's role may not make.
+pub async fn enforce_permissions(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    if let Err(e) = check_permissions(&req) {
+        return Ok(req.error_response(e).map_into_right_body());
+    }
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
@@ -0,0 +1,294 @@
+//! Least-cost ration formulation.
+//!
+//! Each feed in stock that `shared::nutrition::find_feed` recognises becomes
+//! a variable: its kilograms per goat per day. The ration minimises cost
+//! subject to meeting the protein and energy targets, keeping dry matter
+//! between the target and what a goat can eat, and using no more of a feed
+//! than the stock holds for the goats and days asked. The linear program is
+//! solved with a two-phase simplex, which is ample for a handful of feeds.
+
+use crate::errors::AppError;
+use shared::inventory::{InventoryCategory, InventoryItem};
+use shared::nutrition::{FeedProfile, Nutrients, RationItem, RationPlan, RationRequest, find_feed};
+use tracing::{debug, trace};
+
+/// Most dry matter a ration may hold, as a multiple of the target; goats
+/// cannot eat much beyond their appetite.
+pub const MAX_INTAKE: f64 = 1.15;
+
+/// Smallest amount treated as non-zero by the solver.
+const EPSILON: f64 = 1e-9;
+
+/// Pivots after which the solver gives up; far above what small rations need.
+const MAX_PIVOTS: usize = 10_000;
+
+/// Which side of its bound a constraint keeps to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    AtLeast,
+    AtMost,
+}
+
+/// `coefficients · x` kept at least or at most `bound`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    pub coefficients: Vec<f64>,
+    pub relation: Relation,
+    pub bound: f64,
+}
+
+/// A simplex tableau: one row per constraint, the right-hand side last.
+struct Tableau {
+    rows: Vec<Vec<f64>>,
+    basis: Vec<usize>,
+}
+
+impl Tableau {
+    fn pivot(&mut self, row: usize, col: usize) {
+        let scale = self.rows[row][col];
+        self.rows[row].iter_mut().for_each(|v| *v /= scale);
+        let pivot_row = self.rows[row].clone();
+        for (i, other) in self.rows.iter_mut().enumerate() {
+            let factor = other[col];
+            if i != row && factor.abs() > EPSILON {
+                other
+                    .iter_mut()
+                    .zip(&pivot_row)
+                    .for_each(|(v, p)| *v -= factor * p);
+            }
+        }
+        self.basis[row] = col;
+    }
+
+    /// Runs the simplex to minimise `cost · columns`, entering only columns
+    /// `allowed` admits. Bland's rule keeps it from cycling. Returns `false`
+    /// if the objective is unbounded or the pivot limit is reached.
+    fn minimise(&mut self, cost: &[f64], allowed: impl Fn(usize) -> bool) -> bool {
+        let rhs = cost.len();
+        for _ in 0..MAX_PIVOTS {
+            let reduced = |col: usize| {
+                cost[col]
+                    - self
+                        .rows
+                        .iter()
+                        .zip(&self.basis)
+                        .map(|(row, &b)| cost[b] * row[col])
+                        .sum::<f64>()
+            };
+            let Some(col) = (0..rhs).find(|&c| allowed(c) && reduced(c) < -EPSILON) else {
+                return true;
+            };
+            let Some(row) = (0..self.rows.len())
+                .filter(|&r| self.rows[r][col] > EPSILON)
+                .min_by(|&a, &b| {
+                    let ratio = |r: usize| self.rows[r][rhs] / self.rows[r][col];
+                    ratio(a)
+                        .total_cmp(&ratio(b))
+                        .then(self.basis[a].cmp(&self.basis[b]))
+                })
+            else {
+                return false;
+            };
+            self.pivot(row, col);
+        }
+        false
+    }
+}
+
+/// Minimises `cost · x` over `x ≥ 0` subject to `constraints`, returning
+/// `None` if they cannot all be met.
+pub fn minimise(cost: &[f64], constraints: &[Constraint]) -> Option<Vec<f64>> {
+    let n = cost.len();
+    let m = constraints.len();
+    // Columns: the variables, one slack or surplus per constraint, one
+    // artificial per constraint, then the right-hand side
+    let width = n + 2 * m + 1;
+    let mut tableau = Tableau {
+        rows: Vec::with_capacity(m),
+        basis: Vec::with_capacity(m),
+    };
+    for (i, constraint) in constraints.iter().enumerate() {
+        // Keep every right-hand side non-negative
+        let sign = if constraint.bound < 0.0 { -1.0 } else { 1.0 };
+        let mut row = vec![0.0; width];
+        for (j, a) in constraint.coefficients.iter().enumerate().take(n) {
+            row[j] = sign * a;
+        }
+        row[n + i] = match constraint.relation {
+            Relation::AtMost => sign,
+            Relation::AtLeast => -sign,
+        };
+        row[n + m + i] = 1.0;
+        row[width - 1] = sign * constraint.bound;
+        tableau.rows.push(row);
+        tableau.basis.push(n + m + i);
+    }
+    let artificial = |col: usize| col >= n + m && col < n + 2 * m;
+
+    // Phase one: drive the artificials out to find a feasible point
+    let phase_one: Vec<f64> = (0..width - 1)
+        .map(|c| if artificial(c) { 1.0 } else { 0.0 })
+        .collect();
+    if !tableau.minimise(&phase_one, |_| true) {
+        return None;
+    }
+    let infeasibility: f64 = (0..m)
+        .filter(|&r| artificial(tableau.basis[r]))
+        .map(|r| tableau.rows[r][width - 1])
+        .sum();
+    if infeasibility > 1e-7 {
+        trace!(infeasibility, "Ration constraints cannot be met");
+        return None;
+    }
+    // Artificials left in the basis at zero are swapped for real columns,
+    // or their rows dropped as redundant
+    let mut r = 0;
+    while r < tableau.rows.len() {
+        if artificial(tableau.basis[r]) {
+            match (0..n + m).find(|&c| tableau.rows[r][c].abs() > EPSILON) {
+                Some(c) => tableau.pivot(r, c),
+                None => {
+                    tableau.rows.remove(r);
+                    tableau.basis.remove(r);
+                    continue;
+                }
+            }
+        }
+        r += 1;
+    }
+
+    // Phase two: the real objective, artificials kept out
+    let mut phase_two = cost.to_vec();
+    phase_two.resize(width - 1, 0.0);
+    if !tableau.minimise(&phase_two, |c| !artificial(c)) {
+        return None;
+    }
+    let mut x = vec![0.0; n];
+    for (row, &b) in tableau.rows.iter().zip(&tableau.basis) {
+        if b < n {
+            x[b] = row[width - 1].max(0.0);
+        }
+    }
+    Some(x)
+}
+
+/// Composes the cheapest daily ration per goat meeting `request` from the
+/// feed in `stock`. Amounts are rounded up to the next 10 g so the ration
+/// still meets the targets once rounded.
+///
+/// # Errors
+/// - `AppError::InvalidInput` for an invalid request, no usable feed in
+///   stock, or feed that cannot meet the targets.
+pub fn formulate_ration(
+    stock: &[InventoryItem],
+    request: &RationRequest,
+) -> Result<RationPlan, AppError> {
+    request.validate().map_err(AppError::InvalidInput)?;
+    let target = request.target();
+    let feeding_days = f64::from(request.goats) * f64::from(request.days);
+
+    let mut feeds = Vec::new();
+    let mut skipped = Vec::new();
+    for item in stock
+        .iter()
+        .filter(|i| i.category == InventoryCategory::Feed)
+    {
+        // The ration is written back as a diet, so the name must read as one
+        let readable = !item
+            .name
+            .contains(|c: char| c.is_ascii_digit() || matches!(c, ',' | ';' | '+'));
+        let profile = find_feed(&item.name);
+        let reason = if !item.unit.trim().eq_ignore_ascii_case("kg") {
+            Some(format!("{} is not stocked in kg", item.name))
+        } else if item.quantity <= 0.0 {
+            Some(format!("{} is out of stock", item.name))
+        } else if profile.is_none() || !readable {
+            Some(format!("{} is not a known feed", item.name))
+        } else {
+            None
+        };
+        match (reason, profile) {
+            (None, Some(profile)) => feeds.push((item, profile)),
+            (reason, _) => skipped.extend(reason),
+        }
+    }
+    if feeds.is_empty() {
+        return Err(AppError::InvalidInput(
+            "No feed in stock can be used for a ration".into(),
+        ));
+    }
+
+    let column =
+        |f: &dyn Fn(&FeedProfile) -> f64| feeds.iter().map(|(_, p)| f(p)).collect::<Vec<f64>>();
+    let dry_matter = column(&|p| p.dry_matter);
+    let protein = column(&|p| p.dry_matter * p.crude_protein * 1000.0);
+    let energy = column(&|p| p.dry_matter * p.energy_mj);
+    let mut constraints = vec![
+        Constraint {
+            coefficients: dry_matter.clone(),
+            relation: Relation::AtLeast,
+            bound: target.dry_matter_kg,
+        },
+        Constraint {
+            coefficients: dry_matter,
+            relation: Relation::AtMost,
+            bound: target.dry_matter_kg * MAX_INTAKE,
+        },
+        Constraint {
+            coefficients: protein,
+            relation: Relation::AtLeast,
+            bound: target.crude_protein_g,
+        },
+        Constraint {
+            coefficients: energy,
+            relation: Relation::AtLeast,
+            bound: target.energy_mj,
+        },
+    ];
+    for (i, (item, _)) in feeds.iter().enumerate() {
+        let mut coefficients = vec![0.0; feeds.len()];
+        coefficients[i] = 1.0;
+        constraints.push(Constraint {
+            coefficients,
+            relation: Relation::AtMost,
+            bound: item.quantity / feeding_days,
+        });
+    }
+    let cost: Vec<f64> = feeds.iter().map(|(item, _)| item.unit_cost).collect();
+
+    let amounts = minimise(&cost, &constraints).ok_or_else(|| {
+        AppError::InvalidInput(
+            "The feed in stock cannot meet these targets within a goat's appetite".into(),
+        )
+    })?;
+
+    let mut items = Vec::new();
+    let mut supply = Nutrients::default();
+    for ((item, profile), kg) in feeds.iter().zip(amounts) {
+        let kg_per_day = ((kg - EPSILON) * 100.0).ceil() / 100.0;
+        if kg_per_day <= 0.0 {
+            continue;
+        }
+        let dry_matter_kg = kg_per_day * profile.dry_matter;
+        supply = supply
+            + Nutrients {
+                dry_matter_kg,
+                crude_protein_g: dry_matter_kg * profile.crude_protein * 1000.0,
+                energy_mj: dry_matter_kg * profile.energy_mj,
+            };
+        items.push(RationItem {
+            feed: item.name.clone(),
+            kg_per_day,
+            cost_per_day: kg_per_day * item.unit_cost,
+        });
+    }
+    let cost_per_day = items.iter().map(|i| i.cost_per_day).sum();
+    debug!(feeds = items.len(), cost_per_day, "Formulated ration");
+    Ok(RationPlan {
+        items,
+        cost_per_day,
+        target,
+        supply,
+        skipped,
+    })
+}
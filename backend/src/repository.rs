@@ -0,0 +1,806 @@
+//! Storage abstraction for goat records.
+//!
+//! The goat handlers and background jobs talk to a `GoatRepository` instead
+//! of SQLite directly. `SqliteGoatRepository` is the built-in
+//! implementation; a deployment may register its own as
+//! `web::Data<dyn GoatRepository>`, e.g. to add caching.
+//!
+//! Only goat records go through the repository: every other module (health,
+//! breeding, sales, reports, ...) queries the farm's SQLite database
+//! directly and joins its `goats` table. So a farm is always stored in
+//! SQLite, and a registered repository must keep its goats in that same
+//! database.
+//!
+//! A `postgres://` database URL selects `PostgresGoatRepository`, which
+//! serves goat records from PostgreSQL. Its changes are still made in the
+//! SQLite database first, so the other modules keep seeing them, and every
+//! change to the `goats` table, whoever makes it, is then copied over (see
+//! `PostgresGoatStore`).
+//!
+//! Handlers extract a `Goats` handle, which uses a registered repository if
+//! there is one and otherwise wraps the request's `DbPool` (which, in
+//! multi-tenant mode, is the tenant's database).
+//!
+//! A `:memory:` database URL (or the server's `--ephemeral` flag) selects a
+//! throwaway in-memory SQLite database, which needs no setup at all.
+
+use crate::db::{DbPool, get_or_insert_disease, get_or_insert_vaccine, row_to_goat_record};
+use crate::errors::AppError;
+use crate::events::{EventLog, next_id};
+use crate::tags::take_tag;
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpRequest, web};
+use postgres::NoTls;
+use postgres::types::Json;
+use r2d2_postgres::PostgresConnectionManager;
+use rusqlite::types::Value;
+use rusqlite::{Connection, OptionalExtension, params, params_from_iter};
+use shared::events::DomainEvent;
+use shared::physical::{CoatColor, HornStatus};
+use shared::{Breed, DiseaseRef, Gender, Goat, GoatParams, GoatUpdate, NewGoat, VaccineRef};
+use std::collections::HashMap;
+use std::future::{Ready, ready};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info, trace, warn};
+
+/// Persistence operations on goats and their vaccination and disease links.
+pub trait GoatRepository: Send + Sync {
+    /// Every goat, without vaccinations or diseases.
+    fn list(&self) -> Result<Vec<Goat>, AppError>;
+
+    /// Up to `limit` goats with an ID greater than `after_id`, in ID order.
+    /// Used to page through large herds.
+    fn list_after(&self, after_id: i64, limit: i64) -> Result<Vec<Goat>, AppError>;
+
+    /// The goat with ID `id`, without vaccinations or diseases.
+    fn get(&self, id: i64) -> Result<Option<Goat>, AppError>;
+
+    /// Stores a new goat with its links and returns it as stored.
+    fn insert(&self, goat: &NewGoat) -> Result<Goat, AppError>;
+
+    /// Stores several new goats and returns them as stored. Implementations
+    /// backed by a transactional store should add all of them or none.
+    fn insert_batch(&self, goats: &[NewGoat]) -> Result<Vec<Goat>, AppError> {
+        goats.iter().map(|goat| self.insert(goat)).collect()
+    }
+
+    /// Replaces the goat with the same name and its links. Returns `false`
+    /// if there is no such goat.
+    fn update(&self, goat: &GoatParams) -> Result<bool, AppError>;
+
+    /// Changes only the fields set in `update` on the goat with ID `id`,
+    /// replacing its links if the update sets them. Returns the goat as `get`
+    /// would, or `None` if there is no such goat.
+    fn patch(&self, id: i64, update: &GoatUpdate) -> Result<Option<Goat>, AppError>;
+
+    /// Applies `update` to every goat in `ids` and returns those found, as
+    /// stored. Implementations backed by a transactional store should change
+    /// all of them or none.
+    fn patch_batch(&self, ids: &[i64], update: &GoatUpdate) -> Result<Vec<Goat>, AppError> {
+        let mut patched = Vec::with_capacity(ids.len());
+        for &id in ids {
+            patched.extend(self.patch(id, update)?);
+        }
+        Ok(patched)
+    }
+
+    /// Deletes the goat named `name`, keeping it in the trash until the farm's
+    /// retention runs out (see `crate::retention`). Returns the ID of its
+    /// trash entry, or `None` if there is no such goat.
+    fn delete(&self, name: &str) -> Result<Option<i64>, AppError>;
+}
+
+/// Where the backend keeps its data, parsed from a database URL.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StorageBackend {
+    /// A fresh database held in memory and lost on exit (`:memory:`).
+    Memory,
+    /// A SQLite file path; `sqlite://` prefix optional.
+    Sqlite(String),
+    /// A `postgres://` or `postgresql://` connection URL, which goat records
+    /// are served from, and the SQLite file every record is kept in as well
+    /// (`livestock.db` unless changed with `with_sqlite_path`).
+    Postgres { url: String, sqlite: String },
+}
+
+impl StorageBackend {
+    /// Parses a database URL such as `sqlite://livestock.db`,
+    /// `livestock.db`, `:memory:`, or `postgres://user@host/yagi`.
+    pub fn from_url(url: &str) -> Self {
+        let url = url.trim();
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            return StorageBackend::Postgres {
+                url: url.to_string(),
+                sqlite: "livestock.db".to_string(),
+            };
+        }
+        match url.strip_prefix("sqlite://").unwrap_or(url) {
+            ":memory:" => StorageBackend::Memory,
+            path => StorageBackend::Sqlite(path.to_string()),
+        }
+    }
+
+    /// Keeps the records of a PostgreSQL backend in the SQLite file at
+    /// `path`. Other backends are returned unchanged.
+    pub fn with_sqlite_path(self, path: &str) -> Self {
+        match self {
+            StorageBackend::Postgres { url, .. } => StorageBackend::Postgres {
+                url,
+                sqlite: path.to_string(),
+            },
+            other => other,
+        }
+    }
+
+    /// Opens and migrates the SQLite database this backend keeps its
+    /// records in.
+    ///
+    /// # Errors
+    /// Any error from opening or migrating the database.
+    pub fn open(&self) -> Result<DbPool, AppError> {
+        match self {
+            StorageBackend::Memory => DbPool::in_memory(),
+            StorageBackend::Sqlite(path) => DbPool::new(path),
+            StorageBackend::Postgres { sqlite, .. } => DbPool::new(sqlite),
+        }
+    }
+
+    /// Connects to the PostgreSQL goat store of a PostgreSQL backend, whose
+    /// records are kept in `db`. Other backends have none.
+    ///
+    /// # Errors
+    /// Any error from `PostgresGoatStore::open`.
+    pub fn open_goat_store(&self, db: &DbPool) -> Result<Option<PostgresGoatStore>, AppError> {
+        match self {
+            StorageBackend::Postgres { url, .. } => PostgresGoatStore::open(url, db).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// `GoatRepository` backed by the SQLite database in a `DbPool`. Goats it
+/// adds get the next tag of the farm's tag series, if it has one.
+#[derive(Clone)]
+pub struct SqliteGoatRepository {
+    db: DbPool,
+    events: EventLog,
+}
+
+impl SqliteGoatRepository {
+    pub fn new(db: DbPool) -> Self {
+        SqliteGoatRepository {
+            db,
+            events: EventLog::disabled(),
+        }
+    }
+
+    /// Records every change in `events` as well (see `crate::events`).
+    pub fn with_event_log(mut self, events: EventLog) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// A repository over a fresh in-memory database (see `DbPool::in_memory`).
+    pub fn in_memory() -> Result<Self, AppError> {
+        Ok(Self::new(DbPool::in_memory()?))
+    }
+
+    /// Registers `goat` within `tx` under the next free ID, with the next
+    /// tag of the farm's series, and returns it as stored.
+    fn register(&self, tx: &Connection, goat: &NewGoat) -> Result<Goat, AppError> {
+        let goat = Goat {
+            id: next_id(tx, "goats")?,
+            params: goat.clone(),
+            tag_id: take_tag(tx)?,
+            date_of_birth: None,
+            created_at: None,
+            updated_at: None,
+        };
+        self.events
+            .apply(tx, &DomainEvent::GoatRegistered { goat: goat.clone() })?;
+        Ok(Goat {
+            params: goat.params,
+            ..load_goat(tx, goat.id)?
+        })
+    }
+}
+
+impl GoatRepository for SqliteGoatRepository {
+    fn list(&self) -> Result<Vec<Goat>, AppError> {
+        let conn = self.db.get_conn()?;
+        let mut stmt = conn.prepare("SELECT * FROM goats WHERE deleted_at IS NULL")?;
+        let goats = stmt
+            .query_map([], |row| {
+                row_to_goat_record(row)
+                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(goats)
+    }
+
+    fn list_after(&self, after_id: i64, limit: i64) -> Result<Vec<Goat>, AppError> {
+        let conn = self.db.get_conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT * FROM goats WHERE id > ?1 AND deleted_at IS NULL ORDER BY id LIMIT ?2",
+        )?;
+        let rows = stmt
+            .query_map(params![after_id, limit], |row| {
+                row_to_goat_record(row)
+                    .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    fn get(&self, id: i64) -> Result<Option<Goat>, AppError> {
+        let conn = self.db.get_conn()?;
+        Ok(load_goat(&conn, id).optional()?)
+    }
+
+    fn insert(&self, goat: &NewGoat) -> Result<Goat, AppError> {
+        let mut conn = self.db.get_conn()?;
+        let tx = conn.transaction()?;
+        let goat = self.register(&tx, goat)?;
+        tx.commit()?;
+        Ok(goat)
+    }
+
+    fn insert_batch(&self, goats: &[NewGoat]) -> Result<Vec<Goat>, AppError> {
+        let mut conn = self.db.get_conn()?;
+        let tx = conn.transaction()?;
+        let stored = goats
+            .iter()
+            .map(|goat| self.register(&tx, goat))
+            .collect::<Result<Vec<_>, _>>()?;
+        tx.commit()?;
+        debug!(count = stored.len(), "Inserted goat batch");
+        Ok(stored)
+    }
+
+    fn update(&self, goat: &GoatParams) -> Result<bool, AppError> {
+        let mut conn = self.db.get_conn()?;
+        let tx = conn.transaction()?;
+        let Some(before) = tx
+            .query_row(
+                "SELECT * FROM goats WHERE name = ?1 AND deleted_at IS NULL LIMIT 1",
+                [&goat.name],
+                |row| {
+                    row_to_goat_record(row)
+                        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+                },
+            )
+            .optional()?
+        else {
+            return Ok(false);
+        };
+        // The stored goat is loaded without links, so they always count as changed
+        let goat_id = before.id;
+        let update = GoatUpdate {
+            vaccinations: Some(goat.vaccinations.clone()),
+            diseases: Some(goat.diseases.clone()),
+            ..GoatUpdate::diff(&before.params, goat)
+        };
+        self.events
+            .apply(&tx, &DomainEvent::GoatUpdated { goat_id, update })?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    fn patch(&self, id: i64, update: &GoatUpdate) -> Result<Option<Goat>, AppError> {
+        let mut conn = self.db.get_conn()?;
+        let tx = conn.transaction()?;
+        if load_goat(&tx, id).optional()?.is_none() {
+            return Ok(None);
+        }
+        self.events.apply(
+            &tx,
+            &DomainEvent::GoatUpdated {
+                goat_id: id,
+                update: update.clone(),
+            },
+        )?;
+        let goat = load_goat(&tx, id)?;
+        tx.commit()?;
+        debug!(goat_id = id, "Patched goat");
+        Ok(Some(goat))
+    }
+
+    fn patch_batch(&self, ids: &[i64], update: &GoatUpdate) -> Result<Vec<Goat>, AppError> {
+        let mut conn = self.db.get_conn()?;
+        let tx = conn.transaction()?;
+        let mut patched = Vec::with_capacity(ids.len());
+        for &id in ids {
+            if load_goat(&tx, id).optional()?.is_none() {
+                continue;
+            }
+            self.events.apply(
+                &tx,
+                &DomainEvent::GoatUpdated {
+                    goat_id: id,
+                    update: update.clone(),
+                },
+            )?;
+            patched.push(load_goat(&tx, id)?);
+        }
+        tx.commit()?;
+        debug!(count = patched.len(), "Patched goat batch");
+        Ok(patched)
+    }
+
+    fn delete(&self, name: &str) -> Result<Option<i64>, AppError> {
+        let mut conn = self.db.get_conn()?;
+        let tx = conn.transaction()?;
+        let Some(goat_id) = tx
+            .query_row(
+                "SELECT id FROM goats WHERE name = ?1 AND deleted_at IS NULL",
+                [name],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?
+        else {
+            return Ok(None);
+        };
+        self.events.apply(
+            &tx,
+            &DomainEvent::GoatRemoved {
+                goat_id,
+                name: name.to_string(),
+            },
+        )?;
+        let trash_id = tx.query_row(
+            "SELECT MAX(id) FROM goat_trash WHERE goat_id = ?1",
+            [goat_id],
+            |row| row.get(0),
+        )?;
+        tx.commit()?;
+        Ok(Some(trash_id))
+    }
+}
+
+/// Goat records copied from a farm's SQLite database into PostgreSQL, as
+/// JSON in a `goats` table of their own.
+///
+/// Triggers on the SQLite `goats` table count every change to a goat in
+/// `goat_outbox`, so changes made by other modules (a weighing, a sale, a
+/// background job) are copied too. `sync` copies the goats counted there
+/// and clears their counts, unless they changed again meanwhile; a goat
+/// deleted or moved to the trash is removed.
+///
+/// The synchronous PostgreSQL client drives a runtime of its own, so all
+/// its calls are made on a separate thread (see `off_runtime`).
+pub struct PostgresGoatStore {
+    pool: Option<r2d2::Pool<PostgresConnectionManager<NoTls>>>,
+    syncing: Mutex<()>,
+}
+
+impl PostgresGoatStore {
+    /// Connects to the PostgreSQL database at `url` and creates its `goats`
+    /// table if missing. Every goat in either database is queued to be
+    /// checked by the next `sync`, so the store catches up with a farm
+    /// changed without it, and drops goats the farm does not have.
+    ///
+    /// # Errors
+    /// - `AppError::PostgresError` if `url` is not a valid connection URL or
+    ///   the table cannot be read.
+    /// - `AppError::PoolError` if no connection can be made.
+    pub fn open(url: &str, db: &DbPool) -> Result<Self, AppError> {
+        let manager = PostgresConnectionManager::new(url.parse()?, NoTls);
+        let pool = off_runtime(|| r2d2::Pool::builder().max_size(4).build(manager))?;
+        let store = PostgresGoatStore {
+            pool: Some(pool),
+            syncing: Mutex::new(()),
+        };
+        let stored: Vec<i64> = store.with_client(|client| {
+            client.batch_execute(
+                "CREATE TABLE IF NOT EXISTS goats (id BIGINT PRIMARY KEY, goat JSONB NOT NULL)",
+            )?;
+            let rows = client.query("SELECT id FROM goats", &[])?;
+            Ok(rows.iter().map(|row| row.get(0)).collect())
+        })?;
+        let mut conn = db.get_conn()?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO goat_outbox (goat_id, changes) SELECT id, 1 FROM goats WHERE true \
+             ON CONFLICT(goat_id) DO UPDATE SET changes = changes + 1",
+            [],
+        )?;
+        for goat_id in &stored {
+            tx.execute(
+                "INSERT INTO goat_outbox (goat_id, changes) VALUES (?1, 1) \
+                 ON CONFLICT(goat_id) DO UPDATE SET changes = changes + 1",
+                [goat_id],
+            )?;
+        }
+        tx.commit()?;
+        info!(stored = stored.len(), "Opened PostgreSQL goat store");
+        Ok(store)
+    }
+
+    /// Copies the goats changed in `db` since the last sync.
+    ///
+    /// # Errors
+    /// Any database error; the changes are then copied by a later sync.
+    pub fn sync(&self, db: &DbPool) -> Result<(), AppError> {
+        let _syncing = self.syncing.lock().unwrap_or_else(|e| e.into_inner());
+        let conn = db.get_conn()?;
+        let pending: Vec<(i64, i64)> = conn
+            .prepare("SELECT goat_id, changes FROM goat_outbox")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let goats = pending
+            .iter()
+            .map(|&(goat_id, _)| Ok((goat_id, load_goat(&conn, goat_id).optional()?)))
+            .collect::<Result<Vec<_>, AppError>>()?;
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+            for (goat_id, goat) in &goats {
+                match goat {
+                    Some(goat) => tx.execute(
+                        "INSERT INTO goats (id, goat) VALUES ($1, $2) \
+                         ON CONFLICT (id) DO UPDATE SET goat = EXCLUDED.goat",
+                        &[goat_id, &Json(goat)],
+                    )?,
+                    None => tx.execute("DELETE FROM goats WHERE id = $1", &[goat_id])?,
+                };
+            }
+            tx.commit()?;
+            Ok(())
+        })?;
+        for (goat_id, changes) in &pending {
+            conn.execute(
+                "DELETE FROM goat_outbox WHERE goat_id = ?1 AND changes = ?2",
+                [goat_id, changes],
+            )?;
+        }
+        debug!(count = pending.len(), "Copied goats to PostgreSQL");
+        Ok(())
+    }
+
+    /// The stored goats selected by `sql`, whose only column is `goat`.
+    fn query(
+        &self,
+        sql: &str,
+        params: &[&(dyn postgres::types::ToSql + Sync)],
+    ) -> Result<Vec<Goat>, AppError> {
+        self.with_client(|client| {
+            let rows = client.query(sql, params)?;
+            Ok(rows
+                .iter()
+                .map(|row| row.get::<_, Json<Goat>>(0).0)
+                .collect())
+        })
+    }
+
+    /// Runs `f` with a pooled connection, off the caller's runtime.
+    fn with_client<T: Send>(
+        &self,
+        f: impl FnOnce(&mut postgres::Client) -> Result<T, AppError> + Send,
+    ) -> Result<T, AppError> {
+        let pool = self.pool.as_ref().expect("pool is only taken on drop");
+        off_runtime(|| f(&mut *pool.get()?))
+    }
+}
+
+impl Drop for PostgresGoatStore {
+    fn drop(&mut self) {
+        // Closing the connections blocks on the client's runtime as well
+        if let Some(pool) = self.pool.take() {
+            off_runtime(move || drop(pool));
+        }
+    }
+}
+
+/// Runs `f` on a thread of its own and returns its result. Starting the
+/// PostgreSQL client's runtime panics on a thread that already runs one,
+/// such as an actix worker.
+fn off_runtime<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(f)
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+/// `GoatRepository` serving goat records from a `PostgresGoatStore`.
+///
+/// Changes are made through a `SqliteGoatRepository` on the farm's database
+/// and then copied to the store. A change whose copy fails is still saved,
+/// and copied by the next sync; reads sync first, so they fail rather than
+/// serve stale goats.
+#[derive(Clone)]
+pub struct PostgresGoatRepository {
+    store: Arc<PostgresGoatStore>,
+    sqlite: SqliteGoatRepository,
+}
+
+impl PostgresGoatRepository {
+    pub fn new(store: Arc<PostgresGoatStore>, sqlite: SqliteGoatRepository) -> Self {
+        PostgresGoatRepository { store, sqlite }
+    }
+
+    /// Copies the change that produced `result`, if it succeeded.
+    fn synced<T>(&self, result: Result<T, AppError>) -> Result<T, AppError> {
+        let value = result?;
+        if let Err(e) = self.store.sync(&self.sqlite.db) {
+            warn!("Failed to copy goat changes to PostgreSQL: {}", e);
+        }
+        Ok(value)
+    }
+}
+
+impl GoatRepository for PostgresGoatRepository {
+    fn list(&self) -> Result<Vec<Goat>, AppError> {
+        self.store.sync(&self.sqlite.db)?;
+        self.store.query("SELECT goat FROM goats ORDER BY id", &[])
+    }
+
+    fn list_after(&self, after_id: i64, limit: i64) -> Result<Vec<Goat>, AppError> {
+        self.store.sync(&self.sqlite.db)?;
+        self.store.query(
+            "SELECT goat FROM goats WHERE id > $1 ORDER BY id LIMIT $2",
+            &[&after_id, &limit],
+        )
+    }
+
+    fn get(&self, id: i64) -> Result<Option<Goat>, AppError> {
+        self.store.sync(&self.sqlite.db)?;
+        let goats = self
+            .store
+            .query("SELECT goat FROM goats WHERE id = $1", &[&id])?;
+        Ok(goats.into_iter().next())
+    }
+
+    fn insert(&self, goat: &NewGoat) -> Result<Goat, AppError> {
+        self.synced(self.sqlite.insert(goat))
+    }
+
+    fn insert_batch(&self, goats: &[NewGoat]) -> Result<Vec<Goat>, AppError> {
+        self.synced(self.sqlite.insert_batch(goats))
+    }
+
+    fn update(&self, goat: &GoatParams) -> Result<bool, AppError> {
+        self.synced(self.sqlite.update(goat))
+    }
+
+    fn patch(&self, id: i64, update: &GoatUpdate) -> Result<Option<Goat>, AppError> {
+        self.synced(self.sqlite.patch(id, update))
+    }
+
+    fn patch_batch(&self, ids: &[i64], update: &GoatUpdate) -> Result<Vec<Goat>, AppError> {
+        self.synced(self.sqlite.patch_batch(ids, update))
+    }
+
+    fn delete(&self, name: &str) -> Result<Option<i64>, AppError> {
+        self.synced(self.sqlite.delete(name))
+    }
+}
+
+/// Loads the goat with ID `id`, without its links.
+fn load_goat(conn: &Connection, id: i64) -> rusqlite::Result<Goat> {
+    conn.query_row(
+        "SELECT * FROM goats WHERE id = ?1 AND deleted_at IS NULL",
+        [id],
+        |row| {
+            row_to_goat_record(row)
+                .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+        },
+    )
+}
+
+/// Replaces the vaccines linked to goat `goat_id` with `vaccines`. A
+/// vaccine kept without a `given_on` date keeps the date it had.
+fn replace_vaccine_links(
+    tx: &Connection,
+    goat_id: i64,
+    vaccines: &[VaccineRef],
+) -> Result<(), AppError> {
+    let given: HashMap<i64, Option<String>> = tx
+        .prepare("SELECT vaccine_id, given_on FROM goat_vaccines WHERE goat_id = ?1")?
+        .query_map([goat_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    tx.execute("DELETE FROM goat_vaccines WHERE goat_id = ?1", [goat_id])?;
+    debug!(goat_id, "Cleared old vaccine links");
+    for vaccine in vaccines {
+        let vaccine_id = get_or_insert_vaccine(tx, vaccine)?;
+        let given_on = vaccine
+            .given_on
+            .clone()
+            .or_else(|| given.get(&vaccine_id).cloned().flatten());
+        tx.execute(
+            "INSERT OR IGNORE INTO goat_vaccines (goat_id, vaccine_id, given_on) VALUES (?, ?, ?)",
+            params![goat_id, vaccine_id, given_on],
+        )?;
+    }
+    Ok(())
+}
+
+/// Replaces the diseases linked to goat `goat_id` with `diseases`.
+fn replace_disease_links(
+    tx: &Connection,
+    goat_id: i64,
+    diseases: &[DiseaseRef],
+) -> Result<(), AppError> {
+    tx.execute("DELETE FROM goat_diseases WHERE goat_id = ?1", [goat_id])?;
+    debug!(goat_id, "Cleared old disease links");
+    for disease in diseases {
+        let disease_id = get_or_insert_disease(tx, disease)?;
+        tx.execute(
+            "INSERT OR IGNORE INTO goat_diseases (goat_id, disease_id) VALUES (?, ?)",
+            [goat_id, disease_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Changes the fields set in `update` on the goat with ID `id` within `tx`,
+/// replacing its links if the update sets them. Returns the goat without
+/// links, or `None` if there is no such goat.
+pub(crate) fn patch_goat(
+    tx: &Connection,
+    id: i64,
+    update: &GoatUpdate,
+) -> Result<Option<Goat>, AppError> {
+    let mut columns: Vec<(&str, Value)> = Vec::new();
+    if let Some(name) = &update.name {
+        columns.push(("name", name.clone().into()));
+    }
+    if let Some(breed) = &update.breed {
+        columns.push(("breed", Breed::to_str(breed).to_string().into()));
+    }
+    if let Some(gender) = &update.gender {
+        columns.push(("gender", Gender::to_str(gender).to_string().into()));
+    }
+    if let Some(offspring) = update.offspring {
+        columns.push(("offspring", offspring.into()));
+    }
+    if let Some(cost) = update.cost {
+        columns.push(("cost", cost.into()));
+    }
+    if let Some(weight) = update.weight {
+        columns.push(("weight", weight.into()));
+    }
+    if let Some(current_price) = update.current_price {
+        columns.push(("current_price", current_price.into()));
+    }
+    if let Some(diet) = &update.diet {
+        columns.push(("diet", diet.clone().into()));
+    }
+    if let Some(last_bred) = &update.last_bred {
+        columns.push(("last_bred", last_bred.clone().into()));
+    }
+    if let Some(health_status) = &update.health_status {
+        columns.push(("health_status", health_status.clone().into()));
+    }
+    if let Some(horns) = &update.horns {
+        let horns = horns.as_ref().map(|h| HornStatus::to_str(h).to_string());
+        columns.push(("horns", horns.into()));
+    }
+    if let Some(coat_color) = &update.coat_color {
+        let coat_color = coat_color
+            .as_ref()
+            .map(|c| CoatColor::to_str(c).to_string());
+        columns.push(("coat_color", coat_color.into()));
+    }
+    if let Some(marks) = &update.marks {
+        columns.push(("marks", marks.clone().into()));
+    }
+    if let Some(quick_note) = &update.quick_note {
+        columns.push(("quick_note", quick_note.clone().into()));
+    }
+
+    // Only the named columns are written, so a concurrent change to any
+    // other field survives. updated_at is bumped even for link-only updates.
+    let assignments: String = columns
+        .iter()
+        .map(|(column, _)| format!("{} = ?, ", column))
+        .collect();
+    let sql = format!(
+        "UPDATE goats SET {}updated_at = CURRENT_TIMESTAMP WHERE id = ? AND deleted_at IS NULL",
+        assignments
+    );
+    let values = columns.into_iter().map(|(_, value)| value);
+    let affected = tx.execute(&sql, params_from_iter(values.chain([Value::from(id)])))?;
+    if affected == 0 {
+        return Ok(None);
+    }
+
+    if let Some(vaccinations) = &update.vaccinations {
+        replace_vaccine_links(tx, id, vaccinations)?;
+    }
+    if let Some(diseases) = &update.diseases {
+        replace_disease_links(tx, id, diseases)?;
+    }
+    Ok(Some(load_goat(tx, id)?))
+}
+
+/// Inserts `goat` under ID `goat_id`, with its vaccine and disease links,
+/// within `tx`.
+pub(crate) fn insert_goat(tx: &Connection, goat_id: i64, goat: &NewGoat) -> Result<(), AppError> {
+    tx.execute(
+            "INSERT INTO goats (id, breed, name, gender, offspring, cost, weight, current_price, diet, last_bred, health_status, horns, coat_color, marks, quick_note, updated_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)",
+            params![
+                goat_id,
+                Breed::to_str(&goat.breed),
+                &goat.name,
+                Gender::to_str(&goat.gender),
+                &goat.offspring,
+                &goat.cost,
+                &goat.weight,
+                &goat.current_price,
+                &goat.diet,
+                &goat.last_bred,
+                &goat.health_status,
+                goat.horns.as_ref().map(HornStatus::to_str),
+                goat.coat_color.as_ref().map(CoatColor::to_str),
+                &goat.marks,
+                &goat.quick_note,
+            ],
+        )?;
+    debug!(goat_id, "Inserted goat base record");
+
+    for vaccine in &goat.vaccinations {
+        let vaccine_id = get_or_insert_vaccine(tx, vaccine)?;
+        tx.execute(
+            "INSERT INTO goat_vaccines (goat_id, vaccine_id, given_on) VALUES (?, ?, ?)",
+            params![goat_id, vaccine_id, vaccine.given_on],
+        )?;
+        info!(goat_id, vaccine_id, "Linked vaccine");
+    }
+    for disease in &goat.diseases {
+        let disease_id = get_or_insert_disease(tx, disease)?;
+        tx.execute(
+            "INSERT INTO goat_diseases (goat_id, disease_id) VALUES (?, ?)",
+            [goat_id, disease_id],
+        )?;
+        trace!(goat_id, disease_id, "Linked disease");
+    }
+    Ok(())
+}
+
+/// The goat repository serving a request.
+///
+/// Resolves to the registered `web::Data<dyn GoatRepository>` if any, and
+/// otherwise to a `SqliteGoatRepository` over the request's `DbPool`, or a
+/// `PostgresGoatRepository` over it if a `web::Data<PostgresGoatStore>` is
+/// registered.
+#[derive(Clone)]
+pub struct Goats(pub Arc<dyn GoatRepository>);
+
+impl std::ops::Deref for Goats {
+    type Target = dyn GoatRepository;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl FromRequest for Goats {
+    type Error = AppError;
+    type Future = Ready<Result<Self, AppError>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        if let Some(repo) = req.app_data::<web::Data<dyn GoatRepository>>() {
+            return ready(Ok(Goats(repo.clone().into_inner())));
+        }
+        ready(
+            req.app_data::<web::Data<DbPool>>()
+                .map(|db| {
+                    let repo = SqliteGoatRepository::new(db.get_ref().clone())
+                        .with_event_log(EventLog::for_request(req));
+                    match req.app_data::<web::Data<PostgresGoatStore>>() {
+                        Some(store) => Goats(Arc::new(PostgresGoatRepository::new(
+                            store.clone().into_inner(),
+                            repo,
+                        ))),
+                        None => Goats(Arc::new(repo)),
+                    }
+                })
+                .ok_or_else(|| AppError::InvalidInput("No goat storage configured".into())),
+        )
+    }
+}
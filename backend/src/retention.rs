@@ -0,0 +1,229 @@
+//! Trash and retention of deleted and historical data (see
+//! `shared::retention`).
+//!
+//! Deleting a goat calls `move_to_trash` in the deleting transaction, as
+//! the projection of its `DomainEvent::GoatRemoved`, which sets the goat's
+//! `deleted_at` and lists it in `goat_trash`. Everything
+//! else skips deleted goats, but their weighings, milk records, notes and
+//! other records stay, so restoring brings the goat back as it was, under
+//! the same ID.
+//!
+//! `purge_expired` deletes whatever is past the farm's `RetentionPolicy`,
+//! and is the only place goats are deleted for good, with their records,
+//! each through a `DomainEvent::GoatPurged`. It
+//! runs as a `JobRequest::Cleanup` job, which the scheduler queues once a
+//! day through `queue_daily_cleanup`.
+
+use crate::db::{fetch_diseases, fetch_vaccines, row_to_goat_record};
+use crate::errors::AppError;
+use crate::events::EventLog;
+use crate::handlers::settings::load_settings;
+use crate::jobs::{JobRequest, enqueue};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use shared::Goat;
+use shared::events::DomainEvent;
+use shared::jobs::{Job, JobKind};
+use shared::retention::{PurgeSummary, RetentionPolicy, TrashedGoat, UpcomingPurges};
+use tracing::{debug, info};
+
+/// Days ahead `UpcomingPurges::events_next_week` looks.
+const LOOKAHEAD_DAYS: i64 = 7;
+
+/// SQLite date modifier moving a time `days` days on; negative goes back.
+fn days_modifier(days: i64) -> String {
+    format!("{:+} days", days)
+}
+
+/// Marks the goat with ID `goat_id` as deleted and lists it in the trash,
+/// within `tx`. Returns the ID of its trash entry.
+pub fn move_to_trash(tx: &Connection, goat_id: i64) -> Result<i64, AppError> {
+    let mut goat = tx.query_row("SELECT * FROM goats WHERE id = ?1", [goat_id], |row| {
+        row_to_goat_record(row).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+    })?;
+    goat.params.vaccinations = fetch_vaccines(tx, goat_id)?;
+    goat.params.diseases = fetch_diseases(tx, goat_id)?;
+    tx.execute(
+        "INSERT INTO goat_trash (goat_id, name, payload) VALUES (?1, ?2, ?3)",
+        params![goat_id, goat.params.name, serde_json::to_string(&goat)?],
+    )?;
+    let trash_id = tx.last_insert_rowid();
+    tx.execute(
+        "UPDATE goats SET deleted_at = CURRENT_TIMESTAMP WHERE id = ?1",
+        [goat_id],
+    )?;
+    debug!(goat_id, trash_id, "Moved goat to trash");
+    Ok(trash_id)
+}
+
+/// The trash, soonest purged first under `policy`.
+pub fn trashed_goats(
+    conn: &Connection,
+    policy: &RetentionPolicy,
+) -> Result<Vec<TrashedGoat>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, payload, deleted_at, date(deleted_at, ?1) FROM goat_trash \
+         ORDER BY deleted_at, id",
+    )?;
+    let rows = stmt
+        .query_map([days_modifier(policy.trash_days as i64)], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, DateTime<Utc>>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    rows.into_iter()
+        .map(|(id, payload, deleted_at, purge_on)| {
+            Ok(TrashedGoat {
+                id,
+                goat: serde_json::from_str(&payload)?,
+                deleted_at,
+                purge_on,
+            })
+        })
+        .collect()
+}
+
+/// The goat kept in trash entry `id`, if there is one.
+pub fn trashed_goat(conn: &Connection, id: i64) -> Result<Option<Goat>, AppError> {
+    let payload: Option<String> = conn
+        .query_row(
+            "SELECT payload FROM goat_trash WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(payload.map(|p| serde_json::from_str(&p)).transpose()?)
+}
+
+/// Removes trash entry `id`, once its goat is restored.
+pub fn remove_from_trash(conn: &Connection, id: i64) -> Result<(), AppError> {
+    conn.execute("DELETE FROM goat_trash WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+/// Restores the goat kept in trash entry `id` under its own ID, with all
+/// its records, through `events`. Returns `false` if the goat itself is
+/// gone, as it is for entries trashed before goats were only marked deleted.
+///
+/// Names may repeat, as they may for goats added, so a goat added since
+/// under the same name does not stop the restore.
+///
+/// # Errors
+/// - `AppError::InvalidInput` if a goat added since has taken its ear tag.
+pub fn restore_from_trash(
+    conn: &mut Connection,
+    events: &EventLog,
+    id: i64,
+    goat: &Goat,
+) -> Result<bool, AppError> {
+    let tx = conn.transaction()?;
+    let deleted: bool = tx.query_row(
+        "SELECT EXISTS (SELECT 1 FROM goats WHERE id = ?1 AND deleted_at IS NOT NULL)",
+        [goat.id],
+        |row| row.get(0),
+    )?;
+    if !deleted {
+        return Ok(false);
+    }
+    let tag_taken: bool = tx.query_row(
+        "SELECT EXISTS (SELECT 1 FROM goats g JOIN goats t ON t.tag_id = g.tag_id \
+         WHERE t.id = ?1 AND g.deleted_at IS NULL)",
+        [goat.id],
+        |row| row.get(0),
+    )?;
+    if tag_taken {
+        return Err(AppError::InvalidInput(format!(
+            "Another goat has taken the ear tag of {}",
+            goat.params.name
+        )));
+    }
+    events.apply(&tx, &DomainEvent::GoatRestored { goat_id: goat.id })?;
+    tx.commit()?;
+    debug!(trash_id = id, goat_id = goat.id, "Restored goat in place");
+    Ok(true)
+}
+
+/// Deletes the goats in the trash, with all their records, through `events`,
+/// and the event log entries that are past the farm's retention, in one
+/// transaction.
+pub fn purge_expired(conn: &mut Connection, events: &EventLog) -> Result<PurgeSummary, AppError> {
+    let policy = load_settings(conn)?.retention;
+    let tx = conn.transaction()?;
+    let cutoff = days_modifier(-(policy.trash_days as i64));
+    let expired: Vec<i64> = tx
+        .prepare(
+            "SELECT goat_id FROM goat_trash WHERE deleted_at < datetime('now', ?1) \
+             AND goat_id IN (SELECT id FROM goats WHERE deleted_at IS NOT NULL)",
+        )?
+        .query_map([&cutoff], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    for &goat_id in &expired {
+        events.apply(&tx, &DomainEvent::GoatPurged { goat_id })?;
+    }
+    // Entries trashed before goats were only marked deleted have no goat left
+    let goats = expired.len()
+        + tx.execute(
+            "DELETE FROM goat_trash WHERE deleted_at < datetime('now', ?1)",
+            [&cutoff],
+        )?;
+    let events = match policy.event_days {
+        Some(days) => tx.execute(
+            "DELETE FROM events WHERE recorded_at < datetime('now', ?1)",
+            [days_modifier(-(days as i64))],
+        )?,
+        None => 0,
+    };
+    tx.commit()?;
+    info!(goats, events, "Purged expired trash and event log entries");
+    Ok(PurgeSummary { goats, events })
+}
+
+/// What the next cleanups will purge under the farm's retention.
+pub fn upcoming_purges(conn: &Connection) -> Result<UpcomingPurges, AppError> {
+    let policy = load_settings(conn)?.retention;
+    let count_before = |days: i64| -> Result<usize, AppError> {
+        Ok(conn.query_row(
+            "SELECT COUNT(*) FROM events WHERE recorded_at < datetime('now', ?1)",
+            [days_modifier(days)],
+            |row| row.get(0),
+        )?)
+    };
+    let (events_due, events_next_week) = match policy.event_days {
+        Some(days) => {
+            let due = count_before(-(days as i64))?;
+            (due, count_before(LOOKAHEAD_DAYS - days as i64)? - due)
+        }
+        None => (0, 0),
+    };
+    let last_cleanup = conn.query_row(
+        "SELECT MAX(updated_at) FROM jobs WHERE kind = ?1 AND status = 'succeeded'",
+        [JobKind::to_str(&JobKind::Cleanup)],
+        |row| row.get(0),
+    )?;
+    Ok(UpcomingPurges {
+        trash: trashed_goats(conn, &policy)?,
+        policy,
+        events_due,
+        events_next_week,
+        last_cleanup,
+    })
+}
+
+/// Queues a cleanup job unless one was queued within the last day, and
+/// returns it if it did.
+pub fn queue_daily_cleanup(conn: &Connection) -> Result<Option<Job>, AppError> {
+    let recent: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM jobs WHERE kind = ?1 \
+         AND created_at > datetime('now', '-1 day'))",
+        [JobKind::to_str(&JobKind::Cleanup)],
+        |row| row.get(0),
+    )?;
+    if recent {
+        return Ok(None);
+    }
+    enqueue(conn, &JobRequest::Cleanup).map(Some)
+}
@@ -0,0 +1,164 @@
+//! Pasture rotation planner.
+//!
+//! Each week every grazing group moves to a paddock of its own. Groups are
+//! placed largest first, each on the free paddock that has rested longest
+//! among those big enough for it; a paddock grazed in one week rests for its
+//! `rest_days` after that week ends. When no free paddock has rested long
+//! enough or is big enough, the best remaining one is used and the week is
+//! reported as a conflict (see `shared::grazing`).
+
+use crate::errors::AppError;
+use crate::scheduler::DATE_FORMAT;
+use chrono::{Days, NaiveDate};
+use rusqlite::Connection;
+use shared::grazing::{
+    GrazingAssignment, GrazingGroup, Paddock, RotationConflict, RotationPlan, RotationWeek,
+};
+use std::collections::HashMap;
+use tracing::{debug, trace};
+
+/// Days a group spends on a paddock before moving on.
+pub const GRAZING_DAYS: u64 = 7;
+
+/// Loads every paddock, ordered by name.
+pub fn load_paddocks(conn: &Connection) -> Result<Vec<Paddock>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, area_m2, capacity, rest_days, last_grazed_on
+         FROM paddocks ORDER BY name",
+    )?;
+    let paddocks = stmt
+        .query_map([], |row| {
+            Ok(Paddock {
+                id: Some(row.get(0)?),
+                name: row.get(1)?,
+                area_m2: row.get(2)?,
+                capacity: row.get(3)?,
+                rest_days: row.get(4)?,
+                last_grazed_on: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(paddocks)
+}
+
+/// The grazing groups: the goats housed in each pen, ordered by pen name.
+/// Goats in grazing fields or in no space are not grouped.
+pub fn load_groups(conn: &Connection) -> Result<Vec<GrazingGroup>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT s.name, COUNT(g.id)
+         FROM spaces s
+         JOIN goats g ON g.space_id = s.id AND g.deleted_at IS NULL
+         WHERE COALESCE(s.type, 'other') != 'grazing_field'
+         GROUP BY s.id
+         ORDER BY s.name",
+    )?;
+    let groups = stmt
+        .query_map([], |row| {
+            Ok(GrazingGroup {
+                name: row.get(0)?,
+                goats: row.get(1)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(groups)
+}
+
+/// Proposes where each group grazes for `weeks` weeks from `start`.
+pub fn plan_rotation(
+    paddocks: &[Paddock],
+    groups: &[GrazingGroup],
+    start: NaiveDate,
+    weeks: u32,
+) -> RotationPlan {
+    // Last day each paddock was grazed, updated as the plan goes
+    let mut last_grazed: HashMap<&str, NaiveDate> = paddocks
+        .iter()
+        .filter_map(|p| {
+            let day = NaiveDate::parse_from_str(p.last_grazed_on.as_deref()?, DATE_FORMAT).ok()?;
+            Some((p.name.as_str(), day))
+        })
+        .collect();
+    let mut order: Vec<&GrazingGroup> = groups.iter().collect();
+    order.sort_by(|a, b| b.goats.cmp(&a.goats).then_with(|| a.name.cmp(&b.name)));
+
+    let mut plan_weeks = Vec::with_capacity(weeks as usize);
+    let mut conflicts = Vec::new();
+    for week in 0..weeks as u64 {
+        let week_start = start + Days::new(week * GRAZING_DAYS);
+        let week_end = week_start + Days::new(GRAZING_DAYS - 1);
+        let starts_on = week_start.format(DATE_FORMAT).to_string();
+        let mut taken: Vec<&str> = Vec::new();
+        let mut assignments = Vec::with_capacity(order.len());
+        for group in &order {
+            // Days rested by the start of the week; never grazed counts as
+            // rested for ever
+            let rested = |p: &Paddock| {
+                last_grazed
+                    .get(p.name.as_str())
+                    .map_or(i64::MAX, |day| (week_start - *day).num_days() - 1)
+            };
+            let fits = |p: &Paddock| p.max_goats().is_none_or(|max| group.goats <= max);
+            let choice = paddocks
+                .iter()
+                .filter(|p| !taken.contains(&p.name.as_str()))
+                .min_by_key(|p| {
+                    let rested = rested(p);
+                    (
+                        !fits(p),
+                        rested < p.rest_days,
+                        std::cmp::Reverse(rested),
+                        p.name.as_str(),
+                    )
+                });
+            let mut messages = Vec::new();
+            match choice {
+                Some(paddock) => {
+                    let rested = rested(paddock);
+                    if rested < paddock.rest_days {
+                        messages.push(format!(
+                            "{} goes on {} after {} days' rest; it needs {}",
+                            group.name, paddock.name, rested, paddock.rest_days
+                        ));
+                    }
+                    if let Some(max) = paddock.max_goats().filter(|max| group.goats > *max) {
+                        messages.push(format!(
+                            "{} carries {} goats but {} has {}",
+                            paddock.name, max, group.name, group.goats
+                        ));
+                    }
+                    taken.push(&paddock.name);
+                    last_grazed.insert(&paddock.name, week_end);
+                }
+                None => messages.push(format!("No paddock is free for {}", group.name)),
+            }
+            trace!(week = %starts_on, group = %group.name, paddock = ?choice.map(|p| &p.name));
+            assignments.push(GrazingAssignment {
+                group: group.name.clone(),
+                paddock: choice.map(|p| p.name.clone()),
+                conflict: !messages.is_empty(),
+            });
+            conflicts.extend(messages.into_iter().map(|message| RotationConflict {
+                week_starts_on: starts_on.clone(),
+                group: group.name.clone(),
+                message,
+            }));
+        }
+        plan_weeks.push(RotationWeek {
+            starts_on,
+            assignments,
+        });
+    }
+
+    debug!(
+        weeks,
+        groups = groups.len(),
+        paddocks = paddocks.len(),
+        conflicts = conflicts.len(),
+        "Rotation planned"
+    );
+    RotationPlan {
+        groups: order.into_iter().cloned().collect(),
+        weeks: plan_weeks,
+        conflicts,
+    }
+}
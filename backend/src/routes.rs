@@ -0,0 +1,421 @@
+//! Route table for the REST API.
+//!
+//! Shared by the server binary and the integration tests, so both always
+//! exercise the same set of endpoints.
+
+use crate::archive::MAX_ARCHIVE_BYTES;
+use crate::handlers::{
+    activity, alerts, analytics, api_keys, archive, attachments, breeding, breeds, calendar,
+    client_errors, data_health, diseases, events, exports, external_animals, finance, goats, gps,
+    grazing, growth, health, import, insurance, inventory, jobs, lab_results, labels, lifecycle,
+    milk, movements, notes, notifications, nutrition, permissions, pricing, reminders, reports,
+    retention, scale, scoring, search, sensors, sessions, settings, spaces, stats, tags, tasks,
+    tenants, tokens, traceability, vet_visits, water, workers,
+};
+use actix_web::web;
+use shared::attachments::MAX_ATTACHMENT_BYTES;
+
+/// Registers every API scope on `cfg`.
+///
+/// Literal segments (e.g. `/inventory/alerts`) are registered before
+/// `/{id}` patterns so they are not captured as IDs.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/goats")
+            .route("", web::get().to(goats::get_goats))
+            .route("/stream", web::get().to(goats::stream_goats))
+            .route("/next-tag", web::get().to(tags::get_next_tag))
+            .route("", web::post().to(goats::add_goat))
+            .route("", web::put().to(goats::update_goat))
+            .route("", web::delete().to(goats::delete_goat))
+            .route("", web::patch().to(goats::bulk_patch_goats))
+            .route("/batch", web::post().to(goats::add_goats))
+            .service(
+                web::resource("/import")
+                    .app_data(web::PayloadConfig::new(import::MAX_IMPORT_BYTES))
+                    .route(web::post().to(import::parse_import)),
+            )
+            .route("/{id}", web::patch().to(goats::patch_goat))
+            .route("/{id}/history", web::get().to(events::get_field_history)),
+    );
+    cfg.service(
+        web::scope("/lifecycle")
+            .route("", web::get().to(lifecycle::get_pipeline))
+            .route("", web::put().to(lifecycle::change_stage))
+            .route(
+                "/{goat_name}/history",
+                web::get().to(lifecycle::get_history),
+            ),
+    );
+    cfg.service(
+        web::scope("/movements")
+            .route("", web::get().to(movements::get_movements))
+            .route("", web::post().to(movements::add_movement)),
+    );
+    cfg.service(
+        web::scope("/tasks")
+            .route("", web::get().to(tasks::get_tasks))
+            .route("", web::post().to(tasks::add_task))
+            .route("/{id}/complete", web::put().to(tasks::complete_task))
+            .route("/{id}", web::delete().to(tasks::delete_task)),
+    );
+    cfg.service(
+        web::scope("/rules")
+            .route("", web::get().to(reminders::get_rules))
+            .route("", web::post().to(reminders::add_rule))
+            .route("/evaluate", web::post().to(reminders::evaluate_rules_now))
+            .route("/{id}", web::delete().to(reminders::delete_rule)),
+    );
+    cfg.service(
+        web::scope("/alerts")
+            .route("", web::get().to(alerts::get_alert_rules))
+            .route("", web::post().to(alerts::add_alert_rule))
+            .route("/evaluate", web::post().to(alerts::evaluate_alerts_now))
+            .route("/{id}", web::delete().to(alerts::delete_alert_rule)),
+    );
+    cfg.service(
+        web::scope("/reminders")
+            .route("", web::get().to(reminders::get_reminders))
+            .route("/{id}/dismiss", web::put().to(reminders::dismiss_reminder)),
+    );
+    cfg.service(web::scope("/calendar").route("/feed.ics", web::get().to(calendar::get_feed)));
+    cfg.service(
+        web::scope("/inventory")
+            .route("", web::get().to(inventory::get_items))
+            .route("", web::post().to(inventory::add_item))
+            .route("/alerts", web::get().to(inventory::get_alerts))
+            .route("/expiring", web::get().to(inventory::get_expiring))
+            .route("/consumption", web::get().to(inventory::get_consumption))
+            .route("/{id}", web::put().to(inventory::update_item))
+            .route("/{id}", web::delete().to(inventory::delete_item))
+            .route("/{id}/consume", web::post().to(inventory::consume_item))
+            .route("/{id}/dispose", web::post().to(inventory::dispose_item)),
+    );
+    cfg.service(
+        web::scope("/transactions")
+            .route("", web::get().to(finance::get_transactions))
+            .route("", web::post().to(finance::add_transaction))
+            .route("/{id}", web::delete().to(finance::delete_transaction)),
+    );
+    cfg.service(
+        web::scope("/finance")
+            .route("/goats", web::get().to(finance::get_goat_profitability))
+            .route("/tax-rates", web::get().to(finance::get_tax_rates))
+            .route("/tax-rates", web::post().to(finance::add_tax_rate))
+            .route(
+                "/tax-rates/{id}",
+                web::delete().to(finance::delete_tax_rate),
+            )
+            .route(
+                "/sales-register",
+                web::get().to(finance::get_sales_register),
+            )
+            .route("/budgets", web::get().to(finance::get_budgets))
+            .route("/budgets", web::put().to(finance::set_budget))
+            .route(
+                "/budgets/variance",
+                web::get().to(finance::get_budget_variance),
+            )
+            .route(
+                "/budgets/{category}",
+                web::delete().to(finance::delete_budget),
+            ),
+    );
+    cfg.service(
+        web::scope("/client-errors")
+            .route("", web::get().to(client_errors::get_client_errors))
+            .route("", web::post().to(client_errors::report_client_error)),
+    );
+    cfg.service(
+        web::scope("/breeding")
+            .route("/pedigree", web::put().to(breeding::update_pedigree))
+            .route(
+                "/pedigree/{goat_name}",
+                web::get().to(breeding::get_pedigree_tree),
+            )
+            .route("/tags", web::get().to(breeding::get_genetic_tags))
+            .route("/tags", web::put().to(breeding::set_genetic_tags))
+            .route("/neuterings", web::get().to(breeding::get_neuterings))
+            .route("/neuterings", web::put().to(breeding::record_neutering))
+            .route(
+                "/neuterings/{goat_name}",
+                web::delete().to(breeding::delete_neutering),
+            )
+            .route(
+                "/external",
+                web::get().to(external_animals::get_external_animals),
+            )
+            .route(
+                "/external",
+                web::post().to(external_animals::add_external_animal),
+            )
+            .route(
+                "/external/{id}",
+                web::delete().to(external_animals::delete_external_animal),
+            )
+            .route("/kiddings", web::get().to(breeding::get_kiddings))
+            .route("/kiddings", web::post().to(breeding::add_kidding))
+            .route("/services", web::get().to(breeding::get_services))
+            .route("/services", web::post().to(breeding::add_service))
+            .route(
+                "/services/success",
+                web::get().to(breeding::get_service_success),
+            )
+            .route("/heat", web::get().to(breeding::get_heat_predictions))
+            .route(
+                "/recommendations",
+                web::get().to(breeding::get_recommendations),
+            ),
+    );
+    cfg.service(
+        web::scope("/breeds")
+            .route("", web::get().to(breeds::get_breeds))
+            .route("", web::post().to(breeds::add_breed))
+            .route("/{name}", web::delete().to(breeds::delete_breed)),
+    );
+    cfg.service(
+        web::scope("/growth")
+            .route("/weights", web::post().to(growth::add_weight))
+            .route("/weights/{name}", web::get().to(growth::get_weight_history))
+            .service(
+                web::resource("/estimate")
+                    .app_data(web::PayloadConfig::new(growth::MAX_PHOTO_BYTES))
+                    .route(web::post().to(growth::estimate_weight)),
+            )
+            .route("/benchmarks", web::get().to(growth::get_growth_benchmarks)),
+    );
+    cfg.service(
+        web::scope("/milk")
+            .route("/records", web::get().to(milk::get_milk_records))
+            .route("/records", web::post().to(milk::add_milk_record))
+            .route("/lactations", web::get().to(milk::get_lactations)),
+    );
+    cfg.service(web::scope("/nutrition").route("/ration", web::get().to(nutrition::get_ration)));
+    cfg.service(
+        web::scope("/notes")
+            .route("", web::get().to(notes::get_notes))
+            .route("", web::post().to(notes::add_note))
+            .route("/{id}", web::delete().to(notes::delete_note))
+            .service(
+                web::resource("/{id}/photo")
+                    .app_data(web::PayloadConfig::new(notes::MAX_NOTE_PHOTO_BYTES))
+                    .route(web::get().to(notes::get_photo))
+                    .route(web::put().to(notes::set_photo)),
+            ),
+    );
+    cfg.service(
+        web::scope("/attachments")
+            .route("", web::get().to(attachments::get_attachments))
+            .service(
+                web::resource("/voice")
+                    .app_data(web::PayloadConfig::new(MAX_ATTACHMENT_BYTES))
+                    .route(web::post().to(attachments::add_voice_note)),
+            )
+            .route("/{id}", web::get().to(attachments::get_attachment))
+            .route("/{id}", web::delete().to(attachments::delete_attachment)),
+    );
+    cfg.service(
+        web::scope("/workers")
+            .route("", web::get().to(workers::get_workers))
+            .route("", web::post().to(workers::add_worker))
+            .route("/{id}", web::put().to(workers::update_worker))
+            .route("/{id}/pin", web::put().to(workers::set_pin)),
+    );
+    cfg.service(
+        web::scope("/permissions")
+            .route("", web::get().to(permissions::get_permissions))
+            .route("", web::put().to(permissions::update_permissions))
+            .route("/mine", web::get().to(permissions::get_my_permissions)),
+    );
+    cfg.service(
+        web::scope("/notifications")
+            .route("", web::get().to(notifications::get_notifications))
+            .route("/{id}/read", web::put().to(notifications::mark_read)),
+    );
+    cfg.service(
+        web::scope("/spaces")
+            .route("", web::get().to(spaces::get_spaces))
+            .route("", web::post().to(spaces::add_space))
+            .route("/occupancy", web::get().to(spaces::get_occupancy))
+            .route("/{id}/goats", web::put().to(spaces::assign_goats)),
+    );
+    cfg.service(web::scope("/analytics").route(
+        "/feed-efficiency",
+        web::get().to(analytics::get_feed_efficiency),
+    ));
+    cfg.service(
+        web::scope("/health")
+            .route("/incidents", web::get().to(health::get_incidents))
+            .route("/incidents", web::post().to(health::add_incident))
+            .route("/heatmap", web::get().to(health::get_heatmap))
+            .route("/timeline", web::get().to(health::get_timeline)),
+    );
+    cfg.service(web::scope("/traceability").route("", web::get().to(traceability::get_records)));
+    cfg.service(web::scope("/public").route(
+        "/traceability/{token}",
+        web::get().to(traceability::get_shared_record),
+    ));
+    cfg.service(
+        web::scope("/diseases")
+            .route("", web::get().to(diseases::get_diseases))
+            .route("", web::post().to(diseases::add_disease))
+            .route("/compliance", web::get().to(diseases::get_compliance))
+            .route("/{id}/notifiable", web::put().to(diseases::set_notifiable)),
+    );
+    cfg.service(
+        web::scope("/lab-results")
+            .route("", web::get().to(lab_results::get_lab_results))
+            .route("", web::post().to(lab_results::add_lab_result))
+            .service(
+                web::resource("/{id}/report")
+                    .app_data(web::PayloadConfig::new(MAX_ATTACHMENT_BYTES))
+                    .route(web::put().to(lab_results::upload_report))
+                    .route(web::get().to(lab_results::get_report)),
+            ),
+    );
+    cfg.service(
+        web::scope("/vet-visits")
+            .route("", web::get().to(vet_visits::get_visits))
+            .route("", web::post().to(vet_visits::add_visit))
+            .route("/vets", web::get().to(vet_visits::get_vets))
+            .route("/{id}/schedule", web::put().to(vet_visits::schedule_visit))
+            .route("/{id}/complete", web::put().to(vet_visits::complete_visit)),
+    );
+    cfg.service(web::scope("/scoring").route("/goats", web::get().to(scoring::get_goat_scores)));
+    cfg.service(
+        web::scope("/api-keys")
+            .route("", web::get().to(api_keys::get_api_keys))
+            .route("", web::post().to(api_keys::add_api_key))
+            .route("/{id}", web::delete().to(api_keys::revoke_api_key)),
+    );
+    cfg.service(
+        web::scope("/tokens")
+            .route("", web::get().to(tokens::get_tokens))
+            .route("", web::post().to(tokens::add_token))
+            .route("/{id}", web::delete().to(tokens::revoke_token)),
+    );
+    cfg.service(
+        web::scope("/sessions")
+            .route("", web::get().to(sessions::get_sessions))
+            .route("", web::post().to(sessions::sign_in))
+            .route("", web::delete().to(sessions::revoke_other_sessions))
+            .route("/password", web::put().to(sessions::change_password))
+            .route("/oauth", web::get().to(sessions::get_oauth_providers))
+            .route("/oauth/callback", web::post().to(sessions::finish_oauth))
+            .route("/oauth/{provider}", web::post().to(sessions::start_oauth))
+            .route("/{id}", web::delete().to(sessions::revoke_session)),
+    );
+    cfg.service(
+        web::scope("/scale")
+            .route("/readings", web::get().to(scale::get_readings))
+            .route("/readings", web::post().to(scale::add_reading))
+            .route("/readings/{id}/goat", web::put().to(scale::assign_reading)),
+    );
+    cfg.service(
+        web::scope("/water")
+            .route("", web::get().to(water::get_water))
+            .route("/{space_id}/cleaned", web::put().to(water::mark_cleaned)),
+    );
+    cfg.service(
+        web::scope("/sensors")
+            .route("", web::get().to(sensors::get_sensors))
+            .route("", web::post().to(sensors::add_sensor))
+            .route("/readings", web::get().to(sensors::get_readings))
+            .route("/readings", web::post().to(sensors::add_reading))
+            .route("/conditions", web::get().to(sensors::get_conditions))
+            .route(
+                "/{id}/thresholds",
+                web::put().to(sensors::update_thresholds),
+            ),
+    );
+    cfg.service(
+        web::scope("/gps")
+            .route("/positions", web::get().to(gps::get_positions))
+            .route("/positions", web::post().to(gps::add_position))
+            .route("/alerts", web::get().to(gps::get_alerts))
+            .route("/geofences", web::get().to(gps::get_geofences))
+            .route("/geofences", web::post().to(gps::add_geofence))
+            .route("/geofences/{id}", web::delete().to(gps::delete_geofence)),
+    );
+    cfg.service(
+        web::scope("/grazing")
+            .route("/paddocks", web::get().to(grazing::get_paddocks))
+            .route("/paddocks", web::post().to(grazing::add_paddock))
+            .route("/rotation", web::get().to(grazing::get_rotation)),
+    );
+    cfg.service(
+        web::scope("/tenants")
+            .route("", web::get().to(tenants::get_tenants))
+            .route("", web::post().to(tenants::add_tenant)),
+    );
+    cfg.service(
+        web::scope("/reports")
+            .route("/templates", web::get().to(reports::get_templates))
+            .route("/templates", web::post().to(reports::add_template))
+            .route(
+                "/templates/{key}",
+                web::delete().to(reports::delete_template),
+            )
+            .route("/census/{key}", web::get().to(reports::get_census))
+            .route("/tag-sheet", web::post().to(labels::tag_sheet)),
+    );
+    cfg.service(
+        web::scope("/exports")
+            .route("/templates", web::get().to(exports::get_export_templates))
+            .route("/templates", web::post().to(exports::add_export_template))
+            .route(
+                "/templates/{key}",
+                web::delete().to(exports::delete_export_template),
+            )
+            .route("/run/{key}", web::get().to(exports::run_export)),
+    );
+    cfg.service(
+        web::scope("/insurance")
+            .route("/policies", web::get().to(insurance::get_policies))
+            .route("/policies", web::post().to(insurance::add_policy))
+            .route("/alerts", web::get().to(insurance::get_alerts))
+            .route("/claims", web::get().to(insurance::get_claims))
+            .route("/claims/{id}", web::put().to(insurance::update_claim))
+            .route("/policies/{id}", web::put().to(insurance::update_policy))
+            .route("/policies/{id}", web::delete().to(insurance::delete_policy))
+            .route(
+                "/policies/{id}/claims",
+                web::post().to(insurance::add_claim),
+            ),
+    );
+    cfg.service(
+        web::scope("/pricing")
+            .route("/valuations", web::get().to(pricing::get_valuations))
+            .route("/apply", web::post().to(pricing::apply_pricing)),
+    );
+    cfg.service(
+        web::scope("/settings")
+            .route("", web::get().to(settings::get_settings))
+            .route("", web::put().to(settings::update_settings))
+            .route("/read-only", web::put().to(settings::set_read_only)),
+    );
+    cfg.service(web::scope("/stats").route("", web::get().to(stats::get_stats)));
+    cfg.service(web::scope("/search").route("", web::get().to(search::get_search)));
+    cfg.service(web::scope("/activity").route("", web::get().to(activity::get_activity)));
+    cfg.service(
+        web::scope("/jobs")
+            .route("", web::get().to(jobs::get_jobs))
+            .route("/census", web::post().to(jobs::add_census_job))
+            .route("/import", web::post().to(jobs::add_import_job))
+            .route("/cleanup", web::post().to(jobs::add_cleanup_job))
+            .route("/{id}", web::get().to(jobs::get_job))
+            .route("/{id}/result", web::get().to(jobs::get_job_result)),
+    );
+    cfg.service(
+        web::resource("/archive")
+            .app_data(web::PayloadConfig::new(MAX_ARCHIVE_BYTES))
+            .route(web::get().to(archive::get_archive))
+            .route(web::post().to(archive::import_farm_archive)),
+    );
+    cfg.service(web::scope("/events").route("", web::get().to(events::get_events)));
+    cfg.service(web::scope("/retention").route("", web::get().to(retention::get_retention)));
+    cfg.service(
+        web::scope("/trash").route("/{id}/restore", web::post().to(retention::restore_goat)),
+    );
+    cfg.service(web::scope("/data-health").route("", web::get().to(data_health::get_data_health)));
+}
@@ -0,0 +1,299 @@
+//! Background scheduler evaluating recurring reminder rules.
+//!
+//! Every active `ReminderRule` is applied to each goat (or pen, for pen-scoped
+//! rules). When the next occurrence of a rule falls within its lead window a
+//! `Pending` task and a matching reminder are created. Evaluation is idempotent:
+//! running it repeatedly on the same day creates nothing new.
+//!
+//! Insurance policies nearing expiry get a one-off renewal task and reminder
+//! in the same pass, alert rules on herd metrics are checked (see
+//! `crate::alerts`), and once a day the pass queues the cleanup job that
+//! purges data past the farm's retention.
+
+use crate::alerts::evaluate_alerts;
+use crate::db::DbPool;
+use crate::errors::{AppError, ParseEnumError};
+use crate::handlers::settings::farm_today;
+use crate::retention::queue_daily_cleanup;
+use crate::water::evaluate_water;
+use actix_web::rt;
+use actix_web::web;
+use chrono::{Duration, NaiveDate};
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
+use shared::tasks::{ReminderRule, RuleScope};
+use std::time::Duration as StdDuration;
+use tracing::{debug, error, info, trace};
+
+/// Date format used for all task and reminder dates.
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// How many days before expiry an insurance renewal task is created.
+pub const POLICY_RENEWAL_LEAD_DAYS: i64 = 30;
+
+/// Loads all active reminder rules.
+fn active_rules(tx: &Transaction) -> Result<Vec<ReminderRule>, AppError> {
+    let mut stmt = tx.prepare(
+        "SELECT id, name, task_title, scope, interval_days, lead_days, active \
+         FROM reminder_rules WHERE active = 1",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, u32>(4)?,
+                row.get::<_, u32>(5)?,
+                row.get::<_, bool>(6)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    rows.into_iter()
+        .map(
+            |(id, name, task_title, scope, interval_days, lead_days, active)| {
+                let scope = RuleScope::from_str(&scope)
+                    .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "RuleScope")))?;
+                Ok(ReminderRule {
+                    id: Some(id),
+                    name,
+                    task_title,
+                    scope,
+                    interval_days,
+                    lead_days,
+                    active,
+                })
+            },
+        )
+        .collect()
+}
+
+/// Lists the `(id, name)` targets a rule applies to.
+fn rule_targets(tx: &Transaction, scope: &RuleScope) -> Result<Vec<(i64, String)>, AppError> {
+    let sql = match scope {
+        RuleScope::Goat => "SELECT id, name FROM goats WHERE deleted_at IS NULL",
+        RuleScope::Pen => "SELECT id, name FROM spaces WHERE type = 'enclosure'",
+    };
+    let mut stmt = tx.prepare(sql)?;
+    let targets = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(targets)
+}
+
+/// Computes the next due date for a rule/target pair, if a task should be
+/// created for it today.
+///
+/// The first occurrence is due immediately. Later occurrences are due
+/// `interval_days` after the most recent generated task, but never in the past
+/// so that a long pause does not produce a backlog of stale tasks.
+pub fn next_due_date(
+    last_due: Option<NaiveDate>,
+    interval_days: u32,
+    lead_days: u32,
+    today: NaiveDate,
+) -> Option<NaiveDate> {
+    let next = match last_due {
+        None => today,
+        Some(last) => (last + Duration::days(interval_days as i64)).max(today),
+    };
+    if next - Duration::days(lead_days as i64) <= today {
+        Some(next)
+    } else {
+        None
+    }
+}
+
+/// Evaluates every active rule against the herd and creates due tasks and reminders.
+///
+/// # Returns
+/// The number of tasks created.
+///
+/// # Errors
+/// Returns database errors, or a parse error if a stored date or scope is malformed.
+///
+/// # Logging
+/// Debug-level summary per rule, trace-level per created task.
+pub fn evaluate_rules(conn: &mut Connection, today: NaiveDate) -> Result<usize, AppError> {
+    let tx = conn.transaction()?;
+    let mut created = 0;
+
+    for rule in active_rules(&tx)? {
+        let rule_id = rule.id.unwrap_or_default();
+        let target_column = match rule.scope {
+            RuleScope::Goat => "goat_id",
+            RuleScope::Pen => "space_id",
+        };
+
+        for (target_id, target_name) in rule_targets(&tx, &rule.scope)? {
+            let last_due: Option<String> = tx
+                .query_row(
+                    &format!(
+                        "SELECT MAX(due_date) FROM tasks WHERE rule_id = ?1 AND {} = ?2",
+                        target_column
+                    ),
+                    params![rule_id, target_id],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .flatten();
+            let last_due = last_due
+                .map(|d| {
+                    NaiveDate::parse_from_str(&d, DATE_FORMAT).map_err(|_| {
+                        AppError::InvalidInput(format!("Malformed task due date '{}'", d))
+                    })
+                })
+                .transpose()?;
+
+            let Some(due) = next_due_date(last_due, rule.interval_days, rule.lead_days, today)
+            else {
+                continue;
+            };
+            let due_str = due.format(DATE_FORMAT).to_string();
+
+            tx.execute(
+                &format!(
+                    "INSERT INTO tasks (title, {}, rule_id, due_date, status) VALUES (?1, ?2, ?3, ?4, 'Pending')",
+                    target_column
+                ),
+                params![rule.task_title, target_id, rule_id, due_str],
+            )?;
+            let task_id = tx.last_insert_rowid();
+
+            let remind_on = (due - Duration::days(rule.lead_days as i64))
+                .format(DATE_FORMAT)
+                .to_string();
+            tx.execute(
+                "INSERT INTO reminders (task_id, remind_on, message) VALUES (?1, ?2, ?3)",
+                params![
+                    task_id,
+                    remind_on,
+                    format!("{} for {} due on {}", rule.task_title, target_name, due_str)
+                ],
+            )?;
+            trace!(rule_id, task_id, target = %target_name, due = %due_str, "Created task from rule");
+            created += 1;
+        }
+        debug!(rule_id, rule = %rule.name, "Evaluated reminder rule");
+    }
+
+    tx.commit()?;
+    Ok(created)
+}
+
+/// Creates a renewal task and reminder for each insurance policy expiring
+/// within `POLICY_RENEWAL_LEAD_DAYS`. Lapsed policies, policies already
+/// reminded about and policies superseded by a later one for the same goat
+/// are skipped.
+///
+/// # Returns
+/// The number of renewal tasks created.
+pub fn evaluate_policy_renewals(
+    conn: &mut Connection,
+    today: NaiveDate,
+) -> Result<usize, AppError> {
+    let tx = conn.transaction()?;
+    let horizon = today + Duration::days(POLICY_RENEWAL_LEAD_DAYS);
+    let due: Vec<(i64, i64, String, String, String, String)> = {
+        let mut stmt = tx.prepare(
+            "SELECT p.id, p.goat_id, g.name, p.provider, p.policy_number, p.expiry_date \
+             FROM insurance_policies p JOIN goats g ON g.id = p.goat_id AND g.deleted_at IS NULL \
+             WHERE p.renewal_task_id IS NULL AND p.expiry_date >= ?1 AND p.expiry_date <= ?2 \
+             AND NOT EXISTS (SELECT 1 FROM insurance_policies later \
+                             WHERE later.goat_id = p.goat_id AND later.expiry_date > p.expiry_date)",
+        )?;
+        stmt.query_map(
+            params![
+                today.format(DATE_FORMAT).to_string(),
+                horizon.format(DATE_FORMAT).to_string()
+            ],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                ))
+            },
+        )?
+        .collect::<Result<_, _>>()?
+    };
+
+    for (policy_id, goat_id, goat_name, provider, policy_number, expiry) in &due {
+        let expiry_date = NaiveDate::parse_from_str(expiry, DATE_FORMAT).map_err(|_| {
+            AppError::InvalidInput(format!("Malformed policy expiry date '{}'", expiry))
+        })?;
+        tx.execute(
+            "INSERT INTO tasks (title, goat_id, due_date, status) VALUES (?1, ?2, ?3, 'Pending')",
+            params![
+                format!("Renew insurance policy {}", policy_number),
+                goat_id,
+                expiry
+            ],
+        )?;
+        let task_id = tx.last_insert_rowid();
+        let remind_on = (expiry_date - Duration::days(POLICY_RENEWAL_LEAD_DAYS))
+            .format(DATE_FORMAT)
+            .to_string();
+        tx.execute(
+            "INSERT INTO reminders (task_id, remind_on, message) VALUES (?1, ?2, ?3)",
+            params![
+                task_id,
+                remind_on,
+                format!(
+                    "{} insurance policy {} for {} expires on {}",
+                    provider, policy_number, goat_name, expiry
+                )
+            ],
+        )?;
+        tx.execute(
+            "UPDATE insurance_policies SET renewal_task_id = ?1 WHERE id = ?2",
+            params![task_id, policy_id],
+        )?;
+        trace!(policy_id, task_id, goat = %goat_name, expiry = %expiry, "Created policy renewal task");
+    }
+
+    tx.commit()?;
+    debug!(created = due.len(), "Evaluated insurance policy renewals");
+    Ok(due.len())
+}
+
+/// Spawns a background task that evaluates reminder rules, insurance
+/// policy renewals, pen water use and alert rules every `every`, and queues
+/// the daily cleanup job.
+///
+/// Must be called from within the Actix system (e.g. in `main` after startup).
+/// Failures are logged and retried on the next tick.
+pub fn spawn_scheduler(db: DbPool, every: StdDuration) {
+    rt::spawn(async move {
+        let mut ticker = rt::time::interval(every);
+        loop {
+            ticker.tick().await;
+            let db = db.clone();
+            let result = web::block(move || {
+                let mut conn = db.get_conn()?;
+                let today = farm_today(&conn)?;
+                queue_daily_cleanup(&conn)?;
+                let fired = evaluate_alerts(&mut conn, today)?;
+                if fired > 0 {
+                    info!(fired, "Alert rules fired");
+                }
+                Ok::<_, AppError>(
+                    evaluate_rules(&mut conn, today)?
+                        + evaluate_policy_renewals(&mut conn, today)?
+                        + evaluate_water(&mut conn, today)?,
+                )
+            })
+            .await;
+
+            match result {
+                Ok(Ok(created)) => info!(created, "Reminder rules evaluated"),
+                Ok(Err(e)) => error!("Reminder rule evaluation failed: {}", e),
+                Err(e) => error!("Reminder rule evaluation could not run: {}", e),
+            }
+        }
+    });
+}
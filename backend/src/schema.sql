@@ -12,9 +12,29 @@ CREATE TABLE IF NOT EXISTS goats (
     diet TEXT,
     last_bred DATE,
     health_status TEXT,
-    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    sire_id INTEGER REFERENCES goats(id) ON DELETE SET NULL,
+    dam_id INTEGER REFERENCES goats(id) ON DELETE SET NULL,
+    date_of_birth DATE,
+    space_id INTEGER REFERENCES spaces(id) ON DELETE SET NULL,
+    tag_id TEXT,
+    updated_at TIMESTAMP,
+    neutered_on DATE,
+    neutered_reason TEXT,
+    horns TEXT,
+    coat_color TEXT,
+    marks TEXT,
+    lifecycle_stage TEXT,
+    sire_external_id INTEGER REFERENCES external_animals(id) ON DELETE SET NULL,
+    dam_external_id INTEGER REFERENCES external_animals(id) ON DELETE SET NULL,
+    quick_note TEXT,
+    -- Set when the goat is deleted; the retention cleanup purges it later
+    deleted_at TIMESTAMP
 );
 
+CREATE UNIQUE INDEX IF NOT EXISTS idx_goats_tag_id ON goats(tag_id) WHERE deleted_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_goats_deleted ON goats(deleted_at);
+
 -- Vaccines master table
 CREATE TABLE IF NOT EXISTS vaccines (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -24,13 +44,16 @@ CREATE TABLE IF NOT EXISTS vaccines (
 -- Diseases master table
 CREATE TABLE IF NOT EXISTS diseases (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
-    name TEXT UNIQUE NOT NULL
+    name TEXT UNIQUE NOT NULL,
+    -- Whether cases must be reported to authorities (see shared::diseases)
+    notifiable INTEGER NOT NULL DEFAULT 0
 );
 
 -- Join table for goats and vaccines (many-to-many)
 CREATE TABLE IF NOT EXISTS goat_vaccines (
     goat_id INTEGER NOT NULL,
     vaccine_id INTEGER NOT NULL,
+    given_on DATE,
     PRIMARY KEY (goat_id, vaccine_id),
     FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE,
     FOREIGN KEY (vaccine_id) REFERENCES vaccines(id) ON DELETE CASCADE
@@ -53,6 +76,8 @@ CREATE TABLE IF NOT EXISTS workers (
     leaves INTEGER DEFAULT 0,
     role TEXT,
     contact TEXT,
+    notify_by TEXT NOT NULL DEFAULT 'App',
+    pin_hash TEXT,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
@@ -67,7 +92,8 @@ CREATE TABLE IF NOT EXISTS equipment (
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
--- Sensors table
+-- Sensors table; readings outside min/max_threshold raise an alert, and
+-- goat_id links a wearable activity tag to the goat wearing it
 CREATE TABLE IF NOT EXISTS sensors (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     sensor_type TEXT NOT NULL,
@@ -75,6 +101,11 @@ CREATE TABLE IF NOT EXISTS sensors (
     last_reading REAL,
     last_reading_time TIMESTAMP,
     status TEXT,
+    name TEXT,
+    space_id INTEGER REFERENCES spaces(id) ON DELETE SET NULL,
+    min_threshold REAL,
+    max_threshold REAL,
+    goat_id INTEGER REFERENCES goats(id) ON DELETE SET NULL,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
@@ -84,7 +115,668 @@ CREATE TABLE IF NOT EXISTS spaces (
     name TEXT NOT NULL,
     type TEXT CHECK(type IN ('enclosure', 'grazing_field', 'other')),
     capacity INTEGER,
+    area_m2 REAL,
     grass_condition TEXT,
     health TEXT,
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
+
+-- Recurring reminder rules evaluated by the backend scheduler
+CREATE TABLE IF NOT EXISTS reminder_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    task_title TEXT NOT NULL,
+    scope TEXT CHECK(scope IN ('Goat', 'Pen')) NOT NULL,
+    interval_days INTEGER NOT NULL CHECK(interval_days > 0),
+    lead_days INTEGER NOT NULL DEFAULT 0,
+    active INTEGER NOT NULL DEFAULT 1,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Farm tasks, either entered manually or generated from a reminder rule
+CREATE TABLE IF NOT EXISTS tasks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title TEXT NOT NULL,
+    notes TEXT,
+    goat_id INTEGER,
+    space_id INTEGER,
+    rule_id INTEGER,
+    due_date DATE NOT NULL,
+    status TEXT CHECK(status IN ('Pending', 'Done', 'Skipped')) NOT NULL DEFAULT 'Pending',
+    completed_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (space_id) REFERENCES spaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (rule_id) REFERENCES reminder_rules(id) ON DELETE SET NULL
+);
+
+-- Reminders attached to tasks
+CREATE TABLE IF NOT EXISTS reminders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL,
+    remind_on DATE NOT NULL,
+    message TEXT NOT NULL,
+    dismissed INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+-- Stocked supplies: feed, medicines, equipment
+CREATE TABLE IF NOT EXISTS inventory_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    category TEXT CHECK(category IN ('Feed', 'Medicine', 'Equipment', 'Other')) NOT NULL,
+    quantity REAL NOT NULL DEFAULT 0,
+    unit TEXT NOT NULL,
+    unit_cost REAL NOT NULL DEFAULT 0,
+    expiry_date DATE,
+    reorder_level REAL NOT NULL DEFAULT 0,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Consumption log tied to treatments and feedings
+CREATE TABLE IF NOT EXISTS inventory_consumption (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id INTEGER NOT NULL,
+    quantity REAL NOT NULL CHECK(quantity > 0),
+    purpose TEXT CHECK(purpose IN ('Treatment', 'Feeding', 'Other')) NOT NULL,
+    goat_id INTEGER,
+    consumed_on DATE NOT NULL,
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    space_id INTEGER REFERENCES spaces(id) ON DELETE SET NULL,
+    FOREIGN KEY (item_id) REFERENCES inventory_items(id) ON DELETE CASCADE,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE SET NULL
+);
+
+-- Income and expense ledger; consumption costs are allocated here automatically
+CREATE TABLE IF NOT EXISTS transactions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT CHECK(kind IN ('Income', 'Expense')) NOT NULL,
+    category TEXT NOT NULL,
+    amount REAL NOT NULL CHECK(amount >= 0),
+    description TEXT NOT NULL DEFAULT '',
+    goat_id INTEGER,
+    space_id INTEGER,
+    consumption_id INTEGER,
+    date DATE NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    -- NULL currency means the farm's base currency; exchange_rate converts into it
+    currency TEXT,
+    exchange_rate REAL CHECK(exchange_rate > 0),
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE SET NULL,
+    FOREIGN KEY (space_id) REFERENCES spaces(id) ON DELETE SET NULL,
+    FOREIGN KEY (consumption_id) REFERENCES inventory_consumption(id) ON DELETE CASCADE
+);
+
+-- Crash reports submitted by the frontend
+CREATE TABLE IF NOT EXISTS client_errors (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    section TEXT,
+    message TEXT NOT NULL,
+    url TEXT,
+    user_agent TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Kidding outcomes used to score breeding pairs
+CREATE TABLE IF NOT EXISTS kidding_records (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    doe_id INTEGER NOT NULL,
+    buck_id INTEGER,
+    kidded_on DATE NOT NULL,
+    kids_born INTEGER NOT NULL CHECK(kids_born >= 0),
+    kids_alive INTEGER NOT NULL CHECK(kids_alive >= 0 AND kids_alive <= kids_born),
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    external_buck_id INTEGER REFERENCES external_animals(id) ON DELETE SET NULL,
+    FOREIGN KEY (doe_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (buck_id) REFERENCES goats(id) ON DELETE SET NULL
+);
+
+-- Weighings used to benchmark growth against breed standards
+CREATE TABLE IF NOT EXISTS weight_records (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL,
+    weighed_on DATE NOT NULL,
+    weight REAL NOT NULL CHECK(weight > 0),
+    estimated INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_weight_records_goat ON weight_records(goat_id, weighed_on);
+
+-- Milkings used for lactation analytics
+CREATE TABLE IF NOT EXISTS milk_records (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    doe_id INTEGER NOT NULL,
+    recorded_on DATE NOT NULL,
+    yield_litres REAL NOT NULL CHECK(yield_litres >= 0),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (doe_id) REFERENCES goats(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_milk_records_doe ON milk_records(doe_id, recorded_on);
+
+-- Dated health incidents, located at the pen the goat was in when observed
+CREATE TABLE IF NOT EXISTS health_incidents (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL,
+    space_id INTEGER,
+    kind TEXT NOT NULL CHECK(kind IN ('Disease', 'Parasite', 'Injury', 'Other')),
+    condition TEXT NOT NULL,
+    observed_on DATE NOT NULL,
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (space_id) REFERENCES spaces(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_health_incidents_observed ON health_incidents(observed_on);
+
+-- Keys authenticating devices (e.g. weigh scales) that push data; only a
+-- SHA-256 hash of each key is stored
+CREATE TABLE IF NOT EXISTS api_keys (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    revoked INTEGER NOT NULL DEFAULT 0,
+    last_used_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Raw weigh-scale readings by RFID tag, linked to the goat and weight record
+-- once the tag is matched
+CREATE TABLE IF NOT EXISTS scale_readings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    api_key_id INTEGER,
+    tag_id TEXT NOT NULL,
+    weight REAL NOT NULL CHECK(weight > 0),
+    read_at TIMESTAMP NOT NULL,
+    goat_id INTEGER,
+    weight_record_id INTEGER,
+    FOREIGN KEY (api_key_id) REFERENCES api_keys(id) ON DELETE SET NULL,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE SET NULL,
+    FOREIGN KEY (weight_record_id) REFERENCES weight_records(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_scale_readings_tag ON scale_readings(tag_id);
+
+-- Time series pushed by environmental sensors (temperature, humidity)
+CREATE TABLE IF NOT EXISTS sensor_readings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sensor_id INTEGER NOT NULL,
+    value REAL NOT NULL,
+    read_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (sensor_id) REFERENCES sensors(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_sensor_readings_sensor ON sensor_readings(sensor_id, read_at);
+
+-- Grazing areas; boundary is a JSON array of {lat, lon} corners
+CREATE TABLE IF NOT EXISTS geofences (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    boundary TEXT NOT NULL
+);
+
+-- Positions pushed by GPS collars, matched to goats by tag
+CREATE TABLE IF NOT EXISTS goat_positions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL,
+    lat REAL NOT NULL,
+    lon REAL NOT NULL,
+    recorded_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_goat_positions_goat ON goat_positions(goat_id, recorded_at);
+
+-- Farm-defined census report layouts; definition is a JSON ReportTemplate
+CREATE TABLE IF NOT EXISTS report_templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    template_key TEXT NOT NULL UNIQUE,
+    definition TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Per-goat insurance cover; renewal_task_id is the renewal reminder task, once created
+CREATE TABLE IF NOT EXISTS insurance_policies (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL,
+    provider TEXT NOT NULL,
+    policy_number TEXT NOT NULL,
+    sum_insured REAL NOT NULL CHECK(sum_insured > 0),
+    start_date DATE,
+    expiry_date DATE NOT NULL,
+    notes TEXT,
+    renewal_task_id INTEGER,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (provider, policy_number),
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (renewal_task_id) REFERENCES tasks(id) ON DELETE SET NULL
+);
+
+-- Claims log for insurance policies
+CREATE TABLE IF NOT EXISTS insurance_claims (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    policy_id INTEGER NOT NULL,
+    claim_date DATE NOT NULL,
+    reason TEXT NOT NULL,
+    amount_claimed REAL NOT NULL CHECK(amount_claimed > 0),
+    amount_settled REAL,
+    status TEXT CHECK(status IN ('Filed', 'Approved', 'Rejected', 'Settled')) NOT NULL DEFAULT 'Filed',
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (policy_id) REFERENCES insurance_policies(id) ON DELETE CASCADE
+);
+
+-- Farm-wide settings as key/value pairs (see shared::settings::FarmSettings)
+CREATE TABLE IF NOT EXISTS farm_settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+-- GST rates selectable for sales
+CREATE TABLE IF NOT EXISTS tax_rates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    rate_percent REAL NOT NULL CHECK(rate_percent >= 0 AND rate_percent <= 100),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Invoice details of sale transactions; tax_percent is the rate applied at the time
+CREATE TABLE IF NOT EXISTS sale_details (
+    transaction_id INTEGER PRIMARY KEY,
+    invoice_number TEXT UNIQUE,
+    buyer_name TEXT,
+    buyer_gstin TEXT,
+    tax_rate_id INTEGER,
+    tax_percent REAL NOT NULL DEFAULT 0,
+    FOREIGN KEY (transaction_id) REFERENCES transactions(id) ON DELETE CASCADE,
+    FOREIGN KEY (tax_rate_id) REFERENCES tax_rates(id) ON DELETE SET NULL
+);
+
+-- Standing monthly budget per expense category, in the base currency
+CREATE TABLE IF NOT EXISTS budgets (
+    category TEXT PRIMARY KEY,
+    monthly_amount REAL NOT NULL CHECK(monthly_amount >= 0),
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Breeds added to the catalog on top of the built-in `Breed` variants
+CREATE TABLE IF NOT EXISTS breed_catalog (
+    name TEXT PRIMARY KEY COLLATE NOCASE,
+    purpose TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Bloodline and genetic trait tags per goat, e.g. "High twinning"
+CREATE TABLE IF NOT EXISTS genetic_tags (
+    goat_id INTEGER NOT NULL,
+    tag TEXT NOT NULL COLLATE NOCASE,
+    PRIMARY KEY (goat_id, tag),
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE
+);
+
+-- Grazing paddocks and the days each must rest between grazings
+CREATE TABLE IF NOT EXISTS paddocks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    area_m2 REAL,
+    capacity INTEGER,
+    rest_days INTEGER NOT NULL,
+    last_grazed_on DATE
+);
+
+-- Observations left on a goat by workers, with an optional photo
+CREATE TABLE IF NOT EXISTS goat_notes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL,
+    author TEXT NOT NULL,
+    text TEXT NOT NULL,
+    photo BLOB,
+    photo_type TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_goat_notes_goat ON goat_notes(goat_id, created_at);
+
+-- In-app notifications for workers, e.g. when mentioned in a goat's note
+-- or when an alert rule fires (without a goat)
+CREATE TABLE IF NOT EXISTS notifications (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    worker_id INTEGER NOT NULL,
+    goat_id INTEGER,
+    note_id INTEGER,
+    message TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    read INTEGER NOT NULL DEFAULT 0,
+    -- when the notification went out in an email or SMS digest
+    digested_at TIMESTAMP,
+    FOREIGN KEY (worker_id) REFERENCES workers(id) ON DELETE CASCADE,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (note_id) REFERENCES goat_notes(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_worker ON notifications(worker_id, read);
+
+-- Files attached to a goat or a task, such as voice notes
+CREATE TABLE IF NOT EXISTS attachments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER,
+    task_id INTEGER,
+    author TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    data BLOB NOT NULL,
+    duration_secs REAL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((goat_id IS NULL) <> (task_id IS NULL)),
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_attachments_goat ON attachments(goat_id);
+
+CREATE INDEX IF NOT EXISTS idx_attachments_task ON attachments(task_id);
+
+-- Append-only log of domain events (see shared::events), written while the
+-- backend runs event-sourced. goat_id has no foreign key, so events about a
+-- deleted goat stay in the log. actor says who made the change. Goat
+-- registrations and updates are logged for field history even without
+-- event sourcing, with sourced = 0, and left out of the event feed.
+CREATE TABLE IF NOT EXISTS events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    goat_id INTEGER NOT NULL,
+    payload TEXT NOT NULL,
+    recorded_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    actor TEXT,
+    sourced INTEGER NOT NULL DEFAULT 1
+);
+
+CREATE INDEX IF NOT EXISTS idx_events_goat ON events(goat_id);
+
+-- Background jobs (see backend::jobs). payload is the JSON JobRequest; a
+-- failed attempt is retried from run_after until max_attempts is reached.
+CREATE TABLE IF NOT EXISTS jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'queued',
+    description TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    done INTEGER NOT NULL DEFAULT 0,
+    total INTEGER NOT NULL DEFAULT 0,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 1,
+    run_after TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    error TEXT,
+    result BLOB,
+    result_type TEXT,
+    result_name TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_jobs_due ON jobs(status, run_after);
+
+-- Deleted goats, restorable until the cleanup job purges them (see
+-- backend::retention). payload is the JSON Goat with its links as deleted.
+CREATE TABLE IF NOT EXISTS goat_trash (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    deleted_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_goat_trash_deleted ON goat_trash(deleted_at);
+CREATE INDEX IF NOT EXISTS idx_events_recorded ON events(recorded_at);
+
+-- Devices signed in to the dashboard (see backend::auth). Only the hash of
+-- each token is kept; revoked sessions stay with revoked_at set.
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    device TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    user_agent TEXT,
+    ip TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_seen_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    revoked_at TIMESTAMP,
+    worker_id INTEGER REFERENCES workers(id) ON DELETE SET NULL
+);
+
+-- Accounts at OAuth providers (e.g. Google), linked by email to the worker
+-- who signs in with them.
+CREATE TABLE IF NOT EXISTS oauth_identities (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    provider TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    email TEXT,
+    worker_id INTEGER NOT NULL REFERENCES workers(id) ON DELETE CASCADE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (provider, subject)
+);
+
+-- OAuth sign-ins under way, by the hash of the state sent to the provider.
+CREATE TABLE IF NOT EXISTS oauth_states (
+    state_hash TEXT PRIMARY KEY,
+    provider TEXT NOT NULL,
+    redirect_uri TEXT NOT NULL,
+    device TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- What the workers of each role may do (see shared::permissions). granted
+-- is the JSON list of permissions; roles without a row may do anything.
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role TEXT PRIMARY KEY,
+    granted TEXT NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Personal access tokens for scripts (see backend::auth). Only the hash of
+-- each token is kept; modules is the JSON list the token is limited to,
+-- empty for all of them.
+CREATE TABLE IF NOT EXISTS access_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    read_only INTEGER NOT NULL DEFAULT 0,
+    modules TEXT NOT NULL DEFAULT '[]',
+    worker_id INTEGER REFERENCES workers(id) ON DELETE CASCADE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_used_at TIMESTAMP,
+    revoked_at TIMESTAMP
+);
+
+-- Alert rules on herd metrics (see backend::alerts); firing is whether the
+-- condition held at the last check, so each rule notifies once per breach.
+CREATE TABLE IF NOT EXISTS alert_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    metric TEXT NOT NULL,
+    condition TEXT NOT NULL,
+    threshold REAL NOT NULL,
+    active INTEGER NOT NULL DEFAULT 1,
+    firing INTEGER NOT NULL DEFAULT 0,
+    last_fired_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Moves between lifecycle stages (see shared::lifecycle); from_stage is NULL
+-- when the goat entered the lifecycle.
+CREATE TABLE IF NOT EXISTS lifecycle_changes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL,
+    from_stage TEXT,
+    to_stage TEXT NOT NULL,
+    changed_on DATE NOT NULL,
+    note TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (goat_id) REFERENCES goats(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_lifecycle_changes_goat ON lifecycle_changes(goat_id);
+
+-- Services (matings) of each doe, natural or by AI, with the outside sire
+-- when the semen or buck came from outside the herd. Outcomes are worked
+-- out from kidding_records (see backend::breeding).
+CREATE TABLE IF NOT EXISTS breeding_services (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    doe_id INTEGER NOT NULL,
+    served_on DATE NOT NULL,
+    method TEXT CHECK(method IN ('Natural', 'Artificial')) NOT NULL,
+    buck_id INTEGER,
+    outside_sire_name TEXT,
+    outside_sire_breed TEXT,
+    outside_sire_registration TEXT,
+    outside_sire_source TEXT,
+    straw_id TEXT,
+    technician TEXT,
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    external_sire_id INTEGER REFERENCES external_animals(id) ON DELETE SET NULL,
+    FOREIGN KEY (doe_id) REFERENCES goats(id) ON DELETE CASCADE,
+    FOREIGN KEY (buck_id) REFERENCES goats(id) ON DELETE SET NULL
+);
+CREATE INDEX IF NOT EXISTS idx_breeding_services_doe ON breeding_services(doe_id, served_on);
+
+-- Animals kept off the farm (e.g. a neighbour's buck) that pedigrees and
+-- breeding records name without adding them to the herd.
+CREATE TABLE IF NOT EXISTS external_animals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    gender TEXT CHECK(gender IN ('Male', 'Female')) NOT NULL,
+    breed TEXT,
+    registration TEXT,
+    owner TEXT,
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Saved goat-list exports; definition is a JSON ExportTemplate
+CREATE TABLE IF NOT EXISTS export_templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    template_key TEXT NOT NULL UNIQUE,
+    definition TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Vet visits, booked with a worker whose role is a vet's (see shared::vet)
+CREATE TABLE IF NOT EXISTS vet_visits (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    vet_id INTEGER REFERENCES workers(id) ON DELETE SET NULL,
+    status TEXT NOT NULL CHECK(status IN ('Requested', 'Scheduled', 'Completed')),
+    reason TEXT NOT NULL,
+    requested_on DATE NOT NULL,
+    scheduled_for DATE,
+    completed_on DATE,
+    summary TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_vet_visits_vet ON vet_visits(vet_id, scheduled_for);
+
+-- Goats on a vet visit's agenda, linked to the health incident recorded
+-- from the vet's findings
+CREATE TABLE IF NOT EXISTS vet_visit_goats (
+    visit_id INTEGER NOT NULL REFERENCES vet_visits(id) ON DELETE CASCADE,
+    goat_id INTEGER NOT NULL REFERENCES goats(id) ON DELETE CASCADE,
+    incident_id INTEGER REFERENCES health_incidents(id) ON DELETE SET NULL,
+    PRIMARY KEY (visit_id, goat_id)
+);
+
+-- Lab test results of goats' samples, with the lab's PDF report if
+-- uploaded (see shared::lab)
+CREATE TABLE IF NOT EXISTS lab_results (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER NOT NULL REFERENCES goats(id) ON DELETE CASCADE,
+    test_type TEXT NOT NULL,
+    sampled_on DATE NOT NULL,
+    lab TEXT,
+    notes TEXT,
+    report BLOB,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_lab_results_goat ON lab_results(goat_id, sampled_on);
+
+-- Measured values of a lab result, each with its reference range
+CREATE TABLE IF NOT EXISTS lab_values (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    result_id INTEGER NOT NULL REFERENCES lab_results(id) ON DELETE CASCADE,
+    analyte TEXT NOT NULL,
+    value REAL NOT NULL,
+    unit TEXT NOT NULL,
+    low REAL,
+    high REAL
+);
+
+-- Traceability records of sold goats, shared with the buyer through a
+-- public link by token. record is the JSON TraceabilityRecord as it stood at
+-- the sale (see shared::traceability).
+CREATE TABLE IF NOT EXISTS traceability_records (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    goat_id INTEGER REFERENCES goats(id) ON DELETE SET NULL,
+    transaction_id INTEGER NOT NULL UNIQUE REFERENCES transactions(id) ON DELETE CASCADE,
+    token TEXT NOT NULL UNIQUE,
+    record TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_traceability_records_goat ON traceability_records(goat_id);
+
+-- Movements of goats between farms, markets and buyers, with transport
+-- details, for the movement register (see shared::movements)
+CREATE TABLE IF NOT EXISTS goat_movements (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    from_kind TEXT NOT NULL CHECK(from_kind IN ('Farm', 'Market', 'Buyer')),
+    from_name TEXT NOT NULL,
+    to_kind TEXT NOT NULL CHECK(to_kind IN ('Farm', 'Market', 'Buyer')),
+    to_name TEXT NOT NULL,
+    departed_on DATE NOT NULL,
+    arrived_on DATE,
+    transporter TEXT,
+    vehicle TEXT,
+    permit_number TEXT,
+    notes TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_goat_movements_departed ON goat_movements(departed_on);
+
+-- Goats carried by each movement, with their name and ear tag as they
+-- were when moved. Kept when a goat is removed, as the register is kept
+-- by law.
+CREATE TABLE IF NOT EXISTS goat_movement_goats (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    movement_id INTEGER NOT NULL REFERENCES goat_movements(id) ON DELETE CASCADE,
+    goat_id INTEGER REFERENCES goats(id) ON DELETE SET NULL,
+    goat_name TEXT NOT NULL,
+    tag_id TEXT,
+    UNIQUE (movement_id, goat_id)
+);
+CREATE INDEX IF NOT EXISTS idx_goat_movement_goats_goat ON goat_movement_goats(goat_id);
+
+-- Last number assigned in each stem of the farm's tag series, e.g.
+-- "YG-2026-" (see shared::tags)
+CREATE TABLE IF NOT EXISTS tag_counters (
+    stem TEXT PRIMARY KEY,
+    last_number INTEGER NOT NULL
+);
+
+-- The owner's password for signing in, hashed; workers sign in with the
+-- PIN in workers.pin_hash
+CREATE TABLE IF NOT EXISTS owner_credentials (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    password_hash TEXT NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- A session without a worker is the owner's, so a removed worker's
+-- sessions are signed out before their worker_id is cleared
+CREATE TRIGGER IF NOT EXISTS revoke_sessions_of_removed_worker
+BEFORE DELETE ON workers
+BEGIN
+    UPDATE sessions SET revoked_at = CURRENT_TIMESTAMP
+    WHERE worker_id = OLD.id AND revoked_at IS NULL;
+END;
@@ -0,0 +1,161 @@
+//! Goat scoring model behind the keep/cull helper.
+//!
+//! Raw metrics are gathered per goat from the milk, growth, breeding, health
+//! and finance data, rated 0–100 against the rest of the herd, and combined
+//! with the caller's weights (see `shared::scoring`).
+
+use crate::breeding::load_kidding_history;
+use crate::errors::{AppError, ParseEnumError};
+use crate::handlers::finance::load_profitability;
+use crate::handlers::growth::load_benchmarks;
+use crate::lactation::load_lactations;
+use crate::scheduler::DATE_FORMAT;
+use chrono::{Days, NaiveDate};
+use rusqlite::Connection;
+use shared::Gender;
+use shared::scoring::{GoatScore, Recommendation, ScoreBreakdown, ScoreWeights};
+use std::collections::HashMap;
+use tracing::{debug, trace};
+
+/// Health incidents are counted over this many days before today.
+pub const HEALTH_WINDOW_DAYS: u64 = 365;
+
+/// Raw, unscaled metrics for one goat. `None` means no data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoatMetrics {
+    pub name: String,
+    pub gender: Gender,
+    /// Average daily yield of the latest lactation, in litres.
+    pub milk: Option<f64>,
+    /// Weight percentile against the breed standard.
+    pub growth: Option<f64>,
+    /// Live kids per kidding, as dam or sire.
+    pub fertility: Option<f64>,
+    /// Incidents within `HEALTH_WINDOW_DAYS`; fewer is better.
+    pub health_incidents: f64,
+    pub profit: Option<f64>,
+}
+
+/// Rates each value 0–100 by its position between the herd's lowest and
+/// highest value, inverted when lower is better. When every goat has the
+/// same value they all rate 50.
+pub fn rate(values: &[Option<f64>], higher_is_better: bool) -> Vec<Option<f64>> {
+    let present = values.iter().flatten();
+    let min = present.clone().copied().fold(f64::INFINITY, f64::min);
+    let max = present.copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|value| {
+            value.map(|v| {
+                if max <= min {
+                    return 50.0;
+                }
+                let position = (v - min) / (max - min) * 100.0;
+                if higher_is_better {
+                    position
+                } else {
+                    100.0 - position
+                }
+            })
+        })
+        .collect()
+}
+
+/// Scores every goat, lowest score (strongest cull candidate) first.
+///
+/// Goats with no data for any weighted metric are left out.
+pub fn score_goats(metrics: &[GoatMetrics], weights: &ScoreWeights) -> Vec<GoatScore> {
+    let column = |f: fn(&GoatMetrics) -> Option<f64>| metrics.iter().map(f).collect::<Vec<_>>();
+    let milk = rate(&column(|m| m.milk), true);
+    let growth = rate(&column(|m| m.growth), true);
+    let fertility = rate(&column(|m| m.fertility), true);
+    let health = rate(&column(|m| Some(m.health_incidents)), false);
+    let profit = rate(&column(|m| m.profit), true);
+
+    let mut scores: Vec<GoatScore> = metrics
+        .iter()
+        .enumerate()
+        .filter_map(|(i, m)| {
+            let breakdown = ScoreBreakdown {
+                milk: milk[i],
+                growth: growth[i],
+                fertility: fertility[i],
+                health: health[i],
+                profit: profit[i],
+            };
+            let score = breakdown.weighted(weights)?;
+            trace!(goat = %m.name, score, "Scored goat");
+            Some(GoatScore {
+                goat_name: m.name.clone(),
+                gender: m.gender.clone(),
+                score,
+                breakdown,
+                recommendation: Recommendation::for_score(score),
+            })
+        })
+        .collect();
+    scores.sort_by(|a, b| a.score.total_cmp(&b.score));
+    scores
+}
+
+/// Gathers the raw metrics of every goat as of `today`.
+pub fn load_metrics(conn: &Connection, today: NaiveDate) -> Result<Vec<GoatMetrics>, AppError> {
+    let mut milk: HashMap<String, f64> = HashMap::new();
+    // Lactations are ordered by start within each doe, so the last one wins
+    for lactation in load_lactations(conn, None)? {
+        milk.insert(lactation.doe_name, lactation.average_daily_yield);
+    }
+    let growth: HashMap<String, f64> = load_benchmarks(conn)?
+        .into_iter()
+        .map(|b| (b.goat_name, b.percentile))
+        .collect();
+    let profit: HashMap<String, f64> = load_profitability(conn)?
+        .into_iter()
+        .map(|p| (p.goat_name, p.profit))
+        .collect();
+    let kiddings = load_kidding_history(conn)?;
+
+    let since = (today - Days::new(HEALTH_WINDOW_DAYS))
+        .format(DATE_FORMAT)
+        .to_string();
+    let mut stmt = conn.prepare(
+        "SELECT g.id, g.name, g.gender, \
+             (SELECT COUNT(*) FROM health_incidents h WHERE h.goat_id = g.id AND h.observed_on >= ?1) \
+         FROM goats g WHERE g.deleted_at IS NULL ORDER BY g.name",
+    )?;
+    let rows = stmt
+        .query_map([&since], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, u32>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let metrics = rows
+        .into_iter()
+        .map(|(id, name, gender, incidents)| {
+            let gender = Gender::from_str(&gender)
+                .map_err(|e| AppError::ParseError(ParseEnumError::new(&e, "Gender")))?;
+            let stats = match gender {
+                Gender::Female => kiddings.as_doe.get(&id),
+                Gender::Male => kiddings.as_buck.get(&id),
+            };
+            Ok(GoatMetrics {
+                milk: milk.get(&name).copied(),
+                growth: growth.get(&name).copied(),
+                fertility: stats
+                    .filter(|s| s.kiddings > 0)
+                    .map(|s| s.kids_alive as f64 / s.kiddings as f64),
+                health_incidents: incidents as f64,
+                profit: profit.get(&name).copied(),
+                name,
+                gender,
+            })
+        })
+        .collect::<Result<Vec<_>, AppError>>()?;
+    debug!("Loaded scoring metrics for {} goats", metrics.len());
+    Ok(metrics)
+}
@@ -0,0 +1,71 @@
+//! Tags for new goats from the farm's tag series (see `shared::tags`).
+//!
+//! The goat repository takes the tag of each goat it adds through
+//! `take_tag`, within the transaction adding it, and the add goat form previews the next tag
+//! through `peek_tag`. Numbers are counted per stem in `tag_counters`, so a
+//! number is never handed out twice, even after its goat is deleted.
+
+use crate::errors::AppError;
+use crate::handlers::settings::load_settings;
+use chrono::Datelike;
+use rusqlite::{Connection, OptionalExtension, params};
+use shared::tags::TagSeries;
+use shared::time::today_in;
+use tracing::debug;
+
+/// The first number after the last one assigned in `series` in `year`
+/// whose tag no goat has yet, with that tag.
+fn next_tag(conn: &Connection, series: &TagSeries, year: i32) -> Result<(u32, String), AppError> {
+    let last: Option<u32> = conn
+        .query_row(
+            "SELECT last_number FROM tag_counters WHERE stem = ?1",
+            [series.stem(year)],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let mut number = last.unwrap_or(0) + 1;
+    loop {
+        let tag = series.tag(year, number);
+        let taken: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM goats WHERE tag_id = ?1 AND deleted_at IS NULL)",
+            [&tag],
+            |row| row.get(0),
+        )?;
+        if !taken {
+            return Ok((number, tag));
+        }
+        number += 1;
+    }
+}
+
+/// The tag the next goat added will get, or `None` if the farm has no tag
+/// series. The year of a yearly series is taken in the farm's time zone.
+pub fn peek_tag(conn: &Connection) -> Result<Option<String>, AppError> {
+    let settings = load_settings(conn)?;
+    let Some(series) = &settings.tag_series else {
+        return Ok(None);
+    };
+    let year = today_in(settings.tz()).year();
+    Ok(Some(next_tag(conn, series, year)?.1))
+}
+
+/// Takes the next tag of the farm's series, counting it as used, or `None`
+/// if the farm has no tag series.
+///
+/// Called through the database transaction adding the goat that gets it.
+pub fn take_tag(conn: &Connection) -> Result<Option<String>, AppError> {
+    let settings = load_settings(conn)?;
+    let Some(series) = &settings.tag_series else {
+        return Ok(None);
+    };
+    let year = today_in(settings.tz()).year();
+    let (number, tag) = next_tag(conn, series, year)?;
+    conn.execute(
+        "INSERT INTO tag_counters (stem, last_number) VALUES (?1, ?2) \
+         ON CONFLICT(stem) DO UPDATE SET last_number = excluded.last_number",
+        params![series.stem(year), number],
+    )?;
+
+    debug!(%tag, "Tag taken");
+    Ok(Some(tag))
+}
@@ -0,0 +1,355 @@
+//! Multi-tenant deployment mode: many farms on one backend instance.
+//!
+//! Every tenant gets its own SQLite file in the tenants directory, so farms
+//! are isolated at the storage level and every existing query works
+//! unchanged. A small directory database (`tenants.db`) maps each tenant's
+//! key hash to its slug.
+//!
+//! Requests carry the tenant key in an `X-Tenant-Key` header: the dashboard
+//! sends the farm key entered when signing in, and `yagi-cli` the one in
+//! `YAGI_TENANT_KEY`. The `resolve_tenant` middleware looks the key up and
+//! installs that tenant's `DbPool` as request data, so handlers keep
+//! extracting `web::Data<DbPool>`. Without a `TenantRegistry` registered the
+//! middleware does nothing and the backend serves a single farm as before.
+//!
+//! Public share links are opened without a tenant key, so their tokens
+//! start with the tenant's slug instead (see `share_token`).
+
+use crate::auth::{ensure_owner_password, generate_key, hash_key, set_owner_password};
+use crate::db::DbPool;
+use crate::errors::AppError;
+use crate::jobs::JobRunner;
+use crate::scheduler::spawn_scheduler;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{Extensions, ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{HttpMessage, HttpRequest, web};
+use rusqlite::{Connection, OptionalExtension, params};
+use shared::sessions::validate_owner_password;
+pub use shared::tenants::TENANT_KEY_HEADER;
+use shared::tenants::{IssuedTenant, Tenant};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Header carrying the operator's admin key for `/tenants`.
+pub const ADMIN_KEY_HEADER: &str = "X-Admin-Key";
+
+/// Path prefix of the operator endpoints, which are not tenant scoped.
+const ADMIN_SCOPE: &str = "/tenants";
+
+/// Path prefix of the public share links, whose tokens name their tenant.
+const PUBLIC_SCOPE: &str = "/public/";
+
+/// Separates the tenant slug from the token in a public share token. Slugs
+/// and generated keys never contain it.
+const SHARE_TOKEN_SEPARATOR: char = '.';
+
+/// The slug of the tenant a request is served for, as a request extension.
+#[derive(Clone)]
+pub struct CurrentTenant(pub String);
+
+/// Longest accepted tenant slug.
+const MAX_SLUG_LEN: usize = 40;
+
+/// The tenants directory and the pools of the tenants opened so far.
+pub struct TenantRegistry {
+    dir: PathBuf,
+    directory: Mutex<Connection>,
+    pools: Mutex<HashMap<String, DbPool>>,
+    admin_key_hash: String,
+    scheduler_interval: Option<Duration>,
+    job_runner: Option<JobRunner>,
+}
+
+impl TenantRegistry {
+    /// Opens (or creates) the tenants directory at `dir`. `admin_key`
+    /// guards the operator endpoints.
+    ///
+    /// # Errors
+    /// Fails if the directory or its database cannot be created.
+    pub fn open(dir: impl Into<PathBuf>, admin_key: &str) -> Result<Self, AppError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            AppError::InvalidInput(format!("Cannot create tenants directory: {}", e))
+        })?;
+        let directory = Connection::open(dir.join("tenants.db"))?;
+        directory.execute_batch(
+            "CREATE TABLE IF NOT EXISTS tenants (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                slug TEXT NOT NULL UNIQUE,
+                name TEXT NOT NULL,
+                key_hash TEXT NOT NULL UNIQUE,
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+            );",
+        )?;
+        info!(dir = %dir.display(), "Multi-tenant mode enabled");
+        Ok(TenantRegistry {
+            dir,
+            directory: Mutex::new(directory),
+            pools: Mutex::new(HashMap::new()),
+            admin_key_hash: hash_key(admin_key),
+            scheduler_interval: None,
+            job_runner: None,
+        })
+    }
+
+    /// Starts a reminder scheduler for each tenant when its database is
+    /// first opened.
+    pub fn with_scheduler(mut self, every: Duration) -> Self {
+        self.scheduler_interval = Some(every);
+        self
+    }
+
+    /// Starts `runner` on each tenant's background jobs when its database is
+    /// first opened.
+    pub fn with_job_runner(mut self, runner: JobRunner) -> Self {
+        self.job_runner = Some(runner);
+        self
+    }
+
+    /// Whether `key` is the operator's admin key.
+    pub fn is_admin(&self, key: &str) -> bool {
+        hash_key(key) == self.admin_key_hash
+    }
+
+    /// Returns the pool of tenant `slug`, opening (and migrating) its
+    /// database on first use.
+    fn pool(&self, slug: &str) -> Result<DbPool, AppError> {
+        let mut pools = self.pools.lock().expect("tenant pools lock poisoned");
+        if let Some(pool) = pools.get(slug) {
+            return Ok(pool.clone());
+        }
+        let path = self.dir.join(format!("{}.db", slug));
+        let pool = DbPool::new(&path.to_string_lossy())?;
+        if let Some(every) = self.scheduler_interval {
+            spawn_scheduler(pool.clone(), every);
+        }
+        if let Some(runner) = &self.job_runner {
+            runner.spawn(pool.clone());
+        }
+        info!(tenant = slug, "Opened tenant database");
+        pools.insert(slug.to_string(), pool.clone());
+        Ok(pool)
+    }
+
+    /// Finds the tenant owning `key` and returns its slug and pool.
+    ///
+    /// # Errors
+    /// - `AppError::Unauthorized` if no tenant has this key.
+    pub fn resolve(&self, key: &str) -> Result<(String, DbPool), AppError> {
+        let slug: String = self
+            .directory
+            .lock()
+            .expect("tenant directory lock poisoned")
+            .query_row(
+                "SELECT slug FROM tenants WHERE key_hash = ?1",
+                [hash_key(key)],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| {
+                warn!("Rejected unknown tenant key");
+                AppError::Unauthorized("Invalid tenant key".into())
+            })?;
+        let pool = self.pool(&slug)?;
+        debug!(tenant = %slug, "Resolved tenant");
+        Ok((slug, pool))
+    }
+
+    /// Returns the pool of the registered tenant `slug`.
+    ///
+    /// # Errors
+    /// - `AppError::Unauthorized` if no tenant has this slug.
+    pub fn resolve_slug(&self, slug: &str) -> Result<DbPool, AppError> {
+        let exists: bool = self
+            .directory
+            .lock()
+            .expect("tenant directory lock poisoned")
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM tenants WHERE slug = ?1)",
+                [slug],
+                |row| row.get(0),
+            )?;
+        if !exists {
+            warn!("Rejected share link of an unknown tenant");
+            return Err(AppError::Unauthorized("Unknown farm".into()));
+        }
+        self.pool(slug)
+    }
+
+    /// Registers a tenant, creates its database with `owner_password` as
+    /// the owner's password, and issues its key.
+    ///
+    /// # Errors
+    /// - `AppError::InvalidInput` for an empty name, a malformed slug, a
+    ///   slug already in use, or an owner password that is too short.
+    pub fn create(
+        &self,
+        slug: &str,
+        name: &str,
+        owner_password: &str,
+    ) -> Result<IssuedTenant, AppError> {
+        let slug = slug.trim();
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::InvalidInput(
+                "Tenant name must not be empty".into(),
+            ));
+        }
+        validate_owner_password(owner_password).map_err(AppError::InvalidInput)?;
+        let valid = !slug.is_empty()
+            && slug.len() <= MAX_SLUG_LEN
+            && !slug.starts_with('-')
+            && slug
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !valid {
+            return Err(AppError::InvalidInput(format!(
+                "Tenant slug must be 1-{} lowercase letters, digits or dashes, got '{}'",
+                MAX_SLUG_LEN, slug
+            )));
+        }
+        let key = generate_key();
+        {
+            let directory = self
+                .directory
+                .lock()
+                .expect("tenant directory lock poisoned");
+            let exists: bool = directory.query_row(
+                "SELECT EXISTS(SELECT 1 FROM tenants WHERE slug = ?1)",
+                [slug],
+                |row| row.get(0),
+            )?;
+            if exists {
+                return Err(AppError::InvalidInput(format!(
+                    "A tenant with slug {} already exists",
+                    slug
+                )));
+            }
+            directory.execute(
+                "INSERT INTO tenants (slug, name, key_hash) VALUES (?1, ?2, ?3)",
+                params![slug, name, hash_key(&key)],
+            )?;
+        }
+        let conn = self.pool(slug)?.get_conn()?;
+        set_owner_password(&conn, owner_password)?;
+        info!(tenant = slug, "Tenant created");
+        Ok(IssuedTenant {
+            slug: slug.to_string(),
+            name: name.to_string(),
+            key,
+        })
+    }
+
+    /// Lists every tenant, ordered by slug.
+    pub fn list(&self) -> Result<Vec<Tenant>, AppError> {
+        let directory = self
+            .directory
+            .lock()
+            .expect("tenant directory lock poisoned");
+        let mut stmt =
+            directory.prepare("SELECT slug, name, created_at FROM tenants ORDER BY slug")?;
+        let tenants = stmt
+            .query_map([], |row| {
+                Ok(Tenant {
+                    slug: row.get(0)?,
+                    name: row.get(1)?,
+                    created_at: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(tenants)
+    }
+
+    /// Opens every tenant's database, starting their schedulers. A tenant
+    /// created before owners' passwords were set up gets a random one; these
+    /// are returned as `(slug, password)` for the operator to pass on, and
+    /// kept out of the log.
+    pub fn open_all(&self) -> Result<Vec<(String, String)>, AppError> {
+        let mut generated = Vec::new();
+        for tenant in self.list()? {
+            let conn = self.pool(&tenant.slug)?.get_conn()?;
+            if let Some(password) = ensure_owner_password(&conn, None)? {
+                warn!(tenant = %tenant.slug, "The owner had no password; a random one was set");
+                generated.push((tenant.slug, password));
+            }
+        }
+        Ok(generated)
+    }
+}
+
+/// The public share token of `token` for the tenant `req` is served for:
+/// the tenant's slug, then `token`, so the link opens without a tenant key.
+/// Just `token` when serving a single farm.
+pub fn share_token(req: &HttpRequest, token: &str) -> String {
+    match req.extensions().get::<CurrentTenant>() {
+        Some(CurrentTenant(slug)) => format!("{}{}{}", slug, SHARE_TOKEN_SEPARATOR, token),
+        None => token.to_string(),
+    }
+}
+
+/// The token within a public share token, without its tenant slug.
+pub fn token_of_share(share_token: &str) -> &str {
+    share_token
+        .split_once(SHARE_TOKEN_SEPARATOR)
+        .map_or(share_token, |(_, token)| token)
+}
+
+/// Installs the `DbPool` of the tenant `req` is for: the one whose key it
+/// carries or, for a public share link, the one its token names.
+fn install_tenant(req: &mut ServiceRequest, registry: &TenantRegistry) -> Result<(), AppError> {
+    let (slug, pool) = match req.path().strip_prefix(PUBLIC_SCOPE) {
+        Some(rest) => {
+            let share = rest.rsplit('/').next().unwrap_or_default();
+            let (slug, _) = share
+                .split_once(SHARE_TOKEN_SEPARATOR)
+                .ok_or_else(|| AppError::Unauthorized("Unknown farm".into()))?;
+            (slug.to_string(), registry.resolve_slug(slug)?)
+        }
+        None => {
+            let key = req
+                .headers()
+                .get(TENANT_KEY_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .ok_or_else(|| AppError::Unauthorized("Missing tenant key".into()))?;
+            registry.resolve(key)?
+        }
+    };
+    let mut data = Extensions::new();
+    data.insert(web::Data::new(pool));
+    req.add_data_container(Rc::new(data));
+    debug!(tenant = %slug, path = req.path(), "Serving tenant request");
+    req.extensions_mut().insert(CurrentTenant(slug));
+    Ok(())
+}
+
+/// Middleware installing the requesting tenant's `DbPool` as request data.
+///
+/// Passes requests through untouched when no `TenantRegistry` is
+/// registered, and leaves the operator endpoints under `/tenants` alone.
+/// Public share links need no tenant key; their token names the tenant.
+///
+/// # Errors
+/// - Responds with HTTP 401 if the tenant key is missing or unknown, or a
+///   share link names no registered tenant.
+pub async fn resolve_tenant(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    let registry = req.app_data::<web::Data<TenantRegistry>>().cloned();
+    if let Some(registry) = registry
+        && !req.path().starts_with(ADMIN_SCOPE)
+        && let Err(e) = install_tenant(&mut req, &registry)
+    {
+        return Ok(req.error_response(e).map_into_right_body());
+    }
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
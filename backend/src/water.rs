@@ -0,0 +1,271 @@
+//! Water consumption per pen and trough maintenance (see `shared::water`).
+//!
+//! Readings of the water meters in each pen are summed per day in the farm's
+//! time zone. Yesterday, the last whole day, is compared with the pen's
+//! average over the week before it. The scheduler turns a drop into a task
+//! to check the pen and a notification to everyone who may view health
+//! records, and creates a trough cleaning task for every metered pen whose
+//! troughs have gone `TROUGH_CLEANING_DAYS` without one.
+
+use crate::alerts::notify_viewers;
+use crate::errors::AppError;
+use crate::handlers::scale::READ_AT_FORMAT;
+use crate::handlers::settings::farm_timezone;
+use crate::scheduler::DATE_FORMAT;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use chrono_tz::Tz;
+use rusqlite::{Connection, OptionalExtension, params};
+use shared::permissions::PermissionModule;
+use shared::time::{day_start, local_date};
+use shared::water::{
+    BASELINE_DAYS, DROP_SHARE, INTAKE_CHECK_TITLE, MIN_BASELINE_DAYS, PenWater,
+    TROUGH_CLEANING_DAYS, TROUGH_CLEANING_TITLE, WaterDay, WaterDrop,
+};
+use std::collections::BTreeMap;
+use tracing::{debug, info, trace};
+
+/// Days of daily totals returned per pen.
+pub const REPORT_DAYS: u64 = 14;
+
+/// Average daily litres over the `BASELINE_DAYS` before `day`, if enough of
+/// them have readings. `daily` must be sorted by date.
+pub fn baseline(daily: &[(NaiveDate, f64)], day: NaiveDate) -> Option<f64> {
+    let before: Vec<f64> = daily
+        .iter()
+        .filter(|(d, _)| *d < day && (day - *d).num_days() <= BASELINE_DAYS)
+        .map(|(_, litres)| *litres)
+        .collect();
+    (before.len() >= MIN_BASELINE_DAYS).then(|| before.iter().sum::<f64>() / before.len() as f64)
+}
+
+/// `day`'s intake, if it fell below `DROP_SHARE` of the baseline. A day
+/// without readings is not judged, so a meter going offline is not taken
+/// for goats that stopped drinking.
+pub fn detect_drop(daily: &[(NaiveDate, f64)], day: NaiveDate) -> Option<WaterDrop> {
+    let (_, litres) = daily.iter().find(|(d, _)| *d == day)?;
+    let baseline = baseline(daily, day)?;
+    if baseline <= 0.0 || *litres >= baseline * DROP_SHARE {
+        return None;
+    }
+    trace!(%day, litres, baseline, "Water intake drop");
+    Some(WaterDrop {
+        date: day.format(DATE_FORMAT).to_string(),
+        litres: *litres,
+        baseline,
+    })
+}
+
+/// Daily litres per pen from `from` to `to` inclusive, oldest first.
+fn daily_usage(
+    conn: &Connection,
+    tz: Tz,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<BTreeMap<i64, Vec<(NaiveDate, f64)>>, AppError> {
+    // Readings are stored in UTC and summed per day in the farm's time zone
+    let mut stmt = conn.prepare(
+        "SELECT s.space_id, r.read_at, r.value \
+         FROM sensor_readings r JOIN sensors s ON s.id = r.sensor_id \
+         WHERE s.sensor_type = 'Water' AND s.space_id IS NOT NULL \
+             AND r.read_at >= ?1 AND r.read_at < ?2 \
+         ORDER BY r.read_at",
+    )?;
+    let rows = stmt
+        .query_map(
+            params![
+                day_start(from, tz).format(READ_AT_FORMAT).to_string(),
+                day_start(to + Days::new(1), tz)
+                    .format(READ_AT_FORMAT)
+                    .to_string()
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, DateTime<Utc>>(1)?,
+                    row.get::<_, f64>(2)?,
+                ))
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut daily: BTreeMap<i64, Vec<(NaiveDate, f64)>> = BTreeMap::new();
+    for (space_id, read_at, litres) in rows {
+        let day = local_date(&read_at, tz);
+        let days = daily.entry(space_id).or_default();
+        match days.last_mut() {
+            Some((last, total)) if *last == day => *total += litres,
+            _ => days.push((day, litres)),
+        }
+    }
+    Ok(daily)
+}
+
+/// When the troughs of `space_id` were last cleaned, in the farm's time
+/// zone, and the pending cleaning task if there is one.
+fn trough_cleaning(
+    conn: &Connection,
+    tz: Tz,
+    space_id: i64,
+) -> Result<(Option<NaiveDate>, Option<i64>), AppError> {
+    let cleaned_at: Option<DateTime<Utc>> = conn.query_row(
+        "SELECT MAX(completed_at) FROM tasks \
+         WHERE space_id = ?1 AND title = ?2 AND status = 'Done'",
+        params![space_id, TROUGH_CLEANING_TITLE],
+        |row| row.get(0),
+    )?;
+    let pending: Option<i64> = conn.query_row(
+        "SELECT MIN(id) FROM tasks WHERE space_id = ?1 AND title = ?2 AND status = 'Pending'",
+        params![space_id, TROUGH_CLEANING_TITLE],
+        |row| row.get(0),
+    )?;
+    Ok((cleaned_at.map(|at| local_date(&at, tz)), pending))
+}
+
+/// Water use and trough upkeep of every pen with a water meter as of
+/// `today`, ordered by pen name.
+pub fn load_pen_water(conn: &Connection, today: NaiveDate) -> Result<Vec<PenWater>, AppError> {
+    let tz = farm_timezone(conn)?;
+    let yesterday = today - Days::new(1);
+    let daily = daily_usage(conn, tz, today - Days::new(REPORT_DAYS), today)?;
+    let mut stmt = conn.prepare(
+        "SELECT sp.id, sp.name, (SELECT COUNT(*) FROM goats g WHERE g.space_id = sp.id AND g.deleted_at IS NULL) \
+         FROM spaces sp \
+         WHERE EXISTS (SELECT 1 FROM sensors s \
+                       WHERE s.space_id = sp.id AND s.sensor_type = 'Water') \
+         ORDER BY sp.name",
+    )?;
+    let pens = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let no_readings = Vec::new();
+    pens.into_iter()
+        .map(|(space_id, space_name, goats)| {
+            let days = daily.get(&space_id).unwrap_or(&no_readings);
+            let (last_cleaned, cleaning_task_id) = trough_cleaning(conn, tz, space_id)?;
+            Ok(PenWater {
+                space_id,
+                space_name,
+                goats,
+                days: days
+                    .iter()
+                    .map(|(day, litres)| WaterDay {
+                        date: day.format(DATE_FORMAT).to_string(),
+                        litres: *litres,
+                    })
+                    .collect(),
+                baseline: baseline(days, yesterday),
+                drop: detect_drop(days, yesterday),
+                last_cleaned: last_cleaned.map(|d| d.format(DATE_FORMAT).to_string()),
+                cleaning_task_id,
+            })
+        })
+        .collect()
+}
+
+/// Creates a pending task for `space_id` due `today`, with a reminder
+/// carrying `message`, unless one with the same title is already due then.
+/// Returns whether it was created.
+fn create_pen_task(
+    conn: &Connection,
+    space_id: i64,
+    title: &str,
+    notes: Option<&str>,
+    today: &str,
+    message: &str,
+) -> Result<bool, AppError> {
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM tasks WHERE space_id = ?1 AND title = ?2 AND due_date = ?3",
+            params![space_id, title, today],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if existing.is_some() {
+        return Ok(false);
+    }
+    conn.execute(
+        "INSERT INTO tasks (title, notes, space_id, due_date, status) \
+         VALUES (?1, ?2, ?3, ?4, 'Pending')",
+        params![title, notes, space_id, today],
+    )?;
+    let task_id = conn.last_insert_rowid();
+    conn.execute(
+        "INSERT INTO reminders (task_id, remind_on, message) VALUES (?1, ?2, ?3)",
+        params![task_id, today, message],
+    )?;
+    trace!(space_id, task_id, title, "Created pen water task");
+    Ok(true)
+}
+
+/// Creates the intake checks and trough cleaning tasks due `today`, and
+/// notifies workers who may view health records of each drop in intake.
+/// Running it again the same day creates nothing new.
+///
+/// # Returns
+/// The number of tasks created.
+pub fn evaluate_water(conn: &mut Connection, today: NaiveDate) -> Result<usize, AppError> {
+    let pens = load_pen_water(conn, today)?;
+    let due = today.format(DATE_FORMAT).to_string();
+    let sent_at = Utc::now().format(READ_AT_FORMAT).to_string();
+    let tx = conn.transaction()?;
+    let mut created = 0;
+
+    for pen in &pens {
+        if let Some(drop) = &pen.drop {
+            let message = format!(
+                "Water intake in {} fell {:.0}% on {}: {:.0} L against {:.0} L a day the week before",
+                pen.space_name,
+                drop.fall() * 100.0,
+                drop.date,
+                drop.litres,
+                drop.baseline
+            );
+            let notes = "A sudden drop in water intake often comes before illness; \
+                         check the goats and the troughs.";
+            if create_pen_task(
+                &tx,
+                pen.space_id,
+                INTAKE_CHECK_TITLE,
+                Some(notes),
+                &due,
+                &message,
+            )? {
+                let notified = notify_viewers(&tx, PermissionModule::Health, &message, &sent_at)?;
+                info!(pen = %pen.space_name, notified, "Water intake dropped");
+                created += 1;
+            }
+        }
+
+        let cleaning_due = pen.cleaning_task_id.is_none()
+            && pen.last_cleaned.as_deref().is_none_or(|last| {
+                NaiveDate::parse_from_str(last, DATE_FORMAT)
+                    .is_ok_and(|last| (today - last).num_days() >= TROUGH_CLEANING_DAYS)
+            });
+        if cleaning_due
+            && create_pen_task(
+                &tx,
+                pen.space_id,
+                TROUGH_CLEANING_TITLE,
+                None,
+                &due,
+                &format!(
+                    "{} in {} due on {}",
+                    TROUGH_CLEANING_TITLE, pen.space_name, due
+                ),
+            )?
+        {
+            created += 1;
+        }
+    }
+
+    tx.commit()?;
+    debug!(pens = pens.len(), created, "Evaluated pen water");
+    Ok(created)
+}
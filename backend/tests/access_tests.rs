@@ -0,0 +1,137 @@
+mod common;
+
+use actix_web::http::Method;
+use actix_web::middleware::from_fn;
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::access::{allowed_when_read_only, enforce_read_only};
+use backend::auth::{API_KEY_HEADER, check_session, hash_key};
+use backend::routes;
+use serde_json::json;
+use shared::scale::IssuedApiKey;
+use shared::sessions::SESSION_HEADER;
+use shared::settings::FarmSettings;
+
+#[test]
+fn test_read_only_exemptions() {
+    assert!(allowed_when_read_only(&Method::GET, "/goats"));
+    assert!(allowed_when_read_only(&Method::PUT, "/settings/read-only"));
+    assert!(!allowed_when_read_only(&Method::PUT, "/settings"));
+    assert!(allowed_when_read_only(&Method::POST, "/scale/readings"));
+    assert!(allowed_when_read_only(&Method::DELETE, "/sessions/3"));
+    assert!(!allowed_when_read_only(&Method::POST, "/goats"));
+    assert!(!allowed_when_read_only(&Method::PUT, "/scale/readings/3/goat"));
+    assert!(!allowed_when_read_only(&Method::PUT, "/settingsx"));
+}
+
+#[actix_rt::test]
+async fn test_read_only_mode_refuses_changes() {
+    let db_pool = common::temp_pool("access");
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(enforce_read_only))
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let owner = common::owner_token(&db_pool);
+
+    let req = TestRequest::post()
+        .uri("/api-keys")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .set_json(json!({ "name": "Barn scale" }))
+        .to_request();
+    let issued: IssuedApiKey = call_and_read_body_json(&app, req).await;
+    let set_read_only = |on: bool| {
+        TestRequest::put()
+            .uri("/settings/read-only")
+            .insert_header((SESSION_HEADER, owner.as_str()))
+            .set_json(json!({ "read_only": on }))
+            .to_request()
+    };
+    let settings: FarmSettings = call_and_read_body_json(&app, set_read_only(true)).await;
+    assert!(settings.read_only);
+
+    let add_goat = || {
+        TestRequest::post()
+            .uri("/goats")
+            .insert_header((SESSION_HEADER, owner.as_str()))
+            .set_json(common::sample_goat("Rani"))
+            .to_request()
+    };
+    let resp = call_service(&app, add_goat()).await;
+    assert_eq!(resp.status(), 403);
+    let req = TestRequest::delete()
+        .uri("/goats")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 403);
+    let req = TestRequest::get()
+        .uri("/goats")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::put()
+        .uri("/settings")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .set_json(json!({ "farm_name": "Hilltop" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 403);
+
+    // Devices keep reporting
+    let req = TestRequest::post()
+        .uri("/scale/readings")
+        .insert_header((API_KEY_HEADER, issued.key.as_str()))
+        .set_json(json!({ "tag_id": "TAG-1", "weight": 31.0 }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let settings: FarmSettings = call_and_read_body_json(&app, set_read_only(false)).await;
+    assert!(!settings.read_only);
+    assert_eq!(call_service(&app, add_goat()).await.status(), 201);
+}
+
+#[actix_rt::test]
+async fn test_only_the_owner_switches_read_only_mode() {
+    let db_pool = common::temp_pool("access_owner");
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let conn = db_pool.get_conn().unwrap();
+    conn.execute(
+        "INSERT INTO workers (name, role, notify_by) VALUES ('Ravi', 'Milker', 'App')",
+        [],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO sessions (device, token_hash, worker_id) VALUES ('Phone', ?1, 1)",
+        [hash_key("yagi_ravi")],
+    )
+    .unwrap();
+    let owner = common::owner_token(&db_pool);
+    let switch = |token: &str| {
+        TestRequest::put()
+            .uri("/settings/read-only")
+            .insert_header((SESSION_HEADER, token))
+            .set_json(json!({ "read_only": true }))
+            .to_request()
+    };
+
+    assert_eq!(call_service(&app, switch("yagi_ravi")).await.status(), 403);
+    // Replacing the settings leaves the mode alone
+    let req = TestRequest::put()
+        .uri("/settings")
+        .insert_header((SESSION_HEADER, "yagi_ravi"))
+        .set_json(json!({ "read_only": true }))
+        .to_request();
+    let settings: FarmSettings = call_and_read_body_json(&app, req).await;
+    assert!(!settings.read_only);
+
+    let settings: FarmSettings = call_and_read_body_json(&app, switch(&owner)).await;
+    assert!(settings.read_only);
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use shared::activity::{ACTIVITY_LIMIT, ActivityEvent, ActivityKind};
+
+#[actix_rt::test]
+async fn test_recent_activity() {
+    let db_pool = common::temp_pool("activity");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for name in ["Rani", "Moti"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    let conn = db_pool.get_conn().unwrap();
+    conn.execute_batch(
+        "UPDATE goats SET created_at = '2025-06-01 08:00:00';
+         INSERT INTO health_incidents (goat_id, kind, condition, observed_on, created_at)
+             SELECT id, 'Disease', 'Bloat', '2025-06-02', '2025-06-02 09:30:00' FROM goats WHERE name = 'Rani';
+         INSERT INTO transactions (kind, category, amount, goat_id, date, currency, exchange_rate, created_at)
+             SELECT 'Income', 'Sale', 180, id, '2025-06-03', 'USD', 83, '2025-06-03 17:00:00' FROM goats WHERE name = 'Moti';
+         INSERT INTO transactions (kind, category, amount, goat_id, date, created_at)
+             SELECT 'Expense', 'Feed', 20, id, '2025-06-04', '2025-06-04 07:00:00' FROM goats WHERE name = 'Moti';",
+    )
+    .unwrap();
+
+    let req = TestRequest::get().uri("/activity").to_request();
+    let events: Vec<ActivityEvent> = call_and_read_body_json(&app, req).await;
+    let kinds: Vec<(ActivityKind, &str)> = events
+        .iter()
+        .map(|e| (e.kind, e.goat_name.as_str()))
+        .collect();
+    // Newest first; goats entered in the same second by latest ID; expenses are left out
+    assert_eq!(
+        kinds,
+        vec![
+            (ActivityKind::GoatSold, "Moti"),
+            (ActivityKind::GoatTreated, "Rani"),
+            (ActivityKind::GoatAdded, "Moti"),
+            (ActivityKind::GoatAdded, "Rani"),
+        ]
+    );
+    assert_eq!(events[0].summary, "Sold Moti for 180.00 USD");
+    assert_eq!(events[0].at.to_rfc3339(), "2025-06-03T17:00:00+00:00");
+    assert_eq!(events[1].summary, "Treated Rani for Bloat");
+    assert_eq!(events[3].summary, "Added Rani (Beetal)");
+    assert_eq!(events[3].anchor(), format!("goat-{}", events[3].goat_id));
+
+    for i in 0..ACTIVITY_LIMIT {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(&format!("Kid {}", i)))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    let req = TestRequest::get().uri("/activity").to_request();
+    let events: Vec<ActivityEvent> = call_and_read_body_json(&app, req).await;
+    assert_eq!(events.len(), ACTIVITY_LIMIT);
+    assert_eq!(events[0].goat_name, format!("Kid {}", ACTIVITY_LIMIT - 1));
+}
@@ -0,0 +1,191 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::alerts::evaluate_alerts;
+use backend::routes;
+use chrono::NaiveDate;
+use rusqlite::params;
+use serde_json::json;
+use shared::alerts::{AlertCondition, AlertMetric, AlertRule};
+use shared::notifications::Notification;
+
+#[test]
+fn test_alert_conditions() {
+    let rule = AlertRule {
+        id: None,
+        name: "Milk slump".to_string(),
+        metric: AlertMetric::MilkThisWeek,
+        condition: AlertCondition::DropsBy,
+        threshold: 20.0,
+        active: true,
+        firing: false,
+        last_fired_at: None,
+    };
+    assert!(rule.validate().is_ok());
+    assert_eq!(rule.describe(), "Milk this week down 20% week over week");
+    assert!(rule.holds(80.0, Some(100.0)));
+    assert!(!rule.holds(81.0, Some(100.0)));
+    assert!(!rule.holds(0.0, Some(0.0)));
+    assert!(!rule.holds(10.0, None));
+
+    let sick = AlertRule {
+        metric: AlertMetric::SickGoats,
+        condition: AlertCondition::Above,
+        threshold: 3.0,
+        ..rule.clone()
+    };
+    assert!(sick.holds(4.0, None) && !sick.holds(3.0, None));
+    // Sick goats have no history to compare with
+    assert!(
+        AlertRule {
+            condition: AlertCondition::RisesBy,
+            ..sick.clone()
+        }
+        .validate()
+        .is_err()
+    );
+    assert!(
+        AlertRule {
+            name: " ".to_string(),
+            ..sick.clone()
+        }
+        .validate()
+        .is_err()
+    );
+    assert!(
+        AlertRule {
+            threshold: -1.0,
+            ..sick
+        }
+        .validate()
+        .is_err()
+    );
+}
+
+#[actix_rt::test]
+async fn test_alert_rules_notify_workers_once_per_breach() {
+    let db_pool = common::temp_pool("alerts");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for name in ["Rani", "Mini"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    for (name, role) in [("Asha", "Milker"), ("Ravi", "Herder")] {
+        common::add_worker(&db_pool, name, Some(role), None, "App");
+    }
+    // Herders cannot see milk records, so milk alerts skip them
+    let granted = json!([
+        { "module": "Goats", "action": "View" },
+        { "module": "Health", "action": "View" }
+    ]);
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "INSERT INTO role_permissions (role, granted) VALUES ('Herder', ?1)",
+            [granted.to_string()],
+        )
+        .unwrap();
+
+    for (name, metric, condition, threshold) in [
+        ("Milk slump", "MilkThisWeek", "DropsBy", 20.0),
+        ("Sick pen", "SickGoats", "Above", 1.0),
+    ] {
+        let req = TestRequest::post()
+            .uri("/alerts")
+            .set_json(json!({
+                "id": null, "name": name, "metric": metric, "condition": condition,
+                "threshold": threshold, "active": true
+            }))
+            .to_request();
+        let rule: AlertRule = call_and_read_body_json(&app, req).await;
+        assert!(rule.id.is_some() && !rule.firing);
+    }
+    let req = TestRequest::post()
+        .uri("/alerts")
+        .set_json(json!({
+            "id": null, "name": "Bad", "metric": "SickGoats", "condition": "DropsBy",
+            "threshold": 10.0, "active": true
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let today = NaiveDate::from_ymd_opt(2026, 3, 31).unwrap();
+    let mut conn = db_pool.get_conn().unwrap();
+    let rani: i64 = conn
+        .query_row("SELECT id FROM goats WHERE name = 'Rani'", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    for (day, litres) in [("2026-03-20", 10.0), ("2026-03-28", 7.0)] {
+        conn.execute(
+            "INSERT INTO milk_records (doe_id, recorded_on, yield_litres) VALUES (?1, ?2, ?3)",
+            params![rani, day, litres],
+        )
+        .unwrap();
+    }
+    // Milk fell 30% week over week; no goat is sick yet
+    assert_eq!(evaluate_alerts(&mut conn, today).unwrap(), 1);
+    assert_eq!(evaluate_alerts(&mut conn, today).unwrap(), 0);
+    conn.execute("UPDATE goats SET health_status = 'Coughing'", [])
+        .unwrap();
+    assert_eq!(evaluate_alerts(&mut conn, today).unwrap(), 1);
+
+    let inbox = |worker: &str| {
+        TestRequest::get()
+            .uri(&format!("/notifications?worker={}", worker))
+            .to_request()
+    };
+    let asha: Vec<Notification> = call_and_read_body_json(&app, inbox("Asha")).await;
+    assert_eq!(asha.len(), 2);
+    assert!(
+        asha.iter()
+            .all(|n| n.goat_name.is_none() && n.link == "#alerts")
+    );
+    assert!(
+        asha.iter().any(
+            |n| n.message == "Alert Milk slump: Milk this week down 20% week over week (now 7)"
+        )
+    );
+    let ravi: Vec<Notification> = call_and_read_body_json(&app, inbox("Ravi")).await;
+    assert_eq!(ravi.len(), 1);
+    assert!(
+        ravi[0]
+            .message
+            .starts_with("Alert Sick pen: Sick goats above 1 (now 2)")
+    );
+
+    // Once the goats recover the rule clears, and fires again on the next breach
+    conn.execute("UPDATE goats SET health_status = 'healthy'", [])
+        .unwrap();
+    assert_eq!(evaluate_alerts(&mut conn, today).unwrap(), 0);
+    let req = TestRequest::get().uri("/alerts").to_request();
+    let rules: Vec<AlertRule> = call_and_read_body_json(&app, req).await;
+    assert_eq!(rules.len(), 2);
+    assert!(rules[0].firing && rules[0].last_fired_at.is_some());
+    assert!(!rules[1].firing && rules[1].last_fired_at.is_some());
+    conn.execute("UPDATE goats SET health_status = 'lame'", [])
+        .unwrap();
+    assert_eq!(evaluate_alerts(&mut conn, today).unwrap(), 1);
+
+    let req = TestRequest::delete()
+        .uri(&format!("/alerts/{}", rules[0].id.unwrap()))
+        .to_request();
+    assert!(call_service(&app, req).await.status().is_success());
+    let req = TestRequest::delete()
+        .uri(&format!("/alerts/{}", rules[0].id.unwrap()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::post().uri("/alerts/evaluate").to_request();
+    assert!(call_service(&app, req).await.status().is_success());
+}
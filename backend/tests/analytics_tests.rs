@@ -0,0 +1,107 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use serde_json::{Value, json};
+use shared::analytics::FeedEfficiencyReport;
+
+#[actix_rt::test]
+async fn test_feed_efficiency_per_goat_and_pen() {
+    let db_pool = common::temp_pool("analytics");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for name in ["Rani", "Meena"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    let req = TestRequest::post()
+        .uri("/spaces")
+        .set_json(json!({ "id": null, "name": "North Pen", "kind": "Enclosure", "capacity": 10 }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::put()
+        .uri("/spaces/1/goats")
+        .set_json(json!(["Rani", "Meena"]))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::put()
+        .uri("/spaces/1/goats")
+        .set_json(json!(["Nobody"]))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let req = TestRequest::get().uri("/spaces").to_request();
+    let spaces: Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(spaces[0]["goat_names"], json!(["Meena", "Rani"]));
+
+    for (name, day, weight) in [
+        ("Rani", "2025-01-01", 20.0),
+        ("Rani", "2025-03-01", 25.0),
+        ("Meena", "2025-01-01", 18.0),
+        ("Meena", "2025-03-01", 21.0),
+    ] {
+        let req = TestRequest::post()
+            .uri("/growth/weights")
+            .set_json(json!({ "id": null, "goat_name": name, "weighed_on": day, "weight": weight }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    // Concentrate in kg, mineral mix stocked in grams
+    for (name, unit, cost) in [("Concentrate", "kg", 30.0), ("Mineral mix", "g", 0.2)] {
+        let req = TestRequest::post()
+            .uri("/inventory")
+            .set_json(json!({
+                "id": null, "name": name, "category": "Feed", "quantity": 100000.0,
+                "unit": unit, "unit_cost": cost, "expiry_date": null, "reorder_level": 0.0
+            }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    for (item, quantity, goat, space, day) in [
+        (1, 20.0, Some("Rani"), None, "2025-02-01"),
+        // Outside Rani's weighing period, so not counted
+        (1, 50.0, Some("Rani"), None, "2025-04-01"),
+        (2, 1000.0, None, Some(1), "2025-02-01"),
+        (1, 6.0, Some("Meena"), None, "2025-02-01"),
+    ] {
+        let req = TestRequest::post()
+            .uri(&format!("/inventory/{}/consume", item))
+            .set_json(json!({
+                "id": null, "item_id": item, "quantity": quantity, "purpose": "Feeding",
+                "goat_name": goat, "space_id": space, "consumed_on": day, "notes": null
+            }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    let req = TestRequest::get()
+        .uri("/analytics/feed-efficiency")
+        .to_request();
+    let report: FeedEfficiencyReport = call_and_read_body_json(&app, req).await;
+
+    let goats: Vec<(&str, Option<f64>)> = report
+        .goats
+        .iter()
+        .map(|g| (g.name.as_str(), g.fcr))
+        .collect();
+    assert_eq!(goats, vec![("Meena", Some(2.0)), ("Rani", Some(4.0))]);
+    assert_eq!(report.goats[1].cost_per_kg_gain, Some(120.0));
+
+    // 20 + 6 kg concentrate and 1 kg mineral mix for 8 kg gained
+    let pen = &report.pens[0];
+    assert_eq!(pen.name, "North Pen");
+    assert_eq!(pen.feed_kg, 27.0);
+    assert_eq!(pen.weight_gain, 8.0);
+    assert_eq!(pen.feed_cost, 980.0);
+    assert_eq!(pen.cost_per_kg_gain, Some(122.5));
+}
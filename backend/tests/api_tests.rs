@@ -0,0 +1,268 @@
+//! End-to-end contract tests for the REST API.
+//!
+//! Each test mounts the full route table from `backend::routes` on a fresh
+//! temporary database and walks one resource through its lifecycle, so a
+//! changed path, status code or payload shape fails here first.
+
+mod common;
+
+use actix_web::{App, test, web};
+use backend::routes;
+use serde_json::{Value, json};
+
+/// Counts the vaccine links stored for the goat named `name`.
+fn vaccine_links(db: &backend::db::DbPool, name: &str) -> i64 {
+    db.get_conn()
+        .unwrap()
+        .query_row(
+            "SELECT COUNT(*) FROM goat_vaccines gv JOIN goats g ON g.id = gv.goat_id WHERE g.name = ?1",
+            [name],
+            |row| row.get(0),
+        )
+        .unwrap()
+}
+
+#[actix_rt::test]
+async fn test_goat_lifecycle_with_vaccinations() {
+    let db_pool = common::temp_pool("api_goats");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    // Create with two vaccinations and a disease
+    let mut goat = common::sample_goat("Rani");
+    goat["vaccinations"] = json!([{ "id": null, "name": "PPR" }, { "id": null, "name": "FMD" }]);
+    goat["diseases"] = json!([{ "id": null, "name": "Mastitis" }]);
+    let req = test::TestRequest::post().uri("/goats").set_json(&goat).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+    assert_eq!(vaccine_links(&db_pool, "Rani"), 2);
+
+    let req = test::TestRequest::get().uri("/goats").to_request();
+    let goats: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(goats.len(), 1);
+    assert_eq!(goats[0]["name"], "Rani");
+
+    // Updating replaces the vaccination links
+    goat["weight"] = json!(35.0);
+    goat["vaccinations"] = json!([{ "id": null, "name": "PPR" }]);
+    let req = test::TestRequest::put().uri("/goats").set_json(&goat).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+    assert_eq!(vaccine_links(&db_pool, "Rani"), 1);
+
+    let req = test::TestRequest::get().uri("/goats").to_request();
+    let goats: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(goats[0]["weight"], 35.0);
+
+    // Unknown goats are rejected on update
+    let req = test::TestRequest::put()
+        .uri("/goats")
+        .set_json(common::sample_goat("Ghost"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+
+    let req = test::TestRequest::delete()
+        .uri("/goats")
+        .set_json(json!({ "name": "Rani" }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+    // The deleted goat keeps its links until the retention cleanup purges it
+    assert_eq!(vaccine_links(&db_pool, "Rani"), 1);
+
+    let req = test::TestRequest::delete()
+        .uri("/goats")
+        .set_json(json!({ "name": "Rani" }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+}
+
+#[actix_rt::test]
+async fn test_task_lifecycle_by_id() {
+    let db_pool = common::temp_pool("api_tasks");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let task = json!({ "title": "Trim hooves", "due_date": "2025-06-01", "status": "Pending" });
+    let req = test::TestRequest::post().uri("/tasks").set_json(&task).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    let req = test::TestRequest::get().uri("/tasks?status=Pending").to_request();
+    let tasks: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(tasks.len(), 1);
+    let id = tasks[0]["id"].as_i64().unwrap();
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/tasks/{}/complete", id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+
+    let req = test::TestRequest::get().uri("/tasks?status=Done").to_request();
+    let tasks: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(tasks.len(), 1);
+
+    let req = test::TestRequest::delete().uri(&format!("/tasks/{}", id)).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+    let req = test::TestRequest::delete().uri(&format!("/tasks/{}", id)).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+}
+
+#[actix_rt::test]
+async fn test_rules_generate_reminders() {
+    let db_pool = common::temp_pool("api_rules");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    let rule = json!({
+        "name": "Deworming",
+        "task_title": "Deworm",
+        "scope": "Goat",
+        "interval_days": 90,
+        "lead_days": 0,
+        "active": true
+    });
+    let req = test::TestRequest::post().uri("/rules").set_json(&rule).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    let req = test::TestRequest::post().uri("/rules/evaluate").to_request();
+    let outcome: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(outcome["created"], 1);
+
+    let req = test::TestRequest::get().uri("/reminders").to_request();
+    let reminders: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(reminders.len(), 1);
+    let reminder_id = reminders[0]["id"].as_i64().unwrap();
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/reminders/{}/dismiss", reminder_id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+    let req = test::TestRequest::get().uri("/reminders").to_request();
+    let reminders: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert!(reminders.is_empty());
+
+    let req = test::TestRequest::get().uri("/rules").to_request();
+    let rules: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    let rule_id = rules[0]["id"].as_i64().unwrap();
+    let req = test::TestRequest::delete()
+        .uri(&format!("/rules/{}", rule_id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+}
+
+#[actix_rt::test]
+async fn test_inventory_and_finance_flow() {
+    let db_pool = common::temp_pool("api_inventory");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    let item = json!({
+        "name": "Ivermectin",
+        "category": "Medicine",
+        "quantity": 10.0,
+        "unit": "ml",
+        "unit_cost": 2.0,
+        "expiry_date": null,
+        "reorder_level": 5.0
+    });
+    let req = test::TestRequest::post().uri("/inventory").set_json(&item).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    let req = test::TestRequest::get().uri("/inventory").to_request();
+    let items: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    let item_id = items[0]["id"].as_i64().unwrap();
+
+    let mut updated = item.clone();
+    updated["reorder_level"] = json!(8.0);
+    let req = test::TestRequest::put()
+        .uri(&format!("/inventory/{}", item_id))
+        .set_json(&updated)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+
+    let consumption = json!({
+        "item_id": item_id,
+        "quantity": 3.0,
+        "purpose": "Treatment",
+        "goat_name": "Rani",
+        "consumed_on": "2025-06-01"
+    });
+    let req = test::TestRequest::post()
+        .uri(&format!("/inventory/{}/consume", item_id))
+        .set_json(&consumption)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/inventory/consumption?item_id={}", item_id))
+        .to_request();
+    let records: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(records.len(), 1);
+
+    // 7 ml left against a reorder level of 8
+    let req = test::TestRequest::get().uri("/inventory/alerts").to_request();
+    let alerts: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert!(alerts.iter().any(|a| a["kind"] == "LowStock"));
+
+    let income = json!({
+        "kind": "Income",
+        "category": "Milk",
+        "amount": 20.0,
+        "description": "Milk sale",
+        "goat_name": "Rani",
+        "date": "2025-06-02"
+    });
+    let req = test::TestRequest::post().uri("/transactions").set_json(&income).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    let req = test::TestRequest::get()
+        .uri("/transactions?goat_name=Rani")
+        .to_request();
+    let transactions: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(transactions.len(), 2);
+
+    // price 150 + income 20 - cost 100 - medicine 6
+    let req = test::TestRequest::get().uri("/finance/goats").to_request();
+    let rows: Vec<Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(rows[0]["profit"], 64.0);
+
+    let manual_id = transactions
+        .iter()
+        .find(|t| t["kind"] == "Income")
+        .and_then(|t| t["id"].as_i64())
+        .unwrap();
+    let req = test::TestRequest::delete()
+        .uri(&format!("/transactions/{}", manual_id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/inventory/{}", item_id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+}
@@ -0,0 +1,346 @@
+mod common;
+
+use actix_web::body::to_bytes;
+use actix_web::middleware::from_fn;
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::auth::check_session;
+use backend::db::DbPool;
+use backend::routes;
+use serde_json::json;
+use shared::Goat;
+use shared::archive::{
+    ArchiveManifest, ArchiveSummary, MANIFEST_PATH, PASSPHRASE_HEADER, is_encrypted,
+};
+use shared::sessions::SESSION_HEADER;
+use std::io::{Cursor, Read, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Copies `archive`, passing each entry through `edit` first.
+fn rewrite(archive: &[u8], edit: impl Fn(&str, Vec<u8>) -> Vec<u8>) -> Vec<u8> {
+    let mut source = ZipArchive::new(Cursor::new(archive)).unwrap();
+    let mut copy = ZipWriter::new(Cursor::new(Vec::new()));
+    for i in 0..source.len() {
+        let mut entry = source.by_index(i).unwrap();
+        let name = entry.name().to_string();
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).unwrap();
+        copy.start_file(name.as_str(), SimpleFileOptions::default())
+            .unwrap();
+        copy.write_all(&edit(&name, bytes)).unwrap();
+    }
+    copy.finish().unwrap().into_inner()
+}
+
+/// POSTs `archive` to a fresh farm named `name` and returns the status.
+async fn import_into(name: &str, archive: Vec<u8>) -> u16 {
+    let pool = common::temp_pool(name);
+    let owner = common::owner_token(&pool);
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(pool))
+            .configure(routes::configure),
+    )
+    .await;
+    let req = TestRequest::post()
+        .uri("/archive")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .set_payload(archive)
+        .to_request();
+    call_service(&app, req).await.status().as_u16()
+}
+
+#[actix_rt::test]
+async fn test_farm_archive_round_trip() {
+    let source_pool = common::temp_pool("archive_source");
+    let owner = common::owner_token(&source_pool);
+    let source = init_service(
+        App::new()
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(source_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    for name in ["Rani", "Moti"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .insert_header((SESSION_HEADER, owner.as_str()))
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&source, req).await.status(), 201);
+    }
+    let req = TestRequest::put()
+        .uri("/settings")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .set_json(json!({ "farm_name": "Hill Farm" }))
+        .to_request();
+    assert_eq!(call_service(&source, req).await.status(), 200);
+    let recording: Vec<u8> = (0..=255).collect();
+    source_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "INSERT INTO attachments (goat_id, author, content_type, data)
+             VALUES (2, 'Asha', 'audio/webm', ?1)",
+            [&recording],
+        )
+        .unwrap();
+    common::add_worker(&source_pool, "Asha", Some("Herder"), None, "App");
+    source_pool
+        .get_conn()
+        .unwrap()
+        .execute_batch(
+            "UPDATE workers SET pin_hash = 'asha-pin-hash';
+             INSERT INTO owner_credentials (id, password_hash) VALUES (1, 'owner-hash');
+             INSERT INTO oauth_identities (provider, subject, worker_id)
+             VALUES ('google', 'asha-google', 1);",
+        )
+        .unwrap();
+
+    // A worker may not take the farm away
+    let asha = common::worker_token(&source_pool, "Asha");
+    let req = TestRequest::get()
+        .uri("/archive")
+        .insert_header((SESSION_HEADER, asha.as_str()))
+        .to_request();
+    assert_eq!(call_service(&source, req).await.status(), 403);
+
+    let req = TestRequest::get()
+        .uri("/archive")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    let resp = call_service(&source, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "application/zip"
+    );
+    let archive = to_bytes(resp.into_body()).await.unwrap().to_vec();
+    let mut zip = ZipArchive::new(Cursor::new(&archive[..])).unwrap();
+    let manifest: ArchiveManifest =
+        serde_json::from_reader(zip.by_name(MANIFEST_PATH).unwrap()).unwrap();
+    assert_eq!(manifest.files.len(), 1);
+    assert!(
+        manifest
+            .tables
+            .iter()
+            .any(|t| t.name == "goats" && t.rows == 2)
+    );
+    for credentials in ["api_keys", "owner_credentials", "oauth_identities"] {
+        assert!(manifest.tables.iter().all(|t| t.name != credentials));
+    }
+    let workers: serde_json::Value =
+        serde_json::from_reader(zip.by_name("tables/workers.json").unwrap()).unwrap();
+    assert_eq!(workers[0]["name"], "Asha");
+    assert!(workers[0].get("pin_hash").is_none());
+
+    let target_pool = common::temp_pool("archive_target");
+    target_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "INSERT INTO owner_credentials (id, password_hash) VALUES (1, 'target-hash')",
+            [],
+        )
+        .unwrap();
+    let target_owner = common::owner_token(&target_pool);
+    let target = init_service(
+        App::new()
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(target_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let req = TestRequest::post()
+        .uri("/archive")
+        .set_payload(archive.clone())
+        .to_request();
+    assert_eq!(call_service(&target, req).await.status(), 401);
+    let req = TestRequest::post()
+        .uri("/archive")
+        .insert_header((SESSION_HEADER, target_owner.as_str()))
+        .set_payload(archive.clone())
+        .to_request();
+    let summary: ArchiveSummary = call_and_read_body_json(&target, req).await;
+    assert_eq!(summary.files, 1);
+    assert!(summary.skipped.is_empty());
+    // The owner still signs in as before, and Asha needs a new PIN
+    let conn = target_pool.get_conn().unwrap();
+    let password: String = conn
+        .query_row("SELECT password_hash FROM owner_credentials", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(password, "target-hash");
+    let pin: Option<String> = conn
+        .query_row("SELECT pin_hash FROM workers", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(pin, None);
+    drop(conn);
+
+    let get = |uri: &str, token: &str| {
+        TestRequest::get()
+            .uri(uri)
+            .insert_header((SESSION_HEADER, token))
+            .to_request()
+    };
+    let copied: Vec<Goat> = call_and_read_body_json(&target, get("/goats", &target_owner)).await;
+    let original: Vec<Goat> = call_and_read_body_json(&source, get("/goats", &owner)).await;
+    assert_eq!(copied, original);
+    let resp = call_service(&target, get("/attachments/1", &target_owner)).await;
+    assert_eq!(resp.headers().get("content-type").unwrap(), "audio/webm");
+    assert_eq!(to_bytes(resp.into_body()).await.unwrap(), recording);
+    let settings: serde_json::Value =
+        call_and_read_body_json(&target, get("/settings", &target_owner)).await;
+    assert_eq!(settings["farm_name"], "Hill Farm");
+
+    // The farm now has goats, so a second import is refused
+    let req = TestRequest::post()
+        .uri("/archive")
+        .insert_header((SESSION_HEADER, target_owner.as_str()))
+        .set_payload(archive)
+        .to_request();
+    assert_eq!(call_service(&target, req).await.status(), 400);
+}
+
+#[actix_rt::test]
+async fn test_damaged_archives_are_refused() {
+    let pool: DbPool = common::temp_pool("archive_damaged");
+    let owner = common::owner_token(&pool);
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let req = TestRequest::post()
+        .uri("/goats")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    call_service(&app, req).await;
+    let req = TestRequest::get()
+        .uri("/archive")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    let resp = call_service(&app, req).await;
+    let archive = to_bytes(resp.into_body()).await.unwrap().to_vec();
+
+    assert_eq!(
+        import_into("archive_garbage", b"not a zip".to_vec()).await,
+        400
+    );
+    let edited = rewrite(&archive, |name, bytes| {
+        if name == "tables/goats.json" {
+            String::from_utf8(bytes)
+                .unwrap()
+                .replace("Rani", "Raja")
+                .into_bytes()
+        } else {
+            bytes
+        }
+    });
+    assert_eq!(import_into("archive_edited", edited).await, 400);
+    let newer = rewrite(&archive, |name, bytes| {
+        if name == MANIFEST_PATH {
+            let mut manifest: ArchiveManifest = serde_json::from_slice(&bytes).unwrap();
+            manifest.schema_version += 1;
+            serde_json::to_vec(&manifest).unwrap()
+        } else {
+            bytes
+        }
+    });
+    assert_eq!(import_into("archive_newer", newer).await, 400);
+    // Archives with more files than any farm has are not unpacked
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut crowded = ZipWriter::new(Cursor::new(Vec::new()));
+    for i in 0..50_001 {
+        crowded.start_file(format!("files/{}", i), stored).unwrap();
+    }
+    let crowded = crowded.finish().unwrap().into_inner();
+    assert_eq!(import_into("archive_crowded", crowded).await, 400);
+    // An unchanged copy still imports
+    let copy = rewrite(&archive, |_, bytes| bytes);
+    assert_eq!(import_into("archive_copy", copy).await, 200);
+}
+
+#[actix_rt::test]
+async fn test_encrypted_archive_needs_its_passphrase() {
+    let pool = common::temp_pool("archive_encrypted");
+    let owner = common::owner_token(&pool);
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(pool))
+            .configure(routes::configure),
+    )
+    .await;
+    let req = TestRequest::post()
+        .uri("/goats")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    call_service(&app, req).await;
+
+    let req = TestRequest::get()
+        .uri("/archive")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .insert_header((PASSPHRASE_HEADER, "short"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    // Percent-encoded, so the passphrase may hold any character
+    let req = TestRequest::get()
+        .uri("/archive")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .insert_header((PASSPHRASE_HEADER, "ch%C3%A8vre%20pasture%2042"))
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert!(
+        resp.headers()
+            .get("content-disposition")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .ends_with(".zip.enc\"")
+    );
+    let archive = to_bytes(resp.into_body()).await.unwrap().to_vec();
+    assert!(is_encrypted(&archive));
+    assert!(!archive.windows(4).any(|w| w == b"Rani"));
+
+    let restore = async |name: &str, passphrase: Option<&str>| {
+        let pool = common::temp_pool(name);
+        let owner = common::owner_token(&pool);
+        let app = init_service(
+            App::new()
+                .wrap(from_fn(check_session))
+                .app_data(web::Data::new(pool))
+                .configure(routes::configure),
+        )
+        .await;
+        let mut req = TestRequest::post()
+            .uri("/archive")
+            .insert_header((SESSION_HEADER, owner.as_str()))
+            .set_payload(archive.clone());
+        if let Some(passphrase) = passphrase {
+            req = req.insert_header((PASSPHRASE_HEADER, passphrase));
+        }
+        call_service(&app, req.to_request()).await.status().as_u16()
+    };
+    assert_eq!(restore("archive_no_passphrase", None).await, 400);
+    assert_eq!(
+        restore("archive_wrong_passphrase", Some("chevre pasture 42")).await,
+        400
+    );
+    assert_eq!(
+        restore(
+            "archive_right_passphrase",
+            Some("ch%C3%A8vre%20pasture%2042")
+        )
+        .await,
+        200
+    );
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use actix_web::test::{
+    TestRequest, call_and_read_body_json, call_service, init_service, read_body,
+};
+use actix_web::{App, web};
+use backend::routes;
+use serde_json::json;
+use shared::attachments::{Attachment, MAX_VOICE_NOTE_SECS, check_voice_note};
+use shared::tasks::Task;
+
+#[test]
+fn test_voice_note_limits() {
+    assert!(check_voice_note("audio/webm;codecs=opus", 12.5, 4000).is_ok());
+    assert!(check_voice_note("video/webm", 12.5, 4000).is_err());
+    assert!(check_voice_note("audio/ogg", 12.5, 0).is_err());
+    assert!(check_voice_note("audio/ogg", MAX_VOICE_NOTE_SECS + 1.0, 4000).is_err());
+    assert!(check_voice_note("audio/ogg", 0.0, 4000).is_err());
+}
+
+#[actix_rt::test]
+async fn test_voice_notes_on_goats_and_tasks() {
+    let db_pool = common::temp_pool("attachments");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::post()
+        .uri("/tasks")
+        .set_json(json!({
+            "id": null, "title": "Trim hooves", "notes": null, "goat_name": null,
+            "space_id": null, "space_name": null, "rule_id": null,
+            "due_date": "2026-05-01", "status": "Pending"
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::get().uri("/tasks").to_request();
+    let tasks: Vec<Task> = call_and_read_body_json(&app, req).await;
+    let task_id = tasks[0].id.unwrap();
+
+    let voice = |query: &str, content_type: &str| {
+        TestRequest::post()
+            .uri(&format!("/attachments/voice?{}", query))
+            .insert_header(("Content-Type", content_type))
+            .set_payload(&b"OggS fake opus"[..])
+            .to_request()
+    };
+    for (query, content_type) in [
+        ("goat_name=Rani&author=Ravi&duration_secs=8", "text/plain"),
+        ("goat_name=Rani&author=Ravi&duration_secs=600", "audio/ogg"),
+        ("goat_name=Rani&author=%20&duration_secs=8", "audio/ogg"),
+        ("goat_name=Nobody&author=Ravi&duration_secs=8", "audio/ogg"),
+        ("author=Ravi&duration_secs=8", "audio/ogg"),
+        (
+            &format!(
+                "goat_name=Rani&task_id={}&author=Ravi&duration_secs=8",
+                task_id
+            ),
+            "audio/ogg",
+        ),
+    ] {
+        let status = call_service(&app, voice(query, content_type))
+            .await
+            .status();
+        assert_eq!(status, 400, "{}", query);
+    }
+
+    let on_goat: Attachment = call_and_read_body_json(
+        &app,
+        voice("goat_name=Rani&author=Ravi&duration_secs=8.5", "audio/ogg"),
+    )
+    .await;
+    assert_eq!(on_goat.goat_name.as_deref(), Some("Rani"));
+    assert_eq!(on_goat.task_id, None);
+    assert_eq!(on_goat.duration_secs, Some(8.5));
+    assert_eq!(on_goat.size_bytes, 14);
+    let on_task: Attachment = call_and_read_body_json(
+        &app,
+        voice(
+            &format!("task_id={}&author=Asha&duration_secs=3", task_id),
+            "audio/webm;codecs=opus",
+        ),
+    )
+    .await;
+    assert_eq!(on_task.task_id, Some(task_id));
+
+    // Each target lists only its own notes
+    let req = TestRequest::get()
+        .uri("/attachments?goat_name=Rani")
+        .to_request();
+    let listed: Vec<Attachment> = call_and_read_body_json(&app, req).await;
+    assert_eq!(listed, vec![on_goat.clone()]);
+    let req = TestRequest::get()
+        .uri(&format!("/attachments?task_id={}", task_id))
+        .to_request();
+    let listed: Vec<Attachment> = call_and_read_body_json(&app, req).await;
+    assert_eq!(listed, vec![on_task.clone()]);
+
+    let req = TestRequest::get()
+        .uri(&format!("/attachments/{}", on_task.id))
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "audio/webm;codecs=opus"
+    );
+    assert_eq!(read_body(resp).await.to_vec(), b"OggS fake opus".to_vec());
+
+    let req = TestRequest::delete()
+        .uri(&format!("/attachments/{}", on_goat.id))
+        .to_request();
+    assert!(call_service(&app, req).await.status().is_success());
+    let req = TestRequest::get()
+        .uri(&format!("/attachments/{}", on_goat.id))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+}
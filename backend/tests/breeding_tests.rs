@@ -0,0 +1,693 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::breeding::{ParentMap, inbreeding_coefficient, service_outcome};
+use backend::routes;
+use chrono::NaiveDate;
+use serde_json::{Value, json};
+use shared::breeding::{
+    BreedingService, ExternalAnimal, GeneticTags, InheritedTag, KiddingRecord, MethodSuccess,
+    Neutering, PedigreeNode, ServiceMethod, ServiceOutcome, inherited_tags,
+};
+
+/// Builds a parent map from `(id, sire, dam)` triples.
+fn pedigree(links: &[(i64, Option<i64>, Option<i64>)]) -> ParentMap {
+    links
+        .iter()
+        .map(|(id, sire, dam)| (*id, (*sire, *dam)))
+        .collect()
+}
+
+#[test]
+fn test_inbreeding_coefficient() {
+    // 1 × 2 -> 3 and 4 (full siblings); 1 × 5 -> 6 (half sibling of 3)
+    let parents = pedigree(&[
+        (3, Some(1), Some(2)),
+        (4, Some(1), Some(2)),
+        (6, Some(1), Some(5)),
+    ]);
+
+    // Unrelated
+    assert_eq!(inbreeding_coefficient(1, 2, &parents), 0.0);
+    // Parent–offspring
+    assert_eq!(inbreeding_coefficient(1, 3, &parents), 0.25);
+    // Full siblings
+    assert_eq!(inbreeding_coefficient(4, 3, &parents), 0.25);
+    // Half siblings
+    assert_eq!(inbreeding_coefficient(6, 3, &parents), 0.125);
+}
+
+#[test]
+fn test_inherited_tags() {
+    fn goat(
+        name: &str,
+        tags: &[&str],
+        sire: Option<PedigreeNode>,
+        dam: Option<PedigreeNode>,
+    ) -> PedigreeNode {
+        PedigreeNode {
+            name: name.into(),
+            external: false,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            sire: sire.map(Box::new),
+            dam: dam.map(Box::new),
+        }
+    }
+    // Kid of Sultan × Moti; Moti is a kid of Raja × Rani
+    let moti = goat(
+        "Moti",
+        &["fast growth"],
+        Some(goat("Raja", &["High twinning"], None, None)),
+        Some(goat("Rani", &["High twinning", "Fast growth"], None, None)),
+    );
+    let kid = goat(
+        "Kid",
+        &["Heat tolerant"],
+        Some(goat("Sultan", &[], None, None)),
+        Some(moti),
+    );
+
+    assert_eq!(
+        inherited_tags(&kid),
+        vec![
+            InheritedTag {
+                tag: "fast growth".into(),
+                share: 0.5,
+                carriers: vec!["Moti".into(), "Rani".into()],
+            },
+            InheritedTag {
+                tag: "High twinning".into(),
+                share: 0.5,
+                carriers: vec!["Raja".into(), "Rani".into()],
+            },
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn test_breeding_recommendations_flag_relatives() {
+    let db_pool = common::temp_pool("breeding");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for (name, gender) in [
+        ("Raja", "Male"),
+        ("Rani", "Female"),
+        ("Bheem", "Male"),
+        ("Sultan", "Male"),
+        ("Moti", "Female"),
+        ("Chhoti", "Female"),
+    ] {
+        let mut goat = common::sample_goat(name);
+        goat["gender"] = json!(gender);
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(&goat)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    // Sultan and Moti are both kids of Raja × Rani; Chhoti is a newborn.
+    // Bheem is unrelated to all of them.
+    for (goat, dob) in [("Sultan", "2022-03-01"), ("Moti", "2022-03-01")] {
+        let req = TestRequest::put()
+            .uri("/breeding/pedigree")
+            .set_json(json!({
+                "goat_name": goat, "sire_name": "Raja", "dam_name": "Rani", "date_of_birth": dob
+            }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 200);
+    }
+    let today = chrono::Local::now()
+        .date_naive()
+        .format("%Y-%m-%d")
+        .to_string();
+    let req = TestRequest::put()
+        .uri("/breeding/pedigree")
+        .set_json(json!({
+            "goat_name": "Chhoti", "sire_name": null, "dam_name": "Rani", "date_of_birth": today
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    // A doe cannot be listed as a sire
+    let req = TestRequest::put()
+        .uri("/breeding/pedigree")
+        .set_json(json!({
+            "goat_name": "Sultan", "sire_name": "Rani", "dam_name": null, "date_of_birth": null
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    // Kidding records validate counts and are listed back
+    let kidding = json!({
+        "id": null, "doe_name": "Rani", "buck_name": "Raja", "kidded_on": "2022-03-01",
+        "kids_born": 2, "kids_alive": 3, "notes": null
+    });
+    let req = TestRequest::post()
+        .uri("/breeding/kiddings")
+        .set_json(&kidding)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let mut kidding = kidding;
+    kidding["kids_alive"] = json!(2);
+    let req = TestRequest::post()
+        .uri("/breeding/kiddings")
+        .set_json(&kidding)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::get()
+        .uri("/breeding/kiddings?doe_name=Rani")
+        .to_request();
+    let kiddings: Vec<Value> = call_and_read_body_json(&app, req).await;
+    assert_eq!(kiddings.len(), 1);
+    assert_eq!(kiddings[0]["buck_name"], "Raja");
+
+    let req = TestRequest::get()
+        .uri("/breeding/recommendations")
+        .to_request();
+    let recs: Vec<Value> = call_and_read_body_json(&app, req).await;
+    let pair = |buck: &str, doe: &str| {
+        recs.iter()
+            .find(|r| r["buck_name"] == buck && r["doe_name"] == doe)
+            .cloned()
+    };
+
+    // Full siblings are flagged and ranked below an unrelated pairing
+    let siblings = pair("Sultan", "Moti").expect("sibling pairing listed");
+    assert_eq!(siblings["close_relatives"], true);
+    assert_eq!(siblings["inbreeding_coefficient"], 0.25);
+    let unrelated = pair("Bheem", "Moti").expect("unrelated pairing listed");
+    assert_eq!(unrelated["close_relatives"], false);
+    assert!(unrelated["score"].as_f64() > siblings["score"].as_f64());
+    // The newborn doe is too young to be paired
+    assert!(recs.iter().all(|r| r["doe_name"] != "Chhoti"));
+
+    let req = TestRequest::get()
+        .uri("/breeding/recommendations?doe=Moti&limit=1")
+        .to_request();
+    let recs: Vec<Value> = call_and_read_body_json(&app, req).await;
+    assert_eq!(recs.len(), 1);
+    assert_eq!(recs[0]["doe_name"], "Moti");
+
+    // Castrating Bheem makes him a wether: he is listed and no longer paired
+    let wether = json!({ "goat_name": "Bheem", "neutered_on": "2024-05-01", "reason": " Meat " });
+    let req = TestRequest::put()
+        .uri("/breeding/neuterings")
+        .set_json(&wether)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    for bad in [
+        json!({ "goat_name": "Nobody", "neutered_on": "2024-05-01", "reason": null }),
+        json!({ "goat_name": "Bheem", "neutered_on": "May 1", "reason": null }),
+    ] {
+        let req = TestRequest::put()
+            .uri("/breeding/neuterings")
+            .set_json(&bad)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400);
+    }
+    let req = TestRequest::get().uri("/breeding/neuterings").to_request();
+    let neuterings: Vec<Neutering> = call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        neuterings,
+        vec![Neutering {
+            goat_name: "Bheem".into(),
+            neutered_on: "2024-05-01".into(),
+            reason: Some("Meat".into()),
+        }]
+    );
+    let req = TestRequest::get()
+        .uri("/breeding/recommendations")
+        .to_request();
+    let recs: Vec<Value> = call_and_read_body_json(&app, req).await;
+    assert!(recs.iter().all(|r| r["buck_name"] != "Bheem"));
+
+    let delete = || {
+        TestRequest::delete()
+            .uri("/breeding/neuterings/Bheem")
+            .to_request()
+    };
+    assert_eq!(call_service(&app, delete()).await.status(), 200);
+    assert_eq!(call_service(&app, delete()).await.status(), 400);
+
+    // Raja in the trash is still the kids' sire but is never paired
+    let req = TestRequest::delete()
+        .uri("/goats")
+        .set_json(json!({ "name": "Raja" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::get()
+        .uri("/breeding/recommendations")
+        .to_request();
+    let recs: Vec<Value> = call_and_read_body_json(&app, req).await;
+    assert!(recs.iter().all(|r| r["buck_name"] != "Raja"));
+    let siblings = recs
+        .iter()
+        .find(|r| r["buck_name"] == "Sultan" && r["doe_name"] == "Moti")
+        .expect("sibling pairing listed");
+    assert_eq!(siblings["inbreeding_coefficient"], 0.25);
+    let req = TestRequest::get()
+        .uri("/breeding/pedigree/Moti")
+        .to_request();
+    let tree: Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(tree["sire"]["name"], "Raja");
+    let req = TestRequest::get()
+        .uri("/breeding/pedigree/Raja")
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+}
+
+#[actix_rt::test]
+async fn test_genetic_tags_and_pedigree_tree() {
+    let db_pool = common::temp_pool("genetic_tags");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for (name, gender) in [("Raja", "Male"), ("Rani", "Female"), ("Moti", "Female")] {
+        let mut goat = common::sample_goat(name);
+        goat["gender"] = json!(gender);
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(&goat)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    let req = TestRequest::put()
+        .uri("/breeding/pedigree")
+        .set_json(json!({ "goat_name": "Moti", "sire_name": "Raja", "dam_name": "Rani" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    let set_tags = |goat: &str, tags: &[&str]| {
+        TestRequest::put()
+            .uri("/breeding/tags")
+            .set_json(json!({ "goat_name": goat, "tags": tags }))
+            .to_request()
+    };
+    let stored: GeneticTags = call_and_read_body_json(
+        &app,
+        set_tags(
+            "Rani",
+            &[" High   twinning ", "Fast growth", "high twinning"],
+        ),
+    )
+    .await;
+    assert_eq!(stored.tags, vec!["High twinning", "Fast growth"]);
+    let req = set_tags("Raja", &["Fast growth"]);
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    for (goat, tags) in [("Ghost", vec!["Fast growth"]), ("Raja", vec!["  "])] {
+        let req = set_tags(goat, &tags);
+        assert_eq!(call_service(&app, req).await.status(), 400, "{}", goat);
+    }
+
+    let req = TestRequest::get()
+        .uri("/breeding/tags?tag=HIGH%20TWINNING")
+        .to_request();
+    let tagged: Vec<GeneticTags> = call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        tagged,
+        vec![GeneticTags {
+            goat_name: "Rani".into(),
+            tags: vec!["Fast growth".into(), "High twinning".into()],
+        }]
+    );
+    let req = TestRequest::get().uri("/breeding/tags").to_request();
+    let tagged: Vec<GeneticTags> = call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        tagged
+            .iter()
+            .map(|t| t.goat_name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Raja", "Rani"]
+    );
+
+    let req = TestRequest::get()
+        .uri("/breeding/pedigree/Moti")
+        .to_request();
+    let tree: PedigreeNode = call_and_read_body_json(&app, req).await;
+    assert_eq!(tree.name, "Moti");
+    assert!(tree.tags.is_empty());
+    assert_eq!(tree.sire.as_ref().unwrap().tags, vec!["Fast growth"]);
+    assert_eq!(tree.dam.as_ref().unwrap().name, "Rani");
+    let inherited: Vec<(String, f64)> = inherited_tags(&tree)
+        .into_iter()
+        .map(|i| (i.tag, i.share))
+        .collect();
+    assert_eq!(
+        inherited,
+        vec![("Fast growth".into(), 1.0), ("High twinning".into(), 0.5)]
+    );
+
+    let req = TestRequest::get()
+        .uri("/breeding/pedigree/Ghost")
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    // Clearing a goat's tags drops it from the list
+    let req = set_tags("Raja", &[]);
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::get().uri("/breeding/tags").to_request();
+    let tagged: Vec<GeneticTags> = call_and_read_body_json(&app, req).await;
+    assert_eq!(tagged.len(), 1);
+}
+
+#[test]
+fn test_service_outcome() {
+    let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+    let served = date("2024-01-10");
+    // 150 days later, within the window
+    assert_eq!(
+        service_outcome(served, &[date("2024-06-08")], date("2025-01-01")),
+        ServiceOutcome::Kidded
+    );
+    // A kidding too soon after the service belongs to an earlier one
+    assert_eq!(
+        service_outcome(served, &[date("2024-02-01")], date("2025-01-01")),
+        ServiceOutcome::Open
+    );
+    assert_eq!(
+        service_outcome(served, &[], date("2024-06-15")),
+        ServiceOutcome::Pending
+    );
+    assert_eq!(
+        service_outcome(served, &[], date("2024-06-19")),
+        ServiceOutcome::Open
+    );
+}
+
+#[actix_rt::test]
+async fn test_ai_services_and_success_rates() {
+    let db_pool = common::temp_pool("breeding_services");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for (name, gender) in [
+        ("Raja", "Male"),
+        ("Rani", "Female"),
+        ("Moti", "Female"),
+        ("Gita", "Female"),
+    ] {
+        let mut goat = common::sample_goat(name);
+        goat["gender"] = json!(gender);
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(&goat)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    let today = chrono::Local::now()
+        .date_naive()
+        .format("%Y-%m-%d")
+        .to_string();
+    let thunder = json!({
+        "name": "Thunder", "breed": "Boer", "registration": "BR-1187", "source": "  Sunrise Semen Bank "
+    });
+    let services = [
+        json!({
+            "id": null, "doe_name": "Rani", "served_on": "2024-01-10", "method": "Natural",
+            "buck_name": "Raja", "outside_sire": null, "straw_id": null, "technician": null,
+            "notes": null
+        }),
+        json!({
+            "id": null, "doe_name": "Moti", "served_on": "2024-02-01", "method": "Natural",
+            "buck_name": "Raja", "outside_sire": null, "straw_id": null, "technician": "",
+            "notes": null
+        }),
+        json!({
+            "id": null, "doe_name": "Gita", "served_on": "2024-03-01", "method": "Artificial",
+            "buck_name": null, "outside_sire": thunder, "straw_id": "BX-42",
+            "technician": "Dr. Mehta", "notes": null
+        }),
+        json!({
+            "id": null, "doe_name": "Moti", "served_on": today, "method": "Artificial",
+            "buck_name": "Raja", "outside_sire": null, "straw_id": "RAJA-2026-03",
+            "technician": null, "notes": null
+        }),
+    ];
+    for service in &services {
+        let req = TestRequest::post()
+            .uri("/breeding/services")
+            .set_json(service)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    let invalid = |change: Value| {
+        let mut service = services[2].clone();
+        for (key, value) in change.as_object().unwrap() {
+            service[key] = value.clone();
+        }
+        TestRequest::post()
+            .uri("/breeding/services")
+            .set_json(service)
+            .to_request()
+    };
+    for req in [
+        invalid(json!({ "straw_id": "  " })),
+        invalid(json!({ "method": "Natural" })),
+        invalid(json!({ "buck_name": "Raja" })),
+        invalid(json!({ "outside_sire": null })),
+        invalid(json!({ "outside_sire": null, "buck_name": "Rani" })),
+        invalid(json!({ "doe_name": "Raja" })),
+        invalid(json!({ "served_on": "March 1st" })),
+    ] {
+        assert_eq!(call_service(&app, req).await.status(), 400);
+    }
+
+    for (doe, buck, kidded_on) in [
+        ("Rani", Some("Raja"), "2024-06-08"),
+        ("Gita", None, "2024-07-30"),
+    ] {
+        let req = TestRequest::post()
+            .uri("/breeding/kiddings")
+            .set_json(json!({
+                "id": null, "doe_name": doe, "buck_name": buck, "kidded_on": kidded_on,
+                "kids_born": 2, "kids_alive": 2, "notes": null
+            }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    let req = TestRequest::get()
+        .uri("/breeding/services?doe_name=Gita")
+        .to_request();
+    let gita: Vec<BreedingService> = call_and_read_body_json(&app, req).await;
+    assert_eq!(gita.len(), 1);
+    assert_eq!(gita[0].method, ServiceMethod::Artificial);
+    assert_eq!(gita[0].outcome, Some(ServiceOutcome::Kidded));
+    assert_eq!(gita[0].sire_label(), "Thunder (outside)");
+    let sire = gita[0].outside_sire.as_ref().unwrap();
+    assert_eq!(sire.source.as_deref(), Some("Sunrise Semen Bank"));
+    assert_eq!(gita[0].straw_id.as_deref(), Some("BX-42"));
+
+    let req = TestRequest::get().uri("/breeding/services").to_request();
+    let all: Vec<BreedingService> = call_and_read_body_json(&app, req).await;
+    let outcomes: Vec<_> = all
+        .iter()
+        .map(|s| (s.doe_name.as_str(), s.outcome.unwrap()))
+        .collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            ("Moti", ServiceOutcome::Pending),
+            ("Gita", ServiceOutcome::Kidded),
+            ("Moti", ServiceOutcome::Open),
+            ("Rani", ServiceOutcome::Kidded),
+        ]
+    );
+    assert_eq!(all[2].technician, None);
+
+    let req = TestRequest::get()
+        .uri("/breeding/services/success")
+        .to_request();
+    let rates: Vec<MethodSuccess> = call_and_read_body_json(&app, req).await;
+    assert_eq!(rates[0].method, ServiceMethod::Natural);
+    assert_eq!(
+        (rates[0].services, rates[0].kidded, rates[0].open),
+        (2, 1, 1)
+    );
+    assert_eq!(rates[0].success_rate(), Some(0.5));
+    assert_eq!(rates[1].method, ServiceMethod::Artificial);
+    assert_eq!((rates[1].services, rates[1].pending), (2, 1));
+    assert_eq!(rates[1].success_rate(), Some(1.0));
+
+    // Services move the doe's last-bred date forward, never back
+    let conn = db_pool.get_conn().unwrap();
+    let last_bred = |name: &str| -> Option<String> {
+        conn.query_row(
+            "SELECT last_bred FROM goats WHERE name = ?1",
+            [name],
+            |row| row.get(0),
+        )
+        .unwrap()
+    };
+    assert_eq!(last_bred("Gita").as_deref(), Some("2024-03-01"));
+    assert_eq!(last_bred("Moti"), Some(today));
+}
+
+#[actix_rt::test]
+async fn test_external_animals_in_pedigrees_and_records() {
+    let db_pool = common::temp_pool("breeding_external");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for (name, gender) in [("Moti", "Female"), ("Gita", "Female"), ("Sultan", "Male")] {
+        let mut goat = common::sample_goat(name);
+        goat["gender"] = json!(gender);
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(&goat)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    let add = |animal: Value| {
+        TestRequest::post()
+            .uri("/breeding/external")
+            .set_json(animal)
+            .to_request()
+    };
+    let thunder: ExternalAnimal = call_and_read_body_json(
+        &app,
+        add(json!({
+            "id": null, "name": " Thunder ", "gender": "Male", "breed": "Boer",
+            "registration": "BR-1187", "owner": "Patel farm", "notes": "  "
+        })),
+    )
+    .await;
+    assert_eq!(thunder.name, "Thunder");
+    assert_eq!(thunder.notes, None);
+    let req = add(json!({
+        "id": null, "name": "Bela", "gender": "Female", "breed": null,
+        "registration": null, "owner": null, "notes": null
+    }));
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    // Blank names, duplicates and herd names are refused
+    for name in ["  ", "Thunder", "Moti"] {
+        let req = add(json!({
+            "id": null, "name": name, "gender": "Male", "breed": null,
+            "registration": null, "owner": null, "notes": null
+        }));
+        assert_eq!(call_service(&app, req).await.status(), 400);
+    }
+    let req = TestRequest::get().uri("/breeding/external").to_request();
+    let registry: Vec<ExternalAnimal> = call_and_read_body_json(&app, req).await;
+    let names: Vec<_> = registry.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, vec!["Bela", "Thunder"]);
+
+    // Moti and Sultan are both kids of the neighbour's buck Thunder
+    let pedigree = |goat: &str, sire: &str, dam: Option<&str>, dam_external: bool| {
+        TestRequest::put()
+            .uri("/breeding/pedigree")
+            .set_json(json!({
+                "goat_name": goat, "sire_name": sire, "dam_name": dam,
+                "date_of_birth": "2022-03-01", "sire_external": true,
+                "dam_external": dam_external
+            }))
+            .to_request()
+    };
+    for req in [
+        pedigree("Moti", "Thunder", Some("Bela"), true),
+        pedigree("Sultan", "Thunder", None, false),
+    ] {
+        assert_eq!(call_service(&app, req).await.status(), 200);
+    }
+    // Bela is a doe, and Moti is not in the registry
+    for req in [
+        pedigree("Gita", "Bela", None, false),
+        pedigree("Gita", "Moti", None, false),
+    ] {
+        assert_eq!(call_service(&app, req).await.status(), 400);
+    }
+
+    let req = TestRequest::get()
+        .uri("/breeding/pedigree/Moti")
+        .to_request();
+    let tree: PedigreeNode = call_and_read_body_json(&app, req).await;
+    assert!(!tree.external);
+    let sire = tree.sire.as_ref().unwrap();
+    assert_eq!((sire.name.as_str(), sire.external), ("Thunder", true));
+    assert_eq!(tree.dam.as_ref().unwrap().name, "Bela");
+
+    let req = TestRequest::get()
+        .uri("/breeding/recommendations")
+        .to_request();
+    let recs: Vec<Value> = call_and_read_body_json(&app, req).await;
+    let half_siblings = recs
+        .iter()
+        .find(|r| r["buck_name"] == "Sultan" && r["doe_name"] == "Moti")
+        .expect("half sibling pairing listed");
+    assert_eq!(half_siblings["inbreeding_coefficient"], 0.125);
+
+    let req = TestRequest::post()
+        .uri("/breeding/kiddings")
+        .set_json(json!({
+            "id": null, "doe_name": "Gita", "buck_name": "Thunder", "buck_external": true,
+            "kidded_on": "2024-07-30", "kids_born": 1, "kids_alive": 1, "notes": null
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::get()
+        .uri("/breeding/kiddings?doe_name=Gita")
+        .to_request();
+    let kiddings: Vec<KiddingRecord> = call_and_read_body_json(&app, req).await;
+    assert_eq!(kiddings[0].buck_name.as_deref(), Some("Thunder"));
+    assert!(kiddings[0].buck_external);
+
+    // A service by a registry sire picks up the registry's details
+    let req = TestRequest::post()
+        .uri("/breeding/services")
+        .set_json(json!({
+            "id": null, "doe_name": "Gita", "served_on": "2025-01-05", "method": "Natural",
+            "buck_name": null, "outside_sire": { "name": "Thunder" }, "straw_id": null,
+            "technician": null, "notes": null
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::get()
+        .uri("/breeding/services?doe_name=Gita")
+        .to_request();
+    let services: Vec<BreedingService> = call_and_read_body_json(&app, req).await;
+    let sire = services[0].outside_sire.as_ref().unwrap();
+    assert_eq!(sire.breed.as_deref(), Some("Boer"));
+    assert_eq!(sire.registration.as_deref(), Some("BR-1187"));
+    assert_eq!(sire.source.as_deref(), Some("Patel farm"));
+
+    // Removing Thunder unlinks him everywhere
+    let delete = || {
+        TestRequest::delete()
+            .uri(&format!("/breeding/external/{}", thunder.id.unwrap()))
+            .to_request()
+    };
+    assert_eq!(call_service(&app, delete()).await.status(), 200);
+    assert_eq!(call_service(&app, delete()).await.status(), 400);
+    let req = TestRequest::get()
+        .uri("/breeding/pedigree/Moti")
+        .to_request();
+    let tree: PedigreeNode = call_and_read_body_json(&app, req).await;
+    assert!(tree.sire.is_none());
+    let req = TestRequest::get()
+        .uri("/breeding/kiddings?doe_name=Gita")
+        .to_request();
+    let kiddings: Vec<KiddingRecord> = call_and_read_body_json(&app, req).await;
+    assert_eq!(kiddings[0].buck_name, None);
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use actix_web::middleware::from_fn;
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::auth::check_session;
+use backend::permissions::enforce_permissions;
+use backend::routes;
+use serde_json::json;
+use shared::breeds::{BreedPurpose, CatalogBreed, builtin_catalog, search_catalog};
+use shared::sessions::SESSION_HEADER;
+
+#[test]
+fn test_search_catalog_by_purpose_and_name() {
+    let catalog = builtin_catalog();
+    let names = |hits: Vec<&CatalogBreed>| hits.iter().map(|b| b.name.clone()).collect::<Vec<_>>();
+    assert_eq!(
+        names(search_catalog(&catalog, Some(BreedPurpose::Meat), "")),
+        vec!["Osmanabadi", "BlackBengal"]
+    );
+    assert_eq!(
+        names(search_catalog(&catalog, None, " BAR")),
+        vec!["Barbari"]
+    );
+    assert_eq!(
+        names(search_catalog(&catalog, Some(BreedPurpose::Fiber), "che")),
+        vec!["Chegu"]
+    );
+    assert!(search_catalog(&catalog, Some(BreedPurpose::Dairy), "bengal").is_empty());
+}
+
+#[actix_rt::test]
+async fn test_breed_catalog() {
+    let db_pool = common::temp_pool("breeds");
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(enforce_permissions))
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let owner = common::owner_token(&db_pool);
+
+    let req = TestRequest::get()
+        .uri("/breeds")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    let catalog: Vec<CatalogBreed> = call_and_read_body_json(&app, req).await;
+    assert_eq!(catalog, builtin_catalog());
+
+    // Changing the catalog needs a session, and permission to edit settings
+    let boer = json!({ "name": " Boer ", "purpose": "Meat" });
+    let req = TestRequest::post()
+        .uri("/breeds")
+        .set_json(&boer)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+    for (name, role, granted) in [
+        (
+            "Ravi",
+            "Herder",
+            json!([
+                { "module": "Breeding", "action": "View" },
+                { "module": "Breeding", "action": "Edit" },
+                { "module": "Breeding", "action": "Delete" }
+            ]),
+        ),
+        (
+            "Asha",
+            "Manager",
+            json!([
+                { "module": "Breeding", "action": "View" },
+                { "module": "Breeding", "action": "Edit" },
+                { "module": "Settings", "action": "View" },
+                { "module": "Settings", "action": "Edit" }
+            ]),
+        ),
+    ] {
+        common::add_worker(&db_pool, name, Some(role), None, "App");
+        db_pool
+            .get_conn()
+            .unwrap()
+            .execute(
+                "INSERT INTO role_permissions (role, granted) VALUES (?1, ?2)",
+                [role.to_string(), granted.to_string()],
+            )
+            .unwrap();
+    }
+    let ravi = common::worker_token(&db_pool, "Ravi");
+    let asha = common::worker_token(&db_pool, "Asha");
+    let req = TestRequest::post()
+        .uri("/breeds")
+        .insert_header((SESSION_HEADER, ravi.as_str()))
+        .set_json(&boer)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 403);
+    let req = TestRequest::post()
+        .uri("/breeds")
+        .insert_header((SESSION_HEADER, asha.as_str()))
+        .set_json(json!({ "name": "Saanen", "purpose": "Dual" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let add = |body: serde_json::Value| {
+        TestRequest::post()
+            .uri("/breeds")
+            .insert_header((SESSION_HEADER, owner.as_str()))
+            .set_json(body)
+            .to_request()
+    };
+
+    let added: CatalogBreed = call_and_read_body_json(&app, add(boer)).await;
+    assert_eq!(
+        added,
+        CatalogBreed {
+            name: "Boer".into(),
+            purpose: BreedPurpose::Meat,
+            builtin: false,
+        }
+    );
+    let req = add(json!({ "name": "Angora", "purpose": "Fiber", "builtin": true }));
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    for body in [
+        json!({ "name": "boer", "purpose": "Dual" }),
+        json!({ "name": "sirohi", "purpose": "Meat" }),
+        json!({ "name": "Other", "purpose": "Meat" }),
+        json!({ "name": "  ", "purpose": "Meat" }),
+    ] {
+        assert_eq!(
+            call_service(&app, add(body.clone())).await.status(),
+            400,
+            "{}",
+            body
+        );
+    }
+
+    let req = TestRequest::get()
+        .uri("/breeds")
+        .insert_header((SESSION_HEADER, ravi.as_str()))
+        .to_request();
+    let catalog: Vec<CatalogBreed> = call_and_read_body_json(&app, req).await;
+    let added: Vec<(&str, BreedPurpose, bool)> = catalog
+        .iter()
+        .skip(builtin_catalog().len())
+        .map(|b| (b.name.as_str(), b.purpose, b.builtin))
+        .collect();
+    assert_eq!(
+        added,
+        vec![
+            ("Angora", BreedPurpose::Fiber, false),
+            ("Boer", BreedPurpose::Meat, false),
+            ("Saanen", BreedPurpose::Dual, false),
+        ]
+    );
+
+    let delete = |uri: &str, token: &str| {
+        TestRequest::delete()
+            .uri(uri)
+            .insert_header((SESSION_HEADER, token))
+            .to_request()
+    };
+    assert_eq!(
+        call_service(&app, delete("/breeds/Boer", &ravi))
+            .await
+            .status(),
+        403
+    );
+    assert_eq!(
+        call_service(&app, delete("/breeds/Boer", &owner))
+            .await
+            .status(),
+        200
+    );
+    for uri in ["/breeds/Boer", "/breeds/Beetal"] {
+        assert_eq!(
+            call_service(&app, delete(uri, &owner)).await.status(),
+            400,
+            "{}",
+            uri
+        );
+    }
+    let req = TestRequest::delete().uri("/breeds/Angora").to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+}
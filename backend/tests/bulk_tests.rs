@@ -0,0 +1,109 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use shared::bulk::BulkPatch;
+use shared::exports::ExportFilter;
+use shared::lifecycle::LifecycleStage;
+use shared::{Gender, Goat, GoatUpdate};
+
+#[test]
+fn test_bulk_patch_validation() {
+    let milkers = || BulkPatch::diet(ExportFilter::default(), None, " lucerne ");
+    assert_eq!(milkers().update.diet.as_deref(), Some("lucerne"));
+    assert_eq!(milkers().validate(), Ok(()));
+    assert!(BulkPatch::default().validate().is_err());
+    assert!(
+        BulkPatch::diet(ExportFilter::default(), None, "  ")
+            .validate()
+            .is_err()
+    );
+    let mut rename = milkers();
+    rename.update.name = Some("Rani".to_string());
+    assert!(rename.validate().is_err());
+}
+
+#[actix_rt::test]
+async fn test_bulk_patch_previews_then_changes_the_selected_goats() {
+    let db_pool = common::temp_pool("bulk");
+    let conn = db_pool.get_conn().unwrap();
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    for name in ["Rani", "Moti", "Kali", "Ganga"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    conn.execute(
+        "UPDATE goats SET lifecycle_stage = 'Milker' WHERE name IN ('Rani', 'Kali')",
+        [],
+    )
+    .unwrap();
+    conn.execute(
+        "UPDATE goats SET gender = 'Male', lifecycle_stage = 'Breeder' WHERE name = 'Moti'",
+        [],
+    )
+    .unwrap();
+
+    let milkers = BulkPatch::diet(
+        ExportFilter {
+            gender: Some(Gender::Female),
+            ..ExportFilter::default()
+        },
+        Some(LifecycleStage::Milker),
+        "lucerne and dairy ration",
+    );
+    let req = TestRequest::patch()
+        .uri("/goats?preview=true")
+        .set_json(&milkers)
+        .to_request();
+    let preview: Vec<Goat> = call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = preview.iter().map(|g| g.name.as_str()).collect();
+    assert_eq!(names, vec!["Kali", "Rani"]);
+    assert!(preview.iter().all(|g| g.diet == "hay"));
+
+    let req = TestRequest::patch()
+        .uri("/goats")
+        .set_json(&milkers)
+        .to_request();
+    let patched: Vec<Goat> = call_and_read_body_json(&app, req).await;
+    assert_eq!(patched.len(), 2);
+    let req = TestRequest::get().uri("/goats").to_request();
+    let herd: Vec<Goat> = call_and_read_body_json(&app, req).await;
+    for goat in &herd {
+        let expected = match goat.name.as_str() {
+            "Rani" | "Kali" => "lucerne and dairy ration",
+            _ => "hay",
+        };
+        assert_eq!(goat.diet, expected, "diet of {}", goat.name);
+    }
+
+    // Nothing changes if any selected goat would become invalid
+    let invalid = BulkPatch {
+        update: GoatUpdate {
+            weight: Some(-1.0),
+            ..GoatUpdate::default()
+        },
+        ..BulkPatch::default()
+    };
+    let req = TestRequest::patch()
+        .uri("/goats")
+        .set_json(&invalid)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let weights: Vec<f64> = conn
+        .prepare("SELECT weight FROM goats")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert!(weights.iter().all(|w| *w > 0.0));
+}
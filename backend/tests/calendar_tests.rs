@@ -0,0 +1,146 @@
+mod common;
+
+use actix_web::test::{
+    TestRequest, call_and_read_body_json, call_service, init_service, read_body,
+};
+use actix_web::{App, web};
+use backend::auth::API_KEY_HEADER;
+use backend::calendar::{CalendarEvent, escape_text, fold_line, to_ics};
+use backend::routes;
+use chrono::{NaiveDate, TimeZone, Utc};
+use serde_json::json;
+use shared::scale::IssuedApiKey;
+
+#[test]
+fn test_ics_escapes_and_folds() {
+    assert_eq!(
+        escape_text("Trim hooves; pen 2, then\\check\nnotes"),
+        "Trim hooves\\; pen 2\\, then\\\\check\\nnotes"
+    );
+    assert_eq!(fold_line("SUMMARY:Short"), "SUMMARY:Short\r\n");
+    let long = format!("DESCRIPTION:{}", "é".repeat(40));
+    let folded = fold_line(&long);
+    for line in folded.split("\r\n").filter(|l| !l.is_empty()) {
+        assert!(line.len() <= 75, "{} octets", line.len());
+    }
+    assert_eq!(folded.replace("\r\n ", ""), format!("{}\r\n", long));
+
+    let ics = to_ics(
+        &[CalendarEvent {
+            uid: "task-7@yagi".into(),
+            date: NaiveDate::from_ymd_opt(2026, 12, 31).unwrap(),
+            summary: "Deworm, kids".into(),
+            description: Some("Goat: Rani\nPen: North".into()),
+        }],
+        Utc.with_ymd_and_hms(2026, 10, 17, 8, 30, 0).unwrap(),
+    );
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    for line in [
+        "UID:task-7@yagi",
+        "DTSTAMP:20261017T083000Z",
+        "DTSTART;VALUE=DATE:20261231",
+        "DTEND;VALUE=DATE:20270101",
+        "SUMMARY:Deworm\\, kids",
+        "DESCRIPTION:Goat: Rani\\nPen: North",
+    ] {
+        assert!(
+            ics.contains(&format!("\r\n{}\r\n", line)),
+            "missing {}",
+            line
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn test_calendar_feed() {
+    let db_pool = common::temp_pool("calendar");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert!(call_service(&app, req).await.status().is_success());
+    for (title, goat, due) in [
+        ("Deworm", Some("Rani"), "2026-11-02"),
+        ("Fix fence", None, "2026-10-20"),
+    ] {
+        let task = json!({
+            "id": null, "title": title, "notes": null, "goat_name": goat,
+            "space_id": null, "space_name": null, "rule_id": null,
+            "due_date": due, "status": "Pending"
+        });
+        let req = TestRequest::post()
+            .uri("/tasks")
+            .set_json(&task)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    let conn = db_pool.get_conn().unwrap();
+    conn.execute(
+        "INSERT INTO reminders (task_id, remind_on, message) VALUES (1, '2026-10-30', 'Deworm Rani soon')",
+        [],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO reminders (task_id, remind_on, message, dismissed) VALUES (1, '2026-10-29', 'Old', 1)",
+        [],
+    )
+    .unwrap();
+
+    // The feed needs an API key
+    let req = TestRequest::get().uri("/calendar/feed.ics").to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+    let req = TestRequest::get()
+        .uri("/calendar/feed.ics?key=yagi_unknown")
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+
+    let req = TestRequest::post()
+        .uri("/api-keys")
+        .set_json(json!({ "name": "Phone calendar" }))
+        .to_request();
+    let issued: IssuedApiKey = call_and_read_body_json(&app, req).await;
+
+    let req = TestRequest::get()
+        .uri(&format!("/calendar/feed.ics?key={}", issued.key))
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "text/calendar; charset=utf-8"
+    );
+    let ics = String::from_utf8(read_body(resp).await.to_vec()).unwrap();
+    let uids: Vec<&str> = ics.lines().filter_map(|l| l.strip_prefix("UID:")).collect();
+    assert_eq!(uids, vec!["task-2@yagi", "reminder-1@yagi", "task-1@yagi"]);
+    assert!(ics.contains("SUMMARY:Reminder: Deworm Rani soon\r\n"));
+    assert!(ics.contains("DESCRIPTION:Goat: Rani\r\n"));
+    assert!(!ics.contains("Old"));
+
+    // Headers work too, and completed tasks drop out of the feed
+    let req = TestRequest::put().uri("/tasks/2/complete").to_request();
+    assert!(call_service(&app, req).await.status().is_success());
+    let req = TestRequest::get()
+        .uri("/calendar/feed.ics")
+        .insert_header((API_KEY_HEADER, issued.key.as_str()))
+        .to_request();
+    let ics = String::from_utf8(read_body(call_service(&app, req).await).await.to_vec()).unwrap();
+    assert!(!ics.contains("task-2@yagi"));
+    assert!(ics.contains("UID:task-1@yagi\r\n"));
+
+    let req = TestRequest::delete()
+        .uri(&format!("/api-keys/{}", issued.id))
+        .to_request();
+    assert!(call_service(&app, req).await.status().is_success());
+    let req = TestRequest::get()
+        .uri(&format!("/calendar/feed.ics?key={}", issued.key))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+}
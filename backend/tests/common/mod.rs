@@ -0,0 +1,81 @@
+//! Helpers shared by the integration test binaries.
+
+use backend::auth::{generate_key, hash_key};
+use backend::db::DbPool;
+use shared::{Breed, GoatParams};
+
+/// Creates a fresh, fully migrated database in the system temp directory.
+///
+/// `name` must be unique per test so parallel tests do not share a file.
+pub fn temp_pool(name: &str) -> DbPool {
+    let path = std::env::temp_dir().join(format!("yagi_{}_{}.db", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    DbPool::new(path.to_str().unwrap()).expect("Failed to create DbPool")
+}
+
+/// JSON payload for a goat named `name`, as accepted by `POST /goats`.
+#[allow(dead_code)]
+pub fn sample_goat(name: &str) -> serde_json::Value {
+    let goat = GoatParams::builder(name)
+        .breed(Breed::Beetal)
+        .cost(100.0)
+        .weight(30.0)
+        .current_price(150.0)
+        .diet("hay")
+        .build()
+        .expect("Sample goat is valid");
+    serde_json::to_value(goat).unwrap()
+}
+
+/// Signs the owner in on a new device, returning the session token to send
+/// in the `SESSION_HEADER` header.
+#[allow(dead_code)]
+pub fn owner_token(db_pool: &DbPool) -> String {
+    let token = generate_key();
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "INSERT INTO sessions (device, token_hash) VALUES ('Owner laptop', ?1)",
+            [hash_key(&token)],
+        )
+        .unwrap();
+    token
+}
+
+/// Signs worker `name` in on a new device, returning the session token to
+/// send in the `SESSION_HEADER` header.
+#[allow(dead_code)]
+pub fn worker_token(db_pool: &DbPool, name: &str) -> String {
+    let token = generate_key();
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "INSERT INTO sessions (device, token_hash, worker_id)
+             SELECT 'Worker phone', ?1, id FROM workers WHERE name = ?2",
+            [hash_key(&token), name.to_string()],
+        )
+        .unwrap();
+    token
+}
+
+/// Adds a worker straight to the database, as the owner would, since only
+/// the owner's session may give workers a role or contact.
+#[allow(dead_code)]
+pub fn add_worker(
+    db_pool: &DbPool,
+    name: &str,
+    role: Option<&str>,
+    contact: Option<&str>,
+    notify_by: &str,
+) {
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "INSERT INTO workers (name, role, contact, notify_by) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![name, role, contact, notify_by],
+        )
+        .unwrap();
+}
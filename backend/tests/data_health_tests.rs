@@ -0,0 +1,108 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use serde_json::json;
+use shared::data_health::{DataHealthReport, IssueKind};
+
+#[actix_rt::test]
+async fn test_data_health_report() {
+    let db_pool = common::temp_pool("data_health");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let mut pricey = common::sample_goat("Moti");
+    pricey["current_price"] = json!(900.0);
+    let mut light = common::sample_goat("Kali");
+    light["weight"] = json!(0.0);
+    let mut vaccinated = common::sample_goat("Rani");
+    vaccinated["vaccinations"] = json!([
+        { "id": null, "name": "CDT", "given_on": "2025-03-01" },
+        { "id": null, "name": "Rabies" },
+    ]);
+    for goat in [common::sample_goat("Ganga"), pricey, light, vaccinated] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(&goat)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    let conn = db_pool.get_conn().unwrap();
+    conn.execute_batch(
+        "UPDATE goats SET date_of_birth = '2024-01-15' WHERE name != 'Ganga';
+         UPDATE goats SET tag_id = 'TAG-7' WHERE name = 'Ganga';
+         UPDATE goats SET tag_id = ' tag-7' WHERE name = 'Kali';",
+    )
+    .unwrap();
+
+    let req = TestRequest::get().uri("/data-health").to_request();
+    let report: DataHealthReport = call_and_read_body_json(&app, req).await;
+    assert_eq!(report.goats_checked, 4);
+    let found: Vec<(IssueKind, &str, &str)> = report
+        .issues
+        .iter()
+        .map(|i| (i.kind, i.goat_name.as_str(), i.message.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (
+                IssueKind::MissingBirthDate,
+                "Ganga",
+                "No date of birth recorded"
+            ),
+            (IssueKind::ZeroWeight, "Kali", "Weight is 0 kg"),
+            (
+                IssueKind::PriceOffMarket,
+                "Moti",
+                "Price 900.00 is far from the Beetal median of 150.00"
+            ),
+            (
+                IssueKind::DuplicateTag,
+                "Ganga",
+                "Tag 'TAG-7' is also used by Kali"
+            ),
+            (
+                IssueKind::DuplicateTag,
+                "Kali",
+                "Tag ' tag-7' is also used by Ganga"
+            ),
+            (
+                IssueKind::UndatedVaccination,
+                "Rani",
+                "Rabies vaccination has no date"
+            ),
+        ]
+    );
+    assert_eq!(
+        report.issues[0].fix_anchor(),
+        format!("goat-{}", report.issues[0].goat_id)
+    );
+    assert_eq!(report.issues[1].fix_anchor(), "update-goat");
+
+    // Replacing the vaccinations without dates keeps the recorded ones
+    let mut update = common::sample_goat("Rani");
+    update["vaccinations"] = json!([{ "id": null, "name": "CDT" }]);
+    let req = TestRequest::put()
+        .uri("/goats")
+        .set_json(&update)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let given_on: Option<String> = conn
+        .query_row(
+            "SELECT gv.given_on FROM goat_vaccines gv JOIN goats g ON g.id = gv.goat_id \
+             WHERE g.name = 'Rani'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(given_on.as_deref(), Some("2025-03-01"));
+    let req = TestRequest::get().uri("/data-health").to_request();
+    let report: DataHealthReport = call_and_read_body_json(&app, req).await;
+    assert_eq!(report.count(IssueKind::UndatedVaccination), 0);
+}
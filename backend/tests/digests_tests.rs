@@ -0,0 +1,102 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_service, init_service};
+use actix_web::{App, web};
+use backend::digests::{pending_digests, send_digests};
+use backend::errors::AppError;
+use backend::handlers::reports::census_files;
+use backend::messaging::{MessageGateway, OutgoingMessage};
+use backend::routes;
+use serde_json::json;
+use shared::notifications::NotifyChannel;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Gateway keeping the messages it is asked to send, or refusing them all
+/// while `down` is set.
+#[derive(Default)]
+struct RecordingGateway {
+    sent: Mutex<Vec<OutgoingMessage>>,
+    down: AtomicBool,
+}
+
+impl MessageGateway for RecordingGateway {
+    fn send(&self, message: &OutgoingMessage) -> Result<(), AppError> {
+        if self.down.load(Ordering::SeqCst) {
+            return Err(AppError::Upstream("Gateway down".to_string()));
+        }
+        self.sent.lock().unwrap().push(message.clone());
+        Ok(())
+    }
+}
+
+#[actix_rt::test]
+async fn test_cron_reports_and_digests() {
+    let db_pool = common::temp_pool("digests");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    for (name, contact, notify_by) in [
+        ("Asha", None, "App"),
+        ("Ravi", Some("+91 98765 43210"), "Sms"),
+    ] {
+        common::add_worker(&db_pool, name, None, contact, notify_by);
+    }
+    for text in ["@Ravi limping", "@Ravi @Asha hoof trimmed"] {
+        let req = TestRequest::post()
+            .uri("/notes")
+            .set_json(json!({ "goat_name": "Rani", "author": "Meena", "text": text }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    let conn = db_pool.get_conn().unwrap();
+
+    // Every template as CSV and PDF
+    let files = census_files(&conn, &[], Some("2026-03-31")).unwrap();
+    assert!(files.len() >= 6);
+    assert!(
+        files
+            .iter()
+            .any(|(name, _)| name == "breed-headcount-2026-03-31.csv")
+    );
+    let files = census_files(&conn, &["sex-age".to_string()], None).unwrap();
+    assert_eq!(files.len(), 2);
+    assert!(files[1].0.ends_with(".pdf") && files[1].1.starts_with(b"%PDF"));
+    assert!(matches!(
+        census_files(&conn, &["no-such-template".to_string()], None),
+        Err(AppError::InvalidInput(_))
+    ));
+
+    // Only Ravi asked for SMS; Asha is notified in the app only
+    let digests = pending_digests(&conn).unwrap();
+    assert_eq!(digests.len(), 1);
+    assert_eq!(digests[0].notification_ids.len(), 2);
+    assert_eq!(digests[0].message.channel, NotifyChannel::Sms);
+    assert!(
+        digests[0]
+            .message
+            .text
+            .starts_with("You have 2 unread notifications")
+    );
+    assert!(digests[0].message.text.contains("hoof trimmed"));
+
+    // A refused digest is tried again in the next run, and sent only once
+    let gateway = RecordingGateway::default();
+    gateway.down.store(true, Ordering::SeqCst);
+    assert_eq!(send_digests(&conn, &gateway).unwrap(), 0);
+    gateway.down.store(false, Ordering::SeqCst);
+    assert_eq!(send_digests(&conn, &gateway).unwrap(), 1);
+    assert_eq!(send_digests(&conn, &gateway).unwrap(), 0);
+    let sent = gateway.sent.lock().unwrap();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].to, "+91 98765 43210");
+    assert!(sent[0].link.starts_with("#goat-"));
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use actix_web::test::{
+    TestRequest, call_and_read_body_json, call_service, init_service, read_body,
+};
+use actix_web::{App, web};
+use backend::routes;
+use serde_json::json;
+use shared::diseases::{CaseStatus, CatalogDisease, ComplianceReport, names_disease};
+
+#[test]
+fn test_names_disease() {
+    assert!(names_disease("Brucellosis RBPT", "brucellosis"));
+    assert!(names_disease("Suspected bluetongue", "Bluetongue"));
+    assert!(!names_disease("CBC", "Brucellosis"));
+    assert!(!names_disease("CBC", " "));
+}
+
+#[actix_rt::test]
+async fn test_notifiable_diseases_and_compliance_report() {
+    let db_pool = common::temp_pool("diseases");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let mut rani = common::sample_goat("Rani");
+    rani["diseases"] = json!([{ "id": null, "name": "Brucellosis" }]);
+    let mut ganga = common::sample_goat("Ganga");
+    ganga["diseases"] = json!([{ "id": null, "name": "Mastitis" }]);
+    for goat in [
+        rani,
+        ganga,
+        common::sample_goat("Moti"),
+        common::sample_goat("Kali"),
+    ] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(&goat)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "UPDATE goats SET tag_id = 'IN-0042' WHERE name = 'Rani'",
+            [],
+        )
+        .unwrap();
+
+    // Common notifiable diseases come flagged; recorded diseases are not
+    let req = TestRequest::get().uri("/diseases").to_request();
+    let catalog: Vec<CatalogDisease> = call_and_read_body_json(&app, req).await;
+    let find = |name: &str| catalog.iter().find(|d| d.name == name).unwrap().clone();
+    let brucellosis = find("Brucellosis");
+    assert!(brucellosis.notifiable);
+    assert_eq!(brucellosis.goat_count, 1);
+    let mastitis = find("Mastitis");
+    assert!(!mastitis.notifiable);
+    assert_eq!(catalog.iter().filter(|d| d.notifiable).count(), 10);
+
+    let req = TestRequest::post()
+        .uri("/diseases")
+        .set_json(json!({ "name": "anthrax", "notifiable": true }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::post()
+        .uri("/diseases")
+        .set_json(json!({ "name": "  " }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::post()
+        .uri("/diseases")
+        .set_json(json!({ "name": "Johne's disease", "notifiable": true }))
+        .to_request();
+    let johnes: CatalogDisease = call_and_read_body_json(&app, req).await;
+    assert!(johnes.notifiable);
+    assert_eq!(johnes.goat_count, 0);
+
+    let req = TestRequest::post()
+        .uri("/health/incidents")
+        .set_json(json!({
+            "id": null, "goat_name": "Moti", "space_id": null, "kind": "Disease",
+            "condition": "Brucellosis (orchitis)", "observed_on": "2026-03-05", "notes": null
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    for (goat, sampled_on, value) in [
+        ("Kali", "2026-03-10", 0.0),
+        ("Rani", "2026-03-01", 1.0),
+        ("Rani", "2026-02-01", 0.0),
+    ] {
+        let req = TestRequest::post()
+            .uri("/lab-results")
+            .set_json(json!({
+                "id": null, "goat_name": goat, "test_type": "Brucellosis RBPT",
+                "sampled_on": sampled_on, "lab": null, "notes": null,
+                "values": [{ "analyte": "Agglutination", "value": value, "unit": "",
+                             "low": 0.0, "high": 0.0 }]
+            }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    let req = TestRequest::get().uri("/diseases/compliance").to_request();
+    let report: ComplianceReport = call_and_read_body_json(&app, req).await;
+    assert_eq!(report.diseases.len(), 11);
+    assert!(!report.diseases.contains(&"Mastitis".to_string()));
+    let cases: Vec<(&str, &str, CaseStatus)> = report
+        .cases
+        .iter()
+        .map(|c| (c.disease.as_str(), c.goat_name.as_str(), c.status))
+        .collect();
+    assert_eq!(
+        cases,
+        vec![
+            ("Brucellosis", "Rani", CaseStatus::Confirmed),
+            ("Brucellosis", "Moti", CaseStatus::Suspected),
+            ("Brucellosis", "Kali", CaseStatus::Tested),
+        ]
+    );
+    let rani = &report.cases[0];
+    assert_eq!(rani.tag_id.as_deref(), Some("IN-0042"));
+    assert_eq!(
+        rani.tests_label(),
+        "2026-02-01 Brucellosis RBPT; 2026-03-01 Brucellosis RBPT (out of range)"
+    );
+    assert_eq!(
+        report.cases[1].first_reported.as_deref(),
+        Some("2026-03-05")
+    );
+    assert!(report.cases[1].tests.is_empty());
+
+    // Flagging a disease brings its cases into the report
+    let req = TestRequest::put()
+        .uri(&format!("/diseases/{}/notifiable", mastitis.id))
+        .set_json(json!({ "notifiable": true }))
+        .to_request();
+    let flagged: CatalogDisease = call_and_read_body_json(&app, req).await;
+    assert!(flagged.notifiable);
+    let req = TestRequest::put()
+        .uri("/diseases/9999/notifiable")
+        .set_json(json!({ "notifiable": true }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let req = TestRequest::get()
+        .uri("/diseases/compliance?format=csv")
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("Content-Type").unwrap(), "text/csv");
+    let disposition = resp
+        .headers()
+        .get("Content-Disposition")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(disposition.contains("notifiable-diseases-"));
+    let csv = String::from_utf8(read_body(resp).await.to_vec()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "Disease,Goat,Tag ID,Status,First Reported,Tests");
+    assert_eq!(
+        lines[1],
+        "Brucellosis,Rani,IN-0042,Confirmed,,2026-02-01 Brucellosis RBPT; 2026-03-01 Brucellosis RBPT (out of range)"
+    );
+    assert_eq!(lines[4], "Mastitis,Ganga,,Confirmed,,");
+    assert_eq!(lines.len(), 5);
+}
@@ -0,0 +1,466 @@
+mod common;
+
+use actix_web::middleware::from_fn;
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::auth::{API_KEY_HEADER, check_session, hash_key};
+use backend::events::{EventSourcing, replay};
+use backend::routes;
+use rusqlite::types::Value;
+use serde_json::json;
+use shared::Goat;
+use shared::events::{DomainEvent, FieldChange, StoredEvent};
+use shared::finance::Transaction;
+use shared::scale::{IssuedApiKey, ScaleReading};
+use shared::sessions::SESSION_HEADER;
+
+#[actix_rt::test]
+async fn test_changes_are_logged_and_replayed() {
+    let db_pool = common::temp_pool("events");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .app_data(web::Data::new(EventSourcing))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let mut rani = None;
+    for name in ["Rani", "Moti"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        let goat: Goat = call_and_read_body_json(&app, req).await;
+        rani.get_or_insert(goat);
+    }
+    let rani = rani.unwrap();
+    let req = TestRequest::patch()
+        .uri(&format!("/goats/{}", rani.id))
+        .set_json(json!({ "diet": "grass" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::post()
+        .uri("/growth/weights")
+        .set_json(
+            json!({ "id": null, "goat_name": "Rani", "weighed_on": "2025-01-10", "weight": 34.5 }),
+        )
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::post()
+        .uri("/breeding/services")
+        .set_json(json!({
+            "id": null, "doe_name": "Rani", "served_on": "2025-01-11", "method": "Natural",
+            "buck_name": null, "straw_id": null, "technician": null, "notes": null,
+            "outside_sire": { "name": "Thunder", "breed": null, "registration": null, "source": null }
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::post()
+        .uri("/transactions")
+        .set_json(json!({
+            "kind": "Income",
+            "category": "Sale",
+            "amount": 9000.0,
+            "description": "Sold Rani",
+            "goat_name": "Rani",
+            "space_id": null,
+            "consumption_id": null,
+            "date": "2025-01-12"
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    // Records outside `GoatParams` are events too
+    let req = TestRequest::post()
+        .uri("/spaces")
+        .set_json(
+            json!({ "id": null, "name": "Pen A", "kind": "Enclosure", "capacity": 4, "area_m2": 8.0 }),
+        )
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::put()
+        .uri("/spaces/1/goats")
+        .set_json(json!(["Rani"]))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::put()
+        .uri("/lifecycle")
+        .set_json(json!({ "goat_name": "Rani", "stage": "Kid", "changed_on": "2024-06-01" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::put()
+        .uri("/breeding/pedigree")
+        .set_json(json!({
+            "goat_name": "Rani", "sire_name": null, "dam_name": "Moti", "date_of_birth": "2024-01-01"
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    for name in ["Moti", "Rani"] {
+        let req = TestRequest::put()
+            .uri("/breeding/neuterings")
+            .set_json(json!({ "goat_name": name, "neutered_on": "2024-05-01", "reason": "Cull" }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 200);
+    }
+    let req = TestRequest::delete()
+        .uri("/breeding/neuterings/Rani")
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::post()
+        .uri("/api-keys")
+        .set_json(json!({ "name": "Barn scale" }))
+        .to_request();
+    let issued: IssuedApiKey = call_and_read_body_json(&app, req).await;
+    let req = TestRequest::post()
+        .uri("/scale/readings")
+        .insert_header((API_KEY_HEADER, issued.key.as_str()))
+        .set_json(json!({ "tag_id": "TAG-1", "weight": 35.0, "read_at": "2025-01-12T08:00:00" }))
+        .to_request();
+    let reading: ScaleReading = call_and_read_body_json(&app, req).await;
+    let req = TestRequest::put()
+        .uri(&format!("/scale/readings/{}/goat", reading.id))
+        .set_json(json!({ "goat_name": "Rani" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::delete()
+        .uri("/goats")
+        .set_json(json!({ "name": "Moti" }))
+        .to_request();
+    let trash_id: i64 = call_and_read_body_json(&app, req).await;
+    let req = TestRequest::post()
+        .uri(&format!("/trash/{}/restore", trash_id))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let req = TestRequest::get().uri("/events").to_request();
+    let events: Vec<StoredEvent> = call_and_read_body_json(&app, req).await;
+    let kinds: Vec<&str> = events.iter().map(|e| e.event.kind()).collect();
+    assert_eq!(
+        kinds,
+        [
+            "GoatRegistered",
+            "GoatRegistered",
+            "GoatUpdated",
+            "WeightRecorded",
+            "GoatUpdated",
+            "GoatUpdated",
+            "GoatSold",
+            "GoatPlaced",
+            "LifecycleChanged",
+            "PedigreeRecorded",
+            "GoatNeutered",
+            "GoatNeutered",
+            "NeuteringCleared",
+            "TagAssigned",
+            "WeightRecorded",
+            "GoatUpdated",
+            "GoatRemoved",
+            "GoatRestored"
+        ]
+    );
+    assert!(events.windows(2).all(|pair| pair[0].id < pair[1].id));
+    match &events[6].event {
+        DomainEvent::GoatSold {
+            goat_id,
+            transaction,
+        } => {
+            assert_eq!(*goat_id, rani.id);
+            assert!(transaction.id.is_some());
+            assert_eq!(transaction.amount, 9000.0);
+        }
+        other => panic!("Expected a sale, got {:?}", other),
+    }
+
+    // Followers resume after the last event they saw
+    let req = TestRequest::get()
+        .uri(&format!("/events?after={}&limit=2", events[1].id))
+        .to_request();
+    let page: Vec<StoredEvent> = call_and_read_body_json(&app, req).await;
+    assert_eq!(page, events[2..4]);
+
+    // Replaying the log rebuilds the goats, their weighings, lifecycle
+    // history and the sale
+    let req = TestRequest::get().uri("/goats").to_request();
+    let goats: Vec<Goat> = call_and_read_body_json(&app, req).await;
+    let req = TestRequest::get().uri("/transactions").to_request();
+    let transactions: Vec<Transaction> = call_and_read_body_json(&app, req).await;
+
+    let rebuilt_pool = common::temp_pool("events_replay");
+    // Spaces are not goat records, so the pen Rani was placed in comes first
+    rebuilt_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "INSERT INTO spaces (name, type, capacity, area_m2) VALUES ('Pen A', 'enclosure', 4, 8.0)",
+            [],
+        )
+        .unwrap();
+    assert_eq!(replay(&rebuilt_pool, &events).unwrap(), 18);
+    assert!(replay(&rebuilt_pool, &events).is_err());
+    for query in [
+        "SELECT id, name, breed, gender, weight, current_price, diet, last_bred, tag_id, \
+             date_of_birth, sire_id, dam_id, sire_external_id, dam_external_id, space_id, \
+             lifecycle_stage, neutered_on, neutered_reason, deleted_at IS NULL \
+         FROM goats ORDER BY id",
+        "SELECT id, goat_id, weighed_on, weight, estimated FROM weight_records ORDER BY id",
+        "SELECT goat_id, from_stage, to_stage, changed_on, note FROM lifecycle_changes ORDER BY id",
+        "SELECT goat_id, name FROM goat_trash ORDER BY id",
+    ] {
+        let rows = |pool: &backend::db::DbPool| -> Vec<Vec<Value>> {
+            let conn = pool.get_conn().unwrap();
+            let mut stmt = conn.prepare(query).unwrap();
+            let width = stmt.column_count();
+            stmt.query_map([], |row| (0..width).map(|i| row.get(i)).collect())
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+        assert_eq!(rows(&rebuilt_pool), rows(&db_pool), "{}", query);
+    }
+    let rebuilt = init_service(
+        App::new()
+            .app_data(web::Data::new(rebuilt_pool))
+            .configure(routes::configure),
+    )
+    .await;
+    let req = TestRequest::get().uri("/goats").to_request();
+    let rebuilt_goats: Vec<Goat> = call_and_read_body_json(&rebuilt, req).await;
+    assert_eq!(
+        rebuilt_goats
+            .iter()
+            .map(|g| (g.id, &g.params))
+            .collect::<Vec<_>>(),
+        goats.iter().map(|g| (g.id, &g.params)).collect::<Vec<_>>()
+    );
+    assert_eq!(rebuilt_goats[0].params.diet, "grass");
+    assert_eq!(rebuilt_goats[0].params.weight, 35.0);
+    assert_eq!(rebuilt_goats[0].tag_id.as_deref(), Some("TAG-1"));
+    assert_eq!(
+        rebuilt_goats[0].date_of_birth.as_deref(),
+        Some("2024-01-01")
+    );
+    assert_eq!(
+        rebuilt_goats[0].params.last_bred.as_deref(),
+        Some("2025-01-11")
+    );
+    let req = TestRequest::get().uri("/transactions").to_request();
+    let rebuilt_transactions: Vec<Transaction> = call_and_read_body_json(&rebuilt, req).await;
+    assert_eq!(rebuilt_transactions, transactions);
+}
+
+#[actix_rt::test]
+async fn test_only_field_history_is_logged_without_event_sourcing() {
+    let db_pool = common::temp_pool("events_off");
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let owner = common::owner_token(&db_pool);
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    let goat: Goat = call_and_read_body_json(&app, req).await;
+    let req = TestRequest::post()
+        .uri("/workers")
+        .set_json(json!({
+            "id": null, "name": "Ravi", "role": null,
+            "contact": null, "notify_by": "App"
+        }))
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    // As if Ravi had signed in with his PIN
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "INSERT INTO sessions (device, token_hash, worker_id) VALUES ('Phone', ?1, 1)",
+            [hash_key("yagi_ravi")],
+        )
+        .unwrap();
+    let req = TestRequest::patch()
+        .uri(&format!("/goats/{}", goat.id))
+        .set_json(json!({ "current_price": 180.0 }))
+        .insert_header((SESSION_HEADER, "yagi_ravi"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    let req = TestRequest::get()
+        .uri("/events")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    let events: Vec<StoredEvent> = call_and_read_body_json(&app, req).await;
+    assert!(events.is_empty());
+
+    let req = TestRequest::get()
+        .uri(&format!("/goats/{}/history?field=current_price", goat.id))
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    let changes: Vec<FieldChange> = call_and_read_body_json(&app, req).await;
+    let prices: Vec<_> = changes
+        .iter()
+        .map(|c| (c.actor.as_deref(), c.from.clone(), c.to.clone()))
+        .collect();
+    assert_eq!(
+        prices,
+        vec![
+            (Some("Ravi on Phone"), Some(json!(150.0)), json!(180.0)),
+            (Some("Owner on Owner laptop"), None, json!(150.0)),
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn test_field_history_lists_each_change() {
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(common::temp_pool("events_history")))
+            .app_data(web::Data::new(EventSourcing))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    let goat: Goat = call_and_read_body_json(&app, req).await;
+    for patch in [
+        json!({ "current_price": 180.0 }),
+        json!({ "diet": "grass", "current_price": 180.0 }),
+        json!({ "current_price": 165.0 }),
+    ] {
+        let req = TestRequest::patch()
+            .uri(&format!("/goats/{}", goat.id))
+            .set_json(patch)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 200);
+    }
+    // A full update that leaves the price alone
+    let mut params = common::sample_goat("Rani");
+    params["current_price"] = json!(165.0);
+    params["diet"] = json!("browse");
+    let req = TestRequest::put()
+        .uri("/goats")
+        .set_json(params)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    let uri = format!("/goats/{}/history?field=current_price", goat.id);
+    let req = TestRequest::get().uri(&uri).to_request();
+    let changes: Vec<FieldChange> = call_and_read_body_json(&app, req).await;
+    let prices: Vec<_> = changes
+        .iter()
+        .map(|c| (c.from.clone(), c.to.clone()))
+        .collect();
+    assert_eq!(
+        prices,
+        vec![
+            (Some(json!(180.0)), json!(165.0)),
+            (Some(json!(150.0)), json!(180.0)),
+            (None, json!(150.0)),
+        ]
+    );
+
+    let req = TestRequest::get()
+        .uri(&format!("/goats/{}/history", goat.id))
+        .to_request();
+    let changes: Vec<FieldChange> = call_and_read_body_json(&app, req).await;
+    let diets = changes.iter().filter(|c| c.field == "diet").count();
+    assert_eq!(diets, 3);
+
+    let req = TestRequest::get()
+        .uri(&format!("/goats/{}/history?field=secret", goat.id))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::get().uri("/goats/999/history").to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+}
+
+#[actix_rt::test]
+async fn test_field_history_includes_pricing_weighings_and_services() {
+    let db_pool = common::temp_pool("events_derived");
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let owner = common::owner_token(&db_pool);
+    let as_owner = |req: TestRequest| req.insert_header((SESSION_HEADER, owner.as_str()));
+
+    let req = as_owner(TestRequest::post().uri("/goats"))
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    let goat: Goat = call_and_read_body_json(&app, req).await;
+    let req = as_owner(TestRequest::put().uri("/settings"))
+        .set_json(json!({ "pricing": { "formula": "weight * 10" } }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = as_owner(TestRequest::post().uri("/pricing/apply")).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = as_owner(TestRequest::post().uri("/growth/weights"))
+        .set_json(
+            json!({ "id": null, "goat_name": "Rani", "weighed_on": "2025-01-10", "weight": 34.5 }),
+        )
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = as_owner(TestRequest::post().uri("/breeding/services"))
+        .set_json(json!({
+            "id": null, "doe_name": "Rani", "served_on": "2025-01-11", "method": "Natural",
+            "buck_name": null, "straw_id": null, "technician": null, "notes": null,
+            "outside_sire": { "name": "Thunder", "breed": null, "registration": null, "source": null }
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    // A scale weighs Rani once her tag is known
+    let req = as_owner(TestRequest::post().uri("/api-keys"))
+        .set_json(json!({ "name": "Barn scale" }))
+        .to_request();
+    let issued: IssuedApiKey = call_and_read_body_json(&app, req).await;
+    let reading = |weight: f64, at: &str| {
+        TestRequest::post()
+            .uri("/scale/readings")
+            .insert_header((API_KEY_HEADER, issued.key.as_str()))
+            .set_json(json!({ "tag_id": "TAG-1", "weight": weight, "read_at": at }))
+            .to_request()
+    };
+    let first: ScaleReading =
+        call_and_read_body_json(&app, reading(35.0, "2025-01-12T08:00:00")).await;
+    let req = as_owner(TestRequest::put().uri(&format!("/scale/readings/{}/goat", first.id)))
+        .set_json(json!({ "goat_name": "Rani" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = reading(36.0, "2025-01-13T08:00:00");
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let req = as_owner(TestRequest::get().uri(&format!("/goats/{}/history", goat.id))).to_request();
+    let changes: Vec<FieldChange> = call_and_read_body_json(&app, req).await;
+    // Newest first, leaving out the values Rani was registered with
+    let registered = changes.iter().map(|c| c.event_id).min().unwrap();
+    let changes: Vec<_> = changes
+        .iter()
+        .filter(|c| c.event_id != registered)
+        .map(|c| (c.field.as_str(), c.actor.as_deref(), c.to.clone()))
+        .collect();
+    let by_owner = Some("Owner on Owner laptop");
+    assert_eq!(
+        changes,
+        vec![
+            ("weight", Some("Barn scale (API key)"), json!(36.0)),
+            ("weight", by_owner, json!(35.0)),
+            ("last_bred", by_owner, json!("2025-01-11")),
+            ("weight", by_owner, json!(34.5)),
+            ("current_price", by_owner, json!(300.0)),
+        ]
+    );
+}
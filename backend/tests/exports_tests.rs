@@ -0,0 +1,125 @@
+mod common;
+
+use actix_web::body::to_bytes;
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use shared::Gender;
+use shared::exports::{ExportColumn, ExportFilter, ExportFormat, ExportTable, ExportTemplate};
+
+fn template(key: &str, columns: Vec<ExportColumn>) -> ExportTemplate {
+    ExportTemplate {
+        key: key.to_string(),
+        name: "Does for the vet".to_string(),
+        columns,
+        filter: ExportFilter::default(),
+        format: ExportFormat::Csv,
+    }
+}
+
+#[test]
+fn test_export_template_validation() {
+    let columns = vec![ExportColumn::Name, ExportColumn::Weight];
+    assert_eq!(template("vet-does", columns.clone()).validate(), Ok(()));
+    assert!(template("Vet Does", columns.clone()).validate().is_err());
+    assert!(template("-vet", columns.clone()).validate().is_err());
+    assert!(template("vet-does", Vec::new()).validate().is_err());
+    assert!(
+        template("vet-does", vec![ExportColumn::Name, ExportColumn::Name])
+            .validate()
+            .is_err()
+    );
+    let mut unnamed = template("vet-does", columns);
+    unnamed.name = "  ".to_string();
+    assert!(unnamed.validate().is_err());
+}
+
+#[actix_rt::test]
+async fn test_saved_exports_are_run_by_key() {
+    let db_pool = common::temp_pool("exports");
+    let conn = db_pool.get_conn().unwrap();
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    for name in ["Rani", "Moti", "Kali"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    conn.execute("UPDATE goats SET gender = 'Male' WHERE name = 'Moti'", [])
+        .unwrap();
+    conn.execute(
+        "UPDATE goats SET health_status = 'sick', weight = 27.5 WHERE name = 'Kali'",
+        [],
+    )
+    .unwrap();
+
+    let mut does = template(
+        " vet-does ",
+        vec![
+            ExportColumn::Name,
+            ExportColumn::Weight,
+            ExportColumn::HealthStatus,
+        ],
+    );
+    does.filter.gender = Some(Gender::Female);
+    let req = TestRequest::post()
+        .uri("/exports/templates")
+        .set_json(&does)
+        .to_request();
+    let stored: ExportTemplate = call_and_read_body_json(&app, req).await;
+    assert_eq!(stored.key, "vet-does");
+    let req = TestRequest::post()
+        .uri("/exports/templates")
+        .set_json(&does)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let mut sick = template("sick-list", vec![ExportColumn::Name, ExportColumn::Cost]);
+    sick.filter.health_status = Some("Sick".to_string());
+    sick.format = ExportFormat::Json;
+    let req = TestRequest::post()
+        .uri("/exports/templates")
+        .set_json(&sick)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let req = TestRequest::get().uri("/exports/templates").to_request();
+    let templates: Vec<ExportTemplate> = call_and_read_body_json(&app, req).await;
+    let keys: Vec<&str> = templates.iter().map(|t| t.key.as_str()).collect();
+    assert_eq!(keys, vec!["sick-list", "vet-does"]);
+
+    let req = TestRequest::get().uri("/exports/run/vet-does").to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.headers().get("content-type").unwrap(), "text/csv");
+    let disposition = resp.headers().get("content-disposition").unwrap();
+    assert!(disposition.to_str().unwrap().contains("vet-does-"));
+    let body = to_bytes(resp.into_body()).await.unwrap();
+    assert_eq!(
+        body,
+        "Name,Weight,Health Status\nRani,30,healthy\nKali,27.5,sick\n"
+    );
+
+    let req = TestRequest::get()
+        .uri("/exports/run/sick-list")
+        .to_request();
+    let table: ExportTable = call_and_read_body_json(&app, req).await;
+    assert_eq!(table.columns, vec!["Name", "Cost"]);
+    assert_eq!(table.rows, vec![vec!["Kali", "100.00"]]);
+
+    let req = TestRequest::delete()
+        .uri("/exports/templates/sick-list")
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    for req in [
+        TestRequest::get().uri("/exports/run/sick-list"),
+        TestRequest::delete().uri("/exports/templates/sick-list"),
+    ] {
+        assert_eq!(call_service(&app, req.to_request()).await.status(), 400);
+    }
+}
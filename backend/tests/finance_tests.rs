@@ -0,0 +1,424 @@
+mod common;
+
+use actix_web::body::to_bytes;
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use serde_json::{Value, json};
+use shared::finance::{
+    BudgetReport, BudgetVariance, FinanceCategory, SalesRegister, SalesRegisterRow, TaxRate,
+    Transaction, validate_gstin,
+};
+use shared::settings::FarmSettings;
+
+fn sale_row(taxable_value: f64, tax_percent: f64, buyer_state: Option<&str>) -> SalesRegisterRow {
+    SalesRegisterRow {
+        transaction_id: 1,
+        date: "2026-04-10".to_string(),
+        invoice_number: None,
+        buyer_name: None,
+        buyer_gstin: None,
+        place_of_supply: buyer_state.map(str::to_string),
+        description: "Kids".to_string(),
+        taxable_value,
+        tax_percent,
+        cgst: 0.0,
+        sgst: 0.0,
+        igst: 0.0,
+        invoice_total: 0.0,
+    }
+}
+
+#[test]
+fn test_validate_gstin() {
+    assert_eq!(validate_gstin("27AAPFU0939F1ZV"), Ok(()));
+    assert_eq!(validate_gstin("29AAGCB7383J1Z4"), Ok(()));
+    // Wrong check character
+    assert!(validate_gstin("27AAPFU0939F1ZW").is_err());
+    // Wrong length, lowercase, missing Z
+    assert!(validate_gstin("27AAPFU0939F1Z").is_err());
+    assert!(validate_gstin("27aapfu0939f1zv").is_err());
+    assert!(validate_gstin("27AAPFU0939F1XV").is_err());
+}
+
+#[test]
+fn test_sale_tax_split() {
+    // Same state: CGST + SGST, odd paise go to SGST
+    let row = sale_row(1000.33, 5.0, Some("27")).with_tax(Some("27"));
+    assert_eq!((row.cgst, row.sgst, row.igst), (25.01, 25.01, 0.0));
+    assert_eq!(row.invoice_total, 1050.35);
+
+    // Different state: IGST
+    let row = sale_row(1000.0, 12.0, Some("29")).with_tax(Some("27"));
+    assert_eq!((row.cgst, row.sgst, row.igst), (0.0, 0.0, 120.0));
+    assert_eq!(row.invoice_total, 1120.0);
+
+    // Unregistered buyer: treated as a local sale
+    let row = sale_row(200.0, 5.0, None).with_tax(Some("27"));
+    assert_eq!((row.cgst, row.sgst, row.igst), (5.0, 5.0, 0.0));
+}
+
+#[actix_rt::test]
+async fn test_sales_register() {
+    let db_pool = common::temp_pool("sales_register");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({ "gstin": "27AAPFU0939F1ZX" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({ "gstin": " 27aapfu0939f1zv " }))
+        .to_request();
+    let settings: FarmSettings = call_and_read_body_json(&app, req).await;
+    assert_eq!(settings.gstin.as_deref(), Some("27AAPFU0939F1ZV"));
+
+    for (rate, status) in [
+        (json!({ "name": "GST 5%", "rate_percent": 5.0 }), 201),
+        (json!({ "name": "GST 5%", "rate_percent": 5.0 }), 400),
+        (json!({ "name": "Bad", "rate_percent": 120.0 }), 400),
+        (json!({ "name": "GST 12%", "rate_percent": 12.0 }), 201),
+    ] {
+        let req = TestRequest::post()
+            .uri("/finance/tax-rates")
+            .set_json(&rate)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), status, "{}", rate);
+    }
+    let req = TestRequest::get().uri("/finance/tax-rates").to_request();
+    let rates: Vec<TaxRate> = call_and_read_body_json(&app, req).await;
+    let rate_id = |name: &str| rates.iter().find(|r| r.name == name).unwrap().id;
+
+    let transaction = |category: &str, amount: f64, date: &str, sale: Value| {
+        json!({
+            "kind": "Income",
+            "category": category,
+            "amount": amount,
+            "description": format!("{} sale", category),
+            "goat_name": null,
+            "space_id": null,
+            "consumption_id": null,
+            "date": date,
+            "sale": sale
+        })
+    };
+    let local = transaction(
+        "Sale",
+        1000.0,
+        "2026-04-10",
+        json!({
+            "invoice_number": "INV-1",
+            "buyer_name": "Pune Meat Co",
+            "buyer_gstin": "27AAPFU0939F1ZV",
+            "tax_rate_id": rate_id("GST 5%")
+        }),
+    );
+    let interstate = transaction(
+        "Sale",
+        2000.0,
+        "2026-04-20",
+        json!({
+            "invoice_number": "INV-2",
+            "buyer_name": "Bengaluru Traders",
+            "buyer_gstin": "29AAGCB7383J1Z4",
+            "tax_rate_id": rate_id("GST 12%")
+        }),
+    );
+    let milk = transaction("Milk", 150.0, "2026-04-15", Value::Null);
+    let outside_period = transaction("Sale", 500.0, "2026-05-02", Value::Null);
+    for body in [&local, &interstate, &milk, &outside_period] {
+        let req = TestRequest::post()
+            .uri("/transactions")
+            .set_json(body)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201, "{}", body);
+    }
+
+    // Reused invoice number, bad GSTIN, unknown rate, and sale details on an expense
+    let mut reused = interstate.clone();
+    reused["date"] = json!("2026-04-21");
+    let mut bad_gstin = interstate.clone();
+    bad_gstin["sale"]["invoice_number"] = json!("INV-3");
+    bad_gstin["sale"]["buyer_gstin"] = json!("29AAGCB7383J1Z5");
+    let mut unknown_rate = interstate.clone();
+    unknown_rate["sale"]["invoice_number"] = json!("INV-4");
+    unknown_rate["sale"]["tax_rate_id"] = json!(99);
+    let mut expense = local.clone();
+    expense["kind"] = json!("Expense");
+    expense["sale"]["invoice_number"] = json!("INV-5");
+    for body in [&reused, &bad_gstin, &unknown_rate, &expense] {
+        let req = TestRequest::post()
+            .uri("/transactions")
+            .set_json(body)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400, "{}", body);
+    }
+
+    let req = TestRequest::get().uri("/transactions").to_request();
+    let transactions: Vec<Transaction> = call_and_read_body_json(&app, req).await;
+    let sale = transactions
+        .iter()
+        .find_map(|t| {
+            t.sale
+                .as_ref()
+                .filter(|s| s.invoice_number.as_deref() == Some("INV-2"))
+        })
+        .unwrap();
+    assert_eq!(sale.tax_percent, 12.0);
+
+    // Deleting a rate keeps the rate sales were taxed at
+    let req = TestRequest::delete()
+        .uri(&format!(
+            "/finance/tax-rates/{}",
+            rate_id("GST 12%").unwrap()
+        ))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    let req = TestRequest::get()
+        .uri("/finance/sales-register?from=2026-04-01&to=2026-04-30")
+        .to_request();
+    let register: SalesRegister = call_and_read_body_json(&app, req).await;
+    assert_eq!(register.gstin.as_deref(), Some("27AAPFU0939F1ZV"));
+    let invoices: Vec<Option<&str>> = register
+        .rows
+        .iter()
+        .map(|r| r.invoice_number.as_deref())
+        .collect();
+    assert_eq!(invoices, vec![Some("INV-1"), None, Some("INV-2")]);
+    assert_eq!((register.rows[0].cgst, register.rows[0].sgst), (25.0, 25.0));
+    assert_eq!(register.rows[2].igst, 240.0);
+    assert_eq!(register.rows[2].place_of_supply.as_deref(), Some("29"));
+    assert_eq!(register.taxable_value, 3150.0);
+    assert_eq!(register.invoice_total, 3440.0);
+
+    let req = TestRequest::get()
+        .uri("/finance/sales-register?from=2026-04-01&to=2026-04-30&format=csv")
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.headers().get("content-type").unwrap(), "text/csv");
+    let body = to_bytes(resp.into_body()).await.unwrap();
+    let csv = std::str::from_utf8(&body).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("Date,Invoice No,Buyer,Buyer GSTIN"));
+    assert_eq!(
+        lines[1],
+        "2026-04-10,INV-1,Pune Meat Co,27AAPFU0939F1ZV,27,Sale sale,1000.00,5,25.00,25.00,0.00,1050.00"
+    );
+    assert_eq!(lines[4], "Total,,,,,,3150.00,,25.00,25.00,240.00,3440.00");
+
+    for uri in [
+        "/finance/sales-register?from=2026-05-01&to=2026-04-01",
+        "/finance/sales-register?from=April",
+    ] {
+        let req = TestRequest::get().uri(uri).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400, "{}", uri);
+    }
+}
+
+#[actix_rt::test]
+async fn test_foreign_currency_transactions() {
+    let db_pool = common::temp_pool("currency");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::get().uri("/settings").to_request();
+    let settings: FarmSettings = call_and_read_body_json(&app, req).await;
+    assert_eq!(settings.base_currency, "INR");
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({ "base_currency": "Rupees" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let transaction = |kind: &str, amount: f64, currency: Value, exchange_rate: Value| {
+        json!({
+            "kind": kind,
+            "category": if kind == "Income" { "Sale" } else { "Veterinary" },
+            "amount": amount,
+            "description": "Export",
+            "goat_name": "Rani",
+            "space_id": null,
+            "consumption_id": null,
+            "date": "2026-04-10",
+            "currency": currency,
+            "exchange_rate": exchange_rate
+        })
+    };
+    for body in [
+        transaction("Income", 100.0, json!("usd"), json!(83.5)),
+        transaction("Expense", 500.0, json!("INR"), Value::Null),
+        transaction("Expense", 200.0, Value::Null, Value::Null),
+    ] {
+        let req = TestRequest::post()
+            .uri("/transactions")
+            .set_json(&body)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201, "{}", body);
+    }
+    // Missing or bad rates, a rate on the base currency, and bad codes
+    for body in [
+        transaction("Income", 100.0, json!("USD"), Value::Null),
+        transaction("Income", 100.0, json!("USD"), json!(0.0)),
+        transaction("Income", 100.0, Value::Null, json!(83.5)),
+        transaction("Income", 100.0, json!("US$"), json!(83.5)),
+    ] {
+        let req = TestRequest::post()
+            .uri("/transactions")
+            .set_json(&body)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400, "{}", body);
+    }
+
+    let req = TestRequest::get().uri("/transactions").to_request();
+    let transactions: Vec<Transaction> = call_and_read_body_json(&app, req).await;
+    let currencies: Vec<(Option<&str>, Option<f64>)> = transactions
+        .iter()
+        .map(|t| (t.currency.as_deref(), t.exchange_rate))
+        .collect();
+    assert_eq!(
+        currencies,
+        vec![(None, None), (None, None), (Some("USD"), Some(83.5))]
+    );
+    assert_eq!(transactions[2].base_amount(), 8350.0);
+
+    let req = TestRequest::get().uri("/finance/goats").to_request();
+    let rows: Vec<Value> = call_and_read_body_json(&app, req).await;
+    assert_eq!(rows[0]["income"], json!(8350.0));
+    assert_eq!(rows[0]["expenses"], json!(700.0));
+
+    let req = TestRequest::get()
+        .uri("/finance/sales-register?from=2026-04-01&to=2026-04-30")
+        .to_request();
+    let register: SalesRegister = call_and_read_body_json(&app, req).await;
+    assert_eq!(register.currency, "INR");
+    assert_eq!(register.taxable_value, 8350.0);
+
+    // Recorded amounts pin the base currency
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({ "base_currency": "USD" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({ "base_currency": "inr" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+}
+
+#[actix_rt::test]
+async fn test_budget_variance() {
+    let db_pool = common::temp_pool("budgets");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for (budget, status) in [
+        (json!({ "category": "Feed", "monthly_amount": 5000.0 }), 200),
+        (
+            json!({ "category": "Veterinary", "monthly_amount": 2000.0 }),
+            200,
+        ),
+        (
+            json!({ "category": "Labor", "monthly_amount": 8000.0 }),
+            200,
+        ),
+        // Replaces the earlier Feed budget
+        (json!({ "category": "Feed", "monthly_amount": 4000.0 }), 200),
+        (json!({ "category": "Milk", "monthly_amount": 1000.0 }), 400),
+        (json!({ "category": "Labor", "monthly_amount": -1.0 }), 400),
+    ] {
+        let req = TestRequest::put()
+            .uri("/finance/budgets")
+            .set_json(&budget)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), status, "{}", budget);
+    }
+
+    let expense = |category: &str, amount: f64, date: &str| {
+        json!({
+            "kind": "Expense",
+            "category": category,
+            "amount": amount,
+            "description": "",
+            "goat_name": null,
+            "space_id": null,
+            "consumption_id": null,
+            "date": date
+        })
+    };
+    for body in [
+        expense("Feed", 3000.0, "2026-04-02"),
+        expense("Feed", 1500.5, "2026-04-30"),
+        expense("Feed", 9000.0, "2026-05-01"),
+        expense("Veterinary", 1200.0, "2026-04-18"),
+        expense("Equipment", 650.0, "2026-04-11"),
+    ] {
+        let req = TestRequest::post()
+            .uri("/transactions")
+            .set_json(&body)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201, "{}", body);
+    }
+
+    let req = TestRequest::get()
+        .uri("/finance/budgets/variance?month=2026-04")
+        .to_request();
+    let report: BudgetReport = call_and_read_body_json(&app, req).await;
+    assert_eq!(report.month, "2026-04");
+    assert_eq!(
+        report.lines,
+        vec![
+            BudgetVariance::new(FinanceCategory::Feed, Some(4000.0), 4500.5),
+            BudgetVariance::new(FinanceCategory::Veterinary, Some(2000.0), 1200.0),
+            BudgetVariance::new(FinanceCategory::Equipment, None, 650.0),
+            BudgetVariance::new(FinanceCategory::Labor, Some(8000.0), 0.0),
+        ]
+    );
+    assert_eq!(report.lines[0].variance, Some(-500.5));
+    assert!(report.lines[0].over_budget);
+    assert_eq!(
+        report
+            .over_budget()
+            .map(|l| &l.category)
+            .collect::<Vec<_>>(),
+        vec![&FinanceCategory::Feed]
+    );
+    assert_eq!(report.total_budget, 14000.0);
+    assert_eq!(report.total_actual, 6350.5);
+
+    let req = TestRequest::delete()
+        .uri("/finance/budgets/Labor")
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    for uri in ["/finance/budgets/Labor", "/finance/budgets/Goats"] {
+        let req = TestRequest::delete().uri(uri).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400, "{}", uri);
+    }
+    let req = TestRequest::get()
+        .uri("/finance/budgets/variance?month=April")
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::auth::API_KEY_HEADER;
+use backend::routes;
+use serde_json::json;
+use shared::gps::{GeoPoint, Geofence, GoatPosition};
+use shared::scale::IssuedApiKey;
+use shared::tasks::Task;
+
+#[test]
+fn test_geofence_contains() {
+    // An L-shaped paddock; the notch at the top right is outside
+    let corners = [
+        (0.0, 0.0),
+        (0.0, 2.0),
+        (1.0, 2.0),
+        (1.0, 1.0),
+        (2.0, 1.0),
+        (2.0, 0.0),
+    ];
+    let fence = Geofence {
+        id: None,
+        name: "Paddock".to_string(),
+        boundary: corners
+            .iter()
+            .map(|(lat, lon)| GeoPoint {
+                lat: *lat,
+                lon: *lon,
+            })
+            .collect(),
+    };
+    assert!(fence.validate().is_ok());
+    assert!(fence.contains(&GeoPoint { lat: 0.5, lon: 1.5 }));
+    assert!(fence.contains(&GeoPoint { lat: 1.5, lon: 0.5 }));
+    assert!(!fence.contains(&GeoPoint { lat: 1.5, lon: 1.5 }));
+    assert!(!fence.contains(&GeoPoint {
+        lat: -0.1,
+        lon: 0.5
+    }));
+
+    let line = Geofence {
+        boundary: fence.boundary[..2].to_vec(),
+        ..fence.clone()
+    };
+    assert!(line.validate().is_err());
+}
+
+#[actix_rt::test]
+async fn test_positions_and_geofence_alerts() {
+    let db_pool = common::temp_pool("gps");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for name in ["Rani", "Moti"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    {
+        let conn = db_pool.get_conn().unwrap();
+        conn.execute("UPDATE goats SET tag_id = 'GPS-1' WHERE name = 'Rani'", [])
+            .unwrap();
+        conn.execute("UPDATE goats SET tag_id = 'GPS-2' WHERE name = 'Moti'", [])
+            .unwrap();
+    }
+    let req = TestRequest::post()
+        .uri("/api-keys")
+        .set_json(json!({ "name": "Collar gateway" }))
+        .to_request();
+    let key: IssuedApiKey = call_and_read_body_json(&app, req).await;
+
+    let req = TestRequest::post()
+        .uri("/gps/geofences")
+        .set_json(json!({
+            "id": null,
+            "name": "North paddock",
+            "boundary": [
+                { "lat": 28.60, "lon": 77.20 },
+                { "lat": 28.60, "lon": 77.21 },
+                { "lat": 28.61, "lon": 77.21 },
+                { "lat": 28.61, "lon": 77.20 }
+            ]
+        }))
+        .to_request();
+    let fence: Geofence = call_and_read_body_json(&app, req).await;
+    assert!(fence.id.is_some());
+
+    let position = |tag: &str, lat: f64, lon: f64, at: &str| {
+        TestRequest::post()
+            .uri("/gps/positions")
+            .insert_header((API_KEY_HEADER, key.key.as_str()))
+            .set_json(json!({ "tag_id": tag, "lat": lat, "lon": lon, "recorded_at": at }))
+            .to_request()
+    };
+    let req = position("GPS-1", 28.605, 77.205, "2026-06-01T08:00:00");
+    let rani: GoatPosition = call_and_read_body_json(&app, req).await;
+    assert_eq!(rani.geofence.as_deref(), Some("North paddock"));
+    assert!(!rani.outside);
+    let req = position("GPS-2", 28.606, 77.206, "2026-06-01T08:00:00");
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    // Rani wanders off the paddock
+    let req = position("GPS-1", 28.62, 77.205, "2026-06-01T09:00:00");
+    let rani: GoatPosition = call_and_read_body_json(&app, req).await;
+    assert!(rani.outside);
+    assert_eq!(rani.geofence, None);
+
+    let req = TestRequest::get().uri("/gps/positions").to_request();
+    let positions: Vec<GoatPosition> = call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = positions.iter().map(|p| p.goat_name.as_str()).collect();
+    assert_eq!(names, vec!["Moti", "Rani"]);
+    assert_eq!(
+        positions[1].recorded_at.to_rfc3339(),
+        "2026-06-01T09:00:00+00:00"
+    );
+
+    let req = TestRequest::get().uri("/gps/alerts").to_request();
+    let alerts: Vec<GoatPosition> = call_and_read_body_json(&app, req).await;
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].goat_name, "Rani");
+
+    // Leaving the fence created a task to go and find her
+    let req = TestRequest::get().uri("/tasks").to_request();
+    let tasks: Vec<Task> = call_and_read_body_json(&app, req).await;
+    let escapes: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| t.title == "Check on Rani: outside geofence")
+        .collect();
+    assert_eq!(escapes.len(), 1);
+    assert_eq!(escapes[0].goat_name.as_deref(), Some("Rani"));
+
+    // Unknown tags, bad coordinates and missing keys are rejected
+    let req = position("GPS-9", 28.605, 77.205, "2026-06-01T09:00:00");
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = position("GPS-1", 95.0, 77.205, "2026-06-01T09:00:00");
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::post()
+        .uri("/gps/positions")
+        .set_json(json!({ "tag_id": "GPS-1", "lat": 28.6, "lon": 77.2 }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+
+    // Without geofences nobody is outside
+    let req = TestRequest::delete()
+        .uri(&format!("/gps/geofences/{}", fence.id.unwrap()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::get().uri("/gps/alerts").to_request();
+    let alerts: Vec<GoatPosition> = call_and_read_body_json(&app, req).await;
+    assert!(alerts.is_empty());
+}
@@ -0,0 +1,172 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::rotation::plan_rotation;
+use backend::routes;
+use chrono::NaiveDate;
+use serde_json::json;
+use shared::grazing::{GrazingGroup, Paddock, RotationPlan};
+
+fn paddock(name: &str, rest_days: i64) -> Paddock {
+    Paddock {
+        id: None,
+        name: name.to_string(),
+        area_m2: None,
+        capacity: None,
+        rest_days,
+        last_grazed_on: None,
+    }
+}
+
+fn group(name: &str, goats: i64) -> GrazingGroup {
+    GrazingGroup {
+        name: name.to_string(),
+        goats,
+    }
+}
+
+fn start() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 4, 6).unwrap()
+}
+
+/// The paddock of each group, week by week.
+fn schedule(plan: &RotationPlan) -> Vec<Vec<Option<&str>>> {
+    plan.weeks
+        .iter()
+        .map(|w| w.assignments.iter().map(|a| a.paddock.as_deref()).collect())
+        .collect()
+}
+
+#[test]
+fn test_rotation_rests_paddocks() {
+    let paddocks = [paddock("A", 7), paddock("B", 7), paddock("C", 7)];
+    let plan = plan_rotation(&paddocks, &[group("North Pen", 12)], start(), 4);
+    assert_eq!(
+        schedule(&plan),
+        vec![[Some("A")], [Some("B")], [Some("C")], [Some("A")]]
+    );
+    assert_eq!(plan.weeks[3].starts_on, "2026-04-27");
+    assert!(plan.conflicts.is_empty());
+
+    // A paddock grazed just before the plan starts waits out its rest
+    let mut recent = paddocks.to_vec();
+    recent[0].last_grazed_on = Some("2026-04-05".to_string());
+    let plan = plan_rotation(&recent, &[group("North Pen", 12)], start(), 1);
+    assert_eq!(schedule(&plan), vec![[Some("B")]]);
+}
+
+#[test]
+fn test_rotation_conflicts() {
+    // Two groups on two paddocks cannot keep a week's rest
+    let paddocks = [paddock("A", 7), paddock("B", 7)];
+    let groups = [group("Kids", 4), group("Does", 9)];
+    let plan = plan_rotation(&paddocks, &groups, start(), 2);
+    assert_eq!(plan.groups[0].name, "Does");
+    assert_eq!(
+        schedule(&plan),
+        vec![[Some("A"), Some("B")], [Some("A"), Some("B")]]
+    );
+    assert_eq!(plan.conflicts.len(), 2);
+    assert_eq!(plan.conflicts[0].week_starts_on, "2026-04-13");
+    assert_eq!(
+        plan.conflicts[0].message,
+        "Does goes on A after 0 days' rest; it needs 7"
+    );
+    assert!(plan.weeks[1].assignments.iter().all(|a| a.conflict));
+
+    // Big groups prefer paddocks that can carry them
+    let mut small = paddock("Small", 7);
+    small.capacity = Some(5);
+    let mut field = paddock("Field", 7);
+    field.area_m2 = Some(20_000.0);
+    let plan = plan_rotation(&[small.clone(), field], &groups, start(), 1);
+    assert_eq!(schedule(&plan), vec![[Some("Field"), Some("Small")]]);
+    assert!(plan.conflicts.is_empty());
+
+    let plan = plan_rotation(&[small], &groups, start(), 1);
+    assert_eq!(schedule(&plan), vec![[Some("Small"), None]]);
+    let messages: Vec<_> = plan.conflicts.iter().map(|c| c.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Small carries 5 goats but Does has 9",
+            "No paddock is free for Kids"
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn test_paddock_and_rotation_endpoints() {
+    let db_pool = common::temp_pool("grazing");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    for name in ["Rani", "Meena"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    let req = TestRequest::post()
+        .uri("/spaces")
+        .set_json(json!({ "id": null, "name": "North Pen", "kind": "Enclosure", "capacity": 10 }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::put()
+        .uri("/spaces/1/goats")
+        .set_json(json!(["Rani", "Meena"]))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    for body in [
+        json!({ "id": null, "name": "Hill", "rest_days": 7, "last_grazed_on": "2026-04-01" }),
+        json!({ "id": null, "name": "River", "rest_days": 7, "capacity": 20 }),
+    ] {
+        let req = TestRequest::post()
+            .uri("/grazing/paddocks")
+            .set_json(body)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    for body in [
+        json!({ "id": null, "name": "hill", "rest_days": 7 }),
+        json!({ "id": null, "name": " ", "rest_days": 7 }),
+        json!({ "id": null, "name": "Ridge", "rest_days": -1 }),
+        json!({ "id": null, "name": "Ridge", "rest_days": 7, "last_grazed_on": "1 April" }),
+    ] {
+        let req = TestRequest::post()
+            .uri("/grazing/paddocks")
+            .set_json(&body)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400, "{}", body);
+    }
+    let req = TestRequest::get().uri("/grazing/paddocks").to_request();
+    let paddocks: Vec<Paddock> = call_and_read_body_json(&app, req).await;
+    let names: Vec<_> = paddocks.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["Hill", "River"]);
+
+    let req = TestRequest::get()
+        .uri("/grazing/rotation?start=2026-04-06&weeks=3")
+        .to_request();
+    let plan: RotationPlan = call_and_read_body_json(&app, req).await;
+    assert_eq!(plan.groups, vec![group("North Pen", 2)]);
+    assert_eq!(
+        schedule(&plan),
+        vec![[Some("River")], [Some("Hill")], [Some("River")]]
+    );
+    assert!(plan.conflicts.is_empty());
+
+    for uri in [
+        "/grazing/rotation?start=06-04-2026",
+        "/grazing/rotation?weeks=0",
+        "/grazing/rotation?weeks=27",
+    ] {
+        let req = TestRequest::get().uri(uri).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400, "{}", uri);
+    }
+}
@@ -0,0 +1,235 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::estimation::{HttpWeightEstimator, WeightEstimator};
+use backend::routes;
+use chrono::{Duration, Local};
+use serde_json::{Value, json};
+use shared::Breed;
+use shared::growth::{expected_weight, weight_at_percentile, weight_percentile};
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, mpsc};
+
+#[test]
+fn test_breed_growth_curve() {
+    // Interpolated halfway between 90 days (11 kg) and 180 days (17 kg)
+    assert_eq!(expected_weight(&Breed::Beetal, 135), Some(14.0));
+    // Held flat past the last reference age
+    assert_eq!(expected_weight(&Breed::Beetal, 2000), Some(35.0));
+    assert_eq!(expected_weight(&Breed::Other("Boer".into()), 100), None);
+
+    // The reference weight is the median
+    let median = weight_percentile(&Breed::Sirohi, 365, 24.0).unwrap();
+    assert!((median - 50.0).abs() < 0.01);
+    // Percentile and weight-at-percentile are inverses
+    let p10 = weight_at_percentile(&Breed::Sirohi, 365, 10.0).unwrap();
+    assert!((weight_percentile(&Breed::Sirohi, 365, p10).unwrap() - 10.0).abs() < 0.01);
+    assert!(p10 < 24.0);
+}
+
+#[actix_rt::test]
+async fn test_growth_benchmarks_flag_slow_growers() {
+    let db_pool = common::temp_pool("growth");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let dob = (Local::now().date_naive() - Duration::days(365))
+        .format("%Y-%m-%d")
+        .to_string();
+    for name in ["Rani", "Moti", "Unborn"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    for name in ["Rani", "Moti"] {
+        let req = TestRequest::put()
+            .uri("/breeding/pedigree")
+            .set_json(json!({
+                "goat_name": name, "sire_name": null, "dam_name": null, "date_of_birth": dob
+            }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 200);
+    }
+
+    // Rani is on the Beetal curve at one year; Moti is far below it
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    for (name, weight) in [("Rani", 27.0), ("Moti", 15.0)] {
+        let req = TestRequest::post()
+            .uri("/growth/weights")
+            .set_json(
+                json!({ "id": null, "goat_name": name, "weighed_on": today, "weight": weight }),
+            )
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    // An older weighing does not overwrite the current weight
+    let req = TestRequest::post()
+        .uri("/growth/weights")
+        .set_json(json!({ "id": null, "goat_name": "Rani", "weighed_on": dob, "weight": 3.0 }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::post()
+        .uri("/growth/weights")
+        .set_json(json!({ "id": null, "goat_name": "Rani", "weighed_on": today, "weight": 0.0 }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let req = TestRequest::get().uri("/goats").to_request();
+    let goats: Vec<Value> = call_and_read_body_json(&app, req).await;
+    let rani = goats.iter().find(|g| g["name"] == "Rani").unwrap();
+    assert_eq!(rani["weight"], 27.0);
+
+    let req = TestRequest::get().uri("/growth/weights/Rani").to_request();
+    let history: Value = call_and_read_body_json(&app, req).await;
+    let records = history["records"].as_array().unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["age_days"], 0);
+    assert_eq!(records[1]["age_days"], 365);
+
+    // Goats without a date of birth are not benchmarked
+    let req = TestRequest::get().uri("/growth/benchmarks").to_request();
+    let benchmarks: Vec<Value> = call_and_read_body_json(&app, req).await;
+    assert_eq!(benchmarks.len(), 2);
+    assert_eq!(benchmarks[0]["goat_name"], "Moti");
+    assert_eq!(benchmarks[0]["below_expected"], true);
+    assert_eq!(benchmarks[1]["goat_name"], "Rani");
+    assert_eq!(benchmarks[1]["below_expected"], false);
+}
+
+/// Serves one request on a local port with `response`, like an estimation
+/// service would, and hands back the raw request received.
+fn fake_estimation_service(response: &'static str) -> (String, mpsc::Receiver<Vec<u8>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/v1/estimate", listener.local_addr().unwrap());
+    let (sender, received) = mpsc::channel();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        // Read the head, then as much body as it announces
+        loop {
+            let n = stream.read(&mut buffer).unwrap();
+            request.extend_from_slice(&buffer[..n]);
+            let text = String::from_utf8_lossy(&request).to_string();
+            if let Some(head_end) = text.find("\r\n\r\n") {
+                let length: usize = text
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse().ok())?
+                    })
+                    .unwrap_or(0);
+                if request.len() >= head_end + 4 + length {
+                    break;
+                }
+            }
+        }
+        stream.write_all(response.as_bytes()).unwrap();
+        sender.send(request).unwrap();
+    });
+    (url, received)
+}
+
+#[actix_rt::test]
+async fn test_weight_estimation_from_photo() {
+    let db_pool = common::temp_pool("growth_estimate");
+    let photo = |uri: &str, content_type: &str| {
+        TestRequest::post()
+            .uri(uri)
+            .insert_header(("Content-Type", content_type))
+            .set_payload(&b"not really a jpeg"[..])
+            .to_request()
+    };
+
+    // Without an estimator the feature is off
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let req = photo("/growth/estimate?reference_cm=30", "image/jpeg");
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let (url, received) = fake_estimation_service(
+        "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n\
+         {\"weight\": 31.5, \"confidence\": 0.8}",
+    );
+    let estimator: Arc<dyn WeightEstimator> = Arc::new(HttpWeightEstimator::new(&url).unwrap());
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .app_data(web::Data::from(estimator))
+            .configure(routes::configure),
+    )
+    .await;
+    for (uri, content_type) in [
+        ("/growth/estimate?reference_cm=30", "text/plain"),
+        ("/growth/estimate?reference_cm=0", "image/jpeg"),
+    ] {
+        let req = photo(uri, content_type);
+        assert_eq!(call_service(&app, req).await.status(), 400);
+    }
+    let req = photo("/growth/estimate?reference_cm=30", "image/jpeg");
+    let estimate: Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(estimate, json!({ "weight": 31.5, "confidence": 0.8 }));
+
+    // The service got the photo, its type and the reference length
+    let request = String::from_utf8(received.recv().unwrap()).unwrap();
+    assert!(request.starts_with("POST /v1/estimate?reference_cm=30 HTTP/1.1\r\n"));
+    assert!(
+        request
+            .to_lowercase()
+            .contains("content-type: image/jpeg\r\n")
+    );
+    assert!(request.ends_with("\r\n\r\nnot really a jpeg"));
+
+    // An estimated weighing is logged and marked, but the current weight
+    // stays with the last real weighing
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let req = TestRequest::post()
+        .uri("/growth/weights")
+        .set_json(json!({
+            "id": null, "goat_name": "Rani", "weighed_on": today, "weight": 31.5, "estimated": true
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::get().uri("/growth/weights/Rani").to_request();
+    let history: Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(history["records"][0]["weight"], 31.5);
+    assert_eq!(history["records"][0]["estimated"], true);
+    let req = TestRequest::get().uri("/goats").to_request();
+    let goats: Vec<Value> = call_and_read_body_json(&app, req).await;
+    assert_eq!(goats[0]["weight"], 30.0);
+
+    // A service that cannot be reached is a bad gateway
+    let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", closed.local_addr().unwrap());
+    drop(closed);
+    let estimator: Arc<dyn WeightEstimator> = Arc::new(HttpWeightEstimator::new(&url).unwrap());
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .app_data(web::Data::from(estimator))
+            .configure(routes::configure),
+    )
+    .await;
+    let req = photo("/growth/estimate?reference_cm=30", "image/jpeg");
+    assert_eq!(call_service(&app, req).await.status(), 502);
+    assert!(HttpWeightEstimator::new("https://example.com/estimate").is_ok());
+    assert!(HttpWeightEstimator::new("ftp://example.com/estimate").is_err());
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::handlers::health::month_labels;
+use backend::routes;
+use chrono::NaiveDate;
+use serde_json::{Value, json};
+use shared::health::HealthHeatMap;
+
+#[test]
+fn test_month_labels() {
+    let date = |s| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+    assert_eq!(
+        month_labels(date("2024-11-20"), date("2025-02-01")),
+        vec!["2024-11", "2024-12", "2025-01", "2025-02"]
+    );
+    assert_eq!(
+        month_labels(date("2025-03-31"), date("2025-03-31")),
+        vec!["2025-03"]
+    );
+}
+
+#[actix_rt::test]
+async fn test_health_heatmap_by_pen() {
+    let db_pool = common::temp_pool("health");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for name in ["Rani", "Meena", "Moti"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    for pen in ["Low Pen", "Hill Pen"] {
+        let req = TestRequest::post()
+            .uri("/spaces")
+            .set_json(json!({ "id": null, "name": pen, "kind": "Enclosure", "capacity": null }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    for (space_id, goats) in [(1, json!(["Rani", "Meena"])), (2, json!(["Moti"]))] {
+        let req = TestRequest::put()
+            .uri(&format!("/spaces/{}/goats", space_id))
+            .set_json(goats)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 200);
+    }
+
+    let incident = |goat: &str, kind: &str, on: &str| {
+        json!({
+            "id": null, "goat_name": goat, "space_id": null, "kind": kind,
+            "condition": "Worms", "observed_on": on, "notes": null
+        })
+    };
+    for body in [
+        incident("Rani", "Parasite", "2025-01-10"),
+        incident("Meena", "Parasite", "2025-01-20"),
+        incident("Rani", "Parasite", "2025-02-05"),
+        incident("Moti", "Injury", "2025-02-07"),
+    ] {
+        let req = TestRequest::post()
+            .uri("/health/incidents")
+            .set_json(body)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    // Moving Rani later keeps her past incidents in Low Pen
+    let req = TestRequest::put()
+        .uri("/spaces/2/goats")
+        .set_json(json!(["Rani"]))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    let req = TestRequest::get()
+        .uri("/health/incidents?goat_name=Rani")
+        .to_request();
+    let incidents: Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(incidents.as_array().unwrap().len(), 2);
+    assert_eq!(incidents[0]["space_name"], "Low Pen");
+
+    let req = TestRequest::get()
+        .uri("/health/heatmap?from=2025-01-01&to=2025-03-31")
+        .to_request();
+    let heatmap: HealthHeatMap = call_and_read_body_json(&app, req).await;
+    assert_eq!(heatmap.months, vec!["2025-01", "2025-02", "2025-03"]);
+    assert_eq!(heatmap.rows[0].space_name, "Low Pen");
+    assert_eq!(heatmap.rows[0].counts, vec![2, 1, 0]);
+    assert_eq!(heatmap.rows[1].counts, vec![0, 1, 0]);
+    assert_eq!(heatmap.max_count(), 2);
+
+    let req = TestRequest::get()
+        .uri("/health/heatmap?from=2025-01-01&to=2025-03-31&kind=Injury")
+        .to_request();
+    let heatmap: HealthHeatMap = call_and_read_body_json(&app, req).await;
+    assert_eq!(heatmap.rows[0].space_name, "Hill Pen");
+    assert_eq!(heatmap.rows[0].total, 1);
+    assert_eq!(heatmap.rows[1].total, 0);
+
+    for uri in [
+        "/health/heatmap?from=2025-03-01&to=2025-01-01",
+        "/health/heatmap?from=2020-01-01&to=2025-01-01",
+    ] {
+        let req = TestRequest::get().uri(uri).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400);
+    }
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::auth::API_KEY_HEADER;
+use backend::heat::{detect_heats, predict_heat};
+use backend::routes;
+use chrono::{Days, Local, NaiveDate};
+use serde_json::json;
+use shared::heat::HeatPrediction;
+use shared::scale::IssuedApiKey;
+use shared::sensors::{Sensor, SensorCondition};
+
+/// A thousand steps a day from `start`, with the given days overridden.
+fn activity(start: NaiveDate, days: u64, spikes: &[(u64, f64)]) -> Vec<(NaiveDate, f64)> {
+    (0..days)
+        .map(|i| {
+            let steps = spikes
+                .iter()
+                .find(|(day, _)| *day == i)
+                .map_or(1000.0, |(_, steps)| *steps);
+            (start + Days::new(i), steps)
+        })
+        .collect()
+}
+
+#[test]
+fn test_detect_heats_and_predict_next() {
+    let start = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+    let daily = activity(start, 33, &[(10, 2500.0), (11, 1800.0), (31, 2400.0)]);
+
+    let heats = detect_heats(&daily);
+    let dates: Vec<&str> = heats.iter().map(|(_, s)| s.date.as_str()).collect();
+    // Day 11 belongs to the heat that started on day 10
+    assert_eq!(dates, vec!["2026-03-11", "2026-04-01"]);
+    assert!((heats[0].1.confidence - 0.75).abs() < 1e-9);
+    assert_eq!(heats[0].1.baseline, 1000.0);
+    // One cycle after the first heat, so it gets the cycle bonus
+    assert!((heats[1].1.confidence - 0.95).abs() < 1e-9);
+
+    let today = start + Days::new(32);
+    let prediction = predict_heat("Rani", &daily, today);
+    assert!(prediction.in_heat);
+    assert_eq!(prediction.last_heat.as_deref(), Some("2026-04-01"));
+    assert_eq!(prediction.next_heat.as_deref(), Some("2026-04-22"));
+
+    // Projecting two cycles further lowers the confidence
+    let later = predict_heat("Rani", &daily, start + Days::new(60));
+    assert!(!later.in_heat);
+    assert_eq!(later.next_heat.as_deref(), Some("2026-05-13"));
+    assert!(later.confidence < prediction.confidence);
+
+    let flat = predict_heat("Meena", &activity(start, 20, &[]), today);
+    assert_eq!(flat.next_heat, None);
+    assert_eq!(flat.confidence, 0.0);
+}
+
+#[actix_rt::test]
+async fn test_heat_predictions_from_activity_tags() {
+    let db_pool = common::temp_pool("heat");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let today = Local::now().date_naive();
+    let date = |days_ago: u64| (today - Days::new(days_ago)).format("%Y-%m-%d").to_string();
+    for name in ["Rani", "Meena"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    // Meena was bred recently, so she is presumed pregnant
+    let mut meena = common::sample_goat("Meena");
+    meena["last_bred"] = json!(date(30));
+    let req = TestRequest::put()
+        .uri("/goats")
+        .set_json(meena)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    let mut tags = Vec::new();
+    for name in ["Rani", "Meena"] {
+        let req = TestRequest::post()
+            .uri("/sensors")
+            .set_json(json!({
+                "id": null, "name": format!("{} collar", name), "kind": "Activity",
+                "space_id": null, "goat_name": name
+            }))
+            .to_request();
+        let tag: Sensor = call_and_read_body_json(&app, req).await;
+        assert_eq!(tag.goat_name.as_deref(), Some(name));
+        tags.push(tag);
+    }
+    let req = TestRequest::post()
+        .uri("/api-keys")
+        .set_json(json!({ "name": "Collar gateway" }))
+        .to_request();
+    let key: IssuedApiKey = call_and_read_body_json(&app, req).await;
+
+    // Two readings a day; Rani spikes 22 days ago and again yesterday
+    for days_ago in 0..30u64 {
+        for tag in &tags {
+            let steps = if tag.name == "Rani collar" && (days_ago == 22 || days_ago == 1) {
+                1400.0
+            } else {
+                500.0
+            };
+            for hour in ["08", "18"] {
+                let req = TestRequest::post()
+                    .uri("/sensors/readings")
+                    .insert_header((API_KEY_HEADER, key.key.as_str()))
+                    .set_json(json!({
+                        "sensor_id": tag.id,
+                        "value": steps,
+                        "read_at": format!("{}T{}:00:00", date(days_ago), hour)
+                    }))
+                    .to_request();
+                assert_eq!(call_service(&app, req).await.status(), 201);
+            }
+        }
+    }
+
+    let req = TestRequest::get().uri("/breeding/heat").to_request();
+    let predictions: Vec<HeatPrediction> = call_and_read_body_json(&app, req).await;
+    assert_eq!(predictions.len(), 1);
+    let rani = &predictions[0];
+    assert_eq!(rani.doe_name, "Rani");
+    assert!(rani.in_heat);
+    assert_eq!(rani.last_heat, Some(date(1)));
+    assert_eq!(
+        rani.next_heat,
+        Some((today + Days::new(20)).format("%Y-%m-%d").to_string())
+    );
+    assert_eq!(rani.recent_spikes.len(), 2);
+    assert_eq!(rani.recent_spikes[0].activity, 2800.0);
+    assert!(rani.confidence > rani.recent_spikes[0].confidence);
+
+    // Activity tags are not barn conditions
+    let req = TestRequest::get().uri("/sensors/conditions").to_request();
+    let conditions: Vec<SensorCondition> = call_and_read_body_json(&app, req).await;
+    assert!(conditions.is_empty());
+}
@@ -0,0 +1,269 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::handlers::import::{parse_csv, parse_xlsx};
+use backend::routes;
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+use serde_json::json;
+use shared::import::{
+    BatchSummary, GoatField, ImportTable, goat_warnings, guess_mapping, map_row, missing_fields,
+    preview,
+};
+use shared::physical::{CoatColor, HornStatus, describe};
+use shared::{Breed, Gender, GoatParams};
+
+fn row(cells: &[&str]) -> Vec<String> {
+    cells.iter().map(|cell| cell.to_string()).collect()
+}
+
+#[test]
+fn test_parse_csv_detects_delimiter_and_pads_rows() {
+    let table =
+        parse_csv(b"\xEF\xBB\xBFAnimal;Sex;Breed\nRani;F;Beetal\n\n;;\nMoti;buck\n").unwrap();
+    assert_eq!(table.headers, row(&["Animal", "Sex", "Breed"]));
+    assert_eq!(
+        table.rows,
+        vec![row(&["Rani", "F", "Beetal"]), row(&["Moti", "buck", ""])]
+    );
+
+    let quoted = parse_csv(b"name,diet\n\"Rani\",\"hay, grain\"\n").unwrap();
+    assert_eq!(quoted.rows, vec![row(&["Rani", "hay, grain"])]);
+    assert!(parse_csv(b"\n\n").is_err());
+    assert!(parse_csv(b"name\n\xFF\n").is_err());
+}
+
+#[test]
+fn test_parse_xlsx_reads_first_sheet() {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet.write(0, 0, "Name").unwrap();
+    sheet.write(0, 1, "Weight (kg)").unwrap();
+    sheet.write(0, 2, "Last bred").unwrap();
+    sheet.write(1, 0, "Rani").unwrap();
+    sheet.write(1, 1, 32.5).unwrap();
+    let date = ExcelDateTime::from_ymd(2026, 3, 1).unwrap();
+    let format = Format::new().set_num_format("yyyy-mm-dd");
+    sheet
+        .write_datetime_with_format(1, 2, &date, &format)
+        .unwrap();
+    let bytes = workbook.save_to_buffer().unwrap();
+
+    let table = parse_xlsx(&bytes).unwrap();
+    assert_eq!(table.headers, row(&["Name", "Weight (kg)", "Last bred"]));
+    assert_eq!(table.rows, vec![row(&["Rani", "32.5", "2026-03-01"])]);
+    assert!(parse_xlsx(b"PK\x03\x04 not a workbook").is_err());
+}
+
+#[test]
+fn test_mapping_and_preview() {
+    let table = ImportTable {
+        headers: row(&[
+            "Goat Name",
+            "SEX",
+            "breed",
+            "Weight (kg)",
+            "Vaccines",
+            "Notes",
+        ]),
+        rows: vec![
+            row(&["Rani", "doe", "black bengal", "31", "CDT; Rabies", "calm"]),
+            row(&["Moti", "x", "Beetal", "heavy", "", ""]),
+            row(&["Rani", "F", "Saanen", "28", "", ""]),
+            row(&["", "M", "Beetal", "", "", ""]),
+        ],
+    };
+    let mapping = guess_mapping(&table.headers);
+    assert_eq!(
+        mapping,
+        vec![
+            Some(GoatField::Name),
+            Some(GoatField::Gender),
+            Some(GoatField::Breed),
+            Some(GoatField::Weight),
+            Some(GoatField::Vaccinations),
+            None,
+        ]
+    );
+    assert!(missing_fields(&mapping).is_empty());
+    assert_eq!(
+        missing_fields(&mapping[..1]),
+        vec![GoatField::Breed, GoatField::Gender]
+    );
+
+    let rani = map_row(&table.rows[0], &mapping).unwrap();
+    assert_eq!(rani.gender, Gender::Female);
+    assert_eq!(rani.breed, Breed::BlackBengal);
+    assert_eq!(rani.weight, 31.0);
+    assert_eq!(rani.vaccinations.len(), 2);
+
+    let rows = preview(&table, &mapping);
+    assert_eq!(
+        rows.iter().map(|r| r.line).collect::<Vec<_>>(),
+        [2, 3, 4, 5]
+    );
+    assert!(rows[0].errors.is_empty());
+    assert_eq!(rows[1].errors.len(), 2, "{:?}", rows[1].errors);
+    assert!(rows[1].goat.is_none());
+    assert!(rows[2].errors[0].contains("Duplicate name Rani"));
+    assert!(rows[2].goat.is_none());
+    assert_eq!(rows[3].errors, vec!["Name is required"]);
+    // Unpriced goats import, with a warning
+    assert_eq!(
+        rows[0].warnings,
+        vec![
+            "Cost is 0; was the goat born on the farm or a gift?",
+            "Current price is 0"
+        ]
+    );
+}
+
+#[test]
+fn test_map_row_physical_traits() {
+    let headers = row(&["Name", "Breed", "Sex", "Horns", "Colour", "Markings"]);
+    let mapping = guess_mapping(&headers);
+    assert_eq!(
+        mapping[3..],
+        [
+            Some(GoatField::Horns),
+            Some(GoatField::CoatColor),
+            Some(GoatField::Marks)
+        ]
+    );
+
+    let moti = map_row(
+        &row(&["Moti", "Sirohi", "F", "hornless", "gray", "White blaze"]),
+        &mapping,
+    )
+    .unwrap();
+    assert_eq!(moti.horns, Some(HornStatus::Polled));
+    assert_eq!(moti.coat_color, Some(CoatColor::Grey));
+    assert_eq!(describe(&moti), "Grey, polled; White blaze");
+
+    let plain = map_row(&row(&["Rani", "Beetal", "F", "", "", ""]), &mapping).unwrap();
+    assert_eq!((plain.horns, plain.coat_color, &plain.marks), (None, None, &None));
+    assert_eq!(describe(&plain), "");
+
+    let errors = map_row(&row(&["Rani", "Beetal", "F", "two", "striped", ""]), &mapping)
+        .unwrap_err();
+    assert_eq!(
+        errors,
+        vec!["Unknown horn status 'two'", "Unknown coat color 'striped'"]
+    );
+}
+
+#[test]
+fn test_goat_warnings() {
+    let goat = GoatParams::builder("Rani")
+        .cost(100.0)
+        .weight(32.0)
+        .current_price(150.0)
+        .build()
+        .unwrap();
+    assert!(goat_warnings(&goat).is_empty());
+
+    let heavy = GoatParams {
+        weight: 500.0,
+        offspring: 40,
+        ..goat.clone()
+    };
+    assert_eq!(
+        goat_warnings(&heavy),
+        vec![
+            "Weight 500 kg is unusually heavy for a goat",
+            "Offspring 40 is unusually high"
+        ]
+    );
+    let unweighed = GoatParams {
+        weight: 0.0,
+        ..goat
+    };
+    assert!(goat_warnings(&unweighed)[0].starts_with("Weight is 0 kg"));
+}
+
+#[test]
+fn test_goat_params_builder() {
+    let goat = GoatParams::builder("Rani").build().unwrap();
+    assert_eq!(goat.breed, Breed::Other("Unknown".to_string()));
+    assert_eq!(goat.gender, Gender::Female);
+    assert_eq!((goat.offspring, goat.weight), (0, 0.0));
+    assert_eq!(goat.health_status, "healthy");
+    assert_eq!(goat.last_bred, None);
+
+    let goat = GoatParams::builder("Sultan")
+        .breed(Breed::Sirohi)
+        .gender(Gender::Male)
+        .offspring(3)
+        .weight(52.5)
+        .last_bred("2026-03-01")
+        .vaccination("CDT")
+        .vaccination("PPR")
+        .disease("FootRot")
+        .build()
+        .unwrap();
+    assert_eq!(goat.breed, Breed::Sirohi);
+    assert_eq!(goat.weight, 52.5);
+    assert_eq!(goat.last_bred.as_deref(), Some("2026-03-01"));
+    let vaccines: Vec<&str> = goat.vaccinations.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(vaccines, ["CDT", "PPR"]);
+    assert_eq!(goat.diseases[0].name, "FootRot");
+
+    let errors = GoatParams::builder(" ")
+        .offspring(-1)
+        .cost(f64::NAN)
+        .last_bred("March 2026")
+        .build()
+        .unwrap_err();
+    assert_eq!(errors.len(), 4, "{:?}", errors);
+    assert!(errors.iter().any(|e| e.contains("YYYY-MM-DD")));
+}
+
+#[actix_rt::test]
+async fn test_import_and_batch_endpoints() {
+    let db_pool = common::temp_pool("import");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::post()
+        .uri("/goats/import")
+        .set_payload("Name,Gender,Breed\nRani,Female,Beetal\n")
+        .to_request();
+    let table: ImportTable = call_and_read_body_json(&app, req).await;
+    let goats: Vec<GoatParams> = preview(&table, &guess_mapping(&table.headers))
+        .into_iter()
+        .filter_map(|row| row.goat)
+        .collect();
+    assert_eq!(goats.len(), 1);
+
+    let req = TestRequest::post()
+        .uri("/goats/batch")
+        .set_json(&goats)
+        .to_request();
+    let summary: BatchSummary = call_and_read_body_json(&app, req).await;
+    assert_eq!(summary.added, 1);
+
+    // Nothing is added if any goat in the batch is rejected
+    let req = TestRequest::post()
+        .uri("/goats/batch")
+        .set_json(json!([
+            common::sample_goat("Moti"),
+            common::sample_goat("Rani")
+        ]))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::get().uri("/goats").to_request();
+    let herd: Vec<GoatParams> = call_and_read_body_json(&app, req).await;
+    assert_eq!(herd.len(), 1);
+
+    let req = TestRequest::post()
+        .uri("/goats/batch")
+        .set_json(json!([]))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::post().uri("/goats/import").to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+}
@@ -0,0 +1,253 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::handlers::insurance::compute_policy_alerts;
+use backend::routes;
+use backend::scheduler::evaluate_policy_renewals;
+use chrono::{Duration, Local, NaiveDate};
+use serde_json::json;
+use shared::insurance::{
+    ClaimStatus, InsuranceClaim, InsurancePolicy, PolicyAlert, PolicyAlertKind,
+};
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn days_from_today(days: i64) -> String {
+    (Local::now().date_naive() + Duration::days(days))
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+fn policy(id: i64, goat: &str, number: &str, expiry: &str) -> InsurancePolicy {
+    InsurancePolicy {
+        id: Some(id),
+        goat_name: goat.to_string(),
+        provider: "National Insurance".to_string(),
+        policy_number: number.to_string(),
+        sum_insured: 40000.0,
+        start_date: None,
+        expiry_date: expiry.to_string(),
+        notes: None,
+    }
+}
+
+#[test]
+fn test_compute_policy_alerts_uses_latest_policy_per_goat() {
+    let today = date("2025-06-01");
+    let policies = vec![
+        // Renewed: only the later policy counts
+        policy(1, "Rani", "P-1", "2025-05-01"),
+        policy(2, "Rani", "P-2", "2026-05-01"),
+        policy(3, "Moti", "P-3", "2025-06-20"),
+        policy(4, "Kali", "P-4", "2025-03-31"),
+        policy(5, "Gauri", "P-5", "not a date"),
+    ];
+
+    let alerts: Vec<(String, PolicyAlertKind)> = compute_policy_alerts(&policies, today, 30)
+        .into_iter()
+        .map(|a| (a.goat_name, a.kind))
+        .collect();
+    assert_eq!(
+        alerts,
+        vec![
+            ("Kali".to_string(), PolicyAlertKind::Expired),
+            ("Moti".to_string(), PolicyAlertKind::ExpiringSoon),
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_policy_renewals_creates_one_reminder() {
+    let pool = common::temp_pool("policy_renewals");
+    let mut conn = pool.get_conn().unwrap();
+    for name in ["Rani", "Moti", "Kali"] {
+        conn.execute(
+            "INSERT INTO goats (breed, name, gender) VALUES ('Beetal', ?1, 'Female')",
+            [name],
+        )
+        .unwrap();
+    }
+    let insert = |goat_id: i64, number: &str, expiry: &str| {
+        conn.execute(
+            "INSERT INTO insurance_policies (goat_id, provider, policy_number, sum_insured, expiry_date) \
+             VALUES (?1, 'National Insurance', ?2, 40000, ?3)",
+            rusqlite::params![goat_id, number, expiry],
+        )
+        .unwrap();
+    };
+    insert(1, "P-1", "2025-06-20");
+    // Moti's expiring policy has already been renewed
+    insert(2, "P-2", "2025-06-10");
+    insert(2, "P-3", "2026-06-10");
+    // Kali's cover lapsed before the scheduler ran
+    insert(3, "P-4", "2025-05-15");
+
+    assert_eq!(
+        evaluate_policy_renewals(&mut conn, date("2025-06-01")).unwrap(),
+        1
+    );
+    let (title, due, remind_on, message): (String, String, String, String) = conn
+        .query_row(
+            "SELECT t.title, t.due_date, r.remind_on, r.message \
+             FROM tasks t JOIN reminders r ON r.task_id = t.id",
+            [],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+        )
+        .unwrap();
+    assert_eq!(title, "Renew insurance policy P-1");
+    assert_eq!(due, "2025-06-20");
+    assert_eq!(remind_on, "2025-05-21");
+    assert!(message.contains("Rani"));
+
+    // Re-running creates nothing new
+    assert_eq!(
+        evaluate_policy_renewals(&mut conn, date("2025-06-02")).unwrap(),
+        0
+    );
+}
+
+#[actix_rt::test]
+async fn test_policy_and_claim_endpoints() {
+    let db_pool = common::temp_pool("insurance");
+    let mut conn = db_pool.get_conn().unwrap();
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let new_policy = json!({
+        "id": null,
+        "goat_name": "Rani",
+        "provider": "National Insurance",
+        "policy_number": "LIV-2291",
+        "sum_insured": 40000.0,
+        "start_date": days_from_today(-345),
+        "expiry_date": days_from_today(20),
+        "notes": null
+    });
+    let req = TestRequest::post()
+        .uri("/insurance/policies")
+        .set_json(&new_policy)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    // Duplicate number, unknown goat, zero cover and reversed dates are rejected
+    let mut unknown_goat = new_policy.clone();
+    unknown_goat["goat_name"] = json!("Nobody");
+    unknown_goat["policy_number"] = json!("LIV-2292");
+    let mut no_cover = new_policy.clone();
+    no_cover["sum_insured"] = json!(0.0);
+    let mut reversed = new_policy.clone();
+    reversed["start_date"] = json!(days_from_today(30));
+    for body in [&new_policy, &unknown_goat, &no_cover, &reversed] {
+        let req = TestRequest::post()
+            .uri("/insurance/policies")
+            .set_json(body)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400, "{}", body);
+    }
+
+    let req = TestRequest::get()
+        .uri("/insurance/policies?goat_name=Rani")
+        .to_request();
+    let policies: Vec<InsurancePolicy> = call_and_read_body_json(&app, req).await;
+    assert_eq!(policies.len(), 1);
+    let policy_id = policies[0].id.unwrap();
+
+    let req = TestRequest::get().uri("/insurance/alerts").to_request();
+    let alerts: Vec<PolicyAlert> = call_and_read_body_json(&app, req).await;
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].kind, PolicyAlertKind::ExpiringSoon);
+
+    let claim = json!({
+        "id": null,
+        "policy_id": 0,
+        "claim_date": days_from_today(-10),
+        "reason": "Death from pneumonia",
+        "amount_claimed": 40000.0,
+        "amount_settled": null,
+        "status": "Filed",
+        "notes": null
+    });
+    let mut over_insured = claim.clone();
+    over_insured["amount_claimed"] = json!(50000.0);
+    let mut before_cover = claim.clone();
+    before_cover["claim_date"] = json!(days_from_today(-400));
+    for body in [&over_insured, &before_cover] {
+        let req = TestRequest::post()
+            .uri(&format!("/insurance/policies/{}/claims", policy_id))
+            .set_json(body)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400, "{}", body);
+    }
+    let req = TestRequest::post()
+        .uri(&format!("/insurance/policies/{}/claims", policy_id))
+        .set_json(&claim)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let req = TestRequest::get()
+        .uri(&format!("/insurance/claims?policy_id={}", policy_id))
+        .to_request();
+    let claims: Vec<InsuranceClaim> = call_and_read_body_json(&app, req).await;
+    assert_eq!(claims.len(), 1);
+    assert_eq!(claims[0].policy_id, policy_id);
+    let claim_id = claims[0].id.unwrap();
+
+    // Settling needs the amount paid out
+    let mut settled = claims[0].clone();
+    settled.status = ClaimStatus::Settled;
+    let req = TestRequest::put()
+        .uri(&format!("/insurance/claims/{}", claim_id))
+        .set_json(&settled)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    settled.amount_settled = Some(36000.0);
+    let req = TestRequest::put()
+        .uri(&format!("/insurance/claims/{}", claim_id))
+        .set_json(&settled)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    // Moving the expiry drops the pending renewal reminder for the old date
+    assert_eq!(
+        evaluate_policy_renewals(&mut conn, Local::now().date_naive()).unwrap(),
+        1
+    );
+    let mut renewed = policies[0].clone();
+    renewed.expiry_date = days_from_today(385);
+    let req = TestRequest::put()
+        .uri(&format!("/insurance/policies/{}", policy_id))
+        .set_json(&renewed)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let tasks: i64 = conn
+        .query_row("SELECT COUNT(*) FROM tasks", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(tasks, 0);
+    let req = TestRequest::get().uri("/insurance/alerts").to_request();
+    let alerts: Vec<PolicyAlert> = call_and_read_body_json(&app, req).await;
+    assert!(alerts.is_empty());
+
+    let req = TestRequest::delete()
+        .uri(&format!("/insurance/policies/{}", policy_id))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::get().uri("/insurance/claims").to_request();
+    let claims: Vec<InsuranceClaim> = call_and_read_body_json(&app, req).await;
+    assert!(claims.is_empty());
+    let req = TestRequest::delete()
+        .uri(&format!("/insurance/policies/{}", policy_id))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+}
@@ -1,8 +1,11 @@
+mod common;
+
 use std::io::stdin;
 
 use actix_web::{App, test, web};
 use backend::db::DbPool;
-use backend::handlers::goats::{add_goat, delete_goat, get_goats, update_goat};
+use backend::handlers::client_errors::{get_client_errors, report_client_error};
+use backend::handlers::goats::{add_goat, delete_goat, get_goats, stream_goats, update_goat};
 use serde_json::json;
 use tracing::{debug, info};
 use tracing_subscriber;
@@ -65,6 +68,82 @@ async fn test_get_goats_endpoint() {
     );
 }
 
+#[actix_rt::test]
+async fn test_get_goats_etag_not_modified() {
+    let db_pool = common::temp_pool("goats_etag");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .service(web::scope("/goats").route("", web::get().to(get_goats))),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/goats").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    let etag = resp
+        .headers()
+        .get("etag")
+        .expect("Missing ETag header")
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let req = test::TestRequest::get()
+        .uri("/goats")
+        .insert_header(("If-None-Match", etag.as_str()))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 304, "Unchanged list should return 304");
+
+    let req = test::TestRequest::get()
+        .uri("/goats")
+        .insert_header(("If-None-Match", "\"stale\""))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200, "Mismatched tag should return the list");
+}
+
+#[actix_rt::test]
+async fn test_stream_goats_ndjson() {
+    let db_pool = common::temp_pool("goats_stream");
+    {
+        let conn = db_pool.get_conn().unwrap();
+        for i in 0..3 {
+            conn.execute(
+                "INSERT INTO goats (breed, name, gender, offspring, cost, weight, current_price, diet, health_status) \
+                 VALUES ('Beetal', ?1, 'Female', 0, 100.0, 30.0, 150.0, 'Hay', 'Healthy')",
+                [format!("Streamed{}", i)],
+            )
+            .unwrap();
+        }
+    }
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .service(web::scope("/goats").route("/stream", web::get().to(stream_goats))),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/goats/stream").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "application/x-ndjson"
+    );
+
+    let body = test::read_body(resp).await;
+    let names: Vec<String> = std::str::from_utf8(&body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<shared::GoatParams>(line).unwrap().name)
+        .collect();
+    assert_eq!(names, vec!["Streamed0", "Streamed1", "Streamed2"]);
+}
+
 #[actix_rt::test]
 async fn test_add_goat_endpoint() {
     // Initialize tracing (only once per test run)
@@ -73,8 +152,8 @@ async fn test_add_goat_endpoint() {
         .with_test_writer()
         .try_init();
 
-    // Setup DB pool pointing to test database
-    let db_pool = DbPool::new("sample_livestock.db").expect("Failed to create DbPool");
+    // Setup a fresh test database
+    let db_pool = common::temp_pool("add_goat");
 
     // Initialize Actix app with POST /goats route
     let app = test::init_service(
@@ -126,18 +205,26 @@ async fn test_update_goat_endpoint() {
         .with_test_writer()
         .try_init();
 
-    let db_pool = DbPool::new("sample_livestock.db").expect("Failed to create DbPool");
+    let db_pool = common::temp_pool("update_goat");
     debug!("Pool generated");
 
     let app = test::init_service(
-        App::new()
-            .app_data(web::Data::new(db_pool))
-            .service(web::scope("/goats").route("", web::put().to(update_goat))),
+        App::new().app_data(web::Data::new(db_pool)).service(
+            web::scope("/goats")
+                .route("", web::post().to(add_goat))
+                .route("", web::put().to(update_goat)),
+        ),
     )
     .await;
+
+    // Seed the goat that will be updated
+    let req = test::TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("NewGoat"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
     debug!("App created in test_update_goats");
 
-    // Example of goat data with an existing id (adjust id according to your test DB)
     let updated_goat = json!({
         "breed": "Beetal",
         "name": "NewGoat",
@@ -169,7 +256,6 @@ async fn test_update_goat_endpoint() {
     // debug!("Response body: {}", body_str);
 }
 
-//ToDo: Delete goat has to take a hardcoded id
 #[actix_rt::test]
 async fn test_delete_goat_endpoint() {
     // Init tracing
@@ -178,16 +264,24 @@ async fn test_delete_goat_endpoint() {
         .with_test_writer()
         .try_init();
 
-    let db_pool = DbPool::new("sample_livestock.db").expect("Failed to create DbPool");
+    let db_pool = common::temp_pool("delete_goat");
 
     let app = test::init_service(
-        App::new()
-            .app_data(web::Data::new(db_pool))
-            .service(web::scope("/goats").route("", web::delete().to(delete_goat))),
+        App::new().app_data(web::Data::new(db_pool)).service(
+            web::scope("/goats")
+                .route("", web::post().to(add_goat))
+                .route("", web::delete().to(delete_goat)),
+        ),
     )
     .await;
 
-    // Provide the ID of the goat to delete (adjust based on your test DB content)
+    // Seed the goat that will be deleted
+    let req = test::TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("NewGoat8"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
     let name_payload = json!({ "name": "NewGoat8"});
 
     let req = test::TestRequest::delete()
@@ -203,3 +297,45 @@ async fn test_delete_goat_endpoint() {
     let body_str = std::str::from_utf8(&body_bytes).unwrap_or("<invalid utf8>");
     debug!("Response body: {}", body_str);
 }
+
+#[actix_rt::test]
+async fn test_client_error_report_endpoint() {
+    let db_pool = common::temp_pool("client_errors");
+
+    let app = test::init_service(
+        App::new().app_data(web::Data::new(db_pool)).service(
+            web::scope("/client-errors")
+                .route("", web::get().to(get_client_errors))
+                .route("", web::post().to(report_client_error)),
+        ),
+    )
+    .await;
+
+    // Beacon requests arrive as text/plain, so the body must be parsed regardless of content type
+    let report = json!({
+        "id": null,
+        "section": "Goat List",
+        "message": "panicked at src/components/goat_list.rs",
+        "url": "http://127.0.0.1:8080/",
+        "user_agent": "test",
+        "reported_at": null
+    });
+    let req = test::TestRequest::post()
+        .uri("/client-errors")
+        .insert_header(("content-type", "text/plain;charset=UTF-8"))
+        .set_payload(report.to_string())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+
+    let req = test::TestRequest::post()
+        .uri("/client-errors")
+        .set_payload("not json")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let req = test::TestRequest::get().uri("/client-errors").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+}
@@ -0,0 +1,295 @@
+mod common;
+
+use actix_web::{App, test, web};
+use backend::handlers::finance::{get_goat_profitability, get_transactions};
+use backend::handlers::inventory::{
+    add_item, compute_alerts, consume_item, dispose_item, get_alerts, get_consumption,
+    get_expiring, get_items,
+};
+use chrono::{Days, NaiveDate, Utc};
+use serde_json::{Value, json};
+use shared::finance::{FinanceCategory, GoatProfitability, Transaction};
+use shared::inventory::{
+    ConsumptionRecord, Disposal, ExpiringStock, InventoryAlertKind, InventoryCategory,
+    InventoryItem,
+};
+
+fn item(name: &str, quantity: f64, reorder_level: f64, expiry: Option<&str>) -> InventoryItem {
+    InventoryItem {
+        id: Some(1),
+        name: name.to_string(),
+        category: InventoryCategory::Medicine,
+        quantity,
+        unit: "ml".to_string(),
+        unit_cost: 2.0,
+        expiry_date: expiry.map(str::to_string),
+        reorder_level,
+    }
+}
+
+#[actix_rt::test]
+async fn test_compute_alerts() {
+    let today = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+    let items = vec![
+        item("Dewormer", 10.0, 20.0, None),
+        item("Vitamin B", 100.0, 20.0, Some("2025-06-15")),
+        item("Antibiotic", 100.0, 0.0, Some("2025-05-01")),
+        item("Mineral mix", 100.0, 20.0, Some("2026-01-01")),
+    ];
+
+    let kinds: Vec<(String, InventoryAlertKind)> = compute_alerts(&items, today, 30)
+        .into_iter()
+        .map(|a| (a.item_name, a.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ("Dewormer".to_string(), InventoryAlertKind::LowStock),
+            ("Vitamin B".to_string(), InventoryAlertKind::ExpiringSoon),
+            ("Antibiotic".to_string(), InventoryAlertKind::Expired),
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn test_consume_item_reduces_stock() {
+    let pool = common::temp_pool("inventory_consume");
+    let app = test::init_service(
+        App::new().app_data(web::Data::new(pool)).service(
+            web::scope("/inventory")
+                .route("", web::get().to(get_items))
+                .route("", web::post().to(add_item))
+                .route("/alerts", web::get().to(get_alerts))
+                .route("/consumption", web::get().to(get_consumption))
+                .route("/{id}/consume", web::post().to(consume_item)),
+        ),
+    )
+    .await;
+
+    let new_item = json!({
+        "id": null,
+        "name": "Dewormer",
+        "category": "Medicine",
+        "quantity": 50.0,
+        "unit": "ml",
+        "unit_cost": 2.5,
+        "expiry_date": null,
+        "reorder_level": 20.0
+    });
+    let mut bad_item = new_item.clone();
+    bad_item["expiry_date"] = json!("31/12/2025");
+    let req = test::TestRequest::post()
+        .uri("/inventory")
+        .set_json(&bad_item)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+    let req = test::TestRequest::post()
+        .uri("/inventory")
+        .set_json(&new_item)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    let consumption = json!({
+        "id": null,
+        "item_id": 0,
+        "quantity": 35.0,
+        "purpose": "Treatment",
+        "goat_name": null,
+        "consumed_on": "2025-06-01",
+        "notes": "Herd deworming"
+    });
+    let req = test::TestRequest::post()
+        .uri("/inventory/1/consume")
+        .set_json(&consumption)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+    let mut misdated = consumption.clone();
+    misdated["quantity"] = json!(1.0);
+    misdated["consumed_on"] = json!("June 2nd");
+    let req = test::TestRequest::post()
+        .uri("/inventory/1/consume")
+        .set_json(&misdated)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+
+    // Consuming more than is left is rejected
+    let req = test::TestRequest::post()
+        .uri("/inventory/1/consume")
+        .set_json(&consumption)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+
+    let req = test::TestRequest::get().uri("/inventory").to_request();
+    let items: Vec<InventoryItem> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(items[0].quantity, 15.0);
+
+    let req = test::TestRequest::get()
+        .uri("/inventory/consumption?item_id=1")
+        .to_request();
+    let records: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(records.as_array().unwrap().len(), 1);
+
+    let req = test::TestRequest::get().uri("/inventory/alerts").to_request();
+    let alerts: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(alerts[0]["kind"], "LowStock");
+}
+
+#[actix_rt::test]
+async fn test_consumption_cost_is_allocated_to_goat() {
+    let pool = common::temp_pool("inventory_allocation");
+    pool.get_conn()
+        .unwrap()
+        .execute(
+            "INSERT INTO goats (breed, name, gender, cost, current_price) VALUES ('Sirohi', 'Rani', 'Female', 100.0, 150.0)",
+            [],
+        )
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(pool))
+            .route("/inventory", web::post().to(add_item))
+            .route("/inventory/{id}/consume", web::post().to(consume_item))
+            .route("/transactions", web::get().to(get_transactions))
+            .route("/finance/goats", web::get().to(get_goat_profitability)),
+    )
+    .await;
+
+    let feed = json!({
+        "id": null,
+        "name": "Concentrate",
+        "category": "Feed",
+        "quantity": 100.0,
+        "unit": "kg",
+        "unit_cost": 0.5,
+        "expiry_date": null,
+        "reorder_level": 0.0
+    });
+    let req = test::TestRequest::post()
+        .uri("/inventory")
+        .set_json(&feed)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    let consumption = json!({
+        "id": null,
+        "item_id": 1,
+        "quantity": 20.0,
+        "purpose": "Feeding",
+        "goat_name": "Rani",
+        "consumed_on": "2025-06-01",
+        "notes": null
+    });
+    let req = test::TestRequest::post()
+        .uri("/inventory/1/consume")
+        .set_json(&consumption)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    let req = test::TestRequest::get()
+        .uri("/transactions?goat_name=Rani")
+        .to_request();
+    let transactions: Vec<Transaction> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].amount, 10.0);
+    assert_eq!(transactions[0].category, FinanceCategory::Feed);
+    assert!(transactions[0].consumption_id.is_some());
+
+    let req = test::TestRequest::get().uri("/finance/goats").to_request();
+    let profitability: Vec<GoatProfitability> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(profitability[0].expenses, 10.0);
+    assert_eq!(profitability[0].profit, 40.0);
+}
+
+#[actix_rt::test]
+async fn test_dispose_of_expired_stock() {
+    let pool = common::temp_pool("inventory_dispose");
+    let app = test::init_service(
+        App::new().app_data(web::Data::new(pool)).service(
+            web::scope("/inventory")
+                .route("", web::get().to(get_items))
+                .route("", web::post().to(add_item))
+                .route("/expiring", web::get().to(get_expiring))
+                .route("/consumption", web::get().to(get_consumption))
+                .route("/{id}/dispose", web::post().to(dispose_item)),
+        )
+        .route("/transactions", web::get().to(get_transactions)),
+    )
+    .await;
+
+    // The farm keeps UTC, so its today is UTC's
+    let today = Utc::now().date_naive();
+    let expiry = |days: i64| {
+        let date = if days < 0 {
+            today - Days::new(days.unsigned_abs())
+        } else {
+            today + Days::new(days as u64)
+        };
+        date.format("%Y-%m-%d").to_string()
+    };
+    for (name, quantity, expiry_date) in [
+        ("Antibiotic", 40.0, Some(expiry(-10))),
+        ("Vitamin B", 100.0, Some(expiry(5))),
+        ("Mineral mix", 100.0, Some(expiry(100))),
+        ("Old vaccine", 0.0, Some(expiry(-60))),
+        ("Dewormer", 50.0, None),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/inventory")
+            .set_json(json!({
+                "id": null, "name": name, "category": "Medicine", "quantity": quantity,
+                "unit": "ml", "unit_cost": 1.5, "expiry_date": expiry_date, "reorder_level": 0.0
+            }))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 201);
+    }
+
+    let req = test::TestRequest::get().uri("/inventory/expiring").to_request();
+    let expiring: Vec<ExpiringStock> = test::call_and_read_body_json(&app, req).await;
+    let names: Vec<(&str, bool)> = expiring
+        .iter()
+        .map(|e| (e.item.name.as_str(), e.expired()))
+        .collect();
+    assert_eq!(names, vec![("Antibiotic", true), ("Vitamin B", false)]);
+    assert_eq!(expiring[0].days_left, -10);
+    assert_eq!(expiring[0].value, 60.0);
+
+    let antibiotic = expiring[0].item.id.unwrap();
+    let req = test::TestRequest::post()
+        .uri(&format!("/inventory/{}/dispose", antibiotic))
+        .to_request();
+    let disposal: Disposal = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(disposal.quantity, 40.0);
+    assert_eq!(disposal.value, 60.0);
+    assert!(disposal.transaction_id.is_some());
+
+    // Nothing is left to dispose of, and unknown items are rejected
+    for id in [antibiotic, 99] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/inventory/{}/dispose", id))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 400);
+    }
+
+    let req = test::TestRequest::get().uri("/inventory/expiring").to_request();
+    let expiring: Vec<ExpiringStock> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(expiring.len(), 1);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/inventory/consumption?item_id={}", antibiotic))
+        .to_request();
+    let records: Vec<ConsumptionRecord> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].quantity, 40.0);
+    assert_eq!(
+        records[0].notes.as_deref(),
+        Some(format!("Disposed of; expired on {}", expiry(-10)).as_str())
+    );
+
+    let req = test::TestRequest::get().uri("/transactions").to_request();
+    let transactions: Vec<Transaction> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].amount, 60.0);
+    assert_eq!(transactions[0].category, FinanceCategory::Medicine);
+    assert_eq!(transactions[0].description, "Disposed of 40 ml Antibiotic");
+    assert_eq!(transactions[0].consumption_id, Some(records[0].id.unwrap()));
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use actix_web::body::to_bytes;
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::errors::AppError;
+use backend::jobs::{JobQueue, JobRunner};
+use backend::messaging::{MessageGateway, OutgoingMessage};
+use backend::routes;
+use serde_json::json;
+use shared::jobs::{Job, JobKind, JobStatus};
+use shared::{Breed, Goat, GoatParams};
+use std::sync::{Arc, Mutex};
+
+/// Gateway that fails its first `failures` sends and keeps the rest.
+struct FlakyGateway {
+    failures: Mutex<usize>,
+    sent: Mutex<Vec<OutgoingMessage>>,
+}
+
+impl MessageGateway for FlakyGateway {
+    fn send(&self, message: &OutgoingMessage) -> Result<(), AppError> {
+        let mut failures = self.failures.lock().unwrap();
+        if *failures > 0 {
+            *failures -= 1;
+            return Err(AppError::Upstream("Relay unreachable".into()));
+        }
+        self.sent.lock().unwrap().push(message.clone());
+        Ok(())
+    }
+}
+
+#[actix_rt::test]
+async fn test_import_and_report_jobs() {
+    let db_pool = common::temp_pool("jobs");
+    let runner = JobRunner::new(JobQueue::new());
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .app_data(web::Data::new(JobQueue::new()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let goats: Vec<GoatParams> = (1..=120)
+        .map(|i| {
+            GoatParams::builder(format!("Goat {}", i))
+                .breed(Breed::Beetal)
+                .weight(30.0)
+                .build()
+                .unwrap()
+        })
+        .collect();
+    let req = TestRequest::post()
+        .uri("/jobs/import")
+        .set_json(&goats)
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 202);
+    let queued: Job = serde_json::from_slice(&to_bytes(resp.into_body()).await.unwrap()).unwrap();
+    assert_eq!(queued.kind, JobKind::Import);
+    assert_eq!(queued.status, JobStatus::Queued);
+    assert_eq!(queued.description, "Import 120 goats");
+
+    // A batch /goats/batch would refuse is refused before queueing
+    let req = TestRequest::post()
+        .uri("/jobs/import")
+        .set_json(&goats[..1])
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 202);
+    let req = TestRequest::post()
+        .uri("/jobs/import")
+        .set_json(json!([]))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let done = runner.run_next(&db_pool).unwrap().unwrap();
+    assert_eq!(done.id, queued.id);
+    assert_eq!(done.status, JobStatus::Succeeded);
+    assert_eq!((done.done, done.total), (120, 120));
+    assert_eq!(done.progress(), 1.0);
+    // The second import repeats a name that is now taken
+    let failed = runner.run_next(&db_pool).unwrap().unwrap();
+    assert_eq!(failed.status, JobStatus::Failed);
+    assert!(failed.error.is_some());
+    assert!(runner.run_next(&db_pool).unwrap().is_none());
+    let req = TestRequest::get().uri("/goats").to_request();
+    let herd: Vec<Goat> = call_and_read_body_json(&app, req).await;
+    assert_eq!(herd.len(), 120);
+
+    let req = TestRequest::post()
+        .uri("/jobs/census")
+        .set_json(json!({ "key": "nope" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::post()
+        .uri("/jobs/census")
+        .set_json(json!({ "key": "breed-headcount", "as_of": "2026-03-31", "format": "csv" }))
+        .to_request();
+    let report: Job = call_and_read_body_json(&app, req).await;
+    let req = TestRequest::get()
+        .uri(&format!("/jobs/{}/result", report.id))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    runner.run_next(&db_pool).unwrap();
+    let req = TestRequest::get()
+        .uri(&format!("/jobs/{}", report.id))
+        .to_request();
+    let report: Job = call_and_read_body_json(&app, req).await;
+    assert_eq!(report.status, JobStatus::Succeeded);
+    assert_eq!(
+        report.result_name.as_deref(),
+        Some("breed-headcount-2026-03-31.csv")
+    );
+    let req = TestRequest::get()
+        .uri(&format!("/jobs/{}/result", report.id))
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("content-type").unwrap(), "text/csv");
+    let csv = to_bytes(resp.into_body()).await.unwrap();
+    assert!(String::from_utf8_lossy(&csv).contains("120"));
+
+    let req = TestRequest::get().uri("/jobs").to_request();
+    let jobs: Vec<Job> = call_and_read_body_json(&app, req).await;
+    let kinds: Vec<JobKind> = jobs.iter().map(|j| j.kind).collect();
+    assert_eq!(kinds, [JobKind::Report, JobKind::Import, JobKind::Import]);
+}
+
+#[actix_rt::test]
+async fn test_notification_job_retries_undelivered_messages() {
+    let db_pool = common::temp_pool("jobs_notify");
+    let gateway = Arc::new(FlakyGateway {
+        failures: Mutex::new(1),
+        sent: Mutex::new(Vec::new()),
+    });
+    let runner = JobRunner::new(JobQueue::new()).with_gateway(gateway.clone());
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .app_data(web::Data::from(gateway.clone() as Arc<dyn MessageGateway>))
+            .app_data(web::Data::new(JobQueue::new()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    for (name, contact, notify_by) in [
+        ("Ravi", "+91 98765 43210", "Sms"),
+        ("Meena", "meena@farm.com", "Email"),
+    ] {
+        common::add_worker(&db_pool, name, None, Some(contact), notify_by);
+    }
+    let req = TestRequest::post()
+        .uri("/notes")
+        .set_json(json!({ "goat_name": "Rani", "author": "Asha", "text": "@Ravi @Meena vet at 4" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    // Sending waits for the job
+    assert!(gateway.sent.lock().unwrap().is_empty());
+
+    let job = runner.run_next(&db_pool).unwrap().unwrap();
+    assert_eq!(job.kind, JobKind::Notification);
+    assert_eq!(job.status, JobStatus::Queued);
+    assert_eq!(job.attempts, 1);
+    assert_eq!(
+        job.error.as_deref(),
+        Some("Upstream service error: Relay unreachable")
+    );
+    // The retry waits for its delay
+    assert!(runner.run_next(&db_pool).unwrap().is_none());
+
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute("UPDATE jobs SET run_after = CURRENT_TIMESTAMP", [])
+        .unwrap();
+    let job = runner.run_next(&db_pool).unwrap().unwrap();
+    assert_eq!(job.status, JobStatus::Succeeded);
+    assert_eq!(job.attempts, 2);
+    let sent = gateway.sent.lock().unwrap();
+    assert_eq!(sent.len(), 2);
+}
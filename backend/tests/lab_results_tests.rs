@@ -0,0 +1,214 @@
+mod common;
+
+use actix_web::test::{
+    TestRequest, call_and_read_body_json, call_service, init_service, read_body,
+};
+use actix_web::{App, web};
+use backend::routes;
+use chrono::{Days, Utc};
+use serde_json::json;
+use shared::health::{TimelineEntry, TimelineKind};
+use shared::lab::{LabResult, RangeFlag, check_report, reference_range};
+
+#[test]
+fn test_reference_ranges_and_reports() {
+    assert_eq!(reference_range(" pcv ", "%"), Some((22.0, 38.0)));
+    assert_eq!(reference_range("PCV", "L/L"), None);
+    assert!(check_report("application/pdf", 1024).is_ok());
+    assert!(check_report("image/png", 1024).is_err());
+    assert!(check_report("application/pdf", 0).is_err());
+}
+
+#[actix_rt::test]
+async fn test_lab_results_flagging_and_timeline() {
+    let db_pool = common::temp_pool("lab_results");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let mut rani = common::sample_goat("Rani");
+    rani["vaccinations"] = json!([
+        { "id": null, "name": "CDT", "given_on": "2025-03-01" },
+        { "id": null, "name": "Rabies" },
+    ]);
+    for goat in [rani, common::sample_goat("Moti")] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(&goat)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    let req = TestRequest::post()
+        .uri("/lab-results")
+        .set_json(json!({
+            "id": null, "goat_name": "Rani", "test_type": "CBC", "sampled_on": "2025-04-10",
+            "lab": "District vet lab", "notes": null,
+            "values": [
+                { "analyte": "PCV", "value": 15.0, "unit": "%" },
+                { "analyte": "Hemoglobin", "value": 9.5, "unit": "g/dL" },
+                { "analyte": "Eosinophils", "value": 9.0, "unit": "%", "low": 0.0, "high": 8.0 },
+                { "analyte": "Copper", "value": 0.9, "unit": "ppm" }
+            ]
+        }))
+        .to_request();
+    let cbc: LabResult = call_and_read_body_json(&app, req).await;
+    let cbc_id = cbc.id.unwrap();
+    // Known analytes get their typical range; reported ranges are kept
+    assert_eq!(
+        (cbc.values[0].low, cbc.values[0].high),
+        (Some(22.0), Some(38.0))
+    );
+    assert_eq!(cbc.values[0].flag(), Some(RangeFlag::Low));
+    assert_eq!(cbc.values[1].flag(), None);
+    assert_eq!(cbc.values[2].flag(), Some(RangeFlag::High));
+    assert_eq!(cbc.values[3].low, None);
+    assert_eq!(cbc.flagged().len(), 2);
+    assert!(!cbc.has_report);
+
+    let req = TestRequest::post()
+        .uri("/lab-results")
+        .set_json(json!({
+            "id": null, "goat_name": "Rani", "test_type": "Fecal egg count",
+            "sampled_on": "2025-02-01", "lab": null, "notes": "After deworming",
+            "values": [{ "analyte": "Fecal egg count", "value": 200.0, "unit": "EPG" }]
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let tomorrow = (Utc::now().date_naive() + Days::new(1))
+        .format("%Y-%m-%d")
+        .to_string();
+    for body in [
+        json!({ "id": null, "goat_name": "Nobody", "test_type": "CBC", "sampled_on": "2025-04-10",
+                "lab": null, "notes": null,
+                "values": [{ "analyte": "PCV", "value": 30.0, "unit": "%" }] }),
+        json!({ "id": null, "goat_name": "Moti", "test_type": "CBC", "sampled_on": "2025-04-10",
+                "lab": null, "notes": null, "values": [] }),
+        json!({ "id": null, "goat_name": "Moti", "test_type": "CBC", "sampled_on": tomorrow,
+                "lab": null, "notes": null,
+                "values": [{ "analyte": "PCV", "value": 30.0, "unit": "%" }] }),
+        json!({ "id": null, "goat_name": "Moti", "test_type": "CBC", "sampled_on": "2025-04-10",
+                "lab": null, "notes": null,
+                "values": [{ "analyte": "PCV", "value": 30.0, "unit": "%", "low": 40.0, "high": 20.0 }] }),
+    ] {
+        let req = TestRequest::post()
+            .uri("/lab-results")
+            .set_json(body)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400);
+    }
+
+    // The report must be a PDF, and can be read back
+    let upload = |content_type: &str, body: &'static [u8]| {
+        TestRequest::put()
+            .uri(&format!("/lab-results/{}/report", cbc_id))
+            .insert_header(("Content-Type", content_type))
+            .set_payload(body)
+            .to_request()
+    };
+    assert_eq!(
+        call_service(&app, upload("image/png", b"\x89PNG"))
+            .await
+            .status(),
+        400
+    );
+    let req = TestRequest::get()
+        .uri(&format!("/lab-results/{}/report", cbc_id))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    assert_eq!(
+        call_service(&app, upload("application/pdf", b"%PDF-1.4 report"))
+            .await
+            .status(),
+        200
+    );
+    let req = TestRequest::get()
+        .uri(&format!("/lab-results/{}/report", cbc_id))
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("Content-Type").unwrap(),
+        "application/pdf"
+    );
+    assert_eq!(read_body(resp).await.as_ref(), b"%PDF-1.4 report");
+
+    let req = TestRequest::get()
+        .uri("/lab-results?goat_name=Rani")
+        .to_request();
+    let results: Vec<LabResult> = call_and_read_body_json(&app, req).await;
+    let tests: Vec<&str> = results.iter().map(|r| r.test_type.as_str()).collect();
+    assert_eq!(tests, vec!["CBC", "Fecal egg count"]);
+    assert!(results[0].has_report);
+    let req = TestRequest::get()
+        .uri("/lab-results?goat_name=Moti")
+        .to_request();
+    let results: Vec<LabResult> = call_and_read_body_json(&app, req).await;
+    assert!(results.is_empty());
+
+    let req = TestRequest::post()
+        .uri("/health/incidents")
+        .set_json(json!({
+            "id": null, "goat_name": "Rani", "space_id": null, "kind": "Parasite",
+            "condition": "Barber's pole worm", "observed_on": "2025-04-10", "notes": "Pale eyelids"
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    // The timeline merges incidents, dated vaccinations and lab results
+    let req = TestRequest::get()
+        .uri("/health/timeline?goat_name=Rani")
+        .to_request();
+    let timeline: Vec<TimelineEntry> = call_and_read_body_json(&app, req).await;
+    let entries: Vec<(&str, TimelineKind, &str, bool)> = timeline
+        .iter()
+        .map(|e| (e.date.as_str(), e.kind, e.title.as_str(), e.flagged))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            (
+                "2025-04-10",
+                TimelineKind::Incident,
+                "Parasite: Barber's pole worm",
+                false
+            ),
+            (
+                "2025-04-10",
+                TimelineKind::LabResult,
+                "Lab result: CBC",
+                true
+            ),
+            (
+                "2025-03-01",
+                TimelineKind::Vaccination,
+                "Vaccinated: CDT",
+                false
+            ),
+            (
+                "2025-02-01",
+                TimelineKind::LabResult,
+                "Lab result: Fecal egg count",
+                false
+            ),
+        ]
+    );
+    assert_eq!(
+        timeline[1].details.as_deref(),
+        Some("PCV 15 % (low, range 22–38); Eosinophils 9 % (high, range 0–8)")
+    );
+    assert_eq!(timeline[1].lab_result_id, Some(cbc_id));
+    assert_eq!(
+        timeline[3].details.as_deref(),
+        Some("All values within range")
+    );
+
+    let req = TestRequest::get()
+        .uri("/health/timeline?goat_name=Nobody")
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use serde_json::json;
+use shared::Gender;
+use shared::lifecycle::{
+    LifecyclePipeline, LifecycleStage, StageTransition, allowed_stages, check_transition,
+};
+
+#[test]
+fn test_lifecycle_transitions() {
+    use LifecycleStage::*;
+    // Untracked goats may enter at any live stage
+    assert_eq!(
+        allowed_stages(None, &Gender::Female),
+        vec![Kid, Grower, Breeder, Milker, ForSale]
+    );
+    assert_eq!(
+        allowed_stages(Some(Grower), &Gender::Male),
+        vec![Breeder, ForSale, Deceased]
+    );
+    assert!(check_transition(Some(Kid), Grower, &Gender::Male).is_ok());
+    assert!(check_transition(Some(Kid), Breeder, &Gender::Female).is_err());
+    assert!(check_transition(Some(Grower), Milker, &Gender::Male).is_err());
+    assert!(check_transition(Some(ForSale), Sold, &Gender::Male).is_ok());
+    assert!(check_transition(None, Sold, &Gender::Male).is_err());
+    for end in [Sold, Deceased] {
+        assert!(end.next_stages().is_empty());
+        assert!(allowed_stages(Some(end), &Gender::Female).is_empty());
+    }
+}
+
+#[actix_rt::test]
+async fn test_lifecycle_moves_are_enforced_and_recorded() {
+    let db_pool = common::temp_pool("lifecycle");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for (name, gender) in [("Rani", "Female"), ("Raja", "Male"), ("Mini", "Female")] {
+        let mut goat = common::sample_goat(name);
+        goat["gender"] = json!(gender);
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(goat)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    let change = |name: &str, stage: &str, on: Option<&str>| {
+        TestRequest::put()
+            .uri("/lifecycle")
+            .set_json(json!({ "goat_name": name, "stage": stage, "changed_on": on }))
+            .to_request()
+    };
+    let first: StageTransition =
+        call_and_read_body_json(&app, change("Rani", "Kid", Some("2026-01-05"))).await;
+    assert_eq!(first.from, None);
+    assert_eq!(first.to, LifecycleStage::Kid);
+    for (stage, on) in [("Grower", "2026-04-01"), ("Milker", "2027-02-10")] {
+        let req = change("Rani", stage, Some(on));
+        assert!(call_service(&app, req).await.status().is_success());
+    }
+    let req = change("Raja", "Grower", None);
+    assert!(call_service(&app, req).await.status().is_success());
+
+    // Skipping a stage, milking a buck, bad dates and unknown goats are refused
+    for req in [
+        change("Rani", "Kid", None),
+        change("Raja", "Milker", None),
+        change("Raja", "Sold", None),
+        change("Mini", "Grower", Some("April")),
+        change("Nobody", "Kid", None),
+    ] {
+        assert_eq!(call_service(&app, req).await.status(), 400);
+    }
+
+    let req = TestRequest::put()
+        .uri("/lifecycle")
+        .set_json(json!({
+            "goat_name": "Rani", "stage": "ForSale", "changed_on": "2028-05-01",
+            "note": "  Listed at the market  "
+        }))
+        .to_request();
+    let listed: StageTransition = call_and_read_body_json(&app, req).await;
+    assert_eq!(listed.from, Some(LifecycleStage::Milker));
+    assert_eq!(listed.note.as_deref(), Some("Listed at the market"));
+    let req = change("Rani", "Sold", Some("2028-05-20"));
+    assert!(call_service(&app, req).await.status().is_success());
+    let req = change("Rani", "Milker", None);
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let req = TestRequest::get().uri("/lifecycle").to_request();
+    let pipeline: LifecyclePipeline = call_and_read_body_json(&app, req).await;
+    assert_eq!(pipeline.stages.len(), LifecycleStage::ALL.len());
+    let at = |stage: LifecycleStage| {
+        pipeline
+            .stages
+            .iter()
+            .find(|s| s.stage == stage)
+            .unwrap()
+            .goats
+            .iter()
+            .map(|g| g.name.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(at(LifecycleStage::Sold), vec!["Rani"]);
+    assert_eq!(at(LifecycleStage::Grower), vec!["Raja"]);
+    assert!(at(LifecycleStage::Kid).is_empty());
+    assert_eq!(pipeline.untracked.len(), 1);
+    assert_eq!(pipeline.untracked[0].name, "Mini");
+    let raja = &pipeline.stages[1].goats[0];
+    assert!(!raja.next.contains(&LifecycleStage::Milker));
+
+    let req = TestRequest::get()
+        .uri("/lifecycle/Rani/history")
+        .to_request();
+    let history: Vec<StageTransition> = call_and_read_body_json(&app, req).await;
+    let path: Vec<_> = history.iter().map(|t| t.to).collect();
+    assert_eq!(
+        path,
+        vec![
+            LifecycleStage::Kid,
+            LifecycleStage::Grower,
+            LifecycleStage::Milker,
+            LifecycleStage::ForSale,
+            LifecycleStage::Sold
+        ]
+    );
+    assert_eq!(history[1].from, Some(LifecycleStage::Kid));
+    assert_eq!(history[4].changed_on, "2028-05-20");
+    let req = TestRequest::get()
+        .uri("/lifecycle/Nobody/history")
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::lactation::build_lactations;
+use backend::routes;
+use chrono::NaiveDate;
+use serde_json::{Value, json};
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn test_build_lactations() {
+    let kiddings = [date("2025-01-01"), date("2025-12-01")];
+    let daily = [
+        // Before any known kidding
+        (date("2024-12-20"), 0.5),
+        // First lactation: week 1, week 2 (peak), week 3
+        (date("2025-01-02"), 1.0),
+        (date("2025-01-05"), 2.0),
+        (date("2025-01-10"), 3.0),
+        (date("2025-01-20"), 2.0),
+        // Second lactation
+        (date("2025-12-03"), 1.5),
+    ];
+
+    let lactations = build_lactations("Rani", &kiddings, &daily);
+    assert_eq!(lactations.len(), 3);
+
+    let unknown = &lactations[0];
+    assert_eq!(unknown.kidded_on, None);
+    assert_eq!(unknown.days_in_milk, 0);
+
+    let first = &lactations[1];
+    assert_eq!(first.kidded_on.as_deref(), Some("2025-01-01"));
+    assert_eq!(first.days_in_milk, 19);
+    assert_eq!(first.total_yield, 8.0);
+    assert_eq!(first.peak_daily_yield, 3.0);
+    assert_eq!(first.peak_day, 9);
+    assert_eq!(first.average_daily_yield, 2.0);
+    let curve: Vec<(u32, f64)> = first
+        .curve
+        .iter()
+        .map(|p| (p.week, p.average_daily_yield))
+        .collect();
+    assert_eq!(curve, vec![(1, 1.5), (2, 3.0), (3, 2.0)]);
+
+    assert_eq!(lactations[2].kidded_on.as_deref(), Some("2025-12-01"));
+    assert_eq!(lactations[2].days_in_milk, 2);
+}
+
+#[actix_rt::test]
+async fn test_milk_records_and_lactations() {
+    let db_pool = common::temp_pool("milk");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let mut buck = common::sample_goat("Raja");
+    buck["gender"] = json!("Male");
+    for goat in [common::sample_goat("Rani"), buck] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(&goat)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    let req = TestRequest::post()
+        .uri("/breeding/kiddings")
+        .set_json(json!({
+            "id": null, "doe_name": "Rani", "buck_name": "Raja", "kidded_on": "2025-03-01",
+            "kids_born": 2, "kids_alive": 2, "notes": null
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    // Morning and evening milkings are summed per day
+    for (day, litres) in [
+        ("2025-03-02", 0.8),
+        ("2025-03-02", 0.7),
+        ("2025-03-15", 2.0),
+    ] {
+        let req = TestRequest::post()
+            .uri("/milk/records")
+            .set_json(json!({ "id": null, "doe_name": "Rani", "recorded_on": day, "yield_litres": litres }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    // Bucks cannot be milked
+    let req = TestRequest::post()
+        .uri("/milk/records")
+        .set_json(json!({ "id": null, "doe_name": "Raja", "recorded_on": "2025-03-02", "yield_litres": 1.0 }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let req = TestRequest::get()
+        .uri("/milk/records?doe_name=Rani")
+        .to_request();
+    let records: Vec<Value> = call_and_read_body_json(&app, req).await;
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0]["recorded_on"], "2025-03-15");
+
+    let req = TestRequest::get().uri("/milk/lactations").to_request();
+    let lactations: Vec<Value> = call_and_read_body_json(&app, req).await;
+    assert_eq!(lactations.len(), 1);
+    assert_eq!(lactations[0]["doe_name"], "Rani");
+    assert_eq!(lactations[0]["kidded_on"], "2025-03-01");
+    assert_eq!(lactations[0]["days_in_milk"], 14);
+    assert_eq!(lactations[0]["peak_daily_yield"], 2.0);
+    assert_eq!(lactations[0]["total_yield"], 3.5);
+}
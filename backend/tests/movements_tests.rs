@@ -0,0 +1,154 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use serde_json::json;
+use shared::movements::{AnimalMovement, PlaceKind};
+use shared::traceability::{MovementKind, TraceabilityRecord};
+
+#[actix_rt::test]
+async fn test_movement_register_by_goat_and_period() {
+    let db_pool = common::temp_pool("movements");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for name in ["Rani", "Moti", "Kali"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "UPDATE goats SET tag_id = 'IN-0042' WHERE name = 'Moti'",
+            [],
+        )
+        .unwrap();
+
+    let movement = |goats: &[&str], to: &str, departed_on: &str| {
+        json!({
+            "id": null, "goats": goats,
+            "from": { "kind": "Farm", "name": "Green Hills" },
+            "to": { "kind": if to == "Pune market" { "Market" } else { "Farm" }, "name": to },
+            "departed_on": departed_on, "arrived_on": departed_on,
+            "transporter": "Patil Transport", "vehicle": "MH12 AB 1234",
+            "permit_number": "  "
+        })
+    };
+    let req = TestRequest::post()
+        .uri("/movements")
+        .set_json(movement(
+            &["Rani", "Moti", "Rani"],
+            "Pune market",
+            "2026-02-10",
+        ))
+        .to_request();
+    let stored: AnimalMovement = call_and_read_body_json(&app, req).await;
+    assert_eq!(stored.goats, vec!["Moti".to_string(), "Rani".to_string()]);
+    assert_eq!(stored.goats_label(), "Moti (IN-0042), Rani");
+    assert_eq!(stored.to.kind, PlaceKind::Market);
+    assert_eq!(stored.permit_number, None);
+    assert_eq!(
+        stored.summary(),
+        "Green Hills (Farm) → Pune market (Market) by Patil Transport, MH12 AB 1234"
+    );
+    let req = TestRequest::post()
+        .uri("/movements")
+        .set_json(movement(&["Kali"], "Lake Farm", "2026-03-05"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let mut arrives_early = movement(&["Kali"], "Lake Farm", "2026-03-05");
+    arrives_early["arrived_on"] = json!("2026-03-04");
+    let mut nowhere = movement(&["Kali"], "Green Hills", "2026-03-05");
+    nowhere["to"]["name"] = json!("green hills");
+    for invalid in [
+        arrives_early,
+        nowhere,
+        movement(&[], "Lake Farm", "2026-03-05"),
+        movement(&["Nobody"], "Lake Farm", "2026-03-05"),
+        movement(&["Kali"], "Lake Farm", "05/03/2026"),
+    ] {
+        let req = TestRequest::post()
+            .uri("/movements")
+            .set_json(invalid)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400);
+    }
+
+    let departures = |movements: &[AnimalMovement]| {
+        movements
+            .iter()
+            .map(|m| m.departed_on.clone())
+            .collect::<Vec<_>>()
+    };
+    let req = TestRequest::get().uri("/movements").to_request();
+    let all: Vec<AnimalMovement> = call_and_read_body_json(&app, req).await;
+    assert_eq!(departures(&all), vec!["2026-03-05", "2026-02-10"]);
+    let req = TestRequest::get()
+        .uri("/movements?goat_name=Rani")
+        .to_request();
+    let rani: Vec<AnimalMovement> = call_and_read_body_json(&app, req).await;
+    assert_eq!(departures(&rani), vec!["2026-02-10"]);
+    assert_eq!(rani[0].goats.len(), 2);
+    let req = TestRequest::get()
+        .uri("/movements?from=2026-03-01&to=2026-03-31")
+        .to_request();
+    let march: Vec<AnimalMovement> = call_and_read_body_json(&app, req).await;
+    assert_eq!(departures(&march), vec!["2026-03-05"]);
+    for invalid in [
+        "/movements?goat_name=Nobody",
+        "/movements?from=2026-04-01&to=2026-03-01",
+        "/movements?from=March",
+    ] {
+        let req = TestRequest::get().uri(invalid).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400);
+    }
+
+    // Movements before a sale are part of the goat's traceability record
+    let req = TestRequest::post()
+        .uri("/transactions")
+        .set_json(json!({
+            "kind": "Income", "category": "Sale", "amount": 150.0, "description": "Rani",
+            "goat_name": "Rani", "date": "2026-02-10"
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::get()
+        .uri("/traceability?goat_name=Rani")
+        .to_request();
+    let records: Vec<TraceabilityRecord> = call_and_read_body_json(&app, req).await;
+    let transported: Vec<&str> = records[0]
+        .movements
+        .iter()
+        .filter(|m| m.kind == MovementKind::Transported)
+        .map(|m| m.description.as_str())
+        .collect();
+    assert_eq!(
+        transported,
+        vec!["Green Hills (Farm) → Pune market (Market) by Patil Transport, MH12 AB 1234"]
+    );
+
+    // The register outlives the goats in it, as they were when moved
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute_batch(
+            "UPDATE goats SET tag_id = 'IN-0099' WHERE name = 'Moti';
+             DELETE FROM goats WHERE name = 'Moti';",
+        )
+        .unwrap();
+    let req = TestRequest::get()
+        .uri("/movements?from=2026-02-01&to=2026-02-28")
+        .to_request();
+    let february: Vec<AnimalMovement> = call_and_read_body_json(&app, req).await;
+    assert_eq!(february[0].goats_label(), "Moti (IN-0042), Rani");
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use actix_web::test::{
+    TestRequest, call_and_read_body_json, call_service, init_service, read_body,
+};
+use actix_web::{App, web};
+use backend::routes;
+use serde_json::json;
+use shared::notes::{GoatNote, MAX_NOTE_CHARS, NoteInput};
+
+#[test]
+fn test_note_validation() {
+    let note = NoteInput {
+        goat_name: "Rani".to_string(),
+        author: "Asha".to_string(),
+        text: "Limping on left hind leg".to_string(),
+    };
+    assert!(note.validate().is_ok());
+    for broken in [
+        NoteInput {
+            author: "  ".to_string(),
+            ..note.clone()
+        },
+        NoteInput {
+            text: "\n".to_string(),
+            ..note.clone()
+        },
+        NoteInput {
+            text: "a".repeat(MAX_NOTE_CHARS + 1),
+            ..note.clone()
+        },
+    ] {
+        assert!(broken.validate().is_err(), "{:?}", broken);
+    }
+}
+
+#[actix_rt::test]
+async fn test_goat_notes_thread() {
+    let db_pool = common::temp_pool("notes");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for name in ["Rani", "Moti"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    let note = |goat: &str, author: &str, text: &str| {
+        TestRequest::post()
+            .uri("/notes")
+            .set_json(json!({ "goat_name": goat, "author": author, "text": text }))
+            .to_request()
+    };
+    assert_eq!(
+        call_service(&app, note("Nobody", "Asha", "Hello"))
+            .await
+            .status(),
+        400
+    );
+    assert_eq!(
+        call_service(&app, note("Rani", "", "Hello")).await.status(),
+        400
+    );
+    let first: GoatNote =
+        call_and_read_body_json(&app, note("Rani", " Asha ", "Limping on left hind leg")).await;
+    assert_eq!(first.author, "Asha");
+    assert!(!first.has_photo);
+    let second: GoatNote =
+        call_and_read_body_json(&app, note("Rani", "Ravi", "Walking fine after trimming")).await;
+    let req = note("Moti", "Ravi", "Ate well");
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    // Photos must be images
+    let req = TestRequest::put()
+        .uri(&format!("/notes/{}/photo", first.id))
+        .insert_header(("Content-Type", "text/plain"))
+        .set_payload("not a photo")
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::get()
+        .uri(&format!("/notes/{}/photo", first.id))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::put()
+        .uri(&format!("/notes/{}/photo", first.id))
+        .insert_header(("Content-Type", "image/jpeg"))
+        .set_payload(vec![0xFF, 0xD8, 0xFF, 0xE0])
+        .to_request();
+    let with_photo: GoatNote = call_and_read_body_json(&app, req).await;
+    assert!(with_photo.has_photo);
+
+    let req = TestRequest::get()
+        .uri(&format!("/notes/{}/photo", first.id))
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("content-type").unwrap(), "image/jpeg");
+    assert_eq!(read_body(resp).await.to_vec(), vec![0xFF, 0xD8, 0xFF, 0xE0]);
+
+    // The thread is the goat's own notes, oldest first
+    let req = TestRequest::get().uri("/notes?goat_name=Rani").to_request();
+    let thread: Vec<GoatNote> = call_and_read_body_json(&app, req).await;
+    let ids: Vec<i64> = thread.iter().map(|n| n.id).collect();
+    assert_eq!(ids, vec![first.id, second.id]);
+    assert!(thread[0].has_photo);
+    assert!(thread[0].created_at <= thread[1].created_at);
+
+    let req = TestRequest::delete()
+        .uri(&format!("/notes/{}", first.id))
+        .to_request();
+    assert!(call_service(&app, req).await.status().is_success());
+    let req = TestRequest::delete()
+        .uri(&format!("/notes/{}", first.id))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::get().uri("/notes?goat_name=Rani").to_request();
+    let thread: Vec<GoatNote> = call_and_read_body_json(&app, req).await;
+    assert_eq!(thread.len(), 1);
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::errors::AppError;
+use backend::messaging::{HttpMessageGateway, MessageGateway, OutgoingMessage};
+use backend::routes;
+use serde_json::json;
+use shared::notes::GoatNote;
+use shared::notifications::{Notification, NotifyChannel, Worker, mentioned};
+use std::sync::{Arc, Mutex};
+
+/// Gateway keeping the messages it is asked to send.
+#[derive(Default)]
+struct RecordingGateway {
+    sent: Mutex<Vec<OutgoingMessage>>,
+}
+
+impl MessageGateway for RecordingGateway {
+    fn send(&self, message: &OutgoingMessage) -> Result<(), AppError> {
+        self.sent.lock().unwrap().push(message.clone());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_mentions_in_note_text() {
+    let names = vec![
+        "Asha".to_string(),
+        "Asha Devi".to_string(),
+        "Ravi".to_string(),
+    ];
+    assert_eq!(
+        mentioned(
+            "@ravi please check, and @Asha Devi too. Thanks @Ravi!",
+            &names
+        ),
+        vec!["Ravi", "Asha Devi"]
+    );
+    assert_eq!(mentioned("Ask @Asha.", &names), vec!["Asha"]);
+    // Longer words and email addresses are no mentions
+    assert!(mentioned("@Ashastra wrote to ravi@farm.com", &names).is_empty());
+    assert!(mentioned("Nobody here @", &names).is_empty());
+
+    let worker = Worker {
+        id: None,
+        name: "Ravi".to_string(),
+        role: None,
+        contact: Some("ravi@farm.com".to_string()),
+        notify_by: NotifyChannel::Sms,
+    };
+    assert!(worker.validate().is_err());
+    assert!(
+        Worker {
+            notify_by: NotifyChannel::Email,
+            ..worker.clone()
+        }
+        .validate()
+        .is_ok()
+    );
+    assert!(
+        Worker {
+            name: "@Ravi".to_string(),
+            notify_by: NotifyChannel::App,
+            ..worker
+        }
+        .validate()
+        .is_err()
+    );
+    assert!(HttpMessageGateway::new("https://relay.example.com/send").is_ok());
+    assert!(HttpMessageGateway::new("ftp://relay.example.com/send").is_err());
+}
+
+#[actix_rt::test]
+async fn test_mentions_notify_workers() {
+    let db_pool = common::temp_pool("notifications");
+    let gateway = Arc::new(RecordingGateway::default());
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .app_data(web::Data::from(gateway.clone() as Arc<dyn MessageGateway>))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    for (name, contact, notify_by) in [
+        ("Asha", None, "App"),
+        ("Ravi", Some("+91 98765 43210"), "Sms"),
+        ("Meena", Some("meena@farm.com"), "Email"),
+    ] {
+        common::add_worker(&db_pool, name, None, contact, notify_by);
+    }
+    // Mentions go by name, so names must be unique
+    let req = TestRequest::post()
+        .uri("/workers")
+        .set_json(json!({
+            "id": null, "name": "asha", "role": null, "contact": null, "notify_by": "App"
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::get().uri("/workers").to_request();
+    let workers: Vec<Worker> = call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = workers.iter().map(|w| w.name.as_str()).collect();
+    assert_eq!(names, vec!["Asha", "Meena", "Ravi"]);
+
+    let req = TestRequest::post()
+        .uri("/notes")
+        .set_json(json!({
+            "goat_name": "Rani",
+            "author": "Asha",
+            "text": "Limping on left hind leg, @Ravi can you look? cc @asha @Nobody"
+        }))
+        .to_request();
+    let note: GoatNote = call_and_read_body_json(&app, req).await;
+
+    // Ravi is notified in the app and by SMS; the author is not notified
+    let req = TestRequest::get()
+        .uri("/notifications?worker=Ravi")
+        .to_request();
+    let inbox: Vec<Notification> = call_and_read_body_json(&app, req).await;
+    assert_eq!(inbox.len(), 1);
+    assert_eq!(inbox[0].goat_name.as_deref(), Some("Rani"));
+    assert_eq!(inbox[0].note_id, Some(note.id));
+    assert!(
+        inbox[0]
+            .message
+            .starts_with("Asha mentioned you on Rani: Limping")
+    );
+    assert!(inbox[0].link.starts_with("#goat-"));
+    assert!(!inbox[0].read);
+    let req = TestRequest::get()
+        .uri("/notifications?worker=Asha")
+        .to_request();
+    let inbox_asha: Vec<Notification> = call_and_read_body_json(&app, req).await;
+    assert!(inbox_asha.is_empty());
+    {
+        let sent = gateway.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].channel, NotifyChannel::Sms);
+        assert_eq!(sent[0].to, "+91 98765 43210");
+        assert_eq!(sent[0].link, inbox[0].link);
+    }
+
+    // Switching Ravi to in-app only stops the SMS
+    let ravi = workers.iter().find(|w| w.name == "Ravi").unwrap();
+    let req = TestRequest::put()
+        .uri(&format!("/workers/{}", ravi.id.unwrap()))
+        .set_json(json!({
+            "id": ravi.id, "name": "Ravi", "role": null, "contact": ravi.contact,
+            "notify_by": "App"
+        }))
+        .to_request();
+    assert!(call_service(&app, req).await.status().is_success());
+    let req = TestRequest::post()
+        .uri("/notes")
+        .set_json(json!({
+            "goat_name": "Rani", "author": "Meena", "text": "@Ravi hoof trimmed"
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    assert_eq!(gateway.sent.lock().unwrap().len(), 1);
+
+    let req = TestRequest::put()
+        .uri(&format!("/notifications/{}/read", inbox[0].id))
+        .to_request();
+    assert!(call_service(&app, req).await.status().is_success());
+    let req = TestRequest::get()
+        .uri("/notifications?worker=Ravi&unread=true")
+        .to_request();
+    let unread: Vec<Notification> = call_and_read_body_json(&app, req).await;
+    assert_eq!(unread.len(), 1);
+    assert!(unread[0].message.starts_with("Meena mentioned you"));
+    let req = TestRequest::get()
+        .uri("/notifications?worker=Nobody")
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+}
@@ -0,0 +1,105 @@
+use shared::lifecycle::LifecycleStage;
+use shared::nutrition::{
+    Nutrient, NutritionStage, check_nutrition, daily_requirement, find_feed, ration_supply,
+};
+use shared::{Breed, GoatParams};
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 0.01
+}
+
+#[test]
+fn test_requirements_grow_with_weight_and_stage() {
+    let upkeep = daily_requirement(30.0, NutritionStage::Maintenance, &Breed::Beetal);
+    assert!(close(upkeep.dry_matter_kg, 0.75));
+    assert!(close(upkeep.energy_mj, 0.45 * 30f64.powf(0.75)));
+    assert!(close(upkeep.crude_protein_g, 5.0 * 30f64.powf(0.75)));
+    let heavier = daily_requirement(40.0, NutritionStage::Maintenance, &Breed::Beetal);
+    assert!(heavier.energy_mj > upkeep.energy_mj);
+
+    for stage in [
+        NutritionStage::Growing,
+        NutritionStage::Pregnant,
+        NutritionStage::Lactating,
+    ] {
+        let need = daily_requirement(30.0, stage, &Breed::Beetal);
+        assert!(need.energy_mj > upkeep.energy_mj, "{:?}", stage);
+        assert!(need.crude_protein_g > upkeep.crude_protein_g, "{:?}", stage);
+    }
+
+    // Breed sets the milk and gain expected
+    let jamunapari = daily_requirement(30.0, NutritionStage::Lactating, &Breed::Jamunapari);
+    let bengal = daily_requirement(30.0, NutritionStage::Lactating, &Breed::BlackBengal);
+    assert!(close(jamunapari.energy_mj - bengal.energy_mj, 5.0 * 1.7));
+    let meat = daily_requirement(30.0, NutritionStage::Growing, &Breed::Osmanabadi);
+    let dual = daily_requirement(30.0, NutritionStage::Growing, &Breed::Beetal);
+    assert!(meat.crude_protein_g > dual.crude_protein_g);
+
+    assert_eq!(
+        NutritionStage::for_lifecycle(Some(LifecycleStage::Milker)),
+        NutritionStage::Lactating
+    );
+    assert_eq!(
+        NutritionStage::for_lifecycle(Some(LifecycleStage::Kid)),
+        NutritionStage::Growing
+    );
+    assert_eq!(
+        NutritionStage::for_lifecycle(None),
+        NutritionStage::Maintenance
+    );
+    assert_eq!(
+        NutritionStage::from_str("Pregnant"),
+        Ok(NutritionStage::Pregnant)
+    );
+}
+
+#[test]
+fn test_feed_plans_are_read_from_the_diet() {
+    assert_eq!(find_feed("Lucerne hay").unwrap().name, "Lucerne");
+    assert_eq!(find_feed("wheat straw").unwrap().name, "Straw");
+    assert!(find_feed("chocolate").is_none());
+
+    let supply = ration_supply("hay 1 kg, 500g concentrate + green fodder 2.5").unwrap();
+    assert!(close(supply.dry_matter_kg, 0.88 + 0.45 + 0.55));
+    assert!(close(supply.crude_protein_g, 70.4 + 72.0 + 55.0));
+    assert!(close(supply.energy_mj, 7.04 + 5.175 + 4.95));
+
+    let err = ration_supply("hay, chocolate 1 kg").unwrap_err();
+    assert!(err.contains("'hay' has no amount"), "{}", err);
+    assert!(err.contains("'chocolate' is not a known feed"), "{}", err);
+    assert!(ration_supply("  ").is_err());
+}
+
+#[test]
+fn test_nutrition_check_flags_deficits() {
+    let doe = |diet: &str| {
+        GoatParams::builder("Rani")
+            .breed(Breed::Jamunapari)
+            .weight(40.0)
+            .diet(diet)
+            .build()
+            .unwrap()
+    };
+
+    let dry = check_nutrition(&doe("hay 1.5 kg"), NutritionStage::Maintenance);
+    assert!(dry.deficits().is_empty(), "{:?}", dry.deficits());
+    let milking = check_nutrition(&doe("hay 1.5 kg"), NutritionStage::Lactating);
+    let short: Vec<Nutrient> = milking.deficits().into_iter().map(|(n, _)| n).collect();
+    assert_eq!(short, Nutrient::ALL.to_vec());
+    let (_, energy_gap) = milking.deficits()[2];
+    assert!(close(
+        energy_gap,
+        milking.requirement.energy_mj - 1.5 * 0.88 * 8.0
+    ));
+
+    let fed = check_nutrition(
+        &doe("lucerne 1.2 kg, concentrate 0.8 kg, green fodder 2 kg"),
+        NutritionStage::Lactating,
+    );
+    assert!(fed.deficits().is_empty(), "{:?}", fed.deficits());
+
+    // A diet without amounts cannot be checked
+    let unread = check_nutrition(&doe("hay"), NutritionStage::Lactating);
+    assert!(unread.supply.is_err());
+    assert!(unread.deficits().is_empty());
+}
@@ -0,0 +1,228 @@
+mod common;
+
+use actix_web::http::Method;
+use actix_web::middleware::from_fn;
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::auth::{check_session, hash_key};
+use backend::permissions::{action_for_method, enforce_permissions, module_for_path};
+use backend::routes;
+use serde_json::json;
+use shared::permissions::{MyPermissions, PermissionAction, PermissionModule, RolePermissions};
+use shared::sessions::SESSION_HEADER;
+
+#[test]
+fn test_requests_map_to_modules_and_actions() {
+    assert_eq!(module_for_path("/goats/3"), Some(PermissionModule::Goats));
+    assert_eq!(module_for_path("/milk"), Some(PermissionModule::Production));
+    assert_eq!(
+        module_for_path("/jobs/7/result"),
+        Some(PermissionModule::Settings)
+    );
+    assert_eq!(module_for_path("/goatsx"), None);
+    assert_eq!(module_for_path("/stats"), None);
+    assert_eq!(module_for_path("/permissions/mine"), None);
+    assert_eq!(action_for_method(&Method::HEAD), PermissionAction::View);
+    assert_eq!(action_for_method(&Method::PATCH), PermissionAction::Edit);
+    assert_eq!(action_for_method(&Method::DELETE), PermissionAction::Delete);
+}
+
+#[actix_rt::test]
+async fn test_worker_sessions_are_limited_to_their_role() {
+    let db_pool = common::temp_pool("permissions");
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(enforce_permissions))
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let owner = common::owner_token(&db_pool);
+    let req = TestRequest::post()
+        .uri("/workers")
+        .set_json(json!({
+            "id": null, "name": "Ravi", "role": "Milker",
+            "contact": null, "notify_by": "App"
+        }))
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    // As if Ravi had signed in with his PIN
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "INSERT INTO sessions (device, token_hash, worker_id) VALUES ('Phone', ?1, 1)",
+            [hash_key("yagi_ravi")],
+        )
+        .unwrap();
+
+    let req = TestRequest::get()
+        .uri("/permissions")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    let matrix: Vec<RolePermissions> = call_and_read_body_json(&app, req).await;
+    assert_eq!(matrix, vec![RolePermissions::unrestricted("Milker")]);
+
+    let put = |granted: serde_json::Value| {
+        TestRequest::put()
+            .uri("/permissions")
+            .set_json(json!({ "role": "Milker", "granted": granted }))
+    };
+    let req = put(json!([{ "module": "Finance", "action": "Edit" }]))
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let granted = json!([
+        { "module": "Production", "action": "Edit" },
+        { "module": "Goats", "action": "View" },
+        { "module": "Production", "action": "View" },
+    ]);
+    let req = put(granted.clone())
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    let stored: RolePermissions = call_and_read_body_json(&app, req).await;
+    assert!(stored.allows(PermissionModule::Production, PermissionAction::Edit));
+    assert!(!stored.allows(PermissionModule::Goats, PermissionAction::Delete));
+    assert_eq!(stored.granted.len(), 3);
+
+    let as_ravi = |method: Method, uri: &str| {
+        TestRequest::default()
+            .method(method)
+            .uri(uri)
+            .insert_header((SESSION_HEADER, "yagi_ravi"))
+            .to_request()
+    };
+    let resp = call_service(&app, as_ravi(Method::GET, "/goats")).await;
+    assert_eq!(resp.status(), 200);
+    let resp = call_service(&app, as_ravi(Method::GET, "/stats")).await;
+    assert_eq!(resp.status(), 200);
+    let resp = call_service(&app, as_ravi(Method::DELETE, "/goats/1")).await;
+    assert_eq!(resp.status(), 403);
+    let resp = call_service(&app, as_ravi(Method::GET, "/transactions")).await;
+    assert_eq!(resp.status(), 403);
+    // The owner is not limited, and nobody else may do anything
+    let req = TestRequest::get().uri("/stats").to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+    let req = TestRequest::get()
+        .uri("/transactions")
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    let mine: MyPermissions =
+        call_and_read_body_json(&app, as_ravi(Method::GET, "/permissions/mine")).await;
+    assert_eq!(mine.worker.as_deref(), Some("Ravi"));
+    assert_eq!(mine.role.as_deref(), Some("Milker"));
+    assert!(!mine.is_owner());
+    assert_eq!(mine.granted, stored.granted);
+
+    // Only the owner edits the matrix
+    let req = put(granted)
+        .insert_header((SESSION_HEADER, "yagi_ravi"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 403);
+
+    // Removing Ravi signs his devices out rather than leaving them the owner's
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute("DELETE FROM workers WHERE id = 1", [])
+        .unwrap();
+    let resp = call_service(&app, as_ravi(Method::GET, "/goats")).await;
+    assert_eq!(resp.status(), 401);
+}
+
+#[actix_rt::test]
+async fn test_only_the_owner_sets_roles_and_contacts() {
+    let db_pool = common::temp_pool("permissions_roles");
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(enforce_permissions))
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let owner = common::owner_token(&db_pool);
+    let worker = |name: &str, role: Option<&str>, contact: Option<&str>| {
+        let notify_by = if contact.is_some() { "Email" } else { "App" };
+        json!({
+            "id": null, "name": name, "role": role,
+            "contact": contact, "notify_by": notify_by
+        })
+    };
+    for (name, role, contact) in [
+        ("Asha", Some("Manager"), Some("asha@example.com")),
+        ("Ravi", Some("Milker"), Some("ravi@example.com")),
+    ] {
+        let req = TestRequest::post()
+            .uri("/workers")
+            .set_json(worker(name, role, contact))
+            .insert_header((SESSION_HEADER, owner.as_str()))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    // As if Asha, whose role may do anything, had signed in with her PIN
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "INSERT INTO sessions (device, token_hash, worker_id) VALUES ('Phone', ?1, 1)",
+            [hash_key("yagi_asha")],
+        )
+        .unwrap();
+    let as_asha = |req: TestRequest| {
+        req.insert_header((SESSION_HEADER, "yagi_asha"))
+            .to_request()
+    };
+
+    // Asha may not drop her role, nor take over Ravi's sign-in by his email
+    let req = TestRequest::put()
+        .uri("/workers/1")
+        .set_json(worker("Asha", None, Some("asha@example.com")));
+    assert_eq!(call_service(&app, as_asha(req)).await.status(), 403);
+    let req = TestRequest::put()
+        .uri("/workers/2")
+        .set_json(worker("Ravi", Some("Milker"), Some("asha@example.com")));
+    assert_eq!(call_service(&app, as_asha(req)).await.status(), 403);
+    let req = TestRequest::post()
+        .uri("/workers")
+        .set_json(worker("Mallory", Some("Manager"), None));
+    assert_eq!(call_service(&app, as_asha(req)).await.status(), 403);
+
+    // She may still rename workers and add them without a role
+    let req = TestRequest::put()
+        .uri("/workers/2")
+        .set_json(worker("Ravi K", Some("Milker"), Some("ravi@example.com")));
+    assert_eq!(call_service(&app, as_asha(req)).await.status(), 200);
+    let req = TestRequest::post()
+        .uri("/workers")
+        .set_json(worker("Meena", None, None));
+    assert_eq!(call_service(&app, as_asha(req)).await.status(), 201);
+
+    // A worker without a role may do nothing
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "INSERT INTO sessions (device, token_hash, worker_id) VALUES ('Tablet', ?1, 3)",
+            [hash_key("yagi_meena")],
+        )
+        .unwrap();
+    let req = TestRequest::get()
+        .uri("/goats")
+        .insert_header((SESSION_HEADER, "yagi_meena"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 403);
+    let req = TestRequest::put()
+        .uri("/workers/1")
+        .set_json(worker("Asha", None, Some("asha@example.com")))
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::get().uri("/goats");
+    assert_eq!(call_service(&app, as_asha(req)).await.status(), 403);
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use serde_json::json;
+use shared::GoatParams;
+use shared::pricing::{Expr, GoatValuation};
+use std::collections::BTreeMap;
+
+fn eval(formula: &str, vars: &[(&str, f64)]) -> Result<f64, String> {
+    let vars: BTreeMap<String, f64> = vars.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+    Expr::parse(formula)?.eval(&vars)
+}
+
+#[test]
+fn test_formula_evaluation() {
+    assert_eq!(eval("2 + 3 * 4", &[]), Ok(14.0));
+    assert_eq!(eval("(2 + 3) * 4", &[]), Ok(20.0));
+    assert_eq!(eval("10 - 4 - 3", &[]), Ok(3.0));
+    assert_eq!(eval("-weight * 2", &[("weight", 3.0)]), Ok(-6.0));
+    assert_eq!(
+        eval("max(weight * 350, 4000)", &[("weight", 10.0)]),
+        Ok(4000.0)
+    );
+    assert_eq!(eval("min(1, 2, -3)", &[]), Ok(-3.0));
+    assert_eq!(eval("round(2.5) + 0.25", &[]), Ok(3.25));
+    assert_eq!(
+        eval(
+            "weight * rate_per_kg * breed_factor",
+            &[
+                ("weight", 30.0),
+                ("rate_per_kg", 400.0),
+                ("breed_factor", 1.5)
+            ]
+        ),
+        Ok(18000.0)
+    );
+
+    assert!(eval("", &[]).is_err());
+    assert!(eval("2 +", &[]).is_err());
+    assert!(eval("2 $ 3", &[]).is_err());
+    assert!(eval("(2 + 3", &[]).is_err());
+    assert!(eval("round(1, 2)", &[]).is_err());
+    assert!(eval("median(1, 2)", &[]).is_err());
+    assert_eq!(eval("1 / 0", &[]), Err("Division by zero".to_string()));
+    assert_eq!(
+        eval("price * 2", &[]),
+        Err("Unknown variable 'price'".to_string())
+    );
+
+    // Deep nesting and long formulas are refused instead of overflowing
+    // the stack
+    let nested = |open: &str, depth: usize| format!("{}1{}", open.repeat(depth), ")".repeat(depth));
+    assert_eq!(eval(&nested("(", 32), &[]), Ok(1.0));
+    assert_eq!(
+        eval(&nested("(", 33), &[]),
+        Err("Formula nests more than 32 levels deep".to_string())
+    );
+    assert!(eval(&nested("round(", 100_000), &[]).is_err());
+    assert_eq!(eval(&format!("{}1", "-".repeat(32)), &[]), Ok(1.0));
+    assert!(eval(&format!("{}1", "-".repeat(100_000)), &[]).is_err());
+    assert_eq!(eval(&format!("1{}", " + 1".repeat(124)), &[]), Ok(125.0));
+    assert_eq!(
+        eval(&format!("1{}", " + 1".repeat(125)), &[]),
+        Err("Formula is longer than 500 characters".to_string())
+    );
+}
+
+#[actix_rt::test]
+async fn test_herd_valuation() {
+    let db_pool = common::temp_pool("pricing");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let mut sirohi = common::sample_goat("Moti");
+    sirohi["breed"] = json!("Sirohi");
+    for goat in [common::sample_goat("Rani"), sirohi] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(goat)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    let req = TestRequest::get().uri("/pricing/valuations").to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    for formula in ["weight * rate", "weight * * 2", "weight * rate_per_kg"] {
+        let req = TestRequest::put()
+            .uri("/settings")
+            .set_json(json!({ "pricing": { "formula": formula } }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400, "{}", formula);
+    }
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({ "pricing": {
+            "formula": "weight * rate_per_kg * breed_factor",
+            "variables": { "weight": 2.0, "rate_per_kg": 400.0 }
+        } }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({ "pricing": {
+            "formula": "weight * rate_per_kg * breed_factor",
+            "variables": { "rate_per_kg": 400.0 },
+            "breed_factors": { "Sirohi": 1.25 }
+        } }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    let expected = vec![
+        GoatValuation {
+            goat_name: "Moti".to_string(),
+            current_price: 150.0,
+            formula_price: Some(15000.0),
+            error: None,
+        },
+        GoatValuation {
+            goat_name: "Rani".to_string(),
+            current_price: 150.0,
+            formula_price: Some(12000.0),
+            error: None,
+        },
+    ];
+    let req = TestRequest::get().uri("/pricing/valuations").to_request();
+    let valuations: Vec<GoatValuation> = call_and_read_body_json(&app, req).await;
+    assert_eq!(valuations, expected);
+
+    let req = TestRequest::post().uri("/pricing/apply").to_request();
+    let applied: Vec<GoatValuation> = call_and_read_body_json(&app, req).await;
+    assert_eq!(applied, expected);
+
+    let req = TestRequest::get().uri("/goats").to_request();
+    let goats: Vec<GoatParams> = call_and_read_body_json(&app, req).await;
+    let prices: BTreeMap<String, f64> = goats
+        .into_iter()
+        .map(|g| (g.name, g.current_price))
+        .collect();
+    assert_eq!(prices["Moti"], 15000.0);
+    assert_eq!(prices["Rani"], 12000.0);
+
+    // A goat the formula prices below zero keeps its price
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({ "pricing": { "formula": "weight - 40" } }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::post().uri("/pricing/apply").to_request();
+    let applied: Vec<GoatValuation> = call_and_read_body_json(&app, req).await;
+    assert!(
+        applied
+            .iter()
+            .all(|v| v.formula_price.is_none() && v.error.is_some())
+    );
+    let req = TestRequest::get().uri("/goats").to_request();
+    let goats: Vec<GoatParams> = call_and_read_body_json(&app, req).await;
+    let unchanged: BTreeMap<String, f64> = goats
+        .into_iter()
+        .map(|g| (g.name, g.current_price))
+        .collect();
+    assert_eq!(unchanged, prices);
+}
@@ -0,0 +1,180 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::errors::AppError;
+use backend::ration::{Constraint, MAX_INTAKE, Relation, formulate_ration, minimise};
+use backend::routes;
+use serde_json::json;
+use shared::Breed;
+use shared::inventory::{InventoryCategory, InventoryItem};
+use shared::nutrition::{
+    Nutrient, NutritionStage, RationPlan, RationRequest, daily_requirement, ration_supply,
+};
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 0.01
+}
+
+fn item(
+    name: &str,
+    category: InventoryCategory,
+    quantity: f64,
+    unit: &str,
+    cost: f64,
+) -> InventoryItem {
+    InventoryItem {
+        id: None,
+        name: name.to_string(),
+        category,
+        quantity,
+        unit: unit.to_string(),
+        unit_cost: cost,
+        expiry_date: None,
+        reorder_level: 0.0,
+    }
+}
+
+fn feed(name: &str, quantity: f64, cost: f64) -> InventoryItem {
+    item(name, InventoryCategory::Feed, quantity, "kg", cost)
+}
+
+#[test]
+fn test_simplex_finds_the_cheapest_point() {
+    let at_least = |coefficients: Vec<f64>, bound: f64| Constraint {
+        coefficients,
+        relation: Relation::AtLeast,
+        bound,
+    };
+    // Cheapest x + y with x + 2y >= 4 and 3x + y >= 6 is at (1.6, 1.2)
+    let x = minimise(
+        &[1.0, 1.0],
+        &[at_least(vec![1.0, 2.0], 4.0), at_least(vec![3.0, 1.0], 6.0)],
+    )
+    .unwrap();
+    assert!(close(x[0], 1.6) && close(x[1], 1.2), "{:?}", x);
+
+    // A cap moves the optimum onto the dearer variable
+    let capped = Constraint {
+        coefficients: vec![1.0, 0.0],
+        relation: Relation::AtMost,
+        bound: 1.0,
+    };
+    let x = minimise(
+        &[1.0, 3.0],
+        &[at_least(vec![1.0, 1.0], 3.0), capped.clone()],
+    )
+    .unwrap();
+    assert!(close(x[0], 1.0) && close(x[1], 2.0), "{:?}", x);
+
+    assert_eq!(
+        minimise(&[1.0, 1.0], &[at_least(vec![1.0, 0.0], 2.0), capped]),
+        None
+    );
+}
+
+#[test]
+fn test_formulate_ration_meets_targets_at_least_cost() {
+    let target = daily_requirement(40.0, NutritionStage::Lactating, &Breed::Jamunapari);
+    let stock = [
+        feed("Hay", 2000.0, 8.0),
+        feed("Concentrate", 1000.0, 30.0),
+        feed("Soybean meal", 200.0, 45.0),
+        feed("Mineral lick", 50.0, 5.0),
+        item("Maize", InventoryCategory::Feed, 20.0, "bags", 900.0),
+        feed("Wheat bran", 0.0, 12.0),
+        item("Dewormer", InventoryCategory::Medicine, 50.0, "ml", 2.5),
+    ];
+    let request = RationRequest::new(target, 10, 30);
+    let plan = formulate_ration(&stock, &request).unwrap();
+
+    for nutrient in Nutrient::ALL {
+        assert!(
+            nutrient.amount(&plan.supply) >= nutrient.amount(&target),
+            "{:?}: {:?}",
+            nutrient,
+            plan
+        );
+    }
+    // Rounding up adds at most 10 g of each feed
+    let rounding = 0.01 * plan.items.len() as f64;
+    assert!(plan.supply.dry_matter_kg <= target.dry_matter_kg * MAX_INTAKE + rounding);
+    assert!(
+        plan.items.iter().any(|i| i.feed == "Hay"),
+        "{:?}",
+        plan.items
+    );
+    let cost: f64 = plan.items.iter().map(|i| i.cost_per_day).sum();
+    assert!(close(plan.cost_per_day, cost));
+    assert_eq!(
+        plan.skipped,
+        vec![
+            "Mineral lick is not a known feed",
+            "Maize is not stocked in kg",
+            "Wheat bran is out of stock",
+        ]
+    );
+
+    // The plan reads back as a feed plan supplying what it claims
+    let supply = ration_supply(&plan.diet()).unwrap();
+    for nutrient in Nutrient::ALL {
+        assert!(close(
+            nutrient.amount(&supply),
+            nutrient.amount(&plan.supply)
+        ));
+    }
+
+    // Stock must last the goats and days asked: 200 kg of soybean meal for
+    // 10 goats over 100 days is at most 0.2 kg a goat a day
+    let long = formulate_ration(&stock, &RationRequest::new(target, 10, 100)).unwrap();
+    let soy = long.items.iter().find(|i| i.feed == "Soybean meal");
+    assert!(soy.is_none_or(|i| i.kg_per_day <= 0.2 + 0.01), "{:?}", long);
+    assert!(long.cost_per_day >= plan.cost_per_day - 0.01);
+
+    // Straw alone cannot meet a milker's needs within her appetite
+    let err = formulate_ration(&[feed("Straw", 5000.0, 3.0)], &request).unwrap_err();
+    assert!(matches!(err, AppError::InvalidInput(_)), "{:?}", err);
+    let err = formulate_ration(&stock[3..], &request).unwrap_err();
+    assert!(matches!(err, AppError::InvalidInput(_)), "{:?}", err);
+}
+
+#[actix_rt::test]
+async fn test_ration_endpoint() {
+    let db_pool = common::temp_pool("ration");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for (name, cost) in [("Lucerne hay", 20.0), ("Concentrate", 30.0)] {
+        let req = TestRequest::post()
+            .uri("/inventory")
+            .set_json(json!({
+                "id": null, "name": name, "category": "Feed", "quantity": 500.0,
+                "unit": "kg", "unit_cost": cost, "expiry_date": null, "reorder_level": 50.0
+            }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    let req = TestRequest::get()
+        .uri("/nutrition/ration?dry_matter_kg=1.2&crude_protein_g=150&energy_mj=11&goats=5&days=30")
+        .to_request();
+    let plan: RationPlan = call_and_read_body_json(&app, req).await;
+    assert!(!plan.items.is_empty());
+    assert!(plan.supply.crude_protein_g >= 150.0);
+    assert!(plan.supply.energy_mj >= 11.0);
+    assert!(plan.skipped.is_empty());
+
+    for uri in [
+        "/nutrition/ration?dry_matter_kg=1.2&crude_protein_g=150&energy_mj=11&goats=0&days=30",
+        "/nutrition/ration?dry_matter_kg=-1&crude_protein_g=150&energy_mj=11&goats=5&days=30",
+        // Far more energy than the dry matter allowed can hold
+        "/nutrition/ration?dry_matter_kg=1.2&crude_protein_g=150&energy_mj=40&goats=5&days=30",
+    ] {
+        let req = TestRequest::get().uri(uri).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400);
+    }
+}
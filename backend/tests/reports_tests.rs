@@ -0,0 +1,375 @@
+mod common;
+
+use actix_web::body::to_bytes;
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::handlers::reports::age_in_months;
+use backend::routes;
+use chrono::NaiveDate;
+use serde_json::json;
+use shared::census::{
+    AgeBand, CensusAnimal, CensusDimension, CensusReport, ReportTemplate, UNKNOWN_AGE, build_census,
+};
+use shared::labels::LabelLayout;
+
+fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn test_age_in_months() {
+    assert_eq!(
+        age_in_months(date("2025-03-15"), date("2026-03-15")),
+        Some(12)
+    );
+    assert_eq!(
+        age_in_months(date("2025-03-15"), date("2026-03-14")),
+        Some(11)
+    );
+    assert_eq!(
+        age_in_months(date("2026-03-01"), date("2026-03-01")),
+        Some(0)
+    );
+    assert_eq!(age_in_months(date("2026-03-02"), date("2026-03-01")), None);
+}
+
+#[test]
+fn test_template_validation() {
+    for template in ReportTemplate::built_ins() {
+        assert_eq!(template.validate(), Ok(()), "{}", template.key);
+    }
+    let band = |label: &str, min: u32, max: Option<u32>| AgeBand {
+        label: label.to_string(),
+        min_months: min,
+        max_months: max,
+    };
+    let template =
+        |key: &str, group_by: Vec<CensusDimension>, age_bands: Vec<AgeBand>| ReportTemplate {
+            key: key.to_string(),
+            title: "Census".to_string(),
+            group_by,
+            age_bands,
+            built_in: false,
+        };
+    let age = vec![CensusDimension::Age];
+    assert!(
+        template("Bad Key", age.clone(), vec![band("All", 0, None)])
+            .validate()
+            .is_err()
+    );
+    assert!(template("a", vec![], vec![]).validate().is_err());
+    assert!(template("a", age.clone(), vec![]).validate().is_err());
+    assert!(
+        template(
+            "a",
+            vec![CensusDimension::Sex, CensusDimension::Sex],
+            vec![]
+        )
+        .validate()
+        .is_err()
+    );
+    // Gap between bands
+    assert!(
+        template(
+            "a",
+            age.clone(),
+            vec![band("Kids", 0, Some(6)), band("Adults", 12, None)]
+        )
+        .validate()
+        .is_err()
+    );
+    // Open-ended band before the last
+    assert!(
+        template(
+            "a",
+            age.clone(),
+            vec![band("Kids", 0, None), band("Adults", 12, None)]
+        )
+        .validate()
+        .is_err()
+    );
+    assert!(
+        template(
+            "a",
+            age,
+            vec![band("Kids", 0, Some(6)), band("Rest", 6, None)]
+        )
+        .validate()
+        .is_ok()
+    );
+}
+
+#[test]
+fn test_build_census_orders_age_bands() {
+    let template = &ReportTemplate::built_ins()[0];
+    let animal = |breed: &str, sex: &str, age_months: Option<u32>| CensusAnimal {
+        breed: breed.to_string(),
+        sex: sex.to_string(),
+        age_months,
+    };
+    let report = build_census(
+        template,
+        &[
+            animal("Sirohi", "Female", Some(30)),
+            animal("Beetal", "Female", Some(40)),
+            animal("Beetal", "Female", None),
+            animal("Beetal", "Female", Some(3)),
+            animal("Beetal", "Female", Some(20)),
+        ],
+        "2026-03-31",
+    );
+    assert_eq!(report.columns, vec!["Breed", "Sex", "Age"]);
+    let rows: Vec<(Vec<&str>, u32)> = report
+        .rows
+        .iter()
+        .map(|r| (r.groups.iter().map(String::as_str).collect(), r.count))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            (vec!["Beetal", "Female", "Up to 1 year"], 1),
+            (vec!["Beetal", "Female", "Over 1 year"], 2),
+            (vec!["Beetal", "Female", UNKNOWN_AGE], 1),
+            (vec!["Sirohi", "Female", "Over 1 year"], 1),
+        ]
+    );
+    assert_eq!(report.total, 5);
+}
+
+#[actix_rt::test]
+async fn test_census_endpoints() {
+    let db_pool = common::temp_pool("reports");
+    let conn = db_pool.get_conn().unwrap();
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    for name in ["Rani", "Moti", "Kali"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    conn.execute(
+        "UPDATE goats SET date_of_birth = '2025-09-01' WHERE name = 'Rani'",
+        [],
+    )
+    .unwrap();
+    conn.execute(
+        "UPDATE goats SET date_of_birth = '2022-01-10', gender = 'Male' WHERE name = 'Moti'",
+        [],
+    )
+    .unwrap();
+    // Born after the census date, so not counted
+    conn.execute(
+        "UPDATE goats SET date_of_birth = '2026-05-01' WHERE name = 'Kali'",
+        [],
+    )
+    .unwrap();
+
+    let req = TestRequest::get()
+        .uri("/reports/census/sex-age?as_of=2026-03-31")
+        .to_request();
+    let report: CensusReport = call_and_read_body_json(&app, req).await;
+    assert_eq!(report.total, 2);
+    assert_eq!(
+        report.rows[0].groups,
+        vec!["Female", "Young stock (6-12 months)"]
+    );
+    assert_eq!(
+        report.rows[1].groups,
+        vec!["Male", "Adults (1 year and over)"]
+    );
+
+    let req = TestRequest::get()
+        .uri("/reports/census/breed-headcount?as_of=2026-03-31&format=csv")
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.headers().get("content-type").unwrap(), "text/csv");
+    assert!(
+        resp.headers()
+            .get("content-disposition")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .contains("breed-headcount-2026-03-31.csv")
+    );
+    let body = to_bytes(resp.into_body()).await.unwrap();
+    assert_eq!(body, "Breed,Count\nBeetal,2\nTotal,2\n");
+
+    let req = TestRequest::get()
+        .uri("/reports/census/livestock-census?format=pdf")
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "application/pdf"
+    );
+    let body = to_bytes(resp.into_body()).await.unwrap();
+    assert!(body.starts_with(b"%PDF-"));
+
+    for uri in [
+        "/reports/census/missing",
+        "/reports/census/sex-age?as_of=31-03-2026",
+    ] {
+        let req = TestRequest::get().uri(uri).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400, "{}", uri);
+    }
+}
+
+#[actix_rt::test]
+async fn test_custom_report_templates() {
+    let db_pool = common::temp_pool("report_templates");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let custom = json!({
+        "key": "district-return",
+        "title": "District Return",
+        "group_by": ["Sex", "Breed"]
+    });
+    let req = TestRequest::post()
+        .uri("/reports/templates")
+        .set_json(&custom)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::post()
+        .uri("/reports/templates")
+        .set_json(&custom)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let req = TestRequest::get().uri("/reports/templates").to_request();
+    let templates: Vec<ReportTemplate> = call_and_read_body_json(&app, req).await;
+    let last = templates.last().unwrap();
+    assert_eq!(last.key, "district-return");
+    assert!(!last.built_in);
+    assert!(templates.iter().filter(|t| t.built_in).count() >= 3);
+
+    let req = TestRequest::get()
+        .uri("/reports/census/district-return")
+        .to_request();
+    let report: CensusReport = call_and_read_body_json(&app, req).await;
+    assert_eq!(report.columns, vec!["Sex", "Breed"]);
+    assert_eq!(report.total, 0);
+
+    let delete = |key: &str| {
+        TestRequest::delete()
+            .uri(&format!("/reports/templates/{}", key))
+            .to_request()
+    };
+    assert_eq!(
+        call_service(&app, delete("livestock-census"))
+            .await
+            .status(),
+        400
+    );
+    assert_eq!(
+        call_service(&app, delete("district-return")).await.status(),
+        200
+    );
+    assert_eq!(
+        call_service(&app, delete("district-return")).await.status(),
+        400
+    );
+}
+
+#[test]
+fn test_label_sheet_positions() {
+    let sheet = LabelLayout::L7160.sheet();
+    assert_eq!(sheet.per_page(), 21);
+    assert_eq!(sheet.position(0), (0, 7.25, 15.15));
+    let (page, left, top) = sheet.position(4);
+    assert_eq!(page, 0);
+    assert!((left - (7.25 + 66.04)).abs() < 1e-9);
+    assert!((top - (15.15 + 38.1)).abs() < 1e-9);
+    assert_eq!(sheet.position(21), (1, 7.25, 15.15));
+    // Every layout's labels fit on its page
+    for layout in LabelLayout::ALL {
+        let sheet = layout.sheet();
+        let (_, left, top) = sheet.position(sheet.per_page() - 1);
+        assert!(left + sheet.label_width <= sheet.page_width, "{:?}", layout);
+        assert!(
+            top + sheet.label_height <= sheet.page_height,
+            "{:?}",
+            layout
+        );
+    }
+}
+
+#[actix_rt::test]
+async fn test_tag_sheet() {
+    let db_pool = common::temp_pool("tag_sheet");
+    let conn = db_pool.get_conn().unwrap();
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    for name in ["Rani", "Moti"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    conn.execute("UPDATE goats SET tag_id = 'TAG-9' WHERE name = 'Rani'", [])
+        .unwrap();
+
+    let req = TestRequest::post()
+        .uri("/reports/tag-sheet")
+        .set_json(json!({ "goat_names": ["Rani", "Moti"] }))
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "application/pdf"
+    );
+    assert!(
+        resp.headers()
+            .get("content-disposition")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .contains("tag-sheet.pdf")
+    );
+    let body = to_bytes(resp.into_body()).await.unwrap();
+    let text = String::from_utf8_lossy(&body);
+    assert!(text.starts_with("%PDF-"));
+    for shown in ["(Rani)", "(TAG-9)", "(Moti)", "(No tag ID)"] {
+        assert!(text.contains(shown), "missing {}", shown);
+    }
+    assert!(text.contains("/Count 1"));
+
+    // Skipping used labels pushes the second goat onto a new sheet
+    let req = TestRequest::post()
+        .uri("/reports/tag-sheet")
+        .set_json(json!({ "goat_names": ["Rani", "Moti"], "layout": "l7160", "skip": 20 }))
+        .to_request();
+    let body = to_bytes(call_service(&app, req).await.into_body())
+        .await
+        .unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("/Count 2"));
+
+    for request in [
+        json!({ "goat_names": [] }),
+        json!({ "goat_names": ["Rani", "Bholu"] }),
+        json!({ "goat_names": ["Rani"], "layout": "l7163", "skip": 14 }),
+        json!({ "goat_names": ["Rani"], "layout": "a3" }),
+    ] {
+        let req = TestRequest::post()
+            .uri("/reports/tag-sheet")
+            .set_json(&request)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400, "{}", request);
+    }
+}
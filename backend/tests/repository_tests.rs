@@ -0,0 +1,405 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::db::DbPool;
+use backend::errors::AppError;
+use backend::repository::{
+    GoatRepository, PostgresGoatRepository, SqliteGoatRepository, StorageBackend,
+};
+use backend::routes;
+use shared::import::MAX_QUICK_NOTE_LEN;
+use shared::physical::{CoatColor, HornStatus};
+use shared::{Goat, GoatParams, GoatUpdate};
+use std::sync::{Arc, Mutex};
+
+/// Repository keeping goats in memory, standing in for another database.
+#[derive(Default)]
+struct MemoryGoats(Mutex<Vec<Goat>>);
+
+impl GoatRepository for MemoryGoats {
+    fn list(&self) -> Result<Vec<Goat>, AppError> {
+        Ok(self.0.lock().unwrap().clone())
+    }
+
+    fn list_after(&self, after_id: i64, limit: i64) -> Result<Vec<Goat>, AppError> {
+        let goats = self.0.lock().unwrap();
+        let after = goats.iter().filter(|g| g.id > after_id);
+        Ok(after.take(limit as usize).cloned().collect())
+    }
+
+    fn get(&self, id: i64) -> Result<Option<Goat>, AppError> {
+        Ok(self.0.lock().unwrap().iter().find(|g| g.id == id).cloned())
+    }
+
+    fn insert(&self, goat: &GoatParams) -> Result<Goat, AppError> {
+        let mut goats = self.0.lock().unwrap();
+        let stored = Goat {
+            id: goats.len() as i64 + 1,
+            params: goat.clone(),
+            tag_id: None,
+            date_of_birth: None,
+            created_at: None,
+            updated_at: None,
+        };
+        goats.push(stored.clone());
+        Ok(stored)
+    }
+
+    fn update(&self, goat: &GoatParams) -> Result<bool, AppError> {
+        let mut goats = self.0.lock().unwrap();
+        match goats.iter_mut().find(|g| g.name == goat.name) {
+            Some(existing) => {
+                existing.params = goat.clone();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn patch(&self, id: i64, update: &GoatUpdate) -> Result<Option<Goat>, AppError> {
+        let mut goats = self.0.lock().unwrap();
+        Ok(goats.iter_mut().find(|g| g.id == id).map(|existing| {
+            existing.params = update.apply(&existing.params);
+            existing.clone()
+        }))
+    }
+
+    fn delete(&self, name: &str) -> Result<Option<i64>, AppError> {
+        let mut goats = self.0.lock().unwrap();
+        let index = goats.iter().position(|g| g.name == name);
+        Ok(index.map(|index| goats.remove(index).id))
+    }
+}
+
+#[test]
+fn test_storage_backend_from_url() {
+    assert_eq!(
+        StorageBackend::from_url("livestock.db"),
+        StorageBackend::Sqlite("livestock.db".into())
+    );
+    assert_eq!(
+        StorageBackend::from_url("sqlite:///var/lib/yagi/farm.db"),
+        StorageBackend::Sqlite("/var/lib/yagi/farm.db".into())
+    );
+    assert_eq!(
+        StorageBackend::from_url("sqlite://:memory:"),
+        StorageBackend::Memory
+    );
+    assert_eq!(
+        StorageBackend::from_url("postgresql://yagi@db/yagi").with_sqlite_path("/tmp/farm.db"),
+        StorageBackend::Postgres {
+            url: "postgresql://yagi@db/yagi".into(),
+            sqlite: "/tmp/farm.db".into()
+        }
+    );
+    assert_eq!(
+        StorageBackend::from_url("farm.db").with_sqlite_path("/tmp/farm.db"),
+        StorageBackend::Sqlite("farm.db".into())
+    );
+
+    let memory = StorageBackend::from_url(":memory:");
+    let db_pool = memory.open().unwrap();
+    assert!(memory.open_goat_store(&db_pool).unwrap().is_none());
+    assert!(matches!(
+        StorageBackend::from_url("postgres://yagi@db:notaport/yagi").open_goat_store(&db_pool),
+        Err(AppError::PostgresError(_))
+    ));
+}
+
+/// Runs against the PostgreSQL database in `DATABASE_URL`, and is skipped
+/// without one; its `goats` table is replaced first.
+#[actix_rt::test]
+async fn test_postgres_repository_round_trip() {
+    let Some(url) = std::env::var("DATABASE_URL")
+        .ok()
+        .filter(|url| url.starts_with("postgres"))
+    else {
+        return;
+    };
+    let drop_url = url.clone();
+    std::thread::spawn(move || {
+        let mut client = postgres::Client::connect(&drop_url, postgres::NoTls).unwrap();
+        client
+            .batch_execute(
+                "DROP TABLE IF EXISTS goats;
+                 CREATE TABLE goats (id BIGINT PRIMARY KEY, goat JSONB NOT NULL);
+                 INSERT INTO goats VALUES (99, '{}');",
+            )
+            .unwrap();
+    })
+    .join()
+    .unwrap();
+
+    // The store is brought in line with the farm when opened
+    let db_pool = common::temp_pool("postgres");
+    let sqlite = SqliteGoatRepository::new(db_pool.clone());
+    let rani: GoatParams = serde_json::from_value(common::sample_goat("Rani")).unwrap();
+    let rani = sqlite.insert(&rani).unwrap();
+    let backend = StorageBackend::from_url(&url);
+    let store = Arc::new(backend.open_goat_store(&db_pool).unwrap().unwrap());
+    let repo = PostgresGoatRepository::new(store.clone(), sqlite);
+    assert_eq!(repo.list().unwrap(), vec![rani.clone()]);
+
+    let moti: GoatParams = serde_json::from_value(common::sample_goat("Moti")).unwrap();
+    let moti = repo.insert(&moti).unwrap();
+    assert_eq!(repo.get(moti.id).unwrap(), Some(moti.clone()));
+    let update = GoatUpdate {
+        weight: Some(34.0),
+        ..Default::default()
+    };
+    let patched = repo.patch(rani.id, &update).unwrap().unwrap();
+    assert_eq!(patched.weight, 34.0);
+    assert_eq!(repo.get(rani.id).unwrap(), Some(patched.clone()));
+    assert_eq!(repo.list_after(rani.id, 10).unwrap(), vec![moti.clone()]);
+
+    // Changes made by other modules straight in SQLite are copied too
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "UPDATE goats SET health_status = 'sick' WHERE id = ?1",
+            [moti.id],
+        )
+        .unwrap();
+    assert_eq!(repo.get(moti.id).unwrap().unwrap().health_status, "sick");
+
+    assert!(repo.delete("Rani").unwrap().is_some());
+    assert_eq!(repo.get(rani.id).unwrap(), None);
+
+    // Handlers get the PostgreSQL repository once the store is registered
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .app_data(web::Data::from(store))
+            .configure(routes::configure),
+    )
+    .await;
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Gauri"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::get().uri("/goats").to_request();
+    let goats: Vec<Goat> = call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = goats.iter().map(|goat| goat.name.as_str()).collect();
+    assert_eq!(names, ["Moti", "Gauri"]);
+}
+
+#[test]
+fn test_sqlite_repository_round_trip() {
+    let repo = SqliteGoatRepository::new(common::temp_pool("repository"));
+    let mut goat: GoatParams = serde_json::from_value(common::sample_goat("Rani")).unwrap();
+    let stored = repo.insert(&goat).unwrap();
+    assert_eq!(stored.params, goat);
+    assert!(stored.created_at.is_some());
+    assert_eq!(repo.get(stored.id).unwrap().as_ref(), Some(&stored));
+
+    goat.weight = 34.0;
+    goat.horns = Some(HornStatus::Disbudded);
+    goat.coat_color = Some(CoatColor::Tan);
+    goat.marks = Some("Scar on right flank".into());
+    assert!(repo.update(&goat).unwrap());
+    let listed = repo.list_after(0, 10).unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!((listed[0].id, &listed[0].params), (stored.id, &goat));
+    assert!(listed[0].updated_at.is_some());
+    assert!(repo.list_after(stored.id, 10).unwrap().is_empty());
+    assert_eq!(repo.get(stored.id + 1).unwrap(), None);
+
+    goat.name = "Moti".into();
+    assert!(!repo.update(&goat).unwrap());
+    assert!(repo.delete("Rani").unwrap().is_some());
+    assert_eq!(repo.delete("Rani").unwrap(), None);
+    assert!(repo.list().unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn test_goat_endpoints_use_registered_repository() {
+    let repo: Arc<dyn GoatRepository> = Arc::new(MemoryGoats::default());
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::from(repo.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    let created: Goat = call_and_read_body_json(&app, req).await;
+    assert_eq!((created.id, created.name.as_str()), (1, "Rani"));
+    assert_eq!(repo.list().unwrap()[0].name, "Rani");
+
+    let req = TestRequest::get().uri("/goats").to_request();
+    let goats: Vec<Goat> = call_and_read_body_json(&app, req).await;
+    assert_eq!(goats, vec![created]);
+
+    let req = TestRequest::delete()
+        .uri("/goats")
+        .set_json(serde_json::json!({ "name": "Moti" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+}
+
+#[test]
+fn test_in_memory_databases_are_shared_per_pool_and_isolated() {
+    let repo = SqliteGoatRepository::in_memory().unwrap();
+    let other = SqliteGoatRepository::in_memory().unwrap();
+    let goat: GoatParams = serde_json::from_value(common::sample_goat("Rani")).unwrap();
+    repo.insert(&goat).unwrap();
+
+    // Every connection of the pool sees the same database
+    for _ in 0..3 {
+        let goats = repo.list().unwrap();
+        assert_eq!(goats.len(), 1);
+        assert_eq!(goats[0].params, goat);
+    }
+    assert!(other.list().unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn test_goat_endpoints_on_ephemeral_database() {
+    let db_pool = DbPool::in_memory().unwrap();
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let req = TestRequest::get().uri("/goats").to_request();
+    let goats: Vec<Goat> = call_and_read_body_json(&app, req).await;
+    assert_eq!(goats.len(), 1);
+    assert_eq!(goats[0].name, "Rani");
+    assert!(goats[0].id > 0);
+}
+
+#[actix_rt::test]
+async fn test_patch_goat_endpoint() {
+    let db_pool = common::temp_pool("patch_goat");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let mut created = Vec::new();
+    for name in ["Rani", "Moti"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        let goat: Goat = call_and_read_body_json(&app, req).await;
+        created.push(goat);
+    }
+    let rani = &created[0];
+
+    // Two edits made from the same copy each keep the other's change
+    let mut heavier = rani.params.clone();
+    heavier.weight = 34.0;
+    let mut sold = rani.params.clone();
+    sold.current_price = 200.0;
+    sold.last_bred = Some("2024-03-01".into());
+    sold.coat_color = Some(CoatColor::Brown);
+    for after in [&heavier, &sold] {
+        let update = GoatUpdate::diff(&rani.params, after);
+        let req = TestRequest::patch()
+            .uri(&format!("/goats/{}", rani.id))
+            .set_json(&update)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 200);
+    }
+    let req = TestRequest::patch()
+        .uri(&format!("/goats/{}", rani.id))
+        .set_json(serde_json::json!({
+            "last_bred": null,
+            "horns": "Polled",
+            "coat_color": null,
+            "vaccinations": [{ "name": "PPR" }]
+        }))
+        .to_request();
+    let patched: Goat = call_and_read_body_json(&app, req).await;
+    assert_eq!(patched.id, rani.id);
+    assert_eq!((patched.weight, patched.current_price), (34.0, 200.0));
+    assert_eq!(patched.last_bred, None);
+    assert_eq!((patched.horns, patched.coat_color), (Some(HornStatus::Polled), None));
+    assert_eq!(patched.diet, rani.diet);
+    assert!(patched.updated_at.is_some());
+    let conn = db_pool.get_conn().unwrap();
+    let linked: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM goat_vaccines WHERE goat_id = ?1",
+            [rani.id],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(linked, 1);
+
+    for (uri, body) in [
+        ("/goats/999", serde_json::json!({ "weight": 40.0 })),
+        ("/goats/1", serde_json::json!({ "weight": -1.0 })),
+        ("/goats/1", serde_json::json!({ "name": "Moti" })),
+    ] {
+        let req = TestRequest::patch().uri(uri).set_json(&body).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400, "{}", body);
+    }
+    assert_eq!(
+        GoatUpdate::diff(&rani.params, &rani.params),
+        GoatUpdate::default()
+    );
+    assert_eq!(
+        GoatUpdate::diff(&rani.params, &heavier),
+        GoatUpdate {
+            weight: Some(34.0),
+            ..Default::default()
+        }
+    );
+}
+
+#[actix_rt::test]
+async fn test_quick_notes_are_patched_and_cleared() {
+    let db_pool = common::temp_pool("quick_note");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    let rani: Goat = call_and_read_body_json(&app, req).await;
+    assert_eq!(rani.quick_note, None);
+
+    let patch = |body: serde_json::Value| {
+        TestRequest::patch()
+            .uri(&format!("/goats/{}", rani.id))
+            .set_json(body)
+            .to_request()
+    };
+    let req = patch(serde_json::json!({ "quick_note": "check tomorrow" }));
+    let noted: Goat = call_and_read_body_json(&app, req).await;
+    assert_eq!(noted.quick_note.as_deref(), Some("check tomorrow"));
+    assert_eq!(noted.weight, rani.weight);
+    let req = TestRequest::get().uri("/goats").to_request();
+    let goats: Vec<Goat> = call_and_read_body_json(&app, req).await;
+    assert_eq!(goats[0].quick_note.as_deref(), Some("check tomorrow"));
+
+    let long = "x".repeat(MAX_QUICK_NOTE_LEN + 1);
+    let req = patch(serde_json::json!({ "quick_note": long }));
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let req = patch(serde_json::json!({ "quick_note": null }));
+    let cleared: Goat = call_and_read_body_json(&app, req).await;
+    assert_eq!(cleared.quick_note, None);
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::events::{EventLog, EventSourcing};
+use backend::jobs::{JobQueue, JobRunner};
+use backend::retention::{queue_daily_cleanup, restore_from_trash, trashed_goat};
+use backend::routes;
+use serde_json::json;
+use shared::Goat;
+use shared::growth::GrowthHistory;
+use shared::jobs::{Job, JobStatus};
+use shared::retention::UpcomingPurges;
+
+#[actix_rt::test]
+async fn test_deleted_goats_wait_in_trash_and_restore() {
+    let pool = common::temp_pool("trash");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let mut rani = common::sample_goat("Rani");
+    rani["vaccinations"] = json!([{ "id": null, "name": "PPR", "given_on": "2025-01-05" }]);
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(&rani)
+        .to_request();
+    let added: Goat = call_and_read_body_json(&app, req).await;
+    let req = TestRequest::post()
+        .uri("/growth/weights")
+        .set_json(
+            json!({ "id": null, "goat_name": "Rani", "weighed_on": "2025-01-10", "weight": 34.5 }),
+        )
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::delete()
+        .uri("/goats")
+        .set_json(json!({ "name": "Rani" }))
+        .to_request();
+    let trash_id: i64 = call_and_read_body_json(&app, req).await;
+
+    let req = TestRequest::get().uri("/retention").to_request();
+    let purges: UpcomingPurges = call_and_read_body_json(&app, req).await;
+    assert_eq!(purges.policy.trash_days, 30);
+    assert_eq!(purges.trash.len(), 1);
+    let trashed = &purges.trash[0];
+    assert_eq!(trashed.id, trash_id);
+    assert_eq!(trashed.goat.id, added.id);
+    assert_eq!(trashed.goat.params.vaccinations.len(), 1);
+    assert!(trashed.purge_on > trashed.deleted_at.format("%Y-%m-%d").to_string());
+    let req = TestRequest::get().uri("/goats").to_request();
+    let herd: Vec<Goat> = call_and_read_body_json(&app, req).await;
+    assert!(herd.is_empty());
+
+    // Its weighings were kept while it was in the trash
+    let restore = format!("/trash/{}/restore", trashed.id);
+    let req = TestRequest::post().uri(&restore).to_request();
+    let restored: Goat = call_and_read_body_json(&app, req).await;
+    assert_eq!(restored.id, added.id);
+    assert_eq!(restored.params.name, "Rani");
+    assert_eq!(restored.params.vaccinations.len(), 1);
+    let req = TestRequest::get().uri("/growth/weights/Rani").to_request();
+    let history: GrowthHistory = call_and_read_body_json(&app, req).await;
+    assert_eq!(history.records.len(), 1);
+    let req = TestRequest::post().uri(&restore).to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    // Names may repeat, so a goat added under the name in the meantime does
+    // not stop the restore
+    let req = TestRequest::delete()
+        .uri("/goats")
+        .set_json(json!({ "name": "Rani" }))
+        .to_request();
+    let trash_id: i64 = call_and_read_body_json(&app, req).await;
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    let other: Goat = call_and_read_body_json(&app, req).await;
+    let mut conn = pool.get_conn().unwrap();
+    let payload = trashed_goat(&conn, trash_id).unwrap().unwrap();
+    assert!(restore_from_trash(&mut conn, &EventLog::disabled(), trash_id, &payload).unwrap());
+    drop(conn);
+    let req = TestRequest::get().uri("/goats").to_request();
+    let herd: Vec<Goat> = call_and_read_body_json(&app, req).await;
+    let mut ids: Vec<i64> = herd.iter().map(|g| g.id).collect();
+    ids.sort();
+    assert_eq!(ids, vec![added.id, other.id]);
+    assert!(herd.iter().all(|g| g.params.name == "Rani"));
+    let req = TestRequest::get().uri("/retention").to_request();
+    let purges: UpcomingPurges = call_and_read_body_json(&app, req).await;
+    assert!(purges.trash.is_empty());
+}
+
+#[actix_rt::test]
+async fn test_cleanup_job_purges_past_retention() {
+    let pool = common::temp_pool("retention_cleanup");
+    let runner = JobRunner::new(JobQueue::new());
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(EventSourcing))
+            .configure(routes::configure),
+    )
+    .await;
+    for name in ["Rani", "Moti"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    let req = TestRequest::delete()
+        .uri("/goats")
+        .set_json(json!({ "name": "Rani" }))
+        .to_request();
+    call_service(&app, req).await;
+
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({ "retention": { "trash_days": 0 } }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({ "retention": { "trash_days": 7, "event_days": 10 } }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+
+    // Rani was deleted eight days ago; both goats were added eleven days ago
+    // and the deletion was logged five days ago
+    let conn = pool.get_conn().unwrap();
+    conn.execute(
+        "UPDATE goat_trash SET deleted_at = datetime('now', '-8 days')",
+        [],
+    )
+    .unwrap();
+    conn.execute(
+        "UPDATE events SET recorded_at = datetime('now', '-11 days') WHERE id < 3",
+        [],
+    )
+    .unwrap();
+    conn.execute(
+        "UPDATE events SET recorded_at = datetime('now', '-5 days') WHERE id = 3",
+        [],
+    )
+    .unwrap();
+
+    // Rani is only marked deleted until the cleanup
+    let goats: i64 = conn
+        .query_row("SELECT COUNT(*) FROM goats", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(goats, 2);
+
+    let req = TestRequest::get().uri("/retention").to_request();
+    let purges: UpcomingPurges = call_and_read_body_json(&app, req).await;
+    assert_eq!(purges.events_due, 2);
+    assert_eq!(purges.events_next_week, 1);
+    assert_eq!(purges.last_cleanup, None);
+
+    let req = TestRequest::post().uri("/jobs/cleanup").to_request();
+    let queued: Job = call_and_read_body_json(&app, req).await;
+    let job = runner.run_next(&pool).unwrap().unwrap();
+    assert_eq!(job.id, queued.id);
+    assert_eq!(job.status, JobStatus::Succeeded);
+    assert_eq!(job.done, 3);
+
+    let req = TestRequest::get().uri("/retention").to_request();
+    let purges: UpcomingPurges = call_and_read_body_json(&app, req).await;
+    assert!(purges.trash.is_empty());
+    let goats: i64 = conn
+        .query_row("SELECT COUNT(*) FROM goats", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(goats, 1);
+    assert_eq!(purges.events_due, 0);
+    assert_eq!(purges.events_next_week, 1);
+    assert!(purges.last_cleanup.is_some());
+    let left: i64 = conn
+        .query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(left, 1);
+
+    // A cleanup ran within the day, so the scheduler queues none
+    assert!(queue_daily_cleanup(&conn).unwrap().is_none());
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::auth::{API_KEY_HEADER, hash_key};
+use backend::routes;
+use serde_json::{Value, json};
+use shared::growth::GrowthHistory;
+use shared::scale::{ApiKey, IssuedApiKey, ScaleReading};
+
+#[test]
+fn test_hash_key_is_stable_hex() {
+    let hash = hash_key("yagi_test");
+    assert_eq!(hash.len(), 64);
+    assert_eq!(hash, hash_key("yagi_test"));
+    assert_ne!(hash, hash_key("yagi_other"));
+}
+
+#[actix_rt::test]
+async fn test_scale_readings_match_tags_to_goats() {
+    let db_pool = common::temp_pool("scale");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let reading = |tag: &str, weight: f64, at: &str| {
+        json!({ "tag_id": tag, "weight": weight, "read_at": at })
+    };
+
+    // No key, or an unknown one, is rejected
+    let req = TestRequest::post()
+        .uri("/scale/readings")
+        .set_json(reading("TAG-1", 31.0, "2026-01-10T08:00:00"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+    let req = TestRequest::post()
+        .uri("/scale/readings")
+        .insert_header((API_KEY_HEADER, "yagi_bogus"))
+        .set_json(reading("TAG-1", 31.0, "2026-01-10T08:00:00"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+
+    let req = TestRequest::post()
+        .uri("/api-keys")
+        .set_json(json!({ "name": "Barn scale" }))
+        .to_request();
+    let issued: IssuedApiKey = call_and_read_body_json(&app, req).await;
+    assert!(issued.key.starts_with("yagi_"));
+
+    // An unknown tag is stored unmatched
+    let req = TestRequest::post()
+        .uri("/scale/readings")
+        .insert_header((API_KEY_HEADER, issued.key.as_str()))
+        .set_json(reading("TAG-1", 31.0, "2026-01-10T08:00:00"))
+        .to_request();
+    let first: ScaleReading = call_and_read_body_json(&app, req).await;
+    assert_eq!(first.goat_name, None);
+    assert_eq!(first.read_at.to_rfc3339(), "2026-01-10T08:00:00+00:00");
+
+    let req = TestRequest::post()
+        .uri("/scale/readings")
+        .insert_header((API_KEY_HEADER, issued.key.as_str()))
+        .set_json(reading("TAG-1", 0.0, "2026-01-10T08:00:00"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    // Assigning the tag turns the reading into a weight record
+    let req = TestRequest::put()
+        .uri(&format!("/scale/readings/{}/goat", first.id))
+        .set_json(json!({ "goat_name": "Rani" }))
+        .to_request();
+    let matched: Vec<ScaleReading> = call_and_read_body_json(&app, req).await;
+    assert_eq!(matched.len(), 1);
+    assert_eq!(matched[0].goat_name.as_deref(), Some("Rani"));
+    assert!(matched[0].weight_record_id.is_some());
+
+    // Later readings with that tag match on arrival, bearer auth works too
+    let req = TestRequest::post()
+        .uri("/scale/readings")
+        .insert_header(("Authorization", format!("Bearer {}", issued.key)))
+        .set_json(reading("TAG-1", 32.5, "2026-02-10T08:00:00"))
+        .to_request();
+    let second: ScaleReading = call_and_read_body_json(&app, req).await;
+    assert_eq!(second.goat_name.as_deref(), Some("Rani"));
+
+    let req = TestRequest::get().uri("/growth/weights/Rani").to_request();
+    let history: GrowthHistory = call_and_read_body_json(&app, req).await;
+    let weights: Vec<(String, f64)> = history
+        .records
+        .iter()
+        .map(|r| (r.weighed_on.clone(), r.weight))
+        .collect();
+    assert_eq!(
+        weights,
+        vec![
+            ("2026-01-10".to_string(), 31.0),
+            ("2026-02-10".to_string(), 32.5)
+        ]
+    );
+    let req = TestRequest::get().uri("/goats").to_request();
+    let goats: Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(goats[0]["weight"], 32.5);
+
+    // Polling returns only newer readings
+    let req = TestRequest::get()
+        .uri(&format!("/scale/readings?since_id={}", first.id))
+        .to_request();
+    let newer: Vec<ScaleReading> = call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        newer.iter().map(|r| r.id).collect::<Vec<_>>(),
+        vec![second.id]
+    );
+
+    // A second goat cannot take a tag that is already assigned
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Meena"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::put()
+        .uri(&format!("/scale/readings/{}/goat", second.id))
+        .set_json(json!({ "goat_name": "Meena" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    // Revoked keys stop working
+    let req = TestRequest::delete()
+        .uri(&format!("/api-keys/{}", issued.id))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::get().uri("/api-keys").to_request();
+    let keys: Vec<ApiKey> = call_and_read_body_json(&app, req).await;
+    assert!(keys[0].revoked);
+    assert!(keys[0].last_used_at.is_some());
+    let req = TestRequest::post()
+        .uri("/scale/readings")
+        .insert_header((API_KEY_HEADER, issued.key.as_str()))
+        .set_json(reading("TAG-1", 33.0, "2026-03-10T08:00:00"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use backend::scheduler::{evaluate_rules, next_due_date};
+use chrono::NaiveDate;
+use rusqlite::params;
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn test_next_due_date() {
+    let today = date("2025-06-01");
+
+    // First occurrence is due immediately
+    assert_eq!(next_due_date(None, 90, 0, today), Some(today));
+    // Not yet within the lead window
+    assert_eq!(next_due_date(Some(date("2025-05-01")), 90, 7, today), None);
+    // Inside the lead window
+    assert_eq!(
+        next_due_date(Some(date("2025-03-10")), 90, 7, today),
+        Some(date("2025-06-08"))
+    );
+    // Overdue occurrences are moved to today instead of backfilled
+    assert_eq!(
+        next_due_date(Some(date("2024-01-01")), 90, 0, today),
+        Some(today)
+    );
+}
+
+#[test]
+fn test_evaluate_rules_creates_tasks_per_goat() {
+    let pool = common::temp_pool("evaluate_rules");
+    let mut conn = pool.get_conn().unwrap();
+
+    for name in ["Rani", "Moti"] {
+        conn.execute(
+            "INSERT INTO goats (breed, name, gender) VALUES ('Beetal', ?1, 'Female')",
+            params![name],
+        )
+        .unwrap();
+    }
+    conn.execute(
+        "INSERT INTO reminder_rules (name, task_title, scope, interval_days, lead_days) \
+         VALUES ('Deworming', 'Deworm', 'Goat', 90, 7)",
+        [],
+    )
+    .unwrap();
+
+    // One task and reminder per goat on first evaluation
+    assert_eq!(evaluate_rules(&mut conn, date("2025-01-01")).unwrap(), 2);
+    let reminders: i64 = conn
+        .query_row("SELECT COUNT(*) FROM reminders", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(reminders, 2);
+
+    // Re-running on the same day or before the lead window is a no-op
+    assert_eq!(evaluate_rules(&mut conn, date("2025-01-01")).unwrap(), 0);
+    assert_eq!(evaluate_rules(&mut conn, date("2025-03-20")).unwrap(), 0);
+
+    // Next occurrence is generated `lead_days` before it is due
+    assert_eq!(evaluate_rules(&mut conn, date("2025-03-25")).unwrap(), 2);
+    let latest: String = conn
+        .query_row("SELECT MAX(due_date) FROM tasks", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(latest, "2025-04-01");
+}
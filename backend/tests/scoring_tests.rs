@@ -0,0 +1,117 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use backend::scoring::{GoatMetrics, rate, score_goats};
+use chrono::{Days, Local};
+use serde_json::json;
+use shared::Gender;
+use shared::scoring::{GoatScore, Recommendation, ScoreWeights};
+
+fn metrics(name: &str, milk: Option<f64>, incidents: f64, profit: f64) -> GoatMetrics {
+    GoatMetrics {
+        name: name.to_string(),
+        gender: Gender::Female,
+        milk,
+        growth: None,
+        fertility: None,
+        health_incidents: incidents,
+        profit: Some(profit),
+    }
+}
+
+#[test]
+fn test_score_goats() {
+    assert_eq!(
+        rate(&[Some(1.0), None, Some(3.0), Some(2.0)], true),
+        vec![Some(0.0), None, Some(100.0), Some(50.0)]
+    );
+    assert_eq!(rate(&[Some(2.0), Some(2.0)], false), vec![Some(50.0); 2]);
+
+    let herd = [
+        metrics("Rani", Some(3.0), 0.0, 5000.0),
+        metrics("Meena", Some(2.0), 1.0, 2000.0),
+        metrics("Moti", Some(1.0), 4.0, -1000.0),
+        // No milk data; scored on health and profit only
+        metrics("Bholi", None, 0.0, 5000.0),
+    ];
+    let scores = score_goats(&herd, &ScoreWeights::default());
+    let ranked: Vec<(&str, Recommendation)> = scores
+        .iter()
+        .map(|s| (s.goat_name.as_str(), s.recommendation.clone()))
+        .collect();
+    assert_eq!(
+        ranked,
+        vec![
+            ("Moti", Recommendation::Cull),
+            ("Meena", Recommendation::Review),
+            ("Rani", Recommendation::Keep),
+            ("Bholi", Recommendation::Keep),
+        ]
+    );
+    assert_eq!(scores[3].breakdown.milk, None);
+    assert_eq!(scores[3].score, 100.0);
+
+    // Weighting milk only ranks purely on milk and drops goats without it
+    let milk_only = ScoreWeights {
+        milk: 1.0,
+        growth: 0.0,
+        fertility: 0.0,
+        health: 0.0,
+        profit: 0.0,
+    };
+    let scores = score_goats(&herd, &milk_only);
+    assert_eq!(scores.len(), 3);
+    assert_eq!(scores[1].score, 50.0);
+}
+
+#[actix_rt::test]
+async fn test_goat_scores_endpoint() {
+    let db_pool = common::temp_pool("scoring");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for name in ["Rani", "Moti"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    // Incidents count over the last year, so date them relative to today
+    let today = Local::now().date_naive();
+    for days_ago in [30, 90] {
+        let on = (today - Days::new(days_ago)).format("%Y-%m-%d").to_string();
+        let req = TestRequest::post()
+            .uri("/health/incidents")
+            .set_json(json!({
+                "id": null, "goat_name": "Moti", "space_id": null, "kind": "Disease",
+                "condition": "Pneumonia", "observed_on": on, "notes": null
+            }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    // Identical goats apart from Moti's incidents
+    let req = TestRequest::get()
+        .uri("/scoring/goats?milk=0&growth=0&fertility=0&profit=0&health=1")
+        .to_request();
+    let scores: Vec<GoatScore> = call_and_read_body_json(&app, req).await;
+    assert_eq!(scores.len(), 2);
+    assert_eq!(scores[0].goat_name, "Moti");
+    assert_eq!(scores[0].recommendation, Recommendation::Cull);
+    assert_eq!(scores[1].score, 100.0);
+
+    for uri in [
+        "/scoring/goats?milk=-1",
+        "/scoring/goats?milk=0&growth=0&fertility=0&health=0&profit=0",
+    ] {
+        let req = TestRequest::get().uri(uri).to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400);
+    }
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use serde_json::json;
+use shared::search::{SearchKind, SearchResult, fold_name, name_similarity, suggest_names};
+use shared::transliterate::{has_indic, romanize};
+
+#[test]
+fn test_fuzzy_name_matching() {
+    assert_eq!(fold_name("Lakshmi"), fold_name("Laxmi"));
+    assert_eq!(fold_name(" Mo-hee-ni "), "mohini");
+    assert_eq!(name_similarity("Laxmi", "lakshmi"), 1.0);
+    assert_eq!(name_similarity("Rani", "Rany"), 0.75);
+    assert_eq!(name_similarity("", "Rani"), 0.0);
+
+    let names = ["Lakshmi", "Laxmi Junior", "Rani", "Ranu", "Moti"];
+    assert_eq!(suggest_names("laxmi", names), vec!["Lakshmi"]);
+    assert_eq!(suggest_names("Rany", names), vec!["Rani", "Ranu"]);
+    assert!(suggest_names("Ganga", names).is_empty());
+}
+
+#[test]
+fn test_romanize_indic_scripts() {
+    assert_eq!(romanize("लक्ष्मी"), "lakshmii");
+    assert_eq!(romanize("राम"), "raam");
+    assert_eq!(romanize("गंगा"), "gangaa");
+    assert_eq!(romanize("क"), "ka");
+    // Bengali and Gujarati share Devanagari's layout
+    assert_eq!(romanize("রানী"), "raanii");
+    assert_eq!(romanize("મોતી 2"), "motii 2");
+    assert_eq!(romanize("Moti"), "Moti");
+    assert!(has_indic("Rani रानी") && !has_indic("Rani"));
+
+    assert_eq!(fold_name("लक्ष्मी"), fold_name("Laxmi"));
+    assert_eq!(suggest_names("Rany", ["रानी", "मोती"]), vec!["रानी"]);
+}
+
+#[actix_rt::test]
+async fn test_quick_search() {
+    let db_pool = common::temp_pool("search");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let mut sirohi = common::sample_goat("Moti");
+    sirohi["breed"] = json!("Sirohi");
+    sirohi["coat_color"] = json!("Black");
+    sirohi["marks"] = json!("Notched left ear");
+    for goat in [
+        common::sample_goat("Ganga"),
+        common::sample_goat("Rani"),
+        sirohi,
+    ] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(&goat)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    let conn = db_pool.get_conn().unwrap();
+    conn.execute_batch(
+        "INSERT INTO tasks (title, notes, due_date) VALUES ('Deworm Rani', NULL, '2025-07-01');
+         INSERT INTO tasks (title, notes, due_date) VALUES ('Trim hooves', 'Rani limps', '2025-06-01');
+         INSERT INTO transactions (kind, category, amount, description, date, currency, exchange_rate)
+             VALUES ('Expense', 'Veterinary', 50, 'Rani checkup, 50% off', '2025-06-10', 'USD', 83);",
+    )
+    .unwrap();
+
+    let search = |q: &str| {
+        TestRequest::get()
+            .uri(&format!("/search?q={}", q))
+            .to_request()
+    };
+    let results: Vec<SearchResult> = call_and_read_body_json(&app, search("rAn")).await;
+    let hits: Vec<(SearchKind, &str)> =
+        results.iter().map(|r| (r.kind, r.title.as_str())).collect();
+    // "Rani" matches at the start of the name, "Ganga" not at all
+    assert_eq!(
+        hits,
+        vec![
+            (SearchKind::Goat, "Rani"),
+            (SearchKind::Task, "Deworm Rani"),
+            (SearchKind::Task, "Trim hooves"),
+            (SearchKind::Transaction, "Veterinary: Rani checkup, 50% off"),
+        ]
+    );
+    assert_eq!(results[0].detail, "Beetal, Female");
+    assert_eq!(results[0].anchor(), format!("goat-{}", results[0].id));
+    assert_eq!(results[1].detail, "Pending, due 2025-07-01");
+    assert_eq!(results[3].detail, "Expense of 50.00 USD on 2025-06-10");
+
+    let results: Vec<SearchResult> = call_and_read_body_json(&app, search("siro")).await;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].title, "Moti");
+
+    // Coat color and marks are searched and shown to tell goats apart
+    let results: Vec<SearchResult> = call_and_read_body_json(&app, search("notch")).await;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].detail, "Sirohi, Female, Black, Notched left ear");
+
+    // Misspelt names suggest goats with similar names
+    let results: Vec<SearchResult> = call_and_read_body_json(&app, search("Raani")).await;
+    let hits: Vec<(&str, bool)> = results
+        .iter()
+        .map(|r| (r.title.as_str(), r.suggested))
+        .collect();
+    assert_eq!(hits, vec![("Rani", true)]);
+    let results: Vec<SearchResult> = call_and_read_body_json(&app, search("rani")).await;
+    assert!(results.iter().all(|r| !r.suggested));
+
+    // Names match across scripts through their transliteration
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("लक्ष्मी"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    // The last query is "लक्ष्मी", percent-encoded
+    for q in ["lakshmi", "laxmi", "%E0%A4%B2%E0%A4%95%E0%A5%8D%E0%A4%B7%E0%A5%8D%E0%A4%AE%E0%A5%80"] {
+        let results: Vec<SearchResult> = call_and_read_body_json(&app, search(q)).await;
+        let hits: Vec<(&str, bool)> = results
+            .iter()
+            .map(|r| (r.title.as_str(), r.suggested))
+            .collect();
+        assert_eq!(hits, vec![("लक्ष्मी", false)], "{}", q);
+    }
+    // "रानी", percent-encoded
+    let results: Vec<SearchResult> =
+        call_and_read_body_json(&app, search("%E0%A4%B0%E0%A4%BE%E0%A4%A8%E0%A5%80")).await;
+    assert_eq!(results[0].title, "Rani");
+    assert!(!results[0].suggested);
+
+    // LIKE wildcards match literally
+    let results: Vec<SearchResult> = call_and_read_body_json(&app, search("50%25")).await;
+    assert_eq!(results.len(), 1);
+    let results: Vec<SearchResult> = call_and_read_body_json(&app, search("a_i")).await;
+    assert!(results.is_empty());
+
+    let results: Vec<SearchResult> = call_and_read_body_json(&app, search("r")).await;
+    assert!(results.is_empty());
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::auth::API_KEY_HEADER;
+use backend::routes;
+use serde_json::json;
+use shared::scale::IssuedApiKey;
+use shared::sensors::{
+    Sensor, SensorCondition, SensorKind, SensorReading, ThresholdAlert, Thresholds,
+};
+
+#[test]
+fn test_threshold_check() {
+    let range = Thresholds {
+        min: Some(5.0),
+        max: Some(30.0),
+    };
+    assert_eq!(range.check(4.9), Some(ThresholdAlert::Low));
+    assert_eq!(range.check(30.0), None);
+    assert_eq!(range.check(31.0), Some(ThresholdAlert::High));
+    assert_eq!(Thresholds::default().check(-40.0), None);
+    assert!(
+        Thresholds {
+            min: Some(80.0),
+            max: Some(20.0)
+        }
+        .validate()
+        .is_err()
+    );
+    assert_eq!(SensorKind::from_str("Temp Sensor"), SensorKind::Temperature);
+}
+
+#[actix_rt::test]
+async fn test_sensor_readings_and_conditions() {
+    let db_pool = common::temp_pool("sensors");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::post()
+        .uri("/spaces")
+        .set_json(json!({ "id": null, "name": "Main Barn", "kind": "Enclosure", "capacity": null }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let sensor = |name: &str, kind: &str, min: f64, max: f64| {
+        json!({
+            "id": null, "name": name, "kind": kind, "space_id": 1,
+            "thresholds": { "min": min, "max": max }
+        })
+    };
+    let req = TestRequest::post()
+        .uri("/sensors")
+        .set_json(sensor("Barn temp", "Temperature", 5.0, 30.0))
+        .to_request();
+    let temp: Sensor = call_and_read_body_json(&app, req).await;
+    assert_eq!(temp.space_name.as_deref(), Some("Main Barn"));
+    let req = TestRequest::post()
+        .uri("/sensors")
+        .set_json(sensor("Barn humidity", "Humidity", 30.0, 80.0))
+        .to_request();
+    let humidity: Sensor = call_and_read_body_json(&app, req).await;
+    let req = TestRequest::post()
+        .uri("/sensors")
+        .set_json(sensor("Broken", "Humidity", 80.0, 30.0))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::post()
+        .uri("/sensors")
+        .set_json(json!({
+            "id": null, "name": "Gate cam", "kind": { "Other": "Camera" }, "space_id": null
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let reading = |sensor: &Sensor, value: f64, at: &str| {
+        json!({ "sensor_id": sensor.id, "value": value, "read_at": at })
+    };
+    let req = TestRequest::post()
+        .uri("/sensors/readings")
+        .set_json(reading(&temp, 20.0, "2026-06-01T12:00:00"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+
+    let req = TestRequest::post()
+        .uri("/api-keys")
+        .set_json(json!({ "name": "Barn gateway" }))
+        .to_request();
+    let key: IssuedApiKey = call_and_read_body_json(&app, req).await;
+    for (sensor, value, at) in [
+        (&temp, 22.0, "2026-06-01T12:00:00"),
+        (&temp, 34.5, "2026-06-02T12:00:00"),
+        // Arrives late; must not replace the latest reading
+        (&temp, 18.0, "2026-06-01T06:00:00"),
+        (&humidity, 55.0, "2026-06-02T12:00:00"),
+    ] {
+        let req = TestRequest::post()
+            .uri("/sensors/readings")
+            .insert_header((API_KEY_HEADER, key.key.as_str()))
+            .set_json(reading(sensor, value, at))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    let req = TestRequest::post()
+        .uri("/sensors/readings")
+        .insert_header((API_KEY_HEADER, key.key.as_str()))
+        .set_json(json!({ "sensor_id": 99, "value": 1.0 }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let req = TestRequest::get().uri("/sensors/conditions").to_request();
+    let conditions: Vec<SensorCondition> = call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = conditions.iter().map(|c| c.sensor.name.as_str()).collect();
+    assert_eq!(names, vec!["Barn humidity", "Barn temp"]);
+    let temp_now = &conditions[1];
+    assert_eq!(temp_now.latest.as_ref().unwrap().value, 34.5);
+    assert_eq!(temp_now.alert, Some(ThresholdAlert::High));
+    assert_eq!(conditions[0].alert, None);
+
+    // Raising the limit clears the alert
+    let req = TestRequest::put()
+        .uri(&format!("/sensors/{}/thresholds", temp.id.unwrap()))
+        .set_json(json!({ "min": 5.0, "max": 38.0 }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::get().uri("/sensors/conditions").to_request();
+    let conditions: Vec<SensorCondition> = call_and_read_body_json(&app, req).await;
+    assert_eq!(conditions[1].alert, None);
+
+    let req = TestRequest::get()
+        .uri(&format!(
+            "/sensors/readings?sensor_id={}&from=2026-06-01&to=2026-06-01",
+            temp.id.unwrap()
+        ))
+        .to_request();
+    let series: Vec<SensorReading> = call_and_read_body_json(&app, req).await;
+    let values: Vec<f64> = series.iter().map(|r| r.value).collect();
+    assert_eq!(values, vec![18.0, 22.0]);
+}
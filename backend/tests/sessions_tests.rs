@@ -0,0 +1,525 @@
+mod common;
+
+use actix_web::http::header;
+use actix_web::middleware::from_fn;
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::auth::{
+    GoogleOAuth, OAuthProvider, OAuthProviders, check_access_token, check_session,
+    ensure_owner_password,
+};
+use backend::routes;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
+use percent_encoding::percent_decode_str;
+use serde_json::json;
+use shared::sessions::{
+    IssuedSession, OAuthRedirect, SESSION_HEADER, Session, describe_user_agent,
+};
+use shared::tokens::IssuedAccessToken;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, mpsc};
+
+#[test]
+fn test_describe_user_agent() {
+    let firefox = "Mozilla/5.0 (Android 14; Mobile; rv:131.0) Gecko/131.0 Firefox/131.0";
+    assert_eq!(describe_user_agent(firefox), "Firefox on Android");
+    let edge = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 \
+                (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36 Edg/130.0.0.0";
+    assert_eq!(describe_user_agent(edge), "Edge on Windows");
+    assert_eq!(describe_user_agent("curl/8.5.0"), "Unknown device");
+}
+
+#[actix_rt::test]
+async fn test_revoked_sessions_are_refused() {
+    let db_pool = common::temp_pool("sessions");
+    let conn = db_pool.get_conn().unwrap();
+    ensure_owner_password(&conn, Some("correct horse")).unwrap();
+    drop(conn);
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let sign_in = |device: &str| {
+        TestRequest::post()
+            .uri("/sessions")
+            .insert_header((header::USER_AGENT, "Mozilla/5.0 (iPhone) Safari/604.1"))
+            .set_json(json!({ "device": device, "secret": "correct horse" }))
+            .to_request()
+    };
+    let laptop: IssuedSession = call_and_read_body_json(&app, sign_in("Office laptop")).await;
+    let phone: IssuedSession = call_and_read_body_json(&app, sign_in("")).await;
+    assert_eq!(phone.session.device, "Safari on iPhone");
+    assert!(phone.session.current);
+
+    let list = |token: &str| {
+        TestRequest::get()
+            .uri("/sessions")
+            .insert_header((SESSION_HEADER, token))
+            .to_request()
+    };
+    let sessions: Vec<Session> = call_and_read_body_json(&app, list(&laptop.token)).await;
+    assert_eq!(sessions.len(), 2);
+    let current: Vec<&str> = sessions
+        .iter()
+        .filter(|s| s.current)
+        .map(|s| s.device.as_str())
+        .collect();
+    assert_eq!(current, vec!["Office laptop"]);
+
+    // The laptop signs the phone out
+    let req = TestRequest::delete()
+        .uri(&format!("/sessions/{}", phone.session.id))
+        .insert_header((SESSION_HEADER, laptop.token.as_str()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let resp = call_service(&app, list(&phone.token)).await;
+    assert_eq!(resp.status(), 401);
+    let req = TestRequest::get()
+        .uri("/goats")
+        .insert_header((SESSION_HEADER, phone.token.as_str()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+    let req = TestRequest::get()
+        .uri("/goats")
+        .insert_header((SESSION_HEADER, "yagi_forged"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+    let req = TestRequest::get().uri("/goats").to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+
+    // Revoked twice is an error; the phone may sign in again
+    let req = TestRequest::delete()
+        .uri(&format!("/sessions/{}", phone.session.id))
+        .insert_header((SESSION_HEADER, laptop.token.as_str()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::post()
+        .uri("/sessions")
+        .insert_header((SESSION_HEADER, phone.token.as_str()))
+        .set_json(json!({ "device": "Barn phone", "secret": "correct horse" }))
+        .to_request();
+    let phone: IssuedSession = call_and_read_body_json(&app, req).await;
+
+    let req = TestRequest::delete()
+        .uri("/sessions")
+        .insert_header((SESSION_HEADER, phone.token.as_str()))
+        .to_request();
+    let revoked: usize = call_and_read_body_json(&app, req).await;
+    assert_eq!(revoked, 1);
+    let sessions: Vec<Session> = call_and_read_body_json(&app, list(&phone.token)).await;
+    let devices: Vec<&str> = sessions.iter().map(|s| s.device.as_str()).collect();
+    assert_eq!(devices, vec!["Barn phone"]);
+    assert_eq!(call_service(&app, list(&laptop.token)).await.status(), 401);
+}
+
+#[actix_rt::test]
+async fn test_sign_in_checks_credentials() {
+    let db_pool = common::temp_pool("sessions_credentials");
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let sign_in = |body: serde_json::Value| {
+        TestRequest::post()
+            .uri("/sessions")
+            .set_json(body)
+            .to_request()
+    };
+
+    // Nobody signs in as the owner until the password is set up
+    let resp = call_service(&app, sign_in(json!({ "secret": "correct horse" }))).await;
+    assert_eq!(resp.status(), 401);
+    let conn = db_pool.get_conn().unwrap();
+    assert!(ensure_owner_password(&conn, Some("short")).is_err());
+    ensure_owner_password(&conn, Some("correct horse")).unwrap();
+    // Once set, setup leaves it alone
+    assert_eq!(ensure_owner_password(&conn, None).unwrap(), None);
+    drop(conn);
+    let owner: IssuedSession =
+        call_and_read_body_json(&app, sign_in(json!({ "secret": "correct horse" }))).await;
+    assert_eq!(owner.session.worker, None);
+    let resp = call_service(&app, sign_in(json!({ "secret": "wrong horse" }))).await;
+    assert_eq!(resp.status(), 401);
+    let resp = call_service(&app, sign_in(json!({ "device": "Laptop" }))).await;
+    assert_eq!(resp.status(), 400);
+
+    let as_owner = |req: TestRequest| {
+        req.insert_header((SESSION_HEADER, owner.token.as_str()))
+            .to_request()
+    };
+    let req = TestRequest::post().uri("/workers").set_json(json!({
+        "id": null, "name": "Asha", "role": "Herder",
+        "contact": null, "notify_by": "App"
+    }));
+    assert_eq!(call_service(&app, as_owner(req)).await.status(), 201);
+
+    // Asha cannot sign in until she has a PIN
+    let asha = |pin: &str| sign_in(json!({ "worker": "asha", "secret": pin }));
+    assert_eq!(call_service(&app, asha("4321")).await.status(), 401);
+    let set_pin = |pin: &str| {
+        TestRequest::put()
+            .uri("/workers/1/pin")
+            .set_json(json!({ "pin": pin }))
+    };
+    for invalid in ["12", "12a4", "123456789"] {
+        let resp = call_service(&app, as_owner(set_pin(invalid))).await;
+        assert_eq!(resp.status(), 400);
+    }
+    let resp = call_service(&app, as_owner(set_pin("4321"))).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(call_service(&app, asha("1234")).await.status(), 401);
+    let resp = call_service(&app, sign_in(json!({ "worker": "Ravi", "secret": "4321" }))).await;
+    assert_eq!(resp.status(), 401);
+    let issued: IssuedSession = call_and_read_body_json(&app, asha("4321")).await;
+    assert_eq!(issued.session.worker.as_deref(), Some("Asha"));
+
+    // Only the owner sets PINs and changes the password
+    let req = set_pin("1111")
+        .insert_header((SESSION_HEADER, issued.token.as_str()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 403);
+    let change = |current: &str| {
+        TestRequest::put()
+            .uri("/sessions/password")
+            .set_json(json!({ "current": current, "password": "battery staple" }))
+    };
+    let req = change("correct horse")
+        .insert_header((SESSION_HEADER, issued.token.as_str()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 403);
+    let resp = call_service(&app, as_owner(change("wrong horse"))).await;
+    assert_eq!(resp.status(), 401);
+    let resp = call_service(&app, as_owner(change("correct horse"))).await;
+    assert_eq!(resp.status(), 200);
+    let resp = call_service(&app, sign_in(json!({ "secret": "battery staple" }))).await;
+    assert_eq!(resp.status(), 201);
+}
+
+#[actix_rt::test]
+async fn test_repeated_failures_lock_the_account() {
+    let db_pool = common::temp_pool("sessions_lockout");
+    let conn = db_pool.get_conn().unwrap();
+    let generated = ensure_owner_password(&conn, None).unwrap().unwrap();
+    drop(conn);
+    common::add_worker(&db_pool, "Asha", Some("Herder"), None, "App");
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let sign_in = |worker: Option<&str>, secret: &str| {
+        TestRequest::post()
+            .uri("/sessions")
+            .set_json(json!({ "worker": worker, "secret": secret }))
+            .to_request()
+    };
+
+    // Guessing Asha's PIN locks her account, but not the owner's
+    for pin in ["0000", "1111", "2222", "3333", "4444"] {
+        let resp = call_service(&app, sign_in(Some("Asha"), pin)).await;
+        assert_eq!(resp.status(), 401);
+    }
+    let resp = call_service(&app, sign_in(Some("asha"), "5555")).await;
+    assert_eq!(resp.status(), 429);
+    let resp = call_service(&app, sign_in(None, &generated)).await;
+    assert_eq!(resp.status(), 201);
+
+    // Unknown workers are counted too
+    for _ in 0..5 {
+        let resp = call_service(&app, sign_in(Some("Ravi"), "1234")).await;
+        assert_eq!(resp.status(), 401);
+    }
+    let resp = call_service(&app, sign_in(Some("Ravi"), "1234")).await;
+    assert_eq!(resp.status(), 429);
+
+    // Failures age out of the window
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "UPDATE sign_in_attempts SET attempted_at = datetime('now', '-1 hour')",
+            [],
+        )
+        .unwrap();
+    let resp = call_service(&app, sign_in(Some("Asha"), "5555")).await;
+    assert_eq!(resp.status(), 401);
+}
+
+#[actix_rt::test]
+async fn test_workers_manage_only_their_own_sessions() {
+    let db_pool = common::temp_pool("sessions_own");
+    let owner = common::owner_token(&db_pool);
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(check_session))
+            .wrap(from_fn(check_access_token))
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+    let as_owner = |req: TestRequest| {
+        req.insert_header((SESSION_HEADER, owner.as_str()))
+            .to_request()
+    };
+    let req = TestRequest::post().uri("/workers").set_json(json!({
+        "id": null, "name": "Asha", "role": "Herder",
+        "contact": null, "notify_by": "App"
+    }));
+    assert_eq!(call_service(&app, as_owner(req)).await.status(), 201);
+    let req = TestRequest::put()
+        .uri("/workers/1/pin")
+        .set_json(json!({ "pin": "482913" }));
+    assert_eq!(call_service(&app, as_owner(req)).await.status(), 200);
+    let asha_sign_in = |device: &str| {
+        TestRequest::post()
+            .uri("/sessions")
+            .set_json(json!({ "device": device, "worker": "Asha", "secret": "482913" }))
+            .to_request()
+    };
+    let phone: IssuedSession = call_and_read_body_json(&app, asha_sign_in("Barn phone")).await;
+    let tablet: IssuedSession = call_and_read_body_json(&app, asha_sign_in("Tablet")).await;
+    let as_asha = |req: TestRequest| {
+        req.insert_header((SESSION_HEADER, phone.token.as_str()))
+            .to_request()
+    };
+
+    // Asha sees her own devices, the owner everyone's
+    let req = TestRequest::get().uri("/sessions");
+    let sessions: Vec<Session> = call_and_read_body_json(&app, as_asha(req)).await;
+    let devices: Vec<&str> = sessions.iter().map(|s| s.device.as_str()).collect();
+    assert_eq!(devices, vec!["Tablet", "Barn phone"]);
+    let req = TestRequest::get().uri("/sessions");
+    let sessions: Vec<Session> = call_and_read_body_json(&app, as_owner(req)).await;
+    assert_eq!(sessions.len(), 3);
+    let owner_session = sessions.iter().find(|s| s.worker.is_none()).unwrap().id;
+
+    // Asha cannot sign the owner out, one device at a time or all at once
+    let req = TestRequest::delete().uri(&format!("/sessions/{}", owner_session));
+    assert_eq!(call_service(&app, as_asha(req)).await.status(), 403);
+    let req = TestRequest::delete().uri("/sessions");
+    let revoked: usize = call_and_read_body_json(&app, as_asha(req)).await;
+    assert_eq!(revoked, 1);
+    let req = TestRequest::get()
+        .uri("/sessions")
+        .insert_header((SESSION_HEADER, tablet.token.as_str()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+    let req = TestRequest::get().uri("/sessions");
+    assert_eq!(call_service(&app, as_owner(req)).await.status(), 200);
+
+    // An access token lists sessions but cannot sign out "the others"
+    let req = TestRequest::post()
+        .uri("/tokens")
+        .set_json(json!({ "name": "Audit script" }));
+    let issued: IssuedAccessToken = call_and_read_body_json(&app, as_owner(req)).await;
+    let with_token = |req: TestRequest| {
+        req.insert_header(("Authorization", format!("Bearer {}", issued.token)))
+            .to_request()
+    };
+    let req = TestRequest::get().uri("/sessions");
+    let sessions: Vec<Session> = call_and_read_body_json(&app, with_token(req)).await;
+    assert_eq!(sessions.len(), 2);
+    let req = TestRequest::delete().uri("/sessions");
+    assert_eq!(call_service(&app, with_token(req)).await.status(), 401);
+    let req = TestRequest::get().uri("/sessions");
+    assert_eq!(call_service(&app, as_owner(req)).await.status(), 200);
+
+    // The owner signs Asha out
+    let req = TestRequest::delete().uri(&format!("/sessions/{}", phone.session.id));
+    assert_eq!(call_service(&app, as_owner(req)).await.status(), 200);
+}
+
+/// Private key the fake Google endpoint signs ID tokens with.
+const GOOGLE_SIGNING_KEY: &str = include_str!("common/google_signing_key.pem");
+
+/// Modulus of `GOOGLE_SIGNING_KEY`, base64url encoded as in a JWK.
+const GOOGLE_SIGNING_MODULUS: &str = "2H8xSltcPotqIuvYK9gT0DHXeXlON_qrRHC4AL2S8amzxHUK2zFEjDRNW23ZToIlTknIboc4bbs6lpvgbW9JN7egwzZveqvY0lRulVQnKwrUshuTt4mABLdTtMjWwuNAwAwBikqUtmDD3pp8ABGPmp5mZPMU-uyrQVWc6sWzW2H65ONLvnlHHTyYdm6kYbg55sbrRlEHbTG5isadhzDaqCi2UI3qb_skUpv9EoaUYI8AcAt7y-9FPO20u-ZLWpB9a4qVS6YVjeLHCC6MfA1_HnroiiMwZsJDSJCxFMY5ZpNKsX9aVf9NKyHGNPEyem3wnelWvoqc6qed79eUHy96LQ";
+
+/// An ID token for `email` signed like Google would with key `test-key`.
+fn google_id_token(email: &str) -> String {
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some("test-key".into());
+    let claims = json!({
+        "iss": "https://accounts.google.com",
+        "aud": "client1",
+        "sub": format!("sub-{}", email),
+        "email": email,
+        "email_verified": true,
+        "exp": 4102444800i64,
+    });
+    let key = EncodingKey::from_rsa_pem(GOOGLE_SIGNING_KEY.as_bytes()).unwrap();
+    encode(&header, &claims, &key).unwrap()
+}
+
+/// Answers one code exchange per entry of `id_tokens`, like Google's token
+/// endpoint would, and serves the key `test-key` at `/certs`. Hands back
+/// the token and certs URLs and the raw code exchanges received.
+fn fake_google(id_tokens: Vec<String>) -> (String, String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let (sender, received) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut id_tokens = id_tokens.into_iter();
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            loop {
+                let n = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some(head_end) = text.find("\r\n\r\n") {
+                    let length: usize = text
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse().ok())?
+                        })
+                        .unwrap_or(0);
+                    if request.len() >= head_end + 4 + length {
+                        break;
+                    }
+                }
+            }
+            let request = String::from_utf8(request).unwrap();
+            let body = if request.starts_with("GET /certs ") {
+                json!({ "keys": [{
+                    "kty": "RSA", "alg": "RS256", "use": "sig", "kid": "test-key",
+                    "n": GOOGLE_SIGNING_MODULUS, "e": "AQAB"
+                }] })
+            } else {
+                let id_token = id_tokens.next().unwrap();
+                sender.send(request).unwrap();
+                json!({ "access_token": "ya29", "id_token": id_token })
+            };
+            let response = format!(
+                "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n{}",
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    (
+        format!("http://{}/token", address),
+        format!("http://{}/certs", address),
+        received,
+    )
+}
+
+#[actix_rt::test]
+async fn test_google_sign_in_links_workers_by_email() {
+    let db_pool = common::temp_pool("sessions_oauth");
+    let owner = common::owner_token(&db_pool);
+    // The last token claims to be Asha's from another account, keeping
+    // the signature of her real one
+    let asha = google_id_token("asha@example.com");
+    let claims = json!({
+        "iss": "https://accounts.google.com",
+        "aud": "client1",
+        "sub": "sub-mallory",
+        "email": "asha@example.com",
+        "email_verified": true,
+        "exp": 4102444800i64,
+    });
+    let mut forged: Vec<String> = asha.split('.').map(String::from).collect();
+    forged[1] = URL_SAFE_NO_PAD.encode(claims.to_string());
+    let (token_url, certs_url, received) = fake_google(vec![
+        asha,
+        google_id_token("stranger@example.com"),
+        forged.join("."),
+    ]);
+    let google: Arc<dyn OAuthProvider> = Arc::new(
+        GoogleOAuth::new("client1", "secret")
+            .with_endpoints(&token_url, &certs_url)
+            .unwrap(),
+    );
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(check_session))
+            .app_data(web::Data::new(db_pool))
+            .app_data(web::Data::new(OAuthProviders(vec![google])))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::post()
+        .uri("/workers")
+        .set_json(json!({
+            "id": null, "name": "Asha", "role": null,
+            "contact": "Asha@Example.com", "notify_by": "Email"
+        }))
+        .insert_header((SESSION_HEADER, owner.as_str()))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::get().uri("/sessions/oauth").to_request();
+    let providers: Vec<String> = call_and_read_body_json(&app, req).await;
+    assert_eq!(providers, vec!["google"]);
+
+    let start = |provider: &str| {
+        TestRequest::post()
+            .uri(&format!("/sessions/oauth/{}", provider))
+            .set_json(json!({ "redirect_uri": "http://127.0.0.1:8080/", "device": "Barn phone" }))
+            .to_request()
+    };
+    assert_eq!(call_service(&app, start("github")).await.status(), 400);
+    let redirect: OAuthRedirect = call_and_read_body_json(&app, start("google")).await;
+    assert!(
+        redirect
+            .authorize_url
+            .starts_with("https://accounts.google.com/o/oauth2/v2/auth?client_id=client1&")
+    );
+    let state_of = |redirect: &OAuthRedirect| {
+        let state = redirect.authorize_url.split("state=").nth(1).unwrap();
+        let state = state.split('&').next().unwrap();
+        percent_decode_str(state).decode_utf8().unwrap().to_string()
+    };
+    let callback = |state: &str| {
+        TestRequest::post()
+            .uri("/sessions/oauth/callback")
+            .set_json(json!({ "code": "4/abc", "state": state }))
+            .to_request()
+    };
+
+    let state = state_of(&redirect);
+    let issued: IssuedSession = call_and_read_body_json(&app, callback(&state)).await;
+    assert_eq!(issued.session.device, "Barn phone");
+    assert_eq!(issued.session.worker.as_deref(), Some("Asha"));
+    let request = received.recv().unwrap();
+    assert!(request.starts_with("POST /token HTTP/1.1\r\n"));
+    assert!(request.contains("code=4%2Fabc&client_id=client1&"));
+    assert!(request.ends_with("&grant_type=authorization%5Fcode"));
+
+    // A state is good for one try
+    assert_eq!(call_service(&app, callback(&state)).await.status(), 400);
+
+    // Nobody on the farm has this email
+    let redirect: OAuthRedirect = call_and_read_body_json(&app, start("google")).await;
+    let resp = call_service(&app, callback(&state_of(&redirect))).await;
+    assert_eq!(resp.status(), 403);
+
+    // A token whose signature does not match its claims is refused
+    let redirect: OAuthRedirect = call_and_read_body_json(&app, start("google")).await;
+    let resp = call_service(&app, callback(&state_of(&redirect))).await;
+    assert_eq!(resp.status(), 502);
+
+    let req = TestRequest::get()
+        .uri("/sessions")
+        .insert_header((SESSION_HEADER, issued.token.as_str()))
+        .to_request();
+    let sessions: Vec<Session> = call_and_read_body_json(&app, req).await;
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].worker.as_deref(), Some("Asha"));
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use serde_json::json;
+use shared::settings::FarmSettings;
+use shared::setup::SetupStep;
+
+#[test]
+fn test_setup_steps_in_order() {
+    assert_eq!(SetupStep::Farm.next(), SetupStep::Preferences);
+    assert_eq!(SetupStep::Goats.next(), SetupStep::Done);
+    assert_eq!(SetupStep::Done.next(), SetupStep::Done);
+    assert_eq!(SetupStep::Pens.previous(), SetupStep::Preferences);
+    assert_eq!(SetupStep::Farm.previous(), SetupStep::Farm);
+    for step in SetupStep::ALL {
+        assert_eq!(SetupStep::from_str(SetupStep::to_str(&step)), Ok(step));
+    }
+}
+
+#[actix_rt::test]
+async fn test_setup_progress_is_kept_in_settings() {
+    let db_pool = common::temp_pool("setup");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    // A new farm starts setup
+    let req = TestRequest::get().uri("/settings").to_request();
+    let settings: FarmSettings = call_and_read_body_json(&app, req).await;
+    assert_eq!(settings.setup_step, SetupStep::Farm);
+    assert_eq!(settings.language, "en");
+    assert_eq!(settings.farm_name, None);
+
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({ "language": "xx", "setup_step": "Preferences" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({
+            "farm_name": " Green Valley ",
+            "base_currency": "inr",
+            "language": "hi",
+            "timezone": "Asia/Kolkata",
+            "setup_step": "Pens"
+        }))
+        .to_request();
+    let saved: FarmSettings = call_and_read_body_json(&app, req).await;
+    assert_eq!(saved.farm_name.as_deref(), Some("Green Valley"));
+    assert_eq!(saved.language, "hi");
+    assert_eq!(saved.setup_step, SetupStep::Pens);
+    let req = TestRequest::get().uri("/settings").to_request();
+    let loaded: FarmSettings = call_and_read_body_json(&app, req).await;
+    assert_eq!(loaded, saved);
+}
+
+#[actix_rt::test]
+async fn test_farm_with_goats_skips_setup() {
+    let db_pool = common::temp_pool("setup_existing");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::get().uri("/settings").to_request();
+    let settings: FarmSettings = call_and_read_body_json(&app, req).await;
+    assert_eq!(settings.setup_step, SetupStep::Done);
+}
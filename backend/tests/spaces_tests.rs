@@ -0,0 +1,122 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use serde_json::json;
+use shared::spaces::{SpaceKind, SpaceOccupancy};
+
+#[test]
+fn test_space_occupancy() {
+    let pen = |capacity, area, goats| {
+        SpaceOccupancy::new(
+            1,
+            "North Pen".into(),
+            SpaceKind::Enclosure,
+            capacity,
+            area,
+            goats,
+        )
+    };
+    let roomy = pen(Some(10), Some(20.0), 5);
+    assert_eq!(roomy.recommended_max, Some(13));
+    assert_eq!(roomy.occupancy_percent, Some(50.0));
+    assert!(roomy.warnings.is_empty());
+
+    // Within capacity but too little floor for each goat
+    let cramped = pen(Some(10), Some(12.0), 9);
+    assert_eq!(cramped.warnings.len(), 1);
+    assert!(cramped.warnings[0].contains("1.3 m²"));
+
+    let over = pen(Some(4), None, 5);
+    assert_eq!(over.occupancy_percent, Some(125.0));
+    assert_eq!(
+        over.warnings,
+        vec!["North Pen holds 5 goats but has room for 4"]
+    );
+
+    // Without a capacity the area sets the limit
+    let field = SpaceOccupancy::new(
+        2,
+        "Hill".into(),
+        SpaceKind::GrazingField,
+        None,
+        Some(5000.0),
+        4,
+    );
+    assert_eq!(field.recommended_max, Some(5));
+    assert_eq!(field.occupancy_percent, Some(80.0));
+
+    let unknown = SpaceOccupancy::new(3, "Shed".into(), SpaceKind::Other, None, Some(30.0), 7);
+    assert_eq!(unknown.recommended_max, None);
+    assert_eq!(unknown.occupancy_percent, None);
+    assert!(unknown.warnings.is_empty());
+}
+
+#[actix_rt::test]
+async fn test_pen_capacity_endpoints() {
+    let db_pool = common::temp_pool("spaces");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    for name in ["Rani", "Meena", "Moti"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    for space in [
+        json!({ "id": null, "name": "North Pen", "kind": "Enclosure", "capacity": 2, "area_m2": 4.0 }),
+        json!({ "id": null, "name": "Kid Pen", "kind": "Enclosure", "capacity": null }),
+    ] {
+        let req = TestRequest::post()
+            .uri("/spaces")
+            .set_json(space)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    let req = TestRequest::post()
+        .uri("/spaces")
+        .set_json(json!({ "id": null, "name": "Nowhere", "kind": "Other", "capacity": 1, "area_m2": 0.0 }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let req = TestRequest::put()
+        .uri("/spaces/1/goats")
+        .set_json(json!(["Rani", "Meena"]))
+        .to_request();
+    let occupancy: SpaceOccupancy = call_and_read_body_json(&app, req).await;
+    assert_eq!(occupancy.goats, 2);
+    assert_eq!(occupancy.occupancy_percent, Some(100.0));
+    assert!(occupancy.warnings.is_empty());
+
+    // Overstocking is allowed but reported
+    let req = TestRequest::put()
+        .uri("/spaces/1/goats")
+        .set_json(json!(["Moti"]))
+        .to_request();
+    let occupancy: SpaceOccupancy = call_and_read_body_json(&app, req).await;
+    assert_eq!(occupancy.goats, 3);
+    assert_eq!(occupancy.warnings.len(), 2);
+
+    let req = TestRequest::get().uri("/spaces/occupancy").to_request();
+    let all: Vec<SpaceOccupancy> = call_and_read_body_json(&app, req).await;
+    let summary: Vec<_> = all
+        .iter()
+        .map(|o| (o.name.as_str(), o.goats, o.area_m2))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![("Kid Pen", 0, None), ("North Pen", 3, Some(4.0))]
+    );
+    assert_eq!(all[0].occupancy_percent, None);
+    assert_eq!(all[1].occupancy_percent, Some(150.0));
+
+    let req = TestRequest::get().uri("/spaces").to_request();
+    let spaces: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(spaces[1]["area_m2"], json!(4.0));
+}
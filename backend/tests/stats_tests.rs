@@ -0,0 +1,122 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use rusqlite::params;
+use serde_json::json;
+use shared::stats::{DashboardStats, Kpi, TREND_PERIODS};
+
+#[test]
+fn test_kpi_change() {
+    let kpi = Kpi::from_trend(vec![1.0, 4.0, 5.0]);
+    assert_eq!(kpi.value, 5.0);
+    assert_eq!(kpi.previous(), Some(4.0));
+    assert_eq!(kpi.change_percent(), Some(25.0));
+
+    assert_eq!(Kpi::from_trend(vec![0.0, 3.0]).change_percent(), None);
+    assert_eq!(Kpi::from_trend(vec![3.0]).change_percent(), None);
+    assert_eq!(Kpi::from_trend(vec![]).value, 0.0);
+}
+
+#[actix_rt::test]
+async fn test_dashboard_stats() {
+    let db_pool = common::temp_pool("stats");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let mut moti = common::sample_goat("Moti");
+    moti["current_price"] = json!(200.0);
+    for goat in [common::sample_goat("Rani"), moti] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(&goat)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    let conn = db_pool.get_conn().unwrap();
+    for (name, created_at) in [
+        ("Rani", "2025-06-01 09:00:00"),
+        ("Moti", "2025-06-28 18:30:00"),
+    ] {
+        conn.execute(
+            "UPDATE goats SET created_at = ?1 WHERE name = ?2",
+            [created_at, name],
+        )
+        .unwrap();
+    }
+    let rani: i64 = conn
+        .query_row("SELECT id FROM goats WHERE name = 'Rani'", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    for (recorded_on, litres) in [
+        ("2025-06-20", 3.0),
+        ("2025-06-25", 1.5),
+        ("2025-06-30", 2.0),
+    ] {
+        conn.execute(
+            "INSERT INTO milk_records (doe_id, recorded_on, yield_litres) VALUES (?1, ?2, ?3)",
+            params![rani, recorded_on, litres],
+        )
+        .unwrap();
+    }
+    for (kind, amount, date, currency, rate) in [
+        ("Expense", 400.0, "2025-05-05", None, None),
+        ("Expense", 500.0, "2025-06-10", None, None),
+        ("Expense", 10.0, "2025-06-15", Some("USD"), Some(83.0)),
+        // Income, and an expense after the as-of date, are left out
+        ("Income", 1000.0, "2025-06-12", None, None),
+        ("Expense", 999.0, "2025-07-01", None, None),
+    ] {
+        conn.execute(
+            "INSERT INTO transactions (kind, category, amount, date, currency, exchange_rate) \
+             VALUES (?1, 'Feed', ?2, ?3, ?4, ?5)",
+            params![kind, amount, date, currency, rate],
+        )
+        .unwrap();
+    }
+
+    let req = TestRequest::get()
+        .uri("/stats?as_of=2025-06-30")
+        .to_request();
+    let stats: DashboardStats = call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        (stats.as_of.as_str(), stats.currency.as_str()),
+        ("2025-06-30", "INR")
+    );
+    // Weeks end on 05-12, 05-19, 05-26, 06-02, 06-09, 06-16, 06-23 and 06-30
+    assert_eq!(
+        stats.herd_size.trend,
+        vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 2.0]
+    );
+    assert_eq!(stats.herd_size.value, 2.0);
+    assert_eq!(stats.herd_value.value, 350.0);
+    assert_eq!(stats.herd_value.previous(), Some(150.0));
+    assert_eq!(stats.milk_this_week.value, 3.5);
+    assert_eq!(stats.milk_this_week.previous(), Some(3.0));
+    assert_eq!(stats.expenses_this_month.value, 1330.0);
+    assert_eq!(stats.expenses_this_month.previous(), Some(400.0));
+    for kpi in [
+        &stats.herd_size,
+        &stats.herd_value,
+        &stats.milk_this_week,
+        &stats.expenses_this_month,
+    ] {
+        assert_eq!(kpi.trend.len(), TREND_PERIODS);
+    }
+
+    let req = TestRequest::get().uri("/stats").to_request();
+    let today: DashboardStats = call_and_read_body_json(&app, req).await;
+    assert_eq!(today.herd_size.value, 2.0);
+
+    let req = TestRequest::get()
+        .uri("/stats?as_of=30-06-2025")
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use chrono::Datelike;
+use serde_json::json;
+use shared::Goat;
+use shared::settings::FarmSettings;
+use shared::tags::NextTag;
+use shared::time::today_in;
+
+#[actix_rt::test]
+async fn test_new_goats_get_the_next_tag_of_the_series() {
+    let db_pool = common::temp_pool("tags");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let next_tag = || TestRequest::get().uri("/goats/next-tag").to_request();
+    let next: NextTag = call_and_read_body_json(&app, next_tag()).await;
+    assert_eq!(next.tag_id, None);
+
+    let set_series = |series: serde_json::Value| {
+        TestRequest::put()
+            .uri("/settings")
+            .set_json(json!({ "tag_series": series }))
+            .to_request()
+    };
+    for invalid in [
+        json!({ "prefix": " " }),
+        json!({ "prefix": "Y G" }),
+        json!({ "prefix": "YG", "digits": 0 }),
+    ] {
+        assert_eq!(call_service(&app, set_series(invalid)).await.status(), 400);
+    }
+    let settings: FarmSettings = call_and_read_body_json(
+        &app,
+        set_series(json!({ "prefix": " YG ", "yearly": true, "digits": 3 })),
+    )
+    .await;
+    let series = settings.tag_series.clone().unwrap();
+    assert_eq!(series.prefix, "YG");
+    let year = today_in(settings.tz()).year();
+    let tag = |number: u32| format!("YG-{}-{:03}", year, number);
+
+    let next: NextTag = call_and_read_body_json(&app, next_tag()).await;
+    assert_eq!(next.tag_id, Some(tag(1)));
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    let rani: Goat = call_and_read_body_json(&app, req).await;
+    assert_eq!(rani.tag_id, Some(tag(1)));
+    let tag_of = |name: &str| -> Option<String> {
+        let conn = db_pool.get_conn().unwrap();
+        conn.query_row("SELECT tag_id FROM goats WHERE name = ?1", [name], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    };
+    assert_eq!(tag_of("Rani"), Some(tag(1)));
+
+    // A tag already on a goat is skipped, and numbers are never reused
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute("UPDATE goats SET tag_id = ?1 WHERE name = 'Rani'", [tag(2)])
+        .unwrap();
+    let next: NextTag = call_and_read_body_json(&app, next_tag()).await;
+    assert_eq!(next.tag_id, Some(tag(3)));
+    let req = TestRequest::post()
+        .uri("/goats/batch")
+        .set_json(json!([
+            common::sample_goat("Moti"),
+            common::sample_goat("Kali")
+        ]))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::get().uri("/goats").to_request();
+    let goats: Vec<Goat> = call_and_read_body_json(&app, req).await;
+    let tags: Vec<_> = goats.iter().map(|g| g.tag_id.clone()).collect();
+    assert_eq!(tags, vec![Some(tag(2)), Some(tag(3)), Some(tag(4))]);
+    assert_eq!(tag_of("Moti"), Some(tag(3)));
+    assert_eq!(tag_of("Kali"), Some(tag(4)));
+    let next: NextTag = call_and_read_body_json(&app, next_tag()).await;
+    assert_eq!(next.tag_id, Some(tag(5)));
+
+    // Without a series, new goats are left untagged
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({}))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(common::sample_goat("Gauri"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    assert_eq!(tag_of("Gauri"), None);
+}
@@ -0,0 +1,217 @@
+mod common;
+
+use actix_web::middleware::from_fn;
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use backend::tenants::{ADMIN_KEY_HEADER, TENANT_KEY_HEADER, TenantRegistry, resolve_tenant};
+use serde_json::json;
+use shared::GoatParams;
+use shared::tenants::{IssuedTenant, Tenant};
+use shared::traceability::{TraceabilityRecord, share_path};
+
+const ADMIN_KEY: &str = "operator-secret";
+
+#[actix_rt::test]
+async fn test_tenants_are_isolated() {
+    let dir = std::env::temp_dir().join(format!("yagi_tenants_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let registry = TenantRegistry::open(&dir, ADMIN_KEY).unwrap();
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(resolve_tenant))
+            .app_data(web::Data::new(registry))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let mut keys = Vec::new();
+    for (slug, name) in [
+        ("green-valley", "Green Valley Farm"),
+        ("hilltop", "Hilltop"),
+    ] {
+        let req = TestRequest::post()
+            .uri("/tenants")
+            .insert_header((ADMIN_KEY_HEADER, ADMIN_KEY))
+            .set_json(json!({
+                "slug": slug, "name": name, "owner_password": "correct horse"
+            }))
+            .to_request();
+        let issued: IssuedTenant = call_and_read_body_json(&app, req).await;
+        assert_eq!(issued.slug, slug);
+        keys.push(issued.key);
+    }
+    assert!(dir.join("green-valley.db").exists());
+
+    // The owner signs in with the password the farm was set up with
+    let sign_in = |secret: &str| {
+        TestRequest::post()
+            .uri("/sessions")
+            .insert_header((TENANT_KEY_HEADER, keys[0].as_str()))
+            .set_json(json!({ "secret": secret }))
+            .to_request()
+    };
+    assert_eq!(call_service(&app, sign_in("wrong horse")).await.status(), 401);
+    assert_eq!(call_service(&app, sign_in("correct horse")).await.status(), 201);
+
+    let req = TestRequest::get()
+        .uri("/tenants")
+        .insert_header((ADMIN_KEY_HEADER, ADMIN_KEY))
+        .to_request();
+    let tenants: Vec<Tenant> = call_and_read_body_json(&app, req).await;
+    let slugs: Vec<&str> = tenants.iter().map(|t| t.slug.as_str()).collect();
+    assert_eq!(slugs, vec!["green-valley", "hilltop"]);
+
+    // Each farm only sees its own goats
+    let req = TestRequest::post()
+        .uri("/goats")
+        .insert_header((TENANT_KEY_HEADER, keys[0].as_str()))
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let goats = |key: &str| {
+        TestRequest::get()
+            .uri("/goats")
+            .insert_header((TENANT_KEY_HEADER, key))
+            .to_request()
+    };
+    let green: Vec<GoatParams> = call_and_read_body_json(&app, goats(&keys[0])).await;
+    assert_eq!(green.len(), 1);
+    let hilltop: Vec<GoatParams> = call_and_read_body_json(&app, goats(&keys[1])).await;
+    assert!(hilltop.is_empty());
+
+    // The same name may exist on both farms
+    let req = TestRequest::post()
+        .uri("/goats")
+        .insert_header((TENANT_KEY_HEADER, keys[1].as_str()))
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+
+    let req = TestRequest::get().uri("/goats").to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+    assert_eq!(call_service(&app, goats("yagi_wrong")).await.status(), 401);
+}
+
+#[actix_rt::test]
+async fn test_tenant_admin_validation() {
+    let dir = std::env::temp_dir().join(format!("yagi_tenant_admin_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let registry = TenantRegistry::open(&dir, ADMIN_KEY).unwrap();
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(resolve_tenant))
+            .app_data(web::Data::new(registry))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let add_with = |admin_key: &str, slug: &str, owner_password: &str| {
+        TestRequest::post()
+            .uri("/tenants")
+            .insert_header((ADMIN_KEY_HEADER, admin_key))
+            .set_json(json!({
+                "slug": slug, "name": "Farm", "owner_password": owner_password
+            }))
+            .to_request()
+    };
+    let add = |admin_key: &str, slug: &str| add_with(admin_key, slug, "correct horse");
+    assert_eq!(call_service(&app, add("guess", "farm")).await.status(), 401);
+    assert_eq!(
+        call_service(&app, add(ADMIN_KEY, "Farm 1")).await.status(),
+        400
+    );
+    assert_eq!(
+        call_service(&app, add(ADMIN_KEY, "../etc")).await.status(),
+        400
+    );
+    assert_eq!(
+        call_service(&app, add_with(ADMIN_KEY, "farm", "short")).await.status(),
+        400
+    );
+    assert_eq!(
+        call_service(&app, add(ADMIN_KEY, "farm")).await.status(),
+        201
+    );
+    assert_eq!(
+        call_service(&app, add(ADMIN_KEY, "farm")).await.status(),
+        400
+    );
+}
+
+#[actix_rt::test]
+async fn test_single_farm_mode_unchanged() {
+    let db_pool = common::temp_pool("single_farm");
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(resolve_tenant))
+            .app_data(web::Data::new(db_pool))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::get().uri("/goats").to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::get()
+        .uri("/tenants")
+        .insert_header((ADMIN_KEY_HEADER, ADMIN_KEY))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+}
+
+#[actix_rt::test]
+async fn test_share_links_name_their_tenant() {
+    let dir = std::env::temp_dir().join(format!("yagi_tenant_share_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let registry = TenantRegistry::open(&dir, ADMIN_KEY).unwrap();
+    let green = registry
+        .create("green-valley", "Green Valley Farm", "correct horse")
+        .unwrap();
+    registry
+        .create("hilltop", "Hilltop", "battery staple")
+        .unwrap();
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(resolve_tenant))
+            .app_data(web::Data::new(registry))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::post()
+        .uri("/goats")
+        .insert_header((TENANT_KEY_HEADER, green.key.as_str()))
+        .set_json(common::sample_goat("Rani"))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::post()
+        .uri("/transactions")
+        .insert_header((TENANT_KEY_HEADER, green.key.as_str()))
+        .set_json(json!({
+            "kind": "Income", "category": "Sale", "amount": 150.0,
+            "description": "Rani", "goat_name": "Rani", "date": "2026-04-01",
+            "sale": { "invoice_number": "INV-7", "buyer_name": "Mehta Farms" }
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = TestRequest::get()
+        .uri("/traceability")
+        .insert_header((TENANT_KEY_HEADER, green.key.as_str()))
+        .to_request();
+    let records: Vec<TraceabilityRecord> = call_and_read_body_json(&app, req).await;
+    let record = &records[0];
+    assert!(record.token.starts_with("green-valley."));
+
+    // The buyer opens the link without a tenant key
+    let req = TestRequest::get().uri(&record.share_path()).to_request();
+    let shared: TraceabilityRecord = call_and_read_body_json(&app, req).await;
+    assert_eq!(&shared, record);
+    let token = record.token.trim_start_matches("green-valley.");
+    let share = |token: String| TestRequest::get().uri(&share_path(&token)).to_request();
+    let resp = call_service(&app, share(format!("hilltop.{}", token))).await;
+    assert_eq!(resp.status(), 400);
+    let resp = call_service(&app, share(format!("nowhere.{}", token))).await;
+    assert_eq!(resp.status(), 401);
+    let resp = call_service(&app, share(token.to_string())).await;
+    assert_eq!(resp.status(), 401);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::auth::API_KEY_HEADER;
+use backend::routes;
+use chrono::{NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde_json::json;
+use shared::scale::{IssuedApiKey, ScaleReading};
+use shared::settings::FarmSettings;
+use shared::time::{day_start, format_local, local_date, parse_timestamp, parse_timezone};
+
+#[test]
+fn test_timestamps_are_read_in_the_farm_time_zone() {
+    let kolkata: Tz = parse_timezone(" Asia/Kolkata ").unwrap();
+    assert!(parse_timezone("Mars/Olympus").is_err());
+
+    let at = parse_timestamp("2026-01-10T08:00:00", kolkata).unwrap();
+    assert_eq!(at, Utc.with_ymd_and_hms(2026, 1, 10, 2, 30, 0).unwrap());
+    assert_eq!(parse_timestamp("2026-01-10 08:00", kolkata).unwrap(), at);
+    assert_eq!(
+        parse_timestamp("2026-01-10T02:30:00Z", kolkata).unwrap(),
+        at
+    );
+    assert_eq!(format_local(&at, kolkata), "2026-01-10 08:00");
+    assert!(parse_timestamp("10/01/2026", kolkata).is_err());
+
+    // Late evening UTC is already the next day in Kolkata
+    let evening = Utc.with_ymd_and_hms(2026, 3, 1, 20, 0, 0).unwrap();
+    assert_eq!(
+        local_date(&evening, kolkata),
+        NaiveDate::from_ymd_opt(2026, 3, 2).unwrap()
+    );
+    assert_eq!(
+        day_start(NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(), kolkata),
+        Utc.with_ymd_and_hms(2026, 3, 1, 18, 30, 0).unwrap()
+    );
+
+    // Clocks in New York skip 02:00-03:00 and repeat 01:00-02:00
+    let new_york: Tz = "America/New_York".parse().unwrap();
+    assert!(parse_timestamp("2026-03-08T02:30:00", new_york).is_err());
+    assert_eq!(
+        parse_timestamp("2026-11-01T01:30:00", new_york).unwrap(),
+        Utc.with_ymd_and_hms(2026, 11, 1, 5, 30, 0).unwrap()
+    );
+    // Where midnight itself is skipped, the day starts when clocks resume
+    let sao_paulo: Tz = "America/Sao_Paulo".parse().unwrap();
+    assert_eq!(
+        day_start(NaiveDate::from_ymd_opt(2018, 11, 4).unwrap(), sao_paulo),
+        Utc.with_ymd_and_hms(2018, 11, 4, 3, 0, 0).unwrap()
+    );
+}
+
+#[actix_rt::test]
+async fn test_farm_time_zone_setting() {
+    let db_pool = common::temp_pool("time");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::get().uri("/settings").to_request();
+    let settings: FarmSettings = call_and_read_body_json(&app, req).await;
+    assert_eq!(settings.timezone, "UTC");
+
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({ "timezone": "Asia/Atlantis" }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::put()
+        .uri("/settings")
+        .set_json(json!({ "timezone": " Asia/Kolkata" }))
+        .to_request();
+    let settings: FarmSettings = call_and_read_body_json(&app, req).await;
+    assert_eq!(settings.timezone, "Asia/Kolkata");
+    let req = TestRequest::get().uri("/settings").to_request();
+    let settings: FarmSettings = call_and_read_body_json(&app, req).await;
+    assert_eq!(settings.timezone, "Asia/Kolkata");
+
+    // A scale without an offset reports the farm's local time
+    let req = TestRequest::post()
+        .uri("/api-keys")
+        .set_json(json!({ "name": "Barn scale" }))
+        .to_request();
+    let issued: IssuedApiKey = call_and_read_body_json(&app, req).await;
+    let req = TestRequest::post()
+        .uri("/scale/readings")
+        .insert_header((API_KEY_HEADER, issued.key.as_str()))
+        .set_json(json!({ "tag_id": "TAG-1", "weight": 31.0, "read_at": "2026-01-10T08:00:00" }))
+        .to_request();
+    let reading: ScaleReading = call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        reading.read_at,
+        Utc.with_ymd_and_hms(2026, 1, 10, 2, 30, 0).unwrap()
+    );
+}
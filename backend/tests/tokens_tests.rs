@@ -0,0 +1,123 @@
+mod common;
+
+use actix_web::http::Method;
+use actix_web::middleware::from_fn;
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::auth::{check_access_token, check_session};
+use backend::permissions::enforce_permissions;
+use backend::routes;
+use serde_json::json;
+use shared::permissions::PermissionModule;
+use shared::sessions::SESSION_HEADER;
+use shared::tokens::{AccessToken, IssuedAccessToken};
+
+#[actix_rt::test]
+async fn test_access_tokens_are_limited_to_their_scope() {
+    let db_pool = common::temp_pool("tokens");
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(enforce_permissions))
+            .wrap(from_fn(check_session))
+            .wrap(from_fn(check_access_token))
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let owner = common::owner_token(&db_pool);
+    let as_owner = |req: TestRequest| {
+        req.insert_header((SESSION_HEADER, owner.as_str()))
+            .to_request()
+    };
+
+    let req = TestRequest::post()
+        .uri("/tokens")
+        .set_json(json!({ "name": "  " }));
+    assert_eq!(call_service(&app, as_owner(req)).await.status(), 400);
+    let req = TestRequest::post().uri("/tokens").set_json(json!({
+        "name": "Nightly sales pull",
+        "read_only": true,
+        "modules": ["Finance", "Goats", "Finance"]
+    }));
+    let issued: IssuedAccessToken = call_and_read_body_json(&app, as_owner(req)).await;
+    assert_eq!(
+        issued.access_token.modules,
+        vec![PermissionModule::Goats, PermissionModule::Finance]
+    );
+    assert_eq!(issued.access_token.scope(), "Read-only: Goats, Finance");
+    assert!(issued.access_token.last_used_at.is_none());
+
+    let with_token = |method: Method, uri: &str| {
+        TestRequest::default()
+            .method(method)
+            .uri(uri)
+            .insert_header(("Authorization", format!("Bearer {}", issued.token)))
+            .to_request()
+    };
+    let resp = call_service(&app, with_token(Method::GET, "/transactions")).await;
+    assert_eq!(resp.status(), 200);
+    let resp = call_service(&app, with_token(Method::GET, "/goats")).await;
+    assert_eq!(resp.status(), 200);
+    let resp = call_service(&app, with_token(Method::DELETE, "/goats/1")).await;
+    assert_eq!(resp.status(), 403);
+    let resp = call_service(&app, with_token(Method::GET, "/milk")).await;
+    assert_eq!(resp.status(), 403);
+    let resp = call_service(&app, with_token(Method::GET, "/stats")).await;
+    assert_eq!(resp.status(), 403);
+    let resp = call_service(&app, with_token(Method::GET, "/tokens")).await;
+    assert_eq!(resp.status(), 403);
+
+    let req = TestRequest::get().uri("/tokens");
+    let tokens: Vec<AccessToken> = call_and_read_body_json(&app, as_owner(req)).await;
+    assert_eq!(tokens.len(), 1);
+    assert!(tokens[0].last_used_at.is_some());
+    assert!(!tokens[0].revoked);
+
+    let uri = format!("/tokens/{}", issued.access_token.id);
+    let req = TestRequest::delete().uri(&uri);
+    assert_eq!(call_service(&app, as_owner(req)).await.status(), 200);
+    let req = TestRequest::delete().uri(&uri);
+    assert_eq!(call_service(&app, as_owner(req)).await.status(), 400);
+    let resp = call_service(&app, with_token(Method::GET, "/goats")).await;
+    assert_eq!(resp.status(), 401);
+    let req = TestRequest::get().uri("/tokens");
+    let tokens: Vec<AccessToken> = call_and_read_body_json(&app, as_owner(req)).await;
+    assert!(tokens[0].revoked);
+}
+
+#[actix_rt::test]
+async fn test_unauthenticated_writes_are_refused() {
+    let db_pool = common::temp_pool("tokens_unauthenticated");
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(check_access_token))
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+    let goat = json!(common::sample_goat("Intruder"));
+
+    let req = TestRequest::post()
+        .uri("/goats")
+        .set_json(&goat)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+    let req = TestRequest::post()
+        .uri("/goats")
+        .insert_header(("Authorization", "Bearer forged"))
+        .set_json(&goat)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+    let req = TestRequest::post()
+        .uri("/goats")
+        .insert_header(("X-Api-Key", "forged"))
+        .set_json(&goat)
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 401);
+
+    let conn = db_pool.get_conn().unwrap();
+    let goats: i64 = conn
+        .query_row("SELECT COUNT(*) FROM goats", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(goats, 0);
+}
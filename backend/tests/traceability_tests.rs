@@ -0,0 +1,150 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::routes;
+use serde_json::json;
+use shared::health::TimelineKind;
+use shared::traceability::{MovementKind, TraceabilityRecord};
+
+#[actix_rt::test]
+async fn test_sale_takes_shareable_traceability_record() {
+    let db_pool = common::temp_pool("traceability");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let mut rani = common::sample_goat("Rani");
+    rani["vaccinations"] = json!([{ "id": null, "name": "PPR", "given_on": "2026-01-15" }]);
+    rani["diseases"] = json!([{ "id": null, "name": "Mastitis" }]);
+    for goat in [
+        common::sample_goat("Ganga"),
+        rani,
+        common::sample_goat("Tara"),
+    ] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(&goat)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute_batch(
+            "INSERT INTO farm_settings (key, value) VALUES ('farm_name', 'Green Hills');
+             INSERT INTO spaces (name, type) VALUES ('North pen', 'enclosure');
+             UPDATE goats SET tag_id = 'IN-0042', date_of_birth = '2025-11-02',
+                 dam_id = (SELECT id FROM goats WHERE name = 'Ganga'),
+                 space_id = (SELECT id FROM spaces WHERE name = 'North pen')
+                 WHERE name = 'Rani';
+             INSERT INTO lifecycle_changes (goat_id, from_stage, to_stage, changed_on, note)
+                 SELECT id, 'Grower', 'ForSale', '2026-03-20', 'Surplus doe' FROM goats
+                 WHERE name = 'Rani';",
+        )
+        .unwrap();
+    let req = TestRequest::post()
+        .uri("/lab-results")
+        .set_json(json!({
+            "id": null, "goat_name": "Rani", "test_type": "CBC", "sampled_on": "2026-02-10",
+            "lab": null, "notes": null,
+            "values": [{ "analyte": "PCV", "value": 15.0, "unit": "%", "low": 22.0, "high": 38.0 }]
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    // Another goat by the same name keeps its own history
+    let req = TestRequest::post()
+        .uri("/lab-results")
+        .set_json(json!({
+            "id": null, "goat_name": "Tara", "test_type": "FAMACHA", "sampled_on": "2026-02-11",
+            "lab": null, "notes": null,
+            "values": [{ "analyte": "PCV", "value": 12.0, "unit": "%", "low": 22.0, "high": 38.0 }]
+        }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute("UPDATE goats SET name = 'Rani' WHERE name = 'Tara'", [])
+        .unwrap();
+
+    // Milk income is not a sale
+    for category in ["Milk", "Sale"] {
+        let req = TestRequest::post()
+            .uri("/transactions")
+            .set_json(json!({
+                "kind": "Income", "category": category, "amount": 150.0,
+                "description": "Rani", "goat_name": "Rani", "date": "2026-04-01",
+                "sale": if category == "Sale" {
+                    json!({ "invoice_number": "INV-7", "buyer_name": "Mehta Farms" })
+                } else {
+                    json!(null)
+                }
+            }))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 201);
+    }
+
+    let req = TestRequest::get()
+        .uri("/traceability?goat_name=Rani")
+        .to_request();
+    let records: Vec<TraceabilityRecord> = call_and_read_body_json(&app, req).await;
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.farm_name.as_deref(), Some("Green Hills"));
+    assert_eq!(record.tag_id.as_deref(), Some("IN-0042"));
+    assert_eq!(record.buyer_name.as_deref(), Some("Mehta Farms"));
+    assert_eq!(record.invoice_number.as_deref(), Some("INV-7"));
+    assert_eq!(record.sold_on, "2026-04-01");
+    assert_eq!(record.diseases, vec!["Mastitis".to_string()]);
+    assert_eq!(
+        record.health_summary(),
+        "1 vaccination, 0 incidents, 1 lab result (1 out of range)"
+    );
+    assert_eq!(record.health[0].kind, TimelineKind::LabResult);
+    assert_eq!(record.health[0].lab_result_id, None);
+    let movements: Vec<(MovementKind, &str)> = record
+        .movements
+        .iter()
+        .map(|m| (m.kind, m.description.as_str()))
+        .collect();
+    assert_eq!(
+        movements,
+        vec![
+            (MovementKind::Born, "Born on the farm to Ganga"),
+            (
+                MovementKind::StageChanged,
+                "Grower → For sale (Surplus doe)"
+            ),
+            (MovementKind::Kept, "Kept in North pen"),
+            (MovementKind::Sold, "Sold to Mehta Farms"),
+        ]
+    );
+    assert_eq!(record.movements[0].date.as_deref(), Some("2025-11-02"));
+
+    // The buyer reads the record as it stood at the sale
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "UPDATE goats SET tag_id = 'IN-0099' WHERE tag_id = 'IN-0042'",
+            [],
+        )
+        .unwrap();
+    let req = TestRequest::get().uri(&record.share_path()).to_request();
+    let shared: TraceabilityRecord = call_and_read_body_json(&app, req).await;
+    assert_eq!(&shared, record);
+    let req = TestRequest::get()
+        .uri("/public/traceability/yagi_unknown")
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    let req = TestRequest::get()
+        .uri("/traceability?goat_name=Ganga")
+        .to_request();
+    let records: Vec<TraceabilityRecord> = call_and_read_body_json(&app, req).await;
+    assert!(records.is_empty());
+}
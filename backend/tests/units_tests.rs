@@ -0,0 +1,33 @@
+use shared::units::{WeightUnit, currency_symbol, format_decimal, parse_decimal};
+
+#[test]
+fn test_parse_decimal_follows_locale() {
+    assert_eq!(parse_decimal("31.5", '.'), Some(31.5));
+    assert_eq!(parse_decimal(" 31,5 ", ','), Some(31.5));
+    assert_eq!(parse_decimal("1,00,000.50", '.'), Some(100_000.5));
+    assert_eq!(parse_decimal("1.234,5", ','), Some(1234.5));
+    assert_eq!(parse_decimal("1 234,5", ','), Some(1234.5));
+    // A lone other separator is a decimal point unless it groups thousands
+    assert_eq!(parse_decimal("31.5", ','), Some(31.5));
+    assert_eq!(parse_decimal("1,500", '.'), Some(1500.0));
+    assert_eq!(parse_decimal("", '.'), None);
+    assert_eq!(parse_decimal("heavy", '.'), None);
+    assert_eq!(parse_decimal("1.2.3", '.'), None);
+}
+
+#[test]
+fn test_format_decimal_and_units() {
+    assert_eq!(format_decimal(31.5, 2, ','), "31,5");
+    assert_eq!(format_decimal(30.0, 2, '.'), "30");
+    assert_eq!(format_decimal(-0.001, 2, '.'), "0");
+
+    let lb = WeightUnit::Lb.from_kg(30.0);
+    assert_eq!(format_decimal(lb, 2, '.'), "66.14");
+    assert!((WeightUnit::Lb.to_kg(lb) - 30.0).abs() < 1e-9);
+    assert_eq!(WeightUnit::Kg.to_kg(30.0), 30.0);
+    assert_eq!(WeightUnit::from_symbol("lb"), Some(WeightUnit::Lb));
+    assert_eq!(WeightUnit::from_symbol("stone"), None);
+
+    assert_eq!(currency_symbol("INR"), "₹");
+    assert_eq!(currency_symbol("KES"), "KES");
+}
@@ -0,0 +1,76 @@
+use chrono::NaiveDate;
+use shared::vaccination::{VaccinationStatus, vaccination_summary};
+use shared::{Breed, GoatParams, VaccineRef};
+
+fn dose(name: &str, given_on: Option<&str>) -> VaccineRef {
+    VaccineRef {
+        id: None,
+        name: name.to_string(),
+        given_on: given_on.map(str::to_string),
+    }
+}
+
+fn goat(breed: Breed, vaccinations: Vec<VaccineRef>) -> GoatParams {
+    GoatParams::builder("Rani")
+        .breed(breed)
+        .weight(30.0)
+        .diet("hay")
+        .vaccinations(vaccinations)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_vaccination_summary() {
+    let today = NaiveDate::from_ymd_opt(2026, 6, 1).unwrap();
+    let core = |fmd: Option<&str>| {
+        vec![
+            dose("PPR", Some("2026-01-10")),
+            dose("enterotoxaemia ", Some("2025-12-01")),
+            dose("FMD", fmd),
+        ]
+    };
+
+    // Beetal's schedule is PPR, Enterotoxaemia and FMD, matched ignoring case
+    let summary = vaccination_summary(&goat(Breed::Beetal, core(Some("2026-03-01"))), today);
+    assert_eq!(summary.status, VaccinationStatus::UpToDate);
+    assert_eq!(summary.vaccines.len(), 3);
+    assert_eq!(
+        summary.vaccines[1].due_on,
+        NaiveDate::from_ymd_opt(2026, 12, 1)
+    );
+
+    // FMD boosted a year ago falls due within the month
+    let summary = vaccination_summary(&goat(Breed::Beetal, core(Some("2025-06-20"))), today);
+    assert_eq!(summary.status, VaccinationStatus::DueSoon);
+    assert_eq!(
+        summary.vaccines[2].describe(),
+        "FMD: given 2025-06-20, due 2026-06-20"
+    );
+    let summary = vaccination_summary(&goat(Breed::Beetal, core(Some("2025-05-01"))), today);
+    assert_eq!(summary.status, VaccinationStatus::Overdue);
+    assert!(summary.details().contains("overdue since 2026-05-01"));
+
+    // An undated dose cannot be judged; a scheduled vaccine never given is overdue
+    let summary = vaccination_summary(&goat(Breed::Beetal, core(None)), today);
+    assert_eq!(summary.status, VaccinationStatus::Unknown);
+    assert!(summary.details().contains("FMD: given, date not recorded"));
+    let summary = vaccination_summary(&goat(Breed::Sirohi, core(Some("2026-03-01"))), today);
+    assert_eq!(summary.status, VaccinationStatus::Overdue);
+    assert!(summary.details().contains("Goat Pox: never given"));
+
+    // Without any records there is nothing to judge
+    let summary = vaccination_summary(&goat(Breed::Beetal, vec![]), today);
+    assert_eq!(summary.status, VaccinationStatus::Unknown);
+
+    // Breeds without a profile are held to the vaccines they were given
+    let other = goat(
+        Breed::Other("Boer".into()),
+        vec![dose("CDT", Some("2026-02-01"))],
+    );
+    let summary = vaccination_summary(&other, today);
+    assert_eq!(summary.status, VaccinationStatus::UpToDate);
+    assert_eq!(summary.details(), "CDT: given 2026-02-01, due 2027-02-01");
+    let summary = vaccination_summary(&goat(Breed::Other("Boer".into()), vec![]), today);
+    assert_eq!(summary.details(), "No vaccination schedule for this breed");
+}
@@ -0,0 +1,221 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::calendar::load_events;
+use backend::routes;
+use chrono::{Days, Utc};
+use serde_json::json;
+use shared::notifications::Notification;
+use shared::vet::{VetAvailability, VetVisit, VisitStatus, is_vet_role};
+
+#[test]
+fn test_is_vet_role() {
+    assert!(is_vet_role("Vet"));
+    assert!(is_vet_role(" veterinarian "));
+    assert!(is_vet_role("Veterinary surgeon"));
+    assert!(!is_vet_role("Milker"));
+    assert!(!is_vet_role("Vet tech"));
+}
+
+#[actix_rt::test]
+async fn test_vet_visit_scheduling_and_completion() {
+    let db_pool = common::temp_pool("vet_visits");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    for name in ["Rani", "Moti", "Chhoti"] {
+        let req = TestRequest::post()
+            .uri("/goats")
+            .set_json(common::sample_goat(name))
+            .to_request();
+        assert!(call_service(&app, req).await.status().is_success());
+    }
+    for (name, role) in [
+        ("Dr Rao", "Veterinarian"),
+        ("Dr Sen", "Vet"),
+        ("Asha", "Milker"),
+    ] {
+        common::add_worker(&db_pool, name, Some(role), None, "App");
+    }
+
+    // The farm keeps UTC, so days here are the farm's days
+    let today = Utc::now().date_naive();
+    let day = |days: u64| (today + Days::new(days)).format("%Y-%m-%d").to_string();
+
+    let req = TestRequest::post()
+        .uri("/vet-visits")
+        .set_json(json!({ "reason": "Coughing", "agenda": ["Rani", "Moti"] }))
+        .to_request();
+    let requested: VetVisit = call_and_read_body_json(&app, req).await;
+    assert_eq!(requested.status, VisitStatus::Requested);
+    assert_eq!(requested.agenda, vec!["Moti", "Rani"]);
+    assert_eq!(requested.vet_id, None);
+
+    let req = TestRequest::post()
+        .uri("/vet-visits")
+        .set_json(json!({
+            "reason": "Herd check", "agenda": ["Chhoti"], "vet_id": 1, "scheduled_for": day(3)
+        }))
+        .to_request();
+    let booked: VetVisit = call_and_read_body_json(&app, req).await;
+    assert_eq!(booked.status, VisitStatus::Scheduled);
+    assert_eq!(booked.vet_name.as_deref(), Some("Dr Rao"));
+
+    for (body, status) in [
+        (json!({ "reason": "Limping", "agenda": ["Nobody"] }), 400),
+        (json!({ "reason": "Limping", "agenda": [] }), 400),
+        (
+            json!({ "reason": "Limping", "agenda": ["Rani"], "scheduled_for": day(1) }),
+            400,
+        ),
+    ] {
+        let req = TestRequest::post()
+            .uri("/vet-visits")
+            .set_json(body)
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), status);
+    }
+
+    // Everyone sees the days each vet is booked
+    let req = TestRequest::get().uri("/vet-visits/vets").to_request();
+    let vets: Vec<VetAvailability> = call_and_read_body_json(&app, req).await;
+    let names: Vec<&str> = vets.iter().map(|v| v.vet_name.as_str()).collect();
+    assert_eq!(names, vec!["Dr Rao", "Dr Sen"]);
+    assert_eq!(vets[0].booked, vec![day(3)]);
+    assert!(!vets[0].is_free(&day(3)));
+    assert!(vets[1].booked.is_empty());
+
+    let schedule = |vet_id: i64, on: String| {
+        TestRequest::put()
+            .uri(&format!("/vet-visits/{}/schedule", requested.id))
+            .set_json(json!({ "vet_id": vet_id, "scheduled_for": on }))
+            .to_request()
+    };
+    let resp = call_service(&app, schedule(1, day(3))).await;
+    assert_eq!(resp.status(), 400);
+    let body = actix_web::test::read_body(resp).await;
+    assert!(String::from_utf8_lossy(&body).contains("Dr Rao is already booked on"));
+    // Milkers cannot be booked as vets, and booked days cannot be past
+    assert_eq!(call_service(&app, schedule(3, day(0))).await.status(), 400);
+    let yesterday = (today - Days::new(1)).format("%Y-%m-%d").to_string();
+    assert_eq!(
+        call_service(&app, schedule(2, yesterday)).await.status(),
+        400
+    );
+
+    // Completing needs the visit's day to have come
+    let complete = |id: i64, body: serde_json::Value| {
+        TestRequest::put()
+            .uri(&format!("/vet-visits/{}/complete", id))
+            .set_json(body)
+            .to_request()
+    };
+    let summary = json!({
+        "summary": "Rani has a chest infection; Moti is fine",
+        "findings": [
+            { "goat_name": "Rani", "kind": "Disease", "condition": "Pneumonia",
+              "treatment": "Oxytetracycline 5 days" }
+        ]
+    });
+    assert_eq!(
+        call_service(&app, complete(requested.id, summary.clone()))
+            .await
+            .status(),
+        400
+    );
+    assert_eq!(
+        call_service(&app, complete(booked.id, summary.clone()))
+            .await
+            .status(),
+        400
+    );
+
+    let scheduled: VetVisit = call_and_read_body_json(&app, schedule(2, day(0))).await;
+    assert_eq!(scheduled.status, VisitStatus::Scheduled);
+    assert_eq!(scheduled.scheduled_for, Some(day(0)));
+
+    // Both visits are in the calendar feed
+    let events = load_events(&db_pool.get_conn().unwrap()).unwrap();
+    let visit_events: Vec<_> = events
+        .iter()
+        .filter(|e| e.uid.starts_with("vet-visit-"))
+        .collect();
+    assert_eq!(visit_events.len(), 2);
+    assert_eq!(
+        visit_events[0].uid,
+        format!("vet-visit-{}@yagi", requested.id)
+    );
+    assert_eq!(visit_events[0].summary, "Vet visit: Coughing");
+    assert_eq!(
+        visit_events[0].description.as_deref(),
+        Some("Vet: Dr Sen\nGoats: Moti, Rani")
+    );
+
+    // Findings are only taken for goats on the agenda
+    let off_agenda = json!({
+        "summary": "Checked",
+        "findings": [{ "goat_name": "Chhoti", "kind": "Injury", "condition": "Cut" }]
+    });
+    assert_eq!(
+        call_service(&app, complete(requested.id, off_agenda))
+            .await
+            .status(),
+        400
+    );
+
+    let completed: VetVisit =
+        call_and_read_body_json(&app, complete(requested.id, summary.clone())).await;
+    assert_eq!(completed.status, VisitStatus::Completed);
+    assert_eq!(completed.completed_on, Some(day(0)));
+    assert_eq!(completed.treated, vec!["Rani"]);
+    assert_eq!(
+        call_service(&app, complete(requested.id, summary))
+            .await
+            .status(),
+        400
+    );
+
+    let req = TestRequest::get()
+        .uri("/health/incidents?goat_name=Rani")
+        .to_request();
+    let incidents: Vec<serde_json::Value> = call_and_read_body_json(&app, req).await;
+    assert_eq!(incidents.len(), 1);
+    assert_eq!(incidents[0]["condition"], "Pneumonia");
+    assert_eq!(incidents[0]["observed_on"], day(0));
+    assert_eq!(
+        incidents[0]["notes"],
+        format!(
+            "Treatment: Oxytetracycline 5 days\nVet visit #{} with Dr Sen",
+            requested.id
+        )
+    );
+
+    // The vet was told of the booking, and the completed visit leaves the feed
+    let req = TestRequest::get()
+        .uri("/notifications?worker=Dr%20Sen")
+        .to_request();
+    let inbox: Vec<Notification> = call_and_read_body_json(&app, req).await;
+    assert_eq!(inbox.len(), 1);
+    assert_eq!(
+        inbox[0].message,
+        format!("Vet visit booked on {}: Coughing", day(0))
+    );
+    let events = load_events(&db_pool.get_conn().unwrap()).unwrap();
+    assert_eq!(
+        events
+            .iter()
+            .filter(|e| e.uid.starts_with("vet-visit-"))
+            .count(),
+        1
+    );
+
+    let req = TestRequest::get().uri("/vet-visits").to_request();
+    let visits: Vec<VetVisit> = call_and_read_body_json(&app, req).await;
+    let ids: Vec<i64> = visits.iter().map(|v| v.id).collect();
+    assert_eq!(ids, vec![booked.id, requested.id]);
+}
@@ -0,0 +1,89 @@
+use shared::units::WeightUnit;
+use shared::voice::{EntryKind, parse_utterance};
+
+const HERD: [&str; 4] = ["Rani", "Lakshmi", "Moti Rani", "Chandni"];
+
+fn parse(text: &str, kind: EntryKind) -> Result<(String, f64), String> {
+    parse_utterance(text, kind, WeightUnit::Kg, &HERD).map(|e| (e.goat_name, e.amount))
+}
+
+#[test]
+fn test_parse_spoken_weights() {
+    assert_eq!(
+        parse("Rani 32 kilos", EntryKind::Weight),
+        Ok(("Rani".into(), 32.0))
+    );
+    assert_eq!(
+        parse("rani weighs 32.5kg.", EntryKind::Weight),
+        Ok(("Rani".into(), 32.5))
+    );
+    assert_eq!(
+        parse("Moti Rani thirty two point five", EntryKind::Weight),
+        Ok(("Moti Rani".into(), 32.5))
+    );
+    assert_eq!(
+        parse(
+            "Chandni one hundred twenty five kilograms",
+            EntryKind::Weight
+        ),
+        Ok(("Chandni".into(), 125.0))
+    );
+    // Pounds are converted; without a unit the user's weight unit applies
+    let (_, kg) = parse("Rani 66 pounds", EntryKind::Weight).unwrap();
+    assert!((kg - 29.937).abs() < 0.001);
+    let entry = parse_utterance("Rani 66", EntryKind::Weight, WeightUnit::Lb, &HERD).unwrap();
+    assert!((entry.amount - kg).abs() < 1e-9);
+
+    // A recognizer's spelling of the name still finds the goat
+    let entry =
+        parse_utterance("Laxmi 28 kilos", EntryKind::Weight, WeightUnit::Kg, &HERD).unwrap();
+    assert_eq!(entry.goat_name, "Lakshmi");
+    assert_eq!(entry.heard_name, "laxmi");
+    assert_eq!(entry.kind, EntryKind::Weight);
+}
+
+#[test]
+fn test_parse_spoken_milk() {
+    assert_eq!(
+        parse("Lakshmi gave two and a half litres", EntryKind::Milk),
+        Ok(("Lakshmi".into(), 2.5))
+    );
+    assert_eq!(
+        parse("Chandni half a litre", EntryKind::Milk),
+        Ok(("Chandni".into(), 0.5))
+    );
+    assert_eq!(parse("Rani 3", EntryKind::Milk), Ok(("Rani".into(), 3.0)));
+}
+
+#[test]
+fn test_parse_utterance_errors() {
+    assert!(
+        parse("Rani", EntryKind::Weight)
+            .unwrap_err()
+            .contains("amount")
+    );
+    assert!(
+        parse("32 kilos", EntryKind::Weight)
+            .unwrap_err()
+            .contains("name")
+    );
+    assert_eq!(
+        parse("Bholu 32 kilos", EntryKind::Weight),
+        Err("No goat named 'bholu'".into())
+    );
+    assert!(
+        parse("Rani 32 5", EntryKind::Weight)
+            .unwrap_err()
+            .contains("more than one")
+    );
+    assert!(
+        parse("Rani 2 litres", EntryKind::Weight)
+            .unwrap_err()
+            .contains("litres")
+    );
+    assert!(
+        parse("Rani 2 kilos", EntryKind::Milk)
+            .unwrap_err()
+            .contains("weight")
+    );
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use actix_web::test::{TestRequest, call_and_read_body_json, call_service, init_service};
+use actix_web::{App, web};
+use backend::auth::API_KEY_HEADER;
+use backend::routes;
+use backend::water::{baseline, detect_drop};
+use chrono::{Days, NaiveDate, Utc};
+use serde_json::json;
+use shared::notifications::Notification;
+use shared::scale::IssuedApiKey;
+use shared::sensors::Sensor;
+use shared::water::PenWater;
+
+#[test]
+fn test_detect_drop() {
+    let day = NaiveDate::from_ymd_opt(2026, 5, 10).unwrap();
+    let before = |n: u64| day - Days::new(n);
+    let mut daily = vec![
+        (before(9), 500.0),
+        (before(4), 100.0),
+        (before(3), 110.0),
+        (before(2), 90.0),
+        (day, 60.0),
+    ];
+    // Days more than a week back do not count towards the baseline
+    assert_eq!(baseline(&daily, day), Some(100.0));
+    let drop = detect_drop(&daily, day).unwrap();
+    assert_eq!(drop.date, "2026-05-10");
+    assert!((drop.fall() - 0.4).abs() < 1e-9);
+
+    daily[4].1 = 75.0;
+    assert_eq!(detect_drop(&daily, day), None);
+    // No reading that day is not a drop
+    assert_eq!(detect_drop(&daily[..4], day), None);
+    // Too few days to judge against
+    assert_eq!(detect_drop(&daily[2..], day), None);
+}
+
+#[actix_rt::test]
+async fn test_water_tracking_and_trough_cleaning() {
+    let db_pool = common::temp_pool("water");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(db_pool.clone()))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = TestRequest::post()
+        .uri("/spaces")
+        .set_json(json!({ "id": null, "name": "Kid Pen", "kind": "Enclosure", "capacity": null }))
+        .to_request();
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    common::add_worker(&db_pool, "Asha", Some("Milker"), None, "App");
+    let req = TestRequest::post()
+        .uri("/sensors")
+        .set_json(json!({
+            "id": null, "name": "Kid Pen meter", "kind": "Water", "space_id": 1,
+            "thresholds": { "min": null, "max": null }
+        }))
+        .to_request();
+    let meter: Sensor = call_and_read_body_json(&app, req).await;
+    let req = TestRequest::post()
+        .uri("/api-keys")
+        .set_json(json!({ "name": "Trough meters" }))
+        .to_request();
+    let key: IssuedApiKey = call_and_read_body_json(&app, req).await;
+    let reading = |value: f64, at: String| {
+        TestRequest::post()
+            .uri("/sensors/readings")
+            .insert_header((API_KEY_HEADER, key.key.as_str()))
+            .set_json(json!({ "sensor_id": meter.id, "value": value, "read_at": at }))
+            .to_request()
+    };
+
+    // The farm keeps UTC, so days here are the farm's days
+    let today = Utc::now().date_naive();
+    let at = |days_ago: u64, time: &str| format!("{}T{}", today - Days::new(days_ago), time);
+    for days_ago in 2..=6 {
+        for time in ["07:00:00", "18:00:00"] {
+            let req = reading(20.0, at(days_ago, time));
+            assert_eq!(call_service(&app, req).await.status(), 201);
+        }
+    }
+    // Yesterday the pen drank a quarter of its usual 40 L
+    let req = reading(10.0, at(1, "12:00:00"));
+    assert_eq!(call_service(&app, req).await.status(), 201);
+    let req = reading(-5.0, at(1, "13:00:00"));
+    assert_eq!(call_service(&app, req).await.status(), 400);
+
+    // An intake check and the first trough cleaning
+    let evaluate = || TestRequest::post().uri("/rules/evaluate").to_request();
+    let created: serde_json::Value = call_and_read_body_json(&app, evaluate()).await;
+    assert_eq!(created["created"], 2);
+    let created: serde_json::Value = call_and_read_body_json(&app, evaluate()).await;
+    assert_eq!(created["created"], 0);
+
+    let req = TestRequest::get().uri("/water").to_request();
+    let pens: Vec<PenWater> = call_and_read_body_json(&app, req).await;
+    assert_eq!(pens.len(), 1);
+    let pen = &pens[0];
+    assert_eq!(pen.space_name, "Kid Pen");
+    assert_eq!(pen.days.len(), 6);
+    assert_eq!(pen.days[0].litres, 40.0);
+    assert_eq!(pen.baseline, Some(40.0));
+    let drop = pen.drop.as_ref().unwrap();
+    assert_eq!(drop.litres, 10.0);
+    assert!(pen.cleaning_task_id.is_some());
+    assert_eq!(pen.last_cleaned, None);
+
+    let req = TestRequest::get()
+        .uri("/notifications?worker=Asha")
+        .to_request();
+    let inbox: Vec<Notification> = call_and_read_body_json(&app, req).await;
+    assert_eq!(inbox.len(), 1);
+    assert!(
+        inbox[0]
+            .message
+            .starts_with("Water intake in Kid Pen fell 75%"),
+        "{}",
+        inbox[0].message
+    );
+
+    let req = TestRequest::put().uri("/water/1/cleaned").to_request();
+    assert_eq!(call_service(&app, req).await.status(), 200);
+    let req = TestRequest::put().uri("/water/99/cleaned").to_request();
+    assert_eq!(call_service(&app, req).await.status(), 400);
+    let req = TestRequest::get().uri("/water").to_request();
+    let pens: Vec<PenWater> = call_and_read_body_json(&app, req).await;
+    assert_eq!(pens[0].cleaning_task_id, None);
+    assert_eq!(
+        pens[0].last_cleaned,
+        Some(today.format("%Y-%m-%d").to_string())
+    );
+    // Cleaned today, so nothing is due again yet
+    let created: serde_json::Value = call_and_read_body_json(&app, evaluate()).await;
+    assert_eq!(created["created"], 0);
+}
@@ -0,0 +1,112 @@
+//! Blocking HTTP client for the backend's REST API.
+//!
+//! Requests carry the personal access token, if one is given, as
+//! `Authorization: Bearer` (see `shared::tokens`). The backend answers only
+//! its public routes without one; a signed-in owner or worker issues tokens
+//! with `POST /tokens`, and each acts with its issuer's permissions.
+//!
+//! On a multi-tenant backend requests also carry the farm's tenant key
+//! (see `Client::with_tenant_key`).
+
+use crate::errors::CliError;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use shared::tenants::TENANT_KEY_HEADER;
+use std::io::Read;
+use std::time::Duration;
+
+/// Where the backend listens unless told otherwise.
+pub const DEFAULT_API_URL: &str = "http://127.0.0.1:8000";
+
+/// How long the backend may take to answer; reports and backups of large
+/// farms take a while.
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Talks to the backend at `base_url`.
+#[derive(Clone)]
+pub struct Client {
+    agent: ureq::Agent,
+    base_url: String,
+    token: Option<String>,
+    tenant_key: Option<String>,
+}
+
+impl Client {
+    /// A client for the backend at `base_url` (e.g. `DEFAULT_API_URL`),
+    /// sending `token` with every request.
+    pub fn new(base_url: &str, token: Option<String>) -> Self {
+        Client {
+            agent: ureq::AgentBuilder::new().timeout(TIMEOUT).build(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: token.filter(|t| !t.trim().is_empty()),
+            tenant_key: None,
+        }
+    }
+
+    /// The same client, sending `tenant_key` in `TENANT_KEY_HEADER` to pick
+    /// the farm on a multi-tenant backend.
+    pub fn with_tenant_key(mut self, tenant_key: Option<String>) -> Self {
+        self.tenant_key = tenant_key.filter(|k| !k.trim().is_empty());
+        self
+    }
+
+    /// A `method` request to `path`, e.g. `/goats`.
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        let mut request = self
+            .agent
+            .request(method, &format!("{}{}", self.base_url, path));
+        if let Some(tenant_key) = &self.tenant_key {
+            request = request.set(TENANT_KEY_HEADER, tenant_key.trim());
+        }
+        match &self.token {
+            Some(token) => request.set("Authorization", &format!("Bearer {}", token.trim())),
+            None => request,
+        }
+    }
+
+    /// `GET path`, read as JSON.
+    pub fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, CliError> {
+        let resp = self.request("GET", path).call()?;
+        Ok(serde_json::from_reader(resp.into_reader())?)
+    }
+
+    /// `GET path` with `headers`, read as raw bytes, e.g. a file to save.
+    pub fn get_bytes(&self, path: &str, headers: &[(&str, &str)]) -> Result<Vec<u8>, CliError> {
+        let request = headers
+            .iter()
+            .fold(self.request("GET", path), |r, (name, value)| {
+                r.set(name, value)
+            });
+        let mut bytes = Vec::new();
+        request.call()?.into_reader().read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// A `method` request to `path` with a JSON `body`, read as JSON.
+    pub fn send_json<B: Serialize, T: DeserializeOwned>(
+        &self,
+        method: &str,
+        path: &str,
+        body: &B,
+    ) -> Result<T, CliError> {
+        let resp = self
+            .request(method, path)
+            .set("Content-Type", "application/json")
+            .send_bytes(&serde_json::to_vec(body)?)?;
+        Ok(serde_json::from_reader(resp.into_reader())?)
+    }
+
+    /// `POST path` with `bytes` of `content_type`, read as JSON.
+    pub fn post_bytes<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        content_type: &str,
+        bytes: &[u8],
+    ) -> Result<T, CliError> {
+        let resp = self
+            .request("POST", path)
+            .set("Content-Type", content_type)
+            .send_bytes(bytes)?;
+        Ok(serde_json::from_reader(resp.into_reader())?)
+    }
+}
@@ -0,0 +1,214 @@
+//! What each subcommand asks of the backend, kept apart from argument
+//! parsing so scripts and tests can call them directly.
+
+use crate::client::Client;
+use crate::errors::CliError;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::Serialize;
+use shared::archive::PASSPHRASE_HEADER;
+use shared::import::{
+    BatchSummary, ImportRow, ImportTable, guess_mapping, missing_fields, preview,
+};
+use shared::jobs::{Job, JobStatus};
+use shared::{Breed, Goat, GoatUpdate, NewGoat};
+use std::time::Duration;
+
+/// Every goat in the herd.
+pub fn list_goats(client: &Client) -> Result<Vec<Goat>, CliError> {
+    client.get("/goats")
+}
+
+/// `goats` as an aligned text table, one goat per line.
+pub fn goats_table(goats: &[Goat]) -> String {
+    let mut lines = vec![format!(
+        "{:>5}  {:<20}  {:<12}  {:<6}  {:>8}  {}",
+        "ID", "Name", "Breed", "Gender", "Weight", "Health"
+    )];
+    lines.extend(goats.iter().map(|g| {
+        format!(
+            "{:>5}  {:<20}  {:<12}  {:<6}  {:>8.1}  {}",
+            g.id,
+            g.name,
+            Breed::to_str(&g.breed),
+            format!("{:?}", g.gender),
+            g.weight,
+            g.health_status
+        )
+    }));
+    lines.join("\n")
+}
+
+/// Adds `goat` to the herd and returns it as stored.
+pub fn add_goat(client: &Client, goat: &NewGoat) -> Result<Goat, CliError> {
+    client.send_json("POST", "/goats", goat)
+}
+
+/// Changes the fields of goat `id` set in `update`, returning the goat.
+pub fn update_goat(client: &Client, id: i64, update: &GoatUpdate) -> Result<Goat, CliError> {
+    if update.is_empty() {
+        return Err(CliError::invalid_input("Nothing to change"));
+    }
+    client.send_json("PATCH", &format!("/goats/{}", id), update)
+}
+
+/// What `import_csv` did with a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportOutcome {
+    /// Goats added, or queued to be added by `job`.
+    pub added: usize,
+    /// Rows left out for their errors.
+    pub skipped: Vec<ImportRow>,
+    /// The background job adding the goats, if one was asked for.
+    pub job: Option<Job>,
+}
+
+/// Imports the goats in a CSV or XLSX file, mapping its columns the way
+/// the import wizard first guesses them (see `shared::import`).
+///
+/// Rows with errors stop the import unless `skip_invalid` is set, in which
+/// case they are left out. With `background` the goats are added by a
+/// background job, for files too large to add in one request.
+pub fn import_csv(
+    client: &Client,
+    contents: &[u8],
+    skip_invalid: bool,
+    background: bool,
+) -> Result<ImportOutcome, CliError> {
+    let table: ImportTable =
+        client.post_bytes("/goats/import", "application/octet-stream", contents)?;
+    let mapping = guess_mapping(&table.headers);
+    let missing = missing_fields(&mapping);
+    if !missing.is_empty() {
+        let labels: Vec<&str> = missing.iter().map(|f| f.label()).collect();
+        return Err(CliError::invalid_input(format!(
+            "No column found for {}",
+            labels.join(", ")
+        )));
+    }
+    let (valid, skipped): (Vec<ImportRow>, Vec<ImportRow>) = preview(&table, &mapping)
+        .into_iter()
+        .partition(|row| row.errors.is_empty());
+    if !skipped.is_empty() && !skip_invalid {
+        let problems: Vec<String> = skipped
+            .iter()
+            .map(|row| format!("line {}: {}", row.line, row.errors.join("; ")))
+            .collect();
+        return Err(CliError::invalid_input(format!(
+            "{} rows have errors (pass --skip-invalid to leave them out):\n{}",
+            skipped.len(),
+            problems.join("\n")
+        )));
+    }
+    let goats: Vec<NewGoat> = valid.into_iter().filter_map(|row| row.goat).collect();
+    if goats.is_empty() {
+        return Err(CliError::invalid_input("The file has no goats to import"));
+    }
+    if background {
+        let job: Job = client.send_json("POST", "/jobs/import", &goats)?;
+        return Ok(ImportOutcome {
+            added: goats.len(),
+            skipped,
+            job: Some(job),
+        });
+    }
+    let summary: BatchSummary = client.send_json("POST", "/goats/batch", &goats)?;
+    Ok(ImportOutcome {
+        added: summary.added,
+        skipped,
+        job: None,
+    })
+}
+
+/// Downloads the farm archive, encrypted with `passphrase` if one is given.
+pub fn backup(client: &Client, passphrase: Option<&str>) -> Result<Vec<u8>, CliError> {
+    match passphrase {
+        Some(passphrase) => {
+            let encoded = utf8_percent_encode(passphrase, NON_ALPHANUMERIC).to_string();
+            client.get_bytes("/archive", &[(PASSPHRASE_HEADER, &encoded)])
+        }
+        None => client.get_bytes("/archive", &[]),
+    }
+}
+
+/// Output formats of census reports, as the backend names them.
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Csv,
+    Pdf,
+}
+
+impl ReportFormat {
+    /// Value of the `format` query parameter.
+    fn as_query(&self) -> &'static str {
+        match self {
+            ReportFormat::Json => "json",
+            ReportFormat::Csv => "csv",
+            ReportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+/// Body of `POST /jobs/census`.
+#[derive(Serialize)]
+struct CensusJobInput<'a> {
+    key: &'a str,
+    as_of: Option<&'a str>,
+    format: ReportFormat,
+}
+
+/// The census report laid out by template `key`, as of `as_of`
+/// (`YYYY-MM-DD`, default today), in `format`.
+pub fn census(
+    client: &Client,
+    key: &str,
+    as_of: Option<&str>,
+    format: ReportFormat,
+) -> Result<Vec<u8>, CliError> {
+    let mut path = format!(
+        "/reports/census/{}?format={}",
+        utf8_percent_encode(key, NON_ALPHANUMERIC),
+        format.as_query()
+    );
+    if let Some(as_of) = as_of {
+        path.push_str(&format!(
+            "&as_of={}",
+            utf8_percent_encode(as_of, NON_ALPHANUMERIC)
+        ));
+    }
+    client.get_bytes(&path, &[])
+}
+
+/// Like `census`, but builds the report in a background job, checking on
+/// it every `poll` until it is done.
+pub fn census_job(
+    client: &Client,
+    key: &str,
+    as_of: Option<&str>,
+    format: ReportFormat,
+    poll: Duration,
+) -> Result<Vec<u8>, CliError> {
+    let input = CensusJobInput { key, as_of, format };
+    let mut job: Job = client.send_json("POST", "/jobs/census", &input)?;
+    while !job.status.is_finished() {
+        std::thread::sleep(poll);
+        job = client.get(&format!("/jobs/{}", job.id))?;
+    }
+    if job.status == JobStatus::Failed {
+        return Err(CliError::JobFailed(
+            job.error.unwrap_or_else(|| job.description.clone()),
+        ));
+    }
+    client.get_bytes(&format!("/jobs/{}/result", job.id), &[])
+}
+
+/// Runs the saved export template `key`, returning the file it writes.
+pub fn export(client: &Client, key: &str) -> Result<Vec<u8>, CliError> {
+    let path = format!(
+        "/exports/run/{}",
+        utf8_percent_encode(key, NON_ALPHANUMERIC)
+    );
+    client.get_bytes(&path, &[])
+}
@@ -0,0 +1,57 @@
+//! Errors reported by the command-line tool.
+
+use thiserror::Error;
+
+/// Enumerates what can go wrong talking to the backend from the terminal.
+#[derive(Debug, Error)]
+pub enum CliError {
+    /// The backend could not be reached.
+    #[error("Network error: {0}")]
+    NetworkError(String),
+
+    /// The backend answered with a non-success status code.
+    /// `body` carries the backend's error message.
+    #[error("Server error {status}: {body}")]
+    ApiError { status: u16, body: String },
+
+    /// A request or response body could not be (de)serialized.
+    #[error("Malformed data: {0}")]
+    ParseError(String),
+
+    /// The arguments or an input file were not usable.
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    /// A background job gave up; carries its error.
+    #[error("Job failed: {0}")]
+    JobFailed(String),
+
+    /// A file could not be read or written.
+    #[error("File error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+impl CliError {
+    /// Creates an invalid input error with details.
+    pub fn invalid_input<S: Into<String>>(msg: S) -> Self {
+        CliError::InvalidInput(msg.into())
+    }
+}
+
+impl From<ureq::Error> for CliError {
+    fn from(e: ureq::Error) -> Self {
+        match e {
+            ureq::Error::Status(status, resp) => CliError::ApiError {
+                status,
+                body: resp.into_string().unwrap_or_default(),
+            },
+            ureq::Error::Transport(t) => CliError::NetworkError(t.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for CliError {
+    fn from(e: serde_json::Error) -> Self {
+        CliError::ParseError(e.to_string())
+    }
+}
@@ -0,0 +1,13 @@
+//! **Command-line companion to the Livestock Management backend**
+//!
+//! Lists, adds and changes goats, imports herds from CSV, downloads farm
+//! backups and builds census reports and saved exports from the terminal,
+//! through the same REST API and `shared` types as the dashboard. Scripts
+//! authenticate with a personal access token (see `shared::tokens`).
+
+pub mod client;
+pub mod commands;
+pub mod errors;
+
+pub use client::{Client, DEFAULT_API_URL};
+pub use errors::CliError;
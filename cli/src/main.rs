@@ -0,0 +1,286 @@
+//! `yagi-cli`: the backend's REST API from the terminal.
+//!
+//! `YAGI_API_URL` names the backend (default `yagi_cli::DEFAULT_API_URL`)
+//! and `YAGI_TOKEN` the personal access token to send, e.g. for a nightly
+//! census pull:
+//!
+//! ```text
+//! YAGI_TOKEN=yagi_... yagi-cli report census herd --format csv -o herd.csv
+//! ```
+//!
+//! On a multi-tenant backend `YAGI_TENANT_KEY` picks the farm.
+
+use clap::{Args, Parser, Subcommand};
+use shared::import::{parse_breed, parse_gender};
+use shared::{Goat, GoatParams, GoatUpdate};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+use yagi_cli::commands::{self, ReportFormat};
+use yagi_cli::{CliError, Client, DEFAULT_API_URL};
+
+/// How often a background report is checked on.
+const JOB_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Parser)]
+#[command(
+    name = "yagi-cli",
+    version,
+    about = "Manage a Yagi farm from the terminal"
+)]
+struct Cli {
+    /// Base URL of the backend
+    #[arg(long, env = "YAGI_API_URL", default_value = DEFAULT_API_URL)]
+    url: String,
+    /// Personal access token, sent as a bearer token
+    #[arg(long, env = "YAGI_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+    /// Tenant key of the farm, on a multi-tenant backend
+    #[arg(long, env = "YAGI_TENANT_KEY", hide_env_values = true)]
+    tenant_key: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List, add and change goats
+    #[command(subcommand)]
+    Goats(GoatsCommand),
+    /// Add the goats in a CSV or XLSX file
+    Import {
+        file: PathBuf,
+        /// Leave out rows with errors instead of stopping
+        #[arg(long)]
+        skip_invalid: bool,
+        /// Add the goats in a background job
+        #[arg(long)]
+        background: bool,
+    },
+    /// Download the farm archive
+    Backup {
+        /// File to save the archive to
+        output: PathBuf,
+        /// Encrypt the archive with this passphrase
+        #[arg(long, env = "YAGI_BACKUP_PASSPHRASE", hide_env_values = true)]
+        passphrase: Option<String>,
+    },
+    /// Build reports
+    #[command(subcommand)]
+    Report(ReportCommand),
+}
+
+#[derive(Subcommand)]
+enum GoatsCommand {
+    /// List the herd
+    List {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Add a goat
+    Add {
+        name: String,
+        #[command(flatten)]
+        fields: GoatFields,
+    },
+    /// Change some fields of a goat
+    Update {
+        id: i64,
+        #[arg(long)]
+        name: Option<String>,
+        #[command(flatten)]
+        fields: GoatFields,
+    },
+}
+
+/// Goat fields settable from the command line.
+#[derive(Args)]
+struct GoatFields {
+    #[arg(long)]
+    breed: Option<String>,
+    /// Male or female (also buck, doe, m, f)
+    #[arg(long)]
+    gender: Option<String>,
+    /// Weight in kg
+    #[arg(long)]
+    weight: Option<f64>,
+    #[arg(long)]
+    cost: Option<f64>,
+    #[arg(long)]
+    price: Option<f64>,
+    #[arg(long)]
+    diet: Option<String>,
+    #[arg(long)]
+    health: Option<String>,
+}
+
+impl GoatFields {
+    /// The fields as a change to a goat.
+    fn update(&self, name: Option<String>) -> Result<GoatUpdate, CliError> {
+        Ok(GoatUpdate {
+            name,
+            breed: self.breed.as_deref().map(parse_breed),
+            gender: self
+                .gender
+                .as_deref()
+                .map(parse_gender)
+                .transpose()
+                .map_err(CliError::InvalidInput)?,
+            weight: self.weight,
+            cost: self.cost,
+            current_price: self.price,
+            diet: self.diet.clone(),
+            health_status: self.health.clone(),
+            ..GoatUpdate::default()
+        })
+    }
+
+    /// A new goat called `name` with these fields.
+    fn new_goat(&self, name: &str) -> Result<GoatParams, CliError> {
+        let update = self.update(None)?;
+        let mut builder = GoatParams::builder(name);
+        if let Some(breed) = update.breed {
+            builder = builder.breed(breed);
+        }
+        if let Some(gender) = update.gender {
+            builder = builder.gender(gender);
+        }
+        if let Some(weight) = update.weight {
+            builder = builder.weight(weight);
+        }
+        if let Some(cost) = update.cost {
+            builder = builder.cost(cost);
+        }
+        if let Some(price) = update.current_price {
+            builder = builder.current_price(price);
+        }
+        if let Some(diet) = update.diet {
+            builder = builder.diet(diet);
+        }
+        if let Some(health) = update.health_status {
+            builder = builder.health_status(health);
+        }
+        builder
+            .build()
+            .map_err(|errors| CliError::InvalidInput(errors.join("; ")))
+    }
+}
+
+#[derive(Subcommand)]
+enum ReportCommand {
+    /// Headcount laid out by a report template
+    Census {
+        /// Key of the report template
+        key: String,
+        /// Census date (YYYY-MM-DD); defaults to today
+        #[arg(long)]
+        as_of: Option<String>,
+        #[arg(long, value_enum, default_value_t = ReportFormat::Json)]
+        format: ReportFormat,
+        /// File to save the report to; printed if not given
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Build the report in a background job and wait for it
+        #[arg(long)]
+        background: bool,
+    },
+    /// Goats written by a saved export template
+    Export {
+        /// Key of the export template
+        key: String,
+        /// File to save the export to; printed if not given
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Saves `report` to `output`, or prints it if no file is given.
+fn save_report(report: &[u8], output: Option<PathBuf>) -> Result<(), CliError> {
+    match output {
+        Some(output) => {
+            std::fs::write(&output, report)?;
+            println!("Saved {} bytes to {}", report.len(), output.display());
+        }
+        None => {
+            use std::io::Write;
+            std::io::stdout().write_all(report)?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints `goat` as stored after a change.
+fn print_goat(action: &str, goat: &Goat) {
+    println!("{} goat {} ({})", action, goat.id, goat.name);
+}
+
+fn run(cli: Cli) -> Result<(), CliError> {
+    let client = Client::new(&cli.url, cli.token).with_tenant_key(cli.tenant_key);
+    match cli.command {
+        Command::Goats(GoatsCommand::List { json }) => {
+            let goats = commands::list_goats(&client)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&goats)?);
+            } else {
+                println!("{}", commands::goats_table(&goats));
+            }
+        }
+        Command::Goats(GoatsCommand::Add { name, fields }) => {
+            let goat = commands::add_goat(&client, &fields.new_goat(&name)?)?;
+            print_goat("Added", &goat);
+        }
+        Command::Goats(GoatsCommand::Update { id, name, fields }) => {
+            let goat = commands::update_goat(&client, id, &fields.update(name)?)?;
+            print_goat("Updated", &goat);
+        }
+        Command::Import {
+            file,
+            skip_invalid,
+            background,
+        } => {
+            let contents = std::fs::read(&file)?;
+            let outcome = commands::import_csv(&client, &contents, skip_invalid, background)?;
+            for row in &outcome.skipped {
+                eprintln!("Skipped line {}: {}", row.line, row.errors.join("; "));
+            }
+            match &outcome.job {
+                Some(job) => println!("Queued job {} to add {} goats", job.id, outcome.added),
+                None => println!("Added {} goats", outcome.added),
+            }
+        }
+        Command::Backup { output, passphrase } => {
+            let archive = commands::backup(&client, passphrase.as_deref())?;
+            std::fs::write(&output, &archive)?;
+            println!("Saved {} bytes to {}", archive.len(), output.display());
+        }
+        Command::Report(ReportCommand::Census {
+            key,
+            as_of,
+            format,
+            output,
+            background,
+        }) => {
+            let report = if background {
+                commands::census_job(&client, &key, as_of.as_deref(), format, JOB_POLL_INTERVAL)?
+            } else {
+                commands::census(&client, &key, as_of.as_deref(), format)?
+            };
+            save_report(&report, output)?;
+        }
+        Command::Report(ReportCommand::Export { key, output }) => {
+            save_report(&commands::export(&client, &key)?, output)?;
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use actix_web::middleware::from_fn;
+use actix_web::{App, HttpServer, web};
+use backend::auth::{check_access_token, check_session, generate_key, hash_key};
+use backend::db::DbPool;
+use backend::permissions::enforce_permissions;
+use backend::routes;
+use backend::tenants::{TENANT_KEY_HEADER, TenantRegistry, resolve_tenant};
+use shared::exports::{ExportColumn, ExportFilter, ExportFormat, ExportTemplate};
+use shared::sessions::{IssuedSession, SESSION_HEADER};
+use shared::tokens::{IssuedAccessToken, NewAccessToken};
+use shared::{Breed, Gender, GoatParams, GoatUpdate};
+use std::net::TcpListener;
+use yagi_cli::commands::{self, ReportFormat};
+use yagi_cli::{CliError, Client};
+
+/// Starts a backend on a free port with a fresh database and returns its
+/// base URL and database. `name` must be unique per test.
+fn start_backend(name: &str) -> (String, DbPool) {
+    let path = std::env::temp_dir().join(format!("yagi_cli_{}_{}.db", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let db_pool = DbPool::new(path.to_str().unwrap()).expect("Failed to create DbPool");
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let server_pool = db_pool.clone();
+    std::thread::spawn(move || {
+        actix_web::rt::System::new().block_on(async move {
+            HttpServer::new(move || {
+                App::new()
+                    .wrap(from_fn(enforce_permissions))
+                    .wrap(from_fn(check_session))
+                    .wrap(from_fn(check_access_token))
+                    .app_data(web::Data::new(server_pool.clone()))
+                    .configure(routes::configure)
+            })
+            .workers(1)
+            .listen(listener)
+            .unwrap()
+            .run()
+            .await
+        })
+    });
+    (url, db_pool)
+}
+
+/// Issues `new_token` as the owner would from the dashboard: signed in on
+/// a device, whose session token goes in the `SESSION_HEADER` header.
+fn issue_token(url: &str, db_pool: &DbPool, new_token: &NewAccessToken) -> IssuedAccessToken {
+    let session = generate_key();
+    db_pool
+        .get_conn()
+        .unwrap()
+        .execute(
+            "INSERT INTO sessions (device, token_hash) VALUES ('Owner laptop', ?1)",
+            [hash_key(&session)],
+        )
+        .unwrap();
+    ureq::post(&format!("{}/tokens", url))
+        .set(SESSION_HEADER, &session)
+        .send_json(new_token)
+        .unwrap()
+        .into_json()
+        .unwrap()
+}
+
+/// A client acting for the owner, with a token that may do anything.
+fn owner_client(url: &str, db_pool: &DbPool) -> Client {
+    let new_token = NewAccessToken {
+        name: "Owner's terminal".to_string(),
+        read_only: false,
+        modules: Vec::new(),
+    };
+    Client::new(url, Some(issue_token(url, db_pool, &new_token).token))
+}
+
+#[test]
+fn test_goats_are_managed_and_imported_from_the_terminal() {
+    let (url, db_pool) = start_backend("goats");
+    let client = owner_client(&url, &db_pool);
+
+    let goat = GoatParams::builder("Nanny")
+        .breed(Breed::Beetal)
+        .weight(32.5)
+        .build()
+        .unwrap();
+    let added = commands::add_goat(&client, &goat).unwrap();
+    let update = GoatUpdate {
+        weight: Some(34.0),
+        ..GoatUpdate::default()
+    };
+    let updated = commands::update_goat(&client, added.id, &update).unwrap();
+    assert_eq!(updated.weight, 34.0);
+    assert!(matches!(
+        commands::update_goat(&client, added.id, &GoatUpdate::default()),
+        Err(CliError::InvalidInput(_))
+    ));
+
+    let csv = "Name,Breed,Sex,Weight (kg)\nBilly,sirohi,buck,41\nDaisy,Barbari,doe,abc\n";
+    let err = commands::import_csv(&client, csv.as_bytes(), false, false).unwrap_err();
+    assert!(err.to_string().contains("line 3"), "{}", err);
+    let outcome = commands::import_csv(&client, csv.as_bytes(), true, false).unwrap();
+    assert_eq!(outcome.added, 1);
+    assert_eq!(outcome.skipped.len(), 1);
+
+    let goats = commands::list_goats(&client).unwrap();
+    assert_eq!(goats.len(), 2);
+    let billy = goats.iter().find(|g| g.name == "Billy").unwrap();
+    assert_eq!(billy.breed, Breed::Sirohi);
+    assert_eq!(billy.gender, Gender::Male);
+    let table = commands::goats_table(&goats);
+    assert_eq!(table.lines().count(), 3);
+    assert!(table.contains("Nanny") && table.contains("34.0"));
+
+    let err = commands::import_csv(&client, b"Animal,Weight\nRosie,20\n", true, false).unwrap_err();
+    assert!(err.to_string().contains("Breed, Gender"), "{}", err);
+}
+
+#[test]
+fn test_backups_and_reports_use_access_tokens() {
+    let (url, db_pool) = start_backend("reports");
+    let owner = owner_client(&url, &db_pool);
+    let goat = GoatParams::builder("Nanny").build().unwrap();
+    commands::add_goat(&owner, &goat).unwrap();
+
+    let new_token = NewAccessToken {
+        name: "Nightly census".to_string(),
+        read_only: true,
+        modules: Vec::new(),
+    };
+    let issued = issue_token(&url, &db_pool, &new_token);
+    let script = Client::new(&url, Some(issued.token));
+
+    let csv = commands::census(
+        &script,
+        "breed-headcount",
+        Some("2026-03-31"),
+        ReportFormat::Csv,
+    )
+    .unwrap();
+    assert!(String::from_utf8(csv).unwrap().contains("Unknown"));
+    let archive = commands::backup(&script, None).unwrap();
+    assert!(archive.starts_with(b"PK"));
+    let sealed = commands::backup(&script, Some("correct horse battery")).unwrap();
+    assert!(!sealed.starts_with(b"PK"));
+
+    let goat = GoatParams::builder("Billy").build().unwrap();
+    match commands::add_goat(&script, &goat) {
+        Err(CliError::ApiError { status, body }) => {
+            assert_eq!(status, 403);
+            assert!(body.contains("read-only"), "{}", body);
+        }
+        other => panic!("Expected a refusal, got {:?}", other),
+    }
+    let err = commands::census(&script, "no-such-template", None, ReportFormat::Json).unwrap_err();
+    assert!(matches!(err, CliError::ApiError { status: 400, .. }));
+
+    let template = ExportTemplate {
+        key: "weights".to_string(),
+        name: "Weights".to_string(),
+        columns: vec![ExportColumn::Name, ExportColumn::Weight],
+        filter: ExportFilter::default(),
+        format: ExportFormat::Csv,
+    };
+    let _: ExportTemplate = owner
+        .send_json("POST", "/exports/templates", &template)
+        .unwrap();
+    let csv = commands::export(&script, "weights").unwrap();
+    assert_eq!(String::from_utf8(csv).unwrap(), "Name,Weight\nNanny,0\n");
+}
+
+/// Starts a multi-tenant backend on a free port with the farms `slugs` and
+/// returns its base URL and a client acting for each farm's owner.
+fn start_tenant_backend(name: &str, slugs: &[&str]) -> (String, Vec<Client>) {
+    let dir = std::env::temp_dir().join(format!("yagi_cli_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let registry = web::Data::new(TenantRegistry::open(&dir, "operator-secret").unwrap());
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let server_registry = registry.clone();
+    std::thread::spawn(move || {
+        actix_web::rt::System::new().block_on(async move {
+            HttpServer::new(move || {
+                App::new()
+                    .wrap(from_fn(enforce_permissions))
+                    .wrap(from_fn(check_session))
+                    .wrap(from_fn(check_access_token))
+                    .wrap(from_fn(resolve_tenant))
+                    .app_data(server_registry.clone())
+                    .configure(routes::configure)
+            })
+            .workers(1)
+            .listen(listener)
+            .unwrap()
+            .run()
+            .await
+        })
+    });
+
+    let owners = slugs
+        .iter()
+        .map(|slug| {
+            let issued = registry.create(slug, slug, "correct horse").unwrap();
+            let session: IssuedSession = ureq::post(&format!("{}/sessions", url))
+                .set(TENANT_KEY_HEADER, &issued.key)
+                .send_json(
+                    serde_json::json!({ "device": "Owner laptop", "secret": "correct horse" }),
+                )
+                .unwrap()
+                .into_json()
+                .unwrap();
+            let new_token = NewAccessToken {
+                name: "Owner's terminal".to_string(),
+                read_only: false,
+                modules: Vec::new(),
+            };
+            let token: IssuedAccessToken = ureq::post(&format!("{}/tokens", url))
+                .set(TENANT_KEY_HEADER, &issued.key)
+                .set(SESSION_HEADER, &session.token)
+                .send_json(&new_token)
+                .unwrap()
+                .into_json()
+                .unwrap();
+            Client::new(&url, Some(token.token)).with_tenant_key(Some(issued.key))
+        })
+        .collect();
+    (url, owners)
+}
+
+#[test]
+fn test_tenant_key_picks_the_farm() {
+    let (url, owners) = start_tenant_backend("tenants", &["green-valley", "hilltop"]);
+    let goat = GoatParams::builder("Nanny").build().unwrap();
+    commands::add_goat(&owners[0], &goat).unwrap();
+
+    let names = |client: &Client| -> Vec<String> {
+        commands::list_goats(client)
+            .unwrap()
+            .into_iter()
+            .map(|g| g.params.name)
+            .collect()
+    };
+    assert_eq!(names(&owners[0]), vec!["Nanny"]);
+    assert!(names(&owners[1]).is_empty());
+
+    let err = commands::list_goats(&Client::new(&url, None)).unwrap_err();
+    assert!(
+        matches!(err, CliError::ApiError { status: 401, .. }),
+        "{}",
+        err
+    );
+}
@@ -1,14 +1,49 @@
 use yew::prelude::*;
 
-use crate::components::{Dashboard, Sidebar};
+use crate::components::{
+    Dashboard, ErrorBoundary, MentionInbox, QuickSearch, ReadOnlyToggle, SessionsPanel,
+    SetupWizard, Sidebar, UndoControls, UnitSelect,
+};
+use crate::store::use_signed_out;
 
 #[function_component(App)]
 pub fn app() -> Html {
+    // Every request but signing in needs a session, so a device that is not
+    // signed in only gets the sign-in form
+    if use_signed_out() {
+        return html! {
+            <div>
+                <header style="display: flex; align-items: center; gap: 24px; padding: 8px 24px; background-color: #2c3e50; color: white;">
+                    <strong>{"Yagi"}</strong>
+                </header>
+                <div style="padding: 24px;">
+                    <ErrorBoundary name="Sign In">
+                        <SessionsPanel />
+                    </ErrorBoundary>
+                </div>
+            </div>
+        };
+    }
     // Provide GoatStore context to descendant components
     html! {
-        <div style="display: flex; min-height: 100vh;">
-            <Sidebar />
-            <Dashboard />
+        <div>
+            <header style="display: flex; align-items: center; gap: 24px; padding: 8px 24px; background-color: #2c3e50; color: white;">
+                <strong>{"Yagi"}</strong>
+                <QuickSearch />
+                <UndoControls />
+                <ReadOnlyToggle />
+                <UnitSelect />
+                <MentionInbox />
+            </header>
+            <ErrorBoundary name="Farm Setup">
+                <SetupWizard />
+            </ErrorBoundary>
+            <div style="display: flex; min-height: 100vh;">
+                <Sidebar />
+                <ErrorBoundary name="Dashboard">
+                    <Dashboard />
+                </ErrorBoundary>
+            </div>
         </div>
     }
 }
@@ -0,0 +1,251 @@
+//! Access tokens panel: the personal access tokens scripts use with the REST
+//! API, with their scope and last use, and making and revoking them (see
+//! `shared::tokens`).
+
+use crate::components::Spinner;
+use crate::services::use_api;
+use crate::store::{farm_timezone, use_read_only};
+use log::{error, info};
+use shared::permissions::PermissionModule;
+use shared::time::format_local;
+use shared::tokens::{AccessToken, MAX_TOKEN_NAME_LEN, NewAccessToken};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// AccessTokens component:
+/// Lists the access tokens and makes new ones, read-only or not and limited
+/// to the ticked modules (all of them if none is). A new token is shown
+/// once, to be copied into the script. Locked in read-only mode.
+#[function_component(AccessTokens)]
+pub fn access_tokens() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let tokens = use_state(|| None::<Vec<AccessToken>>);
+    let name = use_state(String::new);
+    let token_read_only = use_state(|| true);
+    let modules = use_state(Vec::<PermissionModule>::new);
+    // The token just made, shown until the next one
+    let issued = use_state(|| None::<String>);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let api = api.clone();
+        let tokens = tokens.clone();
+        let error = error.clone();
+        move |_| {
+            spawn_local(async move {
+                match api.access_tokens().await {
+                    Ok(loaded) => tokens.set(Some(loaded)),
+                    Err(e) => {
+                        error!("Failed to load access tokens: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+            || {}
+        }
+    });
+
+    let on_name = {
+        let name = name.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            name.set(input.value());
+        })
+    };
+
+    let on_read_only = {
+        let token_read_only = token_read_only.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            token_read_only.set(input.checked());
+        })
+    };
+
+    let on_module = {
+        let modules = modules.clone();
+        Callback::from(move |(module, on): (PermissionModule, bool)| {
+            let mut updated: Vec<PermissionModule> =
+                modules.iter().copied().filter(|m| *m != module).collect();
+            if on {
+                updated.push(module);
+                updated.sort();
+            }
+            modules.set(updated);
+        })
+    };
+
+    let on_create = {
+        let api = api.clone();
+        let tokens = tokens.clone();
+        let name = name.clone();
+        let token_read_only = token_read_only.clone();
+        let modules = modules.clone();
+        let issued = issued.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let new_token = NewAccessToken {
+                name: name.trim().to_string(),
+                read_only: *token_read_only,
+                modules: (*modules).clone(),
+            };
+            if let Err(e) = new_token.validate() {
+                error.set(Some(e));
+                return;
+            }
+            let api = api.clone();
+            let tokens = tokens.clone();
+            let name = name.clone();
+            let issued = issued.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.create_access_token(&new_token).await {
+                    Ok(created) => {
+                        info!("Created access token {}", created.access_token.id);
+                        let mut updated = (*tokens).clone().unwrap_or_default();
+                        updated.insert(0, created.access_token.clone());
+                        tokens.set(Some(updated));
+                        name.set(String::new());
+                        error.set(None);
+                        message.set(Some(format!(
+                            "Created {}. Copy the token now; it is not shown again.",
+                            created.access_token.name
+                        )));
+                        issued.set(Some(created.token));
+                    }
+                    Err(e) => {
+                        error!("Failed to create access token: {}", e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_revoke = {
+        let tokens = tokens.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |token: AccessToken| {
+            let api = api.clone();
+            let tokens = tokens.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.revoke_access_token(token.id).await {
+                    Ok(()) => {
+                        info!("Revoked access token {}", token.id);
+                        let updated = (*tokens).clone().map(|list| {
+                            list.into_iter()
+                                .map(|t| AccessToken {
+                                    revoked: t.revoked || t.id == token.id,
+                                    ..t
+                                })
+                                .collect()
+                        });
+                        tokens.set(updated);
+                        error.set(None);
+                        message.set(Some(format!("Revoked {}", token.name)));
+                    }
+                    Err(e) => {
+                        error!("Failed to revoke access token {}: {}", token.id, e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let timezone = farm_timezone();
+
+    html! {
+        <div>
+            <h3>{"Access Tokens"}</h3>
+            <p style="font-size: 12px;">
+                {"Scripts send a token as "}<code>{"Authorization: Bearer <token>"}</code>
+                {" to use the REST API, e.g. for a nightly CSV pull."}
+            </p>
+            <p>
+                <label>{"Name: "}
+                    <input class="token-name" value={(*name).clone()}
+                           maxlength={MAX_TOKEN_NAME_LEN.to_string()} oninput={on_name} />
+                </label>
+                {" "}
+                <label>
+                    <input type="checkbox" class="token-read-only"
+                           checked={*token_read_only} onchange={on_read_only} />
+                    {" Read-only"}
+                </label>
+            </p>
+            <p class="token-modules">
+                {"Modules (all if none ticked): "}
+                { for PermissionModule::ALL.into_iter().map(|module| {
+                    let onchange = on_module.reform(move |e: Event| {
+                        let input: HtmlInputElement = e.target_unchecked_into();
+                        (module, input.checked())
+                    });
+                    html! {
+                        <label style="margin-right: 8px;">
+                            <input type="checkbox" title={module.label()}
+                                   checked={modules.contains(&module)} {onchange} />
+                            {format!(" {}", module.label())}
+                        </label>
+                    }
+                }) }
+            </p>
+            <button onclick={on_create} disabled={read_only}>{"Create token"}</button>
+            if let Some(token) = &*issued {
+                <p>{"New token: "}<code class="issued-token">{token}</code></p>
+            }
+            if let Some(tokens) = &*tokens {
+                if tokens.is_empty() {
+                    <p>{"No access tokens yet."}</p>
+                } else {
+                    <table class="access-tokens">
+                        <tr><th>{"Name"}</th><th>{"Scope"}</th><th>{"Created"}</th><th>{"Last used"}</th><th></th></tr>
+                        { for tokens.iter().map(|t| {
+                            let token = t.clone();
+                            let on_revoke = on_revoke.reform(move |_: MouseEvent| token.clone());
+                            html! {
+                                <tr style={t.revoked.then_some("color: #999;")}>
+                                    <td>
+                                        {&t.name}
+                                        if let Some(worker) = &t.worker {
+                                            <span style="font-size: 12px; color: #666;">{format!(" by {}", worker)}</span>
+                                        }
+                                    </td>
+                                    <td class="token-scope">{t.scope()}</td>
+                                    <td>{format_local(&t.created_at, timezone)}</td>
+                                    <td class="token-last-used">
+                                        {t.last_used_at.map(|at| format_local(&at, timezone)).unwrap_or_else(|| "Never".to_string())}
+                                    </td>
+                                    <td>
+                                        if t.revoked {
+                                            {"Revoked"}
+                                        } else {
+                                            <button class="revoke-token" onclick={on_revoke} disabled={read_only}>{"Revoke"}</button>
+                                        }
+                                    </td>
+                                </tr>
+                            }
+                        }) }
+                    </table>
+                }
+            } else {
+                <Spinner label="Loading access tokens..." />
+            }
+            if let Some(msg) = &*message {
+                <p style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Access token error: {}", err)}</p>
+            }
+        </div>
+    }
+}
@@ -1,6 +1,20 @@
+use crate::services::use_api;
+use crate::store::UnitsStore;
+use log::{error, info};
 use shared::Breed;
+use shared::breeds::{
+    BreedProfile, BreedPurpose, CatalogBreed, breed_profile, builtin_catalog, search_catalog,
+};
+use shared::units::format_decimal;
+use wasm_bindgen_futures::spawn_local;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// Reference profile of the breed selected in a `BreedInput`, if known.
+pub fn profile_for(selected: &str) -> Option<BreedProfile> {
+    breed_profile(&Breed::from_str(selected))
+}
 
 /// Props for BreedInput:
 /// - `selected`: current breed value as string (e.g. "Beetal" or "Other")
@@ -15,22 +29,92 @@ pub struct BreedInputProps {
     pub on_other_change: Callback<String>,
 }
 
+/// The breed options shown for `purpose` and `query`: matching catalog
+/// breeds, then the current `selected` breed if it was filtered out (so the
+/// select keeps showing it), then "Other".
+pub fn breed_options(
+    catalog: &[CatalogBreed],
+    purpose: Option<BreedPurpose>,
+    query: &str,
+    selected: &str,
+) -> Vec<String> {
+    let mut options: Vec<String> = search_catalog(catalog, purpose, query)
+        .into_iter()
+        .map(|b| b.name.clone())
+        .collect();
+    if selected != "Other" && !selected.is_empty() && !options.iter().any(|o| o == selected) {
+        options.push(selected.to_string());
+    }
+    options.push("Other".to_string());
+    options
+}
+
+/// Breed picker: a purpose select and a search box narrow down the breed
+/// select, which lists the farm's breed catalog. Until the catalog loads,
+/// or if it fails to, the built-in breeds are offered.
+///
+/// Below it, a known breed's typical adult weight, milk yield and
+/// recommended vaccines are shown as a hint for the rest of the form.
 #[function_component(BreedInput)]
 pub fn breed_input(props: &BreedInputProps) -> Html {
+    let api = use_api();
+    let (units, _) = use_store::<UnitsStore>();
+    let catalog = use_state(builtin_catalog);
+    let purpose = use_state(|| None::<BreedPurpose>);
+    let query = use_state(String::new);
+
+    {
+        let catalog = catalog.clone();
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                match api.breed_catalog().await {
+                    Ok(loaded) => {
+                        info!("Loaded {} catalog breeds", loaded.len());
+                        catalog.set(loaded);
+                    }
+                    Err(e) => error!("Failed to load breed catalog: {}", e),
+                }
+            });
+        });
+    }
+
     let is_other = props.selected == "Other";
-    let breed_options = vec![
-        "Beetal",
-        "Jamunapari",
-        "Barbari",
-        "Sirohi",
-        "Osmanabadi",
-        "BlackBengal",
-        "Kutchi",
-        "Kaghani",
-        "Chegu",
-        "Jakhrana",
-        "Other",
-    ];
+    let hint = profile_for(&props.selected).map(|profile| {
+        let weight = |kg: f64| format_decimal(units.weight.from_kg(kg), 0, units.decimal_separator);
+        let (low, high) = profile.adult_weight_kg;
+        format!(
+            "Adults weigh {}–{} {}; does give about {} L of milk a day. Recommended vaccines: {}.",
+            weight(low),
+            weight(high),
+            units.weight.symbol(),
+            format_decimal(profile.milk_litres_per_day, 1, units.decimal_separator),
+            profile.vaccines.join(", ")
+        )
+    });
+    let options = breed_options(&catalog, *purpose, &query, &props.selected);
+    let purpose_of = |name: &str| {
+        catalog
+            .iter()
+            .find(|b| b.name == name)
+            .map(|b| b.purpose.label())
+    };
+
+    let on_purpose_change = {
+        let purpose = purpose.clone();
+        Callback::from(move |e: Event| {
+            let select = e.target_dyn_into::<web_sys::HtmlSelectElement>().unwrap();
+            purpose.set(BreedPurpose::from_str(&select.value()).ok());
+        })
+    };
+
+    let on_search_input = {
+        let query = query.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                query.set(input.value());
+            }
+        })
+    };
 
     let on_select_change = {
         let cb = props.on_breed_change.clone();
@@ -51,9 +135,31 @@ pub fn breed_input(props: &BreedInputProps) -> Html {
 
     html! {
         <>
-            <select value={props.selected.clone()} onchange={on_select_change}>
-                { for breed_options.iter().map(|option| html! {
-                    <option value={option.to_string()} selected={*option == props.selected}>{option}</option>
+            <select class="breed-purpose" onchange={on_purpose_change}>
+                <option value="" selected={purpose.is_none()}>{"All purposes"}</option>
+                { for BreedPurpose::ALL.iter().map(|p| html! {
+                    <option value={BreedPurpose::to_str(p)} selected={*purpose == Some(*p)}>
+                        {p.label()}
+                    </option>
+                })}
+            </select>
+            <input
+                type="search"
+                class="breed-search"
+                placeholder="Search breeds"
+                value={(*query).clone()}
+                oninput={on_search_input}
+            />
+            <select name="breed" value={props.selected.clone()} onchange={on_select_change}>
+                { for options.iter().map(|option| {
+                    // With no purpose chosen, each breed says what it is kept for
+                    let label = match purpose_of(option) {
+                        Some(kept_for) if purpose.is_none() => format!("{} ({})", option, kept_for),
+                        _ => option.clone(),
+                    };
+                    html! {
+                        <option value={option.clone()} selected={*option == props.selected}>{label}</option>
+                    }
                 })}
             </select>
             if is_other {
@@ -64,6 +170,9 @@ pub fn breed_input(props: &BreedInputProps) -> Html {
                     oninput={on_other_input}
                 />
             }
+            if let Some(hint) = hint {
+                <small class="breed-hints" style="display: block; color: #666;">{hint}</small>
+            }
         </>
     }
 }
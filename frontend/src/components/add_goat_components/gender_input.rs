@@ -11,7 +11,7 @@ pub struct GenderInputProps {
 
 #[function_component(GenderInput)]
 pub fn gender_input(props: &GenderInputProps) -> Html {
-    let options = vec!["Male", "Female"];
+    let options = ["Male", "Female"];
     let on_select_change = {
         let cb = props.on_gender_change.clone();
         Callback::from(move |e: Event| {
@@ -21,7 +21,7 @@ pub fn gender_input(props: &GenderInputProps) -> Html {
     };
 
     html! {
-        <select value={props.selected.clone()} onchange={on_select_change}>
+        <select name="gender" value={props.selected.clone()} onchange={on_select_change}>
             { for options.iter().map(|option| html! {
                 <option value={option.to_string()} selected={*option == props.selected}>{option}</option>
             })}
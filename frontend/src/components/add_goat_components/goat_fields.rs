@@ -0,0 +1,85 @@
+use crate::components::add_goat_wizard::GoatDraft;
+use yew::prelude::*;
+
+/// State handles for the goat fields shared by the add and update forms,
+/// each holding the text as typed.
+#[derive(Clone, PartialEq)]
+pub struct GoatFields {
+    pub name: UseStateHandle<String>,
+    pub breed: UseStateHandle<String>,
+    pub other_breed: UseStateHandle<String>,
+    pub gender: UseStateHandle<String>,
+    pub offspring: UseStateHandle<String>,
+    pub cost: UseStateHandle<String>,
+    pub weight: UseStateHandle<String>,
+    pub current_price: UseStateHandle<String>,
+    pub diet: UseStateHandle<String>,
+    pub last_bred: UseStateHandle<String>,
+    pub health_status: UseStateHandle<String>,
+    pub horns: UseStateHandle<String>,
+    pub coat_color: UseStateHandle<String>,
+    pub marks: UseStateHandle<String>,
+}
+
+impl GoatFields {
+    /// The fields as a draft. Vaccinations and diseases, which these forms
+    /// do not edit, are left blank.
+    pub fn draft(&self) -> GoatDraft {
+        GoatDraft {
+            name: (*self.name).clone(),
+            breed: (*self.breed).clone(),
+            other_breed: (*self.other_breed).clone(),
+            gender: (*self.gender).clone(),
+            offspring: (*self.offspring).clone(),
+            cost: (*self.cost).clone(),
+            weight: (*self.weight).clone(),
+            current_price: (*self.current_price).clone(),
+            diet: (*self.diet).clone(),
+            last_bred: (*self.last_bred).clone(),
+            health_status: (*self.health_status).clone(),
+            horns: (*self.horns).clone(),
+            coat_color: (*self.coat_color).clone(),
+            marks: (*self.marks).clone(),
+            ..GoatDraft::default()
+        }
+    }
+
+    /// Overwrites every field with `draft`'s value.
+    pub fn fill(&self, draft: &GoatDraft) {
+        self.name.set(draft.name.clone());
+        self.breed.set(draft.breed.clone());
+        self.other_breed.set(draft.other_breed.clone());
+        self.gender.set(draft.gender.clone());
+        self.offspring.set(draft.offspring.clone());
+        self.cost.set(draft.cost.clone());
+        self.weight.set(draft.weight.clone());
+        self.current_price.set(draft.current_price.clone());
+        self.diet.set(draft.diet.clone());
+        self.last_bred.set(draft.last_bred.clone());
+        self.health_status.set(draft.health_status.clone());
+        self.horns.set(draft.horns.clone());
+        self.coat_color.set(draft.coat_color.clone());
+        self.marks.set(draft.marks.clone());
+    }
+}
+
+/// Creates the field states, starting from `initial`'s values.
+#[hook]
+pub fn use_goat_fields(initial: &GoatDraft) -> GoatFields {
+    GoatFields {
+        name: use_state(|| initial.name.clone()),
+        breed: use_state(|| initial.breed.clone()),
+        other_breed: use_state(|| initial.other_breed.clone()),
+        gender: use_state(|| initial.gender.clone()),
+        offspring: use_state(|| initial.offspring.clone()),
+        cost: use_state(|| initial.cost.clone()),
+        weight: use_state(|| initial.weight.clone()),
+        current_price: use_state(|| initial.current_price.clone()),
+        diet: use_state(|| initial.diet.clone()),
+        last_bred: use_state(|| initial.last_bred.clone()),
+        health_status: use_state(|| initial.health_status.clone()),
+        horns: use_state(|| initial.horns.clone()),
+        coat_color: use_state(|| initial.coat_color.clone()),
+        marks: use_state(|| initial.marks.clone()),
+    }
+}
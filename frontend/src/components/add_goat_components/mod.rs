@@ -1,5 +1,9 @@
 pub mod breed_input;
 pub mod gender_input;
+pub mod goat_fields;
+pub mod traits_input;
 
-pub use breed_input::BreedInput;
+pub use breed_input::{BreedInput, profile_for};
 pub use gender_input::GenderInput;
+pub use goat_fields::{GoatFields, use_goat_fields};
+pub use traits_input::TraitsInput;
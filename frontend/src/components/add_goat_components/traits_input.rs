@@ -0,0 +1,90 @@
+use shared::import::GoatField;
+use shared::physical::{CoatColor, HornStatus};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+/// Props for TraitsInput.
+/// - `horns`, `coat_color`: selected values as their database strings, or
+///   "" when not recorded
+/// - `marks`: distinguishing marks as typed
+/// - `on_*_change`: callbacks triggered when a value changes
+/// - `dirty`: fields whose label is marked as edited
+#[derive(Properties, PartialEq)]
+pub struct TraitsInputProps {
+    pub horns: String,
+    pub coat_color: String,
+    pub marks: String,
+    pub on_horns_change: Callback<String>,
+    pub on_coat_color_change: Callback<String>,
+    pub on_marks_change: Callback<String>,
+    #[prop_or_default]
+    pub dirty: Vec<GoatField>,
+}
+
+/// A dropdown whose first option, "Not recorded", has the value "".
+fn trait_select<'a>(
+    name: &'static str,
+    selected: &str,
+    options: impl Iterator<Item = (&'a str, &'static str)>,
+    on_change: &Callback<String>,
+) -> Html {
+    let onchange = {
+        let cb = on_change.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                cb.emit(select.value());
+            }
+        })
+    };
+    html! {
+        <select {name} {onchange}>
+            <option value="" selected={selected.is_empty()}>{"Not recorded"}</option>
+            { for options.map(|(value, label)| html! {
+                <option value={value.to_string()} selected={value == selected}>{label}</option>
+            })}
+        </select>
+    }
+}
+
+/// TraitsInput component:
+/// Horn status and coat color dropdowns plus a text field for marks, the
+/// physical traits used to tell goats apart.
+#[function_component(TraitsInput)]
+pub fn traits_input(props: &TraitsInputProps) -> Html {
+    let dirty_class = |field: GoatField| props.dirty.contains(&field).then_some("dirty");
+    let on_marks = {
+        let cb = props.on_marks_change.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                cb.emit(input.value());
+            }
+        })
+    };
+
+    html! {
+        <>
+            <label class={classes!(dirty_class(GoatField::Horns))}>{"Horns: "}
+                { trait_select(
+                    "horns",
+                    &props.horns,
+                    HornStatus::ALL.iter().map(|h| (HornStatus::to_str(h), h.label())),
+                    &props.on_horns_change,
+                ) }
+            </label>
+            <br/>
+            <label class={classes!(dirty_class(GoatField::CoatColor))}>{"Coat Color: "}
+                { trait_select(
+                    "coat_color",
+                    &props.coat_color,
+                    CoatColor::ALL.iter().map(|c| (CoatColor::to_str(c), c.label())),
+                    &props.on_coat_color_change,
+                ) }
+            </label>
+            <br/>
+            <label class={classes!(dirty_class(GoatField::Marks))}>{"Marks: "}
+                <input type="text" name="marks" placeholder="e.g. white blaze, notched left ear"
+                       value={props.marks.clone()} oninput={on_marks} />
+            </label>
+        </>
+    }
+}
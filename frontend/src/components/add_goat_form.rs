@@ -2,17 +2,45 @@
 //! Features:
 //! - Controlled local form state
 //! - Field-level validation and global error handling
+//! - Asks for confirmation before saving unusual values
 //! - Logging for all stages
 //! - Calls async store action to submit to backend
+//! - Autosaves the entry as a draft until it is submitted or discarded
+//! - Shows the tag the backend will assign from the farm's tag series
 
-use crate::components::add_goat_components::{BreedInput, GenderInput};
+use crate::components::add_goat_components::{
+    BreedInput, GenderInput, TraitsInput, profile_for, use_goat_fields,
+};
+use crate::components::add_goat_wizard::GoatDraft;
+use crate::components::date_picker::date_problem;
+use crate::components::error_boundary::use_section_error;
+use crate::components::number_field::text_setter;
+use crate::components::{
+    DatePicker, DraftBar, NumberField, Quantity, SoftWarnings, Spinner, use_soft_warnings,
+};
+use crate::drafts::{discard_draft, load_draft, use_autosave};
+use crate::services::use_api;
 use crate::store::GoatStore;
 use log::{error, info};
+use shared::import::goat_warnings;
 use shared::{Breed, DiseaseRef, Gender, GoatParams, VaccineRef};
+use wasm_bindgen_futures::spawn_local;
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use yewdux::prelude::use_store;
 
+/// Name the form's draft is saved under.
+pub const ADD_GOAT_DRAFT: &str = "add_goat";
+
+/// The form's values before anything is typed.
+fn blank_form() -> GoatDraft {
+    GoatDraft {
+        offspring: String::new(),
+        health_status: String::new(),
+        ..GoatDraft::default()
+    }
+}
+
 /// Helper function to trim and check a string input field.
 fn required_field(field: &str) -> Option<String> {
     let trimmed = field.trim();
@@ -26,17 +54,36 @@ fn required_field(field: &str) -> Option<String> {
 /// The AddGoatForm component allows entry and submission of a new Goat.
 #[function_component(AddGoatForm)]
 pub fn add_goat_form() -> Html {
-    let name = use_state(|| "".to_string());
-    let breed = use_state(|| "Beetal".to_string());
-    let other_breed = use_state(|| "".to_string());
-    let gender = use_state(|| "Male".to_string());
-    let offspring = use_state(|| "".to_string());
-    let cost = use_state(|| "".to_string());
-    let weight = use_state(|| "".to_string());
-    let current_price = use_state(|| "".to_string());
-    let diet = use_state(|| "".to_string());
-    let last_bred = use_state(|| "".to_string());
-    let health_status = use_state(|| "".to_string());
+    let saved = use_memo((), |_| load_draft::<GoatDraft>(ADD_GOAT_DRAFT));
+    let fields = use_goat_fields(&(*saved).clone().unwrap_or_else(blank_form));
+    let restored = use_state(|| saved.is_some());
+    use_autosave(
+        Some(ADD_GOAT_DRAFT.to_string()),
+        fields.draft(),
+        blank_form(),
+    );
+    let name = fields.name.clone();
+    let breed = fields.breed.clone();
+    let other_breed = fields.other_breed.clone();
+    let gender = fields.gender.clone();
+    let offspring = fields.offspring.clone();
+    let cost = fields.cost.clone();
+    let weight = fields.weight.clone();
+    let current_price = fields.current_price.clone();
+    let diet = fields.diet.clone();
+    let last_bred = fields.last_bred.clone();
+    let health_status = fields.health_status.clone();
+
+    let on_discard = {
+        let fields = fields.clone();
+        let restored = restored.clone();
+        Callback::from(move |_: MouseEvent| {
+            info!("Discarding add-goat draft");
+            discard_draft(ADD_GOAT_DRAFT);
+            fields.fill(&blank_form());
+            restored.set(false);
+        })
+    };
 
     // Callbacks for breed/gender (used by subcomponents)
     let update_breed = {
@@ -53,10 +100,30 @@ pub fn add_goat_form() -> Html {
     };
 
     let error = use_state(|| None::<String>);
+    let warnings = use_soft_warnings();
+
+    let (state, dispatch) = use_store::<GoatStore>();
+    let api = use_api();
+    let report_failure = use_section_error();
+    let next_tag = use_state(|| None::<String>);
 
-    let (_state, dispatch) = use_store::<GoatStore>();
+    // Reloaded after each goat added, which uses up the previewed tag
+    use_effect_with(state.goats.len(), {
+        let api = api.clone();
+        let next_tag = next_tag.clone();
+        move |_: &usize| {
+            spawn_local(async move {
+                match api.next_tag().await {
+                    Ok(next) => next_tag.set(next.tag_id),
+                    Err(e) => error!("Failed to load the next tag: {}", e),
+                }
+            });
+            || {}
+        }
+    });
 
     let onsubmit = {
+        let api = api.clone();
         let name = name.clone();
         let breed = breed.clone();
         let other_breed = other_breed.clone();
@@ -68,7 +135,10 @@ pub fn add_goat_form() -> Html {
         let diet = diet.clone();
         let last_bred = last_bred.clone();
         let health_status = health_status.clone();
+        let fields = fields.clone();
+        let restored = restored.clone();
         let error = error.clone();
+        let warnings = warnings.clone();
 
         Callback::from(move |evt: SubmitEvent| {
             evt.prevent_default();
@@ -124,6 +194,15 @@ pub fn add_goat_form() -> Html {
                 }
             };
 
+            // Bucks have no breeding date; a date typed before switching
+            // gender is kept in the form but not saved
+            let breeding = fields.draft().has_breeding_records();
+            if breeding && let Some(problem) = date_problem("Last bred", &last_bred) {
+                error!("Validation failed: {}", problem);
+                error.set(Some(problem));
+                return;
+            }
+
             // Build breed and gender
             let selected_breed = if breed.as_str() == "Other" {
                 Breed::Other((*other_breed).clone())
@@ -131,33 +210,52 @@ pub fn add_goat_form() -> Html {
                 Breed::from_str(&(*breed).clone())
             };
 
-            let selected_gender = Gender::from_str(&(*gender).clone()).unwrap();
-
-            // Create GoatParams (handle other fields or optional fields as needed)
-            let goat = GoatParams {
-                name: (*name).clone(),
-                breed: selected_breed,
-                gender: selected_gender,
-                offspring: offspring_val as i32, // or keep as f64 if that's what your struct expects
-                cost: cost_val,
-                weight: weight_val,
-                current_price: current_price_val,
-                diet: (*diet).clone(),
-                last_bred: Some((*last_bred).clone()),
-                health_status: (*health_status).clone(),
-                vaccinations: vec![],
-                diseases: vec![],
+            // GenderInput only offers valid options, so a parse failure means corrupted form state
+            let selected_gender = match Gender::from_str(&(*gender).clone()) {
+                Ok(g) => g,
+                Err(other) => {
+                    report_failure.emit(format!("Unknown gender '{}' in form state", other));
+                    return;
+                }
             };
 
-            // Call the store: GoatStore::add_goat_async(dispatch, new_goat);
+            // Create GoatParams, leaving the breeding date and any trait
+            // not picked unset
+            let mut builder = fields
+                .draft()
+                .with_traits(GoatParams::builder((*name).clone()))
+                .breed(selected_breed)
+                .gender(selected_gender)
+                .offspring(offspring_val as i32)
+                .cost(cost_val)
+                .weight(weight_val)
+                .current_price(current_price_val)
+                .diet((*diet).clone())
+                .health_status((*health_status).clone());
+            if breeding && !last_bred.trim().is_empty() {
+                builder = builder.last_bred(last_bred.trim());
+            }
+            let goat = match builder.build() {
+                Ok(goat) => goat,
+                Err(errors) => {
+                    error!("Validation failed: {:?}", errors);
+                    error.set(Some(errors.join(" ")));
+                    return;
+                }
+            };
+            error.set(None);
+            if warnings.hold(goat_warnings(&goat)) {
+                info!("Holding new goat until its warnings are confirmed");
+                return;
+            }
 
             // Make request to backend and update store
             info!("Submitting new goat: {:?}", goat);
-            GoatStore::add_goat_async(dispatch.clone(), goat);
+            GoatStore::add_goat_async(api.clone(), dispatch.clone(), goat);
 
-            // Optionally, reset form state
-            name.set("".to_owned());
-            breed.set("".to_owned());
+            // Reset the form, which also drops the draft
+            fields.fill(&blank_form());
+            restored.set(false);
             error.set(None);
         })
     };
@@ -168,6 +266,9 @@ pub fn add_goat_form() -> Html {
             if let Some(msg) = &*error {
                 <p style="color: red;">{msg}</p>
             }
+            if fields.draft() != blank_form() {
+                <DraftBar restored={*restored} {on_discard} />
+            }
             <form onsubmit={onsubmit}>
                 <label>{ "Name:" }
                     <input
@@ -185,6 +286,14 @@ pub fn add_goat_form() -> Html {
                 </label>
                 <br/>
 
+                if let Some(tag) = &*next_tag {
+                    <label>{ "Tag:" }
+                        <input type="text" class="next-tag" value={tag.clone()} readonly=true />
+                        {" (assigned when the goat is added)"}
+                    </label>
+                    <br/>
+                }
+
                 <label>{ "Breed:" }
                     <BreedInput
                         selected={(*breed).clone()}
@@ -220,52 +329,35 @@ pub fn add_goat_form() -> Html {
                 <br/>
 
                 <label>{ "Cost:" }
-                    <input
-                        type="number"
-                        step="0.01"
-                        value={(*cost).clone()}
-                        oninput={Callback::from({
-                            let cost = cost.clone();
-                            move |e: InputEvent| {
-                                if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
-                                    cost.set(input.value());
-                                }
-                            }
-                        })}
+                    <NumberField
+                        name="cost"
+                        quantity={Quantity::Money}
+                        value={cost.parse::<f64>().ok()}
+                        min={0.0}
+                        onchange={text_setter(&cost)}
                     />
                 </label>
                 <br/>
 
                 <label>{ "Weight:" }
-                    <input
-                        type="number"
-                        step="0.01"
-                        value={(*weight).clone()}
-                        oninput={Callback::from({
-                            let weight = weight.clone();
-                            move |e: InputEvent| {
-                                if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
-                                    weight.set(input.value());
-                                }
-                            }
-                        })}
+                    <NumberField
+                        name="weight"
+                        quantity={Quantity::Weight}
+                        value={weight.parse::<f64>().ok()}
+                        min={0.0}
+                        typical={profile_for(&breed).map(|p| p.adult_weight_kg)}
+                        onchange={text_setter(&weight)}
                     />
                 </label>
                 <br/>
 
                 <label>{ "Current Price:" }
-                    <input
-                        type="number"
-                        step="0.01"
-                        value={(*current_price).clone()}
-                        oninput={Callback::from({
-                            let current_price = current_price.clone();
-                            move |e: InputEvent| {
-                                if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
-                                    current_price.set(input.value());
-                                }
-                            }
-                        })}
+                    <NumberField
+                        name="current_price"
+                        quantity={Quantity::Money}
+                        value={current_price.parse::<f64>().ok()}
+                        min={0.0}
+                        onchange={text_setter(&current_price)}
                     />
                 </label>
                 <br/>
@@ -286,22 +378,40 @@ pub fn add_goat_form() -> Html {
                 </label>
                 <br/>
 
-                <label>{ "Last Bred:" }
-                    <input
-                        type="text"
-                        value={(*last_bred).clone()}
-                        oninput={Callback::from({
-                            let last_bred = last_bred.clone();
-                            move |e: InputEvent| {
-                                if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
-                                    last_bred.set(input.value());
-                                }
-                            }
-                        })}
-                    />
-                </label>
+                <TraitsInput
+                    horns={(*fields.horns).clone()}
+                    coat_color={(*fields.coat_color).clone()}
+                    marks={(*fields.marks).clone()}
+                    on_horns_change={{
+                        let horns = fields.horns.clone();
+                        Callback::from(move |v| horns.set(v))
+                    }}
+                    on_coat_color_change={{
+                        let coat_color = fields.coat_color.clone();
+                        Callback::from(move |v| coat_color.set(v))
+                    }}
+                    on_marks_change={{
+                        let marks = fields.marks.clone();
+                        Callback::from(move |v| marks.set(v))
+                    }}
+                />
                 <br/>
 
+                if fields.draft().has_breeding_records() {
+                    <label>{ "Last Bred:" }
+                        <DatePicker
+                            name="last_bred"
+                            label="Last bred"
+                            value={(*last_bred).clone()}
+                            onchange={{
+                                let last_bred = last_bred.clone();
+                                Callback::from(move |date: String| last_bred.set(date))
+                            }}
+                        />
+                    </label>
+                    <br/>
+                }
+
                 <label>{ "Health Status:" }
                     <input
                         type="text"
@@ -317,7 +427,17 @@ pub fn add_goat_form() -> Html {
                     />
                 </label>
                 <br/>
-                <button type="submit">{"Add Goat"}</button>
+                <SoftWarnings
+                    warnings={warnings.shown()}
+                    on_confirm={{
+                        let warnings = warnings.clone();
+                        Callback::from(move |_| warnings.confirm())
+                    }}
+                />
+                <button type="submit" disabled={state.loading.adding}>{"Add Goat"}</button>
+                if state.loading.adding {
+                    { " " }<Spinner label="Saving goat..." />
+                }
             </form>
         </div>
     }
@@ -0,0 +1,654 @@
+//! Step-by-step variant of `AddGoatForm`: Identity, Physical, Health and
+//! Finance pages followed by a review, with the entry savable as a draft in
+//! between (see `crate::drafts`).
+
+use crate::components::add_goat_components::{BreedInput, GenderInput, TraitsInput, profile_for};
+use crate::components::date_picker::date_problem;
+use crate::components::{
+    DatePicker, NumberField, Quantity, SoftWarnings, Spinner, use_soft_warnings,
+};
+use crate::drafts::{discard_draft, load_draft, save_draft};
+use crate::services::use_api;
+use crate::store::GoatStore;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use shared::import::{GoatField, goat_warnings};
+use shared::physical::{CoatColor, HornStatus};
+use shared::{Breed, Gender, GoatParams, GoatParamsBuilder};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// Name the wizard's draft is saved under.
+pub const DRAFT_FORM: &str = "add_goat_wizard";
+
+/// A page of the wizard, in order.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Step {
+    #[default]
+    Identity,
+    Physical,
+    Health,
+    Finance,
+    Review,
+}
+
+impl Step {
+    pub const ALL: [Step; 5] = [
+        Step::Identity,
+        Step::Physical,
+        Step::Health,
+        Step::Finance,
+        Step::Review,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Step::Identity => "Identity",
+            Step::Physical => "Physical",
+            Step::Health => "Health",
+            Step::Finance => "Finance",
+            Step::Review => "Review",
+        }
+    }
+
+    /// Position of the step, starting at 0.
+    pub fn index(&self) -> usize {
+        Step::ALL.iter().position(|s| s == self).unwrap_or(0)
+    }
+
+    fn next(&self) -> Step {
+        Step::ALL[(self.index() + 1).min(Step::ALL.len() - 1)]
+    }
+
+    fn previous(&self) -> Step {
+        Step::ALL[self.index().saturating_sub(1)]
+    }
+}
+
+/// The wizard's fields as typed, plus the page the user is on. This is what
+/// gets saved as a draft, so it holds text rather than parsed values.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct GoatDraft {
+    pub step: Step,
+    pub name: String,
+    pub breed: String,
+    pub other_breed: String,
+    pub gender: String,
+    pub weight: String,
+    pub offspring: String,
+    pub diet: String,
+    pub health_status: String,
+    pub last_bred: String,
+    /// Comma separated vaccine names.
+    pub vaccinations: String,
+    /// Comma separated disease names.
+    pub diseases: String,
+    pub cost: String,
+    pub current_price: String,
+    /// `HornStatus` as its database string; blank if not recorded.
+    pub horns: String,
+    /// `CoatColor` as its database string; blank if not recorded.
+    pub coat_color: String,
+    pub marks: String,
+}
+
+impl Default for GoatDraft {
+    fn default() -> Self {
+        GoatDraft {
+            step: Step::Identity,
+            name: String::new(),
+            breed: "Beetal".to_string(),
+            other_breed: String::new(),
+            gender: "Male".to_string(),
+            weight: String::new(),
+            offspring: "0".to_string(),
+            diet: String::new(),
+            health_status: "healthy".to_string(),
+            last_bred: String::new(),
+            vaccinations: String::new(),
+            diseases: String::new(),
+            cost: String::new(),
+            current_price: String::new(),
+            horns: String::new(),
+            coat_color: String::new(),
+            marks: String::new(),
+        }
+    }
+}
+
+/// Parses a non-negative amount, recording a problem with `label` if it is not one.
+fn amount(label: &str, value: &str, errors: &mut Vec<String>) -> f64 {
+    match value.trim().parse::<f64>() {
+        Ok(x) if x.is_finite() && x >= 0.0 => x,
+        _ => {
+            errors.push(format!("{} must be a non-negative number.", label));
+            0.0
+        }
+    }
+}
+
+/// Splits a comma separated list, dropping blank entries.
+fn names(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl GoatDraft {
+    /// The editable fields of `goat` as text, the way the update form shows
+    /// them. Vaccinations and diseases are left blank.
+    pub fn from_goat(goat: &GoatParams) -> GoatDraft {
+        let (breed, other_breed) = match &goat.breed {
+            Breed::Other(other) => ("Other".to_string(), other.clone()),
+            known => (Breed::to_str(known).to_string(), String::new()),
+        };
+        GoatDraft {
+            name: goat.name.clone(),
+            breed,
+            other_breed,
+            gender: Gender::to_str(&goat.gender).to_string(),
+            offspring: goat.offspring.to_string(),
+            cost: goat.cost.to_string(),
+            weight: goat.weight.to_string(),
+            current_price: goat.current_price.to_string(),
+            diet: goat.diet.clone(),
+            last_bred: goat.last_bred.clone().unwrap_or_default(),
+            health_status: goat.health_status.clone(),
+            horns: goat
+                .horns
+                .as_ref()
+                .map(|h| HornStatus::to_str(h).to_string())
+                .unwrap_or_default(),
+            coat_color: goat
+                .coat_color
+                .as_ref()
+                .map(|c| CoatColor::to_str(c).to_string())
+                .unwrap_or_default(),
+            marks: goat.marks.clone().unwrap_or_default(),
+            ..GoatDraft::default()
+        }
+    }
+
+    /// Fields whose text differs from `other`'s, in form order. A changed
+    /// custom breed counts as a breed change.
+    pub fn changed_fields(&self, other: &GoatDraft) -> Vec<GoatField> {
+        GoatField::ALL
+            .into_iter()
+            .filter(|field| match field {
+                GoatField::Name => self.name != other.name,
+                GoatField::Breed => {
+                    self.breed != other.breed || self.other_breed != other.other_breed
+                }
+                GoatField::Gender => self.gender != other.gender,
+                GoatField::Offspring => self.offspring != other.offspring,
+                GoatField::Cost => self.cost != other.cost,
+                GoatField::Weight => self.weight != other.weight,
+                GoatField::CurrentPrice => self.current_price != other.current_price,
+                GoatField::Diet => self.diet != other.diet,
+                GoatField::LastBred => self.last_bred != other.last_bred,
+                GoatField::HealthStatus => self.health_status != other.health_status,
+                GoatField::Vaccinations => self.vaccinations != other.vaccinations,
+                GoatField::Diseases => self.diseases != other.diseases,
+                GoatField::Horns => self.horns != other.horns,
+                GoatField::CoatColor => self.coat_color != other.coat_color,
+                GoatField::Marks => self.marks != other.marks,
+            })
+            .collect()
+    }
+
+    fn selected_breed(&self) -> Breed {
+        if self.breed == "Other" {
+            Breed::Other(self.other_breed.trim().to_string())
+        } else {
+            Breed::from_str(&self.breed)
+        }
+    }
+
+    /// Whether the selected gender has a last-bred date. Bucks do not, so
+    /// their form hides the field and ignores anything left in it.
+    pub fn has_breeding_records(&self) -> bool {
+        Gender::from_str(&self.gender).map_or(true, |g| g.has_breeding_records())
+    }
+
+    /// Adds the horn status, coat color and marks picked in the draft to
+    /// `builder`; blank ones are left unrecorded.
+    pub fn with_traits(&self, mut builder: GoatParamsBuilder) -> GoatParamsBuilder {
+        if let Ok(horns) = HornStatus::from_str(&self.horns) {
+            builder = builder.horns(horns);
+        }
+        if let Ok(coat_color) = CoatColor::from_str(&self.coat_color) {
+            builder = builder.coat_color(coat_color);
+        }
+        builder.marks(self.marks.trim())
+    }
+
+    /// Problems with the fields on `step`; the review step checks every page.
+    pub fn check(&self, step: Step) -> Vec<String> {
+        let mut errors = Vec::new();
+        match step {
+            Step::Identity => {
+                if self.name.trim().is_empty() {
+                    errors.push("Name is required.".to_string());
+                }
+                if self.breed == "Other" && self.other_breed.trim().is_empty() {
+                    errors.push("Enter the breed.".to_string());
+                }
+                if let Err(other) = Gender::from_str(&self.gender) {
+                    errors.push(format!("Unknown gender '{}'.", other));
+                }
+            }
+            Step::Physical => {
+                amount("Weight", &self.weight, &mut errors);
+                if self.offspring.trim().parse::<u32>().is_err() {
+                    errors.push("Offspring must be a whole number.".to_string());
+                }
+                if !self.horns.is_empty()
+                    && let Err(other) = HornStatus::from_str(&self.horns)
+                {
+                    errors.push(format!("Unknown horn status '{}'.", other));
+                }
+                if !self.coat_color.is_empty()
+                    && let Err(other) = CoatColor::from_str(&self.coat_color)
+                {
+                    errors.push(format!("Unknown coat color '{}'.", other));
+                }
+            }
+            Step::Health => {
+                if self.has_breeding_records() {
+                    errors.extend(date_problem("Last bred", &self.last_bred));
+                }
+            }
+            Step::Finance => {
+                amount("Cost", &self.cost, &mut errors);
+                amount("Current price", &self.current_price, &mut errors);
+            }
+            Step::Review => {
+                for step in &Step::ALL[..Step::Review.index()] {
+                    errors.extend(self.check(*step));
+                }
+            }
+        }
+        errors
+    }
+
+    /// Builds the goat, or returns every problem with the draft.
+    pub fn to_goat(&self) -> Result<GoatParams, Vec<String>> {
+        let errors = self.check(Step::Review);
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let mut ignored = Vec::new();
+        let mut builder = self.with_traits(GoatParams::builder(self.name.trim()))
+            .breed(self.selected_breed())
+            .gender(Gender::from_str(&self.gender).map_err(|e| vec![e])?)
+            .weight(amount("Weight", &self.weight, &mut ignored))
+            .offspring(self.offspring.trim().parse::<i32>().unwrap_or_default())
+            .diet(self.diet.trim())
+            .health_status(self.health_status.trim())
+            .cost(amount("Cost", &self.cost, &mut ignored))
+            .current_price(amount("Current price", &self.current_price, &mut ignored));
+        if self.has_breeding_records() && !self.last_bred.trim().is_empty() {
+            builder = builder.last_bred(self.last_bred.trim());
+        }
+        for vaccine in names(&self.vaccinations) {
+            builder = builder.vaccination(vaccine);
+        }
+        for disease in names(&self.diseases) {
+            builder = builder.disease(disease);
+        }
+        builder.build()
+    }
+}
+
+/// AddGoatWizard component:
+/// Walks through the goat's fields a page at a time, checking each page
+/// before moving on. "Save draft" stores the entry in localStorage; a saved
+/// draft is restored, on its page, when the wizard next opens. The review
+/// page lists everything entered before the goat is added.
+#[function_component(AddGoatWizard)]
+pub fn add_goat_wizard() -> Html {
+    let api = use_api();
+    let (state, dispatch) = use_store::<GoatStore>();
+    let saved = use_memo((), |_| load_draft::<GoatDraft>(DRAFT_FORM));
+    let draft = {
+        let saved = saved.clone();
+        use_state(move || (*saved).clone().unwrap_or_default())
+    };
+    let errors = use_state(Vec::<String>::new);
+    let warnings = use_soft_warnings();
+    let notice = use_state(move || {
+        saved
+            .is_some()
+            .then(|| "Restored your saved draft.".to_string())
+    });
+
+    let text_field = |update: fn(&mut GoatDraft, String)| {
+        let draft = draft.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                let mut updated = (*draft).clone();
+                update(&mut updated, input.value());
+                draft.set(updated);
+            }
+        })
+    };
+    let number_field = |update: fn(&mut GoatDraft, String)| {
+        let draft = draft.clone();
+        Callback::from(move |value: Option<f64>| {
+            let mut updated = (*draft).clone();
+            update(
+                &mut updated,
+                value.map(|v| v.to_string()).unwrap_or_default(),
+            );
+            draft.set(updated);
+        })
+    };
+    let select_field = |update: fn(&mut GoatDraft, String)| {
+        let draft = draft.clone();
+        Callback::from(move |value: String| {
+            let mut updated = (*draft).clone();
+            update(&mut updated, value);
+            draft.set(updated);
+        })
+    };
+
+    // Fill in the breed's recommended vaccines unless the user typed their own
+    let on_breed = {
+        let draft = draft.clone();
+        Callback::from(move |breed: String| {
+            let mut updated = (*draft).clone();
+            let defaults = |breed: &str| {
+                profile_for(breed)
+                    .map(|p| p.vaccines.join(", "))
+                    .unwrap_or_default()
+            };
+            if updated.vaccinations.trim().is_empty()
+                || updated.vaccinations == defaults(&updated.breed)
+            {
+                updated.vaccinations = defaults(&breed);
+            }
+            updated.breed = breed;
+            draft.set(updated);
+        })
+    };
+
+    let go_to = |forward: bool| {
+        let draft = draft.clone();
+        let errors = errors.clone();
+        let warnings = warnings.clone();
+        let notice = notice.clone();
+        Callback::from(move |_: MouseEvent| {
+            let step = draft.step;
+            if forward {
+                let problems = draft.check(step);
+                if !problems.is_empty() {
+                    error!("{} step invalid: {:?}", step.label(), problems);
+                    errors.set(problems);
+                    return;
+                }
+            }
+            let mut updated = (*draft).clone();
+            updated.step = if forward {
+                step.next()
+            } else {
+                step.previous()
+            };
+            draft.set(updated);
+            errors.set(Vec::new());
+            warnings.clear();
+            notice.set(None);
+        })
+    };
+
+    let on_save = {
+        let draft = draft.clone();
+        let notice = notice.clone();
+        Callback::from(move |_: MouseEvent| {
+            let message = if save_draft(DRAFT_FORM, &*draft) {
+                info!("Saved add-goat draft on the {} step", draft.step.label());
+                "Draft saved."
+            } else {
+                "Could not save the draft in this browser."
+            };
+            notice.set(Some(message.to_string()));
+        })
+    };
+
+    let on_discard = {
+        let draft = draft.clone();
+        let errors = errors.clone();
+        let notice = notice.clone();
+        Callback::from(move |_: MouseEvent| {
+            discard_draft(DRAFT_FORM);
+            draft.set(GoatDraft::default());
+            errors.set(Vec::new());
+            notice.set(Some("Draft discarded.".to_string()));
+        })
+    };
+
+    let on_submit = {
+        let draft = draft.clone();
+        let errors = errors.clone();
+        let warnings = warnings.clone();
+        let notice = notice.clone();
+        Callback::from(move |_: MouseEvent| match draft.to_goat() {
+            Ok(goat) if warnings.hold(goat_warnings(&goat)) => {
+                info!("Holding wizard goat until its warnings are confirmed");
+                errors.set(Vec::new());
+            }
+            Ok(goat) => {
+                info!("Submitting new goat from wizard: {:?}", goat);
+                GoatStore::add_goat_async(api.clone(), dispatch.clone(), goat);
+                discard_draft(DRAFT_FORM);
+                draft.set(GoatDraft::default());
+                errors.set(Vec::new());
+                notice.set(Some("Goat added.".to_string()));
+            }
+            Err(problems) => {
+                error!("Wizard goat invalid: {:?}", problems);
+                errors.set(problems);
+            }
+        })
+    };
+
+    let step = draft.step;
+    let page = match step {
+        Step::Identity => html! {
+            <>
+                <label>{"Name: "}
+                    <input type="text" name="name" value={draft.name.clone()}
+                           oninput={text_field(|d, v| d.name = v)} />
+                </label>
+                <br/>
+                <label>{"Breed: "}
+                    <BreedInput
+                        selected={draft.breed.clone()}
+                        other_value={draft.other_breed.clone()}
+                        on_breed_change={on_breed}
+                        on_other_change={select_field(|d, v| d.other_breed = v)}
+                    />
+                </label>
+                <br/>
+                <label>{"Gender: "}
+                    <GenderInput
+                        selected={draft.gender.clone()}
+                        on_gender_change={select_field(|d, v| d.gender = v)}
+                    />
+                </label>
+            </>
+        },
+        Step::Physical => html! {
+            <>
+                <label>{"Weight: "}
+                    <NumberField name="weight" quantity={Quantity::Weight}
+                                 value={draft.weight.parse::<f64>().ok()} min={0.0}
+                                 typical={profile_for(&draft.breed).map(|p| p.adult_weight_kg)}
+                                 onchange={number_field(|d, v| d.weight = v)} />
+                </label>
+                <br/>
+                <label>{"Offspring: "}
+                    <input type="number" name="offspring" value={draft.offspring.clone()}
+                           oninput={text_field(|d, v| d.offspring = v)} />
+                </label>
+                <br/>
+                <label>{"Diet: "}
+                    <input type="text" name="diet" value={draft.diet.clone()}
+                           oninput={text_field(|d, v| d.diet = v)} />
+                </label>
+                <br/>
+                <TraitsInput
+                    horns={draft.horns.clone()}
+                    coat_color={draft.coat_color.clone()}
+                    marks={draft.marks.clone()}
+                    on_horns_change={select_field(|d, v| d.horns = v)}
+                    on_coat_color_change={select_field(|d, v| d.coat_color = v)}
+                    on_marks_change={select_field(|d, v| d.marks = v)}
+                />
+            </>
+        },
+        Step::Health => html! {
+            <>
+                <label>{"Health Status: "}
+                    <input type="text" name="health_status" value={draft.health_status.clone()}
+                           oninput={text_field(|d, v| d.health_status = v)} />
+                </label>
+                <br/>
+                if draft.has_breeding_records() {
+                    <label>{"Last Bred: "}
+                        <DatePicker name="last_bred" label="Last bred"
+                                    value={draft.last_bred.clone()}
+                                    onchange={select_field(|d, v| d.last_bred = v)} />
+                    </label>
+                    <br/>
+                }
+                <label>{"Vaccinations: "}
+                    <input type="text" name="vaccinations" placeholder="e.g. CDT, Rabies"
+                           value={draft.vaccinations.clone()}
+                           oninput={text_field(|d, v| d.vaccinations = v)} />
+                </label>
+                <br/>
+                <label>{"Diseases: "}
+                    <input type="text" name="diseases" placeholder="e.g. Mastitis"
+                           value={draft.diseases.clone()}
+                           oninput={text_field(|d, v| d.diseases = v)} />
+                </label>
+            </>
+        },
+        Step::Finance => html! {
+            <>
+                <label>{"Cost: "}
+                    <NumberField name="cost" quantity={Quantity::Money}
+                                 value={draft.cost.parse::<f64>().ok()} min={0.0}
+                                 onchange={number_field(|d, v| d.cost = v)} />
+                </label>
+                <br/>
+                <label>{"Current Price: "}
+                    <NumberField name="current_price" quantity={Quantity::Money}
+                                 value={draft.current_price.parse::<f64>().ok()} min={0.0}
+                                 onchange={number_field(|d, v| d.current_price = v)} />
+                </label>
+            </>
+        },
+        Step::Review => {
+            let breed = if draft.breed == "Other" {
+                draft.other_breed.clone()
+            } else {
+                draft.breed.clone()
+            };
+            let rows = [
+                ("Name", draft.name.clone()),
+                ("Breed", breed),
+                ("Gender", draft.gender.clone()),
+                ("Weight (kg)", draft.weight.clone()),
+                ("Offspring", draft.offspring.clone()),
+                ("Diet", draft.diet.clone()),
+                ("Horns", draft.horns.clone()),
+                ("Coat Color", draft.coat_color.clone()),
+                ("Marks", draft.marks.clone()),
+                ("Health Status", draft.health_status.clone()),
+                ("Last Bred", draft.last_bred.clone()),
+                ("Vaccinations", draft.vaccinations.clone()),
+                ("Diseases", draft.diseases.clone()),
+                ("Cost", draft.cost.clone()),
+                ("Current Price", draft.current_price.clone()),
+            ]
+            .into_iter()
+            .filter(|(label, _)| *label != "Last Bred" || draft.has_breeding_records());
+            html! {
+                <table class="wizard-review" style="border-collapse: collapse;">
+                    <tbody>
+                        { for rows.map(|(label, value)| html! {
+                            <tr>
+                                <th style="text-align: left; padding-right: 12px;">{label}</th>
+                                <td>{if value.trim().is_empty() { "–".to_string() } else { value }}</td>
+                            </tr>
+                        }) }
+                    </tbody>
+                </table>
+            }
+        }
+    };
+
+    html! {
+        <div class="add-goat-wizard">
+            <h3>{"Add Goat (Step by Step)"}</h3>
+            <ol class="wizard-progress" style="display: flex; gap: 12px; padding: 0; list-style: none;">
+                { for Step::ALL.iter().map(|s| {
+                    let style = match s.index().cmp(&step.index()) {
+                        std::cmp::Ordering::Less => "color: #2e7d32;",
+                        std::cmp::Ordering::Equal => "font-weight: bold;",
+                        std::cmp::Ordering::Greater => "color: #999;",
+                    };
+                    html! {
+                        <li {style} aria-current={(*s == step).then_some("step")}>
+                            {format!("{}. {}", s.index() + 1, s.label())}
+                        </li>
+                    }
+                }) }
+            </ol>
+            <p class="wizard-step" style="font-size: 12px; color: #666;">
+                {format!("Step {} of {}", step.index() + 1, Step::ALL.len())}
+            </p>
+            {page}
+            if !errors.is_empty() {
+                <ul class="wizard-errors" style="color: red;">
+                    { for errors.iter().map(|e| html! { <li>{e}</li> }) }
+                </ul>
+            }
+            if step == Step::Review {
+                <SoftWarnings
+                    warnings={warnings.shown()}
+                    on_confirm={{
+                        let warnings = warnings.clone();
+                        let on_submit = on_submit.clone();
+                        Callback::from(move |e| {
+                            warnings.confirm();
+                            on_submit.emit(e);
+                        })
+                    }}
+                />
+            }
+            <p>
+                <button onclick={go_to(false)} disabled={step == Step::Identity}>{"Back"}</button>
+                if step == Step::Review {
+                    <button onclick={on_submit} disabled={state.loading.adding}>{"Add Goat"}</button>
+                } else {
+                    <button onclick={go_to(true)}>{"Next"}</button>
+                }
+                {" "}
+                <button onclick={on_save}>{"Save draft"}</button>
+                <button onclick={on_discard}>{"Discard draft"}</button>
+                if state.loading.adding {
+                    { " " }<Spinner label="Saving goat..." />
+                }
+            </p>
+            if let Some(msg) = &*notice {
+                <p class="wizard-notice" style="font-size: 12px;">{msg}</p>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,245 @@
+//! Alert rules panel: rules such as "milk this week down 20% week over week"
+//! or "more than 3 sick goats", whether each is firing, and adding and
+//! deleting them (see `shared::alerts`). Alert notifications link here.
+
+use crate::components::Spinner;
+use crate::services::use_api;
+use crate::store::{UnitsStore, use_read_only};
+use log::{error, info};
+use shared::alerts::{AlertCondition, AlertMetric, AlertRule, MAX_ALERT_NAME_LEN};
+use shared::time::format_local;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yewdux::prelude::use_store_value;
+
+/// AlertRules component:
+/// Lists the alert rules with their state, and adds rules comparing a herd
+/// metric with a threshold. The backend checks them on every scheduler pass
+/// and notifies the workers who may see the metric when one fires. Locked
+/// in read-only mode.
+#[function_component(AlertRules)]
+pub fn alert_rules() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let timezone = use_store_value::<UnitsStore>().timezone;
+    let rules = use_state(|| None::<Vec<AlertRule>>);
+    let name = use_state(String::new);
+    let metric = use_state(|| AlertMetric::MilkThisWeek);
+    let condition = use_state(|| AlertCondition::DropsBy);
+    let threshold = use_state(String::new);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let api = api.clone();
+        let rules = rules.clone();
+        let error = error.clone();
+        move |_| {
+            spawn_local(async move {
+                match api.alert_rules().await {
+                    Ok(loaded) => rules.set(Some(loaded)),
+                    Err(e) => {
+                        error!("Failed to load alert rules: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+            || {}
+        }
+    });
+
+    let on_name = {
+        let name = name.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            name.set(input.value());
+        })
+    };
+
+    let on_metric = {
+        let metric = metric.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Ok(selected) = AlertMetric::from_str(&select.value()) {
+                metric.set(selected);
+            }
+        })
+    };
+
+    let on_condition = {
+        let condition = condition.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Ok(selected) = AlertCondition::from_str(&select.value()) {
+                condition.set(selected);
+            }
+        })
+    };
+
+    let on_threshold = {
+        let threshold = threshold.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            threshold.set(input.value());
+        })
+    };
+
+    let on_add = {
+        let api = api.clone();
+        let rules = rules.clone();
+        let name = name.clone();
+        let metric = metric.clone();
+        let condition = condition.clone();
+        let threshold = threshold.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Ok(value) = threshold.trim().parse::<f64>() else {
+                error.set(Some("The threshold must be a number".to_string()));
+                return;
+            };
+            let rule = AlertRule {
+                id: None,
+                name: name.trim().to_string(),
+                metric: *metric,
+                condition: *condition,
+                threshold: value,
+                active: true,
+                firing: false,
+                last_fired_at: None,
+            };
+            if let Err(e) = rule.validate() {
+                error.set(Some(e));
+                return;
+            }
+            let api = api.clone();
+            let rules = rules.clone();
+            let name = name.clone();
+            let threshold = threshold.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.add_alert_rule(&rule).await {
+                    Ok(stored) => {
+                        info!("Added alert rule {:?}", stored.id);
+                        let mut updated = (*rules).clone().unwrap_or_default();
+                        updated.push(stored.clone());
+                        rules.set(Some(updated));
+                        name.set(String::new());
+                        threshold.set(String::new());
+                        error.set(None);
+                        message.set(Some(format!("Added {}", stored.name)));
+                    }
+                    Err(e) => {
+                        error!("Failed to add alert rule: {}", e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_delete = {
+        let rules = rules.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |rule: AlertRule| {
+            let Some(id) = rule.id else {
+                return;
+            };
+            let api = api.clone();
+            let rules = rules.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.delete_alert_rule(id).await {
+                    Ok(()) => {
+                        info!("Deleted alert rule {}", id);
+                        let updated = (*rules)
+                            .clone()
+                            .map(|list| list.into_iter().filter(|r| r.id != Some(id)).collect());
+                        rules.set(updated);
+                        error.set(None);
+                        message.set(Some(format!("Deleted {}", rule.name)));
+                    }
+                    Err(e) => {
+                        error!("Failed to delete alert rule {}: {}", id, e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <div id="alerts">
+            <h3>{"Alert Rules"}</h3>
+            <p>
+                <label>{"Name: "}
+                    <input class="alert-name" value={(*name).clone()}
+                           maxlength={MAX_ALERT_NAME_LEN.to_string()} oninput={on_name} />
+                </label>
+                {" "}
+                <select class="alert-metric" onchange={on_metric}>
+                    { for AlertMetric::ALL.into_iter().map(|m| html! {
+                        <option value={AlertMetric::to_str(&m).to_string()} selected={m == *metric}>
+                            {m.label()}
+                        </option>
+                    }) }
+                </select>
+                {" "}
+                <select class="alert-condition" onchange={on_condition}>
+                    { for AlertCondition::ALL.into_iter().map(|c| html! {
+                        <option value={AlertCondition::to_str(&c).to_string()} selected={c == *condition}>
+                            {c.label()}
+                        </option>
+                    }) }
+                </select>
+                {" "}
+                <input class="alert-threshold" type="number" min="0" step="any"
+                       value={(*threshold).clone()} oninput={on_threshold} />
+                {" "}
+                <button onclick={on_add} disabled={read_only}>{"Add rule"}</button>
+            </p>
+            if let Some(rules) = &*rules {
+                if rules.is_empty() {
+                    <p>{"No alert rules yet."}</p>
+                } else {
+                    <table class="alert-rules">
+                        <tr><th>{"Name"}</th><th>{"Alerts when"}</th><th>{"State"}</th><th></th></tr>
+                        { for rules.iter().map(|r| {
+                            let rule = r.clone();
+                            let on_delete = on_delete.reform(move |_: MouseEvent| rule.clone());
+                            html! {
+                                <tr style={(!r.active).then_some("color: #999;")}>
+                                    <td>{&r.name}</td>
+                                    <td>{r.describe()}</td>
+                                    <td class="alert-state">
+                                        {if r.firing { "Firing" } else { "OK" }}
+                                        if let Some(at) = &r.last_fired_at {
+                                            <span style="font-size: 12px; color: #666;">{format!(" (last fired {})", format_local(at, timezone))}</span>
+                                        }
+                                    </td>
+                                    <td>
+                                        <button class="delete-alert" onclick={on_delete} disabled={read_only}>{"Delete"}</button>
+                                    </td>
+                                </tr>
+                            }
+                        }) }
+                    </table>
+                }
+            } else {
+                <Spinner label="Loading alert rules..." />
+            }
+            if let Some(msg) = &*message {
+                <p style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Alert rule error: {}", err)}</p>
+            }
+        </div>
+    }
+}
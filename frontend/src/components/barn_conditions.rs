@@ -0,0 +1,162 @@
+//! Barn conditions: latest temperature and humidity per sensor, with
+//! readings outside the sensor's thresholds highlighted.
+
+use crate::components::SkeletonRows;
+use crate::services::use_api;
+use crate::store::farm_timezone;
+use log::{error, info};
+use shared::sensors::{SensorCondition, ThresholdAlert, Thresholds};
+use shared::time::format_local;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+use wasm_bindgen_futures::spawn_local;
+use yew::platform::time::sleep;
+use yew::prelude::*;
+
+/// How often the panel refreshes on its own.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Formats a threshold range such as "5–30 °C", "≥ 30 %" or "–".
+fn range_label(thresholds: &Thresholds, unit: &str) -> String {
+    match (thresholds.min, thresholds.max) {
+        (Some(min), Some(max)) => format!("{}–{} {}", min, max, unit),
+        (Some(min), None) => format!("≥ {} {}", min, unit),
+        (None, Some(max)) => format!("≤ {} {}", max, unit),
+        (None, None) => "–".to_string(),
+    }
+}
+
+/// Alert text and row style for a condition.
+fn alert_label(alert: &Option<ThresholdAlert>) -> (&'static str, &'static str) {
+    match alert {
+        Some(ThresholdAlert::High) => ("Too high", "background: #fdecea; color: #b71c1c;"),
+        Some(ThresholdAlert::Low) => ("Too low", "background: #e3f2fd; color: #0d47a1;"),
+        None => ("OK", ""),
+    }
+}
+
+/// BarnConditions component:
+/// Loads the latest reading of every temperature and humidity sensor and
+/// lists them with their thresholds; out-of-range sensors are counted at the
+/// top and shaded. Refreshes every minute and on demand.
+#[function_component(BarnConditions)]
+pub fn barn_conditions() -> Html {
+    let api = use_api();
+    let conditions = use_state(|| None::<Vec<SensorCondition>>);
+    let error = use_state(|| None::<String>);
+
+    let load = {
+        let conditions = conditions.clone();
+        let error = error.clone();
+        Callback::from(move |_: ()| {
+            let api = api.clone();
+            let conditions = conditions.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.sensor_conditions().await {
+                    Ok(loaded) => {
+                        info!("Loaded {} sensor conditions", loaded.len());
+                        error.set(None);
+                        conditions.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load barn conditions: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    use_effect_with((), {
+        let load = load.clone();
+        move |_| {
+            load.emit(());
+            let mounted = Rc::new(Cell::new(true));
+            {
+                let mounted = mounted.clone();
+                spawn_local(async move {
+                    loop {
+                        sleep(REFRESH_INTERVAL).await;
+                        if !mounted.get() {
+                            break;
+                        }
+                        load.emit(());
+                    }
+                });
+            }
+            move || mounted.set(false)
+        }
+    });
+
+    html! {
+        <div>
+            <h3>{"Barn Conditions"}</h3>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading barn conditions: {}", err)}</p>
+            }
+            <button onclick={load.reform(|_| ())} style="margin-bottom: 10px;">{"Refresh"}</button>
+            {
+                match &*conditions {
+                    None => html! {
+                        <table><tbody><SkeletonRows rows={2} columns={6} /></tbody></table>
+                    },
+                    Some(list) if list.is_empty() => html! {
+                        <p>{"No temperature or humidity sensors registered."}</p>
+                    },
+                    Some(list) => {
+                        let alerts = list.iter().filter(|c| c.alert.is_some()).count();
+                        html! {
+                            <>
+                                if alerts > 0 {
+                                    <p class="sensor-alerts" style="color: #b71c1c; font-weight: bold;">
+                                        {format!("{} sensor(s) outside their limits", alerts)}
+                                    </p>
+                                }
+                                <table style="border-collapse: collapse; width: 100%;">
+                                    <thead>
+                                        <tr>
+                                            <th>{"Sensor"}</th>
+                                            <th>{"Location"}</th>
+                                            <th>{"Reading"}</th>
+                                            <th>{"At"}</th>
+                                            <th>{"Limits"}</th>
+                                            <th>{"Status"}</th>
+                                        </tr>
+                                    </thead>
+                                    <tbody>
+                                        { for list.iter().map(condition_row) }
+                                    </tbody>
+                                </table>
+                            </>
+                        }
+                    }
+                }
+            }
+        </div>
+    }
+}
+
+/// One table row for a sensor's condition.
+fn condition_row(condition: &SensorCondition) -> Html {
+    let sensor = &condition.sensor;
+    let unit = sensor.kind.unit();
+    let (label, style) = alert_label(&condition.alert);
+    let reading = condition
+        .latest
+        .as_ref()
+        .map_or("No data".to_string(), |r| {
+            format!("{:.1} {}", r.value, unit)
+        });
+    html! {
+        <tr data-sensor={sensor.name.clone()} {style}>
+            <td>{&sensor.name}</td>
+            <td>{sensor.space_name.clone().unwrap_or_else(|| "–".to_string())}</td>
+            <td class="reading">{reading}</td>
+            <td>{ condition.latest.as_ref().map_or("–".to_string(), |r| format_local(&r.read_at, farm_timezone())) }</td>
+            <td>{range_label(&sensor.thresholds, unit)}</td>
+            <td class="status">{label}</td>
+        </tr>
+    }
+}
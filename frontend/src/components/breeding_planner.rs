@@ -0,0 +1,161 @@
+//! "Plan breeding" panel listing suggested buck–doe pairings from the
+//! backend recommendation engine, with close-relative matings highlighted.
+
+use crate::components::{DraftBar, Spinner};
+use crate::drafts::{discard_draft, load_draft, use_autosave};
+use crate::services::use_api;
+use crate::store::GoatStore;
+use log::{error, info};
+use shared::Gender;
+use shared::breeding::BreedingRecommendation;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// Name the planner's draft, the chosen doe, is saved under.
+pub const BREEDING_DRAFT: &str = "breeding_planner";
+
+/// BreedingPlanner component:
+/// Requests pairings for all does, or one selected doe, when "Plan breeding"
+/// is clicked and shows them best first. Rows for close relatives are shaded
+/// red and every warning from the engine is listed beside the pairing.
+/// The chosen doe is kept as a draft until discarded.
+#[function_component(BreedingPlanner)]
+pub fn breeding_planner() -> Html {
+    let (state, _) = use_store::<GoatStore>();
+    let api = use_api();
+
+    let saved = use_memo((), |_| load_draft::<String>(BREEDING_DRAFT));
+    let doe = {
+        let saved = saved.clone();
+        use_state(move || (*saved).clone().unwrap_or_default())
+    };
+    let restored = use_state(|| saved.is_some());
+    use_autosave(
+        Some(BREEDING_DRAFT.to_string()),
+        (*doe).clone(),
+        String::new(),
+    );
+    let recommendations = use_state(|| None::<Vec<BreedingRecommendation>>);
+    let loading = use_state(|| false);
+    let error = use_state(|| None::<String>);
+
+    let on_plan = {
+        let doe = doe.clone();
+        let recommendations = recommendations.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            let api = api.clone();
+            let doe = (*doe).clone();
+            let recommendations = recommendations.clone();
+            let loading = loading.clone();
+            let error = error.clone();
+            loading.set(true);
+            error.set(None);
+            spawn_local(async move {
+                let filter = (!doe.is_empty()).then_some(doe.as_str());
+                match api.breeding_recommendations(filter).await {
+                    Ok(recs) => {
+                        info!("Received {} breeding recommendations", recs.len());
+                        recommendations.set(Some(recs));
+                    }
+                    Err(e) => {
+                        error!("Failed to fetch breeding recommendations: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+                loading.set(false);
+            });
+        })
+    };
+
+    let on_doe_change = {
+        let doe = doe.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                doe.set(select.value());
+            }
+        })
+    };
+
+    let on_discard = {
+        let doe = doe.clone();
+        let restored = restored.clone();
+        Callback::from(move |_: MouseEvent| {
+            discard_draft(BREEDING_DRAFT);
+            doe.set(String::new());
+            restored.set(false);
+        })
+    };
+
+    html! {
+        <div>
+            <h3>{"Plan Breeding"}</h3>
+            if !doe.is_empty() {
+                <DraftBar restored={*restored} {on_discard} />
+            }
+            <label>{"Doe: "}
+                <select onchange={on_doe_change}>
+                    <option value="" selected={doe.is_empty()}>{"All does"}</option>
+                    { for state.goats.iter().filter(|g| g.gender == Gender::Female).map(|g| html! {
+                        <option value={g.name.clone()} selected={*doe == g.name}>{&g.name}</option>
+                    }) }
+                </select>
+            </label>
+            { " " }
+            <button onclick={on_plan} disabled={*loading}>{"Plan breeding"}</button>
+            if *loading {
+                { " " }<Spinner label="Scoring pairings..." />
+            }
+
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error planning breeding: {}", err)}</p>
+            }
+
+            if let Some(recs) = &*recommendations {
+                if recs.is_empty() {
+                    <p>{"No eligible pairings. Check that bucks and does are old enough and not in gestation."}</p>
+                } else {
+                    <table style="border-collapse: collapse; width: 100%; margin-top: 10px;">
+                        <thead>
+                            <tr>
+                                <th>{"Buck"}</th>
+                                <th>{"Doe"}</th>
+                                <th>{"Score"}</th>
+                                <th>{"Inbreeding"}</th>
+                                <th>{"Why"}</th>
+                                <th>{"Warnings"}</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            { for recs.iter().map(|rec| {
+                                let style = if rec.close_relatives {
+                                    "background: #fdecea;"
+                                } else {
+                                    ""
+                                };
+                                html! {
+                                    <tr key={format!("{}-{}", rec.buck_name, rec.doe_name)} {style}>
+                                        <td>{&rec.buck_name}</td>
+                                        <td>{&rec.doe_name}</td>
+                                        <td>{format!("{:.0}", rec.score)}</td>
+                                        <td>{format!("{:.1}%", rec.inbreeding_coefficient * 100.0)}</td>
+                                        <td>{rec.reasons.join("; ")}</td>
+                                        <td style="color: red;">
+                                            if rec.close_relatives {
+                                                <strong>{"Close relatives! "}</strong>
+                                            }
+                                            {rec.warnings.join("; ")}
+                                        </td>
+                                    </tr>
+                                }
+                            }) }
+                        </tbody>
+                    </table>
+                }
+            }
+        </div>
+    }
+}
@@ -0,0 +1,244 @@
+//! Budget tracker: a month's spending per expense category against its
+//! monthly budget, with categories over budget called out.
+
+use crate::components::{EmptyAction, EmptyState, SkeletonRows};
+use crate::services::use_api;
+use crate::store::use_read_only;
+use log::{error, info};
+use shared::finance::{Budget, BudgetReport, BudgetVariance, FinanceCategory};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+/// Categories that can have a budget; `Sale` and `Milk` are income.
+fn expense_categories() -> impl Iterator<Item = &'static FinanceCategory> {
+    FinanceCategory::ALL
+        .iter()
+        .filter(|c| !matches!(c, FinanceCategory::Sale | FinanceCategory::Milk))
+}
+
+/// Formats an optional amount, or "–" when absent.
+fn amount(value: Option<f64>) -> String {
+    value.map_or("–".to_string(), |v| format!("{:.2}", v))
+}
+
+/// Share of the budget spent, e.g. "113%"; "–" without a positive budget.
+fn percent_used(line: &BudgetVariance) -> String {
+    match line.budget {
+        Some(budget) if budget > 0.0 => format!("{:.0}%", line.actual / budget * 100.0),
+        _ => "–".to_string(),
+    }
+}
+
+/// BudgetTracker component:
+/// Loads the budget report for the selected month (the current month until
+/// one is picked) and lists budget, actual and variance per category, with
+/// over-budget categories shaded and listed at the top. A form sets or
+/// replaces a category's monthly budget and reloads the report; it is
+/// disabled in read-only mode. A month with neither budgets nor spending
+/// shows an `EmptyState` pointing at that form.
+#[function_component(BudgetTracker)]
+pub fn budget_tracker() -> Html {
+    let api = use_api();
+    let month = use_state(|| None::<String>);
+    let report = use_state(|| None::<BudgetReport>);
+    let error = use_state(|| None::<String>);
+    let read_only = use_read_only();
+    let category = use_state(|| FinanceCategory::Feed);
+    let budget_input = use_state(String::new);
+
+    let load = {
+        let api = api.clone();
+        let report = report.clone();
+        let error = error.clone();
+        Callback::from(move |month: Option<String>| {
+            let api = api.clone();
+            let report = report.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.budget_report(month.as_deref()).await {
+                    Ok(loaded) => {
+                        info!("Loaded budget report for {}", loaded.month);
+                        error.set(None);
+                        report.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load budget report: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    use_effect_with((*month).clone(), {
+        let load = load.clone();
+        move |month| load.emit(month.clone())
+    });
+
+    let on_month = {
+        let month = month.clone();
+        Callback::from(move |e: Event| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>()
+                && !input.value().is_empty()
+            {
+                month.set(Some(input.value()));
+            }
+        })
+    };
+
+    let on_category = {
+        let category = category.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>()
+                && let Ok(selected) = FinanceCategory::from_str(&select.value())
+            {
+                category.set(selected);
+            }
+        })
+    };
+
+    let on_budget_input = {
+        let budget_input = budget_input.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                budget_input.set(input.value());
+            }
+        })
+    };
+
+    let on_save = {
+        let category = category.clone();
+        let budget_input = budget_input.clone();
+        let month = month.clone();
+        let error = error.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let monthly_amount = match budget_input.trim().parse::<f64>() {
+                Ok(value) if value >= 0.0 => value,
+                _ => {
+                    error.set(Some("Enter a budget of zero or more".into()));
+                    return;
+                }
+            };
+            let budget = Budget {
+                category: (*category).clone(),
+                monthly_amount,
+            };
+            let api = api.clone();
+            let load = load.clone();
+            let month = (*month).clone();
+            let budget_input = budget_input.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.set_budget(&budget).await {
+                    Ok(()) => {
+                        info!("Saved {:?} budget", budget.category);
+                        budget_input.set(String::new());
+                        load.emit(month);
+                    }
+                    Err(e) => {
+                        error!("Failed to save budget: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <div>
+            <h3>{"Budget vs Actual"}</h3>
+            <label>{"Month: "}
+                <input type="month" name="month" onchange={on_month}
+                       value={report.as_ref().map(|r| r.month.clone()).unwrap_or_default()} />
+            </label>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Budget error: {}", err)}</p>
+            }
+            {
+                match &*report {
+                    None => html! {
+                        <table><tbody><SkeletonRows rows={3} columns={5} /></tbody></table>
+                    },
+                    Some(report) if report.lines.is_empty() => html! {
+                        <EmptyState
+                            title="No budgets set and nothing spent this month"
+                            message="Each expense category's spending shows here against its monthly budget."
+                            sample="Feed · budget 12000.00 · spent 9850.00 · 82% used"
+                            actions={vec![EmptyAction::new("Set a budget", "#set-budget")]}
+                        />
+                    },
+                    Some(report) => html! {
+                        <>
+                            if report.over_budget().next().is_some() {
+                                <ul class="budget-alerts" style="color: #b71c1c; font-weight: bold;">
+                                    { for report.over_budget().map(|line| html! {
+                                        <li>{format!(
+                                            "{} is over budget by {:.2} {}",
+                                            FinanceCategory::to_str(&line.category),
+                                            -line.variance.unwrap_or_default(),
+                                            report.currency
+                                        )}</li>
+                                    }) }
+                                </ul>
+                            }
+                            <table style="border-collapse: collapse; width: 100%;">
+                                <thead>
+                                    <tr>
+                                        <th>{"Category"}</th>
+                                        <th>{format!("Budget ({})", report.currency)}</th>
+                                        <th>{"Actual"}</th>
+                                        <th>{"Variance"}</th>
+                                        <th>{"Used"}</th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    { for report.lines.iter().map(variance_row) }
+                                    <tr style="font-weight: bold;">
+                                        <td>{"Total"}</td>
+                                        <td>{format!("{:.2}", report.total_budget)}</td>
+                                        <td>{format!("{:.2}", report.total_actual)}</td>
+                                        <td></td>
+                                        <td></td>
+                                    </tr>
+                                </tbody>
+                            </table>
+                        </>
+                    },
+                }
+            }
+            <form id="set-budget" onsubmit={on_save} style="margin-top: 10px;">
+                <select name="category" onchange={on_category}>
+                    { for expense_categories().map(|c| html! {
+                        <option value={FinanceCategory::to_str(c)} selected={*c == *category}>
+                            {FinanceCategory::to_str(c)}
+                        </option>
+                    }) }
+                </select>
+                <input type="number" min="0" step="any" name="monthly_amount"
+                       placeholder="Monthly budget" value={(*budget_input).clone()}
+                       oninput={on_budget_input} />
+                <button type="submit" disabled={read_only}>{"Set budget"}</button>
+            </form>
+        </div>
+    }
+}
+
+/// One table row for a category's variance.
+fn variance_row(line: &BudgetVariance) -> Html {
+    let style = if line.over_budget {
+        "background: #fdecea; color: #b71c1c;"
+    } else {
+        ""
+    };
+    html! {
+        <tr data-category={FinanceCategory::to_str(&line.category).to_string()} {style}>
+            <td>{FinanceCategory::to_str(&line.category)}</td>
+            <td class="budget">{amount(line.budget)}</td>
+            <td class="actual">{format!("{:.2}", line.actual)}</td>
+            <td class="variance">{amount(line.variance)}</td>
+            <td class="used">{percent_used(line)}</td>
+        </tr>
+    }
+}
@@ -0,0 +1,32 @@
+//! Conditional rendering by role permissions (see `crate::store::PermissionStore`).
+
+use crate::store::use_can;
+use shared::permissions::{PermissionAction, PermissionModule};
+use yew::prelude::*;
+
+/// Props for Can:
+/// - `module`, `action`: the permission the children need
+/// - `fallback`: shown instead when the session lacks it, nothing by default
+/// - `children`: the actions to offer
+#[derive(Properties, PartialEq)]
+pub struct CanProps {
+    pub module: PermissionModule,
+    pub action: PermissionAction,
+    #[prop_or_default]
+    pub fallback: Html,
+    #[prop_or_default]
+    pub children: Html,
+}
+
+/// Can component:
+/// Renders its children only if this device's session may do `action` in
+/// `module`. The backend refuses the request either way; this keeps the
+/// dashboard from offering it.
+#[function_component(Can)]
+pub fn can(props: &CanProps) -> Html {
+    if use_can(props.module, props.action) {
+        props.children.clone()
+    } else {
+        props.fallback.clone()
+    }
+}
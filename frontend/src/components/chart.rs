@@ -0,0 +1,125 @@
+//! Minimal SVG line chart used by the analytics panels.
+
+use yew::prelude::*;
+
+/// Padding around the plot area, in SVG user units.
+const PADDING: f64 = 32.0;
+
+/// One line on a `LineChart`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartSeries {
+    /// Legend label, also set as the polyline's `data-series` attribute.
+    pub name: String,
+    pub color: String,
+    pub points: Vec<(f64, f64)>,
+    pub dashed: bool,
+    /// Draws a dot at every point, for sparse measured data.
+    pub markers: bool,
+}
+
+impl ChartSeries {
+    /// A solid line without markers.
+    pub fn line(
+        name: impl Into<String>,
+        color: impl Into<String>,
+        points: Vec<(f64, f64)>,
+    ) -> Self {
+        ChartSeries {
+            name: name.into(),
+            color: color.into(),
+            points,
+            dashed: false,
+            markers: false,
+        }
+    }
+}
+
+/// Props for LineChart:
+/// - `series`: lines to draw; all share the same axes starting at zero
+/// - `x_unit` / `y_unit`: units shown on the axis extremes
+/// - `width` / `height`: chart size
+#[derive(Properties, PartialEq)]
+pub struct LineChartProps {
+    pub series: Vec<ChartSeries>,
+    pub x_unit: AttrValue,
+    pub y_unit: AttrValue,
+    #[prop_or(480.0)]
+    pub width: f64,
+    #[prop_or(220.0)]
+    pub height: f64,
+    #[prop_or_default]
+    pub label: AttrValue,
+}
+
+/// LineChart component:
+/// Plots every series on shared axes from zero to the largest value (with
+/// 10% headroom on the y axis) and lists the series in a legend below.
+#[function_component(LineChart)]
+pub fn line_chart(props: &LineChartProps) -> Html {
+    let all = || props.series.iter().flat_map(|s| s.points.iter());
+    let max_x = all().map(|(x, _)| *x).fold(1.0, f64::max);
+    let max_y = all().map(|(_, y)| *y).fold(0.1, f64::max) * 1.1;
+    let (width, height) = (props.width, props.height);
+    let x = |v: f64| PADDING + v / max_x * (width - 2.0 * PADDING);
+    let y = |v: f64| height - PADDING - v / max_y * (height - 2.0 * PADDING);
+    let bottom = (height - PADDING).to_string();
+
+    html! {
+        <div>
+            <svg
+                width={width.to_string()}
+                height={height.to_string()}
+                viewBox={format!("0 0 {} {}", width, height)}
+                role="img"
+                aria-label={props.label.clone()}
+            >
+                <line x1={PADDING.to_string()} y1={bottom.clone()}
+                      x2={(width - PADDING).to_string()} y2={bottom.clone()} stroke="#999" />
+                <line x1={PADDING.to_string()} y1={PADDING.to_string()}
+                      x2={PADDING.to_string()} y2={bottom} stroke="#999" />
+                <text x={PADDING.to_string()} y={(height - 8.0).to_string()} font-size="10">
+                    {format!("0 {}", props.x_unit)}
+                </text>
+                <text x={(width - PADDING).to_string()} y={(height - 8.0).to_string()} font-size="10" text-anchor="end">
+                    {format!("{:.0} {}", max_x, props.x_unit)}
+                </text>
+                <text x="2" y={PADDING.to_string()} font-size="10">
+                    {format!("{:.1} {}", max_y, props.y_unit)}
+                </text>
+                { for props.series.iter().map(|s| {
+                    let points = s
+                        .points
+                        .iter()
+                        .map(|(px, py)| format!("{:.1},{:.1}", x(*px), y(*py)))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    html! {
+                        <g>
+                            <polyline
+                                data-series={s.name.clone()}
+                                {points}
+                                fill="none"
+                                stroke={s.color.clone()}
+                                stroke-width={if s.dashed { "1" } else { "2" }}
+                                stroke-dasharray={s.dashed.then_some("4 3")}
+                            />
+                            if s.markers {
+                                { for s.points.iter().map(|(px, py)| html! {
+                                    <circle cx={format!("{:.1}", x(*px))} cy={format!("{:.1}", y(*py))}
+                                            r="3" fill={s.color.clone()} />
+                                }) }
+                            }
+                        </g>
+                    }
+                }) }
+            </svg>
+            <p style="font-size: 12px;">
+                { for props.series.iter().map(|s| html! {
+                    <span style={format!("color: {}; margin-right: 12px;", s.color)}>
+                        {format!("{} {}", if s.dashed { "- -" } else { "—" }, s.name)}
+                    </span>
+                }) }
+            </p>
+        </div>
+    }
+}
@@ -0,0 +1,151 @@
+//! Keep/cull decision helper: ranks goats by a weighted score over milk,
+//! growth, fertility, health and profitability.
+
+use crate::components::Spinner;
+use crate::services::use_api;
+use log::{error, info};
+use shared::scoring::{CULL_SCORE, GoatScore, KEEP_SCORE, Recommendation, ScoreWeights};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Accessor for one metric's weight.
+type WeightField = fn(&mut ScoreWeights) -> &mut f64;
+
+/// Metric labels in display order, with accessors into `ScoreWeights`.
+const METRICS: [(&str, WeightField); 5] = [
+    ("Milk", |w| &mut w.milk),
+    ("Growth", |w| &mut w.growth),
+    ("Fertility", |w| &mut w.fertility),
+    ("Health", |w| &mut w.health),
+    ("Profit", |w| &mut w.profit),
+];
+
+/// Row background for each recommendation.
+fn recommendation_style(recommendation: &Recommendation) -> &'static str {
+    match recommendation {
+        Recommendation::Keep => "background: #e8f5e9;",
+        Recommendation::Review => "",
+        Recommendation::Cull => "background: #fdecea;",
+    }
+}
+
+/// Formats an optional 0–100 rating.
+fn rating(value: Option<f64>) -> String {
+    value.map_or("–".to_string(), |v| format!("{:.0}", v))
+}
+
+/// CullingHelper component:
+/// Lets the user weight each metric, then lists every goat from lowest to
+/// highest score with its per-metric ratings and a keep/review/cull label.
+#[function_component(CullingHelper)]
+pub fn culling_helper() -> Html {
+    let api = use_api();
+    let weights = use_state(ScoreWeights::default);
+    let scores = use_state(|| None::<Vec<GoatScore>>);
+    let loading = use_state(|| false);
+    let error = use_state(|| None::<String>);
+
+    let on_score = {
+        let weights = weights.clone();
+        let scores = scores.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            let api = api.clone();
+            let weights = (*weights).clone();
+            let scores = scores.clone();
+            let loading = loading.clone();
+            let error = error.clone();
+            if let Err(e) = weights.validate() {
+                error.set(Some(e));
+                return;
+            }
+            loading.set(true);
+            error.set(None);
+            spawn_local(async move {
+                match api.goat_scores(&weights).await {
+                    Ok(list) => {
+                        info!("Received scores for {} goats", list.len());
+                        scores.set(Some(list));
+                    }
+                    Err(e) => {
+                        error!("Failed to score goats: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+                loading.set(false);
+            });
+        })
+    };
+
+    html! {
+        <div>
+            <h3>{"Keep / Cull Candidates"}</h3>
+            <p style="font-size: 12px;">
+                {"Weight each metric (0 to ignore it). Goats are rated 0–100 against the herd on each."}
+            </p>
+            { for METRICS.iter().map(|(label, field)| {
+                let mut current = (*weights).clone();
+                let value = *field(&mut current);
+                let oninput = {
+                    let weights = weights.clone();
+                    let field = *field;
+                    Callback::from(move |e: InputEvent| {
+                        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                            let mut updated = (*weights).clone();
+                            *field(&mut updated) = input.value().parse().unwrap_or(0.0);
+                            weights.set(updated);
+                        }
+                    })
+                };
+                html! {
+                    <label style="margin-right: 10px;">{format!("{}: ", label)}
+                        <input type="number" min="0" step="0.5" style="width: 50px;"
+                               name={label.to_lowercase()} value={value.to_string()} {oninput} />
+                    </label>
+                }
+            }) }
+            <button onclick={on_score} disabled={*loading}>{"Score herd"}</button>
+            if *loading {
+                <Spinner label="Scoring herd..." />
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error scoring goats: {}", err)}</p>
+            }
+            if let Some(list) = &*scores {
+                if list.is_empty() {
+                    <p>{"No goats have data for the weighted metrics."}</p>
+                } else {
+                    <table style="border-collapse: collapse; width: 100%; margin-top: 10px;">
+                        <thead>
+                            <tr>
+                                <th>{"Goat"}</th>
+                                <th>{"Score"}</th>
+                                { for METRICS.iter().map(|(label, _)| html! { <th>{*label}</th> }) }
+                                <th>{"Recommendation"}</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            { for list.iter().map(|s| html! {
+                                <tr data-goat={s.goat_name.clone()} style={recommendation_style(&s.recommendation)}>
+                                    <td>{&s.goat_name}</td>
+                                    <td><strong>{format!("{:.0}", s.score)}</strong></td>
+                                    <td>{rating(s.breakdown.milk)}</td>
+                                    <td>{rating(s.breakdown.growth)}</td>
+                                    <td>{rating(s.breakdown.fertility)}</td>
+                                    <td>{rating(s.breakdown.health)}</td>
+                                    <td>{rating(s.breakdown.profit)}</td>
+                                    <td class="recommendation">{format!("{:?}", s.recommendation)}</td>
+                                </tr>
+                            }) }
+                        </tbody>
+                    </table>
+                    <p style="font-size: 12px;">
+                        {format!("Keep at {:.0} or above; cull candidates below {:.0}.", KEEP_SCORE, CULL_SCORE)}
+                    </p>
+                }
+            }
+        </div>
+    }
+}
@@ -1,23 +1,227 @@
 //! Main dashboard content area component.
 
-use crate::components::{AddGoatForm, DeleteGoatsForm, GoatList, UpdateGoatForm};
+use crate::components::{
+    AccessTokens, AddGoatForm, AddGoatWizard, AlertRules, BarnConditions, BreedingPlanner,
+    BudgetTracker, Can, CullingHelper, DataHealth, DeleteGoatsForm, DietReassignment,
+    DiseaseCompliance, ErrorBoundary, ExportTemplates, ExternalAnimals, FarmArchive,
+    FeedEfficiencyPanel, GoatList, GrazingMap, HeatTracker, ImportWizard, IncidentHeatMap,
+    InventoryList, JobsPanel, KpiCards, LifecyclePipeline, MilkAnalytics, MovementLog,
+    NutritionPanel, PedigreeView, PensView, PermissionsEditor, PricingPreview, QuickEntry,
+    RecentActivity, RetentionPanel, RotationPlanner, ServiceRecords, SessionsPanel, StockExpiry,
+    TagSeriesPanel, TasksList, TransactionsList, UpdateGoatForm, VetVisits, WaterPanel,
+    WeighSession,
+};
+use crate::services::use_api;
+use crate::store::{PermissionStore, use_read_only};
+use shared::permissions::{PermissionAction, PermissionModule};
+use shared::voice::EntryKind;
 use yew::prelude::*;
+use yewdux::prelude::use_dispatch;
 
-/// Dashboard area showing goat list, forms, and visualizations placeholder.
+/// Dashboard area showing goat list, forms, and analytics.
 ///
-/// Currently all rendered for skeleton display.
+/// Currently all rendered for skeleton display. Each section sits in its own
+/// `ErrorBoundary` so a failure in one form does not take down the others.
+/// In read-only mode the forms that only change data are left out, and
+/// sections a worker's role may not use are left out through `Can`. The add
+/// and import forms carry `add-goat` and `import-goats` IDs, which empty
+/// states link to.
 #[function_component(Dashboard)]
 pub fn dashboard() -> Html {
+    let read_only = use_read_only();
+    let api = use_api();
+    let dispatch = use_dispatch::<PermissionStore>();
+    use_effect_with((), move |_| PermissionStore::load(api, dispatch));
+    let goats = PermissionModule::Goats;
+    let production = PermissionModule::Production;
+    let edit = PermissionAction::Edit;
+    let view = PermissionAction::View;
     html! {
         <div class="dashboard" style="flex: 1; padding: 24px;">
             <h1>{"Dashboard"}</h1>
-            <GoatList />
-            <AddGoatForm />
-            <DeleteGoatsForm />
-            <UpdateGoatForm />
+            if read_only {
+                <p class="read-only-notice" style="background: #fff8e1; padding: 8px;">
+                    {"Read-only mode: changes are turned off."}
+                </p>
+            }
+            <ErrorBoundary name="Key Figures">
+                <KpiCards />
+            </ErrorBoundary>
+            <ErrorBoundary name="Recent Activity">
+                <RecentActivity />
+            </ErrorBoundary>
+            <ErrorBoundary name="Background Jobs">
+                <JobsPanel />
+            </ErrorBoundary>
+            <ErrorBoundary name="Barn Conditions">
+                <BarnConditions />
+            </ErrorBoundary>
+            <ErrorBoundary name="Goat List">
+                <GoatList />
+            </ErrorBoundary>
+            <Can module={goats} action={view}>
+                <ErrorBoundary name="Lifecycle">
+                    <LifecyclePipeline />
+                </ErrorBoundary>
+                <ErrorBoundary name="Saved Exports">
+                    <ExportTemplates />
+                </ErrorBoundary>
+                <ErrorBoundary name="Movements">
+                    <MovementLog />
+                </ErrorBoundary>
+            </Can>
+            if !read_only {
+                <Can module={goats} action={edit}>
+                    <div id="add-goat">
+                        <ErrorBoundary name="Add Goat">
+                            <AddGoatForm />
+                        </ErrorBoundary>
+                    </div>
+                    <ErrorBoundary name="Add Goat Wizard">
+                        <AddGoatWizard />
+                    </ErrorBoundary>
+                    <div id="import-goats">
+                        <ErrorBoundary name="Import Goats">
+                            <ImportWizard />
+                        </ErrorBoundary>
+                    </div>
+                </Can>
+                <Can module={goats} action={PermissionAction::Delete}>
+                    <ErrorBoundary name="Delete Goats">
+                        <DeleteGoatsForm />
+                    </ErrorBoundary>
+                </Can>
+                <Can module={goats} action={edit}>
+                    <ErrorBoundary name="Update Goat">
+                        <UpdateGoatForm />
+                    </ErrorBoundary>
+                    <ErrorBoundary name="Reassign Diets">
+                        <DietReassignment />
+                    </ErrorBoundary>
+                </Can>
+                <Can module={production} action={edit}>
+                    <ErrorBoundary name="Quick Weight">
+                        <QuickEntry kind={EntryKind::Weight} />
+                    </ErrorBoundary>
+                    <ErrorBoundary name="Quick Milk">
+                        <QuickEntry kind={EntryKind::Milk} />
+                    </ErrorBoundary>
+                </Can>
+            }
+            <Can module={PermissionModule::Tasks} action={view}>
+                <ErrorBoundary name="Tasks">
+                    <TasksList />
+                </ErrorBoundary>
+                <ErrorBoundary name="Alert Rules">
+                    <AlertRules />
+                </ErrorBoundary>
+            </Can>
+            <Can module={PermissionModule::Breeding} action={view}>
+                <ErrorBoundary name="Plan Breeding">
+                    <BreedingPlanner />
+                </ErrorBoundary>
+                <ErrorBoundary name="Pedigree">
+                    <PedigreeView />
+                </ErrorBoundary>
+                <ErrorBoundary name="Heat Tracker">
+                    <HeatTracker />
+                </ErrorBoundary>
+                <ErrorBoundary name="Services">
+                    <ServiceRecords />
+                </ErrorBoundary>
+                <ErrorBoundary name="External Registry">
+                    <ExternalAnimals />
+                </ErrorBoundary>
+            </Can>
+            <Can module={PermissionModule::Health} action={view}>
+                <ErrorBoundary name="Vet Visits">
+                    <VetVisits />
+                </ErrorBoundary>
+                <ErrorBoundary name="Notifiable Diseases">
+                    <DiseaseCompliance />
+                </ErrorBoundary>
+            </Can>
+            <ErrorBoundary name="Keep / Cull">
+                <CullingHelper />
+            </ErrorBoundary>
+            <Can module={production} action={edit}>
+                <ErrorBoundary name="Weigh Session">
+                    <WeighSession />
+                </ErrorBoundary>
+            </Can>
+            <Can module={PermissionModule::Grazing} action={view}>
+                <ErrorBoundary name="Pens">
+                    <PensView />
+                </ErrorBoundary>
+                <ErrorBoundary name="Water">
+                    <WaterPanel />
+                </ErrorBoundary>
+                <ErrorBoundary name="Grazing Map">
+                    <GrazingMap />
+                </ErrorBoundary>
+                <ErrorBoundary name="Pasture Rotation">
+                    <RotationPlanner />
+                </ErrorBoundary>
+            </Can>
+            <Can module={PermissionModule::Inventory} action={view}>
+                <ErrorBoundary name="Stock Expiry">
+                    <StockExpiry />
+                </ErrorBoundary>
+                <ErrorBoundary name="Inventory">
+                    <InventoryList />
+                </ErrorBoundary>
+            </Can>
+            <ErrorBoundary name="Data Health">
+                <DataHealth />
+            </ErrorBoundary>
+            <Can module={PermissionModule::Settings} action={view}>
+                <ErrorBoundary name="Farm Archive">
+                    <FarmArchive />
+                </ErrorBoundary>
+                <ErrorBoundary name="Trash & Retention">
+                    <RetentionPanel />
+                </ErrorBoundary>
+                <ErrorBoundary name="Tag Series">
+                    <TagSeriesPanel />
+                </ErrorBoundary>
+                <ErrorBoundary name="Access Tokens">
+                    <AccessTokens />
+                </ErrorBoundary>
+            </Can>
+            <ErrorBoundary name="Signed-in Devices">
+                <SessionsPanel />
+            </ErrorBoundary>
+            <ErrorBoundary name="Permissions">
+                <PermissionsEditor />
+            </ErrorBoundary>
+            <Can module={PermissionModule::Finance} action={view}>
+                <div style="border: 1px dashed #bbb; margin-top: 30px; padding: 16px;">
+                    <h3>{"Finance"}</h3>
+                    <ErrorBoundary name="Transactions">
+                        <TransactionsList />
+                    </ErrorBoundary>
+                    <ErrorBoundary name="Budget">
+                        <BudgetTracker />
+                    </ErrorBoundary>
+                    <ErrorBoundary name="Pricing">
+                        <PricingPreview />
+                    </ErrorBoundary>
+                </div>
+            </Can>
             <div style="border: 1px dashed #bbb; margin-top: 30px; padding: 16px;">
-                <h3>{"Visualizations"}</h3>
-                <p>{"Graphs and analytics coming soon!"}</p>
+                <h3>{"Analytics"}</h3>
+                <ErrorBoundary name="Milk Yield">
+                    <MilkAnalytics />
+                </ErrorBoundary>
+                <ErrorBoundary name="Feed Efficiency">
+                    <FeedEfficiencyPanel />
+                </ErrorBoundary>
+                <ErrorBoundary name="Nutrition">
+                    <NutritionPanel />
+                </ErrorBoundary>
+                <ErrorBoundary name="Health Incidents">
+                    <IncidentHeatMap />
+                </ErrorBoundary>
             </div>
         </div>
     }
@@ -0,0 +1,107 @@
+//! "Data health" panel listing problems found in the herd records (see
+//! `shared::data_health`), each with a link to where it is fixed.
+
+use crate::services::use_api;
+use crate::store::GoatStore;
+use log::{error, info};
+use shared::data_health::{DataHealthReport, DataIssue, IssueKind};
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// DataHealth component:
+/// Loads the data quality report and shows a count per kind of problem,
+/// then one row per problem. The goat's name links to its row in the goat
+/// list and "Fix" jumps to where the value is corrected. Reloads when the
+/// goat list changes and on "Check again".
+#[function_component(DataHealth)]
+pub fn data_health() -> Html {
+    let api = use_api();
+    let (state, _) = use_store::<GoatStore>();
+    let report = use_state(|| None::<DataHealthReport>);
+    let error = use_state(|| None::<String>);
+    let reloads = use_state(|| 0u32);
+
+    {
+        let report = report.clone();
+        let error = error.clone();
+        use_effect_with((state.goats.len(), *reloads), move |_| {
+            spawn_local(async move {
+                match api.data_health().await {
+                    Ok(loaded) => {
+                        info!(
+                            "Data health: {} issues in {} goats",
+                            loaded.issues.len(),
+                            loaded.goats_checked
+                        );
+                        error.set(None);
+                        report.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load data health report: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        });
+    }
+
+    let recheck = {
+        let reloads = reloads.clone();
+        Callback::from(move |_: MouseEvent| reloads.set(*reloads + 1))
+    };
+
+    html! {
+        <div id="data-health">
+            <h3>{"Data Health"}</h3>
+            <button onclick={recheck}>{"Check again"}</button>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error checking data: {}", err)}</p>
+            }
+            {
+                match &*report {
+                    None => html! { <p>{"Checking records..."}</p> },
+                    Some(report) if report.issues.is_empty() => html! {
+                        <p class="data-health-ok" style="color: green;">
+                            {format!("No problems found in {} goats.", report.goats_checked)}
+                        </p>
+                    },
+                    Some(report) => html! {
+                        <>
+                            <ul class="data-health-summary" style="display: flex; gap: 16px; list-style: none; padding: 0;">
+                                { for IssueKind::ALL.iter().filter(|k| report.count(**k) > 0).map(|kind| html! {
+                                    <li>{format!("{}: {}", kind.label(), report.count(*kind))}</li>
+                                }) }
+                            </ul>
+                            <table style="border-collapse: collapse; width: 100%;">
+                                <thead>
+                                    <tr>
+                                        <th>{"Problem"}</th>
+                                        <th>{"Goat"}</th>
+                                        <th>{"Details"}</th>
+                                        <th></th>
+                                    </tr>
+                                </thead>
+                                <tbody>
+                                    { for report.issues.iter().map(issue_row) }
+                                </tbody>
+                            </table>
+                        </>
+                    },
+                }
+            }
+        </div>
+    }
+}
+
+/// One table row for a problem, with its goat and quick-fix links.
+fn issue_row(issue: &DataIssue) -> Html {
+    html! {
+        <tr class="data-issue" data-goat={issue.goat_name.clone()}>
+            <td>{issue.kind.label()}</td>
+            <td><a href={format!("#goat-{}", issue.goat_id)}>{&issue.goat_name}</a></td>
+            <td>{&issue.message}</td>
+            <td><a class="fix" href={format!("#{}", issue.fix_anchor())}>{"Fix"}</a></td>
+        </tr>
+    }
+}
@@ -0,0 +1,351 @@
+//! Reusable table for lists of records, such as goats, tasks, transactions
+//! and inventory items.
+//!
+//! Columns are defined once per list with a cell renderer and, optionally,
+//! a sort key. The table sorts by a clicked header, keeps one row selected,
+//! and can be driven from the keyboard: arrow keys, Home and End move the
+//! selection and Enter opens the selected row.
+
+use crate::components::SkeletonRows;
+use std::cmp::Ordering;
+use std::rc::Rc;
+use yew::prelude::*;
+
+/// Value a column sorts its rows by. Text compares case-insensitively.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SortKey {
+    Text(String),
+    Number(f64),
+}
+
+impl SortKey {
+    fn compare(&self, other: &SortKey) -> Ordering {
+        match (self, other) {
+            (SortKey::Text(a), SortKey::Text(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+            (SortKey::Number(a), SortKey::Number(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+            // Numbers before text, e.g. amounts before a "–" placeholder
+            (SortKey::Number(_), SortKey::Text(_)) => Ordering::Less,
+            (SortKey::Text(_), SortKey::Number(_)) => Ordering::Greater,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// Element ID of a row, see `DataTableProps::row_id`.
+pub type RowId<T> = Rc<dyn Fn(&T) -> String>;
+
+/// Inline style of a row, see `DataTableProps::row_style`.
+pub type RowStyle<T> = Rc<dyn Fn(&T) -> &'static str>;
+
+type SortKeyFn<T> = Rc<dyn Fn(&T) -> SortKey>;
+
+/// A column of a `DataTable` over rows of `T`.
+pub struct Column<T> {
+    /// Identifies the column in sort events, e.g. `"name"`.
+    pub key: &'static str,
+    pub title: AttrValue,
+    render: Rc<dyn Fn(&T) -> Html>,
+    sort_key: Option<SortKeyFn<T>>,
+}
+
+impl<T> Column<T> {
+    /// A column whose cells are drawn by `render`. It does not sort until
+    /// given a key with `sort_by`.
+    pub fn new(
+        key: &'static str,
+        title: &'static str,
+        render: impl Fn(&T) -> Html + 'static,
+    ) -> Self {
+        Column {
+            key,
+            title: title.into(),
+            render: Rc::new(render),
+            sort_key: None,
+        }
+    }
+
+    /// A column showing `text` of each row, sorted by it.
+    pub fn text(
+        key: &'static str,
+        title: &'static str,
+        text: impl Fn(&T) -> String + 'static,
+    ) -> Self {
+        let text = Rc::new(text);
+        Column::new(key, title, {
+            let text = text.clone();
+            move |row| html! { {text(row)} }
+        })
+        .sort_by(move |row| SortKey::Text(text(row)))
+    }
+
+    /// A column showing `value` of each row with two decimals, sorted by it.
+    pub fn number(
+        key: &'static str,
+        title: &'static str,
+        value: impl Fn(&T) -> f64 + 'static,
+    ) -> Self {
+        let value = Rc::new(value);
+        Column::new(key, title, {
+            let value = value.clone();
+            move |row| html! { {format!("{:.2}", value(row))} }
+        })
+        .sort_by(move |row| SortKey::Number(value(row)))
+    }
+
+    /// Makes the column sortable by `sort_key`.
+    pub fn sort_by(mut self, sort_key: impl Fn(&T) -> SortKey + 'static) -> Self {
+        self.sort_key = Some(Rc::new(sort_key));
+        self
+    }
+}
+
+impl<T> Clone for Column<T> {
+    fn clone(&self) -> Self {
+        Column {
+            key: self.key,
+            title: self.title.clone(),
+            render: self.render.clone(),
+            sort_key: self.sort_key.clone(),
+        }
+    }
+}
+
+impl<T> PartialEq for Column<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+            && self.title == other.title
+            && Rc::ptr_eq(&self.render, &other.render)
+            && match (&self.sort_key, &other.sort_key) {
+                (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+                (None, None) => true,
+                _ => false,
+            }
+    }
+}
+
+/// Props for DataTable:
+/// - `rows`: the records, in their default order
+/// - `columns`: what to show of each record
+/// - `row_id`: unique element ID of a record's row, also its key, e.g.
+///   `goat-3` so links can jump to it
+/// - `row_style`: optional inline style per row, e.g. to dim one being deleted
+/// - `loading_rows`: skeleton rows shown after the records while more load
+/// - `on_select`: called when a row becomes the selected one
+/// - `on_activate`: called when the selected row is opened with Enter
+/// - `on_sort`: called with the column key and direction when the sort
+///   changes, e.g. to remember it
+/// - `footer`: rows summarising the records, e.g. subtotals, shown below
+///   them
+#[derive(Properties)]
+pub struct DataTableProps<T: PartialEq + 'static> {
+    pub rows: Vec<T>,
+    pub columns: Vec<Column<T>>,
+    pub row_id: RowId<T>,
+    #[prop_or_default]
+    pub row_style: Option<RowStyle<T>>,
+    #[prop_or_default]
+    pub loading_rows: usize,
+    #[prop_or_default]
+    pub on_select: Option<Callback<T>>,
+    #[prop_or_default]
+    pub on_activate: Option<Callback<T>>,
+    #[prop_or_default]
+    pub on_sort: Option<Callback<(&'static str, SortDirection)>>,
+    #[prop_or_default]
+    pub footer: Html,
+}
+
+impl<T: PartialEq + 'static> PartialEq for DataTableProps<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rows == other.rows
+            && self.columns == other.columns
+            && Rc::ptr_eq(&self.row_id, &other.row_id)
+            && match (&self.row_style, &other.row_style) {
+                (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+                (None, None) => true,
+                _ => false,
+            }
+            && self.loading_rows == other.loading_rows
+            && self.on_select == other.on_select
+            && self.on_activate == other.on_activate
+            && self.on_sort == other.on_sort
+            && self.footer == other.footer
+    }
+}
+
+/// DataTable component:
+/// Renders `rows` under a header of `columns`. Clicking a sortable header
+/// sorts by it, ascending first and then flipping direction. Clicking a
+/// row selects it; with the table focused, the arrow keys, Home and End
+/// move the selection (scrolling it into view) and Enter activates it. The
+/// selection follows its record through re-sorting and reloads.
+#[function_component(DataTable)]
+pub fn data_table<T>(props: &DataTableProps<T>) -> Html
+where
+    T: Clone + PartialEq + 'static,
+{
+    let sort = use_state(|| None::<(&'static str, SortDirection)>);
+    let selected = use_state(|| None::<String>);
+
+    // Row indices in display order
+    let mut order: Vec<usize> = (0..props.rows.len()).collect();
+    if let Some((key, direction)) = *sort
+        && let Some(sort_key) = props
+            .columns
+            .iter()
+            .find(|c| c.key == key)
+            .and_then(|c| c.sort_key.clone())
+    {
+        let keys: Vec<SortKey> = props.rows.iter().map(|row| sort_key(row)).collect();
+        order.sort_by(|a, b| {
+            let ordering = keys[*a].compare(&keys[*b]);
+            match direction {
+                SortDirection::Ascending => ordering,
+                SortDirection::Descending => ordering.reverse(),
+            }
+        });
+    }
+    let ids: Vec<String> = order
+        .iter()
+        .map(|i| (props.row_id)(&props.rows[*i]))
+        .collect();
+    let position = selected
+        .as_ref()
+        .and_then(|id| ids.iter().position(|other| other == id));
+
+    let select = {
+        let selected = selected.clone();
+        let rows = props.rows.clone();
+        let order = order.clone();
+        let ids = ids.clone();
+        let on_select = props.on_select.clone();
+        move |at: usize| {
+            selected.set(Some(ids[at].clone()));
+            if let Some(element) = web_sys::window()
+                .and_then(|w| w.document())
+                .and_then(|d| d.get_element_by_id(&ids[at]))
+            {
+                element.scroll_into_view_with_bool(false);
+            }
+            if let Some(on_select) = &on_select {
+                on_select.emit(rows[order[at]].clone());
+            }
+        }
+    };
+
+    let on_keydown = {
+        let select = select.clone();
+        let rows = props.rows.clone();
+        let order = order.clone();
+        let on_activate = props.on_activate.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if order.is_empty() {
+                return;
+            }
+            let last = order.len() - 1;
+            let target = match e.key().as_str() {
+                "ArrowDown" => Some(position.map_or(0, |p| (p + 1).min(last))),
+                "ArrowUp" => Some(position.map_or(0, |p| p.saturating_sub(1))),
+                "Home" => Some(0),
+                "End" => Some(last),
+                "Enter" => {
+                    if let (Some(p), Some(on_activate)) = (position, &on_activate) {
+                        e.prevent_default();
+                        on_activate.emit(rows[order[p]].clone());
+                    }
+                    None
+                }
+                _ => None,
+            };
+            if let Some(target) = target {
+                e.prevent_default();
+                select(target);
+            }
+        })
+    };
+
+    let on_header = |key: &'static str| {
+        let sort = sort.clone();
+        let on_sort = props.on_sort.clone();
+        Callback::from(move |_: MouseEvent| {
+            let direction = match *sort {
+                Some((current, SortDirection::Ascending)) if current == key => {
+                    SortDirection::Descending
+                }
+                _ => SortDirection::Ascending,
+            };
+            sort.set(Some((key, direction)));
+            if let Some(on_sort) = &on_sort {
+                on_sort.emit((key, direction));
+            }
+        })
+    };
+
+    html! {
+        <div class="data-table" tabindex="0" onkeydown={on_keydown}
+             style="overflow-x: auto; outline-offset: 2px;">
+            <table style="border-collapse: collapse; width: 100%;">
+                <thead>
+                    <tr>
+                        { for props.columns.iter().map(|column| {
+                            let sorted = sort.filter(|(key, _)| *key == column.key).map(|(_, d)| d);
+                            let aria_sort = match sorted {
+                                Some(SortDirection::Ascending) => "ascending",
+                                Some(SortDirection::Descending) => "descending",
+                                None => "none",
+                            };
+                            if column.sort_key.is_some() {
+                                html! {
+                                    <th data-column={column.key} aria-sort={aria_sort}
+                                        onclick={on_header(column.key)} style="cursor: pointer;">
+                                        {&column.title}
+                                        { match sorted {
+                                            Some(SortDirection::Ascending) => " ▲",
+                                            Some(SortDirection::Descending) => " ▼",
+                                            None => "",
+                                        } }
+                                    </th>
+                                }
+                            } else {
+                                html! { <th data-column={column.key}>{&column.title}</th> }
+                            }
+                        }) }
+                    </tr>
+                </thead>
+                <tbody>
+                    { for order.iter().enumerate().map(|(at, index)| {
+                        let row = &props.rows[*index];
+                        let is_selected = position == Some(at);
+                        let style = format!(
+                            "{}{}",
+                            props.row_style.as_ref().map_or("", |style| style(row)),
+                            if is_selected { "background: #e3f2fd;" } else { "" }
+                        );
+                        let onclick = {
+                            let select = select.clone();
+                            Callback::from(move |_: MouseEvent| select(at))
+                        };
+                        html! {
+                            <tr key={ids[at].clone()} id={ids[at].clone()} {style} {onclick}
+                                aria-selected={is_selected.to_string()}
+                                class={classes!(is_selected.then_some("selected"))}>
+                                { for props.columns.iter().map(|column| html! {
+                                    <td>{(column.render)(row)}</td>
+                                }) }
+                            </tr>
+                        }
+                    }) }
+                    if props.loading_rows > 0 {
+                        <SkeletonRows rows={props.loading_rows} columns={props.columns.len()} />
+                    }
+                </tbody>
+                <tfoot>{props.footer.clone()}</tfoot>
+            </table>
+        </div>
+    }
+}
@@ -0,0 +1,130 @@
+//! Date input shared by every form that takes a date, with "Today",
+//! "Yesterday" and "N days ago" shortcuts for the dates farmers usually
+//! record after the fact.
+
+use crate::store::farm_timezone;
+use chrono::Days;
+use shared::import::parse_date;
+use shared::time::today_in;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// The date `days` days before today in the farm's time zone, as
+/// `YYYY-MM-DD`.
+pub fn days_ago(days: u32) -> String {
+    (today_in(farm_timezone()) - Days::new(days.into())).to_string()
+}
+
+/// Today's date in the farm's time zone as `YYYY-MM-DD`.
+pub fn today() -> String {
+    days_ago(0)
+}
+
+/// Complains if a non-empty `value` is not a `YYYY-MM-DD` date.
+fn format_problem(label: &str, value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty() && parse_date(value).is_err())
+        .then(|| format!("{} '{}' must be a YYYY-MM-DD date", label, value))
+}
+
+/// What is wrong with `value` as the date called `label`: not a
+/// `YYYY-MM-DD` date, or later than today. Empty values are left to the
+/// form to require.
+pub fn date_problem(label: &str, value: &str) -> Option<String> {
+    let value = value.trim();
+    format_problem(label, value).or_else(|| {
+        // ISO dates compare in calendar order
+        (!value.is_empty() && value > today().as_str())
+            .then(|| format!("{} {} is in the future", label, value))
+    })
+}
+
+#[derive(Properties, PartialEq)]
+pub struct DatePickerProps {
+    pub name: AttrValue,
+    /// Shown in error messages, e.g. "Last bred".
+    pub label: AttrValue,
+    /// The date as `YYYY-MM-DD`, or empty.
+    pub value: AttrValue,
+    /// Called with the new date, or an empty string when cleared.
+    pub onchange: Callback<String>,
+    /// Accept dates after today, e.g. for the end of a report range.
+    #[prop_or_default]
+    pub allow_future: bool,
+}
+
+/// DatePicker component:
+/// A native date input plus shortcut buttons, with the number of days for
+/// "days ago" typed next to it. Future dates are not offered by the
+/// browser's picker and are flagged if typed, unless `allow_future` is set;
+/// forms still check `date_problem` before saving.
+#[function_component(DatePicker)]
+pub fn date_picker(props: &DatePickerProps) -> Html {
+    let days = use_state(String::new);
+
+    let oninput = {
+        let onchange = props.onchange.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                onchange.emit(input.value());
+            }
+        })
+    };
+    let shortcut = |ago: u32| {
+        let onchange = props.onchange.clone();
+        Callback::from(move |_: MouseEvent| onchange.emit(days_ago(ago)))
+    };
+    let on_days = {
+        let days = days.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                days.set(input.value());
+            }
+        })
+    };
+    let on_days_ago = {
+        let onchange = props.onchange.clone();
+        let days = days.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Ok(ago) = days.trim().parse::<u32>() {
+                onchange.emit(days_ago(ago));
+            }
+        })
+    };
+
+    let problem = if props.allow_future {
+        format_problem(&props.label, &props.value)
+    } else {
+        date_problem(&props.label, &props.value)
+    };
+    html! {
+        <span class="date-picker">
+            <input
+                type="date"
+                name={props.name.clone()}
+                value={props.value.clone()}
+                max={(!props.allow_future).then(today)}
+                {oninput}
+            />
+            { " " }
+            <button type="button" onclick={shortcut(0)}>{"Today"}</button>
+            <button type="button" onclick={shortcut(1)}>{"Yesterday"}</button>
+            <input
+                type="number"
+                class="days-ago"
+                min="1"
+                placeholder="N"
+                style="width: 4em;"
+                value={(*days).clone()}
+                oninput={on_days}
+            />
+            <button type="button" onclick={on_days_ago}
+                    disabled={days.trim().parse::<u32>().is_err()}>
+                {"days ago"}
+            </button>
+            if let Some(problem) = problem {
+                <span class="date-error" style="color: red; margin-left: 8px;">{problem}</span>
+            }
+        </span>
+    }
+}
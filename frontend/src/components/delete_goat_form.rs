@@ -1,6 +1,11 @@
+use crate::components::Spinner;
+use crate::errors::AppError;
+use crate::services::use_api;
 use crate::store::GoatStore;
 use log::{info, warn};
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 use yew::prelude::*;
 use yewdux::prelude::use_store;
 
@@ -8,12 +13,14 @@ use yewdux::prelude::use_store;
 pub fn delete_goats_form() -> Html {
     let names_input = use_state(|| "".to_string());
     let results = use_state(|| HashMap::<String, String>::new());
-    let (_state, dispatch) = use_store::<GoatStore>();
+    let (state, dispatch) = use_store::<GoatStore>();
+    let api = use_api();
 
     // OnSubmit handler
     let onsubmit = {
         let names_input = names_input.clone();
         let results = results.clone();
+        let api = api.clone();
         let dispatch = dispatch.clone();
         Callback::from(move |evt: SubmitEvent| {
             evt.prevent_default();
@@ -41,33 +48,22 @@ pub fn delete_goats_form() -> Html {
             // Clear old results and begin new deletes
             results.set(HashMap::new());
 
-            for name in names {
-                let dispatch = dispatch.clone();
-                let name_clone = name.clone();
-                let results = results.clone();
-
-                GoatStore::delete_goat_async(
-                    dispatch,
-                    name.clone(),
-                    Callback::from(move |res| {
-                        results.set({
-                            let mut new_map = (*results).clone();
-                            match res {
-                                Ok(_) => {
-                                    new_map.insert(
-                                        name_clone.clone(),
-                                        "Deleted successfully.".to_string(),
-                                    );
-                                }
-                                Err(e) => {
-                                    new_map.insert(name_clone.clone(), format!("Failed: {}", e));
-                                }
-                            }
-                            new_map
-                        });
-                    }),
-                );
-            }
+            // Collected here, as `results` only sees the state of this render
+            let outcomes = Rc::new(RefCell::new(HashMap::new()));
+            let results = results.clone();
+            GoatStore::delete_goats_async(
+                api.clone(),
+                dispatch.clone(),
+                names,
+                Callback::from(move |(name, res): (String, Result<(), AppError>)| {
+                    let message = match res {
+                        Ok(_) => "Deleted successfully.".to_string(),
+                        Err(e) => format!("Failed: {}", e),
+                    };
+                    outcomes.borrow_mut().insert(name, message);
+                    results.set(outcomes.borrow().clone());
+                }),
+            );
 
             names_input.set("".to_string());
         })
@@ -96,6 +92,11 @@ pub fn delete_goats_form() -> Html {
                 <button type="submit">{ "Delete" }</button>
             </form>
             <ul>
+                { for state.loading.deleting.iter().map(|name| html! {
+                    <li>
+                        <b>{ name }</b>{ ": " }<Spinner label="Deleting..." />
+                    </li>
+                })}
                 { for results.iter().map(|(name, msg)| html! {
                     <li>
                         <b>{ name }</b>{ ": " }{ msg }
@@ -0,0 +1,215 @@
+//! Diet reassignment panel: moves every goat matching a filter, e.g. all
+//! milking does, onto one feed plan through the bulk goat patch (see
+//! `shared::bulk::BulkPatch`), after previewing the goats it changes.
+
+use crate::services::use_api;
+use crate::store::{GoatStore, use_read_only};
+use log::{error, info};
+use shared::bulk::BulkPatch;
+use shared::exports::ExportFilter;
+use shared::inventory::InventoryCategory;
+use shared::lifecycle::LifecycleStage;
+use shared::{Breed, Gender, Goat};
+use std::collections::BTreeSet;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// DietReassignment component:
+/// Picks goats by sex, lifecycle stage, breed and health status and a new
+/// diet, offering the herd's current diets and the feed in inventory as
+/// suggestions. "Preview" lists the goats that would change with their
+/// current diet; "Apply" then sets the diet on exactly those goats and
+/// updates them in the goat list. Changing the filter or diet discards the
+/// preview. Applying is locked in read-only mode.
+#[function_component(DietReassignment)]
+pub fn diet_reassignment() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let (state, dispatch) = use_store::<GoatStore>();
+    let feeds = use_state(Vec::<String>::new);
+    let filter = use_state(ExportFilter::default);
+    let stage = use_state(|| None::<LifecycleStage>);
+    let diet = use_state(String::new);
+    let preview = use_state(|| None::<Vec<Goat>>);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let api = api.clone();
+        let feeds = feeds.clone();
+        move |_| {
+            spawn_local(async move {
+                match api.inventory_items().await {
+                    Ok(items) => feeds.set(
+                        items
+                            .into_iter()
+                            .filter(|item| item.category == InventoryCategory::Feed)
+                            .map(|item| item.name)
+                            .collect(),
+                    ),
+                    // Suggestions only; the diet can still be typed
+                    Err(e) => error!("Failed to load feed for diet suggestions: {}", e),
+                }
+            });
+            || {}
+        }
+    });
+
+    let on_filter = |update: fn(&mut ExportFilter, &str)| {
+        let filter = filter.clone();
+        let preview = preview.clone();
+        Callback::from(move |e: Event| {
+            let value = match e.target_dyn_into::<HtmlSelectElement>() {
+                Some(select) => select.value(),
+                None => e.target_unchecked_into::<HtmlInputElement>().value(),
+            };
+            let mut changed = (*filter).clone();
+            update(&mut changed, value.trim());
+            filter.set(changed);
+            preview.set(None);
+        })
+    };
+    let on_stage = {
+        let stage = stage.clone();
+        let preview = preview.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            stage.set(LifecycleStage::from_str(&select.value()).ok());
+            preview.set(None);
+        })
+    };
+    let on_diet = {
+        let diet = diet.clone();
+        let preview = preview.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            diet.set(input.value());
+            preview.set(None);
+        })
+    };
+
+    let patch = BulkPatch::diet((*filter).clone(), *stage, &diet);
+    let run = {
+        let preview = preview.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |apply: bool| {
+            if let Err(e) = patch.validate() {
+                error.set(Some(e));
+                return;
+            }
+            let api = api.clone();
+            let dispatch = dispatch.clone();
+            let patch = patch.clone();
+            let preview = preview.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.bulk_patch_goats(&patch, !apply).await {
+                    Ok(goats) if apply => {
+                        let diet = patch.update.diet.clone().unwrap_or_default();
+                        info!("Moved {} goats onto diet {}", goats.len(), diet);
+                        message.set(Some(format!("Moved {} goats onto {}", goats.len(), diet)));
+                        dispatch.reduce_mut(|store| {
+                            for patched in &goats {
+                                if let Some(goat) =
+                                    store.goats.iter_mut().find(|g| g.id == patched.id)
+                                {
+                                    *goat = patched.clone();
+                                }
+                            }
+                        });
+                        error.set(None);
+                        preview.set(None);
+                    }
+                    Ok(goats) => {
+                        error.set(None);
+                        message.set(None);
+                        preview.set(Some(goats));
+                    }
+                    Err(e) => {
+                        error!("Failed to reassign diets: {}", e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+    let on_preview = run.reform(|_: MouseEvent| false);
+    let on_apply = run.reform(|_: MouseEvent| true);
+
+    let suggestions: BTreeSet<&str> = state
+        .goats
+        .iter()
+        .map(|g| g.diet.trim())
+        .chain(feeds.iter().map(|f| f.trim()))
+        .filter(|d| !d.is_empty())
+        .collect();
+    let can_apply = !read_only && preview.as_ref().is_some_and(|goats| !goats.is_empty());
+
+    html! {
+        <div id="diet-reassignment">
+            <h3>{"Reassign diets"}</h3>
+            <p class="diet-filter" style="font-size: 12px;">
+                <select name="diet_gender"
+                        onchange={on_filter(|f, v| f.gender = Gender::from_str(v).ok())}>
+                    <option value="" selected={filter.gender.is_none()}>{"Any sex"}</option>
+                    <option value="Female" selected={filter.gender == Some(Gender::Female)}>{"Does"}</option>
+                    <option value="Male" selected={filter.gender == Some(Gender::Male)}>{"Bucks"}</option>
+                </select>
+                {" "}
+                <select name="diet_stage" onchange={on_stage}>
+                    <option value="" selected={stage.is_none()}>{"Any stage"}</option>
+                    { for LifecycleStage::ALL.iter().map(|s| html! {
+                        <option value={LifecycleStage::to_str(s).to_string()}
+                                selected={*stage == Some(*s)}>{s.label()}</option>
+                    }) }
+                </select>
+                {" "}
+                <input name="diet_breed" placeholder="Breed"
+                       onchange={on_filter(|f, v| f.breed = (!v.is_empty()).then(|| Breed::from_str(v)))} />
+                {" "}
+                <input name="diet_health" placeholder="Health status"
+                       onchange={on_filter(|f, v| f.health_status = (!v.is_empty()).then(|| v.to_string()))} />
+            </p>
+            <p>
+                <input name="new_diet" list="diet-suggestions" placeholder="New diet or feed plan"
+                       value={(*diet).clone()} oninput={on_diet} />
+                <datalist id="diet-suggestions">
+                    { for suggestions.iter().map(|d| html! { <option value={d.to_string()} /> }) }
+                </datalist>
+                {" "}
+                <button class="preview-diet" onclick={on_preview}>{"Preview"}</button>
+                {" "}
+                <button class="apply-diet" onclick={on_apply} disabled={!can_apply}>
+                    {format!("Apply to {} goats", preview.as_ref().map_or(0, Vec::len))}
+                </button>
+            </p>
+            if let Some(goats) = &*preview {
+                if goats.is_empty() {
+                    <p>{"No goats match."}</p>
+                } else {
+                    <table class="diet-preview">
+                        <tr><th>{"Goat"}</th><th>{"Current diet"}</th><th>{"New diet"}</th></tr>
+                        { for goats.iter().map(|g| html! {
+                            <tr key={g.id}>
+                                <td>{&g.name}</td>
+                                <td>{&g.diet}</td>
+                                <td>{diet.trim()}</td>
+                            </tr>
+                        }) }
+                    </table>
+                }
+            }
+            if let Some(msg) = &*message {
+                <p style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Diet error: {}", err)}</p>
+            }
+        </div>
+    }
+}
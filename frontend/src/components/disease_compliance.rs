@@ -0,0 +1,260 @@
+//! Notifiable disease compliance: flagging which catalogued diseases must be
+//! reported to authorities, and the report of every goat's cases of them
+//! (see `shared::diseases`).
+
+use crate::components::{FileLink, SkeletonRows};
+use crate::services::api::COMPLIANCE_CSV_URL;
+use crate::services::{Api, use_api};
+use crate::store::use_read_only;
+use log::{error, info};
+use shared::diseases::{CaseStatus, CatalogDisease, ComplianceReport, NewDisease};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Reloads the catalog and the compliance report, reporting failures in
+/// `error`.
+fn load_compliance(
+    api: Api,
+    catalog: UseStateHandle<Vec<CatalogDisease>>,
+    report: UseStateHandle<Option<ComplianceReport>>,
+    error: UseStateHandle<Option<String>>,
+) {
+    spawn_local(async move {
+        match api.diseases().await {
+            Ok(loaded) => {
+                info!("Loaded {} catalogued diseases", loaded.len());
+                catalog.set(loaded);
+            }
+            Err(e) => {
+                error!("Failed to load the disease catalog: {}", e);
+                error.set(Some(e.to_string()));
+            }
+        }
+        match api.compliance_report().await {
+            Ok(loaded) => report.set(Some(loaded)),
+            Err(e) => {
+                error!("Failed to load the compliance report: {}", e);
+                error.set(Some(e.to_string()));
+            }
+        }
+    });
+}
+
+/// DiseaseCompliance component:
+/// Lists the disease catalog with a notifiable checkbox per disease, and a
+/// form adding diseases to it. Below, the compliance report lists each
+/// goat's confirmed, suspected or tested cases of the notifiable diseases
+/// with its tag and test dates, with a link downloading it as CSV for
+/// authorities. Changes to the catalog reload the report. Locked in
+/// read-only mode.
+#[function_component(DiseaseCompliance)]
+pub fn disease_compliance() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let catalog = use_state(Vec::<CatalogDisease>::new);
+    let report = use_state(|| None::<ComplianceReport>);
+    let name = use_state(String::new);
+    let notifiable = use_state(|| true);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let api = api.clone();
+        let catalog = catalog.clone();
+        let report = report.clone();
+        let error = error.clone();
+        move |_| {
+            load_compliance(api, catalog, report, error);
+            || {}
+        }
+    });
+
+    let on_name = {
+        let name = name.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            name.set(input.value());
+        })
+    };
+    let on_notifiable = {
+        let notifiable = notifiable.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            notifiable.set(input.checked());
+        })
+    };
+
+    let on_add = {
+        let api = api.clone();
+        let catalog = catalog.clone();
+        let report = report.clone();
+        let name = name.clone();
+        let notifiable = notifiable.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let disease = NewDisease {
+                name: name.trim().to_string(),
+                notifiable: *notifiable,
+            };
+            if disease.name.is_empty() {
+                error.set(Some("Enter the disease's name".to_string()));
+                return;
+            }
+            let api = api.clone();
+            let catalog = catalog.clone();
+            let report = report.clone();
+            let name = name.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.add_disease(&disease).await {
+                    Ok(stored) => {
+                        info!("Catalogued disease {}", stored.id);
+                        name.set(String::new());
+                        error.set(None);
+                        load_compliance(api, catalog, report, error);
+                    }
+                    Err(e) => {
+                        error!("Failed to catalogue {}: {}", disease.name, e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_flag = |disease: &CatalogDisease| {
+        let api = api.clone();
+        let catalog = catalog.clone();
+        let report = report.clone();
+        let error = error.clone();
+        let id = disease.id;
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let flag = input.checked();
+            let api = api.clone();
+            let catalog = catalog.clone();
+            let report = report.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.set_disease_notifiable(id, flag).await {
+                    Ok(_) => {
+                        info!("Disease {} notifiable: {}", id, flag);
+                        error.set(None);
+                        load_compliance(api, catalog, report, error);
+                    }
+                    Err(e) => {
+                        error!("Failed to flag disease {}: {}", id, e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let csv_name = match &*report {
+        Some(report) => format!("notifiable-diseases-{}.csv", report.generated_on),
+        None => "notifiable-diseases.csv".to_string(),
+    };
+
+    html! {
+        <div id="disease-compliance">
+            <h3>{"Notifiable Diseases"}</h3>
+            <table class="disease-catalog" style="border-collapse: collapse;">
+                <thead>
+                    <tr><th>{"Disease"}</th><th>{"Goats"}</th><th>{"Notifiable"}</th></tr>
+                </thead>
+                <tbody>
+                    { for catalog.iter().map(|d| html! {
+                        <tr key={d.id} data-disease={d.id.to_string()}>
+                            <td>{&d.name}</td>
+                            <td>{d.goat_count}</td>
+                            <td>
+                                <input type="checkbox" class="disease-notifiable"
+                                       checked={d.notifiable} disabled={read_only}
+                                       onchange={on_flag(d)} />
+                            </td>
+                        </tr>
+                    }) }
+                </tbody>
+            </table>
+            <p>
+                <input class="new-disease" placeholder="Disease" value={(*name).clone()}
+                       oninput={on_name} />
+                {" "}
+                <label>
+                    <input type="checkbox" class="new-disease-notifiable" checked={*notifiable}
+                           onchange={on_notifiable} />
+                    {" Notifiable"}
+                </label>
+                {" "}
+                <button class="add-disease" onclick={on_add} disabled={read_only}>
+                    {"Add disease"}
+                </button>
+            </p>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error: {}", err)}</p>
+            }
+            <h4>
+                {"Compliance report "}
+                <FileLink class="compliance-csv" url={COMPLIANCE_CSV_URL} file_name={csv_name}>
+                    {"Download CSV"}
+                </FileLink>
+            </h4>
+            {
+                match &*report {
+                    None => html! {
+                        <table><tbody><SkeletonRows rows={2} columns={6} /></tbody></table>
+                    },
+                    Some(report) => html! {
+                        <>
+                            <p class="compliance-covers">
+                                {format!("As of {}, covering {}", report.generated_on,
+                                         if report.diseases.is_empty() {
+                                             "no notifiable diseases".to_string()
+                                         } else {
+                                             report.diseases.join(", ")
+                                         })}
+                            </p>
+                            if report.cases.is_empty() {
+                                <p class="compliance-empty">{"No cases to report."}</p>
+                            } else {
+                                <table class="compliance-cases"
+                                       style="border-collapse: collapse; width: 100%;">
+                                    <thead>
+                                        <tr>
+                                            <th>{"Disease"}</th>
+                                            <th>{"Goat"}</th>
+                                            <th>{"Tag"}</th>
+                                            <th>{"Status"}</th>
+                                            <th>{"First reported"}</th>
+                                            <th>{"Tests"}</th>
+                                        </tr>
+                                    </thead>
+                                    <tbody>
+                                        { for report.cases.iter().map(|c| {
+                                            let style = match c.status {
+                                                CaseStatus::Confirmed => "color: #c62828;",
+                                                CaseStatus::Suspected => "color: #e65100;",
+                                                CaseStatus::Tested => "",
+                                            };
+                                            html! {
+                                                <tr class="compliance-case" {style}>
+                                                    <td>{&c.disease}</td>
+                                                    <td>{&c.goat_name}</td>
+                                                    <td>{c.tag_id.clone().unwrap_or_default()}</td>
+                                                    <td class="case-status">{CaseStatus::to_str(&c.status)}</td>
+                                                    <td>{c.first_reported.clone().unwrap_or_default()}</td>
+                                                    <td class="case-tests">{c.tests_label()}</td>
+                                                </tr>
+                                            }
+                                        }) }
+                                    </tbody>
+                                </table>
+                            }
+                        </>
+                    },
+                }
+            }
+        </div>
+    }
+}
@@ -0,0 +1,28 @@
+//! Banner shown by forms holding an autosaved draft (see `crate::drafts`).
+
+use yew::prelude::*;
+
+/// Props for DraftBar:
+/// - `restored`: true if the draft was restored when the form opened,
+///   rather than saved while typing
+/// - `on_discard`: callback resetting the form and deleting the draft
+#[derive(Properties, PartialEq)]
+pub struct DraftBarProps {
+    pub restored: bool,
+    pub on_discard: Callback<MouseEvent>,
+}
+
+#[function_component(DraftBar)]
+pub fn draft_bar(props: &DraftBarProps) -> Html {
+    let message = if props.restored {
+        "Restored your unsaved draft."
+    } else {
+        "Draft saved in this browser."
+    };
+    html! {
+        <p class="draft-bar" style="font-size: 12px; color: #666;">
+            {message}{" "}
+            <button type="button" onclick={props.on_discard.clone()}>{"Discard draft"}</button>
+        </p>
+    }
+}
@@ -0,0 +1,79 @@
+//! Placeholder shown in place of a list or table with nothing in it yet,
+//! saying what will appear there and offering the actions that add it.
+
+use crate::store::use_read_only;
+use yew::prelude::*;
+
+/// A link from an empty state to the form that fills it, e.g. the
+/// dashboard's `#add-goat` section.
+#[derive(Clone, PartialEq)]
+pub struct EmptyAction {
+    pub label: AttrValue,
+    pub href: AttrValue,
+}
+
+impl EmptyAction {
+    pub fn new(label: &'static str, href: &'static str) -> Self {
+        EmptyAction {
+            label: label.into(),
+            href: href.into(),
+        }
+    }
+}
+
+/// Props for EmptyState:
+/// - `title`: what is missing, e.g. "No goats yet"
+/// - `message`: optional line on what will show up here
+/// - `sample`: optional example entry, previewing what the list will hold
+/// - `actions`: links to the forms that add data, most useful first
+#[derive(Properties, PartialEq)]
+pub struct EmptyStateProps {
+    pub title: AttrValue,
+    #[prop_or_default]
+    pub message: Option<AttrValue>,
+    #[prop_or_default]
+    pub sample: Option<AttrValue>,
+    #[prop_or_default]
+    pub actions: Vec<EmptyAction>,
+}
+
+/// EmptyState component:
+/// Shows the title and message, the sample entry faded beneath them, and
+/// the actions as buttons, the first one highlighted. The actions all add
+/// data, so they are left out in read-only mode.
+#[function_component(EmptyState)]
+pub fn empty_state(props: &EmptyStateProps) -> Html {
+    let read_only = use_read_only();
+    html! {
+        <div class="empty-state"
+             style="margin: 12px 0; padding: 24px; border: 1px dashed #bbb; border-radius: 8px; text-align: center; color: #555;">
+            <p class="empty-title" style="margin: 0; font-weight: bold; font-size: 16px;">
+                {&props.title}
+            </p>
+            if let Some(message) = &props.message {
+                <p style="margin: 6px 0 0;">{message}</p>
+            }
+            if let Some(sample) = &props.sample {
+                <p class="empty-sample" style="margin: 6px 0 0; opacity: 0.5; font-style: italic;">
+                    {format!("For example: {}", sample)}
+                </p>
+            }
+            if !read_only && !props.actions.is_empty() {
+                <p style="margin: 12px 0 0; display: flex; gap: 8px; justify-content: center;">
+                    { for props.actions.iter().enumerate().map(|(i, action)| {
+                        let style = if i == 0 {
+                            "padding: 6px 12px; border-radius: 4px; background: #2c3e50; color: white; text-decoration: none;"
+                        } else {
+                            "padding: 6px 12px; border-radius: 4px; border: 1px solid #2c3e50; color: #2c3e50; text-decoration: none;"
+                        };
+                        html! {
+                            <a class="empty-action" href={action.href.clone()} {style}>
+                                {&action.label}
+                            </a>
+                        }
+                    }) }
+                </p>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,121 @@
+//! Error boundary isolating failures to one section of the dashboard.
+//!
+//! Descendants report unrecoverable errors through `use_section_error`;
+//! the nearest enclosing boundary then replaces its section with a fallback
+//! offering "Reload section" (remounting the children with fresh state) and
+//! optional crash report submission. Boundaries nest, so a failure only takes
+//! down the innermost section it occurred in.
+
+use crate::crash_report::{auto_report_enabled, build_report, set_auto_report, submit_report};
+use log::error;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Context handed to descendants of an `ErrorBoundary` for reporting failures.
+#[derive(Clone, PartialEq)]
+pub struct SectionErrorReporter(pub Callback<String>);
+
+/// Returns a callback that reports an error to the nearest `ErrorBoundary`.
+///
+/// Outside of any boundary the error is only logged.
+#[hook]
+pub fn use_section_error() -> Callback<String> {
+    use_context::<SectionErrorReporter>()
+        .map(|reporter| reporter.0)
+        .unwrap_or_else(|| Callback::from(|msg: String| error!("Unhandled section error: {}", msg)))
+}
+
+/// Props for ErrorBoundary:
+/// - `name`: human-readable section name shown in the fallback UI
+/// - `children`: the section content
+#[derive(Properties, PartialEq)]
+pub struct ErrorBoundaryProps {
+    pub name: AttrValue,
+    #[prop_or_default]
+    pub children: Html,
+}
+
+#[function_component(ErrorBoundary)]
+pub fn error_boundary(props: &ErrorBoundaryProps) -> Html {
+    let failure = use_state(|| None::<String>);
+    let generation = use_state(|| 0u32);
+    let report_status = use_state(|| None::<String>);
+    let auto_report = use_state(auto_report_enabled);
+
+    let reporter = {
+        let set_failure = failure.setter();
+        let name = props.name.clone();
+        use_memo(props.name.clone(), move |_| {
+            SectionErrorReporter(Callback::from(move |msg: String| {
+                error!("Section '{}' failed: {}", name, msg);
+                set_failure.set(Some(msg));
+            }))
+        })
+    };
+
+    let Some(message) = (*failure).clone() else {
+        return html! {
+            <ContextProvider<SectionErrorReporter> context={(*reporter).clone()}>
+                <div key={generation.to_string()}>
+                    { props.children.clone() }
+                </div>
+            </ContextProvider<SectionErrorReporter>>
+        };
+    };
+
+    let reload = {
+        let failure = failure.clone();
+        let generation = generation.clone();
+        let report_status = report_status.clone();
+        Callback::from(move |_| {
+            generation.set(*generation + 1);
+            report_status.set(None);
+            failure.set(None);
+        })
+    };
+
+    let send_report = {
+        let report_status = report_status.clone();
+        let section = props.name.to_string();
+        let message = message.clone();
+        Callback::from(move |_| {
+            let report_status = report_status.clone();
+            report_status.set(Some("Sending report...".to_string()));
+            submit_report(
+                build_report(Some(section.clone()), message.clone()),
+                Callback::from(move |res| match res {
+                    Ok(()) => report_status.set(Some("Report sent. Thank you!".to_string())),
+                    Err(e) => report_status.set(Some(format!("Failed: {}", e))),
+                }),
+            );
+        })
+    };
+
+    let toggle_auto_report = {
+        let auto_report = auto_report.clone();
+        Callback::from(move |e: Event| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                set_auto_report(input.checked());
+                auto_report.set(input.checked());
+            }
+        })
+    };
+
+    html! {
+        <div class="error-boundary" style="border: 1px solid #d9534f; padding: 12px; margin: 12px 0;">
+            <h3>{ format!("Something went wrong in {}", props.name) }</h3>
+            <p style="font-family: monospace;">{ message }</p>
+            <button onclick={reload}>{ "Reload section" }</button>
+            { " " }
+            <button onclick={send_report}>{ "Send crash report" }</button>
+            <br/>
+            <label>
+                <input type="checkbox" checked={*auto_report} onchange={toggle_auto_report} />
+                { " Automatically send crash reports" }
+            </label>
+            if let Some(status) = &*report_status {
+                <p>{ status }</p>
+            }
+        </div>
+    }
+}
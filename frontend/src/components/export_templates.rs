@@ -0,0 +1,323 @@
+//! Saved exports panel: named goat-list exports with their columns,
+//! filters and format (see `shared::exports::ExportTemplate`), each run
+//! with one click. Scripts run the same templates through the API.
+
+use crate::components::{FileLink, Spinner};
+use crate::services::api::export_run_url;
+use crate::services::{Api, use_api};
+use crate::store::{farm_timezone, use_read_only};
+use log::{error, info};
+use shared::exports::{ExportColumn, ExportFilter, ExportFormat, ExportTemplate};
+use shared::physical::{CoatColor, HornStatus};
+use shared::time::today_in;
+use shared::{Breed, Gender};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+/// Columns ticked for a new template.
+const DEFAULT_COLUMNS: [ExportColumn; 4] = [
+    ExportColumn::Name,
+    ExportColumn::Breed,
+    ExportColumn::Gender,
+    ExportColumn::Weight,
+];
+
+/// Reloads the templates into `templates`, reporting failures in `error`.
+fn load_templates(
+    api: Api,
+    templates: UseStateHandle<Option<Vec<ExportTemplate>>>,
+    error: UseStateHandle<Option<String>>,
+) {
+    spawn_local(async move {
+        match api.export_templates().await {
+            Ok(loaded) => templates.set(Some(loaded)),
+            Err(e) => {
+                error!("Failed to load export templates: {}", e);
+                error.set(Some(e.to_string()));
+            }
+        }
+    });
+}
+
+/// The criteria `filter` sets, e.g. "Female, sick"; "All goats" if none.
+fn filter_summary(filter: &ExportFilter) -> String {
+    let criteria: Vec<String> = [
+        filter.breed.as_ref().map(|b| Breed::to_str(b).to_string()),
+        filter
+            .gender
+            .as_ref()
+            .map(|g| Gender::to_str(g).to_string()),
+        filter.health_status.clone(),
+        filter.traits.horns.map(|h| h.label().to_string()),
+        filter.traits.coat_color.map(|c| c.label().to_string()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if criteria.is_empty() {
+        "All goats".to_string()
+    } else {
+        criteria.join(", ")
+    }
+}
+
+/// ExportTemplates component:
+/// Lists the saved exports with a download link each, named after the
+/// template and today's date, and saves new ones from a key, name, format,
+/// ticked columns and filters. Saving and removing are locked in read-only
+/// mode; running is not.
+#[function_component(ExportTemplates)]
+pub fn export_templates() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let templates = use_state(|| None::<Vec<ExportTemplate>>);
+    let key = use_state(String::new);
+    let name = use_state(String::new);
+    let format = use_state(ExportFormat::default);
+    let columns = use_state(|| DEFAULT_COLUMNS.to_vec());
+    let filter = use_state(ExportFilter::default);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let api = api.clone();
+        let templates = templates.clone();
+        let error = error.clone();
+        move |_| {
+            load_templates(api, templates, error);
+            || {}
+        }
+    });
+
+    let on_input = |field: &UseStateHandle<String>| {
+        let field = field.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            field.set(input.value());
+        })
+    };
+    let on_format = {
+        let format = format.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(selected) = ExportFormat::ALL
+                .into_iter()
+                .find(|f| f.extension() == select.value())
+            {
+                format.set(selected);
+            }
+        })
+    };
+    let on_column = |column: ExportColumn| {
+        let columns = columns.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let mut ticked: Vec<ExportColumn> = (*columns)
+                .iter()
+                .copied()
+                .filter(|c| *c != column)
+                .collect();
+            if input.checked() {
+                ticked.push(column);
+            }
+            // Keep goat-list order whatever order the boxes are ticked in
+            ticked.sort_by_key(|c| ExportColumn::ALL.iter().position(|a| a == c));
+            columns.set(ticked);
+        })
+    };
+    let on_filter = |update: fn(&mut ExportFilter, &str)| {
+        let filter = filter.clone();
+        Callback::from(move |e: Event| {
+            let value = match e.target_dyn_into::<HtmlSelectElement>() {
+                Some(select) => select.value(),
+                None => e.target_unchecked_into::<HtmlInputElement>().value(),
+            };
+            let mut changed = (*filter).clone();
+            update(&mut changed, value.trim());
+            filter.set(changed);
+        })
+    };
+
+    let on_save = {
+        let api = api.clone();
+        let templates = templates.clone();
+        let key = key.clone();
+        let name = name.clone();
+        let format = format.clone();
+        let columns = columns.clone();
+        let filter = filter.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let template = ExportTemplate {
+                key: key.trim().to_string(),
+                name: name.trim().to_string(),
+                columns: (*columns).clone(),
+                filter: (*filter).clone(),
+                format: *format,
+            };
+            if let Err(e) = template.validate() {
+                error.set(Some(e));
+                return;
+            }
+            let api = api.clone();
+            let templates = templates.clone();
+            let fields = [key.clone(), name.clone()];
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.add_export_template(&template).await {
+                    Ok(stored) => {
+                        info!("Saved export template {}", stored.key);
+                        fields.iter().for_each(|f| f.set(String::new()));
+                        error.set(None);
+                        message.set(Some(format!("Saved export {}", stored.name)));
+                        load_templates(api, templates, error);
+                    }
+                    Err(e) => {
+                        error!("Failed to save export template: {}", e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_delete = {
+        let templates = templates.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |template: ExportTemplate| {
+            let api = api.clone();
+            let templates = templates.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.delete_export_template(&template.key).await {
+                    Ok(()) => {
+                        info!("Removed export template {}", template.key);
+                        error.set(None);
+                        message.set(Some(format!("Removed export {}", template.name)));
+                        load_templates(api, templates, error);
+                    }
+                    Err(e) => {
+                        error!("Failed to remove export template {}: {}", template.key, e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let today = today_in(farm_timezone()).to_string();
+
+    html! {
+        <div id="export-templates">
+            <h3>{"Saved exports"}</h3>
+            if let Some(templates) = &*templates {
+                if templates.is_empty() {
+                    <p>{"No saved exports yet."}</p>
+                } else {
+                    <table class="export-templates">
+                        <tr>
+                            <th>{"Name"}</th><th>{"Columns"}</th><th>{"Goats"}</th>
+                            <th>{"Format"}</th><th></th>
+                        </tr>
+                        { for templates.iter().map(|t| {
+                            let onclick = {
+                                let on_delete = on_delete.clone();
+                                let template = t.clone();
+                                Callback::from(move |_: MouseEvent| on_delete.emit(template.clone()))
+                            };
+                            let columns: Vec<&str> = t.columns.iter().map(|c| c.label()).collect();
+                            html! {
+                                <tr key={t.key.clone()}>
+                                    <td title={t.key.clone()}>{&t.name}</td>
+                                    <td>{columns.join(", ")}</td>
+                                    <td>{filter_summary(&t.filter)}</td>
+                                    <td>{t.format.extension().to_uppercase()}</td>
+                                    <td>
+                                        <FileLink class="run-export" url={export_run_url(&t.key)}
+                                                  file_name={t.file_name(&today)}>{"Export"}</FileLink>
+                                        {" "}
+                                        <button class="delete-export" {onclick} disabled={read_only}>
+                                            {"Remove"}
+                                        </button>
+                                    </td>
+                                </tr>
+                            }
+                        }) }
+                    </table>
+                }
+            } else {
+                <Spinner label="Loading saved exports..." />
+            }
+            <p>
+                <input class="export-key" placeholder="Key, e.g. monthly-weights"
+                       value={(*key).clone()} oninput={on_input(&key)} />
+                {" "}
+                <input class="export-name" placeholder="Name" value={(*name).clone()}
+                       oninput={on_input(&name)} />
+                {" "}
+                <select class="export-format" onchange={on_format}>
+                    { for ExportFormat::ALL.iter().map(|f| html! {
+                        <option value={f.extension()} selected={*f == *format}>
+                            {f.extension().to_uppercase()}
+                        </option>
+                    }) }
+                </select>
+            </p>
+            <p class="export-columns" style="font-size: 12px;">
+                { for ExportColumn::ALL.iter().map(|c| html! {
+                    <label>
+                        <input type="checkbox" checked={columns.contains(c)} onchange={on_column(*c)} />
+                        {c.label()}{" "}
+                    </label>
+                }) }
+            </p>
+            <p class="export-filter" style="font-size: 12px;">
+                <input name="export_breed" placeholder="Breed"
+                       onchange={on_filter(|f, v| f.breed = (!v.is_empty()).then(|| Breed::from_str(v)))} />
+                {" "}
+                <select name="export_gender"
+                        onchange={on_filter(|f, v| f.gender = Gender::from_str(v).ok())}>
+                    <option value="" selected={filter.gender.is_none()}>{"Any sex"}</option>
+                    <option value="Female" selected={filter.gender == Some(Gender::Female)}>{"Does"}</option>
+                    <option value="Male" selected={filter.gender == Some(Gender::Male)}>{"Bucks"}</option>
+                </select>
+                {" "}
+                <input name="export_health" placeholder="Health status"
+                       onchange={on_filter(|f, v| f.health_status = (!v.is_empty()).then(|| v.to_string()))} />
+                {" "}
+                <select name="export_horns"
+                        onchange={on_filter(|f, v| f.traits.horns = HornStatus::from_str(v).ok())}>
+                    <option value="" selected={filter.traits.horns.is_none()}>{"Any horns"}</option>
+                    { for HornStatus::ALL.iter().map(|h| html! {
+                        <option value={HornStatus::to_str(h).to_string()}
+                                selected={filter.traits.horns == Some(*h)}>{h.label()}</option>
+                    }) }
+                </select>
+                {" "}
+                <select name="export_coat"
+                        onchange={on_filter(|f, v| f.traits.coat_color = CoatColor::from_str(v).ok())}>
+                    <option value="" selected={filter.traits.coat_color.is_none()}>{"Any coat"}</option>
+                    { for CoatColor::ALL.iter().map(|c| html! {
+                        <option value={CoatColor::to_str(c).to_string()}
+                                selected={filter.traits.coat_color == Some(*c)}>{c.label()}</option>
+                    }) }
+                </select>
+                {" "}
+                <button class="save-export" onclick={on_save} disabled={read_only}>{"Save export"}</button>
+            </p>
+            if let Some(msg) = &*message {
+                <p style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Export error: {}", err)}</p>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,231 @@
+//! External registry panel: bucks and does kept off the farm, e.g. a
+//! neighbour's buck, that pedigrees, kiddings and services can name without
+//! adding them to the herd (see `shared::breeding::ExternalAnimal`).
+
+use crate::components::Spinner;
+use crate::services::{Api, use_api};
+use crate::store::use_read_only;
+use log::{error, info};
+use shared::Gender;
+use shared::breeding::ExternalAnimal;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+/// Reloads the registry into `animals`, reporting failures in `error`.
+fn load_animals(
+    api: Api,
+    animals: UseStateHandle<Option<Vec<ExternalAnimal>>>,
+    error: UseStateHandle<Option<String>>,
+) {
+    spawn_local(async move {
+        match api.external_animals().await {
+            Ok(loaded) => animals.set(Some(loaded)),
+            Err(e) => {
+                error!("Failed to load external animals: {}", e);
+                error.set(Some(e.to_string()));
+            }
+        }
+    });
+}
+
+/// `Some` of the trimmed value, or `None` if blank.
+fn optional(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// ExternalAnimals component:
+/// Lists the registry by name with each animal's breed, registration and
+/// owner, adds animals by name and gender, and removes them (their records
+/// keep everything but the link). Locked in read-only mode.
+#[function_component(ExternalAnimals)]
+pub fn external_animals() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let animals = use_state(|| None::<Vec<ExternalAnimal>>);
+    let name = use_state(String::new);
+    let gender = use_state(|| Gender::Male);
+    let breed = use_state(String::new);
+    let registration = use_state(String::new);
+    let owner = use_state(String::new);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let api = api.clone();
+        let animals = animals.clone();
+        let error = error.clone();
+        move |_| {
+            load_animals(api, animals, error);
+            || {}
+        }
+    });
+
+    let on_input = |field: &UseStateHandle<String>| {
+        let field = field.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            field.set(input.value());
+        })
+    };
+    let on_gender = {
+        let gender = gender.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Ok(selected) = Gender::from_str(&select.value()) {
+                gender.set(selected);
+            }
+        })
+    };
+
+    let on_add = {
+        let api = api.clone();
+        let animals = animals.clone();
+        let name = name.clone();
+        let gender = gender.clone();
+        let breed = breed.clone();
+        let registration = registration.clone();
+        let owner = owner.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let animal = ExternalAnimal {
+                id: None,
+                name: name.trim().to_string(),
+                gender: (*gender).clone(),
+                breed: optional(&breed),
+                registration: optional(&registration),
+                owner: optional(&owner),
+                notes: None,
+            };
+            if let Err(e) = animal.validate() {
+                error.set(Some(e));
+                return;
+            }
+            let api = api.clone();
+            let animals = animals.clone();
+            let fields = [
+                name.clone(),
+                breed.clone(),
+                registration.clone(),
+                owner.clone(),
+            ];
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.add_external_animal(&animal).await {
+                    Ok(stored) => {
+                        info!("Added {} to the external registry", stored.name);
+                        fields.iter().for_each(|f| f.set(String::new()));
+                        error.set(None);
+                        message.set(Some(format!("Added {} to the registry", stored.name)));
+                        load_animals(api, animals, error);
+                    }
+                    Err(e) => {
+                        error!("Failed to add external animal: {}", e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_delete = {
+        let animals = animals.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |animal: ExternalAnimal| {
+            let Some(id) = animal.id else {
+                return;
+            };
+            let api = api.clone();
+            let animals = animals.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.delete_external_animal(id).await {
+                    Ok(()) => {
+                        info!("Removed {} from the external registry", animal.name);
+                        error.set(None);
+                        message.set(Some(format!("Removed {} from the registry", animal.name)));
+                        load_animals(api, animals, error);
+                    }
+                    Err(e) => {
+                        error!("Failed to remove {}: {}", animal.name, e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <div id="external-animals">
+            <h3>{"External registry"}</h3>
+            <p>
+                <input class="external-name" placeholder="Name" value={(*name).clone()}
+                       oninput={on_input(&name)} />
+                {" "}
+                <select class="external-gender" onchange={on_gender}>
+                    <option value="Male" selected={*gender == Gender::Male}>{"Buck"}</option>
+                    <option value="Female" selected={*gender == Gender::Female}>{"Doe"}</option>
+                </select>
+                {" "}
+                <input class="external-breed" placeholder="Breed" value={(*breed).clone()}
+                       oninput={on_input(&breed)} />
+                {" "}
+                <input class="external-registration" placeholder="Registration"
+                       value={(*registration).clone()} oninput={on_input(&registration)} />
+                {" "}
+                <input class="external-owner" placeholder="Owner" value={(*owner).clone()}
+                       oninput={on_input(&owner)} />
+                {" "}
+                <button class="add-external" onclick={on_add} disabled={read_only}>{"Add"}</button>
+            </p>
+            if let Some(animals) = &*animals {
+                if animals.is_empty() {
+                    <p>{"No external animals yet."}</p>
+                } else {
+                    <table class="external-animals">
+                        <tr>
+                            <th>{"Name"}</th><th>{"Gender"}</th><th>{"Breed"}</th>
+                            <th>{"Registration"}</th><th>{"Owner"}</th><th></th>
+                        </tr>
+                        { for animals.iter().map(|a| {
+                            let onclick = {
+                                let on_delete = on_delete.clone();
+                                let animal = a.clone();
+                                Callback::from(move |_: MouseEvent| on_delete.emit(animal.clone()))
+                            };
+                            html! {
+                                <tr key={a.id.unwrap_or_default()}>
+                                    <td>{&a.name}</td>
+                                    <td>{Gender::to_str(&a.gender)}</td>
+                                    <td>{a.breed.clone().unwrap_or_default()}</td>
+                                    <td>{a.registration.clone().unwrap_or_default()}</td>
+                                    <td>{a.owner.clone().unwrap_or_default()}</td>
+                                    <td>
+                                        <button class="delete-external" {onclick} disabled={read_only}>
+                                            {"Remove"}
+                                        </button>
+                                    </td>
+                                </tr>
+                            }
+                        }) }
+                    </table>
+                }
+            } else {
+                <Spinner label="Loading external animals..." />
+            }
+            if let Some(msg) = &*message {
+                <p style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Registry error: {}", err)}</p>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,181 @@
+//! Farm archive panel: downloads the whole farm as a portable archive and
+//! restores one into a new instance (see `shared::archive`), optionally
+//! encrypted with a passphrase.
+
+use crate::components::{FileLink, Spinner};
+use crate::components::import_wizard::read_file;
+use crate::services::api::ARCHIVE_URL;
+use crate::services::use_api;
+use crate::store::{GoatStore, use_read_only};
+use log::{error, info};
+use shared::archive::{ArchiveSummary, check_passphrase};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{Blob, HtmlInputElement, Url};
+use yew::prelude::*;
+use yewdux::prelude::use_dispatch;
+
+/// FarmArchive component:
+/// Links to the archive download, and uploads a chosen archive for the
+/// backend to restore, which it only does into a farm without goats. Shows
+/// what was restored and reloads the goat list. Restoring is disabled in
+/// read-only mode.
+///
+/// With a passphrase entered, the archive is downloaded encrypted and offered
+/// as a saved file, and the passphrase is sent along with a restore.
+#[function_component(FarmArchive)]
+pub fn farm_archive() -> Html {
+    let api = use_api();
+    let dispatch = use_dispatch::<GoatStore>();
+    let read_only = use_read_only();
+    let summary = use_state(|| None::<ArchiveSummary>);
+    let loading = use_state(|| false);
+    let error = use_state(|| None::<String>);
+    let passphrase = use_state(String::new);
+    let encrypted_url = use_state(|| None::<String>);
+
+    let on_passphrase = {
+        let passphrase = passphrase.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            passphrase.set(input.value());
+        })
+    };
+
+    let on_encrypt = {
+        let api = api.clone();
+        let passphrase = passphrase.clone();
+        let encrypted_url = encrypted_url.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Err(e) = check_passphrase(&passphrase) {
+                error.set(Some(e));
+                return;
+            }
+            let api = api.clone();
+            let passphrase = (*passphrase).clone();
+            let encrypted_url = encrypted_url.clone();
+            let loading = loading.clone();
+            let error = error.clone();
+            loading.set(true);
+            error.set(None);
+            spawn_local(async move {
+                match api.export_farm_archive(&passphrase).await {
+                    Ok(bytes) => {
+                        let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(&bytes[..]));
+                        match Blob::new_with_u8_array_sequence(&parts)
+                            .and_then(|blob| Url::create_object_url_with_blob(&blob))
+                        {
+                            Ok(url) => {
+                                if let Some(old) = &*encrypted_url {
+                                    let _ = Url::revoke_object_url(old);
+                                }
+                                encrypted_url.set(Some(url));
+                            }
+                            Err(e) => error.set(Some(format!("{:?}", e))),
+                        }
+                    }
+                    Err(e) => {
+                        error!("Failed to download encrypted archive: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+                loading.set(false);
+            });
+        })
+    };
+
+    let on_file = {
+        let passphrase = passphrase.clone();
+        let summary = summary.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let passphrase = (!passphrase.is_empty()).then(|| (*passphrase).clone());
+            let api = api.clone();
+            let dispatch = dispatch.clone();
+            let summary = summary.clone();
+            let loading = loading.clone();
+            let error = error.clone();
+            loading.set(true);
+            error.set(None);
+            summary.set(None);
+            spawn_local(async move {
+                let restored = match read_file(&input).await {
+                    Ok(bytes) => api
+                        .import_farm_archive(&bytes, passphrase.as_deref())
+                        .await
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e),
+                };
+                match restored {
+                    Ok(restored) => {
+                        info!("Restored {} records from archive", restored.rows);
+                        summary.set(Some(restored));
+                        GoatStore::force_refresh(api, dispatch);
+                    }
+                    Err(e) => {
+                        error!("Failed to restore farm archive: {}", e);
+                        error.set(Some(e));
+                    }
+                }
+                loading.set(false);
+            });
+        })
+    };
+
+    html! {
+        <div>
+            <h3>{"Farm Archive"}</h3>
+            <p style="font-size: 12px;">
+                {"Download every record of this farm as one file, to move it to another Yagi instance."}
+            </p>
+            <p><FileLink url={ARCHIVE_URL} file_name="farm.zip">{"Download archive"}</FileLink></p>
+            <p>
+                <label>{"Passphrase: "}
+                    <input type="password" class="archive-passphrase" value={(*passphrase).clone()}
+                           oninput={on_passphrase} autocomplete="new-password" />
+                </label>
+                {" "}
+                <button onclick={on_encrypt} disabled={passphrase.is_empty() || *loading}>
+                    {"Download encrypted archive"}
+                </button>
+            </p>
+            <p style="font-size: 12px;">
+                {"An encrypted archive can only be restored with its passphrase, which cannot be recovered if lost."}
+            </p>
+            if let Some(url) = &*encrypted_url {
+                <p><a class="encrypted-download" href={url.clone()} download="farm.zip.enc">
+                    {"Save encrypted archive"}
+                </a></p>
+            }
+            <p>
+                <label>{"Restore an archive into this farm: "}
+                    <input type="file" accept=".zip,.enc,application/zip" onchange={on_file}
+                           disabled={read_only || *loading} />
+                </label>
+            </p>
+            if *loading {
+                <Spinner label="Restoring..." />
+            }
+            if let Some(summary) = &*summary {
+                <p class="archive-summary">
+                    {format!(
+                        "Restored {} records from {} tables, with {} files.",
+                        summary.rows, summary.tables, summary.files
+                    )}
+                </p>
+                if !summary.skipped.is_empty() {
+                    <p style="color: #8a6d00;">
+                        {format!("Left out tables this version does not have: {}",
+                                 summary.skipped.join(", "))}
+                    </p>
+                }
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Archive error: {}", err)}</p>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,127 @@
+//! Feed efficiency analytics: feed conversion ratio (FCR) and cost per kg of
+//! weight gain for each goat and each pen.
+
+use crate::components::SkeletonRows;
+use crate::services::use_api;
+use log::{error, info};
+use shared::analytics::{FeedEfficiency, FeedEfficiencyReport};
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// FeedEfficiencyPanel component:
+/// Loads the feed efficiency report and shows one table for goats and one
+/// for pens, most efficient first.
+#[function_component(FeedEfficiencyPanel)]
+pub fn feed_efficiency_panel() -> Html {
+    let api = use_api();
+    let report = use_state(|| None::<FeedEfficiencyReport>);
+    let error = use_state(|| None::<String>);
+
+    let load = {
+        let report = report.clone();
+        let error = error.clone();
+        Callback::from(move |_: ()| {
+            let api = api.clone();
+            let report = report.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.feed_efficiency().await {
+                    Ok(loaded) => {
+                        info!(
+                            "Loaded feed efficiency for {} goats and {} pens",
+                            loaded.goats.len(),
+                            loaded.pens.len()
+                        );
+                        error.set(None);
+                        report.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load feed efficiency: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    use_effect_with((), {
+        let load = load.clone();
+        move |_| {
+            load.emit(());
+            || {}
+        }
+    });
+
+    html! {
+        <div>
+            <h3>{"Feed Efficiency"}</h3>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading feed efficiency: {}", err)}</p>
+            }
+            <button onclick={load.reform(|_| ())} style="margin-bottom: 10px;">{"Refresh"}</button>
+            {
+                match &*report {
+                    None => html! {
+                        <table><tbody><SkeletonRows rows={3} columns={7} /></tbody></table>
+                    },
+                    Some(report) if report.goats.is_empty() && report.pens.is_empty() => html! {
+                        <p>{"No feeding logs yet. Log feed use against goats or pens to see FCR."}</p>
+                    },
+                    Some(report) => html! {
+                        <>
+                            <h4>{"By Goat"}</h4>
+                            { efficiency_table("Goat", &report.goats) }
+                            <h4>{"By Pen"}</h4>
+                            { efficiency_table("Pen", &report.pens) }
+                            <p style="font-size: 12px;">
+                                {"FCR is kg of feed per kg gained between the first and last weighing. \
+                                  Lower is better."}
+                            </p>
+                        </>
+                    },
+                }
+            }
+        </div>
+    }
+}
+
+/// Renders one table of feed efficiency rows, labelling the first column `label`.
+fn efficiency_table(label: &str, rows: &[FeedEfficiency]) -> Html {
+    if rows.is_empty() {
+        return html! { <p>{"No data."}</p> };
+    }
+    let ratio = |value: Option<f64>| value.map_or("–".to_string(), |v| format!("{:.2}", v));
+    html! {
+        <table style="border-collapse: collapse; width: 100%;">
+            <thead>
+                <tr>
+                    <th>{label}</th>
+                    <th>{"Period"}</th>
+                    <th>{"Feed (kg)"}</th>
+                    <th>{"Feed Cost"}</th>
+                    <th>{"Gain (kg)"}</th>
+                    <th>{"FCR"}</th>
+                    <th>{"Cost / kg Gain"}</th>
+                </tr>
+            </thead>
+            <tbody>
+                { for rows.iter().map(|row| html! {
+                    <tr data-name={row.name.clone()}>
+                        <td>{&row.name}</td>
+                        <td>
+                            {match (&row.period_start, &row.period_end) {
+                                (Some(start), Some(end)) => format!("{} – {}", start, end),
+                                _ => "all time".to_string(),
+                            }}
+                        </td>
+                        <td>{format!("{:.1}", row.feed_kg)}</td>
+                        <td>{format!("{:.2}", row.feed_cost)}</td>
+                        <td>{format!("{:.1}", row.weight_gain)}</td>
+                        <td class="fcr">{ratio(row.fcr)}</td>
+                        <td>{ratio(row.cost_per_kg_gain)}</td>
+                    </tr>
+                }) }
+            </tbody>
+        </table>
+    }
+}
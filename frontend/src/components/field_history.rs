@@ -0,0 +1,172 @@
+//! Change history of one field of a goat, derived from the backend's event
+//! log, for settling "who changed the price?" questions.
+
+use crate::components::Spinner;
+use crate::services::use_api;
+use crate::store::UnitsStore;
+use chrono_tz::Tz;
+use log::{error, info};
+use serde_json::Value;
+use shared::GoatParams;
+use shared::events::FieldChange;
+use shared::time::format_local;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::use_store_value;
+
+/// Shows `value` the way the goat forms would.
+pub fn show_value(value: &Value) -> String {
+    match value {
+        Value::Null => "(none)".to_string(),
+        Value::String(s) if s.is_empty() => "(empty)".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// One line for `change`: when it happened, in the farm's time zone
+/// `timezone`, what it changed and, if known, who made it.
+pub fn describe_change(change: &FieldChange, timezone: Tz) -> String {
+    let changed_at = format_local(&change.changed_at, timezone);
+    let line = match &change.from {
+        Some(from) => format!(
+            "{}: {} → {}",
+            changed_at,
+            show_value(from),
+            show_value(&change.to)
+        ),
+        None => format!("{}: recorded as {}", changed_at, show_value(&change.to)),
+    };
+    match &change.actor {
+        Some(actor) => format!("{} by {}", line, actor),
+        None => line,
+    }
+}
+
+/// Props for FieldHistory:
+/// - `goat_id`: goat whose field to show
+/// - `field`: the field, one of `shared::events::HISTORY_FIELDS`
+#[derive(Properties, PartialEq)]
+pub struct FieldHistoryProps {
+    pub goat_id: i64,
+    pub field: AttrValue,
+}
+
+/// FieldHistory component:
+/// Loads the changes to the field and lists them newest first, with the
+/// value before and after each and who made it. Changes made before the
+/// backend logged them may be missing.
+#[function_component(FieldHistory)]
+pub fn field_history(props: &FieldHistoryProps) -> Html {
+    let api = use_api();
+    let timezone = use_store_value::<UnitsStore>().timezone;
+    let changes = use_state(|| None::<Vec<FieldChange>>);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((props.goat_id, props.field.clone()), {
+        let changes = changes.clone();
+        let error = error.clone();
+        move |(goat_id, field): &(i64, AttrValue)| {
+            let (goat_id, field) = (*goat_id, field.clone());
+            changes.set(None);
+            error.set(None);
+            spawn_local(async move {
+                match api.field_history(goat_id, &field).await {
+                    Ok(c) => {
+                        info!(
+                            "Loaded {} changes to {} of goat {}",
+                            c.len(),
+                            field,
+                            goat_id
+                        );
+                        changes.set(Some(c));
+                    }
+                    Err(e) => {
+                        error!("Failed to load history of {}: {}", field, e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+            || {}
+        }
+    });
+
+    html! {
+        <div class="field-history" style="font-size: 12px; margin: 4px 0 8px 16px;">
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading history: {}", err)}</p>
+            } else if let Some(changes) = &*changes {
+                if changes.is_empty() {
+                    <p>{"No changes recorded."}</p>
+                } else {
+                    <ul>
+                        { for changes.iter().map(|c| html! { <li>{describe_change(c, timezone)}</li> }) }
+                    </ul>
+                }
+            } else {
+                <Spinner label="Loading history..." />
+            }
+        </div>
+    }
+}
+
+/// Props for RecordField:
+/// - `goat_id`: goat the field belongs to, if known; without it no history
+///   can be asked for
+/// - `field`: the field's name in `GoatParams`' JSON
+/// - `label`: what to call it
+/// - `value`: its current value, as shown
+#[derive(Properties, PartialEq)]
+pub struct RecordFieldProps {
+    pub goat_id: Option<i64>,
+    pub field: AttrValue,
+    pub label: AttrValue,
+    pub value: String,
+}
+
+/// RecordField component:
+/// One field of a goat with a clock button that opens its `FieldHistory`
+/// below it.
+#[function_component(RecordField)]
+pub fn record_field(props: &RecordFieldProps) -> Html {
+    let open = use_state(|| false);
+    let toggle = {
+        let open = open.clone();
+        Callback::from(move |_: MouseEvent| open.set(!*open))
+    };
+
+    html! {
+        <div class="record-field">
+            <span>{format!("{}: {}", props.label, props.value)}</span>
+            if props.goat_id.is_some() {
+                {" "}
+                <button class="history-toggle" onclick={toggle}
+                        title={format!("Change history of {}", props.label.to_lowercase())}
+                        aria-expanded={open.to_string()}>
+                    {"🕒"}
+                </button>
+            }
+            if let (true, Some(goat_id)) = (*open, props.goat_id) {
+                <FieldHistory {goat_id} field={props.field.clone()} />
+            }
+        </div>
+    }
+}
+
+/// The editable fields of `goat` as (field, label, shown value), in the
+/// order the detail page lists them.
+pub fn record_fields(goat: &GoatParams) -> Vec<(&'static str, &'static str, String)> {
+    let text = |s: &str| show_value(&Value::String(s.to_string()));
+    vec![
+        (
+            "current_price",
+            "Price",
+            format!("{:.2}", goat.current_price),
+        ),
+        ("cost", "Cost", format!("{:.2}", goat.cost)),
+        ("weight", "Weight", format!("{:.1} kg", goat.weight)),
+        ("diet", "Diet", text(&goat.diet)),
+        ("health_status", "Health status", text(&goat.health_status)),
+        ("offspring", "Offspring", goat.offspring.to_string()),
+    ]
+}
@@ -0,0 +1,189 @@
+//! Files the backend serves only to signed-in devices: note photos, voice
+//! notes, reports and exports.
+//!
+//! Browsers send no session header with `<img src>`, `<audio src>` or a
+//! followed `<a href>`, so these components fetch the file through
+//! `ApiClient::download` instead and hand the element a blob URL, revoked
+//! once it is no longer shown.
+
+use crate::services::use_api;
+use log::{error, info};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{Blob, HtmlElement, Url};
+use yew::prelude::*;
+
+/// A blob URL for `bytes`, to be revoked with `Url::revoke_object_url`.
+fn blob_url(bytes: &[u8]) -> Result<String, String> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    Blob::new_with_u8_array_sequence(&parts)
+        .and_then(|blob| Url::create_object_url_with_blob(&blob))
+        .map_err(|e| format!("{:?}", e))
+}
+
+/// Fetches the file at `url` with this device's session and returns a blob
+/// URL for it once loaded. The blob URL is revoked when `url` changes or
+/// the component goes away.
+#[hook]
+pub fn use_file_url(url: String) -> Option<String> {
+    let api = use_api();
+    let object_url = use_state(|| None::<String>);
+    use_effect_with(url, {
+        let object_url = object_url.clone();
+        move |url: &String| {
+            // The blob URL made for `url`, and whether it is still wanted
+            let made = Rc::new(RefCell::new(None::<String>));
+            let wanted = Rc::new(Cell::new(true));
+            {
+                let url = url.clone();
+                let made = made.clone();
+                let wanted = wanted.clone();
+                spawn_local(async move {
+                    let loaded = match api.download(&url).await {
+                        Ok(bytes) => blob_url(&bytes),
+                        Err(e) => Err(e.to_string()),
+                    };
+                    match loaded {
+                        Ok(loaded) if wanted.get() => {
+                            *made.borrow_mut() = Some(loaded.clone());
+                            object_url.set(Some(loaded));
+                        }
+                        Ok(loaded) => {
+                            let _ = Url::revoke_object_url(&loaded);
+                        }
+                        Err(e) => error!("Failed to load {}: {}", url, e),
+                    }
+                });
+            }
+            move || {
+                wanted.set(false);
+                if let Some(made) = made.borrow_mut().take() {
+                    let _ = Url::revoke_object_url(&made);
+                }
+            }
+        }
+    });
+    (*object_url).clone()
+}
+
+/// Props for FileImage:
+/// - `url`: where the backend serves the image
+/// - `alt`, `style`: as on `<img>`
+#[derive(Properties, PartialEq)]
+pub struct FileImageProps {
+    pub url: String,
+    pub alt: AttrValue,
+    #[prop_or_default]
+    pub style: AttrValue,
+}
+
+/// FileImage component:
+/// Shows the image at `url`, linking to it full size, once it has loaded.
+#[function_component(FileImage)]
+pub fn file_image(props: &FileImageProps) -> Html {
+    match use_file_url(props.url.clone()) {
+        Some(src) => html! {
+            <a href={src.clone()} target="_blank" data-url={props.url.clone()}>
+                <img {src} alt={props.alt.clone()} style={props.style.clone()} />
+            </a>
+        },
+        None => html! {},
+    }
+}
+
+/// Props for FileAudio:
+/// - `url`: where the backend serves the recording
+#[derive(Properties, PartialEq)]
+pub struct FileAudioProps {
+    pub url: String,
+}
+
+/// FileAudio component:
+/// Audio controls for the recording at `url`, playable once it has loaded.
+#[function_component(FileAudio)]
+pub fn file_audio(props: &FileAudioProps) -> Html {
+    let src = use_file_url(props.url.clone());
+    html! {
+        <audio controls=true data-url={props.url.clone()} {src} />
+    }
+}
+
+/// Props for FileLink:
+/// - `url`: where the backend serves the file
+/// - `file_name`: what the file is saved as
+/// - `class`, `style`: as on `<a>`
+/// - `children`: the link text
+#[derive(Properties, PartialEq)]
+pub struct FileLinkProps {
+    pub url: String,
+    pub file_name: String,
+    #[prop_or_default]
+    pub class: Classes,
+    #[prop_or_default]
+    pub style: AttrValue,
+    #[prop_or_default]
+    pub children: Html,
+}
+
+/// FileLink component:
+/// A link that downloads the file at `url` as `file_name` when clicked.
+#[function_component(FileLink)]
+pub fn file_link(props: &FileLinkProps) -> Html {
+    let api = use_api();
+    let onclick = {
+        let url = props.url.clone();
+        let file_name = props.file_name.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            let api = api.clone();
+            let url = url.clone();
+            let file_name = file_name.clone();
+            spawn_local(async move {
+                let saved = match api.download(&url).await {
+                    Ok(bytes) => save_file(&bytes, &file_name),
+                    Err(e) => Err(e.to_string()),
+                };
+                match saved {
+                    Ok(()) => info!("Downloaded {} as {}", url, file_name),
+                    Err(e) => error!("Failed to download {}: {}", url, e),
+                }
+            });
+        })
+    };
+    html! {
+        <a href="#" class={props.class.clone()} style={props.style.clone()}
+           data-url={props.url.clone()} download={props.file_name.clone()} {onclick}>
+            {props.children.clone()}
+        </a>
+    }
+}
+
+/// Offers `bytes` to the browser to save as `file_name`.
+fn save_file(bytes: &[u8], file_name: &str) -> Result<(), String> {
+    let url = blob_url(bytes)?;
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or("No document to download into")?;
+    let link: HtmlElement = document
+        .create_element("a")
+        .map_err(|e| format!("{:?}", e))?
+        .unchecked_into();
+    link.set_attribute("href", &url)
+        .and_then(|_| link.set_attribute("download", file_name))
+        .map_err(|e| format!("{:?}", e))?;
+    link.click();
+    // Some browsers start the download only after the click returns
+    let revoke = Closure::once_into_js(move || {
+        let _ = Url::revoke_object_url(&url);
+    });
+    if let Some(window) = web_sys::window() {
+        let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+            revoke.unchecked_ref(),
+            1000,
+        );
+    }
+    Ok(())
+}
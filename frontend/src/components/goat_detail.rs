@@ -0,0 +1,252 @@
+//! Detail panel for a single goat, with its weight history charted against
+//! the breed's reference growth curve, its physical traits, its breeding
+//! status, its record with the change history of each field, its health
+//! timeline, and the notes and voice notes workers left on it.
+
+use crate::components::field_history::record_fields;
+use crate::components::{
+    Can, ChartSeries, GoatNotes, HealthTimeline, LineChart, RecordField, Spinner,
+    TraceabilityRecords, VoiceNotes, WeightLog,
+};
+use crate::services::use_api;
+use crate::store::use_read_only;
+use log::{error, info};
+use shared::attachments::AttachmentTarget;
+use shared::breeding::{Neutering, min_breeding_age_days, neutered_label};
+use shared::growth::{
+    GROWTH_ALERT_PERCENTILE, GrowthBenchmark, GrowthHistory, STANDARD_AGES_DAYS, breed_standard,
+    expected_weight, weight_at_percentile,
+};
+use shared::permissions::{PermissionAction, PermissionModule};
+use shared::physical::describe;
+use shared::{Gender, GoatParams};
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// One line on where `goat` stands for breeding, given its age in days if
+/// known and its neutering if it has one. Bucks have no breeding dates or
+/// lactations, so for them (and for goats too young to breed or neutered)
+/// none are shown.
+pub fn breeding_summary(
+    goat: &GoatParams,
+    age_days: Option<i64>,
+    neutering: Option<&Neutering>,
+) -> String {
+    if let Some(n) = neutering {
+        let label = neutered_label(&goat.gender);
+        return match &n.reason {
+            Some(reason) => format!("{} since {} ({})", label, n.neutered_on, reason),
+            None => format!("{} since {}", label, n.neutered_on),
+        };
+    }
+    let min_age = min_breeding_age_days(&goat.gender);
+    if let Some(age) = age_days
+        && age < min_age
+    {
+        return format!("Too young to breed ({} of {} days)", age, min_age);
+    }
+    match (&goat.gender, &goat.last_bred) {
+        (Gender::Male, _) => "Buck: no breeding dates or lactations are kept".to_string(),
+        (Gender::Female, Some(date)) => format!("Last bred {}", date),
+        (Gender::Female, None) => "Not bred yet".to_string(),
+    }
+}
+
+/// Props for GoatDetail:
+/// - `name`: goat to show
+/// - `goat_id`: its ID, for the change history of its fields
+/// - `goat`: its record, for the breeding status and its fields
+/// - `neutering`: its castration or spaying, if recorded
+/// - `benchmark`: its growth benchmark, if one could be computed
+/// - `on_close`: called when the panel is dismissed
+#[derive(Properties, PartialEq)]
+pub struct GoatDetailProps {
+    pub name: String,
+    #[prop_or_default]
+    pub goat_id: Option<i64>,
+    #[prop_or_default]
+    pub goat: Option<GoatParams>,
+    #[prop_or_default]
+    pub neutering: Option<Neutering>,
+    #[prop_or_default]
+    pub benchmark: Option<GrowthBenchmark>,
+    pub on_close: Callback<()>,
+}
+
+/// GoatDetail component:
+/// Loads the goat's weighings and plots them by age over the breed's
+/// expected growth curve and its alert percentile. Below the title, the
+/// goat's recorded physical traits help identify it, and a line gives its
+/// breeding status for its gender and age. Its price, cost and other
+/// editable fields follow, each with a clock button that lists the changes
+/// made to it. Unless the dashboard is read-only, a `WeightLog` form below
+/// the chart records new weighings, after which the history is reloaded.
+/// Workers who may view health records then see the goat's health
+/// timeline and lab results. The goat's notes thread and voice notes come
+/// last.
+#[function_component(GoatDetail)]
+pub fn goat_detail(props: &GoatDetailProps) -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let history = use_state(|| None::<GrowthHistory>);
+    let error = use_state(|| None::<String>);
+    // Bumped after each logged weighing to reload the history
+    let reloads = use_state(|| 0u32);
+
+    use_effect_with((props.name.clone(), *reloads), {
+        let history = history.clone();
+        let error = error.clone();
+        move |(name, _): &(String, u32)| {
+            let name = name.clone();
+            history.set(None);
+            error.set(None);
+            spawn_local(async move {
+                match api.weight_history(&name).await {
+                    Ok(h) => {
+                        info!("Loaded {} weighings for {}", h.records.len(), name);
+                        history.set(Some(h));
+                    }
+                    Err(e) => {
+                        error!("Failed to load weight history for {}: {}", name, e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+            || {}
+        }
+    });
+
+    let on_close = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_| on_close.emit(()))
+    };
+    let on_saved = {
+        let reloads = reloads.clone();
+        Callback::from(move |_| reloads.set(*reloads + 1))
+    };
+
+    html! {
+        <div style="border: 1px solid #ccc; padding: 16px; margin-bottom: 24px;">
+            <h3>
+                {format!("{} — growth", props.name)}
+                { " " }<button onclick={on_close}>{"Close"}</button>
+            </h3>
+            if let Some(looks) = props.goat.as_ref().map(describe).filter(|d| !d.is_empty()) {
+                <p class="goat-traits">{looks}</p>
+            }
+            if let Some(goat) = &props.goat {
+                <p class="breeding-status">
+                    {breeding_summary(
+                        goat,
+                        props.benchmark.as_ref().map(|b| b.age_days),
+                        props.neutering.as_ref(),
+                    )}
+                </p>
+                <div class="goat-record">
+                    { for record_fields(goat).into_iter().map(|(field, label, value)| html! {
+                        <RecordField goat_id={props.goat_id} {field} {label} {value} />
+                    }) }
+                </div>
+            }
+            if let Some(b) = &props.benchmark {
+                <p>
+                    {format!(
+                        "{:.1} kg at {} days; breed standard {:.1} kg (percentile {:.0})",
+                        b.weight, b.age_days, b.expected_weight, b.percentile
+                    )}
+                </p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading weight history: {}", err)}</p>
+            } else if let Some(h) = &*history {
+                { growth_chart(h) }
+            } else {
+                <Spinner label="Loading weight history..." />
+            }
+            if !read_only {
+                <WeightLog goat_name={props.name.clone()} {on_saved} />
+            }
+            <Can module={PermissionModule::Health} action={PermissionAction::View}>
+                <HealthTimeline goat_name={props.name.clone()} />
+            </Can>
+            <Can module={PermissionModule::Finance} action={PermissionAction::View}>
+                <TraceabilityRecords goat_name={props.name.clone()} />
+            </Can>
+            <GoatNotes goat_name={props.name.clone()} />
+            <VoiceNotes target={AttachmentTarget::Goat(props.name.clone())} />
+        </div>
+    }
+}
+
+/// Renders the weight history as a line chart with the breed overlay.
+/// Weighings estimated from photos are drawn as a separate dashed series.
+fn growth_chart(history: &GrowthHistory) -> Html {
+    let (estimates, weighings): (Vec<_>, Vec<_>) = history
+        .records
+        .iter()
+        .filter_map(|r| Some((r.estimated, (r.age_days? as f64, r.weight))))
+        .partition(|(estimated, _)| *estimated);
+    let points: Vec<(f64, f64)> = weighings.into_iter().map(|(_, p)| p).collect();
+    let estimates: Vec<(f64, f64)> = estimates.into_iter().map(|(_, p)| p).collect();
+    if points.is_empty() && estimates.is_empty() {
+        return html! {
+            <p>{"No dated weighings yet. Record weights and a date of birth to see the growth chart."}</p>
+        };
+    }
+
+    let max_age = points
+        .iter()
+        .chain(&estimates)
+        .map(|(age, _)| *age as i64)
+        .max()
+        .unwrap_or(0)
+        .max(STANDARD_AGES_DAYS[4]);
+    let overlay = |percentile: Option<f64>| -> Vec<(f64, f64)> {
+        let breed = &history.breed;
+        (0..=max_age)
+            .step_by(15)
+            .chain(std::iter::once(max_age))
+            .filter_map(|age| {
+                let weight = match percentile {
+                    Some(p) => weight_at_percentile(breed, age, p),
+                    None => expected_weight(breed, age),
+                }?;
+                Some((age as f64, weight))
+            })
+            .collect()
+    };
+    let expected = overlay(None);
+    let alert = overlay(Some(GROWTH_ALERT_PERCENTILE));
+
+    let mut series = vec![ChartSeries {
+        markers: true,
+        ..ChartSeries::line("weighings", "#2a7", points)
+    }];
+    if !estimates.is_empty() {
+        series.push(ChartSeries {
+            markers: true,
+            dashed: true,
+            ..ChartSeries::line("estimated from photo", "#e90", estimates)
+        });
+    }
+    if !expected.is_empty() {
+        series.push(ChartSeries::line("breed standard", "#888", expected));
+        series.push(ChartSeries {
+            dashed: true,
+            ..ChartSeries::line(
+                format!("{:.0}th percentile", GROWTH_ALERT_PERCENTILE),
+                "#d33",
+                alert,
+            )
+        });
+    }
+
+    html! {
+        <div>
+            <LineChart {series} x_unit="days" y_unit="kg" label="Weight by age against breed standard" />
+            if breed_standard(&history.breed).is_none() {
+                <p style="font-size: 12px;">{"No breed standard for this breed."}</p>
+            }
+        </div>
+    }
+}
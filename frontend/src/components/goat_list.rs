@@ -4,101 +4,598 @@
 //! It triggers fetching on mount and provides a Refresh button,
 //! leveraging application store state for consistency.
 
-use crate::store::GoatStore;
-use log::info;
+use crate::components::{
+    Column, DataTable, EmptyAction, EmptyState, GoatDetail, RowId, RowStyle, SortKey, Spinner,
+};
+use crate::services::use_api;
+use crate::store::{
+    GoatGroup, GoatGrouping, GoatStore, farm_timezone, group_goats, herd_totals, use_read_only,
+};
+use log::{info, warn};
+use shared::breeding::Neutering;
+use shared::growth::GrowthBenchmark;
+use shared::import::MAX_QUICK_NOTE_LEN;
+use shared::physical::{CoatColor, HornStatus, TraitFilter};
+use shared::time::today_in;
+use shared::vaccination::{VaccinationStatus, vaccination_summary};
+use shared::{Goat, GoatUpdate};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 use yewdux::prelude::use_store;
 
+/// Chip colors for a vaccination status: background, then text.
+fn status_colors(status: VaccinationStatus) -> (&'static str, &'static str) {
+    match status {
+        VaccinationStatus::UpToDate => ("#e8f4ea", "#1b5e20"),
+        VaccinationStatus::Unknown => ("#eeeeee", "#555"),
+        VaccinationStatus::DueSoon => ("#fff4e0", "#8a5300"),
+        VaccinationStatus::Overdue => ("#fdecea", "#b00"),
+    }
+}
+
+/// A bold row under `columns` showing `values` in the columns with those
+/// keys and `label` in the first column.
+fn summary_row(
+    class: &'static str,
+    label: &str,
+    columns: &[Column<Goat>],
+    values: &[(&str, String)],
+    style: &'static str,
+) -> Html {
+    html! {
+        <tr {class} style={format!("font-weight: bold; background: #fafafa; {}", style)}>
+            { for columns.iter().enumerate().map(|(at, column)| {
+                let value = values
+                    .iter()
+                    .find(|(key, _)| *key == column.key)
+                    .map(|(_, value)| value.clone())
+                    .unwrap_or_else(|| if at == 0 { label.to_string() } else { String::new() });
+                html! { <td>{value}</td> }
+            }) }
+        </tr>
+    }
+}
+
 /// GoatList component:
-/// Shows all goats fetched from backend in a compact HTML table.
-/// Shows loading text and error messages based on global store state.
+/// Shows all goats fetched from backend in a compact `DataTable`, sortable
+/// by any value column and navigable from the keyboard.
+/// Shows skeleton rows and error messages based on global store state.
 ///
 /// Features:
-/// - Fetch goats list asynchronously on mount.
-/// - Refresh button to re-fetch the goats list.
+/// - Shows cached goats on mount, revalidating them in the background when stale.
+/// - Refresh button to re-fetch the goats list, bypassing the cache.
 /// - Informative logging.
+/// - Skeleton rows on first load; a spinner while refreshing or adding,
+///   and dimmed rows for goats with a pending delete.
 /// - Shows error messages in UI if fetch fails.
+/// - Badges goats growing below their breed standard; clicking a name, or
+///   pressing Enter on the selected row, opens its growth detail.
+/// - Rows have `goat-{id}` element IDs, so quick search hits can jump to them.
+/// - An empty herd shows an `EmptyState` linking to the add and import
+///   forms instead of an empty table.
+/// - Horn status, coat color and genetic tag dropdowns narrow the table to
+///   matching goats; each goat's tags are shown beside its name.
+/// - Vaccinations are summarised as a status chip against the breed's
+///   schedule, with the vaccine-by-vaccine detail in its tooltip.
+/// - A "Group by" menu splits the table by pen, breed or health status into
+///   collapsible groups, each with its goat count and a subtotal row of
+///   total weight and value.
+/// - A footer row stuck to the bottom of the table totals the goats shown,
+///   following the filters: their count, cost and value, and their average
+///   weight.
+/// - Each goat's quick note (e.g. "check tomorrow") is edited in place and
+///   saved as a patch when the box loses focus or Enter is pressed; clearing
+///   the box removes the note. Locked in read-only mode.
 #[function_component(GoatList)]
 pub fn goat_list() -> Html {
     let (state, dispatch) = use_store::<GoatStore>();
+    let api = use_api();
+    let read_only = use_read_only();
+    let benchmarks = use_state(HashMap::<String, GrowthBenchmark>::new);
+    let neuterings = use_state(HashMap::<String, Neutering>::new);
+    let selected = use_state(|| None::<String>);
+    let filter = use_state(TraitFilter::default);
+    let genetic_tags = use_state(HashMap::<String, Vec<String>>::new);
+    let tag_filter = use_state(String::new);
+    let group_by = use_state(|| None::<GoatGrouping>);
+    let collapsed = use_state(HashSet::<String>::new);
+    let pens = use_state(HashMap::<String, String>::new);
+    let note_error = use_state(|| None::<String>);
 
-    // Fetch goats once on mount, using empty dependencies ()
+    // Serve cached goats on mount, revalidating in the background if stale
     use_effect_with(
         (), // empty tuple dependencies means run once
         {
+            let api = api.clone();
             let dispatch = dispatch.clone();
             move |_| {
-                GoatStore::fetch_goats(dispatch);
+                GoatStore::fetch_goats(api, dispatch);
                 || {}
             }
         },
     );
 
-    // Callback for Refresh button to fetch goats again
+    // Load growth benchmarks for the below-standard badges
+    use_effect_with((), {
+        let api = api.clone();
+        let benchmarks = benchmarks.clone();
+        move |_| {
+            spawn_local(async move {
+                match api.growth_benchmarks().await {
+                    Ok(list) => {
+                        benchmarks.set(list.into_iter().map(|b| (b.goat_name.clone(), b)).collect())
+                    }
+                    Err(e) => warn!("Could not load growth benchmarks: {}", e),
+                }
+            });
+            || {}
+        }
+    });
+
+    // Load neuterings so the detail panel can tell wethers from bucks
+    use_effect_with((), {
+        let api = api.clone();
+        let neuterings = neuterings.clone();
+        move |_| {
+            spawn_local(async move {
+                match api.neuterings().await {
+                    Ok(list) => {
+                        neuterings.set(list.into_iter().map(|n| (n.goat_name.clone(), n)).collect())
+                    }
+                    Err(e) => warn!("Could not load neuterings: {}", e),
+                }
+            });
+            || {}
+        }
+    });
+
+    // Load genetic tags for the badges and the tag filter
+    use_effect_with((), {
+        let api = api.clone();
+        let genetic_tags = genetic_tags.clone();
+        move |_| {
+            spawn_local(async move {
+                match api.genetic_tags().await {
+                    Ok(list) => {
+                        genetic_tags.set(list.into_iter().map(|t| (t.goat_name, t.tags)).collect())
+                    }
+                    Err(e) => warn!("Could not load genetic tags: {}", e),
+                }
+            });
+            || {}
+        }
+    });
+
+    // Load pen assignments for grouping by pen
+    use_effect_with((), {
+        let api = api.clone();
+        let pens = pens.clone();
+        move |_| {
+            spawn_local(async move {
+                match api.spaces().await {
+                    Ok(spaces) => pens.set(
+                        spaces
+                            .into_iter()
+                            .flat_map(|s| {
+                                let pen = s.name;
+                                s.goat_names.into_iter().map(move |g| (g, pen.clone()))
+                            })
+                            .collect(),
+                    ),
+                    Err(e) => warn!("Could not load pens: {}", e),
+                }
+            });
+            || {}
+        }
+    });
+
+    // Callback for Refresh button to bypass the cache
     let refresh = {
+        let api = api.clone();
+        let dispatch = dispatch.clone();
         Callback::from(move |_| {
-            GoatStore::fetch_goats(dispatch.clone());
+            GoatStore::force_refresh(api.clone(), dispatch.clone());
+        })
+    };
+
+    // Trait filter dropdowns; "" clears the criterion
+    let on_horns_filter = {
+        let filter = filter.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                filter.set(TraitFilter {
+                    horns: HornStatus::from_str(&select.value()).ok(),
+                    ..(*filter).clone()
+                });
+            }
         })
     };
+    let on_coat_filter = {
+        let filter = filter.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                filter.set(TraitFilter {
+                    coat_color: CoatColor::from_str(&select.value()).ok(),
+                    ..(*filter).clone()
+                });
+            }
+        })
+    };
+    let on_tag_filter = {
+        let tag_filter = tag_filter.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                tag_filter.set(select.value());
+            }
+        })
+    };
+    let on_group_by = {
+        let group_by = group_by.clone();
+        let collapsed = collapsed.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                let value = select.value();
+                group_by.set(GoatGrouping::ALL.into_iter().find(|g| g.label() == value));
+                collapsed.set(HashSet::new());
+            }
+        })
+    };
+    let mut known_tags: Vec<&String> = genetic_tags.values().flatten().collect();
+    known_tags.sort_by_key(|t| t.to_lowercase());
+    known_tags.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    let has_tag = |name: &str| {
+        tag_filter.is_empty()
+            || genetic_tags
+                .get(name)
+                .is_some_and(|tags| tags.iter().any(|t| t.eq_ignore_ascii_case(&tag_filter)))
+    };
+    let shown: Vec<_> = state
+        .goats
+        .iter()
+        .filter(|g| filter.matches(g) && has_tag(&g.name))
+        .collect();
+
+    let loading = &state.loading;
+    let empty = shown.is_empty() && !loading.fetching && !loading.adding && state.error.is_none();
+    // Skeleton rows while the first load is in flight, plus a placeholder
+    // row for a goat that is still being saved
+    let loading_rows = if loading.fetching && state.goats.is_empty() {
+        5
+    } else {
+        0
+    } + usize::from(loading.adding);
+
+    let open = {
+        let selected = selected.clone();
+        Callback::from(move |goat: Goat| selected.set(Some(goat.name.clone())))
+    };
+    let name_column = {
+        let open = open.clone();
+        let benchmarks = benchmarks.clone();
+        let genetic_tags = genetic_tags.clone();
+        Column::new("name", "Name", move |goat: &Goat| {
+            let onclick = {
+                let open = open.clone();
+                let goat = goat.clone();
+                Callback::from(move |e: MouseEvent| {
+                    e.prevent_default();
+                    open.emit(goat.clone());
+                })
+            };
+            let badge = benchmarks
+                .get(&goat.name)
+                .filter(|b| b.below_expected)
+                .map(|b| html! {
+                    <span class="growth-badge" title="Growing below breed standard"
+                          style="margin-left: 6px; padding: 0 6px; border-radius: 8px; background: #fdecea; color: #b00; font-size: 11px;">
+                        {format!("P{:.0}", b.percentile)}
+                    </span>
+                });
+            html! {
+                <>
+                    <a href="#" {onclick}>{&goat.name}</a>
+                    { for badge }
+                    { for genetic_tags.get(&goat.name).into_iter().flatten().map(|tag| html! {
+                        <span class="genetic-tag"
+                              style="margin-left: 6px; padding: 0 6px; border-radius: 8px; background: #e8f4ea; font-size: 11px;">
+                            {tag}
+                        </span>
+                    }) }
+                </>
+            }
+        })
+        .sort_by(|goat| SortKey::Text(goat.name.clone()))
+    };
+    let quick_note_column = {
+        let note_error = note_error.clone();
+        Column::new("quick_note", "Quick Note", move |goat: &Goat| {
+            let onchange = {
+                let api = api.clone();
+                let dispatch = dispatch.clone();
+                let note_error = note_error.clone();
+                let goat = goat.clone();
+                Callback::from(move |e: Event| {
+                    let input: HtmlInputElement = e.target_unchecked_into();
+                    let note = input.value().trim().to_string();
+                    let note = (!note.is_empty()).then_some(note);
+                    if note == goat.quick_note {
+                        return;
+                    }
+                    let update = GoatUpdate {
+                        quick_note: Some(note),
+                        ..GoatUpdate::default()
+                    };
+                    let note_error = note_error.clone();
+                    let name = goat.name.clone();
+                    GoatStore::patch_goat_async(
+                        api.clone(),
+                        dispatch.clone(),
+                        goat.id,
+                        update,
+                        Callback::from(move |res: Result<Goat, _>| match res {
+                            Ok(_) => note_error.set(None),
+                            Err(e) => note_error
+                                .set(Some(format!("Could not save the note for {}: {}", name, e))),
+                        }),
+                    );
+                })
+            };
+            // Typing in the box must not move the table's row selection
+            let onkeydown = Callback::from(|e: KeyboardEvent| e.stop_propagation());
+            html! {
+                <input class="quick-note" placeholder="Add note"
+                       value={goat.quick_note.clone().unwrap_or_default()}
+                       maxlength={MAX_QUICK_NOTE_LEN.to_string()}
+                       disabled={read_only} {onchange} {onkeydown} />
+            }
+        })
+        .sort_by(|goat| SortKey::Text(goat.quick_note.clone().unwrap_or_default()))
+    };
+    let today = today_in(farm_timezone());
+    let columns = vec![
+        name_column,
+        Column::text("breed", "Breed", |goat: &Goat| format!("{:?}", goat.breed)),
+        Column::text("gender", "Gender", |goat: &Goat| {
+            format!("{:?}", goat.gender)
+        }),
+        Column::new(
+            "offspring",
+            "Offspring",
+            |goat: &Goat| html! { {goat.offspring} },
+        )
+        .sort_by(|goat| SortKey::Number(goat.offspring as f64)),
+        Column::number("cost", "Cost", |goat: &Goat| goat.cost),
+        Column::number("weight", "Weight", |goat: &Goat| goat.weight),
+        Column::number("current_price", "Current Price", |goat: &Goat| {
+            goat.current_price
+        }),
+        Column::text("diet", "Diet", |goat: &Goat| goat.diet.clone()),
+        Column::text("last_bred", "Last Bred", |goat: &Goat| {
+            goat.last_bred.clone().unwrap_or_else(|| "-".to_string())
+        }),
+        Column::text("health_status", "Health Status", |goat: &Goat| {
+            goat.health_status.clone()
+        }),
+        Column::new("vaccinations", "Vaccinations", move |goat: &Goat| {
+            let summary = vaccination_summary(goat, today);
+            let (background, color) = status_colors(summary.status);
+            html! {
+                <span class="vaccination-status" title={summary.details()}
+                      style={format!("padding: 0 6px; border-radius: 8px; background: {}; color: {}; font-size: 11px;", background, color)}>
+                    {summary.status.label()}
+                </span>
+            }
+        })
+        .sort_by(move |goat| {
+            SortKey::Number(vaccination_summary(goat, today).status as u8 as f64)
+        }),
+        Column::new("diseases", "Diseases", |goat: &Goat| {
+            html! { {format!("{:?}", goat.diseases)} }
+        }),
+        quick_note_column,
+    ];
+    let row_style: RowStyle<Goat> = {
+        let deleting: Vec<String> = shown
+            .iter()
+            .filter(|g| loading.is_deleting(&g.name))
+            .map(|g| g.name.clone())
+            .collect();
+        Rc::new(move |goat: &Goat| {
+            if deleting.contains(&goat.name) {
+                "opacity: 0.4;"
+            } else {
+                ""
+            }
+        })
+    };
+    let shown_count = shown.len();
+    let row_id: RowId<Goat> = Rc::new(|goat: &Goat| format!("goat-{}", goat.id));
+    let rows: Vec<Goat> = shown.into_iter().cloned().collect();
+    let groups = group_by.map(|grouping| group_goats(&rows, grouping, &pens));
+    let totals = herd_totals(&rows);
+    let totals_label = format!(
+        "Total: {} {}",
+        totals.count,
+        if totals.count == 1 { "goat" } else { "goats" }
+    );
+    let totals_values = [
+        ("cost", format!("{:.2}", totals.total_cost)),
+        ("current_price", format!("{:.2}", totals.total_value)),
+        (
+            "weight",
+            totals
+                .average_weight
+                .map_or("-".to_string(), |w| format!("avg {:.2}", w)),
+        ),
+    ];
+    let totals_row = summary_row(
+        "herd-totals",
+        &totals_label,
+        &columns,
+        &totals_values,
+        "position: sticky; bottom: 0;",
+    );
+    let group_view = |group: &GoatGroup| {
+        let is_collapsed = collapsed.contains(&group.label);
+        let toggle = {
+            let collapsed = collapsed.clone();
+            let label = group.label.clone();
+            Callback::from(move |_: MouseEvent| {
+                let mut next = (*collapsed).clone();
+                if !next.remove(&label) {
+                    next.insert(label.clone());
+                }
+                collapsed.set(next);
+            })
+        };
+        let count = group.goats.len();
+        html! {
+            <div class="goat-group" key={group.label.clone()} style="margin-bottom: 8px;">
+                <button class="goat-group-toggle" onclick={toggle}
+                        aria-expanded={(!is_collapsed).to_string()}>
+                    {format!(
+                        "{} {} ({} {})",
+                        if is_collapsed { "▸" } else { "▾" },
+                        group.label,
+                        count,
+                        if count == 1 { "goat" } else { "goats" }
+                    )}
+                </button>
+                if !is_collapsed {
+                    <DataTable<Goat>
+                        rows={group.goats.clone()}
+                        columns={columns.clone()}
+                        row_id={row_id.clone()}
+                        row_style={Some(row_style.clone())}
+                        on_activate={Some(open.clone())}
+                        footer={summary_row(
+                            "group-subtotal",
+                            "Subtotal",
+                            &columns,
+                            &[
+                                ("weight", format!("{:.2}", group.total_weight)),
+                                ("current_price", format!("{:.2}", group.total_value)),
+                            ],
+                            "",
+                        )}
+                    />
+                }
+            </div>
+        }
+    };
 
     // Render UI based on current loading/error state from store
     html! {
         <div style="margin-bottom: 24px;">
             <h2>{"All Goats"}</h2>
 
-            // Show loading indicator
-            if state.loading {
-                <p>{"Loading goats..."}</p>
-            }
             // Show error message if any
-            else if let Some(err_msg) = &state.error {
+            if let Some(err_msg) = &state.error {
                 <p style="color: red;">{format!("Error loading goats: {}", err_msg)}</p>
             }
-            // Otherwise, render the goats table with refresh option
-            else {
-                <>
-                    <button onclick={refresh} style="margin-bottom: 10px;">{"Refresh"}</button>
-                    <div style="overflow-x: auto;">
-                        <table style="border-collapse: collapse; width: 100%;">
-                            <thead>
-                                <tr>
-                                    <th>{"Name"}</th>
-                                    <th>{"Breed"}</th>
-                                    <th>{"Gender"}</th>
-                                    <th>{"Offspring"}</th>
-                                    <th>{"Cost"}</th>
-                                    <th>{"Weight"}</th>
-                                    <th>{"Current Price"}</th>
-                                    <th>{"Diet"}</th>
-                                    <th>{"Last Bred"}</th>
-                                    <th>{"Health Status"}</th>
-                                    <th>{"Vaccinations"}</th>
-                                    <th>{"Diseases"}</th>
-                                </tr>
-                            </thead>
-                            <tbody>
-                                {
-                                    for state.goats.iter().map(|goat| html! {
-                                        <tr>
-                                            <td>{&goat.name}</td>
-                                            <td>{format!("{:?}", goat.breed)}</td>
-                                            <td>{format!("{:?}", goat.gender)}</td>
-                                            <td>{goat.offspring}</td>
-                                            <td>{format!("{:.2}", goat.cost)}</td>
-                                            <td>{format!("{:.2}", goat.weight)}</td>
-                                            <td>{format!("{:.2}", goat.current_price)}</td>
-                                            <td>{&goat.diet}</td>
-                                            <td>{goat.last_bred.as_deref().unwrap_or("-")}</td>
-                                            <td>{&goat.health_status}</td>
-                                            <td>{format!("{:?}", goat.vaccinations)}</td>
-                                            <td>{format!("{:?}", goat.diseases)}</td>
-                                        </tr>
-                                    })
-                                }
-                            </tbody>
-                        </table>
-                    </div>
-                </>
+            if let Some(err_msg) = &*note_error {
+                <p class="quick-note-error" style="color: red;">{err_msg}</p>
+            }
+
+            <button onclick={refresh} disabled={loading.fetching} style="margin-bottom: 10px;">{"Refresh"}</button>
+            if loading.fetching && !state.goats.is_empty() {
+                { " " }<Spinner label="Loading goats..." />
+            }
+            <p class="trait-filter" style="font-size: 12px;">
+                <label>{"Horns: "}
+                    <select name="horns_filter" onchange={on_horns_filter}>
+                        <option value="" selected={filter.horns.is_none()}>{"Any"}</option>
+                        { for HornStatus::ALL.iter().map(|h| html! {
+                            <option value={HornStatus::to_str(h).to_string()} selected={filter.horns == Some(*h)}>
+                                {h.label()}
+                            </option>
+                        }) }
+                    </select>
+                </label>
+                {" "}
+                <label>{"Coat: "}
+                    <select name="coat_filter" onchange={on_coat_filter}>
+                        <option value="" selected={filter.coat_color.is_none()}>{"Any"}</option>
+                        { for CoatColor::ALL.iter().map(|c| html! {
+                            <option value={CoatColor::to_str(c).to_string()} selected={filter.coat_color == Some(*c)}>
+                                {c.label()}
+                            </option>
+                        }) }
+                    </select>
+                </label>
+                {" "}
+                <label>{"Tag: "}
+                    <select name="tag_filter" onchange={on_tag_filter}>
+                        <option value="" selected={tag_filter.is_empty()}>{"Any"}</option>
+                        { for known_tags.iter().map(|t| html! {
+                            <option value={t.to_string()} selected={t.eq_ignore_ascii_case(&tag_filter)}>
+                                {t}
+                            </option>
+                        }) }
+                    </select>
+                </label>
+                {" "}
+                <label>{"Group by: "}
+                    <select name="group_by" onchange={on_group_by}>
+                        <option value="" selected={group_by.is_none()}>{"None"}</option>
+                        { for GoatGrouping::ALL.iter().map(|g| html! {
+                            <option value={g.label()} selected={*group_by == Some(*g)}>{g.label()}</option>
+                        }) }
+                    </select>
+                </label>
+                if !filter.is_empty() || !tag_filter.is_empty() {
+                    {format!(" Showing {} of {} goats", shown_count, state.goats.len())}
+                }
+            </p>
+            if empty && state.goats.is_empty() {
+                <EmptyState
+                    title="No goats yet"
+                    message="Your herd will be listed here, with growth badges and tags."
+                    sample="Rani · Sirohi doe · 32 kg · healthy"
+                    actions={vec![
+                        EmptyAction::new("Add your first goat", "#add-goat"),
+                        EmptyAction::new("Import CSV", "#import-goats"),
+                    ]}
+                />
+            } else if empty {
+                <EmptyState
+                    title="No goats match these filters"
+                    message="Set a filter back to \"Any\" to see more of the herd."
+                />
+            } else if let Some(groups) = &groups {
+                { for groups.iter().map(group_view) }
+                <p class="herd-totals" style="position: sticky; bottom: 0; font-weight: bold; background: #fafafa; margin: 0; padding: 4px;">
+                    {format!(
+                        "{} · cost {} · value {} · weight {}",
+                        totals_label, totals_values[0].1, totals_values[1].1, totals_values[2].1
+                    )}
+                </p>
+            } else {
+                <DataTable<Goat>
+                    {rows}
+                    {columns}
+                    {row_id}
+                    row_style={Some(row_style)}
+                    {loading_rows}
+                    on_activate={Some(open)}
+                    footer={totals_row}
+                />
+            }
+            if let Some(name) = &*selected {
+                <GoatDetail
+                    name={name.clone()}
+                    goat_id={state.goats.iter().find(|g| &g.name == name).map(|g| g.id)}
+                    goat={state.goats.iter().find(|g| &g.name == name).map(|g| g.params.clone())}
+                    neutering={neuterings.get(name).cloned()}
+                    benchmark={benchmarks.get(name).cloned()}
+                    on_close={
+                        let selected = selected.clone();
+                        Callback::from(move |_| selected.set(None))
+                    }
+                />
             }
         </div>
     }
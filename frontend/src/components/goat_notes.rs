@@ -0,0 +1,256 @@
+//! Notes thread on a goat: observations left by workers, oldest first,
+//! each with its author, time and optional photo (see `shared::notes`).
+
+use crate::components::{FileImage, Spinner};
+use crate::components::weight_log::read_photo;
+use crate::services::api::note_photo_url;
+use crate::services::use_api;
+use crate::store::{UnitsStore, use_read_only};
+use log::{error, info, warn};
+use shared::notes::{GoatNote, NoteInput};
+use shared::time::format_local;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlTextAreaElement};
+use yew::prelude::*;
+use yewdux::prelude::use_store_value;
+
+/// localStorage key the last author is kept under, so workers need not
+/// type their name for every note.
+const AUTHOR_KEY: &str = "yagi.notes.author";
+
+/// Name of whoever last signed a note on this device, or empty.
+pub fn stored_author() -> String {
+    web_sys::window()
+        .and_then(|w| w.local_storage().ok().flatten())
+        .and_then(|s| s.get_item(AUTHOR_KEY).ok().flatten())
+        .unwrap_or_default()
+}
+
+/// Keeps `author` as the name to sign notes with on this device.
+pub fn remember_author(author: &str) {
+    if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
+        let _ = storage.set_item(AUTHOR_KEY, author);
+    }
+}
+
+/// Props for GoatNotes:
+/// - `goat_name`: goat whose thread is shown
+#[derive(Properties, PartialEq)]
+pub struct GoatNotesProps {
+    pub goat_name: String,
+}
+
+/// GoatNotes component:
+/// Loads the goat's notes and lists them as a thread with their author and
+/// local time, showing attached photos as thumbnails linking to the full
+/// image. Unless the dashboard is read-only, a form below adds a note with
+/// an optional photo, taken with the camera on phones, and each note can be
+/// deleted.
+#[function_component(GoatNotes)]
+pub fn goat_notes(props: &GoatNotesProps) -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let timezone = use_store_value::<UnitsStore>().timezone;
+    let notes = use_state(|| None::<Vec<GoatNote>>);
+    let author = use_state(stored_author);
+    let text = use_state(String::new);
+    let photo_ref = use_node_ref();
+    let busy = use_state(|| false);
+    let error = use_state(|| None::<String>);
+    // Bumped after each change to reload the thread
+    let reloads = use_state(|| 0u32);
+
+    use_effect_with((props.goat_name.clone(), *reloads), {
+        let api = api.clone();
+        let notes = notes.clone();
+        let error = error.clone();
+        move |(name, _): &(String, u32)| {
+            let name = name.clone();
+            spawn_local(async move {
+                match api.goat_notes(&name).await {
+                    Ok(loaded) => {
+                        info!("Loaded {} notes for {}", loaded.len(), name);
+                        notes.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load notes for {}: {}", name, e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+            || {}
+        }
+    });
+
+    let on_author = {
+        let author = author.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                author.set(input.value());
+            }
+        })
+    };
+    let on_text = {
+        let text = text.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(area) = e.target_dyn_into::<HtmlTextAreaElement>() {
+                text.set(area.value());
+            }
+        })
+    };
+
+    let on_add = {
+        let api = api.clone();
+        let goat_name = props.goat_name.clone();
+        let author = author.clone();
+        let text = text.clone();
+        let photo_ref = photo_ref.clone();
+        let busy = busy.clone();
+        let error = error.clone();
+        let reloads = reloads.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let note = NoteInput {
+                goat_name: goat_name.clone(),
+                author: author.trim().to_string(),
+                text: text.trim().to_string(),
+            };
+            if let Err(problem) = note.validate() {
+                error.set(Some(problem));
+                return;
+            }
+            let photo = photo_ref
+                .cast::<HtmlInputElement>()
+                .filter(|input| input.files().is_some_and(|files| files.length() > 0));
+            let api = api.clone();
+            let text = text.clone();
+            let busy = busy.clone();
+            let error = error.clone();
+            let reloads = reloads.clone();
+            busy.set(true);
+            error.set(None);
+            remember_author(&note.author);
+            spawn_local(async move {
+                match api.add_note(&note).await {
+                    Ok(stored) => {
+                        info!("Added note {} on {}", stored.id, stored.goat_name);
+                        text.set(String::new());
+                        if let Some(input) = photo {
+                            let attached = match read_photo(&input).await {
+                                Ok((bytes, content_type)) => api
+                                    .attach_note_photo(stored.id, &bytes, &content_type)
+                                    .await
+                                    .map_err(|e| e.to_string()),
+                                Err(e) => Err(e),
+                            };
+                            match attached {
+                                Ok(_) => input.set_value(""),
+                                Err(e) => {
+                                    warn!("Note {} saved without its photo: {}", stored.id, e);
+                                    error.set(Some(format!(
+                                        "The note was saved, but not its photo: {}",
+                                        e
+                                    )));
+                                }
+                            }
+                        }
+                        reloads.set(*reloads + 1);
+                    }
+                    Err(e) => {
+                        error!("Failed to add note on {}: {}", note.goat_name, e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let on_delete = {
+        let error = error.clone();
+        let reloads = reloads.clone();
+        Callback::from(move |id: i64| {
+            let api = api.clone();
+            let error = error.clone();
+            let reloads = reloads.clone();
+            spawn_local(async move {
+                match api.delete_note(id).await {
+                    Ok(()) => {
+                        info!("Deleted note {}", id);
+                        reloads.set(*reloads + 1);
+                    }
+                    Err(e) => {
+                        error!("Failed to delete note {}: {}", id, e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="goat-notes">
+            <h4>{"Notes"}</h4>
+            {
+                match &*notes {
+                    None => html! { <Spinner label="Loading notes..." /> },
+                    Some(list) if list.is_empty() => html! {
+                        <p>{"No notes yet."}</p>
+                    },
+                    Some(list) => html! {
+                        <ul style="list-style: none; padding: 0;">
+                            { for list.iter().map(|note| html! {
+                                <li class="goat-note" data-note={note.id.to_string()}
+                                    style="border-left: 3px solid #c8e6c9; padding: 4px 8px; margin-bottom: 8px;">
+                                    <div style="color: #666; font-size: 12px;">
+                                        <strong class="note-author">{&note.author}</strong>
+                                        {format!(" · {}", format_local(&note.created_at, timezone))}
+                                        if !read_only {
+                                            { " " }
+                                            <button type="button" name="delete_note"
+                                                    onclick={on_delete.reform({
+                                                        let id = note.id;
+                                                        move |_: MouseEvent| id
+                                                    })}>
+                                                {"Delete"}
+                                            </button>
+                                        }
+                                    </div>
+                                    <p class="note-text" style="white-space: pre-wrap; margin: 4px 0;">
+                                        {&note.text}
+                                    </p>
+                                    if note.has_photo {
+                                        <FileImage url={note_photo_url(note.id)}
+                                                   alt="Photo attached to the note"
+                                                   style="max-width: 160px; max-height: 120px;" />
+                                    }
+                                </li>
+                            }) }
+                        </ul>
+                    },
+                }
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{err}</p>
+            }
+            if !read_only {
+                <form class="add-note" onsubmit={on_add}>
+                    <input type="text" name="note_author" placeholder="Your name" size="12"
+                           value={(*author).clone()} oninput={on_author} />
+                    <br />
+                    <textarea name="note_text" rows="3" cols="40"
+                              placeholder="What did you see? e.g. limping on left hind leg, @Ravi please check"
+                              value={(*text).clone()} oninput={on_text} />
+                    <br />
+                    <label>
+                        {"Photo (optional): "}
+                        <input type="file" name="note_photo" accept="image/*"
+                               capture="environment" ref={photo_ref} />
+                    </label>
+                    { " " }
+                    <button type="submit" disabled={*busy}>{"Add note"}</button>
+                </form>
+            }
+        </div>
+    }
+}
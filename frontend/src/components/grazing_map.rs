@@ -0,0 +1,287 @@
+//! Grazing map: last known GPS position of each collared goat drawn over
+//! OpenStreetMap tiles, with geofences outlined and strays listed.
+
+use crate::services::use_api;
+use crate::store::farm_timezone;
+use log::{error, info};
+use shared::gps::{GeoPoint, Geofence, GoatPosition};
+use shared::time::format_local;
+use std::cell::Cell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+use std::time::Duration;
+use wasm_bindgen_futures::spawn_local;
+use yew::platform::time::sleep;
+use yew::prelude::*;
+
+/// How often the map refreshes on its own.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Map size, in SVG user units (screen pixels at the chosen zoom).
+const WIDTH: f64 = 480.0;
+const HEIGHT: f64 = 320.0;
+
+/// Space kept free around the outermost point.
+const MARGIN: f64 = 24.0;
+
+/// Edge length of a map tile.
+const TILE_SIZE: f64 = 256.0;
+
+/// Closest zoom level used, so a single goat does not fill the map.
+const MAX_ZOOM: u32 = 17;
+
+/// Projects a coordinate to Web Mercator pixels at `zoom`, as used by the
+/// OpenStreetMap tiles.
+fn project(point: &GeoPoint, zoom: u32) -> (f64, f64) {
+    let scale = TILE_SIZE * f64::from(1u32 << zoom);
+    let lat = point.lat.to_radians();
+    let x = (point.lon + 180.0) / 360.0 * scale;
+    let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0 * scale;
+    (x, y)
+}
+
+/// The visible area: zoom level and the pixel position of the map's top
+/// left corner at that zoom.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Viewport {
+    zoom: u32,
+    left: f64,
+    top: f64,
+}
+
+impl Viewport {
+    /// Closest view that shows every point, centred on them.
+    fn fit(points: &[GeoPoint]) -> Option<Self> {
+        points.first()?;
+        let bounds = |zoom| {
+            points.iter().map(|p| project(p, zoom)).fold(
+                (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+                |(x0, y0, x1, y1), (x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+            )
+        };
+        let zoom = (0..=MAX_ZOOM)
+            .rev()
+            .find(|zoom| {
+                let (x0, y0, x1, y1) = bounds(*zoom);
+                x1 - x0 <= WIDTH - 2.0 * MARGIN && y1 - y0 <= HEIGHT - 2.0 * MARGIN
+            })
+            .unwrap_or(0);
+        let (x0, y0, x1, y1) = bounds(zoom);
+        Some(Viewport {
+            zoom,
+            left: (x0 + x1 - WIDTH) / 2.0,
+            top: (y0 + y1 - HEIGHT) / 2.0,
+        })
+    }
+
+    /// Position of `point` on the map.
+    fn place(&self, point: &GeoPoint) -> (f64, f64) {
+        let (x, y) = project(point, self.zoom);
+        (x - self.left, y - self.top)
+    }
+
+    /// Tiles covering the map, as (x, y) tile indices.
+    fn tiles(&self) -> Vec<(i64, i64)> {
+        let count = 1i64 << self.zoom;
+        let first_x = (self.left / TILE_SIZE).floor() as i64;
+        let last_x = ((self.left + WIDTH) / TILE_SIZE).floor() as i64;
+        let first_y = ((self.top / TILE_SIZE).floor() as i64).max(0);
+        let last_y = (((self.top + HEIGHT) / TILE_SIZE).floor() as i64).min(count - 1);
+        (first_y..=last_y)
+            .flat_map(|y| (first_x..=last_x).map(move |x| (x, y)))
+            .collect()
+    }
+}
+
+/// GrazingMap component:
+/// Loads goat positions and geofences, fits the map around them, and marks
+/// goats outside every geofence in red with a list below the map.
+/// Refreshes every minute and on demand.
+#[function_component(GrazingMap)]
+pub fn grazing_map() -> Html {
+    let api = use_api();
+    let data = use_state(|| None::<(Vec<GoatPosition>, Vec<Geofence>)>);
+    let error = use_state(|| None::<String>);
+
+    let load = {
+        let data = data.clone();
+        let error = error.clone();
+        Callback::from(move |_: ()| {
+            let api = api.clone();
+            let data = data.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                let loaded = match api.goat_positions().await {
+                    Ok(positions) => api.geofences().await.map(|fences| (positions, fences)),
+                    Err(e) => Err(e),
+                };
+                match loaded {
+                    Ok((positions, fences)) => {
+                        info!(
+                            "Loaded {} goat positions and {} geofences",
+                            positions.len(),
+                            fences.len()
+                        );
+                        error.set(None);
+                        data.set(Some((positions, fences)));
+                    }
+                    Err(e) => {
+                        error!("Failed to load grazing map: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    use_effect_with((), {
+        let load = load.clone();
+        move |_| {
+            load.emit(());
+            let mounted = Rc::new(Cell::new(true));
+            {
+                let mounted = mounted.clone();
+                spawn_local(async move {
+                    loop {
+                        sleep(REFRESH_INTERVAL).await;
+                        if !mounted.get() {
+                            break;
+                        }
+                        load.emit(());
+                    }
+                });
+            }
+            move || mounted.set(false)
+        }
+    });
+
+    html! {
+        <div>
+            <h3>{"Grazing Map"}</h3>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading grazing map: {}", err)}</p>
+            }
+            <button onclick={load.reform(|_| ())} style="margin-bottom: 10px;">{"Refresh"}</button>
+            {
+                match &*data {
+                    None => html! {},
+                    Some((positions, _)) if positions.is_empty() => html! {
+                        <p>{"No collared goats have reported a position yet."}</p>
+                    },
+                    Some((positions, fences)) => map_view(positions, fences),
+                }
+            }
+        </div>
+    }
+}
+
+/// The map itself and the list of goats outside the geofences.
+fn map_view(positions: &[GoatPosition], fences: &[Geofence]) -> Html {
+    let points: Vec<GeoPoint> = positions
+        .iter()
+        .map(|p| p.position)
+        .chain(fences.iter().flat_map(|f| f.boundary.iter().copied()))
+        .collect();
+    let Some(view) = Viewport::fit(&points) else {
+        return html! {};
+    };
+    let strays: Vec<&GoatPosition> = positions.iter().filter(|p| p.outside).collect();
+
+    html! {
+        <>
+            <svg
+                width={WIDTH.to_string()}
+                height={HEIGHT.to_string()}
+                viewBox={format!("0 0 {} {}", WIDTH, HEIGHT)}
+                style="border: 1px solid #ccc; background: #eef2e6;"
+                role="img"
+                aria-label="Goat positions"
+            >
+                {
+                    for view.tiles().into_iter().map(|(x, y)| {
+                        let count = 1i64 << view.zoom;
+                        html! {
+                            <image
+                                href={format!(
+                                    "https://tile.openstreetmap.org/{}/{}/{}.png",
+                                    view.zoom,
+                                    x.rem_euclid(count),
+                                    y
+                                )}
+                                x={(x as f64 * TILE_SIZE - view.left).to_string()}
+                                y={(y as f64 * TILE_SIZE - view.top).to_string()}
+                                width={TILE_SIZE.to_string()}
+                                height={TILE_SIZE.to_string()}
+                            />
+                        }
+                    })
+                }
+                {
+                    for fences.iter().map(|fence| {
+                        let points = fence
+                            .boundary
+                            .iter()
+                            .map(|p| {
+                                let (x, y) = view.place(p);
+                                format!("{:.1},{:.1}", x, y)
+                            })
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        html! {
+                            <polygon
+                                data-geofence={fence.name.clone()}
+                                {points}
+                                fill="rgba(46, 125, 50, 0.15)"
+                                stroke="#2e7d32"
+                                stroke-width="2"
+                            />
+                        }
+                    })
+                }
+                {
+                    for positions.iter().map(|p| {
+                        let (x, y) = view.place(&p.position);
+                        let color = if p.outside { "#c62828" } else { "#1565c0" };
+                        html! {
+                            <g data-goat={p.goat_name.clone()}>
+                                <circle
+                                    cx={format!("{:.1}", x)}
+                                    cy={format!("{:.1}", y)}
+                                    r="6"
+                                    fill={color}
+                                    stroke="white"
+                                    stroke-width="2"
+                                >
+                                    <title>{format!("{} at {}", p.goat_name, format_local(&p.recorded_at, farm_timezone()))}</title>
+                                </circle>
+                                <text x={format!("{:.1}", x + 8.0)} y={format!("{:.1}", y + 4.0)} font-size="11">
+                                    {&p.goat_name}
+                                </text>
+                            </g>
+                        }
+                    })
+                }
+                <text x={(WIDTH - 4.0).to_string()} y={(HEIGHT - 4.0).to_string()} font-size="9" text-anchor="end">
+                    {"© OpenStreetMap contributors"}
+                </text>
+            </svg>
+            if !strays.is_empty() {
+                <ul class="geofence-alerts" style="color: #c62828;">
+                    {
+                        for strays.iter().map(|p| html! {
+                            <li data-goat={p.goat_name.clone()}>
+                                {format!(
+                                    "{} is outside the geofences ({:.5}, {:.5} at {})",
+                                    p.goat_name,
+                                    p.position.lat,
+                                    p.position.lon,
+                                    format_local(&p.recorded_at, farm_timezone())
+                                )}
+                            </li>
+                        })
+                    }
+                </ul>
+            }
+        </>
+    }
+}
@@ -0,0 +1,377 @@
+//! A goat's health timeline: its incidents, vaccinations and lab results by
+//! date, with a form recording lab results and their PDF reports (see
+//! `shared::lab`).
+
+use crate::components::FileLink;
+use crate::components::import_wizard::read_file;
+use crate::services::api::lab_report_url;
+use crate::services::{Api, use_api};
+use crate::store::use_read_only;
+use log::{error, info};
+use shared::health::{TimelineEntry, TimelineKind};
+use shared::lab::{LabResult, LabValue, REFERENCE_RANGES, RangeFlag};
+use std::collections::HashSet;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// One value row of the lab result form, as typed.
+#[derive(Clone, Default, PartialEq)]
+struct ValueRow {
+    analyte: String,
+    value: String,
+    unit: String,
+    low: String,
+    high: String,
+}
+
+impl ValueRow {
+    /// Parses the row, `None` if it was left blank. Without a unit, the
+    /// analyte's usual unit from `REFERENCE_RANGES` is assumed.
+    fn parse(&self) -> Option<Result<LabValue, String>> {
+        let analyte = self.analyte.trim();
+        if analyte.is_empty() {
+            return None;
+        }
+        let bound = |text: &str, what: &str| -> Result<Option<f64>, String> {
+            match text.trim() {
+                "" => Ok(None),
+                t => t
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| format!("The {} of {} must be a number", what, analyte)),
+            }
+        };
+        let parsed = (|| {
+            let value = self
+                .value
+                .trim()
+                .parse()
+                .map_err(|_| format!("The value of {} must be a number", analyte))?;
+            let unit = match self.unit.trim() {
+                "" => REFERENCE_RANGES
+                    .iter()
+                    .find(|(name, ..)| name.eq_ignore_ascii_case(analyte))
+                    .map(|(_, unit, ..)| unit.to_string())
+                    .unwrap_or_default(),
+                unit => unit.to_string(),
+            };
+            Ok(LabValue {
+                analyte: analyte.to_string(),
+                value,
+                unit,
+                low: bound(&self.low, "low end")?,
+                high: bound(&self.high, "high end")?,
+            })
+        })();
+        Some(parsed)
+    }
+}
+
+/// An out-of-range value as listed, e.g. "PCV 15 % (low)".
+fn flagged_label(value: &LabValue) -> Option<String> {
+    let side = match value.flag()? {
+        RangeFlag::Low => "low",
+        RangeFlag::High => "high",
+    };
+    Some(format!(
+        "{} {} {} ({})",
+        value.analyte, value.value, value.unit, side
+    ))
+}
+
+/// Reloads the goat's timeline and lab results, reporting failures in
+/// `error`.
+fn load_timeline(
+    api: Api,
+    goat_name: String,
+    timeline: UseStateHandle<Option<Vec<TimelineEntry>>>,
+    results: UseStateHandle<Vec<LabResult>>,
+    error: UseStateHandle<Option<String>>,
+) {
+    spawn_local(async move {
+        match api.health_timeline(&goat_name).await {
+            Ok(loaded) => {
+                info!("Loaded {} timeline entries for {}", loaded.len(), goat_name);
+                timeline.set(Some(loaded));
+            }
+            Err(e) => {
+                error!("Failed to load the health timeline of {}: {}", goat_name, e);
+                error.set(Some(e.to_string()));
+            }
+        }
+        match api.lab_results(&goat_name).await {
+            Ok(loaded) => results.set(loaded),
+            Err(e) => {
+                error!("Failed to load lab results of {}: {}", goat_name, e);
+                error.set(Some(e.to_string()));
+            }
+        }
+    });
+}
+
+/// Props for HealthTimeline:
+/// - `goat_name`: goat whose health history is shown
+#[derive(Properties, PartialEq)]
+pub struct HealthTimelineProps {
+    pub goat_name: String,
+}
+
+/// HealthTimeline component:
+/// Lists the goat's incidents, dated vaccinations and lab results, newest
+/// first. Lab results with values outside their reference range are
+/// highlighted with those values, and link to their PDF report when one was
+/// uploaded. Unless the dashboard is read-only, a form records a lab result
+/// with a row per value, leaving the range blank for the typical one, and
+/// uploads the chosen PDF as its report.
+#[function_component(HealthTimeline)]
+pub fn health_timeline(props: &HealthTimelineProps) -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let timeline = use_state(|| None::<Vec<TimelineEntry>>);
+    let results = use_state(Vec::<LabResult>::new);
+    let test_type = use_state(String::new);
+    let sampled_on = use_state(String::new);
+    let lab = use_state(String::new);
+    let rows = use_state(|| vec![ValueRow::default()]);
+    let report = use_node_ref();
+    let busy = use_state(|| false);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with(props.goat_name.clone(), {
+        let api = api.clone();
+        let timeline = timeline.clone();
+        let results = results.clone();
+        let error = error.clone();
+        move |goat_name: &String| {
+            load_timeline(api, goat_name.clone(), timeline, results, error);
+            || {}
+        }
+    });
+
+    let on_input = |field: &UseStateHandle<String>| {
+        let field = field.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            field.set(input.value());
+        })
+    };
+    let on_row_input = |index: usize, pick: fn(&mut ValueRow) -> &mut String| {
+        let rows = rows.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let mut updated = (*rows).clone();
+            *pick(&mut updated[index]) = input.value();
+            rows.set(updated);
+        })
+    };
+    let on_add_row = {
+        let rows = rows.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut updated = (*rows).clone();
+            updated.push(ValueRow::default());
+            rows.set(updated);
+        })
+    };
+
+    let on_save = {
+        let api = api.clone();
+        let goat_name = props.goat_name.clone();
+        let timeline = timeline.clone();
+        let results = results.clone();
+        let test_type = test_type.clone();
+        let sampled_on = sampled_on.clone();
+        let lab = lab.clone();
+        let rows = rows.clone();
+        let report = report.clone();
+        let busy = busy.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let values = match rows.iter().filter_map(ValueRow::parse).collect() {
+                Ok(values) => values,
+                Err(e) => {
+                    error.set(Some(e));
+                    return;
+                }
+            };
+            if sampled_on.is_empty() {
+                error.set(Some("Choose the day the sample was taken".to_string()));
+                return;
+            }
+            let result = LabResult {
+                id: None,
+                goat_name: goat_name.clone(),
+                test_type: test_type.trim().to_string(),
+                sampled_on: (*sampled_on).clone(),
+                lab: Some(lab.trim().to_string()).filter(|l| !l.is_empty()),
+                values,
+                notes: None,
+                has_report: false,
+            };
+            if let Err(e) = result.validate() {
+                error.set(Some(e));
+                return;
+            }
+            let input = report.cast::<HtmlInputElement>();
+            let with_report = input
+                .as_ref()
+                .and_then(|i| i.files())
+                .is_some_and(|files| files.length() > 0);
+            let api = api.clone();
+            let goat_name = goat_name.clone();
+            let timeline = timeline.clone();
+            let results = results.clone();
+            let fields = [test_type.clone(), sampled_on.clone(), lab.clone()];
+            let rows = rows.clone();
+            let busy = busy.clone();
+            let message = message.clone();
+            let error = error.clone();
+            busy.set(true);
+            spawn_local(async move {
+                let saved = async {
+                    let stored = api
+                        .add_lab_result(&result)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    if let (true, Some(input), Some(id)) = (with_report, &input, stored.id) {
+                        let bytes = read_file(input).await?;
+                        api.upload_lab_report(id, &bytes)
+                            .await
+                            .map_err(|e| format!("Result saved, but the report was not: {}", e))?;
+                        input.set_value("");
+                    }
+                    Ok::<_, String>(stored)
+                }
+                .await;
+                busy.set(false);
+                match saved {
+                    Ok(stored) => {
+                        info!("Recorded lab result {:?} for {}", stored.id, goat_name);
+                        fields.iter().for_each(|f| f.set(String::new()));
+                        rows.set(vec![ValueRow::default()]);
+                        error.set(None);
+                        let flagged: Vec<String> =
+                            stored.values.iter().filter_map(flagged_label).collect();
+                        message.set(Some(if flagged.is_empty() {
+                            "Lab result saved; all values within range".to_string()
+                        } else {
+                            format!("Lab result saved; out of range: {}", flagged.join(", "))
+                        }));
+                    }
+                    Err(e) => {
+                        error!("Failed to record lab result for {}: {}", goat_name, e);
+                        message.set(None);
+                        error.set(Some(e));
+                    }
+                }
+                load_timeline(api, goat_name, timeline, results, error);
+            });
+        })
+    };
+
+    let with_report: HashSet<i64> = results
+        .iter()
+        .filter(|r| r.has_report)
+        .filter_map(|r| r.id)
+        .collect();
+
+    html! {
+        <div class="health-timeline">
+            <h4>{"Health timeline"}</h4>
+            {
+                match &*timeline {
+                    None => html! { <p>{"Loading…"}</p> },
+                    Some(entries) if entries.is_empty() => html! {
+                        <p>{"No health events recorded yet."}</p>
+                    },
+                    Some(entries) => html! {
+                        <ul class="timeline-entries">
+                            { for entries.iter().map(|entry| {
+                                let kind = match entry.kind {
+                                    TimelineKind::Incident => "incident",
+                                    TimelineKind::Vaccination => "vaccination",
+                                    TimelineKind::LabResult => "lab-result",
+                                };
+                                let style = if entry.flagged { "color: #c62828;" } else { "" };
+                                html! {
+                                    <li class={classes!("timeline-entry", kind, entry.flagged.then_some("flagged"))}
+                                        {style}>
+                                        <strong>{&entry.date}</strong>{" "}{&entry.title}
+                                        if let Some(details) = &entry.details {
+                                            {" — "}<span class="timeline-details">{details}</span>
+                                        }
+                                        if let Some(id) = entry.lab_result_id.filter(|id| with_report.contains(id)) {
+                                            {" "}
+                                            <FileLink class="lab-report" url={lab_report_url(id)}
+                                                      file_name={format!("lab-report-{}.pdf", id)}>
+                                                {"Report (PDF)"}
+                                            </FileLink>
+                                        }
+                                    </li>
+                                }
+                            }) }
+                        </ul>
+                    },
+                }
+            }
+            if !read_only {
+                <fieldset class="lab-result-form">
+                    <legend>{"Record lab result"}</legend>
+                    <input class="lab-test-type" placeholder="Test, e.g. CBC"
+                           value={(*test_type).clone()} oninput={on_input(&test_type)} />
+                    {" "}
+                    <input class="lab-sampled-on" type="date" value={(*sampled_on).clone()}
+                           oninput={on_input(&sampled_on)} />
+                    {" "}
+                    <input class="lab-name" placeholder="Lab" value={(*lab).clone()}
+                           oninput={on_input(&lab)} />
+                    <datalist id="lab-analytes">
+                        { for REFERENCE_RANGES.iter().map(|(name, ..)| html! {
+                            <option value={*name} />
+                        }) }
+                    </datalist>
+                    { for rows.iter().enumerate().map(|(i, row)| html! {
+                        <p class="lab-value-row">
+                            <input class="lab-analyte" list="lab-analytes" placeholder="Analyte"
+                                   value={row.analyte.clone()}
+                                   oninput={on_row_input(i, |r| &mut r.analyte)} />
+                            {" "}
+                            <input class="lab-value" type="number" step="any" placeholder="Value"
+                                   value={row.value.clone()}
+                                   oninput={on_row_input(i, |r| &mut r.value)} />
+                            {" "}
+                            <input class="lab-unit" placeholder="Unit" value={row.unit.clone()}
+                                   oninput={on_row_input(i, |r| &mut r.unit)} />
+                            {" "}
+                            <input class="lab-low" type="number" step="any" placeholder="Low"
+                                   value={row.low.clone()}
+                                   oninput={on_row_input(i, |r| &mut r.low)} />
+                            {"–"}
+                            <input class="lab-high" type="number" step="any" placeholder="High"
+                                   value={row.high.clone()}
+                                   oninput={on_row_input(i, |r| &mut r.high)} />
+                        </p>
+                    }) }
+                    <button class="add-lab-value" onclick={on_add_row}>{"Add value"}</button>
+                    <p>
+                        <label>{"Report (PDF) "}
+                            <input class="lab-report-file" type="file" accept="application/pdf"
+                                   ref={report} />
+                        </label>
+                    </p>
+                    <button class="save-lab-result" onclick={on_save} disabled={*busy}>
+                        {if *busy { "Saving…" } else { "Save lab result" }}
+                    </button>
+                </fieldset>
+            }
+            if let Some(msg) = &*message {
+                <p class="lab-message" style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error: {}", err)}</p>
+            }
+        </div>
+    }
+}
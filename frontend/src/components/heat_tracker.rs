@@ -0,0 +1,102 @@
+//! Heat tracker: does wearing activity tags, whether they are in heat now,
+//! and when the next heat is expected.
+
+use crate::components::SkeletonRows;
+use crate::services::use_api;
+use log::{error, info};
+use shared::heat::HeatPrediction;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// HeatTracker component:
+/// Loads heat predictions on mount and lists each tagged doe with her last
+/// detected heat, the predicted next heat and its confidence. Does in heat
+/// are listed first and shaded.
+#[function_component(HeatTracker)]
+pub fn heat_tracker() -> Html {
+    let api = use_api();
+    let predictions = use_state(|| None::<Vec<HeatPrediction>>);
+    let error = use_state(|| None::<String>);
+
+    {
+        let predictions = predictions.clone();
+        let error = error.clone();
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                match api.heat_predictions().await {
+                    Ok(loaded) => {
+                        info!("Loaded {} heat predictions", loaded.len());
+                        predictions.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load heat predictions: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+            || ()
+        });
+    }
+
+    html! {
+        <div>
+            <h3>{"Heat Tracker"}</h3>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading heat predictions: {}", err)}</p>
+            }
+            {
+                match &*predictions {
+                    None if error.is_none() => html! {
+                        <table><tbody><SkeletonRows rows={2} columns={5} /></tbody></table>
+                    },
+                    None => html! {},
+                    Some(list) if list.is_empty() => html! {
+                        <p>{"No does are wearing activity tags."}</p>
+                    },
+                    Some(list) => html! {
+                        <table style="border-collapse: collapse; width: 100%;">
+                            <thead>
+                                <tr>
+                                    <th>{"Doe"}</th>
+                                    <th>{"Status"}</th>
+                                    <th>{"Last heat"}</th>
+                                    <th>{"Next heat"}</th>
+                                    <th>{"Confidence"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                { for list.iter().map(prediction_row) }
+                            </tbody>
+                        </table>
+                    },
+                }
+            }
+        </div>
+    }
+}
+
+/// One table row for a doe's prediction.
+fn prediction_row(prediction: &HeatPrediction) -> Html {
+    let (status, style) = if prediction.in_heat {
+        (
+            "In heat",
+            "background: #fff3e0; color: #e65100; font-weight: bold;",
+        )
+    } else {
+        ("–", "")
+    };
+    let confidence = if prediction.next_heat.is_some() {
+        format!("{:.0}%", prediction.confidence * 100.0)
+    } else {
+        "–".to_string()
+    };
+    html! {
+        <tr data-doe={prediction.doe_name.clone()} {style}>
+            <td>{&prediction.doe_name}</td>
+            <td class="status">{status}</td>
+            <td>{prediction.last_heat.clone().unwrap_or_else(|| "None detected".to_string())}</td>
+            <td class="next-heat">{prediction.next_heat.clone().unwrap_or_else(|| "–".to_string())}</td>
+            <td class="confidence">{confidence}</td>
+        </tr>
+    }
+}
@@ -0,0 +1,402 @@
+//! Import wizard: brings a herd over from another livestock app's CSV or
+//! XLSX export in three steps — upload, map columns, preview and commit.
+
+use crate::components::{SoftWarnings, Spinner, job_progress, use_soft_warnings};
+use crate::services::use_api;
+use crate::store::GoatStore;
+use log::{error, info};
+use shared::GoatParams;
+use shared::import::{GoatField, ImportRow, ImportTable, guess_mapping, missing_fields, preview};
+use shared::jobs::{Job, JobStatus};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+use wasm_bindgen_futures::{JsFuture, spawn_local};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::platform::time::sleep;
+use yew::prelude::*;
+use yewdux::prelude::use_dispatch;
+
+/// Imports with more valid rows than this run as a background job.
+const BACKGROUND_IMPORT_ROWS: usize = 100;
+
+/// How often a background import's progress is polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Where the user is in the wizard.
+#[derive(Clone, PartialEq)]
+enum Step {
+    Upload,
+    Map,
+    Preview,
+    /// A large import is running as this background job.
+    Queued(Job),
+    /// The import finished, adding this many goats.
+    Done(usize),
+}
+
+/// Reads the file chosen in `input` into memory.
+pub(crate) async fn read_file(input: &HtmlInputElement) -> Result<Vec<u8>, String> {
+    let file = input
+        .files()
+        .and_then(|files| files.get(0))
+        .ok_or("No file selected")?;
+    let buffer = JsFuture::from(file.array_buffer())
+        .await
+        .map_err(|_| format!("Could not read {}", file.name()))?;
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
+
+/// ImportWizard component:
+/// Uploads a spreadsheet for the backend to parse, lets the user map each
+/// column to a goat field (pre-filled from the headings), previews every
+/// row with its validation errors and warnings, and adds the valid rows in
+/// one batch, asking first if any of them has warnings. More than
+/// `BACKGROUND_IMPORT_ROWS` rows are queued as a background job instead,
+/// whose progress is shown until it finishes.
+#[function_component(ImportWizard)]
+pub fn import_wizard() -> Html {
+    let api = use_api();
+    let dispatch = use_dispatch::<GoatStore>();
+    let step = use_state(|| Step::Upload);
+    let table = use_state(ImportTable::default);
+    let mapping = use_state(Vec::<Option<GoatField>>::new);
+    let loading = use_state(|| false);
+    let error = use_state(|| None::<String>);
+    let warnings = use_soft_warnings();
+
+    let on_file = {
+        let api = api.clone();
+        let step = step.clone();
+        let table = table.clone();
+        let mapping = mapping.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            let Some(input) = e.target_dyn_into::<HtmlInputElement>() else {
+                return;
+            };
+            let api = api.clone();
+            let step = step.clone();
+            let table = table.clone();
+            let mapping = mapping.clone();
+            let loading = loading.clone();
+            let error = error.clone();
+            loading.set(true);
+            error.set(None);
+            spawn_local(async move {
+                let parsed = match read_file(&input).await {
+                    Ok(bytes) => api.parse_import(&bytes).await.map_err(|e| e.to_string()),
+                    Err(e) => Err(e),
+                };
+                match parsed {
+                    Ok(parsed) => {
+                        info!(
+                            "Parsed import with {} columns and {} rows",
+                            parsed.headers.len(),
+                            parsed.rows.len()
+                        );
+                        mapping.set(guess_mapping(&parsed.headers));
+                        table.set(parsed);
+                        step.set(Step::Map);
+                    }
+                    Err(e) => {
+                        error!("Failed to parse import file: {}", e);
+                        error.set(Some(e));
+                    }
+                }
+                loading.set(false);
+            });
+        })
+    };
+
+    let queued_id = match &*step {
+        Step::Queued(job) => Some(job.id),
+        _ => None,
+    };
+    {
+        let api = api.clone();
+        let dispatch = dispatch.clone();
+        let step = step.clone();
+        let error = error.clone();
+        use_effect_with(queued_id, move |id| {
+            let running = Rc::new(Cell::new(true));
+            if let Some(id) = *id {
+                let running = running.clone();
+                spawn_local(async move {
+                    while running.get() {
+                        match api.job(id).await {
+                            Ok(job) if job.status == JobStatus::Succeeded => {
+                                info!("Background import {} added {} goats", id, job.done);
+                                step.set(Step::Done(job.done as usize));
+                                GoatStore::force_refresh(api, dispatch);
+                                break;
+                            }
+                            Ok(job) => {
+                                if job.status == JobStatus::Failed {
+                                    error!("Background import {} failed", id);
+                                    error.set(job.error.clone());
+                                    step.set(Step::Queued(job));
+                                    break;
+                                }
+                                step.set(Step::Queued(job));
+                            }
+                            Err(e) => {
+                                error!("Failed to poll import job {}: {}", id, e);
+                                error.set(Some(e.to_string()));
+                                break;
+                            }
+                        }
+                        sleep(POLL_INTERVAL).await;
+                    }
+                });
+            }
+            move || running.set(false)
+        });
+    }
+
+    let go_to = |target: Step| {
+        let step = step.clone();
+        let error = error.clone();
+        let warnings = warnings.clone();
+        Callback::from(move |_| {
+            error.set(None);
+            warnings.clear();
+            step.set(target.clone());
+        })
+    };
+
+    let rows: Vec<ImportRow> = match *step {
+        Step::Preview => preview(&table, &mapping),
+        _ => Vec::new(),
+    };
+    let valid: Vec<GoatParams> = rows.iter().filter_map(|r| r.goat.clone()).collect();
+    let row_warnings: Vec<String> = rows
+        .iter()
+        .filter(|r| r.goat.is_some())
+        .flat_map(|r| {
+            r.warnings
+                .iter()
+                .map(move |w| format!("Line {}: {}", r.line, w))
+        })
+        .collect();
+
+    let on_commit = {
+        let api = api.clone();
+        let step = step.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+        let valid = valid.clone();
+        let warnings = warnings.clone();
+        let row_warnings = row_warnings.clone();
+        Callback::from(move |_: MouseEvent| {
+            if warnings.hold(row_warnings.clone()) {
+                info!(
+                    "Holding import until {} warnings are confirmed",
+                    row_warnings.len()
+                );
+                return;
+            }
+            let api = api.clone();
+            let dispatch = dispatch.clone();
+            let step = step.clone();
+            let loading = loading.clone();
+            let error = error.clone();
+            let valid = valid.clone();
+            loading.set(true);
+            error.set(None);
+            spawn_local(async move {
+                if valid.len() > BACKGROUND_IMPORT_ROWS {
+                    match api.start_import_job(&valid).await {
+                        Ok(job) => {
+                            info!("Queued import of {} goats as job {}", valid.len(), job.id);
+                            step.set(Step::Queued(job));
+                        }
+                        Err(e) => {
+                            error!("Failed to queue import: {}", e);
+                            error.set(Some(e.to_string()));
+                        }
+                    }
+                    loading.set(false);
+                    return;
+                }
+                match api.add_goats_batch(&valid).await {
+                    Ok(summary) => {
+                        info!("Imported {} goats", summary.added);
+                        step.set(Step::Done(summary.added));
+                        GoatStore::force_refresh(api, dispatch);
+                    }
+                    Err(e) => {
+                        error!("Failed to import goats: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+                loading.set(false);
+            });
+        })
+    };
+
+    let column_select = |column: usize| {
+        let mapping = mapping.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                let mut updated = (*mapping).clone();
+                updated[column] = GoatField::from_key(&select.value());
+                mapping.set(updated);
+            }
+        })
+    };
+
+    let body = match &*step {
+        Step::Upload => html! {
+            <p>
+                <label>{"Spreadsheet (CSV or XLSX): "}
+                    <input type="file" accept=".csv,.xlsx,text/csv" onchange={on_file}
+                           disabled={*loading} />
+                </label>
+            </p>
+        },
+        Step::Map => {
+            let missing = missing_fields(&mapping);
+            html! {
+                <>
+                    <p style="font-size: 12px;">
+                        {"Choose the goat field each column holds. Unmapped columns are ignored."}
+                    </p>
+                    <table style="border-collapse: collapse; margin-bottom: 10px;">
+                        <thead>
+                            <tr>
+                                <th>{"Column"}</th>
+                                <th>{"Example"}</th>
+                                <th>{"Goat field"}</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            { for table.headers.iter().enumerate().map(|(i, header)| {
+                                let example = table.rows.first().and_then(|r| r.get(i)).cloned();
+                                html! {
+                                    <tr data-column={header.clone()}>
+                                        <td>{header}</td>
+                                        <td style="color: #666;">{example.unwrap_or_default()}</td>
+                                        <td>
+                                            <select onchange={column_select(i)}>
+                                                <option value="" selected={mapping[i].is_none()}>
+                                                    {"— ignore —"}
+                                                </option>
+                                                { for GoatField::ALL.iter().map(|field| html! {
+                                                    <option value={field.key()}
+                                                            selected={mapping[i] == Some(*field)}>
+                                                        {field.label()}
+                                                    </option>
+                                                }) }
+                                            </select>
+                                        </td>
+                                    </tr>
+                                }
+                            }) }
+                        </tbody>
+                    </table>
+                    if !missing.is_empty() {
+                        <p class="missing-fields" style="color: #c62828;">
+                            {format!(
+                                "Map a column to: {}",
+                                missing.iter().map(GoatField::label).collect::<Vec<_>>().join(", ")
+                            )}
+                        </p>
+                    }
+                    <button onclick={go_to(Step::Upload)}>{"Back"}</button>
+                    <button onclick={go_to(Step::Preview)} disabled={!missing.is_empty()}>
+                        {"Preview"}
+                    </button>
+                </>
+            }
+        }
+        Step::Preview => html! {
+            <>
+                <p class="import-summary">
+                    {format!("{} of {} rows ready to import.", valid.len(), rows.len())}
+                </p>
+                <table style="border-collapse: collapse; width: 100%; margin-bottom: 10px;">
+                    <thead>
+                        <tr>
+                            <th>{"Line"}</th>
+                            <th>{"Name"}</th>
+                            <th>{"Breed"}</th>
+                            <th>{"Gender"}</th>
+                            <th>{"Weight (kg)"}</th>
+                            <th>{"Problems"}</th>
+                            <th>{"Warnings"}</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        { for rows.iter().map(|r| html! {
+                            <tr data-line={r.line.to_string()}
+                                style={if r.errors.is_empty() { "" } else { "background: #fdecea;" }}>
+                                <td>{r.line}</td>
+                                if let Some(goat) = &r.goat {
+                                    <td>{&goat.name}</td>
+                                    <td>{shared::Breed::to_str(&goat.breed)}</td>
+                                    <td>{shared::Gender::to_str(&goat.gender)}</td>
+                                    <td>{format!("{:.1}", goat.weight)}</td>
+                                } else {
+                                    <td colspan="4" style="color: #666;">{"–"}</td>
+                                }
+                                <td class="errors">{r.errors.join("; ")}</td>
+                                <td class="warnings" style="color: #e65100;">
+                                    {r.warnings.join("; ")}
+                                </td>
+                            </tr>
+                        }) }
+                    </tbody>
+                </table>
+                <SoftWarnings
+                    warnings={warnings.shown()}
+                    on_confirm={{
+                        let warnings = warnings.clone();
+                        let on_commit = on_commit.clone();
+                        Callback::from(move |e| {
+                            warnings.confirm();
+                            on_commit.emit(e);
+                        })
+                    }}
+                />
+                <button onclick={go_to(Step::Map)}>{"Back"}</button>
+                <button onclick={on_commit} disabled={valid.is_empty() || *loading}>
+                    {format!("Import {} goats", valid.len())}
+                </button>
+            </>
+        },
+        Step::Queued(job) => html! {
+            <>
+                <p class="import-queued">
+                    {format!("{} in the background: {}", job.description, job.status.label())}
+                </p>
+                {job_progress(job)}
+                if job.status == JobStatus::Failed {
+                    <p>
+                        <button onclick={go_to(Step::Upload)}>{"Import another file"}</button>
+                    </p>
+                }
+            </>
+        },
+        Step::Done(added) => html! {
+            <>
+                <p class="import-done">{format!("Imported {} goats.", added)}</p>
+                <button onclick={go_to(Step::Upload)}>{"Import another file"}</button>
+            </>
+        },
+    };
+
+    html! {
+        <div>
+            <h3>{"Import Goats"}</h3>
+            {body}
+            if *loading {
+                <Spinner label="Importing..." />
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Import error: {}", err)}</p>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,157 @@
+//! Heat map of health incidents per pen and month, to spot pens with
+//! recurring disease or parasite problems.
+
+use crate::components::{DatePicker, EmptyState, SkeletonRows};
+use crate::services::use_api;
+use log::{error, info};
+use shared::health::HealthHeatMap;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// Background for a cell holding `count` incidents when the busiest cell
+/// holds `max`; empty cells stay white.
+fn cell_color(count: u32, max: u32) -> String {
+    if count == 0 || max == 0 {
+        return "#fff".to_string();
+    }
+    let intensity = 0.15 + 0.85 * count as f64 / max as f64;
+    format!("rgba(200, 40, 40, {:.2})", intensity)
+}
+
+/// IncidentHeatMap component:
+/// Shows a pen-by-month grid of incident counts, shaded by how many
+/// incidents each cell holds. The period defaults to the last twelve months
+/// and can be narrowed with the date inputs. Without any pens or incidents
+/// an `EmptyState` describes what will appear.
+#[function_component(IncidentHeatMap)]
+pub fn incident_heatmap() -> Html {
+    let api = use_api();
+    let from = use_state(String::new);
+    let to = use_state(String::new);
+    let heatmap = use_state(|| None::<HealthHeatMap>);
+    let error = use_state(|| None::<String>);
+
+    let load = {
+        let from = from.clone();
+        let to = to.clone();
+        let heatmap = heatmap.clone();
+        let error = error.clone();
+        Callback::from(move |_: ()| {
+            let api = api.clone();
+            let from = (*from).clone();
+            let to = (*to).clone();
+            let heatmap = heatmap.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                let from = (!from.is_empty()).then_some(from.as_str());
+                let to = (!to.is_empty()).then_some(to.as_str());
+                match api.health_heatmap(from, to).await {
+                    Ok(loaded) => {
+                        info!(
+                            "Loaded incident heat map for {} pens over {} months",
+                            loaded.rows.len(),
+                            loaded.months.len()
+                        );
+                        error.set(None);
+                        heatmap.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load incident heat map: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    use_effect_with((), {
+        let load = load.clone();
+        move |_| {
+            load.emit(());
+            || {}
+        }
+    });
+
+    let on_date = |state: UseStateHandle<String>| Callback::from(move |date| state.set(date));
+
+    html! {
+        <div>
+            <h3>{"Health Incidents by Pen"}</h3>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading incident heat map: {}", err)}</p>
+            }
+            <label>{"From: "}
+                <DatePicker name="from" label="From" value={(*from).clone()}
+                            onchange={on_date(from.clone())} />
+            </label>
+            { " " }
+            <label>{"To: "}
+                <DatePicker name="to" label="To" value={(*to).clone()}
+                            onchange={on_date(to.clone())} />
+            </label>
+            { " " }
+            <button onclick={load.reform(|_| ())} style="margin-bottom: 10px;">{"Show"}</button>
+            {
+                match &*heatmap {
+                    None => html! {
+                        <table><tbody><SkeletonRows rows={3} columns={6} /></tbody></table>
+                    },
+                    Some(map) if map.rows.is_empty() => html! {
+                        <EmptyState
+                            title="No pens or incidents recorded yet"
+                            message="Incidents logged for goats in a pen are counted here month by month."
+                            sample="Pen A · March · 3 incidents"
+                        />
+                    },
+                    Some(map) => heatmap_view(map),
+                }
+            }
+        </div>
+    }
+}
+
+/// Renders the shaded pen-by-month grid for `map`.
+fn heatmap_view(map: &HealthHeatMap) -> Html {
+    let max = map.max_count();
+    html! {
+        <div style="overflow-x: auto;">
+            <table style="border-collapse: collapse;">
+                <thead>
+                    <tr>
+                        <th>{"Pen"}</th>
+                        { for map.months.iter().map(|month| html! {
+                            <th style="font-size: 11px; padding: 2px 4px;">{month}</th>
+                        }) }
+                        <th>{"Total"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    { for map.rows.iter().map(|row| html! {
+                        <tr data-pen={row.space_name.clone()}>
+                            <td>{&row.space_name}</td>
+                            { for row.counts.iter().zip(&map.months).map(|(count, month)| html! {
+                                <td
+                                    data-count={count.to_string()}
+                                    title={format!("{} in {}: {} incidents", row.space_name, month, count)}
+                                    style={format!(
+                                        "background: {}; text-align: center; border: 1px solid #eee; min-width: 28px;",
+                                        cell_color(*count, max)
+                                    )}
+                                >
+                                    { if *count > 0 { count.to_string() } else { String::new() } }
+                                </td>
+                            }) }
+                            <td style="font-weight: bold;">{row.total}</td>
+                        </tr>
+                    }) }
+                </tbody>
+            </table>
+            <p style="font-size: 12px;">
+                {format!(
+                    "{} to {}. Darker cells mean more incidents; a pen that stays dark may have sanitation or drainage problems.",
+                    map.from, map.to
+                )}
+            </p>
+        </div>
+    }
+}
@@ -0,0 +1,104 @@
+//! Table of stocked supplies: feed, medicines and equipment, with items at
+//! or below their reorder level highlighted.
+
+use crate::components::{Column, DataTable, EmptyState, RowId, RowStyle};
+use crate::services::use_api;
+use log::{error, info};
+use shared::inventory::{InventoryCategory, InventoryItem};
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// InventoryList component:
+/// Loads every item on mount and on Refresh and lists them in a
+/// `DataTable`, ordered by name. Rows of items at or below their reorder
+/// level are tinted. Rows have `item-{id}` element IDs.
+#[function_component(InventoryList)]
+pub fn inventory_list() -> Html {
+    let api = use_api();
+    let items = use_state(|| None::<Vec<InventoryItem>>);
+    let error = use_state(|| None::<String>);
+    let reloads = use_state(|| 0u32);
+
+    {
+        let items = items.clone();
+        let error = error.clone();
+        use_effect_with(*reloads, move |_| {
+            spawn_local(async move {
+                match api.inventory_items().await {
+                    Ok(loaded) => {
+                        info!("Loaded {} inventory items", loaded.len());
+                        error.set(None);
+                        items.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load inventory: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        });
+    }
+
+    let refresh = {
+        let reloads = reloads.clone();
+        Callback::from(move |_: MouseEvent| reloads.set(*reloads + 1))
+    };
+
+    let columns = vec![
+        Column::text("name", "Item", |item: &InventoryItem| item.name.clone()),
+        Column::text("category", "Category", |item: &InventoryItem| {
+            InventoryCategory::to_str(&item.category).to_string()
+        }),
+        Column::number("quantity", "Quantity", |item: &InventoryItem| item.quantity),
+        Column::text("unit", "Unit", |item: &InventoryItem| item.unit.clone()),
+        Column::number("reorder_level", "Reorder At", |item: &InventoryItem| {
+            item.reorder_level
+        }),
+        Column::number("unit_cost", "Unit Cost", |item: &InventoryItem| {
+            item.unit_cost
+        }),
+        Column::text("expiry_date", "Expires", |item: &InventoryItem| {
+            item.expiry_date.clone().unwrap_or_else(|| "-".to_string())
+        }),
+    ];
+    let row_style: RowStyle<InventoryItem> = Rc::new(|item: &InventoryItem| {
+        if item.quantity <= item.reorder_level {
+            "background: #fff3e0;"
+        } else {
+            ""
+        }
+    });
+
+    let row_id: RowId<InventoryItem> =
+        Rc::new(|item: &InventoryItem| format!("item-{}", item.id.unwrap_or_default()));
+    html! {
+        <div style="margin-bottom: 24px;">
+            <h3>{"Inventory"}</h3>
+            <button onclick={refresh}>{"Refresh"}</button>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading inventory: {}", err)}</p>
+            }
+            {
+                match &*items {
+                    None => html! { <p>{"Loading inventory..."}</p> },
+                    Some(items) if items.is_empty() => html! {
+                        <EmptyState
+                            title="No supplies yet"
+                            message="Feed, medicines and equipment you stock will be listed here."
+                            sample="Concentrate · Feed · 120 kg · reorder at 50 kg"
+                        />
+                    },
+                    Some(items) => html! {
+                        <DataTable<InventoryItem>
+                            rows={items.clone()}
+                            {columns}
+                            {row_id}
+                            row_style={Some(row_style)}
+                        />
+                    },
+                }
+            }
+        </div>
+    }
+}
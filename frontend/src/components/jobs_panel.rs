@@ -0,0 +1,128 @@
+//! "Background jobs" panel listing the reports, imports and notifications
+//! the backend is working through (see `shared::jobs`), with their progress.
+
+use crate::components::FileLink;
+use crate::services::api::job_result_url;
+use crate::services::use_api;
+use crate::store::GoatStore;
+use log::{error, info};
+use shared::jobs::{Job, JobStatus};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+use wasm_bindgen_futures::spawn_local;
+use yew::platform::time::sleep;
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// How often the job list is reloaded while a job is unfinished.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Progress bar and count for one job.
+pub fn job_progress(job: &Job) -> Html {
+    let percent = (job.progress() * 100.0).round();
+    html! {
+        <span class="job-progress">
+            <progress max="100" value={percent.to_string()} style="margin-right: 6px;" />
+            if job.total > 0 {
+                {format!("{} / {}", job.done, job.total)}
+            } else {
+                {format!("{}%", percent)}
+            }
+        </span>
+    }
+}
+
+/// JobsPanel component:
+/// Loads the recent background jobs and shows each one's status and
+/// progress, reloading every `POLL_INTERVAL` until all have finished. A
+/// finished report links to its file; a failed job shows why. Reloads when
+/// the goat list changes and on Refresh.
+#[function_component(JobsPanel)]
+pub fn jobs_panel() -> Html {
+    let api = use_api();
+    let (state, _) = use_store::<GoatStore>();
+    let jobs = use_state(|| None::<Vec<Job>>);
+    let error = use_state(|| None::<String>);
+    let reloads = use_state(|| 0u32);
+
+    {
+        let jobs = jobs.clone();
+        let error = error.clone();
+        use_effect_with((state.goats.len(), *reloads), move |_| {
+            let running = Rc::new(Cell::new(true));
+            {
+                let running = running.clone();
+                spawn_local(async move {
+                    while running.get() {
+                        match api.jobs().await {
+                            Ok(loaded) => {
+                                info!("Loaded {} jobs", loaded.len());
+                                let unfinished = loaded.iter().any(|j| !j.status.is_finished());
+                                error.set(None);
+                                jobs.set(Some(loaded));
+                                if !unfinished {
+                                    break;
+                                }
+                            }
+                            Err(e) => {
+                                error!("Failed to load jobs: {}", e);
+                                error.set(Some(e.to_string()));
+                                break;
+                            }
+                        }
+                        sleep(POLL_INTERVAL).await;
+                    }
+                });
+            }
+            move || running.set(false)
+        });
+    }
+
+    let refresh = {
+        let reloads = reloads.clone();
+        Callback::from(move |_: MouseEvent| reloads.set(*reloads + 1))
+    };
+
+    html! {
+        <div>
+            <h3>{"Background Jobs"}</h3>
+            <button onclick={refresh}>{"Refresh"}</button>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading jobs: {}", err)}</p>
+            }
+            {
+                match &*jobs {
+                    None => html! { <p>{"Loading jobs..."}</p> },
+                    Some(jobs) if jobs.is_empty() => html! {
+                        <p>{"No background jobs yet."}</p>
+                    },
+                    Some(jobs) => html! {
+                        <ul class="jobs" style="list-style: none; padding: 0;">
+                            { for jobs.iter().map(|job| html! {
+                                <li data-status={job.status.label()} style="padding: 4px 0;">
+                                    <strong style="margin-right: 8px;">{&job.description}</strong>
+                                    <span style="color: #666; margin-right: 8px;">
+                                        {job.status.label()}
+                                    </span>
+                                    {job_progress(job)}
+                                    if let (JobStatus::Succeeded, Some(name)) =
+                                        (job.status, &job.result_name)
+                                    {
+                                        <FileLink url={job_result_url(job.id)} file_name={name.clone()}
+                                                  style="margin-left: 8px;">
+                                            {format!("Download {}", name)}
+                                        </FileLink>
+                                    }
+                                    if let Some(err) = &job.error {
+                                        <p style="color: red; margin: 2px 0;">{err}</p>
+                                    }
+                                </li>
+                            }) }
+                        </ul>
+                    },
+                }
+            }
+        </div>
+    }
+}
@@ -0,0 +1,149 @@
+//! Row of headline KPI cards for the top of the dashboard: herd size, herd
+//! value, milk this week and expenses this month, each with a sparkline of
+//! recent periods and the change from the previous one.
+
+use crate::services::use_api;
+use crate::store::GoatStore;
+use log::{error, info};
+use shared::stats::{DashboardStats, Kpi};
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// Sparkline size, in SVG user units.
+const SPARK_WIDTH: f64 = 120.0;
+const SPARK_HEIGHT: f64 = 28.0;
+
+/// Props for KpiCard:
+/// - `id`: stable key, set as the card's `data-kpi` attribute
+/// - `label`: card title
+/// - `value`: the formatted current value
+/// - `period`: what the change is measured against, e.g. "last week"
+/// - `kpi`: the figure and its trend
+/// - `rising_is_good`: colours a rise green (or red, for costs)
+#[derive(Properties, PartialEq)]
+pub struct KpiCardProps {
+    pub id: AttrValue,
+    pub label: AttrValue,
+    pub value: AttrValue,
+    pub period: AttrValue,
+    pub kpi: Kpi,
+    #[prop_or(true)]
+    pub rising_is_good: bool,
+}
+
+/// Polyline points for `trend`, scaled between its lowest and highest
+/// values so small movements still show.
+fn sparkline_points(trend: &[f64]) -> String {
+    let min = trend.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = trend.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = if max > min { max - min } else { 1.0 };
+    let step = SPARK_WIDTH / (trend.len().max(2) - 1) as f64;
+    trend
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let y = SPARK_HEIGHT - 2.0 - (v - min) / range * (SPARK_HEIGHT - 4.0);
+            format!("{:.1},{:.1}", i as f64 * step, y)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// KpiCard component:
+/// One figure with its sparkline and the change from the previous period.
+#[function_component(KpiCard)]
+pub fn kpi_card(props: &KpiCardProps) -> Html {
+    let (change, color) = match props.kpi.change_percent() {
+        Some(pct) if pct.abs() < 0.05 => (format!("No change vs {}", props.period), "#666"),
+        Some(pct) => {
+            let good = (pct > 0.0) == props.rising_is_good;
+            (
+                format!(
+                    "{} {:.1}% vs {}",
+                    if pct > 0.0 { "▲" } else { "▼" },
+                    pct.abs(),
+                    props.period
+                ),
+                if good { "green" } else { "#c0392b" },
+            )
+        }
+        None => (format!("Nothing to compare with {}", props.period), "#666"),
+    };
+
+    html! {
+        <div class="kpi-card" data-kpi={props.id.clone()}
+             style="flex: 1; min-width: 160px; border: 1px solid #ddd; border-radius: 6px; padding: 12px;">
+            <div style="font-size: 12px; color: #666;">{&props.label}</div>
+            <div class="kpi-value" style="font-size: 22px; font-weight: bold;">{&props.value}</div>
+            <svg width={SPARK_WIDTH.to_string()} height={SPARK_HEIGHT.to_string()}
+                 viewBox={format!("0 0 {} {}", SPARK_WIDTH, SPARK_HEIGHT)}
+                 role="img" aria-label={format!("{} trend", props.label)}>
+                <polyline class="sparkline" points={sparkline_points(&props.kpi.trend)}
+                          fill="none" stroke="#2980b9" stroke-width="1.5" />
+            </svg>
+            <div class="kpi-change" style={format!("font-size: 12px; color: {};", color)}>{change}</div>
+        </div>
+    }
+}
+
+/// KpiCards component:
+/// Loads the dashboard stats and shows one card per KPI. Reloads when the
+/// goat list changes, so herd size and value follow adds and deletes.
+#[function_component(KpiCards)]
+pub fn kpi_cards() -> Html {
+    let api = use_api();
+    let (state, _) = use_store::<GoatStore>();
+    let stats = use_state(|| None::<DashboardStats>);
+    let error = use_state(|| None::<String>);
+
+    {
+        let stats = stats.clone();
+        let error = error.clone();
+        use_effect_with(state.goats.len(), move |_| {
+            spawn_local(async move {
+                match api.dashboard_stats().await {
+                    Ok(loaded) => {
+                        info!("Loaded dashboard stats as of {}", loaded.as_of);
+                        error.set(None);
+                        stats.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load dashboard stats: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        });
+    }
+
+    html! {
+        <div>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading KPIs: {}", err)}</p>
+            }
+            {
+                match &*stats {
+                    None => html! { <p>{"Loading KPIs..."}</p> },
+                    Some(stats) => html! {
+                        <div class="kpi-cards" style="display: flex; gap: 12px; flex-wrap: wrap;">
+                            <KpiCard id="herd_size" label="Herd size" period="last week"
+                                     value={format!("{:.0} goats", stats.herd_size.value)}
+                                     kpi={stats.herd_size.clone()} />
+                            <KpiCard id="herd_value" label="Herd value" period="last week"
+                                     value={format!("{:.0} {}", stats.herd_value.value, stats.currency)}
+                                     kpi={stats.herd_value.clone()} />
+                            <KpiCard id="milk_this_week" label="Milk this week" period="last week"
+                                     value={format!("{:.1} L", stats.milk_this_week.value)}
+                                     kpi={stats.milk_this_week.clone()} />
+                            <KpiCard id="expenses_this_month" label="Expenses this month"
+                                     period="last month" rising_is_good={false}
+                                     value={format!("{:.0} {}", stats.expenses_this_month.value, stats.currency)}
+                                     kpi={stats.expenses_this_month.clone()} />
+                        </div>
+                    },
+                }
+            }
+        </div>
+    }
+}
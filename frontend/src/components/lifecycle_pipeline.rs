@@ -0,0 +1,152 @@
+//! Lifecycle pipeline: the herd laid out as columns from Kid to Sold and
+//! Deceased, with each goat moved on through the stages its lifecycle
+//! allows (see `shared::lifecycle`).
+
+use crate::components::Spinner;
+use crate::services::{Api, use_api};
+use crate::store::use_read_only;
+use log::{error, info};
+use shared::lifecycle::{LifecyclePipeline as Pipeline, LifecycleStage, PipelineGoat, StageChange};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+/// Reloads the pipeline into `pipeline`, reporting failures in `error`.
+fn load_pipeline(
+    api: Api,
+    pipeline: UseStateHandle<Option<Pipeline>>,
+    error: UseStateHandle<Option<String>>,
+) {
+    spawn_local(async move {
+        match api.lifecycle_pipeline().await {
+            Ok(loaded) => pipeline.set(Some(loaded)),
+            Err(e) => {
+                error!("Failed to load lifecycle pipeline: {}", e);
+                error.set(Some(e.to_string()));
+            }
+        }
+    });
+}
+
+/// LifecyclePipeline component:
+/// Shows one column per lifecycle stage with its goats and their count,
+/// plus the goats not yet given a stage. Each goat's menu offers only the
+/// stages it may move to next; choosing one records the move (dated today)
+/// and reloads the pipeline. Locked in read-only mode.
+#[function_component(LifecyclePipeline)]
+pub fn lifecycle_pipeline() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let pipeline = use_state(|| None::<Pipeline>);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let api = api.clone();
+        let pipeline = pipeline.clone();
+        let error = error.clone();
+        move |_| {
+            load_pipeline(api, pipeline, error);
+            || {}
+        }
+    });
+
+    let on_move = {
+        let pipeline = pipeline.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |(goat_name, stage): (String, LifecycleStage)| {
+            let change = StageChange {
+                goat_name,
+                stage,
+                changed_on: None,
+                note: None,
+            };
+            let api = api.clone();
+            let pipeline = pipeline.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.change_lifecycle_stage(&change).await {
+                    Ok(_) => {
+                        info!("Moved {} to {:?}", change.goat_name, change.stage);
+                        error.set(None);
+                        message.set(Some(format!(
+                            "{} is now {}",
+                            change.goat_name,
+                            change.stage.label().to_lowercase()
+                        )));
+                        load_pipeline(api, pipeline, error);
+                    }
+                    Err(e) => {
+                        error!("Failed to move {}: {}", change.goat_name, e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let goat_card = |goat: &PipelineGoat| {
+        let name = goat.name.clone();
+        let on_move = on_move.clone();
+        let onchange = Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            let stage = LifecycleStage::from_str(&select.value());
+            select.set_value("");
+            if let Ok(stage) = stage {
+                on_move.emit((name.clone(), stage));
+            }
+        });
+        html! {
+            <li class="lifecycle-goat" style="margin-bottom: 6px;">
+                <span>{&goat.name}</span>
+                if !goat.next.is_empty() {
+                    {" "}
+                    <select class="lifecycle-move" {onchange} disabled={read_only}>
+                        <option value="" selected=true>{"Move to..."}</option>
+                        { for goat.next.iter().map(|stage| html! {
+                            <option value={LifecycleStage::to_str(stage).to_string()}>
+                                {stage.label()}
+                            </option>
+                        }) }
+                    </select>
+                }
+            </li>
+        }
+    };
+    let column = |title: &str, goats: &[PipelineGoat]| {
+        html! {
+            <div class="lifecycle-stage"
+                 style="flex: 1; min-width: 120px; background: #f5f5f5; padding: 8px;">
+                <h4 style="margin: 0 0 8px;">{format!("{} ({})", title, goats.len())}</h4>
+                <ul style="list-style: none; padding: 0; margin: 0;">
+                    { for goats.iter().map(goat_card) }
+                </ul>
+            </div>
+        }
+    };
+
+    html! {
+        <div id="lifecycle">
+            <h3>{"Lifecycle"}</h3>
+            if let Some(pipeline) = &*pipeline {
+                <div class="lifecycle-pipeline" style="display: flex; gap: 8px; overflow-x: auto;">
+                    if !pipeline.untracked.is_empty() {
+                        { column("Untracked", &pipeline.untracked) }
+                    }
+                    { for pipeline.stages.iter().map(|s| column(s.stage.label(), &s.goats)) }
+                </div>
+            } else {
+                <Spinner label="Loading lifecycle..." />
+            }
+            if let Some(msg) = &*message {
+                <p style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Lifecycle error: {}", err)}</p>
+            }
+        </div>
+    }
+}
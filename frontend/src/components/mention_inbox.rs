@@ -0,0 +1,137 @@
+//! Header inbox of the notifications for the worker using this device,
+//! such as being mentioned with `@Name` in a goat's note.
+
+use crate::components::goat_notes::stored_author;
+use crate::services::use_api;
+use crate::store::farm_timezone;
+use log::{error, info, warn};
+use shared::notifications::Notification;
+use shared::time::format_local;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+use wasm_bindgen_futures::spawn_local;
+use yew::platform::time::sleep;
+use yew::prelude::*;
+
+/// How often the inbox checks for new notifications.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// MentionInbox component:
+/// Shows how many unread notifications the worker has, taking the worker
+/// to be whoever last signed a note on this device; nothing is shown until
+/// someone has. Refreshes every minute. Clicking the count lists the
+/// notifications, and opening one jumps to its goat and marks it read.
+#[function_component(MentionInbox)]
+pub fn mention_inbox() -> Html {
+    let api = use_api();
+    let worker = use_state(stored_author);
+    let unread = use_state(Vec::<Notification>::new);
+    let open = use_state(|| false);
+
+    let load = {
+        let api = api.clone();
+        let worker = worker.clone();
+        let unread = unread.clone();
+        Callback::from(move |_: ()| {
+            // Pick up a name signed since the last check
+            let name = stored_author();
+            if name != *worker {
+                worker.set(name.clone());
+            }
+            if name.trim().is_empty() {
+                return;
+            }
+            let api = api.clone();
+            let unread = unread.clone();
+            spawn_local(async move {
+                match api.unread_notifications(&name).await {
+                    Ok(loaded) => {
+                        info!("{} has {} unread notifications", name, loaded.len());
+                        unread.set(loaded);
+                    }
+                    // Not every note author is a registered worker
+                    Err(e) => warn!("Could not load notifications for {}: {}", name, e),
+                }
+            });
+        })
+    };
+
+    use_effect_with((), {
+        let load = load.clone();
+        move |_| {
+            load.emit(());
+            let mounted = Rc::new(Cell::new(true));
+            {
+                let mounted = mounted.clone();
+                spawn_local(async move {
+                    loop {
+                        sleep(REFRESH_INTERVAL).await;
+                        if !mounted.get() {
+                            break;
+                        }
+                        load.emit(());
+                    }
+                });
+            }
+            move || mounted.set(false)
+        }
+    });
+
+    let on_toggle = {
+        let open = open.clone();
+        Callback::from(move |_: MouseEvent| open.set(!*open))
+    };
+
+    // Follows the link as usual, then marks the notification read
+    let on_open = {
+        let unread = unread.clone();
+        let open = open.clone();
+        Callback::from(move |id: i64| {
+            let api = api.clone();
+            let unread = unread.clone();
+            open.set(false);
+            spawn_local(async move {
+                match api.mark_notification_read(id).await {
+                    Ok(()) => {
+                        unread.set(unread.iter().filter(|n| n.id != id).cloned().collect());
+                    }
+                    Err(e) => error!("Failed to mark notification {} read: {}", id, e),
+                }
+            });
+        })
+    };
+
+    if worker.trim().is_empty() {
+        return html! {};
+    }
+    let timezone = farm_timezone();
+    html! {
+        <span class="mention-inbox" style="position: relative;">
+            <button type="button" name="notifications" onclick={on_toggle}
+                    title={format!("Notifications for {}", *worker)}>
+                {format!("🔔 {}", unread.len())}
+            </button>
+            if *open {
+                <ul style="position: absolute; right: 0; z-index: 10; min-width: 280px; margin: 4px 0; padding: 4px; list-style: none; background: white; color: #333; border: 1px solid #ccc;">
+                    if unread.is_empty() {
+                        <li>{"No new notifications."}</li>
+                    }
+                    { for unread.iter().map(|n| html! {
+                        <li data-notification={n.id.to_string()} style="padding: 4px;">
+                            <a href={n.link.clone()} onclick={on_open.reform({
+                                let id = n.id;
+                                move |_: MouseEvent| id
+                            })}>
+                                {&n.message}
+                            </a>
+                            <div style="color: #666; font-size: 12px;">
+                                {format_local(&n.created_at, timezone)}
+                            </div>
+                        </li>
+                    }) }
+                </ul>
+            }
+        </span>
+    }
+}
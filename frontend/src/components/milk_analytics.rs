@@ -0,0 +1,156 @@
+//! Milk yield analytics: lactation curves per doe and a comparison table
+//! to guide culling and breeding decisions.
+
+use crate::components::{ChartSeries, LineChart, SkeletonRows};
+use crate::services::use_api;
+use log::{error, info};
+use shared::milk::Lactation;
+use std::collections::BTreeMap;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// Line colours cycled across does on the curve chart.
+const PALETTE: [&str; 6] = ["#2a7", "#27c", "#d83", "#a3c", "#c33", "#777"];
+
+/// Does averaging below this fraction of the herd's average daily yield are
+/// highlighted as culling candidates.
+const LOW_YIELD_RATIO: f64 = 0.75;
+
+/// MilkAnalytics component:
+/// Charts the weekly lactation curve of each doe's latest lactation and
+/// lists every lactation with its peak, days in milk and totals. Latest
+/// lactations averaging well below the herd are shaded.
+#[function_component(MilkAnalytics)]
+pub fn milk_analytics() -> Html {
+    let api = use_api();
+    let lactations = use_state(|| None::<Vec<Lactation>>);
+    let error = use_state(|| None::<String>);
+
+    let load = {
+        let lactations = lactations.clone();
+        let error = error.clone();
+        Callback::from(move |_: ()| {
+            let api = api.clone();
+            let lactations = lactations.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.lactations(None).await {
+                    Ok(list) => {
+                        info!("Loaded {} lactations", list.len());
+                        error.set(None);
+                        lactations.set(Some(list));
+                    }
+                    Err(e) => {
+                        error!("Failed to load lactations: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    use_effect_with((), {
+        let load = load.clone();
+        move |_| {
+            load.emit(());
+            || {}
+        }
+    });
+
+    html! {
+        <div>
+            <h3>{"Milk Yield"}</h3>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading milk analytics: {}", err)}</p>
+            }
+            <button onclick={load.reform(|_| ())} style="margin-bottom: 10px;">{"Refresh"}</button>
+            {
+                match &*lactations {
+                    None => html! {
+                        <table><tbody><SkeletonRows rows={3} columns={7} /></tbody></table>
+                    },
+                    Some(list) if list.is_empty() => html! {
+                        <p>{"No milk records yet."}</p>
+                    },
+                    Some(list) => lactation_view(list),
+                }
+            }
+        </div>
+    }
+}
+
+/// Renders the curve chart and comparison table for `lactations`.
+fn lactation_view(lactations: &[Lactation]) -> Html {
+    // Latest lactation per doe; lactations arrive ordered by start within each doe
+    let latest: BTreeMap<&str, &Lactation> = lactations
+        .iter()
+        .map(|l| (l.doe_name.as_str(), l))
+        .collect();
+    let herd_average =
+        latest.values().map(|l| l.average_daily_yield).sum::<f64>() / latest.len() as f64;
+
+    let series: Vec<ChartSeries> = latest
+        .values()
+        .zip(PALETTE.iter().cycle())
+        .map(|(l, color)| {
+            let points = l
+                .curve
+                .iter()
+                .map(|p| (p.week as f64, p.average_daily_yield))
+                .collect();
+            ChartSeries {
+                markers: true,
+                ..ChartSeries::line(l.doe_name.clone(), *color, points)
+            }
+        })
+        .collect();
+
+    let mut rows: Vec<&Lactation> = lactations.iter().collect();
+    rows.sort_by(|a, b| b.peak_daily_yield.total_cmp(&a.peak_daily_yield));
+
+    html! {
+        <div>
+            <LineChart {series} x_unit="weeks" y_unit="L/day" label="Lactation curves by doe" />
+            <table style="border-collapse: collapse; width: 100%;">
+                <thead>
+                    <tr>
+                        <th>{"Doe"}</th>
+                        <th>{"Kidded"}</th>
+                        <th>{"Days in Milk"}</th>
+                        <th>{"Peak (L/day)"}</th>
+                        <th>{"Peak Day"}</th>
+                        <th>{"Total (L)"}</th>
+                        <th>{"Avg (L/day)"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    { for rows.iter().map(|l| {
+                        let is_latest = latest
+                            .get(l.doe_name.as_str())
+                            .is_some_and(|current| std::ptr::eq(*current, *l));
+                        let low = is_latest && l.average_daily_yield < herd_average * LOW_YIELD_RATIO;
+                        let style = if low { "background: #fdecea;" } else { "" };
+                        html! {
+                            <tr {style} title={low.then_some("Well below herd average yield")}>
+                                <td>{&l.doe_name}</td>
+                                <td>{l.kidded_on.as_deref().unwrap_or("unknown")}</td>
+                                <td>{l.days_in_milk}</td>
+                                <td>{format!("{:.2}", l.peak_daily_yield)}</td>
+                                <td>{l.peak_day}</td>
+                                <td>{format!("{:.1}", l.total_yield)}</td>
+                                <td>{format!("{:.2}", l.average_daily_yield)}</td>
+                            </tr>
+                        }
+                    }) }
+                </tbody>
+            </table>
+            <p style="font-size: 12px;">
+                {format!(
+                    "Herd average {:.2} L/day. Shaded does' current lactation averages below {:.0}% of it.",
+                    herd_average,
+                    LOW_YIELD_RATIO * 100.0
+                )}
+            </p>
+        </div>
+    }
+}
@@ -1,19 +1,151 @@
 //! The components module groups all reusable UI components for the goat dashboard app.
 //! This mod.rs makes components accessible when imported as `crate::components::*`.
 
+pub mod access_tokens;
 pub mod add_goat_components;
+pub mod alert_rules;
 pub mod add_goat_form;
+pub mod add_goat_wizard;
+pub mod barn_conditions;
+pub mod breeding_planner;
+pub mod budget_tracker;
+pub mod can;
+pub mod chart;
+pub mod culling_helper;
 pub mod dashboard;
+pub mod data_health;
+pub mod data_table;
+pub mod date_picker;
 pub mod delete_goat_form;
+pub mod diet_reassignment;
+pub mod disease_compliance;
+pub mod draft_bar;
+pub mod empty_state;
+pub mod error_boundary;
+pub mod export_templates;
+pub mod external_animals;
+pub mod farm_archive;
+pub mod feed_efficiency;
+pub mod field_history;
+pub mod file_link;
+pub mod goat_detail;
 pub mod goat_list;
+pub mod goat_notes;
+pub mod grazing_map;
+pub mod health_timeline;
+pub mod heat_tracker;
+pub mod import_wizard;
+pub mod incident_heatmap;
+pub mod inventory_list;
+pub mod jobs_panel;
+pub mod kpi_cards;
+pub mod lifecycle_pipeline;
+pub mod mention_inbox;
+pub mod milk_analytics;
+pub mod movement_log;
+pub mod number_field;
+pub mod nutrition_panel;
+pub mod pedigree_view;
+pub mod pens_view;
+pub mod permissions_editor;
+pub mod pricing_preview;
+pub mod quick_entry;
+pub mod quick_search;
+pub mod read_only_toggle;
+pub mod recent_activity;
+pub mod retention_panel;
+pub mod rotation_planner;
+pub mod service_records;
+pub mod sessions_panel;
+pub mod setup_wizard;
 pub mod sidebar;
+pub mod skeleton;
+pub mod soft_warnings;
+pub mod stock_expiry;
+pub mod tag_series_panel;
+pub mod tasks_list;
+pub mod traceability_records;
+pub mod transactions_list;
+pub mod undo_controls;
+pub mod unit_select;
+pub mod unsaved_guard;
 pub mod update_goat_form;
+pub mod vet_visits;
+pub mod voice_notes;
+pub mod water_panel;
+pub mod weigh_session;
+pub mod weight_log;
 
 // Optionally re-export for easier import elsewhere
+pub use access_tokens::AccessTokens;
 pub use add_goat_form::AddGoatForm;
+pub use add_goat_wizard::AddGoatWizard;
+pub use alert_rules::AlertRules;
+pub use barn_conditions::BarnConditions;
+pub use breeding_planner::BreedingPlanner;
+pub use budget_tracker::BudgetTracker;
+pub use can::Can;
+pub use chart::{ChartSeries, LineChart};
+pub use culling_helper::CullingHelper;
 pub use dashboard::Dashboard;
+pub use data_health::DataHealth;
+pub use data_table::{Column, DataTable, RowId, RowStyle, SortDirection, SortKey};
+pub use date_picker::DatePicker;
 pub use delete_goat_form::DeleteGoatsForm;
+pub use diet_reassignment::DietReassignment;
+pub use disease_compliance::DiseaseCompliance;
+pub use draft_bar::DraftBar;
+pub use empty_state::{EmptyAction, EmptyState};
+pub use error_boundary::ErrorBoundary;
+pub use export_templates::ExportTemplates;
+pub use external_animals::ExternalAnimals;
+pub use farm_archive::FarmArchive;
+pub use feed_efficiency::FeedEfficiencyPanel;
+pub use field_history::{FieldHistory, RecordField};
+pub use file_link::{FileAudio, FileImage, FileLink};
+pub use goat_detail::GoatDetail;
 pub use goat_list::GoatList;
+pub use goat_notes::GoatNotes;
+pub use grazing_map::GrazingMap;
+pub use health_timeline::HealthTimeline;
+pub use heat_tracker::HeatTracker;
+pub use import_wizard::ImportWizard;
+pub use incident_heatmap::IncidentHeatMap;
+pub use inventory_list::InventoryList;
+pub use jobs_panel::{JobsPanel, job_progress};
+pub use kpi_cards::{KpiCard, KpiCards};
+pub use lifecycle_pipeline::LifecyclePipeline;
+pub use mention_inbox::MentionInbox;
+pub use milk_analytics::MilkAnalytics;
+pub use movement_log::MovementLog;
+pub use number_field::{NumberField, Quantity};
+pub use nutrition_panel::NutritionPanel;
+pub use pedigree_view::PedigreeView;
+pub use pens_view::PensView;
+pub use permissions_editor::PermissionsEditor;
+pub use pricing_preview::PricingPreview;
+pub use quick_entry::QuickEntry;
+pub use quick_search::QuickSearch;
+pub use read_only_toggle::ReadOnlyToggle;
+pub use recent_activity::RecentActivity;
+pub use retention_panel::RetentionPanel;
+pub use rotation_planner::RotationPlanner;
+pub use service_records::ServiceRecords;
+pub use sessions_panel::SessionsPanel;
+pub use setup_wizard::SetupWizard;
 pub use sidebar::Sidebar;
+pub use skeleton::{SkeletonRows, Spinner};
+pub use soft_warnings::{SoftWarnings, use_soft_warnings};
+pub use stock_expiry::StockExpiry;
+pub use tag_series_panel::TagSeriesPanel;
+pub use tasks_list::TasksList;
+pub use traceability_records::TraceabilityRecords;
+pub use transactions_list::TransactionsList;
+pub use undo_controls::UndoControls;
+pub use unit_select::UnitSelect;
 pub use update_goat_form::UpdateGoatForm;
-
+pub use vet_visits::VetVisits;
+pub use voice_notes::VoiceNotes;
+pub use water_panel::WaterPanel;
+pub use weigh_session::WeighSession;
+pub use weight_log::WeightLog;
@@ -0,0 +1,302 @@
+//! Movement register: logging goats moved between farms, markets and
+//! buyers with their transport details, and listing the register by goat
+//! and period (see `shared::movements`).
+
+use crate::components::SkeletonRows;
+use crate::services::{Api, use_api};
+use crate::store::use_read_only;
+use log::{error, info};
+use shared::movements::{AnimalMovement, Place, PlaceKind};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+/// Reloads the register with the given filters, reporting failures in
+/// `error`. Blank filters are left out.
+fn load_movements(
+    api: Api,
+    filters: [String; 3],
+    movements: UseStateHandle<Option<Vec<AnimalMovement>>>,
+    error: UseStateHandle<Option<String>>,
+) {
+    spawn_local(async move {
+        let [goat, from, to] =
+            filters.map(|f| Some(f.trim().to_string()).filter(|f| !f.is_empty()));
+        match api
+            .movements(goat.as_deref(), from.as_deref(), to.as_deref())
+            .await
+        {
+            Ok(loaded) => {
+                info!("Loaded {} movements", loaded.len());
+                error.set(None);
+                movements.set(Some(loaded));
+            }
+            Err(e) => {
+                error!("Failed to load movements: {}", e);
+                error.set(Some(e.to_string()));
+            }
+        }
+    });
+}
+
+/// A select of every place kind, setting `field` to the chosen one.
+fn kind_select(class: &'static str, field: &UseStateHandle<PlaceKind>) -> Html {
+    let selected = **field;
+    let field = field.clone();
+    let onchange = Callback::from(move |e: Event| {
+        let select: HtmlSelectElement = e.target_unchecked_into();
+        if let Ok(kind) = PlaceKind::from_str(&select.value()) {
+            field.set(kind);
+        }
+    });
+    html! {
+        <select {class} {onchange}>
+            { for PlaceKind::ALL.iter().map(|kind| html! {
+                <option value={PlaceKind::to_str(kind)} selected={*kind == selected}>
+                    {PlaceKind::to_str(kind)}
+                </option>
+            }) }
+        </select>
+    }
+}
+
+/// MovementLog component:
+/// Logs a movement of one or more goats, from a place to another with the
+/// days they left and arrived, the transporter, vehicle and permit number,
+/// and lists the register, filtered by goat and by the period the goats
+/// left in. Logging is locked in read-only mode.
+#[function_component(MovementLog)]
+pub fn movement_log() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let movements = use_state(|| None::<Vec<AnimalMovement>>);
+    let goats = use_state(String::new);
+    let from_kind = use_state(|| PlaceKind::Farm);
+    let from_name = use_state(String::new);
+    let to_kind = use_state(|| PlaceKind::Market);
+    let to_name = use_state(String::new);
+    let departed_on = use_state(String::new);
+    let arrived_on = use_state(String::new);
+    let transporter = use_state(String::new);
+    let vehicle = use_state(String::new);
+    let permit_number = use_state(String::new);
+    let filter_goat = use_state(String::new);
+    let filter_from = use_state(String::new);
+    let filter_to = use_state(String::new);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let api = api.clone();
+        let movements = movements.clone();
+        let error = error.clone();
+        move |_| {
+            load_movements(api, Default::default(), movements, error);
+            || {}
+        }
+    });
+
+    let on_input = |field: &UseStateHandle<String>| {
+        let field = field.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            field.set(input.value());
+        })
+    };
+    let filters = {
+        let filter_goat = filter_goat.clone();
+        let filter_from = filter_from.clone();
+        let filter_to = filter_to.clone();
+        move || {
+            [
+                (*filter_goat).clone(),
+                (*filter_from).clone(),
+                (*filter_to).clone(),
+            ]
+        }
+    };
+
+    let on_filter = {
+        let api = api.clone();
+        let movements = movements.clone();
+        let error = error.clone();
+        let filters = filters.clone();
+        Callback::from(move |_: MouseEvent| {
+            load_movements(api.clone(), filters(), movements.clone(), error.clone());
+        })
+    };
+
+    let on_log = {
+        let api = api.clone();
+        let movements = movements.clone();
+        let goats = goats.clone();
+        let from_kind = from_kind.clone();
+        let from_name = from_name.clone();
+        let to_kind = to_kind.clone();
+        let to_name = to_name.clone();
+        let departed_on = departed_on.clone();
+        let arrived_on = arrived_on.clone();
+        let transporter = transporter.clone();
+        let vehicle = vehicle.clone();
+        let permit_number = permit_number.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let optional = |field: &UseStateHandle<String>| {
+                Some(field.trim().to_string()).filter(|v| !v.is_empty())
+            };
+            let movement = AnimalMovement {
+                id: None,
+                tags: Vec::new(),
+                goats: goats
+                    .split(',')
+                    .map(|g| g.trim().to_string())
+                    .filter(|g| !g.is_empty())
+                    .collect(),
+                from: Place {
+                    kind: *from_kind,
+                    name: from_name.trim().to_string(),
+                },
+                to: Place {
+                    kind: *to_kind,
+                    name: to_name.trim().to_string(),
+                },
+                departed_on: (*departed_on).clone(),
+                arrived_on: optional(&arrived_on),
+                transporter: optional(&transporter),
+                vehicle: optional(&vehicle),
+                permit_number: optional(&permit_number),
+                notes: None,
+            };
+            if let Err(e) = movement.validate() {
+                error.set(Some(e));
+                return;
+            }
+            if movement.departed_on.is_empty() {
+                error.set(Some("Enter the day the goats left".into()));
+                return;
+            }
+            let api = api.clone();
+            let movements = movements.clone();
+            let fields = [
+                goats.clone(),
+                departed_on.clone(),
+                arrived_on.clone(),
+                permit_number.clone(),
+            ];
+            let filters = filters();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.add_movement(&movement).await {
+                    Ok(stored) => {
+                        info!("Logged movement {:?}", stored.id);
+                        fields.iter().for_each(|f| f.set(String::new()));
+                        message.set(Some(format!(
+                            "Logged the movement of {} goat(s): {}",
+                            stored.goats.len(),
+                            stored.summary()
+                        )));
+                        load_movements(api, filters, movements, error);
+                    }
+                    Err(e) => {
+                        error!("Failed to log movement: {}", e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <div id="movement-log">
+            <h3>{"Movement Register"}</h3>
+            <p>
+                <input class="movement-goats" placeholder="Goats, comma separated"
+                       value={(*goats).clone()} oninput={on_input(&goats)} />
+                {" From "}
+                {kind_select("movement-from-kind", &from_kind)}
+                <input class="movement-from" placeholder="Place" value={(*from_name).clone()}
+                       oninput={on_input(&from_name)} />
+                {" To "}
+                {kind_select("movement-to-kind", &to_kind)}
+                <input class="movement-to" placeholder="Place" value={(*to_name).clone()}
+                       oninput={on_input(&to_name)} />
+            </p>
+            <p>
+                {"Left "}
+                <input class="movement-departed" type="date" value={(*departed_on).clone()}
+                       oninput={on_input(&departed_on)} />
+                {" Arrived "}
+                <input class="movement-arrived" type="date" value={(*arrived_on).clone()}
+                       oninput={on_input(&arrived_on)} />
+                {" "}
+                <input class="movement-transporter" placeholder="Transporter"
+                       value={(*transporter).clone()} oninput={on_input(&transporter)} />
+                {" "}
+                <input class="movement-vehicle" placeholder="Vehicle"
+                       value={(*vehicle).clone()} oninput={on_input(&vehicle)} />
+                {" "}
+                <input class="movement-permit" placeholder="Permit number"
+                       value={(*permit_number).clone()} oninput={on_input(&permit_number)} />
+                {" "}
+                <button class="log-movement" onclick={on_log} disabled={read_only}>
+                    {"Log movement"}
+                </button>
+            </p>
+            <p>
+                <input class="movement-filter-goat" placeholder="Goat"
+                       value={(*filter_goat).clone()} oninput={on_input(&filter_goat)} />
+                {" Left from "}
+                <input class="movement-filter-from" type="date" value={(*filter_from).clone()}
+                       oninput={on_input(&filter_from)} />
+                {" to "}
+                <input class="movement-filter-to" type="date" value={(*filter_to).clone()}
+                       oninput={on_input(&filter_to)} />
+                {" "}
+                <button class="filter-movements" onclick={on_filter}>{"Filter"}</button>
+            </p>
+            if let Some(msg) = &*message {
+                <p class="movement-message" style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error: {}", err)}</p>
+            }
+            {
+                match &*movements {
+                    None => html! {
+                        <table><tbody><SkeletonRows rows={2} columns={5} /></tbody></table>
+                    },
+                    Some(list) if list.is_empty() => html! {
+                        <p>{"No movements logged."}</p>
+                    },
+                    Some(list) => html! {
+                        <table class="movements" style="border-collapse: collapse; width: 100%;">
+                            <thead>
+                                <tr>
+                                    <th>{"Left"}</th>
+                                    <th>{"Arrived"}</th>
+                                    <th>{"Goats"}</th>
+                                    <th>{"Route"}</th>
+                                    <th>{"Permit"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                { for list.iter().map(|m| html! {
+                                    <tr class="movement-row">
+                                        <td>{&m.departed_on}</td>
+                                        <td>{m.arrived_on.clone().unwrap_or_default()}</td>
+                                        <td>{m.goats_label()}</td>
+                                        <td class="movement-summary">{m.summary()}</td>
+                                        <td>{m.permit_number.clone().unwrap_or_default()}</td>
+                                    </tr>
+                                }) }
+                            </tbody>
+                        </table>
+                    },
+                }
+            }
+        </div>
+    }
+}
@@ -0,0 +1,159 @@
+//! Numeric input that shows weights and money in the user's units (see
+//! `crate::store::UnitsStore`) while forms only ever see stored values:
+//! kilograms and the farm's base currency.
+
+use crate::store::UnitsStore;
+use shared::units::{WeightUnit, currency_symbol, format_decimal, parse_decimal};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// What a `NumberField` holds, which decides its unit.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Quantity {
+    /// Stored in kilograms, entered in the preferred weight unit.
+    Weight,
+    /// An amount in the farm's base currency.
+    Money,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct NumberFieldProps {
+    pub name: AttrValue,
+    pub quantity: Quantity,
+    /// The stored value, or `None` while the field is empty.
+    pub value: Option<f64>,
+    /// Smallest allowed value, in the same units as `value`.
+    #[prop_or_default]
+    pub min: Option<f64>,
+    /// Largest allowed value, in the same units as `value`.
+    #[prop_or_default]
+    pub max: Option<f64>,
+    /// Called on every edit with the stored value, clamped to `min` and
+    /// `max`, or `None` if the text is empty or not a number.
+    pub onchange: Callback<Option<f64>>,
+    #[prop_or_default]
+    pub disabled: bool,
+    /// Typical range, in the same units as `value`, shown as the
+    /// placeholder, e.g. a breed's usual adult weight.
+    #[prop_or_default]
+    pub typical: Option<(f64, f64)>,
+}
+
+/// Converts between the stored value and the text in the field.
+#[derive(Clone, Copy, PartialEq)]
+struct Format {
+    quantity: Quantity,
+    weight: WeightUnit,
+    decimal_separator: char,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl Format {
+    fn parse(&self, text: &str) -> Option<f64> {
+        let shown = parse_decimal(text, self.decimal_separator)?;
+        let value = match self.quantity {
+            Quantity::Weight => self.weight.to_kg(shown),
+            Quantity::Money => shown,
+        };
+        let value = self.min.map_or(value, |min| value.max(min));
+        Some(self.max.map_or(value, |max| value.min(max)))
+    }
+
+    fn show(&self, value: Option<f64>) -> String {
+        let Some(value) = value else {
+            return String::new();
+        };
+        let shown = match self.quantity {
+            Quantity::Weight => self.weight.from_kg(value),
+            Quantity::Money => value,
+        };
+        format_decimal(shown, 2, self.decimal_separator)
+    }
+}
+
+/// NumberField component:
+/// A text input accepting the locale's decimal separator, labelled with the
+/// preferred weight unit or the currency symbol. Edits are emitted as
+/// stored values right away; leaving the field rewrites its text to the
+/// clamped value. The text follows `value` when it changes from outside,
+/// e.g. a form reset, and when the preferred unit changes.
+#[function_component(NumberField)]
+pub fn number_field(props: &NumberFieldProps) -> Html {
+    let (units, _) = use_store::<UnitsStore>();
+    let format = Format {
+        quantity: props.quantity,
+        weight: units.weight,
+        decimal_separator: units.decimal_separator,
+        min: props.min,
+        max: props.max,
+    };
+    let text = use_state(|| format.show(props.value));
+
+    {
+        let text = text.clone();
+        use_effect_with((props.value, format), move |(value, format)| {
+            if format.parse(&text) != *value {
+                text.set(format.show(*value));
+            }
+        });
+    }
+
+    let oninput = {
+        let text = text.clone();
+        let onchange = props.onchange.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                let raw = input.value();
+                onchange.emit(format.parse(&raw));
+                text.set(raw);
+            }
+        })
+    };
+    let onblur = {
+        let text = text.clone();
+        Callback::from(move |_: FocusEvent| {
+            if let Some(value) = format.parse(&text) {
+                text.set(format.show(Some(value)));
+            }
+        })
+    };
+
+    let placeholder = props
+        .typical
+        .map(|(low, high)| format!("{}–{}", format.show(Some(low)), format.show(Some(high))));
+    let (prefix, suffix) = match props.quantity {
+        Quantity::Weight => (None, Some(units.weight.symbol())),
+        Quantity::Money => (Some(currency_symbol(&units.currency).to_string()), None),
+    };
+    html! {
+        <span class="number-field">
+            if let Some(prefix) = prefix {
+                <span class="unit">{prefix}{" "}</span>
+            }
+            <input
+                type="text"
+                inputmode="decimal"
+                name={props.name.clone()}
+                value={(*text).clone()}
+                {placeholder}
+                disabled={props.disabled}
+                {oninput}
+                {onblur}
+            />
+            if let Some(suffix) = suffix {
+                <span class="unit">{" "}{suffix}</span>
+            }
+        </span>
+    }
+}
+
+/// Callback keeping a `NumberField`'s value in a form's text state, as
+/// forms keep every field as typed text. No value leaves the text empty.
+pub fn text_setter(field: &UseStateHandle<String>) -> Callback<Option<f64>> {
+    let field = field.clone();
+    Callback::from(move |value: Option<f64>| {
+        field.set(value.map(|v| v.to_string()).unwrap_or_default())
+    })
+}
@@ -0,0 +1,328 @@
+//! Nutrition check: each goat's daily dry matter, protein and energy needs
+//! against what its feed plan supplies (see `shared::nutrition`), with the
+//! goats whose plan falls short flagged, and the cheapest ration meeting a
+//! goat's needs from the feed in stock (see `GET /nutrition/ration`).
+
+use crate::services::use_api;
+use crate::store::{GoatStore, use_read_only};
+use log::{error, info};
+use shared::lifecycle::LifecycleStage;
+use shared::nutrition::{
+    Nutrient, NutritionCheck, NutritionStage, RationPlan, RationRequest, check_nutrition,
+};
+use shared::{Goat, GoatUpdate};
+use std::collections::HashMap;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// Formats an amount of `nutrient`, e.g. "12.40 MJ".
+fn amount(nutrient: Nutrient, value: f64) -> String {
+    let decimals = if nutrient == Nutrient::CrudeProtein {
+        0
+    } else {
+        2
+    };
+    format!("{:.*} {}", decimals, value, nutrient.unit())
+}
+
+/// The goats whose plan falls short, with the nutrients it lacks, e.g.
+/// "Rani: energy, crude protein".
+fn deficit_summary(checks: &[(&Goat, NutritionCheck)]) -> Vec<String> {
+    checks
+        .iter()
+        .filter_map(|(goat, check)| {
+            let short: Vec<String> = check
+                .deficits()
+                .iter()
+                .map(|(nutrient, _)| nutrient.label().to_lowercase())
+                .collect();
+            (!short.is_empty()).then(|| format!("{}: {}", goat.name, short.join(", ")))
+        })
+        .collect()
+}
+
+/// NutritionPanel component:
+/// Checks every goat in the store at the stage its lifecycle suggests
+/// (growing for kids and growers, lactating for milkers) and lists those
+/// whose feed plan is short of a nutrient. Picking a goat shows its needs,
+/// supply and shortfalls; its stage can be changed, e.g. to pregnant, to
+/// see how the needs move. Diets without amounts are reported rather than
+/// checked.
+///
+/// "Formulate ration" asks for the cheapest ration meeting the picked goat's
+/// needs with stock lasting the given goats and days; "Apply as feed plan"
+/// then sets it as the goat's diet, locked in read-only mode.
+#[function_component(NutritionPanel)]
+pub fn nutrition_panel() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let (state, dispatch) = use_store::<GoatStore>();
+    let stages = use_state(HashMap::<i64, LifecycleStage>::new);
+    let selected = use_state(|| None::<i64>);
+    let stage = use_state(|| None::<NutritionStage>);
+    let ration_goats = use_state(|| 1u32);
+    let ration_days = use_state(|| 30u32);
+    let plan = use_state(|| None::<RationPlan>);
+    let message = use_state(|| None::<String>);
+    let ration_error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let api = api.clone();
+        let stages = stages.clone();
+        move |_| {
+            spawn_local(async move {
+                match api.lifecycle_pipeline().await {
+                    Ok(pipeline) => {
+                        info!("Loaded lifecycle stages for nutrition checks");
+                        stages.set(
+                            pipeline
+                                .stages
+                                .iter()
+                                .flat_map(|s| s.goats.iter().map(|g| (g.id, s.stage)))
+                                .collect(),
+                        );
+                    }
+                    // Every goat is then checked for maintenance
+                    Err(e) => error!("Failed to load lifecycle stages: {}", e),
+                }
+            });
+            || {}
+        }
+    });
+
+    let stage_of = |goat: &Goat| NutritionStage::for_lifecycle(stages.get(&goat.id).copied());
+    let checks: Vec<(&Goat, NutritionCheck)> = state
+        .goats
+        .iter()
+        .map(|goat| (goat, check_nutrition(goat, stage_of(goat))))
+        .collect();
+    let short = deficit_summary(&checks);
+    let unread = checks.iter().filter(|(_, c)| c.supply.is_err()).count();
+
+    // A formulated ration is for one goat at one stage
+    let reset_ration = {
+        let plan = plan.clone();
+        let message = message.clone();
+        let ration_error = ration_error.clone();
+        move || {
+            plan.set(None);
+            message.set(None);
+            ration_error.set(None);
+        }
+    };
+    let on_goat = {
+        let selected = selected.clone();
+        let stage = stage.clone();
+        let reset_ration = reset_ration.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            selected.set(select.value().parse().ok());
+            stage.set(None);
+            reset_ration();
+        })
+    };
+    let on_stage = {
+        let stage = stage.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            stage.set(NutritionStage::from_str(&select.value()).ok());
+            reset_ration();
+        })
+    };
+    let on_count = |count: &UseStateHandle<u32>| {
+        let count = count.clone();
+        let plan = plan.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            count.set(input.value().trim().parse().unwrap_or(0));
+            plan.set(None);
+        })
+    };
+
+    let goat = selected.and_then(|id| state.goats.iter().find(|g| g.id == id));
+    let check = goat.map(|goat| check_nutrition(goat, stage.unwrap_or_else(|| stage_of(goat))));
+
+    let on_formulate = {
+        let api = api.clone();
+        let plan = plan.clone();
+        let message = message.clone();
+        let ration_error = ration_error.clone();
+        let request = check
+            .as_ref()
+            .map(|c| RationRequest::new(c.requirement, *ration_goats, *ration_days));
+        Callback::from(move |_: MouseEvent| {
+            let Some(request) = request.clone() else {
+                return;
+            };
+            if let Err(e) = request.validate() {
+                ration_error.set(Some(e));
+                return;
+            }
+            let api = api.clone();
+            let plan = plan.clone();
+            let message = message.clone();
+            let ration_error = ration_error.clone();
+            spawn_local(async move {
+                match api.formulate_ration(&request).await {
+                    Ok(formulated) => {
+                        info!("Formulated a ration of {} feeds", formulated.items.len());
+                        ration_error.set(None);
+                        message.set(None);
+                        plan.set(Some(formulated));
+                    }
+                    Err(e) => {
+                        error!("Failed to formulate a ration: {}", e);
+                        plan.set(None);
+                        ration_error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+    let on_apply = {
+        let plan = plan.clone();
+        let message = message.clone();
+        let ration_error = ration_error.clone();
+        let goat = goat.map(|g| (g.id, g.name.clone()));
+        Callback::from(move |_: MouseEvent| {
+            let (Some((id, name)), Some(formulated)) = (goat.clone(), (*plan).clone()) else {
+                return;
+            };
+            let diet = formulated.diet();
+            let update = GoatUpdate {
+                diet: Some(diet.clone()),
+                ..GoatUpdate::default()
+            };
+            let plan = plan.clone();
+            let message = message.clone();
+            let ration_error = ration_error.clone();
+            GoatStore::patch_goat_async(
+                api.clone(),
+                dispatch.clone(),
+                id,
+                update,
+                Callback::from(move |res: Result<Goat, _>| match res {
+                    Ok(_) => {
+                        info!("Applied a formulated ration to {}", name);
+                        plan.set(None);
+                        message.set(Some(format!("{} now eats {}", name, diet)));
+                    }
+                    Err(e) => ration_error.set(Some(format!(
+                        "Could not apply the ration to {}: {}",
+                        name, e
+                    ))),
+                }),
+            );
+        })
+    };
+
+    html! {
+        <div id="nutrition">
+            <h3>{"Nutrition"}</h3>
+            if short.is_empty() {
+                <p class="nutrition-ok">{"No feed plan falls short."}</p>
+            } else {
+                <ul class="nutrition-deficits" style="color: #b71c1c;">
+                    { for short.iter().map(|line| html! { <li>{line}</li> }) }
+                </ul>
+            }
+            if unread > 0 {
+                <p style="font-size: 12px; color: #666;">
+                    {format!(
+                        "{} {} a diet without amounts; write it as e.g. \"hay 1.2 kg, concentrate 300 g\" to check it.",
+                        unread,
+                        if unread == 1 { "goat has" } else { "goats have" }
+                    )}
+                </p>
+            }
+            <p>
+                <select name="nutrition_goat" onchange={on_goat}>
+                    <option value="" selected={goat.is_none()}>{"Choose a goat"}</option>
+                    { for state.goats.iter().map(|g| html! {
+                        <option value={g.id.to_string()} selected={*selected == Some(g.id)}>
+                            {&g.name}
+                        </option>
+                    }) }
+                </select>
+                if let Some(check) = &check {
+                    {" "}
+                    <select name="nutrition_stage" onchange={on_stage}>
+                        { for NutritionStage::ALL.iter().map(|s| html! {
+                            <option value={NutritionStage::to_str(s)} selected={check.stage == *s}>
+                                {NutritionStage::to_str(s)}
+                            </option>
+                        }) }
+                    </select>
+                }
+            </p>
+            if let Some(check) = check {
+                <table class="nutrition-check">
+                    <tr>
+                        <th>{"Nutrient"}</th><th>{"Needed a day"}</th>
+                        <th>{"Supplied"}</th><th>{"Short by"}</th>
+                    </tr>
+                    { for Nutrient::ALL.iter().map(|n| {
+                        let gap = check.deficits().into_iter().find(|(d, _)| d == n);
+                        let style = if gap.is_some() { "color: #b71c1c;" } else { "" };
+                        html! {
+                            <tr class={classes!(gap.is_some().then_some("deficit"))} {style}>
+                                <td>{n.label()}</td>
+                                <td>{amount(*n, n.amount(&check.requirement))}</td>
+                                <td>{check.supply.as_ref().map_or("–".to_string(), |s| amount(*n, n.amount(s)))}</td>
+                                <td>{gap.map_or(String::new(), |(_, short)| amount(*n, short))}</td>
+                            </tr>
+                        }
+                    }) }
+                </table>
+                if let Err(e) = &check.supply {
+                    <p class="feed-plan-error" style="color: red;">{format!("Feed plan: {}", e)}</p>
+                }
+                <p class="ration-request" style="font-size: 12px;">
+                    {"Stock must last "}
+                    <input name="ration_goats" type="number" min="1" style="width: 4em;"
+                           value={ration_goats.to_string()} onchange={on_count(&ration_goats)} />
+                    {" goats for "}
+                    <input name="ration_days" type="number" min="1" style="width: 4em;"
+                           value={ration_days.to_string()} onchange={on_count(&ration_days)} />
+                    {" days "}
+                    <button class="formulate-ration" onclick={on_formulate}>
+                        {"Formulate ration"}
+                    </button>
+                </p>
+            }
+            if let Some(plan) = &*plan {
+                <table class="ration-plan">
+                    <tr><th>{"Feed"}</th><th>{"kg a day"}</th><th>{"Cost a day"}</th></tr>
+                    { for plan.items.iter().map(|item| html! {
+                        <tr>
+                            <td>{&item.feed}</td>
+                            <td>{format!("{:.2}", item.kg_per_day)}</td>
+                            <td>{format!("{:.2}", item.cost_per_day)}</td>
+                        </tr>
+                    }) }
+                    <tr>
+                        <th>{"Total"}</th><th></th>
+                        <th>{format!("{:.2}", plan.cost_per_day)}</th>
+                    </tr>
+                </table>
+                if !plan.skipped.is_empty() {
+                    <p class="ration-skipped" style="font-size: 12px; color: #666;">
+                        {format!("Not used: {}", plan.skipped.join("; "))}
+                    </p>
+                }
+                <button class="apply-ration" onclick={on_apply}
+                        disabled={read_only || plan.items.is_empty()}>
+                    {"Apply as feed plan"}
+                </button>
+            }
+            if let Some(msg) = &*message {
+                <p style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*ration_error {
+                <p style="color: red;">{format!("Ration error: {}", err)}</p>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,232 @@
+//! "Pedigree" panel showing a goat's ancestors with their bloodline and
+//! genetic trait tags, the tags it inherits from them, and an editor for
+//! its own tags.
+
+use crate::components::Spinner;
+use crate::services::use_api;
+use crate::store::{GoatStore, use_read_only};
+use log::{error, info};
+use shared::breeding::{GeneticTags, PedigreeNode, SUGGESTED_TAGS, inherited_tags};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// Splits comma-separated tags as typed, dropping blank entries.
+pub fn parse_tag_list(input: &str) -> Vec<String> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// A goat's name, marked if it is an external animal, followed by its tags
+/// as small badges.
+fn tagged_name(node: &PedigreeNode) -> Html {
+    html! {
+        <>
+            <strong>{&node.name}</strong>
+            if node.external {
+                <em class="external-parent" style="margin-left: 4px;">{"(external)"}</em>
+            }
+            { for node.tags.iter().map(|tag| html! {
+                <span class="genetic-tag"
+                      style="margin-left: 6px; padding: 0 6px; border-radius: 8px; background: #e8f4ea; font-size: 11px;">
+                    {tag}
+                </span>
+            }) }
+        </>
+    }
+}
+
+/// Renders `node`'s parents and their ancestors as nested lists.
+fn ancestors(node: &PedigreeNode) -> Html {
+    if node.sire.is_none() && node.dam.is_none() {
+        return html! {};
+    }
+    let parent = |label: &str, parent: &Option<Box<PedigreeNode>>| match parent {
+        Some(p) => html! { <li>{format!("{}: ", label)}{ tagged_name(p) }{ ancestors(p) }</li> },
+        None => html! { <li>{format!("{}: unknown", label)}</li> },
+    };
+    html! {
+        <ul class="pedigree">
+            { parent("Sire", &node.sire) }
+            { parent("Dam", &node.dam) }
+        </ul>
+    }
+}
+
+/// PedigreeView component:
+/// Loads the chosen goat's pedigree tree and shows each ancestor with its
+/// tags. Below the tree, every tag carried by an ancestor is listed with
+/// the share of the goat's genes expected to come from tagged lines and
+/// the ancestors carrying it, largest share first. The goat's own tags can
+/// be edited as a comma-separated list unless the farm is read-only.
+#[function_component(PedigreeView)]
+pub fn pedigree_view() -> Html {
+    let (state, _) = use_store::<GoatStore>();
+    let api = use_api();
+    let read_only = use_read_only();
+    let goat = use_state(String::new);
+    let tree = use_state(|| None::<PedigreeNode>);
+    let tags_input = use_state(String::new);
+    let loading = use_state(|| false);
+    let saving = use_state(|| false);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((*goat).clone(), {
+        let api = api.clone();
+        let tree = tree.clone();
+        let tags_input = tags_input.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+        move |name: &String| {
+            let name = name.clone();
+            tree.set(None);
+            error.set(None);
+            if !name.is_empty() {
+                loading.set(true);
+                spawn_local(async move {
+                    match api.pedigree_tree(&name).await {
+                        Ok(t) => {
+                            info!("Loaded pedigree tree for {}", name);
+                            tags_input.set(t.tags.join(", "));
+                            tree.set(Some(t));
+                        }
+                        Err(e) => {
+                            error!("Failed to load pedigree for {}: {}", name, e);
+                            error.set(Some(e.to_string()));
+                        }
+                    }
+                    loading.set(false);
+                });
+            }
+            || {}
+        }
+    });
+
+    let on_goat_change = {
+        let goat = goat.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                goat.set(select.value());
+            }
+        })
+    };
+
+    let on_tags_input = {
+        let tags_input = tags_input.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                tags_input.set(input.value());
+            }
+        })
+    };
+
+    let on_save = {
+        let goat = goat.clone();
+        let tree = tree.clone();
+        let tags_input = tags_input.clone();
+        let saving = saving.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let api = api.clone();
+            let tree = tree.clone();
+            let tags_input = tags_input.clone();
+            let saving = saving.clone();
+            let error = error.clone();
+            let tags = GeneticTags {
+                goat_name: (*goat).clone(),
+                tags: parse_tag_list(&tags_input),
+            };
+            saving.set(true);
+            error.set(None);
+            spawn_local(async move {
+                match api.save_genetic_tags(&tags).await {
+                    Ok(stored) => {
+                        info!("Saved {} tags for {}", stored.tags.len(), stored.goat_name);
+                        tags_input.set(stored.tags.join(", "));
+                        if let Some(t) = &*tree {
+                            tree.set(Some(PedigreeNode {
+                                tags: stored.tags,
+                                ..t.clone()
+                            }));
+                        }
+                    }
+                    Err(e) => {
+                        error!("Failed to save tags for {}: {}", tags.goat_name, e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+                saving.set(false);
+            });
+        })
+    };
+
+    html! {
+        <div>
+            <h3>{"Pedigree"}</h3>
+            <label>{"Goat: "}
+                <select name="pedigree_goat" onchange={on_goat_change}>
+                    <option value="" selected={goat.is_empty()}>{"Choose a goat"}</option>
+                    { for state.goats.iter().map(|g| html! {
+                        <option value={g.name.clone()} selected={*goat == g.name}>{&g.name}</option>
+                    }) }
+                </select>
+            </label>
+            if *loading {
+                { " " }<Spinner label="Loading pedigree..." />
+            }
+
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error: {}", err)}</p>
+            }
+
+            if let Some(t) = &*tree {
+                <p>{ tagged_name(t) }</p>
+                { ancestors(t) }
+                if t.sire.is_none() && t.dam.is_none() {
+                    <p>{"No parents recorded."}</p>
+                }
+                {{
+                    let inherited = inherited_tags(t);
+                    if inherited.is_empty() {
+                        html! {}
+                    } else {
+                        html! {
+                            <>
+                                <h4>{"Inherited traits"}</h4>
+                                <ul class="inherited-tags">
+                                    { for inherited.iter().map(|i| html! {
+                                        <li key={i.tag.clone()}>
+                                            {format!(
+                                                "{}: {:.0}% of genes from tagged lines ({})",
+                                                i.tag,
+                                                i.share * 100.0,
+                                                i.carriers.join(", ")
+                                            )}
+                                        </li>
+                                    }) }
+                                </ul>
+                            </>
+                        }
+                    }
+                }}
+                if !read_only {
+                    <label>{"Tags: "}
+                        <input type="text" name="genetic_tags" value={(*tags_input).clone()}
+                               placeholder={format!("e.g. {}", SUGGESTED_TAGS[..2].join(", "))}
+                               oninput={on_tags_input} />
+                    </label>
+                    { " " }
+                    <button onclick={on_save} disabled={*saving}>{"Save tags"}</button>
+                    <p style="font-size: 12px;">
+                        {format!("Separate tags with commas. Common tags: {}.", SUGGESTED_TAGS.join(", "))}
+                    </p>
+                }
+            }
+        </div>
+    }
+}
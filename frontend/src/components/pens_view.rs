@@ -0,0 +1,141 @@
+//! Pens view: how full each pen and field is against its capacity and the
+//! recommended stocking density for its area.
+
+use crate::components::SkeletonRows;
+use crate::services::use_api;
+use log::{error, info};
+use shared::spaces::SpaceOccupancy;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// Bar colour for an occupancy percentage: green with room to spare, amber
+/// when nearly full, red when over.
+fn occupancy_color(percent: f64) -> &'static str {
+    if percent > 100.0 {
+        "#d33"
+    } else if percent >= 90.0 {
+        "#e90"
+    } else {
+        "#2a7"
+    }
+}
+
+/// PensView component:
+/// Loads every space's occupancy and lists the goats in it against its
+/// capacity and area, with a bar showing the occupancy percentage. Overstocked
+/// spaces are shaded and their warnings listed under the table.
+#[function_component(PensView)]
+pub fn pens_view() -> Html {
+    let api = use_api();
+    let spaces = use_state(|| None::<Vec<SpaceOccupancy>>);
+    let error = use_state(|| None::<String>);
+
+    let load = {
+        let spaces = spaces.clone();
+        let error = error.clone();
+        Callback::from(move |_: ()| {
+            let api = api.clone();
+            let spaces = spaces.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.space_occupancy().await {
+                    Ok(loaded) => {
+                        info!("Loaded occupancy of {} spaces", loaded.len());
+                        error.set(None);
+                        spaces.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load pen occupancy: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    use_effect_with((), {
+        let load = load.clone();
+        move |_| {
+            load.emit(());
+            || {}
+        }
+    });
+
+    html! {
+        <div>
+            <h3>{"Pens"}</h3>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading pens: {}", err)}</p>
+            }
+            <button onclick={load.reform(|_| ())} style="margin-bottom: 10px;">{"Refresh"}</button>
+            {
+                match &*spaces {
+                    None => html! {
+                        <table><tbody><SkeletonRows rows={3} columns={5} /></tbody></table>
+                    },
+                    Some(spaces) if spaces.is_empty() => html! {
+                        <p>{"No pens yet. Add pens with a capacity or area to plan stocking."}</p>
+                    },
+                    Some(spaces) => occupancy_table(spaces),
+                }
+            }
+        </div>
+    }
+}
+
+/// Renders one row per space and the warnings of overstocked ones.
+fn occupancy_table(spaces: &[SpaceOccupancy]) -> Html {
+    let warnings: Vec<&String> = spaces.iter().flat_map(|s| &s.warnings).collect();
+    html! {
+        <>
+            <table style="border-collapse: collapse; width: 100%;">
+                <thead>
+                    <tr>
+                        <th>{"Pen"}</th>
+                        <th>{"Goats"}</th>
+                        <th>{"Capacity"}</th>
+                        <th>{"Area (m²)"}</th>
+                        <th>{"Occupancy"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    { for spaces.iter().map(|space| {
+                        let style = if space.warnings.is_empty() { "" } else { "background: #fdecea;" };
+                        let capacity = match (space.capacity, space.recommended_max) {
+                            (Some(capacity), _) => capacity.to_string(),
+                            (None, Some(max)) => format!("{} (by area)", max),
+                            (None, None) => "–".to_string(),
+                        };
+                        html! {
+                            <tr data-name={space.name.clone()} {style}>
+                                <td>{&space.name}</td>
+                                <td>{space.goats}</td>
+                                <td>{capacity}</td>
+                                <td>{space.area_m2.map_or("–".to_string(), |a| format!("{:.0}", a))}</td>
+                                <td class="occupancy">
+                                    if let Some(percent) = space.occupancy_percent {
+                                        <span style="display: inline-block; width: 100px; background: #eee; margin-right: 6px;">
+                                            <span style={format!(
+                                                "display: block; height: 8px; width: {:.0}%; background: {};",
+                                                percent.min(100.0),
+                                                occupancy_color(percent)
+                                            )}></span>
+                                        </span>
+                                        {format!("{:.0}%", percent)}
+                                    } else {
+                                        {"–"}
+                                    }
+                                </td>
+                            </tr>
+                        }
+                    }) }
+                </tbody>
+            </table>
+            if !warnings.is_empty() {
+                <ul class="stocking-warnings" style="color: #b71c1c;">
+                    { for warnings.iter().map(|w| html! { <li>{*w}</li> }) }
+                </ul>
+            }
+        </>
+    }
+}
@@ -0,0 +1,211 @@
+//! Permissions editor: the matrix of what the workers of each role may do
+//! in each module, edited by the owner (see `shared::permissions`).
+
+use crate::components::Spinner;
+use crate::services::use_api;
+use crate::store::{PermissionStore, use_read_only};
+use log::{error, info};
+use shared::permissions::{Permission, PermissionAction, PermissionModule, RolePermissions};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yewdux::prelude::{use_dispatch, use_selector};
+
+/// `granted` with `permission` turned on or off. Changing or deleting needs
+/// viewing, so turning those on grants View too, and turning View off takes
+/// them away.
+pub fn toggle_permission(
+    granted: &[Permission],
+    permission: Permission,
+    on: bool,
+) -> Vec<Permission> {
+    let view = Permission {
+        module: permission.module,
+        action: PermissionAction::View,
+    };
+    let mut granted: Vec<Permission> = granted
+        .iter()
+        .copied()
+        .filter(|p| *p != permission)
+        .filter(|p| on || permission != view || p.module != permission.module)
+        .collect();
+    if on {
+        granted.push(permission);
+        if permission != view && !granted.contains(&view) {
+            granted.push(view);
+        }
+    }
+    granted.sort();
+    granted
+}
+
+/// PermissionsEditor component:
+/// Picks a role, shows a checkbox for each module and action it may take,
+/// and saves the role's permissions. Every role a worker has is listed;
+/// roles never saved may do anything. Only the owner, on a device no worker
+/// signed in to, may save, and not in read-only mode.
+#[function_component(PermissionsEditor)]
+pub fn permissions_editor() -> Html {
+    let api = use_api();
+    let dispatch = use_dispatch::<PermissionStore>();
+    let read_only = use_read_only();
+    let is_owner = *use_selector(|store: &PermissionStore| {
+        store.mine.as_ref().is_none_or(|mine| mine.is_owner())
+    });
+    let matrix = use_state(|| None::<Vec<RolePermissions>>);
+    // The selected role, as being edited
+    let draft = use_state(|| None::<RolePermissions>);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let api = api.clone();
+        let matrix = matrix.clone();
+        let draft = draft.clone();
+        let error = error.clone();
+        move |_| {
+            spawn_local(async move {
+                match api.role_permissions().await {
+                    Ok(loaded) => {
+                        draft.set(loaded.first().cloned());
+                        matrix.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load permissions: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+            || {}
+        }
+    });
+
+    let on_role = {
+        let matrix = matrix.clone();
+        let draft = draft.clone();
+        let message = message.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            let role = select.value();
+            let selected = matrix
+                .as_ref()
+                .and_then(|m| m.iter().find(|r| r.role == role).cloned());
+            message.set(None);
+            draft.set(selected);
+        })
+    };
+
+    let on_toggle = {
+        let draft = draft.clone();
+        Callback::from(move |(permission, on): (Permission, bool)| {
+            if let Some(current) = &*draft {
+                draft.set(Some(RolePermissions {
+                    role: current.role.clone(),
+                    granted: toggle_permission(&current.granted, permission, on),
+                }));
+            }
+        })
+    };
+
+    let on_save = {
+        let matrix = matrix.clone();
+        let draft = draft.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(permissions) = (*draft).clone() else {
+                return;
+            };
+            if let Err(e) = permissions.validate() {
+                error.set(Some(e));
+                return;
+            }
+            let api = api.clone();
+            let dispatch = dispatch.clone();
+            let matrix = matrix.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.update_role_permissions(&permissions).await {
+                    Ok(saved) => {
+                        info!("Saved the permissions of role {}", saved.role);
+                        let mut updated = (*matrix).clone().unwrap_or_default();
+                        updated.retain(|r| r.role != saved.role);
+                        updated.push(saved.clone());
+                        updated.sort_by(|a, b| a.role.cmp(&b.role));
+                        matrix.set(Some(updated));
+                        error.set(None);
+                        message.set(Some(format!("Saved permissions for {}", saved.role)));
+                        PermissionStore::load(api, dispatch);
+                    }
+                    Err(e) => {
+                        error!("Failed to save permissions of {}: {}", permissions.role, e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let locked = read_only || !is_owner;
+
+    html! {
+        <div>
+            <h3>{"Permissions"}</h3>
+            if let Some(matrix) = &*matrix {
+                if matrix.is_empty() {
+                    <p>{"Give workers a role to set what they may do."}</p>
+                } else if let Some(draft) = &*draft {
+                    <label>{"Role: "}
+                        <select class="permission-role" onchange={on_role}>
+                            { for matrix.iter().map(|r| html! {
+                                <option value={r.role.clone()} selected={r.role == draft.role}>
+                                    {&r.role}
+                                </option>
+                            }) }
+                        </select>
+                    </label>
+                    <table class="permissions">
+                        <tr>
+                            <th>{"Module"}</th>
+                            { for PermissionAction::ALL.iter().map(|a| html! { <th>{a.label()}</th> }) }
+                        </tr>
+                        { for PermissionModule::ALL.into_iter().map(|module| html! {
+                            <tr>
+                                <td>{module.label()}</td>
+                                { for PermissionAction::ALL.into_iter().map(|action| {
+                                    let permission = Permission { module, action };
+                                    let onchange = on_toggle.reform(move |e: Event| {
+                                        let input: HtmlInputElement = e.target_unchecked_into();
+                                        (permission, input.checked())
+                                    });
+                                    html! {
+                                        <td>
+                                            <input type="checkbox"
+                                                   title={format!("{} {}", action.label(), module.label())}
+                                                   checked={draft.allows(module, action)}
+                                                   disabled={locked} {onchange} />
+                                        </td>
+                                    }
+                                }) }
+                            </tr>
+                        }) }
+                    </table>
+                    <button onclick={on_save} disabled={locked}>{"Save permissions"}</button>
+                    if !is_owner {
+                        <p style="font-size: 12px;">{"Only the owner can change permissions."}</p>
+                    }
+                }
+            } else {
+                <Spinner label="Loading permissions..." />
+            }
+            if let Some(msg) = &*message {
+                <p style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Permissions error: {}", err)}</p>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,247 @@
+//! Pricing formula editor: previews every goat's formula price as the
+//! formula is typed, using the same `shared::pricing` evaluator as the
+//! backend, then saves the formula or applies it to the herd.
+
+use crate::errors::AppError;
+use crate::services::use_api;
+use crate::store::{GoatStore, use_read_only};
+use log::{error, info};
+use shared::GoatParams;
+use shared::pricing::{DEFAULT_FORMULA, GOAT_VARIABLES, GoatValuation, PricingSettings};
+use shared::settings::FarmSettings;
+use std::collections::BTreeMap;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// Parses `name = value` pairs separated by commas, e.g.
+/// `rate_per_kg = 400, Sirohi = 1.25`.
+fn parse_assignments(text: &str) -> Result<BTreeMap<String, f64>, String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("Expected name = value, got '{}'", pair))?;
+            let value = value
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("'{}' is not a number", value.trim()))?;
+            Ok((name.trim().to_string(), value))
+        })
+        .collect()
+}
+
+/// Formats pairs the way `parse_assignments` reads them.
+fn format_assignments(values: &BTreeMap<String, f64>) -> String {
+    values
+        .iter()
+        .map(|(name, value)| format!("{} = {}", name, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Builds pricing settings from the editor's inputs.
+fn draft_settings(
+    formula: &str,
+    variables: &str,
+    breed_factors: &str,
+) -> Result<PricingSettings, String> {
+    Ok(PricingSettings {
+        formula: formula.to_string(),
+        variables: parse_assignments(variables)?,
+        breed_factors: parse_assignments(breed_factors)?,
+    })
+}
+
+/// Reads an input's value into `state` on every keystroke.
+fn bind(state: &UseStateHandle<String>) -> Callback<InputEvent> {
+    let state = state.clone();
+    Callback::from(move |e: InputEvent| {
+        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+            state.set(input.value());
+        }
+    })
+}
+
+/// PricingPreview component:
+/// Loads the stored pricing formula (or the default one) and shows, for each
+/// goat in the store, its current price next to the formula price. Formula
+/// and constant errors show as the user types. "Save formula" stores the
+/// formula in the farm settings; "Apply to herd" also sets every goat's
+/// current price from it and refreshes the goat list. Both are disabled in
+/// read-only mode.
+#[function_component(PricingPreview)]
+pub fn pricing_preview() -> Html {
+    let api = use_api();
+    let (state, dispatch) = use_store::<GoatStore>();
+    let settings = use_state(|| None::<FarmSettings>);
+    let formula = use_state(|| DEFAULT_FORMULA.to_string());
+    let variables = use_state(String::new);
+    let breed_factors = use_state(String::new);
+    let error = use_state(|| None::<String>);
+    let message = use_state(|| None::<String>);
+    let read_only = use_read_only();
+
+    {
+        let api = api.clone();
+        let settings = settings.clone();
+        let formula = formula.clone();
+        let variables = variables.clone();
+        let breed_factors = breed_factors.clone();
+        let error = error.clone();
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                match api.farm_settings().await {
+                    Ok(loaded) => {
+                        if let Some(pricing) = &loaded.pricing {
+                            info!("Loaded pricing formula '{}'", pricing.formula);
+                            formula.set(pricing.formula.clone());
+                            variables.set(format_assignments(&pricing.variables));
+                            breed_factors.set(format_assignments(&pricing.breed_factors));
+                        }
+                        settings.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load farm settings: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        });
+    }
+
+    let draft = draft_settings(&formula, &variables, &breed_factors);
+    let herd: Vec<GoatParams> = state.goats.iter().map(|g| g.params.clone()).collect();
+    let valuations: Result<Vec<GoatValuation>, String> = draft
+        .clone()
+        .and_then(|pricing| pricing.value_herd(&herd));
+
+    let save = {
+        let api = api.clone();
+        let settings = settings.clone();
+        let error = error.clone();
+        let message = message.clone();
+        let draft = draft.clone();
+        Callback::from(move |apply: bool| {
+            let (Some(current), Ok(pricing)) = ((*settings).clone(), draft.clone()) else {
+                return;
+            };
+            let api = api.clone();
+            let dispatch = dispatch.clone();
+            let settings = settings.clone();
+            let error = error.clone();
+            let message = message.clone();
+            spawn_local(async move {
+                // The mode may have been switched since the settings loaded
+                let updated = FarmSettings {
+                    pricing: Some(pricing),
+                    read_only,
+                    ..current
+                };
+                let result: Result<String, AppError> = async {
+                    let saved = api.update_settings(&updated).await?;
+                    settings.set(Some(saved));
+                    if apply {
+                        let applied = api.apply_pricing().await?;
+                        let priced = applied.iter().filter(|v| v.formula_price.is_some());
+                        return Ok(format!("Repriced {} goats", priced.count()));
+                    }
+                    Ok("Pricing formula saved".to_string())
+                }
+                .await;
+                match result {
+                    Ok(done) => {
+                        info!("{}", done);
+                        error.set(None);
+                        message.set(Some(done));
+                        if apply {
+                            GoatStore::force_refresh(api, dispatch);
+                        }
+                    }
+                    Err(e) => {
+                        error!("Failed to save pricing formula: {}", e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+    let on_save = save.reform(|_: MouseEvent| false);
+    let on_apply = save.reform(|_: MouseEvent| true);
+    let ready = settings.is_some() && valuations.is_ok() && !read_only;
+
+    html! {
+        <div>
+            <h3>{"Pricing Formula"}</h3>
+            <label>{"current_price = "}
+                <input type="text" name="formula" size="40" value={(*formula).clone()}
+                       oninput={bind(&formula)} />
+            </label>
+            <p style="color: #666; font-size: 0.9em;">
+                {format!("Goat values: {}; functions: min, max, round", GOAT_VARIABLES.join(", "))}
+            </p>
+            <label>{"Constants: "}
+                <input type="text" name="variables" placeholder="rate_per_kg = 400"
+                       value={(*variables).clone()} oninput={bind(&variables)} />
+            </label>
+            <label>{" Breed factors: "}
+                <input type="text" name="breed_factors" placeholder="Sirohi = 1.25"
+                       value={(*breed_factors).clone()} oninput={bind(&breed_factors)} />
+            </label>
+            <div style="margin-top: 8px;">
+                <button name="save" onclick={on_save} disabled={!ready}>{"Save formula"}</button>
+                <button name="apply" onclick={on_apply} disabled={!ready}>{"Apply to herd"}</button>
+            </div>
+            if let Some(msg) = &*message {
+                <p style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Pricing error: {}", err)}</p>
+            }
+            {
+                match &valuations {
+                    Err(e) => html! { <p class="formula-error" style="color: red;">{e}</p> },
+                    Ok(valuations) => html! {
+                        <table style="border-collapse: collapse; width: 100%;">
+                            <thead>
+                                <tr>
+                                    <th>{"Goat"}</th>
+                                    <th>{"Current price"}</th>
+                                    <th>{"Formula price"}</th>
+                                    <th>{"Change"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                { for valuations.iter().map(valuation_row) }
+                            </tbody>
+                        </table>
+                    },
+                }
+            }
+        </div>
+    }
+}
+
+/// One table row comparing a goat's current and formula price.
+fn valuation_row(valuation: &GoatValuation) -> Html {
+    let (formula, change) = match (valuation.formula_price, &valuation.error) {
+        (Some(price), _) => (
+            format!("{:.2}", price),
+            format!("{:+.2}", price - valuation.current_price),
+        ),
+        (None, Some(e)) => (e.clone(), String::new()),
+        (None, None) => ("–".to_string(), String::new()),
+    };
+    html! {
+        <tr data-goat={valuation.goat_name.clone()}>
+            <td>{&valuation.goat_name}</td>
+            <td class="current">{format!("{:.2}", valuation.current_price)}</td>
+            <td class="formula">{formula}</td>
+            <td class="change">{change}</td>
+        </tr>
+    }
+}
@@ -0,0 +1,296 @@
+//! Quick entry of a weighing or milking in one line, typed or spoken
+//! through the browser's speech recognizer, e.g. "Rani 32 kilos". The
+//! parsed record is shown for confirmation before it is saved.
+
+use crate::components::date_picker::today;
+use crate::services::use_api;
+use crate::store::{GoatStore, UnitsStore};
+use log::{error, info};
+use shared::Gender;
+use shared::growth::WeightRecord;
+use shared::milk::MilkRecord;
+use shared::units::format_decimal;
+use shared::voice::{EntryKind, SpokenEntry, parse_utterance};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, SpeechRecognition, SpeechRecognitionEvent};
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// Language assumed for speech when the browser does not name one.
+const DEFAULT_SPEECH_LANG: &str = "en-IN";
+
+/// The browser's speech recognizer class, if it has one. Chrome and Safari
+/// only provide it under the `webkit` prefix.
+fn recognizer_class() -> Option<js_sys::Function> {
+    let window = web_sys::window()?;
+    ["SpeechRecognition", "webkitSpeechRecognition"]
+        .into_iter()
+        .find_map(|name| {
+            js_sys::Reflect::get(&window, &JsValue::from_str(name))
+                .ok()?
+                .dyn_into::<js_sys::Function>()
+                .ok()
+        })
+}
+
+/// A recognizer for one utterance in the browser's language.
+fn new_recognizer() -> Option<SpeechRecognition> {
+    let class = recognizer_class()?;
+    let recognizer: SpeechRecognition = js_sys::Reflect::construct(&class, &js_sys::Array::new())
+        .ok()?
+        .unchecked_into();
+    let lang = web_sys::window()
+        .and_then(|w| w.navigator().language())
+        .unwrap_or_else(|| DEFAULT_SPEECH_LANG.to_string());
+    recognizer.set_lang(&lang);
+    recognizer.set_interim_results(false);
+    recognizer.set_max_alternatives(1);
+    Some(recognizer)
+}
+
+/// "32 kg for Rani", with weights in the user's unit.
+fn describe(entry: &SpokenEntry, units: &UnitsStore) -> String {
+    let separator = units.decimal_separator;
+    let amount = match entry.kind {
+        EntryKind::Weight => {
+            let value = units.weight.from_kg(entry.amount);
+            let symbol = units.weight.symbol();
+            format!("{} {}", format_decimal(value, 2, separator), symbol)
+        }
+        EntryKind::Milk => format!("{} L", format_decimal(entry.amount, 2, separator)),
+    };
+    format!("{} for {}", amount, entry.goat_name)
+}
+
+#[derive(Properties, PartialEq)]
+pub struct QuickEntryProps {
+    /// Whether weighings or milkings are entered.
+    pub kind: EntryKind,
+}
+
+/// QuickEntry component:
+/// Takes a line such as "Rani 32 kilos" or "Lakshmi 2.5 litres", typed or
+/// spoken after pressing the microphone button (shown only where the
+/// browser supports speech recognition). The line is parsed with
+/// `shared::voice::parse_utterance`; milk is only matched against does.
+/// The parsed record, dated today, waits for the user to save or cancel it.
+#[function_component(QuickEntry)]
+pub fn quick_entry(props: &QuickEntryProps) -> Html {
+    let (state, _) = use_store::<GoatStore>();
+    let (units, _) = use_store::<UnitsStore>();
+    let api = use_api();
+    let kind = props.kind;
+    let utterance = use_state(String::new);
+    let pending = use_state(|| None::<SpokenEntry>);
+    let listening = use_state(|| false);
+    let saving = use_state(|| false);
+    let error = use_state(|| None::<String>);
+    let saved = use_state(|| None::<String>);
+
+    let parse = {
+        let state = state.clone();
+        let units = units.clone();
+        let pending = pending.clone();
+        let error = error.clone();
+        let saved = saved.clone();
+        Callback::from(move |text: String| {
+            let names: Vec<&str> = state
+                .goats
+                .iter()
+                .filter(|g| kind == EntryKind::Weight || g.gender == Gender::Female)
+                .map(|g| g.name.as_str())
+                .collect();
+            saved.set(None);
+            match parse_utterance(&text, kind, units.weight, &names) {
+                Ok(entry) => {
+                    info!("Parsed '{}' as {:?}", text, entry);
+                    error.set(None);
+                    pending.set(Some(entry));
+                }
+                Err(message) => {
+                    pending.set(None);
+                    error.set(Some(message));
+                }
+            }
+        })
+    };
+
+    let on_input = {
+        let utterance = utterance.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                utterance.set(input.value());
+            }
+        })
+    };
+
+    let on_check = {
+        let utterance = utterance.clone();
+        let parse = parse.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            parse.emit((*utterance).clone());
+        })
+    };
+
+    let on_listen = {
+        let utterance = utterance.clone();
+        let listening = listening.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(recognizer) = new_recognizer() else {
+                error.set(Some("Speech input is not available in this browser".into()));
+                return;
+            };
+            let on_result = {
+                let utterance = utterance.clone();
+                let parse = parse.clone();
+                Closure::once_into_js(move |event: SpeechRecognitionEvent| {
+                    let heard = event
+                        .results()
+                        .and_then(|results| results.get(0))
+                        .and_then(|result| result.get(0))
+                        .map(|alternative| alternative.transcript());
+                    if let Some(text) = heard {
+                        info!("Heard '{}'", text);
+                        utterance.set(text.clone());
+                        parse.emit(text);
+                    }
+                })
+            };
+            let on_error = {
+                let error = error.clone();
+                Closure::once_into_js(move |_: Event| {
+                    error.set(Some("Didn't catch that; try again or type it".into()));
+                })
+            };
+            let on_end = {
+                let listening = listening.clone();
+                Closure::once_into_js(move || listening.set(false))
+            };
+            recognizer.set_onresult(Some(on_result.unchecked_ref()));
+            recognizer.set_onerror(Some(on_error.unchecked_ref()));
+            recognizer.set_onend(Some(on_end.unchecked_ref()));
+            error.set(None);
+            match recognizer.start() {
+                Ok(()) => listening.set(true),
+                Err(e) => {
+                    error!("Could not start speech recognition: {:?}", e);
+                    error.set(Some("Could not use the microphone".into()));
+                }
+            }
+        })
+    };
+
+    let on_save = {
+        let units = units.clone();
+        let utterance = utterance.clone();
+        let pending = pending.clone();
+        let saving = saving.clone();
+        let error = error.clone();
+        let saved = saved.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(entry) = (*pending).clone() else {
+                return;
+            };
+            let api = api.clone();
+            let utterance = utterance.clone();
+            let pending = pending.clone();
+            let saving = saving.clone();
+            let error = error.clone();
+            let saved = saved.clone();
+            let description = describe(&entry, &units);
+            saving.set(true);
+            spawn_local(async move {
+                let result = match entry.kind {
+                    EntryKind::Weight => {
+                        let record = WeightRecord {
+                            id: None,
+                            goat_name: entry.goat_name.clone(),
+                            weighed_on: today(),
+                            weight: entry.amount,
+                            age_days: None,
+                            estimated: false,
+                        };
+                        api.add_weight(&record).await
+                    }
+                    EntryKind::Milk => {
+                        let record = MilkRecord {
+                            id: None,
+                            doe_name: entry.goat_name.clone(),
+                            recorded_on: today(),
+                            yield_litres: entry.amount,
+                        };
+                        api.add_milk_record(&record).await
+                    }
+                };
+                match result {
+                    Ok(()) => {
+                        info!("Recorded {}", description);
+                        saved.set(Some(format!("Recorded {}.", description)));
+                        pending.set(None);
+                        utterance.set(String::new());
+                    }
+                    Err(e) => {
+                        error!("Failed to record {}: {}", description, e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+                saving.set(false);
+            });
+        })
+    };
+
+    let on_cancel = {
+        let pending = pending.clone();
+        Callback::from(move |_: MouseEvent| pending.set(None))
+    };
+
+    let (title, placeholder) = match kind {
+        EntryKind::Weight => ("Quick Weight", "e.g. Rani 32 kilos"),
+        EntryKind::Milk => ("Quick Milk", "e.g. Rani 2.5 litres"),
+    };
+
+    html! {
+        <div class="quick-entry">
+            <h3>{title}</h3>
+            <form onsubmit={on_check}>
+                <input type="text" name="utterance" value={(*utterance).clone()}
+                       placeholder={placeholder} oninput={on_input} />
+                if recognizer_class().is_some() {
+                    { " " }
+                    <button type="button" class="mic" onclick={on_listen} disabled={*listening}
+                            title="Say the goat's name and the amount">
+                        { if *listening { "Listening..." } else { "🎤 Speak" } }
+                    </button>
+                }
+                { " " }
+                <button type="submit">{"Check"}</button>
+            </form>
+
+            if let Some(err) = &*error {
+                <p style="color: red;">{err}</p>
+            }
+
+            if let Some(entry) = &*pending {
+                <div class="voice-confirm">
+                    <p>
+                        {format!("Record {} on {}?", describe(entry, &units), today())}
+                        if !entry.goat_name.eq_ignore_ascii_case(&entry.heard_name) {
+                            {format!(" (heard \"{}\")", entry.heard_name)}
+                        }
+                    </p>
+                    <button onclick={on_save} disabled={*saving}>{"Save"}</button>
+                    { " " }
+                    <button onclick={on_cancel} disabled={*saving}>{"Cancel"}</button>
+                </div>
+            }
+
+            if let Some(message) = &*saved {
+                <p class="voice-saved">{message}</p>
+            }
+        </div>
+    }
+}
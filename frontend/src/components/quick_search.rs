@@ -0,0 +1,202 @@
+//! Header search box finding goats, tasks and transactions as the user
+//! types, with keyboard selection of the hits and "did you mean"
+//! suggestions for misspelt goat names.
+
+use crate::services::use_api;
+use log::{error, trace};
+use shared::search::{MIN_QUERY_LEN, SearchKind, SearchResult};
+use std::time::Duration;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::platform::time::sleep;
+use yew::prelude::*;
+
+/// Pause in typing after which the search runs.
+pub const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Label shown before each hit.
+fn kind_label(kind: SearchKind) -> &'static str {
+    match kind {
+        SearchKind::Goat => "Goat",
+        SearchKind::Task => "Task",
+        SearchKind::Transaction => "Transaction",
+    }
+}
+
+/// Jumps to a hit by setting the URL fragment to its anchor, which scrolls
+/// the record's element into view.
+fn navigate(result: &SearchResult) {
+    let anchor = result.anchor();
+    trace!("Navigating to #{}", anchor);
+    if let Some(window) = web_sys::window()
+        && let Err(e) = window.location().set_hash(&anchor)
+    {
+        error!("Could not navigate to #{}: {:?}", anchor, e);
+    }
+}
+
+/// QuickSearch component:
+/// Searches the backend once typing pauses for `DEBOUNCE`, ignoring
+/// responses to queries that have since changed. Goats suggested because
+/// their names are close to the query are listed under "Did you mean". Up
+/// and down arrows move the highlight, Enter (or a click) opens the
+/// highlighted hit, and Escape closes the list.
+#[function_component(QuickSearch)]
+pub fn quick_search() -> Html {
+    let api = use_api();
+    let query = use_state(String::new);
+    let results = use_state(Vec::<SearchResult>::new);
+    let active = use_state(|| 0usize);
+    let open = use_state(|| false);
+    // Bumped on every keystroke; a search only applies if it is still current
+    let generation = use_mut_ref(|| 0u32);
+
+    let oninput = {
+        let query = query.clone();
+        let results = results.clone();
+        let active = active.clone();
+        let open = open.clone();
+        Callback::from(move |e: InputEvent| {
+            let Some(input) = e.target_dyn_into::<HtmlInputElement>() else {
+                return;
+            };
+            let text = input.value();
+            query.set(text.clone());
+            *generation.borrow_mut() += 1;
+            let current = *generation.borrow();
+            if text.trim().chars().count() < MIN_QUERY_LEN {
+                results.set(Vec::new());
+                open.set(false);
+                return;
+            }
+
+            let api = api.clone();
+            let generation = generation.clone();
+            let results = results.clone();
+            let active = active.clone();
+            let open = open.clone();
+            spawn_local(async move {
+                sleep(DEBOUNCE).await;
+                if *generation.borrow() != current {
+                    return;
+                }
+                match api.search(text.trim()).await {
+                    Ok(hits) if *generation.borrow() == current => {
+                        trace!("{} hits for '{}'", hits.len(), text);
+                        active.set(0);
+                        open.set(true);
+                        results.set(hits);
+                    }
+                    Ok(_) => trace!("Dropping stale results for '{}'", text),
+                    Err(e) => error!("Search for '{}' failed: {}", text, e),
+                }
+            });
+        })
+    };
+
+    let select = {
+        let query = query.clone();
+        let results = results.clone();
+        let open = open.clone();
+        Callback::from(move |result: SearchResult| {
+            navigate(&result);
+            query.set(String::new());
+            results.set(Vec::new());
+            open.set(false);
+        })
+    };
+
+    let onkeydown = {
+        let results = results.clone();
+        let active = active.clone();
+        let open = open.clone();
+        let select = select.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            let count = results.len();
+            match e.key().as_str() {
+                "ArrowDown" if count > 0 => {
+                    e.prevent_default();
+                    open.set(true);
+                    active.set((*active + 1) % count);
+                }
+                "ArrowUp" if count > 0 => {
+                    e.prevent_default();
+                    open.set(true);
+                    active.set((*active + count - 1) % count);
+                }
+                "Enter" if *open => {
+                    if let Some(result) = results.get(*active) {
+                        e.prevent_default();
+                        select.emit(result.clone());
+                    }
+                }
+                "Escape" => open.set(false),
+                _ => {}
+            }
+        })
+    };
+
+    html! {
+        <div class="quick-search" style="position: relative; flex: 1; max-width: 420px;">
+            <input
+                type="search"
+                name="quick-search"
+                placeholder="Search goats, tasks, transactions"
+                aria-label="Quick search"
+                autocomplete="off"
+                style="width: 100%; padding: 6px 8px;"
+                value={(*query).clone()}
+                {oninput}
+                {onkeydown}
+            />
+            if *open {
+                <ul class="quick-search-results" role="listbox" style="
+                    position: absolute; left: 0; right: 0; z-index: 10;
+                    margin: 2px 0 0; padding: 0; list-style: none;
+                    background: white; color: #222; border: 1px solid #ccc;
+                ">
+                    if results.is_empty() {
+                        <li style="padding: 6px 8px; color: #666;">{"No matches"}</li>
+                    }
+                    { for results.iter().enumerate().map(|(i, result)| {
+                        let selected = i == *active;
+                        let onmousedown = {
+                            let select = select.clone();
+                            let result = result.clone();
+                            Callback::from(move |e: MouseEvent| {
+                                // Before the input's blur, so the click lands
+                                e.prevent_default();
+                                select.emit(result.clone());
+                            })
+                        };
+                        // Heads the suggestions, which the backend lists first
+                        let heading = (result.suggested && i == 0).then(|| html! {
+                            <li class="did-you-mean" style="padding: 6px 8px; color: #666; font-size: 12px;">
+                                {"Did you mean:"}
+                            </li>
+                        });
+                        html! {
+                            <>
+                                { for heading }
+                                <li role="option" aria-selected={selected.to_string()}
+                                    data-anchor={result.anchor()} {onmousedown}
+                                    style={format!(
+                                        "padding: 6px 8px; cursor: pointer;{}",
+                                        if selected { " background: #e3f2fd;" } else { "" }
+                                    )}>
+                                    <span style="font-size: 11px; color: #666; margin-right: 6px;">
+                                        {kind_label(result.kind)}
+                                    </span>
+                                    <strong>{&result.title}</strong>
+                                    <span style="font-size: 12px; color: #666; margin-left: 6px;">
+                                        {&result.detail}
+                                    </span>
+                                </li>
+                            </>
+                        }
+                    }) }
+                </ul>
+            }
+        </div>
+    }
+}
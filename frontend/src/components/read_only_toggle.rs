@@ -0,0 +1,44 @@
+//! Header switch for read-only mode (see `crate::store::AccessStore`).
+
+use crate::services::use_api;
+use crate::store::AccessStore;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// ReadOnlyToggle component:
+/// Loads the farm's read-only mode on mount and switches it from a
+/// checkbox. While on, the dashboard hides its forms and disables the
+/// buttons that change data; the backend refuses those changes as well.
+#[function_component(ReadOnlyToggle)]
+pub fn read_only_toggle() -> Html {
+    let api = use_api();
+    let (state, dispatch) = use_store::<AccessStore>();
+
+    {
+        let api = api.clone();
+        let dispatch = dispatch.clone();
+        use_effect_with((), move |_| {
+            AccessStore::load(api, dispatch);
+        });
+    }
+
+    let on_change = Callback::from(move |e: Event| {
+        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+            AccessStore::set_read_only(api.clone(), dispatch.clone(), input.checked());
+        }
+    });
+
+    html! {
+        <span class="read-only-toggle">
+            <label>
+                <input type="checkbox" name="read_only" checked={state.read_only}
+                       disabled={state.pending} onchange={on_change} />
+                {" Read-only"}
+            </label>
+            if let Some(err) = &state.error {
+                <span style="color: #ffab91; margin-left: 8px;">{err}</span>
+            }
+        </span>
+    }
+}
@@ -0,0 +1,93 @@
+//! "Recent activity" panel listing the latest goats added, sold and treated
+//! (see `shared::activity`), so the owner sees what was recorded today.
+
+use crate::services::use_api;
+use crate::store::{GoatStore, UnitsStore};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use log::{error, info};
+use shared::activity::ActivityEvent;
+use shared::time::{format_local, local_date, today_in};
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::{use_store, use_store_value};
+
+/// Formats `at` as "Today 09:30" for events entered today in the farm's
+/// time zone, and as the full local date and time otherwise.
+fn when(at: &DateTime<Utc>, timezone: Tz) -> String {
+    let local = format_local(at, timezone);
+    if local_date(at, timezone) == today_in(timezone) {
+        format!("Today {}", &local[11..])
+    } else {
+        local
+    }
+}
+
+/// RecentActivity component:
+/// Loads the activity feed and lists each event with when it was entered;
+/// each event links to the goat's row in the goat list. Reloads when the goat
+/// list changes and on Refresh.
+#[function_component(RecentActivity)]
+pub fn recent_activity() -> Html {
+    let api = use_api();
+    let (state, _) = use_store::<GoatStore>();
+    let timezone = use_store_value::<UnitsStore>().timezone;
+    let events = use_state(|| None::<Vec<ActivityEvent>>);
+    let error = use_state(|| None::<String>);
+    let reloads = use_state(|| 0u32);
+
+    {
+        let events = events.clone();
+        let error = error.clone();
+        use_effect_with((state.goats.len(), *reloads), move |_| {
+            spawn_local(async move {
+                match api.recent_activity().await {
+                    Ok(loaded) => {
+                        info!("Loaded {} activity events", loaded.len());
+                        error.set(None);
+                        events.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load recent activity: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        });
+    }
+
+    let refresh = {
+        let reloads = reloads.clone();
+        Callback::from(move |_: MouseEvent| reloads.set(*reloads + 1))
+    };
+
+    html! {
+        <div>
+            <h3>{"Recent Activity"}</h3>
+            <button onclick={refresh}>{"Refresh"}</button>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading activity: {}", err)}</p>
+            }
+            {
+                match &*events {
+                    None => html! { <p>{"Loading activity..."}</p> },
+                    Some(events) if events.is_empty() => html! {
+                        <p>{"Nothing recorded yet."}</p>
+                    },
+                    Some(events) => html! {
+                        <ul class="recent-activity" style="list-style: none; padding: 0;">
+                            { for events.iter().map(|event| html! {
+                                <li data-kind={event.kind.label()} style="padding: 4px 0;">
+                                    <span style="color: #666; font-size: 12px; margin-right: 8px;">
+                                        {when(&event.at, timezone)}
+                                    </span>
+                                    <a href={format!("#{}", event.anchor())}>{&event.summary}</a>
+                                </li>
+                            }) }
+                        </ul>
+                    },
+                }
+            }
+        </div>
+    }
+}
@@ -0,0 +1,271 @@
+//! Retention panel: how long deleted goats and event log entries are kept,
+//! the trash of deleted goats, and what the next cleanups will purge (see
+//! `shared::retention`).
+
+use crate::components::Spinner;
+use crate::errors::AppError;
+use crate::services::use_api;
+use crate::store::{GoatStore, UnitsStore, use_read_only};
+use log::{error, info};
+use shared::retention::{RetentionPolicy, UpcomingPurges};
+use shared::settings::FarmSettings;
+use shared::time::format_local;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yewdux::prelude::{use_dispatch, use_store_value};
+
+/// Reads the entered periods into a policy. A blank event log period keeps
+/// entries for good.
+pub fn policy_from_inputs(trash_days: &str, event_days: &str) -> Result<RetentionPolicy, String> {
+    let days = |what: &str, value: &str| {
+        value
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| format!("{} must be a whole number of days", what))
+    };
+    let policy = RetentionPolicy {
+        trash_days: days("Deleted goats", trash_days)?,
+        event_days: match event_days.trim() {
+            "" => None,
+            value => Some(days("Event log entries", value)?),
+        },
+    };
+    policy.validate()?;
+    Ok(policy)
+}
+
+/// RetentionPanel component:
+/// Edits the farm's retention periods, lists the deleted goats in the trash
+/// with the day each is purged and a Restore button, and says how many
+/// event log entries the next cleanups purge and when the last one ran.
+/// "Run cleanup now" queues the cleanup without waiting for the daily one.
+/// Saving, restoring and cleaning up are disabled in read-only mode.
+#[function_component(RetentionPanel)]
+pub fn retention_panel() -> Html {
+    let api = use_api();
+    let dispatch = use_dispatch::<GoatStore>();
+    let read_only = use_read_only();
+    let timezone = use_store_value::<UnitsStore>().timezone;
+    let purges = use_state(|| None::<UpcomingPurges>);
+    let trash_days = use_state(String::new);
+    let event_days = use_state(String::new);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+    // Bumped after each change to reload the trash and upcoming purges
+    let reloads = use_state(|| 0u32);
+
+    use_effect_with(*reloads, {
+        let api = api.clone();
+        let purges = purges.clone();
+        let trash_days = trash_days.clone();
+        let event_days = event_days.clone();
+        let error = error.clone();
+        move |_: &u32| {
+            spawn_local(async move {
+                match api.retention().await {
+                    Ok(loaded) => {
+                        trash_days.set(loaded.policy.trash_days.to_string());
+                        event_days.set(
+                            loaded
+                                .policy
+                                .event_days
+                                .map(|d| d.to_string())
+                                .unwrap_or_default(),
+                        );
+                        purges.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load retention: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+            || {}
+        }
+    });
+
+    let on_input = |state: &UseStateHandle<String>| {
+        let state = state.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            state.set(input.value());
+        })
+    };
+    let on_trash_days = on_input(&trash_days);
+    let on_event_days = on_input(&event_days);
+
+    let on_save = {
+        let api = api.clone();
+        let trash_days = trash_days.clone();
+        let event_days = event_days.clone();
+        let message = message.clone();
+        let error = error.clone();
+        let reloads = reloads.clone();
+        Callback::from(move |_: MouseEvent| {
+            let retention = match policy_from_inputs(&trash_days, &event_days) {
+                Ok(policy) => policy,
+                Err(e) => {
+                    error.set(Some(e));
+                    return;
+                }
+            };
+            let api = api.clone();
+            let message = message.clone();
+            let error = error.clone();
+            let reloads = reloads.clone();
+            spawn_local(async move {
+                let result: Result<FarmSettings, AppError> = async {
+                    let current = api.farm_settings().await?;
+                    api.update_settings(&FarmSettings {
+                        retention,
+                        ..current
+                    })
+                    .await
+                }
+                .await;
+                match result {
+                    Ok(_) => {
+                        info!("Saved retention {:?}", retention);
+                        error.set(None);
+                        message.set(Some("Retention saved".to_string()));
+                        reloads.set(*reloads + 1);
+                    }
+                    Err(e) => {
+                        error!("Failed to save retention: {}", e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_restore = {
+        let api = api.clone();
+        let message = message.clone();
+        let error = error.clone();
+        let reloads = reloads.clone();
+        Callback::from(move |id: i64| {
+            let api = api.clone();
+            let dispatch = dispatch.clone();
+            let message = message.clone();
+            let error = error.clone();
+            let reloads = reloads.clone();
+            spawn_local(async move {
+                match api.restore_goat(id).await {
+                    Ok(goat) => {
+                        info!("Restored goat {}", goat.name);
+                        error.set(None);
+                        message.set(Some(format!("Restored {}", goat.name)));
+                        reloads.set(*reloads + 1);
+                        GoatStore::force_refresh(api, dispatch);
+                    }
+                    Err(e) => {
+                        error!("Failed to restore goat from trash entry {}: {}", id, e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_cleanup = {
+        let message = message.clone();
+        let error = error.clone();
+        let reloads = reloads.clone();
+        Callback::from(move |_: MouseEvent| {
+            let api = api.clone();
+            let message = message.clone();
+            let error = error.clone();
+            let reloads = reloads.clone();
+            spawn_local(async move {
+                match api.start_cleanup_job().await {
+                    Ok(job) => {
+                        info!("Queued cleanup job {}", job.id);
+                        error.set(None);
+                        message.set(Some(format!("Cleanup queued as job {}", job.id)));
+                        reloads.set(*reloads + 1);
+                    }
+                    Err(e) => {
+                        error!("Failed to queue cleanup: {}", e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <div>
+            <h3>{"Trash & Retention"}</h3>
+            <p>
+                <label>{"Keep deleted goats for "}
+                    <input type="number" class="trash-days" min="1" value={(*trash_days).clone()}
+                           oninput={on_trash_days} style="width: 5em;" />
+                    {" days"}
+                </label>
+            </p>
+            <p>
+                <label>{"Keep event log entries for "}
+                    <input type="number" class="event-days" min="1" value={(*event_days).clone()}
+                           oninput={on_event_days} placeholder="always" style="width: 5em;" />
+                    {" days"}
+                </label>
+            </p>
+            <p style="font-size: 12px;">
+                {"Purged log entries no longer show in change histories. Leave blank to keep them all."}
+            </p>
+            <button onclick={on_save} disabled={read_only}>{"Save retention"}</button>
+            if let Some(purges) = &*purges {
+                if purges.trash.is_empty() {
+                    <p>{"The trash is empty."}</p>
+                } else {
+                    <table class="trash">
+                        <tr><th>{"Goat"}</th><th>{"Deleted"}</th><th>{"Purged on"}</th><th></th></tr>
+                        { for purges.trash.iter().map(|t| {
+                            let id = t.id;
+                            let on_restore = on_restore.reform(move |_: MouseEvent| id);
+                            html! {
+                                <tr>
+                                    <td>{&t.goat.name}</td>
+                                    <td>{format_local(&t.deleted_at, timezone)}</td>
+                                    <td>{&t.purge_on}</td>
+                                    <td>
+                                        <button class="restore-goat" onclick={on_restore}
+                                                disabled={read_only}>{"Restore"}</button>
+                                    </td>
+                                </tr>
+                            }
+                        }) }
+                    </table>
+                }
+                if purges.policy.event_days.is_some() {
+                    <p class="events-due">
+                        {format!(
+                            "{} event log entries are due for purging, and {} more within a week.",
+                            purges.events_due, purges.events_next_week
+                        )}
+                    </p>
+                }
+                <p style="font-size: 12px;">
+                    {match &purges.last_cleanup {
+                        Some(at) => format!("Last cleanup: {}", format_local(at, timezone)),
+                        None => "No cleanup has run yet.".to_string(),
+                    }}
+                </p>
+            } else {
+                <Spinner label="Loading trash..." />
+            }
+            <button onclick={on_cleanup} disabled={read_only}>{"Run cleanup now"}</button>
+            if let Some(msg) = &*message {
+                <p style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Retention error: {}", err)}</p>
+            }
+        </div>
+    }
+}
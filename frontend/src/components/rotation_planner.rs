@@ -0,0 +1,339 @@
+//! Pasture rotation planner: the farm's paddocks with their rest periods,
+//! and a week-by-week calendar of where each pen's goats should graze.
+
+use crate::components::date_picker::{date_problem, today};
+use crate::components::{DatePicker, SkeletonRows};
+use crate::services::use_api;
+use crate::store::use_read_only;
+use log::{error, info};
+use shared::grazing::{DEFAULT_REST_DAYS, MAX_ROTATION_WEEKS, Paddock, RotationPlan};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+/// Weeks shown until the user picks another span.
+const DEFAULT_WEEKS: u32 = 8;
+
+/// Spans offered in the weeks selector.
+const WEEK_CHOICES: [u32; 4] = [4, 8, 12, MAX_ROTATION_WEEKS];
+
+/// Callback copying an input's text into `field`.
+fn text_input(field: &UseStateHandle<String>) -> Callback<InputEvent> {
+    let field = field.clone();
+    Callback::from(move |e: InputEvent| {
+        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+            field.set(input.value());
+        }
+    })
+}
+
+/// RotationPlanner component:
+/// Lists the paddocks and, unless the dashboard is read-only, adds new ones
+/// with a rest period (35 days suggested) and optional capacity and last
+/// grazing date. Below, the proposed rotation for the chosen start and
+/// number of weeks is drawn as a calendar with a row per grazing group and
+/// a column per week; cells that break a rest period or a paddock's
+/// capacity are shaded and explained under the calendar.
+#[function_component(RotationPlanner)]
+pub fn rotation_planner() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let paddocks = use_state(|| None::<Vec<Paddock>>);
+    let plan = use_state(|| None::<RotationPlan>);
+    let start = use_state(today);
+    let weeks = use_state(|| DEFAULT_WEEKS);
+    let name = use_state(String::new);
+    let rest_days = use_state(|| DEFAULT_REST_DAYS.to_string());
+    let capacity = use_state(String::new);
+    let last_grazed = use_state(String::new);
+    let error = use_state(|| None::<String>);
+
+    let load = {
+        let api = api.clone();
+        let paddocks = paddocks.clone();
+        let plan = plan.clone();
+        let start = start.clone();
+        let weeks = weeks.clone();
+        let error = error.clone();
+        Callback::from(move |_: ()| {
+            let api = api.clone();
+            let paddocks = paddocks.clone();
+            let plan = plan.clone();
+            let start = (*start).clone();
+            let weeks = *weeks;
+            let error = error.clone();
+            spawn_local(async move {
+                let loaded = match api.paddocks().await {
+                    Ok(loaded) => {
+                        let start = (!start.is_empty()).then_some(start.as_str());
+                        api.rotation_plan(start, weeks)
+                            .await
+                            .map(|rotation| (loaded, rotation))
+                    }
+                    Err(e) => Err(e),
+                };
+                match loaded {
+                    Ok((loaded, rotation)) => {
+                        info!(
+                            "Loaded {} paddocks and a {} week rotation with {} conflicts",
+                            loaded.len(),
+                            rotation.weeks.len(),
+                            rotation.conflicts.len()
+                        );
+                        error.set(None);
+                        paddocks.set(Some(loaded));
+                        plan.set(Some(rotation));
+                    }
+                    Err(e) => {
+                        error!("Failed to load pasture rotation: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    use_effect_with((), {
+        let load = load.clone();
+        move |_| {
+            load.emit(());
+            || {}
+        }
+    });
+
+    let on_start = {
+        let start = start.clone();
+        Callback::from(move |value: String| start.set(value))
+    };
+    let on_weeks = {
+        let weeks = weeks.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>()
+                && let Ok(value) = select.value().parse()
+            {
+                weeks.set(value);
+            }
+        })
+    };
+    let on_last_grazed = {
+        let last_grazed = last_grazed.clone();
+        Callback::from(move |value: String| last_grazed.set(value))
+    };
+
+    let on_add = {
+        let name = name.clone();
+        let rest_days = rest_days.clone();
+        let capacity = capacity.clone();
+        let last_grazed = last_grazed.clone();
+        let error = error.clone();
+        let load = load.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let Ok(rest) = rest_days.trim().parse::<i64>() else {
+                error.set(Some("Rest period must be a whole number of days".into()));
+                return;
+            };
+            let max_goats = match capacity.trim() {
+                "" => None,
+                text => match text.parse::<i64>() {
+                    Ok(goats) => Some(goats),
+                    Err(_) => {
+                        error.set(Some("Capacity must be a whole number of goats".into()));
+                        return;
+                    }
+                },
+            };
+            if let Some(problem) = date_problem("Last grazed", &last_grazed) {
+                error.set(Some(problem));
+                return;
+            }
+            let paddock = Paddock {
+                id: None,
+                name: name.trim().to_string(),
+                area_m2: None,
+                capacity: max_goats,
+                rest_days: rest,
+                last_grazed_on: (!last_grazed.is_empty()).then(|| (*last_grazed).clone()),
+            };
+            if let Err(problem) = paddock.validate() {
+                error.set(Some(problem));
+                return;
+            }
+            let api = api.clone();
+            let name = name.clone();
+            let capacity = capacity.clone();
+            let last_grazed = last_grazed.clone();
+            let error = error.clone();
+            let load = load.clone();
+            spawn_local(async move {
+                match api.add_paddock(&paddock).await {
+                    Ok(stored) => {
+                        info!("Added paddock {}", stored.name);
+                        name.set(String::new());
+                        capacity.set(String::new());
+                        last_grazed.set(String::new());
+                        load.emit(());
+                    }
+                    Err(e) => {
+                        error!("Failed to add paddock {}: {}", paddock.name, e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <div>
+            <h3>{"Pasture Rotation"}</h3>
+            if let Some(err) = &*error {
+                <p style="color: red;">{err}</p>
+            }
+            {
+                match &*paddocks {
+                    None => html! {
+                        <table><tbody><SkeletonRows rows={2} columns={4} /></tbody></table>
+                    },
+                    Some(list) if list.is_empty() => html! {
+                        <p>{"No paddocks yet. Add the farm's paddocks to plan a rotation."}</p>
+                    },
+                    Some(list) => paddock_table(list),
+                }
+            }
+            if !read_only {
+                <form class="add-paddock" onsubmit={on_add}>
+                    <input type="text" name="paddock_name" placeholder="Paddock name"
+                           value={(*name).clone()} oninput={text_input(&name)} />
+                    { " " }
+                    <label>
+                        {"Rest (days): "}
+                        <input type="text" inputmode="numeric" name="rest_days" size="4"
+                               value={(*rest_days).clone()} oninput={text_input(&rest_days)} />
+                    </label>
+                    { " " }
+                    <label>
+                        {"Capacity (goats): "}
+                        <input type="text" inputmode="numeric" name="paddock_capacity" size="4"
+                               value={(*capacity).clone()} oninput={text_input(&capacity)} />
+                    </label>
+                    { " " }
+                    <label>
+                        {"Last grazed: "}
+                        <DatePicker name="last_grazed_on" label="Last grazed"
+                                    value={(*last_grazed).clone()} onchange={on_last_grazed} />
+                    </label>
+                    { " " }
+                    <button type="submit">{"Add paddock"}</button>
+                </form>
+            }
+            <p>
+                <label>
+                    {"Starting: "}
+                    <DatePicker name="rotation_start" label="Start" value={(*start).clone()}
+                                onchange={on_start} allow_future={true} />
+                </label>
+                { " " }
+                <label>
+                    {"Weeks: "}
+                    <select name="rotation_weeks" onchange={on_weeks}>
+                        { for WEEK_CHOICES.iter().map(|w| html! {
+                            <option value={w.to_string()} selected={*w == *weeks}>{w}</option>
+                        }) }
+                    </select>
+                </label>
+                { " " }
+                <button onclick={load.reform(|_| ())}>{"Plan"}</button>
+            </p>
+            if let Some(plan) = &*plan {
+                { rotation_calendar(plan) }
+            }
+        </div>
+    }
+}
+
+/// Renders the paddocks with their rules.
+fn paddock_table(paddocks: &[Paddock]) -> Html {
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "–".to_string());
+    html! {
+        <table style="border-collapse: collapse; margin-bottom: 10px;">
+            <thead>
+                <tr>
+                    <th>{"Paddock"}</th>
+                    <th>{"Rest (days)"}</th>
+                    <th>{"Capacity"}</th>
+                    <th>{"Last grazed"}</th>
+                </tr>
+            </thead>
+            <tbody>
+                { for paddocks.iter().map(|p| html! {
+                    <tr data-name={p.name.clone()}>
+                        <td>{&p.name}</td>
+                        <td>{p.rest_days}</td>
+                        <td>{or_dash(p.max_goats().map(|m| m.to_string()))}</td>
+                        <td>{or_dash(p.last_grazed_on.clone())}</td>
+                    </tr>
+                }) }
+            </tbody>
+        </table>
+    }
+}
+
+/// Renders the rotation as a group-by-week calendar and lists its
+/// conflicts.
+fn rotation_calendar(plan: &RotationPlan) -> Html {
+    if plan.groups.is_empty() {
+        return html! {
+            <p>{"No goats are housed in pens yet, so there are no groups to rotate."}</p>
+        };
+    }
+    let messages = |week: &str, group: &str| -> Vec<&str> {
+        plan.conflicts
+            .iter()
+            .filter(|c| c.week_starts_on == week && c.group == group)
+            .map(|c| c.message.as_str())
+            .collect()
+    };
+    html! {
+        <div style="overflow-x: auto;">
+            <table class="rotation-calendar" style="border-collapse: collapse;">
+                <thead>
+                    <tr>
+                        <th>{"Group"}</th>
+                        { for plan.weeks.iter().map(|week| html! {
+                            <th style="font-size: 11px; padding: 2px 4px;">{&week.starts_on}</th>
+                        }) }
+                    </tr>
+                </thead>
+                <tbody>
+                    { for plan.groups.iter().enumerate().map(|(i, group)| html! {
+                        <tr data-group={group.name.clone()}>
+                            <td>{format!("{} ({} goats)", group.name, group.goats)}</td>
+                            { for plan.weeks.iter().map(|week| {
+                                let cell = week.assignments.get(i);
+                                let conflict = cell.is_some_and(|a| a.conflict);
+                                let style = format!(
+                                    "background: {}; text-align: center; border: 1px solid #eee; padding: 2px 6px;",
+                                    if conflict { "#fdecea" } else { "#e8f5e9" }
+                                );
+                                html! {
+                                    <td class={classes!(conflict.then_some("conflict"))}
+                                        title={messages(&week.starts_on, &group.name).join("; ")}
+                                        {style}>
+                                        {cell.and_then(|a| a.paddock.clone()).unwrap_or_else(|| "–".to_string())}
+                                    </td>
+                                }
+                            }) }
+                        </tr>
+                    }) }
+                </tbody>
+            </table>
+            if !plan.conflicts.is_empty() {
+                <ul class="rotation-conflicts" style="color: #b71c1c;">
+                    { for plan.conflicts.iter().map(|c| html! {
+                        <li>{format!("Week of {}: {}", c.week_starts_on, c.message)}</li>
+                    }) }
+                </ul>
+            }
+        </div>
+    }
+}
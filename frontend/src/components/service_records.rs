@@ -0,0 +1,298 @@
+//! Service records panel: each time a doe was bred, by a herd buck or by AI
+//! with a semen straw from an outside sire, whether the service took, and
+//! how natural service and AI compare (see `shared::breeding`).
+
+use crate::components::Spinner;
+use crate::services::{Api, use_api};
+use crate::store::{GoatStore, use_read_only};
+use log::{error, info};
+use shared::Gender;
+use shared::breeding::{
+    BreedingService, MethodSuccess, OutsideSire, ServiceMethod, ServiceOutcome,
+};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// Reloads the services and success rates, reporting failures in `error`.
+fn load_services(
+    api: Api,
+    services: UseStateHandle<Option<Vec<BreedingService>>>,
+    success: UseStateHandle<Vec<MethodSuccess>>,
+    error: UseStateHandle<Option<String>>,
+) {
+    spawn_local(async move {
+        match api.breeding_services().await {
+            Ok(loaded) => services.set(Some(loaded)),
+            Err(e) => {
+                error!("Failed to load services: {}", e);
+                error.set(Some(e.to_string()));
+            }
+        }
+        match api.service_success().await {
+            Ok(rates) => success.set(rates),
+            Err(e) => error!("Failed to load service success rates: {}", e),
+        }
+    });
+}
+
+/// `Some` of the trimmed value, or `None` if blank.
+fn optional(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn outcome_label(outcome: Option<ServiceOutcome>) -> &'static str {
+    match outcome {
+        Some(ServiceOutcome::Kidded) => "Kidded",
+        Some(ServiceOutcome::Open) => "Open",
+        Some(ServiceOutcome::Pending) | None => "Pending",
+    }
+}
+
+/// ServiceRecords component:
+/// Records services with the doe, the date and either a herd buck or an
+/// outside sire; AI services also take the straw or batch ID and the
+/// technician. Lists the services newest first with their outcome, and
+/// compares the success rates of natural service and AI. Locked in
+/// read-only mode.
+#[function_component(ServiceRecords)]
+pub fn service_records() -> Html {
+    let (state, _) = use_store::<GoatStore>();
+    let api = use_api();
+    let read_only = use_read_only();
+    let services = use_state(|| None::<Vec<BreedingService>>);
+    let success = use_state(Vec::<MethodSuccess>::new);
+    let doe = use_state(String::new);
+    let served_on = use_state(String::new);
+    let method = use_state(|| ServiceMethod::Natural);
+    let buck = use_state(String::new);
+    let sire_name = use_state(String::new);
+    let sire_breed = use_state(String::new);
+    let sire_source = use_state(String::new);
+    let straw_id = use_state(String::new);
+    let technician = use_state(String::new);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let api = api.clone();
+        let services = services.clone();
+        let success = success.clone();
+        let error = error.clone();
+        move |_| {
+            load_services(api, services, success, error);
+            || {}
+        }
+    });
+
+    let on_input = |field: &UseStateHandle<String>| {
+        let field = field.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            field.set(input.value());
+        })
+    };
+    let on_select = |field: &UseStateHandle<String>| {
+        let field = field.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            field.set(select.value());
+        })
+    };
+    let on_method = {
+        let method = method.clone();
+        Callback::from(move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Ok(selected) = ServiceMethod::from_str(&select.value()) {
+                method.set(selected);
+            }
+        })
+    };
+
+    let on_add = {
+        let services = services.clone();
+        let success = success.clone();
+        let doe = doe.clone();
+        let served_on = served_on.clone();
+        let method = method.clone();
+        let buck = buck.clone();
+        let sire_name = sire_name.clone();
+        let sire_breed = sire_breed.clone();
+        let sire_source = sire_source.clone();
+        let straw_id = straw_id.clone();
+        let technician = technician.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            if doe.is_empty() || served_on.is_empty() {
+                error.set(Some("Choose the doe and the service date".to_string()));
+                return;
+            }
+            let artificial = *method == ServiceMethod::Artificial;
+            let service = BreedingService {
+                id: None,
+                doe_name: (*doe).clone(),
+                served_on: (*served_on).clone(),
+                method: *method,
+                buck_name: optional(&buck),
+                outside_sire: buck.is_empty().then(|| OutsideSire {
+                    name: sire_name.trim().to_string(),
+                    breed: optional(&sire_breed),
+                    registration: None,
+                    source: optional(&sire_source),
+                }),
+                straw_id: optional(&straw_id).filter(|_| artificial),
+                technician: optional(&technician).filter(|_| artificial),
+                notes: None,
+                outcome: None,
+            };
+            if let Err(e) = service.validate() {
+                error.set(Some(e));
+                return;
+            }
+            let api = api.clone();
+            let services = services.clone();
+            let success = success.clone();
+            let straw_id = straw_id.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.add_breeding_service(&service).await {
+                    Ok(()) => {
+                        info!("Recorded service of {}", service.doe_name);
+                        straw_id.set(String::new());
+                        error.set(None);
+                        message.set(Some(format!(
+                            "Recorded {} of {} by {}",
+                            service.method.label(),
+                            service.doe_name,
+                            service.sire_label()
+                        )));
+                        load_services(api, services, success, error);
+                    }
+                    Err(e) => {
+                        error!("Failed to record service: {}", e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let goats_of = |gender: Gender| {
+        state
+            .goats
+            .iter()
+            .filter(move |g| g.gender == gender)
+            .map(|g| g.name.clone())
+            .collect::<Vec<_>>()
+    };
+    let artificial = *method == ServiceMethod::Artificial;
+
+    html! {
+        <div id="services">
+            <h3>{"Services"}</h3>
+            <p>
+                <label>{"Doe: "}
+                    <select class="service-doe" onchange={on_select(&doe)}>
+                        <option value="" selected={doe.is_empty()}>{"Choose..."}</option>
+                        { for goats_of(Gender::Female).into_iter().map(|name| html! {
+                            <option value={name.clone()} selected={*doe == name}>{name.clone()}</option>
+                        }) }
+                    </select>
+                </label>
+                {" "}
+                <input class="service-date" type="date" value={(*served_on).clone()}
+                       oninput={on_input(&served_on)} />
+                {" "}
+                <select class="service-method" onchange={on_method}>
+                    { for ServiceMethod::ALL.into_iter().map(|m| html! {
+                        <option value={ServiceMethod::to_str(&m).to_string()} selected={m == *method}>
+                            {m.label()}
+                        </option>
+                    }) }
+                </select>
+                {" "}
+                <label>{"Sire: "}
+                    <select class="service-buck" onchange={on_select(&buck)}>
+                        <option value="" selected={buck.is_empty()}>{"Outside sire"}</option>
+                        { for goats_of(Gender::Male).into_iter().map(|name| html! {
+                            <option value={name.clone()} selected={*buck == name}>{name.clone()}</option>
+                        }) }
+                    </select>
+                </label>
+            </p>
+            if buck.is_empty() {
+                <p>
+                    <input class="outside-name" placeholder="Sire name" value={(*sire_name).clone()}
+                           oninput={on_input(&sire_name)} />
+                    {" "}
+                    <input class="outside-breed" placeholder="Breed" value={(*sire_breed).clone()}
+                           oninput={on_input(&sire_breed)} />
+                    {" "}
+                    <input class="outside-source" placeholder="Stud or semen bank"
+                           value={(*sire_source).clone()} oninput={on_input(&sire_source)} />
+                </p>
+            }
+            if artificial {
+                <p>
+                    <input class="service-straw" placeholder="Straw or batch ID"
+                           value={(*straw_id).clone()} oninput={on_input(&straw_id)} />
+                    {" "}
+                    <input class="service-technician" placeholder="Technician"
+                           value={(*technician).clone()} oninput={on_input(&technician)} />
+                </p>
+            }
+            <button class="add-service" onclick={on_add} disabled={read_only}>{"Record service"}</button>
+            if !success.is_empty() {
+                <ul class="service-success">
+                    { for success.iter().map(|s| html! {
+                        <li>
+                            {format!(
+                                "{}: {} services, {} kidded, {} open, {} pending",
+                                s.method.label(), s.services, s.kidded, s.open, s.pending
+                            )}
+                            if let Some(rate) = s.success_rate() {
+                                <strong>{format!(" ({:.0}% success)", rate * 100.0)}</strong>
+                            }
+                        </li>
+                    }) }
+                </ul>
+            }
+            if let Some(services) = &*services {
+                if services.is_empty() {
+                    <p>{"No services recorded yet."}</p>
+                } else {
+                    <table class="breeding-services">
+                        <tr>
+                            <th>{"Date"}</th><th>{"Doe"}</th><th>{"Method"}</th><th>{"Sire"}</th>
+                            <th>{"Straw"}</th><th>{"Technician"}</th><th>{"Outcome"}</th>
+                        </tr>
+                        { for services.iter().map(|s| html! {
+                            <tr key={s.id.unwrap_or_default()}>
+                                <td>{&s.served_on}</td>
+                                <td>{&s.doe_name}</td>
+                                <td>{s.method.label()}</td>
+                                <td>{s.sire_label()}</td>
+                                <td>{s.straw_id.clone().unwrap_or_default()}</td>
+                                <td>{s.technician.clone().unwrap_or_default()}</td>
+                                <td class="service-outcome">{outcome_label(s.outcome)}</td>
+                            </tr>
+                        }) }
+                    </table>
+                }
+            } else {
+                <Spinner label="Loading services..." />
+            }
+            if let Some(msg) = &*message {
+                <p style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Service error: {}", err)}</p>
+            }
+        </div>
+    }
+}
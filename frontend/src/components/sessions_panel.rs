@@ -0,0 +1,415 @@
+//! Signed-in devices panel: the sessions of the devices using the dashboard,
+//! with their last activity, and signing devices in and out, with the
+//! owner's password or a worker's PIN, or as a worker through an OAuth
+//! provider (see `shared::sessions`).
+
+use crate::components::Spinner;
+use crate::errors::AppError;
+use crate::services::use_api;
+use crate::store::{PermissionStore, farm_timezone};
+use log::{error, info};
+use shared::sessions::{MAX_DEVICE_NAME_LEN, NewSession, Session, describe_user_agent};
+use shared::time::format_local;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yewdux::prelude::use_dispatch;
+
+/// Name suggested for this device, after its browser.
+fn this_device() -> String {
+    web_sys::window()
+        .and_then(|w| w.navigator().user_agent().ok())
+        .map(|ua| describe_user_agent(&ua))
+        .unwrap_or_default()
+}
+
+/// The provider's answer when it sent the browser back here after an OAuth
+/// sign-in: `Ok((code, state))`, or `Err` with the provider's error, e.g.
+/// "access_denied" when the worker cancelled. The query is removed from the
+/// address so a reload does not try again.
+fn take_oauth_return() -> Option<Result<(String, String), String>> {
+    let window = web_sys::window()?;
+    let location = window.location();
+    let params = web_sys::UrlSearchParams::new_with_str(&location.search().ok()?).ok()?;
+    let answer = match (params.get("code"), params.get("state"), params.get("error")) {
+        (Some(code), Some(state), _) => Ok((code, state)),
+        (_, _, Some(error)) => Err(error),
+        _ => return None,
+    };
+    let path = location.pathname().ok()?;
+    if let Ok(history) = window.history() {
+        let _ = history.replace_state_with_url(&JsValue::NULL, "", Some(&path));
+    }
+    Some(answer)
+}
+
+/// The address OAuth providers send the browser back to: this page.
+fn oauth_redirect_uri() -> String {
+    web_sys::window()
+        .and_then(|w| {
+            let location = w.location();
+            Some(format!(
+                "{}{}",
+                location.origin().ok()?,
+                location.pathname().ok()?
+            ))
+        })
+        .unwrap_or_default()
+}
+
+/// "google" as "Google".
+fn provider_title(provider: &str) -> String {
+    let mut chars = provider.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// SessionsPanel component:
+/// Lists the signed-in devices, this one first marked "(this device)", with
+/// when each signed in and was last active and a Sign out button. A device
+/// that has not signed in, or was signed out from another one, gets a form
+/// to sign in under a name of its choice with the owner's password, or a
+/// worker's name and PIN, or as a worker with one of the OAuth providers
+/// the server offers. On a server hosting many farms, the form also takes
+/// the farm's key, which later requests then go to. Coming back from a
+/// provider finishes that sign-in. "Sign out all other devices" revokes every session but
+/// this one. Signing in reloads what the dashboard may offer (see
+/// `PermissionStore`).
+#[function_component(SessionsPanel)]
+pub fn sessions_panel() -> Html {
+    let api = use_api();
+    let permissions = use_dispatch::<PermissionStore>();
+    let sessions = use_state(|| None::<Vec<Session>>);
+    // Set when this device's session was revoked
+    let signed_out = use_state(|| false);
+    let device = use_state(this_device);
+    let worker = use_state(String::new);
+    let secret = use_state(String::new);
+    let farm = use_state(String::new);
+    let providers = use_state(Vec::<String>::new);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+    // Bumped after each change to reload the sessions
+    let reloads = use_state(|| 0u32);
+
+    use_effect_with(*reloads, {
+        let api = api.clone();
+        let sessions = sessions.clone();
+        let signed_out = signed_out.clone();
+        let error = error.clone();
+        move |_: &u32| {
+            spawn_local(async move {
+                match api.sessions().await {
+                    Ok(mut loaded) => {
+                        loaded.sort_by_key(|s| !s.current);
+                        signed_out.set(false);
+                        sessions.set(Some(loaded));
+                    }
+                    Err(AppError::ApiError { status: 401, .. }) => {
+                        info!("This device was signed out");
+                        signed_out.set(true);
+                        sessions.set(Some(Vec::new()));
+                    }
+                    Err(e) => {
+                        error!("Failed to load sessions: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+            || {}
+        }
+    });
+
+    use_effect_with((), {
+        let api = api.clone();
+        let permissions = permissions.clone();
+        let farm = farm.clone();
+        let providers = providers.clone();
+        let message = message.clone();
+        let error = error.clone();
+        let reloads = reloads.clone();
+        move |_| {
+            spawn_local(async move {
+                match api.farm_key().await {
+                    Ok(key) => farm.set(key.unwrap_or_default()),
+                    Err(e) => error!("Failed to read the farm key: {}", e),
+                }
+                match api.oauth_providers().await {
+                    Ok(loaded) => providers.set(loaded),
+                    Err(e) => error!("Failed to load sign-in providers: {}", e),
+                }
+                match take_oauth_return() {
+                    Some(Ok((code, state))) => match api.finish_oauth(&code, &state).await {
+                        Ok(issued) => {
+                            info!("Signed in as session {}", issued.session.id);
+                            let who = issued.session.worker.unwrap_or(issued.session.device);
+                            message.set(Some(format!("Signed in as {}", who)));
+                            reloads.set(*reloads + 1);
+                            PermissionStore::load(api, permissions);
+                        }
+                        Err(e) => {
+                            error!("Failed to finish signing in: {}", e);
+                            error.set(Some(e.to_string()));
+                        }
+                    },
+                    Some(Err(reason)) => {
+                        info!("OAuth sign-in did not complete: {}", reason);
+                        error.set(Some(format!("Sign-in was not completed ({})", reason)));
+                    }
+                    None => {}
+                }
+            });
+            || {}
+        }
+    });
+
+    let on_input = |field: &UseStateHandle<String>| {
+        let field = field.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            field.set(input.value());
+        })
+    };
+
+    let on_sign_in = {
+        let api = api.clone();
+        let device = device.clone();
+        let worker = worker.clone();
+        let secret = secret.clone();
+        let farm = farm.clone();
+        let message = message.clone();
+        let error = error.clone();
+        let reloads = reloads.clone();
+        Callback::from(move |_: MouseEvent| {
+            if device.trim().chars().count() > MAX_DEVICE_NAME_LEN {
+                error.set(Some(format!(
+                    "Device names must be at most {} characters",
+                    MAX_DEVICE_NAME_LEN
+                )));
+                return;
+            }
+            let api = api.clone();
+            let new_session = NewSession {
+                device: device.trim().to_string(),
+                worker: Some(worker.trim().to_string()).filter(|w| !w.is_empty()),
+                secret: (*secret).clone(),
+            };
+            let secret = secret.clone();
+            let farm = (*farm).clone();
+            let message = message.clone();
+            let error = error.clone();
+            let reloads = reloads.clone();
+            let permissions = permissions.clone();
+            spawn_local(async move {
+                let signed_in = match api.select_farm(Some(&farm)).await {
+                    Ok(()) => api.sign_in(&new_session).await,
+                    Err(e) => Err(e),
+                };
+                match signed_in {
+                    Ok(issued) => {
+                        info!("Signed in as session {}", issued.session.id);
+                        secret.set(String::new());
+                        error.set(None);
+                        let who = issued.session.worker.unwrap_or(issued.session.device);
+                        message.set(Some(format!("Signed in as {}", who)));
+                        reloads.set(*reloads + 1);
+                        PermissionStore::load(api, permissions);
+                    }
+                    Err(e) => {
+                        error!("Failed to sign in: {}", e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_oauth = {
+        let api = api.clone();
+        let device = device.clone();
+        let farm = farm.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |provider: String| {
+            let api = api.clone();
+            let device = device.trim().to_string();
+            let farm = (*farm).clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                let started = match api.select_farm(Some(&farm)).await {
+                    Ok(()) => {
+                        api.start_oauth(&provider, &oauth_redirect_uri(), &device)
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+                match started {
+                    Ok(redirect) => {
+                        info!("Sending the browser to {} to sign in", provider);
+                        if let Some(window) = web_sys::window()
+                            && let Err(e) = window.location().set_href(&redirect.authorize_url)
+                        {
+                            error!("Failed to open the sign-in page: {:?}", e);
+                        }
+                    }
+                    Err(e) => {
+                        error!("Failed to start signing in with {}: {}", provider, e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_revoke = {
+        let api = api.clone();
+        let message = message.clone();
+        let error = error.clone();
+        let reloads = reloads.clone();
+        Callback::from(move |session: Session| {
+            let api = api.clone();
+            let message = message.clone();
+            let error = error.clone();
+            let reloads = reloads.clone();
+            spawn_local(async move {
+                match api.revoke_session(session.id).await {
+                    Ok(()) => {
+                        info!("Signed out session {}", session.id);
+                        error.set(None);
+                        message.set(Some(format!("Signed out {}", session.device)));
+                        reloads.set(*reloads + 1);
+                    }
+                    Err(e) => {
+                        error!("Failed to sign out session {}: {}", session.id, e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_revoke_others = {
+        let message = message.clone();
+        let error = error.clone();
+        let reloads = reloads.clone();
+        Callback::from(move |_: MouseEvent| {
+            let api = api.clone();
+            let message = message.clone();
+            let error = error.clone();
+            let reloads = reloads.clone();
+            spawn_local(async move {
+                match api.revoke_other_sessions().await {
+                    Ok(revoked) => {
+                        info!("Signed out {} other devices", revoked);
+                        error.set(None);
+                        message.set(Some(format!("Signed out {} other devices", revoked)));
+                        reloads.set(*reloads + 1);
+                    }
+                    Err(e) => {
+                        error!("Failed to sign out other devices: {}", e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let timezone = farm_timezone();
+    let signed_in = sessions
+        .as_ref()
+        .is_some_and(|sessions| sessions.iter().any(|s| s.current));
+
+    html! {
+        <div>
+            <h3>{"Signed-in Devices"}</h3>
+            if let Some(sessions) = &*sessions {
+                if *signed_out {
+                    <p class="signed-out" style="color: #8a6d00;">
+                        {"This device was signed out. Sign in again to see the other devices."}
+                    </p>
+                }
+                if !signed_in {
+                    <p>
+                        <label>{"Device name: "}
+                            <input class="device-name" value={(*device).clone()}
+                                   oninput={on_input(&device)} />
+                        </label>
+                        {" "}
+                        <label>{"Worker: "}
+                            <input class="sign-in-worker" placeholder="Blank for the owner"
+                                   value={(*worker).clone()} oninput={on_input(&worker)} />
+                        </label>
+                        {" "}
+                        <label>{"PIN or password: "}
+                            <input class="sign-in-secret" type="password"
+                                   value={(*secret).clone()} oninput={on_input(&secret)} />
+                        </label>
+                        {" "}
+                        <label>{"Farm key: "}
+                            <input class="sign-in-farm" type="password"
+                                   placeholder="Only for hosted farms"
+                                   value={(*farm).clone()} oninput={on_input(&farm)} />
+                        </label>
+                        {" "}
+                        <button onclick={on_sign_in}>{"Sign in this device"}</button>
+                        { for providers.iter().map(|provider| {
+                            let name = provider.clone();
+                            let on_oauth = on_oauth.reform(move |_: MouseEvent| name.clone());
+                            html! {
+                                <>
+                                    {" "}
+                                    <button class="oauth-sign-in" onclick={on_oauth}>
+                                        {format!("Sign in with {}", provider_title(provider))}
+                                    </button>
+                                </>
+                            }
+                        }) }
+                    </p>
+                }
+                if !sessions.is_empty() {
+                    <table class="sessions">
+                        <tr><th>{"Device"}</th><th>{"Worker"}</th><th>{"Signed in"}</th><th>{"Last active"}</th><th></th></tr>
+                        { for sessions.iter().map(|s| {
+                            let session = s.clone();
+                            let on_revoke = on_revoke.reform(move |_: MouseEvent| session.clone());
+                            html! {
+                                <tr title={s.user_agent.clone().unwrap_or_default()}>
+                                    <td>
+                                        {&s.device}
+                                        if s.current { {" (this device)"} }
+                                        if let Some(ip) = &s.ip {
+                                            <span style="font-size: 12px; color: #666;">{format!(" {}", ip)}</span>
+                                        }
+                                    </td>
+                                    <td class="session-worker">{s.worker.clone().unwrap_or_default()}</td>
+                                    <td>{format_local(&s.created_at, timezone)}</td>
+                                    <td>{format_local(&s.last_seen_at, timezone)}</td>
+                                    <td><button class="revoke-session" onclick={on_revoke}>{"Sign out"}</button></td>
+                                </tr>
+                            }
+                        }) }
+                    </table>
+                }
+                if signed_in && sessions.len() > 1 {
+                    <button onclick={on_revoke_others}>{"Sign out all other devices"}</button>
+                }
+            } else {
+                <Spinner label="Loading devices..." />
+            }
+            if let Some(msg) = &*message {
+                <p style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Session error: {}", err)}</p>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,406 @@
+//! First-run setup: names the farm, picks its units, currency, language and
+//! time zone, adds its pens and brings in its first goats, a step at a time
+//! (see `shared::setup`). Each step reached is saved in the farm settings,
+//! so setup resumes where it was left.
+
+use crate::components::{AddGoatForm, ErrorBoundary, ImportWizard};
+use crate::services::use_api;
+use crate::store::{GoatStore, UnitsStore, use_read_only};
+use chrono_tz::TZ_VARIANTS;
+use log::{error, info, warn};
+use shared::settings::{FarmSettings, LANGUAGES};
+use shared::setup::SetupStep;
+use shared::spaces::{Space, SpaceKind};
+use shared::units::WeightUnit;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yewdux::prelude::{use_store, use_store_value};
+
+/// Where setup stands.
+#[derive(Debug, Clone, PartialEq)]
+enum Setup {
+    /// The settings are being read.
+    Loading,
+    /// On `draft.setup_step`, with the settings as edited so far.
+    Open {
+        draft: Box<FarmSettings>,
+        saving: bool,
+        error: Option<String>,
+    },
+    /// Finished, skipped, or the settings could not be read.
+    Closed,
+}
+
+enum SetupAction {
+    /// The settings were read or saved; setup moves to their step.
+    Loaded(FarmSettings),
+    /// A field of the current step changed.
+    Edit(FarmSettings),
+    Saving,
+    Failed(String),
+    /// The settings could not be read.
+    Unavailable,
+}
+
+impl Reducible for Setup {
+    type Action = SetupAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let next = match (action, (*self).clone()) {
+            (SetupAction::Loaded(settings), _) if settings.setup_step == SetupStep::Done => {
+                Setup::Closed
+            }
+            (SetupAction::Loaded(settings), _) => Setup::Open {
+                draft: Box::new(settings),
+                saving: false,
+                error: None,
+            },
+            (SetupAction::Edit(draft), Setup::Open { saving, error, .. }) => Setup::Open {
+                draft: Box::new(draft),
+                saving,
+                error,
+            },
+            (SetupAction::Saving, Setup::Open { draft, .. }) => Setup::Open {
+                draft,
+                saving: true,
+                error: None,
+            },
+            (SetupAction::Failed(message), Setup::Open { draft, .. }) => Setup::Open {
+                draft,
+                saving: false,
+                error: Some(message),
+            },
+            (SetupAction::Unavailable, _) => Setup::Closed,
+            // Edits and saves only apply while setup is open
+            (_, current) => current,
+        };
+        Rc::new(next)
+    }
+}
+
+/// Problems with the fields on `step` of `draft`.
+fn step_problem(step: SetupStep, draft: &FarmSettings) -> Option<String> {
+    match step {
+        SetupStep::Farm if draft.farm_name.as_deref().unwrap_or("").trim().is_empty() => {
+            Some("Enter the farm's name.".to_string())
+        }
+        SetupStep::Preferences if draft.base_currency.trim().len() != 3 => {
+            Some("Enter a three-letter currency code, e.g. INR.".to_string())
+        }
+        _ => None,
+    }
+}
+
+/// SetupWizard component:
+/// Shown while the farm has not finished setup, and not in read-only mode.
+/// Walks through naming the farm; choosing the weight unit (this browser's
+/// preference), base currency, language and time zone; adding pens; and
+/// importing or adding the first goats with the usual `ImportWizard` and
+/// `AddGoatForm`. "Continue" and "Back" save the settings with the step
+/// moved to, and "Skip setup" marks it finished.
+#[function_component(SetupWizard)]
+pub fn setup_wizard() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let setup = use_reducer(|| Setup::Loading);
+    let (units, units_dispatch) = use_store::<UnitsStore>();
+    let goats = use_store_value::<GoatStore>().goats.len();
+    let pen_name = use_state(String::new);
+    let pen_capacity = use_state(String::new);
+    let pens = use_state(Vec::<String>::new);
+    let pen_error = use_state(|| None::<String>);
+
+    {
+        let api = api.clone();
+        let setup = setup.dispatcher();
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                match api.farm_settings().await {
+                    Ok(settings) => {
+                        info!("Farm setup is at {}", settings.setup_step.label());
+                        setup.dispatch(SetupAction::Loaded(settings));
+                    }
+                    Err(e) => {
+                        warn!("Failed to load the farm settings for setup: {}", e);
+                        setup.dispatch(SetupAction::Unavailable);
+                    }
+                }
+            });
+        });
+    }
+
+    let Setup::Open {
+        draft,
+        saving,
+        error,
+    } = (*setup).clone()
+    else {
+        return html! {};
+    };
+    if read_only {
+        return html! {};
+    }
+    let step = draft.setup_step;
+
+    // Saves the draft with setup moved to `to`
+    let move_to = {
+        let api = api.clone();
+        let setup = setup.dispatcher();
+        let units_dispatch = units_dispatch.clone();
+        let draft = draft.clone();
+        move |to: SetupStep| {
+            let api = api.clone();
+            let setup = setup.clone();
+            let units_dispatch = units_dispatch.clone();
+            let draft = draft.clone();
+            Callback::from(move |_: MouseEvent| {
+                if to.index() > step.index()
+                    && to != SetupStep::Done
+                    && let Some(problem) = step_problem(step, &draft)
+                {
+                    setup.dispatch(SetupAction::Failed(problem));
+                    return;
+                }
+                let updated = FarmSettings {
+                    base_currency: draft.base_currency.trim().to_uppercase(),
+                    setup_step: to,
+                    ..(*draft).clone()
+                };
+                let api = api.clone();
+                let setup = setup.clone();
+                let units_dispatch = units_dispatch.clone();
+                setup.dispatch(SetupAction::Saving);
+                spawn_local(async move {
+                    match api.update_settings(&updated).await {
+                        Ok(saved) => {
+                            info!("Farm setup moved to {}", saved.setup_step.label());
+                            UnitsStore::apply_settings(&units_dispatch, &saved);
+                            setup.dispatch(SetupAction::Loaded(saved));
+                        }
+                        Err(e) => {
+                            error!("Failed to save farm setup: {}", e);
+                            setup.dispatch(SetupAction::Failed(e.to_string()));
+                        }
+                    }
+                });
+            })
+        }
+    };
+
+    let edit = |update: fn(&mut FarmSettings, String)| {
+        let setup = setup.dispatcher();
+        let draft = draft.clone();
+        move |value: String| {
+            let mut updated = (*draft).clone();
+            update(&mut updated, value);
+            setup.dispatch(SetupAction::Edit(updated));
+        }
+    };
+    let text_field = |update: fn(&mut FarmSettings, String)| {
+        let edit = edit(update);
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                edit(input.value());
+            }
+        })
+    };
+    let select_field = |update: fn(&mut FarmSettings, String)| {
+        let edit = edit(update);
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                edit(select.value());
+            }
+        })
+    };
+    let on_weight_unit = {
+        let units_dispatch = units_dispatch.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>()
+                && let Some(unit) = WeightUnit::from_symbol(&select.value())
+            {
+                UnitsStore::set_weight_unit(units_dispatch.clone(), unit);
+            }
+        })
+    };
+
+    let on_pen_input = |field: UseStateHandle<String>| {
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                field.set(input.value());
+            }
+        })
+    };
+    let on_add_pen = {
+        let pen_name = pen_name.clone();
+        let pen_capacity = pen_capacity.clone();
+        let pens = pens.clone();
+        let pen_error = pen_error.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let name = pen_name.trim().to_string();
+            if name.is_empty() {
+                pen_error.set(Some("Enter the pen's name.".to_string()));
+                return;
+            }
+            let capacity = match pen_capacity.trim() {
+                "" => None,
+                text => match text.parse::<i64>() {
+                    Ok(capacity) if capacity >= 0 => Some(capacity),
+                    _ => {
+                        pen_error.set(Some("Capacity must be a whole number.".to_string()));
+                        return;
+                    }
+                },
+            };
+            let space = Space {
+                id: None,
+                name: name.clone(),
+                kind: SpaceKind::Enclosure,
+                capacity,
+                area_m2: None,
+                goat_names: Vec::new(),
+            };
+            let api = api.clone();
+            let pen_name = pen_name.clone();
+            let pen_capacity = pen_capacity.clone();
+            let pens = pens.clone();
+            let pen_error = pen_error.clone();
+            spawn_local(async move {
+                match api.add_space(&space).await {
+                    Ok(()) => {
+                        info!("Added pen {} during setup", name);
+                        let mut added = (*pens).clone();
+                        added.push(name);
+                        pens.set(added);
+                        pen_name.set(String::new());
+                        pen_capacity.set(String::new());
+                        pen_error.set(None);
+                    }
+                    Err(e) => {
+                        error!("Failed to add pen {}: {}", name, e);
+                        pen_error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let page = match step {
+        SetupStep::Farm => html! {
+            <label>{"Farm name: "}
+                <input type="text" name="farm_name"
+                       value={draft.farm_name.clone().unwrap_or_default()}
+                       oninput={text_field(|d, v| d.farm_name = Some(v))} />
+            </label>
+        },
+        SetupStep::Preferences => html! {
+            <>
+                <label>{"Weights in "}
+                    <select name="weight_unit" onchange={on_weight_unit}>
+                        { for WeightUnit::ALL.iter().map(|unit| html! {
+                            <option value={unit.symbol()} selected={*unit == units.weight}>
+                                {unit.symbol()}
+                            </option>
+                        }) }
+                    </select>
+                </label>
+                <br/>
+                <label>{"Currency: "}
+                    <input type="text" name="base_currency" size="4" maxlength="3"
+                           value={draft.base_currency.clone()}
+                           oninput={text_field(|d, v| d.base_currency = v)} />
+                </label>
+                <br/>
+                <label>{"Language: "}
+                    <select name="language" onchange={select_field(|d, v| d.language = v)}>
+                        { for LANGUAGES.iter().map(|(code, name)| html! {
+                            <option value={*code} selected={*code == draft.language}>{*name}</option>
+                        }) }
+                    </select>
+                </label>
+                <br/>
+                <label>{"Time zone: "}
+                    <select name="timezone" onchange={select_field(|d, v| d.timezone = v)}>
+                        { for TZ_VARIANTS.iter().map(|tz| html! {
+                            <option value={tz.name()} selected={tz.name() == draft.timezone}>
+                                {tz.name()}
+                            </option>
+                        }) }
+                    </select>
+                </label>
+            </>
+        },
+        SetupStep::Pens => html! {
+            <>
+                if pens.is_empty() {
+                    <p>{"Add the pens your goats are kept in. You can add more later."}</p>
+                } else {
+                    <ul class="setup-pens">
+                        { for pens.iter().map(|pen| html! { <li>{pen}</li> }) }
+                    </ul>
+                }
+                <form class="add-pen" onsubmit={on_add_pen}>
+                    <input type="text" name="pen_name" placeholder="Pen name"
+                           value={(*pen_name).clone()} oninput={on_pen_input(pen_name.clone())} />
+                    { " " }
+                    <input type="number" name="pen_capacity" placeholder="Capacity" min="0"
+                           value={(*pen_capacity).clone()}
+                           oninput={on_pen_input(pen_capacity.clone())} />
+                    { " " }
+                    <button type="submit">{"Add pen"}</button>
+                </form>
+                if let Some(err) = &*pen_error {
+                    <p style="color: red;">{err}</p>
+                }
+            </>
+        },
+        SetupStep::Goats => html! {
+            <>
+                <p>{format!("{} goats so far. Import a spreadsheet or add them one by one.", goats)}</p>
+                <ErrorBoundary name="Import Goats">
+                    <ImportWizard />
+                </ErrorBoundary>
+                <ErrorBoundary name="Add Goat">
+                    <AddGoatForm />
+                </ErrorBoundary>
+            </>
+        },
+        SetupStep::Done => html! {},
+    };
+
+    html! {
+        <section class="setup-wizard" data-step={SetupStep::to_str(&step).to_string()}
+                 style="margin: 24px; padding: 16px; border: 2px solid #2c3e50;">
+            <h2>{"Set up your farm"}</h2>
+            <ol class="setup-steps" style="display: flex; gap: 16px; padding-left: 16px;">
+                { for SetupStep::ALL[..SetupStep::Done.index()].iter().map(|s| html! {
+                    <li style={if *s == step { "font-weight: bold;" } else { "" }}>{s.label()}</li>
+                }) }
+            </ol>
+            { page }
+            if let Some(err) = &error {
+                <p style="color: red;">{err}</p>
+            }
+            <div style="margin-top: 12px;">
+                if step != SetupStep::Farm {
+                    <button type="button" name="setup_back" disabled={saving}
+                            onclick={move_to(step.previous())}>
+                        {"Back"}
+                    </button>
+                    { " " }
+                }
+                <button type="button" name="setup_continue" disabled={saving}
+                        onclick={move_to(step.next())}>
+                    { if step == SetupStep::Goats { "Finish" } else { "Continue" } }
+                </button>
+                { " " }
+                <button type="button" name="setup_skip" disabled={saving}
+                        onclick={move_to(SetupStep::Done)}>
+                    {"Skip setup"}
+                </button>
+            </div>
+        </section>
+    }
+}
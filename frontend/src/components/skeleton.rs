@@ -0,0 +1,46 @@
+//! Placeholder UI shown while data for a section is still in flight.
+//!
+//! `SkeletonRows` fills table bodies with shimmering placeholder cells and
+//! `Spinner` marks a single pending action, so progress is shown only
+//! where the affected data will appear.
+
+use yew::prelude::*;
+
+/// Props for SkeletonRows:
+/// - `rows`: number of placeholder rows to render
+/// - `columns`: number of cells per row, matching the table header
+#[derive(Properties, PartialEq)]
+pub struct SkeletonRowsProps {
+    pub rows: usize,
+    pub columns: usize,
+}
+
+#[function_component(SkeletonRows)]
+pub fn skeleton_rows(props: &SkeletonRowsProps) -> Html {
+    html! {
+        { for (0..props.rows).map(|_| html! {
+            <tr class="skeleton-row" aria-hidden="true">
+                { for (0..props.columns).map(|_| html! {
+                    <td><span class="skeleton-cell"></span></td>
+                })}
+            </tr>
+        })}
+    }
+}
+
+/// Props for Spinner:
+/// - `label`: text shown next to the spinner, also used for screen readers
+#[derive(Properties, PartialEq)]
+pub struct SpinnerProps {
+    pub label: AttrValue,
+}
+
+#[function_component(Spinner)]
+pub fn spinner(props: &SpinnerProps) -> Html {
+    html! {
+        <span class="spinner" role="status">
+            <span class="spinner-icon" aria-hidden="true"></span>
+            { " " }{ props.label.clone() }
+        </span>
+    }
+}
@@ -0,0 +1,82 @@
+//! Confirmation step for values that are allowed but look like mistakes
+//! (see `shared::import::goat_warnings`).
+
+use std::cell::Cell;
+use std::rc::Rc;
+use yew::prelude::*;
+
+/// State behind a form's "Submit anyway" confirmation.
+#[derive(Clone, PartialEq)]
+pub struct SoftWarningsHandle {
+    shown: UseStateHandle<Vec<String>>,
+    confirmed: Rc<Cell<bool>>,
+}
+
+impl SoftWarningsHandle {
+    /// Decides whether a submission with `warnings` must wait for the user.
+    /// Returns `true`, and shows the warnings, unless there are none or the
+    /// user just pressed "Submit anyway" on these same warnings.
+    pub fn hold(&self, warnings: Vec<String>) -> bool {
+        let confirmed = self.confirmed.replace(false);
+        if warnings.is_empty() || (confirmed && warnings == *self.shown) {
+            self.clear();
+            return false;
+        }
+        self.shown.set(warnings);
+        true
+    }
+
+    /// Hides the warnings, e.g. once the form is reset.
+    pub fn clear(&self) {
+        if !self.shown.is_empty() {
+            self.shown.set(Vec::new());
+        }
+    }
+
+    /// Warnings waiting for confirmation.
+    pub fn shown(&self) -> Vec<String> {
+        (*self.shown).clone()
+    }
+
+    /// Marks the next `hold` as confirmed by the user.
+    pub fn confirm(&self) {
+        self.confirmed.set(true);
+    }
+}
+
+/// Keeps the warnings shown by a form and whether the user confirmed them.
+#[hook]
+pub fn use_soft_warnings() -> SoftWarningsHandle {
+    let shown = use_state(Vec::<String>::new);
+    let confirmed = use_memo((), |_| Cell::new(false));
+    SoftWarningsHandle { shown, confirmed }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SoftWarningsProps {
+    pub warnings: Vec<String>,
+    /// Called by "Submit anyway". Inside a form the button also submits it.
+    pub on_confirm: Callback<MouseEvent>,
+}
+
+/// SoftWarnings component:
+/// Lists the warnings of a held submission with a "Submit anyway" button.
+/// Renders nothing when there are no warnings.
+#[function_component(SoftWarnings)]
+pub fn soft_warnings(props: &SoftWarningsProps) -> Html {
+    if props.warnings.is_empty() {
+        return html! {};
+    }
+    html! {
+        <div class="validation-warnings"
+             style="background: #fff8e1; border: 1px solid #f9a825; padding: 8px; margin: 8px 0;">
+            <p style="margin: 0 0 4px;">{"Please check these values:"}</p>
+            <ul style="margin: 0 0 8px;">
+                { for props.warnings.iter().map(|w| html! { <li>{w}</li> }) }
+            </ul>
+            <button type="submit" class="submit-anyway" onclick={props.on_confirm.clone()}>
+                {"Submit anyway"}
+            </button>
+        </div>
+    }
+}
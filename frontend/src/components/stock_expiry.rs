@@ -0,0 +1,159 @@
+//! Stock expiry: medicines and other supplies that have expired or expire
+//! within 30 days, each disposable in one click.
+
+use crate::components::SkeletonRows;
+use crate::services::use_api;
+use crate::store::use_read_only;
+use log::{error, info};
+use shared::inventory::ExpiringStock;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// How long until or since an item's expiry, e.g. "Expired 3 days ago".
+fn expiry_label(days_left: i64) -> String {
+    match days_left {
+        d if d < -1 => format!("Expired {} days ago", -d),
+        -1 => "Expired yesterday".to_string(),
+        0 => "Expires today".to_string(),
+        1 => "Expires tomorrow".to_string(),
+        d => format!("Expires in {} days", d),
+    }
+}
+
+/// StockExpiry component:
+/// Lists stock that has expired or expires soon with its quantity and
+/// value, expired items first and shaded. "Dispose & log" empties the item,
+/// logs the loss as consumption and writes its cost off as an expense.
+#[function_component(StockExpiry)]
+pub fn stock_expiry() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let stock = use_state(|| None::<Vec<ExpiringStock>>);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    let load = {
+        let api = api.clone();
+        let stock = stock.clone();
+        let error = error.clone();
+        Callback::from(move |_: ()| {
+            let api = api.clone();
+            let stock = stock.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.expiring_stock().await {
+                    Ok(loaded) => {
+                        info!("Loaded {} expiring items", loaded.len());
+                        error.set(None);
+                        stock.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load expiring stock: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    use_effect_with((), {
+        let load = load.clone();
+        move |_| {
+            load.emit(());
+            || ()
+        }
+    });
+
+    let on_dispose = {
+        let load = load.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |id: i64| {
+            let api = api.clone();
+            let load = load.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.dispose_item(id).await {
+                    Ok(disposal) => {
+                        info!("Disposed of item {}", id);
+                        error.set(None);
+                        message.set(Some(format!(
+                            "Disposed of {} {} {}; {:.2} written off",
+                            disposal.quantity, disposal.unit, disposal.item_name, disposal.value
+                        )));
+                        load.emit(());
+                    }
+                    Err(e) => {
+                        error!("Failed to dispose of item {}: {}", id, e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <div>
+            <h3>{"Stock Expiry"}</h3>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error: {}", err)}</p>
+            }
+            if let Some(msg) = &*message {
+                <p class="disposal-message" style="color: green;">{msg}</p>
+            }
+            {
+                match &*stock {
+                    None => html! {
+                        <table><tbody><SkeletonRows rows={2} columns={5} /></tbody></table>
+                    },
+                    Some(list) if list.is_empty() => html! {
+                        <p>{"Nothing in stock expires within 30 days."}</p>
+                    },
+                    Some(list) => {
+                        let expired = list.iter().filter(|s| s.expired()).count();
+                        let value: f64 = list.iter().filter(|s| s.expired()).map(|s| s.value).sum();
+                        html! {
+                            <>
+                                if expired > 0 {
+                                    <p class="expired-summary" style="color: #b71c1c; font-weight: bold;">
+                                        {format!("{} expired item(s) worth {:.2} still in stock", expired, value)}
+                                    </p>
+                                }
+                                <table class="stock-expiry" style="border-collapse: collapse; width: 100%;">
+                                    <thead>
+                                        <tr>
+                                            <th>{"Item"}</th>
+                                            <th>{"In stock"}</th>
+                                            <th>{"Value"}</th>
+                                            <th>{"Expiry"}</th>
+                                            <th></th>
+                                        </tr>
+                                    </thead>
+                                    <tbody>
+                                        { for list.iter().filter_map(|s| {
+                                            let id = s.item.id?;
+                                            let style = s.expired().then_some("background: #fdecea; color: #b71c1c;");
+                                            Some(html! {
+                                                <tr data-item={s.item.name.clone()} {style}>
+                                                    <td>{&s.item.name}</td>
+                                                    <td>{format!("{} {}", s.item.quantity, s.item.unit)}</td>
+                                                    <td>{format!("{:.2}", s.value)}</td>
+                                                    <td class="expiry">{expiry_label(s.days_left)}</td>
+                                                    <td>
+                                                        <button class="dispose-item" onclick={on_dispose.reform(move |_| id)} disabled={read_only}>{"Dispose & log"}</button>
+                                                    </td>
+                                                </tr>
+                                            })
+                                        }) }
+                                    </tbody>
+                                </table>
+                            </>
+                        }
+                    }
+                }
+            }
+        </div>
+    }
+}
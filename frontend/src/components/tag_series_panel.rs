@@ -0,0 +1,199 @@
+//! Tag series panel: the farm's tag numbering scheme, from which the
+//! backend tags each goat added (see `shared::tags`).
+
+use crate::errors::AppError;
+use crate::services::{Api, use_api};
+use crate::store::use_read_only;
+use log::{error, info};
+use shared::settings::FarmSettings;
+use shared::tags::{DEFAULT_TAG_DIGITS, TagSeries};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Reads the entered scheme into a series. A blank prefix turns the series
+/// off, so new goats are left untagged.
+pub fn series_from_inputs(
+    prefix: &str,
+    yearly: bool,
+    digits: &str,
+) -> Result<Option<TagSeries>, String> {
+    if prefix.trim().is_empty() {
+        return Ok(None);
+    }
+    let series = TagSeries {
+        prefix: prefix.trim().to_string(),
+        yearly,
+        digits: digits
+            .trim()
+            .parse()
+            .map_err(|_| "Tag numbers need a whole number of digits".to_string())?,
+    };
+    series.validate()?;
+    Ok(Some(series))
+}
+
+/// Reloads the tag the next goat will get, reporting failures in `error`.
+fn load_next_tag(
+    api: Api,
+    next_tag: UseStateHandle<Option<String>>,
+    error: UseStateHandle<Option<String>>,
+) {
+    spawn_local(async move {
+        match api.next_tag().await {
+            Ok(next) => next_tag.set(next.tag_id),
+            Err(e) => {
+                error!("Failed to load the next tag: {}", e);
+                error.set(Some(e.to_string()));
+            }
+        }
+    });
+}
+
+/// TagSeriesPanel component:
+/// Edits the farm's tag series, a prefix with the year if asked and a
+/// counter padded to a number of digits, e.g. "YG-2026-0001", and shows the
+/// tag the next goat added will get. Clearing the prefix turns tagging off.
+/// Saving is disabled in read-only mode.
+#[function_component(TagSeriesPanel)]
+pub fn tag_series_panel() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let prefix = use_state(String::new);
+    let yearly = use_state(|| true);
+    let digits = use_state(|| DEFAULT_TAG_DIGITS.to_string());
+    let next_tag = use_state(|| None::<String>);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let api = api.clone();
+        let prefix = prefix.clone();
+        let yearly = yearly.clone();
+        let digits = digits.clone();
+        let next_tag = next_tag.clone();
+        let error = error.clone();
+        move |_| {
+            spawn_local(async move {
+                match api.farm_settings().await {
+                    Ok(settings) => {
+                        if let Some(series) = settings.tag_series {
+                            prefix.set(series.prefix);
+                            yearly.set(series.yearly);
+                            digits.set(series.digits.to_string());
+                        }
+                        load_next_tag(api, next_tag, error);
+                    }
+                    Err(e) => {
+                        error!("Failed to load farm settings: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+            || {}
+        }
+    });
+
+    let on_input = |state: &UseStateHandle<String>| {
+        let state = state.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            state.set(input.value());
+        })
+    };
+    let on_yearly = {
+        let yearly = yearly.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            yearly.set(input.checked());
+        })
+    };
+
+    let on_save = {
+        let prefix = prefix.clone();
+        let yearly = yearly.clone();
+        let digits = digits.clone();
+        let next_tag = next_tag.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let tag_series = match series_from_inputs(&prefix, *yearly, &digits) {
+                Ok(series) => series,
+                Err(e) => {
+                    error.set(Some(e));
+                    return;
+                }
+            };
+            let api = api.clone();
+            let next_tag = next_tag.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                let result: Result<FarmSettings, AppError> = async {
+                    let current = api.farm_settings().await?;
+                    api.update_settings(&FarmSettings {
+                        tag_series: tag_series.clone(),
+                        ..current
+                    })
+                    .await
+                }
+                .await;
+                match result {
+                    Ok(_) => {
+                        info!("Saved tag series {:?}", tag_series);
+                        error.set(None);
+                        message.set(Some(match tag_series {
+                            Some(_) => "Tag series saved".to_string(),
+                            None => "New goats are no longer tagged".to_string(),
+                        }));
+                        load_next_tag(api, next_tag, error);
+                    }
+                    Err(e) => {
+                        error!("Failed to save tag series: {}", e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <div>
+            <h3>{"Tag Series"}</h3>
+            <p>
+                <label>{"Prefix "}
+                    <input class="tag-prefix" placeholder="e.g. YG" value={(*prefix).clone()}
+                           oninput={on_input(&prefix)} style="width: 6em;" />
+                </label>
+                {" "}
+                <label>
+                    <input type="checkbox" class="tag-yearly" checked={*yearly}
+                           onchange={on_yearly} />
+                    {" Add the year and restart each year"}
+                </label>
+                {" "}
+                <label>{"Digits "}
+                    <input type="number" class="tag-digits" min="1" value={(*digits).clone()}
+                           oninput={on_input(&digits)} style="width: 4em;" />
+                </label>
+                {" "}
+                <button class="save-tag-series" onclick={on_save} disabled={read_only}>
+                    {"Save tag series"}
+                </button>
+            </p>
+            <p class="next-tag" style="font-size: 12px;">
+                {match &*next_tag {
+                    Some(tag) => format!("The next goat added is tagged {}.", tag),
+                    None => "New goats are not tagged. Set a prefix to start a series.".to_string(),
+                }}
+            </p>
+            if let Some(msg) = &*message {
+                <p class="tag-message" style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error: {}", err)}</p>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,104 @@
+//! Table of farm tasks, both entered manually and generated by reminder
+//! rules, so the day's work can be reviewed and sorted by due date or goat.
+
+use crate::components::{Column, DataTable, EmptyState, RowId};
+use crate::services::use_api;
+use log::{error, info};
+use shared::tasks::{Task, TaskStatus};
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// TasksList component:
+/// Loads every task on mount and on Refresh and lists them in a
+/// `DataTable`, earliest due first. Selecting a task, by click or with the
+/// arrow keys, shows its notes beneath the table. Rows have `task-{id}`
+/// element IDs.
+#[function_component(TasksList)]
+pub fn tasks_list() -> Html {
+    let api = use_api();
+    let tasks = use_state(|| None::<Vec<Task>>);
+    let error = use_state(|| None::<String>);
+    let reloads = use_state(|| 0u32);
+    let selected = use_state(|| None::<Task>);
+
+    {
+        let tasks = tasks.clone();
+        let error = error.clone();
+        use_effect_with(*reloads, move |_| {
+            spawn_local(async move {
+                match api.tasks().await {
+                    Ok(loaded) => {
+                        info!("Loaded {} tasks", loaded.len());
+                        error.set(None);
+                        tasks.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load tasks: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        });
+    }
+
+    let refresh = {
+        let reloads = reloads.clone();
+        Callback::from(move |_: MouseEvent| reloads.set(*reloads + 1))
+    };
+    let on_select = {
+        let selected = selected.clone();
+        Callback::from(move |task: Task| selected.set(Some(task)))
+    };
+
+    let columns = vec![
+        Column::text("due_date", "Due", |task: &Task| task.due_date.clone()),
+        Column::text("title", "Task", |task: &Task| task.title.clone()),
+        Column::text("goat", "Goat", |task: &Task| {
+            task.goat_name.clone().unwrap_or_else(|| "-".to_string())
+        }),
+        Column::text("pen", "Pen", |task: &Task| {
+            task.space_name.clone().unwrap_or_else(|| "-".to_string())
+        }),
+        Column::text("status", "Status", |task: &Task| {
+            TaskStatus::to_str(&task.status).to_string()
+        }),
+    ];
+
+    let row_id: RowId<Task> =
+        Rc::new(|task: &Task| format!("task-{}", task.id.unwrap_or_default()));
+    html! {
+        <div style="margin-bottom: 24px;">
+            <h3>{"Tasks"}</h3>
+            <button onclick={refresh}>{"Refresh"}</button>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading tasks: {}", err)}</p>
+            }
+            {
+                match &*tasks {
+                    None => html! { <p>{"Loading tasks..."}</p> },
+                    Some(tasks) if tasks.is_empty() => html! {
+                        <EmptyState
+                            title="No tasks yet"
+                            message="Tasks from reminder rules and ones you add will be listed here."
+                            sample="2024-06-01 · Trim hooves · Pen A · Pending"
+                        />
+                    },
+                    Some(tasks) => html! {
+                        <DataTable<Task>
+                            rows={tasks.clone()}
+                            {columns}
+                            {row_id}
+                            on_select={Some(on_select)}
+                        />
+                    },
+                }
+            }
+            if let Some(task) = &*selected {
+                <p class="task-notes" style="font-size: 12px;">
+                    {format!("{}: {}", task.title, task.notes.as_deref().unwrap_or("No notes."))}
+                </p>
+            }
+        </div>
+    }
+}
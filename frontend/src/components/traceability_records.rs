@@ -0,0 +1,95 @@
+//! Traceability records of a sold goat, with the public share links given
+//! to buyers (see `shared::traceability`).
+
+use crate::services::api::traceability_share_url;
+use crate::services::use_api;
+use log::{error, info};
+use shared::traceability::TraceabilityRecord;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct TraceabilityRecordsProps {
+    pub goat_name: String,
+}
+
+/// TraceabilityRecords component:
+/// Lists the records taken when the goat was sold, each with its origin
+/// farm, buyer, health history summary and movement record, and the public
+/// share link to give the buyer. Renders nothing for goats never sold.
+#[function_component(TraceabilityRecords)]
+pub fn traceability_records(props: &TraceabilityRecordsProps) -> Html {
+    let api = use_api();
+    let records = use_state(Vec::<TraceabilityRecord>::new);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with(props.goat_name.clone(), {
+        let records = records.clone();
+        let error = error.clone();
+        move |goat_name: &String| {
+            let goat_name = goat_name.clone();
+            spawn_local(async move {
+                match api.traceability(&goat_name).await {
+                    Ok(loaded) => {
+                        info!("Loaded {} traceability records", loaded.len());
+                        records.set(loaded);
+                    }
+                    Err(e) => {
+                        error!("Failed to load traceability records: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+            || {}
+        }
+    });
+
+    if let Some(err) = &*error {
+        return html! { <p style="color: red;">{format!("Error: {}", err)}</p> };
+    }
+    if records.is_empty() {
+        return html! {};
+    }
+    html! {
+        <div class="traceability">
+            <h4>{"Traceability"}</h4>
+            { for records.iter().map(|r| {
+                let url = traceability_share_url(r);
+                html! {
+                    <div class="traceability-record" key={r.token.clone()}>
+                        <p class="traceability-sale">
+                            {format!("Sold {} to {}", r.sold_on,
+                                     r.buyer_name.as_deref().unwrap_or("an unnamed buyer"))}
+                            if let Some(invoice) = &r.invoice_number {
+                                {format!(" (invoice {})", invoice)}
+                            }
+                        </p>
+                        <p>
+                            {format!("Origin: {}", r.farm_name.as_deref().unwrap_or("this farm"))}
+                        </p>
+                        <p class="traceability-health">{r.health_summary()}</p>
+                        if !r.diseases.is_empty() {
+                            <p>{format!("Diseases on record: {}", r.diseases.join(", "))}</p>
+                        }
+                        <ol class="traceability-movements">
+                            { for r.movements.iter().map(|m| html! {
+                                <li>
+                                    {match &m.date {
+                                        Some(date) => format!("{}: {}", date, m.description),
+                                        None => m.description.clone(),
+                                    }}
+                                </li>
+                            }) }
+                        </ol>
+                        <p>
+                            {"Share with the buyer: "}
+                            <a class="traceability-link" href={url.clone()} target="_blank">
+                                {url}
+                            </a>
+                        </p>
+                    </div>
+                }
+            }) }
+        </div>
+    }
+}
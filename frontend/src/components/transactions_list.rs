@@ -0,0 +1,96 @@
+//! Table of income and expense entries, newest first, sortable by date,
+//! category or amount.
+
+use crate::components::{Column, DataTable, EmptyState, RowId};
+use crate::services::use_api;
+use log::{error, info};
+use shared::finance::{FinanceCategory, Transaction, TransactionKind};
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// TransactionsList component:
+/// Loads every transaction on mount and on Refresh and lists them in a
+/// `DataTable`. Amounts are shown in the farm's base currency, so entries
+/// in other currencies sort alongside the rest. Rows have
+/// `transaction-{id}` element IDs.
+#[function_component(TransactionsList)]
+pub fn transactions_list() -> Html {
+    let api = use_api();
+    let transactions = use_state(|| None::<Vec<Transaction>>);
+    let error = use_state(|| None::<String>);
+    let reloads = use_state(|| 0u32);
+
+    {
+        let transactions = transactions.clone();
+        let error = error.clone();
+        use_effect_with(*reloads, move |_| {
+            spawn_local(async move {
+                match api.transactions().await {
+                    Ok(loaded) => {
+                        info!("Loaded {} transactions", loaded.len());
+                        error.set(None);
+                        transactions.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load transactions: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        });
+    }
+
+    let refresh = {
+        let reloads = reloads.clone();
+        Callback::from(move |_: MouseEvent| reloads.set(*reloads + 1))
+    };
+
+    let columns = vec![
+        Column::text("date", "Date", |t: &Transaction| t.date.clone()),
+        Column::text("kind", "Kind", |t: &Transaction| {
+            TransactionKind::to_str(&t.kind).to_string()
+        }),
+        Column::text("category", "Category", |t: &Transaction| {
+            FinanceCategory::to_str(&t.category).to_string()
+        }),
+        Column::text("description", "Description", |t: &Transaction| {
+            t.description.clone()
+        }),
+        Column::text("goat", "Goat", |t: &Transaction| {
+            t.goat_name.clone().unwrap_or_else(|| "-".to_string())
+        }),
+        Column::number("amount", "Amount", |t: &Transaction| t.base_amount()),
+    ];
+
+    let row_id: RowId<Transaction> =
+        Rc::new(|t: &Transaction| format!("transaction-{}", t.id.unwrap_or_default()));
+    html! {
+        <div style="margin-bottom: 24px;">
+            <h3>{"Transactions"}</h3>
+            <button onclick={refresh}>{"Refresh"}</button>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error loading transactions: {}", err)}</p>
+            }
+            {
+                match &*transactions {
+                    None => html! { <p>{"Loading transactions..."}</p> },
+                    Some(transactions) if transactions.is_empty() => html! {
+                        <EmptyState
+                            title="No transactions yet"
+                            message="Sales, purchases and other income and expenses will be listed here."
+                            sample="2024-06-01 · Expense · Feed · 20 kg concentrate · 900.00"
+                        />
+                    },
+                    Some(transactions) => html! {
+                        <DataTable<Transaction>
+                            rows={transactions.clone()}
+                            {columns}
+                            {row_id}
+                        />
+                    },
+                }
+            }
+        </div>
+    }
+}
@@ -0,0 +1,126 @@
+//! Header buttons and keyboard shortcuts for undoing and redoing changes to
+//! the goat list (see `crate::store::GoatCommand`).
+
+use crate::services::use_api;
+use crate::store::{GoatStore, use_read_only};
+use log::trace;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::closure::Closure;
+use web_sys::{HtmlElement, KeyboardEvent};
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// Whether `e` was typed into a text field, where the browser's own undo
+/// applies instead.
+fn in_text_field(e: &KeyboardEvent) -> bool {
+    e.target()
+        .and_then(|t| t.dyn_into::<HtmlElement>().ok())
+        .is_some_and(|el| {
+            matches!(el.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+                || el.is_content_editable()
+        })
+}
+
+/// UndoControls component:
+/// Undo and Redo buttons naming the change they act on, e.g. "Undo delete
+/// Rani". Ctrl+Z (Cmd+Z on a Mac) undoes and Ctrl+Shift+Z or Ctrl+Y redoes,
+/// except while typing in a field. Left out in read-only mode, since both
+/// change data.
+#[function_component(UndoControls)]
+pub fn undo_controls() -> Html {
+    let api = use_api();
+    let (state, dispatch) = use_store::<GoatStore>();
+    let read_only = use_read_only();
+
+    // Window-wide shortcuts, listening only while changes are allowed
+    {
+        let api = api.clone();
+        let dispatch = dispatch.clone();
+        use_effect_with(read_only, move |read_only| {
+            let window = web_sys::window();
+            let listener = match (&window, *read_only) {
+                (Some(window), false) => {
+                    let listener =
+                        Closure::<dyn Fn(KeyboardEvent)>::new(move |e: KeyboardEvent| {
+                            if !(e.ctrl_key() || e.meta_key()) || in_text_field(&e) {
+                                return;
+                            }
+                            match e.key().to_lowercase().as_str() {
+                                "z" if e.shift_key() => {
+                                    e.prevent_default();
+                                    GoatStore::redo(api.clone(), dispatch.clone());
+                                }
+                                "z" => {
+                                    e.prevent_default();
+                                    GoatStore::undo(api.clone(), dispatch.clone());
+                                }
+                                "y" => {
+                                    e.prevent_default();
+                                    GoatStore::redo(api.clone(), dispatch.clone());
+                                }
+                                _ => {}
+                            }
+                        });
+                    let added = window
+                        .add_event_listener_with_callback(
+                            "keydown",
+                            listener.as_ref().unchecked_ref(),
+                        )
+                        .is_ok();
+                    trace!("Undo shortcuts listening: {}", added);
+                    added.then_some(listener)
+                }
+                _ => None,
+            };
+            move || {
+                if let (Some(window), Some(listener)) = (window, listener) {
+                    let _ = window.remove_event_listener_with_callback(
+                        "keydown",
+                        listener.as_ref().unchecked_ref(),
+                    );
+                }
+            }
+        });
+    }
+
+    if read_only {
+        return html! {};
+    }
+    let history = &state.history;
+    let on_undo = {
+        let api = api.clone();
+        let dispatch = dispatch.clone();
+        Callback::from(move |_: MouseEvent| GoatStore::undo(api.clone(), dispatch.clone()))
+    };
+    let on_redo =
+        Callback::from(move |_: MouseEvent| GoatStore::redo(api.clone(), dispatch.clone()));
+    let undo_title = history
+        .undo
+        .last()
+        .map_or("Nothing to undo".to_string(), |c| {
+            format!("Undo {} (Ctrl+Z)", c.label())
+        });
+    let redo_title = history
+        .redo
+        .last()
+        .map_or("Nothing to redo".to_string(), |c| {
+            format!("Redo {} (Ctrl+Shift+Z)", c.label())
+        });
+
+    html! {
+        <span class="undo-controls">
+            <button name="undo" title={undo_title} onclick={on_undo}
+                    disabled={history.pending || history.undo.is_empty()}>
+                {"Undo"}
+            </button>
+            {" "}
+            <button name="redo" title={redo_title} onclick={on_redo}
+                    disabled={history.pending || history.redo.is_empty()}>
+                {"Redo"}
+            </button>
+            if let Some(err) = &history.error {
+                <span style="color: #ffab91; margin-left: 8px;">{err}</span>
+            }
+        </span>
+    }
+}
@@ -0,0 +1,65 @@
+//! Header pickers for the unit weights are entered and shown in and the
+//! farm's time zone (see `crate::store::UnitsStore`).
+
+use crate::services::use_api;
+use crate::store::UnitsStore;
+use chrono_tz::{TZ_VARIANTS, Tz};
+use shared::units::WeightUnit;
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// UnitSelect component:
+/// Loads the farm's base currency and time zone on mount and switches the
+/// preferred weight unit, which every `NumberField` for a weight follows,
+/// and the farm's time zone, which dates and times are shown in.
+#[function_component(UnitSelect)]
+pub fn unit_select() -> Html {
+    let api = use_api();
+    let (state, dispatch) = use_store::<UnitsStore>();
+
+    {
+        let dispatch = dispatch.clone();
+        let api = api.clone();
+        use_effect_with((), move |_| {
+            UnitsStore::load_settings(api, dispatch);
+        });
+    }
+
+    let on_change = {
+        let dispatch = dispatch.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>()
+                && let Some(unit) = WeightUnit::from_symbol(&select.value())
+            {
+                UnitsStore::set_weight_unit(dispatch.clone(), unit);
+            }
+        })
+    };
+    let on_timezone = Callback::from(move |e: Event| {
+        if let Some(select) = e.target_dyn_into::<HtmlSelectElement>()
+            && let Ok(timezone) = select.value().parse::<Tz>()
+        {
+            UnitsStore::set_timezone(api.clone(), dispatch.clone(), timezone);
+        }
+    });
+
+    html! {
+        <label class="unit-select">
+            {"Weights in "}
+            <select name="weight_unit" onchange={on_change}>
+                { for WeightUnit::ALL.iter().map(|unit| html! {
+                    <option value={unit.symbol()} selected={*unit == state.weight}>
+                        {unit.symbol()}
+                    </option>
+                }) }
+            </select>
+            {" · times in "}
+            <select name="timezone" onchange={on_timezone}>
+                { for TZ_VARIANTS.iter().map(|tz| html! {
+                    <option value={tz.name()} selected={*tz == state.timezone}>{tz.name()}</option>
+                }) }
+            </select>
+        </label>
+    }
+}
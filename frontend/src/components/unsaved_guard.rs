@@ -0,0 +1,41 @@
+//! Browser prompt guarding forms with unsaved changes.
+
+use log::trace;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::closure::Closure;
+use web_sys::BeforeUnloadEvent;
+use yew::prelude::*;
+
+/// While `dirty` is true, asks the browser to confirm before the page is
+/// closed or reloaded. The browser shows its own wording for the prompt.
+#[hook]
+pub fn use_unsaved_changes_guard(dirty: bool) {
+    use_effect_with(dirty, |dirty| {
+        let window = web_sys::window();
+        let listener = match (&window, *dirty) {
+            (Some(window), true) => {
+                let listener = Closure::<dyn Fn(BeforeUnloadEvent)>::new(|e: BeforeUnloadEvent| {
+                    e.prevent_default();
+                    e.set_return_value("You have unsaved changes.");
+                });
+                let added = window
+                    .add_event_listener_with_callback(
+                        "beforeunload",
+                        listener.as_ref().unchecked_ref(),
+                    )
+                    .is_ok();
+                trace!("Unsaved changes guard armed: {}", added);
+                added.then_some(listener)
+            }
+            _ => None,
+        };
+        move || {
+            if let (Some(window), Some(listener)) = (window, listener) {
+                let _ = window.remove_event_listener_with_callback(
+                    "beforeunload",
+                    listener.as_ref().unchecked_ref(),
+                );
+            }
+        }
+    });
+}
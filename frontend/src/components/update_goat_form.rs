@@ -1,100 +1,219 @@
-use crate::components::add_goat_components::{BreedInput, GenderInput};
-use crate::store::GoatStore;
+use crate::components::add_goat_components::{
+    BreedInput, GenderInput, TraitsInput, profile_for, use_goat_fields,
+};
+use crate::components::add_goat_wizard::GoatDraft;
+use crate::components::date_picker::date_problem;
+use crate::components::number_field::text_setter;
+use crate::components::unsaved_guard::use_unsaved_changes_guard;
+use crate::components::{
+    DatePicker, DraftBar, NumberField, Quantity, SoftWarnings, Spinner, use_soft_warnings,
+};
+use crate::drafts::{discard_draft, goat_draft_key, load_draft, use_autosave};
+use crate::services::use_api;
+use crate::store::{GoatStore, farm_timezone};
 use log::{error, info, trace, warn};
-use shared::{Breed, Gender, GoatParams};
+use shared::data_health::UPDATE_FORM_ANCHOR;
+use shared::import::{GoatField, goat_warnings};
+use shared::search::suggest_names;
+use shared::time::local_date;
+use shared::{Breed, Gender, Goat, GoatParams, GoatUpdate};
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use yewdux::prelude::use_store;
 
+/// Name the form's drafts are saved under, one per goat.
+pub const UPDATE_GOAT_DRAFT: &str = "update_goat";
+
+/// Goats whose name matches `query`, ignoring case: exact matches if there
+/// are any, otherwise those whose name contains it.
+pub fn matching_goats<'a>(goats: &'a [Goat], query: &str) -> Vec<&'a Goat> {
+    let query = query.trim().to_lowercase();
+    let exact: Vec<&Goat> = goats
+        .iter()
+        .filter(|g| g.name.to_lowercase() == query)
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+    goats
+        .iter()
+        .filter(|g| g.name.to_lowercase().contains(&query))
+        .collect()
+}
+
+/// One line telling a goat apart from others with a similar name, e.g.
+/// "#12 · tag IN-0042 · born 2023-02-01 · Beetal · added 2024-03-01". The
+/// tag and birth date are left out for goats without them.
+pub fn goat_identity(goat: &Goat) -> String {
+    let mut parts = vec![format!("#{}", goat.id)];
+    if let Some(tag) = &goat.tag_id {
+        parts.push(format!("tag {}", tag));
+    }
+    if let Some(born) = &goat.date_of_birth {
+        parts.push(format!("born {}", born));
+    }
+    parts.push(Breed::to_str(&goat.breed).to_string());
+    if let Some(added) = &goat.created_at {
+        parts.push(format!("added {}", local_date(added, farm_timezone())));
+    }
+    parts.join(" · ")
+}
+
+/// Form for editing a goat found by name. When the name matches several
+/// goats, they are listed with their IDs and identifying details to pick
+/// from; when it matches none, goats with similar names are offered the
+/// same way as "did you mean" suggestions. Fields edited since the goat was loaded are highlighted and can
+/// be reset, and closing the page with such edits asks for confirmation.
+/// Unsaved edits are also autosaved per goat and restored the next time
+/// that goat is loaded.
 #[function_component(UpdateGoatForm)]
 pub fn update_goat_form() -> Html {
     let (state, dispatch) = use_store::<GoatStore>();
+    let api = use_api();
 
     // States for inputs and control flow
     let search_name = use_state(|| "".to_string());
-    let found_goat = use_state(|| None::<GoatParams>);
+    let found_goat = use_state(|| None::<Goat>);
+    let matches = use_state(Vec::<Goat>::new);
+    // Whether `matches` are suggestions for a name that matched nothing
+    let suggested = use_state(|| false);
     let error = use_state(|| None::<String>);
     let success = use_state(|| None::<String>);
+    let warnings = use_soft_warnings();
 
     // Editable fields state
-    let name = use_state(|| "".to_string());
-    let breed = use_state(|| "Beetal".to_string());
-    let other_breed = use_state(|| "".to_string());
-    let gender = use_state(|| "Male".to_string());
-    let offspring = use_state(|| "".to_string());
-    let cost = use_state(|| "".to_string());
-    let weight = use_state(|| "".to_string());
-    let current_price = use_state(|| "".to_string());
-    let diet = use_state(|| "".to_string());
-    let last_bred = use_state(|| "".to_string());
-    let health_status = use_state(|| "".to_string());
+    let fields = use_goat_fields(&GoatDraft::default());
+    let restored = use_state(|| false);
+    let name = fields.name.clone();
+    let breed = fields.breed.clone();
+    let other_breed = fields.other_breed.clone();
+    let gender = fields.gender.clone();
+    let offspring = fields.offspring.clone();
+    let cost = fields.cost.clone();
+    let weight = fields.weight.clone();
+    let current_price = fields.current_price.clone();
+    let diet = fields.diet.clone();
+    let last_bred = fields.last_bred.clone();
+    let health_status = fields.health_status.clone();
+
+    // The loaded goat's values, which the draft is kept against
+    let loaded = found_goat
+        .as_ref()
+        .map(|goat| GoatDraft::from_goat(goat))
+        .unwrap_or_default();
+    use_autosave(
+        found_goat
+            .as_ref()
+            .map(|goat| goat_draft_key(UPDATE_GOAT_DRAFT, goat.id)),
+        fields.draft(),
+        loaded.clone(),
+    );
+    let dirty = if found_goat.is_some() {
+        fields.draft().changed_fields(&loaded)
+    } else {
+        Vec::new()
+    };
+    use_unsaved_changes_guard(!dirty.is_empty());
+    // Marks the label of a field edited since the goat was loaded
+    let dirty_class = |field: GoatField| dirty.contains(&field).then_some("dirty");
 
-    // Handler to load goat details from store by name
+    // Resets every field to the loaded goat's values, dropping the draft
+    let on_reset = {
+        let fields = fields.clone();
+        let restored = restored.clone();
+        let found_goat = found_goat.clone();
+        let loaded = loaded.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(goat) = &*found_goat {
+                info!("Discarding draft for goat {}", goat.id);
+                discard_draft(&goat_draft_key(UPDATE_GOAT_DRAFT, goat.id));
+            }
+            fields.fill(&loaded);
+            restored.set(false);
+        })
+    };
+
+    // Loads a goat into the form, picking up unsaved edits left from an
+    // earlier visit
+    let load_goat = {
+        let found_goat = found_goat.clone();
+        let matches = matches.clone();
+        let error = error.clone();
+        let success = success.clone();
+        let fields = fields.clone();
+        let restored = restored.clone();
+        Callback::from(move |goat: Goat| {
+            let draft = load_draft::<GoatDraft>(&goat_draft_key(UPDATE_GOAT_DRAFT, goat.id));
+            restored.set(draft.is_some());
+            fields.fill(&draft.unwrap_or_else(|| GoatDraft::from_goat(&goat)));
+            found_goat.set(Some(goat));
+            matches.set(Vec::new());
+            error.set(None);
+            success.set(None);
+        })
+    };
+
+    // Handler to find the goat in the store by name
     let on_search = {
         let search_name = search_name.clone();
         let state = state.clone();
         let found_goat = found_goat.clone();
+        let matches = matches.clone();
+        let suggested = suggested.clone();
         let error = error.clone();
         let success = success.clone();
-
-        let name = name.clone();
-        let breed = breed.clone();
-        let other_breed = other_breed.clone();
-        let gender = gender.clone();
-        let offspring = offspring.clone();
-        let cost = cost.clone();
-        let weight = weight.clone();
-        let current_price = current_price.clone();
-        let diet = diet.clone();
-        let last_bred = last_bred.clone();
-        let health_status = health_status.clone();
+        let load_goat = load_goat.clone();
 
         Callback::from(move |_| {
             success.set(None);
             error.set(None);
-            let query = (*search_name).to_lowercase();
-            if query.is_empty() {
+            matches.set(Vec::new());
+            suggested.set(false);
+            if search_name.trim().is_empty() {
                 error.set(Some("Please enter the name of goat to update".to_string()));
                 found_goat.set(None);
                 return;
             }
-            if let Some(goat) = state.goats.iter().find(|g| g.name.to_lowercase() == query) {
-                found_goat.set(Some(goat.clone()));
-                name.set(goat.name.clone());
-
-                // Use to_str methods to populate strings
-                breed.set(match &goat.breed {
-                    Breed::Other(_) => "Other".to_string(),
-                    other => Breed::to_str(other).to_string(),
-                });
-                other_breed.set(if let Breed::Other(s) = &goat.breed {
-                    s.clone()
-                } else {
-                    "".to_string()
-                });
-                gender.set(Gender::to_str(&goat.gender).to_string());
-
-                offspring.set(goat.offspring.to_string());
-                cost.set(goat.cost.to_string());
-                weight.set(goat.weight.to_string());
-                current_price.set(goat.current_price.to_string());
-                diet.set(goat.diet.clone());
-                last_bred.set(goat.last_bred.clone().unwrap_or_default());
-                health_status.set(goat.health_status.clone());
-                error.set(None);
-            } else {
-                error.set(Some(format!("Goat '{}' not found", *search_name)));
-                found_goat.set(None);
+            let found = matching_goats(&state.goats, &search_name);
+            match found.as_slice() {
+                [] => {
+                    found_goat.set(None);
+                    let names =
+                        suggest_names(&search_name, state.goats.iter().map(|g| g.name.as_str()));
+                    if names.is_empty() {
+                        error.set(Some(format!("Goat '{}' not found", *search_name)));
+                    } else {
+                        info!("Suggesting {:?} for '{}'", names, *search_name);
+                        suggested.set(true);
+                        matches.set(
+                            names
+                                .iter()
+                                .filter_map(|name| state.goats.iter().find(|g| &g.name == name))
+                                .cloned()
+                                .collect(),
+                        );
+                    }
+                }
+                [goat] => load_goat.emit((*goat).clone()),
+                several => {
+                    info!("{} goats match '{}'", several.len(), *search_name);
+                    found_goat.set(None);
+                    matches.set(several.iter().map(|g| (*g).clone()).collect());
+                }
             }
         })
     };
 
     // Submit handler
     let onsubmit = {
+        let api = api.clone();
         let dispatch = dispatch.clone();
         let found_goat = found_goat.clone();
         let error = error.clone();
         let success = success.clone();
+        let fields = fields.clone();
+        let restored = restored.clone();
+        let warnings = warnings.clone();
 
         let name = name.clone();
         let breed = breed.clone();
@@ -140,6 +259,12 @@ pub fn update_goat_form() -> Html {
                 errs.push("Current price must be number.");
                 0.0
             });
+            // Bucks have no breeding date, so any stored one is cleared
+            let breeding = fields.draft().has_breeding_records();
+            let date_error = date_problem("Last bred", &last_bred).filter(|_| breeding);
+            if let Some(problem) = &date_error {
+                errs.push(problem);
+            }
 
             if !errs.is_empty() {
                 error.set(Some(errs.join(" ")));
@@ -155,42 +280,76 @@ pub fn update_goat_form() -> Html {
 
             let gender_enum = Gender::from_str(gender.as_str()).unwrap_or(Gender::Male);
 
-            if found_goat.is_none() {
+            let Some(original) = (*found_goat).clone() else {
                 error.set(Some("No goat loaded to update".to_string()));
                 success.set(None);
                 return;
-            }
-
-            let updated = GoatParams {
-                name: name.to_string(),
-                breed: breed_enum,
-                gender: gender_enum,
-                offspring: offspring_val,
-                cost: cost_val,
-                weight: weight_val,
-                current_price: current_price_val,
-                diet: diet.to_string(),
-                last_bred: Some(last_bred.to_string()),
-                health_status: health_status.to_string(),
-                vaccinations: found_goat.as_ref().unwrap().vaccinations.clone(),
-                diseases: found_goat.as_ref().unwrap().diseases.clone(),
             };
 
-            dispatch.reduce_mut(|store| {
-                if let Some(pos) = store.goats.iter().position(|g| g.name == updated.name) {
-                    store.goats[pos] = updated.clone();
+            let mut builder = fields
+                .draft()
+                .with_traits(GoatParams::builder(name.to_string()))
+                .breed(breed_enum)
+                .gender(gender_enum)
+                .offspring(offspring_val)
+                .cost(cost_val)
+                .weight(weight_val)
+                .current_price(current_price_val)
+                .diet(diet.to_string())
+                .health_status(health_status.to_string())
+                .vaccinations(original.vaccinations.clone())
+                .diseases(original.diseases.clone())
+                .quick_note(original.quick_note.clone().unwrap_or_default());
+            if breeding && !last_bred.trim().is_empty() {
+                builder = builder.last_bred(last_bred.trim());
+            }
+            let updated = match builder.build() {
+                Ok(goat) => goat,
+                Err(errors) => {
+                    error.set(Some(errors.join(" ")));
+                    success.set(None);
+                    return;
                 }
-            });
+            };
+
+            // Send only what changed, so edits others made meanwhile survive
+            let update = GoatUpdate::diff(&original.params, &updated);
+            if update.is_empty() {
+                error.set(None);
+                success.set(Some("No changes to save.".to_string()));
+                return;
+            }
+
+            // Only ask about unusual values the edit introduces
+            let existing = goat_warnings(&original.params);
+            let introduced = goat_warnings(&updated)
+                .into_iter()
+                .filter(|w| !existing.contains(w))
+                .collect();
+            if warnings.hold(introduced) {
+                error.set(None);
+                success.set(None);
+                return;
+            }
 
             let dispatch = dispatch.clone();
+            let found_goat = found_goat.clone();
             let error = error.clone();
             let success = success.clone();
-            GoatStore::update_goat_async(
+            let fields = fields.clone();
+            let restored = restored.clone();
+            GoatStore::patch_goat_async(
+                api.clone(),
                 dispatch,
-                updated.clone(),
-                Callback::from(move |res| match res {
-                    Ok(_) => {
-                        trace!("Deleted successfully.");
+                original.id,
+                update,
+                Callback::from(move |res: Result<Goat, _>| match res {
+                    Ok(patched) => {
+                        trace!("Patched goat {}", patched.id);
+                        // Show the saved values, which also drops the draft
+                        fields.fill(&GoatDraft::from_goat(&patched));
+                        restored.set(false);
+                        found_goat.set(Some(patched));
                         error.set(None);
                         success.set(Some("Goat updated successfully.".to_string()));
                     }
@@ -200,8 +359,9 @@ pub fn update_goat_form() -> Html {
         })
     };
 
+    let typical_weight = profile_for(&breed).map(|p| p.adult_weight_kg);
     html! {
-        <div>
+        <div id={UPDATE_FORM_ANCHOR}>
             <h3>{ "Update Goat Details" }</h3>
 
             // Search Box
@@ -227,9 +387,43 @@ pub fn update_goat_form() -> Html {
                 <p style="color: green;">{msg.clone()}</p>
             }
 
+            if !matches.is_empty() {
+                <div class="goat-matches">
+                    if *suggested {
+                        <p>{format!("No goat named '{}'. Did you mean:", *search_name)}</p>
+                    } else {
+                        <p>{format!("{} goats match '{}'. Choose one:", matches.len(), *search_name)}</p>
+                    }
+                    <ul>
+                        { for matches.iter().map(|goat| {
+                            let onclick = {
+                                let load_goat = load_goat.clone();
+                                let goat = goat.clone();
+                                Callback::from(move |_: MouseEvent| load_goat.emit(goat.clone()))
+                            };
+                            html! {
+                                <li key={goat.id}>
+                                    <button type="button" {onclick}>{&goat.name}</button>
+                                    { " " }{goat_identity(goat)}
+                                </li>
+                            }
+                        }) }
+                    </ul>
+                </div>
+            }
+
             if found_goat.is_some() {
+                if !dirty.is_empty() {
+                    <DraftBar restored={*restored} on_discard={on_reset.clone()} />
+                    <p class="unsaved-summary" style="font-size: 12px;">
+                        {format!(
+                            "Unsaved changes: {}",
+                            dirty.iter().map(GoatField::label).collect::<Vec<_>>().join(", ")
+                        )}
+                    </p>
+                }
                 <form onsubmit={onsubmit}>
-                    <label>{ "Name:" }
+                    <label class={classes!(dirty_class(GoatField::Name))}>{ "Name:" }
                         <input type="text"
                             value={(*name).clone()}
                             oninput={Callback::from({
@@ -243,7 +437,7 @@ pub fn update_goat_form() -> Html {
                         />
                     </label>
                     <br/>
-                    <label>{ "Breed:" }
+                    <label class={classes!(dirty_class(GoatField::Breed))}>{ "Breed:" }
                         <BreedInput
                             selected={(*breed).clone()}
                             other_value={(*other_breed).clone()}
@@ -252,14 +446,14 @@ pub fn update_goat_form() -> Html {
                         />
                     </label>
                     <br/>
-                    <label>{ "Gender:" }
+                    <label class={classes!(dirty_class(GoatField::Gender))}>{ "Gender:" }
                         <GenderInput
                             selected={(*gender).clone()}
                             on_gender_change={Callback::from(move |v| gender.set(v))}
                         />
                     </label>
                     <br/>
-                    <label>{ "Offspring:" }
+                    <label class={classes!(dirty_class(GoatField::Offspring))}>{ "Offspring:" }
                         <input
                             type="number"
                             value={(*offspring).clone()}
@@ -274,55 +468,38 @@ pub fn update_goat_form() -> Html {
                         />
                     </label>
                     <br/>
-                    <label>{ "Cost:" }
-                        <input
-                            type="number"
-                            step="0.01"
-                            value={(*cost).clone()}
-                            oninput={Callback::from({
-                                let cost = cost.clone();
-                                move |e: InputEvent| {
-                                    if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
-                                        cost.set(input.value());
-                                    }
-                                }
-                            })}
+                    <label class={classes!(dirty_class(GoatField::Cost))}>{ "Cost:" }
+                        <NumberField
+                            name="cost"
+                            quantity={Quantity::Money}
+                            value={cost.parse::<f64>().ok()}
+                            min={0.0}
+                            onchange={text_setter(&cost)}
                         />
                     </label>
                     <br/>
-                    <label>{ "Weight:" }
-                        <input
-                            type="number"
-                            step="0.01"
-                            value={(*weight).clone()}
-                            oninput={Callback::from({
-                                let weight = weight.clone();
-                                move |e: InputEvent| {
-                                    if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
-                                        weight.set(input.value());
-                                    }
-                                }
-                            })}
+                    <label class={classes!(dirty_class(GoatField::Weight))}>{ "Weight:" }
+                        <NumberField
+                            name="weight"
+                            quantity={Quantity::Weight}
+                            value={weight.parse::<f64>().ok()}
+                            min={0.0}
+                            typical={typical_weight}
+                            onchange={text_setter(&weight)}
                         />
                     </label>
                     <br/>
-                    <label>{ "Current Price:" }
-                        <input
-                            type="number"
-                            step="0.01"
-                            value={(*current_price).clone()}
-                            oninput={Callback::from({
-                                let current_price = current_price.clone();
-                                move |e: InputEvent| {
-                                    if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
-                                        current_price.set(input.value());
-                                    }
-                                }
-                            })}
+                    <label class={classes!(dirty_class(GoatField::CurrentPrice))}>{ "Current Price:" }
+                        <NumberField
+                            name="current_price"
+                            quantity={Quantity::Money}
+                            value={current_price.parse::<f64>().ok()}
+                            min={0.0}
+                            onchange={text_setter(&current_price)}
                         />
                     </label>
                     <br/>
-                    <label>{ "Diet:" }
+                    <label class={classes!(dirty_class(GoatField::Diet))}>{ "Diet:" }
                         <input
                             type="text"
                             value={(*diet).clone()}
@@ -337,22 +514,40 @@ pub fn update_goat_form() -> Html {
                         />
                     </label>
                     <br/>
-                    <label>{ "Last Bred:" }
-                        <input
-                            type="text"
-                            value={(*last_bred).clone()}
-                            oninput={Callback::from({
-                                let last_bred = last_bred.clone();
-                                move |e: InputEvent| {
-                                    if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
-                                        last_bred.set(input.value());
-                                    }
-                                }
-                            })}
-                        />
-                    </label>
+                    <TraitsInput
+                        horns={(*fields.horns).clone()}
+                        coat_color={(*fields.coat_color).clone()}
+                        marks={(*fields.marks).clone()}
+                        on_horns_change={{
+                            let horns = fields.horns.clone();
+                            Callback::from(move |v| horns.set(v))
+                        }}
+                        on_coat_color_change={{
+                            let coat_color = fields.coat_color.clone();
+                            Callback::from(move |v| coat_color.set(v))
+                        }}
+                        on_marks_change={{
+                            let marks = fields.marks.clone();
+                            Callback::from(move |v| marks.set(v))
+                        }}
+                        dirty={dirty.clone()}
+                    />
                     <br/>
-                    <label>{ "Health Status:" }
+                    if fields.draft().has_breeding_records() {
+                        <label class={classes!(dirty_class(GoatField::LastBred))}>{ "Last Bred:" }
+                            <DatePicker
+                                name="last_bred"
+                                label="Last bred"
+                                value={(*last_bred).clone()}
+                                onchange={{
+                                    let last_bred = last_bred.clone();
+                                    Callback::from(move |date: String| last_bred.set(date))
+                                }}
+                            />
+                        </label>
+                        <br/>
+                    }
+                    <label class={classes!(dirty_class(GoatField::HealthStatus))}>{ "Health Status:" }
                         <input
                             type="text"
                             value={(*health_status).clone()}
@@ -367,7 +562,21 @@ pub fn update_goat_form() -> Html {
                         />
                     </label>
                     <br/>
-                    <button type="submit">{ "Save Changes" }</button>
+                    <SoftWarnings
+                        warnings={warnings.shown()}
+                        on_confirm={{
+                            let warnings = warnings.clone();
+                            Callback::from(move |_| warnings.confirm())
+                        }}
+                    />
+                    <button type="submit" disabled={state.loading.updating}>{ "Save Changes" }</button>
+                    { " " }
+                    <button type="button" onclick={on_reset} disabled={dirty.is_empty()}>
+                        { "Reset to loaded values" }
+                    </button>
+                    if state.loading.updating {
+                        { " " }<Spinner label="Saving changes..." />
+                    }
                 </form>
             }
         </div>
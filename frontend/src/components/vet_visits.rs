@@ -0,0 +1,482 @@
+//! Vet visits: requesting a visit for a list of goats, booking it with a vet
+//! on a day the vet is free, and completing it with the vet's findings,
+//! which become health incidents (see `shared::vet`).
+
+use crate::components::SkeletonRows;
+use crate::services::{Api, use_api};
+use crate::store::use_read_only;
+use log::{error, info};
+use shared::health::IncidentKind;
+use shared::vet::{
+    VetAvailability, VetVisit, VisitFinding, VisitRequest, VisitSchedule, VisitStatus, VisitSummary,
+};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+/// Incident kinds a finding can be recorded as.
+const KINDS: [IncidentKind; 4] = [
+    IncidentKind::Disease,
+    IncidentKind::Parasite,
+    IncidentKind::Injury,
+    IncidentKind::Other,
+];
+
+/// Reloads the visits and the vets' bookings, reporting failures in `error`.
+fn load_visits(
+    api: Api,
+    visits: UseStateHandle<Option<Vec<VetVisit>>>,
+    vets: UseStateHandle<Vec<VetAvailability>>,
+    error: UseStateHandle<Option<String>>,
+) {
+    spawn_local(async move {
+        match api.vet_visits().await {
+            Ok(loaded) => {
+                info!("Loaded {} vet visits", loaded.len());
+                visits.set(Some(loaded));
+            }
+            Err(e) => {
+                error!("Failed to load vet visits: {}", e);
+                error.set(Some(e.to_string()));
+            }
+        }
+        match api.vets().await {
+            Ok(loaded) => vets.set(loaded),
+            Err(e) => {
+                error!("Failed to load vets: {}", e);
+                error.set(Some(e.to_string()));
+            }
+        }
+    });
+}
+
+/// A vet's name with the days they are booked, e.g. "Dr Rao (booked
+/// 2026-05-04)".
+fn vet_label(vet: &VetAvailability) -> String {
+    if vet.booked.is_empty() {
+        format!("{} (free)", vet.vet_name)
+    } else {
+        format!("{} (booked {})", vet.vet_name, vet.booked.join(", "))
+    }
+}
+
+/// A select of every vet, calling `onchange` with the chosen vet's ID.
+fn vet_select(
+    class: &'static str,
+    vets: &[VetAvailability],
+    selected: Option<i64>,
+    onchange: Callback<Option<i64>>,
+) -> Html {
+    let onchange = Callback::from(move |e: Event| {
+        let select: HtmlSelectElement = e.target_unchecked_into();
+        onchange.emit(select.value().parse().ok());
+    });
+    html! {
+        <select {class} {onchange}>
+            <option value="" selected={selected.is_none()}>{"No vet yet"}</option>
+            { for vets.iter().map(|v| html! {
+                <option value={v.vet_id.to_string()} selected={selected == Some(v.vet_id)}>
+                    {vet_label(v)}
+                </option>
+            }) }
+        </select>
+    }
+}
+
+/// Why `vet_id` cannot be booked on `day`, if they are already booked then.
+fn clash(vets: &[VetAvailability], vet_id: Option<i64>, day: &str) -> Option<String> {
+    let vet = vets.iter().find(|v| Some(v.vet_id) == vet_id)?;
+    (!vet.is_free(day)).then(|| format!("{} is already booked on {}", vet.vet_name, day))
+}
+
+/// VetVisits component:
+/// Requests visits for the goats to examine, optionally booked straight
+/// away, and lists every visit with its vet and day. Each vet's booked days
+/// are shown when choosing one, and a clashing booking is flagged before it
+/// is sent. Requested visits can be booked and scheduled ones completed with
+/// the vet's summary and a finding per goat; findings are recorded as
+/// health incidents. Locked in read-only mode.
+#[function_component(VetVisits)]
+pub fn vet_visits() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let visits = use_state(|| None::<Vec<VetVisit>>);
+    let vets = use_state(Vec::<VetAvailability>::new);
+    let reason = use_state(String::new);
+    let agenda = use_state(String::new);
+    let vet = use_state(|| None::<i64>);
+    let day = use_state(String::new);
+    let booking = use_state(|| None::<i64>);
+    let book_vet = use_state(|| None::<i64>);
+    let book_day = use_state(String::new);
+    let completing = use_state(|| None::<VetVisit>);
+    let summary = use_state(String::new);
+    let findings = use_state(Vec::<VisitFinding>::new);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let api = api.clone();
+        let visits = visits.clone();
+        let vets = vets.clone();
+        let error = error.clone();
+        move |_| {
+            load_visits(api, visits, vets, error);
+            || {}
+        }
+    });
+
+    let on_input = |field: &UseStateHandle<String>| {
+        let field = field.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            field.set(input.value());
+        })
+    };
+    let on_vet = |field: &UseStateHandle<Option<i64>>| {
+        let field = field.clone();
+        Callback::from(move |id: Option<i64>| field.set(id))
+    };
+
+    let on_request = {
+        let api = api.clone();
+        let visits = visits.clone();
+        let vets = vets.clone();
+        let reason = reason.clone();
+        let agenda = agenda.clone();
+        let vet = vet.clone();
+        let day = day.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let request = VisitRequest {
+                reason: reason.trim().to_string(),
+                agenda: agenda
+                    .split(',')
+                    .map(|g| g.trim().to_string())
+                    .filter(|g| !g.is_empty())
+                    .collect(),
+                vet_id: *vet,
+                scheduled_for: (vet.is_some() && !day.is_empty()).then(|| (*day).clone()),
+            };
+            if let Err(e) = request.validate() {
+                error.set(Some(e));
+                return;
+            }
+            let api = api.clone();
+            let visits = visits.clone();
+            let vets = vets.clone();
+            let fields = [reason.clone(), agenda.clone(), day.clone()];
+            let vet = vet.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.request_vet_visit(&request).await {
+                    Ok(visit) => {
+                        info!("Requested vet visit {}", visit.id);
+                        fields.iter().for_each(|f| f.set(String::new()));
+                        vet.set(None);
+                        error.set(None);
+                        message.set(Some(match &visit.scheduled_for {
+                            Some(on) => format!("Visit booked on {}", on),
+                            None => "Visit requested".to_string(),
+                        }));
+                        load_visits(api, visits, vets, error);
+                    }
+                    Err(e) => {
+                        error!("Failed to request vet visit: {}", e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_book = {
+        let api = api.clone();
+        let visits = visits.clone();
+        let vets = vets.clone();
+        let booking = booking.clone();
+        let book_vet = book_vet.clone();
+        let book_day = book_day.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let (Some(id), Some(vet_id)) = (*booking, *book_vet) else {
+                error.set(Some("Choose a vet to book".to_string()));
+                return;
+            };
+            if book_day.is_empty() {
+                error.set(Some("Choose a day for the visit".to_string()));
+                return;
+            }
+            let schedule = VisitSchedule {
+                vet_id,
+                scheduled_for: (*book_day).clone(),
+            };
+            let api = api.clone();
+            let visits = visits.clone();
+            let vets = vets.clone();
+            let booking = booking.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.schedule_vet_visit(id, &schedule).await {
+                    Ok(visit) => {
+                        info!("Scheduled vet visit {}", id);
+                        booking.set(None);
+                        error.set(None);
+                        message.set(Some(format!(
+                            "Visit booked with {} on {}",
+                            visit.vet_name.unwrap_or_default(),
+                            schedule.scheduled_for
+                        )));
+                        load_visits(api, visits, vets, error);
+                    }
+                    Err(e) => {
+                        error!("Failed to schedule vet visit {}: {}", id, e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_complete = {
+        let visits = visits.clone();
+        let vets = vets.clone();
+        let completing = completing.clone();
+        let summary = summary.clone();
+        let findings = findings.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(visit) = (*completing).clone() else {
+                return;
+            };
+            let report = VisitSummary {
+                summary: summary.trim().to_string(),
+                findings: findings
+                    .iter()
+                    .filter(|f| !f.condition.trim().is_empty())
+                    .cloned()
+                    .collect(),
+            };
+            if let Err(e) = report.validate() {
+                error.set(Some(e));
+                return;
+            }
+            let api = api.clone();
+            let visits = visits.clone();
+            let vets = vets.clone();
+            let completing = completing.clone();
+            let summary = summary.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.complete_vet_visit(visit.id, &report).await {
+                    Ok(done) => {
+                        info!("Completed vet visit {}", done.id);
+                        completing.set(None);
+                        summary.set(String::new());
+                        error.set(None);
+                        message.set(Some(format!(
+                            "Visit completed; {} treatment record(s) added",
+                            done.treated.len()
+                        )));
+                        load_visits(api, visits, vets, error);
+                    }
+                    Err(e) => {
+                        error!("Failed to complete vet visit {}: {}", visit.id, e);
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let request_clash = clash(&vets, *vet, &day);
+    let booking_clash = clash(&vets, *book_vet, &book_day);
+
+    html! {
+        <div id="vet-visits">
+            <h3>{"Vet Visits"}</h3>
+            <p>
+                <input class="visit-reason" placeholder="Reason" value={(*reason).clone()}
+                       oninput={on_input(&reason)} />
+                {" "}
+                <input class="visit-agenda" placeholder="Goats, comma separated"
+                       value={(*agenda).clone()} oninput={on_input(&agenda)} />
+                {" "}
+                {vet_select("visit-vet", &vets, *vet, on_vet(&vet))}
+                {" "}
+                <input class="visit-day" type="date" value={(*day).clone()} oninput={on_input(&day)} />
+                {" "}
+                <button class="request-visit" onclick={on_request}
+                        disabled={read_only || request_clash.is_some()}>{"Request visit"}</button>
+            </p>
+            if let Some(clash) = request_clash {
+                <p class="visit-clash" style="color: #e65100;">{clash}</p>
+            }
+            if let Some(msg) = &*message {
+                <p class="visit-message" style="color: green;">{msg}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error: {}", err)}</p>
+            }
+            {
+                match &*visits {
+                    None => html! {
+                        <table><tbody><SkeletonRows rows={2} columns={6} /></tbody></table>
+                    },
+                    Some(list) if list.is_empty() => html! {
+                        <p>{"No vet visits yet."}</p>
+                    },
+                    Some(list) => html! {
+                        <table class="vet-visits" style="border-collapse: collapse; width: 100%;">
+                            <thead>
+                                <tr>
+                                    <th>{"Reason"}</th>
+                                    <th>{"Goats"}</th>
+                                    <th>{"Status"}</th>
+                                    <th>{"Vet"}</th>
+                                    <th>{"Day"}</th>
+                                    <th></th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                { for list.iter().map(|v| {
+                                    let on_schedule = {
+                                        let booking = booking.clone();
+                                        let book_vet = book_vet.clone();
+                                        let book_day = book_day.clone();
+                                        let visit = v.clone();
+                                        Callback::from(move |_: MouseEvent| {
+                                            booking.set(Some(visit.id));
+                                            book_vet.set(visit.vet_id);
+                                            book_day.set(visit.scheduled_for.clone().unwrap_or_default());
+                                        })
+                                    };
+                                    let on_start_complete = {
+                                        let completing = completing.clone();
+                                        let findings = findings.clone();
+                                        let visit = v.clone();
+                                        Callback::from(move |_: MouseEvent| {
+                                            findings.set(visit.agenda.iter().map(|goat| VisitFinding {
+                                                goat_name: goat.clone(),
+                                                kind: IncidentKind::Disease,
+                                                condition: String::new(),
+                                                treatment: None,
+                                            }).collect());
+                                            completing.set(Some(visit.clone()));
+                                        })
+                                    };
+                                    html! {
+                                        <tr key={v.id} data-visit={v.id.to_string()}>
+                                            <td>{&v.reason}</td>
+                                            <td>{v.agenda.join(", ")}</td>
+                                            <td class="visit-status">{VisitStatus::to_str(&v.status)}</td>
+                                            <td>{v.vet_name.clone().unwrap_or_default()}</td>
+                                            <td>{v.scheduled_for.clone().unwrap_or_default()}</td>
+                                            <td>
+                                                if v.status != VisitStatus::Completed {
+                                                    <button class="schedule-visit" onclick={on_schedule}
+                                                            disabled={read_only}>
+                                                        {if v.status == VisitStatus::Scheduled { "Rebook" } else { "Book" }}
+                                                    </button>
+                                                }
+                                                if v.status == VisitStatus::Scheduled {
+                                                    {" "}
+                                                    <button class="complete-visit" onclick={on_start_complete}
+                                                            disabled={read_only}>{"Complete"}</button>
+                                                }
+                                                if let Some(text) = &v.summary {
+                                                    <span class="visit-summary">{text}</span>
+                                                }
+                                            </td>
+                                        </tr>
+                                    }
+                                }) }
+                            </tbody>
+                        </table>
+                    },
+                }
+            }
+            if let Some(id) = *booking {
+                <p class="visit-booking">
+                    {format!("Book visit #{} with ", id)}
+                    {vet_select("book-vet", &vets, *book_vet, on_vet(&book_vet))}
+                    {" on "}
+                    <input class="book-day" type="date" value={(*book_day).clone()}
+                           oninput={on_input(&book_day)} />
+                    {" "}
+                    <button class="book-visit" onclick={on_book}
+                            disabled={read_only || booking_clash.is_some()}>{"Book"}</button>
+                    if let Some(clash) = booking_clash {
+                        <span class="visit-clash" style="color: #e65100;">{format!(" {}", clash)}</span>
+                    }
+                </p>
+            }
+            if let Some(visit) = &*completing {
+                <div class="visit-completion">
+                    <h4>{format!("Complete visit #{}: {}", visit.id, visit.reason)}</h4>
+                    <p>
+                        <input class="visit-summary-input" placeholder="Summary"
+                               value={(*summary).clone()} oninput={on_input(&summary)} />
+                    </p>
+                    { for findings.iter().enumerate().map(|(i, f)| {
+                        let on_kind = {
+                            let findings = findings.clone();
+                            Callback::from(move |e: Event| {
+                                let select: HtmlSelectElement = e.target_unchecked_into();
+                                if let Ok(kind) = IncidentKind::from_str(&select.value()) {
+                                    let mut list = (*findings).clone();
+                                    list[i].kind = kind;
+                                    findings.set(list);
+                                }
+                            })
+                        };
+                        let on_field = |treatment: bool| {
+                            let findings = findings.clone();
+                            Callback::from(move |e: InputEvent| {
+                                let input: HtmlInputElement = e.target_unchecked_into();
+                                let mut list = (*findings).clone();
+                                if treatment {
+                                    list[i].treatment = Some(input.value()).filter(|t| !t.trim().is_empty());
+                                } else {
+                                    list[i].condition = input.value();
+                                }
+                                findings.set(list);
+                            })
+                        };
+                        html! {
+                            <p class="visit-finding" data-goat={f.goat_name.clone()}>
+                                {format!("{}: ", f.goat_name)}
+                                <select class="finding-kind" onchange={on_kind}>
+                                    { for KINDS.iter().map(|k| html! {
+                                        <option value={IncidentKind::to_str(k)} selected={*k == f.kind}>
+                                            {IncidentKind::to_str(k)}
+                                        </option>
+                                    }) }
+                                </select>
+                                {" "}
+                                <input class="finding-condition" placeholder="Condition (blank if healthy)"
+                                       value={f.condition.clone()} oninput={on_field(false)} />
+                                {" "}
+                                <input class="finding-treatment" placeholder="Treatment"
+                                       value={f.treatment.clone().unwrap_or_default()}
+                                       oninput={on_field(true)} />
+                            </p>
+                        }
+                    }) }
+                    <button class="submit-completion" onclick={on_complete} disabled={read_only}>
+                        {"Complete visit"}
+                    </button>
+                </div>
+            }
+        </div>
+    }
+}
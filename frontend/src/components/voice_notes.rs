@@ -0,0 +1,339 @@
+//! Voice notes on a goat or task: short recordings made with the browser's
+//! `MediaRecorder` for workers who would rather speak than type, uploaded
+//! as attachments and played back inline (see `shared::attachments`).
+
+use crate::components::FileAudio;
+use crate::components::goat_notes::{remember_author, stored_author};
+use crate::services::api::attachment_url;
+use crate::services::use_api;
+use crate::store::{UnitsStore, use_read_only};
+use log::{error, info, warn};
+use shared::attachments::{Attachment, AttachmentTarget, MAX_VOICE_NOTE_SECS};
+use shared::time::format_local;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::{JsFuture, spawn_local};
+use web_sys::{
+    Blob, BlobEvent, HtmlInputElement, MediaRecorder, MediaStream, MediaStreamConstraints,
+    MediaStreamTrack, RecordingState,
+};
+use yew::platform::time::sleep;
+use yew::prelude::*;
+use yewdux::prelude::use_store_value;
+
+/// Type assumed for recordings when the browser does not name one.
+const DEFAULT_RECORDING_TYPE: &str = "audio/webm";
+
+/// A finished recording.
+struct Recording {
+    bytes: Vec<u8>,
+    content_type: String,
+    duration_secs: f64,
+}
+
+/// Asks for the microphone and starts recording from it. `on_done` gets
+/// the recording once the returned recorder is stopped; the microphone is
+/// released then.
+async fn start_recording(
+    on_done: Callback<Result<Recording, String>>,
+) -> Result<MediaRecorder, String> {
+    let devices = web_sys::window()
+        .and_then(|w| w.navigator().media_devices().ok())
+        .ok_or("Recording is not available in this browser")?;
+    let constraints = MediaStreamConstraints::new();
+    constraints.set_audio(&JsValue::TRUE);
+    let request = devices
+        .get_user_media_with_constraints(&constraints)
+        .map_err(|_| "Recording is not available in this browser")?;
+    let stream: MediaStream = JsFuture::from(request)
+        .await
+        .map_err(|_| "Microphone access was refused")?
+        .unchecked_into();
+    let recorder = MediaRecorder::new_with_media_stream(&stream)
+        .map_err(|e| format!("Could not record: {:?}", e))?;
+
+    let chunks = Rc::new(RefCell::new(Vec::<Blob>::new()));
+    let on_data = {
+        let chunks = chunks.clone();
+        Closure::<dyn FnMut(BlobEvent)>::new(move |event: BlobEvent| {
+            if let Some(chunk) = event.data() {
+                chunks.borrow_mut().push(chunk);
+            }
+        })
+        .into_js_value()
+    };
+    let started = js_sys::Date::now();
+    let on_stop = {
+        let recorder = recorder.clone();
+        Closure::once_into_js(move || {
+            let duration_secs = ((js_sys::Date::now() - started) / 1000.0).min(MAX_VOICE_NOTE_SECS);
+            for track in stream.get_tracks().iter() {
+                track.unchecked_into::<MediaStreamTrack>().stop();
+            }
+            let content_type = Some(recorder.mime_type())
+                .filter(|t| !t.is_empty())
+                .unwrap_or_else(|| DEFAULT_RECORDING_TYPE.to_string());
+            let parts: Vec<Blob> = chunks.borrow().clone();
+            spawn_local(async move {
+                let mut bytes = Vec::new();
+                for part in parts {
+                    match JsFuture::from(part.array_buffer()).await {
+                        Ok(buffer) => bytes.extend(js_sys::Uint8Array::new(&buffer).to_vec()),
+                        Err(_) => {
+                            on_done.emit(Err("Could not read the recording".into()));
+                            return;
+                        }
+                    }
+                }
+                on_done.emit(Ok(Recording {
+                    bytes,
+                    content_type,
+                    duration_secs,
+                }));
+            });
+        })
+    };
+    recorder.set_ondataavailable(Some(on_data.unchecked_ref()));
+    recorder.set_onstop(Some(on_stop.unchecked_ref()));
+    recorder
+        .start()
+        .map_err(|e| format!("Could not record: {:?}", e))?;
+    Ok(recorder)
+}
+
+/// Props for VoiceNotes:
+/// - `target`: the goat or task the notes belong to
+#[derive(Properties, PartialEq)]
+pub struct VoiceNotesProps {
+    pub target: AttachmentTarget,
+}
+
+/// VoiceNotes component:
+/// Lists the voice notes on a goat or task with inline players. Unless the
+/// dashboard is read-only, a button records a new note from the microphone
+/// (stopping on its own after `MAX_VOICE_NOTE_SECS`) and uploads it under
+/// the worker's name, and each note can be deleted.
+#[function_component(VoiceNotes)]
+pub fn voice_notes(props: &VoiceNotesProps) -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let timezone = use_store_value::<UnitsStore>().timezone;
+    let notes = use_state(|| None::<Vec<Attachment>>);
+    let author = use_state(stored_author);
+    let recorder = use_mut_ref(|| None::<MediaRecorder>);
+    let recording = use_state(|| false);
+    let busy = use_state(|| false);
+    let error = use_state(|| None::<String>);
+    // Bumped after each change to reload the list
+    let reloads = use_state(|| 0u32);
+
+    use_effect_with((props.target.clone(), *reloads), {
+        let api = api.clone();
+        let notes = notes.clone();
+        let error = error.clone();
+        move |(target, _): &(AttachmentTarget, u32)| {
+            let target = target.clone();
+            spawn_local(async move {
+                match api.attachments(&target).await {
+                    Ok(loaded) => {
+                        info!("Loaded {} voice notes for {:?}", loaded.len(), target);
+                        notes.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load voice notes for {:?}: {}", target, e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+            || {}
+        }
+    });
+
+    let on_author = {
+        let author = author.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                author.set(input.value());
+            }
+        })
+    };
+
+    // Uploads a finished recording
+    let on_recorded = {
+        let api = api.clone();
+        let target = props.target.clone();
+        let author = author.clone();
+        let busy = busy.clone();
+        let error = error.clone();
+        let reloads = reloads.clone();
+        Callback::from(move |result: Result<Recording, String>| {
+            let recording = match result {
+                Ok(recording) => recording,
+                Err(e) => {
+                    error.set(Some(e));
+                    return;
+                }
+            };
+            let api = api.clone();
+            let target = target.clone();
+            let author = author.trim().to_string();
+            let busy = busy.clone();
+            let error = error.clone();
+            let reloads = reloads.clone();
+            busy.set(true);
+            spawn_local(async move {
+                let uploaded = api
+                    .add_voice_note(
+                        &target,
+                        &author,
+                        recording.duration_secs,
+                        &recording.bytes,
+                        &recording.content_type,
+                    )
+                    .await;
+                match uploaded {
+                    Ok(stored) => {
+                        info!("Saved voice note {}", stored.id);
+                        reloads.set(*reloads + 1);
+                    }
+                    Err(e) => {
+                        error!("Failed to save voice note: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let on_record = {
+        let author = author.clone();
+        let recorder = recorder.clone();
+        let recording = recording.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            // A second press stops the recording, which uploads it
+            if let Some(active) = recorder.borrow_mut().take() {
+                if let Err(e) = active.stop() {
+                    warn!("Could not stop recording: {:?}", e);
+                }
+                recording.set(false);
+                return;
+            }
+            if author.trim().is_empty() {
+                error.set(Some("Enter your name before recording".into()));
+                return;
+            }
+            remember_author(author.trim());
+            error.set(None);
+            let recorder = recorder.clone();
+            let recording = recording.clone();
+            let error = error.clone();
+            let on_recorded = on_recorded.clone();
+            spawn_local(async move {
+                match start_recording(on_recorded).await {
+                    Ok(active) => {
+                        info!("Recording a voice note");
+                        *recorder.borrow_mut() = Some(active.clone());
+                        recording.set(true);
+                        sleep(Duration::from_secs_f64(MAX_VOICE_NOTE_SECS)).await;
+                        if active.state() == RecordingState::Recording {
+                            info!("Voice note reached its time limit");
+                            recorder.borrow_mut().take();
+                            let _ = active.stop();
+                            recording.set(false);
+                        }
+                    }
+                    Err(e) => {
+                        error!("Could not start recording: {}", e);
+                        error.set(Some(e));
+                    }
+                }
+            });
+        })
+    };
+
+    let on_delete = {
+        let error = error.clone();
+        let reloads = reloads.clone();
+        Callback::from(move |id: i64| {
+            let api = api.clone();
+            let error = error.clone();
+            let reloads = reloads.clone();
+            spawn_local(async move {
+                match api.delete_attachment(id).await {
+                    Ok(()) => {
+                        info!("Deleted voice note {}", id);
+                        reloads.set(*reloads + 1);
+                    }
+                    Err(e) => {
+                        error!("Failed to delete voice note {}: {}", id, e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="voice-notes">
+            <h4>{"Voice notes"}</h4>
+            {
+                match &*notes {
+                    None => html! {},
+                    Some(list) if list.is_empty() => html! {
+                        <p>{"No voice notes yet."}</p>
+                    },
+                    Some(list) => html! {
+                        <ul style="list-style: none; padding: 0;">
+                            { for list.iter().map(|note| html! {
+                                <li class="voice-note" data-attachment={note.id.to_string()}
+                                    style="margin-bottom: 8px;">
+                                    <div style="color: #666; font-size: 12px;">
+                                        <strong>{&note.author}</strong>
+                                        {format!(" · {}", format_local(&note.created_at, timezone))}
+                                        if let Some(secs) = note.duration_secs {
+                                            {format!(" · {:.0} s", secs)}
+                                        }
+                                        if !read_only {
+                                            { " " }
+                                            <button type="button" name="delete_voice_note"
+                                                    onclick={on_delete.reform({
+                                                        let id = note.id;
+                                                        move |_: MouseEvent| id
+                                                    })}>
+                                                {"Delete"}
+                                            </button>
+                                        }
+                                    </div>
+                                    <FileAudio url={attachment_url(note.id)} />
+                                </li>
+                            }) }
+                        </ul>
+                    },
+                }
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{err}</p>
+            }
+            if !read_only {
+                <div class="record-voice-note">
+                    <input type="text" name="voice_author" placeholder="Your name" size="12"
+                           value={(*author).clone()} oninput={on_author} />
+                    { " " }
+                    <button type="button" name="record" onclick={on_record} disabled={*busy}>
+                        { if *recording { "■ Stop and save" } else { "🎤 Record" } }
+                    </button>
+                    if *recording {
+                        <span style="color: #b71c1c; margin-left: 8px;">
+                            {format!("Recording… (up to {} s)", MAX_VOICE_NOTE_SECS)}
+                        </span>
+                    }
+                </div>
+            }
+        </div>
+    }
+}
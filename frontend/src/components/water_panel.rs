@@ -0,0 +1,179 @@
+//! Pen water: litres drawn per pen, drops in intake against the week before,
+//! and when each pen's troughs were last cleaned.
+
+use crate::components::SkeletonRows;
+use crate::services::use_api;
+use crate::store::use_read_only;
+use log::{error, info};
+use shared::water::{PenWater, TROUGH_CLEANING_DAYS};
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+/// WaterPanel component:
+/// Lists every pen with a water meter: its latest day's litres in total and
+/// per goat, its daily average over the week before, and when its troughs
+/// were last cleaned. Pens whose intake dropped yesterday are counted at the
+/// top and shaded, and troughs can be marked cleaned from the table.
+#[function_component(WaterPanel)]
+pub fn water_panel() -> Html {
+    let api = use_api();
+    let read_only = use_read_only();
+    let pens = use_state(|| None::<Vec<PenWater>>);
+    let message = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    let load = {
+        let api = api.clone();
+        let pens = pens.clone();
+        let error = error.clone();
+        Callback::from(move |_: ()| {
+            let api = api.clone();
+            let pens = pens.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.pen_water().await {
+                    Ok(loaded) => {
+                        info!("Loaded water use of {} pens", loaded.len());
+                        error.set(None);
+                        pens.set(Some(loaded));
+                    }
+                    Err(e) => {
+                        error!("Failed to load pen water: {}", e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    use_effect_with((), {
+        let load = load.clone();
+        move |_| {
+            load.emit(());
+            || ()
+        }
+    });
+
+    let on_cleaned = {
+        let load = load.clone();
+        let message = message.clone();
+        let error = error.clone();
+        Callback::from(move |pen: PenWater| {
+            let api = api.clone();
+            let load = load.clone();
+            let message = message.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.mark_trough_cleaned(pen.space_id).await {
+                    Ok(()) => {
+                        info!("Troughs of {} cleaned", pen.space_name);
+                        message.set(Some(format!(
+                            "Troughs in {} cleaned; next due in {} days",
+                            pen.space_name, TROUGH_CLEANING_DAYS
+                        )));
+                        load.emit(());
+                    }
+                    Err(e) => {
+                        error!(
+                            "Failed to mark troughs of {} cleaned: {}",
+                            pen.space_name, e
+                        );
+                        message.set(None);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <div>
+            <h3>{"Water"}</h3>
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Error: {}", err)}</p>
+            }
+            if let Some(msg) = &*message {
+                <p class="water-message" style="color: green;">{msg}</p>
+            }
+            <button onclick={load.reform(|_| ())} style="margin-bottom: 10px;">{"Refresh"}</button>
+            {
+                match &*pens {
+                    None => html! {
+                        <table><tbody><SkeletonRows rows={2} columns={6} /></tbody></table>
+                    },
+                    Some(list) if list.is_empty() => html! {
+                        <p>{"No water meters registered in any pen."}</p>
+                    },
+                    Some(list) => {
+                        let drops = list.iter().filter(|p| p.drop.is_some()).count();
+                        html! {
+                            <>
+                                if drops > 0 {
+                                    <p class="water-drops" style="color: #b71c1c; font-weight: bold;">
+                                        {format!("Water intake dropped in {} pen(s); check the goats", drops)}
+                                    </p>
+                                }
+                                <table class="pen-water" style="border-collapse: collapse; width: 100%;">
+                                    <thead>
+                                        <tr>
+                                            <th>{"Pen"}</th>
+                                            <th>{"Latest day"}</th>
+                                            <th>{"Per goat"}</th>
+                                            <th>{"Week average"}</th>
+                                            <th>{"Troughs cleaned"}</th>
+                                            <th></th>
+                                        </tr>
+                                    </thead>
+                                    <tbody>
+                                        { for list.iter().map(|pen| {
+                                            let on_cleaned = {
+                                                let pen = pen.clone();
+                                                on_cleaned.reform(move |_: MouseEvent| pen.clone())
+                                            };
+                                            pen_row(pen, on_cleaned, read_only)
+                                        }) }
+                                    </tbody>
+                                </table>
+                            </>
+                        }
+                    }
+                }
+            }
+        </div>
+    }
+}
+
+/// One table row for a pen.
+fn pen_row(pen: &PenWater, on_cleaned: Callback<MouseEvent>, read_only: bool) -> Html {
+    let style = pen
+        .drop
+        .is_some()
+        .then_some("background: #fdecea; color: #b71c1c;");
+    let latest = pen.days.last().map_or("No data".to_string(), |day| {
+        format!("{:.0} L on {}", day.litres, day.date)
+    });
+    html! {
+        <tr data-pen={pen.space_name.clone()} {style}>
+            <td>{&pen.space_name}</td>
+            <td class="latest">
+                {latest}
+                if let Some(drop) = &pen.drop {
+                    <span class="water-drop">
+                        {format!(" ({:.0}% below average on {})", drop.fall() * 100.0, drop.date)}
+                    </span>
+                }
+            </td>
+            <td>{pen.per_goat().map_or("–".to_string(), |l| format!("{:.1} L", l))}</td>
+            <td>{pen.baseline.map_or("–".to_string(), |l| format!("{:.0} L", l))}</td>
+            <td class="last-cleaned">
+                {pen.last_cleaned.clone().unwrap_or_else(|| "Never".to_string())}
+                if pen.cleaning_task_id.is_some() {
+                    <span style="color: #e65100;">{" (due)"}</span>
+                }
+            </td>
+            <td>
+                <button class="mark-cleaned" onclick={on_cleaned} disabled={read_only}>{"Mark cleaned"}</button>
+            </td>
+        </tr>
+    }
+}
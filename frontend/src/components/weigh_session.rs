@@ -0,0 +1,191 @@
+//! Live weigh session: shows readings as the scale bridge pushes them and
+//! lets the user assign unknown tags to goats.
+
+use crate::services::use_api;
+use crate::store::{GoatStore, farm_timezone, use_read_only};
+use log::{error, info};
+use shared::scale::ScaleReading;
+use shared::time::format_local;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::platform::time::sleep;
+use yew::prelude::*;
+use yewdux::prelude::use_store;
+
+/// How often the backend is polled for new readings while a session runs.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Readings seen in the current session, newest last.
+#[derive(Default, PartialEq)]
+struct Readings(Vec<ScaleReading>);
+
+impl Reducible for Readings {
+    /// New or updated readings; existing ones are replaced by ID.
+    type Action = Vec<ScaleReading>;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut readings = self.0.clone();
+        for reading in action {
+            match readings.iter_mut().find(|r| r.id == reading.id) {
+                Some(existing) => *existing = reading,
+                None => readings.push(reading),
+            }
+        }
+        Rc::new(Readings(readings))
+    }
+}
+
+/// WeighSession component:
+/// While started, polls the backend for new scale readings. Readings whose
+/// tag is known show the goat and are already recorded as weights; for
+/// unknown tags the user enters a goat name and assigns the tag, which
+/// records every waiting reading with that tag. Assigning is disabled in
+/// read-only mode.
+#[function_component(WeighSession)]
+pub fn weigh_session() -> Html {
+    let (state, _) = use_store::<GoatStore>();
+    let api = use_api();
+    let active = use_state(|| false);
+    let readings = use_reducer(Readings::default);
+    let goat_inputs = use_state(HashMap::<i64, String>::new);
+    let error = use_state(|| None::<String>);
+    let read_only = use_read_only();
+
+    {
+        let api = api.clone();
+        let readings = readings.dispatcher();
+        let error = error.clone();
+        use_effect_with(*active, move |active| {
+            let running = Rc::new(Cell::new(*active));
+            if *active {
+                let running = running.clone();
+                spawn_local(async move {
+                    let mut since_id = None;
+                    while running.get() {
+                        match api.scale_readings(since_id).await {
+                            Ok(batch) => {
+                                if let Some(last) = batch.last() {
+                                    info!("Received {} scale readings", batch.len());
+                                    since_id = Some(last.id);
+                                    readings.dispatch(batch);
+                                }
+                            }
+                            Err(e) => {
+                                error!("Failed to poll scale readings: {}", e);
+                                error.set(Some(e.to_string()));
+                            }
+                        }
+                        sleep(POLL_INTERVAL).await;
+                    }
+                });
+            }
+            move || running.set(false)
+        });
+    }
+
+    let on_toggle = {
+        let active = active.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            error.set(None);
+            active.set(!*active);
+        })
+    };
+
+    let assign = |reading_id: i64| {
+        let api = api.clone();
+        let readings = readings.dispatcher();
+        let goat_inputs = goat_inputs.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            let Some(goat_name) = goat_inputs
+                .get(&reading_id)
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+            else {
+                error.set(Some("Enter a goat name to assign the tag to".into()));
+                return;
+            };
+            let api = api.clone();
+            let readings = readings.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match api.assign_reading(reading_id, &goat_name).await {
+                    Ok(matched) => {
+                        info!("Matched {} readings to {}", matched.len(), goat_name);
+                        error.set(None);
+                        readings.dispatch(matched);
+                    }
+                    Err(e) => {
+                        error!("Failed to assign reading {}: {}", reading_id, e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+            });
+        })
+    };
+
+    let goat_input = |reading_id: i64| {
+        let goat_inputs = goat_inputs.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                let mut updated = (*goat_inputs).clone();
+                updated.insert(reading_id, input.value());
+                goat_inputs.set(updated);
+            }
+        })
+    };
+
+    html! {
+        <div>
+            <h3>{"Weigh Session"}</h3>
+            <button onclick={on_toggle}>
+                { if *active { "Stop session" } else { "Start session" } }
+            </button>
+            if *active {
+                <span style="margin-left: 8px; font-size: 12px;">{"Listening for scale readings..."}</span>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{format!("Weigh session error: {}", err)}</p>
+            }
+            <datalist id="weigh-session-goats">
+                { for state.goats.iter().map(|g| html! { <option value={g.name.clone()} /> }) }
+            </datalist>
+            if !readings.0.is_empty() {
+                <table style="border-collapse: collapse; width: 100%; margin-top: 10px;">
+                    <thead>
+                        <tr>
+                            <th>{"Time"}</th>
+                            <th>{"Tag"}</th>
+                            <th>{"Weight (kg)"}</th>
+                            <th>{"Goat"}</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        { for readings.0.iter().rev().map(|r| html! {
+                            <tr data-reading={r.id.to_string()}
+                                style={if r.goat_name.is_none() { "background: #fff8e1;" } else { "" }}>
+                                <td>{format_local(&r.read_at, farm_timezone())}</td>
+                                <td>{&r.tag_id}</td>
+                                <td>{format!("{:.1}", r.weight)}</td>
+                                <td class="goat">
+                                    if let Some(name) = &r.goat_name {
+                                        {name}
+                                    } else {
+                                        <input list="weigh-session-goats" placeholder="Goat name"
+                                               oninput={goat_input(r.id)} />
+                                        <button onclick={assign(r.id)} disabled={read_only}>{"Assign"}</button>
+                                    }
+                                </td>
+                            </tr>
+                        }) }
+                    </tbody>
+                </table>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,247 @@
+//! Form logging one weighing of a goat, typed in or estimated from a photo
+//! of the goat beside an object of known length.
+
+use crate::components::date_picker::{date_problem, today};
+use crate::components::{DatePicker, NumberField, Quantity};
+use crate::services::use_api;
+use log::{error, info};
+use shared::growth::WeightRecord;
+use wasm_bindgen_futures::{JsFuture, spawn_local};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/// Reference length assumed until the user changes it: the long side of an
+/// A4 sheet, which most farms have to hand.
+const DEFAULT_REFERENCE_CM: f64 = 29.7;
+
+/// Reads the photo chosen in `input` with its MIME type.
+pub async fn read_photo(input: &HtmlInputElement) -> Result<(Vec<u8>, String), String> {
+    let file = input
+        .files()
+        .and_then(|files| files.get(0))
+        .ok_or("Take or choose a photo first")?;
+    let buffer = JsFuture::from(file.array_buffer())
+        .await
+        .map_err(|_| format!("Could not read {}", file.name()))?;
+    Ok((js_sys::Uint8Array::new(&buffer).to_vec(), file.type_()))
+}
+
+/// Props for WeightLog:
+/// - `goat_name`: goat being weighed
+/// - `on_saved`: called after a weighing is saved
+#[derive(Properties, PartialEq)]
+pub struct WeightLogProps {
+    pub goat_name: String,
+    pub on_saved: Callback<()>,
+}
+
+/// WeightLog component:
+/// Records a weighing on a date. Instead of typing the weight, the user may
+/// photograph the goat beside a reference object and have the backend's
+/// estimation service pre-fill it; such weighings are saved marked as
+/// estimated unless the user unticks the box, e.g. after checking the
+/// figure on a scale.
+#[function_component(WeightLog)]
+pub fn weight_log(props: &WeightLogProps) -> Html {
+    let api = use_api();
+    let weighed_on = use_state(today);
+    let weight = use_state(|| None::<f64>);
+    let estimated = use_state(|| false);
+    let reference_cm = use_state(|| DEFAULT_REFERENCE_CM.to_string());
+    let photo_ref = use_node_ref();
+    let busy = use_state(|| false);
+    let note = use_state(|| None::<String>);
+    let error = use_state(|| None::<String>);
+
+    let on_date = {
+        let weighed_on = weighed_on.clone();
+        Callback::from(move |value: String| weighed_on.set(value))
+    };
+    let on_weight = {
+        let weight = weight.clone();
+        Callback::from(move |value: Option<f64>| weight.set(value))
+    };
+    let on_estimated = {
+        let estimated = estimated.clone();
+        Callback::from(move |e: Event| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                estimated.set(input.checked());
+            }
+        })
+    };
+    let on_reference = {
+        let reference_cm = reference_cm.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                reference_cm.set(input.value());
+            }
+        })
+    };
+
+    let on_estimate = {
+        let api = api.clone();
+        let weight = weight.clone();
+        let estimated = estimated.clone();
+        let reference_cm = reference_cm.clone();
+        let photo_ref = photo_ref.clone();
+        let busy = busy.clone();
+        let note = note.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let reference = match reference_cm.trim().parse::<f64>() {
+                Ok(cm) if cm > 0.0 => cm,
+                _ => {
+                    error.set(Some(
+                        "Reference length must be a positive number of cm".into(),
+                    ));
+                    return;
+                }
+            };
+            let Some(input) = photo_ref.cast::<HtmlInputElement>() else {
+                return;
+            };
+            let api = api.clone();
+            let weight = weight.clone();
+            let estimated = estimated.clone();
+            let busy = busy.clone();
+            let note = note.clone();
+            let error = error.clone();
+            busy.set(true);
+            error.set(None);
+            note.set(None);
+            spawn_local(async move {
+                let result = match read_photo(&input).await {
+                    Ok((bytes, content_type)) => api
+                        .estimate_weight(&bytes, &content_type, reference)
+                        .await
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e),
+                };
+                match result {
+                    Ok(estimate) => {
+                        info!("Estimated {:.1} kg from photo", estimate.weight);
+                        weight.set(Some(estimate.weight));
+                        estimated.set(true);
+                        note.set(Some(match estimate.confidence {
+                            Some(c) => format!(
+                                "Estimated from the photo ({:.0}% confidence); check before saving.",
+                                c * 100.0
+                            ),
+                            None => "Estimated from the photo; check before saving.".to_string(),
+                        }));
+                    }
+                    Err(e) => {
+                        error!("Weight estimation failed: {}", e);
+                        error.set(Some(e));
+                    }
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let on_save = {
+        let goat_name = props.goat_name.clone();
+        let on_saved = props.on_saved.clone();
+        let weighed_on = weighed_on.clone();
+        let weight = weight.clone();
+        let estimated = estimated.clone();
+        let busy = busy.clone();
+        let note = note.clone();
+        let error = error.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let Some(kg) = *weight else {
+                error.set(Some("Enter a weight or estimate one from a photo".into()));
+                return;
+            };
+            if let Some(problem) = date_problem("Weighing date", &weighed_on) {
+                error.set(Some(problem));
+                return;
+            }
+            let record = WeightRecord {
+                id: None,
+                goat_name: goat_name.clone(),
+                weighed_on: (*weighed_on).clone(),
+                weight: kg,
+                age_days: None,
+                estimated: *estimated,
+            };
+            let api = api.clone();
+            let on_saved = on_saved.clone();
+            let weight = weight.clone();
+            let estimated = estimated.clone();
+            let busy = busy.clone();
+            let note = note.clone();
+            let error = error.clone();
+            busy.set(true);
+            error.set(None);
+            spawn_local(async move {
+                match api.add_weight(&record).await {
+                    Ok(()) => {
+                        info!(
+                            "Logged {:.1} kg for {} (estimated: {})",
+                            record.weight, record.goat_name, record.estimated
+                        );
+                        weight.set(None);
+                        estimated.set(false);
+                        note.set(None);
+                        on_saved.emit(());
+                    }
+                    Err(e) => {
+                        error!("Failed to log weighing for {}: {}", record.goat_name, e);
+                        error.set(Some(e.to_string()));
+                    }
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    html! {
+        <form class="weight-log" onsubmit={on_save}>
+            <h4>{"Log weighing"}</h4>
+            <label>
+                {"Weighed on: "}
+                <DatePicker name="weighed_on" label="Weighed on"
+                            value={(*weighed_on).clone()} onchange={on_date} />
+            </label>
+            { " " }
+            <label>
+                {"Weight: "}
+                <NumberField name="weight" quantity={Quantity::Weight} value={*weight}
+                             min={0.0} onchange={on_weight} disabled={*busy} />
+            </label>
+            { " " }
+            <label title="Estimated weighings are charted apart and never become the goat's current weight">
+                <input type="checkbox" name="estimated" checked={*estimated}
+                       onchange={on_estimated} />
+                {" Estimated"}
+            </label>
+            <p>
+                <label>
+                    {"Photo beside a reference object: "}
+                    <input type="file" name="goat_photo" accept="image/*"
+                           capture="environment" ref={photo_ref} />
+                </label>
+                { " " }
+                <label>
+                    {"Reference length (cm): "}
+                    <input type="text" inputmode="decimal" name="reference_cm" size="5"
+                           value={(*reference_cm).clone()} oninput={on_reference} />
+                </label>
+                { " " }
+                <button type="button" onclick={on_estimate} disabled={*busy}>
+                    {"Estimate from photo"}
+                </button>
+            </p>
+            if let Some(message) = &*note {
+                <p class="estimate-note">{message}</p>
+            }
+            if let Some(err) = &*error {
+                <p style="color: red;">{err}</p>
+            }
+            <button type="submit" disabled={*busy}>{"Save weighing"}</button>
+        </form>
+    }
+}
@@ -0,0 +1,143 @@
+//! Crash reporting for the goat dashboard app.
+//!
+//! Installs a panic hook that replaces the dead app with a static fallback
+//! screen and, when the user has opted in, sends a crash report to the
+//! backend's `/client-errors` endpoint via `navigator.sendBeacon`
+//! (a panicking WASM module cannot drive async requests to completion).
+//! Section-level failures caught by `ErrorBoundary` can be submitted on demand.
+
+use crate::errors::{AppError, check_response};
+use crate::services::api::with_tenant;
+use gloo_net::http::Request;
+use log::{error, info, warn};
+use shared::diagnostics::ClientErrorReport;
+use wasm_bindgen_futures::spawn_local;
+use yew::Callback;
+
+/// Backend endpoint receiving crash reports.
+pub const CLIENT_ERRORS_URL: &str = "http://127.0.0.1:8000/client-errors";
+
+/// localStorage key holding the user's automatic crash report preference.
+const AUTO_REPORT_KEY: &str = "yagi.crash_reports";
+
+/// Returns true if the user opted in to automatic crash reports.
+pub fn auto_report_enabled() -> bool {
+    web_sys::window()
+        .and_then(|w| w.local_storage().ok().flatten())
+        .and_then(|s| s.get_item(AUTO_REPORT_KEY).ok().flatten())
+        .as_deref()
+        == Some("on")
+}
+
+/// Persists the user's automatic crash report preference.
+pub fn set_auto_report(enabled: bool) {
+    let storage = web_sys::window().and_then(|w| w.local_storage().ok().flatten());
+    match storage {
+        Some(storage) => {
+            let value = if enabled { "on" } else { "off" };
+            if storage.set_item(AUTO_REPORT_KEY, value).is_err() {
+                warn!("Could not persist crash report preference");
+            }
+        }
+        None => warn!("localStorage unavailable; crash report preference not saved"),
+    }
+}
+
+/// Builds a report for `message`, capturing the current page URL and user agent.
+pub fn build_report(section: Option<String>, message: String) -> ClientErrorReport {
+    let window = web_sys::window();
+    ClientErrorReport {
+        id: None,
+        section,
+        message,
+        url: window.as_ref().and_then(|w| w.location().href().ok()),
+        user_agent: window.as_ref().and_then(|w| w.navigator().user_agent().ok()),
+        reported_at: None,
+    }
+}
+
+/// Submits a report with a regular request, reporting the outcome via `on_result`.
+pub fn submit_report(report: ClientErrorReport, on_result: Callback<Result<(), AppError>>) {
+    spawn_local(async move {
+        let outcome = post_report(&report).await;
+        match &outcome {
+            Ok(()) => info!("Crash report submitted"),
+            Err(e) => warn!("Could not submit crash report: {}", e),
+        }
+        on_result.emit(outcome);
+    });
+}
+
+async fn post_report(report: &ClientErrorReport) -> Result<(), AppError> {
+    let resp = with_tenant(Request::post(CLIENT_ERRORS_URL))
+        .json(report)?
+        .send()
+        .await?;
+    check_response(resp).await?;
+    Ok(())
+}
+
+/// Sends a report synchronously with `navigator.sendBeacon`. Safe to call from a panic hook.
+fn send_beacon(report: &ClientErrorReport) -> bool {
+    let Some(window) = web_sys::window() else {
+        return false;
+    };
+    match serde_json::to_string(report) {
+        Ok(body) => window
+            .navigator()
+            .send_beacon_with_opt_str(CLIENT_ERRORS_URL, Some(&body))
+            .unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+/// Replaces the page content with a static crash screen offering a reload.
+///
+/// Built with plain DOM calls because the Yew app can no longer render.
+fn show_crash_screen(message: &str) {
+    let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+        return;
+    };
+    let Some(body) = document.body() else {
+        return;
+    };
+
+    let build = || -> Result<(), wasm_bindgen::JsValue> {
+        let screen = document.create_element("div")?;
+        screen.set_attribute(
+            "style",
+            "position: fixed; inset: 0; background: #fff; padding: 40px; z-index: 1000;",
+        )?;
+
+        let title = document.create_element("h2")?;
+        title.set_text_content(Some("Yagi ran into a problem and stopped."));
+        let detail = document.create_element("pre")?;
+        detail.set_text_content(Some(message));
+        let reload = document.create_element("button")?;
+        reload.set_text_content(Some("Reload page"));
+        reload.set_attribute("onclick", "window.location.reload()")?;
+
+        screen.append_child(&title)?;
+        screen.append_child(&detail)?;
+        screen.append_child(&reload)?;
+        body.append_child(&screen)?;
+        Ok(())
+    };
+    if build().is_err() {
+        error!("Failed to render crash screen");
+    }
+}
+
+/// Installs the application-wide panic hook. Call once at startup.
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|panic_info| {
+        let message = panic_info.to_string();
+        error!("Application crashed: {}", message);
+
+        if auto_report_enabled() {
+            let sent = send_beacon(&build_report(None, message.clone()));
+            info!("Crash report beacon queued: {}", sent);
+        }
+        show_crash_screen(&message);
+    }));
+}
@@ -0,0 +1,83 @@
+//! Form drafts kept in localStorage, so a half-finished entry survives a
+//! reload, a closed tab or a dropped connection.
+//!
+//! Drafts are keyed by form, plus the goat's ID for forms editing one goat
+//! (see `goat_draft_key`). Forms either save explicitly or autosave with
+//! `use_autosave`.
+
+use log::warn;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use web_sys::Storage;
+use yew::prelude::*;
+
+/// Prefix of the localStorage key each form's draft is stored under.
+const DRAFT_KEY_PREFIX: &str = "yagi.draft.";
+
+fn storage() -> Option<Storage> {
+    web_sys::window().and_then(|w| w.local_storage().ok().flatten())
+}
+
+fn key(form: &str) -> String {
+    format!("{}{}", DRAFT_KEY_PREFIX, form)
+}
+
+/// Draft key for `form` editing the goat with ID `goat_id`, e.g. `update_goat.12`.
+pub fn goat_draft_key(form: &str, goat_id: i64) -> String {
+    format!("{}.{}", form, goat_id)
+}
+
+/// Returns the draft saved for `form`, if there is one that still parses.
+pub fn load_draft<T: DeserializeOwned>(form: &str) -> Option<T> {
+    let json = storage()?.get_item(&key(form)).ok().flatten()?;
+    match serde_json::from_str(&json) {
+        Ok(draft) => Some(draft),
+        Err(e) => {
+            warn!("Ignoring unreadable draft for {}: {}", form, e);
+            None
+        }
+    }
+}
+
+/// Saves `draft` for `form`, replacing any earlier one. Returns false if
+/// localStorage is unavailable or full.
+pub fn save_draft<T: Serialize>(form: &str, draft: &T) -> bool {
+    let Some(storage) = storage() else {
+        warn!("localStorage unavailable; draft for {} not saved", form);
+        return false;
+    };
+    let saved = serde_json::to_string(draft)
+        .ok()
+        .is_some_and(|json| storage.set_item(&key(form), &json).is_ok());
+    if !saved {
+        warn!("Could not save draft for {}", form);
+    }
+    saved
+}
+
+/// Deletes the draft saved for `form`, if any.
+pub fn discard_draft(form: &str) {
+    if let Some(storage) = storage() {
+        let _ = storage.remove_item(&key(form));
+    }
+}
+
+/// Saves `value` as the draft for `form` whenever it changes, and discards
+/// the draft once `value` is back to `pristine`, so an untouched form leaves
+/// nothing behind. Does nothing while `form` is `None`, e.g. before the
+/// goat being edited is loaded.
+#[hook]
+pub fn use_autosave<T>(form: Option<String>, value: T, pristine: T)
+where
+    T: Serialize + PartialEq + 'static,
+{
+    use_effect_with((form, value, pristine), |(form, value, pristine)| {
+        if let Some(form) = form {
+            if value == pristine {
+                discard_draft(form);
+            } else {
+                save_draft(form, value);
+            }
+        }
+    });
+}
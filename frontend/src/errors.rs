@@ -1,7 +1,6 @@
 //! Custom errors used throughout the goat dashboard app.
 //! Designed for extensibility and detailed error reporting.
 
-use std::fmt;
 use thiserror::Error; // Use thiserror crate for convenient error derive
 
 /// Enumerates possible application errors for goat management.
@@ -11,6 +10,15 @@ pub enum AppError {
     #[error("Network or API error: {0}")]
     NetworkError(String),
 
+    /// The backend answered with a non-success status code.
+    /// `body` carries the backend's error message.
+    #[error("Server error {status}: {body}")]
+    ApiError { status: u16, body: String },
+
+    /// A request or response body could not be (de)serialized.
+    #[error("Malformed data: {0}")]
+    ParseError(String),
+
     /// Errors related to invalid user input or form data.
     #[error("Invalid input: {0}")]
     InvalidInput(String),
@@ -44,4 +52,40 @@ impl AppError {
     pub fn unexpected<S: Into<String>>(msg: S) -> Self {
         AppError::Unexpected(msg.into())
     }
+
+    /// Creates an API error from a response status and body.
+    pub fn api<S: Into<String>>(status: u16, body: S) -> Self {
+        AppError::ApiError {
+            status,
+            body: body.into(),
+        }
+    }
+}
+
+/// Passes successful responses through and turns any other status into
+/// `AppError::ApiError` carrying the backend's error body.
+pub async fn check_response(
+    resp: gloo_net::http::Response,
+) -> Result<gloo_net::http::Response, AppError> {
+    if resp.ok() {
+        return Ok(resp);
+    }
+    let status = resp.status();
+    let body = resp.text().await.unwrap_or_default();
+    Err(AppError::api(status, body))
+}
+
+impl From<gloo_net::Error> for AppError {
+    fn from(err: gloo_net::Error) -> Self {
+        match err {
+            gloo_net::Error::SerdeError(e) => AppError::ParseError(e.to_string()),
+            other => AppError::NetworkError(other.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> Self {
+        AppError::ParseError(err.to_string())
+    }
 }
@@ -1,4 +1,7 @@
 pub mod app;
-mod components;
-mod errors;
-mod store;
+pub mod components;
+pub mod crash_report;
+pub mod drafts;
+pub mod errors;
+pub mod services;
+pub mod store;
@@ -1,12 +1,14 @@
 //! Entrypoint of the Yew goat management webapp.
-//! Initializes wasm_logger for descriptive logging in browser console.
+//! Initializes wasm_logger for descriptive logging in browser console
+//! and installs the crash reporting panic hook.
 
-use frontend::app;
+use frontend::{app, crash_report};
 use wasm_logger;
 use yew::Renderer;
 
 fn main() {
     wasm_logger::init(wasm_logger::Config::default());
+    crash_report::install_panic_hook();
 
     Renderer::<app::App>::new().render();
 }
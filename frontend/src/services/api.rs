@@ -0,0 +1,2178 @@
+//! HTTP access to the backend, behind a mockable trait.
+//!
+//! `GoatStore` and the forms talk to the backend only through `ApiClient`.
+//! `HttpApiClient` is the real gloo-net implementation; tests provide a
+//! `MockApiClient` through `ApiProvider` instead, so store reducers and form
+//! submit flows run deterministically without a backend.
+//!
+//! Once this device has signed in, `HttpApiClient` sends its session token
+//! with every request (see `shared::sessions`).
+
+use crate::errors::{AppError, check_response};
+use gloo_net::http::RequestBuilder;
+use log::{info, trace};
+use shared::activity::ActivityEvent;
+use shared::alerts::AlertRule;
+use shared::analytics::FeedEfficiencyReport;
+use shared::archive::{ArchiveSummary, PASSPHRASE_HEADER};
+use shared::attachments::{Attachment, AttachmentTarget};
+use shared::breeding::{
+    BreedingRecommendation, BreedingService, ExternalAnimal, GeneticTags, MethodSuccess, Neutering,
+    PedigreeNode,
+};
+use shared::breeds::CatalogBreed;
+use shared::bulk::BulkPatch;
+use shared::data_health::DataHealthReport;
+use shared::diseases::{CatalogDisease, ComplianceReport, NewDisease, NotifiableFlag};
+use shared::events::FieldChange;
+use shared::exports::ExportTemplate;
+use shared::finance::{Budget, BudgetReport, Transaction};
+use shared::gps::{Geofence, GoatPosition};
+use shared::grazing::{Paddock, RotationPlan};
+use shared::growth::{GrowthBenchmark, GrowthHistory, WeightEstimate, WeightRecord};
+use shared::health::{HealthHeatMap, TimelineEntry};
+use shared::heat::HeatPrediction;
+use shared::import::{BatchSummary, ImportTable};
+use shared::inventory::{Disposal, ExpiringStock, InventoryItem};
+use shared::jobs::Job;
+use shared::lab::LabResult;
+use shared::lifecycle::{LifecyclePipeline, StageChange, StageTransition};
+use shared::milk::{Lactation, MilkRecord};
+use shared::movements::AnimalMovement;
+use shared::notes::{GoatNote, NoteInput};
+use shared::notifications::Notification;
+use shared::nutrition::{RationPlan, RationRequest};
+use shared::permissions::{MyPermissions, RolePermissions};
+use shared::pricing::GoatValuation;
+use shared::retention::UpcomingPurges;
+use shared::scale::{ScaleReading, TagAssignment};
+use shared::scoring::{GoatScore, ScoreWeights};
+use shared::search::SearchResult;
+use shared::sensors::SensorCondition;
+use shared::sessions::{
+    IssuedSession, NewSession, OAuthCallback, OAuthRedirect, OAuthStart, SESSION_HEADER, Session,
+};
+use shared::settings::{FarmSettings, ReadOnlyMode};
+use shared::spaces::{Space, SpaceOccupancy};
+use shared::stats::DashboardStats;
+use shared::tags::NextTag;
+use shared::tasks::Task;
+use shared::tenants::TENANT_KEY_HEADER;
+use shared::tokens::{AccessToken, IssuedAccessToken, NewAccessToken};
+use shared::traceability::TraceabilityRecord;
+use shared::vet::{VetAvailability, VetVisit, VisitRequest, VisitSchedule, VisitSummary};
+use shared::water::PenWater;
+use shared::{Goat, GoatUpdate, NewGoat};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::ReadableStreamDefaultReader;
+use yew::prelude::*;
+
+/// Backend endpoint for goat records.
+const GOATS_URL: &str = "http://127.0.0.1:8000/goats";
+
+/// Backend endpoint previewing the tag the next goat added will get.
+const NEXT_TAG_URL: &str = "http://127.0.0.1:8000/goats/next-tag";
+
+/// Backend endpoint adding many goats in one request.
+const GOATS_BATCH_URL: &str = "http://127.0.0.1:8000/goats/batch";
+
+/// Backend endpoint reading an uploaded CSV or XLSX file for import.
+const GOATS_IMPORT_URL: &str = "http://127.0.0.1:8000/goats/import";
+
+/// Backend endpoint streaming goat records as NDJSON.
+const GOATS_STREAM_URL: &str = "http://127.0.0.1:8000/goats/stream";
+
+/// Backend endpoint suggesting buck–doe pairings.
+const BREEDING_RECOMMENDATIONS_URL: &str = "http://127.0.0.1:8000/breeding/recommendations";
+
+/// Backend endpoint listing castrated and spayed goats.
+const NEUTERINGS_URL: &str = "http://127.0.0.1:8000/breeding/neuterings";
+
+/// Backend endpoint for services (matings), natural or by AI.
+const SERVICES_URL: &str = "http://127.0.0.1:8000/breeding/services";
+
+/// Backend endpoint comparing the success rates of natural service and AI.
+const SERVICE_SUCCESS_URL: &str = "http://127.0.0.1:8000/breeding/services/success";
+
+/// Backend endpoint for the registry of animals kept off the farm.
+const EXTERNAL_ANIMALS_URL: &str = "http://127.0.0.1:8000/breeding/external";
+
+/// Backend endpoint for goats' bloodline and genetic trait tags.
+const GENETIC_TAGS_URL: &str = "http://127.0.0.1:8000/breeding/tags";
+
+/// Backend endpoint for a goat's pedigree tree, by goat name.
+const PEDIGREE_URL: &str = "http://127.0.0.1:8000/breeding/pedigree";
+
+/// Backend endpoint for the breed catalog.
+const BREEDS_URL: &str = "http://127.0.0.1:8000/breeds";
+
+/// Backend endpoint benchmarking goats against breed growth curves.
+const GROWTH_BENCHMARKS_URL: &str = "http://127.0.0.1:8000/growth/benchmarks";
+
+/// Backend endpoint for weighings: POST records one, and a goat's weight
+/// history is fetched with its name appended.
+const WEIGHT_HISTORY_URL: &str = "http://127.0.0.1:8000/growth/weights";
+
+/// Backend endpoint estimating a goat's weight from a photo.
+const WEIGHT_ESTIMATE_URL: &str = "http://127.0.0.1:8000/growth/estimate";
+
+/// Backend endpoint for recording milkings.
+const MILK_RECORDS_URL: &str = "http://127.0.0.1:8000/milk/records";
+
+/// Backend endpoint summarising lactations from milk records.
+const LACTATIONS_URL: &str = "http://127.0.0.1:8000/milk/lactations";
+
+/// Backend endpoint reporting feed conversion per goat and per pen.
+const FEED_EFFICIENCY_URL: &str = "http://127.0.0.1:8000/analytics/feed-efficiency";
+
+/// Backend endpoint for pens, fields and other spaces.
+const SPACES_URL: &str = "http://127.0.0.1:8000/spaces";
+
+/// Backend endpoint reporting how full each pen and field is.
+const SPACE_OCCUPANCY_URL: &str = "http://127.0.0.1:8000/spaces/occupancy";
+
+/// Backend endpoint aggregating health incidents by pen and month.
+const HEALTH_HEATMAP_URL: &str = "http://127.0.0.1:8000/health/heatmap";
+
+/// Backend endpoint listing a goat's incidents, vaccinations and lab
+/// results by date.
+const HEALTH_TIMELINE_URL: &str = "http://127.0.0.1:8000/health/timeline";
+
+/// Backend endpoint for vet visits and the vets' bookings.
+const VET_VISITS_URL: &str = "http://127.0.0.1:8000/vet-visits";
+
+/// Backend endpoint for lab test results; each result's PDF report is
+/// served from `{id}/report` below it.
+const LAB_RESULTS_URL: &str = "http://127.0.0.1:8000/lab-results";
+
+/// Backend endpoint for the disease catalog and which diseases are
+/// notifiable.
+const DISEASES_URL: &str = "http://127.0.0.1:8000/diseases";
+
+/// Backend endpoint for the notifiable disease compliance report.
+const COMPLIANCE_URL: &str = "http://127.0.0.1:8000/diseases/compliance";
+
+/// Where the compliance report is downloaded as CSV for authorities.
+pub const COMPLIANCE_CSV_URL: &str = "http://127.0.0.1:8000/diseases/compliance?format=csv";
+
+/// Where the PDF report of the lab result with `id` is served.
+pub fn lab_report_url(id: i64) -> String {
+    format!("{}/{}/report", LAB_RESULTS_URL, id)
+}
+
+/// Backend endpoint for the register of goat movements between farms,
+/// markets and buyers.
+const MOVEMENTS_URL: &str = "http://127.0.0.1:8000/movements";
+
+/// Backend endpoint for the traceability records of sold goats.
+const TRACEABILITY_URL: &str = "http://127.0.0.1:8000/traceability";
+
+/// Address buyers reach the backend at, set through `YAGI_PUBLIC_URL` when
+/// the dashboard is built (e.g. `https://farm.example.com`). Defaults to
+/// the backend this dashboard talks to, which only works on the farm's own
+/// network.
+const PUBLIC_BASE_URL: &str = match option_env!("YAGI_PUBLIC_URL") {
+    Some(url) => url,
+    None => "http://127.0.0.1:8000",
+};
+
+/// The public share link of a traceability record, as given to the buyer.
+pub fn traceability_share_url(record: &TraceabilityRecord) -> String {
+    format!(
+        "{}{}",
+        PUBLIC_BASE_URL.trim_end_matches('/'),
+        record.share_path()
+    )
+}
+
+/// Backend endpoint scoring goats for keep/cull decisions.
+const GOAT_SCORES_URL: &str = "http://127.0.0.1:8000/scoring/goats";
+
+/// Backend endpoint composing the cheapest ration from the feed in stock.
+const RATION_URL: &str = "http://127.0.0.1:8000/nutrition/ration";
+
+/// Backend endpoint for weigh-scale readings.
+const SCALE_READINGS_URL: &str = "http://127.0.0.1:8000/scale/readings";
+
+/// Backend endpoint reporting the latest temperature and humidity readings.
+const SENSOR_CONDITIONS_URL: &str = "http://127.0.0.1:8000/sensors/conditions";
+
+/// Backend endpoint for pen water use and trough cleaning.
+const WATER_URL: &str = "http://127.0.0.1:8000/water";
+
+/// Backend endpoint predicting heats from activity tags.
+const HEAT_PREDICTIONS_URL: &str = "http://127.0.0.1:8000/breeding/heat";
+
+/// Backend endpoint for the last known position of each collared goat.
+const GPS_POSITIONS_URL: &str = "http://127.0.0.1:8000/gps/positions";
+
+/// Backend endpoint for grazing geofences.
+const GEOFENCES_URL: &str = "http://127.0.0.1:8000/gps/geofences";
+
+/// Backend endpoint for grazing paddocks.
+const PADDOCKS_URL: &str = "http://127.0.0.1:8000/grazing/paddocks";
+
+/// Backend endpoint proposing a weekly pasture rotation.
+const ROTATION_URL: &str = "http://127.0.0.1:8000/grazing/rotation";
+
+/// Backend endpoint for notes on goats; a note's photo is at
+/// `{id}/photo` below it.
+const NOTES_URL: &str = "http://127.0.0.1:8000/notes";
+
+/// Where the photo attached to the note with `id` is served (see
+/// `crate::components::file_link::FileImage`).
+pub fn note_photo_url(id: i64) -> String {
+    format!("{}/{}/photo", NOTES_URL, id)
+}
+
+/// Backend endpoint for files attached to goats and tasks; voice notes are
+/// uploaded to `voice` below it.
+const ATTACHMENTS_URL: &str = "http://127.0.0.1:8000/attachments";
+
+/// Where the attachment with `id` is served (see
+/// `crate::components::file_link::FileAudio`).
+pub fn attachment_url(id: i64) -> String {
+    format!("{}/{}", ATTACHMENTS_URL, id)
+}
+
+/// Backend endpoint for workers' notifications, e.g. mentions in notes.
+const NOTIFICATIONS_URL: &str = "http://127.0.0.1:8000/notifications";
+
+/// Backend endpoint for monthly budgets per expense category.
+const BUDGETS_URL: &str = "http://127.0.0.1:8000/finance/budgets";
+
+/// Backend endpoint comparing a month's spending against budget.
+const BUDGET_VARIANCE_URL: &str = "http://127.0.0.1:8000/finance/budgets/variance";
+
+/// Backend endpoint for farm-wide settings.
+const SETTINGS_URL: &str = "http://127.0.0.1:8000/settings";
+
+/// Backend endpoint switching read-only mode, which only the owner may do.
+const READ_ONLY_URL: &str = "http://127.0.0.1:8000/settings/read-only";
+
+/// Backend endpoint setting goat prices from the pricing formula.
+const PRICING_APPLY_URL: &str = "http://127.0.0.1:8000/pricing/apply";
+
+/// Backend endpoint for the dashboard's KPI figures.
+const STATS_URL: &str = "http://127.0.0.1:8000/stats";
+
+/// Backend endpoint searching goats, tasks and transactions.
+const SEARCH_URL: &str = "http://127.0.0.1:8000/search";
+
+/// Backend endpoint for the recent activity feed.
+const ACTIVITY_URL: &str = "http://127.0.0.1:8000/activity";
+
+/// Backend endpoint for the data quality report.
+const DATA_HEALTH_URL: &str = "http://127.0.0.1:8000/data-health";
+
+/// Backend endpoint for farm tasks.
+const TASKS_URL: &str = "http://127.0.0.1:8000/tasks";
+
+/// Backend endpoint for income and expense entries.
+const TRANSACTIONS_URL: &str = "http://127.0.0.1:8000/transactions";
+
+/// Backend endpoint for stocked supplies.
+const INVENTORY_URL: &str = "http://127.0.0.1:8000/inventory";
+
+/// Backend endpoint for background jobs; large imports are queued at
+/// `import` below it.
+const JOBS_URL: &str = "http://127.0.0.1:8000/jobs";
+
+/// Backend endpoint serving the whole farm as a portable archive, and
+/// restoring one.
+pub const ARCHIVE_URL: &str = "http://127.0.0.1:8000/archive";
+
+/// Backend endpoint for saved export templates.
+const EXPORT_TEMPLATES_URL: &str = "http://127.0.0.1:8000/exports/templates";
+
+/// Backend endpoint running a saved export, by template key.
+const EXPORT_RUN_URL: &str = "http://127.0.0.1:8000/exports/run";
+
+/// Backend endpoint for the trash and the purges the retention has coming.
+const RETENTION_URL: &str = "http://127.0.0.1:8000/retention";
+
+/// Backend endpoint for restoring deleted goats.
+const TRASH_URL: &str = "http://127.0.0.1:8000/trash";
+
+/// Backend endpoint for the sessions of signed-in devices.
+const SESSIONS_URL: &str = "http://127.0.0.1:8000/sessions";
+
+/// Backend endpoint for the permissions of workers' roles.
+const PERMISSIONS_URL: &str = "http://127.0.0.1:8000/permissions";
+
+/// Backend endpoint for personal access tokens.
+const TOKENS_URL: &str = "http://127.0.0.1:8000/tokens";
+
+/// Backend endpoint for alert rules on herd metrics.
+const ALERTS_URL: &str = "http://127.0.0.1:8000/alerts";
+
+/// Backend endpoint for goats' lifecycle stages.
+const LIFECYCLE_URL: &str = "http://127.0.0.1:8000/lifecycle";
+
+/// localStorage key holding this device's session token.
+const SESSION_TOKEN_KEY: &str = "yagi.session";
+
+/// localStorage key holding the tenant key of the farm this device uses, on
+/// a multi-tenant backend.
+const TENANT_KEY_KEY: &str = "yagi.tenant";
+
+/// Backend endpoint for signing in with OAuth providers.
+const OAUTH_URL: &str = "http://127.0.0.1:8000/sessions/oauth";
+
+fn session_storage() -> Option<web_sys::Storage> {
+    web_sys::window().and_then(|w| w.local_storage().ok().flatten())
+}
+
+/// Keeps the token of a new session for later requests.
+fn keep_session_token(issued: &IssuedSession) -> Result<(), AppError> {
+    if let Some(storage) = session_storage() {
+        storage
+            .set_item(SESSION_TOKEN_KEY, &issued.token)
+            .map_err(|e| AppError::unexpected(format!("{:?}", e)))?;
+    }
+    Ok(())
+}
+
+/// The tenant key of the farm this device uses, if it picked one.
+fn stored_tenant_key() -> Option<String> {
+    session_storage().and_then(|s| s.get_item(TENANT_KEY_KEY).ok().flatten())
+}
+
+/// `request` with the tenant key of the farm this device uses, if any.
+pub fn with_tenant(request: RequestBuilder) -> RequestBuilder {
+    match stored_tenant_key() {
+        Some(key) => request.header(TENANT_KEY_HEADER, &key),
+        None => request,
+    }
+}
+
+/// gloo-net's `Request`, with this device's session token, if it has
+/// signed in, and farm, if it picked one, on every request.
+struct Request;
+
+impl Request {
+    fn with_session(request: RequestBuilder) -> RequestBuilder {
+        let request = with_tenant(request);
+        let token = session_storage().and_then(|s| s.get_item(SESSION_TOKEN_KEY).ok().flatten());
+        match token {
+            Some(token) => request.header(SESSION_HEADER, &token),
+            None => request,
+        }
+    }
+
+    fn get(url: &str) -> RequestBuilder {
+        Self::with_session(gloo_net::http::Request::get(url))
+    }
+
+    fn post(url: &str) -> RequestBuilder {
+        Self::with_session(gloo_net::http::Request::post(url))
+    }
+
+    fn put(url: &str) -> RequestBuilder {
+        Self::with_session(gloo_net::http::Request::put(url))
+    }
+
+    fn patch(url: &str) -> RequestBuilder {
+        Self::with_session(gloo_net::http::Request::patch(url))
+    }
+
+    fn delete(url: &str) -> RequestBuilder {
+        Self::with_session(gloo_net::http::Request::delete(url))
+    }
+}
+
+/// Where the file a finished job produced is downloaded from.
+pub fn job_result_url(id: i64) -> String {
+    format!("{}/{}/result", JOBS_URL, id)
+}
+
+/// Where the file saved export template `key` writes is downloaded from.
+pub fn export_run_url(key: &str) -> String {
+    format!(
+        "{}/{}",
+        EXPORT_RUN_URL,
+        String::from(js_sys::encode_uri_component(key))
+    )
+}
+
+/// Boxed future returned by `ApiClient` methods, keeping the trait object safe.
+pub type ApiFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, AppError>> + 'a>>;
+
+/// Result of a conditional goat list request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GoatsFetch {
+    /// The list changed (or no tag was sent); carries the new list and its ETag.
+    Modified {
+        goats: Vec<Goat>,
+        etag: Option<String>,
+    },
+    /// The server answered 304; the cached list is still current.
+    NotModified,
+}
+
+/// Operations the frontend performs against the backend.
+pub trait ApiClient {
+    /// GETs the full goat list, sending `etag` as `If-None-Match` if given.
+    fn fetch_goats<'a>(&'a self, etag: Option<&'a str>) -> ApiFuture<'a, GoatsFetch>;
+
+    /// Reads the goat list incrementally, handing each batch to `on_chunk`.
+    /// Returns the total number of goats received.
+    fn stream_goats<'a>(&'a self, on_chunk: &'a dyn Fn(Vec<Goat>)) -> ApiFuture<'a, usize>;
+
+    /// Creates a new goat, returning it with its server-assigned ID.
+    fn add_goat<'a>(&'a self, goat: &'a NewGoat) -> ApiFuture<'a, Goat>;
+
+    /// Fetches the tag the next goat added will get from the farm's tag
+    /// series; the backend assigns it when the goat is created.
+    fn next_tag(&self) -> ApiFuture<'_, NextTag>;
+
+    /// Creates all of `goats` at once; the backend adds all or none.
+    fn add_goats_batch<'a>(&'a self, goats: &'a [NewGoat]) -> ApiFuture<'a, BatchSummary>;
+
+    /// Uploads a CSV or XLSX file and returns its cells for column mapping.
+    fn parse_import<'a>(&'a self, file: &'a [u8]) -> ApiFuture<'a, ImportTable>;
+
+    /// Replaces a goat's fields, matched by name.
+    fn update_goat<'a>(&'a self, goat: &'a NewGoat) -> ApiFuture<'a, ()>;
+
+    /// Changes only the fields set in `update` on the goat with ID `id`,
+    /// returning the goat as stored.
+    fn patch_goat<'a>(&'a self, id: i64, update: &'a GoatUpdate) -> ApiFuture<'a, Goat>;
+
+    /// Applies `patch` to every goat it selects, returning them as stored,
+    /// or with `preview` only returns the goats it would change.
+    fn bulk_patch_goats<'a>(
+        &'a self,
+        patch: &'a BulkPatch,
+        preview: bool,
+    ) -> ApiFuture<'a, Vec<Goat>>;
+
+    /// Deletes a goat by name, returning the trash entry it moved to (see
+    /// `restore_goat`).
+    fn delete_goat<'a>(&'a self, name: &'a str) -> ApiFuture<'a, i64>;
+
+    /// Fetches the changes to the field `field` of the goat with ID `id`,
+    /// newest first.
+    fn field_history<'a>(&'a self, id: i64, field: &'a str) -> ApiFuture<'a, Vec<FieldChange>>;
+
+    /// Fetches suggested pairings, optionally only for the doe named `doe`.
+    fn breeding_recommendations<'a>(
+        &'a self,
+        doe: Option<&'a str>,
+    ) -> ApiFuture<'a, Vec<BreedingRecommendation>>;
+
+    /// Fetches the castrated and spayed goats, by name.
+    fn neuterings(&self) -> ApiFuture<'_, Vec<Neutering>>;
+
+    /// Fetches every service, newest first, with its outcome.
+    fn breeding_services(&self) -> ApiFuture<'_, Vec<BreedingService>>;
+
+    /// Records a service, natural or by AI.
+    fn add_breeding_service<'a>(&'a self, service: &'a BreedingService) -> ApiFuture<'a, ()>;
+
+    /// Fetches how often services by each method ended in a kidding.
+    fn service_success(&self) -> ApiFuture<'_, Vec<MethodSuccess>>;
+
+    /// Fetches the external animal registry, by name.
+    fn external_animals(&self) -> ApiFuture<'_, Vec<ExternalAnimal>>;
+
+    /// Adds an animal to the external registry, returning it as stored.
+    fn add_external_animal<'a>(
+        &'a self,
+        animal: &'a ExternalAnimal,
+    ) -> ApiFuture<'a, ExternalAnimal>;
+
+    /// Removes the external animal with `id`, unlinking its records.
+    fn delete_external_animal(&self, id: i64) -> ApiFuture<'_, ()>;
+
+    /// Fetches the genetic tags of every tagged goat, by name.
+    fn genetic_tags(&self) -> ApiFuture<'_, Vec<GeneticTags>>;
+
+    /// Replaces a goat's genetic tags, returning them as stored.
+    fn save_genetic_tags<'a>(&'a self, tags: &'a GeneticTags) -> ApiFuture<'a, GeneticTags>;
+
+    /// Fetches the pedigree tree of the goat named `name`, with tags.
+    fn pedigree_tree<'a>(&'a self, name: &'a str) -> ApiFuture<'a, PedigreeNode>;
+
+    /// Fetches the breed catalog: built-in breeds, then those the farm added.
+    fn breed_catalog(&self) -> ApiFuture<'_, Vec<CatalogBreed>>;
+
+    /// Fetches every goat's growth benchmark against its breed standard.
+    fn growth_benchmarks(&self) -> ApiFuture<'_, Vec<GrowthBenchmark>>;
+
+    /// Fetches the weight history of the goat named `name`.
+    fn weight_history<'a>(&'a self, name: &'a str) -> ApiFuture<'a, GrowthHistory>;
+
+    /// Records a weighing; the goat's current weight follows if it is the
+    /// most recent one.
+    fn add_weight<'a>(&'a self, record: &'a WeightRecord) -> ApiFuture<'a, ()>;
+
+    /// Records a milking of a doe.
+    fn add_milk_record<'a>(&'a self, record: &'a MilkRecord) -> ApiFuture<'a, ()>;
+
+    /// Estimates a goat's weight from a photo of type `content_type` (e.g.
+    /// `image/jpeg`) showing a reference object `reference_cm` long.
+    fn estimate_weight<'a>(
+        &'a self,
+        photo: &'a [u8],
+        content_type: &'a str,
+        reference_cm: f64,
+    ) -> ApiFuture<'a, WeightEstimate>;
+
+    /// Fetches lactation summaries, optionally only for the doe named `doe`.
+    fn lactations<'a>(&'a self, doe: Option<&'a str>) -> ApiFuture<'a, Vec<Lactation>>;
+
+    /// Fetches feed conversion ratio and cost per kg gain per goat and pen.
+    fn feed_efficiency(&self) -> ApiFuture<'_, FeedEfficiencyReport>;
+
+    /// Fetches the pens, fields and other spaces with their goats.
+    fn spaces(&self) -> ApiFuture<'_, Vec<Space>>;
+
+    /// Creates a pen, field or other space.
+    fn add_space<'a>(&'a self, space: &'a Space) -> ApiFuture<'a, ()>;
+
+    /// Fetches the occupancy of every pen and field, ordered by name.
+    fn space_occupancy(&self) -> ApiFuture<'_, Vec<SpaceOccupancy>>;
+
+    /// Fetches incident counts per pen and month between `from` and `to`
+    /// (`YYYY-MM-DD`); the backend defaults to the last twelve months.
+    fn health_heatmap<'a>(
+        &'a self,
+        from: Option<&'a str>,
+        to: Option<&'a str>,
+    ) -> ApiFuture<'a, HealthHeatMap>;
+
+    /// Fetches every vet visit, most recent first.
+    fn vet_visits(&self) -> ApiFuture<'_, Vec<VetVisit>>;
+
+    /// Requests a vet visit, booked straight away if it names a vet and a
+    /// day, returning it as stored.
+    fn request_vet_visit<'a>(&'a self, request: &'a VisitRequest) -> ApiFuture<'a, VetVisit>;
+
+    /// Fetches the vets and the days they are booked over the next 30 days.
+    fn vets(&self) -> ApiFuture<'_, Vec<VetAvailability>>;
+
+    /// Books visit `id` with a vet on a day, returning the scheduled visit.
+    fn schedule_vet_visit<'a>(
+        &'a self,
+        id: i64,
+        schedule: &'a VisitSchedule,
+    ) -> ApiFuture<'a, VetVisit>;
+
+    /// Completes visit `id` with the vet's summary, recording each finding
+    /// as a health incident.
+    fn complete_vet_visit<'a>(
+        &'a self,
+        id: i64,
+        summary: &'a VisitSummary,
+    ) -> ApiFuture<'a, VetVisit>;
+
+    /// Fetches the goat's incidents, dated vaccinations and lab results,
+    /// newest first.
+    fn health_timeline<'a>(&'a self, goat_name: &'a str) -> ApiFuture<'a, Vec<TimelineEntry>>;
+
+    /// Fetches the goat's lab results, newest sample first.
+    fn lab_results<'a>(&'a self, goat_name: &'a str) -> ApiFuture<'a, Vec<LabResult>>;
+
+    /// Records a lab result, returning it as stored with the typical
+    /// ranges of values reported without one.
+    fn add_lab_result<'a>(&'a self, result: &'a LabResult) -> ApiFuture<'a, LabResult>;
+
+    /// Uploads `report` as the PDF report of lab result `id`.
+    fn upload_lab_report<'a>(&'a self, id: i64, report: &'a [u8]) -> ApiFuture<'a, ()>;
+
+    /// Fetches the disease catalog by name.
+    fn diseases(&self) -> ApiFuture<'_, Vec<CatalogDisease>>;
+
+    /// Adds a disease to the catalog, returning it as stored.
+    fn add_disease<'a>(&'a self, disease: &'a NewDisease) -> ApiFuture<'a, CatalogDisease>;
+
+    /// Flags disease `id` notifiable, or clears the flag.
+    fn set_disease_notifiable(&self, id: i64, notifiable: bool) -> ApiFuture<'_, CatalogDisease>;
+
+    /// Fetches every goat's cases of notifiable diseases.
+    fn compliance_report(&self) -> ApiFuture<'_, ComplianceReport>;
+
+    /// Fetches the movement register, latest departure first, of one goat
+    /// and of goats leaving between `from` and `to` if given.
+    fn movements<'a>(
+        &'a self,
+        goat_name: Option<&'a str>,
+        from: Option<&'a str>,
+        to: Option<&'a str>,
+    ) -> ApiFuture<'a, Vec<AnimalMovement>>;
+
+    /// Logs a movement, returning it as stored.
+    fn add_movement<'a>(&'a self, movement: &'a AnimalMovement) -> ApiFuture<'a, AnimalMovement>;
+
+    /// Fetches the traceability records taken when the goat was sold,
+    /// newest first.
+    fn traceability<'a>(&'a self, goat_name: &'a str) -> ApiFuture<'a, Vec<TraceabilityRecord>>;
+
+    /// Scores every goat with the given metric weights, lowest score first.
+    fn goat_scores<'a>(&'a self, weights: &'a ScoreWeights) -> ApiFuture<'a, Vec<GoatScore>>;
+
+    /// Composes the cheapest daily ration per goat meeting `request` from
+    /// the feed in stock.
+    fn formulate_ration<'a>(&'a self, request: &'a RationRequest) -> ApiFuture<'a, RationPlan>;
+
+    /// Fetches scale readings newer than `since_id`, oldest first; without
+    /// it, the most recent readings.
+    fn scale_readings(&self, since_id: Option<i64>) -> ApiFuture<'_, Vec<ScaleReading>>;
+
+    /// Assigns the tag of reading `id` to the goat named `goat_name` and
+    /// returns the readings that were matched as a result.
+    fn assign_reading<'a>(
+        &'a self,
+        id: i64,
+        goat_name: &'a str,
+    ) -> ApiFuture<'a, Vec<ScaleReading>>;
+
+    /// Fetches every temperature and humidity sensor with its latest reading.
+    fn sensor_conditions(&self) -> ApiFuture<'_, Vec<SensorCondition>>;
+
+    /// Fetches the water use and trough upkeep of every metered pen.
+    fn pen_water(&self) -> ApiFuture<'_, Vec<PenWater>>;
+
+    /// Records that the troughs of pen `space_id` were cleaned.
+    fn mark_trough_cleaned(&self, space_id: i64) -> ApiFuture<'_, ()>;
+
+    /// Fetches the heat status and predicted next heat of every tagged doe.
+    fn heat_predictions(&self) -> ApiFuture<'_, Vec<HeatPrediction>>;
+
+    /// Fetches the last known position of every collared goat.
+    fn goat_positions(&self) -> ApiFuture<'_, Vec<GoatPosition>>;
+
+    /// Fetches every grazing geofence.
+    fn geofences(&self) -> ApiFuture<'_, Vec<Geofence>>;
+
+    /// Fetches every grazing paddock, ordered by name.
+    fn paddocks(&self) -> ApiFuture<'_, Vec<Paddock>>;
+
+    /// Adds a paddock, returning it with its ID.
+    fn add_paddock<'a>(&'a self, paddock: &'a Paddock) -> ApiFuture<'a, Paddock>;
+
+    /// Proposes a pasture rotation for `weeks` weeks from `start`
+    /// (`YYYY-MM-DD`); the backend defaults to today.
+    fn rotation_plan<'a>(
+        &'a self,
+        start: Option<&'a str>,
+        weeks: u32,
+    ) -> ApiFuture<'a, RotationPlan>;
+
+    /// Fetches the notes left on the goat named `goat_name`, oldest first.
+    fn goat_notes<'a>(&'a self, goat_name: &'a str) -> ApiFuture<'a, Vec<GoatNote>>;
+
+    /// Adds a note to a goat, returning it with its ID and time.
+    fn add_note<'a>(&'a self, note: &'a NoteInput) -> ApiFuture<'a, GoatNote>;
+
+    /// Attaches a photo of type `content_type` (e.g. `image/jpeg`) to the
+    /// note with `id`, replacing any earlier one.
+    fn attach_note_photo<'a>(
+        &'a self,
+        id: i64,
+        photo: &'a [u8],
+        content_type: &'a str,
+    ) -> ApiFuture<'a, GoatNote>;
+
+    /// Deletes the note with `id` and its photo.
+    fn delete_note(&self, id: i64) -> ApiFuture<'_, ()>;
+
+    /// Fetches the files attached to `target`, oldest first.
+    fn attachments<'a>(&'a self, target: &'a AttachmentTarget) -> ApiFuture<'a, Vec<Attachment>>;
+
+    /// Uploads a voice note by `author` lasting `duration_secs`, recorded
+    /// as `content_type` (e.g. `audio/webm`), and attaches it to `target`.
+    fn add_voice_note<'a>(
+        &'a self,
+        target: &'a AttachmentTarget,
+        author: &'a str,
+        duration_secs: f64,
+        recording: &'a [u8],
+        content_type: &'a str,
+    ) -> ApiFuture<'a, Attachment>;
+
+    /// Deletes the attachment with `id`.
+    fn delete_attachment(&self, id: i64) -> ApiFuture<'_, ()>;
+
+    /// Fetches the unread notifications of the worker named `worker`,
+    /// newest first.
+    fn unread_notifications<'a>(&'a self, worker: &'a str) -> ApiFuture<'a, Vec<Notification>>;
+
+    /// Marks the notification with `id` read.
+    fn mark_notification_read(&self, id: i64) -> ApiFuture<'_, ()>;
+
+    /// Fetches spending against budget for `month` (`YYYY-MM`); the backend
+    /// defaults to the current month.
+    fn budget_report<'a>(&'a self, month: Option<&'a str>) -> ApiFuture<'a, BudgetReport>;
+
+    /// Sets a category's monthly budget, replacing any earlier one.
+    fn set_budget<'a>(&'a self, budget: &'a Budget) -> ApiFuture<'a, ()>;
+
+    /// Fetches the farm-wide settings.
+    fn farm_settings(&self) -> ApiFuture<'_, FarmSettings>;
+
+    /// Replaces the farm-wide settings, returning them as stored. Leaves
+    /// read-only mode as it is.
+    fn update_settings<'a>(&'a self, settings: &'a FarmSettings) -> ApiFuture<'a, FarmSettings>;
+
+    /// Switches read-only mode on or off, returning the settings as stored.
+    fn set_read_only(&self, mode: ReadOnlyMode) -> ApiFuture<'_, FarmSettings>;
+
+    /// Sets every goat's current price from the stored pricing formula.
+    fn apply_pricing(&self) -> ApiFuture<'_, Vec<GoatValuation>>;
+
+    /// Fetches the KPI figures, with their trends, as of today.
+    fn dashboard_stats(&self) -> ApiFuture<'_, DashboardStats>;
+
+    /// Searches goats, tasks and transactions for `query`.
+    fn search<'a>(&'a self, query: &'a str) -> ApiFuture<'a, Vec<SearchResult>>;
+
+    /// Fetches the most recent goats added, sold and treated, newest first.
+    fn recent_activity(&self) -> ApiFuture<'_, Vec<ActivityEvent>>;
+
+    /// Scans the herd records for missing or suspicious values.
+    fn data_health(&self) -> ApiFuture<'_, DataHealthReport>;
+
+    /// Fetches every task, earliest due first.
+    fn tasks(&self) -> ApiFuture<'_, Vec<Task>>;
+
+    /// Fetches every income and expense entry, newest first.
+    fn transactions(&self) -> ApiFuture<'_, Vec<Transaction>>;
+
+    /// Fetches every stocked supply item, ordered by name.
+    fn inventory_items(&self) -> ApiFuture<'_, Vec<InventoryItem>>;
+
+    /// Fetches the stock that has expired or expires within 30 days,
+    /// expired items first.
+    fn expiring_stock(&self) -> ApiFuture<'_, Vec<ExpiringStock>>;
+
+    /// Disposes of all remaining stock of item `id`, logging it as consumed
+    /// and writing its cost off as an expense.
+    fn dispose_item(&self, id: i64) -> ApiFuture<'_, Disposal>;
+
+    /// Fetches the most recent background jobs, newest first.
+    fn jobs(&self) -> ApiFuture<'_, Vec<Job>>;
+
+    /// Fetches one background job's status and progress.
+    fn job(&self, id: i64) -> ApiFuture<'_, Job>;
+
+    /// Queues `goats` to be added in the background, returning the job.
+    fn start_import_job<'a>(&'a self, goats: &'a [NewGoat]) -> ApiFuture<'a, Job>;
+
+    /// Queues a cleanup of the trash and event log entries past the farm's
+    /// retention, returning the job.
+    fn start_cleanup_job(&self) -> ApiFuture<'_, Job>;
+
+    /// Fetches the trash and what the next cleanups will purge.
+    fn retention(&self) -> ApiFuture<'_, UpcomingPurges>;
+
+    /// Restores the goat in trash entry `id`, returning it as added back.
+    fn restore_goat(&self, id: i64) -> ApiFuture<'_, Goat>;
+
+    /// Fetches the tenant key of the farm this device uses, if it picked
+    /// one.
+    fn farm_key(&self) -> ApiFuture<'_, Option<String>>;
+
+    /// Makes later requests go to the farm with tenant key `key` on a
+    /// multi-tenant backend, or to the backend's only farm for `None`.
+    fn select_farm<'a>(&'a self, key: Option<&'a str>) -> ApiFuture<'a, ()>;
+
+    /// Signs this device in with the owner's password or a worker's PIN
+    /// (see `NewSession`) and keeps the session token for later requests.
+    fn sign_in<'a>(&'a self, new_session: &'a NewSession) -> ApiFuture<'a, IssuedSession>;
+
+    /// Fetches the signed-in devices, most recently active first.
+    fn sessions(&self) -> ApiFuture<'_, Vec<Session>>;
+
+    /// Signs out the device with session `id`.
+    fn revoke_session(&self, id: i64) -> ApiFuture<'_, ()>;
+
+    /// Signs out every device but this one, returning how many were.
+    fn revoke_other_sessions(&self) -> ApiFuture<'_, usize>;
+
+    /// Fetches the names of the OAuth providers workers can sign in with.
+    fn oauth_providers(&self) -> ApiFuture<'_, Vec<String>>;
+
+    /// Starts signing this device in as `device` with `provider`, which
+    /// sends the browser back to `redirect_uri`. Returns the provider's
+    /// sign-in page to go to.
+    fn start_oauth<'a>(
+        &'a self,
+        provider: &'a str,
+        redirect_uri: &'a str,
+        device: &'a str,
+    ) -> ApiFuture<'a, OAuthRedirect>;
+
+    /// Finishes an OAuth sign-in with the `code` and `state` the provider
+    /// sent back, and keeps the session token for later requests.
+    fn finish_oauth<'a>(&'a self, code: &'a str, state: &'a str) -> ApiFuture<'a, IssuedSession>;
+
+    /// Fetches the permissions of every role.
+    fn role_permissions(&self) -> ApiFuture<'_, Vec<RolePermissions>>;
+
+    /// Sets what the workers of a role may do; only the owner may.
+    fn update_role_permissions<'a>(
+        &'a self,
+        permissions: &'a RolePermissions,
+    ) -> ApiFuture<'a, RolePermissions>;
+
+    /// Fetches what this device's session may do.
+    fn my_permissions(&self) -> ApiFuture<'_, MyPermissions>;
+
+    /// Fetches the personal access tokens, newest first.
+    fn access_tokens(&self) -> ApiFuture<'_, Vec<AccessToken>>;
+
+    /// Makes a personal access token; the token is returned only this once.
+    fn create_access_token<'a>(
+        &'a self,
+        new_token: &'a NewAccessToken,
+    ) -> ApiFuture<'a, IssuedAccessToken>;
+
+    /// Revokes the personal access token `id`.
+    fn revoke_access_token(&self, id: i64) -> ApiFuture<'_, ()>;
+
+    /// Fetches the alert rules, oldest first.
+    fn alert_rules(&self) -> ApiFuture<'_, Vec<AlertRule>>;
+
+    /// Creates an alert rule, returning it as stored.
+    fn add_alert_rule<'a>(&'a self, rule: &'a AlertRule) -> ApiFuture<'a, AlertRule>;
+
+    /// Deletes the alert rule `id`.
+    fn delete_alert_rule(&self, id: i64) -> ApiFuture<'_, ()>;
+
+    /// Fetches the herd laid out by lifecycle stage.
+    fn lifecycle_pipeline(&self) -> ApiFuture<'_, LifecyclePipeline>;
+
+    /// Moves a goat to another lifecycle stage, returning the recorded move.
+    fn change_lifecycle_stage<'a>(
+        &'a self,
+        change: &'a StageChange,
+    ) -> ApiFuture<'a, StageTransition>;
+
+    /// Downloads the file served at `url`, e.g. a note photo or a report,
+    /// with this device's session (see `crate::components::file_link`).
+    fn download<'a>(&'a self, url: &'a str) -> ApiFuture<'a, Vec<u8>>;
+
+    /// Downloads the farm archive encrypted with `passphrase`.
+    fn export_farm_archive<'a>(&'a self, passphrase: &'a str) -> ApiFuture<'a, Vec<u8>>;
+
+    /// Restores a farm archive into this (empty) farm, unlocking it with
+    /// `passphrase` if it is encrypted.
+    fn import_farm_archive<'a>(
+        &'a self,
+        archive: &'a [u8],
+        passphrase: Option<&'a str>,
+    ) -> ApiFuture<'a, ArchiveSummary>;
+
+    /// Fetches the saved export templates, by key.
+    fn export_templates(&self) -> ApiFuture<'_, Vec<ExportTemplate>>;
+
+    /// Saves an export template, returning it as stored.
+    fn add_export_template<'a>(
+        &'a self,
+        template: &'a ExportTemplate,
+    ) -> ApiFuture<'a, ExportTemplate>;
+
+    /// Removes the export template with `key`.
+    fn delete_export_template<'a>(&'a self, key: &'a str) -> ApiFuture<'a, ()>;
+}
+
+/// Shared handle to the active `ApiClient`, cheap to clone into callbacks.
+#[derive(Clone)]
+pub struct Api(pub Rc<dyn ApiClient>);
+
+impl Api {
+    /// Wraps a client in a handle.
+    pub fn new(client: impl ApiClient + 'static) -> Self {
+        Api(Rc::new(client))
+    }
+}
+
+impl Default for Api {
+    fn default() -> Self {
+        Api::new(HttpApiClient)
+    }
+}
+
+impl PartialEq for Api {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl std::ops::Deref for Api {
+    type Target = dyn ApiClient;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+/// Props for ApiProvider:
+/// - `api`: client made available to all descendants
+/// - `children`: the subtree using it
+#[derive(Properties, PartialEq)]
+pub struct ApiProviderProps {
+    pub api: Api,
+    #[prop_or_default]
+    pub children: Html,
+}
+
+/// Overrides the `ApiClient` used by descendants, e.g. with a mock in tests.
+#[function_component(ApiProvider)]
+pub fn api_provider(props: &ApiProviderProps) -> Html {
+    html! {
+        <ContextProvider<Api> context={props.api.clone()}>
+            { props.children.clone() }
+        </ContextProvider<Api>>
+    }
+}
+
+/// Returns the nearest provided `Api`, falling back to the HTTP client.
+#[hook]
+pub fn use_api() -> Api {
+    use_context::<Api>().unwrap_or_default()
+}
+
+/// `ApiClient` talking to the backend over HTTP with gloo-net.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HttpApiClient;
+
+impl ApiClient for HttpApiClient {
+    fn fetch_goats<'a>(&'a self, etag: Option<&'a str>) -> ApiFuture<'a, GoatsFetch> {
+        Box::pin(async move {
+            info!("Sending fetch_goats request to {}", GOATS_URL);
+            let mut request = Request::get(GOATS_URL);
+            if let Some(etag) = etag {
+                request = request.header("If-None-Match", etag);
+            }
+            let resp = request.send().await?;
+            if resp.status() == 304 {
+                return Ok(GoatsFetch::NotModified);
+            }
+            let resp = check_response(resp).await?;
+            let etag = resp.headers().get("etag");
+            let goats = resp.json::<Vec<Goat>>().await?;
+            Ok(GoatsFetch::Modified { goats, etag })
+        })
+    }
+
+    fn stream_goats<'a>(&'a self, on_chunk: &'a dyn Fn(Vec<Goat>)) -> ApiFuture<'a, usize> {
+        Box::pin(async move {
+            info!("Streaming goats from {}", GOATS_STREAM_URL);
+            let resp = check_response(Request::get(GOATS_STREAM_URL).send().await?).await?;
+            let body = resp
+                .body()
+                .ok_or_else(|| AppError::unexpected("Goat stream has no body"))?;
+            let reader: ReadableStreamDefaultReader = body.get_reader().unchecked_into();
+
+            let mut buffer = Vec::new();
+            let mut total = 0;
+            loop {
+                let chunk = JsFuture::from(reader.read())
+                    .await
+                    .map_err(|e| AppError::NetworkError(format!("{:?}", e)))?;
+                let done = js_sys::Reflect::get(&chunk, &"done".into())
+                    .ok()
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true);
+                if !done {
+                    let value = js_sys::Reflect::get(&chunk, &"value".into())
+                        .map_err(|e| AppError::NetworkError(format!("{:?}", e)))?;
+                    buffer.extend(js_sys::Uint8Array::new(&value).to_vec());
+                } else if !buffer.is_empty() {
+                    // Terminate a final line sent without a trailing newline
+                    buffer.push(b'\n');
+                }
+
+                let goats = drain_ndjson_lines(&mut buffer)?;
+                if !goats.is_empty() {
+                    trace!("Received chunk of {} goats", goats.len());
+                    total += goats.len();
+                    on_chunk(goats);
+                }
+                if done {
+                    return Ok(total);
+                }
+            }
+        })
+    }
+
+    fn add_goat<'a>(&'a self, goat: &'a NewGoat) -> ApiFuture<'a, Goat> {
+        Box::pin(async move {
+            let resp = check_response(Request::post(GOATS_URL).json(goat)?.send().await?).await?;
+            Ok(resp.json::<Goat>().await?)
+        })
+    }
+
+    fn next_tag(&self) -> ApiFuture<'_, NextTag> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(NEXT_TAG_URL).send().await?).await?;
+            Ok(resp.json::<NextTag>().await?)
+        })
+    }
+
+    fn add_goats_batch<'a>(&'a self, goats: &'a [NewGoat]) -> ApiFuture<'a, BatchSummary> {
+        Box::pin(async move {
+            info!("Adding a batch of {} goats", goats.len());
+            let resp =
+                check_response(Request::post(GOATS_BATCH_URL).json(goats)?.send().await?).await?;
+            Ok(resp.json::<BatchSummary>().await?)
+        })
+    }
+
+    fn parse_import<'a>(&'a self, file: &'a [u8]) -> ApiFuture<'a, ImportTable> {
+        Box::pin(async move {
+            info!("Uploading {} bytes for import", file.len());
+            let body = js_sys::Uint8Array::from(file);
+            let resp =
+                check_response(Request::post(GOATS_IMPORT_URL).body(body)?.send().await?).await?;
+            Ok(resp.json::<ImportTable>().await?)
+        })
+    }
+
+    fn update_goat<'a>(&'a self, goat: &'a NewGoat) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            check_response(Request::put(GOATS_URL).json(goat)?.send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn patch_goat<'a>(&'a self, id: i64, update: &'a GoatUpdate) -> ApiFuture<'a, Goat> {
+        Box::pin(async move {
+            info!("Patching goat {}", id);
+            let url = format!("{}/{}", GOATS_URL, id);
+            let resp = check_response(Request::patch(&url).json(update)?.send().await?).await?;
+            Ok(resp.json::<Goat>().await?)
+        })
+    }
+
+    fn bulk_patch_goats<'a>(
+        &'a self,
+        patch: &'a BulkPatch,
+        preview: bool,
+    ) -> ApiFuture<'a, Vec<Goat>> {
+        Box::pin(async move {
+            info!("Bulk patching goats (preview: {})", preview);
+            let url = format!("{}?preview={}", GOATS_URL, preview);
+            let resp = check_response(Request::patch(&url).json(patch)?.send().await?).await?;
+            Ok(resp.json::<Vec<Goat>>().await?)
+        })
+    }
+
+    fn field_history<'a>(&'a self, id: i64, field: &'a str) -> ApiFuture<'a, Vec<FieldChange>> {
+        Box::pin(async move {
+            let url = format!("{}/{}/history?field={}", GOATS_URL, id, field);
+            let resp = check_response(Request::get(&url).send().await?).await?;
+            Ok(resp.json::<Vec<FieldChange>>().await?)
+        })
+    }
+
+    fn delete_goat<'a>(&'a self, name: &'a str) -> ApiFuture<'a, i64> {
+        Box::pin(async move {
+            let body = serde_json::json!({ "name": name });
+            let resp =
+                check_response(Request::delete(GOATS_URL).json(&body)?.send().await?).await?;
+            Ok(resp.json::<i64>().await?)
+        })
+    }
+
+    fn breeding_recommendations<'a>(
+        &'a self,
+        doe: Option<&'a str>,
+    ) -> ApiFuture<'a, Vec<BreedingRecommendation>> {
+        Box::pin(async move {
+            info!("Fetching breeding recommendations for {:?}", doe);
+            let mut request = Request::get(BREEDING_RECOMMENDATIONS_URL);
+            if let Some(doe) = doe {
+                request = request.query([("doe", doe)]);
+            }
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<Vec<BreedingRecommendation>>().await?)
+        })
+    }
+
+    fn neuterings(&self) -> ApiFuture<'_, Vec<Neutering>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(NEUTERINGS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<Neutering>>().await?)
+        })
+    }
+
+    fn breeding_services(&self) -> ApiFuture<'_, Vec<BreedingService>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(SERVICES_URL).send().await?).await?;
+            Ok(resp.json::<Vec<BreedingService>>().await?)
+        })
+    }
+
+    fn add_breeding_service<'a>(&'a self, service: &'a BreedingService) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            info!(
+                "Recording {:?} service of {}",
+                service.method, service.doe_name
+            );
+            check_response(Request::post(SERVICES_URL).json(service)?.send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn service_success(&self) -> ApiFuture<'_, Vec<MethodSuccess>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(SERVICE_SUCCESS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<MethodSuccess>>().await?)
+        })
+    }
+
+    fn external_animals(&self) -> ApiFuture<'_, Vec<ExternalAnimal>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(EXTERNAL_ANIMALS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<ExternalAnimal>>().await?)
+        })
+    }
+
+    fn add_external_animal<'a>(
+        &'a self,
+        animal: &'a ExternalAnimal,
+    ) -> ApiFuture<'a, ExternalAnimal> {
+        Box::pin(async move {
+            info!("Adding {} to the external registry", animal.name);
+            let resp = check_response(
+                Request::post(EXTERNAL_ANIMALS_URL)
+                    .json(animal)?
+                    .send()
+                    .await?,
+            )
+            .await?;
+            Ok(resp.json::<ExternalAnimal>().await?)
+        })
+    }
+
+    fn delete_external_animal(&self, id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            info!("Deleting external animal {}", id);
+            let url = format!("{}/{}", EXTERNAL_ANIMALS_URL, id);
+            check_response(Request::delete(&url).send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn genetic_tags(&self) -> ApiFuture<'_, Vec<GeneticTags>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(GENETIC_TAGS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<GeneticTags>>().await?)
+        })
+    }
+
+    fn save_genetic_tags<'a>(&'a self, tags: &'a GeneticTags) -> ApiFuture<'a, GeneticTags> {
+        Box::pin(async move {
+            info!(
+                "Saving {} genetic tags for {}",
+                tags.tags.len(),
+                tags.goat_name
+            );
+            let resp =
+                check_response(Request::put(GENETIC_TAGS_URL).json(tags)?.send().await?).await?;
+            Ok(resp.json::<GeneticTags>().await?)
+        })
+    }
+
+    fn pedigree_tree<'a>(&'a self, name: &'a str) -> ApiFuture<'a, PedigreeNode> {
+        Box::pin(async move {
+            info!("Fetching pedigree tree for {}", name);
+            let url = format!("{}/{}", PEDIGREE_URL, name);
+            let resp = check_response(Request::get(&url).send().await?).await?;
+            Ok(resp.json::<PedigreeNode>().await?)
+        })
+    }
+
+    fn breed_catalog(&self) -> ApiFuture<'_, Vec<CatalogBreed>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(BREEDS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<CatalogBreed>>().await?)
+        })
+    }
+
+    fn growth_benchmarks(&self) -> ApiFuture<'_, Vec<GrowthBenchmark>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(GROWTH_BENCHMARKS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<GrowthBenchmark>>().await?)
+        })
+    }
+
+    fn weight_history<'a>(&'a self, name: &'a str) -> ApiFuture<'a, GrowthHistory> {
+        Box::pin(async move {
+            let url = format!(
+                "{}/{}",
+                WEIGHT_HISTORY_URL,
+                String::from(js_sys::encode_uri_component(name))
+            );
+            let resp = check_response(Request::get(&url).send().await?).await?;
+            Ok(resp.json::<GrowthHistory>().await?)
+        })
+    }
+
+    fn add_weight<'a>(&'a self, record: &'a WeightRecord) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            info!("Recording weighing of {}", record.goat_name);
+            let request = Request::post(WEIGHT_HISTORY_URL).json(record)?;
+            check_response(request.send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn add_milk_record<'a>(&'a self, record: &'a MilkRecord) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            info!("Recording milking of {}", record.doe_name);
+            let request = Request::post(MILK_RECORDS_URL).json(record)?;
+            check_response(request.send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn estimate_weight<'a>(
+        &'a self,
+        photo: &'a [u8],
+        content_type: &'a str,
+        reference_cm: f64,
+    ) -> ApiFuture<'a, WeightEstimate> {
+        Box::pin(async move {
+            info!("Sending a {} byte photo for weight estimation", photo.len());
+            let request = Request::post(WEIGHT_ESTIMATE_URL)
+                .query([("reference_cm", reference_cm.to_string())])
+                .header("Content-Type", content_type)
+                .body(js_sys::Uint8Array::from(photo))?;
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<WeightEstimate>().await?)
+        })
+    }
+
+    fn lactations<'a>(&'a self, doe: Option<&'a str>) -> ApiFuture<'a, Vec<Lactation>> {
+        Box::pin(async move {
+            let mut request = Request::get(LACTATIONS_URL);
+            if let Some(doe) = doe {
+                request = request.query([("doe_name", doe)]);
+            }
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<Vec<Lactation>>().await?)
+        })
+    }
+
+    fn feed_efficiency(&self) -> ApiFuture<'_, FeedEfficiencyReport> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(FEED_EFFICIENCY_URL).send().await?).await?;
+            Ok(resp.json::<FeedEfficiencyReport>().await?)
+        })
+    }
+
+    fn spaces(&self) -> ApiFuture<'_, Vec<Space>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(SPACES_URL).send().await?).await?;
+            Ok(resp.json::<Vec<Space>>().await?)
+        })
+    }
+
+    fn add_space<'a>(&'a self, space: &'a Space) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            info!("Adding space {}", space.name);
+            check_response(Request::post(SPACES_URL).json(space)?.send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn space_occupancy(&self) -> ApiFuture<'_, Vec<SpaceOccupancy>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(SPACE_OCCUPANCY_URL).send().await?).await?;
+            Ok(resp.json::<Vec<SpaceOccupancy>>().await?)
+        })
+    }
+
+    fn health_heatmap<'a>(
+        &'a self,
+        from: Option<&'a str>,
+        to: Option<&'a str>,
+    ) -> ApiFuture<'a, HealthHeatMap> {
+        Box::pin(async move {
+            let mut request = Request::get(HEALTH_HEATMAP_URL);
+            if let Some(from) = from {
+                request = request.query([("from", from)]);
+            }
+            if let Some(to) = to {
+                request = request.query([("to", to)]);
+            }
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<HealthHeatMap>().await?)
+        })
+    }
+
+    fn vet_visits(&self) -> ApiFuture<'_, Vec<VetVisit>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(VET_VISITS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<VetVisit>>().await?)
+        })
+    }
+
+    fn request_vet_visit<'a>(&'a self, request: &'a VisitRequest) -> ApiFuture<'a, VetVisit> {
+        Box::pin(async move {
+            info!("Requesting a vet visit: {}", request.reason);
+            let resp =
+                check_response(Request::post(VET_VISITS_URL).json(request)?.send().await?).await?;
+            Ok(resp.json::<VetVisit>().await?)
+        })
+    }
+
+    fn vets(&self) -> ApiFuture<'_, Vec<VetAvailability>> {
+        Box::pin(async move {
+            let url = format!("{}/vets", VET_VISITS_URL);
+            let resp = check_response(Request::get(&url).send().await?).await?;
+            Ok(resp.json::<Vec<VetAvailability>>().await?)
+        })
+    }
+
+    fn schedule_vet_visit<'a>(
+        &'a self,
+        id: i64,
+        schedule: &'a VisitSchedule,
+    ) -> ApiFuture<'a, VetVisit> {
+        Box::pin(async move {
+            info!("Scheduling vet visit {} on {}", id, schedule.scheduled_for);
+            let url = format!("{}/{}/schedule", VET_VISITS_URL, id);
+            let resp = check_response(Request::put(&url).json(schedule)?.send().await?).await?;
+            Ok(resp.json::<VetVisit>().await?)
+        })
+    }
+
+    fn complete_vet_visit<'a>(
+        &'a self,
+        id: i64,
+        summary: &'a VisitSummary,
+    ) -> ApiFuture<'a, VetVisit> {
+        Box::pin(async move {
+            info!("Completing vet visit {}", id);
+            let url = format!("{}/{}/complete", VET_VISITS_URL, id);
+            let resp = check_response(Request::put(&url).json(summary)?.send().await?).await?;
+            Ok(resp.json::<VetVisit>().await?)
+        })
+    }
+
+    fn health_timeline<'a>(&'a self, goat_name: &'a str) -> ApiFuture<'a, Vec<TimelineEntry>> {
+        Box::pin(async move {
+            let request = Request::get(HEALTH_TIMELINE_URL).query([("goat_name", goat_name)]);
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<Vec<TimelineEntry>>().await?)
+        })
+    }
+
+    fn lab_results<'a>(&'a self, goat_name: &'a str) -> ApiFuture<'a, Vec<LabResult>> {
+        Box::pin(async move {
+            let request = Request::get(LAB_RESULTS_URL).query([("goat_name", goat_name)]);
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<Vec<LabResult>>().await?)
+        })
+    }
+
+    fn add_lab_result<'a>(&'a self, result: &'a LabResult) -> ApiFuture<'a, LabResult> {
+        Box::pin(async move {
+            info!(
+                "Recording a {} lab result for {}",
+                result.test_type, result.goat_name
+            );
+            let resp =
+                check_response(Request::post(LAB_RESULTS_URL).json(result)?.send().await?).await?;
+            Ok(resp.json::<LabResult>().await?)
+        })
+    }
+
+    fn upload_lab_report<'a>(&'a self, id: i64, report: &'a [u8]) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            info!(
+                "Uploading a {} byte report for lab result {}",
+                report.len(),
+                id
+            );
+            let request = Request::put(&lab_report_url(id))
+                .header("Content-Type", "application/pdf")
+                .body(js_sys::Uint8Array::from(report))?;
+            check_response(request.send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn diseases(&self) -> ApiFuture<'_, Vec<CatalogDisease>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(DISEASES_URL).send().await?).await?;
+            Ok(resp.json::<Vec<CatalogDisease>>().await?)
+        })
+    }
+
+    fn add_disease<'a>(&'a self, disease: &'a NewDisease) -> ApiFuture<'a, CatalogDisease> {
+        Box::pin(async move {
+            info!("Cataloguing disease {}", disease.name);
+            let resp =
+                check_response(Request::post(DISEASES_URL).json(disease)?.send().await?).await?;
+            Ok(resp.json::<CatalogDisease>().await?)
+        })
+    }
+
+    fn set_disease_notifiable(&self, id: i64, notifiable: bool) -> ApiFuture<'_, CatalogDisease> {
+        Box::pin(async move {
+            info!("Setting disease {} notifiable: {}", id, notifiable);
+            let url = format!("{}/{}/notifiable", DISEASES_URL, id);
+            let flag = NotifiableFlag { notifiable };
+            let resp = check_response(Request::put(&url).json(&flag)?.send().await?).await?;
+            Ok(resp.json::<CatalogDisease>().await?)
+        })
+    }
+
+    fn compliance_report(&self) -> ApiFuture<'_, ComplianceReport> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(COMPLIANCE_URL).send().await?).await?;
+            Ok(resp.json::<ComplianceReport>().await?)
+        })
+    }
+
+    fn movements<'a>(
+        &'a self,
+        goat_name: Option<&'a str>,
+        from: Option<&'a str>,
+        to: Option<&'a str>,
+    ) -> ApiFuture<'a, Vec<AnimalMovement>> {
+        Box::pin(async move {
+            let mut request = Request::get(MOVEMENTS_URL);
+            for (key, value) in [("goat_name", goat_name), ("from", from), ("to", to)] {
+                if let Some(value) = value {
+                    request = request.query([(key, value)]);
+                }
+            }
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<Vec<AnimalMovement>>().await?)
+        })
+    }
+
+    fn add_movement<'a>(&'a self, movement: &'a AnimalMovement) -> ApiFuture<'a, AnimalMovement> {
+        Box::pin(async move {
+            let resp =
+                check_response(Request::post(MOVEMENTS_URL).json(movement)?.send().await?).await?;
+            Ok(resp.json::<AnimalMovement>().await?)
+        })
+    }
+
+    fn traceability<'a>(&'a self, goat_name: &'a str) -> ApiFuture<'a, Vec<TraceabilityRecord>> {
+        Box::pin(async move {
+            let request = Request::get(TRACEABILITY_URL).query([("goat_name", goat_name)]);
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<Vec<TraceabilityRecord>>().await?)
+        })
+    }
+
+    fn goat_scores<'a>(&'a self, weights: &'a ScoreWeights) -> ApiFuture<'a, Vec<GoatScore>> {
+        Box::pin(async move {
+            let request = Request::get(GOAT_SCORES_URL).query([
+                ("milk", weights.milk.to_string()),
+                ("growth", weights.growth.to_string()),
+                ("fertility", weights.fertility.to_string()),
+                ("health", weights.health.to_string()),
+                ("profit", weights.profit.to_string()),
+            ]);
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<Vec<GoatScore>>().await?)
+        })
+    }
+
+    fn formulate_ration<'a>(&'a self, request: &'a RationRequest) -> ApiFuture<'a, RationPlan> {
+        Box::pin(async move {
+            info!(
+                "Formulating a ration for {} goats over {} days",
+                request.goats, request.days
+            );
+            let request = Request::get(RATION_URL).query([
+                ("dry_matter_kg", request.dry_matter_kg.to_string()),
+                ("crude_protein_g", request.crude_protein_g.to_string()),
+                ("energy_mj", request.energy_mj.to_string()),
+                ("goats", request.goats.to_string()),
+                ("days", request.days.to_string()),
+            ]);
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<RationPlan>().await?)
+        })
+    }
+
+    fn scale_readings(&self, since_id: Option<i64>) -> ApiFuture<'_, Vec<ScaleReading>> {
+        Box::pin(async move {
+            trace!("Polling scale readings since {:?}", since_id);
+            let mut request = Request::get(SCALE_READINGS_URL);
+            if let Some(since_id) = since_id {
+                request = request.query([("since_id", since_id.to_string())]);
+            }
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<Vec<ScaleReading>>().await?)
+        })
+    }
+
+    fn assign_reading<'a>(
+        &'a self,
+        id: i64,
+        goat_name: &'a str,
+    ) -> ApiFuture<'a, Vec<ScaleReading>> {
+        Box::pin(async move {
+            info!("Assigning reading {} to {}", id, goat_name);
+            let url = format!("{}/{}/goat", SCALE_READINGS_URL, id);
+            let body = TagAssignment {
+                goat_name: goat_name.to_string(),
+            };
+            let resp = check_response(Request::put(&url).json(&body)?.send().await?).await?;
+            Ok(resp.json::<Vec<ScaleReading>>().await?)
+        })
+    }
+
+    fn sensor_conditions(&self) -> ApiFuture<'_, Vec<SensorCondition>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(SENSOR_CONDITIONS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<SensorCondition>>().await?)
+        })
+    }
+
+    fn pen_water(&self) -> ApiFuture<'_, Vec<PenWater>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(WATER_URL).send().await?).await?;
+            Ok(resp.json::<Vec<PenWater>>().await?)
+        })
+    }
+
+    fn mark_trough_cleaned(&self, space_id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            info!("Marking the troughs of space {} cleaned", space_id);
+            let url = format!("{}/{}/cleaned", WATER_URL, space_id);
+            check_response(Request::put(&url).send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn heat_predictions(&self) -> ApiFuture<'_, Vec<HeatPrediction>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(HEAT_PREDICTIONS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<HeatPrediction>>().await?)
+        })
+    }
+
+    fn goat_positions(&self) -> ApiFuture<'_, Vec<GoatPosition>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(GPS_POSITIONS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<GoatPosition>>().await?)
+        })
+    }
+
+    fn geofences(&self) -> ApiFuture<'_, Vec<Geofence>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(GEOFENCES_URL).send().await?).await?;
+            Ok(resp.json::<Vec<Geofence>>().await?)
+        })
+    }
+
+    fn paddocks(&self) -> ApiFuture<'_, Vec<Paddock>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(PADDOCKS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<Paddock>>().await?)
+        })
+    }
+
+    fn add_paddock<'a>(&'a self, paddock: &'a Paddock) -> ApiFuture<'a, Paddock> {
+        Box::pin(async move {
+            info!("Adding paddock {}", paddock.name);
+            let resp =
+                check_response(Request::post(PADDOCKS_URL).json(paddock)?.send().await?).await?;
+            Ok(resp.json::<Paddock>().await?)
+        })
+    }
+
+    fn rotation_plan<'a>(
+        &'a self,
+        start: Option<&'a str>,
+        weeks: u32,
+    ) -> ApiFuture<'a, RotationPlan> {
+        Box::pin(async move {
+            let mut request = Request::get(ROTATION_URL).query([("weeks", weeks.to_string())]);
+            if let Some(start) = start {
+                request = request.query([("start", start)]);
+            }
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<RotationPlan>().await?)
+        })
+    }
+
+    fn goat_notes<'a>(&'a self, goat_name: &'a str) -> ApiFuture<'a, Vec<GoatNote>> {
+        Box::pin(async move {
+            let request = Request::get(NOTES_URL).query([("goat_name", goat_name)]);
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<Vec<GoatNote>>().await?)
+        })
+    }
+
+    fn add_note<'a>(&'a self, note: &'a NoteInput) -> ApiFuture<'a, GoatNote> {
+        Box::pin(async move {
+            info!("Adding a note on {} by {}", note.goat_name, note.author);
+            let resp = check_response(Request::post(NOTES_URL).json(note)?.send().await?).await?;
+            Ok(resp.json::<GoatNote>().await?)
+        })
+    }
+
+    fn attach_note_photo<'a>(
+        &'a self,
+        id: i64,
+        photo: &'a [u8],
+        content_type: &'a str,
+    ) -> ApiFuture<'a, GoatNote> {
+        Box::pin(async move {
+            info!("Attaching a {} byte photo to note {}", photo.len(), id);
+            let request = Request::put(&note_photo_url(id))
+                .header("Content-Type", content_type)
+                .body(js_sys::Uint8Array::from(photo))?;
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<GoatNote>().await?)
+        })
+    }
+
+    fn delete_note(&self, id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            info!("Deleting note {}", id);
+            let url = format!("{}/{}", NOTES_URL, id);
+            check_response(Request::delete(&url).send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn attachments<'a>(&'a self, target: &'a AttachmentTarget) -> ApiFuture<'a, Vec<Attachment>> {
+        Box::pin(async move {
+            let request = Request::get(ATTACHMENTS_URL).query([target.query()]);
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<Vec<Attachment>>().await?)
+        })
+    }
+
+    fn add_voice_note<'a>(
+        &'a self,
+        target: &'a AttachmentTarget,
+        author: &'a str,
+        duration_secs: f64,
+        recording: &'a [u8],
+        content_type: &'a str,
+    ) -> ApiFuture<'a, Attachment> {
+        Box::pin(async move {
+            info!(
+                "Uploading a {:.1} s voice note by {} to {:?}",
+                duration_secs, author, target
+            );
+            let (key, value) = target.query();
+            let duration = duration_secs.to_string();
+            let request = Request::post(&format!("{}/voice", ATTACHMENTS_URL))
+                .query([
+                    (key, value.as_str()),
+                    ("author", author),
+                    ("duration_secs", duration.as_str()),
+                ])
+                .header("Content-Type", content_type)
+                .body(js_sys::Uint8Array::from(recording))?;
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<Attachment>().await?)
+        })
+    }
+
+    fn delete_attachment(&self, id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            info!("Deleting attachment {}", id);
+            check_response(Request::delete(&attachment_url(id)).send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn unread_notifications<'a>(&'a self, worker: &'a str) -> ApiFuture<'a, Vec<Notification>> {
+        Box::pin(async move {
+            let request =
+                Request::get(NOTIFICATIONS_URL).query([("worker", worker), ("unread", "true")]);
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<Vec<Notification>>().await?)
+        })
+    }
+
+    fn mark_notification_read(&self, id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            info!("Marking notification {} read", id);
+            let url = format!("{}/{}/read", NOTIFICATIONS_URL, id);
+            check_response(Request::put(&url).send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn budget_report<'a>(&'a self, month: Option<&'a str>) -> ApiFuture<'a, BudgetReport> {
+        Box::pin(async move {
+            let mut request = Request::get(BUDGET_VARIANCE_URL);
+            if let Some(month) = month {
+                request = request.query([("month", month)]);
+            }
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<BudgetReport>().await?)
+        })
+    }
+
+    fn set_budget<'a>(&'a self, budget: &'a Budget) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            info!(
+                "Setting {:?} budget to {}",
+                budget.category, budget.monthly_amount
+            );
+            check_response(Request::put(BUDGETS_URL).json(budget)?.send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn farm_settings(&self) -> ApiFuture<'_, FarmSettings> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(SETTINGS_URL).send().await?).await?;
+            Ok(resp.json::<FarmSettings>().await?)
+        })
+    }
+
+    fn update_settings<'a>(&'a self, settings: &'a FarmSettings) -> ApiFuture<'a, FarmSettings> {
+        Box::pin(async move {
+            info!("Saving farm settings");
+            let resp =
+                check_response(Request::put(SETTINGS_URL).json(settings)?.send().await?).await?;
+            Ok(resp.json::<FarmSettings>().await?)
+        })
+    }
+
+    fn set_read_only(&self, mode: ReadOnlyMode) -> ApiFuture<'_, FarmSettings> {
+        Box::pin(async move {
+            info!("Switching read-only mode to {}", mode.read_only);
+            let resp =
+                check_response(Request::put(READ_ONLY_URL).json(&mode)?.send().await?).await?;
+            Ok(resp.json::<FarmSettings>().await?)
+        })
+    }
+
+    fn apply_pricing(&self) -> ApiFuture<'_, Vec<GoatValuation>> {
+        Box::pin(async move {
+            info!("Applying pricing formula to the herd");
+            let resp = check_response(Request::post(PRICING_APPLY_URL).send().await?).await?;
+            Ok(resp.json::<Vec<GoatValuation>>().await?)
+        })
+    }
+
+    fn dashboard_stats(&self) -> ApiFuture<'_, DashboardStats> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(STATS_URL).send().await?).await?;
+            Ok(resp.json::<DashboardStats>().await?)
+        })
+    }
+
+    fn search<'a>(&'a self, query: &'a str) -> ApiFuture<'a, Vec<SearchResult>> {
+        Box::pin(async move {
+            trace!("Searching for '{}'", query);
+            let request = Request::get(SEARCH_URL).query([("q", query)]);
+            let resp = check_response(request.send().await?).await?;
+            Ok(resp.json::<Vec<SearchResult>>().await?)
+        })
+    }
+
+    fn recent_activity(&self) -> ApiFuture<'_, Vec<ActivityEvent>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(ACTIVITY_URL).send().await?).await?;
+            Ok(resp.json::<Vec<ActivityEvent>>().await?)
+        })
+    }
+
+    fn data_health(&self) -> ApiFuture<'_, DataHealthReport> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(DATA_HEALTH_URL).send().await?).await?;
+            Ok(resp.json::<DataHealthReport>().await?)
+        })
+    }
+
+    fn tasks(&self) -> ApiFuture<'_, Vec<Task>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(TASKS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<Task>>().await?)
+        })
+    }
+
+    fn transactions(&self) -> ApiFuture<'_, Vec<Transaction>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(TRANSACTIONS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<Transaction>>().await?)
+        })
+    }
+
+    fn inventory_items(&self) -> ApiFuture<'_, Vec<InventoryItem>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(INVENTORY_URL).send().await?).await?;
+            Ok(resp.json::<Vec<InventoryItem>>().await?)
+        })
+    }
+
+    fn expiring_stock(&self) -> ApiFuture<'_, Vec<ExpiringStock>> {
+        Box::pin(async move {
+            let url = format!("{}/expiring", INVENTORY_URL);
+            let resp = check_response(Request::get(&url).send().await?).await?;
+            Ok(resp.json::<Vec<ExpiringStock>>().await?)
+        })
+    }
+
+    fn dispose_item(&self, id: i64) -> ApiFuture<'_, Disposal> {
+        Box::pin(async move {
+            info!("Disposing of the stock of item {}", id);
+            let url = format!("{}/{}/dispose", INVENTORY_URL, id);
+            let resp = check_response(Request::post(&url).send().await?).await?;
+            Ok(resp.json::<Disposal>().await?)
+        })
+    }
+
+    fn jobs(&self) -> ApiFuture<'_, Vec<Job>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(JOBS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<Job>>().await?)
+        })
+    }
+
+    fn job(&self, id: i64) -> ApiFuture<'_, Job> {
+        Box::pin(async move {
+            trace!("Polling job {}", id);
+            let url = format!("{}/{}", JOBS_URL, id);
+            let resp = check_response(Request::get(&url).send().await?).await?;
+            Ok(resp.json::<Job>().await?)
+        })
+    }
+
+    fn start_import_job<'a>(&'a self, goats: &'a [NewGoat]) -> ApiFuture<'a, Job> {
+        Box::pin(async move {
+            info!("Queueing import of {} goats", goats.len());
+            let url = format!("{}/import", JOBS_URL);
+            let resp = check_response(Request::post(&url).json(goats)?.send().await?).await?;
+            Ok(resp.json::<Job>().await?)
+        })
+    }
+
+    fn start_cleanup_job(&self) -> ApiFuture<'_, Job> {
+        Box::pin(async move {
+            info!("Queueing a retention cleanup");
+            let url = format!("{}/cleanup", JOBS_URL);
+            let resp = check_response(Request::post(&url).send().await?).await?;
+            Ok(resp.json::<Job>().await?)
+        })
+    }
+
+    fn retention(&self) -> ApiFuture<'_, UpcomingPurges> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(RETENTION_URL).send().await?).await?;
+            Ok(resp.json::<UpcomingPurges>().await?)
+        })
+    }
+
+    fn restore_goat(&self, id: i64) -> ApiFuture<'_, Goat> {
+        Box::pin(async move {
+            info!("Restoring goat from trash entry {}", id);
+            let url = format!("{}/{}/restore", TRASH_URL, id);
+            let resp = check_response(Request::post(&url).send().await?).await?;
+            Ok(resp.json::<Goat>().await?)
+        })
+    }
+
+    fn farm_key(&self) -> ApiFuture<'_, Option<String>> {
+        Box::pin(async move { Ok(stored_tenant_key()) })
+    }
+
+    fn select_farm<'a>(&'a self, key: Option<&'a str>) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            let Some(storage) = session_storage() else {
+                return Ok(());
+            };
+            let stored = match key.map(str::trim).filter(|k| !k.is_empty()) {
+                Some(key) => {
+                    info!("Selecting a hosted farm");
+                    storage.set_item(TENANT_KEY_KEY, key)
+                }
+                None => storage.remove_item(TENANT_KEY_KEY),
+            };
+            stored.map_err(|e| AppError::unexpected(format!("{:?}", e)))
+        })
+    }
+
+    fn sign_in<'a>(&'a self, new_session: &'a NewSession) -> ApiFuture<'a, IssuedSession> {
+        Box::pin(async move {
+            info!("Signing in as {}", new_session.device);
+            let resp = check_response(
+                Request::post(SESSIONS_URL)
+                    .json(new_session)?
+                    .send()
+                    .await?,
+            )
+            .await?;
+            let issued = resp.json::<IssuedSession>().await?;
+            keep_session_token(&issued)?;
+            Ok(issued)
+        })
+    }
+
+    fn sessions(&self) -> ApiFuture<'_, Vec<Session>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(SESSIONS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<Session>>().await?)
+        })
+    }
+
+    fn revoke_session(&self, id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            info!("Revoking session {}", id);
+            let url = format!("{}/{}", SESSIONS_URL, id);
+            check_response(Request::delete(&url).send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn revoke_other_sessions(&self) -> ApiFuture<'_, usize> {
+        Box::pin(async move {
+            info!("Signing out other devices");
+            let resp = check_response(Request::delete(SESSIONS_URL).send().await?).await?;
+            Ok(resp.json::<usize>().await?)
+        })
+    }
+
+    fn oauth_providers(&self) -> ApiFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(OAUTH_URL).send().await?).await?;
+            Ok(resp.json::<Vec<String>>().await?)
+        })
+    }
+
+    fn start_oauth<'a>(
+        &'a self,
+        provider: &'a str,
+        redirect_uri: &'a str,
+        device: &'a str,
+    ) -> ApiFuture<'a, OAuthRedirect> {
+        Box::pin(async move {
+            info!("Starting sign-in with {}", provider);
+            let start = OAuthStart {
+                redirect_uri: redirect_uri.to_string(),
+                device: device.to_string(),
+            };
+            let url = format!("{}/{}", OAUTH_URL, provider);
+            let resp = check_response(Request::post(&url).json(&start)?.send().await?).await?;
+            Ok(resp.json::<OAuthRedirect>().await?)
+        })
+    }
+
+    fn finish_oauth<'a>(&'a self, code: &'a str, state: &'a str) -> ApiFuture<'a, IssuedSession> {
+        Box::pin(async move {
+            info!("Finishing OAuth sign-in");
+            let callback = OAuthCallback {
+                code: code.to_string(),
+                state: state.to_string(),
+            };
+            let url = format!("{}/callback", OAUTH_URL);
+            let resp = check_response(Request::post(&url).json(&callback)?.send().await?).await?;
+            let issued = resp.json::<IssuedSession>().await?;
+            keep_session_token(&issued)?;
+            Ok(issued)
+        })
+    }
+
+    fn role_permissions(&self) -> ApiFuture<'_, Vec<RolePermissions>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(PERMISSIONS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<RolePermissions>>().await?)
+        })
+    }
+
+    fn update_role_permissions<'a>(
+        &'a self,
+        permissions: &'a RolePermissions,
+    ) -> ApiFuture<'a, RolePermissions> {
+        Box::pin(async move {
+            info!("Updating the permissions of role {}", permissions.role);
+            let resp = check_response(
+                Request::put(PERMISSIONS_URL)
+                    .json(permissions)?
+                    .send()
+                    .await?,
+            )
+            .await?;
+            Ok(resp.json::<RolePermissions>().await?)
+        })
+    }
+
+    fn my_permissions(&self) -> ApiFuture<'_, MyPermissions> {
+        Box::pin(async move {
+            let url = format!("{}/mine", PERMISSIONS_URL);
+            let resp = check_response(Request::get(&url).send().await?).await?;
+            Ok(resp.json::<MyPermissions>().await?)
+        })
+    }
+
+    fn access_tokens(&self) -> ApiFuture<'_, Vec<AccessToken>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(TOKENS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<AccessToken>>().await?)
+        })
+    }
+
+    fn create_access_token<'a>(
+        &'a self,
+        new_token: &'a NewAccessToken,
+    ) -> ApiFuture<'a, IssuedAccessToken> {
+        Box::pin(async move {
+            info!("Creating access token {}", new_token.name);
+            let resp =
+                check_response(Request::post(TOKENS_URL).json(new_token)?.send().await?).await?;
+            Ok(resp.json::<IssuedAccessToken>().await?)
+        })
+    }
+
+    fn revoke_access_token(&self, id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            info!("Revoking access token {}", id);
+            let url = format!("{}/{}", TOKENS_URL, id);
+            check_response(Request::delete(&url).send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn alert_rules(&self) -> ApiFuture<'_, Vec<AlertRule>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(ALERTS_URL).send().await?).await?;
+            Ok(resp.json::<Vec<AlertRule>>().await?)
+        })
+    }
+
+    fn add_alert_rule<'a>(&'a self, rule: &'a AlertRule) -> ApiFuture<'a, AlertRule> {
+        Box::pin(async move {
+            info!("Adding alert rule {}", rule.name);
+            let resp = check_response(Request::post(ALERTS_URL).json(rule)?.send().await?).await?;
+            Ok(resp.json::<AlertRule>().await?)
+        })
+    }
+
+    fn delete_alert_rule(&self, id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            info!("Deleting alert rule {}", id);
+            let url = format!("{}/{}", ALERTS_URL, id);
+            check_response(Request::delete(&url).send().await?).await?;
+            Ok(())
+        })
+    }
+
+    fn lifecycle_pipeline(&self) -> ApiFuture<'_, LifecyclePipeline> {
+        Box::pin(async move {
+            info!("Fetching lifecycle pipeline");
+            let resp = check_response(Request::get(LIFECYCLE_URL).send().await?).await?;
+            Ok(resp.json::<LifecyclePipeline>().await?)
+        })
+    }
+
+    fn change_lifecycle_stage<'a>(
+        &'a self,
+        change: &'a StageChange,
+    ) -> ApiFuture<'a, StageTransition> {
+        Box::pin(async move {
+            info!("Moving {} to {:?}", change.goat_name, change.stage);
+            let resp =
+                check_response(Request::put(LIFECYCLE_URL).json(change)?.send().await?).await?;
+            Ok(resp.json::<StageTransition>().await?)
+        })
+    }
+
+    fn download<'a>(&'a self, url: &'a str) -> ApiFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            info!("Downloading {}", url);
+            let resp = check_response(Request::get(url).send().await?).await?;
+            Ok(resp.binary().await?)
+        })
+    }
+
+    fn export_farm_archive<'a>(&'a self, passphrase: &'a str) -> ApiFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            info!("Downloading an encrypted farm archive");
+            let passphrase = String::from(js_sys::encode_uri_component(passphrase));
+            let resp = check_response(
+                Request::get(ARCHIVE_URL)
+                    .header(PASSPHRASE_HEADER, &passphrase)
+                    .send()
+                    .await?,
+            )
+            .await?;
+            Ok(resp.binary().await?)
+        })
+    }
+
+    fn import_farm_archive<'a>(
+        &'a self,
+        archive: &'a [u8],
+        passphrase: Option<&'a str>,
+    ) -> ApiFuture<'a, ArchiveSummary> {
+        Box::pin(async move {
+            info!("Uploading a {} byte farm archive", archive.len());
+            let mut req = Request::post(ARCHIVE_URL);
+            if let Some(passphrase) = passphrase {
+                let passphrase = String::from(js_sys::encode_uri_component(passphrase));
+                req = req.header(PASSPHRASE_HEADER, &passphrase);
+            }
+            let body = js_sys::Uint8Array::from(archive);
+            let resp = check_response(req.body(body)?.send().await?).await?;
+            Ok(resp.json::<ArchiveSummary>().await?)
+        })
+    }
+
+    fn export_templates(&self) -> ApiFuture<'_, Vec<ExportTemplate>> {
+        Box::pin(async move {
+            let resp = check_response(Request::get(EXPORT_TEMPLATES_URL).send().await?).await?;
+            Ok(resp.json::<Vec<ExportTemplate>>().await?)
+        })
+    }
+
+    fn add_export_template<'a>(
+        &'a self,
+        template: &'a ExportTemplate,
+    ) -> ApiFuture<'a, ExportTemplate> {
+        Box::pin(async move {
+            info!("Saving export template {}", template.key);
+            let resp = check_response(
+                Request::post(EXPORT_TEMPLATES_URL)
+                    .json(template)?
+                    .send()
+                    .await?,
+            )
+            .await?;
+            Ok(resp.json::<ExportTemplate>().await?)
+        })
+    }
+
+    fn delete_export_template<'a>(&'a self, key: &'a str) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            info!("Deleting export template {}", key);
+            let url = format!(
+                "{}/{}",
+                EXPORT_TEMPLATES_URL,
+                String::from(js_sys::encode_uri_component(key))
+            );
+            check_response(Request::delete(&url).send().await?).await?;
+            Ok(())
+        })
+    }
+}
+
+/// Parses every complete line in `buffer`, leaving a trailing partial line in place.
+fn drain_ndjson_lines(buffer: &mut Vec<u8>) -> Result<Vec<Goat>, AppError> {
+    let Some(end) = buffer.iter().rposition(|b| *b == b'\n') else {
+        return Ok(Vec::new());
+    };
+    let complete: Vec<u8> = buffer.drain(..=end).collect();
+    complete
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| Ok(serde_json::from_slice::<Goat>(line)?))
+        .collect()
+}
@@ -0,0 +1,2268 @@
+//! In-memory `ApiClient` for tests.
+//!
+//! Keeps goats in a local list, records every call, and can be told to fail
+//! the next request with a given status, so tests can assert on both the
+//! happy path and error handling without a backend.
+
+use crate::errors::AppError;
+use crate::services::api::{ApiClient, ApiFuture, GoatsFetch};
+use chrono::{Duration, Utc};
+use shared::activity::ActivityEvent;
+use shared::alerts::AlertRule;
+use shared::analytics::FeedEfficiencyReport;
+use shared::archive::ArchiveSummary;
+use shared::attachments::{Attachment, AttachmentTarget, check_voice_note};
+use shared::breeding::{
+    BreedingRecommendation, BreedingService, ExternalAnimal, GeneticTags, MethodSuccess, Neutering,
+    PedigreeNode, ServiceOutcome, normalize_tags,
+};
+use shared::breeds::{CatalogBreed, builtin_catalog};
+use shared::bulk::BulkPatch;
+use shared::data_health::DataHealthReport;
+use shared::diseases::{CatalogDisease, ComplianceReport, NewDisease};
+use shared::events::FieldChange;
+use shared::exports::ExportTemplate;
+use shared::finance::{Budget, BudgetReport, FinanceCategory, Transaction};
+use shared::gps::{Geofence, GoatPosition};
+use shared::grazing::{Paddock, RotationPlan};
+use shared::growth::{GrowthBenchmark, GrowthHistory, WeightEstimate, WeightRecord};
+use shared::health::{HealthHeatMap, TimelineEntry};
+use shared::heat::HeatPrediction;
+use shared::import::{BatchSummary, ImportTable};
+use shared::inventory::{Disposal, ExpiringStock, InventoryItem};
+use shared::jobs::{Job, JobKind, JobStatus};
+use shared::lab::{LabResult, check_report};
+use shared::lifecycle::{LifecyclePipeline, StageChange, StageTransition};
+use shared::milk::{Lactation, MilkRecord};
+use shared::movements::AnimalMovement;
+use shared::notes::{GoatNote, NoteInput};
+use shared::notifications::Notification;
+use shared::nutrition::{RationPlan, RationRequest};
+use shared::permissions::{MyPermissions, RolePermissions};
+use shared::pricing::GoatValuation;
+use shared::retention::{TrashedGoat, UpcomingPurges};
+use shared::scale::ScaleReading;
+use shared::scoring::{GoatScore, ScoreWeights};
+use shared::search::{SearchKind, SearchResult, suggest_names};
+use shared::sensors::SensorCondition;
+use shared::sessions::{IssuedSession, NewSession, OAuthRedirect, Session};
+use shared::settings::{FarmSettings, ReadOnlyMode, validate_language};
+use shared::spaces::{Space, SpaceOccupancy};
+use shared::stats::DashboardStats;
+use shared::tags::NextTag;
+use shared::tasks::Task;
+use shared::tokens::{AccessToken, IssuedAccessToken, NewAccessToken};
+use shared::traceability::TraceabilityRecord;
+use shared::vet::{
+    VetAvailability, VetVisit, VisitRequest, VisitSchedule, VisitStatus, VisitSummary,
+};
+use shared::water::PenWater;
+use shared::{Gender, Goat, GoatParams, GoatUpdate, NewGoat};
+use std::cell::RefCell;
+
+/// Mock backend holding goats in memory.
+#[derive(Default)]
+pub struct MockApiClient {
+    goats: RefCell<Vec<Goat>>,
+    recommendations: RefCell<Vec<BreedingRecommendation>>,
+    neuterings: RefCell<Vec<Neutering>>,
+    services: RefCell<Vec<BreedingService>>,
+    service_success: RefCell<Vec<MethodSuccess>>,
+    external_animals: RefCell<Vec<ExternalAnimal>>,
+    genetic_tags: RefCell<Vec<GeneticTags>>,
+    pedigrees: RefCell<Vec<PedigreeNode>>,
+    added_breeds: RefCell<Vec<CatalogBreed>>,
+    benchmarks: RefCell<Vec<GrowthBenchmark>>,
+    histories: RefCell<Vec<GrowthHistory>>,
+    weights: RefCell<Vec<WeightRecord>>,
+    milk_records: RefCell<Vec<MilkRecord>>,
+    weight_estimate: RefCell<Option<WeightEstimate>>,
+    lactations: RefCell<Vec<Lactation>>,
+    feed_efficiency: RefCell<FeedEfficiencyReport>,
+    spaces: RefCell<Vec<Space>>,
+    occupancy: RefCell<Vec<SpaceOccupancy>>,
+    heatmap: RefCell<HealthHeatMap>,
+    vet_visits: RefCell<Vec<VetVisit>>,
+    vets: RefCell<Vec<VetAvailability>>,
+    timeline: RefCell<Vec<TimelineEntry>>,
+    lab_results: RefCell<Vec<LabResult>>,
+    diseases: RefCell<Vec<CatalogDisease>>,
+    compliance: RefCell<ComplianceReport>,
+    movements: RefCell<Vec<AnimalMovement>>,
+    traceability: RefCell<Vec<TraceabilityRecord>>,
+    scores: RefCell<Vec<GoatScore>>,
+    ration: RefCell<Option<RationPlan>>,
+    readings: RefCell<Vec<ScaleReading>>,
+    conditions: RefCell<Vec<SensorCondition>>,
+    water: RefCell<Vec<PenWater>>,
+    heat_predictions: RefCell<Vec<HeatPrediction>>,
+    positions: RefCell<Vec<GoatPosition>>,
+    geofences: RefCell<Vec<Geofence>>,
+    paddocks: RefCell<Vec<Paddock>>,
+    rotation_plan: RefCell<RotationPlan>,
+    notes: RefCell<Vec<GoatNote>>,
+    notifications: RefCell<Vec<Notification>>,
+    attachments: RefCell<Vec<Attachment>>,
+    import_table: RefCell<ImportTable>,
+    next_tag: RefCell<NextTag>,
+    budget_report: RefCell<BudgetReport>,
+    budgets: RefCell<Vec<Budget>>,
+    settings: RefCell<FarmSettings>,
+    stats: RefCell<DashboardStats>,
+    search_results: RefCell<Vec<SearchResult>>,
+    activity: RefCell<Vec<ActivityEvent>>,
+    data_health: RefCell<DataHealthReport>,
+    tasks: RefCell<Vec<Task>>,
+    transactions: RefCell<Vec<Transaction>>,
+    inventory: RefCell<Vec<InventoryItem>>,
+    expiring: RefCell<Vec<ExpiringStock>>,
+    jobs: RefCell<Vec<Job>>,
+    archive_summary: RefCell<ArchiveSummary>,
+    export_templates: RefCell<Vec<ExportTemplate>>,
+    field_changes: RefCell<Vec<FieldChange>>,
+    retention: RefCell<UpcomingPurges>,
+    farm_key: RefCell<Option<String>>,
+    sessions: RefCell<Vec<Session>>,
+    oauth_providers: RefCell<Vec<String>>,
+    oauth_device: RefCell<Option<String>>,
+    role_permissions: RefCell<Vec<RolePermissions>>,
+    my_permissions: RefCell<Option<MyPermissions>>,
+    access_tokens: RefCell<Vec<AccessToken>>,
+    alert_rules: RefCell<Vec<AlertRule>>,
+    lifecycle: RefCell<LifecyclePipeline>,
+    calls: RefCell<Vec<String>>,
+    fail_next: RefCell<Option<(u16, String)>>,
+}
+
+impl MockApiClient {
+    /// Creates a mock whose backend already holds `goats`, with IDs from 1.
+    pub fn with_goats(goats: Vec<GoatParams>) -> Self {
+        let mock = MockApiClient::default();
+        for goat in &goats {
+            mock.store_goat(goat);
+        }
+        mock
+    }
+
+    /// Sets the pairings returned by `breeding_recommendations`.
+    pub fn set_recommendations(&self, recommendations: Vec<BreedingRecommendation>) {
+        *self.recommendations.borrow_mut() = recommendations;
+    }
+
+    /// Sets the neuterings returned by `neuterings`.
+    pub fn set_neuterings(&self, neuterings: Vec<Neutering>) {
+        *self.neuterings.borrow_mut() = neuterings;
+    }
+
+    /// Sets the services returned by `breeding_services`;
+    /// `add_breeding_service` adds to them.
+    pub fn set_breeding_services(&self, services: Vec<BreedingService>) {
+        *self.services.borrow_mut() = services;
+    }
+
+    /// Sets the rates returned by `service_success`.
+    pub fn set_service_success(&self, rates: Vec<MethodSuccess>) {
+        *self.service_success.borrow_mut() = rates;
+    }
+
+    /// Sets the registry returned by `external_animals`;
+    /// `add_external_animal` and `delete_external_animal` change it.
+    pub fn set_external_animals(&self, animals: Vec<ExternalAnimal>) {
+        *self.external_animals.borrow_mut() = animals;
+    }
+
+    /// Sets the tags returned by `genetic_tags`; `save_genetic_tags`
+    /// updates them.
+    pub fn set_genetic_tags(&self, tags: Vec<GeneticTags>) {
+        *self.genetic_tags.borrow_mut() = tags;
+    }
+
+    /// Adds a pedigree tree returned by `pedigree_tree` for its root goat.
+    pub fn add_pedigree(&self, tree: PedigreeNode) {
+        self.pedigrees.borrow_mut().push(tree);
+    }
+
+    /// Adds a breed to the catalog returned by `breed_catalog`, after the
+    /// built-in ones.
+    pub fn add_catalog_breed(&self, breed: CatalogBreed) {
+        self.added_breeds.borrow_mut().push(breed);
+    }
+
+    /// Sets the benchmarks returned by `growth_benchmarks`.
+    pub fn set_benchmarks(&self, benchmarks: Vec<GrowthBenchmark>) {
+        *self.benchmarks.borrow_mut() = benchmarks;
+    }
+
+    /// Adds a weight history returned by `weight_history` for its goat.
+    pub fn add_history(&self, history: GrowthHistory) {
+        self.histories.borrow_mut().push(history);
+    }
+
+    /// Weighings recorded through `add_weight`.
+    pub fn weights(&self) -> Vec<WeightRecord> {
+        self.weights.borrow().clone()
+    }
+
+    /// Milkings recorded through `add_milk_record`.
+    pub fn milk_records(&self) -> Vec<MilkRecord> {
+        self.milk_records.borrow().clone()
+    }
+
+    /// Sets the estimate returned by `estimate_weight`, whatever the photo.
+    /// Until one is set, estimating fails as if no service were configured.
+    pub fn set_weight_estimate(&self, estimate: WeightEstimate) {
+        *self.weight_estimate.borrow_mut() = Some(estimate);
+    }
+
+    /// Sets the lactations returned by `lactations`.
+    pub fn set_lactations(&self, lactations: Vec<Lactation>) {
+        *self.lactations.borrow_mut() = lactations;
+    }
+
+    /// Sets the report returned by `feed_efficiency`.
+    pub fn set_feed_efficiency(&self, report: FeedEfficiencyReport) {
+        *self.feed_efficiency.borrow_mut() = report;
+    }
+
+    /// Spaces created through `add_space`.
+    pub fn spaces(&self) -> Vec<Space> {
+        self.spaces.borrow().clone()
+    }
+
+    /// Sets the spaces returned by `ApiClient::spaces`.
+    pub fn set_spaces(&self, spaces: Vec<Space>) {
+        *self.spaces.borrow_mut() = spaces;
+    }
+
+    /// Sets the spaces returned by `space_occupancy`.
+    pub fn set_occupancy(&self, occupancy: Vec<SpaceOccupancy>) {
+        *self.occupancy.borrow_mut() = occupancy;
+    }
+
+    /// Sets the heat map returned by `health_heatmap`.
+    pub fn set_heatmap(&self, heatmap: HealthHeatMap) {
+        *self.heatmap.borrow_mut() = heatmap;
+    }
+
+    /// Sets the visits returned by `vet_visits`.
+    pub fn set_vet_visits(&self, visits: Vec<VetVisit>) {
+        *self.vet_visits.borrow_mut() = visits;
+    }
+
+    /// Sets the vets returned by `vets`.
+    pub fn set_vets(&self, vets: Vec<VetAvailability>) {
+        *self.vets.borrow_mut() = vets;
+    }
+
+    /// Sets the entries returned by `health_timeline`.
+    pub fn set_timeline(&self, timeline: Vec<TimelineEntry>) {
+        *self.timeline.borrow_mut() = timeline;
+    }
+
+    /// Sets the results returned by `lab_results`.
+    pub fn set_lab_results(&self, results: Vec<LabResult>) {
+        *self.lab_results.borrow_mut() = results;
+    }
+
+    /// Sets the catalog returned by `diseases`.
+    pub fn set_diseases(&self, diseases: Vec<CatalogDisease>) {
+        *self.diseases.borrow_mut() = diseases;
+    }
+
+    /// Sets the report returned by `compliance_report`.
+    pub fn set_compliance(&self, report: ComplianceReport) {
+        *self.compliance.borrow_mut() = report;
+    }
+
+    /// Sets the register returned by `movements`, latest departure first.
+    pub fn set_movements(&self, movements: Vec<AnimalMovement>) {
+        *self.movements.borrow_mut() = movements;
+    }
+
+    /// Sets the records returned by `traceability`, for any goat.
+    pub fn set_traceability(&self, records: Vec<TraceabilityRecord>) {
+        *self.traceability.borrow_mut() = records;
+    }
+
+    /// Sets the scores returned by `goat_scores`.
+    pub fn set_scores(&self, scores: Vec<GoatScore>) {
+        *self.scores.borrow_mut() = scores;
+    }
+
+    /// Sets the plan returned by `formulate_ration`; without one, no feed
+    /// in stock can be used.
+    pub fn set_ration(&self, plan: RationPlan) {
+        *self.ration.borrow_mut() = Some(plan);
+    }
+
+    /// Adds a reading, as if a scale had just pushed it.
+    pub fn add_reading(&self, reading: ScaleReading) {
+        self.readings.borrow_mut().push(reading);
+    }
+
+    /// Readings currently held by the mock backend.
+    pub fn readings(&self) -> Vec<ScaleReading> {
+        self.readings.borrow().clone()
+    }
+
+    /// Sets the sensor conditions returned by `sensor_conditions`.
+    pub fn set_conditions(&self, conditions: Vec<SensorCondition>) {
+        *self.conditions.borrow_mut() = conditions;
+    }
+
+    /// Sets the pens returned by `pen_water`.
+    pub fn set_water(&self, pens: Vec<PenWater>) {
+        *self.water.borrow_mut() = pens;
+    }
+
+    /// Sets the predictions returned by `heat_predictions`.
+    pub fn set_heat_predictions(&self, predictions: Vec<HeatPrediction>) {
+        *self.heat_predictions.borrow_mut() = predictions;
+    }
+
+    /// Sets the positions returned by `goat_positions`.
+    pub fn set_positions(&self, positions: Vec<GoatPosition>) {
+        *self.positions.borrow_mut() = positions;
+    }
+
+    /// Sets the geofences returned by `geofences`.
+    pub fn set_geofences(&self, geofences: Vec<Geofence>) {
+        *self.geofences.borrow_mut() = geofences;
+    }
+
+    /// Paddocks held by the mock backend, including those added through
+    /// `add_paddock`.
+    pub fn paddocks(&self) -> Vec<Paddock> {
+        self.paddocks.borrow().clone()
+    }
+
+    /// Sets the plan returned by `rotation_plan`.
+    pub fn set_rotation_plan(&self, plan: RotationPlan) {
+        *self.rotation_plan.borrow_mut() = plan;
+    }
+
+    /// Notes held by the mock backend, including those added through
+    /// `add_note`.
+    pub fn notes(&self) -> Vec<GoatNote> {
+        self.notes.borrow().clone()
+    }
+
+    /// Adds a stored note returned by `goat_notes` for its goat.
+    pub fn add_stored_note(&self, note: GoatNote) {
+        self.notes.borrow_mut().push(note);
+    }
+
+    /// Stored attachments.
+    pub fn attachments(&self) -> Vec<Attachment> {
+        self.attachments.borrow().clone()
+    }
+
+    /// Adds a stored attachment returned by `attachments` for its target.
+    pub fn add_stored_attachment(&self, attachment: Attachment) {
+        self.attachments.borrow_mut().push(attachment);
+    }
+
+    /// Stored notifications, read or not.
+    pub fn notifications(&self) -> Vec<Notification> {
+        self.notifications.borrow().clone()
+    }
+
+    /// Adds a notification returned by `unread_notifications` for its
+    /// worker until marked read.
+    pub fn add_notification(&self, notification: Notification) {
+        self.notifications.borrow_mut().push(notification);
+    }
+
+    /// Sets the table returned by `parse_import`, whatever the upload.
+    pub fn set_import_table(&self, table: ImportTable) {
+        *self.import_table.borrow_mut() = table;
+    }
+
+    /// Sets the report returned by `budget_report`.
+    pub fn set_budget_report(&self, report: BudgetReport) {
+        *self.budget_report.borrow_mut() = report;
+    }
+
+    /// The budgets set through `set_budget`, in call order.
+    pub fn budgets(&self) -> Vec<Budget> {
+        self.budgets.borrow().clone()
+    }
+
+    /// Sets the tag returned by `next_tag`.
+    pub fn set_next_tag(&self, tag_id: Option<&str>) {
+        *self.next_tag.borrow_mut() = NextTag {
+            tag_id: tag_id.map(str::to_string),
+        };
+    }
+
+    /// Sets the settings returned by `farm_settings`.
+    pub fn set_settings(&self, settings: FarmSettings) {
+        *self.settings.borrow_mut() = settings;
+    }
+
+    /// Settings currently held by the mock backend.
+    pub fn settings(&self) -> FarmSettings {
+        self.settings.borrow().clone()
+    }
+
+    /// Sets the figures returned by `dashboard_stats`.
+    pub fn set_stats(&self, stats: DashboardStats) {
+        *self.stats.borrow_mut() = stats;
+    }
+
+    /// Sets the records `search` looks through; it returns those whose
+    /// title contains the query, ignoring case.
+    pub fn set_search_results(&self, results: Vec<SearchResult>) {
+        *self.search_results.borrow_mut() = results;
+    }
+
+    /// Sets the events returned by `recent_activity`.
+    pub fn set_activity(&self, activity: Vec<ActivityEvent>) {
+        *self.activity.borrow_mut() = activity;
+    }
+
+    /// Sets the report returned by `data_health`.
+    pub fn set_data_health(&self, report: DataHealthReport) {
+        *self.data_health.borrow_mut() = report;
+    }
+
+    /// Sets the tasks returned by `tasks`.
+    pub fn set_tasks(&self, tasks: Vec<Task>) {
+        *self.tasks.borrow_mut() = tasks;
+    }
+
+    /// Sets the entries returned by `transactions`.
+    pub fn set_transactions(&self, transactions: Vec<Transaction>) {
+        *self.transactions.borrow_mut() = transactions;
+    }
+
+    /// Sets the items returned by `inventory_items`.
+    pub fn set_inventory(&self, items: Vec<InventoryItem>) {
+        *self.inventory.borrow_mut() = items;
+    }
+
+    /// Sets the stock returned by `expiring_stock`.
+    pub fn set_expiring(&self, expiring: Vec<ExpiringStock>) {
+        *self.expiring.borrow_mut() = expiring;
+    }
+
+    /// Sets the jobs returned by `jobs`, newest first; `job` looks them up
+    /// by ID.
+    pub fn set_jobs(&self, jobs: Vec<Job>) {
+        *self.jobs.borrow_mut() = jobs;
+    }
+
+    /// Sets the summary returned by `import_farm_archive`.
+    pub fn set_archive_summary(&self, summary: ArchiveSummary) {
+        *self.archive_summary.borrow_mut() = summary;
+    }
+
+    /// Export templates as saved so far.
+    pub fn export_templates(&self) -> Vec<ExportTemplate> {
+        self.export_templates.borrow().clone()
+    }
+
+    /// Sets the templates returned by `ApiClient::export_templates`;
+    /// `add_export_template` and `delete_export_template` change them.
+    pub fn set_export_templates(&self, templates: Vec<ExportTemplate>) {
+        *self.export_templates.borrow_mut() = templates;
+    }
+
+    /// Sets the changes `field_history` picks a field's from, newest first.
+    pub fn set_field_changes(&self, changes: Vec<FieldChange>) {
+        *self.field_changes.borrow_mut() = changes;
+    }
+
+    /// Sets the trash and upcoming purges returned by `retention`; its
+    /// policy is always the one in the settings.
+    pub fn set_retention(&self, purges: UpcomingPurges) {
+        *self.retention.borrow_mut() = purges;
+    }
+
+    /// Sets the signed-in devices returned by `sessions`.
+    pub fn set_sessions(&self, sessions: Vec<Session>) {
+        *self.sessions.borrow_mut() = sessions;
+    }
+
+    /// Sets the OAuth providers returned by `oauth_providers`.
+    pub fn set_oauth_providers(&self, providers: Vec<String>) {
+        *self.oauth_providers.borrow_mut() = providers;
+    }
+
+    /// Sets the matrix returned by `role_permissions`.
+    pub fn set_role_permissions(&self, matrix: Vec<RolePermissions>) {
+        *self.role_permissions.borrow_mut() = matrix;
+    }
+
+    /// Sets what `my_permissions` returns; the owner's permissions if never
+    /// set.
+    pub fn set_my_permissions(&self, permissions: MyPermissions) {
+        *self.my_permissions.borrow_mut() = Some(permissions);
+    }
+
+    /// Sets the tokens returned by `access_tokens`.
+    pub fn set_access_tokens(&self, tokens: Vec<AccessToken>) {
+        *self.access_tokens.borrow_mut() = tokens;
+    }
+
+    /// Sets the rules returned by `alert_rules`.
+    pub fn set_alert_rules(&self, rules: Vec<AlertRule>) {
+        *self.alert_rules.borrow_mut() = rules;
+    }
+
+    /// Sets the pipeline returned by `lifecycle_pipeline`.
+    pub fn set_lifecycle_pipeline(&self, pipeline: LifecyclePipeline) {
+        *self.lifecycle.borrow_mut() = pipeline;
+    }
+
+    /// Makes the next request fail with `AppError::ApiError { status, body }`.
+    pub fn fail_next(&self, status: u16, body: &str) {
+        *self.fail_next.borrow_mut() = Some((status, body.to_string()));
+    }
+
+    /// Names of the calls made so far, e.g. `"add_goat:Rani"`.
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.borrow().clone()
+    }
+
+    /// Goats currently held by the mock backend.
+    pub fn goats(&self) -> Vec<GoatParams> {
+        self.goats
+            .borrow()
+            .iter()
+            .map(|g| g.params.clone())
+            .collect()
+    }
+
+    /// Stores `goat` under the next free ID, as the backend would.
+    fn store_goat(&self, goat: &NewGoat) -> Goat {
+        let mut goats = self.goats.borrow_mut();
+        let stored = Goat {
+            id: goats.iter().map(|g| g.id).max().unwrap_or(0) + 1,
+            params: goat.clone(),
+            tag_id: None,
+            date_of_birth: None,
+            created_at: None,
+            updated_at: None,
+        };
+        goats.push(stored.clone());
+        stored
+    }
+
+    /// Signs in a new current session as the backend would.
+    fn add_session(&self, device: &str, worker: Option<String>) -> IssuedSession {
+        let mut sessions = self.sessions.borrow_mut();
+        for session in sessions.iter_mut() {
+            session.current = false;
+        }
+        let now = Utc::now();
+        let session = Session {
+            id: sessions.iter().map(|s| s.id).max().unwrap_or(0) + 1,
+            device: device.to_string(),
+            worker,
+            user_agent: None,
+            ip: None,
+            created_at: now,
+            last_seen_at: now,
+            current: true,
+        };
+        sessions.insert(0, session.clone());
+        IssuedSession {
+            session,
+            token: "yagi_mock".to_string(),
+        }
+    }
+
+    /// Records `call` and returns the queued failure, if any.
+    fn record(&self, call: String) -> Result<(), AppError> {
+        self.calls.borrow_mut().push(call);
+        match self.fail_next.borrow_mut().take() {
+            Some((status, body)) => Err(AppError::api(status, body)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ApiClient for MockApiClient {
+    fn fetch_goats<'a>(&'a self, etag: Option<&'a str>) -> ApiFuture<'a, GoatsFetch> {
+        Box::pin(async move {
+            self.record(format!("fetch_goats:{}", etag.unwrap_or("")))?;
+            Ok(GoatsFetch::Modified {
+                goats: self.goats.borrow().clone(),
+                etag: None,
+            })
+        })
+    }
+
+    fn stream_goats<'a>(&'a self, on_chunk: &'a dyn Fn(Vec<Goat>)) -> ApiFuture<'a, usize> {
+        Box::pin(async move {
+            self.record("stream_goats".to_string())?;
+            let goats = self.goats.borrow().clone();
+            let total = goats.len();
+            if total > 0 {
+                on_chunk(goats);
+            }
+            Ok(total)
+        })
+    }
+
+    fn add_goat<'a>(&'a self, goat: &'a NewGoat) -> ApiFuture<'a, Goat> {
+        Box::pin(async move {
+            self.record(format!("add_goat:{}", goat.name))?;
+            Ok(self.store_goat(goat))
+        })
+    }
+
+    fn next_tag(&self) -> ApiFuture<'_, NextTag> {
+        Box::pin(async move {
+            self.record("next_tag".to_string())?;
+            Ok(self.next_tag.borrow().clone())
+        })
+    }
+
+    fn add_goats_batch<'a>(&'a self, goats: &'a [NewGoat]) -> ApiFuture<'a, BatchSummary> {
+        Box::pin(async move {
+            self.record(format!("add_goats_batch:{}", goats.len()))?;
+            for goat in goats {
+                self.store_goat(goat);
+            }
+            Ok(BatchSummary { added: goats.len() })
+        })
+    }
+
+    fn parse_import<'a>(&'a self, file: &'a [u8]) -> ApiFuture<'a, ImportTable> {
+        Box::pin(async move {
+            self.record(format!("parse_import:{}", file.len()))?;
+            Ok(self.import_table.borrow().clone())
+        })
+    }
+
+    fn update_goat<'a>(&'a self, goat: &'a NewGoat) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            self.record(format!("update_goat:{}", goat.name))?;
+            let mut goats = self.goats.borrow_mut();
+            match goats.iter_mut().find(|g| g.name == goat.name) {
+                Some(existing) => {
+                    existing.params = goat.clone();
+                    Ok(())
+                }
+                None => Err(AppError::api(
+                    400,
+                    format!("No goat found with name {}", goat.name),
+                )),
+            }
+        })
+    }
+
+    fn patch_goat<'a>(&'a self, id: i64, update: &'a GoatUpdate) -> ApiFuture<'a, Goat> {
+        Box::pin(async move {
+            self.record(format!("patch_goat:{}", id))?;
+            let mut goats = self.goats.borrow_mut();
+            match goats.iter_mut().find(|g| g.id == id) {
+                Some(existing) => {
+                    existing.params = update.apply(&existing.params);
+                    Ok(existing.clone())
+                }
+                None => Err(AppError::api(400, format!("No goat found with ID {}", id))),
+            }
+        })
+    }
+
+    fn bulk_patch_goats<'a>(
+        &'a self,
+        patch: &'a BulkPatch,
+        preview: bool,
+    ) -> ApiFuture<'a, Vec<Goat>> {
+        Box::pin(async move {
+            self.record(format!("bulk_patch_goats:{}", preview))?;
+            patch.validate().map_err(|e| AppError::api(400, e))?;
+            // Stages come from the pipeline set with `set_lifecycle_pipeline`
+            let staged: Option<Vec<i64>> = patch.stage.map(|stage| {
+                self.lifecycle
+                    .borrow()
+                    .stages
+                    .iter()
+                    .filter(|s| s.stage == stage)
+                    .flat_map(|s| s.goats.iter().map(|g| g.id))
+                    .collect()
+            });
+            let mut goats = self.goats.borrow_mut();
+            let mut selected: Vec<&mut Goat> = goats
+                .iter_mut()
+                .filter(|g| staged.as_ref().is_none_or(|ids| ids.contains(&g.id)))
+                .filter(|g| patch.filter.matches(g))
+                .collect();
+            selected.sort_by(|a, b| a.name.cmp(&b.name));
+            if !preview {
+                for goat in selected.iter_mut() {
+                    goat.params = patch.update.apply(&goat.params);
+                }
+            }
+            Ok(selected.into_iter().map(|g| g.clone()).collect())
+        })
+    }
+
+    fn field_history<'a>(&'a self, id: i64, field: &'a str) -> ApiFuture<'a, Vec<FieldChange>> {
+        Box::pin(async move {
+            self.record(format!("field_history:{}:{}", id, field))?;
+            Ok(self
+                .field_changes
+                .borrow()
+                .iter()
+                .filter(|c| c.field == field)
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn delete_goat<'a>(&'a self, name: &'a str) -> ApiFuture<'a, i64> {
+        Box::pin(async move {
+            self.record(format!("delete_goat:{}", name))?;
+            let mut goats = self.goats.borrow_mut();
+            let Some(index) = goats.iter().position(|g| g.name == name) else {
+                return Err(AppError::api(
+                    400,
+                    format!("No goat found with name {}", name),
+                ));
+            };
+            let goat = goats.remove(index);
+            let mut retention = self.retention.borrow_mut();
+            let id = retention.trash.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+            let deleted_at = Utc::now();
+            let trash_days = self.settings.borrow().retention.trash_days;
+            retention.trash.push(TrashedGoat {
+                id,
+                goat,
+                deleted_at,
+                purge_on: (deleted_at + Duration::days(trash_days as i64))
+                    .format("%Y-%m-%d")
+                    .to_string(),
+            });
+            Ok(id)
+        })
+    }
+
+    fn breeding_recommendations<'a>(
+        &'a self,
+        doe: Option<&'a str>,
+    ) -> ApiFuture<'a, Vec<BreedingRecommendation>> {
+        Box::pin(async move {
+            self.record(format!("breeding_recommendations:{}", doe.unwrap_or("")))?;
+            Ok(self
+                .recommendations
+                .borrow()
+                .iter()
+                .filter(|r| doe.is_none_or(|name| r.doe_name == name))
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn neuterings(&self) -> ApiFuture<'_, Vec<Neutering>> {
+        Box::pin(async move {
+            self.record("neuterings".to_string())?;
+            Ok(self.neuterings.borrow().clone())
+        })
+    }
+
+    fn breeding_services(&self) -> ApiFuture<'_, Vec<BreedingService>> {
+        Box::pin(async move {
+            self.record("breeding_services".to_string())?;
+            Ok(self.services.borrow().clone())
+        })
+    }
+
+    fn add_breeding_service<'a>(&'a self, service: &'a BreedingService) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            self.record(format!(
+                "add_breeding_service:{}:{:?}",
+                service.doe_name, service.method
+            ))?;
+            service.validate().map_err(|e| AppError::api(400, e))?;
+            let mut services = self.services.borrow_mut();
+            let id = services.iter().filter_map(|s| s.id).max().unwrap_or(0) + 1;
+            services.insert(
+                0,
+                BreedingService {
+                    id: Some(id),
+                    outcome: Some(ServiceOutcome::Pending),
+                    ..service.clone()
+                },
+            );
+            Ok(())
+        })
+    }
+
+    fn service_success(&self) -> ApiFuture<'_, Vec<MethodSuccess>> {
+        Box::pin(async move {
+            self.record("service_success".to_string())?;
+            Ok(self.service_success.borrow().clone())
+        })
+    }
+
+    fn external_animals(&self) -> ApiFuture<'_, Vec<ExternalAnimal>> {
+        Box::pin(async move {
+            self.record("external_animals".to_string())?;
+            Ok(self.external_animals.borrow().clone())
+        })
+    }
+
+    fn add_external_animal<'a>(
+        &'a self,
+        animal: &'a ExternalAnimal,
+    ) -> ApiFuture<'a, ExternalAnimal> {
+        Box::pin(async move {
+            self.record(format!("add_external_animal:{}", animal.name))?;
+            animal.validate().map_err(|e| AppError::api(400, e))?;
+            let name = animal.name.trim();
+            let taken = self.goats.borrow().iter().any(|g| g.name == name)
+                || self.external_animals.borrow().iter().any(|a| a.name == name);
+            if taken {
+                return Err(AppError::api(
+                    400,
+                    format!("{} is already the name of a goat or external animal", name),
+                ));
+            }
+            let mut animals = self.external_animals.borrow_mut();
+            let id = animals.iter().filter_map(|a| a.id).max().unwrap_or(0) + 1;
+            let stored = ExternalAnimal {
+                id: Some(id),
+                name: name.to_string(),
+                ..animal.clone()
+            };
+            animals.push(stored.clone());
+            animals.sort_by(|a, b| a.name.cmp(&b.name));
+            Ok(stored)
+        })
+    }
+
+    fn delete_external_animal(&self, id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            self.record(format!("delete_external_animal:{}", id))?;
+            let mut animals = self.external_animals.borrow_mut();
+            let before = animals.len();
+            animals.retain(|a| a.id != Some(id));
+            if animals.len() == before {
+                return Err(AppError::api(
+                    400,
+                    format!("No external animal found with id {}", id),
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    fn genetic_tags(&self) -> ApiFuture<'_, Vec<GeneticTags>> {
+        Box::pin(async move {
+            self.record("genetic_tags".to_string())?;
+            Ok(self.genetic_tags.borrow().clone())
+        })
+    }
+
+    fn save_genetic_tags<'a>(&'a self, tags: &'a GeneticTags) -> ApiFuture<'a, GeneticTags> {
+        Box::pin(async move {
+            self.record(format!(
+                "save_genetic_tags:{}:{}",
+                tags.goat_name,
+                tags.tags.join(",")
+            ))?;
+            let stored = GeneticTags {
+                goat_name: tags.goat_name.clone(),
+                tags: normalize_tags(&tags.tags).map_err(|e| AppError::api(400, e))?,
+            };
+            let mut all = self.genetic_tags.borrow_mut();
+            all.retain(|t| t.goat_name != stored.goat_name);
+            if !stored.tags.is_empty() {
+                all.push(stored.clone());
+            }
+            Ok(stored)
+        })
+    }
+
+    fn pedigree_tree<'a>(&'a self, name: &'a str) -> ApiFuture<'a, PedigreeNode> {
+        Box::pin(async move {
+            self.record(format!("pedigree_tree:{}", name))?;
+            self.pedigrees
+                .borrow()
+                .iter()
+                .find(|p| p.name == name)
+                .cloned()
+                .ok_or_else(|| AppError::api(400, format!("No goat found with name {}", name)))
+        })
+    }
+
+    // Every goat form loads the catalog, so it is neither recorded in
+    // `calls` nor consumes a failure queued for the form's own request.
+    fn breed_catalog(&self) -> ApiFuture<'_, Vec<CatalogBreed>> {
+        Box::pin(async move {
+            let mut catalog = builtin_catalog();
+            catalog.extend(self.added_breeds.borrow().iter().cloned());
+            Ok(catalog)
+        })
+    }
+
+    fn growth_benchmarks(&self) -> ApiFuture<'_, Vec<GrowthBenchmark>> {
+        Box::pin(async move {
+            self.record("growth_benchmarks".to_string())?;
+            Ok(self.benchmarks.borrow().clone())
+        })
+    }
+
+    fn weight_history<'a>(&'a self, name: &'a str) -> ApiFuture<'a, GrowthHistory> {
+        Box::pin(async move {
+            self.record(format!("weight_history:{}", name))?;
+            self.histories
+                .borrow()
+                .iter()
+                .find(|h| h.goat_name == name)
+                .cloned()
+                .ok_or_else(|| AppError::api(400, format!("No goat found with name {}", name)))
+        })
+    }
+
+    fn add_weight<'a>(&'a self, record: &'a WeightRecord) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            self.record(format!("add_weight:{}:{}", record.goat_name, record.weight))?;
+            let goats = self.goats.borrow();
+            if !goats.iter().any(|g| g.name == record.goat_name) {
+                return Err(AppError::api(
+                    400,
+                    format!("No goat found with name {}", record.goat_name),
+                ));
+            }
+            self.weights.borrow_mut().push(record.clone());
+            Ok(())
+        })
+    }
+
+    fn add_milk_record<'a>(&'a self, record: &'a MilkRecord) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            self.record(format!(
+                "add_milk_record:{}:{}",
+                record.doe_name, record.yield_litres
+            ))?;
+            let goats = self.goats.borrow();
+            match goats.iter().find(|g| g.name == record.doe_name) {
+                Some(g) if g.gender == Gender::Female => {}
+                Some(_) => {
+                    return Err(AppError::api(
+                        400,
+                        format!("Goat {} is Male, expected Female", record.doe_name),
+                    ));
+                }
+                None => {
+                    return Err(AppError::api(
+                        400,
+                        format!("No goat found with name {}", record.doe_name),
+                    ));
+                }
+            }
+            self.milk_records.borrow_mut().push(record.clone());
+            Ok(())
+        })
+    }
+
+    fn estimate_weight<'a>(
+        &'a self,
+        photo: &'a [u8],
+        content_type: &'a str,
+        reference_cm: f64,
+    ) -> ApiFuture<'a, WeightEstimate> {
+        Box::pin(async move {
+            self.record(format!(
+                "estimate_weight:{}:{}:{}",
+                photo.len(),
+                content_type,
+                reference_cm
+            ))?;
+            self.weight_estimate.borrow().clone().ok_or_else(|| {
+                AppError::api(
+                    400,
+                    "No weight estimation service is configured".to_string(),
+                )
+            })
+        })
+    }
+
+    fn lactations<'a>(&'a self, doe: Option<&'a str>) -> ApiFuture<'a, Vec<Lactation>> {
+        Box::pin(async move {
+            self.record(format!("lactations:{}", doe.unwrap_or("")))?;
+            Ok(self
+                .lactations
+                .borrow()
+                .iter()
+                .filter(|l| doe.is_none_or(|name| l.doe_name == name))
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn feed_efficiency(&self) -> ApiFuture<'_, FeedEfficiencyReport> {
+        Box::pin(async move {
+            self.record("feed_efficiency".to_string())?;
+            Ok(self.feed_efficiency.borrow().clone())
+        })
+    }
+
+    fn spaces(&self) -> ApiFuture<'_, Vec<Space>> {
+        Box::pin(async move {
+            self.record("spaces".to_string())?;
+            Ok(self.spaces.borrow().clone())
+        })
+    }
+
+    fn add_space<'a>(&'a self, space: &'a Space) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            self.record(format!("add_space:{}", space.name))?;
+            if space.name.trim().is_empty() {
+                return Err(AppError::api(400, "Space name must not be empty".to_string()));
+            }
+            self.spaces.borrow_mut().push(space.clone());
+            Ok(())
+        })
+    }
+
+    fn space_occupancy(&self) -> ApiFuture<'_, Vec<SpaceOccupancy>> {
+        Box::pin(async move {
+            self.record("space_occupancy".to_string())?;
+            Ok(self.occupancy.borrow().clone())
+        })
+    }
+
+    fn health_heatmap<'a>(
+        &'a self,
+        from: Option<&'a str>,
+        to: Option<&'a str>,
+    ) -> ApiFuture<'a, HealthHeatMap> {
+        Box::pin(async move {
+            self.record(format!(
+                "health_heatmap:{}..{}",
+                from.unwrap_or(""),
+                to.unwrap_or("")
+            ))?;
+            Ok(self.heatmap.borrow().clone())
+        })
+    }
+
+    fn vet_visits(&self) -> ApiFuture<'_, Vec<VetVisit>> {
+        Box::pin(async move {
+            self.record("vet_visits".to_string())?;
+            Ok(self.vet_visits.borrow().clone())
+        })
+    }
+
+    fn request_vet_visit<'a>(&'a self, request: &'a VisitRequest) -> ApiFuture<'a, VetVisit> {
+        Box::pin(async move {
+            self.record(format!("request_vet_visit:{}", request.reason))?;
+            request.validate().map_err(|e| AppError::api(400, e))?;
+            let mut visits = self.vet_visits.borrow_mut();
+            let vet_name = request.vet_id.and_then(|id| {
+                self.vets.borrow().iter().find(|v| v.vet_id == id).map(|v| v.vet_name.clone())
+            });
+            let visit = VetVisit {
+                id: visits.iter().map(|v| v.id).max().unwrap_or(0) + 1,
+                status: if request.scheduled_for.is_some() {
+                    VisitStatus::Scheduled
+                } else {
+                    VisitStatus::Requested
+                },
+                reason: request.reason.trim().to_string(),
+                agenda: request.agenda.clone(),
+                vet_id: request.vet_id,
+                vet_name,
+                requested_on: Utc::now().format("%Y-%m-%d").to_string(),
+                scheduled_for: request.scheduled_for.clone(),
+                completed_on: None,
+                summary: None,
+                treated: Vec::new(),
+            };
+            visits.insert(0, visit.clone());
+            Ok(visit)
+        })
+    }
+
+    fn vets(&self) -> ApiFuture<'_, Vec<VetAvailability>> {
+        Box::pin(async move {
+            self.record("vets".to_string())?;
+            Ok(self.vets.borrow().clone())
+        })
+    }
+
+    fn schedule_vet_visit<'a>(
+        &'a self,
+        id: i64,
+        schedule: &'a VisitSchedule,
+    ) -> ApiFuture<'a, VetVisit> {
+        Box::pin(async move {
+            self.record(format!(
+                "schedule_vet_visit:{}:{}:{}",
+                id, schedule.vet_id, schedule.scheduled_for
+            ))?;
+            let mut vets = self.vets.borrow_mut();
+            let vet = vets
+                .iter_mut()
+                .find(|v| v.vet_id == schedule.vet_id)
+                .ok_or_else(|| {
+                    AppError::api(400, format!("No worker found with ID {}", schedule.vet_id))
+                })?;
+            if !vet.is_free(&schedule.scheduled_for) {
+                return Err(AppError::api(
+                    400,
+                    format!("{} is already booked on {}", vet.vet_name, schedule.scheduled_for),
+                ));
+            }
+            let mut visits = self.vet_visits.borrow_mut();
+            let visit = visits
+                .iter_mut()
+                .find(|v| v.id == id)
+                .ok_or_else(|| AppError::api(400, format!("No vet visit found with ID {}", id)))?;
+            vet.booked.push(schedule.scheduled_for.clone());
+            visit.status = VisitStatus::Scheduled;
+            visit.vet_id = Some(vet.vet_id);
+            visit.vet_name = Some(vet.vet_name.clone());
+            visit.scheduled_for = Some(schedule.scheduled_for.clone());
+            Ok(visit.clone())
+        })
+    }
+
+    fn complete_vet_visit<'a>(
+        &'a self,
+        id: i64,
+        summary: &'a VisitSummary,
+    ) -> ApiFuture<'a, VetVisit> {
+        Box::pin(async move {
+            self.record(format!("complete_vet_visit:{}", id))?;
+            summary.validate().map_err(|e| AppError::api(400, e))?;
+            let mut visits = self.vet_visits.borrow_mut();
+            let visit = visits
+                .iter_mut()
+                .find(|v| v.id == id)
+                .ok_or_else(|| AppError::api(400, format!("No vet visit found with ID {}", id)))?;
+            if visit.status != VisitStatus::Scheduled {
+                return Err(AppError::api(
+                    400,
+                    format!("Vet visit #{} has not been scheduled", id),
+                ));
+            }
+            visit.status = VisitStatus::Completed;
+            visit.completed_on = Some(Utc::now().format("%Y-%m-%d").to_string());
+            visit.summary = Some(summary.summary.trim().to_string());
+            visit.treated = summary.findings.iter().map(|f| f.goat_name.clone()).collect();
+            Ok(visit.clone())
+        })
+    }
+
+    fn health_timeline<'a>(&'a self, goat_name: &'a str) -> ApiFuture<'a, Vec<TimelineEntry>> {
+        Box::pin(async move {
+            self.record(format!("health_timeline:{}", goat_name))?;
+            Ok(self.timeline.borrow().clone())
+        })
+    }
+
+    fn lab_results<'a>(&'a self, goat_name: &'a str) -> ApiFuture<'a, Vec<LabResult>> {
+        Box::pin(async move {
+            self.record(format!("lab_results:{}", goat_name))?;
+            Ok(self
+                .lab_results
+                .borrow()
+                .iter()
+                .filter(|r| r.goat_name == goat_name)
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn add_lab_result<'a>(&'a self, result: &'a LabResult) -> ApiFuture<'a, LabResult> {
+        Box::pin(async move {
+            self.record(format!("add_lab_result:{}:{}", result.goat_name, result.test_type))?;
+            result.validate().map_err(|e| AppError::api(400, e))?;
+            let mut results = self.lab_results.borrow_mut();
+            let mut stored = result.clone();
+            stored.id = Some(results.iter().filter_map(|r| r.id).max().unwrap_or(0) + 1);
+            stored.has_report = false;
+            stored.apply_reference_ranges();
+            results.insert(0, stored.clone());
+            Ok(stored)
+        })
+    }
+
+    fn upload_lab_report<'a>(&'a self, id: i64, report: &'a [u8]) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            self.record(format!("upload_lab_report:{}:{}", id, report.len()))?;
+            check_report("application/pdf", report.len()).map_err(|e| AppError::api(400, e))?;
+            let mut results = self.lab_results.borrow_mut();
+            let result = results
+                .iter_mut()
+                .find(|r| r.id == Some(id))
+                .ok_or_else(|| AppError::api(400, format!("No lab result found with ID {}", id)))?;
+            result.has_report = true;
+            Ok(())
+        })
+    }
+
+    fn diseases(&self) -> ApiFuture<'_, Vec<CatalogDisease>> {
+        Box::pin(async move {
+            self.record("diseases".to_string())?;
+            Ok(self.diseases.borrow().clone())
+        })
+    }
+
+    fn add_disease<'a>(&'a self, disease: &'a NewDisease) -> ApiFuture<'a, CatalogDisease> {
+        Box::pin(async move {
+            self.record(format!("add_disease:{}:{}", disease.name, disease.notifiable))?;
+            let name = disease.name.trim();
+            if name.is_empty() {
+                return Err(AppError::api(400, "Disease name must not be empty"));
+            }
+            let mut diseases = self.diseases.borrow_mut();
+            if diseases.iter().any(|d| d.name.eq_ignore_ascii_case(name)) {
+                return Err(AppError::api(400, format!("{} is already in the catalog", name)));
+            }
+            let stored = CatalogDisease {
+                id: diseases.iter().map(|d| d.id).max().unwrap_or(0) + 1,
+                name: name.to_string(),
+                notifiable: disease.notifiable,
+                goat_count: 0,
+            };
+            diseases.push(stored.clone());
+            diseases.sort_by_key(|d| d.name.to_lowercase());
+            Ok(stored)
+        })
+    }
+
+    fn set_disease_notifiable(&self, id: i64, notifiable: bool) -> ApiFuture<'_, CatalogDisease> {
+        Box::pin(async move {
+            self.record(format!("set_disease_notifiable:{}:{}", id, notifiable))?;
+            let mut diseases = self.diseases.borrow_mut();
+            let disease = diseases
+                .iter_mut()
+                .find(|d| d.id == id)
+                .ok_or_else(|| AppError::api(400, format!("No disease found with ID {}", id)))?;
+            disease.notifiable = notifiable;
+            Ok(disease.clone())
+        })
+    }
+
+    fn compliance_report(&self) -> ApiFuture<'_, ComplianceReport> {
+        Box::pin(async move {
+            self.record("compliance_report".to_string())?;
+            Ok(self.compliance.borrow().clone())
+        })
+    }
+
+    fn movements<'a>(
+        &'a self,
+        goat_name: Option<&'a str>,
+        from: Option<&'a str>,
+        to: Option<&'a str>,
+    ) -> ApiFuture<'a, Vec<AnimalMovement>> {
+        Box::pin(async move {
+            self.record(format!(
+                "movements:{}:{}..{}",
+                goat_name.unwrap_or(""),
+                from.unwrap_or(""),
+                to.unwrap_or("")
+            ))?;
+            Ok(self
+                .movements
+                .borrow()
+                .iter()
+                .filter(|m| goat_name.is_none_or(|g| m.goats.iter().any(|n| n == g)))
+                .filter(|m| from.is_none_or(|from| m.departed_on.as_str() >= from))
+                .filter(|m| to.is_none_or(|to| m.departed_on.as_str() <= to))
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn add_movement<'a>(&'a self, movement: &'a AnimalMovement) -> ApiFuture<'a, AnimalMovement> {
+        Box::pin(async move {
+            self.record(format!("add_movement:{}", movement.goats.join(",")))?;
+            movement.validate().map_err(|e| AppError::api(400, e))?;
+            let mut movements = self.movements.borrow_mut();
+            let stored = AnimalMovement {
+                id: Some(movements.len() as i64 + 1),
+                ..movement.clone()
+            };
+            movements.push(stored.clone());
+            movements.sort_by(|a, b| b.departed_on.cmp(&a.departed_on));
+            Ok(stored)
+        })
+    }
+
+    fn traceability<'a>(&'a self, goat_name: &'a str) -> ApiFuture<'a, Vec<TraceabilityRecord>> {
+        Box::pin(async move {
+            self.record(format!("traceability:{}", goat_name))?;
+            Ok(self.traceability.borrow().clone())
+        })
+    }
+
+    fn goat_scores<'a>(&'a self, weights: &'a ScoreWeights) -> ApiFuture<'a, Vec<GoatScore>> {
+        Box::pin(async move {
+            self.record(format!(
+                "goat_scores:{},{},{},{},{}",
+                weights.milk, weights.growth, weights.fertility, weights.health, weights.profit
+            ))?;
+            Ok(self.scores.borrow().clone())
+        })
+    }
+
+    fn formulate_ration<'a>(&'a self, request: &'a RationRequest) -> ApiFuture<'a, RationPlan> {
+        Box::pin(async move {
+            self.record(format!("formulate_ration:{},{}", request.goats, request.days))?;
+            request.validate().map_err(|e| AppError::api(400, e))?;
+            self.ration
+                .borrow()
+                .clone()
+                .ok_or_else(|| AppError::api(400, "No feed in stock can be used for a ration"))
+        })
+    }
+
+    fn scale_readings(&self, since_id: Option<i64>) -> ApiFuture<'_, Vec<ScaleReading>> {
+        Box::pin(async move {
+            self.record(format!(
+                "scale_readings:{}",
+                since_id.map(|id| id.to_string()).unwrap_or_default()
+            ))?;
+            Ok(self
+                .readings
+                .borrow()
+                .iter()
+                .filter(|r| since_id.is_none_or(|since| r.id > since))
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn assign_reading<'a>(
+        &'a self,
+        id: i64,
+        goat_name: &'a str,
+    ) -> ApiFuture<'a, Vec<ScaleReading>> {
+        Box::pin(async move {
+            self.record(format!("assign_reading:{}:{}", id, goat_name))?;
+            let mut readings = self.readings.borrow_mut();
+            let tag_id = readings
+                .iter()
+                .find(|r| r.id == id)
+                .map(|r| r.tag_id.clone())
+                .ok_or_else(|| {
+                    AppError::api(400, format!("No scale reading found with ID {}", id))
+                })?;
+            let mut matched = Vec::new();
+            for reading in readings
+                .iter_mut()
+                .filter(|r| r.tag_id == tag_id && r.goat_name.is_none())
+            {
+                reading.goat_name = Some(goat_name.to_string());
+                reading.weight_record_id = Some(reading.id);
+                matched.push(reading.clone());
+            }
+            Ok(matched)
+        })
+    }
+
+    fn sensor_conditions(&self) -> ApiFuture<'_, Vec<SensorCondition>> {
+        Box::pin(async move {
+            self.record("sensor_conditions".to_string())?;
+            Ok(self.conditions.borrow().clone())
+        })
+    }
+
+    fn pen_water(&self) -> ApiFuture<'_, Vec<PenWater>> {
+        Box::pin(async move {
+            self.record("pen_water".to_string())?;
+            Ok(self.water.borrow().clone())
+        })
+    }
+
+    fn mark_trough_cleaned(&self, space_id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            self.record(format!("mark_trough_cleaned:{}", space_id))?;
+            let mut pens = self.water.borrow_mut();
+            let pen = pens.iter_mut().find(|p| p.space_id == space_id).ok_or_else(|| {
+                AppError::api(400, format!("No space found with ID {}", space_id))
+            })?;
+            pen.last_cleaned = Some(Utc::now().format("%Y-%m-%d").to_string());
+            pen.cleaning_task_id = None;
+            Ok(())
+        })
+    }
+
+    fn heat_predictions(&self) -> ApiFuture<'_, Vec<HeatPrediction>> {
+        Box::pin(async move {
+            self.record("heat_predictions".to_string())?;
+            Ok(self.heat_predictions.borrow().clone())
+        })
+    }
+
+    fn goat_positions(&self) -> ApiFuture<'_, Vec<GoatPosition>> {
+        Box::pin(async move {
+            self.record("goat_positions".to_string())?;
+            Ok(self.positions.borrow().clone())
+        })
+    }
+
+    fn geofences(&self) -> ApiFuture<'_, Vec<Geofence>> {
+        Box::pin(async move {
+            self.record("geofences".to_string())?;
+            Ok(self.geofences.borrow().clone())
+        })
+    }
+
+    fn paddocks(&self) -> ApiFuture<'_, Vec<Paddock>> {
+        Box::pin(async move {
+            self.record("paddocks".to_string())?;
+            Ok(self.paddocks.borrow().clone())
+        })
+    }
+
+    fn add_paddock<'a>(&'a self, paddock: &'a Paddock) -> ApiFuture<'a, Paddock> {
+        Box::pin(async move {
+            self.record(format!(
+                "add_paddock:{}:{}",
+                paddock.name, paddock.rest_days
+            ))?;
+            paddock.validate().map_err(|e| AppError::api(400, e))?;
+            let mut paddocks = self.paddocks.borrow_mut();
+            if paddocks
+                .iter()
+                .any(|p| p.name.eq_ignore_ascii_case(&paddock.name))
+            {
+                return Err(AppError::api(
+                    400,
+                    format!("A paddock named {} already exists", paddock.name),
+                ));
+            }
+            let stored = Paddock {
+                id: Some(paddocks.len() as i64 + 1),
+                ..paddock.clone()
+            };
+            paddocks.push(stored.clone());
+            Ok(stored)
+        })
+    }
+
+    fn rotation_plan<'a>(
+        &'a self,
+        start: Option<&'a str>,
+        weeks: u32,
+    ) -> ApiFuture<'a, RotationPlan> {
+        Box::pin(async move {
+            self.record(format!("rotation_plan:{}:{}", start.unwrap_or(""), weeks))?;
+            Ok(self.rotation_plan.borrow().clone())
+        })
+    }
+
+    fn goat_notes<'a>(&'a self, goat_name: &'a str) -> ApiFuture<'a, Vec<GoatNote>> {
+        Box::pin(async move {
+            self.record(format!("goat_notes:{}", goat_name))?;
+            Ok(self
+                .notes
+                .borrow()
+                .iter()
+                .filter(|n| n.goat_name == goat_name)
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn add_note<'a>(&'a self, note: &'a NoteInput) -> ApiFuture<'a, GoatNote> {
+        Box::pin(async move {
+            self.record(format!("add_note:{}:{}", note.goat_name, note.author))?;
+            note.validate().map_err(|e| AppError::api(400, e))?;
+            let mut notes = self.notes.borrow_mut();
+            let stored = GoatNote {
+                id: notes.len() as i64 + 1,
+                goat_name: note.goat_name.trim().to_string(),
+                author: note.author.trim().to_string(),
+                text: note.text.trim().to_string(),
+                created_at: Utc::now(),
+                has_photo: false,
+            };
+            notes.push(stored.clone());
+            Ok(stored)
+        })
+    }
+
+    fn attach_note_photo<'a>(
+        &'a self,
+        id: i64,
+        photo: &'a [u8],
+        content_type: &'a str,
+    ) -> ApiFuture<'a, GoatNote> {
+        Box::pin(async move {
+            self.record(format!(
+                "attach_note_photo:{}:{}:{}",
+                id,
+                photo.len(),
+                content_type
+            ))?;
+            let mut notes = self.notes.borrow_mut();
+            let note = notes
+                .iter_mut()
+                .find(|n| n.id == id)
+                .ok_or_else(|| AppError::api(400, format!("No note found with ID {}", id)))?;
+            note.has_photo = true;
+            Ok(note.clone())
+        })
+    }
+
+    fn delete_note(&self, id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            self.record(format!("delete_note:{}", id))?;
+            let mut notes = self.notes.borrow_mut();
+            let before = notes.len();
+            notes.retain(|n| n.id != id);
+            if notes.len() == before {
+                return Err(AppError::api(400, format!("No note found with ID {}", id)));
+            }
+            Ok(())
+        })
+    }
+
+    fn attachments<'a>(&'a self, target: &'a AttachmentTarget) -> ApiFuture<'a, Vec<Attachment>> {
+        Box::pin(async move {
+            let (key, value) = target.query();
+            self.record(format!("attachments:{}={}", key, value))?;
+            Ok(self
+                .attachments
+                .borrow()
+                .iter()
+                .filter(|a| match target {
+                    AttachmentTarget::Goat(name) => a.goat_name.as_ref() == Some(name),
+                    AttachmentTarget::Task(id) => a.task_id == Some(*id),
+                })
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn add_voice_note<'a>(
+        &'a self,
+        target: &'a AttachmentTarget,
+        author: &'a str,
+        duration_secs: f64,
+        recording: &'a [u8],
+        content_type: &'a str,
+    ) -> ApiFuture<'a, Attachment> {
+        Box::pin(async move {
+            let (key, value) = target.query();
+            self.record(format!(
+                "add_voice_note:{}={}:{}:{}:{}",
+                key,
+                value,
+                author,
+                recording.len(),
+                content_type
+            ))?;
+            check_voice_note(content_type, duration_secs, recording.len())
+                .map_err(|e| AppError::api(400, e))?;
+            let mut attachments = self.attachments.borrow_mut();
+            let (goat_name, task_id) = match target {
+                AttachmentTarget::Goat(name) => (Some(name.clone()), None),
+                AttachmentTarget::Task(id) => (None, Some(*id)),
+            };
+            let stored = Attachment {
+                id: attachments.len() as i64 + 1,
+                goat_name,
+                task_id,
+                author: author.to_string(),
+                content_type: content_type.to_string(),
+                duration_secs: Some(duration_secs),
+                size_bytes: recording.len() as i64,
+                created_at: Utc::now(),
+            };
+            attachments.push(stored.clone());
+            Ok(stored)
+        })
+    }
+
+    fn delete_attachment(&self, id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            self.record(format!("delete_attachment:{}", id))?;
+            let mut attachments = self.attachments.borrow_mut();
+            let before = attachments.len();
+            attachments.retain(|a| a.id != id);
+            if attachments.len() == before {
+                return Err(AppError::api(
+                    400,
+                    format!("No attachment found with ID {}", id),
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    fn unread_notifications<'a>(&'a self, worker: &'a str) -> ApiFuture<'a, Vec<Notification>> {
+        Box::pin(async move {
+            self.record(format!("unread_notifications:{}", worker))?;
+            Ok(self
+                .notifications
+                .borrow()
+                .iter()
+                .filter(|n| n.worker_name.eq_ignore_ascii_case(worker) && !n.read)
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn mark_notification_read(&self, id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            self.record(format!("mark_notification_read:{}", id))?;
+            let mut notifications = self.notifications.borrow_mut();
+            let notification = notifications
+                .iter_mut()
+                .find(|n| n.id == id)
+                .ok_or_else(|| {
+                    AppError::api(400, format!("No notification found with ID {}", id))
+                })?;
+            notification.read = true;
+            Ok(())
+        })
+    }
+
+    fn budget_report<'a>(&'a self, month: Option<&'a str>) -> ApiFuture<'a, BudgetReport> {
+        Box::pin(async move {
+            self.record(format!("budget_report:{}", month.unwrap_or("")))?;
+            Ok(self.budget_report.borrow().clone())
+        })
+    }
+
+    fn set_budget<'a>(&'a self, budget: &'a Budget) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            self.record(format!(
+                "set_budget:{}:{}",
+                FinanceCategory::to_str(&budget.category),
+                budget.monthly_amount
+            ))?;
+            self.budgets.borrow_mut().push(budget.clone());
+            Ok(())
+        })
+    }
+
+    fn farm_settings(&self) -> ApiFuture<'_, FarmSettings> {
+        Box::pin(async move {
+            self.record("farm_settings".to_string())?;
+            Ok(self.settings.borrow().clone())
+        })
+    }
+
+    fn update_settings<'a>(&'a self, settings: &'a FarmSettings) -> ApiFuture<'a, FarmSettings> {
+        Box::pin(async move {
+            self.record("update_settings".to_string())?;
+            validate_language(&settings.language).map_err(|e| AppError::api(400, e))?;
+            settings
+                .retention
+                .validate()
+                .map_err(|e| AppError::api(400, e))?;
+            if let Some(pricing) = &settings.pricing {
+                pricing
+                    .compile()
+                    .map_err(|e| AppError::api(400, format!("Pricing formula: {}", e)))?;
+            }
+            if let Some(series) = &settings.tag_series {
+                series.validate().map_err(|e| AppError::api(400, e))?;
+            }
+            let read_only = self.settings.borrow().read_only;
+            *self.settings.borrow_mut() = FarmSettings {
+                read_only,
+                ..settings.clone()
+            };
+            Ok(self.settings.borrow().clone())
+        })
+    }
+
+    fn set_read_only(&self, mode: ReadOnlyMode) -> ApiFuture<'_, FarmSettings> {
+        Box::pin(async move {
+            self.record(format!("set_read_only:{}", mode.read_only))?;
+            self.settings.borrow_mut().read_only = mode.read_only;
+            Ok(self.settings.borrow().clone())
+        })
+    }
+
+    fn apply_pricing(&self) -> ApiFuture<'_, Vec<GoatValuation>> {
+        Box::pin(async move {
+            self.record("apply_pricing".to_string())?;
+            let pricing = self
+                .settings
+                .borrow()
+                .pricing
+                .clone()
+                .ok_or_else(|| AppError::api(400, "No pricing formula is set".to_string()))?;
+            let valuations = pricing
+                .value_herd(&self.goats())
+                .map_err(|e| AppError::api(400, e))?;
+            for (goat, valuation) in self.goats.borrow_mut().iter_mut().zip(&valuations) {
+                if let Some(price) = valuation.formula_price {
+                    goat.current_price = price;
+                }
+            }
+            Ok(valuations)
+        })
+    }
+
+    fn dashboard_stats(&self) -> ApiFuture<'_, DashboardStats> {
+        Box::pin(async move {
+            self.record("dashboard_stats".to_string())?;
+            Ok(self.stats.borrow().clone())
+        })
+    }
+
+    fn search<'a>(&'a self, query: &'a str) -> ApiFuture<'a, Vec<SearchResult>> {
+        Box::pin(async move {
+            self.record(format!("search:{}", query))?;
+            let lowered = query.to_lowercase();
+            let results = self.search_results.borrow();
+            let mut hits: Vec<SearchResult> = results
+                .iter()
+                .filter(|r| r.title.to_lowercase().contains(&lowered))
+                .cloned()
+                .collect();
+            // Like the backend, suggest similar goat names if no goat matched
+            if !hits.iter().any(|r| r.kind == SearchKind::Goat) {
+                let goats: Vec<&SearchResult> = results
+                    .iter()
+                    .filter(|r| r.kind == SearchKind::Goat)
+                    .collect();
+                let names = suggest_names(query, goats.iter().map(|g| g.title.as_str()));
+                let suggestions = names
+                    .iter()
+                    .filter_map(|name| goats.iter().find(|g| &g.title == name))
+                    .map(|g| SearchResult {
+                        suggested: true,
+                        ..(*g).clone()
+                    });
+                hits.splice(0..0, suggestions);
+            }
+            Ok(hits)
+        })
+    }
+
+    fn recent_activity(&self) -> ApiFuture<'_, Vec<ActivityEvent>> {
+        Box::pin(async move {
+            self.record("recent_activity".to_string())?;
+            Ok(self.activity.borrow().clone())
+        })
+    }
+
+    fn data_health(&self) -> ApiFuture<'_, DataHealthReport> {
+        Box::pin(async move {
+            self.record("data_health".to_string())?;
+            Ok(self.data_health.borrow().clone())
+        })
+    }
+
+    fn tasks(&self) -> ApiFuture<'_, Vec<Task>> {
+        Box::pin(async move {
+            self.record("tasks".to_string())?;
+            Ok(self.tasks.borrow().clone())
+        })
+    }
+
+    fn transactions(&self) -> ApiFuture<'_, Vec<Transaction>> {
+        Box::pin(async move {
+            self.record("transactions".to_string())?;
+            Ok(self.transactions.borrow().clone())
+        })
+    }
+
+    fn inventory_items(&self) -> ApiFuture<'_, Vec<InventoryItem>> {
+        Box::pin(async move {
+            self.record("inventory_items".to_string())?;
+            Ok(self.inventory.borrow().clone())
+        })
+    }
+
+    fn expiring_stock(&self) -> ApiFuture<'_, Vec<ExpiringStock>> {
+        Box::pin(async move {
+            self.record("expiring_stock".to_string())?;
+            Ok(self.expiring.borrow().clone())
+        })
+    }
+
+    fn dispose_item(&self, id: i64) -> ApiFuture<'_, Disposal> {
+        Box::pin(async move {
+            self.record(format!("dispose_item:{}", id))?;
+            let mut expiring = self.expiring.borrow_mut();
+            let index = expiring
+                .iter()
+                .position(|e| e.item.id == Some(id))
+                .ok_or_else(|| AppError::api(400, format!("No item found with id {}", id)))?;
+            let stock = expiring.remove(index);
+            if let Some(item) = self
+                .inventory
+                .borrow_mut()
+                .iter_mut()
+                .find(|i| i.id == Some(id))
+            {
+                item.quantity = 0.0;
+            }
+            Ok(Disposal {
+                item_id: id,
+                item_name: stock.item.name,
+                quantity: stock.item.quantity,
+                unit: stock.item.unit,
+                value: stock.value,
+                consumption_id: id,
+                transaction_id: (stock.value > 0.0).then_some(id),
+            })
+        })
+    }
+
+    fn jobs(&self) -> ApiFuture<'_, Vec<Job>> {
+        Box::pin(async move {
+            self.record("jobs".to_string())?;
+            Ok(self.jobs.borrow().clone())
+        })
+    }
+
+    fn job(&self, id: i64) -> ApiFuture<'_, Job> {
+        Box::pin(async move {
+            self.record(format!("job:{}", id))?;
+            self.jobs
+                .borrow()
+                .iter()
+                .find(|j| j.id == id)
+                .cloned()
+                .ok_or_else(|| AppError::api(400, format!("No job found with id {}", id)))
+        })
+    }
+
+    /// Adds the goats at once and returns the job as already succeeded.
+    fn start_import_job<'a>(&'a self, goats: &'a [NewGoat]) -> ApiFuture<'a, Job> {
+        Box::pin(async move {
+            self.record(format!("start_import_job:{}", goats.len()))?;
+            for goat in goats {
+                self.store_goat(goat);
+            }
+            let mut jobs = self.jobs.borrow_mut();
+            let now = Utc::now();
+            let job = Job {
+                id: jobs.iter().map(|j| j.id).max().unwrap_or(0) + 1,
+                kind: JobKind::Import,
+                status: JobStatus::Succeeded,
+                description: format!("Import {} goats", goats.len()),
+                done: goats.len() as u32,
+                total: goats.len() as u32,
+                attempts: 1,
+                error: None,
+                result_name: None,
+                created_at: now,
+                updated_at: now,
+            };
+            jobs.insert(0, job.clone());
+            Ok(job)
+        })
+    }
+
+    fn start_cleanup_job(&self) -> ApiFuture<'_, Job> {
+        Box::pin(async move {
+            self.record("start_cleanup_job".to_string())?;
+            let purged = std::mem::take(&mut self.retention.borrow_mut().trash).len() as u32;
+            let mut jobs = self.jobs.borrow_mut();
+            let now = Utc::now();
+            let job = Job {
+                id: jobs.iter().map(|j| j.id).max().unwrap_or(0) + 1,
+                kind: JobKind::Cleanup,
+                status: JobStatus::Succeeded,
+                description: "Purge expired trash and log entries".to_string(),
+                done: purged,
+                total: purged,
+                attempts: 1,
+                error: None,
+                result_name: None,
+                created_at: now,
+                updated_at: now,
+            };
+            jobs.insert(0, job.clone());
+            Ok(job)
+        })
+    }
+
+    fn retention(&self) -> ApiFuture<'_, UpcomingPurges> {
+        Box::pin(async move {
+            self.record("retention".to_string())?;
+            Ok(UpcomingPurges {
+                policy: self.settings.borrow().retention,
+                ..self.retention.borrow().clone()
+            })
+        })
+    }
+
+    fn restore_goat(&self, id: i64) -> ApiFuture<'_, Goat> {
+        Box::pin(async move {
+            self.record(format!("restore_goat:{}", id))?;
+            let mut retention = self.retention.borrow_mut();
+            let Some(index) = retention.trash.iter().position(|t| t.id == id) else {
+                return Err(AppError::api(400, format!("No deleted goat found with ID {}", id)));
+            };
+            let trashed = retention.trash.remove(index);
+            self.goats.borrow_mut().push(trashed.goat.clone());
+            Ok(trashed.goat)
+        })
+    }
+
+    fn farm_key(&self) -> ApiFuture<'_, Option<String>> {
+        Box::pin(async move {
+            self.record("farm_key".to_string())?;
+            Ok(self.farm_key.borrow().clone())
+        })
+    }
+
+    fn select_farm<'a>(&'a self, key: Option<&'a str>) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            let key = key.map(str::trim).filter(|k| !k.is_empty());
+            self.record(format!("select_farm:{}", key.unwrap_or_default()))?;
+            *self.farm_key.borrow_mut() = key.map(str::to_string);
+            Ok(())
+        })
+    }
+
+    fn sign_in<'a>(&'a self, new_session: &'a NewSession) -> ApiFuture<'a, IssuedSession> {
+        Box::pin(async move {
+            self.record(format!("sign_in:{}", new_session.device))?;
+            Ok(self.add_session(&new_session.device, new_session.worker.clone()))
+        })
+    }
+
+    fn sessions(&self) -> ApiFuture<'_, Vec<Session>> {
+        Box::pin(async move {
+            self.record("sessions".to_string())?;
+            Ok(self.sessions.borrow().clone())
+        })
+    }
+
+    fn revoke_session(&self, id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            self.record(format!("revoke_session:{}", id))?;
+            let mut sessions = self.sessions.borrow_mut();
+            let before = sessions.len();
+            sessions.retain(|s| s.id != id);
+            if sessions.len() == before {
+                return Err(AppError::api(
+                    400,
+                    format!("No active session found with ID {}", id),
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    fn revoke_other_sessions(&self) -> ApiFuture<'_, usize> {
+        Box::pin(async move {
+            self.record("revoke_other_sessions".to_string())?;
+            let mut sessions = self.sessions.borrow_mut();
+            let before = sessions.len();
+            sessions.retain(|s| s.current);
+            Ok(before - sessions.len())
+        })
+    }
+
+    fn oauth_providers(&self) -> ApiFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            self.record("oauth_providers".to_string())?;
+            Ok(self.oauth_providers.borrow().clone())
+        })
+    }
+
+    fn start_oauth<'a>(
+        &'a self,
+        provider: &'a str,
+        redirect_uri: &'a str,
+        device: &'a str,
+    ) -> ApiFuture<'a, OAuthRedirect> {
+        Box::pin(async move {
+            self.record(format!(
+                "start_oauth:{}:{}:{}",
+                provider, redirect_uri, device
+            ))?;
+            if !self.oauth_providers.borrow().iter().any(|p| p == provider) {
+                return Err(AppError::api(
+                    400,
+                    format!("Signing in with {} is not set up", provider),
+                ));
+            }
+            *self.oauth_device.borrow_mut() = Some(device.to_string());
+            Ok(OAuthRedirect {
+                authorize_url: format!("https://auth.example/{}?state=mock_state", provider),
+            })
+        })
+    }
+
+    /// Only `state` "mock_state" is accepted; the mock signs in the worker
+    /// named by `code`.
+    fn finish_oauth<'a>(&'a self, code: &'a str, state: &'a str) -> ApiFuture<'a, IssuedSession> {
+        Box::pin(async move {
+            self.record(format!("finish_oauth:{}:{}", code, state))?;
+            if state != "mock_state" {
+                return Err(AppError::api(
+                    400,
+                    "This sign-in has expired; start again".to_string(),
+                ));
+            }
+            let device = self.oauth_device.borrow_mut().take().unwrap_or_default();
+            Ok(self.add_session(&device, Some(code.to_string())))
+        })
+    }
+
+    fn role_permissions(&self) -> ApiFuture<'_, Vec<RolePermissions>> {
+        Box::pin(async move {
+            self.record("role_permissions".to_string())?;
+            Ok(self.role_permissions.borrow().clone())
+        })
+    }
+
+    fn update_role_permissions<'a>(
+        &'a self,
+        permissions: &'a RolePermissions,
+    ) -> ApiFuture<'a, RolePermissions> {
+        Box::pin(async move {
+            self.record(format!(
+                "update_role_permissions:{}:{}",
+                permissions.role,
+                permissions.granted.len()
+            ))?;
+            permissions.validate().map_err(|e| AppError::api(400, e))?;
+            let mut stored = permissions.clone();
+            stored.granted.sort();
+            let mut matrix = self.role_permissions.borrow_mut();
+            matrix.retain(|r| r.role != stored.role);
+            matrix.push(stored.clone());
+            matrix.sort_by(|a, b| a.role.cmp(&b.role));
+            Ok(stored)
+        })
+    }
+
+    fn my_permissions(&self) -> ApiFuture<'_, MyPermissions> {
+        Box::pin(async move {
+            self.record("my_permissions".to_string())?;
+            Ok(self
+                .my_permissions
+                .borrow()
+                .clone()
+                .unwrap_or_else(MyPermissions::owner))
+        })
+    }
+
+    fn access_tokens(&self) -> ApiFuture<'_, Vec<AccessToken>> {
+        Box::pin(async move {
+            self.record("access_tokens".to_string())?;
+            Ok(self.access_tokens.borrow().clone())
+        })
+    }
+
+    /// Every token the mock issues is "yagi_mock_token".
+    fn create_access_token<'a>(
+        &'a self,
+        new_token: &'a NewAccessToken,
+    ) -> ApiFuture<'a, IssuedAccessToken> {
+        Box::pin(async move {
+            self.record(format!(
+                "create_access_token:{}:{}:{}",
+                new_token.name,
+                new_token.read_only,
+                new_token.modules.len()
+            ))?;
+            new_token.validate().map_err(|e| AppError::api(400, e))?;
+            let mut tokens = self.access_tokens.borrow_mut();
+            let mut modules = new_token.modules.clone();
+            modules.sort();
+            modules.dedup();
+            let access_token = AccessToken {
+                id: tokens.iter().map(|t| t.id).max().unwrap_or(0) + 1,
+                name: new_token.name.trim().to_string(),
+                read_only: new_token.read_only,
+                modules,
+                worker: None,
+                created_at: Utc::now(),
+                last_used_at: None,
+                revoked: false,
+            };
+            tokens.insert(0, access_token.clone());
+            Ok(IssuedAccessToken {
+                access_token,
+                token: "yagi_mock_token".to_string(),
+            })
+        })
+    }
+
+    fn revoke_access_token(&self, id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            self.record(format!("revoke_access_token:{}", id))?;
+            let mut tokens = self.access_tokens.borrow_mut();
+            let Some(token) = tokens.iter_mut().find(|t| t.id == id && !t.revoked) else {
+                return Err(AppError::api(
+                    400,
+                    format!("No active access token found with ID {}", id),
+                ));
+            };
+            token.revoked = true;
+            Ok(())
+        })
+    }
+
+    fn alert_rules(&self) -> ApiFuture<'_, Vec<AlertRule>> {
+        Box::pin(async move {
+            self.record("alert_rules".to_string())?;
+            Ok(self.alert_rules.borrow().clone())
+        })
+    }
+
+    fn add_alert_rule<'a>(&'a self, rule: &'a AlertRule) -> ApiFuture<'a, AlertRule> {
+        Box::pin(async move {
+            self.record(format!("add_alert_rule:{}", rule.name))?;
+            rule.validate().map_err(|e| AppError::api(400, e))?;
+            let mut rules = self.alert_rules.borrow_mut();
+            let stored = AlertRule {
+                id: Some(rules.iter().filter_map(|r| r.id).max().unwrap_or(0) + 1),
+                name: rule.name.trim().to_string(),
+                firing: false,
+                last_fired_at: None,
+                ..rule.clone()
+            };
+            rules.push(stored.clone());
+            Ok(stored)
+        })
+    }
+
+    fn delete_alert_rule(&self, id: i64) -> ApiFuture<'_, ()> {
+        Box::pin(async move {
+            self.record(format!("delete_alert_rule:{}", id))?;
+            let mut rules = self.alert_rules.borrow_mut();
+            let before = rules.len();
+            rules.retain(|r| r.id != Some(id));
+            if rules.len() == before {
+                return Err(AppError::api(
+                    400,
+                    format!("No alert rule found with id {}", id),
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    fn lifecycle_pipeline(&self) -> ApiFuture<'_, LifecyclePipeline> {
+        Box::pin(async move {
+            self.record("lifecycle_pipeline".to_string())?;
+            Ok(self.lifecycle.borrow().clone())
+        })
+    }
+
+    fn change_lifecycle_stage<'a>(
+        &'a self,
+        change: &'a StageChange,
+    ) -> ApiFuture<'a, StageTransition> {
+        Box::pin(async move {
+            self.record(format!(
+                "change_lifecycle_stage:{}:{:?}",
+                change.goat_name, change.stage
+            ))?;
+            let mut pipeline = self.lifecycle.borrow_mut();
+            let tracked = pipeline
+                .stages
+                .iter_mut()
+                .find(|s| s.goats.iter().any(|g| g.name == change.goat_name));
+            let from = tracked.as_ref().map(|s| s.stage);
+            let current = match tracked {
+                Some(stage) => &mut stage.goats,
+                None => &mut pipeline.untracked,
+            };
+            let Some(i) = current.iter().position(|g| g.name == change.goat_name) else {
+                return Err(AppError::api(
+                    400,
+                    format!("No goat found with name {}", change.goat_name),
+                ));
+            };
+            if !current[i].next.contains(&change.stage) {
+                return Err(AppError::api(400, "The lifecycle does not allow this move"));
+            }
+            let mut goat = current.remove(i);
+            goat.next = change.stage.next_stages().to_vec();
+            if let Some(stage) = pipeline.stages.iter_mut().find(|s| s.stage == change.stage) {
+                stage.goats.push(goat);
+            }
+            Ok(StageTransition {
+                from,
+                to: change.stage,
+                changed_on: change
+                    .changed_on
+                    .clone()
+                    .unwrap_or_else(|| Utc::now().format("%Y-%m-%d").to_string()),
+                note: change.note.clone(),
+            })
+        })
+    }
+
+    fn download<'a>(&'a self, url: &'a str) -> ApiFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            self.record(format!("download:{}", url))?;
+            Ok(b"file".to_vec())
+        })
+    }
+
+    fn export_farm_archive<'a>(&'a self, passphrase: &'a str) -> ApiFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            self.record(format!("export_farm_archive:{}", passphrase))?;
+            Ok(b"YAGIENC1sealed".to_vec())
+        })
+    }
+
+    fn import_farm_archive<'a>(
+        &'a self,
+        archive: &'a [u8],
+        passphrase: Option<&'a str>,
+    ) -> ApiFuture<'a, ArchiveSummary> {
+        Box::pin(async move {
+            self.record(format!(
+                "import_farm_archive:{}:{}",
+                archive.len(),
+                passphrase.unwrap_or("")
+            ))?;
+            Ok(self.archive_summary.borrow().clone())
+        })
+    }
+
+    fn export_templates(&self) -> ApiFuture<'_, Vec<ExportTemplate>> {
+        Box::pin(async move {
+            self.record("export_templates".to_string())?;
+            Ok(self.export_templates.borrow().clone())
+        })
+    }
+
+    fn add_export_template<'a>(
+        &'a self,
+        template: &'a ExportTemplate,
+    ) -> ApiFuture<'a, ExportTemplate> {
+        Box::pin(async move {
+            self.record(format!("add_export_template:{}", template.key))?;
+            template.validate().map_err(|e| AppError::api(400, e))?;
+            let mut templates = self.export_templates.borrow_mut();
+            if templates.iter().any(|t| t.key == template.key) {
+                return Err(AppError::api(
+                    400,
+                    format!("An export template with key {} already exists", template.key),
+                ));
+            }
+            templates.push(template.clone());
+            templates.sort_by(|a, b| a.key.cmp(&b.key));
+            Ok(template.clone())
+        })
+    }
+
+    fn delete_export_template<'a>(&'a self, key: &'a str) -> ApiFuture<'a, ()> {
+        Box::pin(async move {
+            self.record(format!("delete_export_template:{}", key))?;
+            let mut templates = self.export_templates.borrow_mut();
+            let before = templates.len();
+            templates.retain(|t| t.key != key);
+            if templates.len() == before {
+                return Err(AppError::api(
+                    400,
+                    format!("No export template with key {}", key),
+                ));
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,7 @@
+//! Services used by the stores and components to reach the backend.
+
+pub mod api;
+pub mod mock;
+
+pub use api::{Api, ApiClient, ApiProvider, HttpApiClient, use_api};
+pub use mock::MockApiClient;
@@ -1,96 +1,349 @@
 //! Global state management for the goat dashboard app.
 //!
 //! Uses `yewdux` for reactive state updates,
-//! provides asynchronous fetching of goats from backend API,
-//! and implements robust error handling and logging.
+//! provides asynchronous fetching of goats from backend API
+//! (through an `Api` handle, so tests can substitute a mock),
+//! and implements robust error handling and logging. Changes made through
+//! `GoatStore` are kept in an undo history (see `GoatCommand`). `AccessStore` holds
+//! whether the farm is in read-only mode, `PermissionStore` what this
+//! device's session may do, and `UnitsStore` the units values are entered
+//! and shown in and the farm's time zone.
 
 use crate::errors::AppError;
-use gloo_net::http::Request;
+use crate::services::Api;
+use crate::services::api::GoatsFetch;
+use chrono_tz::Tz;
 use log::{error, info, trace, warn};
-use shared::GoatParams;
+use shared::permissions::{MyPermissions, PermissionAction, PermissionModule};
+use shared::settings::{DEFAULT_BASE_CURRENCY, FarmSettings, ReadOnlyMode};
+use shared::units::WeightUnit;
+use shared::{Breed, Goat, GoatParams, GoatUpdate, NewGoat};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 use yewdux::prelude::*;
 
+/// In-flight flags for each store operation, so the UI can show progress
+/// only where it applies instead of blanking the whole dashboard.
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct LoadingState {
+    /// True while the goat list is being fetched
+    pub fetching: bool,
+
+    /// True while a new goat is being submitted
+    pub adding: bool,
+
+    /// True while an updated goat is being submitted
+    pub updating: bool,
+
+    /// Names of goats with a pending delete request
+    pub deleting: HashSet<String>,
+}
+
+impl LoadingState {
+    /// Returns true if a delete request for `name` is in flight.
+    pub fn is_deleting(&self, name: &str) -> bool {
+        self.deleting.contains(name)
+    }
+}
+
+/// Most changes kept in the undo history; older ones are dropped.
+pub const HISTORY_LIMIT: usize = 50;
+
+/// A change made to the goat list, recorded so it can be undone.
+///
+/// Undoing a change runs its `inverse` against the backend: an added goat is
+/// deleted again, a deleted one re-added, and an edit re-applied with the
+/// fields it replaced.
+#[derive(Clone, PartialEq, Debug)]
+pub enum GoatCommand {
+    /// A goat was added, as stored by the backend.
+    Add(Goat),
+    /// Every field of a goat was replaced, matched by name.
+    Update {
+        before: GoatParams,
+        after: GoatParams,
+    },
+    /// Some fields of the goat with `id` were changed.
+    Patch {
+        id: i64,
+        before: GoatParams,
+        after: GoatParams,
+    },
+    /// A goat was deleted; `goat` is its last known state and `trash_id` the
+    /// trash entry it moved to, once the backend has said.
+    Delete { goat: Goat, trash_id: Option<i64> },
+    /// A deleted goat was brought back from trash entry `trash_id`, under
+    /// its own ID.
+    Restore { goat: Goat, trash_id: i64 },
+    /// Several changes made together, e.g. by one bulk delete, in order.
+    Batch(Vec<GoatCommand>),
+}
+
+impl GoatCommand {
+    /// The command that reverses this one.
+    pub fn inverse(&self) -> GoatCommand {
+        match self {
+            GoatCommand::Add(goat) => GoatCommand::Delete {
+                goat: goat.clone(),
+                trash_id: None,
+            },
+            GoatCommand::Delete {
+                goat,
+                trash_id: Some(trash_id),
+            } => GoatCommand::Restore {
+                goat: goat.clone(),
+                trash_id: *trash_id,
+            },
+            // Only a delete not yet carried out has no trash entry
+            GoatCommand::Delete {
+                goat,
+                trash_id: None,
+            } => GoatCommand::Add(goat.clone()),
+            GoatCommand::Restore { goat, trash_id } => GoatCommand::Delete {
+                goat: goat.clone(),
+                trash_id: Some(*trash_id),
+            },
+            GoatCommand::Update { before, after } => GoatCommand::Update {
+                before: after.clone(),
+                after: before.clone(),
+            },
+            GoatCommand::Patch { id, before, after } => GoatCommand::Patch {
+                id: *id,
+                before: after.clone(),
+                after: before.clone(),
+            },
+            GoatCommand::Batch(commands) => {
+                GoatCommand::Batch(commands.iter().rev().map(GoatCommand::inverse).collect())
+            }
+        }
+    }
+
+    /// Short description for the undo and redo buttons, e.g. "delete Rani".
+    pub fn label(&self) -> String {
+        match self {
+            GoatCommand::Add(goat) => format!("add {}", goat.name),
+            GoatCommand::Update { after, .. } => format!("edit {}", after.name),
+            GoatCommand::Patch { after, .. } => format!("edit {}", after.name),
+            GoatCommand::Delete { goat, .. } => format!("delete {}", goat.name),
+            // Redoes an undone add
+            GoatCommand::Restore { goat, .. } => format!("add {}", goat.name),
+            GoatCommand::Batch(commands) if commands.len() == 1 => commands[0].label(),
+            GoatCommand::Batch(commands) => format!("{} changes", commands.len()),
+        }
+    }
+}
+
+/// Undo and redo stacks of `GoatCommand`s, most recent last.
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct History {
+    pub undo: Vec<GoatCommand>,
+    pub redo: Vec<GoatCommand>,
+
+    /// True while an undo or redo is being sent to the backend
+    pub pending: bool,
+
+    /// Contains error message if the last undo or redo failed
+    pub error: Option<String>,
+}
+
+impl History {
+    /// Records a new change. A new change clears the redo stack, and the
+    /// oldest change is dropped past `HISTORY_LIMIT`.
+    fn record(&mut self, command: GoatCommand) {
+        trace!("Recording '{}' in the undo history", command.label());
+        self.undo.push(command);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+}
+
+/// How far a command got when one of its requests failed.
+struct PartialRun {
+    /// The part that was carried out, if any
+    done: Option<GoatCommand>,
+    /// The part that was not, starting with the failed change
+    remaining: GoatCommand,
+    error: AppError,
+}
+
 /// Shared global store for the application's goat data.
 ///
 /// Holds the current list of goats,
-/// the loading state for ongoing fetches,
+/// the loading state of each ongoing operation,
 /// and any error messages from network or parsing failures.
 #[derive(Default, Clone, PartialEq, Store)]
 pub struct GoatStore {
     /// The complete list of goats retrieved from backend
-    pub goats: Vec<GoatParams>,
+    pub goats: Vec<Goat>,
 
-    /// True while data is currently being loaded
-    pub loading: bool,
+    /// Per-operation loading flags
+    pub loading: LoadingState,
 
     /// Contains error message if the last fetch failed
     pub error: Option<String>,
+
+    /// Time of the last successful fetch in milliseconds since the epoch,
+    /// or `None` if the cache has never been filled
+    pub fetched_at: Option<f64>,
+
+    /// ETag of the cached goat list, sent as `If-None-Match` on revalidation
+    pub etag: Option<String>,
+
+    /// Changes that can be undone and redone
+    pub history: History,
 }
 
+/// Age in milliseconds after which cached goats are revalidated on access.
+pub const CACHE_MAX_AGE_MS: f64 = 30_000.0;
+
 impl GoatStore {
-    /// Asynchronously fetches the list of goats from the backend API.
+    /// Returns the goat list using a stale-while-revalidate cache.
     ///
-    /// Issues a GET request to `"http://sample/goats"`.
-    /// Updates the store's `goats`, `loading`, and `error` fields as appropriate.
+    /// Cached goats are served immediately. If they are older than
+    /// `CACHE_MAX_AGE_MS`, a fetch runs in the background and replaces them
+    /// once it completes. An empty cache is filled by streaming, so large
+    /// herds appear chunk by chunk.
     ///
     /// # Arguments
     ///
-    /// * `dinamespatch` - A `Dispatch` handle to the current `GoatStore` state,
-    ///                allowing mutation through yewdux reducers.
+    /// * `api` - Backend client performing the request.
+    /// * `dispatch` - A `Dispatch` handle to the current `GoatStore` state,
+    ///   allowing mutation through yewdux reducers.
+    ///
+    /// # Logging
+    ///
+    /// Logs at trace level when the cache is fresh enough to skip the request.
+    pub fn fetch_goats(api: Api, dispatch: Dispatch<Self>) {
+        let state = dispatch.get();
+        if state.loading.fetching {
+            trace!("Goat fetch already in flight");
+            return;
+        }
+        if let Some(fetched_at) = state.fetched_at {
+            let age = js_sys::Date::now() - fetched_at;
+            if age < CACHE_MAX_AGE_MS {
+                trace!("Serving cached goats ({} ms old)", age);
+                return;
+            }
+            info!("Cached goats are stale ({} ms old), revalidating", age);
+            Self::revalidate(api, dispatch);
+        } else {
+            Self::stream_goats(api, dispatch);
+        }
+    }
+
+    /// Fills the store from the NDJSON goat stream, appending goats to
+    /// the table as each chunk arrives.
+    ///
+    /// # Behavior
+    ///
+    /// - Clears `goats` and sets `loading.fetching` before the request.
+    /// - Stamps `fetched_at` once the stream completes; goats received
+    ///   before a failure are kept and the error is recorded.
+    fn stream_goats(api: Api, dispatch: Dispatch<Self>) {
+        dispatch.reduce_mut(|state| {
+            state.goats.clear();
+            state.etag = None;
+            state.loading.fetching = true;
+            state.error = None;
+        });
+
+        spawn_local(async move {
+            let result = api
+                .stream_goats(&|goats| {
+                    dispatch.reduce_mut(|state| state.goats.extend(goats));
+                })
+                .await;
+            match result {
+                Ok(total) => {
+                    info!("Streamed {} goats", total);
+                    dispatch.reduce_mut(|state| {
+                        state.loading.fetching = false;
+                        state.fetched_at = Some(js_sys::Date::now());
+                    });
+                }
+                Err(err) => {
+                    let err_msg = format!("Failed to stream goats: {}", err);
+                    error!("{}", err_msg);
+                    dispatch.reduce_mut(|state| {
+                        state.loading.fetching = false;
+                        state.error = Some(err_msg);
+                    });
+                }
+            }
+        });
+    }
+
+    /// Fetches the goat list regardless of cache age, e.g. for a Refresh button.
+    pub fn force_refresh(api: Api, dispatch: Dispatch<Self>) {
+        if dispatch.get().loading.fetching {
+            trace!("Goat fetch already in flight");
+            return;
+        }
+        Self::revalidate(api, dispatch);
+    }
+
+    /// Asynchronously fetches the list of goats from the backend API.
+    ///
+    /// Issues a conditional GET for the goat list through `api`.
+    /// Updates the store's `goats`, `loading`, `error` and `fetched_at` fields as appropriate.
     ///
     /// # Behavior
     ///
-    /// - Sets `loading` to true and clears previous errors before fetching.
-    /// - On success, sets `goats` with received data, `loading` to false, clears errors.
-    /// - On failure (network or parse), records error messages and sets `loading` to false.
+    /// - Sets `loading.fetching` to true and clears previous errors before fetching.
+    /// - Sends the cached `etag` as `If-None-Match`; a 304 reply keeps the
+    ///   cached goats and only stamps `fetched_at`.
+    /// - On success, sets `goats` and `etag` with received data, stamps `fetched_at`,
+    ///   clears `loading.fetching` and errors.
+    /// - On failure (network or parse), records error messages and clears
+    ///   `loading.fetching`; cached goats are kept.
     ///
     /// # Logging
     ///
     /// Logs info on success and detailed errors on failure.
-    pub fn fetch_goats(dispatch: Dispatch<Self>) {
+    fn revalidate(api: Api, dispatch: Dispatch<Self>) {
         // Set the loading flag & clear errors before fetching
         dispatch.reduce_mut(|state| {
-            state.loading = true;
+            state.loading.fetching = true;
             state.error = None;
         });
 
+        // Revalidate only against a cache that actually holds data
+        let etag = {
+            let state = dispatch.get();
+            state.fetched_at.and(state.etag.clone())
+        };
+
         // Spawn a local future compatible with WASM runtime
         spawn_local(async move {
-            let url = String::from("http://127.0.0.1:8000/goats");
-            let response = Request::get(&url).send().await;
-            info!("Sending fetch_goats request to {}", url);
-
-            match response {
-                Ok(resp) => {
-                    // Attempt to parse JSON response into Vec<GoatParams>
-                    let parse_result = resp.json::<Vec<GoatParams>>().await;
-
-                    match parse_result {
-                        Ok(goats) => {
-                            info!("Successfully fetched {} goats", goats.len());
-                            dispatch.reduce_mut(|state| {
-                                state.goats = goats;
-                                state.loading = false;
-                                state.error = None;
-                            });
-                        }
-                        Err(parse_err) => {
-                            let err_msg = format!("Failed to parse goats JSON: {}", parse_err);
-                            error!("{}", err_msg);
-                            dispatch.reduce_mut(|state| {
-                                state.loading = false;
-                                state.error = Some(err_msg);
-                            });
-                        }
-                    }
+            match api.fetch_goats(etag.as_deref()).await {
+                Ok(GoatsFetch::Modified { goats, etag }) => {
+                    info!("Successfully fetched {} goats", goats.len());
+                    dispatch.reduce_mut(|state| {
+                        state.goats = goats;
+                        state.etag = etag;
+                        state.loading.fetching = false;
+                        state.error = None;
+                        state.fetched_at = Some(js_sys::Date::now());
+                    });
                 }
-                Err(req_err) => {
-                    let err_msg = format!("HTTP request failed: {}", req_err);
+                Ok(GoatsFetch::NotModified) => {
+                    info!("Goat list unchanged on server");
+                    dispatch.reduce_mut(|state| {
+                        state.loading.fetching = false;
+                        state.error = None;
+                        state.fetched_at = Some(js_sys::Date::now());
+                    });
+                }
+                Err(err) => {
+                    let err_msg = format!("Failed to fetch goats: {}", err);
                     error!("{}", err_msg);
                     dispatch.reduce_mut(|state| {
-                        state.loading = false;
+                        state.loading.fetching = false;
                         state.error = Some(err_msg);
                     });
                 }
@@ -100,12 +353,13 @@ impl GoatStore {
 
     /// Attempts to add a new goat by sending it to the backend.
     ///
-    /// On success, updates store state and appends to goats list.
-    /// On failure, records error and logs it.
-    pub fn add_goat_async(dispatch: Dispatch<Self>, goat: GoatParams) {
+    /// On success, appends the goat as stored by the backend (with its ID)
+    /// to the goats list and records it in the undo history. On failure,
+    /// records error and logs it.
+    pub fn add_goat_async(api: Api, dispatch: Dispatch<Self>, goat: NewGoat) {
         // Set loading state, clear previous errors
         dispatch.reduce_mut(|store| {
-            store.loading = true;
+            store.loading.adding = true;
             store.error = None;
         });
 
@@ -113,33 +367,21 @@ impl GoatStore {
         spawn_local({
             let dispatch = dispatch.clone();
             async move {
-                match Request::post("http://127.0.0.1:8000/goats")
-                    .json(&goat)
-                    .unwrap()
-                    .send()
-                    .await
-                {
-                    Ok(resp) if resp.ok() => {
-                        info!("Successfully added goat to backend.");
-                        dispatch.reduce_mut(|store| {
-                            store.goats.push(goat);
-                            store.loading = false;
-                        });
-                    }
-                    Ok(resp) => {
-                        let err_msg = format!("Server error: HTTP {}", resp.status());
-                        error!("{}", err_msg);
+                match api.add_goat(&goat).await {
+                    Ok(stored) => {
+                        info!("Successfully added goat {} to backend.", stored.id);
                         dispatch.reduce_mut(|store| {
-                            store.loading = false;
-                            store.error = Some(err_msg.clone());
+                            store.history.record(GoatCommand::Add(stored.clone()));
+                            store.goats.push(stored);
+                            store.loading.adding = false;
                         });
                     }
-                    Err(net_err) => {
-                        let err_msg = format!("Network error: {}", net_err);
+                    Err(err) => {
+                        let err_msg = format!("Failed to add goat: {}", err);
                         error!("{}", err_msg);
                         dispatch.reduce_mut(|store| {
-                            store.loading = false;
-                            store.error = Some(err_msg.clone());
+                            store.loading.adding = false;
+                            store.error = Some(err_msg);
                         });
                     }
                 }
@@ -149,17 +391,18 @@ impl GoatStore {
 
     /// Asynchronously attempts to delete a goat by name from the backend and updates the local store accordingly.
     /// Reports success or returns an error if unable to delete the goat.
+    /// A goat that was in the local store is recorded in the undo history.
     ///
     /// --------ARGUMENTS---------
     ///
-    /// - `dispatch`:   Dispatch<Self>
-    ///                 A `Dispatch` handle to the current `GoatStore` state,
-    ///                 allowing mutation through yewdux reducers.
-    /// - `goat_name`:  String
-    ///                 The name of the goat to be deleted (case-sensitive).
-    /// - `on_result`:  Callback<Result<(), AppError>>
-    ///                 Callback called after the delete attempt finishes,
-    ///                 with `Ok(())` on success or an `AppError` variant on failure.
+    /// - `api`: Api, the backend client performing the request.
+    /// - `dispatch`: Dispatch<Self>, a handle to the current `GoatStore`
+    ///   state, allowing mutation through yewdux reducers.
+    /// - `goat_name`: String, the name of the goat to be deleted
+    ///   (case-sensitive).
+    /// - `on_result`: Callback<Result<(), AppError>>, called after the delete
+    ///   attempt finishes, with `Ok(())` on success or an `AppError` variant
+    ///   on failure.
     ///
     /// --------RETURNS----------
     ///
@@ -169,59 +412,109 @@ impl GoatStore {
     /// ------UPGRADE PENDING------
     ///     Retry or user confirmation for network/server errors.
     pub fn delete_goat_async(
+        api: Api,
         dispatch: Dispatch<Self>,
         goat_name: String,
         on_result: Callback<Result<(), AppError>>,
     ) {
+        dispatch.reduce_mut(|store| {
+            store.loading.deleting.insert(goat_name.clone());
+        });
+
         spawn_local(async move {
-            trace!("Deleting goat {}", goat_name);
-            // Attempt HTTP DELETE, expecting backend to handle /goats/{name}
-            let url = "http://127.0.0.1:8000/goats";
-            let body = serde_json::json!({ "name": goat_name });
-            let outcome = match Request::delete(url).json(&body).unwrap().send().await {
-                Ok(response) if response.ok() => {
-                    dispatch.reduce_mut(|store| {
-                        let initial_len = store.goats.len();
-                        store.goats.retain(|g| g.name != goat_name);
-                        if store.goats.len() < initial_len {
-                            info!("Deleted goat '{}' from local store and backend.", goat_name);
-                        } else {
-                            warn!("Goat '{}' not found in local store, but backend deletion succeeded.", goat_name);
-                        }
-                    });
-                    Ok(())
-                }
-                Ok(response) => {
-                    let msg = format!(
-                        "Server error {} for deleting '{}'",
-                        response.status(),
-                        goat_name
-                    );
-                    error!("{}", msg);
-                    Err(AppError::Unexpected(msg))
-                }
-                Err(e) => {
-                    let msg = format!("Network error: {} while deleting '{}'", e, goat_name);
-                    error!("{}", msg);
-                    Err(AppError::NetworkError(msg))
+            let outcome = Self::delete_goat(&api, &dispatch, &goat_name).await;
+            if let Ok(Some(command)) = &outcome {
+                dispatch.reduce_mut(|store| store.history.record(command.clone()));
+            }
+            on_result.emit(outcome.map(|_| ()));
+        });
+    }
+
+    /// Deletes several goats one after another, recording them as a single
+    /// change in the undo history. `on_result` is called once per name.
+    pub fn delete_goats_async(
+        api: Api,
+        dispatch: Dispatch<Self>,
+        goat_names: Vec<String>,
+        on_result: Callback<(String, Result<(), AppError>)>,
+    ) {
+        dispatch.reduce_mut(|store| {
+            store.loading.deleting.extend(goat_names.iter().cloned());
+        });
+
+        spawn_local(async move {
+            let mut deleted = Vec::new();
+            for goat_name in goat_names {
+                let outcome = Self::delete_goat(&api, &dispatch, &goat_name).await;
+                if let Ok(Some(command)) = outcome.as_ref() {
+                    deleted.push(command.clone());
                 }
-            };
-            on_result.emit(outcome);
+                on_result.emit((goat_name, outcome.map(|_| ())));
+            }
+            if !deleted.is_empty() {
+                dispatch.reduce_mut(|store| store.history.record(GoatCommand::Batch(deleted)));
+            }
         });
     }
 
+    /// Deletes a goat by name, returning the change to record in the undo
+    /// history if the goat was in the local store.
+    async fn delete_goat(
+        api: &Api,
+        dispatch: &Dispatch<Self>,
+        goat_name: &str,
+    ) -> Result<Option<GoatCommand>, AppError> {
+        let goat = dispatch
+            .get()
+            .goats
+            .iter()
+            .find(|g| g.name == goat_name)
+            .cloned();
+        dispatch.reduce_mut(|store| {
+            store.loading.deleting.insert(goat_name.to_string());
+        });
+
+        trace!("Deleting goat {}", goat_name);
+        let outcome = api.delete_goat(goat_name).await;
+        dispatch.reduce_mut(|store| {
+            store.loading.deleting.remove(goat_name);
+        });
+        match outcome {
+            Ok(trash_id) => {
+                dispatch.reduce_mut(|store| {
+                    let initial_len = store.goats.len();
+                    store.goats.retain(|g| g.name != goat_name);
+                    if store.goats.len() < initial_len {
+                        info!("Deleted goat '{}' from local store and backend.", goat_name);
+                    } else {
+                        warn!(
+                            "Goat '{}' not found in local store, but backend deletion succeeded.",
+                            goat_name
+                        );
+                    }
+                });
+                Ok(goat.map(|goat| GoatCommand::Delete {
+                    goat,
+                    trash_id: Some(trash_id),
+                }))
+            }
+            Err(e) => {
+                error!("Failed to delete goat '{}': {}", goat_name, e);
+                Err(e)
+            }
+        }
+    }
+
     /// Asynchronously sends an updated goat record to the backend server and updates the local store on success.
     ///
     /// --------ARGUMENTS---------
     ///
-    /// - `dispatch`:    Dispatch<Self>
-    ///                  A `Dispatch` handle to the current `GoatStore` state,
-    ///                  allowing mutation through yewdux reducers.
-    /// - `updated_goat`: GoatParams
-    ///                  Struct containing the updated details of the goat.
-    /// - `on_result`:   Callback<Result<(), AppError>>
-    ///                  Callback which receives `Ok(())` on successful update,
-    ///                  or an `AppError` detailing any failure.
+    /// - `api`: Api, the backend client performing the request.
+    /// - `dispatch`: Dispatch<Self>, a handle to the current `GoatStore`
+    ///   state, allowing mutation through yewdux reducers.
+    /// - `updated_goat`: NewGoat, every field of the goat, matched by name.
+    /// - `on_result`: Callback<Result<(), AppError>>, which receives `Ok(())`
+    ///   on successful update, or an `AppError` detailing any failure.
     ///
     /// --------RETURNS----------
     ///
@@ -231,51 +524,633 @@ impl GoatStore {
     /// - Improve retry logic on network errors.
     /// - Add validation or conflict resolution based on backend response.
     pub fn update_goat_async(
+        api: Api,
         dispatch: Dispatch<Self>,
-        updated_goat: GoatParams,
+        updated_goat: NewGoat,
         on_result: Callback<Result<(), AppError>>,
     ) {
+        dispatch.reduce_mut(|store| store.loading.updating = true);
+
         spawn_local(async move {
             trace!("Updating goat");
-            // Assume your backend expects PUT with JSON payload at /goats/{name}
-            let url = "http://127.0.0.1:8000/goats";
-            let response = Request::put(&url).json(&updated_goat).unwrap().send().await;
-
-            let outcome = match response {
-                Ok(resp) if resp.ok() => {
+            let outcome = api.update_goat(&updated_goat).await;
+            dispatch.reduce_mut(|store| store.loading.updating = false);
+            match &outcome {
+                Ok(()) => {
                     // Update local store on success
                     dispatch.reduce_mut(|store| {
-                        if let Some(pos) =
-                            store.goats.iter().position(|g| g.name == updated_goat.name)
-                        {
-                            store.goats[pos] = updated_goat.clone();
-                        } else {
-                            // Optionally add if not found
-                            store.goats.push(updated_goat.clone());
+                        match store.goats.iter_mut().find(|g| g.name == updated_goat.name) {
+                            Some(goat) => {
+                                let before =
+                                    std::mem::replace(&mut goat.params, updated_goat.clone());
+                                store.history.record(GoatCommand::Update {
+                                    before,
+                                    after: updated_goat.clone(),
+                                });
+                            }
+                            None => warn!(
+                                "Goat '{}' not in local store; it appears on the next fetch",
+                                updated_goat.name
+                            ),
                         }
                     });
                     info!("Successfully updated goat '{}'", updated_goat.name);
-                    Ok(())
-                }
-                Ok(resp) => {
-                    let msg = format!(
-                        "Server returned error {} while updating '{}'",
-                        resp.status(),
-                        updated_goat.name
-                    );
-                    error!("{}", msg);
-                    Err(AppError::Unexpected(msg))
                 }
-                Err(err) => {
-                    let msg = format!(
-                        "Network error while updating '{}': {}",
-                        updated_goat.name, err
-                    );
-                    error!("{}", msg);
-                    Err(AppError::NetworkError(msg))
+                Err(e) => error!("Failed to update goat '{}': {}", updated_goat.name, e),
+            }
+            on_result.emit(outcome);
+        });
+    }
+
+    /// Asynchronously sends only the changed fields of a goat to the backend and replaces the local copy with the stored goat on success.
+    ///
+    /// --------ARGUMENTS---------
+    ///
+    /// - `api`: Api, the backend client performing the request.
+    /// - `dispatch`: Dispatch<Self>, a handle to the current `GoatStore` state.
+    /// - `id`: i64, the goat's server-assigned ID.
+    /// - `update`: GoatUpdate, the changed fields, e.g. from `GoatUpdate::diff`.
+    /// - `on_result`: Callback<Result<Goat, AppError>>, which receives the
+    ///   stored goat on success, or an `AppError` detailing any failure.
+    ///
+    /// --------RETURNS----------
+    ///
+    /// - This function returns nothing directly; it reports via the `on_result` callback.
+    pub fn patch_goat_async(
+        api: Api,
+        dispatch: Dispatch<Self>,
+        id: i64,
+        update: GoatUpdate,
+        on_result: Callback<Result<Goat, AppError>>,
+    ) {
+        dispatch.reduce_mut(|store| store.loading.updating = true);
+
+        spawn_local(async move {
+            trace!("Patching goat {}", id);
+            let outcome = api.patch_goat(id, &update).await;
+            dispatch.reduce_mut(|store| store.loading.updating = false);
+            match &outcome {
+                Ok(patched) => {
+                    dispatch.reduce_mut(|store| {
+                        match store.goats.iter_mut().find(|g| g.id == id) {
+                            Some(goat) => {
+                                let before = std::mem::replace(goat, patched.clone()).params;
+                                store.history.record(GoatCommand::Patch {
+                                    id,
+                                    before,
+                                    after: patched.params.clone(),
+                                });
+                            }
+                            None => warn!(
+                                "Goat {} not in local store; it appears on the next fetch",
+                                id
+                            ),
+                        }
+                    });
+                    info!("Successfully patched goat '{}'", patched.name);
                 }
-            };
+                Err(e) => error!("Failed to patch goat {}: {}", id, e),
+            }
             on_result.emit(outcome);
         });
     }
+
+    /// Undoes the most recent change by sending its inverse to the backend,
+    /// then moves it to the redo stack.
+    ///
+    /// If a request fails, the part already undone moves to the redo stack
+    /// and the rest stays on the undo stack, with the error recorded in
+    /// `history.error`.
+    pub fn undo(api: Api, dispatch: Dispatch<Self>) {
+        let state = dispatch.get();
+        if state.history.pending {
+            trace!("Undo or redo already in flight");
+            return;
+        }
+        let Some(command) = state.history.undo.last().cloned() else {
+            return;
+        };
+        dispatch.reduce_mut(|store| {
+            store.history.undo.pop();
+            store.history.pending = true;
+            store.history.error = None;
+        });
+
+        spawn_local(async move {
+            info!("Undoing '{}'", command.label());
+            let outcome = Self::run(&api, &dispatch, command.inverse()).await;
+            dispatch.reduce_mut(|store| {
+                store.history.pending = false;
+                match outcome {
+                    Ok(done) => store.history.redo.push(done.inverse()),
+                    Err(partial) => {
+                        let err_msg =
+                            format!("Failed to undo '{}': {}", command.label(), partial.error);
+                        error!("{}", err_msg);
+                        if let Some(done) = partial.done {
+                            store.history.redo.push(done.inverse());
+                        }
+                        store.history.undo.push(partial.remaining.inverse());
+                        store.history.error = Some(err_msg);
+                    }
+                }
+            });
+        });
+    }
+
+    /// Redoes the most recently undone change, then moves it back to the
+    /// undo stack. Failures are handled as in `undo`.
+    pub fn redo(api: Api, dispatch: Dispatch<Self>) {
+        let state = dispatch.get();
+        if state.history.pending {
+            trace!("Undo or redo already in flight");
+            return;
+        }
+        let Some(command) = state.history.redo.last().cloned() else {
+            return;
+        };
+        dispatch.reduce_mut(|store| {
+            store.history.redo.pop();
+            store.history.pending = true;
+            store.history.error = None;
+        });
+
+        spawn_local(async move {
+            info!("Redoing '{}'", command.label());
+            let outcome = Self::run(&api, &dispatch, command.clone()).await;
+            dispatch.reduce_mut(|store| {
+                store.history.pending = false;
+                match outcome {
+                    Ok(done) => store.history.undo.push(done),
+                    Err(partial) => {
+                        let err_msg =
+                            format!("Failed to redo '{}': {}", command.label(), partial.error);
+                        error!("{}", err_msg);
+                        if let Some(done) = partial.done {
+                            store.history.undo.push(done);
+                        }
+                        store.history.redo.push(partial.remaining);
+                        store.history.error = Some(err_msg);
+                    }
+                }
+            });
+        });
+    }
+
+    /// Carries out `command` against the backend and the local goat list,
+    /// without recording it. Returns the command as carried out, with the
+    /// trash entry each deleted goat moved to.
+    async fn run(
+        api: &Api,
+        dispatch: &Dispatch<Self>,
+        command: GoatCommand,
+    ) -> Result<GoatCommand, PartialRun> {
+        let failed = |command: &GoatCommand, error: AppError| PartialRun {
+            done: None,
+            remaining: command.clone(),
+            error,
+        };
+        match &command {
+            GoatCommand::Add(goat) => {
+                let stored = api
+                    .add_goat(&goat.params)
+                    .await
+                    .map_err(|e| failed(&command, e))?;
+                dispatch.reduce_mut(|store| store.goats.push(stored.clone()));
+                Ok(GoatCommand::Add(stored))
+            }
+            GoatCommand::Update { after, .. } => {
+                api.update_goat(after)
+                    .await
+                    .map_err(|e| failed(&command, e))?;
+                dispatch.reduce_mut(|store| {
+                    if let Some(goat) = store.goats.iter_mut().find(|g| g.name == after.name) {
+                        goat.params = after.clone();
+                    }
+                });
+                Ok(command)
+            }
+            GoatCommand::Patch { id, before, after } => {
+                let patched = api
+                    .patch_goat(*id, &GoatUpdate::diff(before, after))
+                    .await
+                    .map_err(|e| failed(&command, e))?;
+                dispatch.reduce_mut(|store| {
+                    if let Some(goat) = store.goats.iter_mut().find(|g| g.id == *id) {
+                        *goat = patched;
+                    }
+                });
+                Ok(command)
+            }
+            GoatCommand::Delete { goat, .. } => {
+                let trash_id = api
+                    .delete_goat(&goat.name)
+                    .await
+                    .map_err(|e| failed(&command, e))?;
+                dispatch.reduce_mut(|store| store.goats.retain(|g| g.name != goat.name));
+                Ok(GoatCommand::Delete {
+                    goat: goat.clone(),
+                    trash_id: Some(trash_id),
+                })
+            }
+            GoatCommand::Restore { trash_id, .. } => {
+                let restored = api
+                    .restore_goat(*trash_id)
+                    .await
+                    .map_err(|e| failed(&command, e))?;
+                dispatch.reduce_mut(|store| store.goats.push(restored.clone()));
+                Ok(GoatCommand::Restore {
+                    goat: restored,
+                    trash_id: *trash_id,
+                })
+            }
+            GoatCommand::Batch(commands) => {
+                let mut done = Vec::new();
+                let mut remaining = commands.clone();
+                while !remaining.is_empty() {
+                    let next = remaining.remove(0);
+                    match Box::pin(Self::run(api, dispatch, next)).await {
+                        Ok(step) => done.push(step),
+                        Err(partial) => {
+                            if let Some(step) = partial.done {
+                                done.push(step);
+                            }
+                            remaining.insert(0, partial.remaining);
+                            return Err(PartialRun {
+                                done: (!done.is_empty()).then_some(GoatCommand::Batch(done)),
+                                remaining: GoatCommand::Batch(remaining),
+                                error: partial.error,
+                            });
+                        }
+                    }
+                }
+                Ok(GoatCommand::Batch(done))
+            }
+        }
+    }
+}
+
+/// What `GoatList` can group the herd by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GoatGrouping {
+    /// The pen or other space the goat is assigned to.
+    Pen,
+    Breed,
+    /// Health status.
+    Status,
+}
+
+impl GoatGrouping {
+    /// Every grouping, in menu order.
+    pub const ALL: [GoatGrouping; 3] =
+        [GoatGrouping::Pen, GoatGrouping::Breed, GoatGrouping::Status];
+
+    /// Human-readable label.
+    pub fn label(&self) -> &'static str {
+        match self {
+            GoatGrouping::Pen => "Pen",
+            GoatGrouping::Breed => "Breed",
+            GoatGrouping::Status => "Status",
+        }
+    }
+
+    /// Group label of a goat without a value to group by.
+    fn missing(&self) -> &'static str {
+        match self {
+            GoatGrouping::Pen => "No pen",
+            GoatGrouping::Breed => "No breed",
+            GoatGrouping::Status => "No status",
+        }
+    }
+}
+
+/// Goats sharing a pen, breed or status, with their subtotals.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GoatGroup {
+    pub label: String,
+    pub goats: Vec<Goat>,
+    /// Total weight in kg.
+    pub total_weight: f64,
+    /// Total current price.
+    pub total_value: f64,
+}
+
+/// Groups `goats` by `grouping`, keeping their order within each group.
+/// Groups are ordered by label, ignoring case, with the goats lacking a
+/// value (e.g. in no pen) last. `pens` maps goat names to their pen's name.
+pub fn group_goats(
+    goats: &[Goat],
+    grouping: GoatGrouping,
+    pens: &HashMap<String, String>,
+) -> Vec<GoatGroup> {
+    let mut groups: Vec<GoatGroup> = Vec::new();
+    let mut missing = None::<GoatGroup>;
+    for goat in goats {
+        let value = match grouping {
+            GoatGrouping::Pen => pens.get(&goat.name).cloned(),
+            GoatGrouping::Breed => Some(Breed::to_str(&goat.breed).to_string()),
+            GoatGrouping::Status => Some(goat.health_status.trim().to_string()),
+        }
+        .filter(|v| !v.trim().is_empty());
+        let group = match value {
+            Some(label) => match groups.iter().position(|g| g.label == label) {
+                Some(at) => &mut groups[at],
+                None => {
+                    groups.push(GoatGroup {
+                        label,
+                        goats: Vec::new(),
+                        total_weight: 0.0,
+                        total_value: 0.0,
+                    });
+                    groups.last_mut().unwrap()
+                }
+            },
+            None => missing.get_or_insert_with(|| GoatGroup {
+                label: grouping.missing().to_string(),
+                goats: Vec::new(),
+                total_weight: 0.0,
+                total_value: 0.0,
+            }),
+        };
+        group.total_weight += goat.weight;
+        group.total_value += goat.current_price;
+        group.goats.push(goat.clone());
+    }
+    groups.sort_by_key(|g| g.label.to_lowercase());
+    groups.extend(missing);
+    trace!(
+        "Grouped goats by {:?} into {} groups",
+        grouping,
+        groups.len()
+    );
+    groups
+}
+
+/// Totals and averages over the goats shown in `GoatList`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HerdTotals {
+    pub count: usize,
+    pub total_cost: f64,
+    /// Total current price.
+    pub total_value: f64,
+    /// Mean weight in kg, or `None` without goats.
+    pub average_weight: Option<f64>,
+}
+
+/// Sums up `goats`.
+pub fn herd_totals(goats: &[Goat]) -> HerdTotals {
+    let count = goats.len();
+    HerdTotals {
+        count,
+        total_cost: goats.iter().map(|g| g.cost).sum(),
+        total_value: goats.iter().map(|g| g.current_price).sum(),
+        average_weight: (count > 0)
+            .then(|| goats.iter().map(|g| g.weight).sum::<f64>() / count as f64),
+    }
+}
+
+/// Whether the farm is in read-only mode, shared so every form can hide or
+/// disable its changes. Mirrors `FarmSettings::read_only` on the backend,
+/// which refuses changes on its own while the mode is on.
+#[derive(Default, Clone, PartialEq, Store)]
+pub struct AccessStore {
+    /// True while the dashboard only shows data
+    pub read_only: bool,
+
+    /// True while the mode is being loaded or switched
+    pub pending: bool,
+
+    /// Contains error message if the last load or switch failed
+    pub error: Option<String>,
+}
+
+impl AccessStore {
+    /// Loads the mode from the farm settings.
+    pub fn load(api: Api, dispatch: Dispatch<Self>) {
+        dispatch.reduce_mut(|store| store.pending = true);
+        spawn_local(async move {
+            let outcome = api.farm_settings().await;
+            Self::finish(&dispatch, outcome.map(|s| s.read_only), "load");
+        });
+    }
+
+    /// Switches read-only mode on or off; only the owner may.
+    pub fn set_read_only(api: Api, dispatch: Dispatch<Self>, read_only: bool) {
+        dispatch.reduce_mut(|store| store.pending = true);
+        spawn_local(async move {
+            let outcome = api.set_read_only(ReadOnlyMode { read_only }).await;
+            Self::finish(&dispatch, outcome.map(|s| s.read_only), "switch");
+        });
+    }
+
+    fn finish(dispatch: &Dispatch<Self>, outcome: Result<bool, AppError>, action: &str) {
+        match outcome {
+            Ok(read_only) => {
+                info!("Read-only mode is {}", if read_only { "on" } else { "off" });
+                dispatch.set(AccessStore {
+                    read_only,
+                    ..Default::default()
+                });
+            }
+            Err(err) => {
+                let err_msg = format!("Failed to {} read-only mode: {}", action, err);
+                error!("{}", err_msg);
+                dispatch.reduce_mut(|store| {
+                    store.pending = false;
+                    store.error = Some(err_msg);
+                });
+            }
+        }
+    }
+}
+
+/// Returns true while the farm is in read-only mode.
+#[hook]
+pub fn use_read_only() -> bool {
+    *use_selector(|store: &AccessStore| store.read_only)
+}
+
+/// What this device's session may do (see `shared::permissions`), so the
+/// dashboard only offers actions the backend would allow.
+#[derive(Default, Clone, PartialEq, Store)]
+pub struct PermissionStore {
+    /// The session's permissions, once loaded
+    pub mine: Option<MyPermissions>,
+
+    /// True once the backend refused this device for not being signed in
+    pub signed_out: bool,
+}
+
+impl PermissionStore {
+    /// Loads the session's permissions, e.g. after signing in. Until they
+    /// arrive, or if they cannot be read, everything is offered and the
+    /// backend has the last word. A device that is not signed in is marked
+    /// `signed_out`.
+    pub fn load(api: Api, dispatch: Dispatch<Self>) {
+        spawn_local(async move {
+            match api.my_permissions().await {
+                Ok(mine) => {
+                    info!("Loaded permissions of role {:?}", mine.role);
+                    dispatch.set(PermissionStore {
+                        mine: Some(mine),
+                        signed_out: false,
+                    });
+                }
+                Err(AppError::ApiError { status: 401, .. }) => {
+                    info!("This device is not signed in");
+                    dispatch.set(PermissionStore {
+                        mine: None,
+                        signed_out: true,
+                    });
+                }
+                Err(err) => warn!("Failed to load permissions: {}", err),
+            }
+        });
+    }
+}
+
+/// Returns true once the backend refused this device for not being signed
+/// in, until it signs in again.
+#[hook]
+pub fn use_signed_out() -> bool {
+    *use_selector(|store: &PermissionStore| store.signed_out)
+}
+
+/// Returns true unless this session may not do `action` in `module`.
+#[hook]
+pub fn use_can(module: PermissionModule, action: PermissionAction) -> bool {
+    *use_selector(move |store: &PermissionStore| {
+        store
+            .mine
+            .as_ref()
+            .is_none_or(|mine| mine.allows(module, action))
+    })
+}
+
+/// localStorage key the preferred weight unit is kept under.
+const WEIGHT_UNIT_KEY: &str = "yagi.units.weight";
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window().and_then(|w| w.local_storage().ok().flatten())
+}
+
+/// The decimal separator of the browser's locale, '.' or ','.
+fn locale_decimal_separator() -> char {
+    let locale = web_sys::window()
+        .and_then(|w| w.navigator().language())
+        .unwrap_or_else(|| "en".to_string());
+    let sample: String = js_sys::Number::from(1.5).to_locale_string(&locale).into();
+    if sample.contains(',') { ',' } else { '.' }
+}
+
+thread_local! {
+    /// Copy of `UnitsStore::timezone` for code that runs outside components,
+    /// such as `date_picker::today`.
+    static FARM_TIMEZONE: Cell<Tz> = const { Cell::new(Tz::UTC) };
+}
+
+/// The farm's time zone, UTC until the settings are loaded.
+pub fn farm_timezone() -> Tz {
+    FARM_TIMEZONE.with(Cell::get)
+}
+
+/// Units values are entered and shown in (see `shared::units`). Values are
+/// always stored in kilograms and the farm's base currency; the weight unit
+/// is this browser's preference and survives reloads. Times are shown in the
+/// farm's time zone rather than the browser's (see `shared::time`).
+#[derive(Clone, PartialEq)]
+pub struct UnitsStore {
+    /// Unit weights are entered and shown in
+    pub weight: WeightUnit,
+
+    /// ISO 4217 code of the farm's base currency
+    pub currency: String,
+
+    /// Decimal separator of the browser's locale
+    pub decimal_separator: char,
+
+    /// Time zone of the farm
+    pub timezone: Tz,
+}
+
+impl Store for UnitsStore {
+    fn new(_cx: &yewdux::Context) -> Self {
+        let weight = local_storage()
+            .and_then(|s| s.get_item(WEIGHT_UNIT_KEY).ok().flatten())
+            .and_then(|symbol| WeightUnit::from_symbol(&symbol))
+            .unwrap_or_default();
+        UnitsStore {
+            weight,
+            currency: DEFAULT_BASE_CURRENCY.to_string(),
+            decimal_separator: locale_decimal_separator(),
+            timezone: farm_timezone(),
+        }
+    }
+
+    fn should_notify(&self, old: &Self) -> bool {
+        self != old
+    }
+}
+
+impl UnitsStore {
+    /// Loads the base currency and time zone from the farm settings.
+    /// Amounts keep the default currency's symbol, and times stay in UTC,
+    /// if the settings cannot be read.
+    pub fn load_settings(api: Api, dispatch: Dispatch<Self>) {
+        spawn_local(async move {
+            match api.farm_settings().await {
+                Ok(settings) => Self::apply_settings(&dispatch, &settings),
+                Err(err) => warn!("Failed to load the farm settings: {}", err),
+            }
+        });
+    }
+
+    /// Takes the base currency and time zone from `settings`, e.g. as just
+    /// saved by the setup wizard.
+    pub fn apply_settings(dispatch: &Dispatch<Self>, settings: &FarmSettings) {
+        let timezone = settings.tz();
+        info!(
+            "Base currency is {}, time zone {}",
+            settings.base_currency, timezone
+        );
+        FARM_TIMEZONE.with(|tz| tz.set(timezone));
+        dispatch.reduce_mut(|store| {
+            store.currency = settings.base_currency.clone();
+            store.timezone = timezone;
+        });
+    }
+
+    /// Saves the farm's time zone. The settings are re-read first so the
+    /// others are stored as the backend has them.
+    pub fn set_timezone(api: Api, dispatch: Dispatch<Self>, timezone: Tz) {
+        spawn_local(async move {
+            let outcome: Result<FarmSettings, AppError> = async {
+                let current = api.farm_settings().await?;
+                let updated = FarmSettings {
+                    timezone: timezone.name().to_string(),
+                    ..current
+                };
+                api.update_settings(&updated).await
+            }
+            .await;
+            match outcome {
+                Ok(saved) => {
+                    let timezone = saved.tz();
+                    info!("Farm time zone is now {}", timezone);
+                    FARM_TIMEZONE.with(|tz| tz.set(timezone));
+                    dispatch.reduce_mut(|store| store.timezone = timezone);
+                }
+                Err(err) => error!("Failed to change the time zone: {}", err),
+            }
+        });
+    }
+
+    /// Switches the unit weights are entered and shown in, remembering it
+    /// for this browser.
+    pub fn set_weight_unit(dispatch: Dispatch<Self>, weight: WeightUnit) {
+        if let Some(storage) = local_storage() {
+            let _ = storage.set_item(WEIGHT_UNIT_KEY, weight.symbol());
+        }
+        dispatch.reduce_mut(|store| store.weight = weight);
+    }
 }
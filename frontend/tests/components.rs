@@ -0,0 +1,5746 @@
+//! Browser component tests for the key dashboard forms.
+//!
+//! Run with `wasm-pack test --headless --firefox` (or `--chrome`) from the
+//! `frontend` directory. Each test mounts a component into a fresh element
+//! and inspects the rendered DOM.
+
+use frontend::components::add_goat_wizard::{DRAFT_FORM, GoatDraft, Step};
+use frontend::components::date_picker::days_ago;
+use frontend::components::error_boundary::use_section_error;
+use frontend::components::field_history::RecordFieldProps;
+use frontend::components::goat_detail::{GoatDetailProps, breeding_summary};
+use frontend::components::goat_notes::GoatNotesProps;
+use frontend::components::health_timeline::HealthTimelineProps;
+use frontend::components::permissions_editor::toggle_permission;
+use frontend::components::quick_entry::QuickEntryProps;
+use frontend::components::quick_search::DEBOUNCE;
+use frontend::components::traceability_records::TraceabilityRecordsProps;
+use frontend::components::update_goat_form::UPDATE_GOAT_DRAFT;
+use frontend::components::voice_notes::VoiceNotesProps;
+use frontend::components::{
+    AccessTokens, AddGoatForm, AddGoatWizard, AlertRules, BarnConditions, BreedingPlanner,
+    BudgetTracker, Can, CullingHelper, DataHealth, DatePicker, DeleteGoatsForm, DietReassignment,
+    DiseaseCompliance, ErrorBoundary, ExportTemplates, ExternalAnimals, FarmArchive,
+    FeedEfficiencyPanel, GoatDetail, GoatList, GoatNotes, GrazingMap, HealthTimeline, HeatTracker,
+    ImportWizard, IncidentHeatMap, JobsPanel, KpiCards, LifecyclePipeline, MentionInbox,
+    MilkAnalytics, MovementLog, NumberField, NutritionPanel, PedigreeView, PensView,
+    PermissionsEditor, PricingPreview, Quantity, QuickEntry, QuickSearch, ReadOnlyToggle,
+    RecentActivity, RecordField, RetentionPanel, RotationPlanner, ServiceRecords, SessionsPanel,
+    SetupWizard, StockExpiry, TagSeriesPanel, TasksList, TraceabilityRecords, UndoControls,
+    UnitSelect, UpdateGoatForm, VetVisits, VoiceNotes, WaterPanel, WeighSession,
+};
+use frontend::drafts::{discard_draft, goat_draft_key, load_draft, save_draft};
+use frontend::services::{Api, ApiProvider, MockApiClient};
+use frontend::store::{AccessStore, GoatStore, PermissionStore, UnitsStore};
+use shared::activity::{ActivityEvent, ActivityKind};
+use shared::alerts::{AlertCondition, AlertMetric, AlertRule};
+use shared::analytics::{FeedEfficiency, FeedEfficiencyReport};
+use shared::archive::ArchiveSummary;
+use shared::attachments::{Attachment, AttachmentTarget};
+use shared::breeding::{
+    BreedingRecommendation, BreedingService, ExternalAnimal, MethodSuccess, Neutering,
+    PedigreeNode, ServiceMethod, ServiceOutcome,
+};
+use shared::breeds::{BreedPurpose, CatalogBreed};
+use shared::data_health::{DataHealthReport, DataIssue, IssueKind};
+use shared::diseases::{CaseStatus, CatalogDisease, ComplianceCase, ComplianceReport, DiseaseTest};
+use shared::events::FieldChange;
+use shared::exports::{ExportColumn, ExportFilter, ExportFormat, ExportTemplate};
+use shared::finance::{BudgetReport, BudgetVariance, FinanceCategory};
+use shared::gps::{GeoPoint, Geofence, GoatPosition};
+use shared::grazing::{
+    GrazingAssignment, GrazingGroup, RotationConflict, RotationPlan, RotationWeek,
+};
+use shared::growth::{GrowthHistory, WeightEstimate, WeightRecord};
+use shared::health::{HealthHeatMap, HeatMapRow, TimelineEntry, TimelineKind};
+use shared::heat::{ActivitySpike, HeatPrediction};
+use shared::import::ImportTable;
+use shared::inventory::{ExpiringStock, InventoryCategory, InventoryItem};
+use shared::jobs::{Job, JobKind, JobStatus};
+use shared::lab::{LabResult, LabValue};
+use shared::lifecycle::{
+    LifecyclePipeline as Pipeline, LifecycleStage, PipelineGoat, PipelineStage,
+};
+use shared::milk::{Lactation, LactationPoint};
+use shared::movements::{AnimalMovement, Place, PlaceKind};
+use shared::notes::GoatNote;
+use shared::notifications::Notification;
+use shared::nutrition::{Nutrients, RationItem, RationPlan};
+use shared::permissions::{
+    MyPermissions, Permission, PermissionAction, PermissionModule, RolePermissions,
+};
+use shared::physical::{CoatColor, HornStatus, TraitFilter, describe};
+use shared::pricing::PricingSettings;
+use shared::retention::{RetentionPolicy, TrashedGoat, UpcomingPurges};
+use shared::scale::ScaleReading;
+use shared::scoring::{GoatScore, Recommendation, ScoreBreakdown};
+use shared::search::{SearchKind, SearchResult};
+use shared::sensors::{
+    Sensor, SensorCondition, SensorKind, SensorReading, ThresholdAlert, Thresholds,
+};
+use shared::sessions::Session;
+use shared::settings::FarmSettings;
+use shared::setup::SetupStep;
+use shared::spaces::{Space, SpaceKind, SpaceOccupancy};
+use shared::stats::{DashboardStats, Kpi};
+use shared::tags::TagSeries;
+use shared::tasks::{Task, TaskStatus};
+use shared::tokens::AccessToken;
+use shared::traceability::{Movement, MovementKind, TraceabilityRecord};
+use shared::units::WeightUnit;
+use shared::vet::{VetAvailability, VetVisit, VisitStatus};
+use shared::voice::EntryKind;
+use shared::water::{PenWater, WaterDay, WaterDrop};
+use shared::{Breed, Gender, Goat, GoatParams, VaccineRef};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+use web_sys::{Element, HtmlElement, HtmlInputElement, HtmlSelectElement};
+use yew::platform::time::sleep;
+use yew::prelude::*;
+use yew::virtual_dom::VComp;
+use yewdux::prelude::Dispatch;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Creates a fresh mount point in the document body.
+fn mount_point() -> Element {
+    let document = document();
+    let root = document.create_element("div").unwrap();
+    document.body().unwrap().append_child(&root).unwrap();
+    root
+}
+
+fn document() -> web_sys::Document {
+    web_sys::window().unwrap().document().unwrap()
+}
+
+/// Lets Yew flush pending renders and effects.
+async fn settle() {
+    sleep(std::time::Duration::from_millis(10)).await;
+}
+
+#[wasm_bindgen_test]
+async fn add_goat_form_renders_fields_and_submit() {
+    let root = mount_point();
+    yew::Renderer::<AddGoatForm>::with_root(root.clone()).render();
+    settle().await;
+
+    let text = root.text_content().unwrap_or_default();
+    assert!(text.contains("Add Goat"));
+    assert!(text.contains("Name:"));
+    assert!(text.contains("Gender:"));
+    let submit = root.query_selector("button[type=submit]").unwrap();
+    assert!(submit.is_some(), "Add goat form has no submit button");
+}
+
+#[wasm_bindgen_test]
+async fn delete_goats_form_renders_name_input() {
+    let root = mount_point();
+    yew::Renderer::<DeleteGoatsForm>::with_root(root.clone()).render();
+    settle().await;
+
+    let input = root
+        .query_selector("input[placeholder='Goat names, comma separated']")
+        .unwrap();
+    assert!(input.is_some(), "Delete form has no names input");
+}
+
+#[wasm_bindgen_test]
+async fn update_goat_form_hides_fields_until_goat_loaded() {
+    let root = mount_point();
+    yew::Renderer::<UpdateGoatForm>::with_root(root.clone()).render();
+    settle().await;
+
+    let text = root.text_content().unwrap_or_default();
+    assert!(text.contains("Load Goat"));
+    assert!(root.query_selector("form").unwrap().is_none());
+}
+
+/// Props for Harness: the mock backend, the wrapped component's own props
+/// and anything rendered after it under the same provider.
+#[derive(Properties)]
+struct HarnessProps<C: BaseComponent> {
+    api: Api,
+    props: Rc<C::Properties>,
+    #[prop_or_default]
+    children: Html,
+}
+
+impl<C: BaseComponent> PartialEq for HarnessProps<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.api == other.api && self.props == other.props && self.children == other.children
+    }
+}
+
+impl<C: BaseComponent<Properties = ()>> HarnessProps<C> {
+    fn new(mock: &Rc<MockApiClient>) -> Self {
+        Self::with_props(mock, ())
+    }
+}
+
+impl<C: BaseComponent> HarnessProps<C> {
+    fn with_props(mock: &Rc<MockApiClient>, props: C::Properties) -> Self {
+        Self {
+            api: Api(mock.clone()),
+            props: Rc::new(props),
+            children: Html::default(),
+        }
+    }
+
+    fn with_children(mut self, children: Html) -> Self {
+        self.children = children;
+        self
+    }
+}
+
+/// Harness component:
+/// Mounts `C` under an `ApiProvider` backed by the test's mock.
+#[function_component(Harness)]
+fn harness<C: BaseComponent>(props: &HarnessProps<C>) -> Html {
+    html! {
+        <ApiProvider api={props.api.clone()}>
+            { Html::from(VComp::new::<C>(Rc::clone(&props.props), None)) }
+            { props.children.clone() }
+        </ApiProvider>
+    }
+}
+
+#[wasm_bindgen_test]
+async fn update_goat_form_patches_only_changed_fields() {
+    Dispatch::<GoatStore>::global().set(GoatStore {
+        goats: vec![Goat {
+            id: 1,
+            params: goat("Rani"),
+            tag_id: None,
+            date_of_birth: None,
+            created_at: None,
+            updated_at: None,
+        }],
+        ..Default::default()
+    });
+    // Someone else changed Rani's diet since the store was loaded
+    let mut changed = goat("Rani");
+    changed.diet = "silage".to_string();
+    let mock = Rc::new(MockApiClient::with_goats(vec![changed]));
+    let root = mount_point();
+    yew::Renderer::<Harness<UpdateGoatForm>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let type_into = |input: &HtmlInputElement, text: &str| {
+        input.set_value(text);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        input.dispatch_event(&event).unwrap();
+    };
+    let search: HtmlInputElement = root
+        .query_selector("input[placeholder='Goat name to edit']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    type_into(&search, "rani");
+    settle().await;
+    let load: HtmlElement = root
+        .query_selector("button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    load.click();
+    settle().await;
+
+    let labels = root.query_selector_all("form label").unwrap();
+    let weight: HtmlInputElement = (0..labels.length())
+        .filter_map(|i| labels.item(i))
+        .map(|label| label.unchecked_into::<Element>())
+        .find(|label| {
+            label
+                .text_content()
+                .unwrap_or_default()
+                .starts_with("Weight:")
+        })
+        .and_then(|label| label.query_selector("input").unwrap())
+        .unwrap()
+        .unchecked_into();
+    type_into(&weight, "34");
+    settle().await;
+    let submit: HtmlElement = root
+        .query_selector("button[type=submit]")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    submit.click();
+    settle().await;
+
+    assert_eq!(mock.calls(), vec!["patch_goat:1"]);
+    let stored = &mock.goats()[0];
+    assert_eq!((stored.weight, stored.diet.as_str()), (34.0, "silage"));
+    let state = Dispatch::<GoatStore>::global().get();
+    assert_eq!(state.goats[0].params, *stored);
+    assert!(
+        root.text_content()
+            .unwrap_or_default()
+            .contains("Goat updated successfully.")
+    );
+
+    // Saving again without edits sends nothing
+    submit.click();
+    settle().await;
+    assert_eq!(mock.calls(), vec!["patch_goat:1"]);
+    assert!(
+        root.text_content()
+            .unwrap_or_default()
+            .contains("No changes to save.")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn update_goat_form_lists_goats_with_similar_names() {
+    let with_id = |id: i64, params: GoatParams| Goat {
+        id,
+        params,
+        tag_id: None,
+        date_of_birth: None,
+        created_at: Some("2024-03-01T10:00:00Z".parse().unwrap()),
+        updated_at: None,
+    };
+    let tagged = Goat {
+        tag_id: Some("IN-0042".to_string()),
+        date_of_birth: Some("2023-02-01".to_string()),
+        ..with_id(8, goat("Rani II"))
+    };
+    Dispatch::<GoatStore>::global().set(GoatStore {
+        goats: vec![with_id(3, goat("Rani")), tagged, with_id(9, goat("Moti"))],
+        ..Default::default()
+    });
+    let mock = Rc::new(MockApiClient::default());
+    let root = mount_point();
+    yew::Renderer::<Harness<UpdateGoatForm>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let search: HtmlInputElement = root
+        .query_selector("input[placeholder='Goat name to edit']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    search.set_value("ran");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    search.dispatch_event(&event).unwrap();
+    settle().await;
+    let load: HtmlElement = root
+        .query_selector("button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    load.click();
+    settle().await;
+
+    // Nothing is loaded until one of the matches is chosen
+    assert!(root.query_selector("form").unwrap().is_none());
+    let matches = root.query_selector_all(".goat-matches li").unwrap();
+    assert_eq!(matches.length(), 2);
+    assert_eq!(
+        matches.item(1).unwrap().text_content().unwrap_or_default(),
+        "Rani II #8 · tag IN-0042 · born 2023-02-01 · Beetal · added 2024-03-01"
+    );
+    let pick: HtmlElement = root
+        .query_selector_all(".goat-matches button")
+        .unwrap()
+        .item(1)
+        .unwrap()
+        .unchecked_into();
+    pick.click();
+    settle().await;
+
+    assert!(root.query_selector(".goat-matches").unwrap().is_none());
+    let name: HtmlInputElement = root
+        .query_selector("form input[type=text]")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    assert_eq!(name.value(), "Rani II");
+    assert!(mock.calls().is_empty());
+
+    // A misspelt name offers similar ones instead of failing
+    search.set_value("Motti");
+    search.dispatch_event(&event).unwrap();
+    settle().await;
+    load.click();
+    settle().await;
+    let text = root.text_content().unwrap_or_default();
+    assert!(text.contains("No goat named 'Motti'. Did you mean:"));
+    let matches = root.query_selector_all(".goat-matches li").unwrap();
+    assert_eq!(matches.length(), 1);
+    assert!(
+        matches
+            .item(0)
+            .unwrap()
+            .text_content()
+            .unwrap_or_default()
+            .starts_with("Moti #9")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn update_goat_form_restores_and_discards_drafts() {
+    let key = goat_draft_key(UPDATE_GOAT_DRAFT, 5);
+    discard_draft(&key);
+    Dispatch::<GoatStore>::global().set(GoatStore {
+        goats: vec![Goat {
+            id: 5,
+            params: goat("Moti"),
+            tag_id: None,
+            date_of_birth: None,
+            created_at: None,
+            updated_at: None,
+        }],
+        ..Default::default()
+    });
+    let mock = Rc::new(MockApiClient::with_goats(vec![goat("Moti")]));
+    let mount = || {
+        let root = mount_point();
+        yew::Renderer::<Harness<UpdateGoatForm>>::with_root_and_props(
+            root.clone(),
+            HarnessProps::new(&mock),
+        )
+        .render();
+        root
+    };
+    let load = |root: &Element| {
+        let search: HtmlInputElement = root
+            .query_selector("input[placeholder='Goat name to edit']")
+            .unwrap()
+            .unwrap()
+            .unchecked_into();
+        search.set_value("Moti");
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        search.dispatch_event(&event).unwrap();
+    };
+    let load_button = |root: &Element| -> HtmlElement {
+        root.query_selector("button")
+            .unwrap()
+            .unwrap()
+            .unchecked_into()
+    };
+    let diet_input = |root: &Element| -> HtmlInputElement {
+        let labels = root.query_selector_all("form label").unwrap();
+        (0..labels.length())
+            .filter_map(|i| labels.item(i))
+            .map(|label| label.unchecked_into::<Element>())
+            .find(|label| {
+                label
+                    .text_content()
+                    .unwrap_or_default()
+                    .starts_with("Diet:")
+            })
+            .and_then(|label| label.query_selector("input").unwrap())
+            .unwrap()
+            .unchecked_into()
+    };
+
+    let root = mount();
+    settle().await;
+    load(&root);
+    settle().await;
+    load_button(&root).click();
+    settle().await;
+    assert!(root.query_selector(".draft-bar").unwrap().is_none());
+    let diet = diet_input(&root);
+    diet.set_value("silage");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    diet.dispatch_event(&event).unwrap();
+    settle().await;
+    assert_eq!(load_draft::<GoatDraft>(&key).unwrap().diet, "silage");
+
+    // Reopening the goat, as after a closed tab, brings the edit back
+    let root = mount();
+    settle().await;
+    load(&root);
+    settle().await;
+    load_button(&root).click();
+    settle().await;
+    assert_eq!(diet_input(&root).value(), "silage");
+    let bar = root.query_selector(".draft-bar").unwrap().unwrap();
+    assert!(
+        bar.text_content()
+            .unwrap_or_default()
+            .contains("Restored your unsaved draft.")
+    );
+    let discard: HtmlElement = bar
+        .query_selector("button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    discard.click();
+    settle().await;
+    assert_eq!(diet_input(&root).value(), "hay");
+    assert!(root.query_selector(".draft-bar").unwrap().is_none());
+    assert!(load_draft::<GoatDraft>(&key).is_none());
+    assert!(mock.calls().is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn update_goat_form_highlights_and_resets_dirty_fields() {
+    discard_draft(&goat_draft_key(UPDATE_GOAT_DRAFT, 8));
+    Dispatch::<GoatStore>::global().set(GoatStore {
+        goats: vec![Goat {
+            id: 8,
+            params: goat("Ganga"),
+            tag_id: None,
+            date_of_birth: None,
+            created_at: None,
+            updated_at: None,
+        }],
+        ..Default::default()
+    });
+    let mock = Rc::new(MockApiClient::with_goats(vec![goat("Ganga")]));
+    let root = mount_point();
+    yew::Renderer::<Harness<UpdateGoatForm>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let type_into = |input: &HtmlInputElement, text: &str| {
+        input.set_value(text);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        input.dispatch_event(&event).unwrap();
+    };
+    let search: HtmlInputElement = root
+        .query_selector("input[placeholder='Goat name to edit']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    type_into(&search, "ganga");
+    settle().await;
+    let load: HtmlElement = root
+        .query_selector("button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    load.click();
+    settle().await;
+    let reset = || -> HtmlElement {
+        let buttons = root.query_selector_all("button").unwrap();
+        (0..buttons.length())
+            .filter_map(|i| buttons.get(i))
+            .find(|b| b.text_content().as_deref() == Some("Reset to loaded values"))
+            .unwrap()
+            .unchecked_into()
+    };
+    let weight = || -> HtmlInputElement {
+        let labels = root.query_selector_all("form label").unwrap();
+        (0..labels.length())
+            .filter_map(|i| labels.item(i))
+            .map(|label| label.unchecked_into::<Element>())
+            .find(|label| {
+                label
+                    .text_content()
+                    .unwrap_or_default()
+                    .starts_with("Weight:")
+            })
+            .and_then(|label| label.query_selector("input").unwrap())
+            .unwrap()
+            .unchecked_into()
+    };
+    assert!(reset().has_attribute("disabled"));
+    assert_eq!(root.query_selector_all("label.dirty").unwrap().length(), 0);
+
+    type_into(&weight(), "33");
+    settle().await;
+    let dirty = root.query_selector_all("label.dirty").unwrap();
+    assert_eq!(dirty.length(), 1);
+    let label = dirty.item(0).unwrap().text_content().unwrap_or_default();
+    assert!(label.starts_with("Weight:"));
+    let summary = root.query_selector(".unsaved-summary").unwrap().unwrap();
+    assert_eq!(
+        summary.text_content().as_deref(),
+        Some("Unsaved changes: Weight (kg)")
+    );
+    assert!(!reset().has_attribute("disabled"));
+
+    reset().click();
+    settle().await;
+    assert_eq!(weight().value(), "30");
+    assert_eq!(root.query_selector_all("label.dirty").unwrap().length(), 0);
+    assert!(root.query_selector(".unsaved-summary").unwrap().is_none());
+    assert!(mock.calls().is_empty());
+}
+
+#[function_component(FailingSection)]
+fn failing_section() -> Html {
+    let report = use_section_error();
+    use_effect_with((), move |_| {
+        report.emit("boom".to_string());
+        || {}
+    });
+    html! { <p>{ "healthy" }</p> }
+}
+
+#[function_component(BoundaryHarness)]
+fn boundary_harness() -> Html {
+    html! {
+        <ErrorBoundary name="Test Section">
+            <FailingSection />
+        </ErrorBoundary>
+    }
+}
+
+#[wasm_bindgen_test]
+async fn error_boundary_shows_fallback_on_reported_error() {
+    let root = mount_point();
+    yew::Renderer::<BoundaryHarness>::with_root(root.clone()).render();
+    settle().await;
+
+    let text = root.text_content().unwrap_or_default();
+    assert!(text.contains("Something went wrong in Test Section"));
+    assert!(text.contains("boom"));
+    assert!(text.contains("Reload section"));
+}
+
+fn goat(name: &str) -> GoatParams {
+    GoatParams::builder(name)
+        .breed(Breed::Beetal)
+        .cost(100.0)
+        .weight(30.0)
+        .current_price(150.0)
+        .diet("hay")
+        .build()
+        .unwrap()
+}
+
+#[wasm_bindgen_test]
+async fn delete_goats_form_submits_through_api() {
+    let mock = Rc::new(MockApiClient::with_goats(vec![goat("Rani"), goat("Moti")]));
+    let root = mount_point();
+    yew::Renderer::<Harness<DeleteGoatsForm>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let input: HtmlInputElement = root
+        .query_selector("input")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    input.set_value("Rani, Ghost");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    input.dispatch_event(&event).unwrap();
+    settle().await;
+
+    let submit: HtmlElement = root
+        .query_selector("button[type=submit]")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    submit.click();
+    settle().await;
+
+    let names: Vec<String> = mock.goats().into_iter().map(|g| g.name).collect();
+    assert_eq!(names, vec!["Moti"]);
+    let text = root.text_content().unwrap_or_default();
+    assert!(text.contains("Deleted successfully."));
+    assert!(text.contains("No goat found with name Ghost"));
+}
+
+#[wasm_bindgen_test]
+async fn breeding_planner_flags_close_relatives() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_recommendations(vec![BreedingRecommendation {
+        buck_name: "Sultan".to_string(),
+        doe_name: "Moti".to_string(),
+        score: 20.0,
+        inbreeding_coefficient: 0.25,
+        close_relatives: true,
+        reasons: vec!["Same breed (Beetal)".to_string()],
+        warnings: vec!["Close relatives: Inbreeding coefficient 25.0%".to_string()],
+    }]);
+    let root = mount_point();
+    yew::Renderer::<Harness<BreedingPlanner>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let plan: HtmlElement = root
+        .query_selector("button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    plan.click();
+    settle().await;
+
+    assert_eq!(mock.calls(), vec!["breeding_recommendations:"]);
+    let text = root.text_content().unwrap_or_default();
+    assert!(text.contains("Sultan"));
+    assert!(text.contains("Close relatives!"));
+    assert!(text.contains("25.0%"));
+}
+
+#[wasm_bindgen_test]
+async fn goat_detail_charts_weights_over_breed_standard() {
+    let mock = Rc::new(MockApiClient::default());
+    let weighing = |age_days: i64, weight: f64| WeightRecord {
+        id: None,
+        goat_name: "Rani".to_string(),
+        weighed_on: String::new(),
+        weight,
+        age_days: Some(age_days),
+        estimated: false,
+    };
+    mock.add_history(GrowthHistory {
+        goat_name: "Rani".to_string(),
+        breed: Breed::Beetal,
+        date_of_birth: Some("2024-01-01".to_string()),
+        records: vec![weighing(0, 3.0), weighing(180, 15.0)],
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<GoatDetail>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::with_props(
+            &mock,
+            yew::props!(GoatDetailProps {
+                name: "Rani",
+                on_close: Callback::noop()
+            }),
+        ),
+    )
+    .render();
+    settle().await;
+
+    // The notes thread and voice notes below the chart load first, as child
+    // effects run before their parent's
+    assert_eq!(
+        mock.calls(),
+        vec![
+            "goat_notes:Rani",
+            "attachments:goat_name=Rani",
+            "weight_history:Rani"
+        ]
+    );
+    for series in ["weighings", "breed standard", "10th percentile"] {
+        let selector = format!("polyline[data-series='{}']", series);
+        assert!(
+            root.query_selector(&selector).unwrap().is_some(),
+            "missing {}",
+            series
+        );
+    }
+    assert_eq!(root.query_selector_all("circle").unwrap().length(), 2);
+}
+
+#[wasm_bindgen_test]
+async fn goat_detail_prefills_weight_estimated_from_photo() {
+    Dispatch::<UnitsStore>::global().reduce_mut(|units| {
+        units.weight = WeightUnit::Kg;
+        units.decimal_separator = '.';
+    });
+    let mock = Rc::new(MockApiClient::with_goats(vec![goat("Rani")]));
+    mock.add_history(GrowthHistory {
+        goat_name: "Rani".to_string(),
+        breed: Breed::Beetal,
+        date_of_birth: None,
+        records: vec![],
+    });
+    mock.set_weight_estimate(WeightEstimate {
+        weight: 31.5,
+        confidence: Some(0.8),
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<GoatDetail>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::with_props(
+            &mock,
+            yew::props!(GoatDetailProps {
+                name: "Rani",
+                on_close: Callback::noop()
+            }),
+        ),
+    )
+    .render();
+    settle().await;
+
+    let photo = b"\xff\xd8 not really a jpeg";
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(&photo[..]));
+    let options = web_sys::FilePropertyBag::new();
+    options.set_type("image/jpeg");
+    let file = web_sys::File::new_with_u8_array_sequence_and_options(&parts, "rani.jpg", &options)
+        .unwrap();
+    let transfer = web_sys::DataTransfer::new().unwrap();
+    transfer.items().add_with_file(&file).unwrap();
+    let photo_input: HtmlInputElement = root
+        .query_selector("input[name='goat_photo']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    photo_input.set_files(transfer.files().as_ref());
+    let button = |label: &str| -> HtmlElement {
+        let buttons = root.query_selector_all("button").unwrap();
+        (0..buttons.length())
+            .filter_map(|i| buttons.get(i))
+            .find(|b| b.text_content().as_deref() == Some(label))
+            .unwrap()
+            .unchecked_into()
+    };
+    button("Estimate from photo").click();
+    settle().await;
+    settle().await;
+
+    let input = |name: &str| -> HtmlInputElement {
+        root.query_selector(&format!("input[name='{}']", name))
+            .unwrap()
+            .unwrap()
+            .unchecked_into()
+    };
+    assert_eq!(input("weight").value(), "31.5");
+    assert!(input("estimated").checked());
+    let note = root.query_selector(".estimate-note").unwrap().unwrap();
+    assert!(
+        note.text_content()
+            .unwrap_or_default()
+            .contains("80% confidence")
+    );
+
+    button("Save weighing").click();
+    settle().await;
+    let weights = mock.weights();
+    assert_eq!(weights.len(), 1);
+    assert!(weights[0].estimated);
+    assert_eq!(weights[0].weight, 31.5);
+    assert_eq!(
+        mock.calls(),
+        vec![
+            "goat_notes:Rani".to_string(),
+            "attachments:goat_name=Rani".to_string(),
+            "weight_history:Rani".to_string(),
+            format!("estimate_weight:{}:image/jpeg:29.7", photo.len()),
+            "add_weight:Rani:31.5".to_string(),
+            "weight_history:Rani".to_string(),
+        ]
+    );
+}
+
+#[wasm_bindgen_test]
+async fn goat_notes_thread_adds_notes_with_photos() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.add_stored_note(GoatNote {
+        id: 1,
+        goat_name: "Rani".to_string(),
+        author: "Asha".to_string(),
+        text: "Limping on left hind leg".to_string(),
+        created_at: "2026-03-01T09:30:00Z".parse().unwrap(),
+        has_photo: true,
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<GoatNotes>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::with_props(&mock, yew::props!(GoatNotesProps { goat_name: "Rani" })),
+    )
+    .render();
+    settle().await;
+
+    let first = root.query_selector("li[data-note='1']").unwrap().unwrap();
+    let text = first.text_content().unwrap_or_default();
+    assert!(text.contains("Asha"));
+    assert!(text.contains("2026-03-01 09:30"));
+    let photo = first.query_selector("img").unwrap().unwrap();
+    assert!(photo.get_attribute("src").unwrap().starts_with("blob:"));
+    assert!(
+        mock.calls()
+            .contains(&"download:http://127.0.0.1:8000/notes/1/photo".to_string())
+    );
+
+    let input: HtmlInputElement = root
+        .query_selector("input[name='note_author']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    input.set_value("Ravi");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    input
+        .dispatch_event(&web_sys::Event::new_with_event_init_dict("input", &init).unwrap())
+        .unwrap();
+    let area: web_sys::HtmlTextAreaElement = root
+        .query_selector("textarea[name='note_text']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    area.set_value("Walking fine after trimming");
+    area.dispatch_event(&web_sys::Event::new_with_event_init_dict("input", &init).unwrap())
+        .unwrap();
+
+    let bytes = b"\xff\xd8 hoof";
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(&bytes[..]));
+    let options = web_sys::FilePropertyBag::new();
+    options.set_type("image/jpeg");
+    let file = web_sys::File::new_with_u8_array_sequence_and_options(&parts, "hoof.jpg", &options)
+        .unwrap();
+    let transfer = web_sys::DataTransfer::new().unwrap();
+    transfer.items().add_with_file(&file).unwrap();
+    let photo_input: HtmlInputElement = root
+        .query_selector("input[name='note_photo']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    photo_input.set_files(transfer.files().as_ref());
+
+    let submit: HtmlElement = root
+        .query_selector("form.add-note button[type='submit']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    submit.click();
+    settle().await;
+    settle().await;
+
+    // Photos are fetched as they are shown; only the writes matter here
+    let calls: Vec<String> = mock
+        .calls()
+        .into_iter()
+        .filter(|c| !c.starts_with("download:"))
+        .collect();
+    assert_eq!(
+        calls,
+        vec![
+            "goat_notes:Rani".to_string(),
+            "add_note:Rani:Ravi".to_string(),
+            format!("attach_note_photo:2:{}:image/jpeg", bytes.len()),
+            "goat_notes:Rani".to_string(),
+        ]
+    );
+    let notes = mock.notes();
+    assert_eq!(notes[1].text, "Walking fine after trimming");
+    assert!(notes[1].has_photo);
+    assert_eq!(root.query_selector_all("li.goat-note").unwrap().length(), 2);
+    assert_eq!(area.value(), "");
+
+    let delete: HtmlElement = first
+        .query_selector("button[name='delete_note']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    delete.click();
+    settle().await;
+    assert_eq!(mock.notes().len(), 1);
+    assert!(root.query_selector("li[data-note='1']").unwrap().is_none());
+}
+
+#[wasm_bindgen_test]
+async fn voice_notes_play_inline_and_delete() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.add_stored_attachment(Attachment {
+        id: 1,
+        goat_name: Some("Rani".to_string()),
+        task_id: None,
+        author: "Asha".to_string(),
+        content_type: "audio/webm".to_string(),
+        duration_secs: Some(12.4),
+        size_bytes: 4000,
+        created_at: "2026-03-01T09:30:00Z".parse().unwrap(),
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<VoiceNotes>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::with_props(
+            &mock,
+            yew::props!(VoiceNotesProps {
+                target: AttachmentTarget::Goat("Rani".to_string())
+            }),
+        ),
+    )
+    .render();
+    settle().await;
+
+    let note = root
+        .query_selector("li[data-attachment='1']")
+        .unwrap()
+        .unwrap();
+    let text = note.text_content().unwrap_or_default();
+    assert!(text.contains("Asha"));
+    assert!(text.contains("12 s"));
+    let audio = note.query_selector("audio").unwrap().unwrap();
+    assert_eq!(
+        audio.get_attribute("data-url").as_deref(),
+        Some("http://127.0.0.1:8000/attachments/1")
+    );
+    assert!(audio.get_attribute("src").unwrap().starts_with("blob:"));
+    assert!(
+        root.query_selector("button[name='record']")
+            .unwrap()
+            .is_some()
+    );
+
+    let delete: HtmlElement = note
+        .query_selector("button[name='delete_voice_note']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    delete.click();
+    settle().await;
+    assert_eq!(
+        mock.calls(),
+        vec![
+            "attachments:goat_name=Rani",
+            "download:http://127.0.0.1:8000/attachments/1",
+            "delete_attachment:1",
+            "attachments:goat_name=Rani",
+        ]
+    );
+    assert!(mock.attachments().is_empty());
+    assert!(root.query_selector("li.voice-note").unwrap().is_none());
+}
+
+#[wasm_bindgen_test]
+async fn mention_inbox_lists_and_marks_notifications_read() {
+    let storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+    storage.set_item("yagi.notes.author", "Ravi").unwrap();
+    let mock = Rc::new(MockApiClient::default());
+    mock.add_notification(Notification {
+        id: 7,
+        worker_name: "Ravi".to_string(),
+        goat_name: Some("Rani".to_string()),
+        note_id: Some(1),
+        message: "Asha mentioned you on Rani: Limping, @Ravi can you look?".to_string(),
+        link: "#goat-1".to_string(),
+        created_at: "2026-03-01T09:30:00Z".parse().unwrap(),
+        read: false,
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<MentionInbox>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let bell: HtmlElement = root
+        .query_selector("button[name='notifications']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    assert!(bell.text_content().unwrap().ends_with('1'));
+    bell.click();
+    settle().await;
+    let link: HtmlElement = root
+        .query_selector("li[data-notification='7'] a")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    assert_eq!(link.get_attribute("href").unwrap(), "#goat-1");
+    assert!(
+        link.text_content()
+            .unwrap()
+            .starts_with("Asha mentioned you")
+    );
+
+    link.click();
+    settle().await;
+    assert_eq!(
+        mock.calls(),
+        vec![
+            "unread_notifications:Ravi".to_string(),
+            "mark_notification_read:7".to_string(),
+        ]
+    );
+    assert!(mock.notifications()[0].read);
+    assert!(bell.text_content().unwrap().ends_with('0'));
+    storage.remove_item("yagi.notes.author").unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn milk_analytics_compares_does() {
+    let lactation = |doe: &str, average: f64| Lactation {
+        doe_name: doe.to_string(),
+        kidded_on: Some("2025-03-01".to_string()),
+        days_in_milk: 60,
+        total_yield: average * 60.0,
+        peak_daily_yield: average * 1.5,
+        peak_day: 35,
+        average_daily_yield: average,
+        curve: vec![
+            LactationPoint {
+                week: 1,
+                average_daily_yield: average,
+            },
+            LactationPoint {
+                week: 5,
+                average_daily_yield: average * 1.5,
+            },
+        ],
+    };
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_lactations(vec![lactation("Rani", 2.0), lactation("Moti", 0.5)]);
+    let root = mount_point();
+    yew::Renderer::<Harness<MilkAnalytics>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    assert_eq!(mock.calls(), vec!["lactations:"]);
+    for doe in ["Rani", "Moti"] {
+        let selector = format!("polyline[data-series='{}']", doe);
+        assert!(
+            root.query_selector(&selector).unwrap().is_some(),
+            "missing curve for {}",
+            doe
+        );
+    }
+    // Moti averages far below the herd and is highlighted
+    let flagged = root.query_selector("tr[title]").unwrap().unwrap();
+    assert!(flagged.text_content().unwrap_or_default().contains("Moti"));
+}
+
+#[wasm_bindgen_test]
+async fn feed_efficiency_panel_lists_goats_and_pens() {
+    let period = Some(("2025-01-01".to_string(), "2025-03-01".to_string()));
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_feed_efficiency(FeedEfficiencyReport {
+        goats: vec![
+            FeedEfficiency::new("Rani".to_string(), period.clone(), 20.0, 600.0, 5.0),
+            // Lost weight, so no ratio
+            FeedEfficiency::new("Moti".to_string(), period.clone(), 10.0, 300.0, -1.0),
+        ],
+        pens: vec![FeedEfficiency::new(
+            "North Pen".to_string(),
+            period,
+            30.0,
+            900.0,
+            4.0,
+        )],
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<FeedEfficiencyPanel>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    assert_eq!(mock.calls(), vec!["feed_efficiency"]);
+    let fcr = |name: &str| {
+        root.query_selector(&format!("tr[data-name='{}'] td.fcr", name))
+            .unwrap()
+            .unwrap()
+            .text_content()
+            .unwrap_or_default()
+    };
+    assert_eq!(fcr("Rani"), "4.00");
+    assert_eq!(fcr("Moti"), "–");
+    assert_eq!(fcr("North Pen"), "7.50");
+}
+
+#[wasm_bindgen_test]
+async fn pens_view_shows_occupancy_and_overstocking() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_occupancy(vec![
+        SpaceOccupancy::new(
+            1,
+            "Kid Pen".into(),
+            SpaceKind::Enclosure,
+            None,
+            Some(15.0),
+            5,
+        ),
+        SpaceOccupancy::new(
+            2,
+            "North Pen".into(),
+            SpaceKind::Enclosure,
+            Some(4),
+            None,
+            5,
+        ),
+    ]);
+    let root = mount_point();
+    yew::Renderer::<Harness<PensView>>::with_root_and_props(root.clone(), HarnessProps::new(&mock))
+        .render();
+    settle().await;
+
+    assert_eq!(mock.calls(), vec!["space_occupancy"]);
+    let occupancy = |name: &str| {
+        root.query_selector(&format!("tr[data-name='{}'] td.occupancy", name))
+            .unwrap()
+            .unwrap()
+            .text_content()
+            .unwrap_or_default()
+    };
+    assert_eq!(occupancy("Kid Pen"), "50%");
+    assert_eq!(occupancy("North Pen"), "125%");
+    let warnings = root.query_selector(".stocking-warnings").unwrap().unwrap();
+    assert_eq!(
+        warnings.text_content().unwrap_or_default(),
+        "North Pen holds 5 goats but has room for 4"
+    );
+}
+
+#[wasm_bindgen_test]
+async fn rotation_planner_shades_conflicts_and_adds_paddocks() {
+    let assign = |group: &str, paddock: &str, conflict: bool| GrazingAssignment {
+        group: group.to_string(),
+        paddock: Some(paddock.to_string()),
+        conflict,
+    };
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_rotation_plan(RotationPlan {
+        groups: vec![
+            GrazingGroup {
+                name: "North Pen".into(),
+                goats: 6,
+            },
+            GrazingGroup {
+                name: "Kid Pen".into(),
+                goats: 3,
+            },
+        ],
+        weeks: vec![
+            RotationWeek {
+                starts_on: "2026-04-06".into(),
+                assignments: vec![
+                    assign("North Pen", "Hill", false),
+                    assign("Kid Pen", "Brook", false),
+                ],
+            },
+            RotationWeek {
+                starts_on: "2026-04-13".into(),
+                assignments: vec![
+                    assign("North Pen", "Hill", true),
+                    assign("Kid Pen", "Brook", false),
+                ],
+            },
+        ],
+        conflicts: vec![RotationConflict {
+            week_starts_on: "2026-04-13".into(),
+            group: "North Pen".into(),
+            message: "North Pen goes on Hill after 0 days' rest; it needs 35".into(),
+        }],
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<RotationPlanner>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let calls = mock.calls();
+    assert_eq!(calls[0], "paddocks");
+    assert!(calls[1].starts_with("rotation_plan:") && calls[1].ends_with(":8"));
+    let cells = root
+        .query_selector_all("tr[data-group='North Pen'] td.conflict")
+        .unwrap();
+    assert_eq!(cells.length(), 1);
+    let cell: Element = cells.get(0).unwrap().unchecked_into();
+    assert_eq!(cell.text_content().unwrap_or_default(), "Hill");
+    assert!(cell.get_attribute("style").unwrap().contains("#fdecea"));
+    assert_eq!(
+        cell.get_attribute("title").unwrap(),
+        "North Pen goes on Hill after 0 days' rest; it needs 35"
+    );
+    assert_eq!(
+        root.query_selector_all("tr[data-group='Kid Pen'] td.conflict")
+            .unwrap()
+            .length(),
+        0
+    );
+    let conflicts = root.query_selector(".rotation-conflicts").unwrap().unwrap();
+    assert_eq!(
+        conflicts.text_content().unwrap_or_default(),
+        "Week of 2026-04-13: North Pen goes on Hill after 0 days' rest; it needs 35"
+    );
+
+    let name: HtmlInputElement = root
+        .query_selector("input[name='paddock_name']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    name.set_value("Brook");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    name.dispatch_event(&event).unwrap();
+    settle().await;
+    let submit: HtmlElement = root
+        .query_selector("form.add-paddock button[type=submit]")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    submit.click();
+    settle().await;
+
+    assert!(mock.calls().contains(&"add_paddock:Brook:35".to_string()));
+    assert_eq!(mock.paddocks()[0].name, "Brook");
+    assert!(
+        root.query_selector("tr[data-name='Brook']")
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[wasm_bindgen_test]
+async fn incident_heatmap_shades_busiest_pen() {
+    let row = |name: &str, counts: Vec<u32>| HeatMapRow {
+        space_id: Some(1),
+        space_name: name.to_string(),
+        total: counts.iter().sum(),
+        counts,
+    };
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_heatmap(HealthHeatMap {
+        from: "2025-01-01".to_string(),
+        to: "2025-02-28".to_string(),
+        months: vec!["2025-01".to_string(), "2025-02".to_string()],
+        rows: vec![row("Low Pen", vec![4, 1]), row("Hill Pen", vec![0, 0])],
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<IncidentHeatMap>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    assert_eq!(mock.calls(), vec!["health_heatmap:.."]);
+    let cells = root
+        .query_selector_all("tr[data-pen='Low Pen'] td[data-count]")
+        .unwrap();
+    assert_eq!(cells.length(), 2);
+    let style = |selector: &str| {
+        root.query_selector(selector)
+            .unwrap()
+            .unwrap()
+            .get_attribute("style")
+            .unwrap_or_default()
+    };
+    assert!(style("tr[data-pen='Low Pen'] td[data-count='4']").contains("rgba(200, 40, 40, 1.00)"));
+    assert!(style("tr[data-pen='Hill Pen'] td[data-count='0']").contains("#fff"));
+}
+
+#[wasm_bindgen_test]
+async fn culling_helper_sends_weights_and_ranks_goats() {
+    let score = |name: &str, score: f64, recommendation: Recommendation| GoatScore {
+        goat_name: name.to_string(),
+        gender: Gender::Female,
+        score,
+        breakdown: ScoreBreakdown {
+            health: Some(score),
+            ..Default::default()
+        },
+        recommendation,
+    };
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_scores(vec![
+        score("Moti", 20.0, Recommendation::Cull),
+        score("Rani", 90.0, Recommendation::Keep),
+    ]);
+    let root = mount_point();
+    yew::Renderer::<Harness<CullingHelper>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let milk: HtmlInputElement = root
+        .query_selector("input[name='milk']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    milk.set_value("3");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    milk.dispatch_event(&event).unwrap();
+    settle().await;
+    let button: HtmlElement = root
+        .query_selector("button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    button.click();
+    settle().await;
+
+    assert_eq!(mock.calls(), vec!["goat_scores:3,1,1,1,1"]);
+    let first = root.query_selector("tbody tr").unwrap().unwrap();
+    assert_eq!(first.get_attribute("data-goat").as_deref(), Some("Moti"));
+    let label = root
+        .query_selector("tr[data-goat='Moti'] td.recommendation")
+        .unwrap()
+        .unwrap();
+    assert_eq!(label.text_content().as_deref(), Some("Cull"));
+}
+
+#[wasm_bindgen_test]
+async fn weigh_session_assigns_unknown_tags() {
+    let reading = |id: i64, tag: &str, goat: Option<&str>| ScaleReading {
+        id,
+        tag_id: tag.to_string(),
+        weight: 30.0 + id as f64,
+        read_at: format!("2026-01-10T08:0{}:00Z", id).parse().unwrap(),
+        goat_name: goat.map(str::to_string),
+        weight_record_id: goat.map(|_| id),
+    };
+    let mock = Rc::new(MockApiClient::default());
+    mock.add_reading(reading(1, "TAG-1", Some("Rani")));
+    mock.add_reading(reading(2, "TAG-2", None));
+    let root = mount_point();
+    yew::Renderer::<Harness<WeighSession>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+    assert!(mock.calls().is_empty());
+
+    let start: HtmlElement = root
+        .query_selector("button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    start.click();
+    settle().await;
+    assert_eq!(mock.calls(), vec!["scale_readings:"]);
+    let goat_cell = |id: i64| {
+        root.query_selector(&format!("tr[data-reading='{}'] td.goat", id))
+            .unwrap()
+            .unwrap()
+    };
+    assert_eq!(goat_cell(1).text_content().as_deref(), Some("Rani"));
+
+    let input: HtmlInputElement = goat_cell(2)
+        .query_selector("input")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    input.set_value("Moti");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    input.dispatch_event(&event).unwrap();
+    settle().await;
+    let assign: HtmlElement = goat_cell(2)
+        .query_selector("button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    assign.click();
+    settle().await;
+
+    assert!(mock.calls().contains(&"assign_reading:2:Moti".to_string()));
+    assert_eq!(goat_cell(2).text_content().as_deref(), Some("Moti"));
+    start.click();
+    settle().await;
+}
+
+#[wasm_bindgen_test]
+async fn barn_conditions_highlight_out_of_range_sensors() {
+    let condition =
+        |name: &str, kind: SensorKind, value: f64, alert: Option<ThresholdAlert>| SensorCondition {
+            sensor: Sensor {
+                id: Some(1),
+                name: name.to_string(),
+                kind,
+                space_id: None,
+                space_name: Some("Main Barn".to_string()),
+                goat_name: None,
+                thresholds: Thresholds {
+                    min: Some(5.0),
+                    max: Some(30.0),
+                },
+            },
+            latest: Some(SensorReading {
+                sensor_id: 1,
+                value,
+                read_at: "2026-06-02T12:00:00Z".parse().unwrap(),
+            }),
+            alert,
+        };
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_conditions(vec![
+        condition("Barn humidity", SensorKind::Humidity, 20.0, None),
+        condition(
+            "Barn temp",
+            SensorKind::Temperature,
+            34.5,
+            Some(ThresholdAlert::High),
+        ),
+    ]);
+    let root = mount_point();
+    yew::Renderer::<Harness<BarnConditions>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    assert_eq!(mock.calls(), vec!["sensor_conditions"]);
+    let cell = |selector: &str| {
+        root.query_selector(selector)
+            .unwrap()
+            .unwrap()
+            .text_content()
+            .unwrap_or_default()
+    };
+    assert_eq!(cell("tr[data-sensor='Barn temp'] td.reading"), "34.5 °C");
+    assert_eq!(cell("tr[data-sensor='Barn temp'] td.status"), "Too high");
+    assert_eq!(cell("tr[data-sensor='Barn humidity'] td.status"), "OK");
+    assert_eq!(cell(".sensor-alerts"), "1 sensor(s) outside their limits");
+}
+
+#[wasm_bindgen_test]
+async fn water_panel_flags_intake_drops_and_marks_troughs_cleaned() {
+    let pen = |id: i64, name: &str, litres: f64, drop: Option<WaterDrop>| PenWater {
+        space_id: id,
+        space_name: name.to_string(),
+        goats: 4,
+        days: vec![
+            WaterDay {
+                date: "2026-06-01".to_string(),
+                litres: 40.0,
+            },
+            WaterDay {
+                date: "2026-06-02".to_string(),
+                litres,
+            },
+        ],
+        baseline: Some(40.0),
+        drop,
+        last_cleaned: None,
+        cleaning_task_id: Some(id),
+    };
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_water(vec![
+        pen(
+            1,
+            "Kid Pen",
+            10.0,
+            Some(WaterDrop {
+                date: "2026-06-02".to_string(),
+                litres: 10.0,
+                baseline: 40.0,
+            }),
+        ),
+        pen(2, "Main Barn", 38.0, None),
+    ]);
+    let root = mount_point();
+    yew::Renderer::<Harness<WaterPanel>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let cell = |selector: &str| {
+        root.query_selector(selector)
+            .unwrap()
+            .unwrap()
+            .text_content()
+            .unwrap_or_default()
+    };
+    assert_eq!(
+        cell(".water-drops"),
+        "Water intake dropped in 1 pen(s); check the goats"
+    );
+    assert_eq!(
+        cell("tr[data-pen='Kid Pen'] .water-drop"),
+        " (75% below average on 2026-06-02)"
+    );
+    assert_eq!(cell("tr[data-pen='Kid Pen'] .last-cleaned"), "Never (due)");
+    assert!(
+        root.query_selector("tr[data-pen='Main Barn'] .water-drop")
+            .unwrap()
+            .is_none()
+    );
+
+    let clean: HtmlElement = root
+        .query_selector("tr[data-pen='Kid Pen'] .mark-cleaned")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    clean.click();
+    settle().await;
+    assert!(mock.calls().contains(&"mark_trough_cleaned:1".to_string()));
+    assert_eq!(
+        cell(".water-message"),
+        "Troughs in Kid Pen cleaned; next due in 3 days"
+    );
+    assert!(!cell("tr[data-pen='Kid Pen'] .last-cleaned").contains("Never"));
+}
+
+#[wasm_bindgen_test]
+async fn stock_expiry_disposes_of_expired_items() {
+    let stock = |id: i64, name: &str, quantity: f64, days_left: i64| ExpiringStock {
+        item: InventoryItem {
+            id: Some(id),
+            name: name.to_string(),
+            category: InventoryCategory::Medicine,
+            quantity,
+            unit: "ml".to_string(),
+            unit_cost: 1.5,
+            expiry_date: Some("2026-06-01".to_string()),
+            reorder_level: 0.0,
+        },
+        days_left,
+        value: quantity * 1.5,
+    };
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_expiring(vec![
+        stock(1, "Antibiotic", 40.0, -10),
+        stock(2, "Vitamin B", 100.0, 5),
+    ]);
+    let root = mount_point();
+    yew::Renderer::<Harness<StockExpiry>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let cell = |selector: &str| {
+        root.query_selector(selector)
+            .unwrap()
+            .unwrap()
+            .text_content()
+            .unwrap_or_default()
+    };
+    assert_eq!(
+        cell(".expired-summary"),
+        "1 expired item(s) worth 60.00 still in stock"
+    );
+    assert_eq!(
+        cell("tr[data-item='Antibiotic'] .expiry"),
+        "Expired 10 days ago"
+    );
+    assert_eq!(
+        cell("tr[data-item='Vitamin B'] .expiry"),
+        "Expires in 5 days"
+    );
+
+    let dispose: HtmlElement = root
+        .query_selector("tr[data-item='Antibiotic'] .dispose-item")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    dispose.click();
+    settle().await;
+    assert!(mock.calls().contains(&"dispose_item:1".to_string()));
+    assert_eq!(
+        cell(".disposal-message"),
+        "Disposed of 40 ml Antibiotic; 60.00 written off"
+    );
+    assert!(root.query_selector(".expired-summary").unwrap().is_none());
+    assert_eq!(
+        root.query_selector_all(".dispose-item").unwrap().length(),
+        1
+    );
+}
+
+#[wasm_bindgen_test]
+async fn vet_visits_book_a_free_vet_and_record_findings() {
+    let visit = |id: i64, reason: &str, status: VisitStatus, vet: Option<(i64, &str)>| VetVisit {
+        id,
+        status,
+        reason: reason.to_string(),
+        agenda: vec!["Moti".to_string(), "Rani".to_string()],
+        vet_id: vet.map(|(id, _)| id),
+        vet_name: vet.map(|(_, name)| name.to_string()),
+        requested_on: "2026-10-01".to_string(),
+        scheduled_for: vet.map(|_| "2026-10-15".to_string()),
+        completed_on: None,
+        summary: None,
+        treated: Vec::new(),
+    };
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_vets(vec![
+        VetAvailability {
+            vet_id: 1,
+            vet_name: "Dr Rao".to_string(),
+            booked: vec!["2026-11-02".to_string()],
+        },
+        VetAvailability {
+            vet_id: 2,
+            vet_name: "Dr Sen".to_string(),
+            booked: Vec::new(),
+        },
+    ]);
+    mock.set_vet_visits(vec![
+        visit(2, "Herd check", VisitStatus::Scheduled, Some((2, "Dr Sen"))),
+        visit(1, "Coughing", VisitStatus::Requested, None),
+    ]);
+    let root = mount_point();
+    yew::Renderer::<Harness<VetVisits>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let query = |selector: &str| root.query_selector(selector).unwrap().unwrap();
+    let cell = |selector: &str| query(selector).text_content().unwrap_or_default();
+    let type_into = |selector: &str, text: &str| {
+        let input: HtmlInputElement = query(selector).unchecked_into();
+        input.set_value(text);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        input.dispatch_event(&event).unwrap();
+    };
+    let click = |selector: &str| query(selector).unchecked_into::<HtmlElement>().click();
+    assert_eq!(cell("tr[data-visit='1'] .visit-status"), "Requested");
+    assert_eq!(
+        cell(".visit-vet option[value='1']"),
+        "Dr Rao (booked 2026-11-02)"
+    );
+    assert_eq!(cell(".visit-vet option[value='2']"), "Dr Sen (free)");
+
+    // A day the vet is already booked is flagged before it is sent
+    click("tr[data-visit='1'] .schedule-visit");
+    settle().await;
+    let vet: HtmlSelectElement = query(".book-vet").unchecked_into();
+    vet.set_value("1");
+    change(&vet);
+    type_into(".book-day", "2026-11-02");
+    settle().await;
+    assert_eq!(
+        cell(".visit-booking .visit-clash"),
+        " Dr Rao is already booked on 2026-11-02"
+    );
+    assert!(query(".book-visit").has_attribute("disabled"));
+
+    type_into(".book-day", "2026-11-03");
+    settle().await;
+    click(".book-visit");
+    settle().await;
+    assert!(
+        mock.calls()
+            .contains(&"schedule_vet_visit:1:1:2026-11-03".to_string())
+    );
+    assert_eq!(
+        cell(".visit-message"),
+        "Visit booked with Dr Rao on 2026-11-03"
+    );
+    assert_eq!(cell("tr[data-visit='1'] .visit-status"), "Scheduled");
+
+    // Completing records a finding for each goat with a condition
+    click("tr[data-visit='2'] .complete-visit");
+    settle().await;
+    assert_eq!(
+        root.query_selector_all(".visit-finding").unwrap().length(),
+        2
+    );
+    type_into(".visit-summary-input", "Rani has a chest infection");
+    type_into(
+        ".visit-finding[data-goat='Rani'] .finding-condition",
+        "Pneumonia",
+    );
+    type_into(
+        ".visit-finding[data-goat='Rani'] .finding-treatment",
+        "Oxytetracycline",
+    );
+    settle().await;
+    click(".submit-completion");
+    settle().await;
+    assert!(mock.calls().contains(&"complete_vet_visit:2".to_string()));
+    assert_eq!(
+        cell(".visit-message"),
+        "Visit completed; 1 treatment record(s) added"
+    );
+    assert_eq!(cell("tr[data-visit='2'] .visit-status"), "Completed");
+    assert!(root.query_selector(".visit-completion").unwrap().is_none());
+}
+
+#[wasm_bindgen_test]
+async fn health_timeline_flags_lab_results_out_of_range() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_timeline(vec![
+        TimelineEntry {
+            date: "2026-04-10".to_string(),
+            kind: TimelineKind::LabResult,
+            title: "Lab result: CBC".to_string(),
+            details: Some("PCV 15 % (low, range 22–38)".to_string()),
+            flagged: true,
+            lab_result_id: Some(1),
+        },
+        TimelineEntry {
+            date: "2026-03-01".to_string(),
+            kind: TimelineKind::Vaccination,
+            title: "Vaccinated: CDT".to_string(),
+            details: None,
+            flagged: false,
+            lab_result_id: None,
+        },
+    ]);
+    mock.set_lab_results(vec![LabResult {
+        id: Some(1),
+        goat_name: "Rani".to_string(),
+        test_type: "CBC".to_string(),
+        sampled_on: "2026-04-10".to_string(),
+        lab: None,
+        values: vec![LabValue {
+            analyte: "PCV".to_string(),
+            value: 15.0,
+            unit: "%".to_string(),
+            low: Some(22.0),
+            high: Some(38.0),
+        }],
+        notes: None,
+        has_report: true,
+    }]);
+    let root = mount_point();
+    yew::Renderer::<Harness<HealthTimeline>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::with_props(
+            &mock,
+            yew::props!(HealthTimelineProps { goat_name: "Rani" }),
+        ),
+    )
+    .render();
+    settle().await;
+
+    let query = |selector: &str| root.query_selector(selector).unwrap().unwrap();
+    let cell = |selector: &str| query(selector).text_content().unwrap_or_default();
+    let type_into = |selector: &str, text: &str| {
+        let input: HtmlInputElement = query(selector).unchecked_into();
+        input.set_value(text);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        input.dispatch_event(&event).unwrap();
+    };
+    assert!(mock.calls().contains(&"health_timeline:Rani".to_string()));
+    assert_eq!(
+        root.query_selector_all(".timeline-entry").unwrap().length(),
+        2
+    );
+    assert_eq!(
+        root.query_selector_all(".timeline-entry.flagged")
+            .unwrap()
+            .length(),
+        1
+    );
+    assert_eq!(
+        cell(".flagged .timeline-details"),
+        "PCV 15 % (low, range 22–38)"
+    );
+    assert_eq!(
+        query(".flagged .lab-report")
+            .get_attribute("data-url")
+            .as_deref(),
+        Some("http://127.0.0.1:8000/lab-results/1/report")
+    );
+    assert!(
+        root.query_selector(".vaccination .lab-report")
+            .unwrap()
+            .is_none()
+    );
+
+    // Values without a unit or range take the analyte's usual ones
+    type_into(".lab-test-type", "CBC");
+    type_into(".lab-sampled-on", "2026-05-02");
+    type_into(".lab-analyte", "Hemoglobin");
+    type_into(".lab-value", "14.5");
+    settle().await;
+    query(".save-lab-result")
+        .unchecked_into::<HtmlElement>()
+        .click();
+    settle().await;
+    assert!(
+        mock.calls()
+            .contains(&"add_lab_result:Rani:CBC".to_string())
+    );
+    assert_eq!(
+        cell(".lab-message"),
+        "Lab result saved; out of range: Hemoglobin 14.5 g/dL (high)"
+    );
+    assert_eq!(
+        query(".lab-test-type")
+            .unchecked_into::<HtmlInputElement>()
+            .value(),
+        ""
+    );
+}
+
+#[wasm_bindgen_test]
+async fn disease_compliance_flags_diseases_and_lists_cases() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_diseases(vec![
+        CatalogDisease {
+            id: 1,
+            name: "Brucellosis".to_string(),
+            notifiable: true,
+            goat_count: 1,
+        },
+        CatalogDisease {
+            id: 2,
+            name: "Mastitis".to_string(),
+            notifiable: false,
+            goat_count: 2,
+        },
+    ]);
+    mock.set_compliance(ComplianceReport {
+        generated_on: "2026-04-20".to_string(),
+        diseases: vec!["Brucellosis".to_string()],
+        cases: vec![ComplianceCase {
+            disease: "Brucellosis".to_string(),
+            goat_name: "Rani".to_string(),
+            tag_id: Some("IN-0042".to_string()),
+            status: CaseStatus::Confirmed,
+            first_reported: None,
+            tests: vec![DiseaseTest {
+                sampled_on: "2026-03-01".to_string(),
+                test_type: "Brucellosis RBPT".to_string(),
+                out_of_range: true,
+            }],
+        }],
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<DiseaseCompliance>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let query = |selector: &str| root.query_selector(selector).unwrap().unwrap();
+    let cell = |selector: &str| query(selector).text_content().unwrap_or_default();
+    assert_eq!(
+        cell(".compliance-covers"),
+        "As of 2026-04-20, covering Brucellosis"
+    );
+    assert_eq!(
+        root.query_selector_all(".compliance-case")
+            .unwrap()
+            .length(),
+        1
+    );
+    assert_eq!(cell(".case-status"), "Confirmed");
+    assert_eq!(
+        cell(".case-tests"),
+        "2026-03-01 Brucellosis RBPT (out of range)"
+    );
+    let csv = query(".compliance-csv");
+    assert_eq!(
+        csv.get_attribute("data-url").as_deref(),
+        Some("http://127.0.0.1:8000/diseases/compliance?format=csv")
+    );
+    assert_eq!(
+        csv.get_attribute("download").as_deref(),
+        Some("notifiable-diseases-2026-04-20.csv")
+    );
+
+    // Flagging a disease reloads the report
+    let mastitis: HtmlInputElement =
+        query("[data-disease='2'] .disease-notifiable").unchecked_into();
+    assert!(!mastitis.checked());
+    mastitis.set_checked(true);
+    change(&mastitis);
+    settle().await;
+    assert!(
+        mock.calls()
+            .contains(&"set_disease_notifiable:2:true".to_string())
+    );
+    assert_eq!(
+        mock.calls()
+            .iter()
+            .filter(|c| *c == "compliance_report")
+            .count(),
+        2
+    );
+
+    let name: HtmlInputElement = query(".new-disease").unchecked_into();
+    name.set_value("Brucellosis");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    name.dispatch_event(&web_sys::Event::new_with_event_init_dict("input", &init).unwrap())
+        .unwrap();
+    settle().await;
+    query(".add-disease")
+        .unchecked_into::<HtmlElement>()
+        .click();
+    settle().await;
+    assert!(
+        mock.calls()
+            .contains(&"add_disease:Brucellosis:true".to_string())
+    );
+    assert!(root.text_content().unwrap().contains("Error: "));
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("Brucellosis is already in the catalog")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn traceability_records_link_buyers_to_the_record() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_traceability(vec![TraceabilityRecord {
+        token: "yagi_abc".to_string(),
+        goat_name: "Rani".to_string(),
+        tag_id: Some("IN-0042".to_string()),
+        breed: "Beetal".to_string(),
+        gender: "Female".to_string(),
+        date_of_birth: Some("2025-11-02".to_string()),
+        farm_name: Some("Green Hills".to_string()),
+        farm_gstin: None,
+        sold_on: "2026-04-01".to_string(),
+        buyer_name: Some("Mehta Farms".to_string()),
+        invoice_number: Some("INV-7".to_string()),
+        diseases: vec![],
+        health: vec![TimelineEntry {
+            date: "2026-01-15".to_string(),
+            kind: TimelineKind::Vaccination,
+            title: "Vaccinated: PPR".to_string(),
+            details: None,
+            flagged: false,
+            lab_result_id: None,
+        }],
+        movements: vec![
+            Movement {
+                date: Some("2025-11-02".to_string()),
+                kind: MovementKind::Born,
+                description: "Born on the farm to Ganga".to_string(),
+            },
+            Movement {
+                date: Some("2026-04-01".to_string()),
+                kind: MovementKind::Sold,
+                description: "Sold to Mehta Farms".to_string(),
+            },
+        ],
+    }]);
+    let root = mount_point();
+    yew::Renderer::<Harness<TraceabilityRecords>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::with_props(
+            &mock,
+            yew::props!(TraceabilityRecordsProps { goat_name: "Rani" }),
+        ),
+    )
+    .render();
+    settle().await;
+
+    let query = |selector: &str| root.query_selector(selector).unwrap().unwrap();
+    let cell = |selector: &str| query(selector).text_content().unwrap_or_default();
+    assert_eq!(mock.calls(), vec!["traceability:Rani"]);
+    assert_eq!(
+        cell(".traceability-sale"),
+        "Sold 2026-04-01 to Mehta Farms (invoice INV-7)"
+    );
+    assert_eq!(
+        cell(".traceability-health"),
+        "1 vaccination, 0 incidents, 0 lab results"
+    );
+    assert_eq!(
+        root.query_selector_all(".traceability-movements li")
+            .unwrap()
+            .length(),
+        2
+    );
+    assert_eq!(
+        query(".traceability-link").get_attribute("href").as_deref(),
+        Some("http://127.0.0.1:8000/public/traceability/yagi_abc")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn movement_log_registers_movements_by_goat_and_period() {
+    let place = |kind: PlaceKind, name: &str| Place {
+        kind,
+        name: name.to_string(),
+    };
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_movements(vec![AnimalMovement {
+        id: Some(1),
+        goats: vec!["Moti".to_string(), "Rani".to_string()],
+        tags: vec![Some("IN-0042".to_string()), None],
+        from: place(PlaceKind::Farm, "Green Hills"),
+        to: place(PlaceKind::Market, "Pune market"),
+        departed_on: "2026-02-10".to_string(),
+        arrived_on: None,
+        transporter: Some("Patil Transport".to_string()),
+        vehicle: None,
+        permit_number: Some("MP-12".to_string()),
+        notes: None,
+    }]);
+    let root = mount_point();
+    yew::Renderer::<Harness<MovementLog>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let query = |selector: &str| root.query_selector(selector).unwrap().unwrap();
+    let cell = |selector: &str| query(selector).text_content().unwrap_or_default();
+    let type_into = |selector: &str, text: &str| {
+        let input: HtmlInputElement = query(selector).unchecked_into();
+        input.set_value(text);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        input.dispatch_event(&event).unwrap();
+    };
+    let click = |selector: &str| query(selector).unchecked_into::<HtmlElement>().click();
+    let rows = || root.query_selector_all(".movement-row").unwrap().length();
+    assert_eq!(mock.calls(), vec!["movements:::.."]);
+    assert_eq!(
+        cell(".movement-summary"),
+        "Green Hills (Farm) → Pune market (Market) by Patil Transport"
+    );
+
+    // A movement back to where it started is refused before it is sent
+    type_into(".movement-goats", "Kali");
+    type_into(".movement-from", "Green Hills");
+    let to_kind: HtmlSelectElement = query(".movement-to-kind").unchecked_into();
+    to_kind.set_value("Farm");
+    change(&to_kind);
+    type_into(".movement-to", "green hills");
+    type_into(".movement-departed", "2026-03-05");
+    settle().await;
+    click(".log-movement");
+    settle().await;
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("Error: A movement must go to another place")
+    );
+
+    type_into(".movement-to", "Lake Farm");
+    type_into(".movement-vehicle", "MH12 AB 1234");
+    settle().await;
+    click(".log-movement");
+    settle().await;
+    assert!(mock.calls().contains(&"add_movement:Kali".to_string()));
+    assert_eq!(
+        cell(".movement-message"),
+        "Logged the movement of 1 goat(s): Green Hills (Farm) → Lake Farm (Farm) by MH12 AB 1234"
+    );
+    assert_eq!(rows(), 2);
+
+    // The register is filtered by goat and by the period the goats left in
+    type_into(".movement-filter-goat", "Rani");
+    type_into(".movement-filter-to", "2026-02-28");
+    settle().await;
+    click(".filter-movements");
+    settle().await;
+    assert!(
+        mock.calls()
+            .contains(&"movements:Rani:..2026-02-28".to_string())
+    );
+    assert_eq!(rows(), 1);
+}
+
+#[wasm_bindgen_test]
+async fn tag_series_panel_sets_the_series_shown_in_add_goat_form() {
+    Dispatch::<AccessStore>::global().set(AccessStore::default());
+    Dispatch::<GoatStore>::global().set(GoatStore::default());
+    let mock = Rc::new(MockApiClient::default());
+    let root = mount_point();
+    yew::Renderer::<Harness<TagSeriesPanel>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock).with_children(html! {
+            <AddGoatForm />
+        }),
+    )
+    .render();
+    settle().await;
+
+    let query = |selector: &str| root.query_selector(selector).unwrap();
+    let input = |selector: &str| -> HtmlInputElement { query(selector).unwrap().unchecked_into() };
+    let type_into = |selector: &str, text: &str| {
+        let input = input(selector);
+        input.set_value(text);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        input.dispatch_event(&event).unwrap();
+    };
+    let click = |selector: &str| {
+        query(selector)
+            .unwrap()
+            .unchecked_into::<HtmlElement>()
+            .click()
+    };
+    assert!(query("input.next-tag").is_none());
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("New goats are not tagged.")
+    );
+
+    type_into(".tag-prefix", "Y G");
+    settle().await;
+    click(".save-tag-series");
+    settle().await;
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("Error: A tag prefix cannot contain spaces")
+    );
+    assert!(!mock.calls().contains(&"update_settings".to_string()));
+
+    type_into(".tag-prefix", "YG");
+    type_into(".tag-digits", "3");
+    mock.set_next_tag(Some("YG-2026-001"));
+    settle().await;
+    click(".save-tag-series");
+    settle().await;
+    assert_eq!(
+        mock.settings().tag_series,
+        Some(TagSeries {
+            prefix: "YG".to_string(),
+            yearly: true,
+            digits: 3,
+        })
+    );
+    assert_eq!(
+        query("p.next-tag")
+            .unwrap()
+            .text_content()
+            .unwrap_or_default(),
+        "The next goat added is tagged YG-2026-001."
+    );
+
+    // The add goat form picks the tag up once it reloads
+    Dispatch::<GoatStore>::global().set(GoatStore {
+        goats: vec![Goat {
+            id: 1,
+            params: goat("Rani"),
+            tag_id: None,
+            date_of_birth: None,
+            created_at: None,
+            updated_at: None,
+        }],
+        ..Default::default()
+    });
+    settle().await;
+    let tag = input("input.next-tag");
+    assert_eq!(tag.value(), "YG-2026-001");
+    assert!(tag.read_only());
+}
+
+#[wasm_bindgen_test]
+async fn heat_tracker_flags_does_in_heat() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_heat_predictions(vec![
+        HeatPrediction {
+            doe_name: "Rani".to_string(),
+            in_heat: true,
+            last_heat: Some("2026-06-01".to_string()),
+            next_heat: Some("2026-06-22".to_string()),
+            confidence: 0.9,
+            recent_spikes: vec![ActivitySpike {
+                date: "2026-06-01".to_string(),
+                activity: 2800.0,
+                baseline: 1000.0,
+                confidence: 0.9,
+            }],
+        },
+        HeatPrediction {
+            doe_name: "Meena".to_string(),
+            in_heat: false,
+            last_heat: None,
+            next_heat: None,
+            confidence: 0.0,
+            recent_spikes: Vec::new(),
+        },
+    ]);
+    let root = mount_point();
+    yew::Renderer::<Harness<HeatTracker>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    assert_eq!(mock.calls(), vec!["heat_predictions"]);
+    let cell = |selector: &str| {
+        root.query_selector(selector)
+            .unwrap()
+            .unwrap()
+            .text_content()
+            .unwrap_or_default()
+    };
+    assert_eq!(cell("tr[data-doe='Rani'] td.status"), "In heat");
+    assert_eq!(cell("tr[data-doe='Rani'] td.next-heat"), "2026-06-22");
+    assert_eq!(cell("tr[data-doe='Rani'] td.confidence"), "90%");
+    assert_eq!(cell("tr[data-doe='Meena'] td.confidence"), "–");
+}
+
+#[wasm_bindgen_test]
+async fn grazing_map_lists_goats_outside_geofences() {
+    let position = |name: &str, lat: f64, lon: f64, outside: bool| GoatPosition {
+        goat_name: name.to_string(),
+        position: GeoPoint { lat, lon },
+        recorded_at: "2026-06-01T09:00:00Z".parse().unwrap(),
+        geofence: (!outside).then(|| "North paddock".to_string()),
+        outside,
+    };
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_positions(vec![
+        position("Moti", 28.605, 77.205, false),
+        position("Rani", 28.62, 77.205, true),
+    ]);
+    mock.set_geofences(vec![Geofence {
+        id: Some(1),
+        name: "North paddock".to_string(),
+        boundary: vec![
+            GeoPoint {
+                lat: 28.60,
+                lon: 77.20,
+            },
+            GeoPoint {
+                lat: 28.60,
+                lon: 77.21,
+            },
+            GeoPoint {
+                lat: 28.61,
+                lon: 77.21,
+            },
+        ],
+    }]);
+    let root = mount_point();
+    yew::Renderer::<Harness<GrazingMap>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    assert_eq!(mock.calls(), vec!["goat_positions", "geofences"]);
+    assert!(
+        root.query_selector("polygon[data-geofence='North paddock']")
+            .unwrap()
+            .is_some()
+    );
+    assert_eq!(
+        root.query_selector_all("g[data-goat] circle")
+            .unwrap()
+            .length(),
+        2
+    );
+    let fill = |name: &str| {
+        root.query_selector(&format!("g[data-goat='{}'] circle", name))
+            .unwrap()
+            .unwrap()
+            .get_attribute("fill")
+    };
+    assert_eq!(fill("Rani").as_deref(), Some("#c62828"));
+    assert_eq!(fill("Moti").as_deref(), Some("#1565c0"));
+    let strays = root.query_selector_all(".geofence-alerts li").unwrap();
+    assert_eq!(strays.length(), 1);
+    assert!(
+        strays
+            .get(0)
+            .unwrap()
+            .text_content()
+            .unwrap_or_default()
+            .starts_with("Rani is outside")
+    );
+}
+
+/// Dispatches a bubbling `change` event on `target`.
+fn change(target: &Element) {
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("change", &init).unwrap();
+    target.dispatch_event(&event).unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn import_wizard_maps_columns_and_imports_valid_rows() {
+    let row = |cells: &[&str]| cells.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_import_table(ImportTable {
+        headers: row(&["Goat Name", "Sex", "Breed", "Colour"]),
+        rows: vec![
+            row(&["Rani", "F", "Beetal", "brown"]),
+            row(&["Moti", "x", "Beetal", "white"]),
+        ],
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<ImportWizard>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let csv = b"Goat Name,Sex,Breed,Colour\n";
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(&csv[..]));
+    let file = web_sys::File::new_with_u8_array_sequence(&parts, "herd.csv").unwrap();
+    let transfer = web_sys::DataTransfer::new().unwrap();
+    transfer.items().add_with_file(&file).unwrap();
+    let input: HtmlInputElement = root
+        .query_selector("input[type=file]")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    input.set_files(transfer.files().as_ref());
+    change(&input);
+    settle().await;
+    assert_eq!(mock.calls(), vec![format!("parse_import:{}", csv.len())]);
+
+    let select = |column: &str| -> HtmlSelectElement {
+        root.query_selector(&format!("tr[data-column='{}'] select", column))
+            .unwrap()
+            .unwrap()
+            .unchecked_into()
+    };
+    assert_eq!(select("Sex").value(), "gender");
+    assert_eq!(select("Colour").value(), "coat_color");
+    let button = |label: &str| -> HtmlElement {
+        let buttons = root.query_selector_all("button").unwrap();
+        (0..buttons.length())
+            .filter_map(|i| buttons.get(i))
+            .find(|b| b.text_content().as_deref() == Some(label))
+            .unwrap()
+            .unchecked_into()
+    };
+
+    select("Sex").set_value("");
+    change(&select("Sex"));
+    settle().await;
+    let missing = root.query_selector(".missing-fields").unwrap().unwrap();
+    assert!(
+        missing
+            .text_content()
+            .unwrap_or_default()
+            .contains("Gender")
+    );
+    assert!(button("Preview").has_attribute("disabled"));
+    select("Sex").set_value("gender");
+    change(&select("Sex"));
+    settle().await;
+
+    button("Preview").click();
+    settle().await;
+    let summary = root.query_selector(".import-summary").unwrap().unwrap();
+    assert_eq!(
+        summary.text_content().as_deref(),
+        Some("1 of 2 rows ready to import.")
+    );
+    let errors = root
+        .query_selector("tr[data-line='3'] td.errors")
+        .unwrap()
+        .unwrap();
+    assert!(
+        errors
+            .text_content()
+            .unwrap_or_default()
+            .contains("Unknown gender 'x'")
+    );
+
+    button("Import 1 goats").click();
+    settle().await;
+    assert!(mock.calls().contains(&"add_goats_batch:1".to_string()));
+    assert_eq!(mock.goats()[0].name, "Rani");
+    assert_eq!(mock.goats()[0].coat_color, Some(CoatColor::Brown));
+    let done = root.query_selector(".import-done").unwrap().unwrap();
+    assert_eq!(done.text_content().as_deref(), Some("Imported 1 goats."));
+}
+
+#[wasm_bindgen_test]
+async fn budget_tracker_flags_overspent_categories() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_budget_report(BudgetReport {
+        month: "2026-04".to_string(),
+        currency: "INR".to_string(),
+        lines: vec![
+            BudgetVariance::new(FinanceCategory::Feed, Some(4000.0), 4500.5),
+            BudgetVariance::new(FinanceCategory::Veterinary, Some(2000.0), 1200.0),
+            BudgetVariance::new(FinanceCategory::Equipment, None, 650.0),
+        ],
+        total_budget: 6000.0,
+        total_actual: 6350.5,
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<BudgetTracker>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    assert_eq!(mock.calls(), vec!["budget_report:"]);
+    let cell = |selector: &str| {
+        root.query_selector(selector)
+            .unwrap()
+            .unwrap()
+            .text_content()
+            .unwrap_or_default()
+    };
+    assert_eq!(cell(".budget-alerts"), "Feed is over budget by 500.50 INR");
+    assert_eq!(cell("tr[data-category='Feed'] td.variance"), "-500.50");
+    assert_eq!(cell("tr[data-category='Feed'] td.used"), "113%");
+    assert_eq!(cell("tr[data-category='Veterinary'] td.used"), "60%");
+    assert_eq!(cell("tr[data-category='Equipment'] td.budget"), "–");
+
+    let select: HtmlSelectElement = root
+        .query_selector("select[name='category']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    select.set_value("Labor");
+    change(&select);
+    let amount: HtmlInputElement = root
+        .query_selector("input[name='monthly_amount']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    amount.set_value("8000");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    amount.dispatch_event(&event).unwrap();
+    settle().await;
+    let button: HtmlElement = root
+        .query_selector("button[type='submit']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    button.click();
+    settle().await;
+
+    assert_eq!(
+        mock.calls(),
+        vec!["budget_report:", "set_budget:Labor:8000", "budget_report:"]
+    );
+    assert_eq!(mock.budgets()[0].category, FinanceCategory::Labor);
+}
+
+#[wasm_bindgen_test]
+async fn budget_tracker_shows_empty_state_with_action() {
+    let mock = Rc::new(MockApiClient::default());
+    let root = mount_point();
+    yew::Renderer::<Harness<BudgetTracker>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let empty = root.query_selector(".empty-state").unwrap().unwrap();
+    let text = empty.text_content().unwrap_or_default();
+    assert!(text.contains("No budgets set"));
+    assert!(text.contains("For example: Feed"));
+    let action = empty.query_selector("a.empty-action").unwrap().unwrap();
+    assert_eq!(action.text_content().as_deref(), Some("Set a budget"));
+    assert_eq!(action.get_attribute("href").as_deref(), Some("#set-budget"));
+    assert!(root.query_selector("form#set-budget").unwrap().is_some());
+    assert!(root.query_selector("table").unwrap().is_none());
+}
+
+#[wasm_bindgen_test]
+async fn pricing_preview_prices_goats_as_the_formula_changes() {
+    let mut sirohi = goat("Moti");
+    sirohi.breed = Breed::Sirohi;
+    let goats = vec![goat("Rani"), sirohi];
+    let stored = (1..).zip(&goats).map(|(id, goat)| Goat {
+        id,
+        params: goat.clone(),
+        tag_id: None,
+        date_of_birth: None,
+        created_at: None,
+        updated_at: None,
+    });
+    Dispatch::<GoatStore>::global().set(GoatStore {
+        goats: stored.collect(),
+        ..Default::default()
+    });
+    let mock = Rc::new(MockApiClient::with_goats(goats));
+    mock.set_settings(FarmSettings {
+        pricing: Some(PricingSettings {
+            formula: "weight * rate_per_kg * breed_factor".to_string(),
+            variables: BTreeMap::from([("rate_per_kg".to_string(), 400.0)]),
+            breed_factors: BTreeMap::from([("Sirohi".to_string(), 1.25)]),
+        }),
+        ..Default::default()
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<PricingPreview>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let cell = |selector: &str| {
+        root.query_selector(selector)
+            .unwrap()
+            .and_then(|e| e.text_content())
+            .unwrap_or_default()
+    };
+    assert_eq!(cell("tr[data-goat='Rani'] td.formula"), "12000.00");
+    assert_eq!(cell("tr[data-goat='Moti'] td.formula"), "15000.00");
+    assert_eq!(cell("tr[data-goat='Moti'] td.change"), "+14850.00");
+
+    let formula: HtmlInputElement = root
+        .query_selector("input[name='formula']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    let type_formula = |text: &str| {
+        formula.set_value(text);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        formula.dispatch_event(&event).unwrap();
+    };
+    type_formula("weight * price_per_kg");
+    settle().await;
+    assert_eq!(cell(".formula-error"), "Unknown variable 'price_per_kg'");
+
+    type_formula("max(weight * rate_per_kg, 13000)");
+    settle().await;
+    assert_eq!(cell("tr[data-goat='Rani'] td.formula"), "13000.00");
+    let apply: HtmlElement = root
+        .query_selector("button[name='apply']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    apply.click();
+    settle().await;
+
+    assert_eq!(
+        mock.calls()[..3],
+        ["farm_settings", "update_settings", "apply_pricing"]
+    );
+    let prices: Vec<f64> = mock.goats().iter().map(|g| g.current_price).collect();
+    assert_eq!(prices, vec![13000.0, 13000.0]);
+    let text = root.text_content().unwrap_or_default();
+    assert!(text.contains("Repriced 2 goats"));
+}
+
+#[wasm_bindgen_test]
+async fn kpi_cards_show_values_and_change() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_stats(DashboardStats {
+        as_of: "2025-06-30".to_string(),
+        currency: "INR".to_string(),
+        herd_size: Kpi::from_trend(vec![10.0, 12.0, 12.0]),
+        herd_value: Kpi::from_trend(vec![5000.0, 6000.0]),
+        milk_this_week: Kpi::from_trend(vec![40.0, 30.0]),
+        expenses_this_month: Kpi::from_trend(vec![800.0, 1000.0]),
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<KpiCards>>::with_root_and_props(root.clone(), HarnessProps::new(&mock))
+        .render();
+    settle().await;
+
+    assert_eq!(mock.calls(), vec!["dashboard_stats"]);
+    let text = |selector: &str| {
+        root.query_selector(selector)
+            .unwrap()
+            .and_then(|e| e.text_content())
+            .unwrap_or_default()
+    };
+    assert_eq!(text("[data-kpi='herd_size'] .kpi-value"), "12 goats");
+    assert_eq!(
+        text("[data-kpi='herd_size'] .kpi-change"),
+        "No change vs last week"
+    );
+    assert_eq!(text("[data-kpi='herd_value'] .kpi-value"), "6000 INR");
+    assert_eq!(
+        text("[data-kpi='milk_this_week'] .kpi-change"),
+        "▼ 25.0% vs last week"
+    );
+    assert_eq!(
+        text("[data-kpi='expenses_this_month'] .kpi-change"),
+        "▲ 25.0% vs last month"
+    );
+    let sparklines = root.query_selector_all("polyline.sparkline").unwrap();
+    assert_eq!(sparklines.length(), 4);
+}
+
+#[wasm_bindgen_test]
+async fn quick_search_debounces_and_navigates_with_keys() {
+    let hit = |kind, id, title: &str| SearchResult {
+        kind,
+        id,
+        title: title.to_string(),
+        detail: String::new(),
+        suggested: false,
+    };
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_search_results(vec![
+        hit(SearchKind::Goat, 2, "Rani"),
+        hit(SearchKind::Task, 7, "Deworm Rani"),
+        hit(SearchKind::Goat, 3, "Moti"),
+    ]);
+    let root = mount_point();
+    yew::Renderer::<Harness<QuickSearch>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let input: HtmlInputElement = root
+        .query_selector("input[name='quick-search']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    for text in ["r", "ra", "ran"] {
+        input.set_value(text);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        input.dispatch_event(&event).unwrap();
+        settle().await;
+    }
+    sleep(DEBOUNCE * 2).await;
+
+    // Only the query typing paused on is sent
+    assert_eq!(mock.calls(), vec!["search:ran"]);
+    let options = root.query_selector_all("li[role='option']").unwrap();
+    assert_eq!(options.length(), 2);
+
+    let press = |key: &str| {
+        let init = web_sys::KeyboardEventInit::new();
+        init.set_key(key);
+        init.set_bubbles(true);
+        let event =
+            web_sys::KeyboardEvent::new_with_keyboard_event_init_dict("keydown", &init).unwrap();
+        input.dispatch_event(&event).unwrap();
+    };
+    press("ArrowDown");
+    settle().await;
+    let selected = root
+        .query_selector("li[aria-selected='true']")
+        .unwrap()
+        .unwrap();
+    assert_eq!(selected.get_attribute("data-anchor").unwrap(), "task-7");
+
+    press("Enter");
+    settle().await;
+    let hash = web_sys::window().unwrap().location().hash().unwrap();
+    assert_eq!(hash, "#task-7");
+    assert_eq!(input.value(), "");
+    assert!(root.query_selector("li[role='option']").unwrap().is_none());
+}
+
+#[wasm_bindgen_test]
+async fn quick_search_suggests_similar_goat_names() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_search_results(vec![SearchResult {
+        kind: SearchKind::Goat,
+        id: 4,
+        title: "Lakshmi".to_string(),
+        detail: "Beetal, Female".to_string(),
+        suggested: false,
+    }]);
+    let root = mount_point();
+    yew::Renderer::<Harness<QuickSearch>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let input: HtmlInputElement = root
+        .query_selector("input[name='quick-search']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    input.set_value("Laxmi");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    input.dispatch_event(&event).unwrap();
+    sleep(DEBOUNCE * 2).await;
+    settle().await;
+
+    assert!(root.query_selector(".did-you-mean").unwrap().is_some());
+    let options = root.query_selector_all("li[role='option']").unwrap();
+    assert_eq!(options.length(), 1);
+    let option: Element = options.item(0).unwrap().unchecked_into();
+    assert_eq!(option.get_attribute("data-anchor").unwrap(), "goat-4");
+}
+
+#[wasm_bindgen_test]
+async fn add_goat_wizard_checks_steps_and_restores_drafts() {
+    discard_draft(DRAFT_FORM);
+    let mock = Rc::new(MockApiClient::default());
+    let mount = || {
+        let root = mount_point();
+        yew::Renderer::<Harness<AddGoatWizard>>::with_root_and_props(
+            root.clone(),
+            HarnessProps::new(&mock),
+        )
+        .render();
+        root
+    };
+    let button = |root: &Element, label: &str| -> HtmlElement {
+        let buttons = root.query_selector_all("button").unwrap();
+        (0..buttons.length())
+            .filter_map(|i| buttons.get(i))
+            .find(|b| b.text_content().as_deref() == Some(label))
+            .unwrap()
+            .unchecked_into()
+    };
+    let type_into = |root: &Element, name: &str, text: &str| {
+        let input: HtmlInputElement = root
+            .query_selector(&format!("input[name='{}']", name))
+            .unwrap()
+            .unwrap()
+            .unchecked_into();
+        input.set_value(text);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        input.dispatch_event(&event).unwrap();
+    };
+    let step_text = |root: &Element| {
+        root.query_selector(".wizard-step")
+            .unwrap()
+            .unwrap()
+            .text_content()
+            .unwrap_or_default()
+    };
+
+    let root = mount();
+    settle().await;
+    assert_eq!(step_text(&root), "Step 1 of 5");
+    button(&root, "Next").click();
+    settle().await;
+    let errors = root.query_selector(".wizard-errors").unwrap().unwrap();
+    assert!(
+        errors
+            .text_content()
+            .unwrap_or_default()
+            .contains("Name is required.")
+    );
+    assert_eq!(step_text(&root), "Step 1 of 5");
+
+    type_into(&root, "name", "Rani");
+    // Only does have a last-bred date on the health step
+    let gender: HtmlSelectElement = root
+        .query_selector("select[name='gender']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    gender.set_value("Female");
+    change(&gender);
+    settle().await;
+    button(&root, "Next").click();
+    settle().await;
+    assert_eq!(step_text(&root), "Step 2 of 5");
+    assert!(root.query_selector(".wizard-errors").unwrap().is_none());
+    type_into(&root, "weight", "31.5");
+    settle().await;
+    button(&root, "Save draft").click();
+    settle().await;
+    let saved = load_draft::<GoatDraft>(DRAFT_FORM).unwrap();
+    assert_eq!(saved.step, Step::Physical);
+    assert_eq!(saved.weight, "31.5");
+
+    // A fresh wizard picks up where the draft left off
+    let root = mount();
+    settle().await;
+    assert_eq!(step_text(&root), "Step 2 of 5");
+    let notice = root.query_selector(".wizard-notice").unwrap().unwrap();
+    assert_eq!(
+        notice.text_content().as_deref(),
+        Some("Restored your saved draft.")
+    );
+    button(&root, "Next").click();
+    settle().await;
+    type_into(&root, "last_bred", "2999-01-01");
+    settle().await;
+    button(&root, "Next").click();
+    settle().await;
+    assert_eq!(step_text(&root), "Step 3 of 5");
+    let errors = root.query_selector(".wizard-errors").unwrap().unwrap();
+    assert!(
+        errors
+            .text_content()
+            .unwrap_or_default()
+            .contains("Last bred 2999-01-01 is in the future")
+    );
+    type_into(&root, "last_bred", "");
+    type_into(&root, "vaccinations", "CDT, Rabies");
+    settle().await;
+    button(&root, "Next").click();
+    settle().await;
+    type_into(&root, "cost", "100");
+    type_into(&root, "current_price", "150");
+    settle().await;
+    button(&root, "Next").click();
+    settle().await;
+    assert_eq!(step_text(&root), "Step 5 of 5");
+    let review = root.query_selector(".wizard-review").unwrap().unwrap();
+    assert!(review.text_content().unwrap_or_default().contains("Rani"));
+
+    button(&root, "Add Goat").click();
+    settle().await;
+    assert_eq!(mock.calls(), vec!["add_goat:Rani"]);
+    let stored = &mock.goats()[0];
+    assert_eq!(stored.weight, 31.5);
+    assert_eq!(stored.vaccinations.len(), 2);
+    assert!(load_draft::<GoatDraft>(DRAFT_FORM).is_none());
+    assert_eq!(step_text(&root), "Step 1 of 5");
+}
+
+#[wasm_bindgen_test]
+async fn unusual_values_need_submit_anyway() {
+    save_draft(
+        DRAFT_FORM,
+        &GoatDraft {
+            step: Step::Review,
+            name: "Bulky".to_string(),
+            weight: "500".to_string(),
+            diet: "hay".to_string(),
+            cost: "0".to_string(),
+            current_price: "150".to_string(),
+            ..GoatDraft::default()
+        },
+    );
+    let mock = Rc::new(MockApiClient::default());
+    let root = mount_point();
+    yew::Renderer::<Harness<AddGoatWizard>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+    let button = |label: &str| -> HtmlElement {
+        let buttons = root.query_selector_all("button").unwrap();
+        (0..buttons.length())
+            .filter_map(|i| buttons.get(i))
+            .find(|b| b.text_content().as_deref() == Some(label))
+            .unwrap()
+            .unchecked_into()
+    };
+
+    button("Add Goat").click();
+    settle().await;
+    assert!(mock.calls().is_empty());
+    let warnings = root
+        .query_selector(".validation-warnings")
+        .unwrap()
+        .unwrap()
+        .text_content()
+        .unwrap_or_default();
+    assert!(warnings.contains("Weight 500 kg is unusually heavy"));
+    assert!(warnings.contains("Cost is 0"));
+
+    button("Submit anyway").click();
+    settle().await;
+    assert_eq!(mock.calls(), vec!["add_goat:Bulky"]);
+    assert_eq!(mock.goats()[0].weight, 500.0);
+    assert!(
+        root.query_selector(".validation-warnings")
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[wasm_bindgen_test]
+async fn read_only_toggle_disables_changes_and_keeps_settings() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_settings(FarmSettings {
+        gstin: Some("27AAPFU0939F1ZV".to_string()),
+        ..Default::default()
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<ReadOnlyToggle>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock).with_children(html! {
+            <PricingPreview />
+        }),
+    )
+    .render();
+    settle().await;
+
+    let toggle: HtmlInputElement = root
+        .query_selector("input[name='read_only']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    let save = || root.query_selector("button[name='save']").unwrap().unwrap();
+    assert!(!toggle.checked());
+    assert!(!save().has_attribute("disabled"));
+
+    toggle.click();
+    settle().await;
+    assert!(Dispatch::<AccessStore>::global().get().read_only);
+    let stored = mock.settings();
+    assert!(stored.read_only);
+    assert_eq!(stored.gstin.as_deref(), Some("27AAPFU0939F1ZV"));
+    assert!(save().has_attribute("disabled"));
+    assert!(
+        root.query_selector("button[name='apply']")
+            .unwrap()
+            .unwrap()
+            .has_attribute("disabled")
+    );
+
+    toggle.click();
+    settle().await;
+    assert!(!mock.settings().read_only);
+    assert!(!save().has_attribute("disabled"));
+    Dispatch::<AccessStore>::global().set(AccessStore::default());
+}
+
+#[wasm_bindgen_test]
+async fn recent_activity_links_events_to_goats() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_activity(vec![
+        ActivityEvent {
+            kind: ActivityKind::GoatSold,
+            goat_id: 2,
+            goat_name: "Moti".to_string(),
+            summary: "Sold Moti for 180.00".to_string(),
+            at: "2025-06-03T17:00:00Z".parse().unwrap(),
+        },
+        ActivityEvent {
+            kind: ActivityKind::GoatAdded,
+            goat_id: 1,
+            goat_name: "Rani".to_string(),
+            summary: "Added Rani (Beetal)".to_string(),
+            at: "2025-06-01T08:00:00Z".parse().unwrap(),
+        },
+    ]);
+    let root = mount_point();
+    yew::Renderer::<Harness<RecentActivity>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let links = root.query_selector_all(".recent-activity a").unwrap();
+    assert_eq!(links.length(), 2);
+    let first: Element = links.get(0).unwrap().unchecked_into();
+    assert_eq!(
+        first.text_content().as_deref(),
+        Some("Sold Moti for 180.00")
+    );
+    assert_eq!(first.get_attribute("href").as_deref(), Some("#goat-2"));
+    assert!(
+        root.text_content()
+            .unwrap_or_default()
+            .contains("2025-06-01 08:00")
+    );
+    assert_eq!(mock.calls(), vec!["recent_activity"]);
+}
+
+#[wasm_bindgen_test]
+async fn jobs_panel_shows_progress_downloads_and_failures() {
+    let job = |id: i64, kind: JobKind, status: JobStatus, description: &str| Job {
+        id,
+        kind,
+        status,
+        description: description.to_string(),
+        done: 0,
+        total: 0,
+        attempts: 1,
+        error: None,
+        result_name: None,
+        created_at: "2026-04-02T09:00:00Z".parse().unwrap(),
+        updated_at: "2026-04-02T09:01:00Z".parse().unwrap(),
+    };
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_jobs(vec![
+        Job {
+            result_name: Some("breed-headcount-2026-03-31.csv".to_string()),
+            ..job(
+                2,
+                JobKind::Report,
+                JobStatus::Succeeded,
+                "Census report breed-headcount",
+            )
+        },
+        Job {
+            done: 50,
+            total: 120,
+            error: Some("Goat 51: A goat named Rani already exists".to_string()),
+            ..job(1, JobKind::Import, JobStatus::Failed, "Import 120 goats")
+        },
+    ]);
+    let root = mount_point();
+    yew::Renderer::<Harness<JobsPanel>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let items = root.query_selector_all(".jobs li").unwrap();
+    assert_eq!(items.length(), 2);
+    let link = root.query_selector(".jobs li a").unwrap().unwrap();
+    assert_eq!(
+        link.get_attribute("data-url").as_deref(),
+        Some("http://127.0.0.1:8000/jobs/2/result")
+    );
+    let failed = root
+        .query_selector("li[data-status='Failed']")
+        .unwrap()
+        .unwrap()
+        .text_content()
+        .unwrap_or_default();
+    assert!(failed.contains("50 / 120"));
+    assert!(failed.contains("already exists"));
+    // Every job has finished, so the list is not polled again
+    assert_eq!(mock.calls(), vec!["jobs"]);
+}
+
+#[wasm_bindgen_test]
+async fn farm_archive_uploads_and_summarises_restore() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_archive_summary(ArchiveSummary {
+        tables: 38,
+        rows: 412,
+        files: 3,
+        skipped: vec!["drone_flights".to_string()],
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<FarmArchive>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let download = root.query_selector("a[download]").unwrap().unwrap();
+    assert_eq!(
+        download.get_attribute("data-url").as_deref(),
+        Some("http://127.0.0.1:8000/archive")
+    );
+    let archive = b"PK\x03\x04farm";
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(&archive[..]));
+    let file = web_sys::File::new_with_u8_array_sequence(&parts, "farm.zip").unwrap();
+    let transfer = web_sys::DataTransfer::new().unwrap();
+    transfer.items().add_with_file(&file).unwrap();
+    let input: HtmlInputElement = root
+        .query_selector("input[type=file]")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    input.set_files(transfer.files().as_ref());
+    change(&input);
+    settle().await;
+
+    assert_eq!(
+        mock.calls()[0],
+        format!("import_farm_archive:{}:", archive.len())
+    );
+    let summary = root.query_selector(".archive-summary").unwrap().unwrap();
+    assert_eq!(
+        summary.text_content().as_deref(),
+        Some("Restored 412 records from 38 tables, with 3 files.")
+    );
+    assert!(
+        root.text_content()
+            .unwrap_or_default()
+            .contains("drone_flights")
+    );
+
+    // A passphrase encrypts the download and unlocks the next restore
+    let passphrase: HtmlInputElement = root
+        .query_selector(".archive-passphrase")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    let type_passphrase = |text: &str| {
+        passphrase.set_value(text);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        passphrase.dispatch_event(&event).unwrap();
+    };
+    let encrypt: HtmlElement = root
+        .query_selector("button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    type_passphrase("short");
+    settle().await;
+    encrypt.click();
+    settle().await;
+    assert_eq!(mock.calls().len(), 1);
+    assert!(
+        root.text_content()
+            .unwrap_or_default()
+            .contains("at least 10 characters")
+    );
+
+    type_passphrase("goats on the hill");
+    settle().await;
+    encrypt.click();
+    settle().await;
+    assert_eq!(mock.calls()[1], "export_farm_archive:goats on the hill");
+    let saved = root.query_selector(".encrypted-download").unwrap().unwrap();
+    assert!(
+        saved
+            .get_attribute("href")
+            .unwrap_or_default()
+            .starts_with("blob:")
+    );
+    change(&input);
+    settle().await;
+    assert_eq!(
+        mock.calls()[2],
+        format!("import_farm_archive:{}:goats on the hill", archive.len())
+    );
+}
+
+#[wasm_bindgen_test]
+async fn record_field_clock_lists_changes_to_the_field() {
+    Dispatch::<UnitsStore>::global().reduce_mut(|units| units.timezone = chrono_tz::Asia::Kolkata);
+    let mock = Rc::new(MockApiClient::default());
+    let change = |event_id: i64, from: Option<f64>, to: f64| FieldChange {
+        event_id,
+        changed_at: format!("2026-10-0{}T09:00:00Z", event_id).parse().unwrap(),
+        actor: from.map(|_| "Ravi on Phone".to_string()),
+        field: "current_price".to_string(),
+        from: from.map(|v| serde_json::json!(v)),
+        to: serde_json::json!(to),
+    };
+    mock.set_field_changes(vec![change(3, Some(180.0), 165.0), change(1, None, 150.0)]);
+    let root = mount_point();
+    yew::Renderer::<Harness<RecordField>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::with_props(
+            &mock,
+            yew::props!(RecordFieldProps {
+                goat_id: Some(7),
+                field: "current_price",
+                label: "Price",
+                value: "165.00".to_string()
+            }),
+        ),
+    )
+    .render();
+    settle().await;
+
+    assert!(mock.calls().is_empty());
+    assert!(root.query_selector(".field-history").unwrap().is_none());
+    let clock: HtmlElement = root
+        .query_selector(".history-toggle")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    clock.click();
+    settle().await;
+
+    assert_eq!(mock.calls(), vec!["field_history:7:current_price"]);
+    let items = root.query_selector_all(".field-history li").unwrap();
+    assert_eq!(items.length(), 2);
+    assert_eq!(
+        items.get(0).unwrap().text_content().as_deref(),
+        Some("2026-10-03 14:30: 180.0 → 165.0 by Ravi on Phone")
+    );
+    assert_eq!(
+        items.get(1).unwrap().text_content().as_deref(),
+        Some("2026-10-01 14:30: recorded as 150.0")
+    );
+
+    clock.click();
+    settle().await;
+    assert!(root.query_selector(".field-history").unwrap().is_none());
+    Dispatch::<UnitsStore>::global().reduce_mut(|units| units.timezone = chrono_tz::UTC);
+}
+
+#[wasm_bindgen_test]
+async fn times_are_shown_in_the_farm_time_zone() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_settings(FarmSettings {
+        timezone: "Asia/Kolkata".to_string(),
+        ..Default::default()
+    });
+    mock.set_activity(vec![ActivityEvent {
+        kind: ActivityKind::GoatAdded,
+        goat_id: 1,
+        goat_name: "Rani".to_string(),
+        summary: "Added Rani (Beetal)".to_string(),
+        at: "2025-06-01T20:00:00Z".parse().unwrap(),
+    }]);
+    let root = mount_point();
+    yew::Renderer::<Harness<UnitSelect>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock).with_children(html! {
+            <RecentActivity />
+        }),
+    )
+    .render();
+    settle().await;
+
+    // 20:00 UTC is already the next morning in Kolkata
+    let text = || root.text_content().unwrap_or_default();
+    assert!(text().contains("2025-06-02 01:30"), "{}", text());
+    let select: HtmlSelectElement = root
+        .query_selector("select[name='timezone']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    assert_eq!(select.value(), "Asia/Kolkata");
+
+    select.set_value("UTC");
+    change(&select);
+    settle().await;
+    assert_eq!(mock.settings().timezone, "UTC");
+    assert!(text().contains("2025-06-01 20:00"), "{}", text());
+    assert_eq!(
+        Dispatch::<UnitsStore>::global().get().timezone,
+        chrono_tz::UTC
+    );
+}
+
+#[wasm_bindgen_test]
+async fn data_health_lists_issues_with_fix_links() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_data_health(DataHealthReport {
+        goats_checked: 3,
+        issues: vec![
+            DataIssue {
+                kind: IssueKind::MissingBirthDate,
+                goat_id: 1,
+                goat_name: "Ganga".to_string(),
+                message: "No date of birth recorded".to_string(),
+            },
+            DataIssue {
+                kind: IssueKind::ZeroWeight,
+                goat_id: 2,
+                goat_name: "Kali".to_string(),
+                message: "Weight is 0 kg".to_string(),
+            },
+        ],
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<DataHealth>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let summary = root
+        .query_selector(".data-health-summary")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        summary.text_content().as_deref(),
+        Some("Missing date of birth: 1Zero weight: 1")
+    );
+    let fix = |goat: &str| {
+        root.query_selector(&format!("tr[data-goat='{}'] a.fix", goat))
+            .unwrap()
+            .unwrap()
+            .get_attribute("href")
+    };
+    assert_eq!(fix("Ganga").as_deref(), Some("#goat-1"));
+    assert_eq!(fix("Kali").as_deref(), Some("#update-goat"));
+
+    mock.set_data_health(DataHealthReport {
+        goats_checked: 3,
+        issues: Vec::new(),
+    });
+    let recheck: HtmlElement = root
+        .query_selector("button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    recheck.click();
+    settle().await;
+    let ok = root.query_selector(".data-health-ok").unwrap().unwrap();
+    assert_eq!(
+        ok.text_content().as_deref(),
+        Some("No problems found in 3 goats.")
+    );
+    assert_eq!(mock.calls(), vec!["data_health", "data_health"]);
+}
+
+#[function_component(NumberFieldHarness)]
+fn number_field_harness() -> Html {
+    let value = use_state(|| Some(30.0));
+    let onchange = {
+        let value = value.clone();
+        Callback::from(move |v: Option<f64>| value.set(v))
+    };
+    html! {
+        <>
+            <NumberField name="weight" quantity={Quantity::Weight} value={*value}
+                         min={0.0} max={150.0} {onchange} />
+            <p class="stored">{value.map(|v| format!("{:.2}", v)).unwrap_or_default()}</p>
+        </>
+    }
+}
+
+#[wasm_bindgen_test]
+async fn number_field_converts_units_and_clamps() {
+    Dispatch::<UnitsStore>::global().reduce_mut(|units| {
+        units.weight = WeightUnit::Lb;
+        units.decimal_separator = ',';
+    });
+    let root = mount_point();
+    yew::Renderer::<NumberFieldHarness>::with_root(root.clone()).render();
+    settle().await;
+
+    let input: HtmlInputElement = root
+        .query_selector("input[name='weight']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    let stored = || {
+        root.query_selector(".stored")
+            .unwrap()
+            .unwrap()
+            .text_content()
+            .unwrap_or_default()
+    };
+    let fire = |kind: &str| {
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict(kind, &init).unwrap();
+        input.dispatch_event(&event).unwrap();
+    };
+    assert_eq!(input.value(), "66,14");
+    assert!(root.text_content().unwrap_or_default().contains("lb"));
+
+    input.set_value("22,05");
+    fire("input");
+    settle().await;
+    assert_eq!(stored(), "10.00");
+    assert_eq!(input.value(), "22,05");
+
+    // "1.000" is a thousand pounds, clamped to 150 kg
+    input.set_value("1.000");
+    fire("input");
+    settle().await;
+    assert_eq!(stored(), "150.00");
+    fire("blur");
+    settle().await;
+    assert_eq!(input.value(), "330,69");
+
+    input.set_value("");
+    fire("input");
+    settle().await;
+    assert_eq!(stored(), "");
+
+    Dispatch::<UnitsStore>::global().reduce_mut(|units| {
+        units.weight = WeightUnit::Kg;
+        units.decimal_separator = '.';
+    });
+}
+
+#[function_component(DatePickerHarness)]
+fn date_picker_harness() -> Html {
+    let date = use_state(String::new);
+    let onchange = {
+        let date = date.clone();
+        Callback::from(move |value: String| date.set(value))
+    };
+    html! {
+        <DatePicker name="last_bred" label="Last bred" value={(*date).clone()} {onchange} />
+    }
+}
+
+#[wasm_bindgen_test]
+async fn date_picker_shortcuts_and_future_dates() {
+    let root = mount_point();
+    yew::Renderer::<DatePickerHarness>::with_root(root.clone()).render();
+    settle().await;
+
+    let input: HtmlInputElement = root
+        .query_selector("input[name='last_bred']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    let button = |label: &str| -> HtmlElement {
+        let buttons = root.query_selector_all("button").unwrap();
+        (0..buttons.length())
+            .filter_map(|i| buttons.get(i))
+            .find(|b| b.text_content().as_deref() == Some(label))
+            .unwrap()
+            .unchecked_into()
+    };
+    let type_into = |input: &HtmlInputElement, text: &str| {
+        input.set_value(text);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        input.dispatch_event(&event).unwrap();
+    };
+    assert_eq!(input.get_attribute("max"), Some(days_ago(0)));
+
+    button("Yesterday").click();
+    settle().await;
+    assert_eq!(input.value(), days_ago(1));
+    button("Today").click();
+    settle().await;
+    assert_eq!(input.value(), days_ago(0));
+
+    let days: HtmlInputElement = root
+        .query_selector(".days-ago")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    type_into(&days, "40");
+    settle().await;
+    button("days ago").click();
+    settle().await;
+    assert_eq!(input.value(), days_ago(40));
+    assert!(root.query_selector(".date-error").unwrap().is_none());
+
+    type_into(&input, "2999-01-01");
+    settle().await;
+    let error = root.query_selector(".date-error").unwrap().unwrap();
+    assert_eq!(
+        error.text_content().as_deref(),
+        Some("Last bred 2999-01-01 is in the future")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn breed_choice_hints_weight_and_fills_vaccines() {
+    discard_draft(DRAFT_FORM);
+    let mock = Rc::new(MockApiClient::default());
+    let root = mount_point();
+    yew::Renderer::<Harness<AddGoatWizard>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+    let hints = || {
+        root.query_selector(".breed-hints")
+            .unwrap()
+            .unwrap()
+            .text_content()
+            .unwrap_or_default()
+    };
+    assert!(hints().contains("Adults weigh 35–60 kg"));
+
+    let select: HtmlSelectElement = root
+        .query_selector("select[name='breed']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    select.set_value("Sirohi");
+    change(&select);
+    settle().await;
+    assert!(hints().contains("Adults weigh 30–50 kg"));
+    assert!(hints().contains("Goat Pox"));
+    let input = |name: &str| -> HtmlInputElement {
+        root.query_selector(&format!("input[name='{}']", name))
+            .unwrap()
+            .unwrap()
+            .unchecked_into()
+    };
+    let next = || {
+        let buttons = root.query_selector_all("button").unwrap();
+        let next: HtmlElement = (0..buttons.length())
+            .filter_map(|i| buttons.get(i))
+            .find(|b| b.text_content().as_deref() == Some("Next"))
+            .unwrap()
+            .unchecked_into();
+        next.click();
+    };
+    let name = input("name");
+    name.set_value("Rani");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    name.dispatch_event(&event).unwrap();
+    settle().await;
+
+    next();
+    settle().await;
+    assert_eq!(input("weight").placeholder(), "30–50");
+    next();
+    settle().await;
+    assert_eq!(
+        input("vaccinations").value(),
+        "PPR, Enterotoxaemia, FMD, Goat Pox"
+    );
+}
+
+#[wasm_bindgen_test]
+async fn breed_input_groups_by_purpose_and_searches_catalog() {
+    discard_draft(DRAFT_FORM);
+    let mock = Rc::new(MockApiClient::default());
+    mock.add_catalog_breed(CatalogBreed {
+        name: "Boer".into(),
+        purpose: BreedPurpose::Meat,
+        builtin: false,
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<AddGoatWizard>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+    let options = || {
+        let options = root
+            .query_selector_all("select[name='breed'] option")
+            .unwrap();
+        (0..options.length())
+            .filter_map(|i| options.get(i))
+            .map(|o| o.text_content().unwrap_or_default())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(options().len(), 12);
+    assert_eq!(options()[0], "Beetal (Dual purpose)");
+    let breed: HtmlSelectElement = root
+        .query_selector("select[name='breed']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    assert!(breed.text_content().unwrap().contains("Boer (Meat)"));
+
+    let purpose: HtmlSelectElement = root
+        .query_selector("select.breed-purpose")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    purpose.set_value("Meat");
+    change(&purpose);
+    settle().await;
+    // The selected Beetal stays listed although it is dual purpose
+    assert_eq!(
+        options(),
+        vec!["Osmanabadi", "BlackBengal", "Boer", "Beetal", "Other"]
+    );
+
+    let search: HtmlInputElement = root
+        .query_selector("input.breed-search")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    search.set_value("bo");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    search.dispatch_event(&event).unwrap();
+    settle().await;
+    assert_eq!(options(), vec!["Boer", "Beetal", "Other"]);
+
+    breed.set_value("Boer");
+    change(&breed);
+    settle().await;
+    assert_eq!(options(), vec!["Boer", "Other"]);
+    assert!(mock.calls().is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn breeding_date_only_shown_for_does() {
+    let buck = GoatDraft {
+        step: Step::Health,
+        name: "Raja".to_string(),
+        gender: "Male".to_string(),
+        weight: "40".to_string(),
+        diet: "hay".to_string(),
+        last_bred: "2025-03-01".to_string(),
+        cost: "100".to_string(),
+        current_price: "150".to_string(),
+        ..GoatDraft::default()
+    };
+    assert!(!buck.has_breeding_records());
+    assert_eq!(buck.to_goat().unwrap().last_bred, None);
+    let mock = Rc::new(MockApiClient::default());
+    let mount = |draft: &GoatDraft| {
+        save_draft(DRAFT_FORM, draft);
+        let root = mount_point();
+        yew::Renderer::<Harness<AddGoatWizard>>::with_root_and_props(
+            root.clone(),
+            HarnessProps::new(&mock),
+        )
+        .render();
+        root
+    };
+    let button = |root: &Element, label: &str| -> HtmlElement {
+        let buttons = root.query_selector_all("button").unwrap();
+        (0..buttons.length())
+            .filter_map(|i| buttons.get(i))
+            .find(|b| b.text_content().as_deref() == Some(label))
+            .unwrap()
+            .unchecked_into()
+    };
+    let last_bred = |root: &Element| {
+        root.query_selector("input[name='last_bred']")
+            .unwrap()
+            .map(|input| input.unchecked_into::<HtmlInputElement>().value())
+    };
+
+    let root = mount(&GoatDraft {
+        gender: "Female".to_string(),
+        ..buck.clone()
+    });
+    settle().await;
+    assert_eq!(last_bred(&root).as_deref(), Some("2025-03-01"));
+
+    // A buck's stale date is neither shown, reviewed nor saved
+    let root = mount(&buck);
+    settle().await;
+    assert_eq!(last_bred(&root), None);
+    button(&root, "Next").click();
+    settle().await;
+    button(&root, "Next").click();
+    settle().await;
+    let review = root.query_selector(".wizard-review").unwrap().unwrap();
+    assert!(
+        !review
+            .text_content()
+            .unwrap_or_default()
+            .contains("Last Bred")
+    );
+    button(&root, "Add Goat").click();
+    settle().await;
+    assert_eq!(mock.calls(), vec!["add_goat:Raja"]);
+    assert_eq!(mock.goats()[0].last_bred, None);
+}
+
+#[wasm_bindgen_test]
+async fn wizard_records_physical_traits() {
+    save_draft(
+        DRAFT_FORM,
+        &GoatDraft {
+            step: Step::Physical,
+            name: "Moti".to_string(),
+            gender: "Female".to_string(),
+            weight: "30".to_string(),
+            cost: "100".to_string(),
+            current_price: "150".to_string(),
+            ..GoatDraft::default()
+        },
+    );
+    let mock = Rc::new(MockApiClient::default());
+    let root = mount_point();
+    yew::Renderer::<Harness<AddGoatWizard>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+    let button = |label: &str| -> HtmlElement {
+        let buttons = root.query_selector_all("button").unwrap();
+        (0..buttons.length())
+            .filter_map(|i| buttons.get(i))
+            .find(|b| b.text_content().as_deref() == Some(label))
+            .unwrap()
+            .unchecked_into()
+    };
+    let pick = |name: &str, value: &str| {
+        let select: HtmlSelectElement = root
+            .query_selector(&format!("select[name='{}']", name))
+            .unwrap()
+            .unwrap()
+            .unchecked_into();
+        select.set_value(value);
+        change(&select);
+    };
+
+    pick("horns", "Disbudded");
+    settle().await;
+    pick("coat_color", "Spotted");
+    settle().await;
+    let marks: HtmlInputElement = root
+        .query_selector("input[name='marks']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    marks.set_value(" Torn right ear ");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    marks.dispatch_event(&event).unwrap();
+    settle().await;
+    for _ in 0..3 {
+        button("Next").click();
+        settle().await;
+    }
+    let review = root.query_selector(".wizard-review").unwrap().unwrap();
+    assert!(
+        review
+            .text_content()
+            .unwrap_or_default()
+            .contains("Spotted")
+    );
+    button("Add Goat").click();
+    settle().await;
+
+    let goats = mock.goats();
+    let moti = &goats[0];
+    assert_eq!(moti.horns, Some(HornStatus::Disbudded));
+    assert_eq!(moti.coat_color, Some(CoatColor::Spotted));
+    assert_eq!(moti.marks.as_deref(), Some("Torn right ear"));
+    assert_eq!(describe(moti), "Spotted, disbudded; Torn right ear");
+    let filter = TraitFilter {
+        coat_color: Some(CoatColor::Spotted),
+        ..TraitFilter::default()
+    };
+    assert!(filter.matches(moti));
+    assert!(!filter.matches(&goat("Rani")));
+}
+
+#[wasm_bindgen_test]
+fn breeding_summary_depends_on_gender_and_age() {
+    let mut doe = goat("Rani");
+    assert_eq!(breeding_summary(&doe, None, None), "Not bred yet");
+    doe.last_bred = Some("2025-03-01".to_string());
+    assert_eq!(
+        breeding_summary(&doe, Some(400), None),
+        "Last bred 2025-03-01"
+    );
+    assert_eq!(
+        breeding_summary(&doe, Some(200), None),
+        "Too young to breed (200 of 240 days)"
+    );
+    let mut buck = goat("Raja");
+    buck.gender = Gender::Male;
+    assert_eq!(
+        breeding_summary(&buck, Some(200), None),
+        "Buck: no breeding dates or lactations are kept"
+    );
+    let mut neutering = Neutering {
+        goat_name: "Raja".to_string(),
+        neutered_on: "2025-01-10".to_string(),
+        reason: Some("Raised for meat".to_string()),
+    };
+    assert_eq!(
+        breeding_summary(&buck, Some(200), Some(&neutering)),
+        "Wether since 2025-01-10 (Raised for meat)"
+    );
+    neutering.reason = None;
+    assert_eq!(
+        breeding_summary(&doe, Some(400), Some(&neutering)),
+        "Spayed doe since 2025-01-10"
+    );
+}
+
+#[wasm_bindgen_test]
+async fn pedigree_view_shows_inherited_tags_and_saves_tags() {
+    Dispatch::<GoatStore>::global().set(GoatStore {
+        goats: vec![Goat {
+            id: 1,
+            params: goat("Moti"),
+            tag_id: None,
+            date_of_birth: None,
+            created_at: None,
+            updated_at: None,
+        }],
+        ..Default::default()
+    });
+    let ancestor = |name: &str, tags: &[&str]| {
+        Some(Box::new(PedigreeNode {
+            name: name.to_string(),
+            external: false,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            sire: None,
+            dam: None,
+        }))
+    };
+    let mock = Rc::new(MockApiClient::default());
+    mock.add_pedigree(PedigreeNode {
+        name: "Moti".to_string(),
+        external: false,
+        tags: vec![],
+        // Raja is the neighbour's buck, kept in the external registry
+        sire: ancestor("Raja", &["High twinning"]).map(|raja| {
+            Box::new(PedigreeNode {
+                external: true,
+                ..*raja
+            })
+        }),
+        dam: ancestor("Rani", &[]),
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<PedigreeView>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let select: HtmlSelectElement = root
+        .query_selector("select[name='pedigree_goat']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    select.set_value("Moti");
+    change(&select);
+    settle().await;
+
+    let inherited = root.query_selector(".inherited-tags").unwrap().unwrap();
+    assert_eq!(
+        inherited.text_content().unwrap_or_default(),
+        "High twinning: 50% of genes from tagged lines (Raja)"
+    );
+    assert!(
+        root.text_content()
+            .unwrap_or_default()
+            .contains("Dam: Rani")
+    );
+    assert!(
+        root.text_content()
+            .unwrap_or_default()
+            .contains("Sire: Raja(external)")
+    );
+    assert_eq!(
+        root.query_selector_all(".external-parent")
+            .unwrap()
+            .length(),
+        1
+    );
+
+    let tags: HtmlInputElement = root
+        .query_selector("input[name='genetic_tags']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    tags.set_value("Fast growth, , heat tolerant ");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    tags.dispatch_event(&event).unwrap();
+    settle().await;
+    let save: HtmlElement = root
+        .query_selector("button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    save.click();
+    settle().await;
+
+    assert_eq!(
+        mock.calls(),
+        vec![
+            "pedigree_tree:Moti",
+            "save_genetic_tags:Moti:Fast growth,heat tolerant"
+        ]
+    );
+    let badges = root.query_selector_all("p .genetic-tag").unwrap();
+    assert_eq!(badges.length(), 2);
+}
+
+#[wasm_bindgen_test]
+async fn quick_entry_confirms_parsed_weight_before_saving() {
+    Dispatch::<UnitsStore>::global().reduce_mut(|units| {
+        units.weight = WeightUnit::Kg;
+        units.decimal_separator = '.';
+    });
+    let goats = vec![goat("Rani"), goat("Lakshmi")];
+    Dispatch::<GoatStore>::global().set(GoatStore {
+        goats: goats
+            .iter()
+            .enumerate()
+            .map(|(i, params)| Goat {
+                id: i as i64 + 1,
+                params: params.clone(),
+                tag_id: None,
+                date_of_birth: None,
+                created_at: None,
+                updated_at: None,
+            })
+            .collect(),
+        ..Default::default()
+    });
+    let mock = Rc::new(MockApiClient::with_goats(goats));
+    let root = mount_point();
+    yew::Renderer::<Harness<QuickEntry>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::with_props(
+            &mock,
+            yew::props!(QuickEntryProps {
+                kind: EntryKind::Weight
+            }),
+        ),
+    )
+    .render();
+    settle().await;
+
+    let input: HtmlInputElement = root
+        .query_selector("input[name='utterance']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    let button = |label: &str| -> HtmlElement {
+        let buttons = root.query_selector_all("button").unwrap();
+        (0..buttons.length())
+            .filter_map(|i| buttons.get(i))
+            .find(|b| b.text_content().as_deref() == Some(label))
+            .unwrap()
+            .unchecked_into()
+    };
+    input.set_value("Laxmi thirty two kilos");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    input.dispatch_event(&event).unwrap();
+    settle().await;
+    button("Check").click();
+    settle().await;
+
+    // Nothing is saved until the parsed entry is confirmed
+    let confirm = root.query_selector(".voice-confirm p").unwrap().unwrap();
+    assert_eq!(
+        confirm.text_content().unwrap_or_default(),
+        format!(
+            "Record 32 kg for Lakshmi on {}? (heard \"laxmi\")",
+            days_ago(0)
+        )
+    );
+    assert!(mock.weights().is_empty());
+    button("Cancel").click();
+    settle().await;
+    assert!(root.query_selector(".voice-confirm").unwrap().is_none());
+
+    button("Check").click();
+    settle().await;
+    button("Save").click();
+    settle().await;
+    assert_eq!(mock.calls(), vec!["add_weight:Lakshmi:32"]);
+    let weights = mock.weights();
+    assert_eq!(weights[0].weighed_on, days_ago(0));
+    assert!(
+        root.text_content()
+            .unwrap_or_default()
+            .contains("Recorded 32 kg for Lakshmi.")
+    );
+
+    // Unparseable entries explain what is missing
+    input.set_value("Rani");
+    input.dispatch_event(&event).unwrap();
+    settle().await;
+    button("Check").click();
+    settle().await;
+    assert!(
+        root.text_content()
+            .unwrap_or_default()
+            .contains("Didn't hear an amount")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn setup_wizard_saves_progress_step_by_step() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_settings(FarmSettings {
+        setup_step: SetupStep::Farm,
+        ..Default::default()
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<SetupWizard>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let wizard = || root.query_selector("section.setup-wizard").unwrap();
+    let button = |name: &str| -> HtmlElement {
+        root.query_selector(&format!("button[name='{}']", name))
+            .unwrap()
+            .unwrap()
+            .unchecked_into()
+    };
+    let type_into = |name: &str, value: &str| {
+        let input: HtmlInputElement = root
+            .query_selector(&format!("input[name='{}']", name))
+            .unwrap()
+            .unwrap()
+            .unchecked_into();
+        input.set_value(value);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        input
+            .dispatch_event(&web_sys::Event::new_with_event_init_dict("input", &init).unwrap())
+            .unwrap();
+    };
+    assert_eq!(
+        wizard().unwrap().get_attribute("data-step").as_deref(),
+        Some("farm")
+    );
+
+    // The farm needs a name before moving on
+    button("setup_continue").click();
+    settle().await;
+    assert!(
+        root.text_content()
+            .unwrap_or_default()
+            .contains("Enter the farm's name.")
+    );
+    assert_eq!(mock.calls(), vec!["farm_settings"]);
+
+    type_into("farm_name", "Green Valley");
+    settle().await;
+    button("setup_continue").click();
+    settle().await;
+    let saved = mock.settings();
+    assert_eq!(saved.farm_name.as_deref(), Some("Green Valley"));
+    assert_eq!(saved.setup_step, SetupStep::Preferences);
+    assert_eq!(
+        wizard().unwrap().get_attribute("data-step").as_deref(),
+        Some("preferences")
+    );
+
+    let language: HtmlSelectElement = root
+        .query_selector("select[name='language']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    language.set_value("hi");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    language
+        .dispatch_event(&web_sys::Event::new_with_event_init_dict("change", &init).unwrap())
+        .unwrap();
+    settle().await;
+    button("setup_continue").click();
+    settle().await;
+    assert_eq!(mock.settings().language, "hi");
+    assert_eq!(mock.settings().setup_step, SetupStep::Pens);
+
+    type_into("pen_name", "Main pen");
+    type_into("pen_capacity", "20");
+    settle().await;
+    let add: HtmlElement = root
+        .query_selector("form.add-pen button[type='submit']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    add.click();
+    settle().await;
+    let spaces = mock.spaces();
+    assert_eq!(spaces.len(), 1);
+    assert_eq!(spaces[0].name, "Main pen");
+    assert_eq!(spaces[0].capacity, Some(20));
+    assert!(root.text_content().unwrap_or_default().contains("Main pen"));
+
+    button("setup_skip").click();
+    settle().await;
+    assert_eq!(mock.settings().setup_step, SetupStep::Done);
+    assert!(wizard().is_none());
+    assert_eq!(
+        mock.calls(),
+        vec![
+            "farm_settings",
+            "update_settings",
+            "update_settings",
+            "add_space:Main pen",
+            "update_settings",
+        ]
+    );
+}
+
+#[wasm_bindgen_test]
+async fn data_table_sorts_and_moves_selection_with_keys() {
+    let task = |id: i64, title: &str, due_date: &str| Task {
+        id: Some(id),
+        title: title.to_string(),
+        notes: Some(format!("Notes for {}", title)),
+        goat_name: None,
+        space_id: None,
+        space_name: None,
+        rule_id: None,
+        due_date: due_date.to_string(),
+        status: TaskStatus::Pending,
+    };
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_tasks(vec![
+        task(1, "Trim hooves", "2024-06-01"),
+        task(2, "Deworm", "2024-06-02"),
+        task(3, "Vaccinate", "2024-06-03"),
+    ]);
+    let root = mount_point();
+    yew::Renderer::<Harness<TasksList>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+    assert_eq!(mock.calls(), vec!["tasks"]);
+
+    let row_ids = || {
+        let rows = root.query_selector_all(".data-table tbody tr").unwrap();
+        (0..rows.length())
+            .map(|i| rows.item(i).unwrap().unchecked_into::<Element>().id())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(row_ids(), vec!["task-1", "task-2", "task-3"]);
+
+    // Sorting by title, then flipping the direction
+    let header: HtmlElement = root
+        .query_selector("th[data-column='title']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    header.click();
+    settle().await;
+    assert_eq!(row_ids(), vec!["task-2", "task-1", "task-3"]);
+    assert_eq!(
+        header.get_attribute("aria-sort").as_deref(),
+        Some("ascending")
+    );
+    header.click();
+    settle().await;
+    assert_eq!(row_ids(), vec!["task-3", "task-1", "task-2"]);
+
+    let table = root.query_selector(".data-table").unwrap().unwrap();
+    let press = |key: &str| {
+        let init = web_sys::KeyboardEventInit::new();
+        init.set_key(key);
+        init.set_bubbles(true);
+        let event =
+            web_sys::KeyboardEvent::new_with_keyboard_event_init_dict("keydown", &init).unwrap();
+        table.dispatch_event(&event).unwrap();
+    };
+    let selected = || {
+        root.query_selector("tr[aria-selected='true']")
+            .unwrap()
+            .map(|row| row.id())
+    };
+    press("ArrowDown");
+    settle().await;
+    assert_eq!(selected().as_deref(), Some("task-3"));
+    press("ArrowDown");
+    settle().await;
+    assert_eq!(selected().as_deref(), Some("task-1"));
+    press("End");
+    settle().await;
+    assert_eq!(selected().as_deref(), Some("task-2"));
+    press("ArrowDown");
+    settle().await;
+    assert_eq!(selected().as_deref(), Some("task-2"));
+    press("Home");
+    settle().await;
+    assert_eq!(selected().as_deref(), Some("task-3"));
+    let notes = root.query_selector(".task-notes").unwrap().unwrap();
+    assert_eq!(
+        notes.text_content().as_deref(),
+        Some("Vaccinate: Notes for Vaccinate")
+    );
+
+    // The selection follows its task when the sort changes
+    header.click();
+    settle().await;
+    assert_eq!(row_ids(), vec!["task-2", "task-1", "task-3"]);
+    assert_eq!(selected().as_deref(), Some("task-3"));
+}
+
+#[wasm_bindgen_test]
+async fn undo_restores_bulk_deleted_goats_and_redo_deletes_them_again() {
+    Dispatch::<AccessStore>::global().set(AccessStore::default());
+    let goats = vec![goat("Rani"), goat("Moti")];
+    Dispatch::<GoatStore>::global().set(GoatStore {
+        goats: goats
+            .iter()
+            .enumerate()
+            .map(|(i, params)| Goat {
+                id: i as i64 + 1,
+                params: params.clone(),
+                tag_id: None,
+                date_of_birth: None,
+                created_at: None,
+                updated_at: None,
+            })
+            .collect(),
+        ..Default::default()
+    });
+    let mock = Rc::new(MockApiClient::with_goats(goats));
+    let root = mount_point();
+    yew::Renderer::<Harness<UndoControls>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock).with_children(html! {
+            <DeleteGoatsForm />
+        }),
+    )
+    .render();
+    settle().await;
+
+    let undo: HtmlElement = root
+        .query_selector("button[name='undo']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    assert!(undo.has_attribute("disabled"));
+
+    let names: HtmlInputElement = root
+        .query_selector("input[type='text']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    names.set_value("Rani, Moti");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    names.dispatch_event(&event).unwrap();
+    settle().await;
+    let delete: HtmlElement = root
+        .query_selector("button[type='submit']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    delete.click();
+    settle().await;
+    assert!(Dispatch::<GoatStore>::global().get().goats.is_empty());
+    assert_eq!(mock.calls().len(), 2);
+
+    // One undo brings back both goats of the bulk delete
+    assert!(!undo.has_attribute("disabled"));
+    assert_eq!(
+        undo.get_attribute("title").as_deref(),
+        Some("Undo 2 changes (Ctrl+Z)")
+    );
+    undo.click();
+    settle().await;
+    let calls = mock.calls();
+    assert_eq!(calls.len(), 4);
+    assert!(calls[2..].iter().all(|c| c.starts_with("restore_goat:")));
+    // Both come back from the trash under their own IDs
+    let mut restored: Vec<(i64, String)> = Dispatch::<GoatStore>::global()
+        .get()
+        .goats
+        .iter()
+        .map(|g| (g.id, g.name.clone()))
+        .collect();
+    restored.sort();
+    assert_eq!(
+        restored,
+        vec![(1, "Rani".to_string()), (2, "Moti".to_string())]
+    );
+    assert!(undo.has_attribute("disabled"));
+
+    // Ctrl+Shift+Z redoes the delete
+    let init = web_sys::KeyboardEventInit::new();
+    init.set_key("Z");
+    init.set_ctrl_key(true);
+    init.set_shift_key(true);
+    init.set_bubbles(true);
+    let event =
+        web_sys::KeyboardEvent::new_with_keyboard_event_init_dict("keydown", &init).unwrap();
+    web_sys::window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .body()
+        .unwrap()
+        .dispatch_event(&event)
+        .unwrap();
+    settle().await;
+    let calls = mock.calls();
+    assert_eq!(calls.len(), 6);
+    assert!(calls[4..].iter().all(|c| c.starts_with("delete_goat:")));
+    assert!(Dispatch::<GoatStore>::global().get().goats.is_empty());
+    assert!(!undo.has_attribute("disabled"));
+}
+
+#[wasm_bindgen_test]
+async fn retention_panel_saves_policy_and_restores_trash() {
+    Dispatch::<AccessStore>::global().set(AccessStore::default());
+    Dispatch::<UnitsStore>::global().reduce_mut(|units| units.timezone = chrono_tz::Asia::Kolkata);
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_retention(UpcomingPurges {
+        trash: vec![TrashedGoat {
+            id: 4,
+            goat: Goat {
+                id: 9,
+                params: goat("Rani"),
+                tag_id: None,
+                date_of_birth: None,
+                created_at: None,
+                updated_at: None,
+            },
+            deleted_at: "2026-10-01T08:00:00Z".parse().unwrap(),
+            purge_on: "2026-10-31".to_string(),
+        }],
+        ..Default::default()
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<RetentionPanel>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let rows = root.query_selector_all(".trash tr").unwrap();
+    assert_eq!(rows.length(), 2);
+    assert!(
+        rows.get(1)
+            .unwrap()
+            .text_content()
+            .unwrap()
+            .contains("Rani2026-10-01 13:302026-10-31")
+    );
+
+    let input = |selector: &str| -> HtmlInputElement {
+        root.query_selector(selector)
+            .unwrap()
+            .unwrap()
+            .unchecked_into()
+    };
+    let type_into = |input: &HtmlInputElement, text: &str| {
+        input.set_value(text);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        input.dispatch_event(&event).unwrap();
+    };
+    assert_eq!(input(".trash-days").value(), "30");
+    assert_eq!(input(".event-days").value(), "");
+    let button = |label: &str| -> HtmlElement {
+        let buttons = root.query_selector_all("button").unwrap();
+        (0..buttons.length())
+            .map(|i| buttons.get(i).unwrap())
+            .find(|b| b.text_content().as_deref() == Some(label))
+            .unwrap()
+            .unchecked_into()
+    };
+
+    type_into(&input(".trash-days"), "0");
+    button("Save retention").click();
+    settle().await;
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("Deleted goats must be kept between 1 and 3650 days")
+    );
+
+    type_into(&input(".trash-days"), "14");
+    type_into(&input(".event-days"), "365");
+    button("Save retention").click();
+    settle().await;
+    assert_eq!(
+        mock.settings().retention,
+        RetentionPolicy {
+            trash_days: 14,
+            event_days: Some(365),
+        }
+    );
+    assert!(root.query_selector(".events-due").unwrap().is_some());
+
+    button("Restore").click();
+    settle().await;
+    assert!(mock.calls().contains(&"restore_goat:4".to_string()));
+    assert_eq!(mock.goats()[0].name, "Rani");
+    assert!(root.text_content().unwrap().contains("The trash is empty."));
+    Dispatch::<UnitsStore>::global().reduce_mut(|units| units.timezone = chrono_tz::UTC);
+}
+
+#[wasm_bindgen_test]
+async fn sessions_panel_signs_in_and_out_devices() {
+    let mock = Rc::new(MockApiClient::default());
+    let at = chrono::DateTime::parse_from_rfc3339("2026-10-01T08:00:00Z")
+        .unwrap()
+        .to_utc();
+    mock.set_sessions(vec![Session {
+        id: 1,
+        device: "Office laptop".to_string(),
+        worker: None,
+        user_agent: None,
+        ip: Some("192.168.1.20".to_string()),
+        created_at: at,
+        last_seen_at: at,
+        current: false,
+    }]);
+    mock.fail_next(401, "This device was signed out; sign in again");
+    let root = mount_point();
+    yew::Renderer::<Harness<SessionsPanel>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    assert!(root.query_selector(".signed-out").unwrap().is_some());
+    assert!(root.query_selector(".sessions").unwrap().is_none());
+    let device: HtmlInputElement = root
+        .query_selector(".device-name")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    device.set_value("Barn phone");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    device.dispatch_event(&event).unwrap();
+    let secret: HtmlInputElement = root
+        .query_selector(".sign-in-secret")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    secret.set_value("correct horse");
+    secret.dispatch_event(&event).unwrap();
+    let farm: HtmlInputElement = root
+        .query_selector(".sign-in-farm")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    assert_eq!(farm.value(), "");
+    farm.set_value(" yagi_green ");
+    farm.dispatch_event(&event).unwrap();
+    let button = |label: &str| -> HtmlElement {
+        let buttons = root.query_selector_all("button").unwrap();
+        (0..buttons.length())
+            .map(|i| buttons.get(i).unwrap())
+            .find(|b| b.text_content().as_deref() == Some(label))
+            .unwrap()
+            .unchecked_into()
+    };
+    button("Sign in this device").click();
+    settle().await;
+
+    // The farm is picked before signing in, so the session is the farm's
+    let calls = mock.calls();
+    let signed_in = calls
+        .iter()
+        .position(|c| c == "sign_in:Barn phone")
+        .unwrap();
+    assert_eq!(calls[signed_in - 1], "select_farm:yagi_green");
+    assert!(root.query_selector(".signed-out").unwrap().is_none());
+    assert!(root.query_selector(".device-name").unwrap().is_none());
+    let rows = root.query_selector_all(".sessions tr").unwrap();
+    assert_eq!(rows.length(), 3);
+    let first = rows.get(1).unwrap().text_content().unwrap();
+    assert!(first.starts_with("Barn phone (this device)"), "{}", first);
+    let second = rows.get(2).unwrap().text_content().unwrap();
+    assert!(
+        second.starts_with("Office laptop 192.168.1.20"),
+        "{}",
+        second
+    );
+
+    button("Sign out all other devices").click();
+    settle().await;
+    assert!(mock.calls().contains(&"revoke_other_sessions".to_string()));
+    assert_eq!(root.query_selector_all(".sessions tr").unwrap().length(), 2);
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("Signed out 1 other devices")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn sessions_panel_finishes_oauth_sign_in() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_oauth_providers(vec!["google".to_string()]);
+    // As if Google just sent the browser back here
+    let window = web_sys::window().unwrap();
+    let path = window.location().pathname().unwrap();
+    window
+        .history()
+        .unwrap()
+        .replace_state_with_url(
+            &wasm_bindgen::JsValue::NULL,
+            "",
+            Some(&format!("{}?code=Asha&state=mock_state", path)),
+        )
+        .unwrap();
+    let root = mount_point();
+    yew::Renderer::<Harness<SessionsPanel>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    assert!(
+        mock.calls()
+            .contains(&"finish_oauth:Asha:mock_state".to_string())
+    );
+    assert_eq!(window.location().search().unwrap(), "");
+    assert!(root.text_content().unwrap().contains("Signed in as Asha"));
+    let worker = root.query_selector(".session-worker").unwrap().unwrap();
+    assert_eq!(worker.text_content().as_deref(), Some("Asha"));
+
+    // Signed out again, the device is offered Google
+    let sign_out: HtmlElement = root
+        .query_selector(".revoke-session")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    sign_out.click();
+    settle().await;
+    let google = root.query_selector(".oauth-sign-in").unwrap().unwrap();
+    assert_eq!(
+        google.text_content().as_deref(),
+        Some("Sign in with Google")
+    );
+}
+
+#[wasm_bindgen_test]
+fn toggle_permission_keeps_view_with_changes() {
+    let permission = |module, action| Permission { module, action };
+    let finance_view = permission(PermissionModule::Finance, PermissionAction::View);
+    let finance_delete = permission(PermissionModule::Finance, PermissionAction::Delete);
+    let goats_view = permission(PermissionModule::Goats, PermissionAction::View);
+
+    let granted = toggle_permission(&[goats_view], finance_delete, true);
+    assert_eq!(granted, vec![goats_view, finance_view, finance_delete]);
+    let granted = toggle_permission(&granted, finance_view, false);
+    assert_eq!(granted, vec![goats_view]);
+}
+
+#[wasm_bindgen_test]
+async fn permissions_editor_saves_role_and_can_hides_actions() {
+    Dispatch::<AccessStore>::global().set(AccessStore::default());
+    Dispatch::<PermissionStore>::global().set(PermissionStore::default());
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_role_permissions(vec![RolePermissions::unrestricted("Milker")]);
+    let root = mount_point();
+    yew::Renderer::<Harness<PermissionsEditor>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock).with_children(html! {
+            <Can module={PermissionModule::Finance} action={PermissionAction::View}>
+                <p class="finance-only">{"Transactions"}</p>
+            </Can>
+        }),
+    )
+    .render();
+    settle().await;
+
+    assert!(root.query_selector(".finance-only").unwrap().is_some());
+    let checkbox = |title: &str| -> HtmlInputElement {
+        root.query_selector(&format!(".permissions input[title='{}']", title))
+            .unwrap()
+            .unwrap()
+            .unchecked_into()
+    };
+    assert!(checkbox("Delete Finance").checked());
+    checkbox("View Finance").click();
+    settle().await;
+    assert!(!checkbox("Edit Finance").checked());
+    assert!(!checkbox("Delete Finance").checked());
+
+    // Saved for a worker with this role, whose session is now limited
+    mock.set_my_permissions(MyPermissions {
+        worker: Some("Ravi".to_string()),
+        role: Some("Milker".to_string()),
+        granted: vec![Permission {
+            module: PermissionModule::Goats,
+            action: PermissionAction::View,
+        }],
+    });
+    let save: HtmlElement = root
+        .query_selector(".permissions + button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    save.click();
+    settle().await;
+    assert!(
+        mock.calls()
+            .contains(&"update_role_permissions:Milker:27".to_string())
+    );
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("Saved permissions for Milker")
+    );
+    assert!(root.query_selector(".finance-only").unwrap().is_none());
+    assert!(checkbox("View Goats").disabled());
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("Only the owner can change permissions.")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn access_tokens_are_made_once_and_revoked() {
+    Dispatch::<AccessStore>::global().set(AccessStore::default());
+    let mock = Rc::new(MockApiClient::default());
+    let at = chrono::DateTime::parse_from_rfc3339("2026-10-01T08:00:00Z")
+        .unwrap()
+        .to_utc();
+    mock.set_access_tokens(vec![AccessToken {
+        id: 1,
+        name: "Backup".to_string(),
+        read_only: false,
+        modules: Vec::new(),
+        worker: None,
+        created_at: at,
+        last_used_at: Some(at),
+        revoked: false,
+    }]);
+    let root = mount_point();
+    yew::Renderer::<Harness<AccessTokens>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let text = root.text_content().unwrap();
+    assert!(text.contains("Read & write: all modules"));
+    assert!(root.query_selector(".issued-token").unwrap().is_none());
+
+    let name: HtmlInputElement = root
+        .query_selector(".token-name")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    name.set_value("Nightly sales pull");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    name.dispatch_event(&event).unwrap();
+    let finance: HtmlInputElement = root
+        .query_selector(".token-modules input[title='Finance']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    finance.click();
+    settle().await;
+    let create: HtmlElement = root
+        .query_selector(".token-modules + button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    create.click();
+    settle().await;
+    assert!(
+        mock.calls()
+            .contains(&"create_access_token:Nightly sales pull:true:1".to_string())
+    );
+    let issued = root.query_selector(".issued-token").unwrap().unwrap();
+    assert_eq!(issued.text_content().as_deref(), Some("yagi_mock_token"));
+    let scopes = root.query_selector_all(".token-scope").unwrap();
+    assert_eq!(scopes.length(), 2);
+    assert_eq!(
+        scopes.get(0).unwrap().text_content().as_deref(),
+        Some("Read-only: Finance")
+    );
+    let last_used = root.query_selector(".token-last-used").unwrap().unwrap();
+    assert_eq!(last_used.text_content().as_deref(), Some("Never"));
+
+    let revoke: HtmlElement = root
+        .query_selector(".revoke-token")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    revoke.click();
+    settle().await;
+    assert!(mock.calls().contains(&"revoke_access_token:2".to_string()));
+    assert_eq!(
+        root.query_selector_all(".revoke-token").unwrap().length(),
+        1
+    );
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("Revoked Nightly sales pull")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn alert_rules_are_added_and_deleted() {
+    Dispatch::<AccessStore>::global().set(AccessStore::default());
+    Dispatch::<UnitsStore>::global().reduce_mut(|units| units.timezone = chrono_tz::Asia::Kolkata);
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_alert_rules(vec![AlertRule {
+        id: Some(1),
+        name: "Sick pen".to_string(),
+        metric: AlertMetric::SickGoats,
+        condition: AlertCondition::Above,
+        threshold: 3.0,
+        active: true,
+        firing: true,
+        last_fired_at: Some("2026-10-01T08:00:00Z".parse().unwrap()),
+    }]);
+    let root = mount_point();
+    yew::Renderer::<Harness<AlertRules>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let text = root.text_content().unwrap();
+    assert!(text.contains("Sick goats above 3"));
+    assert!(text.contains("Firing (last fired 2026-10-01 13:30)"));
+
+    let input = |selector: &str, value: &str| {
+        let field: HtmlInputElement = root
+            .query_selector(selector)
+            .unwrap()
+            .unwrap()
+            .unchecked_into();
+        field.set_value(value);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        field.dispatch_event(&event).unwrap();
+    };
+    input(".alert-name", "Milk slump");
+    input(".alert-threshold", "20");
+    settle().await;
+    let add: HtmlElement = root
+        .query_selector(".alert-threshold + button")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    add.click();
+    settle().await;
+    assert!(
+        mock.calls()
+            .contains(&"add_alert_rule:Milk slump".to_string())
+    );
+    let states = root.query_selector_all(".alert-state").unwrap();
+    assert_eq!(states.length(), 2);
+    assert_eq!(states.get(1).unwrap().text_content().as_deref(), Some("OK"));
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("Milk this week down 20% week over week")
+    );
+
+    let delete: HtmlElement = root
+        .query_selector(".delete-alert")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    delete.click();
+    settle().await;
+    assert!(mock.calls().contains(&"delete_alert_rule:1".to_string()));
+    assert_eq!(
+        root.query_selector_all(".delete-alert").unwrap().length(),
+        1
+    );
+    assert!(root.text_content().unwrap().contains("Deleted Sick pen"));
+    Dispatch::<UnitsStore>::global().reduce_mut(|units| units.timezone = chrono_tz::UTC);
+}
+
+#[wasm_bindgen_test]
+async fn lifecycle_pipeline_moves_goats_between_stages() {
+    Dispatch::<AccessStore>::global().set(AccessStore::default());
+    let mock = Rc::new(MockApiClient::default());
+    let goat = |id: i64, name: &str, next: &[LifecycleStage]| PipelineGoat {
+        id,
+        name: name.to_string(),
+        next: next.to_vec(),
+    };
+    mock.set_lifecycle_pipeline(Pipeline {
+        stages: LifecycleStage::ALL
+            .into_iter()
+            .map(|stage| PipelineStage {
+                stage,
+                goats: if stage == LifecycleStage::Kid {
+                    vec![goat(1, "Mini", LifecycleStage::Kid.next_stages())]
+                } else {
+                    Vec::new()
+                },
+            })
+            .collect(),
+        untracked: vec![goat(
+            2,
+            "Raja",
+            &[LifecycleStage::Kid, LifecycleStage::Grower],
+        )],
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<LifecyclePipeline>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let headers = || {
+        let stages = root.query_selector_all(".lifecycle-stage h4").unwrap();
+        (0..stages.length())
+            .map(|i| stages.get(i).unwrap().text_content().unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(headers().len(), 8);
+    assert_eq!(headers()[0], "Untracked (1)");
+    assert!(headers().contains(&"Kid (1)".to_string()));
+    assert!(headers().contains(&"For sale (0)".to_string()));
+    // Sold and Deceased goats have nowhere to go; Mini's menu offers only
+    // the moves a kid may make
+    let menu: HtmlSelectElement = root
+        .query_selector(".lifecycle-stage:nth-child(2) .lifecycle-move")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    assert_eq!(menu.length(), 4);
+
+    menu.set_value("Grower");
+    change(&menu);
+    settle().await;
+    assert!(
+        mock.calls()
+            .contains(&"change_lifecycle_stage:Mini:Grower".to_string())
+    );
+    assert!(headers().contains(&"Kid (0)".to_string()));
+    assert!(headers().contains(&"Grower (1)".to_string()));
+    assert!(root.text_content().unwrap().contains("Mini is now grower"));
+}
+
+#[wasm_bindgen_test]
+async fn service_records_add_ai_services_and_compare_methods() {
+    Dispatch::<AccessStore>::global().set(AccessStore::default());
+    let mut raja = goat("Raja");
+    raja.gender = Gender::Male;
+    Dispatch::<GoatStore>::global().set(GoatStore {
+        goats: [goat("Rani"), raja]
+            .into_iter()
+            .enumerate()
+            .map(|(i, params)| Goat {
+                id: i as i64 + 1,
+                params,
+                tag_id: None,
+                date_of_birth: None,
+                created_at: None,
+                updated_at: None,
+            })
+            .collect(),
+        ..Default::default()
+    });
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_breeding_services(vec![BreedingService {
+        id: Some(1),
+        doe_name: "Rani".to_string(),
+        served_on: "2026-01-10".to_string(),
+        method: ServiceMethod::Natural,
+        buck_name: Some("Raja".to_string()),
+        outside_sire: None,
+        straw_id: None,
+        technician: None,
+        notes: None,
+        outcome: Some(ServiceOutcome::Kidded),
+    }]);
+    mock.set_service_success(vec![MethodSuccess {
+        method: ServiceMethod::Natural,
+        services: 2,
+        kidded: 1,
+        open: 1,
+        pending: 0,
+    }]);
+    let root = mount_point();
+    yew::Renderer::<Harness<ServiceRecords>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let text = root.text_content().unwrap();
+    assert!(
+        text.contains("Natural service: 2 services, 1 kidded, 1 open, 0 pending (50% success)")
+    );
+    assert_eq!(
+        root.query_selector(".service-outcome")
+            .unwrap()
+            .unwrap()
+            .text_content()
+            .as_deref(),
+        Some("Kidded")
+    );
+    // Straw and technician only appear for AI
+    assert!(root.query_selector(".service-straw").unwrap().is_none());
+
+    let select = |selector: &str, value: &str| {
+        let field: HtmlSelectElement = root
+            .query_selector(selector)
+            .unwrap()
+            .unwrap()
+            .unchecked_into();
+        field.set_value(value);
+        change(&field);
+    };
+    let input = |selector: &str, value: &str| {
+        let field: HtmlInputElement = root
+            .query_selector(selector)
+            .unwrap()
+            .unwrap()
+            .unchecked_into();
+        field.set_value(value);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        field.dispatch_event(&event).unwrap();
+    };
+    select(".service-doe", "Rani");
+    select(".service-method", "Artificial");
+    settle().await;
+    input(".service-date", "2026-09-01");
+    input(".outside-name", "Thunder");
+    input(".outside-breed", "Boer");
+    input(".service-straw", "BX-42");
+    settle().await;
+    let add: HtmlElement = root
+        .query_selector(".add-service")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    add.click();
+    settle().await;
+
+    assert!(
+        mock.calls()
+            .contains(&"add_breeding_service:Rani:Artificial".to_string())
+    );
+    assert_eq!(
+        root.query_selector_all(".service-outcome")
+            .unwrap()
+            .length(),
+        2
+    );
+    let text = root.text_content().unwrap();
+    assert!(text.contains("Recorded AI of Rani by Thunder (outside)"));
+    assert!(text.contains("BX-42"));
+}
+
+#[wasm_bindgen_test]
+async fn external_animals_registry_adds_and_removes_animals() {
+    Dispatch::<AccessStore>::global().set(AccessStore::default());
+    Dispatch::<GoatStore>::global().set(GoatStore {
+        goats: vec![Goat {
+            id: 1,
+            params: goat("Rani"),
+            tag_id: None,
+            date_of_birth: None,
+            created_at: None,
+            updated_at: None,
+        }],
+        ..Default::default()
+    });
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_external_animals(vec![ExternalAnimal {
+        id: Some(1),
+        name: "Bela".to_string(),
+        gender: Gender::Female,
+        breed: None,
+        registration: None,
+        owner: Some("Patel farm".to_string()),
+        notes: None,
+    }]);
+    let root = mount_point();
+    yew::Renderer::<Harness<ExternalAnimals>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+    assert!(root.text_content().unwrap().contains("Patel farm"));
+
+    let input = |selector: &str, value: &str| {
+        let field: HtmlInputElement = root
+            .query_selector(selector)
+            .unwrap()
+            .unwrap()
+            .unchecked_into();
+        field.set_value(value);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        field.dispatch_event(&event).unwrap();
+    };
+    let add = || {
+        let button: HtmlElement = root
+            .query_selector(".add-external")
+            .unwrap()
+            .unwrap()
+            .unchecked_into();
+        button.click();
+    };
+
+    // A herd goat's name is refused
+    input(".external-name", "Rani");
+    settle().await;
+    add();
+    settle().await;
+    assert!(root.text_content().unwrap().contains("Registry error"));
+
+    input(".external-name", "Thunder");
+    input(".external-breed", "Boer");
+    settle().await;
+    add();
+    settle().await;
+    assert!(
+        mock.calls()
+            .contains(&"add_external_animal:Thunder".to_string())
+    );
+    let text = root.text_content().unwrap();
+    assert!(text.contains("Added Thunder to the registry"));
+    assert!(text.contains("Boer"));
+    let rows = root.query_selector_all(".delete-external").unwrap();
+    assert_eq!(rows.length(), 2);
+
+    // Bela sorts first; removing her leaves only Thunder
+    let remove: HtmlElement = rows.get(0).unwrap().unchecked_into();
+    remove.click();
+    settle().await;
+    assert!(
+        mock.calls()
+            .contains(&"delete_external_animal:1".to_string())
+    );
+    assert_eq!(
+        root.query_selector_all(".delete-external")
+            .unwrap()
+            .length(),
+        1
+    );
+    let table = root.query_selector(".external-animals").unwrap().unwrap();
+    assert!(!table.text_content().unwrap().contains("Bela"));
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("Removed Bela from the registry")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn goat_list_summarises_vaccinations_as_status_chips() {
+    Dispatch::<GoatStore>::global().set(GoatStore::default());
+    let dose = |name: &str, days: u32| VaccineRef {
+        id: None,
+        name: name.to_string(),
+        given_on: Some(days_ago(days)),
+    };
+    let mut rani = goat("Rani");
+    rani.vaccinations = vec![dose("PPR", 30), dose("Enterotoxaemia", 60), dose("FMD", 90)];
+    let mut moti = goat("Moti");
+    moti.vaccinations = vec![
+        dose("PPR", 30),
+        dose("Enterotoxaemia", 60),
+        dose("FMD", 350),
+    ];
+    let mock = Rc::new(MockApiClient::with_goats(vec![rani, moti, goat("Ganga")]));
+    let root = mount_point();
+    yew::Renderer::<Harness<GoatList>>::with_root_and_props(root.clone(), HarnessProps::new(&mock))
+        .render();
+    settle().await;
+
+    let chip = |row: u32| {
+        let chips = root.query_selector_all(".vaccination-status").unwrap();
+        let chip: HtmlElement = chips.get(row).unwrap().unchecked_into();
+        (chip.text_content().unwrap(), chip.title())
+    };
+    assert_eq!(
+        root.query_selector_all(".vaccination-status")
+            .unwrap()
+            .length(),
+        3
+    );
+    let (label, title) = chip(0);
+    assert_eq!(label, "Up to date");
+    assert!(title.contains(&format!("PPR: given {}", days_ago(30))));
+    let (label, title) = chip(1);
+    assert_eq!(label, "Due soon");
+    assert!(title.contains("FMD: given"));
+    assert_eq!(chip(2).0, "Unknown");
+    assert!(!root.text_content().unwrap().contains("VaccineRef"));
+}
+
+#[wasm_bindgen_test]
+async fn goat_list_groups_goats_into_collapsible_groups_with_subtotals() {
+    Dispatch::<GoatStore>::global().set(GoatStore::default());
+    let mut rani = goat("Rani");
+    rani.breed = Breed::Sirohi;
+    let mock = Rc::new(MockApiClient::with_goats(vec![
+        rani,
+        goat("Moti"),
+        goat("Ganga"),
+    ]));
+    mock.set_spaces(vec![Space {
+        id: Some(1),
+        name: "Kid pen".to_string(),
+        kind: SpaceKind::Enclosure,
+        capacity: None,
+        area_m2: None,
+        goat_names: vec!["Moti".to_string()],
+    }]);
+    let root = mount_point();
+    yew::Renderer::<Harness<GoatList>>::with_root_and_props(root.clone(), HarnessProps::new(&mock))
+        .render();
+    settle().await;
+    assert!(root.query_selector(".goat-group").unwrap().is_none());
+
+    let group_by = |value: &str| {
+        let select: HtmlSelectElement = root
+            .query_selector("select[name='group_by']")
+            .unwrap()
+            .unwrap()
+            .unchecked_into();
+        select.set_value(value);
+        change(&select);
+    };
+    let headers = || {
+        let toggles = root.query_selector_all(".goat-group-toggle").unwrap();
+        (0..toggles.length())
+            .map(|i| toggles.get(i).unwrap().text_content().unwrap())
+            .collect::<Vec<_>>()
+    };
+    group_by("Breed");
+    settle().await;
+    assert_eq!(headers(), vec!["▾ Beetal (2 goats)", "▾ Sirohi (1 goat)"]);
+    let subtotals = root.query_selector_all(".group-subtotal").unwrap();
+    assert_eq!(subtotals.length(), 2);
+    let beetal = subtotals.get(0).unwrap().text_content().unwrap();
+    assert!(beetal.starts_with("Subtotal"));
+    assert!(beetal.contains("60.00"));
+    assert!(beetal.contains("300.00"));
+
+    // Collapsing a group hides its rows but keeps its header
+    let toggle: HtmlElement = root
+        .query_selector(".goat-group-toggle")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    toggle.click();
+    settle().await;
+    assert_eq!(headers()[0], "▸ Beetal (2 goats)");
+    assert_eq!(
+        root.query_selector_all(".group-subtotal").unwrap().length(),
+        1
+    );
+    assert!(root.query_selector("#goat-2").unwrap().is_none());
+
+    group_by("Pen");
+    settle().await;
+    assert_eq!(headers(), vec!["▾ Kid pen (1 goat)", "▾ No pen (2 goats)"]);
+
+    group_by("");
+    settle().await;
+    assert!(root.query_selector(".goat-group").unwrap().is_none());
+    assert_eq!(root.query_selector_all("tbody tr").unwrap().length(), 3);
+}
+
+#[wasm_bindgen_test]
+async fn goat_list_footer_totals_follow_the_filters() {
+    Dispatch::<GoatStore>::global().set(GoatStore::default());
+    let mut rani = goat("Rani");
+    rani.horns = Some(HornStatus::Polled);
+    rani.weight = 40.0;
+    rani.current_price = 250.0;
+    let mock = Rc::new(MockApiClient::with_goats(vec![
+        rani,
+        goat("Moti"),
+        goat("Ganga"),
+    ]));
+    let root = mount_point();
+    yew::Renderer::<Harness<GoatList>>::with_root_and_props(root.clone(), HarnessProps::new(&mock))
+        .render();
+    settle().await;
+
+    let totals = || {
+        let row = root.query_selector("tfoot .herd-totals").unwrap().unwrap();
+        let cells = row.query_selector_all("td").unwrap();
+        (0..cells.length())
+            .map(|i| cells.get(i).unwrap().text_content().unwrap())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        totals(),
+        vec!["Total: 3 goats", "300.00", "avg 33.33", "550.00"]
+    );
+
+    let horns: HtmlSelectElement = root
+        .query_selector("select[name='horns_filter']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    horns.set_value("Polled");
+    change(&horns);
+    settle().await;
+    assert_eq!(
+        totals(),
+        vec!["Total: 1 goat", "100.00", "avg 40.00", "250.00"]
+    );
+}
+
+#[wasm_bindgen_test]
+async fn goat_list_quick_notes_are_saved_inline() {
+    Dispatch::<GoatStore>::global().set(GoatStore::default());
+    Dispatch::<AccessStore>::global().set(AccessStore::default());
+    let mock = Rc::new(MockApiClient::with_goats(vec![goat("Rani")]));
+    let root = mount_point();
+    yew::Renderer::<Harness<GoatList>>::with_root_and_props(root.clone(), HarnessProps::new(&mock))
+        .render();
+    settle().await;
+
+    let note: HtmlInputElement = root
+        .query_selector("input.quick-note")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    note.set_value("  check tomorrow ");
+    change(&note);
+    settle().await;
+    assert!(mock.calls().contains(&"patch_goat:1".to_string()));
+    let state = Dispatch::<GoatStore>::global().get();
+    assert_eq!(
+        state.goats[0].params.quick_note.as_deref(),
+        Some("check tomorrow")
+    );
+    assert!(root.query_selector(".quick-note-error").unwrap().is_none());
+}
+
+#[wasm_bindgen_test]
+async fn diet_reassignment_previews_then_moves_milkers_to_a_new_diet() {
+    Dispatch::<AccessStore>::global().set(AccessStore::default());
+    let moti = GoatParams {
+        gender: Gender::Male,
+        ..goat("Moti")
+    };
+    let mock = Rc::new(MockApiClient::with_goats(vec![
+        goat("Rani"),
+        moti,
+        goat("Kali"),
+        goat("Ganga"),
+    ]));
+    let stored: Vec<Goat> = mock
+        .goats()
+        .into_iter()
+        .zip(1..)
+        .map(|(params, id)| Goat {
+            id,
+            params,
+            tag_id: None,
+            date_of_birth: None,
+            created_at: None,
+            updated_at: None,
+        })
+        .collect();
+    Dispatch::<GoatStore>::global().set(GoatStore {
+        goats: stored,
+        ..Default::default()
+    });
+    let at = |id: i64, name: &str| PipelineGoat {
+        id,
+        name: name.to_string(),
+        next: Vec::new(),
+    };
+    mock.set_lifecycle_pipeline(Pipeline {
+        stages: LifecycleStage::ALL
+            .into_iter()
+            .map(|stage| PipelineStage {
+                stage,
+                goats: match stage {
+                    LifecycleStage::Milker => vec![at(1, "Rani"), at(3, "Kali")],
+                    LifecycleStage::Breeder => vec![at(2, "Moti")],
+                    _ => Vec::new(),
+                },
+            })
+            .collect(),
+        untracked: vec![at(4, "Ganga")],
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<DietReassignment>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let stage: HtmlSelectElement = root
+        .query_selector("select[name='diet_stage']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    stage.set_value("Milker");
+    change(&stage);
+    let diet: HtmlInputElement = root
+        .query_selector("input[name='new_diet']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    diet.set_value("lucerne");
+    let init = web_sys::EventInit::new();
+    init.set_bubbles(true);
+    let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+    diet.dispatch_event(&event).unwrap();
+    settle().await;
+    let apply: HtmlElement = root
+        .query_selector(".apply-diet")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    assert!(apply.has_attribute("disabled"));
+
+    let preview: HtmlElement = root
+        .query_selector(".preview-diet")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    preview.click();
+    settle().await;
+    let rows = root
+        .query_selector_all(".diet-preview td:first-child")
+        .unwrap();
+    let names: Vec<String> = (0..rows.length())
+        .map(|i| rows.get(i).unwrap().text_content().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Kali", "Rani"]);
+    assert!(mock.goats().iter().all(|g| g.diet == "hay"));
+
+    assert_eq!(apply.text_content().unwrap(), "Apply to 2 goats");
+    apply.click();
+    settle().await;
+    assert!(mock.calls().contains(&"bulk_patch_goats:false".to_string()));
+    let diets: Vec<(String, String)> = Dispatch::<GoatStore>::global()
+        .get()
+        .goats
+        .iter()
+        .map(|g| (g.name.clone(), g.diet.clone()))
+        .collect();
+    assert_eq!(
+        diets,
+        vec![
+            ("Rani".to_string(), "lucerne".to_string()),
+            ("Moti".to_string(), "hay".to_string()),
+            ("Kali".to_string(), "lucerne".to_string()),
+            ("Ganga".to_string(), "hay".to_string()),
+        ]
+    );
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("Moved 2 goats onto lucerne")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn nutrition_panel_flags_plans_short_for_the_goats_stage() {
+    let fed = |id: i64, name: &str, diet: &str| Goat {
+        id,
+        params: GoatParams {
+            diet: diet.to_string(),
+            ..goat(name)
+        },
+        tag_id: None,
+        date_of_birth: None,
+        created_at: None,
+        updated_at: None,
+    };
+    Dispatch::<GoatStore>::global().set(GoatStore {
+        goats: vec![
+            fed(1, "Rani", "hay 1.2 kg"),
+            fed(2, "Moti", "hay"),
+            fed(3, "Ganga", "hay 1.2 kg"),
+        ],
+        ..Default::default()
+    });
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_lifecycle_pipeline(Pipeline {
+        stages: LifecycleStage::ALL
+            .into_iter()
+            .map(|stage| PipelineStage {
+                stage,
+                goats: if stage == LifecycleStage::Milker {
+                    vec![PipelineGoat {
+                        id: 1,
+                        name: "Rani".to_string(),
+                        next: Vec::new(),
+                    }]
+                } else {
+                    Vec::new()
+                },
+            })
+            .collect(),
+        untracked: Vec::new(),
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<NutritionPanel>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    // Only the milker is short; Moti's diet has no amounts to check
+    let flagged = root.query_selector(".nutrition-deficits").unwrap().unwrap();
+    assert_eq!(
+        flagged.text_content().unwrap(),
+        "Rani: dry matter, crude protein, energy"
+    );
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("1 goat has a diet without amounts")
+    );
+
+    let pick: HtmlSelectElement = root
+        .query_selector("select[name='nutrition_goat']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    pick.set_value("3");
+    change(&pick);
+    settle().await;
+    let stage: HtmlSelectElement = root
+        .query_selector("select[name='nutrition_stage']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    assert_eq!(stage.value(), "Maintenance");
+    assert_eq!(root.query_selector_all("tr.deficit").unwrap().length(), 0);
+
+    stage.set_value("Pregnant");
+    change(&stage);
+    settle().await;
+    let short = root
+        .query_selector_all("tr.deficit td:first-child")
+        .unwrap();
+    let short: Vec<String> = (0..short.length())
+        .map(|i| short.get(i).unwrap().text_content().unwrap())
+        .collect();
+    assert_eq!(short, vec!["Crude protein"]);
+}
+
+#[wasm_bindgen_test]
+async fn nutrition_panel_applies_a_formulated_ration_as_the_feed_plan() {
+    Dispatch::<AccessStore>::global().set(AccessStore::default());
+    let mock = Rc::new(MockApiClient::with_goats(vec![goat("Rani")]));
+    Dispatch::<GoatStore>::global().set(GoatStore {
+        goats: vec![Goat {
+            id: 1,
+            params: goat("Rani"),
+            tag_id: None,
+            date_of_birth: None,
+            created_at: None,
+            updated_at: None,
+        }],
+        ..Default::default()
+    });
+    let nutrients = Nutrients {
+        dry_matter_kg: 1.0,
+        crude_protein_g: 120.0,
+        energy_mj: 10.0,
+    };
+    mock.set_ration(RationPlan {
+        items: vec![
+            RationItem {
+                feed: "Hay".to_string(),
+                kg_per_day: 0.8,
+                cost_per_day: 6.4,
+            },
+            RationItem {
+                feed: "Concentrate".to_string(),
+                kg_per_day: 0.35,
+                cost_per_day: 10.5,
+            },
+        ],
+        cost_per_day: 16.9,
+        target: nutrients,
+        supply: nutrients,
+        skipped: vec!["Mineral lick is not a known feed".to_string()],
+    });
+    let root = mount_point();
+    yew::Renderer::<Harness<NutritionPanel>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+
+    let pick: HtmlSelectElement = root
+        .query_selector("select[name='nutrition_goat']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    pick.set_value("1");
+    change(&pick);
+    settle().await;
+    let goats: HtmlInputElement = root
+        .query_selector("input[name='ration_goats']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    goats.set_value("12");
+    change(&goats);
+    settle().await;
+    let formulate: HtmlElement = root
+        .query_selector(".formulate-ration")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    formulate.click();
+    settle().await;
+    assert!(mock.calls().contains(&"formulate_ration:12,30".to_string()));
+    let plan = root.query_selector("table.ration-plan").unwrap().unwrap();
+    assert!(
+        plan.text_content()
+            .unwrap()
+            .contains("Concentrate0.3510.50")
+    );
+    assert!(plan.text_content().unwrap().contains("Total16.90"));
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("Not used: Mineral lick")
+    );
+
+    let apply: HtmlElement = root
+        .query_selector(".apply-ration")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    apply.click();
+    settle().await;
+    assert!(mock.calls().contains(&"patch_goat:1".to_string()));
+    let state = Dispatch::<GoatStore>::global().get();
+    assert_eq!(
+        state.goats[0].params.diet,
+        "Hay 0.8 kg, Concentrate 0.35 kg"
+    );
+    assert!(root.query_selector("table.ration-plan").unwrap().is_none());
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("Rani now eats Hay 0.8 kg")
+    );
+}
+
+#[wasm_bindgen_test]
+async fn export_templates_save_run_and_remove_exports() {
+    Dispatch::<AccessStore>::global().set(AccessStore::default());
+    let mock = Rc::new(MockApiClient::default());
+    mock.set_export_templates(vec![ExportTemplate {
+        key: "vet-does".to_string(),
+        name: "Does for the vet".to_string(),
+        columns: vec![ExportColumn::Name, ExportColumn::HealthStatus],
+        filter: ExportFilter {
+            gender: Some(Gender::Female),
+            ..ExportFilter::default()
+        },
+        format: ExportFormat::Csv,
+    }]);
+    let root = mount_point();
+    yew::Renderer::<Harness<ExportTemplates>>::with_root_and_props(
+        root.clone(),
+        HarnessProps::new(&mock),
+    )
+    .render();
+    settle().await;
+    let text = root.text_content().unwrap();
+    assert!(text.contains("Name, Health Status"));
+    assert!(text.contains("Female"));
+    let link = root.query_selector(".run-export").unwrap().unwrap();
+    assert!(
+        link.get_attribute("data-url")
+            .unwrap()
+            .ends_with("/exports/run/vet-does")
+    );
+    assert!(
+        link.get_attribute("download")
+            .unwrap()
+            .starts_with("vet-does-")
+    );
+
+    let input = |selector: &str, value: &str| {
+        let field: HtmlInputElement = root
+            .query_selector(selector)
+            .unwrap()
+            .unwrap()
+            .unchecked_into();
+        field.set_value(value);
+        let init = web_sys::EventInit::new();
+        init.set_bubbles(true);
+        let event = web_sys::Event::new_with_event_init_dict("input", &init).unwrap();
+        field.dispatch_event(&event).unwrap();
+    };
+    let save = || {
+        let button: HtmlElement = root
+            .query_selector(".save-export")
+            .unwrap()
+            .unwrap()
+            .unchecked_into();
+        button.click();
+    };
+
+    // Keys are checked before anything is sent
+    input(".export-key", "Monthly Weights");
+    input(".export-name", "Monthly weights");
+    settle().await;
+    save();
+    settle().await;
+    assert!(root.text_content().unwrap().contains("Export error"));
+    assert!(
+        !mock
+            .calls()
+            .iter()
+            .any(|c| c.starts_with("add_export_template"))
+    );
+
+    input(".export-key", "monthly-weights");
+    let format: HtmlSelectElement = root
+        .query_selector(".export-format")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    format.set_value("json");
+    change(&format);
+    let horns: HtmlSelectElement = root
+        .query_selector("select[name='export_horns']")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    horns.set_value("Polled");
+    change(&horns);
+    settle().await;
+    save();
+    settle().await;
+    assert!(
+        mock.calls()
+            .contains(&"add_export_template:monthly-weights".to_string())
+    );
+    assert!(
+        root.text_content()
+            .unwrap()
+            .contains("Saved export Monthly weights")
+    );
+    let saved = mock
+        .export_templates()
+        .into_iter()
+        .find(|t| t.key == "monthly-weights")
+        .unwrap();
+    assert_eq!(saved.format, ExportFormat::Json);
+    assert_eq!(saved.filter.traits.horns, Some(HornStatus::Polled));
+    assert_eq!(
+        saved.columns,
+        vec![
+            ExportColumn::Name,
+            ExportColumn::Breed,
+            ExportColumn::Gender,
+            ExportColumn::Weight,
+        ]
+    );
+
+    // "monthly-weights" sorts first; removing it leaves the vet export
+    let remove: HtmlElement = root
+        .query_selector(".delete-export")
+        .unwrap()
+        .unwrap()
+        .unchecked_into();
+    remove.click();
+    settle().await;
+    assert!(
+        mock.calls()
+            .contains(&"delete_export_template:monthly-weights".to_string())
+    );
+    assert_eq!(
+        root.query_selector_all(".delete-export").unwrap().length(),
+        1
+    );
+}
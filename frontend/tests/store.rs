@@ -0,0 +1,195 @@
+//! Browser tests for `GoatStore` reducers, driven by `MockApiClient`.
+//!
+//! Run with `wasm-pack test --headless --firefox` from the `frontend` directory.
+
+use frontend::errors::AppError;
+use frontend::services::{Api, MockApiClient};
+use frontend::store::{GoatGrouping, GoatStore, group_goats, herd_totals};
+use shared::{Breed, Goat, GoatParams};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen_test::*;
+use yew::Callback;
+use yew::platform::time::sleep;
+use yewdux::prelude::Dispatch;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn goat(name: &str) -> GoatParams {
+    GoatParams::builder(name)
+        .breed(Breed::Beetal)
+        .cost(100.0)
+        .weight(30.0)
+        .current_price(150.0)
+        .diet("hay")
+        .build()
+        .unwrap()
+}
+
+/// Returns the global store dispatch, reset to its default state.
+fn fresh_store() -> Dispatch<GoatStore> {
+    let dispatch = Dispatch::<GoatStore>::global();
+    dispatch.set(GoatStore::default());
+    dispatch
+}
+
+/// Lets spawned store futures run to completion.
+async fn settle() {
+    sleep(std::time::Duration::from_millis(10)).await;
+}
+
+#[wasm_bindgen_test]
+async fn fetch_goats_fills_empty_cache_then_serves_it() {
+    let mock = Rc::new(MockApiClient::with_goats(vec![goat("Rani"), goat("Moti")]));
+    let dispatch = fresh_store();
+
+    GoatStore::fetch_goats(Api(mock.clone()), dispatch.clone());
+    settle().await;
+    let state = dispatch.get();
+    assert_eq!(state.goats.len(), 2);
+    assert!(!state.loading.fetching);
+    assert!(state.fetched_at.is_some());
+
+    // A second call within the cache lifetime does not reach the backend
+    GoatStore::fetch_goats(Api(mock.clone()), dispatch.clone());
+    settle().await;
+    assert_eq!(mock.calls(), vec!["stream_goats"]);
+}
+
+#[wasm_bindgen_test]
+async fn add_goat_appends_and_clears_flag() {
+    let mock = Rc::new(MockApiClient::default());
+    let dispatch = fresh_store();
+
+    GoatStore::add_goat_async(Api(mock.clone()), dispatch.clone(), goat("Rani"));
+    assert!(dispatch.get().loading.adding);
+    settle().await;
+
+    let state = dispatch.get();
+    assert!(!state.loading.adding);
+    assert_eq!(state.goats.len(), 1);
+    assert_eq!(
+        (state.goats[0].id, &state.goats[0].params),
+        (1, &goat("Rani"))
+    );
+    assert_eq!(mock.goats(), vec![goat("Rani")]);
+}
+
+#[wasm_bindgen_test]
+async fn failed_add_records_error() {
+    let mock = Rc::new(MockApiClient::default());
+    mock.fail_next(500, "database locked");
+    let dispatch = fresh_store();
+
+    GoatStore::add_goat_async(Api(mock.clone()), dispatch.clone(), goat("Rani"));
+    settle().await;
+
+    let state = dispatch.get();
+    assert!(state.goats.is_empty());
+    assert!(state.error.as_deref().unwrap().contains("database locked"));
+}
+
+#[wasm_bindgen_test]
+async fn failed_delete_keeps_goat_and_reports_api_error() {
+    let mock = Rc::new(MockApiClient::with_goats(vec![goat("Rani")]));
+    let dispatch = fresh_store();
+    dispatch.reduce_mut(|state| {
+        state.goats.push(Goat {
+            id: 1,
+            params: goat("Rani"),
+            tag_id: None,
+            date_of_birth: None,
+            created_at: None,
+            updated_at: None,
+        })
+    });
+    mock.fail_next(409, "goat has open tasks");
+
+    let outcome = Rc::new(RefCell::new(None));
+    let sink = outcome.clone();
+    GoatStore::delete_goat_async(
+        Api(mock.clone()),
+        dispatch.clone(),
+        "Rani".to_string(),
+        Callback::from(move |res| *sink.borrow_mut() = Some(res)),
+    );
+    assert!(dispatch.get().loading.is_deleting("Rani"));
+    settle().await;
+
+    assert!(matches!(
+        outcome.borrow().as_ref(),
+        Some(Err(AppError::ApiError { status: 409, .. }))
+    ));
+    let state = dispatch.get();
+    assert!(!state.loading.is_deleting("Rani"));
+    assert_eq!(state.goats.len(), 1);
+}
+
+#[wasm_bindgen_test]
+fn group_goats_subtotals_each_group_with_missing_values_last() {
+    let goat = |id: i64, name: &str, breed: Breed, status: &str| {
+        let mut params = goat(name);
+        params.breed = breed;
+        params.health_status = status.to_string();
+        params.weight = 10.0 * id as f64;
+        Goat {
+            id,
+            params,
+            tag_id: None,
+            date_of_birth: None,
+            created_at: None,
+            updated_at: None,
+        }
+    };
+    let goats = vec![
+        goat(1, "Rani", Breed::Sirohi, "healthy"),
+        goat(2, "Moti", Breed::Beetal, " "),
+        goat(3, "Ganga", Breed::Sirohi, "healthy"),
+    ];
+
+    let by_breed = group_goats(&goats, GoatGrouping::Breed, &HashMap::new());
+    let labels: Vec<_> = by_breed.iter().map(|g| g.label.as_str()).collect();
+    assert_eq!(labels, vec!["Beetal", "Sirohi"]);
+    assert_eq!(by_breed[1].goats.len(), 2);
+    assert_eq!(by_breed[1].total_weight, 40.0);
+    assert_eq!(by_breed[1].total_value, 300.0);
+
+    let by_status = group_goats(&goats, GoatGrouping::Status, &HashMap::new());
+    let labels: Vec<_> = by_status.iter().map(|g| g.label.as_str()).collect();
+    assert_eq!(labels, vec!["healthy", "No status"]);
+
+    let pens = HashMap::from([("Ganga".to_string(), "Kid pen".to_string())]);
+    let by_pen = group_goats(&goats, GoatGrouping::Pen, &pens);
+    let labels: Vec<_> = by_pen.iter().map(|g| g.label.as_str()).collect();
+    assert_eq!(labels, vec!["Kid pen", "No pen"]);
+    let names: Vec<_> = by_pen[1].goats.iter().map(|g| g.name.as_str()).collect();
+    assert_eq!(names, vec!["Rani", "Moti"]);
+}
+
+#[wasm_bindgen_test]
+fn herd_totals_sum_cost_and_value_and_average_weight() {
+    let goats: Vec<Goat> = [("Rani", 40.0), ("Moti", 20.0)]
+        .into_iter()
+        .enumerate()
+        .map(|(at, (name, weight))| {
+            let mut params = goat(name);
+            params.weight = weight;
+            Goat {
+                id: at as i64 + 1,
+                params,
+                tag_id: None,
+                date_of_birth: None,
+                created_at: None,
+                updated_at: None,
+            }
+        })
+        .collect();
+
+    let totals = herd_totals(&goats);
+    assert_eq!(totals.count, 2);
+    assert_eq!(totals.total_cost, goats[0].cost * 2.0);
+    assert_eq!(totals.total_value, goats[0].current_price * 2.0);
+    assert_eq!(totals.average_weight, Some(30.0));
+    assert_eq!(herd_totals(&[]).average_weight, None);
+}
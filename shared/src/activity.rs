@@ -0,0 +1,56 @@
+//! Recent activity on the farm, for the dashboard's feed.
+//!
+//! Events are read back from the records themselves (goats, sale
+//! transactions and health incidents, by the time they were entered), so
+//! every way of recording them shows up without separate bookkeeping.
+
+use crate::search::SearchKind;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Number of events the feed shows.
+pub const ACTIVITY_LIMIT: usize = 20;
+
+/// What happened.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub enum ActivityKind {
+    /// A goat joined the herd.
+    GoatAdded,
+    /// A sale was recorded against a goat.
+    GoatSold,
+    /// A health incident was recorded for a goat.
+    GoatTreated,
+}
+
+impl ActivityKind {
+    /// Short label shown before each event, e.g. "Added".
+    pub fn label(&self) -> &'static str {
+        match self {
+            ActivityKind::GoatAdded => "Added",
+            ActivityKind::GoatSold => "Sold",
+            ActivityKind::GoatTreated => "Treated",
+        }
+    }
+}
+
+/// One entry in the activity feed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ActivityEvent {
+    pub kind: ActivityKind,
+    /// The goat the event concerns.
+    pub goat_id: i64,
+    pub goat_name: String,
+    /// One line describing the event, e.g. "Sold Rani for 150.00".
+    pub summary: String,
+    /// When the record was entered.
+    pub at: DateTime<Utc>,
+}
+
+impl ActivityEvent {
+    /// The element ID of the goat's row, e.g. `goat-12`, which the feed
+    /// links to as a URL fragment (as quick search does).
+    pub fn anchor(&self) -> String {
+        format!("{}-{}", SearchKind::Goat.anchor_prefix(), self.goat_id)
+    }
+}
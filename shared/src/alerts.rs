@@ -0,0 +1,260 @@
+//! Alert rules on herd metrics, such as "milk this week down 20% week over
+//! week" or "more than 3 sick goats".
+//!
+//! The backend scheduler checks every active rule against the dashboard
+//! figures (see `stats`). A rule fires when its condition starts to hold and
+//! notifies the workers who may see the metric's module; it fires again only
+//! after the condition has stopped holding in between.
+
+use crate::permissions::PermissionModule;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// Link the dashboard opens for alert notifications: the alert rules panel.
+pub const ALERTS_LINK: &str = "#alerts";
+
+/// Longest allowed alert rule name, in characters.
+pub const MAX_ALERT_NAME_LEN: usize = 80;
+
+/// A herd figure alert rules watch.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum AlertMetric {
+    /// Goats in the herd.
+    HerdSize,
+    /// Current price of the herd, in the base currency.
+    HerdValue,
+    /// Litres of milk recorded in the last seven days.
+    MilkThisWeek,
+    /// Expenses this calendar month, in the base currency.
+    ExpensesThisMonth,
+    /// Goats whose health status is not healthy, good or excellent.
+    SickGoats,
+}
+
+impl AlertMetric {
+    /// Every metric, in display order.
+    pub const ALL: [AlertMetric; 5] = [
+        AlertMetric::HerdSize,
+        AlertMetric::HerdValue,
+        AlertMetric::MilkThisWeek,
+        AlertMetric::ExpensesThisMonth,
+        AlertMetric::SickGoats,
+    ];
+
+    /// Converts a database string to `AlertMetric`.
+    pub fn from_str(s: &str) -> Result<AlertMetric, String> {
+        trace!("Parsing AlertMetric from '{}'", s);
+        match s {
+            "HerdSize" => Ok(AlertMetric::HerdSize),
+            "HerdValue" => Ok(AlertMetric::HerdValue),
+            "MilkThisWeek" => Ok(AlertMetric::MilkThisWeek),
+            "ExpensesThisMonth" => Ok(AlertMetric::ExpensesThisMonth),
+            "SickGoats" => Ok(AlertMetric::SickGoats),
+            other => {
+                debug!("Failed to parse AlertMetric enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts an `AlertMetric` to a database string.
+    pub fn to_str(metric: &AlertMetric) -> &str {
+        match metric {
+            AlertMetric::HerdSize => "HerdSize",
+            AlertMetric::HerdValue => "HerdValue",
+            AlertMetric::MilkThisWeek => "MilkThisWeek",
+            AlertMetric::ExpensesThisMonth => "ExpensesThisMonth",
+            AlertMetric::SickGoats => "SickGoats",
+        }
+    }
+
+    /// Human-readable name, e.g. "Milk this week".
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertMetric::HerdSize => "Herd size",
+            AlertMetric::HerdValue => "Herd value",
+            AlertMetric::MilkThisWeek => "Milk this week",
+            AlertMetric::ExpensesThisMonth => "Expenses this month",
+            AlertMetric::SickGoats => "Sick goats",
+        }
+    }
+
+    /// The period the metric is compared with for `DropsBy` and `RisesBy`,
+    /// or `None` if it has no history.
+    pub fn period(&self) -> Option<&'static str> {
+        match self {
+            AlertMetric::ExpensesThisMonth => Some("month"),
+            AlertMetric::SickGoats => None,
+            _ => Some("week"),
+        }
+    }
+
+    /// The module whose viewers are notified when a rule on the metric fires.
+    pub fn module(&self) -> PermissionModule {
+        match self {
+            AlertMetric::HerdSize | AlertMetric::HerdValue => PermissionModule::Goats,
+            AlertMetric::MilkThisWeek => PermissionModule::Production,
+            AlertMetric::ExpensesThisMonth => PermissionModule::Finance,
+            AlertMetric::SickGoats => PermissionModule::Health,
+        }
+    }
+}
+
+/// How an alert rule compares its metric with its threshold.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum AlertCondition {
+    /// The metric is more than the threshold.
+    Above,
+    /// The metric is less than the threshold.
+    Below,
+    /// The metric fell by at least the threshold, in percent, since the
+    /// previous period.
+    DropsBy,
+    /// The metric grew by at least the threshold, in percent, since the
+    /// previous period.
+    RisesBy,
+}
+
+impl AlertCondition {
+    /// Every condition, in display order.
+    pub const ALL: [AlertCondition; 4] = [
+        AlertCondition::Above,
+        AlertCondition::Below,
+        AlertCondition::DropsBy,
+        AlertCondition::RisesBy,
+    ];
+
+    /// Converts a database string to `AlertCondition`.
+    pub fn from_str(s: &str) -> Result<AlertCondition, String> {
+        trace!("Parsing AlertCondition from '{}'", s);
+        match s {
+            "Above" => Ok(AlertCondition::Above),
+            "Below" => Ok(AlertCondition::Below),
+            "DropsBy" => Ok(AlertCondition::DropsBy),
+            "RisesBy" => Ok(AlertCondition::RisesBy),
+            other => {
+                debug!("Failed to parse AlertCondition enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts an `AlertCondition` to a database string.
+    pub fn to_str(condition: &AlertCondition) -> &str {
+        match condition {
+            AlertCondition::Above => "Above",
+            AlertCondition::Below => "Below",
+            AlertCondition::DropsBy => "DropsBy",
+            AlertCondition::RisesBy => "RisesBy",
+        }
+    }
+
+    /// Human-readable name, e.g. "drops by (%)".
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertCondition::Above => "is above",
+            AlertCondition::Below => "is below",
+            AlertCondition::DropsBy => "drops by (%)",
+            AlertCondition::RisesBy => "rises by (%)",
+        }
+    }
+
+    /// Whether the condition compares the metric with its previous period.
+    pub fn is_change(&self) -> bool {
+        matches!(self, AlertCondition::DropsBy | AlertCondition::RisesBy)
+    }
+}
+
+/// A rule notifying workers when a herd metric crosses a threshold.
+///
+/// `firing` and `last_fired_at` are kept by the backend and ignored when a
+/// rule is created.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    pub id: Option<i64>,
+    pub name: String,
+    pub metric: AlertMetric,
+    pub condition: AlertCondition,
+    /// A value of the metric, or a percentage for `DropsBy` and `RisesBy`.
+    pub threshold: f64,
+    pub active: bool,
+    /// Whether the condition held when the rule was last checked.
+    #[serde(default)]
+    pub firing: bool,
+    /// When the rule last fired.
+    #[serde(default)]
+    pub last_fired_at: Option<DateTime<Utc>>,
+}
+
+impl AlertRule {
+    /// Checks the rule is named and its threshold fits its condition.
+    pub fn validate(&self) -> Result<(), String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Alert rules need a name".to_string());
+        }
+        if name.chars().count() > MAX_ALERT_NAME_LEN {
+            return Err(format!(
+                "Alert rule names must be at most {} characters",
+                MAX_ALERT_NAME_LEN
+            ));
+        }
+        if !self.threshold.is_finite() || self.threshold < 0.0 {
+            return Err("The threshold must be a number of at least 0".to_string());
+        }
+        if self.condition.is_change() {
+            if self.metric.period().is_none() {
+                return Err(format!(
+                    "{} has no history to compare with",
+                    self.metric.label()
+                ));
+            }
+            if self.threshold == 0.0 {
+                return Err("The change must be more than 0%".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// What the rule watches for, e.g. "Milk this week down 20% week over
+    /// week".
+    pub fn describe(&self) -> String {
+        let period = self.metric.period().unwrap_or("period");
+        match self.condition {
+            AlertCondition::Above => format!("{} above {}", self.metric.label(), self.threshold),
+            AlertCondition::Below => format!("{} below {}", self.metric.label(), self.threshold),
+            AlertCondition::DropsBy => format!(
+                "{} down {}% {} over {}",
+                self.metric.label(),
+                self.threshold,
+                period,
+                period
+            ),
+            AlertCondition::RisesBy => format!(
+                "{} up {}% {} over {}",
+                self.metric.label(),
+                self.threshold,
+                period,
+                period
+            ),
+        }
+    }
+
+    /// Whether the condition holds for the metric at `value`, having been
+    /// `previous` in the period before. Changes from a previous value of
+    /// zero, or without one, never hold.
+    pub fn holds(&self, value: f64, previous: Option<f64>) -> bool {
+        let change = previous
+            .filter(|previous| *previous != 0.0)
+            .map(|previous| (value - previous) / previous * 100.0);
+        match self.condition {
+            AlertCondition::Above => value > self.threshold,
+            AlertCondition::Below => value < self.threshold,
+            AlertCondition::DropsBy => change.is_some_and(|c| -c >= self.threshold),
+            AlertCondition::RisesBy => change.is_some_and(|c| c >= self.threshold),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Herd performance analytics computed by the backend.
+//!
+//! Feed efficiency combines feeding logs from inventory consumption with
+//! weight history: the feed conversion ratio (FCR) is kilograms of feed per
+//! kilogram of weight gained, and cost per kg gain prices the same feed.
+
+use serde::{Deserialize, Serialize};
+
+/// Feed efficiency of one goat, or of one pen as a whole.
+///
+/// `fcr` and `cost_per_kg_gain` are `None` when no weight was gained over the
+/// period (fewer than two weighings, or a loss), since the ratio is undefined.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FeedEfficiency {
+    pub name: String,
+    /// First and last weighing bounding the period analysed.
+    pub period_start: Option<String>,
+    pub period_end: Option<String>,
+    pub feed_kg: f64,
+    pub feed_cost: f64,
+    pub weight_gain: f64,
+    pub fcr: Option<f64>,
+    pub cost_per_kg_gain: Option<f64>,
+}
+
+impl FeedEfficiency {
+    /// Builds an entry, deriving the ratios from the totals.
+    pub fn new(
+        name: String,
+        period: Option<(String, String)>,
+        feed_kg: f64,
+        feed_cost: f64,
+        weight_gain: f64,
+    ) -> Self {
+        let gained = weight_gain > 0.0;
+        let (period_start, period_end) = period.unzip();
+        FeedEfficiency {
+            name,
+            period_start,
+            period_end,
+            feed_kg,
+            feed_cost,
+            weight_gain,
+            fcr: gained.then(|| feed_kg / weight_gain),
+            cost_per_kg_gain: gained.then(|| feed_cost / weight_gain),
+        }
+    }
+}
+
+/// Feed efficiency per goat and per pen.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct FeedEfficiencyReport {
+    pub goats: Vec<FeedEfficiency>,
+    pub pens: Vec<FeedEfficiency>,
+}
@@ -0,0 +1,101 @@
+//! Portable farm archives: one ZIP file holding every farm record, for
+//! moving a farm between self-hosted instances or to the hosted service.
+//!
+//! The archive holds `manifest.json`, one JSON file per table under
+//! `tables/` (an array of rows, each an object of column values) and the
+//! binary values, such as note photos and voice notes, under `files/`. A
+//! cell holding a binary value is the object `{"file": "<path>"}`. The
+//! manifest lists every table and file with its SHA-256, so an archive that
+//! was damaged or edited is refused as a whole.
+//!
+//! Since herd records include the farm's finances, an archive can be
+//! encrypted with a passphrase. The encrypted file is `ENCRYPTED_MAGIC`, a
+//! 16-byte salt, a 12-byte nonce and the archive sealed with AES-256-GCM
+//! under a key derived from the passphrase and salt with Argon2id (19 MiB,
+//! 2 passes). The passphrase travels in the `PASSPHRASE_HEADER` header,
+//! percent-encoded so it may hold any character.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// `ArchiveManifest::format` of every farm archive.
+pub const ARCHIVE_FORMAT: &str = "yagi-farm-archive";
+
+/// Version of the archive layout described above.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Path of the manifest inside the archive.
+pub const MANIFEST_PATH: &str = "manifest.json";
+
+/// First bytes of an encrypted archive; a plain archive starts with the ZIP
+/// signature instead.
+pub const ENCRYPTED_MAGIC: &[u8; 8] = b"YAGIENC1";
+
+/// Request header carrying the passphrase that encrypts an exported archive
+/// or unlocks one being restored.
+pub const PASSPHRASE_HEADER: &str = "X-Archive-Passphrase";
+
+/// Fewest characters a passphrase may have.
+pub const MIN_PASSPHRASE_CHARS: usize = 10;
+
+/// Checks that `passphrase` is long enough to encrypt an archive with.
+pub fn check_passphrase(passphrase: &str) -> Result<(), String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(format!(
+            "The passphrase must be at least {} characters long",
+            MIN_PASSPHRASE_CHARS
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `file` is an encrypted archive.
+pub fn is_encrypted(file: &[u8]) -> bool {
+    file.starts_with(ENCRYPTED_MAGIC)
+}
+
+/// A table stored in the archive.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchiveTable {
+    pub name: String,
+    pub rows: usize,
+    /// Path of the table's JSON file inside the archive.
+    pub path: String,
+    /// Hex-encoded SHA-256 of that file.
+    pub sha256: String,
+}
+
+/// A binary value stored in the archive.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchiveFile {
+    pub path: String,
+    pub bytes: u64,
+    /// Hex-encoded SHA-256 of the file.
+    pub sha256: String,
+}
+
+/// Contents of `manifest.json`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchiveManifest {
+    /// Always `ARCHIVE_FORMAT`.
+    pub format: String,
+    /// `ARCHIVE_VERSION` of the instance that wrote the archive.
+    pub version: u32,
+    /// Latest database migration of the instance that wrote the archive;
+    /// an instance only imports archives it has every migration for.
+    pub schema_version: i64,
+    /// When the archive was exported.
+    pub exported_at: DateTime<Utc>,
+    pub tables: Vec<ArchiveTable>,
+    pub files: Vec<ArchiveFile>,
+}
+
+/// What an import restored.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ArchiveSummary {
+    pub tables: usize,
+    pub rows: usize,
+    pub files: usize,
+    /// Tables in the archive this instance does not have, left out.
+    pub skipped: Vec<String>,
+}
@@ -0,0 +1,85 @@
+//! Files attached to a goat or a task.
+//!
+//! So far these are voice notes: short recordings made in the browser by
+//! workers who would rather speak than type, played back inline next to
+//! the goat or task they are about.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Longest accepted voice note, in seconds.
+pub const MAX_VOICE_NOTE_SECS: f64 = 120.0;
+
+/// Largest accepted attachment, in bytes; two minutes of compressed
+/// speech stay well below it.
+pub const MAX_ATTACHMENT_BYTES: usize = 5 * 1024 * 1024;
+
+/// What an attachment belongs to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum AttachmentTarget {
+    /// The goat with this name.
+    Goat(String),
+    /// The task with this ID.
+    Task(i64),
+}
+
+impl AttachmentTarget {
+    /// The query parameter selecting the target's attachments, e.g.
+    /// `("goat_name", "Rani")`.
+    pub fn query(&self) -> (&'static str, String) {
+        match self {
+            AttachmentTarget::Goat(name) => ("goat_name", name.clone()),
+            AttachmentTarget::Task(id) => ("task_id", id.to_string()),
+        }
+    }
+}
+
+/// A stored attachment. Its content is fetched separately, from
+/// `/attachments/{id}`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Attachment {
+    pub id: i64,
+    /// Set for attachments on a goat.
+    pub goat_name: Option<String>,
+    /// Set for attachments on a task.
+    pub task_id: Option<i64>,
+    /// Who recorded it.
+    pub author: String,
+    /// MIME type, e.g. `audio/webm`.
+    pub content_type: String,
+    /// Length of a recording, in seconds.
+    pub duration_secs: Option<f64>,
+    pub size_bytes: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Checks a voice note of `size_bytes` bytes lasting `duration_secs` is
+/// audio and within the limits.
+pub fn check_voice_note(
+    content_type: &str,
+    duration_secs: f64,
+    size_bytes: usize,
+) -> Result<(), String> {
+    if !content_type.starts_with("audio/") {
+        return Err(format!(
+            "A voice note must be audio, got '{}'",
+            content_type
+        ));
+    }
+    if size_bytes == 0 {
+        return Err("The voice note is empty".to_string());
+    }
+    if size_bytes > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "A voice note must be at most {} MB",
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        ));
+    }
+    if !(duration_secs > 0.0 && duration_secs <= MAX_VOICE_NOTE_SECS) {
+        return Err(format!(
+            "A voice note must last between 0 and {} seconds, got {}",
+            MAX_VOICE_NOTE_SECS, duration_secs
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,416 @@
+//! Breeding records and pairing recommendations.
+//!
+//! Pedigree links each goat to its sire and dam, kidding records capture the
+//! outcome of each birth, and recommendations suggest buck–doe pairings
+//! scored on breed, age, health and past kidding results, with close-relative
+//! matings flagged by their inbreeding coefficient. Genetic tags name the
+//! bloodlines and traits (e.g. "High twinning") a goat carries, and the
+//! pedigree tree shows which of them it inherits from its ancestors.
+//! Service records log each time a doe is bred, by a buck or by artificial
+//! insemination (AI) with a semen straw, so the two can be compared by how
+//! often they end in a kidding. Animals kept off the farm, such as a
+//! neighbour's buck, live in a small registry of external animals that
+//! pedigrees and breeding records can name without adding them to the herd.
+
+use crate::Gender;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// Minimum age, in days, at which a doe is considered for breeding.
+pub const DOE_MIN_AGE_DAYS: i64 = 240;
+
+/// Minimum age, in days, at which a buck is considered for breeding.
+pub const BUCK_MIN_AGE_DAYS: i64 = 180;
+
+/// Minimum age, in days, at which a goat of `gender` is bred.
+pub fn min_breeding_age_days(gender: &Gender) -> i64 {
+    match gender {
+        Gender::Male => BUCK_MIN_AGE_DAYS,
+        Gender::Female => DOE_MIN_AGE_DAYS,
+    }
+}
+
+/// Parentage and birth date of a goat, identified by name.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Pedigree {
+    pub goat_name: String,
+    pub sire_name: Option<String>,
+    pub dam_name: Option<String>,
+    pub date_of_birth: Option<String>,
+    /// `sire_name` names an external animal rather than a herd goat.
+    #[serde(default)]
+    pub sire_external: bool,
+    /// `dam_name` names an external animal rather than a herd goat.
+    #[serde(default)]
+    pub dam_external: bool,
+}
+
+/// Longest external animal name accepted, in characters.
+pub const MAX_EXTERNAL_NAME_LEN: usize = 60;
+
+/// An animal kept off the farm, e.g. a neighbour's buck, that pedigrees and
+/// breeding records may name. It never appears in the herd.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExternalAnimal {
+    pub id: Option<i64>,
+    pub name: String,
+    pub gender: Gender,
+    pub breed: Option<String>,
+    /// Herd-book or registry number.
+    pub registration: Option<String>,
+    /// Who keeps the animal, e.g. "Ramesh, next village".
+    pub owner: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl ExternalAnimal {
+    /// Checks the name is neither blank nor longer than
+    /// `MAX_EXTERNAL_NAME_LEN`.
+    pub fn validate(&self) -> Result<(), String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("External animals need a name".into());
+        }
+        if name.chars().count() > MAX_EXTERNAL_NAME_LEN {
+            return Err(format!(
+                "Name is longer than {} characters",
+                MAX_EXTERNAL_NAME_LEN
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Castration of a buck (making it a wether) or spaying of a doe, identified
+/// by goat name. Neutered goats are left out of pairings and heat tracking.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Neutering {
+    pub goat_name: String,
+    /// Date of the procedure (`YYYY-MM-DD`).
+    pub neutered_on: String,
+    /// Why the goat was neutered, e.g. "Raised for meat".
+    pub reason: Option<String>,
+}
+
+/// What a neutered goat of `gender` is called.
+pub fn neutered_label(gender: &Gender) -> &'static str {
+    match gender {
+        Gender::Male => "Wether",
+        Gender::Female => "Spayed doe",
+    }
+}
+
+/// Outcome of a single kidding.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KiddingRecord {
+    pub id: Option<i64>,
+    pub doe_name: String,
+    pub buck_name: Option<String>,
+    /// `buck_name` names an external animal rather than a herd goat.
+    #[serde(default)]
+    pub buck_external: bool,
+    pub kidded_on: String,
+    pub kids_born: u32,
+    pub kids_alive: u32,
+    pub notes: Option<String>,
+}
+
+/// How a doe was bred.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub enum ServiceMethod {
+    /// Covered by a buck.
+    Natural,
+    /// Artificial insemination with a semen straw.
+    Artificial,
+}
+
+impl ServiceMethod {
+    pub const ALL: [ServiceMethod; 2] = [ServiceMethod::Natural, ServiceMethod::Artificial];
+
+    /// Converts a database string to `ServiceMethod`.
+    pub fn from_str(s: &str) -> Result<ServiceMethod, String> {
+        trace!("Parsing ServiceMethod from '{}'", s);
+        match s {
+            "Natural" => Ok(ServiceMethod::Natural),
+            "Artificial" => Ok(ServiceMethod::Artificial),
+            other => {
+                debug!("Failed to parse ServiceMethod enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `ServiceMethod` to a database string.
+    pub fn to_str(method: &ServiceMethod) -> &str {
+        match method {
+            ServiceMethod::Natural => "Natural",
+            ServiceMethod::Artificial => "Artificial",
+        }
+    }
+
+    /// Human-readable name.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ServiceMethod::Natural => "Natural service",
+            ServiceMethod::Artificial => "AI",
+        }
+    }
+}
+
+/// A buck from outside the herd, e.g. the donor of bought semen or a
+/// borrowed stud. A name matching a male in the external animal registry
+/// links the service to it, and missing details are taken from there.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OutsideSire {
+    pub name: String,
+    pub breed: Option<String>,
+    /// Herd-book or registry number.
+    pub registration: Option<String>,
+    /// Where the semen or buck came from, e.g. a stud or semen bank.
+    pub source: Option<String>,
+}
+
+/// Whether a service was followed by a kidding.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub enum ServiceOutcome {
+    /// The kidding window has not passed yet.
+    Pending,
+    /// The doe kidded within the window.
+    Kidded,
+    /// The window passed without a kidding; the doe did not settle.
+    Open,
+}
+
+/// One time a doe was bred, by a herd buck or an outside sire.
+///
+/// `outcome` is worked out by the backend from the kidding records and is
+/// ignored when a service is recorded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BreedingService {
+    pub id: Option<i64>,
+    pub doe_name: String,
+    /// Date of the service (`YYYY-MM-DD`).
+    pub served_on: String,
+    pub method: ServiceMethod,
+    /// Sire from the herd; for AI, the buck the semen was collected from.
+    pub buck_name: Option<String>,
+    pub outside_sire: Option<OutsideSire>,
+    /// Straw or semen batch ID; AI only.
+    pub straw_id: Option<String>,
+    /// Who inseminated the doe; AI only.
+    pub technician: Option<String>,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub outcome: Option<ServiceOutcome>,
+}
+
+fn is_blank(value: &Option<String>) -> bool {
+    value.as_deref().is_none_or(|v| v.trim().is_empty())
+}
+
+impl BreedingService {
+    /// Checks the service names exactly one sire, and that AI services carry
+    /// a straw ID while natural ones have no straw or technician.
+    pub fn validate(&self) -> Result<(), String> {
+        match (&self.buck_name, &self.outside_sire) {
+            (Some(_), Some(_)) => {
+                return Err("Give either a herd buck or an outside sire, not both".into());
+            }
+            (None, None) => return Err("A service needs a sire".into()),
+            (None, Some(sire)) if sire.name.trim().is_empty() => {
+                return Err("The outside sire needs a name".into());
+            }
+            _ => {}
+        }
+        match self.method {
+            ServiceMethod::Artificial if is_blank(&self.straw_id) => {
+                Err("AI services need a straw or batch ID".into())
+            }
+            ServiceMethod::Natural if !is_blank(&self.straw_id) || !is_blank(&self.technician) => {
+                Err("Straw IDs and technicians only apply to AI".into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Name of the sire, marking outside sires, e.g. "Thunder (outside)".
+    pub fn sire_label(&self) -> String {
+        match (&self.buck_name, &self.outside_sire) {
+            (Some(buck), _) => buck.clone(),
+            (None, Some(sire)) => format!("{} (outside)", sire.name),
+            (None, None) => "Unknown".to_string(),
+        }
+    }
+}
+
+/// How often services by one method ended in a kidding.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct MethodSuccess {
+    pub method: ServiceMethod,
+    pub services: u32,
+    pub kidded: u32,
+    pub open: u32,
+    pub pending: u32,
+}
+
+impl MethodSuccess {
+    /// Share of settled services (kidded or open) that kidded, or `None`
+    /// before any has settled.
+    pub fn success_rate(&self) -> Option<f64> {
+        let settled = self.kidded + self.open;
+        (settled > 0).then(|| self.kidded as f64 / settled as f64)
+    }
+}
+
+/// A suggested buck–doe pairing.
+///
+/// `score` ranges from 0 to 100; `reasons` explain what raised it and
+/// `warnings` what lowered it. `close_relatives` is set when the expected
+/// inbreeding coefficient of the kids reaches that of a first-cousin mating.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BreedingRecommendation {
+    pub buck_name: String,
+    pub doe_name: String,
+    pub score: f64,
+    pub inbreeding_coefficient: f64,
+    pub close_relatives: bool,
+    pub reasons: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Longest genetic tag accepted, in characters.
+pub const MAX_TAG_LEN: usize = 40;
+
+/// Bloodline and trait tags offered as suggestions; any other name may be
+/// used too.
+pub const SUGGESTED_TAGS: [&str; 5] = [
+    "High twinning",
+    "Fast growth",
+    "High milk yield",
+    "Parasite resistant",
+    "Heat tolerant",
+];
+
+/// Named bloodlines and genetic traits a goat is tagged with, e.g.
+/// "High twinning", used to pick breeding stock.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GeneticTags {
+    pub goat_name: String,
+    pub tags: Vec<String>,
+}
+
+/// Trims `raw` and collapses inner whitespace, rejecting blank and
+/// overlong tags.
+pub fn normalize_tag(raw: &str) -> Result<String, String> {
+    let tag = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    if tag.is_empty() {
+        return Err("Tag cannot be blank".into());
+    }
+    if tag.chars().count() > MAX_TAG_LEN {
+        return Err(format!(
+            "Tag '{}' is longer than {} characters",
+            tag, MAX_TAG_LEN
+        ));
+    }
+    Ok(tag)
+}
+
+/// Normalizes every tag and drops case-insensitive duplicates, keeping the
+/// first spelling.
+pub fn normalize_tags(raw: &[String]) -> Result<Vec<String>, String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in raw {
+        let tag = normalize_tag(tag)?;
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            tags.push(tag);
+        }
+    }
+    Ok(tags)
+}
+
+/// A goat in a pedigree tree with its tags and known parents.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PedigreeNode {
+    pub name: String,
+    /// An animal from the external registry; its own parents are unknown.
+    #[serde(default)]
+    pub external: bool,
+    pub tags: Vec<String>,
+    pub sire: Option<Box<PedigreeNode>>,
+    pub dam: Option<Box<PedigreeNode>>,
+}
+
+impl PedigreeNode {
+    /// Whether this goat is tagged with `tag`, ignoring case.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    fn parents(&self) -> impl Iterator<Item = &PedigreeNode> {
+        [&self.sire, &self.dam].into_iter().flatten().map(|p| &**p)
+    }
+}
+
+/// A tag carried by a goat's ancestors.
+///
+/// `share` is the expected fraction of the goat's genes that come from
+/// tagged ancestors: each parent passes on half, and a tagged ancestor
+/// passes on its whole line. `carriers` names the tagged ancestors, nearest
+/// generation first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InheritedTag {
+    pub tag: String,
+    pub share: f64,
+    pub carriers: Vec<String>,
+}
+
+/// Tags `goat` inherits from the ancestors in its tree, largest share
+/// first, then by tag ignoring case. Unknown parents contribute nothing.
+pub fn inherited_tags(goat: &PedigreeNode) -> Vec<InheritedTag> {
+    fn share(node: &PedigreeNode, tag: &str) -> f64 {
+        if node.has_tag(tag) {
+            1.0
+        } else {
+            node.parents().map(|p| share(p, tag)).sum::<f64>() / 2.0
+        }
+    }
+
+    let mut generation: Vec<&PedigreeNode> = goat.parents().collect();
+    let mut inherited: Vec<InheritedTag> = Vec::new();
+    while !generation.is_empty() {
+        for ancestor in &generation {
+            for tag in &ancestor.tags {
+                let entry = match inherited
+                    .iter_mut()
+                    .position(|i| i.tag.eq_ignore_ascii_case(tag))
+                {
+                    Some(i) => &mut inherited[i],
+                    None => {
+                        inherited.push(InheritedTag {
+                            tag: tag.clone(),
+                            share: 0.0,
+                            carriers: Vec::new(),
+                        });
+                        inherited.last_mut().unwrap()
+                    }
+                };
+                if !entry.carriers.contains(&ancestor.name) {
+                    entry.carriers.push(ancestor.name.clone());
+                }
+            }
+        }
+        generation = generation.iter().flat_map(|g| g.parents()).collect();
+    }
+
+    for entry in &mut inherited {
+        entry.share = goat.parents().map(|p| share(p, &entry.tag)).sum::<f64>() / 2.0;
+    }
+    inherited.sort_by(|a, b| {
+        b.share
+            .total_cmp(&a.share)
+            .then_with(|| a.tag.to_lowercase().cmp(&b.tag.to_lowercase()))
+    });
+    inherited
+}
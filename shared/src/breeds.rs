@@ -0,0 +1,193 @@
+//! Reference data about each known breed, used to hint at typical values
+//! while a goat is entered.
+//!
+//! Breeds are also grouped by what they are kept for. The breed catalog is
+//! the built-in breeds plus any the farm has added, so new breeds can be
+//! picked from the list without a new release.
+
+use crate::Breed;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// What a breed is mainly kept for.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "PascalCase")]
+pub enum BreedPurpose {
+    Dairy,
+    Meat,
+    Dual,
+    Fiber,
+}
+
+impl BreedPurpose {
+    /// Every purpose, in display order.
+    pub const ALL: [BreedPurpose; 4] = [
+        BreedPurpose::Dairy,
+        BreedPurpose::Meat,
+        BreedPurpose::Dual,
+        BreedPurpose::Fiber,
+    ];
+
+    /// Converts a database string to `BreedPurpose`.
+    pub fn from_str(s: &str) -> Result<BreedPurpose, String> {
+        trace!("Parsing BreedPurpose from '{}'", s);
+        BreedPurpose::ALL
+            .into_iter()
+            .find(|p| BreedPurpose::to_str(p) == s)
+            .ok_or_else(|| {
+                debug!("Failed to parse BreedPurpose enum from '{}'", s);
+                s.to_string()
+            })
+    }
+
+    /// Converts a `BreedPurpose` to a database string.
+    pub fn to_str(purpose: &BreedPurpose) -> &'static str {
+        match purpose {
+            BreedPurpose::Dairy => "Dairy",
+            BreedPurpose::Meat => "Meat",
+            BreedPurpose::Dual => "Dual",
+            BreedPurpose::Fiber => "Fiber",
+        }
+    }
+
+    /// Human-readable name, e.g. "Dual purpose".
+    pub fn label(&self) -> &'static str {
+        match self {
+            BreedPurpose::Dairy => "Dairy",
+            BreedPurpose::Meat => "Meat",
+            BreedPurpose::Dual => "Dual purpose",
+            BreedPurpose::Fiber => "Fiber",
+        }
+    }
+}
+
+/// A breed offered when entering a goat.
+///
+/// `builtin` is set for the breeds of the `Breed` enum; the others were
+/// added to the farm's catalog and are stored on goats as `Breed::Other`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CatalogBreed {
+    pub name: String,
+    pub purpose: BreedPurpose,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+/// Every `Breed` variant with a name, in display order.
+pub const BUILTIN_BREEDS: [Breed; 10] = [
+    Breed::Beetal,
+    Breed::Jamunapari,
+    Breed::Barbari,
+    Breed::Sirohi,
+    Breed::Osmanabadi,
+    Breed::BlackBengal,
+    Breed::Kutchi,
+    Breed::Kaghani,
+    Breed::Chegu,
+    Breed::Jakhrana,
+];
+
+/// What a built-in breed is kept for, or `None` for `Breed::Other`.
+pub fn breed_purpose(breed: &Breed) -> Option<BreedPurpose> {
+    let purpose = match breed {
+        Breed::Jamunapari | Breed::Jakhrana => BreedPurpose::Dairy,
+        Breed::Osmanabadi | Breed::BlackBengal => BreedPurpose::Meat,
+        Breed::Beetal | Breed::Barbari | Breed::Sirohi | Breed::Kutchi => BreedPurpose::Dual,
+        Breed::Kaghani | Breed::Chegu => BreedPurpose::Fiber,
+        Breed::Other(_) => return None,
+    };
+    Some(purpose)
+}
+
+/// The built-in breeds as catalog entries.
+pub fn builtin_catalog() -> Vec<CatalogBreed> {
+    BUILTIN_BREEDS
+        .iter()
+        .filter_map(|breed| {
+            Some(CatalogBreed {
+                name: Breed::to_str(breed).to_string(),
+                purpose: breed_purpose(breed)?,
+                builtin: true,
+            })
+        })
+        .collect()
+}
+
+/// Checks the name of a breed to be added to the catalog, returning it
+/// trimmed. It must not be blank, "Other", or a built-in breed.
+pub fn validate_catalog_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Breed name must not be empty".to_string());
+    }
+    if name.eq_ignore_ascii_case("Other") {
+        return Err("\"Other\" is reserved for unlisted breeds".to_string());
+    }
+    if BUILTIN_BREEDS
+        .iter()
+        .any(|b| Breed::to_str(b).eq_ignore_ascii_case(name))
+    {
+        return Err(format!("{} is already a built-in breed", name));
+    }
+    Ok(name.to_string())
+}
+
+/// The entries of `catalog` kept for `purpose` (any purpose if `None`)
+/// whose name contains `query`, ignoring case, in catalog order.
+pub fn search_catalog<'a>(
+    catalog: &'a [CatalogBreed],
+    purpose: Option<BreedPurpose>,
+    query: &str,
+) -> Vec<&'a CatalogBreed> {
+    let query = query.trim().to_lowercase();
+    catalog
+        .iter()
+        .filter(|b| purpose.is_none_or(|p| b.purpose == p))
+        .filter(|b| b.name.to_lowercase().contains(&query))
+        .collect()
+}
+
+/// What a healthy adult of a breed typically looks like.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BreedProfile {
+    /// Usual adult body weight in kg, from small does to large bucks.
+    pub adult_weight_kg: (f64, f64),
+    /// Average daily milk yield of a doe in milk, in litres.
+    pub milk_litres_per_day: f64,
+    /// Vaccines recommended for the breed's usual region and husbandry.
+    pub vaccines: &'static [&'static str],
+}
+
+/// Vaccines every goat should have, whatever the breed.
+const CORE_VACCINES: &[&str] = &["PPR", "Enterotoxaemia", "FMD"];
+
+/// Core vaccines plus goat pox, for breeds of the dry north-west.
+const ARID_VACCINES: &[&str] = &["PPR", "Enterotoxaemia", "FMD", "Goat Pox"];
+
+/// Core vaccines plus haemorrhagic septicaemia, for breeds of humid regions.
+const HUMID_VACCINES: &[&str] = &["PPR", "Enterotoxaemia", "FMD", "HS"];
+
+/// Core vaccines plus contagious caprine pleuropneumonia, for hill breeds.
+const HILL_VACCINES: &[&str] = &["PPR", "Enterotoxaemia", "FMD", "CCPP"];
+
+/// The reference profile of `breed`, or `None` for breeds not on record.
+pub fn breed_profile(breed: &Breed) -> Option<BreedProfile> {
+    let (adult_weight_kg, milk_litres_per_day, vaccines) = match breed {
+        Breed::Beetal => ((35.0, 60.0), 2.0, CORE_VACCINES),
+        Breed::Jamunapari => ((40.0, 65.0), 2.0, CORE_VACCINES),
+        Breed::Barbari => ((25.0, 40.0), 1.0, ARID_VACCINES),
+        Breed::Sirohi => ((30.0, 50.0), 0.8, ARID_VACCINES),
+        Breed::Osmanabadi => ((30.0, 45.0), 0.6, HUMID_VACCINES),
+        Breed::BlackBengal => ((15.0, 25.0), 0.3, HUMID_VACCINES),
+        Breed::Kutchi => ((35.0, 50.0), 1.0, ARID_VACCINES),
+        Breed::Kaghani => ((30.0, 45.0), 0.7, HILL_VACCINES),
+        Breed::Chegu => ((20.0, 35.0), 0.4, HILL_VACCINES),
+        Breed::Jakhrana => ((40.0, 55.0), 2.0, CORE_VACCINES),
+        Breed::Other(_) => return None,
+    };
+    Some(BreedProfile {
+        adult_weight_kg,
+        milk_litres_per_day,
+        vaccines,
+    })
+}
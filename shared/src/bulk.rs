@@ -0,0 +1,56 @@
+//! Bulk changes to the herd.
+//!
+//! A `BulkPatch` applies one `GoatUpdate` to every goat a filter selects,
+//! e.g. moving all milkers onto a new feed plan by setting their diet. The
+//! backend can preview the goats a patch would change before applying it.
+
+use crate::GoatUpdate;
+use crate::exports::ExportFilter;
+use crate::lifecycle::LifecycleStage;
+use serde::{Deserialize, Serialize};
+
+/// One change to every goat matching a filter.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct BulkPatch {
+    /// Breed, sex, health status and traits to match, as in saved exports.
+    #[serde(default)]
+    pub filter: ExportFilter,
+    /// Only goats at this lifecycle stage, e.g. `Milker` for lactating does.
+    #[serde(default)]
+    pub stage: Option<LifecycleStage>,
+    pub update: GoatUpdate,
+}
+
+impl BulkPatch {
+    /// A patch setting the diet of the goats `filter` and `stage` select.
+    pub fn diet(filter: ExportFilter, stage: Option<LifecycleStage>, diet: &str) -> BulkPatch {
+        BulkPatch {
+            filter,
+            stage,
+            update: GoatUpdate {
+                diet: Some(diet.trim().to_string()),
+                ..GoatUpdate::default()
+            },
+        }
+    }
+
+    /// Checks the update: it must change something, and not the name,
+    /// which every goat keeps to itself.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.update.is_empty() {
+            return Err("A bulk change must set at least one field".to_string());
+        }
+        if self.update.name.is_some() {
+            return Err("A bulk change cannot rename goats".to_string());
+        }
+        if self
+            .update
+            .diet
+            .as_deref()
+            .is_some_and(|d| d.trim().is_empty())
+        {
+            return Err("Diet must not be empty".to_string());
+        }
+        Ok(())
+    }
+}
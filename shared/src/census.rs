@@ -0,0 +1,260 @@
+//! Census-style headcount reports.
+//!
+//! Indian farmers periodically report livestock counts to the government.
+//! A `ReportTemplate` describes one such summary: which dimensions the herd
+//! is counted by (breed, sex, age class) and how ages are banded. The
+//! built-in templates cover the common formats; farms can add their own.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::debug;
+
+/// Label for animals without a recorded date of birth.
+pub const UNKNOWN_AGE: &str = "Age unknown";
+
+/// Longest accepted template key.
+const MAX_KEY_LEN: usize = 40;
+
+/// A property animals are counted by.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CensusDimension {
+    Breed,
+    Sex,
+    Age,
+}
+
+impl CensusDimension {
+    /// Column heading in rendered reports.
+    pub fn label(&self) -> &'static str {
+        match self {
+            CensusDimension::Breed => "Breed",
+            CensusDimension::Sex => "Sex",
+            CensusDimension::Age => "Age",
+        }
+    }
+}
+
+/// An age class: at least `min_months` old and, if set, younger than
+/// `max_months`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AgeBand {
+    pub label: String,
+    pub min_months: u32,
+    pub max_months: Option<u32>,
+}
+
+impl AgeBand {
+    fn new(label: &str, min_months: u32, max_months: Option<u32>) -> Self {
+        AgeBand {
+            label: label.to_string(),
+            min_months,
+            max_months,
+        }
+    }
+}
+
+/// Layout of a census report.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReportTemplate {
+    /// Identifier used in report URLs, e.g. `livestock-census`.
+    pub key: String,
+    pub title: String,
+    /// Dimensions to count by, in column order.
+    pub group_by: Vec<CensusDimension>,
+    /// Age classes, youngest first; required when grouping by age.
+    #[serde(default)]
+    pub age_bands: Vec<AgeBand>,
+    /// Whether the template ships with Yagi and cannot be deleted.
+    #[serde(default)]
+    pub built_in: bool,
+}
+
+impl ReportTemplate {
+    /// The templates every farm has.
+    pub fn built_ins() -> Vec<ReportTemplate> {
+        vec![
+            ReportTemplate {
+                key: "livestock-census".to_string(),
+                title: "Livestock Census: Goats by Breed, Sex and Age".to_string(),
+                group_by: vec![
+                    CensusDimension::Breed,
+                    CensusDimension::Sex,
+                    CensusDimension::Age,
+                ],
+                age_bands: vec![
+                    AgeBand::new("Up to 1 year", 0, Some(12)),
+                    AgeBand::new("Over 1 year", 12, None),
+                ],
+                built_in: true,
+            },
+            ReportTemplate {
+                key: "breed-headcount".to_string(),
+                title: "Goat Headcount by Breed".to_string(),
+                group_by: vec![CensusDimension::Breed],
+                age_bands: Vec::new(),
+                built_in: true,
+            },
+            ReportTemplate {
+                key: "sex-age".to_string(),
+                title: "Goats by Sex and Age Class".to_string(),
+                group_by: vec![CensusDimension::Sex, CensusDimension::Age],
+                age_bands: vec![
+                    AgeBand::new("Kids (under 6 months)", 0, Some(6)),
+                    AgeBand::new("Young stock (6-12 months)", 6, Some(12)),
+                    AgeBand::new("Adults (1 year and over)", 12, None),
+                ],
+                built_in: true,
+            },
+        ]
+    }
+
+    /// Checks the key, title, dimensions and age bands. Bands must be
+    /// contiguous from birth, and only the last may be open-ended.
+    pub fn validate(&self) -> Result<(), String> {
+        let key_valid = !self.key.is_empty()
+            && self.key.len() <= MAX_KEY_LEN
+            && !self.key.starts_with('-')
+            && self
+                .key
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !key_valid {
+            return Err(format!(
+                "Template key must be 1-{} lowercase letters, digits or dashes, got '{}'",
+                MAX_KEY_LEN, self.key
+            ));
+        }
+        if self.title.trim().is_empty() {
+            return Err("Template title must not be empty".to_string());
+        }
+        if self.group_by.is_empty() {
+            return Err("Template must group by at least one dimension".to_string());
+        }
+        for (i, dimension) in self.group_by.iter().enumerate() {
+            if self.group_by[..i].contains(dimension) {
+                return Err(format!("{} is listed twice", dimension.label()));
+            }
+        }
+        if !self.group_by.contains(&CensusDimension::Age) {
+            return Ok(());
+        }
+        if self.age_bands.is_empty() {
+            return Err("Grouping by age needs at least one age band".to_string());
+        }
+        let mut expected_min = 0;
+        for (i, band) in self.age_bands.iter().enumerate() {
+            if band.label.trim().is_empty() {
+                return Err("Age band labels must not be empty".to_string());
+            }
+            if band.min_months != expected_min {
+                return Err(format!(
+                    "Age band {} must start at {} months",
+                    band.label, expected_min
+                ));
+            }
+            match band.max_months {
+                Some(max) if max > band.min_months => expected_min = max,
+                Some(_) => {
+                    return Err(format!("Age band {} must end after it starts", band.label));
+                }
+                None if i + 1 == self.age_bands.len() => {}
+                None => {
+                    return Err(format!(
+                        "Only the last age band may be open-ended, not {}",
+                        band.label
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Index and label of the band `age_months` falls in. Animals of
+    /// unknown age, or older than a closed last band, sort last.
+    fn age_class(&self, age_months: Option<u32>) -> (usize, String) {
+        age_months
+            .and_then(|age| {
+                self.age_bands.iter().position(|band| {
+                    age >= band.min_months && band.max_months.is_none_or(|max| age < max)
+                })
+            })
+            .map(|i| (i, self.age_bands[i].label.clone()))
+            .unwrap_or((self.age_bands.len(), UNKNOWN_AGE.to_string()))
+    }
+}
+
+/// What a census counts about one animal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CensusAnimal {
+    pub breed: String,
+    pub sex: String,
+    /// Completed months of age on the census date, if the birth date is known.
+    pub age_months: Option<u32>,
+}
+
+/// The headcount of one combination of dimension values.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CensusRow {
+    /// One value per `CensusReport::columns` entry.
+    pub groups: Vec<String>,
+    pub count: u32,
+}
+
+/// A rendered census.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CensusReport {
+    pub template: String,
+    pub title: String,
+    /// The census date (`YYYY-MM-DD`) ages are computed at.
+    pub as_of: String,
+    /// Headings of the grouping columns; the count column is implied.
+    pub columns: Vec<String>,
+    pub rows: Vec<CensusRow>,
+    pub total: u32,
+}
+
+/// Counts `animals` as laid out by `template`. Rows are ordered by breed
+/// and sex alphabetically and by age class from youngest.
+pub fn build_census(
+    template: &ReportTemplate,
+    animals: &[CensusAnimal],
+    as_of: &str,
+) -> CensusReport {
+    let mut counts: BTreeMap<Vec<(usize, String)>, u32> = BTreeMap::new();
+    for animal in animals {
+        let key = template
+            .group_by
+            .iter()
+            .map(|dimension| match dimension {
+                CensusDimension::Breed => (0, animal.breed.clone()),
+                CensusDimension::Sex => (0, animal.sex.clone()),
+                CensusDimension::Age => template.age_class(animal.age_months),
+            })
+            .collect();
+        *counts.entry(key).or_default() += 1;
+    }
+    debug!(
+        template = %template.key,
+        animals = animals.len(),
+        rows = counts.len(),
+        "Built census"
+    );
+    CensusReport {
+        template: template.key.clone(),
+        title: template.title.clone(),
+        as_of: as_of.to_string(),
+        columns: template
+            .group_by
+            .iter()
+            .map(|d| d.label().to_string())
+            .collect(),
+        rows: counts
+            .into_iter()
+            .map(|(key, count)| CensusRow {
+                groups: key.into_iter().map(|(_, value)| value).collect(),
+                count,
+            })
+            .collect(),
+        total: animals.len() as u32,
+    }
+}
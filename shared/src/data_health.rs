@@ -0,0 +1,117 @@
+//! Data quality checks over the herd records.
+//!
+//! The backend scans for values that are missing or look wrong, such as a
+//! goat without a date of birth or a price far from what similar goats
+//! fetch, and the "Data health" panel lists them with links to fix each one.
+
+use crate::search::SearchKind;
+use serde::{Deserialize, Serialize};
+
+/// A price is flagged when it is off the breed's median price by more than
+/// this share of the median, e.g. 0.5 flags prices below half or above one
+/// and a half times the median.
+pub const PRICE_TOLERANCE: f64 = 0.5;
+
+/// Fewest priced goats of a breed for its median to count as the market
+/// price; rarer breeds are not price-checked.
+pub const MIN_PRICE_PEERS: usize = 3;
+
+/// Element ID of the Update Goat form.
+pub const UPDATE_FORM_ANCHOR: &str = "update-goat";
+
+/// The kind of problem found.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub enum IssueKind {
+    MissingBirthDate,
+    ZeroWeight,
+    PriceOffMarket,
+    DuplicateTag,
+    UndatedVaccination,
+}
+
+impl IssueKind {
+    /// Every kind, in the order the report lists them.
+    pub const ALL: [IssueKind; 5] = [
+        IssueKind::MissingBirthDate,
+        IssueKind::ZeroWeight,
+        IssueKind::PriceOffMarket,
+        IssueKind::DuplicateTag,
+        IssueKind::UndatedVaccination,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            IssueKind::MissingBirthDate => "Missing date of birth",
+            IssueKind::ZeroWeight => "Zero weight",
+            IssueKind::PriceOffMarket => "Price far from market",
+            IssueKind::DuplicateTag => "Duplicate tag ID",
+            IssueKind::UndatedVaccination => "Vaccination without date",
+        }
+    }
+
+    /// Whether the Update Goat form edits the value at fault; other problems
+    /// are fixed from the goat's row.
+    pub fn fixed_in_update_form(&self) -> bool {
+        matches!(self, IssueKind::ZeroWeight | IssueKind::PriceOffMarket)
+    }
+}
+
+/// One problem with one goat's record.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DataIssue {
+    pub kind: IssueKind,
+    pub goat_id: i64,
+    pub goat_name: String,
+    /// What is wrong, e.g. "Price 900.00 is far from the Beetal median of
+    /// 150.00".
+    pub message: String,
+}
+
+impl DataIssue {
+    /// Element ID the quick-fix link jumps to: the Update Goat form
+    /// (`update-goat`) or the goat's row (e.g. `goat-12`).
+    pub fn fix_anchor(&self) -> String {
+        if self.kind.fixed_in_update_form() {
+            UPDATE_FORM_ANCHOR.to_string()
+        } else {
+            format!("{}-{}", SearchKind::Goat.anchor_prefix(), self.goat_id)
+        }
+    }
+}
+
+/// Result of `GET /data-health`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct DataHealthReport {
+    /// Number of goats scanned.
+    pub goats_checked: usize,
+    /// Problems found, grouped by kind in `IssueKind::ALL` order, then by
+    /// goat name.
+    pub issues: Vec<DataIssue>,
+}
+
+impl DataHealthReport {
+    /// Number of issues of `kind`.
+    pub fn count(&self, kind: IssueKind) -> usize {
+        self.issues.iter().filter(|i| i.kind == kind).count()
+    }
+}
+
+/// The median of `values`, or `None` if empty.
+pub fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    })
+}
+
+/// Whether `price` is off `market` by more than `PRICE_TOLERANCE`.
+pub fn price_off_market(price: f64, market: f64) -> bool {
+    market > 0.0 && ((price - market) / market).abs() > PRICE_TOLERANCE
+}
@@ -0,0 +1,19 @@
+//! Client-side diagnostics submitted by the frontend, such as crash reports.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A crash or section failure reported by the frontend.
+///
+/// `section` names the UI section whose error boundary caught the failure;
+/// it is `None` for application-wide panics. `reported_at` is filled in by the
+/// backend when reading reports back.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClientErrorReport {
+    pub id: Option<i64>,
+    pub section: Option<String>,
+    pub message: String,
+    pub url: Option<String>,
+    pub user_agent: Option<String>,
+    pub reported_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,131 @@
+//! The disease catalog and compliance reports for notifiable diseases.
+//!
+//! Diseases are catalogued as goats are recorded with them. Some, such as
+//! brucellosis or tuberculosis, are notifiable: authorities must be told of
+//! every case, and of testing done for them. Those are flagged in the
+//! catalog, and the compliance report lists every goat with one on record,
+//! reported in a health incident, or tested for one in a lab result whose
+//! test names the disease.
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// A catalogued disease with how many goats have it on record.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CatalogDisease {
+    pub id: i64,
+    pub name: String,
+    pub notifiable: bool,
+    #[serde(default)]
+    pub goat_count: u32,
+}
+
+/// A disease to add to the catalog.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewDisease {
+    pub name: String,
+    #[serde(default)]
+    pub notifiable: bool,
+}
+
+/// Sets whether a catalogued disease is notifiable.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct NotifiableFlag {
+    pub notifiable: bool,
+}
+
+/// Where a goat stands with a notifiable disease. Ordered from most to
+/// least serious.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "PascalCase")]
+pub enum CaseStatus {
+    /// The disease is on the goat's record.
+    Confirmed,
+    /// Reported in a health incident, or a test for it was out of range,
+    /// but not on the goat's record.
+    Suspected,
+    /// Only tested for it, with every value within range.
+    Tested,
+}
+
+impl CaseStatus {
+    /// Converts a database or CSV string to `CaseStatus`.
+    pub fn from_str(s: &str) -> Result<CaseStatus, String> {
+        trace!("Parsing CaseStatus from '{}'", s);
+        match s {
+            "Confirmed" => Ok(CaseStatus::Confirmed),
+            "Suspected" => Ok(CaseStatus::Suspected),
+            "Tested" => Ok(CaseStatus::Tested),
+            other => {
+                debug!("Failed to parse CaseStatus enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `CaseStatus` to a database or CSV string.
+    pub fn to_str(status: &CaseStatus) -> &str {
+        match status {
+            CaseStatus::Confirmed => "Confirmed",
+            CaseStatus::Suspected => "Suspected",
+            CaseStatus::Tested => "Tested",
+        }
+    }
+}
+
+/// Whether `text`, e.g. an incident's condition or a lab test's type,
+/// names `disease`. Matched ignoring case, so "Brucellosis RBPT" names
+/// "brucellosis".
+pub fn names_disease(text: &str, disease: &str) -> bool {
+    let disease = disease.trim().to_lowercase();
+    !disease.is_empty() && text.to_lowercase().contains(&disease)
+}
+
+/// A lab test of a goat for a notifiable disease.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DiseaseTest {
+    pub sampled_on: String,
+    pub test_type: String,
+    /// Whether any value was outside its reference range.
+    pub out_of_range: bool,
+}
+
+/// One goat's case of one notifiable disease.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ComplianceCase {
+    pub disease: String,
+    pub goat_name: String,
+    pub tag_id: Option<String>,
+    pub status: CaseStatus,
+    /// Earliest health incident reporting the disease.
+    pub first_reported: Option<String>,
+    /// Tests for the disease, oldest first.
+    pub tests: Vec<DiseaseTest>,
+}
+
+impl ComplianceCase {
+    /// The tests as one line, e.g. "2026-04-10 Brucellosis RBPT (out of
+    /// range)".
+    pub fn tests_label(&self) -> String {
+        self.tests
+            .iter()
+            .map(|t| {
+                if t.out_of_range {
+                    format!("{} {} (out of range)", t.sampled_on, t.test_type)
+                } else {
+                    format!("{} {}", t.sampled_on, t.test_type)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Every goat's cases of notifiable diseases, by disease and then status.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ComplianceReport {
+    pub generated_on: String,
+    /// The notifiable diseases covered, including those without cases.
+    pub diseases: Vec<String>,
+    pub cases: Vec<ComplianceCase>,
+}
@@ -0,0 +1,165 @@
+//! Domain events: the changes to the herd the backend records in its event
+//! log when it runs event-sourced (see `backend::events`). Goat
+//! registrations and updates are logged either way, for `FieldChange`s.
+//!
+//! Each event states a change as it happened, with the IDs the backend
+//! assigned, so the log can be replayed to rebuild the goats, their
+//! weighings and sales, and clients can follow it for an audit trail, delta
+//! sync or webhooks. Every change to a goat's record is an event.
+
+use crate::finance::Transaction;
+use crate::growth::WeightRecord;
+use crate::lifecycle::LifecycleStage;
+use crate::{Goat, GoatUpdate};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A change to the herd.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum DomainEvent {
+    /// A goat was added, with its vaccinations, diseases and ear tag.
+    GoatRegistered { goat: Goat },
+    /// Fields of a goat were changed; `update` holds only the changed ones.
+    GoatUpdated { goat_id: i64, update: GoatUpdate },
+    /// A goat was deleted, into the trash.
+    GoatRemoved { goat_id: i64, name: String },
+    /// A deleted goat was restored from the trash.
+    GoatRestored { goat_id: i64 },
+    /// A deleted goat was purged from the trash for good.
+    GoatPurged { goat_id: i64 },
+    /// A goat was given an ear tag.
+    TagAssigned { goat_id: i64, tag_id: String },
+    /// A goat was moved into the pen or other space with ID `space_id`.
+    GoatPlaced { goat_id: i64, space_id: i64 },
+    /// A goat moved to another stage of its life on the farm on
+    /// `changed_on` (`YYYY-MM-DD`).
+    LifecycleChanged {
+        goat_id: i64,
+        stage: LifecycleStage,
+        changed_on: String,
+        note: Option<String>,
+    },
+    /// A goat's parents and birth date were set, replacing the ones before.
+    /// A parent is a goat of the herd or an external animal, by ID.
+    PedigreeRecorded {
+        goat_id: i64,
+        sire_id: Option<i64>,
+        dam_id: Option<i64>,
+        sire_external_id: Option<i64>,
+        dam_external_id: Option<i64>,
+        date_of_birth: Option<String>,
+    },
+    /// A goat was castrated or spayed.
+    GoatNeutered {
+        goat_id: i64,
+        neutered_on: String,
+        reason: Option<String>,
+    },
+    /// A neutering recorded by mistake was cleared.
+    NeuteringCleared { goat_id: i64 },
+    /// A goat was weighed, or its weight estimated from a photo.
+    WeightRecorded { goat_id: i64, record: WeightRecord },
+    /// A goat was sold; `transaction` is the income entry as stored, with
+    /// its ID, currency and sale details.
+    GoatSold {
+        goat_id: i64,
+        transaction: Transaction,
+    },
+}
+
+impl DomainEvent {
+    /// Name of the event, as in the `type` field of its JSON.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DomainEvent::GoatRegistered { .. } => "GoatRegistered",
+            DomainEvent::GoatUpdated { .. } => "GoatUpdated",
+            DomainEvent::GoatRemoved { .. } => "GoatRemoved",
+            DomainEvent::GoatRestored { .. } => "GoatRestored",
+            DomainEvent::GoatPurged { .. } => "GoatPurged",
+            DomainEvent::TagAssigned { .. } => "TagAssigned",
+            DomainEvent::GoatPlaced { .. } => "GoatPlaced",
+            DomainEvent::LifecycleChanged { .. } => "LifecycleChanged",
+            DomainEvent::PedigreeRecorded { .. } => "PedigreeRecorded",
+            DomainEvent::GoatNeutered { .. } => "GoatNeutered",
+            DomainEvent::NeuteringCleared { .. } => "NeuteringCleared",
+            DomainEvent::WeightRecorded { .. } => "WeightRecorded",
+            DomainEvent::GoatSold { .. } => "GoatSold",
+        }
+    }
+
+    /// ID of the goat the event is about.
+    pub fn goat_id(&self) -> i64 {
+        match self {
+            DomainEvent::GoatRegistered { goat } => goat.id,
+            DomainEvent::GoatUpdated { goat_id, .. }
+            | DomainEvent::GoatRemoved { goat_id, .. }
+            | DomainEvent::GoatRestored { goat_id }
+            | DomainEvent::GoatPurged { goat_id }
+            | DomainEvent::TagAssigned { goat_id, .. }
+            | DomainEvent::GoatPlaced { goat_id, .. }
+            | DomainEvent::LifecycleChanged { goat_id, .. }
+            | DomainEvent::PedigreeRecorded { goat_id, .. }
+            | DomainEvent::GoatNeutered { goat_id, .. }
+            | DomainEvent::NeuteringCleared { goat_id }
+            | DomainEvent::WeightRecorded { goat_id, .. }
+            | DomainEvent::GoatSold { goat_id, .. } => *goat_id,
+        }
+    }
+}
+
+/// An event as kept in the log.
+///
+/// `id` increases with every event, so a client that has seen events up to
+/// some ID asks for the ones after it. `recorded_at` is when the event was
+/// appended. `actor` says who made the change, such as `Ravi on Pixel 7`
+/// for a session, as it was when the event was recorded; `None` if the
+/// change came without a session.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StoredEvent {
+    pub id: i64,
+    pub recorded_at: DateTime<Utc>,
+    #[serde(default)]
+    pub actor: Option<String>,
+    pub event: DomainEvent,
+}
+
+/// Goat fields `GET /goats/{id}/history` can report on, named as in the
+/// JSON of `GoatParams`.
+pub const HISTORY_FIELDS: [&str; 16] = [
+    "name",
+    "breed",
+    "gender",
+    "offspring",
+    "cost",
+    "weight",
+    "current_price",
+    "diet",
+    "last_bred",
+    "health_status",
+    "vaccinations",
+    "diseases",
+    "horns",
+    "coat_color",
+    "marks",
+    "quick_note",
+];
+
+/// One change to a field of a goat, derived from the event log.
+///
+/// Values are the field's JSON, as in `GoatParams`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    /// ID of the event that made the change.
+    pub event_id: i64,
+    /// `StoredEvent::recorded_at` of that event.
+    pub changed_at: DateTime<Utc>,
+    /// `StoredEvent::actor` of that event: who made the change.
+    #[serde(default)]
+    pub actor: Option<String>,
+    pub field: String,
+    /// Value before the change; `None` for the value the goat was
+    /// registered with, or if the log starts after that.
+    pub from: Option<serde_json::Value>,
+    pub to: serde_json::Value,
+}
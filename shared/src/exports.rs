@@ -0,0 +1,235 @@
+//! Saved export templates.
+//!
+//! An `ExportTemplate` names a recurring goat-list export: which columns
+//! to write, which goats to include and the file format. Templates are
+//! stored by the backend and run by key, from the dashboard with one click
+//! or by scripts through the API.
+
+use crate::physical::{CoatColor, HornStatus, TraitFilter};
+use crate::{Breed, Gender, Goat};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Longest accepted template key.
+const MAX_KEY_LEN: usize = 40;
+
+/// A goat field an export can include.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportColumn {
+    Name,
+    Breed,
+    Gender,
+    Offspring,
+    Cost,
+    Weight,
+    CurrentPrice,
+    Diet,
+    LastBred,
+    HealthStatus,
+    Horns,
+    CoatColor,
+    Marks,
+}
+
+impl ExportColumn {
+    /// Every column, in goat-list order.
+    pub const ALL: [ExportColumn; 13] = [
+        ExportColumn::Name,
+        ExportColumn::Breed,
+        ExportColumn::Gender,
+        ExportColumn::Offspring,
+        ExportColumn::Cost,
+        ExportColumn::Weight,
+        ExportColumn::CurrentPrice,
+        ExportColumn::Diet,
+        ExportColumn::LastBred,
+        ExportColumn::HealthStatus,
+        ExportColumn::Horns,
+        ExportColumn::CoatColor,
+        ExportColumn::Marks,
+    ];
+
+    /// Column heading in exported files.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportColumn::Name => "Name",
+            ExportColumn::Breed => "Breed",
+            ExportColumn::Gender => "Gender",
+            ExportColumn::Offspring => "Offspring",
+            ExportColumn::Cost => "Cost",
+            ExportColumn::Weight => "Weight",
+            ExportColumn::CurrentPrice => "Current Price",
+            ExportColumn::Diet => "Diet",
+            ExportColumn::LastBred => "Last Bred",
+            ExportColumn::HealthStatus => "Health Status",
+            ExportColumn::Horns => "Horns",
+            ExportColumn::CoatColor => "Coat Color",
+            ExportColumn::Marks => "Marks",
+        }
+    }
+
+    /// The column's value for `goat`; empty if unrecorded.
+    pub fn value(&self, goat: &Goat) -> String {
+        match self {
+            ExportColumn::Name => goat.name.clone(),
+            ExportColumn::Breed => Breed::to_str(&goat.breed).to_string(),
+            ExportColumn::Gender => Gender::to_str(&goat.gender).to_string(),
+            ExportColumn::Offspring => goat.offspring.to_string(),
+            ExportColumn::Cost => format!("{:.2}", goat.cost),
+            ExportColumn::Weight => goat.weight.to_string(),
+            ExportColumn::CurrentPrice => format!("{:.2}", goat.current_price),
+            ExportColumn::Diet => goat.diet.clone(),
+            ExportColumn::LastBred => goat.last_bred.clone().unwrap_or_default(),
+            ExportColumn::HealthStatus => goat.health_status.clone(),
+            ExportColumn::Horns => goat
+                .horns
+                .as_ref()
+                .map(|h| HornStatus::to_str(h).to_string())
+                .unwrap_or_default(),
+            ExportColumn::CoatColor => goat
+                .coat_color
+                .as_ref()
+                .map(|c| CoatColor::to_str(c).to_string())
+                .unwrap_or_default(),
+            ExportColumn::Marks => goat.marks.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// File format of an export.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    /// Every format, default first.
+    pub const ALL: [ExportFormat; 2] = [ExportFormat::Csv, ExportFormat::Json];
+
+    /// File extension, which is also the format's name in the API.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Which goats an export includes. Unset criteria match every goat.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ExportFilter {
+    #[serde(default)]
+    pub breed: Option<Breed>,
+    #[serde(default)]
+    pub gender: Option<Gender>,
+    /// Health status, compared ignoring case.
+    #[serde(default)]
+    pub health_status: Option<String>,
+    #[serde(default)]
+    pub traits: TraitFilter,
+}
+
+impl ExportFilter {
+    /// Whether `goat` meets every criterion set.
+    pub fn matches(&self, goat: &Goat) -> bool {
+        self.breed.as_ref().is_none_or(|b| goat.breed == *b)
+            && self.gender.as_ref().is_none_or(|g| goat.gender == *g)
+            && self
+                .health_status
+                .as_deref()
+                .is_none_or(|s| goat.health_status.trim().eq_ignore_ascii_case(s.trim()))
+            && self.traits.matches(goat)
+    }
+}
+
+/// A named, saved goat-list export.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExportTemplate {
+    /// Identifier used in export URLs, e.g. `monthly-weights`.
+    pub key: String,
+    pub name: String,
+    /// Columns to write, in order.
+    pub columns: Vec<ExportColumn>,
+    #[serde(default)]
+    pub filter: ExportFilter,
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+impl ExportTemplate {
+    /// Checks the key, name and columns.
+    pub fn validate(&self) -> Result<(), String> {
+        let key_valid = !self.key.is_empty()
+            && self.key.len() <= MAX_KEY_LEN
+            && !self.key.starts_with('-')
+            && self
+                .key
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if !key_valid {
+            return Err(format!(
+                "Template key must be 1-{} lowercase letters, digits or dashes, got '{}'",
+                MAX_KEY_LEN, self.key
+            ));
+        }
+        if self.name.trim().is_empty() {
+            return Err("Template name must not be empty".to_string());
+        }
+        if self.columns.is_empty() {
+            return Err("Template must export at least one column".to_string());
+        }
+        for (i, column) in self.columns.iter().enumerate() {
+            if self.columns[..i].contains(column) {
+                return Err(format!("{} is listed twice", column.label()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Name of the file the export is saved as, e.g.
+    /// `monthly-weights-2026-03-31.csv`.
+    pub fn file_name(&self, date: &str) -> String {
+        format!("{}-{}.{}", self.key, date, self.format.extension())
+    }
+}
+
+/// The rows an export writes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExportTable {
+    pub template: String,
+    pub name: String,
+    /// Column headings.
+    pub columns: Vec<String>,
+    /// One row per goat, one value per column.
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Lays out the `goats` matching `template`'s filter in its columns, in
+/// the order given.
+pub fn build_export(template: &ExportTemplate, goats: &[Goat]) -> ExportTable {
+    let rows: Vec<Vec<String>> = goats
+        .iter()
+        .filter(|goat| template.filter.matches(goat))
+        .map(|goat| template.columns.iter().map(|c| c.value(goat)).collect())
+        .collect();
+    debug!(
+        template = %template.key,
+        goats = goats.len(),
+        rows = rows.len(),
+        "Built export"
+    );
+    ExportTable {
+        template: template.key.clone(),
+        name: template.name.clone(),
+        columns: template
+            .columns
+            .iter()
+            .map(|c| c.label().to_string())
+            .collect(),
+        rows,
+    }
+}
@@ -0,0 +1,347 @@
+//! Finance records: income and expense transactions and per-goat profitability.
+//!
+//! Expenses may be attributed to a goat or a pen. Besides manual entries,
+//! transactions are created automatically when inventory is consumed, so a
+//! goat's input costs are tracked without double entry.
+//!
+//! Sales can carry invoice details (buyer, GSTIN, tax rate) and are listed in
+//! a GST sales register for the farm's accountant.
+//!
+//! Entries may be recorded in a foreign currency together with the exchange
+//! rate on the day; reports convert them to the farm's base currency.
+//!
+//! Expense categories can be given a monthly budget; the variance report
+//! compares each month's spending against it.
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum TransactionKind {
+    Income,
+    Expense,
+}
+
+impl TransactionKind {
+    /// Converts a database string to `TransactionKind`.
+    pub fn from_str(s: &str) -> Result<TransactionKind, String> {
+        trace!("Parsing TransactionKind from '{}'", s);
+        match s {
+            "Income" => Ok(TransactionKind::Income),
+            "Expense" => Ok(TransactionKind::Expense),
+            other => {
+                debug!("Failed to parse TransactionKind enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `TransactionKind` to a database string.
+    pub fn to_str(kind: &TransactionKind) -> &str {
+        match kind {
+            TransactionKind::Income => "Income",
+            TransactionKind::Expense => "Expense",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "PascalCase")]
+pub enum FinanceCategory {
+    Feed,
+    Medicine,
+    Veterinary,
+    Equipment,
+    Labor,
+    Purchase,
+    Sale,
+    Milk,
+    Other,
+}
+
+impl FinanceCategory {
+    /// Every category, in display order.
+    pub const ALL: [FinanceCategory; 9] = [
+        FinanceCategory::Feed,
+        FinanceCategory::Medicine,
+        FinanceCategory::Veterinary,
+        FinanceCategory::Equipment,
+        FinanceCategory::Labor,
+        FinanceCategory::Purchase,
+        FinanceCategory::Sale,
+        FinanceCategory::Milk,
+        FinanceCategory::Other,
+    ];
+
+    /// Converts a database string to `FinanceCategory`.
+    pub fn from_str(s: &str) -> Result<FinanceCategory, String> {
+        trace!("Parsing FinanceCategory from '{}'", s);
+        FinanceCategory::ALL
+            .iter()
+            .find(|c| FinanceCategory::to_str(c) == s)
+            .cloned()
+            .ok_or_else(|| {
+                debug!("Failed to parse FinanceCategory enum from '{}'", s);
+                s.to_string()
+            })
+    }
+
+    /// Converts a `FinanceCategory` to a database string.
+    pub fn to_str(category: &FinanceCategory) -> &str {
+        match category {
+            FinanceCategory::Feed => "Feed",
+            FinanceCategory::Medicine => "Medicine",
+            FinanceCategory::Veterinary => "Veterinary",
+            FinanceCategory::Equipment => "Equipment",
+            FinanceCategory::Labor => "Labor",
+            FinanceCategory::Purchase => "Purchase",
+            FinanceCategory::Sale => "Sale",
+            FinanceCategory::Milk => "Milk",
+            FinanceCategory::Other => "Other",
+        }
+    }
+}
+
+/// A single income or expense entry.
+///
+/// `consumption_id` is set when the entry was generated from an inventory
+/// consumption record. `date` uses `YYYY-MM-DD`. For income with `sale`
+/// details, `amount` is the taxable value before GST.
+///
+/// `amount` is in `currency`, or in the farm's base currency when that is
+/// `None`; `exchange_rate` is the base currency value of one unit of
+/// `currency`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Transaction {
+    pub id: Option<i64>,
+    pub kind: TransactionKind,
+    pub category: FinanceCategory,
+    pub amount: f64,
+    pub description: String,
+    pub goat_name: Option<String>,
+    pub space_id: Option<i64>,
+    pub consumption_id: Option<i64>,
+    pub date: String,
+    #[serde(default)]
+    pub sale: Option<SaleDetails>,
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub exchange_rate: Option<f64>,
+}
+
+impl Transaction {
+    /// The amount converted to the farm's base currency.
+    pub fn base_amount(&self) -> f64 {
+        self.amount * self.exchange_rate.unwrap_or(1.0)
+    }
+}
+
+/// Checks that `code` looks like an ISO 4217 currency code, e.g. `USD`.
+pub fn validate_currency_code(code: &str) -> Result<(), String> {
+    if code.len() == 3 && code.bytes().all(|c| c.is_ascii_uppercase()) {
+        Ok(())
+    } else {
+        debug!("Rejected currency code '{}'", code);
+        Err(format!("'{}' is not a three-letter currency code", code))
+    }
+}
+
+/// A configurable GST rate, e.g. "GST 5%" for processed milk products.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TaxRate {
+    pub id: Option<i64>,
+    pub name: String,
+    pub rate_percent: f64,
+}
+
+/// Invoice details of a sale, as needed for the GST sales register.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SaleDetails {
+    pub invoice_number: Option<String>,
+    pub buyer_name: Option<String>,
+    /// The buyer's GSTIN, for sales to registered businesses.
+    pub buyer_gstin: Option<String>,
+    pub tax_rate_id: Option<i64>,
+    /// The rate applied, copied from `tax_rate_id` when the sale is recorded
+    /// so later rate changes do not alter past invoices. Filled in on reads.
+    #[serde(default)]
+    pub tax_percent: f64,
+}
+
+/// Characters of the GSTIN check digit alphabet, in value order.
+const GSTIN_CHARSET: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Checks that `gstin` is a well-formed 15-character GSTIN: a two-digit
+/// state code, a PAN, an entity number, `Z` and a valid check character.
+pub fn validate_gstin(gstin: &str) -> Result<(), String> {
+    let bytes = gstin.as_bytes();
+    let well_formed = bytes.len() == 15
+        && bytes[..2].iter().all(u8::is_ascii_digit)
+        && bytes[2..7].iter().all(u8::is_ascii_uppercase)
+        && bytes[7..11].iter().all(u8::is_ascii_digit)
+        && bytes[11].is_ascii_uppercase()
+        && GSTIN_CHARSET.contains(&bytes[12])
+        && bytes[13] == b'Z'
+        && GSTIN_CHARSET.contains(&bytes[14]);
+    if !well_formed {
+        return Err(format!("'{}' is not a valid GSTIN", gstin));
+    }
+    let sum: usize = bytes[..14]
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let value = GSTIN_CHARSET.iter().position(|x| x == c).unwrap_or(0);
+            let product = value * if i % 2 == 0 { 1 } else { 2 };
+            product / 36 + product % 36
+        })
+        .sum();
+    let check = GSTIN_CHARSET[(36 - sum % 36) % 36];
+    if bytes[14] != check {
+        debug!("GSTIN {} fails its check character", gstin);
+        return Err(format!("GSTIN '{}' has an invalid check character", gstin));
+    }
+    Ok(())
+}
+
+/// The two-digit state code a GSTIN is registered in.
+pub fn gstin_state_code(gstin: &str) -> Option<&str> {
+    gstin.get(..2)
+}
+
+/// Rounds an amount to whole paise.
+pub fn round_paise(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// One sale in the GST sales register. Tax amounts are in paise precision;
+/// intra-state sales split the tax into equal CGST and SGST, inter-state
+/// sales carry IGST.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SalesRegisterRow {
+    pub transaction_id: i64,
+    pub date: String,
+    pub invoice_number: Option<String>,
+    pub buyer_name: Option<String>,
+    pub buyer_gstin: Option<String>,
+    /// State code of the place of supply, when known from the buyer's GSTIN.
+    pub place_of_supply: Option<String>,
+    pub description: String,
+    pub taxable_value: f64,
+    pub tax_percent: f64,
+    pub cgst: f64,
+    pub sgst: f64,
+    pub igst: f64,
+    pub invoice_total: f64,
+}
+
+impl SalesRegisterRow {
+    /// Computes the tax columns of a sale. A sale is inter-state when both
+    /// the farm's and the buyer's state codes are known and differ.
+    pub fn with_tax(mut self, farm_state: Option<&str>) -> Self {
+        let tax = round_paise(self.taxable_value * self.tax_percent / 100.0);
+        let inter_state = matches!(
+            (farm_state, self.place_of_supply.as_deref()),
+            (Some(farm), Some(buyer)) if farm != buyer
+        );
+        if inter_state {
+            self.igst = tax;
+        } else {
+            self.cgst = round_paise(tax / 2.0);
+            self.sgst = round_paise(tax - self.cgst);
+        }
+        self.invoice_total = round_paise(self.taxable_value + tax);
+        trace!(
+            transaction_id = self.transaction_id,
+            tax, inter_state, "Computed sale tax"
+        );
+        self
+    }
+}
+
+/// Sales over `from`..=`to` with column totals, oldest first. Amounts are
+/// in `currency`, the farm's base currency.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SalesRegister {
+    pub from: String,
+    pub to: String,
+    pub currency: String,
+    /// The farm's GSTIN at the time the register was produced.
+    pub gstin: Option<String>,
+    pub rows: Vec<SalesRegisterRow>,
+    pub taxable_value: f64,
+    pub cgst: f64,
+    pub sgst: f64,
+    pub igst: f64,
+    pub invoice_total: f64,
+}
+
+/// A standing monthly spending limit for one expense category, in the
+/// farm's base currency.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Budget {
+    pub category: FinanceCategory,
+    pub monthly_amount: f64,
+}
+
+/// A category's spending over one month against its budget.
+///
+/// `variance` is `budget - actual`, so it is negative when the category is
+/// over budget; it is `None` for spending with no budget set.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BudgetVariance {
+    pub category: FinanceCategory,
+    pub budget: Option<f64>,
+    pub actual: f64,
+    pub variance: Option<f64>,
+    pub over_budget: bool,
+}
+
+impl BudgetVariance {
+    /// Compares `actual` spending with `budget`.
+    pub fn new(category: FinanceCategory, budget: Option<f64>, actual: f64) -> Self {
+        let actual = round_paise(actual);
+        let variance = budget.map(|b| round_paise(b - actual));
+        BudgetVariance {
+            over_budget: variance.is_some_and(|v| v < 0.0),
+            category,
+            budget,
+            actual,
+            variance,
+        }
+    }
+}
+
+/// Budget variance of every budgeted or charged category for `month`
+/// (`YYYY-MM`), in category order. Amounts are in `currency`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct BudgetReport {
+    pub month: String,
+    pub currency: String,
+    pub lines: Vec<BudgetVariance>,
+    pub total_budget: f64,
+    pub total_actual: f64,
+}
+
+impl BudgetReport {
+    /// The categories that have exceeded their budget.
+    pub fn over_budget(&self) -> impl Iterator<Item = &BudgetVariance> {
+        self.lines.iter().filter(|l| l.over_budget)
+    }
+}
+
+/// Profitability summary for one goat.
+///
+/// `profit` is `current_price + income - cost - expenses`, i.e. the value
+/// realised or realisable from the goat minus everything spent on it, in the
+/// farm's base currency.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GoatProfitability {
+    pub goat_name: String,
+    pub cost: f64,
+    pub current_price: f64,
+    pub expenses: f64,
+    pub income: f64,
+    pub profit: f64,
+}
@@ -0,0 +1,105 @@
+//! GPS collar positions and grazing geofences.
+//!
+//! Collars push positions through an API key, matched to goats by tag like
+//! weigh-scale readings. A geofence is a polygon around a paddock or grazing
+//! area; a goat whose last known position lies outside every geofence is
+//! reported as an alert.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+
+/// A WGS 84 coordinate in decimal degrees.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl GeoPoint {
+    /// Checks the coordinate is a real position on the globe.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.lat.is_finite() && (-90.0..=90.0).contains(&self.lat)) {
+            return Err(format!("Latitude {} must be between -90 and 90", self.lat));
+        }
+        if !(self.lon.is_finite() && (-180.0..=180.0).contains(&self.lon)) {
+            return Err(format!(
+                "Longitude {} must be between -180 and 180",
+                self.lon
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A named grazing area bounded by a polygon. The boundary is closed
+/// implicitly; the first point is not repeated at the end.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Geofence {
+    pub id: Option<i64>,
+    pub name: String,
+    pub boundary: Vec<GeoPoint>,
+}
+
+impl Geofence {
+    /// Checks the fence has a name and at least three valid corners.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Geofence name must not be empty".to_string());
+        }
+        if self.boundary.len() < 3 {
+            return Err(format!(
+                "Geofence {} needs at least 3 points, got {}",
+                self.name,
+                self.boundary.len()
+            ));
+        }
+        self.boundary.iter().try_for_each(GeoPoint::validate)
+    }
+
+    /// Whether `point` lies inside the boundary (ray casting). Fences are
+    /// small enough that latitude and longitude can be treated as planar.
+    pub fn contains(&self, point: &GeoPoint) -> bool {
+        let mut inside = false;
+        let mut prev = match self.boundary.last() {
+            Some(last) => last,
+            None => return false,
+        };
+        for corner in &self.boundary {
+            if (corner.lat > point.lat) != (prev.lat > point.lat) {
+                let crossing = corner.lon
+                    + (point.lat - corner.lat) / (prev.lat - corner.lat) * (prev.lon - corner.lon);
+                if point.lon < crossing {
+                    inside = !inside;
+                }
+            }
+            prev = corner;
+        }
+        trace!(fence = %self.name, ?point, inside, "Geofence check");
+        inside
+    }
+}
+
+/// A position pushed by a collar. `recorded_at` is RFC 3339 or a
+/// `YYYY-MM-DDTHH:MM:SS` time in the farm's time zone, and defaults to the
+/// time it is received.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PositionInput {
+    pub tag_id: String,
+    pub lat: f64,
+    pub lon: f64,
+    #[serde(default)]
+    pub recorded_at: Option<String>,
+}
+
+/// A goat's last known position and the geofence it is in.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GoatPosition {
+    pub goat_name: String,
+    pub position: GeoPoint,
+    pub recorded_at: DateTime<Utc>,
+    /// Name of the geofence containing the position, if any.
+    pub geofence: Option<String>,
+    /// Geofences are defined and the position is outside all of them.
+    pub outside: bool,
+}
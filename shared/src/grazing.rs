@@ -0,0 +1,111 @@
+//! Paddocks and rotational grazing.
+//!
+//! Grazing each paddock for a week and then resting it lets the grass
+//! regrow and breaks the life cycle of worms shed onto it. Each paddock
+//! sets how many days it must rest between grazings; the backend proposes a
+//! weekly rotation of the herd's grazing groups (the goats housed in each
+//! pen) and flags weeks where the rules cannot all be kept.
+
+use crate::spaces::SpaceKind;
+use serde::{Deserialize, Serialize};
+
+/// Rest period suggested for new paddocks, in days; long enough for most
+/// worm larvae on the pasture to die off.
+pub const DEFAULT_REST_DAYS: i64 = 35;
+
+/// Longest accepted rest period, in days.
+pub const MAX_REST_DAYS: i64 = 365;
+
+/// Most weeks a rotation is planned ahead.
+pub const MAX_ROTATION_WEEKS: u32 = 26;
+
+/// A fenced grazing area.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Paddock {
+    pub id: Option<i64>,
+    pub name: String,
+    /// Area in square metres.
+    #[serde(default)]
+    pub area_m2: Option<f64>,
+    /// Most goats grazed at once.
+    #[serde(default)]
+    pub capacity: Option<i64>,
+    /// Days the paddock must rest after being grazed.
+    pub rest_days: i64,
+    /// Last day it was grazed (`YYYY-MM-DD`), if ever.
+    #[serde(default)]
+    pub last_grazed_on: Option<String>,
+}
+
+impl Paddock {
+    /// Checks the paddock has a name, a rest period within bounds and a
+    /// positive area and capacity if given.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Paddock name must not be empty".to_string());
+        }
+        if !(0..=MAX_REST_DAYS).contains(&self.rest_days) {
+            return Err(format!(
+                "Rest period must be between 0 and {} days, got {}",
+                MAX_REST_DAYS, self.rest_days
+            ));
+        }
+        if self.area_m2.is_some_and(|a| a <= 0.0) {
+            return Err("Paddock area must be positive".to_string());
+        }
+        if self.capacity.is_some_and(|c| c <= 0) {
+            return Err("Paddock capacity must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    /// Most goats the paddock should carry: its capacity, or what its area
+    /// holds at the recommended grazing density.
+    pub fn max_goats(&self) -> Option<i64> {
+        self.capacity.or_else(|| {
+            let per_goat = SpaceKind::GrazingField.area_per_goat()?;
+            self.area_m2.map(|area| (area / per_goat).floor() as i64)
+        })
+    }
+}
+
+/// Goats that graze together.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GrazingGroup {
+    pub name: String,
+    pub goats: i64,
+}
+
+/// Where a group grazes in one week.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GrazingAssignment {
+    pub group: String,
+    /// `None` when no paddock was free for the group.
+    pub paddock: Option<String>,
+    /// Whether the assignment breaks a rule; see the plan's conflicts.
+    pub conflict: bool,
+}
+
+/// One week of a rotation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RotationWeek {
+    /// First day of the week (`YYYY-MM-DD`).
+    pub starts_on: String,
+    pub assignments: Vec<GrazingAssignment>,
+}
+
+/// A rule the proposed rotation could not keep.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RotationConflict {
+    pub week_starts_on: String,
+    pub group: String,
+    pub message: String,
+}
+
+/// A proposed weekly rotation.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RotationPlan {
+    pub groups: Vec<GrazingGroup>,
+    pub weeks: Vec<RotationWeek>,
+    pub conflicts: Vec<RotationConflict>,
+}
@@ -0,0 +1,155 @@
+//! Weight history and growth benchmarking against breed standards.
+//!
+//! Each breed has a reference growth curve of typical body weight by age.
+//! A goat's weight is placed on that curve as a percentile, assuming weights
+//! at a given age are normally distributed around the reference value, and
+//! goats below `GROWTH_ALERT_PERCENTILE` are flagged as growing too slowly.
+
+use crate::Breed;
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+
+/// Goats whose weight falls below this percentile for their age are flagged.
+pub const GROWTH_ALERT_PERCENTILE: f64 = 10.0;
+
+/// Spread of weights around the breed reference, as a fraction of it.
+pub const GROWTH_COEFFICIENT_OF_VARIATION: f64 = 0.15;
+
+/// Ages, in days, at which the reference weights below are given.
+pub const STANDARD_AGES_DAYS: [i64; 6] = [0, 90, 180, 270, 365, 730];
+
+/// Reference body weight in kg at each of `STANDARD_AGES_DAYS`, or `None`
+/// for breeds without a published standard.
+pub fn breed_standard(breed: &Breed) -> Option<[f64; 6]> {
+    let weights = match breed {
+        Breed::Beetal => [3.2, 11.0, 17.0, 22.0, 27.0, 35.0],
+        Breed::Jamunapari => [3.5, 12.0, 18.0, 23.0, 29.0, 38.0],
+        Breed::Barbari => [2.0, 7.0, 11.0, 14.0, 18.0, 24.0],
+        Breed::Sirohi => [2.8, 10.0, 15.0, 19.0, 24.0, 32.0],
+        Breed::Osmanabadi => [2.4, 8.0, 12.0, 15.0, 20.0, 27.0],
+        Breed::BlackBengal => [1.2, 4.5, 7.0, 9.0, 12.0, 16.0],
+        Breed::Kutchi => [2.8, 9.5, 14.0, 18.0, 23.0, 30.0],
+        Breed::Kaghani => [2.5, 9.0, 14.0, 18.0, 24.0, 32.0],
+        Breed::Chegu => [2.0, 7.0, 11.0, 14.0, 18.0, 24.0],
+        Breed::Jakhrana => [3.0, 11.0, 16.0, 21.0, 26.0, 34.0],
+        Breed::Other(_) => return None,
+    };
+    Some(weights)
+}
+
+/// Expected weight in kg at `age_days`, interpolated linearly between the
+/// standard ages and held flat past the last one.
+pub fn expected_weight(breed: &Breed, age_days: i64) -> Option<f64> {
+    let weights = breed_standard(breed)?;
+    if age_days < 0 {
+        return None;
+    }
+    let last = STANDARD_AGES_DAYS.len() - 1;
+    if age_days >= STANDARD_AGES_DAYS[last] {
+        return Some(weights[last]);
+    }
+    let upper = STANDARD_AGES_DAYS.iter().position(|a| *a > age_days)?;
+    let (a0, a1) = (STANDARD_AGES_DAYS[upper - 1], STANDARD_AGES_DAYS[upper]);
+    let (w0, w1) = (weights[upper - 1], weights[upper]);
+    Some(w0 + (w1 - w0) * (age_days - a0) as f64 / (a1 - a0) as f64)
+}
+
+/// Weight in kg at the given percentile for `age_days`.
+pub fn weight_at_percentile(breed: &Breed, age_days: i64, percentile: f64) -> Option<f64> {
+    let expected = expected_weight(breed, age_days)?;
+    let z = inverse_normal_cdf(percentile / 100.0);
+    Some(expected * (1.0 + z * GROWTH_COEFFICIENT_OF_VARIATION))
+}
+
+/// Percentile (0–100) of `weight` among goats of the breed at `age_days`.
+pub fn weight_percentile(breed: &Breed, age_days: i64, weight: f64) -> Option<f64> {
+    let expected = expected_weight(breed, age_days)?;
+    let z = (weight - expected) / (expected * GROWTH_COEFFICIENT_OF_VARIATION);
+    let percentile = normal_cdf(z) * 100.0;
+    trace!(
+        "Weight {} at {} days is percentile {:.1} (expected {:.1})",
+        weight, age_days, percentile, expected
+    );
+    Some(percentile)
+}
+
+/// Standard normal CDF, using the Abramowitz–Stegun erf approximation.
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let poly = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let erf = 1.0 - poly * (-x * x).exp();
+    if z >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+/// Inverse of `normal_cdf`, found by bisection.
+fn inverse_normal_cdf(p: f64) -> f64 {
+    let p = p.clamp(1e-6, 1.0 - 1e-6);
+    let (mut lo, mut hi) = (-6.0, 6.0);
+    for _ in 0..60 {
+        let mid = (lo + hi) / 2.0;
+        if normal_cdf(mid) < p {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+/// A single weighing of a goat.
+///
+/// `age_days` is filled in by the backend when the goat's date of birth is known.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WeightRecord {
+    pub id: Option<i64>,
+    pub goat_name: String,
+    pub weighed_on: String,
+    pub weight: f64,
+    #[serde(default)]
+    pub age_days: Option<i64>,
+    /// True if the weight was estimated from a photo rather than weighed.
+    /// Estimates are logged and charted but never become the goat's
+    /// current weight.
+    #[serde(default)]
+    pub estimated: bool,
+}
+
+/// A weight estimated from a photo of a goat by the farm's estimation
+/// service, used to pre-fill a weighing marked as estimated.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WeightEstimate {
+    /// Estimated weight in kilograms.
+    pub weight: f64,
+    /// How sure the service is, from 0 to 1, if it says.
+    #[serde(default)]
+    pub confidence: Option<f64>,
+}
+
+/// A goat's weight history together with what the chart overlay needs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GrowthHistory {
+    pub goat_name: String,
+    pub breed: Breed,
+    pub date_of_birth: Option<String>,
+    pub records: Vec<WeightRecord>,
+}
+
+/// Where a goat's latest weight sits against its breed standard.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GrowthBenchmark {
+    pub goat_name: String,
+    pub breed: Breed,
+    pub age_days: i64,
+    pub weight: f64,
+    pub expected_weight: f64,
+    pub percentile: f64,
+    pub below_expected: bool,
+}
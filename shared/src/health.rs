@@ -0,0 +1,121 @@
+//! Health incidents and their distribution across pens.
+//!
+//! Each incident records the pen the goat was housed in when it was
+//! observed, so later moves do not shift past incidents. Counting incidents
+//! per pen and month highlights pens with recurring disease or parasite
+//! problems, which often point at sanitation or drainage. A goat's health
+//! timeline lists its incidents, vaccinations and lab results by date.
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum IncidentKind {
+    Disease,
+    Parasite,
+    Injury,
+    Other,
+}
+
+impl IncidentKind {
+    /// Converts a database string to `IncidentKind`.
+    pub fn from_str(s: &str) -> Result<IncidentKind, String> {
+        trace!("Parsing IncidentKind from '{}'", s);
+        match s {
+            "Disease" => Ok(IncidentKind::Disease),
+            "Parasite" => Ok(IncidentKind::Parasite),
+            "Injury" => Ok(IncidentKind::Injury),
+            "Other" => Ok(IncidentKind::Other),
+            other => {
+                debug!("Failed to parse IncidentKind enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts an `IncidentKind` to a database string.
+    pub fn to_str(kind: &IncidentKind) -> &str {
+        match kind {
+            IncidentKind::Disease => "Disease",
+            IncidentKind::Parasite => "Parasite",
+            IncidentKind::Injury => "Injury",
+            IncidentKind::Other => "Other",
+        }
+    }
+}
+
+/// A health problem observed in a goat.
+///
+/// `space_id` is the pen the goat was in at the time; when omitted on
+/// creation it defaults to the goat's current pen. `space_name` is filled in
+/// on reads only.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HealthIncident {
+    pub id: Option<i64>,
+    pub goat_name: String,
+    pub space_id: Option<i64>,
+    #[serde(default)]
+    pub space_name: Option<String>,
+    pub kind: IncidentKind,
+    /// Diagnosis or parasite, e.g. "Coccidiosis" or "Barber's pole worm".
+    pub condition: String,
+    pub observed_on: String,
+    pub notes: Option<String>,
+}
+
+/// Incident counts for one pen, one count per month of the heat map.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HeatMapRow {
+    /// `None` for incidents in goats not housed in any pen.
+    pub space_id: Option<i64>,
+    pub space_name: String,
+    pub counts: Vec<u32>,
+    pub total: u32,
+}
+
+/// Incidents per pen and month over `from`..=`to`, busiest pen first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct HealthHeatMap {
+    pub from: String,
+    pub to: String,
+    /// Column labels as `YYYY-MM`, oldest first.
+    pub months: Vec<String>,
+    pub rows: Vec<HeatMapRow>,
+}
+
+impl HealthHeatMap {
+    /// Largest single cell count, used to scale colour intensity.
+    pub fn max_count(&self) -> u32 {
+        self.rows
+            .iter()
+            .flat_map(|row| row.counts.iter().copied())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// What a health timeline entry records.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineKind {
+    Incident,
+    Vaccination,
+    LabResult,
+}
+
+/// One dated event in a goat's health history: an incident, a vaccine
+/// given, or a lab test's sample.
+///
+/// `flagged` marks lab results with values outside their reference range;
+/// `lab_result_id` links lab results to their record and report.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TimelineEntry {
+    pub date: String,
+    pub kind: TimelineKind,
+    pub title: String,
+    pub details: Option<String>,
+    #[serde(default)]
+    pub flagged: bool,
+    #[serde(default)]
+    pub lab_result_id: Option<i64>,
+}
@@ -0,0 +1,40 @@
+//! Heat (estrus) detection from wearable activity tags.
+//!
+//! Does in heat are restless and typically move far more than usual for a
+//! day or two. Daily activity counts from pedometer tags are compared with
+//! each doe's recent baseline; spikes are treated as heats and projected
+//! forward by the estrous cycle to predict the next one.
+
+use serde::{Deserialize, Serialize};
+
+/// Average length of a goat's estrous cycle, in days.
+pub const ESTROUS_CYCLE_DAYS: i64 = 21;
+
+/// A day on which a doe's activity rose well above her baseline.
+///
+/// `confidence` (0–1) grows with the size of the rise and is raised when the
+/// spike falls one cycle after an earlier one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ActivitySpike {
+    pub date: String,
+    /// Total activity count for the day.
+    pub activity: f64,
+    /// Average daily activity over the preceding days.
+    pub baseline: f64,
+    pub confidence: f64,
+}
+
+/// Heat status and prediction for one doe.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HeatPrediction {
+    pub doe_name: String,
+    /// A heat was detected today or yesterday.
+    pub in_heat: bool,
+    pub last_heat: Option<String>,
+    pub next_heat: Option<String>,
+    /// Confidence (0–1) in `next_heat`; it drops for each cycle projected
+    /// past the last detected heat.
+    pub confidence: f64,
+    /// Detected heats, most recent last.
+    pub recent_spikes: Vec<ActivitySpike>,
+}
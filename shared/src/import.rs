@@ -0,0 +1,478 @@
+//! Importing herds exported from other livestock apps.
+//!
+//! The backend parses an uploaded CSV or XLSX file into an `ImportTable` of
+//! raw text cells without interpreting them. The user then maps each column
+//! to a `GoatField`, previews the rows as `GoatParams` with their validation
+//! errors, and commits the valid ones through `POST /goats/batch`.
+
+use crate::physical::{CoatColor, HornStatus};
+use crate::{Breed, DiseaseRef, Gender, GoatParams, VaccineRef};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{debug, trace};
+
+/// The cells of an uploaded spreadsheet, as text.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ImportTable {
+    /// Column headings from the first row.
+    pub headers: Vec<String>,
+    /// The remaining non-empty rows, padded or truncated to `headers.len()`.
+    pub rows: Vec<Vec<String>>,
+}
+
+/// A `GoatParams` field a column can be mapped to.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GoatField {
+    Name,
+    Breed,
+    Gender,
+    Offspring,
+    Cost,
+    Weight,
+    CurrentPrice,
+    Diet,
+    LastBred,
+    HealthStatus,
+    Vaccinations,
+    Diseases,
+    Horns,
+    CoatColor,
+    Marks,
+}
+
+impl GoatField {
+    /// Every field, in form order.
+    pub const ALL: [GoatField; 15] = [
+        GoatField::Name,
+        GoatField::Breed,
+        GoatField::Gender,
+        GoatField::Offspring,
+        GoatField::Cost,
+        GoatField::Weight,
+        GoatField::CurrentPrice,
+        GoatField::Diet,
+        GoatField::LastBred,
+        GoatField::HealthStatus,
+        GoatField::Vaccinations,
+        GoatField::Diseases,
+        GoatField::Horns,
+        GoatField::CoatColor,
+        GoatField::Marks,
+    ];
+
+    /// Fields every row needs a value for.
+    pub const REQUIRED: [GoatField; 3] = [GoatField::Name, GoatField::Breed, GoatField::Gender];
+
+    /// Human readable name, as shown in the mapping step.
+    pub fn label(&self) -> &'static str {
+        match self {
+            GoatField::Name => "Name",
+            GoatField::Breed => "Breed",
+            GoatField::Gender => "Gender",
+            GoatField::Offspring => "Offspring",
+            GoatField::Cost => "Cost",
+            GoatField::Weight => "Weight (kg)",
+            GoatField::CurrentPrice => "Current price",
+            GoatField::Diet => "Diet",
+            GoatField::LastBred => "Last bred",
+            GoatField::HealthStatus => "Health status",
+            GoatField::Vaccinations => "Vaccinations",
+            GoatField::Diseases => "Diseases",
+            GoatField::Horns => "Horns",
+            GoatField::CoatColor => "Coat color",
+            GoatField::Marks => "Distinguishing marks",
+        }
+    }
+
+    /// Stable identifier, matching the `GoatParams` field name.
+    pub fn key(&self) -> &'static str {
+        match self {
+            GoatField::Name => "name",
+            GoatField::Breed => "breed",
+            GoatField::Gender => "gender",
+            GoatField::Offspring => "offspring",
+            GoatField::Cost => "cost",
+            GoatField::Weight => "weight",
+            GoatField::CurrentPrice => "current_price",
+            GoatField::Diet => "diet",
+            GoatField::LastBred => "last_bred",
+            GoatField::HealthStatus => "health_status",
+            GoatField::Vaccinations => "vaccinations",
+            GoatField::Diseases => "diseases",
+            GoatField::Horns => "horns",
+            GoatField::CoatColor => "coat_color",
+            GoatField::Marks => "marks",
+        }
+    }
+
+    /// Parses a `key()` back into a field.
+    pub fn from_key(key: &str) -> Option<GoatField> {
+        GoatField::ALL.into_iter().find(|field| field.key() == key)
+    }
+
+    /// Guesses the field a column heading refers to, ignoring case, spacing
+    /// and punctuation, e.g. `"Goat Name"`, `"SEX"` or `"Weight (kg)"`.
+    pub fn guess(header: &str) -> Option<GoatField> {
+        let header = normalize(header);
+        let field = match header.as_str() {
+            "name" | "goatname" | "animal" | "animalname" => GoatField::Name,
+            "breed" => GoatField::Breed,
+            "gender" | "sex" => GoatField::Gender,
+            "offspring" | "kids" | "kidding" | "kidscount" => GoatField::Offspring,
+            "cost" | "purchaseprice" | "boughtfor" => GoatField::Cost,
+            "weight" | "weightkg" | "liveweight" => GoatField::Weight,
+            "currentprice" | "price" | "value" | "marketvalue" => GoatField::CurrentPrice,
+            "diet" | "feed" | "ration" => GoatField::Diet,
+            "lastbred" | "lastbreeding" | "breddate" | "servicedate" => GoatField::LastBred,
+            "healthstatus" | "health" | "status" => GoatField::HealthStatus,
+            "vaccinations" | "vaccines" | "vaccination" => GoatField::Vaccinations,
+            "diseases" | "disease" | "conditions" => GoatField::Diseases,
+            "horns" | "horned" | "polled" | "hornstatus" => GoatField::Horns,
+            "coatcolor" | "coatcolour" | "color" | "colour" | "coat" => GoatField::CoatColor,
+            "marks" | "markings" | "distinguishingmarks" | "identification" => GoatField::Marks,
+            _ => return None,
+        };
+        Some(field)
+    }
+}
+
+/// Lowercases `s` and drops everything but letters and digits.
+fn normalize(s: &str) -> String {
+    s.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Guesses a mapping for `headers`, one entry per column. A field is
+/// assigned to at most one column, the first that matches it.
+pub fn guess_mapping(headers: &[String]) -> Vec<Option<GoatField>> {
+    let mut taken = Vec::new();
+    headers
+        .iter()
+        .map(|header| {
+            let field = GoatField::guess(header).filter(|field| !taken.contains(field))?;
+            taken.push(field);
+            Some(field)
+        })
+        .collect()
+}
+
+/// Required fields that no column is mapped to.
+pub fn missing_fields(mapping: &[Option<GoatField>]) -> Vec<GoatField> {
+    GoatField::REQUIRED
+        .into_iter()
+        .filter(|field| !mapping.contains(&Some(*field)))
+        .collect()
+}
+
+/// One row of the import preview.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    /// 1-based line in the uploaded file, counting the header line.
+    pub line: usize,
+    /// The parsed goat; `None` if the row has errors.
+    pub goat: Option<GoatParams>,
+    /// Everything wrong with the row; empty if it can be imported.
+    pub errors: Vec<String>,
+    /// Values that are allowed but unusual (see `goat_warnings`); they do
+    /// not stop the row from being imported.
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// Result of `POST /goats/batch`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BatchSummary {
+    pub added: usize,
+}
+
+/// Heaviest weight, in kg, not flagged as unusual.
+pub const MAX_PLAUSIBLE_WEIGHT: f64 = 150.0;
+
+/// Most kids not flagged as unusual.
+pub const MAX_PLAUSIBLE_OFFSPRING: i32 = 20;
+
+/// Longest quick note accepted, in characters.
+pub const MAX_QUICK_NOTE_LEN: usize = 80;
+
+/// Checks the values of a goat about to be stored.
+pub fn check_goat(goat: &GoatParams) -> Vec<String> {
+    let mut errors = Vec::new();
+    if goat.name.trim().is_empty() {
+        errors.push("Name must not be empty".to_string());
+    }
+    if goat.offspring < 0 {
+        errors.push(format!("Offspring {} must not be negative", goat.offspring));
+    }
+    for (label, value) in [
+        ("Cost", goat.cost),
+        ("Weight", goat.weight),
+        ("Current price", goat.current_price),
+    ] {
+        if !value.is_finite() || value < 0.0 {
+            errors.push(format!("{} {} must be a non-negative number", label, value));
+        }
+    }
+    if let Some(note) = &goat.quick_note
+        && note.chars().count() > MAX_QUICK_NOTE_LEN
+    {
+        errors.push(format!(
+            "Quick note must be at most {} characters",
+            MAX_QUICK_NOTE_LEN
+        ));
+    }
+    for vaccine in &goat.vaccinations {
+        if let Some(date) = &vaccine.given_on
+            && parse_date(date).is_err()
+        {
+            errors.push(format!(
+                "Vaccination {} date '{}' must be a YYYY-MM-DD date",
+                vaccine.name, date
+            ));
+        }
+    }
+    errors
+}
+
+/// Flags values of a goat that pass `check_goat` but look like mistakes,
+/// such as a 500 kg goat or a purchase cost of 0. Forms ask before storing
+/// a goat with warnings; the backend stores it regardless.
+pub fn goat_warnings(goat: &GoatParams) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if goat.weight == 0.0 {
+        warnings.push("Weight is 0 kg; has the goat been weighed?".to_string());
+    } else if goat.weight > MAX_PLAUSIBLE_WEIGHT {
+        warnings.push(format!(
+            "Weight {} kg is unusually heavy for a goat",
+            goat.weight
+        ));
+    }
+    if goat.cost == 0.0 {
+        warnings.push("Cost is 0; was the goat born on the farm or a gift?".to_string());
+    }
+    if goat.current_price == 0.0 {
+        warnings.push("Current price is 0".to_string());
+    }
+    if goat.offspring > MAX_PLAUSIBLE_OFFSPRING {
+        warnings.push(format!("Offspring {} is unusually high", goat.offspring));
+    }
+    warnings
+}
+
+/// Parses a breed, matching known breeds regardless of case and spacing.
+pub fn parse_breed(value: &str) -> Breed {
+    let wanted = normalize(value);
+    let known = [
+        Breed::Beetal,
+        Breed::Jamunapari,
+        Breed::Barbari,
+        Breed::Sirohi,
+        Breed::Osmanabadi,
+        Breed::BlackBengal,
+        Breed::Kutchi,
+        Breed::Kaghani,
+        Breed::Chegu,
+        Breed::Jakhrana,
+    ];
+    known
+        .into_iter()
+        .find(|breed| normalize(Breed::to_str(breed)) == wanted)
+        .unwrap_or_else(|| Breed::Other(value.to_string()))
+}
+
+/// Parses a gender, accepting the common abbreviations and goat terms.
+pub fn parse_gender(value: &str) -> Result<Gender, String> {
+    match normalize(value).as_str() {
+        "male" | "m" | "buck" | "billy" => Ok(Gender::Male),
+        "female" | "f" | "doe" | "nanny" => Ok(Gender::Female),
+        _ => Err(format!("Unknown gender '{}'", value)),
+    }
+}
+
+/// Parses a horn status regardless of case and spacing; "hornless" is
+/// taken as polled.
+fn parse_horns(value: &str) -> Result<HornStatus, String> {
+    let wanted = normalize(value);
+    if wanted == "hornless" {
+        return Ok(HornStatus::Polled);
+    }
+    HornStatus::ALL
+        .into_iter()
+        .find(|horns| normalize(HornStatus::to_str(horns)) == wanted)
+        .ok_or_else(|| format!("Unknown horn status '{}'", value))
+}
+
+/// Parses a coat color regardless of case and spacing, accepting "gray".
+fn parse_coat_color(value: &str) -> Result<CoatColor, String> {
+    let wanted = normalize(value).replace("gray", "grey");
+    CoatColor::ALL
+        .into_iter()
+        .find(|color| normalize(CoatColor::to_str(color)) == wanted)
+        .ok_or_else(|| format!("Unknown coat color '{}'", value))
+}
+
+/// Parses a non-empty number cell; empty cells are zero.
+fn parse_number(label: &str, value: &str) -> Result<f64, String> {
+    if value.is_empty() {
+        return Ok(0.0);
+    }
+    value
+        .replace(',', "")
+        .parse()
+        .map_err(|_| format!("{} '{}' is not a number", label, value))
+}
+
+/// Parses a `YYYY-MM-DD` date.
+pub fn parse_date(value: &str) -> Result<String, String> {
+    let parts: Vec<&str> = value.split('-').collect();
+    let valid = matches!(parts.as_slice(), [y, m, d]
+        if y.len() == 4 && m.len() == 2 && d.len() == 2
+            && y.parse::<u16>().is_ok()
+            && m.parse::<u8>().is_ok_and(|m| (1..=12).contains(&m))
+            && d.parse::<u8>().is_ok_and(|d| (1..=31).contains(&d)));
+    if valid {
+        Ok(value.to_string())
+    } else {
+        Err(format!("Last bred '{}' must be a YYYY-MM-DD date", value))
+    }
+}
+
+/// Splits a list cell such as `"CDT; Rabies"` into its entries.
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split([';', ','])
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Builds a goat from `row` using `mapping`, collecting every problem.
+/// Unmapped fields take their defaults: zero, empty, or no entries.
+pub fn map_row(row: &[String], mapping: &[Option<GoatField>]) -> Result<GoatParams, Vec<String>> {
+    let cells: HashMap<GoatField, &str> = mapping
+        .iter()
+        .zip(row)
+        .filter_map(|(field, cell)| Some(((*field)?, cell.trim())))
+        .collect();
+    let cell = |field| cells.get(&field).copied().unwrap_or_default();
+    let mut errors = Vec::new();
+
+    for field in GoatField::REQUIRED {
+        if cell(field).is_empty() {
+            errors.push(format!("{} is required", field.label()));
+        }
+    }
+    let gender = match cell(GoatField::Gender) {
+        "" => None,
+        value => parse_gender(value).map_err(|e| errors.push(e)).ok(),
+    };
+    let offspring = match cell(GoatField::Offspring) {
+        "" => 0,
+        value => value
+            .parse()
+            .map_err(|_| errors.push(format!("Offspring '{}' is not a whole number", value)))
+            .unwrap_or_default(),
+    };
+    let mut number = |field: GoatField| {
+        parse_number(field.label(), cell(field))
+            .map_err(|e| errors.push(e))
+            .unwrap_or_default()
+    };
+    let cost = number(GoatField::Cost);
+    let weight = number(GoatField::Weight);
+    let current_price = number(GoatField::CurrentPrice);
+    let last_bred = match cell(GoatField::LastBred) {
+        "" => None,
+        value => parse_date(value).map_err(|e| errors.push(e)).ok(),
+    };
+    let horns = match cell(GoatField::Horns) {
+        "" => None,
+        value => parse_horns(value).map_err(|e| errors.push(e)).ok(),
+    };
+    let coat_color = match cell(GoatField::CoatColor) {
+        "" => None,
+        value => parse_coat_color(value).map_err(|e| errors.push(e)).ok(),
+    };
+
+    let goat = GoatParams {
+        name: cell(GoatField::Name).to_string(),
+        breed: parse_breed(cell(GoatField::Breed)),
+        gender: gender.unwrap_or(Gender::Female),
+        offspring,
+        cost,
+        weight,
+        current_price,
+        diet: cell(GoatField::Diet).to_string(),
+        last_bred,
+        health_status: cell(GoatField::HealthStatus).to_string(),
+        vaccinations: parse_list(cell(GoatField::Vaccinations))
+            .into_iter()
+            .map(|name| VaccineRef {
+                id: None,
+                name,
+                given_on: None,
+            })
+            .collect(),
+        diseases: parse_list(cell(GoatField::Diseases))
+            .into_iter()
+            .map(|name| DiseaseRef { id: None, name })
+            .collect(),
+        horns,
+        coat_color,
+        marks: Some(cell(GoatField::Marks).to_string()).filter(|m| !m.is_empty()),
+        quick_note: None,
+    };
+    if !errors.is_empty() {
+        trace!(?errors, "Row failed to parse");
+        return Err(errors);
+    }
+    let errors = check_goat(&goat);
+    if errors.is_empty() {
+        Ok(goat)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parses every row of `table` with `mapping`. Besides per-cell errors,
+/// a name repeated within the file is flagged on its later rows.
+pub fn preview(table: &ImportTable, mapping: &[Option<GoatField>]) -> Vec<ImportRow> {
+    let mut first_line: HashMap<String, usize> = HashMap::new();
+    let rows: Vec<ImportRow> = table
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let line = i + 2;
+            let (goat, mut errors) = match map_row(row, mapping) {
+                Ok(goat) => (Some(goat), Vec::new()),
+                Err(errors) => (None, errors),
+            };
+            if let Some(goat) = &goat
+                && let Some(first) = first_line.get(&goat.name)
+            {
+                errors.push(format!(
+                    "Duplicate name {} (first on line {})",
+                    goat.name, first
+                ));
+            } else if let Some(goat) = &goat {
+                first_line.insert(goat.name.clone(), line);
+            }
+            let warnings = goat.as_ref().map(goat_warnings).unwrap_or_default();
+            ImportRow {
+                line,
+                goat: goat.filter(|_| errors.is_empty()),
+                errors,
+                warnings,
+            }
+        })
+        .collect();
+    debug!(
+        rows = rows.len(),
+        valid = rows.iter().filter(|r| r.goat.is_some()).count(),
+        "Import preview"
+    );
+    rows
+}
@@ -0,0 +1,94 @@
+//! Livestock insurance policies and the claims made against them.
+//!
+//! High-value breeding stock is commonly insured per animal. Each policy
+//! covers one goat until its expiry date; renewals are recorded as new
+//! policies so the claims log of earlier periods stays attached to the
+//! policy it was made under.
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// An insurance policy covering one goat.
+///
+/// Dates use `YYYY-MM-DD`. `sum_insured` is the payout for the loss of the
+/// animal, in the farm's currency.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InsurancePolicy {
+    pub id: Option<i64>,
+    pub goat_name: String,
+    pub provider: String,
+    pub policy_number: String,
+    pub sum_insured: f64,
+    pub start_date: Option<String>,
+    pub expiry_date: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum ClaimStatus {
+    Filed,
+    Approved,
+    Rejected,
+    Settled,
+}
+
+impl ClaimStatus {
+    /// Converts a database string to `ClaimStatus`.
+    pub fn from_str(s: &str) -> Result<ClaimStatus, String> {
+        trace!("Parsing ClaimStatus from '{}'", s);
+        match s {
+            "Filed" => Ok(ClaimStatus::Filed),
+            "Approved" => Ok(ClaimStatus::Approved),
+            "Rejected" => Ok(ClaimStatus::Rejected),
+            "Settled" => Ok(ClaimStatus::Settled),
+            other => {
+                debug!("Failed to parse ClaimStatus enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `ClaimStatus` to a database string.
+    pub fn to_str(status: &ClaimStatus) -> &str {
+        match status {
+            ClaimStatus::Filed => "Filed",
+            ClaimStatus::Approved => "Approved",
+            ClaimStatus::Rejected => "Rejected",
+            ClaimStatus::Settled => "Settled",
+        }
+    }
+}
+
+/// A claim made under a policy, e.g. for the death of the insured goat.
+///
+/// `amount_settled` is what the insurer paid out and is required once the
+/// claim is `Settled`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InsuranceClaim {
+    pub id: Option<i64>,
+    pub policy_id: i64,
+    pub claim_date: String,
+    pub reason: String,
+    pub amount_claimed: f64,
+    pub amount_settled: Option<f64>,
+    pub status: ClaimStatus,
+    pub notes: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum PolicyAlertKind {
+    ExpiringSoon,
+    Expired,
+}
+
+/// A warning that a goat's cover is about to lapse or has lapsed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PolicyAlert {
+    pub policy_id: i64,
+    pub goat_name: String,
+    pub policy_number: String,
+    pub kind: PolicyAlertKind,
+    pub message: String,
+}
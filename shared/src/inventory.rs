@@ -0,0 +1,159 @@
+//! Inventory of farm supplies: feed, medicines, and equipment.
+//!
+//! Stock levels are reduced through `ConsumptionRecord`s, which tie each use of
+//! an item to a treatment or feeding (optionally of a specific goat). Expired
+//! or expiring stock can be disposed of in one go, which logs the loss both as
+//! consumption and as an expense.
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum InventoryCategory {
+    Feed,
+    Medicine,
+    Equipment,
+    Other,
+}
+
+impl InventoryCategory {
+    /// Converts a database string to `InventoryCategory`.
+    pub fn from_str(s: &str) -> Result<InventoryCategory, String> {
+        trace!("Parsing InventoryCategory from '{}'", s);
+        match s {
+            "Feed" => Ok(InventoryCategory::Feed),
+            "Medicine" => Ok(InventoryCategory::Medicine),
+            "Equipment" => Ok(InventoryCategory::Equipment),
+            "Other" => Ok(InventoryCategory::Other),
+            other => {
+                debug!("Failed to parse InventoryCategory enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts an `InventoryCategory` to a database string.
+    pub fn to_str(category: &InventoryCategory) -> &str {
+        match category {
+            InventoryCategory::Feed => "Feed",
+            InventoryCategory::Medicine => "Medicine",
+            InventoryCategory::Equipment => "Equipment",
+            InventoryCategory::Other => "Other",
+        }
+    }
+}
+
+/// A stocked supply item.
+///
+/// `quantity` and `reorder_level` are expressed in `unit` (e.g. "kg", "ml", "bag").
+/// `unit_cost` is the purchase cost per unit. `expiry_date` uses `YYYY-MM-DD`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InventoryItem {
+    pub id: Option<i64>,
+    pub name: String,
+    pub category: InventoryCategory,
+    pub quantity: f64,
+    pub unit: String,
+    pub unit_cost: f64,
+    pub expiry_date: Option<String>,
+    pub reorder_level: f64,
+}
+
+/// Why an item was consumed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum ConsumptionPurpose {
+    Treatment,
+    Feeding,
+    Other,
+}
+
+impl ConsumptionPurpose {
+    /// Converts a database string to `ConsumptionPurpose`.
+    pub fn from_str(s: &str) -> Result<ConsumptionPurpose, String> {
+        trace!("Parsing ConsumptionPurpose from '{}'", s);
+        match s {
+            "Treatment" => Ok(ConsumptionPurpose::Treatment),
+            "Feeding" => Ok(ConsumptionPurpose::Feeding),
+            "Other" => Ok(ConsumptionPurpose::Other),
+            other => {
+                debug!("Failed to parse ConsumptionPurpose enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `ConsumptionPurpose` to a database string.
+    pub fn to_str(purpose: &ConsumptionPurpose) -> &str {
+        match purpose {
+            ConsumptionPurpose::Treatment => "Treatment",
+            ConsumptionPurpose::Feeding => "Feeding",
+            ConsumptionPurpose::Other => "Other",
+        }
+    }
+}
+
+/// A logged use of an inventory item, e.g. 5 ml of dewormer for one goat.
+///
+/// The cost of the consumed quantity is charged to `goat_name` or, for
+/// pen-wide use such as feeding, to the pen `space_id`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConsumptionRecord {
+    pub id: Option<i64>,
+    pub item_id: i64,
+    pub quantity: f64,
+    pub purpose: ConsumptionPurpose,
+    pub goat_name: Option<String>,
+    pub space_id: Option<i64>,
+    pub consumed_on: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum InventoryAlertKind {
+    LowStock,
+    ExpiringSoon,
+    Expired,
+}
+
+/// A stock warning raised for an inventory item.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InventoryAlert {
+    pub item_id: i64,
+    pub item_name: String,
+    pub kind: InventoryAlertKind,
+    pub message: String,
+}
+
+/// Stock left of an item that has expired or expires soon.
+///
+/// `days_left` is negative once the item has expired; `value` is what the
+/// remaining stock cost.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExpiringStock {
+    pub item: InventoryItem,
+    pub days_left: i64,
+    pub value: f64,
+}
+
+impl ExpiringStock {
+    /// Whether the item is past its expiry date.
+    pub fn expired(&self) -> bool {
+        self.days_left < 0
+    }
+}
+
+/// The result of disposing of an item's remaining stock: the consumption
+/// logged and the expense recorded for it, if the stock had a cost.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Disposal {
+    pub item_id: i64,
+    pub item_name: String,
+    pub quantity: f64,
+    pub unit: String,
+    pub value: f64,
+    pub consumption_id: i64,
+    pub transaction_id: Option<i64>,
+}
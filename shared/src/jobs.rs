@@ -0,0 +1,136 @@
+//! Background jobs: long-running work the backend queues and runs after
+//! answering the request, such as building a large report, importing a big
+//! spreadsheet or sending email and SMS notifications. Clients poll a job's
+//! status and progress until it is finished.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// What a job does.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobKind {
+    /// Builds a census report file to download.
+    Report,
+    /// Adds goats from an imported spreadsheet.
+    Import,
+    /// Sends email and SMS notifications through the message gateway.
+    Notification,
+    /// Purges the trash and event log entries past the farm's retention.
+    Cleanup,
+}
+
+impl JobKind {
+    /// Converts a database string to `JobKind`.
+    pub fn from_str(s: &str) -> Result<JobKind, String> {
+        trace!("Parsing JobKind from '{}'", s);
+        match s {
+            "report" => Ok(JobKind::Report),
+            "import" => Ok(JobKind::Import),
+            "notification" => Ok(JobKind::Notification),
+            "cleanup" => Ok(JobKind::Cleanup),
+            other => {
+                debug!("Failed to parse JobKind enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `JobKind` to a database string.
+    pub fn to_str(kind: &JobKind) -> &str {
+        match kind {
+            JobKind::Report => "report",
+            JobKind::Import => "import",
+            JobKind::Notification => "notification",
+            JobKind::Cleanup => "cleanup",
+        }
+    }
+}
+
+/// Where a job is in its life.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    /// Waiting to run, or to be retried after a failed attempt.
+    Queued,
+    Running,
+    Succeeded,
+    /// Gave up after its last attempt; `Job::error` says why.
+    Failed,
+}
+
+impl JobStatus {
+    /// Converts a database string to `JobStatus`.
+    pub fn from_str(s: &str) -> Result<JobStatus, String> {
+        trace!("Parsing JobStatus from '{}'", s);
+        match s {
+            "queued" => Ok(JobStatus::Queued),
+            "running" => Ok(JobStatus::Running),
+            "succeeded" => Ok(JobStatus::Succeeded),
+            "failed" => Ok(JobStatus::Failed),
+            other => {
+                debug!("Failed to parse JobStatus enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `JobStatus` to a database string.
+    pub fn to_str(status: &JobStatus) -> &str {
+        match status {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "Queued",
+            JobStatus::Running => "Running",
+            JobStatus::Succeeded => "Done",
+            JobStatus::Failed => "Failed",
+        }
+    }
+
+    /// Whether the job will not change any more.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Succeeded | JobStatus::Failed)
+    }
+}
+
+/// A queued, running or finished job.
+///
+/// Progress is counted in steps of the job's own unit (goats imported,
+/// messages sent); `total` is 0 until the job knows how many there are.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Job {
+    pub id: i64,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// What the job is about, e.g. "Import 250 goats".
+    pub description: String,
+    pub done: u32,
+    pub total: u32,
+    /// Attempts made so far, including a running one.
+    pub attempts: u32,
+    /// Why the last attempt failed, if it did.
+    pub error: Option<String>,
+    /// File name of the job's result, if it has one to download.
+    pub result_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Job {
+    /// Share of the work done, from 0 to 1. A finished job counts as done.
+    pub fn progress(&self) -> f64 {
+        if self.status == JobStatus::Succeeded {
+            1.0
+        } else if self.total == 0 {
+            0.0
+        } else {
+            (self.done as f64 / self.total as f64).min(1.0)
+        }
+    }
+}
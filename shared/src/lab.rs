@@ -0,0 +1,168 @@
+//! Lab test results.
+//!
+//! A result records one test of one goat's sample, e.g. a blood panel or a
+//! fecal egg count, with a value per analyte measured. Each value carries
+//! the reference range it is judged against, taken from
+//! `REFERENCE_RANGES` when the lab did not report one, and is flagged when
+//! it falls outside. The lab's report can be attached as a PDF.
+
+use crate::attachments::MAX_ATTACHMENT_BYTES;
+use serde::{Deserialize, Serialize};
+
+/// Typical ranges for adult goats of common analytes, as
+/// `(analyte, unit, low, high)`.
+pub const REFERENCE_RANGES: [(&str, &str, f64, f64); 9] = [
+    ("PCV", "%", 22.0, 38.0),
+    ("Hemoglobin", "g/dL", 8.0, 12.0),
+    ("WBC", "10^3/uL", 4.0, 13.0),
+    ("Total protein", "g/dL", 6.4, 7.0),
+    ("Glucose", "mg/dL", 50.0, 75.0),
+    ("BUN", "mg/dL", 10.0, 20.0),
+    ("Creatinine", "mg/dL", 1.0, 1.8),
+    ("Calcium", "mg/dL", 8.9, 11.7),
+    ("Fecal egg count", "EPG", 0.0, 500.0),
+];
+
+/// The typical `(low, high)` range of `analyte` measured in `unit`, if known.
+/// Names are matched ignoring case.
+pub fn reference_range(analyte: &str, unit: &str) -> Option<(f64, f64)> {
+    REFERENCE_RANGES
+        .iter()
+        .find(|(name, range_unit, _, _)| {
+            name.eq_ignore_ascii_case(analyte.trim())
+                && range_unit.eq_ignore_ascii_case(unit.trim())
+        })
+        .map(|&(_, _, low, high)| (low, high))
+}
+
+/// Which side of its reference range a value falls on.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeFlag {
+    Low,
+    High,
+}
+
+/// One analyte's measured value and the range it is judged against.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LabValue {
+    pub analyte: String,
+    pub value: f64,
+    pub unit: String,
+    #[serde(default)]
+    pub low: Option<f64>,
+    #[serde(default)]
+    pub high: Option<f64>,
+}
+
+impl LabValue {
+    /// Whether the value is below or above its reference range; `None` if
+    /// within it or no range is known.
+    pub fn flag(&self) -> Option<RangeFlag> {
+        match (self.low, self.high) {
+            (Some(low), _) if self.value < low => Some(RangeFlag::Low),
+            (_, Some(high)) if self.value > high => Some(RangeFlag::High),
+            _ => None,
+        }
+    }
+
+    /// The range as shown next to the value, e.g. "22–38".
+    pub fn range_label(&self) -> Option<String> {
+        match (self.low, self.high) {
+            (Some(low), Some(high)) => Some(format!("{}–{}", low, high)),
+            (Some(low), None) => Some(format!("≥ {}", low)),
+            (None, Some(high)) => Some(format!("≤ {}", high)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// A lab test of one goat's sample.
+///
+/// `has_report` is set on reads when a PDF report is attached; it is
+/// fetched from `/lab-results/{id}/report`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LabResult {
+    pub id: Option<i64>,
+    pub goat_name: String,
+    /// What was tested, e.g. "CBC" or "Fecal egg count".
+    pub test_type: String,
+    /// When the sample was taken (`YYYY-MM-DD`).
+    pub sampled_on: String,
+    pub lab: Option<String>,
+    pub values: Vec<LabValue>,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub has_report: bool,
+}
+
+impl LabResult {
+    /// Checks the result names its goat and test and every value its
+    /// analyte, with a sensible range.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.goat_name.trim().is_empty() {
+            return Err("A lab result needs a goat".into());
+        }
+        if self.test_type.trim().is_empty() {
+            return Err("A lab result needs a test type".into());
+        }
+        if self.values.is_empty() {
+            return Err("A lab result needs at least one value".into());
+        }
+        for value in &self.values {
+            if value.analyte.trim().is_empty() {
+                return Err("Every value needs an analyte".into());
+            }
+            if !value.value.is_finite() {
+                return Err(format!("{} must be a number", value.analyte));
+            }
+            if let (Some(low), Some(high)) = (value.low, value.high)
+                && low > high
+            {
+                return Err(format!(
+                    "The range of {} must not end below its start",
+                    value.analyte
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills in the typical range of values reported without one.
+    pub fn apply_reference_ranges(&mut self) {
+        for value in &mut self.values {
+            if value.low.is_none()
+                && value.high.is_none()
+                && let Some((low, high)) = reference_range(&value.analyte, &value.unit)
+            {
+                value.low = Some(low);
+                value.high = Some(high);
+            }
+        }
+    }
+
+    /// The values outside their reference range.
+    pub fn flagged(&self) -> Vec<&LabValue> {
+        self.values.iter().filter(|v| v.flag().is_some()).collect()
+    }
+}
+
+/// Checks an uploaded lab report of `size_bytes` bytes is a PDF within the
+/// attachment limit.
+pub fn check_report(content_type: &str, size_bytes: usize) -> Result<(), String> {
+    if content_type != "application/pdf" {
+        return Err(format!(
+            "A lab report must be a PDF, got '{}'",
+            content_type
+        ));
+    }
+    if size_bytes == 0 {
+        return Err("The lab report is empty".to_string());
+    }
+    if size_bytes > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "A lab report must be at most {} MB",
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+//! Ear-tag label sheets.
+//!
+//! Each label carries a goat's name, its tag ID and a QR code a phone can
+//! scan to find the goat. Sheets are printed on standard self-adhesive
+//! label stock; a `LabelLayout` names the stock and `LabelSheet` gives its
+//! geometry in millimetres, measured from the top-left corner of the page.
+
+use serde::{Deserialize, Serialize};
+
+/// Common label stock.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelLayout {
+    /// A4, 21 labels of 63.5 x 38.1 mm (Avery L7160 and equivalents).
+    #[default]
+    L7160,
+    /// A4, 14 labels of 99.1 x 38.1 mm (Avery L7163).
+    L7163,
+    /// A4, 65 labels of 38.1 x 21.2 mm (Avery L7651), for small tags.
+    L7651,
+    /// US Letter, 30 labels of 66.7 x 25.4 mm (Avery 5160).
+    Avery5160,
+}
+
+/// Where the labels of a layout sit on the page, in millimetres.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LabelSheet {
+    pub page_width: f64,
+    pub page_height: f64,
+    pub columns: usize,
+    pub rows: usize,
+    pub label_width: f64,
+    pub label_height: f64,
+    /// Distance from the left edge of the page to the first column.
+    pub left: f64,
+    /// Distance from the top edge of the page to the first row.
+    pub top: f64,
+    /// Distance between the left edges of neighbouring columns.
+    pub column_pitch: f64,
+    /// Distance between the top edges of neighbouring rows.
+    pub row_pitch: f64,
+}
+
+impl LabelLayout {
+    /// Every layout, in the order offered.
+    pub const ALL: [LabelLayout; 4] = [
+        LabelLayout::L7160,
+        LabelLayout::L7163,
+        LabelLayout::L7651,
+        LabelLayout::Avery5160,
+    ];
+
+    /// Name shown when choosing the stock.
+    pub fn label(&self) -> &'static str {
+        match self {
+            LabelLayout::L7160 => "A4, 21 per sheet (63.5 x 38.1 mm, L7160)",
+            LabelLayout::L7163 => "A4, 14 per sheet (99.1 x 38.1 mm, L7163)",
+            LabelLayout::L7651 => "A4, 65 per sheet (38.1 x 21.2 mm, L7651)",
+            LabelLayout::Avery5160 => "Letter, 30 per sheet (2.63 x 1 in, 5160)",
+        }
+    }
+
+    /// The manufacturer's geometry for the stock.
+    pub fn sheet(&self) -> LabelSheet {
+        let a4 = |columns, rows, size: (f64, f64), left, top, pitch: (f64, f64)| LabelSheet {
+            page_width: 210.0,
+            page_height: 297.0,
+            columns,
+            rows,
+            label_width: size.0,
+            label_height: size.1,
+            left,
+            top,
+            column_pitch: pitch.0,
+            row_pitch: pitch.1,
+        };
+        match self {
+            LabelLayout::L7160 => a4(3, 7, (63.5, 38.1), 7.25, 15.15, (66.04, 38.1)),
+            LabelLayout::L7163 => a4(2, 7, (99.1, 38.1), 4.65, 15.15, (101.6, 38.1)),
+            LabelLayout::L7651 => a4(5, 13, (38.1, 21.2), 4.75, 10.7, (40.64, 21.2)),
+            LabelLayout::Avery5160 => LabelSheet {
+                page_width: 215.9,
+                page_height: 279.4,
+                columns: 3,
+                rows: 10,
+                label_width: 66.675,
+                label_height: 25.4,
+                left: 4.7625,
+                top: 12.7,
+                column_pitch: 69.85,
+                row_pitch: 25.4,
+            },
+        }
+    }
+}
+
+impl LabelSheet {
+    /// Labels on one page.
+    pub fn per_page(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    /// Page and top-left corner (in millimetres from the top-left of the
+    /// page) of the label in slot `index`, counting across each row and
+    /// then down, continuing onto further pages.
+    pub fn position(&self, index: usize) -> (usize, f64, f64) {
+        let page = index / self.per_page();
+        let slot = index % self.per_page();
+        let (row, column) = (slot / self.columns, slot % self.columns);
+        (
+            page,
+            self.left + column as f64 * self.column_pitch,
+            self.top + row as f64 * self.row_pitch,
+        )
+    }
+}
+
+/// Body of `POST /reports/tag-sheet`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TagSheetRequest {
+    /// Goats to print a label for, in order.
+    pub goat_names: Vec<String>,
+    #[serde(default)]
+    pub layout: LabelLayout,
+    /// Labels already used on the first sheet, left blank so a partly
+    /// used sheet can go back through the printer.
+    #[serde(default)]
+    pub skip: usize,
+}
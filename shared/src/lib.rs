@@ -1,6 +1,66 @@
+use chrono::{DateTime, Utc};
+use physical::{CoatColor, HornStatus};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, trace, warn};
 
+pub mod activity;
+pub mod alerts;
+pub mod analytics;
+pub mod archive;
+pub mod attachments;
+pub mod breeding;
+pub mod breeds;
+pub mod bulk;
+pub mod census;
+pub mod data_health;
+pub mod diagnostics;
+pub mod diseases;
+pub mod events;
+pub mod exports;
+pub mod finance;
+pub mod gps;
+pub mod grazing;
+pub mod growth;
+pub mod health;
+pub mod heat;
+pub mod import;
+pub mod insurance;
+pub mod jobs;
+pub mod lab;
+pub mod inventory;
+pub mod labels;
+pub mod lifecycle;
+pub mod milk;
+pub mod movements;
+pub mod notes;
+pub mod notifications;
+pub mod nutrition;
+pub mod permissions;
+pub mod physical;
+pub mod pricing;
+pub mod retention;
+pub mod scale;
+pub mod scoring;
+pub mod search;
+pub mod settings;
+pub mod sensors;
+pub mod sessions;
+pub mod setup;
+pub mod spaces;
+pub mod stats;
+pub mod tags;
+pub mod tasks;
+pub mod tenants;
+pub mod time;
+pub mod tokens;
+pub mod traceability;
+pub mod transliterate;
+pub mod units;
+pub mod vaccination;
+pub mod vet;
+pub mod voice;
+pub mod water;
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub enum Breed {
@@ -85,6 +145,12 @@ impl Gender {
             Gender::Female => "Female",
         }
     }
+
+    /// Whether goats of this gender kid and give milk, and so have a
+    /// last-bred date and lactation records. Bucks have neither.
+    pub fn has_breeding_records(&self) -> bool {
+        matches!(self, Gender::Female)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -107,13 +173,15 @@ pub enum Disease {
     Other(String),
 }
 
-// VaccineRf and DiseaseRef currently look the same.
-// However, we can add more functionality like booster date for vaccine
-// and symptoms for disease.
+// VaccineRef and DiseaseRef started out the same; a vaccine also records
+// when the dose was given. Symptoms for disease may follow.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct VaccineRef {
     pub id: Option<i64>,
     pub name: String,
+    /// Date the dose was given (`YYYY-MM-DD`), if recorded.
+    #[serde(default)]
+    pub given_on: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -136,4 +204,373 @@ pub struct GoatParams {
     pub health_status: String,
     pub vaccinations: Vec<VaccineRef>,
     pub diseases: Vec<DiseaseRef>,
+    #[serde(default)]
+    pub horns: Option<HornStatus>,
+    #[serde(default)]
+    pub coat_color: Option<CoatColor>,
+    /// Distinguishing marks, e.g. "white blaze, notch in left ear".
+    #[serde(default)]
+    pub marks: Option<String>,
+    /// A short, transient flag edited in the goat list, e.g. "check
+    /// tomorrow". Unlike the goat's notes (see `notes::GoatNote`) it is one
+    /// line, overwritten rather than threaded.
+    #[serde(default)]
+    pub quick_note: Option<String>,
+}
+
+impl GoatParams {
+    /// Starts building a goat named `name`; see `GoatParamsBuilder`.
+    pub fn builder(name: impl Into<String>) -> GoatParamsBuilder {
+        GoatParamsBuilder::new(name)
+    }
+}
+
+/// Builds a `GoatParams` without spelling out every field.
+///
+/// Unset fields default to an unknown breed, a female with no offspring,
+/// zero cost, weight and price, no diet or breeding date, a "healthy"
+/// status, no vaccinations or diseases and no physical traits recorded.
+/// `build` checks the result the same way the backend checks new goats.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoatParamsBuilder {
+    goat: GoatParams,
+}
+
+impl GoatParamsBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        GoatParamsBuilder {
+            goat: GoatParams {
+                name: name.into(),
+                breed: Breed::Other("Unknown".to_string()),
+                gender: Gender::Female,
+                offspring: 0,
+                cost: 0.0,
+                weight: 0.0,
+                current_price: 0.0,
+                diet: String::new(),
+                last_bred: None,
+                health_status: "healthy".to_string(),
+                vaccinations: Vec::new(),
+                diseases: Vec::new(),
+                horns: None,
+                coat_color: None,
+                marks: None,
+                quick_note: None,
+            },
+        }
+    }
+
+    pub fn breed(mut self, breed: Breed) -> Self {
+        self.goat.breed = breed;
+        self
+    }
+
+    pub fn gender(mut self, gender: Gender) -> Self {
+        self.goat.gender = gender;
+        self
+    }
+
+    pub fn offspring(mut self, offspring: i32) -> Self {
+        self.goat.offspring = offspring;
+        self
+    }
+
+    pub fn cost(mut self, cost: f64) -> Self {
+        self.goat.cost = cost;
+        self
+    }
+
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.goat.weight = weight;
+        self
+    }
+
+    pub fn current_price(mut self, current_price: f64) -> Self {
+        self.goat.current_price = current_price;
+        self
+    }
+
+    pub fn diet(mut self, diet: impl Into<String>) -> Self {
+        self.goat.diet = diet.into();
+        self
+    }
+
+    /// Sets the last breeding date (`YYYY-MM-DD`).
+    pub fn last_bred(mut self, date: impl Into<String>) -> Self {
+        self.goat.last_bred = Some(date.into());
+        self
+    }
+
+    pub fn health_status(mut self, health_status: impl Into<String>) -> Self {
+        self.goat.health_status = health_status.into();
+        self
+    }
+
+    /// Adds a vaccination by name.
+    pub fn vaccination(mut self, name: impl Into<String>) -> Self {
+        self.goat.vaccinations.push(VaccineRef {
+            id: None,
+            name: name.into(),
+            given_on: None,
+        });
+        self
+    }
+
+    /// Replaces the vaccinations, keeping their IDs.
+    pub fn vaccinations(mut self, vaccinations: Vec<VaccineRef>) -> Self {
+        self.goat.vaccinations = vaccinations;
+        self
+    }
+
+    /// Adds a disease by name.
+    pub fn disease(mut self, name: impl Into<String>) -> Self {
+        self.goat.diseases.push(DiseaseRef {
+            id: None,
+            name: name.into(),
+        });
+        self
+    }
+
+    /// Replaces the diseases, keeping their IDs.
+    pub fn diseases(mut self, diseases: Vec<DiseaseRef>) -> Self {
+        self.goat.diseases = diseases;
+        self
+    }
+
+    pub fn horns(mut self, horns: HornStatus) -> Self {
+        self.goat.horns = Some(horns);
+        self
+    }
+
+    pub fn coat_color(mut self, coat_color: CoatColor) -> Self {
+        self.goat.coat_color = Some(coat_color);
+        self
+    }
+
+    /// Sets the distinguishing marks; blank marks are left unrecorded.
+    pub fn marks(mut self, marks: impl Into<String>) -> Self {
+        let marks = marks.into();
+        self.goat.marks = (!marks.trim().is_empty()).then(|| marks.trim().to_string());
+        self
+    }
+
+    /// Sets the quick note; a blank note is left unrecorded.
+    pub fn quick_note(mut self, note: impl Into<String>) -> Self {
+        let note = note.into();
+        self.goat.quick_note = (!note.trim().is_empty()).then(|| note.trim().to_string());
+        self
+    }
+
+    /// Returns the goat, or every problem found with it: those reported by
+    /// `import::check_goat` and a malformed breeding date.
+    pub fn build(self) -> Result<GoatParams, Vec<String>> {
+        let mut errors = import::check_goat(&self.goat);
+        if let Some(date) = &self.goat.last_bred
+            && let Err(e) = import::parse_date(date)
+        {
+            errors.push(e);
+        }
+        if errors.is_empty() {
+            Ok(self.goat)
+        } else {
+            debug!(name = %self.goat.name, ?errors, "Goat failed validation");
+            Err(errors)
+        }
+    }
+}
+
+/// The payload for creating a goat. `GoatParams` holds exactly the fields a
+/// user enters and none the server assigns, so it doubles as this type;
+/// build one with `GoatParams::builder`.
+pub type NewGoat = GoatParams;
+
+/// A stored goat as the server returns it: the user-entered fields plus the
+/// ID, ear tag, birth date and timestamps kept by the server. Serialized
+/// flat, so clients that only read `GoatParams` fields can ignore the rest.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Goat {
+    pub id: i64,
+    #[serde(flatten)]
+    pub params: GoatParams,
+    /// Ear tag, assigned from the farm's tag series or by a scale.
+    #[serde(default)]
+    pub tag_id: Option<String>,
+    /// Birth date (`YYYY-MM-DD`), recorded with the goat's pedigree.
+    #[serde(default)]
+    pub date_of_birth: Option<String>,
+    /// When the goat was added.
+    pub created_at: Option<DateTime<Utc>>,
+    /// When any of the `GoatParams` fields last changed.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl std::ops::Deref for Goat {
+    type Target = GoatParams;
+
+    fn deref(&self) -> &GoatParams {
+        &self.params
+    }
+}
+
+impl std::ops::DerefMut for Goat {
+    fn deref_mut(&mut self) -> &mut GoatParams {
+        &mut self.params
+    }
+}
+
+/// A partial change to a goat: `None` fields are left as they are.
+/// `last_bred` is doubly optional so a change can also clear the date
+/// (`Some(None)`, sent as `null`).
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct GoatUpdate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breed: Option<Breed>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gender: Option<Gender>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offspring: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_price: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diet: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "present_or_null"
+    )]
+    pub last_bred: Option<Option<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vaccinations: Option<Vec<VaccineRef>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diseases: Option<Vec<DiseaseRef>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "present_or_null"
+    )]
+    pub horns: Option<Option<HornStatus>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "present_or_null"
+    )]
+    pub coat_color: Option<Option<CoatColor>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "present_or_null"
+    )]
+    pub marks: Option<Option<String>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "present_or_null"
+    )]
+    pub quick_note: Option<Option<String>>,
+}
+
+/// Reads a field that is present, so `null` means "clear" rather than
+/// "unchanged" (a missing field never reaches this and stays `None`).
+fn present_or_null<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+impl GoatUpdate {
+    /// True if the update changes nothing.
+    pub fn is_empty(&self) -> bool {
+        *self == GoatUpdate::default()
+    }
+
+    /// The update turning `before` into `after`, holding only the fields
+    /// that differ.
+    pub fn diff(before: &GoatParams, after: &GoatParams) -> GoatUpdate {
+        fn changed<T: PartialEq + Clone>(before: &T, after: &T) -> Option<T> {
+            (before != after).then(|| after.clone())
+        }
+        GoatUpdate {
+            name: changed(&before.name, &after.name),
+            breed: changed(&before.breed, &after.breed),
+            gender: changed(&before.gender, &after.gender),
+            offspring: changed(&before.offspring, &after.offspring),
+            cost: changed(&before.cost, &after.cost),
+            weight: changed(&before.weight, &after.weight),
+            current_price: changed(&before.current_price, &after.current_price),
+            diet: changed(&before.diet, &after.diet),
+            last_bred: changed(&before.last_bred, &after.last_bred),
+            health_status: changed(&before.health_status, &after.health_status),
+            vaccinations: changed(&before.vaccinations, &after.vaccinations),
+            diseases: changed(&before.diseases, &after.diseases),
+            horns: changed(&before.horns, &after.horns),
+            coat_color: changed(&before.coat_color, &after.coat_color),
+            marks: changed(&before.marks, &after.marks),
+            quick_note: changed(&before.quick_note, &after.quick_note),
+        }
+    }
+
+    /// Returns `goat` with this update's fields replaced.
+    pub fn apply(&self, goat: &GoatParams) -> GoatParams {
+        trace!(name = %goat.name, "Applying goat update");
+        let mut updated = goat.clone();
+        if let Some(name) = &self.name {
+            updated.name = name.clone();
+        }
+        if let Some(breed) = &self.breed {
+            updated.breed = breed.clone();
+        }
+        if let Some(gender) = &self.gender {
+            updated.gender = gender.clone();
+        }
+        if let Some(offspring) = self.offspring {
+            updated.offspring = offspring;
+        }
+        if let Some(cost) = self.cost {
+            updated.cost = cost;
+        }
+        if let Some(weight) = self.weight {
+            updated.weight = weight;
+        }
+        if let Some(current_price) = self.current_price {
+            updated.current_price = current_price;
+        }
+        if let Some(diet) = &self.diet {
+            updated.diet = diet.clone();
+        }
+        if let Some(last_bred) = &self.last_bred {
+            updated.last_bred = last_bred.clone();
+        }
+        if let Some(health_status) = &self.health_status {
+            updated.health_status = health_status.clone();
+        }
+        if let Some(vaccinations) = &self.vaccinations {
+            updated.vaccinations = vaccinations.clone();
+        }
+        if let Some(diseases) = &self.diseases {
+            updated.diseases = diseases.clone();
+        }
+        if let Some(horns) = self.horns {
+            updated.horns = horns;
+        }
+        if let Some(coat_color) = self.coat_color {
+            updated.coat_color = coat_color;
+        }
+        if let Some(marks) = &self.marks {
+            updated.marks = marks.clone();
+        }
+        if let Some(quick_note) = &self.quick_note {
+            updated.quick_note = quick_note.clone();
+        }
+        updated
+    }
 }
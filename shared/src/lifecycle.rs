@@ -0,0 +1,190 @@
+//! Goat lifecycle as a state machine.
+//!
+//! A goat moves Kid → Grower → Breeder or Milker → ForSale → Sold, and may
+//! die at any stage. Sold and Deceased are final. Goats start untracked and
+//! may enter at any live stage, so adult goats already on the farm need not
+//! pass through Kid. The backend refuses any other move (see
+//! `check_transition`).
+
+use crate::Gender;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// Where a goat is in its life on the farm.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "PascalCase")]
+pub enum LifecycleStage {
+    /// Born on the farm and not yet weaned.
+    Kid,
+    /// Weaned and growing on.
+    Grower,
+    /// Kept for breeding.
+    Breeder,
+    /// A doe kept for milk.
+    Milker,
+    /// Offered for sale.
+    ForSale,
+    Sold,
+    Deceased,
+}
+
+impl LifecycleStage {
+    /// Every stage, in pipeline order.
+    pub const ALL: [LifecycleStage; 7] = [
+        LifecycleStage::Kid,
+        LifecycleStage::Grower,
+        LifecycleStage::Breeder,
+        LifecycleStage::Milker,
+        LifecycleStage::ForSale,
+        LifecycleStage::Sold,
+        LifecycleStage::Deceased,
+    ];
+
+    /// Converts a database string to `LifecycleStage`.
+    pub fn from_str(s: &str) -> Result<LifecycleStage, String> {
+        trace!("Parsing LifecycleStage from '{}'", s);
+        match s {
+            "Kid" => Ok(LifecycleStage::Kid),
+            "Grower" => Ok(LifecycleStage::Grower),
+            "Breeder" => Ok(LifecycleStage::Breeder),
+            "Milker" => Ok(LifecycleStage::Milker),
+            "ForSale" => Ok(LifecycleStage::ForSale),
+            "Sold" => Ok(LifecycleStage::Sold),
+            "Deceased" => Ok(LifecycleStage::Deceased),
+            other => {
+                debug!("Failed to parse LifecycleStage enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `LifecycleStage` to a database string.
+    pub fn to_str(stage: &LifecycleStage) -> &str {
+        match stage {
+            LifecycleStage::Kid => "Kid",
+            LifecycleStage::Grower => "Grower",
+            LifecycleStage::Breeder => "Breeder",
+            LifecycleStage::Milker => "Milker",
+            LifecycleStage::ForSale => "ForSale",
+            LifecycleStage::Sold => "Sold",
+            LifecycleStage::Deceased => "Deceased",
+        }
+    }
+
+    /// Human-readable name, e.g. "For sale".
+    pub fn label(&self) -> &'static str {
+        match self {
+            LifecycleStage::Kid => "Kid",
+            LifecycleStage::Grower => "Grower",
+            LifecycleStage::Breeder => "Breeder",
+            LifecycleStage::Milker => "Milker",
+            LifecycleStage::ForSale => "For sale",
+            LifecycleStage::Sold => "Sold",
+            LifecycleStage::Deceased => "Deceased",
+        }
+    }
+
+    /// Whether the goat has left the herd for good.
+    pub fn is_final(&self) -> bool {
+        matches!(self, LifecycleStage::Sold | LifecycleStage::Deceased)
+    }
+
+    /// The stages a goat at this stage may move to.
+    pub fn next_stages(&self) -> &'static [LifecycleStage] {
+        use LifecycleStage::*;
+        match self {
+            Kid => &[Grower, ForSale, Deceased],
+            Grower => &[Breeder, Milker, ForSale, Deceased],
+            Breeder => &[Milker, ForSale, Deceased],
+            Milker => &[Breeder, ForSale, Deceased],
+            // Taken off the market, or sold
+            ForSale => &[Grower, Breeder, Milker, Sold, Deceased],
+            Sold | Deceased => &[],
+        }
+    }
+}
+
+/// The stages a goat of `gender` at `from` (`None` while untracked) may move
+/// to, in pipeline order.
+pub fn allowed_stages(from: Option<LifecycleStage>, gender: &Gender) -> Vec<LifecycleStage> {
+    LifecycleStage::ALL
+        .into_iter()
+        .filter(|to| check_transition(from, *to, gender).is_ok())
+        .collect()
+}
+
+/// Checks a goat of `gender` may move from `from` (`None` while untracked)
+/// to `to`. Only does are milked.
+pub fn check_transition(
+    from: Option<LifecycleStage>,
+    to: LifecycleStage,
+    gender: &Gender,
+) -> Result<(), String> {
+    if to == LifecycleStage::Milker && *gender != Gender::Female {
+        return Err("Only does can be milkers".to_string());
+    }
+    match from {
+        None if to.is_final() => Err(format!(
+            "A goat must be in the herd before it is {}",
+            to.label().to_lowercase()
+        )),
+        None => Ok(()),
+        Some(from) if from.is_final() => Err(format!(
+            "The goat is {}; its lifecycle is over",
+            from.label().to_lowercase()
+        )),
+        Some(from) if from.next_stages().contains(&to) => Ok(()),
+        Some(from) => Err(format!(
+            "A goat cannot go from {} to {}",
+            from.label(),
+            to.label()
+        )),
+    }
+}
+
+/// A request to move a goat, identified by name, to `stage`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StageChange {
+    pub goat_name: String,
+    pub stage: LifecycleStage,
+    /// Date of the change (`YYYY-MM-DD`); defaults to today.
+    #[serde(default)]
+    pub changed_on: Option<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// A move recorded in a goat's lifecycle history.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StageTransition {
+    /// `None` when the goat entered the lifecycle.
+    pub from: Option<LifecycleStage>,
+    pub to: LifecycleStage,
+    /// `YYYY-MM-DD`.
+    pub changed_on: String,
+    pub note: Option<String>,
+}
+
+/// A goat in the pipeline, with the stages it may move to next.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PipelineGoat {
+    pub id: i64,
+    pub name: String,
+    pub next: Vec<LifecycleStage>,
+}
+
+/// The goats at one stage of the pipeline.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PipelineStage {
+    pub stage: LifecycleStage,
+    pub goats: Vec<PipelineGoat>,
+}
+
+/// The herd laid out by lifecycle stage.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct LifecyclePipeline {
+    /// One entry per stage, in pipeline order, including empty ones.
+    pub stages: Vec<PipelineStage>,
+    /// Goats not yet given a stage.
+    pub untracked: Vec<PipelineGoat>,
+}
@@ -0,0 +1,41 @@
+//! Milk records and lactation analytics.
+//!
+//! A lactation runs from a doe's kidding to her next one. Milkings within it
+//! are summed per day and summarised as a weekly lactation curve, with peak
+//! yield, days in milk and totals used to compare does.
+
+use serde::{Deserialize, Serialize};
+
+/// A single milking of a doe, in litres.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MilkRecord {
+    pub id: Option<i64>,
+    pub doe_name: String,
+    pub recorded_on: String,
+    pub yield_litres: f64,
+}
+
+/// Average daily yield during one week of a lactation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LactationPoint {
+    /// Week of lactation, starting at 1 for days in milk 0–6.
+    pub week: u32,
+    pub average_daily_yield: f64,
+}
+
+/// Summary of one lactation of a doe.
+///
+/// `kidded_on` is `None` when milk was recorded before any known kidding; the
+/// lactation is then measured from the first milking.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Lactation {
+    pub doe_name: String,
+    pub kidded_on: Option<String>,
+    /// Days from the start of the lactation to the latest milking.
+    pub days_in_milk: i64,
+    pub total_yield: f64,
+    pub peak_daily_yield: f64,
+    pub peak_day: i64,
+    pub average_daily_yield: f64,
+    pub curve: Vec<LactationPoint>,
+}
@@ -0,0 +1,148 @@
+//! Movements of goats off and between holdings.
+//!
+//! Many regional livestock regulations require a register of every animal
+//! movement: where from and where to (farm → market → buyer, or farm →
+//! farm), when it left and arrived, and how it was transported. A movement
+//! carries one or more goats, like a truck load, and the register can be
+//! queried per goat and per period of departure. Movements logged before a
+//! goat is sold appear in its traceability record (see
+//! `crate::traceability`). The register names each goat and its ear tag as
+//! they were when it moved, and keeps them after the goat is removed.
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// The kind of place a movement starts or ends at.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub enum PlaceKind {
+    Farm,
+    Market,
+    Buyer,
+}
+
+impl PlaceKind {
+    /// Every kind, in the order offered to users.
+    pub const ALL: [PlaceKind; 3] = [PlaceKind::Farm, PlaceKind::Market, PlaceKind::Buyer];
+
+    /// Converts a database string to `PlaceKind`.
+    pub fn from_str(s: &str) -> Result<PlaceKind, String> {
+        trace!("Parsing PlaceKind from '{}'", s);
+        match s {
+            "Farm" => Ok(PlaceKind::Farm),
+            "Market" => Ok(PlaceKind::Market),
+            "Buyer" => Ok(PlaceKind::Buyer),
+            other => {
+                debug!("Failed to parse PlaceKind enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `PlaceKind` to a database string.
+    pub fn to_str(kind: &PlaceKind) -> &str {
+        match kind {
+            PlaceKind::Farm => "Farm",
+            PlaceKind::Market => "Market",
+            PlaceKind::Buyer => "Buyer",
+        }
+    }
+}
+
+/// Where a movement starts or ends, e.g. a market by its name and town.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Place {
+    pub kind: PlaceKind,
+    pub name: String,
+}
+
+impl Place {
+    /// The place as shown in the register, e.g. "Pune market (Market)".
+    pub fn label(&self) -> String {
+        format!("{} ({})", self.name.trim(), PlaceKind::to_str(&self.kind))
+    }
+}
+
+/// One movement of goats, with its transport details.
+///
+/// Dates are `YYYY-MM-DD`; `arrived_on` is left out while the goats are
+/// on their way, or when they arrived the day they left.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AnimalMovement {
+    pub id: Option<i64>,
+    pub goats: Vec<String>,
+    /// Ear tags of `goats`, in the same order, as they were when the goats
+    /// moved. Set by the backend; ignored when logging a movement.
+    #[serde(default)]
+    pub tags: Vec<Option<String>>,
+    pub from: Place,
+    pub to: Place,
+    pub departed_on: String,
+    #[serde(default)]
+    pub arrived_on: Option<String>,
+    /// The haulier, or who drove the goats.
+    #[serde(default)]
+    pub transporter: Option<String>,
+    /// Registration number of the vehicle.
+    #[serde(default)]
+    pub vehicle: Option<String>,
+    /// Movement permit or health certificate number, where one is needed.
+    #[serde(default)]
+    pub permit_number: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+impl AnimalMovement {
+    /// Checks the movement names its goats and both places, and that it
+    /// goes somewhere else. Dates are checked by the backend.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.goats.iter().all(|g| g.trim().is_empty()) {
+            return Err("A movement needs at least one goat".into());
+        }
+        if self.from.name.trim().is_empty() || self.to.name.trim().is_empty() {
+            return Err("A movement needs the places it goes from and to".into());
+        }
+        let same_name = self
+            .from
+            .name
+            .trim()
+            .eq_ignore_ascii_case(self.to.name.trim());
+        if self.from.kind == self.to.kind && same_name {
+            return Err("A movement must go to another place".into());
+        }
+        Ok(())
+    }
+
+    /// The goats moved, with their ear tags where known, e.g. "Moti
+    /// (IN-0042), Rani".
+    pub fn goats_label(&self) -> String {
+        self.goats
+            .iter()
+            .enumerate()
+            .map(
+                |(i, goat)| match self.tags.get(i).and_then(Option::as_deref) {
+                    Some(tag) => format!("{} ({})", goat, tag),
+                    None => goat.clone(),
+                },
+            )
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The route and transport in one line, e.g. "Green Hills (Farm) →
+    /// Pune market (Market) by Patil Transport, MH12 AB 1234".
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{} → {}", self.from.label(), self.to.label());
+        let transport: Vec<&str> = [&self.transporter, &self.vehicle]
+            .into_iter()
+            .flatten()
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if !transport.is_empty() {
+            summary = format!("{} by {}", summary, transport.join(", "));
+        }
+        summary
+    }
+}
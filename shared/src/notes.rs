@@ -0,0 +1,68 @@
+//! Notes threads on goats.
+//!
+//! Workers leave dated observations on a goat ("limping on left hind leg",
+//! "ate well after deworming"), optionally with a photo, so whoever checks
+//! on it next can read what was seen before. A goat's notes are shown as a
+//! thread, oldest first.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Longest accepted note, in characters.
+pub const MAX_NOTE_CHARS: usize = 2000;
+
+/// Longest accepted author name, in characters.
+pub const MAX_AUTHOR_CHARS: usize = 80;
+
+/// Request to add a note to the goat called `goat_name`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NoteInput {
+    pub goat_name: String,
+    /// Who left the note, e.g. the worker's first name.
+    pub author: String,
+    pub text: String,
+}
+
+impl NoteInput {
+    /// Checks the note names its goat and author and has text, within the
+    /// length limits.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.goat_name.trim().is_empty() {
+            return Err("Goat name must not be empty".to_string());
+        }
+        let author = self.author.trim().chars().count();
+        if author == 0 {
+            return Err("Author must not be empty".to_string());
+        }
+        if author > MAX_AUTHOR_CHARS {
+            return Err(format!(
+                "Author must be at most {} characters",
+                MAX_AUTHOR_CHARS
+            ));
+        }
+        let text = self.text.trim().chars().count();
+        if text == 0 {
+            return Err("Note must not be empty".to_string());
+        }
+        if text > MAX_NOTE_CHARS {
+            return Err(format!(
+                "Note must be at most {} characters, got {}",
+                MAX_NOTE_CHARS, text
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A stored note. The photo itself is fetched separately, from
+/// `/notes/{id}/photo`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GoatNote {
+    pub id: i64,
+    pub goat_name: String,
+    pub author: String,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+    /// True if a photo is attached.
+    pub has_photo: bool,
+}
@@ -0,0 +1,170 @@
+//! Farm workers and the notifications they receive.
+//!
+//! Writing `@Name` in a goat's note (see `crate::notes`) mentions the worker
+//! of that name. Each mention becomes an in-app `Notification` for them,
+//! linking to the goat, and is also sent by email or SMS to their contact
+//! if they chose that `NotifyChannel`. Alert rules firing (see
+//! `crate::alerts`) notify workers in the app as well, without a goat.
+
+use crate::alerts::ALERTS_LINK;
+use crate::search::SearchKind;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// Longest accepted worker name, in characters.
+pub const MAX_WORKER_NAME_CHARS: usize = 80;
+
+/// How a worker is told about mentions besides the in-app list.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "PascalCase")]
+pub enum NotifyChannel {
+    /// In the dashboard only.
+    #[default]
+    App,
+    /// Also by email to the worker's contact address.
+    Email,
+    /// Also by SMS to the worker's contact number.
+    Sms,
+}
+
+impl NotifyChannel {
+    /// Converts a database string to `NotifyChannel`.
+    pub fn from_str(s: &str) -> Result<NotifyChannel, String> {
+        trace!("Parsing NotifyChannel from '{}'", s);
+        match s {
+            "App" => Ok(NotifyChannel::App),
+            "Email" => Ok(NotifyChannel::Email),
+            "Sms" => Ok(NotifyChannel::Sms),
+            other => {
+                debug!("Failed to parse NotifyChannel enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `NotifyChannel` to a database string.
+    pub fn to_str(channel: &NotifyChannel) -> &str {
+        match channel {
+            NotifyChannel::App => "App",
+            NotifyChannel::Email => "Email",
+            NotifyChannel::Sms => "Sms",
+        }
+    }
+}
+
+/// A farm worker who can be mentioned in notes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Worker {
+    pub id: Option<i64>,
+    /// Name written after `@` to mention the worker, e.g. `Asha`.
+    pub name: String,
+    pub role: Option<String>,
+    /// Email address or phone number, depending on `notify_by`.
+    pub contact: Option<String>,
+    pub notify_by: NotifyChannel,
+}
+
+impl Worker {
+    /// Checks the worker has a name that can be mentioned, and a contact
+    /// fitting their channel when notified by email or SMS.
+    pub fn validate(&self) -> Result<(), String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Worker name must not be empty".to_string());
+        }
+        if name.chars().count() > MAX_WORKER_NAME_CHARS {
+            return Err(format!(
+                "Worker name must be at most {} characters",
+                MAX_WORKER_NAME_CHARS
+            ));
+        }
+        if name.contains('@') {
+            return Err(format!("Worker name must not contain '@', got '{}'", name));
+        }
+        let contact = self.contact.as_deref().map(str::trim).unwrap_or_default();
+        match self.notify_by {
+            NotifyChannel::App => {}
+            NotifyChannel::Email if !contact.contains('@') => {
+                return Err(format!(
+                    "{} is notified by email but has no email address",
+                    name
+                ));
+            }
+            NotifyChannel::Sms if contact.chars().filter(char::is_ascii_digit).count() < 6 => {
+                return Err(format!(
+                    "{} is notified by SMS but has no phone number",
+                    name
+                ));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Something a worker should look at, such as a mention in a goat's note
+/// or an alert rule firing.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub id: i64,
+    pub worker_name: String,
+    /// The goat the notification is about; `None` for alerts.
+    pub goat_name: Option<String>,
+    /// The note the worker was mentioned in, if it still exists.
+    pub note_id: Option<i64>,
+    pub message: String,
+    /// Where the dashboard opens it, see `notification_link`.
+    pub link: String,
+    pub created_at: DateTime<Utc>,
+    pub read: bool,
+}
+
+/// Deep link to a goat's row in the dashboard, e.g. `#goat-12`; the same
+/// anchor the search box jumps to.
+pub fn goat_link(goat_id: i64) -> String {
+    format!("#{}-{}", SearchKind::Goat.anchor_prefix(), goat_id)
+}
+
+/// Deep link for a notification about goat `goat_id`, or to the alert
+/// rules for a notification without a goat.
+pub fn notification_link(goat_id: Option<i64>) -> String {
+    goat_id.map_or_else(|| ALERTS_LINK.to_string(), goat_link)
+}
+
+/// The `names` mentioned in `text`, each once, in order of first mention.
+///
+/// A mention is `@` followed by the name, in any case, and not followed by
+/// another letter or digit, so `@Asha Devi` mentions "Asha Devi" (the
+/// longest matching name wins) and `@Ashastra` mentions nobody. An `@`
+/// inside a word, as in an email address, is no mention.
+pub fn mentioned<'a>(text: &str, names: &'a [String]) -> Vec<&'a str> {
+    let mut by_length: Vec<&String> = names.iter().filter(|n| !n.trim().is_empty()).collect();
+    by_length.sort_by_key(|name| std::cmp::Reverse(name.chars().count()));
+
+    let mut found: Vec<&str> = Vec::new();
+    let mut previous: Option<char> = None;
+    for (at, c) in text.char_indices() {
+        let starts_mention = c == '@' && !previous.is_some_and(char::is_alphanumeric);
+        previous = Some(c);
+        if !starts_mention {
+            continue;
+        }
+        let rest = &text[at + 1..];
+        let hit = by_length.iter().find(|name| {
+            let mut chars = rest.chars();
+            let prefix_matches = name.trim().chars().all(|n| {
+                chars
+                    .next()
+                    .is_some_and(|r| r.to_lowercase().eq(n.to_lowercase()))
+            });
+            prefix_matches && !chars.next().is_some_and(char::is_alphanumeric)
+        });
+        if let Some(name) = hit
+            && !found.contains(&name.as_str())
+        {
+            found.push(name.as_str());
+        }
+    }
+    found
+}
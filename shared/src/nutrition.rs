@@ -0,0 +1,499 @@
+//! Daily nutrient requirements and feed plan checks.
+//!
+//! A goat's daily need for dry matter (DM), crude protein (CP) and
+//! metabolisable energy (ME) follows from its weight, stage and breed, with
+//! the usual feeding-standard approximations: maintenance scales with
+//! metabolic weight (kg^0.75), and growth, late pregnancy and each litre of
+//! milk add to it. Breed sets the expected daily gain and milk yield.
+//!
+//! A feed plan is the goat's diet written as feeds with amounts, e.g.
+//! "hay 1.2 kg, concentrate 300 g". Each feed is looked up in `FEEDS` to
+//! work out what the plan supplies. The figures are guides for spotting
+//! shortfalls, not a formulated ration.
+
+use crate::breeds::{BreedPurpose, breed_profile, breed_purpose};
+use crate::lifecycle::LifecycleStage;
+use crate::{Breed, GoatParams};
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+
+/// Maintenance energy, in MJ ME per kg of metabolic weight.
+const MAINTENANCE_ME: f64 = 0.45;
+
+/// Maintenance protein, in g CP per kg of metabolic weight.
+const MAINTENANCE_CP: f64 = 5.0;
+
+/// Energy and protein per kg of weight gained, in MJ ME and g CP.
+const GAIN_ME: f64 = 23.0;
+const GAIN_CP: f64 = 400.0;
+
+/// Energy and protein per litre of milk, in MJ ME and g CP.
+const MILK_ME: f64 = 5.0;
+const MILK_CP: f64 = 70.0;
+
+/// Energy and protein in late pregnancy, as multiples of maintenance.
+const PREGNANCY_ME: f64 = 1.4;
+const PREGNANCY_CP: f64 = 1.6;
+
+/// Daily gain of growing goats, in kg: meat breeds and the rest.
+const MEAT_GAIN_KG: f64 = 0.07;
+const GAIN_KG: f64 = 0.05;
+
+/// Daily milk of a doe whose breed has no profile, in litres.
+const DEFAULT_MILK_LITRES: f64 = 1.0;
+
+/// A plan supplying less than this share of a need is short of it.
+pub const DEFICIT_SHARE: f64 = 0.9;
+
+/// What a goat's ration has to cover besides maintenance.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum NutritionStage {
+    /// Adults that are neither pregnant nor in milk, and bucks.
+    #[default]
+    Maintenance,
+    Growing,
+    /// The last weeks of pregnancy, when the kids grow fastest.
+    Pregnant,
+    Lactating,
+}
+
+impl NutritionStage {
+    /// Every stage, in display order.
+    pub const ALL: [NutritionStage; 4] = [
+        NutritionStage::Maintenance,
+        NutritionStage::Growing,
+        NutritionStage::Pregnant,
+        NutritionStage::Lactating,
+    ];
+
+    /// Parses a stage name, e.g. "Lactating".
+    pub fn from_str(s: &str) -> Result<NutritionStage, String> {
+        NutritionStage::ALL
+            .into_iter()
+            .find(|stage| NutritionStage::to_str(stage) == s)
+            .ok_or_else(|| s.to_string())
+    }
+
+    /// The stage's name, as `from_str` reads it.
+    pub fn to_str(stage: &NutritionStage) -> &'static str {
+        match stage {
+            NutritionStage::Maintenance => "Maintenance",
+            NutritionStage::Growing => "Growing",
+            NutritionStage::Pregnant => "Pregnant",
+            NutritionStage::Lactating => "Lactating",
+        }
+    }
+
+    /// The stage a goat at lifecycle stage `stage` usually feeds for.
+    /// Pregnancy is not tracked by the lifecycle, so it is never suggested.
+    pub fn for_lifecycle(stage: Option<LifecycleStage>) -> NutritionStage {
+        match stage {
+            Some(LifecycleStage::Kid | LifecycleStage::Grower) => NutritionStage::Growing,
+            Some(LifecycleStage::Milker) => NutritionStage::Lactating,
+            _ => NutritionStage::Maintenance,
+        }
+    }
+}
+
+/// Daily amounts of the nutrients a ration is checked for.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Nutrients {
+    pub dry_matter_kg: f64,
+    pub crude_protein_g: f64,
+    pub energy_mj: f64,
+}
+
+impl std::ops::Add for Nutrients {
+    type Output = Nutrients;
+
+    fn add(self, other: Nutrients) -> Nutrients {
+        Nutrients {
+            dry_matter_kg: self.dry_matter_kg + other.dry_matter_kg,
+            crude_protein_g: self.crude_protein_g + other.crude_protein_g,
+            energy_mj: self.energy_mj + other.energy_mj,
+        }
+    }
+}
+
+/// One of the nutrients in `Nutrients`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Nutrient {
+    DryMatter,
+    CrudeProtein,
+    Energy,
+}
+
+impl Nutrient {
+    /// Every nutrient, in display order.
+    pub const ALL: [Nutrient; 3] = [
+        Nutrient::DryMatter,
+        Nutrient::CrudeProtein,
+        Nutrient::Energy,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Nutrient::DryMatter => "Dry matter",
+            Nutrient::CrudeProtein => "Crude protein",
+            Nutrient::Energy => "Energy",
+        }
+    }
+
+    /// Unit the nutrient is measured in, e.g. "MJ".
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Nutrient::DryMatter => "kg",
+            Nutrient::CrudeProtein => "g",
+            Nutrient::Energy => "MJ",
+        }
+    }
+
+    /// The amount of this nutrient in `nutrients`.
+    pub fn amount(&self, nutrients: &Nutrients) -> f64 {
+        match self {
+            Nutrient::DryMatter => nutrients.dry_matter_kg,
+            Nutrient::CrudeProtein => nutrients.crude_protein_g,
+            Nutrient::Energy => nutrients.energy_mj,
+        }
+    }
+}
+
+/// The daily need of a goat weighing `weight_kg` at `stage`.
+pub fn daily_requirement(weight_kg: f64, stage: NutritionStage, breed: &Breed) -> Nutrients {
+    let weight_kg = weight_kg.max(0.0);
+    let metabolic = weight_kg.powf(0.75);
+    let (mut energy_mj, mut crude_protein_g) =
+        (MAINTENANCE_ME * metabolic, MAINTENANCE_CP * metabolic);
+    // Appetite, as a share of body weight
+    let intake = match stage {
+        NutritionStage::Maintenance => 0.025,
+        NutritionStage::Growing => {
+            let gain = match breed_purpose(breed) {
+                Some(BreedPurpose::Meat) => MEAT_GAIN_KG,
+                _ => GAIN_KG,
+            };
+            energy_mj += GAIN_ME * gain;
+            crude_protein_g += GAIN_CP * gain;
+            0.035
+        }
+        NutritionStage::Pregnant => {
+            energy_mj *= PREGNANCY_ME;
+            crude_protein_g *= PREGNANCY_CP;
+            0.03
+        }
+        NutritionStage::Lactating => {
+            let litres =
+                breed_profile(breed).map_or(DEFAULT_MILK_LITRES, |p| p.milk_litres_per_day);
+            energy_mj += MILK_ME * litres;
+            crude_protein_g += MILK_CP * litres;
+            0.04
+        }
+    };
+    trace!(weight_kg, ?stage, energy_mj, "Daily requirement");
+    Nutrients {
+        dry_matter_kg: intake * weight_kg,
+        crude_protein_g,
+        energy_mj,
+    }
+}
+
+/// Composition of a common feed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeedProfile {
+    pub name: &'static str,
+    /// Words that name the feed in a diet, lowercase.
+    pub aliases: &'static [&'static str],
+    /// Share of the fresh feed that is dry matter.
+    pub dry_matter: f64,
+    /// Share of the dry matter that is crude protein.
+    pub crude_protein: f64,
+    /// MJ ME per kg of dry matter.
+    pub energy_mj: f64,
+}
+
+/// Feeds a plan can name, by typical composition.
+pub const FEEDS: [FeedProfile; 11] = [
+    FeedProfile {
+        name: "Lucerne",
+        aliases: &["lucerne", "alfalfa"],
+        dry_matter: 0.89,
+        crude_protein: 0.18,
+        energy_mj: 8.5,
+    },
+    FeedProfile {
+        name: "Berseem",
+        aliases: &["berseem", "clover"],
+        dry_matter: 0.18,
+        crude_protein: 0.18,
+        energy_mj: 9.5,
+    },
+    FeedProfile {
+        name: "Green fodder",
+        aliases: &["green", "grass", "fodder", "pasture"],
+        dry_matter: 0.22,
+        crude_protein: 0.10,
+        energy_mj: 9.0,
+    },
+    FeedProfile {
+        name: "Tree leaves",
+        aliases: &["leaves", "browse", "loppings"],
+        dry_matter: 0.35,
+        crude_protein: 0.15,
+        energy_mj: 8.5,
+    },
+    FeedProfile {
+        name: "Hay",
+        aliases: &["hay"],
+        dry_matter: 0.88,
+        crude_protein: 0.08,
+        energy_mj: 8.0,
+    },
+    FeedProfile {
+        name: "Straw",
+        aliases: &["straw", "bhusa", "stover"],
+        dry_matter: 0.90,
+        crude_protein: 0.035,
+        energy_mj: 6.0,
+    },
+    FeedProfile {
+        name: "Concentrate",
+        aliases: &["concentrate", "pellets", "mix", "ration"],
+        dry_matter: 0.90,
+        crude_protein: 0.16,
+        energy_mj: 11.5,
+    },
+    FeedProfile {
+        name: "Maize",
+        aliases: &["maize", "corn"],
+        dry_matter: 0.88,
+        crude_protein: 0.09,
+        energy_mj: 13.0,
+    },
+    FeedProfile {
+        name: "Wheat bran",
+        aliases: &["bran", "chokar"],
+        dry_matter: 0.89,
+        crude_protein: 0.16,
+        energy_mj: 10.0,
+    },
+    FeedProfile {
+        name: "Soybean meal",
+        aliases: &["soybean", "soya"],
+        dry_matter: 0.89,
+        crude_protein: 0.48,
+        energy_mj: 12.5,
+    },
+    FeedProfile {
+        name: "Oilseed cake",
+        aliases: &["cake", "groundnut", "mustard", "khal"],
+        dry_matter: 0.91,
+        crude_protein: 0.40,
+        energy_mj: 12.0,
+    },
+];
+
+/// The feed in `FEEDS` that `name` mentions, e.g. "lucerne hay" is
+/// lucerne. Feeds earlier in `FEEDS` win.
+pub fn find_feed(name: &str) -> Option<&'static FeedProfile> {
+    let name = name.to_lowercase();
+    let words: Vec<&str> = name.split(|c: char| !c.is_alphabetic()).collect();
+    FEEDS
+        .iter()
+        .find(|feed| feed.aliases.iter().any(|alias| words.contains(alias)))
+}
+
+/// Splits one feed of a plan, e.g. "hay 1.2 kg" or "300g concentrate",
+/// into its as-fed amount in kg and the rest of the text. A bare number is
+/// taken as kg.
+fn split_amount(part: &str) -> Option<(f64, String)> {
+    let tokens: Vec<String> = part.split_whitespace().map(str::to_lowercase).collect();
+    let mut amount = None;
+    let mut name = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        let digits = token
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(token.len());
+        let value = token[..digits]
+            .parse::<f64>()
+            .ok()
+            .filter(|_| amount.is_none());
+        let mut unit = &token[digits..];
+        if let Some(value) = value {
+            if unit.is_empty() && matches!(tokens.get(i + 1).map(String::as_str), Some("kg" | "g"))
+            {
+                i += 1;
+                unit = &tokens[i];
+            }
+            match unit {
+                "" | "kg" => amount = Some(value),
+                "g" => amount = Some(value / 1000.0),
+                _ => name.push(token.clone()),
+            }
+        } else {
+            name.push(token.clone());
+        }
+        i += 1;
+    }
+    amount.map(|amount| (amount, name.join(" ")))
+}
+
+/// What the feed plan `diet` supplies a day, e.g. for
+/// "hay 1.2 kg, concentrate 300 g". Feeds are separated by commas,
+/// semicolons or "+".
+///
+/// # Errors
+/// - Lists every feed without an amount or not found in `FEEDS`.
+pub fn ration_supply(diet: &str) -> Result<Nutrients, String> {
+    let mut supply = Nutrients::default();
+    let mut problems = Vec::new();
+    for part in diet
+        .split([',', ';', '+'])
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+    {
+        let Some((amount, name)) = split_amount(part) else {
+            problems.push(format!("'{}' has no amount", part));
+            continue;
+        };
+        let Some(feed) = find_feed(&name) else {
+            problems.push(format!("'{}' is not a known feed", name));
+            continue;
+        };
+        let dry_matter_kg = amount * feed.dry_matter;
+        supply = supply
+            + Nutrients {
+                dry_matter_kg,
+                crude_protein_g: dry_matter_kg * feed.crude_protein * 1000.0,
+                energy_mj: dry_matter_kg * feed.energy_mj,
+            };
+    }
+    if !problems.is_empty() {
+        return Err(problems.join("; "));
+    }
+    if supply == Nutrients::default() {
+        return Err("The diet lists no feeds".to_string());
+    }
+    Ok(supply)
+}
+
+/// A goat's daily need beside what its feed plan supplies.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NutritionCheck {
+    pub stage: NutritionStage,
+    pub requirement: Nutrients,
+    /// The plan's supply, or why the diet could not be read as one.
+    pub supply: Result<Nutrients, String>,
+}
+
+impl NutritionCheck {
+    /// The nutrients the plan supplies less than `DEFICIT_SHARE` of, with
+    /// the shortfall; empty if the plan could not be read.
+    pub fn deficits(&self) -> Vec<(Nutrient, f64)> {
+        let Ok(supply) = &self.supply else {
+            return Vec::new();
+        };
+        Nutrient::ALL
+            .into_iter()
+            .filter_map(|nutrient| {
+                let needed = nutrient.amount(&self.requirement);
+                let supplied = nutrient.amount(supply);
+                (supplied < needed * DEFICIT_SHARE).then_some((nutrient, needed - supplied))
+            })
+            .collect()
+    }
+}
+
+/// Checks `goat`'s diet against its need at `stage`.
+pub fn check_nutrition(goat: &GoatParams, stage: NutritionStage) -> NutritionCheck {
+    NutritionCheck {
+        stage,
+        requirement: daily_requirement(goat.weight, stage, &goat.breed),
+        supply: ration_supply(&goat.diet),
+    }
+}
+
+/// The daily needs a least-cost ration is formulated for, and how many
+/// goats share the feed in stock for how long.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RationRequest {
+    pub dry_matter_kg: f64,
+    pub crude_protein_g: f64,
+    pub energy_mj: f64,
+    /// Goats fed the ration.
+    pub goats: u32,
+    /// Days the stock has to last them.
+    pub days: u32,
+}
+
+impl RationRequest {
+    /// A request meeting `target` for `goats` goats over `days` days.
+    pub fn new(target: Nutrients, goats: u32, days: u32) -> RationRequest {
+        RationRequest {
+            dry_matter_kg: target.dry_matter_kg,
+            crude_protein_g: target.crude_protein_g,
+            energy_mj: target.energy_mj,
+            goats,
+            days,
+        }
+    }
+
+    pub fn target(&self) -> Nutrients {
+        Nutrients {
+            dry_matter_kg: self.dry_matter_kg,
+            crude_protein_g: self.crude_protein_g,
+            energy_mj: self.energy_mj,
+        }
+    }
+
+    /// Checks the targets are positive and at least one goat is fed for at
+    /// least one day.
+    pub fn validate(&self) -> Result<(), String> {
+        let target = self.target();
+        for nutrient in Nutrient::ALL {
+            let amount = nutrient.amount(&target);
+            if !amount.is_finite() || amount <= 0.0 {
+                return Err(format!("{} target must be positive", nutrient.label()));
+            }
+        }
+        if self.goats == 0 || self.days == 0 {
+            return Err("A ration must feed at least one goat for one day".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// One feed in a formulated ration.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RationItem {
+    /// The inventory item fed.
+    pub feed: String,
+    /// As-fed amount per goat.
+    pub kg_per_day: f64,
+    pub cost_per_day: f64,
+}
+
+/// The cheapest daily ration per goat meeting a `RationRequest` from the
+/// feed in stock.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RationPlan {
+    pub items: Vec<RationItem>,
+    pub cost_per_day: f64,
+    pub target: Nutrients,
+    pub supply: Nutrients,
+    /// Feed in stock that could not be used, with why.
+    pub skipped: Vec<String>,
+}
+
+impl RationPlan {
+    /// The ration as a feed plan, e.g. "Lucerne hay 1.2 kg, Pellets 0.35 kg",
+    /// which `ration_supply` reads back.
+    pub fn diet(&self) -> String {
+        self.items
+            .iter()
+            .map(|item| format!("{} {} kg", item.feed, item.kg_per_day))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
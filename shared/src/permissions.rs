@@ -0,0 +1,188 @@
+//! Permissions of workers' roles.
+//!
+//! Beyond the free-text role on each worker, the owner sets per role what
+//! its workers may do in each module of the dashboard: view, edit (add and
+//! change) or delete. Requests made in a session a worker signed in to with
+//! an OAuth provider (see `shared::sessions`) are checked against the
+//! worker's role; every other request is the owner's and may do anything.
+//! A role the owner never restricted keeps every permission; a worker
+//! without a role has none.
+
+use serde::{Deserialize, Serialize};
+
+/// A part of the dashboard permissions are granted for.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PermissionModule {
+    /// Goat records, notes, attachments, weighing and the trash.
+    Goats,
+    /// Health records, reminders and insurance.
+    Health,
+    /// Breeding and the breed catalog.
+    Breeding,
+    /// Growth and milk records.
+    Production,
+    /// Tasks, automation and alert rules, and the calendar.
+    Tasks,
+    /// Feed and supplies.
+    Inventory,
+    /// Transactions, budgets, pricing and reports.
+    Finance,
+    /// Pens, paddocks, GPS and barn sensors.
+    Grazing,
+    /// Workers and their notifications.
+    Workers,
+    /// Farm settings, API keys, access tokens, archives, retention and
+    /// background jobs.
+    Settings,
+}
+
+impl PermissionModule {
+    /// Every module, in display order.
+    pub const ALL: [PermissionModule; 10] = [
+        PermissionModule::Goats,
+        PermissionModule::Health,
+        PermissionModule::Breeding,
+        PermissionModule::Production,
+        PermissionModule::Tasks,
+        PermissionModule::Inventory,
+        PermissionModule::Finance,
+        PermissionModule::Grazing,
+        PermissionModule::Workers,
+        PermissionModule::Settings,
+    ];
+
+    /// Human-readable name, e.g. "Pens & grazing".
+    pub fn label(&self) -> &'static str {
+        match self {
+            PermissionModule::Goats => "Goats",
+            PermissionModule::Health => "Health",
+            PermissionModule::Breeding => "Breeding",
+            PermissionModule::Production => "Growth & milk",
+            PermissionModule::Tasks => "Tasks",
+            PermissionModule::Inventory => "Inventory",
+            PermissionModule::Finance => "Finance",
+            PermissionModule::Grazing => "Pens & grazing",
+            PermissionModule::Workers => "Workers",
+            PermissionModule::Settings => "Settings",
+        }
+    }
+}
+
+/// What a permission allows within a module.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PermissionAction {
+    /// Reading (`GET`).
+    View,
+    /// Adding and changing (`POST`, `PUT`, `PATCH`).
+    Edit,
+    /// Deleting (`DELETE`).
+    Delete,
+}
+
+impl PermissionAction {
+    /// Every action, in display order.
+    pub const ALL: [PermissionAction; 3] = [
+        PermissionAction::View,
+        PermissionAction::Edit,
+        PermissionAction::Delete,
+    ];
+
+    /// Human-readable name.
+    pub fn label(&self) -> &'static str {
+        match self {
+            PermissionAction::View => "View",
+            PermissionAction::Edit => "Edit",
+            PermissionAction::Delete => "Delete",
+        }
+    }
+}
+
+/// One cell of the permissions matrix.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Permission {
+    pub module: PermissionModule,
+    pub action: PermissionAction,
+}
+
+/// Every cell of the matrix, module by module.
+pub fn all_permissions() -> Vec<Permission> {
+    PermissionModule::ALL
+        .into_iter()
+        .flat_map(|module| {
+            PermissionAction::ALL
+                .into_iter()
+                .map(move |action| Permission { module, action })
+        })
+        .collect()
+}
+
+/// What the workers with `role` may do.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RolePermissions {
+    pub role: String,
+    pub granted: Vec<Permission>,
+}
+
+impl RolePermissions {
+    /// A role that may do anything, as every role the owner never restricted.
+    pub fn unrestricted(role: &str) -> Self {
+        RolePermissions {
+            role: role.to_string(),
+            granted: all_permissions(),
+        }
+    }
+
+    /// Whether the role may do `action` in `module`.
+    pub fn allows(&self, module: PermissionModule, action: PermissionAction) -> bool {
+        self.granted.contains(&Permission { module, action })
+    }
+
+    /// Checks the role is named and can see every module it may change.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.role.trim().is_empty() {
+            return Err("Permissions need a role".to_string());
+        }
+        for permission in &self.granted {
+            if permission.action != PermissionAction::View
+                && !self.allows(permission.module, PermissionAction::View)
+            {
+                return Err(format!(
+                    "{} may only be changed by roles that can view it",
+                    permission.module.label()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What the requesting session may do. `worker` and `role` are set when a
+/// worker signed in to it; otherwise it is the owner's, with every
+/// permission.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MyPermissions {
+    pub worker: Option<String>,
+    pub role: Option<String>,
+    pub granted: Vec<Permission>,
+}
+
+impl MyPermissions {
+    /// The owner's permissions.
+    pub fn owner() -> Self {
+        MyPermissions {
+            worker: None,
+            role: None,
+            granted: all_permissions(),
+        }
+    }
+
+    /// Whether this is the owner, who alone edits the permissions.
+    pub fn is_owner(&self) -> bool {
+        self.worker.is_none()
+    }
+
+    /// Whether the session may do `action` in `module`.
+    pub fn allows(&self, module: PermissionModule, action: PermissionAction) -> bool {
+        self.granted.contains(&Permission { module, action })
+    }
+}
@@ -0,0 +1,179 @@
+//! Physical traits that help tell goats apart: horns, coat color and
+//! distinguishing marks.
+//!
+//! Horns and coat color are closed lists so forms can offer them as
+//! dropdowns and the herd can be filtered by them; anything else that
+//! identifies the animal goes in the free-text marks.
+
+use crate::GoatParams;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// Whether a goat has horns, and if not, why.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HornStatus {
+    Horned,
+    /// Naturally hornless.
+    Polled,
+    /// Horn buds removed as a kid.
+    Disbudded,
+    /// Small loose horn growths after disbudding.
+    Scurred,
+}
+
+impl HornStatus {
+    pub const ALL: [HornStatus; 4] = [
+        HornStatus::Horned,
+        HornStatus::Polled,
+        HornStatus::Disbudded,
+        HornStatus::Scurred,
+    ];
+
+    /// Converts a database string to `HornStatus`.
+    pub fn from_str(s: &str) -> Result<HornStatus, String> {
+        trace!("Parsing HornStatus from '{}'", s);
+        match s {
+            "Horned" => Ok(HornStatus::Horned),
+            "Polled" => Ok(HornStatus::Polled),
+            "Disbudded" => Ok(HornStatus::Disbudded),
+            "Scurred" => Ok(HornStatus::Scurred),
+            other => {
+                debug!("Failed to parse HornStatus enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `HornStatus` to a database string.
+    pub fn to_str(horns: &HornStatus) -> &str {
+        match horns {
+            HornStatus::Horned => "Horned",
+            HornStatus::Polled => "Polled",
+            HornStatus::Disbudded => "Disbudded",
+            HornStatus::Scurred => "Scurred",
+        }
+    }
+
+    /// Human readable name, as shown in dropdowns.
+    pub fn label(&self) -> &'static str {
+        match self {
+            HornStatus::Horned => "Horned",
+            HornStatus::Polled => "Polled (naturally hornless)",
+            HornStatus::Disbudded => "Disbudded",
+            HornStatus::Scurred => "Scurred",
+        }
+    }
+}
+
+/// Main coat color of a goat.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoatColor {
+    White,
+    Black,
+    Brown,
+    Tan,
+    Red,
+    Grey,
+    /// Spots or patches of a second color.
+    Spotted,
+    /// No single main color.
+    Mixed,
+}
+
+impl CoatColor {
+    pub const ALL: [CoatColor; 8] = [
+        CoatColor::White,
+        CoatColor::Black,
+        CoatColor::Brown,
+        CoatColor::Tan,
+        CoatColor::Red,
+        CoatColor::Grey,
+        CoatColor::Spotted,
+        CoatColor::Mixed,
+    ];
+
+    /// Converts a database string to `CoatColor`.
+    pub fn from_str(s: &str) -> Result<CoatColor, String> {
+        trace!("Parsing CoatColor from '{}'", s);
+        match s {
+            "White" => Ok(CoatColor::White),
+            "Black" => Ok(CoatColor::Black),
+            "Brown" => Ok(CoatColor::Brown),
+            "Tan" => Ok(CoatColor::Tan),
+            "Red" => Ok(CoatColor::Red),
+            "Grey" => Ok(CoatColor::Grey),
+            "Spotted" => Ok(CoatColor::Spotted),
+            "Mixed" => Ok(CoatColor::Mixed),
+            other => {
+                debug!("Failed to parse CoatColor enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `CoatColor` to a database string.
+    pub fn to_str(color: &CoatColor) -> &'static str {
+        match color {
+            CoatColor::White => "White",
+            CoatColor::Black => "Black",
+            CoatColor::Brown => "Brown",
+            CoatColor::Tan => "Tan",
+            CoatColor::Red => "Red",
+            CoatColor::Grey => "Grey",
+            CoatColor::Spotted => "Spotted",
+            CoatColor::Mixed => "Mixed",
+        }
+    }
+
+    /// Human readable name, as shown in dropdowns.
+    pub fn label(&self) -> &'static str {
+        CoatColor::to_str(self)
+    }
+}
+
+/// Narrows a goat list to the given horn status and coat color; unset
+/// criteria match every goat, including those with the trait unrecorded.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TraitFilter {
+    pub horns: Option<HornStatus>,
+    pub coat_color: Option<CoatColor>,
+}
+
+impl TraitFilter {
+    /// True if no criterion is set.
+    pub fn is_empty(&self) -> bool {
+        self.horns.is_none() && self.coat_color.is_none()
+    }
+
+    /// Whether `goat` has every trait set in the filter.
+    pub fn matches(&self, goat: &GoatParams) -> bool {
+        self.horns.is_none_or(|h| goat.horns == Some(h))
+            && self.coat_color.is_none_or(|c| goat.coat_color == Some(c))
+    }
+}
+
+/// A one-line description of `goat`'s recorded traits for telling it
+/// apart, e.g. "Brown, polled; white blaze on forehead". Empty if none are
+/// recorded.
+pub fn describe(goat: &GoatParams) -> String {
+    let mut looks: Vec<String> = Vec::new();
+    if let Some(color) = &goat.coat_color {
+        looks.push(CoatColor::to_str(color).to_string());
+    }
+    if let Some(horns) = &goat.horns {
+        let horns = HornStatus::to_str(horns);
+        looks.push(if looks.is_empty() {
+            horns.to_string()
+        } else {
+            horns.to_lowercase()
+        });
+    }
+    let mut description = looks.join(", ");
+    if let Some(marks) = goat.marks.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+        if !description.is_empty() {
+            description.push_str("; ");
+        }
+        description.push_str(marks);
+    }
+    description
+}
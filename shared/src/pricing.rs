@@ -0,0 +1,468 @@
+//! Pricing formulas for valuing goats.
+//!
+//! Farms price stock differently — by live weight, by breed, with a floor
+//! price — so the valuation is a user-defined arithmetic formula such as
+//! `weight * rate_per_kg * breed_factor`. Formulas support numbers, named
+//! variables, `+ - * /`, parentheses, unary minus and the functions `min`,
+//! `max` and `round`. The same evaluator runs in the backend when the herd
+//! is revalued and in the frontend for live previews, so both always agree.
+
+use crate::{Breed, GoatParams};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::{debug, trace};
+
+/// Variables every formula can use, taken from the goat being priced:
+/// live weight in kg, its breed's factor (1 unless configured), purchase
+/// cost and number of offspring.
+pub const GOAT_VARIABLES: [&str; 4] = ["weight", "breed_factor", "cost", "offspring"];
+
+/// The formula suggested to farms setting up pricing.
+pub const DEFAULT_FORMULA: &str = "weight * rate_per_kg * breed_factor";
+
+/// Longest formula accepted, in characters. Also bounds how long a chain
+/// of operators can be, and so how deep evaluation recurses.
+pub const MAX_FORMULA_LEN: usize = 500;
+
+/// How deep parentheses, function calls and unary minus may nest.
+pub const MAX_FORMULA_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Function {
+    Min,
+    Max,
+    Round,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Function> {
+        match name {
+            "min" => Some(Function::Min),
+            "max" => Some(Function::Max),
+            "round" => Some(Function::Round),
+            _ => None,
+        }
+    }
+
+    /// Whether `count` arguments are accepted.
+    fn accepts(self, count: usize) -> bool {
+        match self {
+            Function::Min | Function::Max => count >= 2,
+            Function::Round => count == 1,
+        }
+    }
+}
+
+/// A parsed formula.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Variable(String),
+    Negate(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    Open,
+    Close,
+    Comma,
+}
+
+/// Splits `src` into tokens, each with its character position.
+fn tokenize(src: &str) -> Result<Vec<(usize, Token)>, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '0'..='9' | '.' => {
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let value = text
+                    .parse()
+                    .map_err(|_| format!("Invalid number '{}' at position {}", text, start + 1))?;
+                tokens.push((start, Token::Number(value)));
+                continue;
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push((start, Token::Ident(chars[start..i].iter().collect())));
+                continue;
+            }
+            '+' | '-' | '*' | '/' => Token::Op(c),
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            other => {
+                return Err(format!("Unexpected '{}' at position {}", other, start + 1));
+            }
+        };
+        tokens.push((start, token));
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent parser over the token list.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    len: usize,
+    /// Nesting level of the expression being parsed.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(_, t)| t.clone());
+        self.pos += 1;
+        token
+    }
+
+    /// Error for the token at the current position, or for the end of input.
+    fn unexpected(&self) -> String {
+        match self.tokens.get(self.pos) {
+            Some((at, token)) => {
+                let text = match token {
+                    Token::Number(n) => n.to_string(),
+                    Token::Ident(name) => name.clone(),
+                    Token::Op(op) => op.to_string(),
+                    Token::Open => "(".to_string(),
+                    Token::Close => ")".to_string(),
+                    Token::Comma => ",".to_string(),
+                };
+                format!("Unexpected '{}' at position {}", text, at + 1)
+            }
+            None => format!("Formula ends unexpectedly at position {}", self.len + 1),
+        }
+    }
+
+    /// Enters one more level of nesting, refusing formulas nested deeper
+    /// than `MAX_FORMULA_DEPTH`; `leave` goes back out.
+    fn enter(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_FORMULA_DEPTH {
+            return Err(format!(
+                "Formula nests more than {} levels deep",
+                MAX_FORMULA_DEPTH
+            ));
+        }
+        Ok(())
+    }
+
+    fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut left = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek() {
+            let op = if *op == '+' {
+                BinaryOp::Add
+            } else {
+                BinaryOp::Sub
+            };
+            self.pos += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.term()?));
+        }
+        Ok(left)
+    }
+
+    /// term := unary (('*' | '/') unary)*
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        while let Some(Token::Op(op @ ('*' | '/'))) = self.peek() {
+            let op = if *op == '*' {
+                BinaryOp::Mul
+            } else {
+                BinaryOp::Div
+            };
+            self.pos += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    /// unary := '-' unary | primary
+    fn unary(&mut self) -> Result<Expr, String> {
+        if let Some(Token::Op('-')) = self.peek() {
+            self.pos += 1;
+            self.enter()?;
+            let inner = self.unary()?;
+            self.leave();
+            return Ok(Expr::Negate(Box::new(inner)));
+        }
+        self.primary()
+    }
+
+    /// primary := number | name | name '(' expr (',' expr)* ')' | '(' expr ')'
+    fn primary(&mut self) -> Result<Expr, String> {
+        let error = self.unexpected();
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::Ident(name)) => {
+                if self.peek() != Some(&Token::Open) {
+                    return Ok(Expr::Variable(name));
+                }
+                let function = Function::from_name(&name)
+                    .ok_or_else(|| format!("Unknown function '{}'", name))?;
+                self.pos += 1;
+                self.enter()?;
+                let mut args = vec![self.expr()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.pos += 1;
+                    args.push(self.expr()?);
+                }
+                self.expect_close()?;
+                self.leave();
+                if !function.accepts(args.len()) {
+                    return Err(format!(
+                        "{}() does not take {} argument(s)",
+                        name,
+                        args.len()
+                    ));
+                }
+                Ok(Expr::Call(function, args))
+            }
+            Some(Token::Open) => {
+                self.enter()?;
+                let inner = self.expr()?;
+                self.expect_close()?;
+                self.leave();
+                Ok(inner)
+            }
+            _ => Err(error),
+        }
+    }
+
+    fn expect_close(&mut self) -> Result<(), String> {
+        if self.peek() == Some(&Token::Close) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+}
+
+impl Expr {
+    /// Parses a formula such as `max(weight * 350, 4000)`, of at most
+    /// `MAX_FORMULA_LEN` characters and `MAX_FORMULA_DEPTH` levels of
+    /// nesting.
+    pub fn parse(src: &str) -> Result<Expr, String> {
+        trace!("Parsing pricing formula '{}'", src);
+        let len = src.chars().count();
+        if len > MAX_FORMULA_LEN {
+            debug!(len, "Pricing formula too long");
+            return Err(format!(
+                "Formula is longer than {} characters",
+                MAX_FORMULA_LEN
+            ));
+        }
+        let tokens = tokenize(src)?;
+        if tokens.is_empty() {
+            return Err("Formula is empty".to_string());
+        }
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            len,
+            depth: 0,
+        };
+        let expr = parser.expr()?;
+        if parser.pos < parser.tokens.len() {
+            debug!("Trailing input in pricing formula '{}'", src);
+            return Err(parser.unexpected());
+        }
+        Ok(expr)
+    }
+
+    /// Names of the variables the formula refers to.
+    pub fn variables(&self) -> BTreeSet<&str> {
+        let mut names = BTreeSet::new();
+        self.collect_variables(&mut names);
+        names
+    }
+
+    fn collect_variables<'a>(&'a self, names: &mut BTreeSet<&'a str>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Variable(name) => {
+                names.insert(name);
+            }
+            Expr::Negate(inner) => inner.collect_variables(names),
+            Expr::Binary(_, left, right) => {
+                left.collect_variables(names);
+                right.collect_variables(names);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|a| a.collect_variables(names)),
+        }
+    }
+
+    /// Evaluates the formula with the given variable values.
+    pub fn eval(&self, vars: &BTreeMap<String, f64>) -> Result<f64, String> {
+        let value = match self {
+            Expr::Number(value) => *value,
+            Expr::Variable(name) => *vars
+                .get(name)
+                .ok_or_else(|| format!("Unknown variable '{}'", name))?,
+            Expr::Negate(inner) => -inner.eval(vars)?,
+            Expr::Binary(op, left, right) => {
+                let (left, right) = (left.eval(vars)?, right.eval(vars)?);
+                match op {
+                    BinaryOp::Add => left + right,
+                    BinaryOp::Sub => left - right,
+                    BinaryOp::Mul => left * right,
+                    BinaryOp::Div if right == 0.0 => return Err("Division by zero".to_string()),
+                    BinaryOp::Div => left / right,
+                }
+            }
+            Expr::Call(function, args) => {
+                let values = args
+                    .iter()
+                    .map(|a| a.eval(vars))
+                    .collect::<Result<Vec<f64>, String>>()?;
+                match function {
+                    Function::Min => values.into_iter().fold(f64::INFINITY, f64::min),
+                    Function::Max => values.into_iter().fold(f64::NEG_INFINITY, f64::max),
+                    Function::Round => values[0].round(),
+                }
+            }
+        };
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err("Formula result is not a finite number".to_string())
+        }
+    }
+}
+
+/// The farm's pricing formula with the constants it uses.
+///
+/// `variables` holds farm-wide constants such as `rate_per_kg`;
+/// `breed_factors` maps `Breed::to_str` names to the `breed_factor` of goats
+/// of that breed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct PricingSettings {
+    pub formula: String,
+    #[serde(default)]
+    pub variables: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub breed_factors: BTreeMap<String, f64>,
+}
+
+impl PricingSettings {
+    /// Parses the formula and checks that every variable it uses is defined
+    /// and every constant is a finite number.
+    pub fn compile(&self) -> Result<Expr, String> {
+        let expr = Expr::parse(&self.formula)?;
+        for (name, value) in &self.variables {
+            let valid_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid_name {
+                return Err(format!("'{}' is not a valid variable name", name));
+            }
+            if GOAT_VARIABLES.contains(&name.as_str()) {
+                return Err(format!("'{}' is taken from each goat", name));
+            }
+            if !value.is_finite() {
+                return Err(format!("{} must be a number", name));
+            }
+        }
+        if let Some((breed, _)) = self
+            .breed_factors
+            .iter()
+            .find(|(_, f)| !f.is_finite() || **f < 0.0)
+        {
+            return Err(format!("Breed factor for {} must not be negative", breed));
+        }
+        if let Some(unknown) = expr
+            .variables()
+            .into_iter()
+            .find(|v| !GOAT_VARIABLES.contains(v) && !self.variables.contains_key(*v))
+        {
+            return Err(format!("Unknown variable '{}'", unknown));
+        }
+        Ok(expr)
+    }
+
+    /// The factor applied to goats of `breed`, 1 unless configured.
+    pub fn breed_factor(&self, breed: &Breed) -> f64 {
+        self.breed_factors
+            .get(Breed::to_str(breed))
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    /// Prices `goat` with a formula from `compile`, rounded to two decimals.
+    pub fn price(&self, expr: &Expr, goat: &GoatParams) -> Result<f64, String> {
+        let mut vars = self.variables.clone();
+        vars.insert("weight".to_string(), goat.weight);
+        vars.insert("breed_factor".to_string(), self.breed_factor(&goat.breed));
+        vars.insert("cost".to_string(), goat.cost);
+        vars.insert("offspring".to_string(), goat.offspring as f64);
+        let price = expr.eval(&vars)?;
+        if price < 0.0 {
+            return Err(format!("Formula gives a negative price ({:.2})", price));
+        }
+        Ok((price * 100.0).round() / 100.0)
+    }
+
+    /// Values every goat in `goats`. Goats the formula cannot price carry
+    /// the reason instead of a price.
+    pub fn value_herd(&self, goats: &[GoatParams]) -> Result<Vec<GoatValuation>, String> {
+        let expr = self.compile()?;
+        Ok(goats
+            .iter()
+            .map(|goat| {
+                let (formula_price, error) = match self.price(&expr, goat) {
+                    Ok(price) => (Some(price), None),
+                    Err(e) => (None, Some(e)),
+                };
+                GoatValuation {
+                    goat_name: goat.name.clone(),
+                    current_price: goat.current_price,
+                    formula_price,
+                    error,
+                }
+            })
+            .collect())
+    }
+}
+
+/// A goat's recorded price next to the price its formula gives.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GoatValuation {
+    pub goat_name: String,
+    pub current_price: f64,
+    pub formula_price: Option<f64>,
+    pub error: Option<String>,
+}
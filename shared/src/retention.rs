@@ -0,0 +1,98 @@
+//! Retention of deleted and historical data.
+//!
+//! Deleting a goat moves it to the trash, where it can be restored for
+//! `RetentionPolicy::trash_days`. A daily cleanup job then purges it for
+//! good, along with entries of the event log (the audit trail) older than
+//! `RetentionPolicy::event_days`, if the farm set a limit.
+
+use crate::Goat;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Days a deleted goat stays in the trash on a farm that never chose.
+pub const DEFAULT_TRASH_DAYS: u32 = 30;
+
+/// Longest retention that can be set, about ten years.
+pub const MAX_RETENTION_DAYS: u32 = 3650;
+
+fn default_trash_days() -> u32 {
+    DEFAULT_TRASH_DAYS
+}
+
+/// How long deleted goats and event log entries are kept.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct RetentionPolicy {
+    /// Days a deleted goat can be restored before it is purged.
+    #[serde(default = "default_trash_days")]
+    pub trash_days: u32,
+    /// Days event log entries are kept; `None` keeps them for good. Purged
+    /// entries are gone from change histories, and the log can no longer
+    /// rebuild the herd from scratch.
+    #[serde(default)]
+    pub event_days: Option<u32>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy {
+            trash_days: DEFAULT_TRASH_DAYS,
+            event_days: None,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Checks every period is between a day and `MAX_RETENTION_DAYS`.
+    pub fn validate(&self) -> Result<(), String> {
+        let check = |what: &str, days: u32| {
+            if (1..=MAX_RETENTION_DAYS).contains(&days) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{} must be kept between 1 and {} days",
+                    what, MAX_RETENTION_DAYS
+                ))
+            }
+        };
+        check("Deleted goats", self.trash_days)?;
+        if let Some(days) = self.event_days {
+            check("Event log entries", days)?;
+        }
+        Ok(())
+    }
+}
+
+/// A deleted goat waiting in the trash.
+///
+/// `goat` is the goat as it was deleted, with its vaccinations and
+/// diseases. `purge_on` is the date (`YYYY-MM-DD`, UTC) from which the
+/// cleanup job purges it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TrashedGoat {
+    pub id: i64,
+    pub goat: Goat,
+    pub deleted_at: DateTime<Utc>,
+    pub purge_on: String,
+}
+
+/// What the cleanup job is going to purge, for the retention settings.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct UpcomingPurges {
+    pub policy: RetentionPolicy,
+    /// The trash, soonest purged first.
+    pub trash: Vec<TrashedGoat>,
+    /// Event log entries already past their retention, purged by the next
+    /// cleanup.
+    pub events_due: usize,
+    /// Event log entries that pass their retention within the next week.
+    pub events_next_week: usize,
+    /// When the last cleanup finished, if one has run.
+    pub last_cleanup: Option<DateTime<Utc>>,
+}
+
+/// What a cleanup purged.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct PurgeSummary {
+    pub goats: usize,
+    pub events: usize,
+}
@@ -0,0 +1,63 @@
+//! Weigh-scale integration: device API keys and tagged weight readings.
+//!
+//! Digital scales (usually through a serial or Bluetooth bridge) push each
+//! reading with the RFID/ear tag of the goat on the platform. Readings whose
+//! tag belongs to a goat become weight records immediately; the rest wait in
+//! the weigh session until the tag is assigned to a goat.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A device API key as listed to users; the key itself is never returned
+/// after creation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ApiKey {
+    pub id: i64,
+    pub name: String,
+    pub revoked: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Request to create an API key for the device called `name`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewApiKey {
+    pub name: String,
+}
+
+/// A freshly created API key. `key` is shown only this once.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IssuedApiKey {
+    pub id: i64,
+    pub name: String,
+    pub key: String,
+}
+
+/// A reading pushed by a scale. `read_at` is RFC 3339 or a
+/// `YYYY-MM-DDTHH:MM:SS` time in the farm's time zone (see
+/// `crate::time::parse_timestamp`), and defaults to the time it is received.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScaleReadingInput {
+    pub tag_id: String,
+    pub weight: f64,
+    #[serde(default)]
+    pub read_at: Option<String>,
+}
+
+/// A stored reading. `goat_name` and `weight_record_id` are set once the tag
+/// is matched to a goat.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScaleReading {
+    pub id: i64,
+    pub tag_id: String,
+    pub weight: f64,
+    pub read_at: DateTime<Utc>,
+    pub goat_name: Option<String>,
+    pub weight_record_id: Option<i64>,
+}
+
+/// Request to give a reading's tag to a goat.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TagAssignment {
+    pub goat_name: String,
+}
@@ -0,0 +1,123 @@
+//! Goat scoring for keep/cull decisions.
+//!
+//! Each goat is rated 0–100 on milk, growth, fertility, health and
+//! profitability relative to the rest of the herd, and the ratings are
+//! combined with user-chosen weights into a single score. Metrics a goat has
+//! no data for (e.g. milk for a buck) are left out of its average rather
+//! than counted as zero.
+
+use crate::Gender;
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+
+/// Goats scoring at or above this are recommended to keep.
+pub const KEEP_SCORE: f64 = 60.0;
+
+/// Goats scoring below this are flagged as cull candidates.
+pub const CULL_SCORE: f64 = 40.0;
+
+/// Relative importance of each metric. Zero leaves a metric out entirely.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ScoreWeights {
+    pub milk: f64,
+    pub growth: f64,
+    pub fertility: f64,
+    pub health: f64,
+    pub profit: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        ScoreWeights {
+            milk: 1.0,
+            growth: 1.0,
+            fertility: 1.0,
+            health: 1.0,
+            profit: 1.0,
+        }
+    }
+}
+
+impl ScoreWeights {
+    /// Checks every weight is a finite non-negative number and at least one
+    /// is positive.
+    pub fn validate(&self) -> Result<(), String> {
+        let weights = [
+            self.milk,
+            self.growth,
+            self.fertility,
+            self.health,
+            self.profit,
+        ];
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err("Score weights must be non-negative numbers".to_string());
+        }
+        if weights.iter().all(|w| *w == 0.0) {
+            return Err("At least one score weight must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Per-metric ratings, 0 (worst in herd) to 100 (best in herd); `None` when
+/// the goat has no data for that metric.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ScoreBreakdown {
+    pub milk: Option<f64>,
+    pub growth: Option<f64>,
+    pub fertility: Option<f64>,
+    pub health: Option<f64>,
+    pub profit: Option<f64>,
+}
+
+impl ScoreBreakdown {
+    /// Weighted average of the available ratings, or `None` if the goat has
+    /// no data for any weighted metric.
+    pub fn weighted(&self, weights: &ScoreWeights) -> Option<f64> {
+        let pairs = [
+            (self.milk, weights.milk),
+            (self.growth, weights.growth),
+            (self.fertility, weights.fertility),
+            (self.health, weights.health),
+            (self.profit, weights.profit),
+        ];
+        let (total, weight) = pairs
+            .iter()
+            .filter_map(|(rating, weight)| rating.map(|r| (r * weight, *weight)))
+            .fold((0.0, 0.0), |(t, w), (rt, rw)| (t + rt, w + rw));
+        trace!(total, weight, "Weighted score");
+        (weight > 0.0).then(|| total / weight)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum Recommendation {
+    Keep,
+    Review,
+    Cull,
+}
+
+impl Recommendation {
+    /// Maps a score to a recommendation using `KEEP_SCORE` and `CULL_SCORE`.
+    pub fn for_score(score: f64) -> Recommendation {
+        if score >= KEEP_SCORE {
+            Recommendation::Keep
+        } else if score < CULL_SCORE {
+            Recommendation::Cull
+        } else {
+            Recommendation::Review
+        }
+    }
+}
+
+/// A goat's overall score with the ratings behind it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GoatScore {
+    pub goat_name: String,
+    pub gender: Gender,
+    pub score: f64,
+    pub breakdown: ScoreBreakdown,
+    pub recommendation: Recommendation,
+}
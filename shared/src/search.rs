@@ -0,0 +1,135 @@
+//! Quick search across goats, tasks and transactions.
+//!
+//! The backend matches the query against names, titles and descriptions and
+//! returns a short, mixed list of hits; the header search box lists them and
+//! jumps to the selected one. Goat names are also matched fuzzily, so a
+//! misspelt name ("Lakshmi" for "Laxmi") still offers "did you mean"
+//! suggestions, and across scripts, so "रानी" finds "Rani" and vice versa.
+
+use crate::transliterate::romanize;
+use serde::{Deserialize, Serialize};
+
+/// Shortest query the backend searches for; shorter ones return nothing.
+pub const MIN_QUERY_LEN: usize = 2;
+
+/// Most hits returned per kind of record.
+pub const MAX_RESULTS_PER_KIND: usize = 5;
+
+/// Smallest `name_similarity` at which a name is offered as a "did you
+/// mean" suggestion.
+pub const SUGGESTION_THRESHOLD: f64 = 0.7;
+
+/// Most "did you mean" suggestions offered.
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// The kind of record a search hit points at.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub enum SearchKind {
+    Goat,
+    Task,
+    Transaction,
+}
+
+impl SearchKind {
+    /// Prefix of the page anchor for records of this kind, e.g. `goat`.
+    pub fn anchor_prefix(&self) -> &'static str {
+        match self {
+            SearchKind::Goat => "goat",
+            SearchKind::Task => "task",
+            SearchKind::Transaction => "transaction",
+        }
+    }
+}
+
+/// One search hit.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub kind: SearchKind,
+    pub id: i64,
+    /// Goat name, task title, or transaction category.
+    pub title: String,
+    /// One line of context, e.g. breed, due date, or amount and date.
+    pub detail: String,
+    /// Set for goats offered as "did you mean" suggestions because nothing
+    /// matched the query exactly.
+    #[serde(default)]
+    pub suggested: bool,
+}
+
+impl SearchResult {
+    /// The element ID the record is rendered under, e.g. `goat-12`, which
+    /// the search box navigates to as a URL fragment.
+    pub fn anchor(&self) -> String {
+        format!("{}-{}", self.kind.anchor_prefix(), self.id)
+    }
+}
+
+/// Folds a name for fuzzy comparison: spelt in Latin letters (see
+/// `romanize`), lowercase letters and digits only, with common
+/// transliteration variants spelled one way ("ksh" as "x", "ph" as "f",
+/// "w" as "v", "ee" as "i", "oo" as "u") and doubled letters collapsed, so
+/// "Lakshmi", "Laxmi" and "लक्ष्मी" fold alike.
+pub fn fold_name(name: &str) -> String {
+    let mut folded: String = romanize(name)
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    for (from, to) in [
+        ("ksh", "x"),
+        ("ph", "f"),
+        ("w", "v"),
+        ("ee", "i"),
+        ("oo", "u"),
+    ] {
+        folded = folded.replace(from, to);
+    }
+    let mut chars: Vec<char> = folded.chars().collect();
+    chars.dedup();
+    chars.into_iter().collect()
+}
+
+/// Edit distance between `a` and `b`: insertions, deletions and
+/// substitutions needed to turn one into the other.
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// How alike two names are, from 0 (nothing in common) to 1 (the same once
+/// folded with `fold_name`): one minus their edit distance over the longer
+/// folded length.
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = fold_name(a).chars().collect();
+    let b: Vec<char> = fold_name(b).chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / longest as f64
+}
+
+/// Names at least `SUGGESTION_THRESHOLD` similar to `query`, most similar
+/// first (ties by name), at most `MAX_SUGGESTIONS` of them.
+pub fn suggest_names<'a>(query: &str, names: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut scored: Vec<(f64, &str)> = names
+        .into_iter()
+        .map(|name| (name_similarity(query, name), name))
+        .filter(|(similarity, _)| *similarity >= SUGGESTION_THRESHOLD)
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, name)| name)
+        .collect()
+}
@@ -0,0 +1,147 @@
+//! Environmental sensors (barn temperature, humidity), water meters,
+//! wearable activity tags, and their readings.
+//!
+//! Sensors push readings through an API key like the weigh scales do. Each
+//! sensor may have a minimum and maximum threshold; a latest reading outside
+//! them is flagged as an alert on the dashboard. Activity tags are worn by a
+//! goat and feed heat detection (see `crate::heat`); water meters feed the
+//! pen water report (see `crate::water`).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum SensorKind {
+    Temperature,
+    Humidity,
+    /// Pedometer tag; each reading is a step or movement count.
+    Activity,
+    /// Water meter on a pen's troughs; each reading is the litres drawn
+    /// since the last one.
+    Water,
+    Other(String),
+}
+
+impl SensorKind {
+    /// Converts a database string to `SensorKind`, treating unknown values
+    /// (cameras, RFID readers, ...) as `Other`.
+    pub fn from_str(s: &str) -> Self {
+        trace!("Parsing SensorKind from '{}'", s);
+        match s {
+            "Temperature" | "Temp Sensor" => SensorKind::Temperature,
+            "Humidity" | "Humidity Sensor" => SensorKind::Humidity,
+            "Activity" | "Pedometer" => SensorKind::Activity,
+            "Water" | "Water Meter" => SensorKind::Water,
+            other => {
+                debug!("Unknown SensorKind '{}', mapping to Other", other);
+                SensorKind::Other(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `SensorKind` to a database string.
+    pub fn to_str(kind: &Self) -> &str {
+        match kind {
+            SensorKind::Temperature => "Temperature",
+            SensorKind::Humidity => "Humidity",
+            SensorKind::Activity => "Activity",
+            SensorKind::Water => "Water",
+            SensorKind::Other(name) => name,
+        }
+    }
+
+    /// Unit of the sensor's readings, empty when unknown.
+    pub fn unit(&self) -> &'static str {
+        match self {
+            SensorKind::Temperature => "°C",
+            SensorKind::Humidity => "%",
+            SensorKind::Activity => "steps",
+            SensorKind::Water => "L",
+            SensorKind::Other(_) => "",
+        }
+    }
+}
+
+/// Acceptable range of readings; either bound may be left open.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Thresholds {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl Thresholds {
+    /// Checks the minimum is below the maximum.
+    pub fn validate(&self) -> Result<(), String> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min >= max => Err(format!(
+                "Minimum threshold {} must be below maximum {}",
+                min, max
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether `value` falls below or above the range.
+    pub fn check(&self, value: f64) -> Option<ThresholdAlert> {
+        if self.min.is_some_and(|min| value < min) {
+            Some(ThresholdAlert::Low)
+        } else if self.max.is_some_and(|max| value > max) {
+            Some(ThresholdAlert::High)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum ThresholdAlert {
+    Low,
+    High,
+}
+
+/// A registered sensor. `space_name` is filled in on reads only.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Sensor {
+    pub id: Option<i64>,
+    pub name: String,
+    pub kind: SensorKind,
+    /// Pen or barn the sensor is installed in.
+    pub space_id: Option<i64>,
+    #[serde(default)]
+    pub space_name: Option<String>,
+    /// Goat wearing the sensor, for activity tags.
+    #[serde(default)]
+    pub goat_name: Option<String>,
+    #[serde(default)]
+    pub thresholds: Thresholds,
+}
+
+/// A reading pushed by a sensor. `read_at` is RFC 3339 or a
+/// `YYYY-MM-DDTHH:MM:SS` time in the farm's time zone, and defaults to the
+/// time it is received.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SensorReadingInput {
+    pub sensor_id: i64,
+    pub value: f64,
+    #[serde(default)]
+    pub read_at: Option<String>,
+}
+
+/// A stored reading.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SensorReading {
+    pub sensor_id: i64,
+    pub value: f64,
+    pub read_at: DateTime<Utc>,
+}
+
+/// A sensor with its latest reading and whether that reading is out of range.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SensorCondition {
+    pub sensor: Sensor,
+    pub latest: Option<SensorReading>,
+    pub alert: Option<ThresholdAlert>,
+}
@@ -0,0 +1,161 @@
+//! Signed-in devices.
+//!
+//! Each browser or phone using the dashboard signs in once and gets a
+//! session token, which it sends on every request in the `SESSION_HEADER`
+//! header. The farm sees its sessions with their last activity and can
+//! revoke any of them, after which requests with that token are refused
+//! until the device signs in again.
+//!
+//! Signing in takes credentials: the owner's password, set when the farm is
+//! set up, or a worker's name and PIN. After too many failures an account
+//! is locked out for a while. A worker can also sign in with an OAuth
+//! provider such as Google, which links the provider's account to the
+//! worker whose contact is the same verified email; the session then shows
+//! who is using the device.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Header carrying a session token.
+pub const SESSION_HEADER: &str = "X-Session-Token";
+
+/// Longest accepted device name.
+pub const MAX_DEVICE_NAME_LEN: usize = 60;
+
+/// Shortest accepted owner password.
+pub const MIN_OWNER_PASSWORD_LEN: usize = 8;
+
+/// Fewest and most digits in a worker's PIN.
+pub const PIN_DIGITS: std::ops::RangeInclusive<usize> = 4..=8;
+
+/// A signed-in device; the token itself is never returned after sign-in.
+///
+/// `last_seen_at` is updated at most once a minute. `current` marks the
+/// session the listing request was made in. `worker` is the name of the
+/// worker who signed in with an OAuth provider, if one did.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Session {
+    pub id: i64,
+    pub device: String,
+    #[serde(default)]
+    pub worker: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    #[serde(default)]
+    pub current: bool,
+}
+
+/// Request to sign in the device called `device`. Left empty, the device
+/// is named after its browser (see `describe_user_agent`).
+///
+/// `worker` names the worker signing in with their PIN as `secret`; without
+/// it the owner signs in with their password.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct NewSession {
+    #[serde(default)]
+    pub device: String,
+    #[serde(default)]
+    pub worker: Option<String>,
+    pub secret: String,
+}
+
+/// Request to change the owner's password.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PasswordChange {
+    pub current: String,
+    pub password: String,
+}
+
+/// A worker's new PIN.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewPin {
+    pub pin: String,
+}
+
+/// Checks `password` is long enough to be the owner's password.
+pub fn validate_owner_password(password: &str) -> Result<(), String> {
+    if password.chars().count() < MIN_OWNER_PASSWORD_LEN {
+        return Err(format!(
+            "The owner's password must be at least {} characters",
+            MIN_OWNER_PASSWORD_LEN
+        ));
+    }
+    Ok(())
+}
+
+/// Checks `pin` is all digits, and between 4 and 8 of them.
+pub fn validate_pin(pin: &str) -> Result<(), String> {
+    if !pin.chars().all(|c| c.is_ascii_digit()) || !PIN_DIGITS.contains(&pin.len()) {
+        return Err(format!(
+            "A PIN must be {} to {} digits",
+            PIN_DIGITS.start(),
+            PIN_DIGITS.end()
+        ));
+    }
+    Ok(())
+}
+
+/// A new session. `token` is shown only this once.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IssuedSession {
+    pub session: Session,
+    pub token: String,
+}
+
+/// Request to start signing in with an OAuth provider. The provider sends
+/// the browser back to `redirect_uri` with a code and the state, which go
+/// to `OAuthCallback`. `device` is as in `NewSession`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OAuthStart {
+    pub redirect_uri: String,
+    #[serde(default)]
+    pub device: String,
+}
+
+/// The provider's sign-in page to send the browser to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OAuthRedirect {
+    pub authorize_url: String,
+}
+
+/// The `code` and `state` query parameters the provider sent the browser
+/// back with.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OAuthCallback {
+    pub code: String,
+    pub state: String,
+}
+
+/// Short name for the browser and system in a `User-Agent` header, e.g.
+/// "Firefox on Android", for devices signed in without a name.
+pub fn describe_user_agent(user_agent: &str) -> String {
+    // Order matters: Edge and Opera also claim Chrome, and Chrome claims Safari
+    let browser = [
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+    ]
+    .into_iter()
+    .find(|(marker, _)| user_agent.contains(marker))
+    .map(|(_, name)| name);
+    let system = [
+        ("Android", "Android"),
+        ("iPhone", "iPhone"),
+        ("iPad", "iPad"),
+        ("Windows", "Windows"),
+        ("Mac OS X", "macOS"),
+        ("Linux", "Linux"),
+    ]
+    .into_iter()
+    .find(|(marker, _)| user_agent.contains(marker))
+    .map(|(_, name)| name);
+    match (browser, system) {
+        (Some(browser), Some(system)) => format!("{} on {}", browser, system),
+        (Some(name), None) | (None, Some(name)) => name.to_string(),
+        (None, None) => "Unknown device".to_string(),
+    }
+}
@@ -0,0 +1,126 @@
+//! Farm-wide settings that apply across modules, such as the farm's own
+//! GST registration used when preparing the sales register, its time zone,
+//! its language, read-only mode, how long deleted data is kept, or how new
+//! goats are tagged.
+
+use crate::pricing::PricingSettings;
+use crate::retention::RetentionPolicy;
+use crate::setup::SetupStep;
+use crate::tags::TagSeries;
+use crate::time::{DEFAULT_TIMEZONE, parse_timezone};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// The base currency of a farm that never set one.
+pub const DEFAULT_BASE_CURRENCY: &str = "INR";
+
+/// The language of a farm that never chose one.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Languages a farm can choose, as ISO 639-1 codes with the language's
+/// own name.
+pub const LANGUAGES: [(&str, &str); 8] = [
+    ("en", "English"),
+    ("hi", "हिन्दी"),
+    ("mr", "मराठी"),
+    ("bn", "বাংলা"),
+    ("gu", "ગુજરાતી"),
+    ("ta", "தமிழ்"),
+    ("te", "తెలుగు"),
+    ("kn", "ಕನ್ನಡ"),
+];
+
+/// Checks `code` is one of the `LANGUAGES`.
+pub fn validate_language(code: &str) -> Result<(), String> {
+    if LANGUAGES.iter().any(|(known, _)| *known == code) {
+        Ok(())
+    } else {
+        Err(format!("'{}' is not a supported language", code))
+    }
+}
+
+fn default_base_currency() -> String {
+    DEFAULT_BASE_CURRENCY.to_string()
+}
+
+fn default_timezone() -> String {
+    DEFAULT_TIMEZONE.to_string()
+}
+
+fn default_language() -> String {
+    DEFAULT_LANGUAGE.to_string()
+}
+
+/// Settings stored once per farm. Unset values fall back to defaults.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FarmSettings {
+    /// The farm's name, as given during setup.
+    #[serde(default)]
+    pub farm_name: Option<String>,
+    /// The farm's GSTIN; its state code decides whether sales are taxed as
+    /// intra-state (CGST + SGST) or inter-state (IGST).
+    #[serde(default)]
+    pub gstin: Option<String>,
+    /// ISO 4217 code that reports convert amounts into. Costs and prices
+    /// kept outside the ledger, such as goat purchase costs, are in this
+    /// currency too.
+    #[serde(default = "default_base_currency")]
+    pub base_currency: String,
+    /// How goats are valued; `None` until the farm sets up a formula.
+    #[serde(default)]
+    pub pricing: Option<PricingSettings>,
+    /// IANA name of the farm's time zone, e.g. `Asia/Kolkata`. Dates such as
+    /// "today" and the day a timestamp falls on are taken in this zone (see
+    /// `crate::time`).
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// ISO 639-1 code of the farm's language, one of `LANGUAGES`.
+    #[serde(default = "default_language")]
+    pub language: String,
+    /// While true the dashboard only shows data, and the backend refuses
+    /// every change except switching the mode off and device readings.
+    /// Only the owner switches it, with a `ReadOnlyMode`; replacing the
+    /// settings keeps it as it is.
+    #[serde(default)]
+    pub read_only: bool,
+    /// The setup step the farm has reached. Left out of a request, setup
+    /// counts as finished.
+    #[serde(default)]
+    pub setup_step: SetupStep,
+    /// How long deleted goats and event log entries are kept.
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    /// How new goats are tagged; `None` leaves them without a tag.
+    #[serde(default)]
+    pub tag_series: Option<TagSeries>,
+}
+
+impl Default for FarmSettings {
+    fn default() -> Self {
+        FarmSettings {
+            farm_name: None,
+            gstin: None,
+            base_currency: default_base_currency(),
+            pricing: None,
+            timezone: default_timezone(),
+            language: default_language(),
+            read_only: false,
+            setup_step: SetupStep::Done,
+            retention: RetentionPolicy::default(),
+            tag_series: None,
+        }
+    }
+}
+
+impl FarmSettings {
+    /// The farm's time zone, or UTC if the stored name is not recognised.
+    pub fn tz(&self) -> Tz {
+        parse_timezone(&self.timezone).unwrap_or(Tz::UTC)
+    }
+}
+
+/// Request body switching the farm's read-only mode on or off.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ReadOnlyMode {
+    pub read_only: bool,
+}
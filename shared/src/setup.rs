@@ -0,0 +1,85 @@
+//! First-run setup of a farm: the steps the setup wizard walks a new farm
+//! through, in order. The step reached is kept in the farm settings (see
+//! `crate::settings::FarmSettings::setup_step`), so setup resumes where it
+//! was left after a reload or on another device.
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// A step of farm setup, in order.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SetupStep {
+    /// Naming the farm.
+    Farm,
+    /// Choosing units, currency, language and time zone.
+    Preferences,
+    /// Adding the first pens.
+    Pens,
+    /// Importing or adding the first goats.
+    Goats,
+    /// Setup is finished, or was never needed.
+    #[default]
+    Done,
+}
+
+impl SetupStep {
+    pub const ALL: [SetupStep; 5] = [
+        SetupStep::Farm,
+        SetupStep::Preferences,
+        SetupStep::Pens,
+        SetupStep::Goats,
+        SetupStep::Done,
+    ];
+
+    /// Converts a database string to `SetupStep`.
+    pub fn from_str(s: &str) -> Result<SetupStep, String> {
+        trace!("Parsing SetupStep from '{}'", s);
+        match s {
+            "farm" => Ok(SetupStep::Farm),
+            "preferences" => Ok(SetupStep::Preferences),
+            "pens" => Ok(SetupStep::Pens),
+            "goats" => Ok(SetupStep::Goats),
+            "done" => Ok(SetupStep::Done),
+            other => {
+                debug!("Failed to parse SetupStep enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `SetupStep` to a database string.
+    pub fn to_str(step: &SetupStep) -> &str {
+        match step {
+            SetupStep::Farm => "farm",
+            SetupStep::Preferences => "preferences",
+            SetupStep::Pens => "pens",
+            SetupStep::Goats => "goats",
+            SetupStep::Done => "done",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SetupStep::Farm => "Farm",
+            SetupStep::Preferences => "Preferences",
+            SetupStep::Pens => "Pens",
+            SetupStep::Goats => "Goats",
+            SetupStep::Done => "Done",
+        }
+    }
+
+    /// Position of the step, starting at 0.
+    pub fn index(&self) -> usize {
+        SetupStep::ALL.iter().position(|s| s == self).unwrap_or(0)
+    }
+
+    /// The step after this one; `Done` stays `Done`.
+    pub fn next(&self) -> SetupStep {
+        SetupStep::ALL[(self.index() + 1).min(SetupStep::ALL.len() - 1)]
+    }
+
+    /// The step before this one; `Farm` stays `Farm`.
+    pub fn previous(&self) -> SetupStep {
+        SetupStep::ALL[self.index().saturating_sub(1)]
+    }
+}
@@ -0,0 +1,139 @@
+//! Farm spaces: pens (enclosures), grazing fields, and other areas.
+//!
+//! Goats can be assigned to one space at a time, which lets per-pen analytics
+//! reach the animals housed there. A space's capacity and floor area are
+//! checked against the goats assigned to it, so overcrowded pens stand out.
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum SpaceKind {
+    Enclosure,
+    GrazingField,
+    Other,
+}
+
+impl SpaceKind {
+    /// Converts a database string to `SpaceKind`.
+    pub fn from_str(s: &str) -> Result<SpaceKind, String> {
+        trace!("Parsing SpaceKind from '{}'", s);
+        match s {
+            "enclosure" => Ok(SpaceKind::Enclosure),
+            "grazing_field" => Ok(SpaceKind::GrazingField),
+            "other" => Ok(SpaceKind::Other),
+            other => {
+                debug!("Failed to parse SpaceKind enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `SpaceKind` to a database string.
+    pub fn to_str(kind: &SpaceKind) -> &str {
+        match kind {
+            SpaceKind::Enclosure => "enclosure",
+            SpaceKind::GrazingField => "grazing_field",
+            SpaceKind::Other => "other",
+        }
+    }
+
+    /// Recommended area per adult goat in square metres: 1.5 m² of floor
+    /// in a pen, and 10 goats per hectare of grazing. `None` for other
+    /// spaces, which have no recommendation.
+    pub fn area_per_goat(&self) -> Option<f64> {
+        match self {
+            SpaceKind::Enclosure => Some(1.5),
+            SpaceKind::GrazingField => Some(1000.0),
+            SpaceKind::Other => None,
+        }
+    }
+}
+
+/// A pen, field or other area. `goat_names` lists the goats assigned to it
+/// and is ignored on creation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Space {
+    pub id: Option<i64>,
+    pub name: String,
+    pub kind: SpaceKind,
+    pub capacity: Option<i64>,
+    /// Usable area in square metres.
+    #[serde(default)]
+    pub area_m2: Option<f64>,
+    #[serde(default)]
+    pub goat_names: Vec<String>,
+}
+
+/// How full a space is.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpaceOccupancy {
+    pub space_id: i64,
+    pub name: String,
+    pub kind: SpaceKind,
+    pub capacity: Option<i64>,
+    pub area_m2: Option<f64>,
+    /// Goats assigned to the space.
+    pub goats: i64,
+    /// Most goats the area holds at the recommended stocking density.
+    pub recommended_max: Option<i64>,
+    /// `goats` as a percentage of the capacity, or of `recommended_max`
+    /// for spaces without one; `None` if neither is known.
+    pub occupancy_percent: Option<f64>,
+    /// Why the space is overstocked, if it is.
+    pub warnings: Vec<String>,
+}
+
+impl SpaceOccupancy {
+    /// Works out the occupancy of a space holding `goats` goats.
+    pub fn new(
+        space_id: i64,
+        name: String,
+        kind: SpaceKind,
+        capacity: Option<i64>,
+        area_m2: Option<f64>,
+        goats: i64,
+    ) -> Self {
+        let recommended_max = area_m2
+            .zip(kind.area_per_goat())
+            .map(|(area, per_goat)| (area / per_goat).floor() as i64);
+        let occupancy_percent = capacity
+            .or(recommended_max)
+            .filter(|limit| *limit > 0)
+            .map(|limit| goats as f64 / limit as f64 * 100.0);
+
+        let mut warnings = Vec::new();
+        if let Some(capacity) = capacity.filter(|c| goats > *c) {
+            warnings.push(format!(
+                "{} holds {} goats but has room for {}",
+                name, goats, capacity
+            ));
+        }
+        if let (Some(area), Some(per_goat)) = (area_m2, kind.area_per_goat())
+            && goats > 0
+            && area / (goats as f64) < per_goat
+        {
+            warnings.push(format!(
+                "{} gives each goat {:.1} m², less than the recommended {} m²",
+                name,
+                area / goats as f64,
+                per_goat
+            ));
+        }
+        if !warnings.is_empty() {
+            debug!(space_id, goats, ?capacity, ?area_m2, "Space is overstocked");
+        }
+        SpaceOccupancy {
+            space_id,
+            name,
+            kind,
+            capacity,
+            area_m2,
+            goats,
+            recommended_max,
+            occupancy_percent,
+            warnings,
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Headline figures for the dashboard's KPI cards.
+//!
+//! Each figure carries its value for the last few periods so the cards can
+//! draw a trend sparkline and compare the current period with the one
+//! before it.
+
+use serde::{Deserialize, Serialize};
+
+/// Number of periods in each KPI's trend, including the current one.
+pub const TREND_PERIODS: usize = 8;
+
+/// One KPI with its recent history.
+///
+/// `trend` holds one value per period, oldest first; its last entry is the
+/// current period and equals `value`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Kpi {
+    pub value: f64,
+    pub trend: Vec<f64>,
+}
+
+impl Kpi {
+    /// Builds a KPI from its trend, taking the last entry as the value.
+    pub fn from_trend(trend: Vec<f64>) -> Self {
+        Kpi {
+            value: trend.last().copied().unwrap_or(0.0),
+            trend,
+        }
+    }
+
+    /// The value in the period before the current one.
+    pub fn previous(&self) -> Option<f64> {
+        self.trend.iter().rev().nth(1).copied()
+    }
+
+    /// Percentage change from the previous period, or `None` if there was
+    /// no previous period or it was zero.
+    pub fn change_percent(&self) -> Option<f64> {
+        self.previous()
+            .filter(|previous| *previous != 0.0)
+            .map(|previous| (self.value - previous) / previous * 100.0)
+    }
+}
+
+/// The figures behind the dashboard's KPI cards.
+///
+/// Herd figures and milk are weekly (the seven days ending on `as_of`, and
+/// the weeks before); expenses are per calendar month, the current month
+/// counting up to `as_of`. Herd size and value count the goats added by the
+/// end of each week, at their current prices.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct DashboardStats {
+    /// Date the figures are computed for, `YYYY-MM-DD`.
+    pub as_of: String,
+    /// The farm's base currency, which herd value and expenses are in.
+    pub currency: String,
+    pub herd_size: Kpi,
+    pub herd_value: Kpi,
+    /// Litres of milk recorded.
+    pub milk_this_week: Kpi,
+    pub expenses_this_month: Kpi,
+}
@@ -0,0 +1,78 @@
+//! Tag numbering schemes.
+//!
+//! A farm can set a `TagSeries` in its settings, such as `YG-2026-0001`,
+//! `YG-2026-0002`, ... Each goat added then gets the next tag of the series
+//! (see the backend's `tags` module). A yearly series starts again at 1
+//! each year; tags already on a goat, e.g. assigned from a scale reading,
+//! are skipped.
+
+use serde::{Deserialize, Serialize};
+
+/// Digits the counter of a series is padded to when the farm never chose.
+pub const DEFAULT_TAG_DIGITS: u8 = 4;
+
+/// Most digits a counter can be padded to.
+pub const MAX_TAG_DIGITS: u8 = 9;
+
+fn default_digits() -> u8 {
+    DEFAULT_TAG_DIGITS
+}
+
+/// A farm's tag numbering scheme: a prefix, the year if `yearly`, and a
+/// counter padded with zeros to `digits`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TagSeries {
+    pub prefix: String,
+    /// Puts the year after the prefix and restarts the counter each year.
+    #[serde(default)]
+    pub yearly: bool,
+    #[serde(default = "default_digits")]
+    pub digits: u8,
+}
+
+impl TagSeries {
+    /// Checks the prefix is set and has no spaces, and the counter is
+    /// padded to between 1 and `MAX_TAG_DIGITS` digits.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.prefix.trim().is_empty() {
+            return Err("A tag series needs a prefix".into());
+        }
+        if self.prefix.trim().contains(char::is_whitespace) {
+            return Err("A tag prefix cannot contain spaces".into());
+        }
+        if !(1..=MAX_TAG_DIGITS).contains(&self.digits) {
+            return Err(format!(
+                "Tag numbers must have between 1 and {} digits",
+                MAX_TAG_DIGITS
+            ));
+        }
+        Ok(())
+    }
+
+    /// The part of the tags before the counter in `year`, e.g. "YG-2026-";
+    /// each stem is counted separately.
+    pub fn stem(&self, year: i32) -> String {
+        if self.yearly {
+            format!("{}-{}-", self.prefix.trim(), year)
+        } else {
+            format!("{}-", self.prefix.trim())
+        }
+    }
+
+    /// Tag number `number` of the series in `year`, e.g. "YG-2026-0042".
+    pub fn tag(&self, year: i32, number: u32) -> String {
+        format!(
+            "{}{:0width$}",
+            self.stem(year),
+            number,
+            width = self.digits as usize
+        )
+    }
+}
+
+/// The tag the next goat added will get.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct NextTag {
+    /// `None` when the farm has no tag series.
+    pub tag_id: Option<String>,
+}
@@ -0,0 +1,115 @@
+//! Farm tasks, reminders, and the recurring reminder rules that generate them.
+//!
+//! Rules such as "deworm every 90 days per goat" are evaluated by the backend
+//! scheduler, which creates a `Task` (and a matching `Reminder`) for every goat
+//! or pen the rule applies to once the next occurrence falls due.
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// Lifecycle of a single task.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum TaskStatus {
+    Pending,
+    Done,
+    Skipped,
+}
+
+impl TaskStatus {
+    /// Converts a database string to `TaskStatus`.
+    pub fn from_str(s: &str) -> Result<TaskStatus, String> {
+        trace!("Parsing TaskStatus from '{}'", s);
+        match s {
+            "Pending" => Ok(TaskStatus::Pending),
+            "Done" => Ok(TaskStatus::Done),
+            "Skipped" => Ok(TaskStatus::Skipped),
+            other => {
+                debug!("Failed to parse TaskStatus enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `TaskStatus` to a database string.
+    pub fn to_str(status: &TaskStatus) -> &str {
+        match status {
+            TaskStatus::Pending => "Pending",
+            TaskStatus::Done => "Done",
+            TaskStatus::Skipped => "Skipped",
+        }
+    }
+}
+
+/// What a reminder rule is applied to: every goat, or every pen (enclosure).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub enum RuleScope {
+    Goat,
+    Pen,
+}
+
+impl RuleScope {
+    /// Converts a database string to `RuleScope`.
+    pub fn from_str(s: &str) -> Result<RuleScope, String> {
+        trace!("Parsing RuleScope from '{}'", s);
+        match s {
+            "Goat" => Ok(RuleScope::Goat),
+            "Pen" => Ok(RuleScope::Pen),
+            other => {
+                debug!("Failed to parse RuleScope enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `RuleScope` to a database string.
+    pub fn to_str(scope: &RuleScope) -> &str {
+        match scope {
+            RuleScope::Goat => "Goat",
+            RuleScope::Pen => "Pen",
+        }
+    }
+}
+
+/// A unit of farm work, either entered manually or generated by a `ReminderRule`.
+///
+/// Dates use the `YYYY-MM-DD` format. `goat_name` and `space_name` are filled
+/// in by the backend when reading; on creation a task may reference a goat by name.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Task {
+    pub id: Option<i64>,
+    pub title: String,
+    pub notes: Option<String>,
+    pub goat_name: Option<String>,
+    pub space_id: Option<i64>,
+    pub space_name: Option<String>,
+    pub rule_id: Option<i64>,
+    pub due_date: String,
+    pub status: TaskStatus,
+}
+
+/// A recurring rule such as "trim hooves every 56 days per pen".
+///
+/// `lead_days` controls how long before the due date the task and its
+/// reminder are created.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReminderRule {
+    pub id: Option<i64>,
+    pub name: String,
+    pub task_title: String,
+    pub scope: RuleScope,
+    pub interval_days: u32,
+    pub lead_days: u32,
+    pub active: bool,
+}
+
+/// A notification that a task is coming up or due.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Reminder {
+    pub id: Option<i64>,
+    pub task_id: i64,
+    pub remind_on: String,
+    pub message: String,
+    pub dismissed: bool,
+}
@@ -0,0 +1,37 @@
+//! Farms hosted on a shared, multi-tenant backend.
+//!
+//! Each tenant is identified by a short slug and authenticates with its own
+//! tenant key. Tenant keys are issued by the instance operator and shown only
+//! once, like device API keys.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Header carrying a tenant key, on every request to a multi-tenant
+/// backend.
+pub const TENANT_KEY_HEADER: &str = "X-Tenant-Key";
+
+/// A hosted farm as listed to the operator.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Tenant {
+    /// Lowercase letters, digits and dashes, e.g. `green-valley`.
+    pub slug: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to host a new farm, whose owner signs in with `owner_password`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewTenant {
+    pub slug: String,
+    pub name: String,
+    pub owner_password: String,
+}
+
+/// A freshly created tenant. `key` is shown only this once.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IssuedTenant {
+    pub slug: String,
+    pub name: String,
+    pub key: String,
+}
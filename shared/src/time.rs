@@ -0,0 +1,84 @@
+//! Timestamps and the farm's time zone.
+//!
+//! Every timestamp is recorded in UTC (`DateTime<Utc>`, sent as RFC 3339) so
+//! it names the same instant wherever it is read. Calendar dates a farmer
+//! thinks in, such as "today", the day a goat was last bred or the day a
+//! record was logged, are taken in the farm's time zone
+//! (`FarmSettings::timezone`, an IANA name such as `Asia/Kolkata`) rather
+//! than the server's or the browser's, so both agree on which day it is.
+
+use chrono::{
+    DateTime, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+
+/// Time zone of a farm that never set one.
+pub const DEFAULT_TIMEZONE: &str = "UTC";
+
+/// How local times are shown, e.g. `2026-04-06 09:30`.
+pub const DISPLAY_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Formats accepted for a local time without an offset.
+const NAIVE_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Parses an IANA time zone name such as `Asia/Kolkata`.
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.trim()
+        .parse()
+        .map_err(|_| format!("Unknown time zone '{}'", name.trim()))
+}
+
+/// The calendar date of `at` in `tz`.
+pub fn local_date(at: &DateTime<Utc>, tz: Tz) -> NaiveDate {
+    at.with_timezone(&tz).date_naive()
+}
+
+/// Today's date in `tz`.
+pub fn today_in(tz: Tz) -> NaiveDate {
+    local_date(&Utc::now(), tz)
+}
+
+/// The first instant of `date` in `tz`. Where clocks skip midnight, the
+/// day starts when they resume.
+pub fn day_start(date: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    let midnight = date.and_time(NaiveTime::MIN);
+    (0..=2)
+        .find_map(|hours| {
+            tz.from_local_datetime(&(midnight + TimeDelta::hours(hours)))
+                .earliest()
+        })
+        .map_or_else(|| midnight.and_utc(), |at| at.with_timezone(&Utc))
+}
+
+/// Shows `at` as local time in `tz`, e.g. `2026-04-06 09:30`.
+pub fn format_local(at: &DateTime<Utc>, tz: Tz) -> String {
+    at.with_timezone(&tz).format(DISPLAY_FORMAT).to_string()
+}
+
+/// Parses a timestamp sent by a device or user: RFC 3339 with an offset,
+/// or a local `YYYY-MM-DDTHH:MM[:SS]` time in `tz`. A local time repeated
+/// when clocks go back is taken at its first occurrence.
+pub fn parse_timestamp(value: &str, tz: Tz) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Ok(at.with_timezone(&Utc));
+    }
+    let naive = NAIVE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .ok_or_else(|| {
+            format!(
+                "'{}' must be RFC 3339 or a local YYYY-MM-DDTHH:MM:SS time",
+                value
+            )
+        })?;
+    match tz.from_local_datetime(&naive) {
+        LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => Ok(at.with_timezone(&Utc)),
+        LocalResult::None => Err(format!("'{}' does not exist in {}", value, tz.name())),
+    }
+}
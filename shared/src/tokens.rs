@@ -0,0 +1,84 @@
+//! Personal access tokens for scripts using the REST API, e.g. a nightly
+//! pull of the sales register as CSV.
+//!
+//! Scripts send a token as `Authorization: Bearer <token>`. Each token is
+//! scoped: a read-only token can only make `GET` requests, and a token
+//! limited to some modules (see `shared::permissions`) can only reach
+//! those. A token made by a worker signed in to the dashboard can also do
+//! no more than the worker's role.
+
+use crate::permissions::PermissionModule;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Longest accepted token name.
+pub const MAX_TOKEN_NAME_LEN: usize = 60;
+
+/// A personal access token as listed; the token itself is never returned
+/// after it is made.
+///
+/// `modules` is empty for a token that may reach every module. `worker` is
+/// the name of the worker who made it, if one did.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AccessToken {
+    pub id: i64,
+    pub name: String,
+    pub read_only: bool,
+    #[serde(default)]
+    pub modules: Vec<PermissionModule>,
+    #[serde(default)]
+    pub worker: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked: bool,
+}
+
+impl AccessToken {
+    /// Short description of the scope, e.g. "Read-only: Goats, Finance".
+    pub fn scope(&self) -> String {
+        let access = if self.read_only {
+            "Read-only"
+        } else {
+            "Read & write"
+        };
+        if self.modules.is_empty() {
+            return format!("{}: all modules", access);
+        }
+        let modules: Vec<&str> = self.modules.iter().map(|m| m.label()).collect();
+        format!("{}: {}", access, modules.join(", "))
+    }
+}
+
+/// Request to make a token called `name` with the given scope.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewAccessToken {
+    pub name: String,
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default)]
+    pub modules: Vec<PermissionModule>,
+}
+
+impl NewAccessToken {
+    /// Checks the name is given and not longer than `MAX_TOKEN_NAME_LEN`.
+    pub fn validate(&self) -> Result<(), String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Access tokens need a name".to_string());
+        }
+        if name.chars().count() > MAX_TOKEN_NAME_LEN {
+            return Err(format!(
+                "Token names must be at most {} characters",
+                MAX_TOKEN_NAME_LEN
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A new access token. `token` is shown only this once.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IssuedAccessToken {
+    pub access_token: AccessToken,
+    pub token: String,
+}
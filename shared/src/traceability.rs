@@ -0,0 +1,96 @@
+//! Traceability records of sold goats, for food-chain traceability.
+//!
+//! When a goat is sold the backend takes a snapshot of where it came from,
+//! its health history and its movements up to the sale, and keeps it under
+//! an unguessable token. The buyer reads it through the public share link
+//! (`TraceabilityRecord::share_path`) without an account. Later changes to
+//! the goat's records do not alter a record already shared.
+
+use crate::health::{TimelineEntry, TimelineKind};
+use serde::{Deserialize, Serialize};
+
+/// Path of the public share link of the record with `token`.
+pub fn share_path(token: &str) -> String {
+    format!("/public/traceability/{}", token)
+}
+
+/// How a goat came to be or moved on the farm.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovementKind {
+    /// Born to a doe of the herd.
+    Born,
+    /// Entered the herd from elsewhere.
+    Arrived,
+    /// Moved between lifecycle stages (see `crate::lifecycle`).
+    StageChanged,
+    /// Moved off the farm or between holdings (see `crate::movements`).
+    Transported,
+    /// Where the goat was kept when sold.
+    Kept,
+    /// Left the farm with the buyer.
+    Sold,
+}
+
+/// One entry of a goat's movement record.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Movement {
+    /// `YYYY-MM-DD`, if known.
+    pub date: Option<String>,
+    pub kind: MovementKind,
+    pub description: String,
+}
+
+/// A sold goat's origin, health history and movements, as shared with the
+/// buyer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TraceabilityRecord {
+    pub token: String,
+    pub goat_name: String,
+    pub tag_id: Option<String>,
+    pub breed: String,
+    pub gender: String,
+    pub date_of_birth: Option<String>,
+    /// The origin farm, as named in the farm settings.
+    pub farm_name: Option<String>,
+    pub farm_gstin: Option<String>,
+    pub sold_on: String,
+    pub buyer_name: Option<String>,
+    pub invoice_number: Option<String>,
+    /// Diseases on the goat's record when sold.
+    pub diseases: Vec<String>,
+    /// Incidents, vaccinations and lab results, newest first.
+    pub health: Vec<TimelineEntry>,
+    /// Oldest first, ending with the sale.
+    pub movements: Vec<Movement>,
+}
+
+impl TraceabilityRecord {
+    /// Path of the record's public share link.
+    pub fn share_path(&self) -> String {
+        share_path(&self.token)
+    }
+
+    /// The health history in one line, e.g. "2 vaccinations, 1 incident,
+    /// 1 lab result (1 out of range)".
+    pub fn health_summary(&self) -> String {
+        let count = |kind: TimelineKind| self.health.iter().filter(|e| e.kind == kind).count();
+        let plural = |n: usize, noun: &str| {
+            if n == 1 {
+                format!("1 {}", noun)
+            } else {
+                format!("{} {}s", n, noun)
+            }
+        };
+        let flagged = self.health.iter().filter(|e| e.flagged).count();
+        let mut lab = plural(count(TimelineKind::LabResult), "lab result");
+        if flagged > 0 {
+            lab = format!("{} ({} out of range)", lab, flagged);
+        }
+        [
+            plural(count(TimelineKind::Vaccination), "vaccination"),
+            plural(count(TimelineKind::Incident), "incident"),
+            lab,
+        ]
+        .join(", ")
+    }
+}
@@ -0,0 +1,186 @@
+//! Latin transliteration of Indic scripts, so a goat named "लक्ष्मी" is
+//! found by typing "Lakshmi" and "Rani" by typing "रानी".
+//!
+//! Devanagari, Bengali, Gurmukhi, Gujarati, Oriya, Tamil, Telugu, Kannada
+//! and Malayalam share one layout: each occupies a block of 128 code points
+//! with the same letter at the same offset. Letters are looked up by that
+//! offset in a single table. The spelling is a plain ASCII approximation
+//! (no diacritics) meant for matching, not for display.
+
+/// First code point of the Devanagari block; the other scripts follow in
+/// blocks of `BLOCK_LEN`.
+const FIRST_BLOCK: u32 = 0x0900;
+
+/// Size of each script's block.
+const BLOCK_LEN: u32 = 0x80;
+
+/// Number of consecutive Indic script blocks handled, Devanagari to
+/// Malayalam.
+const BLOCKS: u32 = 9;
+
+/// Offset of the virama, which removes a consonant's inherent "a".
+const VIRAMA: u32 = 0x4D;
+
+/// Offset of the nukta, which modifies the preceding consonant.
+const NUKTA: u32 = 0x3C;
+
+/// Latin digits, in the order of each script's digits.
+const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+/// What a character of an Indic script is, by its offset in the block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Letter {
+    /// A vowel written on its own, e.g. at the start of a word.
+    Vowel(&'static str),
+    /// A consonant, pronounced with an inherent "a" unless followed by a
+    /// vowel sign or virama.
+    Consonant(&'static str),
+    /// A vowel sign replacing the inherent "a" of the consonant before it.
+    VowelSign(&'static str),
+    /// Nasal or aspiration marks and digits, written as is.
+    Other(&'static str),
+    /// Virama, nukta and unknown characters, which write nothing.
+    Silent,
+}
+
+/// Looks up the letter at `offset` in a script block.
+fn letter(offset: u32) -> Letter {
+    use Letter::*;
+    match offset {
+        0x01 | 0x02 => Other("n"),
+        0x03 => Other("h"),
+        0x05 => Vowel("a"),
+        0x06 => Vowel("aa"),
+        0x07 => Vowel("i"),
+        0x08 => Vowel("ii"),
+        0x09 => Vowel("u"),
+        0x0A => Vowel("uu"),
+        0x0B => Vowel("ri"),
+        0x0D..=0x0F => Vowel("e"),
+        0x10 => Vowel("ai"),
+        0x11..=0x13 => Vowel("o"),
+        0x14 => Vowel("au"),
+        0x15 => Consonant("k"),
+        0x16 => Consonant("kh"),
+        0x17 => Consonant("g"),
+        0x18 => Consonant("gh"),
+        0x19 => Consonant("ng"),
+        0x1A => Consonant("ch"),
+        0x1B => Consonant("chh"),
+        0x1C => Consonant("j"),
+        0x1D => Consonant("jh"),
+        0x1E => Consonant("ny"),
+        0x1F | 0x24 => Consonant("t"),
+        0x20 | 0x25 => Consonant("th"),
+        0x21 | 0x26 => Consonant("d"),
+        0x22 | 0x27 => Consonant("dh"),
+        0x23 | 0x28 | 0x29 => Consonant("n"),
+        0x2A => Consonant("p"),
+        0x2B => Consonant("ph"),
+        0x2C => Consonant("b"),
+        0x2D => Consonant("bh"),
+        0x2E => Consonant("m"),
+        0x2F => Consonant("y"),
+        0x30 | 0x31 => Consonant("r"),
+        0x32..=0x34 => Consonant("l"),
+        0x35 => Consonant("v"),
+        0x36 | 0x37 => Consonant("sh"),
+        0x38 => Consonant("s"),
+        0x39 => Consonant("h"),
+        0x3E => VowelSign("aa"),
+        0x3F => VowelSign("i"),
+        0x40 => VowelSign("ii"),
+        0x41 => VowelSign("u"),
+        0x42 => VowelSign("uu"),
+        0x43 => VowelSign("ri"),
+        0x45..=0x47 => VowelSign("e"),
+        0x48 => VowelSign("ai"),
+        0x49..=0x4B => VowelSign("o"),
+        0x4C => VowelSign("au"),
+        0x50 => Other("om"),
+        0x58 => Consonant("q"),
+        0x59 => Consonant("kh"),
+        0x5A => Consonant("gh"),
+        0x5B => Consonant("z"),
+        0x5C => Consonant("d"),
+        0x5D => Consonant("dh"),
+        0x5E => Consonant("f"),
+        0x5F => Consonant("y"),
+        0x66..=0x6F => Other(DIGITS[(offset - 0x66) as usize]),
+        _ => Silent,
+    }
+}
+
+/// Offset of `c` within its Indic script block, or `None` for characters of
+/// other scripts.
+fn indic_offset(c: char) -> Option<u32> {
+    let code = c as u32;
+    (FIRST_BLOCK..FIRST_BLOCK + BLOCKS * BLOCK_LEN)
+        .contains(&code)
+        .then(|| (code - FIRST_BLOCK) % BLOCK_LEN)
+}
+
+/// True if `text` contains any character of a supported Indic script.
+pub fn has_indic(text: &str) -> bool {
+    text.chars().any(|c| indic_offset(c).is_some())
+}
+
+/// Spells `text` in Latin letters, leaving characters of other scripts
+/// unchanged, e.g. "लक्ष्मी" becomes "lakshmii" and "राम" "ram".
+///
+/// Consonants carry an inherent "a" unless a vowel sign or virama follows;
+/// as in Hindi, the inherent "a" of a word's last consonant is dropped
+/// when the word has more than one letter.
+pub fn romanize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    // Whether the last consonant still owes its inherent "a", and how many
+    // letters the current word has
+    let mut pending_a = false;
+    let mut word_letters = 0;
+    for c in text.chars() {
+        let Some(offset) = indic_offset(c) else {
+            // End of an Indic word: its last inherent "a" is only kept
+            // in one-letter words
+            if pending_a && word_letters == 1 {
+                out.push('a');
+            }
+            pending_a = false;
+            word_letters = 0;
+            out.push(c);
+            continue;
+        };
+        if offset == VIRAMA {
+            pending_a = false;
+            continue;
+        }
+        if offset == NUKTA {
+            continue;
+        }
+        match letter(offset) {
+            Letter::VowelSign(sign) => {
+                pending_a = false;
+                out.push_str(sign);
+            }
+            other => {
+                if pending_a {
+                    out.push('a');
+                    pending_a = false;
+                }
+                match other {
+                    Letter::Vowel(v) => out.push_str(v),
+                    Letter::Consonant(consonant) => {
+                        out.push_str(consonant);
+                        pending_a = true;
+                    }
+                    Letter::Other(s) => out.push_str(s),
+                    Letter::VowelSign(_) | Letter::Silent => {}
+                }
+            }
+        }
+        word_letters += 1;
+    }
+    if pending_a && word_letters == 1 {
+        out.push('a');
+    }
+    out
+}
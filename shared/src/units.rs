@@ -0,0 +1,109 @@
+//! Units and number formats the dashboard shows values in.
+//!
+//! Weights are stored in kilograms and money in the farm's base currency
+//! (see `crate::settings::FarmSettings::base_currency`). Forms let users enter
+//! weights in the unit they prefer, with their locale's decimal separator,
+//! and convert to the stored value before anything is saved.
+
+use serde::{Deserialize, Serialize};
+
+/// Kilograms in one pound (avoirdupois).
+pub const KG_PER_LB: f64 = 0.453_592_37;
+
+/// A unit weights can be entered and shown in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "PascalCase")]
+pub enum WeightUnit {
+    #[default]
+    Kg,
+    Lb,
+}
+
+impl WeightUnit {
+    pub const ALL: [WeightUnit; 2] = [WeightUnit::Kg, WeightUnit::Lb];
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            WeightUnit::Kg => "kg",
+            WeightUnit::Lb => "lb",
+        }
+    }
+
+    /// Parses a unit symbol as returned by `symbol`.
+    pub fn from_symbol(symbol: &str) -> Option<WeightUnit> {
+        WeightUnit::ALL.into_iter().find(|u| u.symbol() == symbol)
+    }
+
+    /// Converts `value`, in this unit, to kilograms.
+    pub fn to_kg(&self, value: f64) -> f64 {
+        match self {
+            WeightUnit::Kg => value,
+            WeightUnit::Lb => value * KG_PER_LB,
+        }
+    }
+
+    /// Converts `kg` to this unit.
+    pub fn from_kg(&self, kg: f64) -> f64 {
+        match self {
+            WeightUnit::Kg => kg,
+            WeightUnit::Lb => kg / KG_PER_LB,
+        }
+    }
+}
+
+/// The symbol amounts in the ISO 4217 currency `code` are shown with, e.g.
+/// "₹" for INR. Currencies without a well-known symbol show their code.
+pub fn currency_symbol(code: &str) -> &str {
+    match code {
+        "INR" => "₹",
+        "USD" => "$",
+        "EUR" => "€",
+        "GBP" => "£",
+        "JPY" => "¥",
+        "NPR" => "रू",
+        other => other,
+    }
+}
+
+/// Parses a number typed with `decimal_separator` ('.' or ','), ignoring
+/// spaces and the other separator as digit grouping ("1.234,5" or
+/// "1,00,000.50"). A lone other separator not followed by exactly three
+/// digits is taken as the decimal point too, so "31.5" still means 31.5
+/// where the locale writes "31,5".
+pub fn parse_decimal(text: &str, decimal_separator: char) -> Option<f64> {
+    let grouping = if decimal_separator == ',' { '.' } else { ',' };
+    let mut cleaned: String = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '\'')
+        .collect();
+    if !cleaned.contains(decimal_separator)
+        && let Some((_, after)) = cleaned.split_once(grouping)
+    {
+        let grouped = after.len() == 3 && after.chars().all(|c| c.is_ascii_digit());
+        if !grouped && !after.contains(grouping) {
+            cleaned = cleaned.replacen(grouping, &decimal_separator.to_string(), 1);
+        }
+    }
+    let normalized: String = cleaned
+        .chars()
+        .filter(|c| *c != grouping)
+        .map(|c| if c == decimal_separator { '.' } else { c })
+        .collect();
+    if normalized.is_empty() {
+        return None;
+    }
+    normalized.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+/// Formats `value` with at most `max_decimals` decimals, dropping trailing
+/// zeros, and `decimal_separator` as the decimal point.
+pub fn format_decimal(value: f64, max_decimals: usize, decimal_separator: char) -> String {
+    let mut text = format!("{:.*}", max_decimals, value);
+    if text.contains('.') {
+        text = text.trim_end_matches('0').trim_end_matches('.').to_string();
+    }
+    if text == "-0" {
+        text = "0".to_string();
+    }
+    text.replace('.', &decimal_separator.to_string())
+}
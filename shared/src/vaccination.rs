@@ -0,0 +1,164 @@
+//! Vaccination status of a goat against its vaccination schedule.
+//!
+//! A goat's schedule is the vaccines its breed profile recommends (see
+//! `crate::breeds::breed_profile`) plus any other vaccine it has been given,
+//! each boosted every `BOOSTER_INTERVAL_DAYS`. Each vaccine is judged from
+//! its latest dated dose, and the goat takes the worst of them.
+
+use crate::breeds::breed_profile;
+use crate::{GoatParams, VaccineRef};
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use tracing::trace;
+
+/// Days between doses of the same vaccine.
+pub const BOOSTER_INTERVAL_DAYS: u64 = 365;
+
+/// A booster due within this many days is due soon.
+pub const DUE_SOON_DAYS: u64 = 30;
+
+/// How a goat, or one of its vaccines, stands against the schedule. Ordered
+/// from best to worst.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "PascalCase")]
+pub enum VaccinationStatus {
+    UpToDate,
+    /// No dated dose to judge from.
+    Unknown,
+    DueSoon,
+    /// A booster is past due, or a scheduled vaccine was never given.
+    Overdue,
+}
+
+impl VaccinationStatus {
+    /// Every status, from best to worst.
+    pub const ALL: [VaccinationStatus; 4] = [
+        VaccinationStatus::UpToDate,
+        VaccinationStatus::Unknown,
+        VaccinationStatus::DueSoon,
+        VaccinationStatus::Overdue,
+    ];
+
+    /// Human-readable label.
+    pub fn label(&self) -> &'static str {
+        match self {
+            VaccinationStatus::UpToDate => "Up to date",
+            VaccinationStatus::Unknown => "Unknown",
+            VaccinationStatus::DueSoon => "Due soon",
+            VaccinationStatus::Overdue => "Overdue",
+        }
+    }
+}
+
+/// Where one scheduled vaccine stands.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VaccineDue {
+    pub vaccine: String,
+    /// Latest dated dose.
+    pub last_given: Option<NaiveDate>,
+    /// When the next booster is due.
+    pub due_on: Option<NaiveDate>,
+    /// Whether the goat has a dose of this vaccine at all, dated or not.
+    pub given: bool,
+    pub status: VaccinationStatus,
+}
+
+impl VaccineDue {
+    /// One line describing the vaccine, e.g. "PPR: due 2027-01-05".
+    pub fn describe(&self) -> String {
+        match (self.last_given, self.due_on) {
+            (Some(given), Some(due)) if self.status == VaccinationStatus::Overdue => {
+                format!("{}: given {}, overdue since {}", self.vaccine, given, due)
+            }
+            (Some(given), Some(due)) => {
+                format!("{}: given {}, due {}", self.vaccine, given, due)
+            }
+            _ if self.given => format!("{}: given, date not recorded", self.vaccine),
+            _ => format!("{}: never given", self.vaccine),
+        }
+    }
+}
+
+/// A goat's overall vaccination status with the vaccine-by-vaccine detail.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VaccinationSummary {
+    pub status: VaccinationStatus,
+    /// Scheduled vaccines, breed recommendations first.
+    pub vaccines: Vec<VaccineDue>,
+}
+
+impl VaccinationSummary {
+    /// One line per vaccine, for a tooltip.
+    pub fn details(&self) -> String {
+        if self.vaccines.is_empty() {
+            return "No vaccination schedule for this breed".to_string();
+        }
+        self.vaccines
+            .iter()
+            .map(VaccineDue::describe)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Judges one vaccine from the goat's doses of it.
+fn vaccine_due(vaccine: &str, doses: &[&VaccineRef], today: NaiveDate) -> VaccineDue {
+    let last_given = doses
+        .iter()
+        .filter_map(|d| d.given_on.as_deref())
+        .filter_map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .max();
+    let due_on = last_given.map(|d| d + Days::new(BOOSTER_INTERVAL_DAYS));
+    let status = match due_on {
+        Some(due) if due < today => VaccinationStatus::Overdue,
+        Some(due) if due <= today + Days::new(DUE_SOON_DAYS) => VaccinationStatus::DueSoon,
+        Some(_) => VaccinationStatus::UpToDate,
+        None if doses.is_empty() => VaccinationStatus::Overdue,
+        None => VaccinationStatus::Unknown,
+    };
+    VaccineDue {
+        vaccine: vaccine.to_string(),
+        last_given,
+        due_on,
+        given: !doses.is_empty(),
+        status,
+    }
+}
+
+/// The vaccination status of `goat` on `today`. A goat with no vaccinations
+/// recorded at all is Unknown rather than overdue on everything, since its
+/// records may simply not have been entered.
+pub fn vaccination_summary(goat: &GoatParams, today: NaiveDate) -> VaccinationSummary {
+    let mut schedule: Vec<String> = breed_profile(&goat.breed)
+        .map(|p| p.vaccines.iter().map(|v| v.to_string()).collect())
+        .unwrap_or_default();
+    for dose in &goat.vaccinations {
+        let name = dose.name.trim();
+        if !name.is_empty() && !schedule.iter().any(|v| v.eq_ignore_ascii_case(name)) {
+            schedule.push(name.to_string());
+        }
+    }
+
+    let vaccines: Vec<VaccineDue> = schedule
+        .iter()
+        .map(|vaccine| {
+            let doses: Vec<&VaccineRef> = goat
+                .vaccinations
+                .iter()
+                .filter(|d| d.name.trim().eq_ignore_ascii_case(vaccine))
+                .collect();
+            vaccine_due(vaccine, &doses, today)
+        })
+        .collect();
+    let status = if goat.vaccinations.is_empty() {
+        VaccinationStatus::Unknown
+    } else {
+        vaccines
+            .iter()
+            .map(|v| v.status)
+            .max()
+            .unwrap_or(VaccinationStatus::Unknown)
+    };
+    trace!(goat = %goat.name, ?status, "Computed vaccination status");
+    VaccinationSummary { status, vaccines }
+}
@@ -0,0 +1,168 @@
+//! Veterinary visits.
+//!
+//! A visit is requested with a reason and an agenda of goats to examine,
+//! scheduled with a vet on a day the vet is free, and completed with the
+//! vet's summary. Vets are workers whose role is a vet's (see
+//! `is_vet_role`), so they can sign in and see their own visits. The days
+//! each vet is booked are shared with everyone scheduling visits, and
+//! scheduled visits appear in the farm's calendar feed. Each finding in a
+//! visit's summary becomes a health incident of the goat, keeping its
+//! treatment history in one place.
+
+use crate::health::IncidentKind;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// Longest accepted visit reason.
+pub const MAX_REASON_LEN: usize = 200;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub enum VisitStatus {
+    Requested,
+    Scheduled,
+    Completed,
+}
+
+impl VisitStatus {
+    /// Converts a database string to `VisitStatus`.
+    pub fn from_str(s: &str) -> Result<VisitStatus, String> {
+        trace!("Parsing VisitStatus from '{}'", s);
+        match s {
+            "Requested" => Ok(VisitStatus::Requested),
+            "Scheduled" => Ok(VisitStatus::Scheduled),
+            "Completed" => Ok(VisitStatus::Completed),
+            other => {
+                debug!("Failed to parse VisitStatus enum from '{}'", other);
+                Err(other.to_string())
+            }
+        }
+    }
+
+    /// Converts a `VisitStatus` to a database string.
+    pub fn to_str(status: &VisitStatus) -> &str {
+        match status {
+            VisitStatus::Requested => "Requested",
+            VisitStatus::Scheduled => "Scheduled",
+            VisitStatus::Completed => "Completed",
+        }
+    }
+}
+
+/// Whether a worker's role is a vet's, e.g. "Vet" or "Veterinarian".
+pub fn is_vet_role(role: &str) -> bool {
+    let role = role.trim().to_lowercase();
+    role == "vet" || role.starts_with("veterinar")
+}
+
+/// A vet visit as listed.
+///
+/// `vet_name` is filled in on reads only. `treated` names the goats whose
+/// findings were recorded as health incidents when the visit was completed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VetVisit {
+    pub id: i64,
+    pub status: VisitStatus,
+    pub reason: String,
+    pub agenda: Vec<String>,
+    pub vet_id: Option<i64>,
+    pub vet_name: Option<String>,
+    pub requested_on: String,
+    pub scheduled_for: Option<String>,
+    pub completed_on: Option<String>,
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub treated: Vec<String>,
+}
+
+/// Request for a visit to examine the goats on `agenda`. With a vet and a
+/// day the visit is scheduled straight away.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VisitRequest {
+    pub reason: String,
+    pub agenda: Vec<String>,
+    #[serde(default)]
+    pub vet_id: Option<i64>,
+    #[serde(default)]
+    pub scheduled_for: Option<String>,
+}
+
+impl VisitRequest {
+    /// Checks the request has a reason, goats to examine, and a vet if it
+    /// has a day.
+    pub fn validate(&self) -> Result<(), String> {
+        let reason = self.reason.trim();
+        if reason.is_empty() {
+            return Err("A visit needs a reason".into());
+        }
+        if reason.chars().count() > MAX_REASON_LEN {
+            return Err(format!(
+                "The reason must be at most {} characters",
+                MAX_REASON_LEN
+            ));
+        }
+        if self.agenda.iter().all(|g| g.trim().is_empty()) {
+            return Err("A visit needs at least one goat to examine".into());
+        }
+        if self.scheduled_for.is_some() && self.vet_id.is_none() {
+            return Err("A visit can only be scheduled with a vet".into());
+        }
+        Ok(())
+    }
+}
+
+/// Books a visit with vet `vet_id` on `scheduled_for` (`YYYY-MM-DD`).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VisitSchedule {
+    pub vet_id: i64,
+    pub scheduled_for: String,
+}
+
+/// What the vet found in one goat, and how it was treated.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VisitFinding {
+    pub goat_name: String,
+    pub kind: IncidentKind,
+    pub condition: String,
+    #[serde(default)]
+    pub treatment: Option<String>,
+}
+
+/// The vet's summary of a visit, completing it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VisitSummary {
+    pub summary: String,
+    #[serde(default)]
+    pub findings: Vec<VisitFinding>,
+}
+
+impl VisitSummary {
+    /// Checks the summary is written and every finding names a condition.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.summary.trim().is_empty() {
+            return Err("A completed visit needs a summary".into());
+        }
+        if let Some(finding) = self.findings.iter().find(|f| f.condition.trim().is_empty()) {
+            return Err(format!(
+                "The finding for {} needs a condition",
+                finding.goat_name
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A vet and the days they are booked for visits.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VetAvailability {
+    pub vet_id: i64,
+    pub vet_name: String,
+    pub booked: Vec<String>,
+}
+
+impl VetAvailability {
+    /// Whether the vet is free on `day` (`YYYY-MM-DD`).
+    pub fn is_free(&self, day: &str) -> bool {
+        !self.booked.iter().any(|b| b == day)
+    }
+}
@@ -0,0 +1,253 @@
+//! Spoken quick entries such as "Rani 32 kilos" or "Lakshmi gave two point
+//! five litres".
+//!
+//! The browser's speech recognizer turns what the user says into text; this
+//! module finds the goat, the amount and the unit in that text. Amounts may
+//! be spoken as digits or number words, and names are matched to the herd
+//! with `search::name_similarity`, so a recognizer's spelling ("Laxmi" for
+//! "Lakshmi") still finds the goat. Nothing is saved until the user has
+//! confirmed the parsed entry.
+
+use crate::search::{fold_name, suggest_names};
+use crate::units::{WeightUnit, parse_decimal};
+use serde::{Deserialize, Serialize};
+
+/// Words said around the name and amount that carry no information.
+const FILLER_WORDS: [&str; 20] = [
+    "a", "about", "and", "around", "at", "for", "gave", "gives", "has", "is", "milk", "milked",
+    "of", "the", "today", "was", "weighed", "weighing", "weighs", "weight",
+];
+
+/// What a quick-entry form records.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    /// A weighing, in kilograms.
+    Weight,
+    /// A milking, in litres.
+    Milk,
+}
+
+/// A record parsed from an utterance, waiting for the user to confirm it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SpokenEntry {
+    /// The herd's name for the goat.
+    pub goat_name: String,
+    /// The name as heard, which differs from `goat_name` when it was
+    /// matched fuzzily.
+    pub heard_name: String,
+    pub kind: EntryKind,
+    /// Kilograms for a weighing, litres for a milking.
+    pub amount: f64,
+}
+
+/// A unit said after the amount.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SpokenUnit {
+    Weight(WeightUnit),
+    Litre,
+}
+
+fn spoken_unit(word: &str) -> Option<SpokenUnit> {
+    match word {
+        "kg" | "kgs" | "kilo" | "kilos" | "kilogram" | "kilograms" | "kilogramme"
+        | "kilogrammes" => Some(SpokenUnit::Weight(WeightUnit::Kg)),
+        "lb" | "lbs" | "pound" | "pounds" => Some(SpokenUnit::Weight(WeightUnit::Lb)),
+        "l" | "ltr" | "ltrs" | "litre" | "litres" | "liter" | "liters" => Some(SpokenUnit::Litre),
+        _ => None,
+    }
+}
+
+/// The value of a number word below one hundred, e.g. "seven" or "forty".
+fn number_word(word: &str) -> Option<f64> {
+    let value = match word {
+        "zero" => 0,
+        "one" => 1,
+        "two" => 2,
+        "three" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => 10,
+        "eleven" => 11,
+        "twelve" => 12,
+        "thirteen" => 13,
+        "fourteen" => 14,
+        "fifteen" => 15,
+        "sixteen" => 16,
+        "seventeen" => 17,
+        "eighteen" => 18,
+        "nineteen" => 19,
+        "twenty" => 20,
+        "thirty" => 30,
+        "forty" => 40,
+        "fifty" => 50,
+        "sixty" => 60,
+        "seventy" => 70,
+        "eighty" => 80,
+        "ninety" => 90,
+        _ => return None,
+    };
+    Some(value as f64)
+}
+
+/// Reads an amount from the start of `words`, written ("32.5") or spoken
+/// ("thirty two point five", "two and a half", "half a"). Returns it with
+/// the number of words used.
+fn read_number(words: &[String]) -> Option<(f64, usize)> {
+    let word = |i: usize| words.get(i).map(String::as_str);
+    if word(0) == Some("half") {
+        let used = if word(1) == Some("a") { 2 } else { 1 };
+        return Some((0.5, used));
+    }
+    let mut value = None::<f64>;
+    // Number words may only follow a larger place: "thirty two" is one
+    // amount, "two thirty" or "32 5" are not
+    let mut next_below = None::<f64>;
+    let mut i = 0;
+    while let Some(w) = word(i) {
+        if let Some(n) = number_word(w) {
+            match value {
+                None => value = Some(n),
+                Some(v) if next_below.is_some_and(|max| n < max) => value = Some(v + n),
+                Some(_) => break,
+            }
+            next_below = (n >= 20.0).then_some(10.0);
+        } else if value.is_none()
+            && let Some(n) = parse_decimal(w, '.')
+        {
+            value = Some(n);
+            next_below = None;
+        } else if w == "hundred"
+            && let Some(v) = value
+        {
+            value = Some(v * 100.0);
+            next_below = Some(100.0);
+        } else if w == "point"
+            && let Some(v) = value
+        {
+            let mut digits = String::new();
+            while let Some(d) = word(i + 1) {
+                match number_word(d) {
+                    Some(n) if n < 10.0 => digits.push_str(&n.to_string()),
+                    _ if d.chars().all(|c| c.is_ascii_digit()) => digits.push_str(d),
+                    _ => break,
+                }
+                i += 1;
+            }
+            value = Some(v + format!("0.{}", digits).parse::<f64>().unwrap_or(0.0));
+            return value.map(|v| (v, i + 1));
+        } else if w == "and"
+            && value.is_some()
+            && (word(i + 1), word(i + 2)) == (Some("a"), Some("half"))
+        {
+            return value.map(|v| (v + 0.5, i + 3));
+        } else {
+            break;
+        }
+        i += 1;
+    }
+    value.map(|v| (v, i))
+}
+
+/// Splits an utterance into lowercase words, dropping punctuation and
+/// separating amounts from units written together ("32kg").
+fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for raw in text.split_whitespace() {
+        let word = raw
+            .trim_matches(|c: char| c.is_ascii_punctuation() || c == '।')
+            .to_lowercase();
+        match word.find(char::is_alphabetic) {
+            Some(at) if at > 0 && word.starts_with(|c: char| c.is_ascii_digit()) => {
+                words.push(word[..at].to_string());
+                words.push(word[at..].to_string());
+            }
+            _ if word.is_empty() => {}
+            _ => words.push(word),
+        }
+    }
+    words
+}
+
+/// Parses an utterance such as "Rani 32 kilos" for a `kind` form.
+///
+/// Weights without a unit are taken to be in `weight_unit`, the unit the
+/// user enters weights in; milk is always in litres. The goat is the herd
+/// name in `goat_names` the remaining words name, matched exactly when
+/// possible and otherwise by the closest `search::suggest_names` match.
+/// Returns a message for the user when there is no amount or goat, or the
+/// unit does not fit the form.
+pub fn parse_utterance(
+    text: &str,
+    kind: EntryKind,
+    weight_unit: WeightUnit,
+    goat_names: &[&str],
+) -> Result<SpokenEntry, String> {
+    let words = words(text);
+    let mut amount = None;
+    let mut unit = None;
+    let mut name_words = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        if let Some((value, used)) = read_number(&words[i..]) {
+            if amount.is_some() {
+                return Err(format!("Heard more than one amount in \"{}\"", text.trim()));
+            }
+            amount = Some(value);
+            i += used;
+            continue;
+        }
+        let word = words[i].as_str();
+        if let Some(u) = spoken_unit(word) {
+            unit = Some(u);
+        } else if !FILLER_WORDS.contains(&word) {
+            name_words.push(word);
+        }
+        i += 1;
+    }
+
+    let example = match kind {
+        EntryKind::Weight => "\"Rani 32 kilos\"",
+        EntryKind::Milk => "\"Rani 2.5 litres\"",
+    };
+    let amount = match amount {
+        Some(a) if a > 0.0 => a,
+        _ => return Err(format!("Didn't hear an amount; say e.g. {}", example)),
+    };
+    let amount = match (kind, unit) {
+        (EntryKind::Weight, Some(SpokenUnit::Weight(u))) => u.to_kg(amount),
+        (EntryKind::Weight, None) => weight_unit.to_kg(amount),
+        (EntryKind::Milk, Some(SpokenUnit::Litre) | None) => amount,
+        (EntryKind::Weight, Some(SpokenUnit::Litre)) => {
+            return Err("Heard litres, but this form records weights".to_string());
+        }
+        (EntryKind::Milk, Some(SpokenUnit::Weight(_))) => {
+            return Err("Heard a weight, but milk is recorded in litres".to_string());
+        }
+    };
+
+    let heard_name = name_words.join(" ");
+    if heard_name.is_empty() {
+        return Err(format!("Didn't hear a goat's name; say e.g. {}", example));
+    }
+    let folded = fold_name(&heard_name);
+    let goat_name = goat_names
+        .iter()
+        .copied()
+        .find(|name| fold_name(name) == folded)
+        .or_else(|| {
+            suggest_names(&heard_name, goat_names.iter().copied())
+                .first()
+                .copied()
+        })
+        .ok_or_else(|| format!("No goat named '{}'", heard_name))?;
+    Ok(SpokenEntry {
+        goat_name: goat_name.to_string(),
+        heard_name,
+        kind,
+        amount,
+    })
+}
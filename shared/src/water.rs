@@ -0,0 +1,84 @@
+//! Water consumption per pen and trough maintenance.
+//!
+//! Water meters are registered as `SensorKind::Water` sensors in a pen and
+//! push the litres drawn since their last reading, like any other sensor.
+//! Readings are summed per day and each day is compared with the pen's
+//! average over the preceding week: goats often drink less a day or two
+//! before illness shows, so a sudden drop raises an alert and a task to
+//! check the pen. Troughs in metered pens get a cleaning task every
+//! `TROUGH_CLEANING_DAYS`.
+
+use serde::{Deserialize, Serialize};
+
+/// Days before a day that form its baseline.
+pub const BASELINE_DAYS: i64 = 7;
+
+/// Fewest baseline days needed before a day can be judged.
+pub const MIN_BASELINE_DAYS: usize = 3;
+
+/// Share of the baseline below which a day's intake counts as a drop.
+pub const DROP_SHARE: f64 = 0.7;
+
+/// Days between trough cleanings.
+pub const TROUGH_CLEANING_DAYS: i64 = 3;
+
+/// Title of the task created when a pen's troughs are due for cleaning.
+pub const TROUGH_CLEANING_TITLE: &str = "Clean water troughs";
+
+/// Title of the task created when a pen's water intake drops.
+pub const INTAKE_CHECK_TITLE: &str = "Check water intake";
+
+/// Litres drawn in a pen on one day, `YYYY-MM-DD` in the farm's time zone.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WaterDay {
+    pub date: String,
+    pub litres: f64,
+}
+
+/// A day on which a pen drank well below its baseline.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WaterDrop {
+    pub date: String,
+    pub litres: f64,
+    /// Average daily litres over the preceding days.
+    pub baseline: f64,
+}
+
+impl WaterDrop {
+    /// How far intake fell below the baseline, e.g. 0.4 for 40% less.
+    pub fn fall(&self) -> f64 {
+        if self.baseline > 0.0 {
+            1.0 - self.litres / self.baseline
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Water use and trough upkeep of one metered pen.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PenWater {
+    pub space_id: i64,
+    pub space_name: String,
+    /// Goats housed in the pen.
+    pub goats: i64,
+    /// Daily totals over the last two weeks, oldest first; days without
+    /// readings are left out.
+    pub days: Vec<WaterDay>,
+    /// Average daily litres over the `BASELINE_DAYS` before yesterday.
+    pub baseline: Option<f64>,
+    /// Yesterday's intake, if it fell below `DROP_SHARE` of the baseline.
+    pub drop: Option<WaterDrop>,
+    /// Date the troughs were last cleaned.
+    pub last_cleaned: Option<String>,
+    /// The pending cleaning task, if the troughs are due.
+    pub cleaning_task_id: Option<i64>,
+}
+
+impl PenWater {
+    /// Litres drawn per goat on the latest day with readings.
+    pub fn per_goat(&self) -> Option<f64> {
+        let latest = self.days.last()?;
+        (self.goats > 0).then(|| latest.litres / self.goats as f64)
+    }
+}